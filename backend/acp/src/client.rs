@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 use uuid::Uuid;
 
-use crate::types::{PermissionRequest, PermissionResponse, SpawnRequest, SubAgentSession};
+use crate::types::{PermissionRequest, PermissionResponse, PermissionScope, SpawnRequest, SubAgentSession};
 
 pub struct AcpClient {
     base_url: String,
@@ -58,17 +58,40 @@ impl AcpClient {
         Ok(res)
     }
 
-    /// Send a permission decision back to the requesting agent.
+    /// Ask for more scopes for a running sub-agent.
+    pub async fn request_permission(
+        &self,
+        session_id: Uuid,
+        scopes: Vec<PermissionScope>,
+        description: String,
+    ) -> Result<PermissionRequest> {
+        let url = format!("{}/api/acp/sessions/{}/permission/request", self.base_url, session_id);
+        let body = serde_json::json!({ "scopes": scopes, "description": description });
+        let res = self
+            .auth(self.http.post(&url).json(&body))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<PermissionRequest>()
+            .await?;
+        Ok(res)
+    }
+
+    /// Send a permission decision back to the requesting agent. Returns the
+    /// session with its updated grants.
     pub async fn respond_permission(
         &self,
         session_id: Uuid,
-        approved: bool,
-    ) -> Result<()> {
+        response: PermissionResponse,
+    ) -> Result<SubAgentSession> {
         let url = format!("{}/api/acp/sessions/{}/permission", self.base_url, session_id);
-        self.auth(self.http.post(&url).json(&PermissionResponse { approved }))
+        let res = self
+            .auth(self.http.post(&url).json(&response))
             .send()
             .await?
-            .error_for_status()?;
-        Ok(())
+            .error_for_status()?
+            .json::<SubAgentSession>()
+            .await?;
+        Ok(res)
     }
 }
@@ -1,16 +1,20 @@
 pub mod client;
+pub mod orchestrate;
 pub mod registry;
 pub mod server;
 pub mod telemetry;
 pub mod types;
+pub mod ws;
 pub mod canvas_host;
 pub mod acp_announce;
 
 pub use client::AcpClient;
+pub use orchestrate::{FanoutReport, FanoutSpec, FanoutTool, Orchestrator, SubAgentRunner, TaskResult};
 pub use registry::{SubAgentRegistry, MAX_SUBAGENT_DEPTH};
 pub use server::{build_acp_router, AcpServerState};
 pub use telemetry::{AcpRequestRecord, AcpTelemetry, AcpTimer, MethodStats};
 pub use types::{
-    PermissionRequest, PermissionResponse, SpawnRequest, SubAgentAnnouncement, SubAgentSession,
-    SubAgentStatus,
+    granted_capabilities, PermissionRequest, PermissionResponse, PermissionScope, SpawnRequest,
+    SubAgentAnnouncement, SubAgentNode, SubAgentSession, SubAgentStatus,
 };
+pub use ws::{AcpFrame, AcpHostLink, AcpHosts, HostStatus};
@@ -0,0 +1,221 @@
+/// Map-reduce orchestration over sub-agents.
+///
+/// `Orchestrator::map_reduce` registers a fan-out node, spawns one sub-agent
+/// per work item beneath it (map), retries each failed task up to
+/// `max_attempts`, collects the results — parsed as JSON when the sub-agent
+/// answered JSON — and finally runs the reduce prompt in the fan-out node
+/// with every result attached. The nodes stay in the registry, so the run
+/// shows up as a tree in `/subagents list`. `FanoutTool` exposes it to agents.
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use clawforge_core::Tool;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::registry::SubAgentRegistry;
+use crate::types::SubAgentStatus;
+
+/// Runs one sub-agent prompt to completion in the given session.
+#[async_trait]
+pub trait SubAgentRunner: Send + Sync {
+    async fn run(&self, session_id: Uuid, prompt: &str) -> Result<String>;
+}
+
+/// A fan-out over `items`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FanoutSpec {
+    pub items: Vec<String>,
+    /// Prompt for each task; `{{item}}` is replaced by the work item.
+    pub prompt: String,
+    /// Prompt for the reduce step; the task results are appended as JSON.
+    #[serde(default)]
+    pub reduce: Option<String>,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+}
+
+fn default_max_attempts() -> u32 {
+    2
+}
+
+fn default_concurrency() -> usize {
+    4
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskResult {
+    pub index: usize,
+    pub item: String,
+    pub session_id: Uuid,
+    pub attempts: u32,
+    /// The sub-agent's answer, as JSON when it parses.
+    pub output: Option<Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FanoutReport {
+    pub session_id: Uuid,
+    pub results: Vec<TaskResult>,
+    pub reduced: Option<Value>,
+}
+
+impl FanoutReport {
+    pub fn failed(&self) -> usize {
+        self.results.iter().filter(|r| r.error.is_some()).count()
+    }
+}
+
+fn parse_output(text: String) -> Value {
+    serde_json::from_str(text.trim()).unwrap_or(Value::String(text))
+}
+
+pub struct Orchestrator {
+    registry: Arc<SubAgentRegistry>,
+    runner: Arc<dyn SubAgentRunner>,
+}
+
+impl Orchestrator {
+    pub fn new(registry: Arc<SubAgentRegistry>, runner: Arc<dyn SubAgentRunner>) -> Self {
+        Self { registry, runner }
+    }
+
+    /// Run `spec` under `parent` (a sub-agent session, or none for a root).
+    pub async fn map_reduce(&self, parent: Option<Uuid>, spec: FanoutSpec) -> Result<FanoutReport> {
+        if spec.items.is_empty() {
+            bail!("fanout needs at least one item");
+        }
+        let node = self
+            .registry
+            .register(parent, None, format!("fanout: {} tasks", spec.items.len()))
+            .await?;
+        self.registry.set_label(node.session_id, "fanout").await;
+        self.registry.update_status(node.session_id, SubAgentStatus::Running, None).await;
+        info!("[ACP] Fan-out {} over {} items", node.session_id, spec.items.len());
+
+        let limit = Arc::new(Semaphore::new(spec.concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (index, item) in spec.items.iter().enumerate() {
+            let session = self
+                .registry
+                .register(Some(node.session_id), None, spec.prompt.replace("{{item}}", item))
+                .await?;
+            self.registry.set_label(session.session_id, item.as_str()).await;
+            let (registry, runner, limit) = (self.registry.clone(), self.runner.clone(), limit.clone());
+            let (item, max_attempts) = (item.clone(), spec.max_attempts.max(1));
+            tasks.spawn(async move {
+                let _permit = limit.acquire_owned().await;
+                run_task(&registry, runner.as_ref(), index, item, session.session_id, &session.prompt, max_attempts).await
+            });
+        }
+        let mut results = Vec::with_capacity(spec.items.len());
+        while let Some(result) = tasks.join_next().await {
+            results.push(result?);
+        }
+        results.sort_by_key(|r| r.index);
+
+        let reduced = match &spec.reduce {
+            None => None,
+            Some(prompt) => {
+                let prompt = format!("{}\n\nResults:\n{}", prompt, serde_json::to_string_pretty(&results)?);
+                match self.runner.run(node.session_id, &prompt).await {
+                    Ok(output) => Some(parse_output(output)),
+                    Err(e) => {
+                        self.registry
+                            .update_status(node.session_id, SubAgentStatus::Failed, Some(format!("reduce failed: {}", e)))
+                            .await;
+                        return Err(e.context("fanout reduce step failed"));
+                    }
+                }
+            }
+        };
+
+        let report = FanoutReport { session_id: node.session_id, results, reduced };
+        let summary = format!("{}/{} tasks succeeded", report.results.len() - report.failed(), report.results.len());
+        self.registry.update_status(node.session_id, SubAgentStatus::Completed, Some(summary)).await;
+        Ok(report)
+    }
+}
+
+async fn run_task(
+    registry: &SubAgentRegistry,
+    runner: &dyn SubAgentRunner,
+    index: usize,
+    item: String,
+    session_id: Uuid,
+    prompt: &str,
+    max_attempts: u32,
+) -> TaskResult {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        registry.update_status(session_id, SubAgentStatus::Running, None).await;
+        match runner.run(session_id, prompt).await {
+            Ok(output) => {
+                registry.update_status(session_id, SubAgentStatus::Completed, Some(output.clone())).await;
+                return TaskResult { index, item, session_id, attempts, output: Some(parse_output(output)), error: None };
+            }
+            Err(e) if attempts < max_attempts => {
+                warn!("[ACP] Fan-out task {} failed (attempt {}/{}): {}", session_id, attempts, max_attempts, e);
+            }
+            Err(e) => {
+                registry.update_status(session_id, SubAgentStatus::Failed, Some(e.to_string())).await;
+                return TaskResult { index, item, session_id, attempts, output: None, error: Some(e.to_string()) };
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// fanout tool
+// ---------------------------------------------------------------------------
+
+/// Lets an agent fan a task out over sub-agents and get the reduced result.
+pub struct FanoutTool {
+    orchestrator: Arc<Orchestrator>,
+}
+
+impl FanoutTool {
+    pub fn new(orchestrator: Arc<Orchestrator>) -> Self {
+        Self { orchestrator }
+    }
+}
+
+#[async_trait]
+impl Tool for FanoutTool {
+    fn name(&self) -> &str {
+        "fanout"
+    }
+
+    fn description(&self) -> &str {
+        "Run the same task over a list of items in parallel sub-agents, retrying failures, then optionally combine the results with a reduce prompt."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "items": { "type": "array", "items": { "type": "string" }, "description": "Work items, one sub-agent each" },
+                "prompt": { "type": "string", "description": "Task prompt; {{item}} is replaced by the item" },
+                "reduce": { "type": "string", "description": "Prompt that combines the results (optional)" },
+                "max_attempts": { "type": "integer", "description": "Attempts per task (default 2)" },
+                "concurrency": { "type": "integer", "description": "Sub-agents running at once (default 4)" }
+            },
+            "required": ["items", "prompt"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let spec: FanoutSpec = serde_json::from_value(args)?;
+        let report = self.orchestrator.map_reduce(None, spec).await?;
+        Ok(serde_json::to_string(&report)?)
+    }
+}
@@ -1,15 +1,25 @@
 /// Sub-agent registry — tracks all spawned sub-agent sessions,
 /// enforces depth limits, and manages lifecycle.
+///
+/// It also negotiates permissions: a sub-agent asks for scopes (tools,
+/// domains, budget) at spawn or later, the parent or user grants them per
+/// scope, and the grants are kept on the session. A sub-agent can never be
+/// granted more than its parent sub-agent holds, and scopes the parent
+/// already holds are granted without asking.
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
+use clawforge_core::Capabilities;
 use chrono::Utc;
 use tokio::sync::RwLock;
 use tracing::{info, warn};
 use uuid::Uuid;
 
-use crate::types::{SubAgentSession, SubAgentStatus};
+use crate::types::{
+    granted_capabilities, PermissionRequest, PermissionResponse, PermissionScope, SubAgentNode, SubAgentSession,
+    SubAgentStatus,
+};
 
 /// Maximum nesting depth for sub-agents (prevents infinite recursion).
 pub const MAX_SUBAGENT_DEPTH: usize = 5;
@@ -31,6 +41,18 @@ impl SubAgentRegistry {
         parent_session_id: Option<Uuid>,
         agent_id: Option<Uuid>,
         prompt: String,
+    ) -> Result<SubAgentSession> {
+        self.register_with_scopes(parent_session_id, agent_id, prompt, Vec::new()).await
+    }
+
+    /// Register a sub-agent that needs `scopes`. Unless its parent already
+    /// holds them all, it waits in `AwaitingPermission` for `resolve_permission`.
+    pub async fn register_with_scopes(
+        &self,
+        parent_session_id: Option<Uuid>,
+        agent_id: Option<Uuid>,
+        prompt: String,
+        scopes: Vec<PermissionScope>,
     ) -> Result<SubAgentSession> {
         let depth = if let Some(parent_id) = parent_session_id {
             let sessions = self.sessions.read().await;
@@ -49,28 +71,126 @@ impl SubAgentRegistry {
         }
 
         let now = Utc::now().timestamp();
-        let session = SubAgentSession {
+        let mut session = SubAgentSession {
             session_id: Uuid::new_v4(),
             parent_session_id,
             agent_id,
             depth,
             status: SubAgentStatus::Starting,
             prompt,
+            label: None,
             result: None,
+            grants: Vec::new(),
+            pending_permission: None,
             created_at: now,
             updated_at: now,
         };
+        let mut sessions = self.sessions.write().await;
+        if !scopes.is_empty() {
+            let description = format!("Sub-agent spawn: {}", session.prompt);
+            Self::ask(&sessions, &mut session, scopes, description);
+        }
 
         info!(
             "Registered sub-agent session {} (depth {})",
             session.session_id, depth
         );
 
-        let mut sessions = self.sessions.write().await;
         sessions.insert(session.session_id, session.clone());
         Ok(session)
     }
 
+    /// Ask for more scopes while running. Returns the request, which is
+    /// already granted when `auto_approvable`.
+    pub async fn request_permissions(
+        &self,
+        id: Uuid,
+        scopes: Vec<PermissionScope>,
+        description: String,
+    ) -> Result<PermissionRequest> {
+        let mut sessions = self.sessions.write().await;
+        let mut session = sessions.get(&id).cloned().ok_or_else(|| anyhow!("Sub-agent {} not found", id))?;
+        if session.pending_permission.is_some() {
+            bail!("Sub-agent {} already has a permission request pending", id);
+        }
+        let request = Self::ask(&sessions, &mut session, scopes, description);
+        sessions.insert(id, session);
+        Ok(request)
+    }
+
+    /// Apply the parent's or user's decision to the pending request and
+    /// resume the session. Only requested scopes can be granted, and never
+    /// more than the parent sub-agent holds.
+    pub async fn resolve_permission(&self, id: Uuid, response: PermissionResponse) -> Result<SubAgentSession> {
+        let mut sessions = self.sessions.write().await;
+        let parent_grants = sessions.get(&id).and_then(|s| Self::parent_grants(&sessions, s));
+        let session = sessions.get_mut(&id).ok_or_else(|| anyhow!("Sub-agent {} not found", id))?;
+        let request = session
+            .pending_permission
+            .take()
+            .ok_or_else(|| anyhow!("Sub-agent {} has no pending permission request", id))?;
+
+        let granted: Vec<PermissionScope> = request
+            .scopes
+            .iter()
+            .filter_map(|scope| response.granted.iter().find_map(|g| scope.narrow(g)))
+            .filter_map(|scope| match &parent_grants {
+                Some(held) => held.iter().find_map(|g| scope.narrow(g)),
+                None => Some(scope),
+            })
+            .collect();
+        info!(
+            "Sub-agent {} granted {}/{} requested scopes",
+            id,
+            granted.len(),
+            request.scopes.len()
+        );
+        session.grants.extend(granted);
+        session.status = SubAgentStatus::Running;
+        session.updated_at = Utc::now().timestamp();
+        Ok(session.clone())
+    }
+
+    /// The executor capabilities for a sub-agent: `base` (the root agent's
+    /// capabilities) narrowed to its grants. Grants are already bounded by
+    /// every ancestor's, so one narrowing is enough.
+    pub async fn capabilities(&self, id: Uuid, base: &Capabilities) -> Option<Capabilities> {
+        self.sessions.read().await.get(&id).map(|s| granted_capabilities(base, &s.grants))
+    }
+
+    /// Grants of `session`'s parent, if the parent is itself a sub-agent.
+    fn parent_grants(sessions: &HashMap<Uuid, SubAgentSession>, session: &SubAgentSession) -> Option<Vec<PermissionScope>> {
+        session.parent_session_id.and_then(|p| sessions.get(&p)).map(|p| p.grants.clone())
+    }
+
+    /// Build a request for `scopes` on `session`, granting it at once when the
+    /// parent sub-agent already holds every scope.
+    fn ask(
+        sessions: &HashMap<Uuid, SubAgentSession>,
+        session: &mut SubAgentSession,
+        scopes: Vec<PermissionScope>,
+        description: String,
+    ) -> PermissionRequest {
+        let auto_approvable = Self::parent_grants(sessions, session)
+            .is_some_and(|held| scopes.iter().all(|scope| held.iter().any(|g| g.covers(scope))));
+        let request = PermissionRequest {
+            request_id: Uuid::new_v4(),
+            session_id: session.session_id,
+            scopes,
+            description,
+            auto_approvable,
+        };
+        if auto_approvable {
+            session.grants.extend(request.scopes.iter().cloned());
+        } else {
+            info!("Sub-agent {} awaiting permission for {} scopes", session.session_id, request.scopes.len());
+            session.pending_permission = Some(request.clone());
+            session.status = SubAgentStatus::AwaitingPermission;
+        }
+        session.updated_at = Utc::now().timestamp();
+        request
+    }
+
     /// Update the status of a session.
     pub async fn update_status(&self, id: Uuid, status: SubAgentStatus, message: Option<String>) {
         let mut sessions = self.sessions.write().await;
@@ -86,6 +206,13 @@ impl SubAgentRegistry {
         }
     }
 
+    /// Name the session in usage breakdowns.
+    pub async fn set_label(&self, id: Uuid, label: impl Into<String>) {
+        if let Some(session) = self.sessions.write().await.get_mut(&id) {
+            session.label = Some(label.into());
+        }
+    }
+
     /// Get a session by ID.
     pub async fn get(&self, id: Uuid) -> Option<SubAgentSession> {
         self.sessions.read().await.get(&id).cloned()
@@ -102,6 +229,32 @@ impl SubAgentRegistry {
             .collect()
     }
 
+    /// Every session arranged under its parent, oldest first. Sessions whose
+    /// parent isn't registered are roots.
+    pub async fn tree(&self) -> Vec<SubAgentNode> {
+        fn build(id: Uuid, by_parent: &HashMap<Option<Uuid>, Vec<SubAgentSession>>, session: SubAgentSession) -> SubAgentNode {
+            let children = by_parent
+                .get(&Some(id))
+                .map(|kids| kids.iter().map(|k| build(k.session_id, by_parent, k.clone())).collect())
+                .unwrap_or_default();
+            SubAgentNode { session, children }
+        }
+
+        let sessions = self.sessions.read().await;
+        let mut by_parent: HashMap<Option<Uuid>, Vec<SubAgentSession>> = HashMap::new();
+        for session in sessions.values() {
+            let parent = session.parent_session_id.filter(|p| sessions.contains_key(p));
+            by_parent.entry(parent).or_default().push(session.clone());
+        }
+        for kids in by_parent.values_mut() {
+            kids.sort_by_key(|s| s.created_at);
+        }
+        by_parent
+            .get(&None)
+            .map(|roots| roots.iter().map(|r| build(r.session_id, &by_parent, r.clone())).collect())
+            .unwrap_or_default()
+    }
+
     /// List all active (non-terminal) sessions.
     pub async fn active(&self) -> Vec<SubAgentSession> {
         self.sessions
@@ -15,14 +15,17 @@ use uuid::Uuid;
 
 use crate::registry::SubAgentRegistry;
 use crate::types::{
-    PermissionResponse, SpawnRequest, SubAgentAnnouncement, SubAgentSession, SubAgentStatus,
+    PermissionResponse, PermissionScope, SpawnRequest, SubAgentAnnouncement, SubAgentSession, SubAgentStatus,
 };
+use crate::ws::{acp_ws_handler, AcpHosts};
 
 #[derive(Clone)]
 pub struct AcpServerState {
     pub registry: Arc<SubAgentRegistry>,
     /// Announce broadcast so gateway can relay status to connected WS clients.
     pub announce_tx: mpsc::Sender<SubAgentAnnouncement>,
+    /// Remote sub-agent hosts connected over `/api/acp/ws`.
+    pub hosts: AcpHosts,
 }
 
 pub fn build_acp_router(state: AcpServerState) -> Router {
@@ -31,25 +34,42 @@ pub fn build_acp_router(state: AcpServerState) -> Router {
         .route("/api/acp/sessions/:id", get(session_handler))
         .route("/api/acp/sessions/:id/status", post(status_handler))
         .route("/api/acp/sessions/:id/permission", post(permission_handler))
+        .route("/api/acp/sessions/:id/permission/request", post(permission_request_handler))
         .route("/api/acp/sessions", get(list_sessions_handler))
+        .route("/api/acp/hosts", get(list_hosts_handler))
+        .route("/api/acp/ws", get(acp_ws_handler))
         .with_state(state)
 }
 
-/// POST /api/acp/spawn — register and start a sub-agent session.
+/// POST /api/acp/spawn — register and start a sub-agent session, on a remote
+/// host when `host` is set.
 async fn spawn_handler(
     State(state): State<AcpServerState>,
     Json(req): Json<SpawnRequest>,
 ) -> impl IntoResponse {
     match state
         .registry
-        .register(req.parent_session_id, req.agent_id, req.prompt)
+        .register_with_scopes(req.parent_session_id, req.agent_id, req.prompt, req.scopes)
         .await
     {
-        Ok(session) => {
+        Ok(mut session) => {
+            if let Some(label) = req.label {
+                state.registry.set_label(session.session_id, label.clone()).await;
+                session.label = Some(label);
+            }
+            if let Some(host) = &req.host {
+                if let Err(e) = state.hosts.assign(host, session.clone()) {
+                    state
+                        .registry
+                        .update_status(session.session_id, SubAgentStatus::Failed, Some(e.to_string()))
+                        .await;
+                    return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+                }
+            }
             let ann = SubAgentAnnouncement {
                 session_id: session.session_id,
-                status: SubAgentStatus::Starting,
-                message: None,
+                status: session.status.clone(),
+                message: session.pending_permission.as_ref().map(|p| p.description.clone()),
                 timestamp: Utc::now().timestamp(),
             };
             let _ = state.announce_tx.send(ann).await;
@@ -89,6 +109,9 @@ async fn status_handler(
         .registry
         .update_status(id, body.status.clone(), body.message.clone())
         .await;
+    if body.status == SubAgentStatus::Cancelled {
+        state.hosts.cancel(id);
+    }
 
     let ann = SubAgentAnnouncement {
         session_id: id,
@@ -100,19 +123,56 @@ async fn status_handler(
     StatusCode::OK
 }
 
-/// POST /api/acp/sessions/:id/permission — respond to a permission request.
+/// POST /api/acp/sessions/:id/permission/request — sub-agent asks for more scopes.
+#[derive(serde::Deserialize)]
+struct ScopeRequest {
+    scopes: Vec<PermissionScope>,
+    description: String,
+}
+
+async fn permission_request_handler(
+    State(state): State<AcpServerState>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<ScopeRequest>,
+) -> impl IntoResponse {
+    match state.registry.request_permissions(id, body.scopes, body.description).await {
+        Ok(request) => {
+            if !request.auto_approvable {
+                let ann = SubAgentAnnouncement {
+                    session_id: id,
+                    status: SubAgentStatus::AwaitingPermission,
+                    message: Some(request.description.clone()),
+                    timestamp: Utc::now().timestamp(),
+                };
+                let _ = state.announce_tx.send(ann).await;
+            }
+            (StatusCode::OK, Json(request)).into_response()
+        }
+        Err(e) => (StatusCode::CONFLICT, e.to_string()).into_response(),
+    }
+}
+
+/// POST /api/acp/sessions/:id/permission — grant or deny the pending request, per scope.
 async fn permission_handler(
     State(state): State<AcpServerState>,
     Path(id): Path<Uuid>,
     Json(body): Json<PermissionResponse>,
 ) -> impl IntoResponse {
-    // Permission decisions are logged; in full impl they'd wake a waiting executor.
-    info!(
-        "[ACP] Permission for session {}: {}",
-        id,
-        if body.approved { "approved" } else { "denied" }
-    );
-    StatusCode::OK
+    match state.registry.resolve_permission(id, body).await {
+        Ok(session) => {
+            info!("[ACP] Permission for session {}: {} scopes granted", id, session.grants.len());
+            state.hosts.notify_permission(&session);
+            let ann = SubAgentAnnouncement {
+                session_id: id,
+                status: session.status.clone(),
+                message: None,
+                timestamp: Utc::now().timestamp(),
+            };
+            let _ = state.announce_tx.send(ann).await;
+            (StatusCode::OK, Json(session)).into_response()
+        }
+        Err(e) => (StatusCode::CONFLICT, e.to_string()).into_response(),
+    }
 }
 
 /// GET /api/acp/sessions — list all active sessions.
@@ -122,3 +182,10 @@ async fn list_sessions_handler(
     let sessions: Vec<SubAgentSession> = state.registry.active().await;
     (StatusCode::OK, Json(sessions)).into_response()
 }
+
+/// GET /api/acp/hosts — remote hosts, whether they are connected and what they run.
+async fn list_hosts_handler(
+    State(state): State<AcpServerState>,
+) -> impl IntoResponse {
+    (StatusCode::OK, Json(state.hosts.statuses())).into_response()
+}
@@ -1,6 +1,8 @@
 //! ACP (Agent Communication Protocol) telemetry: tracks request/response metrics.
 //!
-//! Mirrors `src/acp/telemetry.ts`.
+//! Mirrors `src/acp/telemetry.ts`. With a cost tracker attached, sub-agent
+//! token usage and tool counts are also rolled up into the parent run's cost
+//! records, tagged with the sub-agent's depth and label.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -9,6 +11,10 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::debug;
 
+use infra::{CostRecord, CostTracker, SubAgentOrigin, TokenUsage};
+
+use crate::types::SubAgentSession;
+
 /// A single ACP request telemetry record.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -50,6 +56,7 @@ pub struct AcpTelemetry {
     records: Arc<RwLock<Vec<AcpRequestRecord>>>,
     max_records: usize,
     stats: Arc<RwLock<HashMap<String, MethodStats>>>,
+    costs: Option<CostTracker>,
 }
 
 impl AcpTelemetry {
@@ -58,9 +65,38 @@ impl AcpTelemetry {
             records: Arc::new(RwLock::new(Vec::new())),
             max_records,
             stats: Arc::new(RwLock::new(HashMap::new())),
+            costs: None,
         }
     }
 
+    /// Roll sub-agent usage up into `costs`.
+    pub fn with_costs(mut self, costs: CostTracker) -> Self {
+        self.costs = Some(costs);
+        self
+    }
+
+    /// Record one sub-agent model call against `run_session`, the session of
+    /// the run at the root of its tree. No-op without a cost tracker.
+    pub async fn record_subagent_usage(
+        &self,
+        run_session: &str,
+        session: &SubAgentSession,
+        model_name: &str,
+        usage: TokenUsage,
+        tool_calls: u32,
+    ) -> anyhow::Result<Option<CostRecord>> {
+        let Some(costs) = &self.costs else { return Ok(None) };
+        let origin = SubAgentOrigin {
+            session_id: session.session_id.to_string(),
+            depth: session.depth,
+            label: session.label(),
+            tool_calls,
+        };
+        let agent_id = session.agent_id.map(|id| id.to_string()).unwrap_or_default();
+        let record = costs.record_subagent_usage(run_session, &agent_id, model_name, usage, origin).await?;
+        Ok(Some(record))
+    }
+
     /// Record a completed ACP request.
     pub async fn record(&self, rec: AcpRequestRecord) {
         debug!(method = %rec.method, duration_ms = rec.duration_ms, success = rec.success, "ACP telemetry");
@@ -1,4 +1,5 @@
 /// ACP (Agent Communication Protocol) types shared between client and server.
+use clawforge_core::Capabilities;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -23,25 +24,166 @@ pub struct SubAgentSession {
     pub depth: usize,
     pub status: SubAgentStatus,
     pub prompt: String,
+    /// Short name for usage breakdowns; defaults to the prompt's first line.
+    #[serde(default)]
+    pub label: Option<String>,
     pub result: Option<String>,
+    /// Scopes granted so far; the sub-agent's capabilities are derived from these.
+    #[serde(default)]
+    pub grants: Vec<PermissionScope>,
+    /// Request waiting on the parent or user while status is `AwaitingPermission`.
+    #[serde(default)]
+    pub pending_permission: Option<PermissionRequest>,
     pub created_at: i64,
     pub updated_at: i64,
 }
 
-/// ACP permission request from an agent.
+impl SubAgentSession {
+    pub fn label(&self) -> String {
+        match &self.label {
+            Some(label) => label.clone(),
+            None => self.prompt.lines().next().unwrap_or_default().chars().take(40).collect(),
+        }
+    }
+}
+
+/// A session with its sub-agents, for tree views.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubAgentNode {
+    pub session: SubAgentSession,
+    pub children: Vec<SubAgentNode>,
+}
+
+/// A capability a sub-agent can ask for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PermissionScope {
+    Tool { name: String },
+    /// HTTP access to a domain and its subdomains.
+    Domain { domain: String },
+    Budget {
+        #[serde(default)]
+        max_tokens: Option<u64>,
+        #[serde(default)]
+        max_cost_usd: Option<f64>,
+    },
+}
+
+impl PermissionScope {
+    /// Whether holding `self` already grants `other`.
+    pub fn covers(&self, other: &PermissionScope) -> bool {
+        match (self, other) {
+            (Self::Tool { name: a }, Self::Tool { name: b }) => a == b,
+            (Self::Domain { domain: a }, Self::Domain { domain: b }) => b.ends_with(a.as_str()),
+            (
+                Self::Budget { max_tokens: held_tokens, max_cost_usd: held_cost },
+                Self::Budget { max_tokens, max_cost_usd },
+            ) => within(*max_tokens, *held_tokens) && within(*max_cost_usd, *held_cost),
+            _ => false,
+        }
+    }
+
+    /// The part of a requested scope that `granted` allows: the scope itself
+    /// when covered, or a budget lowered to the grant.
+    pub fn narrow(&self, granted: &PermissionScope) -> Option<PermissionScope> {
+        match (self, granted) {
+            (
+                Self::Budget { max_tokens, max_cost_usd },
+                Self::Budget { max_tokens: granted_tokens, max_cost_usd: granted_cost },
+            ) => Some(Self::Budget {
+                max_tokens: lower(*max_tokens, *granted_tokens),
+                max_cost_usd: lower(*max_cost_usd, *granted_cost),
+            }),
+            _ if granted.covers(self) => Some(self.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// `None` is unlimited.
+fn within<T: PartialOrd>(wanted: Option<T>, held: Option<T>) -> bool {
+    match (wanted, held) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(wanted), Some(held)) => wanted <= held,
+    }
+}
+
+fn lower<T: PartialOrd>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b < a { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Narrow `base` (the parent's capabilities) to what `grants` allow. Tools and
+/// domains must be granted explicitly and stay within `base`'s allowlists —
+/// shell commands need the `shell_execute` tool — and budgets take the lower
+/// of the two.
+pub fn granted_capabilities(base: &Capabilities, grants: &[PermissionScope]) -> Capabilities {
+    let tools: Vec<String> = grants
+        .iter()
+        .filter_map(|g| match g {
+            PermissionScope::Tool { name } => Some(name.clone()),
+            _ => None,
+        })
+        .filter(|name| base.allowed_tools.is_empty() || base.allowed_tools.contains(name))
+        .collect();
+    let domains: Vec<String> = grants
+        .iter()
+        .filter_map(|g| match g {
+            PermissionScope::Domain { domain } => Some(domain.clone()),
+            _ => None,
+        })
+        .filter(|domain| base.allowed_domains.is_empty() || base.allowed_domains.iter().any(|d| domain.ends_with(d.as_str())))
+        .collect();
+    let (mut max_tokens, mut max_cost) = (base.max_tokens_per_run, base.max_cost_per_run_usd);
+    for grant in grants {
+        if let PermissionScope::Budget { max_tokens: tokens, max_cost_usd } = grant {
+            max_tokens = lower(max_tokens, *tokens);
+            max_cost = lower(max_cost, *max_cost_usd);
+        }
+    }
+    Capabilities {
+        can_execute_commands: base.can_execute_commands && tools.iter().any(|t| t == "shell_execute"),
+        can_use_tools: base.can_use_tools && !tools.is_empty(),
+        can_make_http_requests: base.can_make_http_requests && !domains.is_empty(),
+        allowed_tools: tools,
+        allowed_domains: domains,
+        max_tokens_per_run: max_tokens,
+        max_cost_per_run_usd: max_cost,
+        ..base.clone()
+    }
+}
+
+/// ACP permission request from an agent: the scopes it wants, decided per scope.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermissionRequest {
+    pub request_id: Uuid,
     pub session_id: Uuid,
-    pub tool_name: String,
-    pub tool_kind: String,
+    pub scopes: Vec<PermissionScope>,
     pub description: String,
+    /// True when the parent already holds every scope, so no one needs asking.
     pub auto_approvable: bool,
 }
 
 /// ACP permission response.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PermissionResponse {
-    pub approved: bool,
+    /// Requested scopes to grant; anything not covered here is denied. A
+    /// budget may be granted lower than asked.
+    #[serde(default)]
+    pub granted: Vec<PermissionScope>,
+}
+
+impl PermissionResponse {
+    pub fn approve_all(request: &PermissionRequest) -> Self {
+        Self { granted: request.scopes.clone() }
+    }
+
+    pub fn deny_all() -> Self {
+        Self::default()
+    }
 }
 
 /// Request to spawn a new sub-agent.
@@ -51,6 +193,15 @@ pub struct SpawnRequest {
     pub agent_id: Option<Uuid>,
     pub prompt: String,
     pub workspace: Option<String>,
+    /// Capabilities the sub-agent needs; it waits in `AwaitingPermission`
+    /// until they are decided.
+    #[serde(default)]
+    pub scopes: Vec<PermissionScope>,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Remote host (connected over `/api/acp/ws`) to run the sub-agent on.
+    #[serde(default)]
+    pub host: Option<String>,
 }
 
 /// Announcement broadcasted by a sub-agent about its status.
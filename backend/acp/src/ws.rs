@@ -0,0 +1,680 @@
+/// ACP over WebSocket — persistent links for remote sub-agent hosts.
+///
+/// A host (a node host or another ClawForge instance) dials
+/// `/api/acp/ws` and opens with a `hello` carrying its id, its token and
+/// the sessions it is still running. The gateway answers `welcome` with the
+/// sessions it resumed; sessions it had assigned to the host that the host
+/// no longer knows are failed. On the open link the host streams
+/// announcements (status and progress) and permission requests, and the
+/// gateway sends session assignments, permission decisions and cancels —
+/// queued while the host is offline and delivered when it reconnects. Both
+/// sides ping every heartbeat interval and drop a link that stays silent for
+/// three; `AcpHostLink` redials with backoff.
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use chrono::Utc;
+use futures::{future, Sink, SinkExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::server::AcpServerState;
+use crate::types::{PermissionScope, SubAgentAnnouncement, SubAgentSession, SubAgentStatus};
+
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// Silent heartbeat intervals before a link is considered dead.
+const HEARTBEAT_MISSES: u32 = 3;
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Frames exchanged on an ACP link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AcpFrame {
+    /// Host -> gateway: first frame on a new link.
+    Hello {
+        host_id: String,
+        token: String,
+        /// Sessions the host is still running.
+        #[serde(default)]
+        resume: Vec<Uuid>,
+    },
+    /// Gateway -> host: the link is authenticated.
+    Welcome { resumed: Vec<Uuid> },
+    Ping,
+    Pong,
+    /// Host -> gateway: status or progress of one of its sessions.
+    Announce { announcement: SubAgentAnnouncement },
+    /// Host -> gateway: a running session needs more scopes.
+    RequestPermission {
+        session_id: Uuid,
+        scopes: Vec<PermissionScope>,
+        description: String,
+    },
+    /// Gateway -> host: run this session.
+    Assign { session: SubAgentSession },
+    /// Gateway -> host: a permission request was decided; carries the new grants.
+    Permission { session: SubAgentSession },
+    /// Gateway -> host: stop this session.
+    Cancel { session_id: Uuid },
+}
+
+fn is_terminal(status: &SubAgentStatus) -> bool {
+    matches!(status, SubAgentStatus::Completed | SubAgentStatus::Failed | SubAgentStatus::Cancelled)
+}
+
+// ---------------------------------------------------------------------------
+// Gateway side
+// ---------------------------------------------------------------------------
+
+struct Link {
+    id: u64,
+    tx: mpsc::UnboundedSender<AcpFrame>,
+}
+
+#[derive(Default)]
+struct Inner {
+    tokens: HashMap<String, String>,
+    links: Mutex<HashMap<String, Link>>,
+    /// session -> host running it
+    owners: Mutex<HashMap<Uuid, String>>,
+    /// Frames for hosts that are offline, delivered on reconnect.
+    outbox: Mutex<HashMap<String, Vec<AcpFrame>>>,
+    next_link: AtomicU64,
+}
+
+/// Remote sub-agent hosts: their links, the sessions they run and frames
+/// waiting for them.
+#[derive(Clone, Default)]
+pub struct AcpHosts {
+    inner: Arc<Inner>,
+    heartbeat: Option<Duration>,
+}
+
+/// A host as shown by status views.
+#[derive(Debug, Clone, Serialize)]
+pub struct HostStatus {
+    pub id: String,
+    pub online: bool,
+    pub sessions: Vec<Uuid>,
+    pub queued: usize,
+}
+
+impl AcpHosts {
+    /// Hosts that may connect, by id, with their tokens.
+    pub fn new(tokens: HashMap<String, String>) -> Self {
+        Self { inner: Arc::new(Inner { tokens, ..Default::default() }), heartbeat: None }
+    }
+
+    pub fn with_heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat = Some(interval);
+        self
+    }
+
+    pub fn is_online(&self, host: &str) -> bool {
+        self.inner.links.lock().unwrap_or_else(|e| e.into_inner()).contains_key(host)
+    }
+
+    pub fn host_of(&self, session_id: Uuid) -> Option<String> {
+        self.inner.owners.lock().unwrap_or_else(|e| e.into_inner()).get(&session_id).cloned()
+    }
+
+    pub fn statuses(&self) -> Vec<HostStatus> {
+        let owners = self.inner.owners.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let outbox = self.inner.outbox.lock().unwrap_or_else(|e| e.into_inner());
+        let mut hosts: Vec<HostStatus> = self
+            .inner
+            .tokens
+            .keys()
+            .map(|id| HostStatus {
+                id: id.clone(),
+                online: self.is_online(id),
+                sessions: owners.iter().filter(|(_, h)| *h == id).map(|(s, _)| *s).collect(),
+                queued: outbox.get(id).map_or(0, Vec::len),
+            })
+            .collect();
+        hosts.sort_by(|a, b| a.id.cmp(&b.id));
+        hosts
+    }
+
+    /// Hand `session` to `host`, now or when it next connects.
+    pub fn assign(&self, host: &str, session: SubAgentSession) -> Result<()> {
+        if !self.inner.tokens.contains_key(host) {
+            bail!("Unknown ACP host '{}'", host);
+        }
+        self.inner.owners.lock().unwrap_or_else(|e| e.into_inner()).insert(session.session_id, host.to_string());
+        self.send(host, AcpFrame::Assign { session });
+        Ok(())
+    }
+
+    /// Tell the host running `session` about a permission decision. No-op
+    /// for sessions that don't run on a remote host.
+    pub fn notify_permission(&self, session: &SubAgentSession) {
+        if let Some(host) = self.host_of(session.session_id) {
+            self.send(&host, AcpFrame::Permission { session: session.clone() });
+        }
+    }
+
+    pub fn cancel(&self, session_id: Uuid) {
+        if let Some(host) = self.host_of(session_id) {
+            self.send(&host, AcpFrame::Cancel { session_id });
+        }
+    }
+
+    /// Send on the live link, or queue for the next one.
+    fn send(&self, host: &str, frame: AcpFrame) {
+        let links = self.inner.links.lock().unwrap_or_else(|e| e.into_inner());
+        let frame = match links.get(host) {
+            Some(link) => match link.tx.send(frame) {
+                Ok(()) => return,
+                Err(mpsc::error::SendError(frame)) => frame,
+            },
+            None => frame,
+        };
+        drop(links);
+        debug!("[ACP] Host {} offline — queueing frame", host);
+        self.inner.outbox.lock().unwrap_or_else(|e| e.into_inner()).entry(host.to_string()).or_default().push(frame);
+    }
+
+    fn authenticate(&self, host_id: &str, token: &str) -> bool {
+        self.inner.tokens.get(host_id).is_some_and(|t| bool::from(t.as_bytes().ct_eq(token.as_bytes())))
+    }
+
+    /// Register a live link to `host`, replacing any older one. Resumes the
+    /// listed sessions, fails the host's others that it no longer knows, and
+    /// flushes queued frames.
+    async fn attach(
+        &self,
+        state: &AcpServerState,
+        host: &str,
+        resume: Vec<Uuid>,
+    ) -> (u64, Vec<Uuid>, mpsc::UnboundedReceiver<AcpFrame>) {
+        let mut resumed = Vec::new();
+        for id in resume {
+            let live = state.registry.get(id).await.is_some_and(|s| !is_terminal(&s.status));
+            let mut owners = self.inner.owners.lock().unwrap_or_else(|e| e.into_inner());
+            if live && owners.get(&id).is_none_or(|h| h == host) {
+                owners.insert(id, host.to_string());
+                resumed.push(id);
+            }
+        }
+
+        let queued = self.inner.outbox.lock().unwrap_or_else(|e| e.into_inner()).remove(host).unwrap_or_default();
+        let assigned: HashSet<Uuid> = queued
+            .iter()
+            .filter_map(|f| match f {
+                AcpFrame::Assign { session } => Some(session.session_id),
+                _ => None,
+            })
+            .collect();
+        let lost: Vec<Uuid> = self
+            .inner
+            .owners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|(id, h)| *h == host && !resumed.contains(id) && !assigned.contains(id))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in lost {
+            self.inner.owners.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+            if state.registry.get(id).await.is_some_and(|s| !is_terminal(&s.status)) {
+                warn!("[ACP] Host {} reconnected without session {} — failing it", host, id);
+                let message = format!("host {} lost the session", host);
+                state.registry.update_status(id, SubAgentStatus::Failed, Some(message.clone())).await;
+                announce(state, id, SubAgentStatus::Failed, Some(message)).await;
+            }
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        for frame in queued {
+            let _ = tx.send(frame);
+        }
+        let id = self.inner.next_link.fetch_add(1, Ordering::Relaxed);
+        self.inner.links.lock().unwrap_or_else(|e| e.into_inner()).insert(host.to_string(), Link { id, tx });
+        info!("[ACP] Host {} connected, {} sessions resumed", host, resumed.len());
+        (id, resumed, rx)
+    }
+
+    /// Drop the link unless a newer one replaced it. The host keeps its
+    /// sessions until it reconnects.
+    fn detach(&self, host: &str, link_id: u64) {
+        let mut links = self.inner.links.lock().unwrap_or_else(|e| e.into_inner());
+        if links.get(host).is_some_and(|link| link.id == link_id) {
+            links.remove(host);
+            warn!("[ACP] Host {} disconnected", host);
+        }
+    }
+
+    async fn handle_frame(&self, state: &AcpServerState, host: &str, frame: AcpFrame) {
+        match frame {
+            AcpFrame::Announce { announcement } => {
+                if self.host_of(announcement.session_id).as_deref() != Some(host) {
+                    warn!("[ACP] Host {} announced session {} it doesn't run", host, announcement.session_id);
+                    return;
+                }
+                state
+                    .registry
+                    .update_status(announcement.session_id, announcement.status.clone(), announcement.message.clone())
+                    .await;
+                if is_terminal(&announcement.status) {
+                    self.inner.owners.lock().unwrap_or_else(|e| e.into_inner()).remove(&announcement.session_id);
+                }
+                let _ = state.announce_tx.send(announcement).await;
+            }
+            AcpFrame::RequestPermission { session_id, scopes, description } => {
+                if self.host_of(session_id).as_deref() != Some(host) {
+                    warn!("[ACP] Host {} asked permissions for session {} it doesn't run", host, session_id);
+                    return;
+                }
+                match state.registry.request_permissions(session_id, scopes, description).await {
+                    Ok(request) if request.auto_approvable => {
+                        if let Some(session) = state.registry.get(session_id).await {
+                            self.notify_permission(&session);
+                        }
+                    }
+                    Ok(request) => announce(state, session_id, SubAgentStatus::AwaitingPermission, Some(request.description)).await,
+                    Err(e) => warn!("[ACP] Permission request for session {} refused: {}", session_id, e),
+                }
+            }
+            AcpFrame::Ping | AcpFrame::Pong => {}
+            other => warn!("[ACP] Unexpected frame from host {}: {:?}", host, other),
+        }
+    }
+}
+
+async fn announce(state: &AcpServerState, session_id: Uuid, status: SubAgentStatus, message: Option<String>) {
+    let ann = SubAgentAnnouncement { session_id, status, message, timestamp: Utc::now().timestamp() };
+    let _ = state.announce_tx.send(ann).await;
+}
+
+/// GET /api/acp/ws — remote sub-agent hosts dial in here.
+pub async fn acp_ws_handler(ws: WebSocketUpgrade, State(state): State<AcpServerState>) -> Response {
+    ws.on_upgrade(move |socket| accept_host(socket, state))
+}
+
+async fn accept_host(socket: WebSocket, state: AcpServerState) {
+    let hosts = state.hosts.clone();
+    let (mut sink, stream) = socket.split();
+    let mut texts = stream
+        .take_while(|msg| future::ready(matches!(msg, Ok(m) if !matches!(m, Message::Close(_)))))
+        .filter_map(|msg| {
+            future::ready(match msg {
+                Ok(Message::Text(text)) => Some(text.to_string()),
+                _ => None,
+            })
+        });
+
+    let hello = match tokio::time::timeout(HANDSHAKE_TIMEOUT, texts.next()).await {
+        Ok(Some(text)) => match serde_json::from_str::<AcpFrame>(&text) {
+            Ok(AcpFrame::Hello { host_id, token, resume }) if hosts.authenticate(&host_id, &token) => Some((host_id, resume)),
+            _ => None,
+        },
+        _ => None,
+    };
+    let Some((host, resume)) = hello else {
+        warn!("[ACP] Rejected host link: bad or missing hello");
+        let _ = sink.send(Message::Close(None)).await;
+        return;
+    };
+
+    let (link_id, resumed, mut outbound) = hosts.attach(&state, &host, resume).await;
+    let Ok(text) = serde_json::to_string(&AcpFrame::Welcome { resumed }) else { return };
+    if sink.send(Message::Text(text)).await.is_ok() {
+        let tx = sink.with(|text: String| future::ready(Ok::<_, axum::Error>(Message::Text(text))));
+        let (inbound_tx, mut inbound) = mpsc::unbounded_channel();
+        let heartbeat = hosts.heartbeat.unwrap_or(HEARTBEAT_INTERVAL);
+        let handle = async {
+            while let Some(frame) = inbound.recv().await {
+                hosts.handle_frame(&state, &host, frame).await;
+            }
+        };
+        tokio::join!(pump(Box::pin(tx), Box::pin(texts), &mut outbound, inbound_tx, heartbeat), handle);
+    }
+    hosts.detach(&host, link_id);
+}
+
+/// Move frames between a socket and the link's channels until the socket
+/// closes or goes quiet for `HEARTBEAT_MISSES` heartbeats. Pings are
+/// answered here; every other frame goes to `inbound`. Frames still in
+/// `outbound` when the link drops stay there for the next link.
+async fn pump<Tx, Rx>(
+    mut tx: Tx,
+    mut rx: Rx,
+    outbound: &mut mpsc::UnboundedReceiver<AcpFrame>,
+    inbound: mpsc::UnboundedSender<AcpFrame>,
+    heartbeat: Duration,
+) where
+    Tx: Sink<String> + Unpin,
+    Rx: Stream<Item = String> + Unpin,
+{
+    let mut ticker = tokio::time::interval_at(Instant::now() + heartbeat, heartbeat);
+    let mut last_heard = Instant::now();
+    loop {
+        let frame = tokio::select! {
+            frame = outbound.recv() => match frame {
+                Some(frame) => frame,
+                None => break,
+            },
+            text = rx.next() => {
+                let Some(text) = text else { break };
+                last_heard = Instant::now();
+                match serde_json::from_str::<AcpFrame>(&text) {
+                    Ok(AcpFrame::Ping) => AcpFrame::Pong,
+                    Ok(frame) => {
+                        let _ = inbound.send(frame);
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!("[ACP] Invalid frame: {}", e);
+                        continue;
+                    }
+                }
+            }
+            _ = ticker.tick() => {
+                if last_heard.elapsed() > heartbeat * HEARTBEAT_MISSES {
+                    warn!("[ACP] Link silent for {:?} — dropping it", last_heard.elapsed());
+                    break;
+                }
+                AcpFrame::Ping
+            }
+        };
+        let Ok(text) = serde_json::to_string(&frame) else { continue };
+        if tx.send(text).await.is_err() {
+            break;
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Host side
+// ---------------------------------------------------------------------------
+
+/// A host's persistent link to a gateway. Frames sent while disconnected are
+/// delivered after the next successful dial.
+pub struct AcpHostLink {
+    outbound: mpsc::UnboundedSender<AcpFrame>,
+    frames: Mutex<Option<mpsc::UnboundedReceiver<AcpFrame>>>,
+    sessions: Arc<Mutex<HashSet<Uuid>>>,
+    connected: watch::Receiver<bool>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl AcpHostLink {
+    /// Start dialing `url` (e.g. `ws://gateway:3000/api/acp/ws`) and keep the link up.
+    pub fn connect(url: impl Into<String>, host_id: impl Into<String>, token: impl Into<String>) -> Self {
+        Self::connect_with_heartbeat(url, host_id, token, HEARTBEAT_INTERVAL)
+    }
+
+    pub fn connect_with_heartbeat(
+        url: impl Into<String>,
+        host_id: impl Into<String>,
+        token: impl Into<String>,
+        heartbeat: Duration,
+    ) -> Self {
+        let (outbound, mut outbound_rx) = mpsc::unbounded_channel();
+        let (frames_tx, frames) = mpsc::unbounded_channel();
+        let (connected_tx, connected) = watch::channel(false);
+        let sessions = Arc::new(Mutex::new(HashSet::new()));
+        let (url, host_id, token) = (url.into(), host_id.into(), token.into());
+        let task = tokio::spawn({
+            let sessions = sessions.clone();
+            async move {
+                let mut backoff = MIN_BACKOFF;
+                loop {
+                    let resume: Vec<Uuid> = sessions.lock().unwrap_or_else(|e| e.into_inner()).iter().copied().collect();
+                    let link = HostDial { url: &url, host_id: &host_id, token: &token, heartbeat, resume };
+                    match link.run(&mut outbound_rx, &frames_tx, &sessions, &connected_tx).await {
+                        Ok(()) => backoff = MIN_BACKOFF,
+                        Err(e) => warn!("[ACP] Dial to {} failed: {}", url, e),
+                    }
+                    connected_tx.send_replace(false);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        });
+        Self { outbound, frames: Mutex::new(Some(frames)), sessions, connected, task }
+    }
+
+    /// Frames from the gateway: assignments, permission decisions and cancels.
+    /// Can be taken once.
+    pub fn frames(&self) -> Option<mpsc::UnboundedReceiver<AcpFrame>> {
+        self.frames.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        *self.connected.borrow()
+    }
+
+    /// Resolves once the link is up (again).
+    pub async fn wait_connected(&self) {
+        let _ = self.connected.clone().wait_for(|up| *up).await;
+    }
+
+    /// Report status or progress; terminal statuses end the session here.
+    pub fn announce(&self, session_id: Uuid, status: SubAgentStatus, message: Option<String>) {
+        if is_terminal(&status) {
+            self.sessions.lock().unwrap_or_else(|e| e.into_inner()).remove(&session_id);
+        }
+        let announcement = SubAgentAnnouncement { session_id, status, message, timestamp: Utc::now().timestamp() };
+        let _ = self.outbound.send(AcpFrame::Announce { announcement });
+    }
+
+    pub fn request_permission(&self, session_id: Uuid, scopes: Vec<PermissionScope>, description: String) {
+        let _ = self.outbound.send(AcpFrame::RequestPermission { session_id, scopes, description });
+    }
+}
+
+impl Drop for AcpHostLink {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+struct HostDial<'a> {
+    url: &'a str,
+    host_id: &'a str,
+    token: &'a str,
+    heartbeat: Duration,
+    resume: Vec<Uuid>,
+}
+
+impl HostDial<'_> {
+    async fn run(
+        self,
+        outbound: &mut mpsc::UnboundedReceiver<AcpFrame>,
+        frames: &mpsc::UnboundedSender<AcpFrame>,
+        sessions: &Mutex<HashSet<Uuid>>,
+        connected: &watch::Sender<bool>,
+    ) -> Result<()> {
+        let (socket, _) = tokio_tungstenite::connect_async(self.url).await?;
+        let (mut sink, stream) = socket.split();
+        let mut texts = stream
+            .take_while(|msg| future::ready(matches!(msg, Ok(m) if !m.is_close())))
+            .filter_map(|msg| {
+                future::ready(match msg {
+                    Ok(WsMessage::Text(text)) => Some(text),
+                    _ => None,
+                })
+            });
+
+        let hello = AcpFrame::Hello { host_id: self.host_id.to_string(), token: self.token.to_string(), resume: self.resume.clone() };
+        sink.send(WsMessage::Text(serde_json::to_string(&hello)?)).await?;
+        let first = tokio::time::timeout(HANDSHAKE_TIMEOUT, texts.next())
+            .await
+            .map_err(|_| anyhow!("no welcome within {:?}", HANDSHAKE_TIMEOUT))?
+            .ok_or_else(|| anyhow!("gateway closed the link during the handshake"))?;
+        let AcpFrame::Welcome { resumed } = serde_json::from_str::<AcpFrame>(&first)? else {
+            bail!("expected welcome, got {}", first);
+        };
+        {
+            let mut sessions = sessions.lock().unwrap_or_else(|e| e.into_inner());
+            for id in self.resume.iter().filter(|id| !resumed.contains(id)) {
+                warn!("[ACP] Gateway did not resume session {}", id);
+                sessions.remove(id);
+            }
+        }
+        info!("[ACP] Linked to {}, {} sessions resumed", self.url, resumed.len());
+        connected.send_replace(true);
+
+        let tx = sink.with(|text: String| future::ready(Ok::<_, tokio_tungstenite::tungstenite::Error>(WsMessage::Text(text))));
+        let (inbound_tx, mut inbound) = mpsc::unbounded_channel();
+        let handle = async {
+            while let Some(frame) = inbound.recv().await {
+                if let AcpFrame::Assign { session } = &frame {
+                    sessions.lock().unwrap_or_else(|e| e.into_inner()).insert(session.session_id);
+                }
+                let _ = frames.send(frame);
+            }
+        };
+        tokio::join!(pump(Box::pin(tx), Box::pin(texts), outbound, inbound_tx, self.heartbeat), handle);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::SubAgentRegistry;
+    use crate::server::build_acp_router;
+    use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+    type Client = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+    /// Serve the ACP routes on a loopback port with one host, `h1`/`secret`.
+    async fn serve() -> (String, AcpServerState, mpsc::Receiver<SubAgentAnnouncement>) {
+        let (announce_tx, announce_rx) = mpsc::channel(16);
+        let hosts = AcpHosts::new(HashMap::from([("h1".to_string(), "secret".to_string())]));
+        let state = AcpServerState { registry: Arc::new(SubAgentRegistry::new()), announce_tx, hosts };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/api/acp/ws", listener.local_addr().unwrap());
+        let app = build_acp_router(state.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, state, announce_rx)
+    }
+
+    async fn send(client: &mut Client, frame: &AcpFrame) {
+        client.send(WsMessage::Text(serde_json::to_string(frame).unwrap())).await.unwrap();
+    }
+
+    /// Next text frame, or `None` once the gateway closes the link.
+    async fn recv(client: &mut Client) -> Option<AcpFrame> {
+        let next = tokio::time::timeout(Duration::from_secs(5), client.next()).await.expect("no frame in time");
+        match next? {
+            Ok(WsMessage::Text(text)) => Some(serde_json::from_str(&text).unwrap()),
+            _ => None,
+        }
+    }
+
+    async fn until(mut check: impl FnMut() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !check() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("condition not reached in time");
+    }
+
+    #[test]
+    fn frames_are_tagged_by_snake_case_type() {
+        let frame = AcpFrame::RequestPermission { session_id: Uuid::nil(), scopes: Vec::new(), description: "net".into() };
+        let json = serde_json::to_value(&frame).unwrap();
+        assert_eq!(json["type"], "request_permission");
+        assert_eq!(json["description"], "net");
+
+        // `resume` may be left out of a hello.
+        let hello: AcpFrame = serde_json::from_str(r#"{"type":"hello","host_id":"h1","token":"t"}"#).unwrap();
+        assert!(matches!(hello, AcpFrame::Hello { resume, .. } if resume.is_empty()));
+        assert!(serde_json::from_str::<AcpFrame>(r#"{"type":"shout"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn links_with_a_wrong_token_or_no_hello_are_closed() {
+        let (url, state, _announcements) = serve().await;
+
+        let (mut client, _) = connect_async(url.as_str()).await.unwrap();
+        send(&mut client, &AcpFrame::Hello { host_id: "h1".into(), token: "guess".into(), resume: Vec::new() }).await;
+        assert!(recv(&mut client).await.is_none());
+
+        let (mut client, _) = connect_async(url.as_str()).await.unwrap();
+        send(&mut client, &AcpFrame::Hello { host_id: "h2".into(), token: "secret".into(), resume: Vec::new() }).await;
+        assert!(recv(&mut client).await.is_none());
+
+        let (mut client, _) = connect_async(url.as_str()).await.unwrap();
+        send(&mut client, &AcpFrame::Ping).await;
+        assert!(recv(&mut client).await.is_none());
+
+        assert!(!state.hosts.is_online("h1"));
+    }
+
+    #[tokio::test]
+    async fn a_good_hello_is_welcomed_and_pings_are_answered() {
+        let (url, state, _announcements) = serve().await;
+        let (mut client, _) = connect_async(url.as_str()).await.unwrap();
+        send(&mut client, &AcpFrame::Hello { host_id: "h1".into(), token: "secret".into(), resume: vec![Uuid::new_v4()] })
+            .await;
+
+        // Sessions the gateway doesn't know aren't resumed.
+        assert!(matches!(recv(&mut client).await, Some(AcpFrame::Welcome { resumed }) if resumed.is_empty()));
+        assert!(state.hosts.is_online("h1"));
+
+        // Garbage is skipped without dropping the link.
+        client.send(WsMessage::Text("not json".into())).await.unwrap();
+        send(&mut client, &AcpFrame::Ping).await;
+        assert!(matches!(recv(&mut client).await, Some(AcpFrame::Pong)));
+    }
+
+    #[tokio::test]
+    async fn frames_queue_while_the_host_is_away_and_sessions_survive_a_disconnect() {
+        let (url, state, mut announcements) = serve().await;
+        let session = state.registry.register(None, None, "crawl".into()).await.unwrap();
+        let id = session.session_id;
+        state.hosts.assign("h1", session).unwrap();
+        assert_eq!(state.hosts.statuses()[0].queued, 1);
+
+        let link = AcpHostLink::connect(url.as_str(), "h1", "secret");
+        let mut frames = link.frames().unwrap();
+        link.wait_connected().await;
+        let frame = tokio::time::timeout(Duration::from_secs(5), frames.recv()).await.unwrap();
+        assert!(matches!(frame, Some(AcpFrame::Assign { session }) if session.session_id == id));
+
+        link.announce(id, SubAgentStatus::Running, Some("started".into()));
+        let ann = tokio::time::timeout(Duration::from_secs(5), announcements.recv()).await.unwrap().unwrap();
+        assert_eq!((ann.session_id, ann.status), (id, SubAgentStatus::Running));
+        assert_eq!(state.registry.get(id).await.unwrap().status, SubAgentStatus::Running);
+
+        // Dropping the link takes the host offline but it keeps the session,
+        // and a cancel waits for its return.
+        drop(link);
+        until(|| !state.hosts.is_online("h1")).await;
+        assert_eq!(state.hosts.host_of(id).as_deref(), Some("h1"));
+        state.hosts.cancel(id);
+        assert_eq!(state.hosts.statuses()[0].queued, 1);
+
+        // Coming back without the session fails it.
+        let (mut client, _) = connect_async(url.as_str()).await.unwrap();
+        send(&mut client, &AcpFrame::Hello { host_id: "h1".into(), token: "secret".into(), resume: Vec::new() }).await;
+        assert!(matches!(recv(&mut client).await, Some(AcpFrame::Welcome { .. })));
+        assert!(matches!(recv(&mut client).await, Some(AcpFrame::Cancel { session_id }) if session_id == id));
+        assert_eq!(state.registry.get(id).await.unwrap().status, SubAgentStatus::Failed);
+        assert_eq!(state.hosts.host_of(id), None);
+    }
+}
@@ -2,14 +2,16 @@
 //!
 //! Mirrors `src/agents/runtime.ts` and `src/agents/agent-loop.ts`.
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, instrument, warn};
+use uuid::Uuid;
 
 use crate::chat::{ChatMessage, ToolCallRequest};
 use crate::context_window::ContextWindow;
+use crate::loop_guard::LoopAction;
 use crate::assistant_identity::AssistantIdentity;
 use crate::prompt_cache::PromptCache;
 use crate::session_state::SessionState;
@@ -29,6 +31,17 @@ pub enum StepResult {
     Error(String),
 }
 
+/// How a run of the agent loop ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RunOutcome {
+    /// The agent answered or stopped on its own.
+    Completed,
+    /// The agent is stuck and handed the conversation back with this question.
+    AwaitingUser(String),
+    /// `max_steps` ran out first.
+    StepLimit,
+}
+
 /// The core agent runner that manages the conversation loop.
 pub struct AgentRunner {
     pub session: Arc<tokio::sync::RwLock<SessionState>>,
@@ -56,16 +69,27 @@ impl AgentRunner {
         }
     }
 
-    /// Run the agent loop until it produces a final response or hits the max steps limit.
+    /// Personalize prompts with the preferences of each session's principal.
+    pub fn with_preferences(mut self, preferences: Arc<clawforge_security::PreferenceStore>) -> Self {
+        let cache = Arc::new(PromptCache::new());
+        self.prompt_builder = Arc::new(PromptBuilder::new(cache).with_preferences(preferences));
+        self
+    }
+
+    /// Run the agent loop until it produces a final response, needs the
+    /// user, or hits the max steps limit. A tool loop that persists past the
+    /// guard's abort threshold fails the run.
     #[instrument(skip(self), fields(session_id = %self.session.read().await.session_id))]
-    pub async fn run_loop(&self) -> Result<()> {
+    pub async fn run_loop(&self) -> Result<RunOutcome> {
         info!("Starting agent loop");
+        let agent_id = Uuid::parse_str(&self.session.read().await.agent_id).unwrap_or_default();
+        self.tool_dispatcher.loop_guard().begin_run(Uuid::new_v4(), agent_id);
 
         let mut step_count = 0;
         loop {
             if step_count >= self.max_steps {
                 warn!("Max steps ({}) reached, stopping loop", self.max_steps);
-                break;
+                return Ok(RunOutcome::StepLimit);
             }
 
             step_count += 1;
@@ -94,7 +118,21 @@ impl AgentRunner {
                             serde_json::to_string(&res).unwrap_or_else(|e| e.to_string()),
                         ));
                     }
-                    // Loop naturally continues
+                    match self.tool_dispatcher.loop_guard().take_action() {
+                        Some(LoopAction::Nudge(nudge)) => session.transcript.push(ChatMessage::system(nudge)),
+                        Some(LoopAction::Escalate(question)) => {
+                            warn!("Tool loop persisted, handing back to the user");
+                            session.transcript.push(ChatMessage::assistant(question.clone()));
+                            return Ok(RunOutcome::AwaitingUser(question));
+                        }
+                        Some(LoopAction::Abort(reason)) => {
+                            error!("{}", reason);
+                            session.transcript.push(ChatMessage::assistant(reason.clone()));
+                            bail!(reason);
+                        }
+                        // Loop naturally continues
+                        None => {}
+                    }
                 }
                 StepResult::Stop => {
                     info!("Agent requested stop");
@@ -107,7 +145,7 @@ impl AgentRunner {
             }
         }
 
-        Ok(())
+        Ok(RunOutcome::Completed)
     }
 
     /// Single interaction with the LLM.
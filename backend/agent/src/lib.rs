@@ -7,13 +7,17 @@ pub mod agent_loop;
 pub mod assistant_identity;
 pub mod chat;
 pub mod context_window;
+pub mod loop_guard;
 pub mod prompt_cache;
+pub mod session_fork;
 pub mod session_state;
 pub mod system_prompt;
 pub mod tool_dispatcher;
 
-pub use agent_loop::{AgentRunner, StepResult};
+pub use agent_loop::{AgentRunner, RunOutcome, StepResult};
 pub use context_window::ContextWindow;
-pub use session_state::{SessionState, ModelConfig};
+pub use loop_guard::{LoopAction, LoopGuard, LoopGuardConfig, LoopIntervention};
+pub use session_fork::{compare_branches, BranchComparison, BranchSummary, SessionStore};
+pub use session_state::{ForkOrigin, SessionState, ModelConfig, Transcript};
 pub use system_prompt::PromptBuilder;
 pub use tool_dispatcher::{ToolDispatcher, ToolResult};
@@ -0,0 +1,235 @@
+//! Tool-loop intervention.
+//!
+//! Wraps `clawforge_tools::LoopDetector` for one run. Each detected loop
+//! (identical calls, or two calls alternating such as an edit and its revert)
+//! escalates the response: first a system nudge to the model, then handing
+//! the conversation back to the user, then aborting the run. Every
+//! intervention is kept for inspection and, with an event sender, broadcast
+//! as a `ToolLoopDetected` event of the current run. A guard is reused
+//! across runs; `begin_run` starts each one with a clean slate.
+
+use std::sync::Mutex;
+
+use clawforge_core::{Event, EventKind};
+use clawforge_tools::{hash_input, LoopDetector, LoopKind, ToolCall};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::chat::ToolCallRequest;
+
+/// Thresholds for detection and for each level of intervention.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoopGuardConfig {
+    /// Identical calls allowed before a loop is declared.
+    pub max_identical: usize,
+    /// A/B alternation rounds that count as a loop; 0 disables the check.
+    pub oscillation_cycles: usize,
+    /// Detections after which the user is asked to step in.
+    pub escalate_after: usize,
+    /// Detections after which the run is aborted.
+    pub abort_after: usize,
+}
+
+impl Default for LoopGuardConfig {
+    fn default() -> Self {
+        Self { max_identical: 3, oscillation_cycles: 3, escalate_after: 2, abort_after: 3 }
+    }
+}
+
+impl LoopGuardConfig {
+    /// `agents.defaults.loopGuard`, with defaults for whatever it leaves out.
+    pub fn from_settings(settings: &clawforge_config::schema::LoopGuardSettings) -> Self {
+        let defaults = Self::default();
+        Self {
+            max_identical: settings.max_identical.unwrap_or(defaults.max_identical),
+            oscillation_cycles: settings.oscillation_cycles.unwrap_or(defaults.oscillation_cycles),
+            escalate_after: settings.escalate_after.unwrap_or(defaults.escalate_after),
+            abort_after: settings.abort_after.unwrap_or(defaults.abort_after),
+        }
+    }
+}
+
+/// How to respond to a detected loop.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "message", rename_all = "snake_case")]
+pub enum LoopAction {
+    /// Tell the model it is looping and let it continue.
+    Nudge(String),
+    /// Stop and ask the user how to proceed.
+    Escalate(String),
+    /// Stop the run.
+    Abort(String),
+}
+
+impl LoopAction {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Nudge(m) | Self::Escalate(m) | Self::Abort(m) => m,
+        }
+    }
+
+    fn severity(&self) -> u8 {
+        match self {
+            Self::Nudge(_) => 0,
+            Self::Escalate(_) => 1,
+            Self::Abort(_) => 2,
+        }
+    }
+}
+
+/// One intervention, as recorded and broadcast.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopIntervention {
+    pub detection: usize,
+    #[serde(rename = "loop")]
+    pub kind: LoopKind,
+    #[serde(flatten)]
+    pub action: LoopAction,
+}
+
+struct GuardState {
+    detector: LoopDetector,
+    detections: usize,
+    /// Strongest action not yet taken by the runner.
+    pending: Option<LoopAction>,
+    log: Vec<LoopIntervention>,
+    /// Run and agent the events are attributed to.
+    run: (Uuid, Uuid),
+}
+
+pub struct LoopGuard {
+    config: LoopGuardConfig,
+    state: Mutex<GuardState>,
+    events: Option<broadcast::Sender<Event>>,
+}
+
+impl LoopGuard {
+    pub fn new(config: LoopGuardConfig) -> Self {
+        let detector = LoopDetector::new(config.max_identical).with_oscillation(config.oscillation_cycles);
+        Self {
+            config,
+            state: Mutex::new(GuardState { detector, detections: 0, pending: None, log: Vec::new(), run: (Uuid::nil(), Uuid::nil()) }),
+            events: None,
+        }
+    }
+
+    /// Broadcast interventions as `ToolLoopDetected` events.
+    pub fn with_events(mut self, tx: broadcast::Sender<Event>) -> Self {
+        self.events = Some(tx);
+        self
+    }
+
+    /// Record a call about to run. Returns the intervention when it completes
+    /// a loop, in which case the call should not run.
+    pub fn observe(&self, call: &ToolCallRequest) -> Option<LoopAction> {
+        let mut state = self.state.lock().unwrap();
+        let record = ToolCall { tool_name: call.name.clone(), input_hash: hash_input(&call.arguments.to_string()) };
+        let kind = state.detector.check(&record)?;
+        state.detections += 1;
+        let action = self.action_for(state.detections, &kind);
+        warn!(detection = state.detections, kind = ?kind, action = ?action, "Tool loop detected");
+
+        let intervention = LoopIntervention { detection: state.detections, kind, action: action.clone() };
+        if let Some(tx) = &self.events {
+            let (run_id, agent_id) = state.run;
+            let payload = serde_json::to_value(&intervention).unwrap_or_default();
+            let _ = tx.send(Event::new(run_id, agent_id, EventKind::ToolLoopDetected, payload));
+        }
+        state.log.push(intervention);
+        if state.pending.as_ref().is_none_or(|p| p.severity() < action.severity()) {
+            state.pending = Some(action.clone());
+        }
+        Some(action)
+    }
+
+    /// The strongest intervention since the last call, for the runner to act on.
+    pub fn take_action(&self) -> Option<LoopAction> {
+        self.state.lock().unwrap().pending.take()
+    }
+
+    /// Interventions so far in this run.
+    pub fn interventions(&self) -> Vec<LoopIntervention> {
+        self.state.lock().unwrap().log.clone()
+    }
+
+    /// Forget the previous run's calls and attribute events to this one.
+    pub fn begin_run(&self, run_id: Uuid, agent_id: Uuid) {
+        let mut state = self.state.lock().unwrap();
+        state.detector.reset();
+        state.detections = 0;
+        state.pending = None;
+        state.log.clear();
+        state.run = (run_id, agent_id);
+    }
+
+    fn action_for(&self, detection: usize, kind: &LoopKind) -> LoopAction {
+        let what = match kind {
+            LoopKind::Repeated { tool, count } => format!("called `{}` with the same arguments {} times", tool, count),
+            LoopKind::Oscillating { tools: (a, b), cycles } => {
+                format!("alternated between the same `{}` and `{}` calls {} times", a, b, cycles)
+            }
+        };
+        if detection >= self.config.abort_after {
+            LoopAction::Abort(format!("Run aborted: the agent {} and did not recover.", what))
+        } else if detection >= self.config.escalate_after {
+            LoopAction::Escalate(format!(
+                "I seem to be stuck: I {} without making progress. How would you like me to proceed?",
+                what
+            ))
+        } else {
+            LoopAction::Nudge(format!(
+                "You have {}. That call was not run again. Its result will not change; try a different approach or explain what is blocking you.",
+                what
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(name: &str, arguments: serde_json::Value) -> ToolCallRequest {
+        ToolCallRequest { id: "c".into(), name: name.into(), arguments }
+    }
+
+    #[test]
+    fn escalates_step_by_step_and_starts_over_per_run() {
+        let (tx, mut events) = broadcast::channel(8);
+        let guard = LoopGuard::new(LoopGuardConfig { max_identical: 1, ..Default::default() }).with_events(tx);
+        let (run, agent) = (Uuid::new_v4(), Uuid::new_v4());
+        guard.begin_run(run, agent);
+
+        // Each second identical call completes a loop; the detector then starts over.
+        let read = call("file_read", json!({ "path": "a.txt" }));
+        assert_eq!(guard.observe(&read), None);
+        assert!(matches!(guard.observe(&read), Some(LoopAction::Nudge(_))));
+        assert_eq!(guard.observe(&read), None);
+        assert!(matches!(guard.observe(&read), Some(LoopAction::Escalate(_))));
+        // The runner acts on the strongest pending action once.
+        assert!(matches!(guard.take_action(), Some(LoopAction::Escalate(_))));
+        assert_eq!(guard.take_action(), None);
+        assert_eq!(guard.observe(&read), None);
+        assert!(matches!(guard.observe(&read), Some(LoopAction::Abort(_))));
+
+        let event = events.try_recv().unwrap();
+        assert_eq!((event.run_id, event.agent_id, event.kind), (run, agent, EventKind::ToolLoopDetected));
+        assert_eq!(event.payload["action"], "nudge");
+        assert_eq!(guard.interventions().len(), 3);
+
+        guard.begin_run(Uuid::new_v4(), agent);
+        assert!(guard.interventions().is_empty());
+        assert_eq!(guard.observe(&read), None);
+    }
+
+    #[test]
+    fn settings_override_only_what_they_set() {
+        let settings = clawforge_config::schema::LoopGuardSettings { abort_after: Some(5), ..Default::default() };
+        let config = LoopGuardConfig::from_settings(&settings);
+        assert_eq!((config.max_identical, config.escalate_after, config.abort_after), (3, 2, 5));
+    }
+}
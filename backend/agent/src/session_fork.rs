@@ -0,0 +1,181 @@
+//! Conversation forking and branch comparison.
+//!
+//! A fork copies nothing up front: it shares the parent's transcript up to the
+//! fork point and diverges from there. Each branch lives in the store as its
+//! own session, so both can be driven by separate `AgentRunner`s (for example
+//! with different models) and compared afterwards.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::chat::{ChatMessage, MessageRole};
+use crate::session_state::SessionState;
+
+/// Outcome of one branch past the point where it diverged.
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchSummary {
+    pub session_id: String,
+    pub model: String,
+    pub message_count: usize,
+    /// Messages after the common prefix.
+    pub divergent: Vec<ChatMessage>,
+    pub tool_calls: usize,
+    /// Last assistant reply anywhere in the branch.
+    pub last_reply: Option<String>,
+}
+
+/// Side-by-side view of two sessions that share a transcript prefix.
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchComparison {
+    /// Number of leading messages both branches have in common.
+    pub common_prefix: usize,
+    /// Last shared message, if any.
+    pub fork_message_id: Option<Uuid>,
+    pub left: BranchSummary,
+    pub right: BranchSummary,
+}
+
+fn summarize(session: &SessionState, common_prefix: usize) -> BranchSummary {
+    let transcript = &session.transcript;
+    BranchSummary {
+        session_id: session.session_id.clone(),
+        model: session.model_config.model_name.clone(),
+        message_count: transcript.len(),
+        divergent: transcript[common_prefix..].to_vec(),
+        tool_calls: transcript[common_prefix..]
+            .iter()
+            .filter_map(|m| m.tool_calls.as_ref())
+            .map(Vec::len)
+            .sum(),
+        last_reply: transcript
+            .iter()
+            .rev()
+            .find(|m| m.role == MessageRole::Assistant)
+            .map(|m| m.content.clone()),
+    }
+}
+
+/// Compare two sessions by the message IDs they share.
+pub fn compare_branches(left: &SessionState, right: &SessionState) -> BranchComparison {
+    let common_prefix = left
+        .transcript
+        .iter()
+        .zip(right.transcript.iter())
+        .take_while(|(a, b)| a.id == b.id)
+        .count();
+    BranchComparison {
+        common_prefix,
+        fork_message_id: common_prefix.checked_sub(1).map(|i| left.transcript[i].id),
+        left: summarize(left, common_prefix),
+        right: summarize(right, common_prefix),
+    }
+}
+
+/// Live sessions addressable by ID, so branches can be forked and compared.
+#[derive(Default)]
+pub struct SessionStore {
+    sessions: RwLock<HashMap<String, Arc<RwLock<SessionState>>>>,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn insert(&self, session: SessionState) -> Arc<RwLock<SessionState>> {
+        let id = session.session_id.clone();
+        let handle = Arc::new(RwLock::new(session));
+        self.sessions.write().await.insert(id, Arc::clone(&handle));
+        handle
+    }
+
+    pub async fn get(&self, session_id: &str) -> Option<Arc<RwLock<SessionState>>> {
+        self.sessions.read().await.get(session_id).cloned()
+    }
+
+    /// Fork `session_id` after `message_id`, optionally switching the model.
+    /// Returns the new session's ID.
+    pub async fn fork(&self, session_id: &str, message_id: Uuid, model: Option<String>) -> Result<String> {
+        let parent = self
+            .get(session_id)
+            .await
+            .ok_or_else(|| anyhow!("Session '{}' not found", session_id))?;
+        let new_id = Uuid::new_v4().to_string();
+        let mut fork = parent
+            .read()
+            .await
+            .fork_at(message_id, new_id.clone())
+            .ok_or_else(|| anyhow!("Message {} not in session '{}'", message_id, session_id))?;
+        if let Some(model) = model {
+            fork.model_config.model_name = model;
+        }
+        self.insert(fork).await;
+        Ok(new_id)
+    }
+
+    /// Compare two stored sessions.
+    pub async fn compare(&self, left: &str, right: &str) -> Option<BranchComparison> {
+        let left = self.get(left).await?;
+        let right = self.get(right).await?;
+        let (left, right) = (left.read().await, right.read().await);
+        Some(compare_branches(&left, &right))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, messages: Vec<ChatMessage>) -> SessionState {
+        let mut session = SessionState::new(id, "agent");
+        for msg in messages {
+            session.transcript.push(msg);
+        }
+        session
+    }
+
+    #[tokio::test]
+    async fn fork_shares_prefix_and_diverges() {
+        let (question, answer) = (ChatMessage::user("hi"), ChatMessage::assistant("hello"));
+        let fork_point = question.id;
+        let store = SessionStore::new();
+        let parent = store.insert(session("main", vec![question, answer])).await;
+
+        let fork_id = store.fork("main", fork_point, Some("other-model".into())).await.unwrap();
+        let fork = store.get(&fork_id).await.unwrap();
+        {
+            let (parent, fork) = (parent.read().await, fork.read().await);
+            assert_eq!(fork.transcript.len(), 1);
+            assert!(fork.transcript.shares_storage_with(&parent.transcript));
+            assert_eq!(fork.forked_from.as_ref().unwrap().shared_len, 1);
+            assert_eq!(fork.model_config.model_name, "other-model");
+        }
+
+        fork.write().await.transcript.push(ChatMessage::assistant("hey there"));
+        {
+            let (parent, fork) = (parent.read().await, fork.read().await);
+            assert!(!fork.transcript.shares_storage_with(&parent.transcript));
+            assert_eq!(parent.transcript.len(), 2);
+            assert_eq!(parent.transcript[1].content, "hello");
+        }
+
+        let comparison = store.compare("main", &fork_id).await.unwrap();
+        assert_eq!(comparison.common_prefix, 1);
+        assert_eq!(comparison.fork_message_id, Some(fork_point));
+        assert_eq!(comparison.left.last_reply.as_deref(), Some("hello"));
+        assert_eq!(comparison.right.last_reply.as_deref(), Some("hey there"));
+        assert_eq!(comparison.right.divergent.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn fork_rejects_unknown_session_or_message() {
+        let store = SessionStore::new();
+        store.insert(session("main", vec![ChatMessage::user("hi")])).await;
+        assert!(store.fork("missing", Uuid::new_v4(), None).await.is_err());
+        assert!(store.fork("main", Uuid::new_v4(), None).await.is_err());
+    }
+}
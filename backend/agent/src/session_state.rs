@@ -3,6 +3,10 @@
 //! Mirrors `src/agents/runtime.ts` state holding aspect.
 
 use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use crate::chat::ChatMessage;
 
 /// Configuration for the model being used in the session.
@@ -23,16 +27,84 @@ impl Default for ModelConfig {
     }
 }
 
+/// Conversation transcript with copy-on-write storage.
+///
+/// Forks share the parent's messages up to the fork point; a branch only
+/// copies that prefix the first time it appends while the storage is shared.
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    messages: Arc<Vec<ChatMessage>>,
+    /// Visible length; forks taken mid-conversation see a prefix of `messages`.
+    len: usize,
+}
+
+impl Transcript {
+    pub fn push(&mut self, msg: ChatMessage) {
+        if Arc::strong_count(&self.messages) > 1 {
+            self.messages = Arc::new(self.messages[..self.len].to_vec());
+        }
+        let messages = Arc::get_mut(&mut self.messages).expect("transcript storage is unshared");
+        messages.truncate(self.len);
+        messages.push(msg);
+        self.len += 1;
+    }
+
+    /// Index of the message with the given ID.
+    pub fn position(&self, message_id: Uuid) -> Option<usize> {
+        self.iter().position(|m| m.id == message_id)
+    }
+
+    /// A new transcript sharing the first `len` messages of this one.
+    pub fn fork(&self, len: usize) -> Self {
+        Self { messages: Arc::clone(&self.messages), len: len.min(self.len) }
+    }
+
+    /// Whether both transcripts still read from the same storage.
+    pub fn shares_storage_with(&self, other: &Transcript) -> bool {
+        Arc::ptr_eq(&self.messages, &other.messages)
+    }
+}
+
+impl Deref for Transcript {
+    type Target = [ChatMessage];
+
+    fn deref(&self) -> &[ChatMessage] {
+        &self.messages[..self.len]
+    }
+}
+
+impl From<Vec<ChatMessage>> for Transcript {
+    fn from(messages: Vec<ChatMessage>) -> Self {
+        let len = messages.len();
+        Self { messages: Arc::new(messages), len }
+    }
+}
+
+/// Where a forked session branched off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForkOrigin {
+    pub session_id: String,
+    /// Last message shared with the parent.
+    pub message_id: Uuid,
+    /// Number of messages inherited from the parent.
+    pub shared_len: usize,
+}
+
 /// Active state of a conversation session.
 #[derive(Debug, Clone)]
 pub struct SessionState {
     pub session_id: String,
     pub agent_id: String,
     /// Full, un-compacted conversation transcript.
-    pub transcript: Vec<ChatMessage>,
+    pub transcript: Transcript,
     pub model_config: ModelConfig,
     /// Variables and context scoped to this session.
     pub context_vars: HashMap<String, String>,
+    /// Set when this session was forked from another one.
+    pub forked_from: Option<ForkOrigin>,
+    /// Person (or `channel:sender` account) the session is with; keys
+    /// per-person preferences.
+    pub principal: Option<String>,
 }
 
 impl SessionState {
@@ -40,9 +112,35 @@ impl SessionState {
         Self {
             session_id: session_id.into(),
             agent_id: agent_id.into(),
-            transcript: Vec::new(),
+            transcript: Transcript::default(),
             model_config: ModelConfig::default(),
             context_vars: HashMap::new(),
+            forked_from: None,
+            principal: None,
         }
     }
+
+    pub fn with_principal(mut self, principal: impl Into<String>) -> Self {
+        self.principal = Some(principal.into());
+        self
+    }
+
+    /// Branch this session after `message_id`. The fork keeps the model
+    /// config and context vars; callers may swap the model before continuing.
+    pub fn fork_at(&self, message_id: Uuid, new_session_id: impl Into<String>) -> Option<Self> {
+        let shared_len = self.transcript.position(message_id)? + 1;
+        Some(Self {
+            session_id: new_session_id.into(),
+            agent_id: self.agent_id.clone(),
+            transcript: self.transcript.fork(shared_len),
+            model_config: self.model_config.clone(),
+            context_vars: self.context_vars.clone(),
+            forked_from: Some(ForkOrigin {
+                session_id: self.session_id.clone(),
+                message_id,
+                shared_len,
+            }),
+            principal: self.principal.clone(),
+        })
+    }
 }
@@ -6,20 +6,37 @@ use crate::assistant_identity::AssistantIdentity;
 use crate::chat::ChatMessage;
 use crate::prompt_cache::PromptCache;
 use crate::session_state::SessionState;
+use clawforge_security::PreferenceStore;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 pub struct PromptBuilder {
     cache: Arc<PromptCache>,
+    preferences: Option<Arc<PreferenceStore>>,
 }
 
 impl PromptBuilder {
     pub fn new(cache: Arc<PromptCache>) -> Self {
-        Self { cache }
+        Self { cache, preferences: None }
+    }
+
+    /// Inject the preferences of the session's principal.
+    pub fn with_preferences(mut self, preferences: Arc<PreferenceStore>) -> Self {
+        self.preferences = Some(preferences);
+        self
     }
 
     /// Builds the monolithic system prompt that configures the agent's behavior.
     pub fn build(&self, session: &SessionState, identity: &AssistantIdentity) -> ChatMessage {
-        let cache_key = format!("{}:{}", session.session_id, session.agent_id);
+        let preferences = match (&self.preferences, &session.principal) {
+            (Some(store), Some(principal)) => store.get(principal).to_prompt(),
+            _ => None,
+        };
+        // Preferences can change mid-session; key the cache on them too.
+        let mut hasher = DefaultHasher::new();
+        preferences.hash(&mut hasher);
+        let cache_key = format!("{}:{}:{:x}", session.session_id, session.agent_id, hasher.finish());
 
         if let Some(cached) = self.cache.get(&cache_key) {
             return cached;
@@ -35,10 +52,14 @@ impl PromptBuilder {
         let tools = "Tools available: []";
 
         // Assemble into a single system message
-        let content = format!(
+        let mut content = format!(
             "{}\n\n{}\n\nRULES:\n1. Be helpful.\n2. Do NOT use fake tool calls.\n\n{}",
             persona, memory, tools
         );
+        if let Some(preferences) = preferences {
+            content.push_str("\n\n");
+            content.push_str(&preferences);
+        }
 
         let msg = ChatMessage::system(content);
         self.cache.insert(cache_key, msg.clone());
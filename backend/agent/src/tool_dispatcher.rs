@@ -1,13 +1,28 @@
 //! Dispatcher for agent tool calls.
 //!
 //! Routes the model's requested tool invocations to the actual execution layer.
+//! Calls are checked against the tool policy first; denied calls come back as
+//! failed results so the model sees why, and are logged with the matched rule.
+//! Allowed calls run in the environment the execution matrix picks for the tool.
+//! Calls pass a loop guard (default thresholds unless one is attached); a
+//! call that completes a tool loop is not run and its result carries the
+//! intervention instead.
 
 use anyhow::Result;
 use crate::chat::ToolCallRequest;
+use crate::loop_guard::{LoopAction, LoopGuard, LoopGuardConfig};
+use clawforge_core::{ExecutionEnv, ExecutionMatrix, ToolPolicyDecision, ToolPolicyEngine};
 use serde_json::Value;
+use std::sync::Arc;
+use tracing::{debug, warn};
 
 pub struct ToolDispatcher {
     // In a real implementation this would hold a registry of Tool handlers.
+    policy: Option<Arc<ToolPolicyEngine>>,
+    execution: Option<Arc<ExecutionMatrix>>,
+    agent: Option<String>,
+    channel: Option<String>,
+    loop_guard: Arc<LoopGuard>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -19,16 +34,95 @@ pub struct ToolResult {
 
 impl ToolDispatcher {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            policy: None,
+            execution: None,
+            agent: None,
+            channel: None,
+            loop_guard: Arc::new(LoopGuard::new(LoopGuardConfig::default())),
+        }
+    }
+
+    /// Agent and channel this dispatcher serves, for policy and execution lookups.
+    pub fn with_context(mut self, agent: Option<String>, channel: Option<String>) -> Self {
+        self.agent = agent;
+        self.channel = channel;
+        self
+    }
+
+    /// Check every call against `policy`.
+    pub fn with_policy(mut self, policy: Arc<ToolPolicyEngine>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Pick each tool's execution environment from `matrix` instead of the global sandbox driver.
+    pub fn with_execution(mut self, matrix: Arc<ExecutionMatrix>) -> Self {
+        self.execution = Some(matrix);
+        self
+    }
+
+    /// Watch calls with `guard`, e.g. one built from `agents.defaults.loopGuard`
+    /// that broadcasts its interventions.
+    pub fn with_loop_guard(mut self, guard: Arc<LoopGuard>) -> Self {
+        self.loop_guard = guard;
+        self
+    }
+
+    pub fn loop_guard(&self) -> &Arc<LoopGuard> {
+        &self.loop_guard
+    }
+
+    /// Environment `tool` runs in for this dispatcher's agent.
+    pub fn environment(&self, tool: &str) -> ExecutionEnv {
+        self.execution
+            .as_ref()
+            .map(|m| m.resolve(tool, self.agent.as_deref()))
+            .unwrap_or(ExecutionEnv::Host)
+    }
+
+    /// Policy decision for `tool`, or `None` when no policy is configured.
+    pub fn check(&self, tool: &str) -> Option<ToolPolicyDecision> {
+        let decision = self.policy.as_ref()?.evaluate(tool, self.agent.as_deref(), self.channel.as_deref());
+        if decision.allowed {
+            debug!(tool = %tool, rule = ?decision.rule, "Tool call allowed by policy");
+        } else {
+            warn!(tool = %tool, rule = ?decision.rule, "Tool call denied by policy");
+        }
+        Some(decision)
+    }
+
+    fn denied(decision: ToolPolicyDecision) -> ToolResult {
+        ToolResult {
+            success: false,
+            data: serde_json::json!({ "rule": decision.rule }),
+            error: Some(decision.reason()),
+        }
+    }
+
+    fn looped(action: LoopAction) -> ToolResult {
+        ToolResult {
+            success: false,
+            data: serde_json::json!({ "loop_intervention": action }),
+            error: Some(action.message().to_string()),
+        }
     }
 
     /// Dispatch a single tool call to the corresponding handler.
     pub async fn execute(&self, call: ToolCallRequest) -> Result<ToolResult> {
+        if let Some(decision) = self.check(&call.name).filter(|d| !d.allowed) {
+            return Ok(Self::denied(decision));
+        }
+        if let Some(action) = self.loop_guard.observe(&call) {
+            return Ok(Self::looped(action));
+        }
+        let environment = self.environment(&call.name);
+        debug!(tool = %call.name, environment = environment.as_str(), "Dispatching tool call");
         // Mock tool execution logic.
         // Would look up `call.name` in registry, deserialize `call.arguments`, invoke, and return.
         Ok(ToolResult {
             success: true,
-            data: serde_json::json!({ "note": format!("Executed {}", call.name) }),
+            data: serde_json::json!({ "note": format!("Executed {}", call.name), "environment": environment }),
             error: None,
         })
     }
@@ -41,10 +135,20 @@ impl ToolDispatcher {
             // but in real code we'd use futures::future::join_all or spawn.
             // For this mock, we'll just run them semi-sequentially or spawn if thread-safe.
             
+            if let Some(decision) = self.check(&call.name).filter(|d| !d.allowed) {
+                handlers.push(Self::denied(decision));
+                continue;
+            }
+            if let Some(action) = self.loop_guard.observe(&call) {
+                handlers.push(Self::looped(action));
+                continue;
+            }
+
             // Just returning mock success for all tools.
+            let environment = self.environment(&call.name);
             handlers.push(ToolResult {
                 success: true,
-                data: serde_json::json!({ "note": format!("Executed {}", call.name) }),
+                data: serde_json::json!({ "note": format!("Executed {}", call.name), "environment": environment }),
                 error: None,
             });
         }
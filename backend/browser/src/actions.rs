@@ -0,0 +1,316 @@
+//! Browser Actions
+//!
+//! A model-friendly layer over CDP: the page is described as an accessibility
+//! snapshot in which every interactive or landmark element carries a short id
+//! (`e7`), and actions address elements by that id instead of by selectors or
+//! coordinates. Ids are tied to Chromium's backend node ids, so an element
+//! keeps its id across snapshots for as long as it stays in the DOM.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::screenshot::ScreenshotCapturer;
+use crate::session::BrowserSession;
+
+/// Roles listed in a snapshot; everything else is structure or plain text.
+const SNAPSHOT_ROLES: &[&str] = &[
+    "button", "link", "textbox", "searchbox", "checkbox", "radio", "combobox", "listbox", "option", "menuitem",
+    "tab", "switch", "slider", "spinbutton", "heading", "img", "dialog", "alert",
+];
+/// Snapshot lines beyond this are dropped; the model can scroll and re-snapshot.
+const MAX_SNAPSHOT_ELEMENTS: usize = 400;
+const WAIT_POLL: Duration = Duration::from_millis(250);
+const DEFAULT_WAIT: Duration = Duration::from_secs(10);
+
+/// Short ids handed out per backend DOM node; kept with the session.
+#[derive(Debug, Default)]
+pub struct ElementRefs {
+    by_node: HashMap<i64, String>,
+    by_id: HashMap<String, i64>,
+    next: usize,
+}
+
+impl ElementRefs {
+    fn id_for(&mut self, backend_node: i64) -> String {
+        if let Some(id) = self.by_node.get(&backend_node) {
+            return id.clone();
+        }
+        self.next += 1;
+        let id = format!("e{}", self.next);
+        self.by_node.insert(backend_node, id.clone());
+        self.by_id.insert(id.clone(), backend_node);
+        id
+    }
+
+    fn node(&self, id: &str) -> Result<i64> {
+        self.by_id
+            .get(id)
+            .copied()
+            .ok_or_else(|| anyhow!("Unknown element id '{}'; take a new snapshot", id))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnapshotElement {
+    pub id: String,
+    pub role: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageSnapshot {
+    pub url: String,
+    pub title: String,
+    pub elements: Vec<SnapshotElement>,
+    pub truncated: bool,
+}
+
+impl PageSnapshot {
+    /// One line per element: `[e3] button "Sign in"`.
+    pub fn render(&self) -> String {
+        let mut out = format!("{} — {}\n", self.title, self.url);
+        for el in &self.elements {
+            out.push_str(&format!("[{}] {} \"{}\"", el.id, el.role, el.name));
+            if let Some(value) = &el.value {
+                out.push_str(&format!(" = \"{}\"", value));
+            }
+            if el.disabled {
+                out.push_str(" (disabled)");
+            }
+            out.push('\n');
+        }
+        if self.truncated {
+            out.push_str("… more elements below; scroll and snapshot again\n");
+        }
+        out
+    }
+}
+
+/// What `wait_for` waits for.
+#[derive(Debug, Clone)]
+pub enum WaitTarget {
+    Selector(String),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ScrollTarget<'a> {
+    /// Scroll the page by this many pixels (negative is up).
+    By(i64),
+    /// Bring an element into view.
+    Element(&'a str),
+}
+
+/// Actions on one session's current tab.
+pub struct BrowserActions<'a> {
+    session: &'a mut BrowserSession,
+}
+
+impl<'a> BrowserActions<'a> {
+    pub fn new(session: &'a mut BrowserSession) -> Self {
+        Self { session }
+    }
+
+    async fn cdp(&self, method: &str, params: Value) -> Result<Value> {
+        self.session.cdp.send_command(method, params).await
+    }
+
+    pub async fn navigate(&mut self, url: &str) -> Result<()> {
+        info!(agent = %self.session.profile.agent, url = %url, "Browser navigate");
+        let result = self.cdp("Page.navigate", json!({ "url": url })).await?;
+        if let Some(error) = result.get("errorText").and_then(|e| e.as_str()) {
+            bail!("Navigation to {} failed: {}", url, error);
+        }
+        Ok(())
+    }
+
+    /// Accessibility snapshot of the page with stable element ids.
+    pub async fn snapshot(&mut self) -> Result<PageSnapshot> {
+        let tree = self.cdp("Accessibility.getFullAXTree", json!({})).await?;
+        let url = self.evaluate("location.href").await?.as_str().unwrap_or_default().to_string();
+        let title = self.evaluate("document.title").await?.as_str().unwrap_or_default().to_string();
+        let (elements, truncated) = elements_from_ax_tree(&tree, &mut self.session.elements);
+        Ok(PageSnapshot { url, title, elements, truncated })
+    }
+
+    pub async fn click(&mut self, id: &str) -> Result<()> {
+        let node = self.session.elements.node(id)?;
+        self.cdp("DOM.scrollIntoViewIfNeeded", json!({ "backendNodeId": node })).await?;
+        let (x, y) = self.center(node).await?;
+        for kind in ["mousePressed", "mouseReleased"] {
+            self.cdp(
+                "Input.dispatchMouseEvent",
+                json!({ "type": kind, "x": x, "y": y, "button": "left", "clickCount": 1 }),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Focus the element, clear it, and insert `text`.
+    pub async fn type_text(&mut self, id: &str, text: &str) -> Result<()> {
+        let node = self.session.elements.node(id)?;
+        self.cdp("DOM.focus", json!({ "backendNodeId": node })).await?;
+        self.evaluate("(() => { const el = document.activeElement; if (el && 'value' in el) el.value = ''; })()").await?;
+        self.cdp("Input.insertText", json!({ "text": text })).await?;
+        Ok(())
+    }
+
+    /// Press a key such as `Enter`, `Tab` or `Escape` in the focused element.
+    pub async fn press(&mut self, key: &str) -> Result<()> {
+        for kind in ["keyDown", "keyUp"] {
+            self.cdp("Input.dispatchKeyEvent", json!({ "type": kind, "key": key, "code": key })).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn scroll(&mut self, target: ScrollTarget<'_>) -> Result<()> {
+        match target {
+            ScrollTarget::Element(id) => {
+                let node = self.session.elements.node(id)?;
+                self.cdp("DOM.scrollIntoViewIfNeeded", json!({ "backendNodeId": node })).await?;
+            }
+            ScrollTarget::By(pixels) => {
+                self.evaluate(&format!("window.scrollBy(0, {})", pixels)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Poll until the selector matches or the text appears, or `timeout` passes.
+    pub async fn wait_for(&mut self, target: &WaitTarget, timeout: Option<Duration>) -> Result<()> {
+        let check = match target {
+            WaitTarget::Selector(selector) => format!("!!document.querySelector({})", js_string(selector)),
+            WaitTarget::Text(text) => format!("!!document.body && document.body.innerText.includes({})", js_string(text)),
+        };
+        let timeout = timeout.unwrap_or(DEFAULT_WAIT);
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if self.evaluate(&check).await?.as_bool() == Some(true) {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                bail!("Timed out after {:?} waiting for {:?}", timeout, target);
+            }
+            tokio::time::sleep(WAIT_POLL).await;
+        }
+    }
+
+    /// Screenshots and PDFs of the current page, labelled with its URL.
+    pub async fn capturer(&self) -> Result<ScreenshotCapturer<'_>> {
+        let url = self.evaluate("location.href").await?.as_str().unwrap_or_default().to_string();
+        Ok(ScreenshotCapturer::new(&self.session.cdp).with_source(url))
+    }
+
+    async fn evaluate(&self, expression: &str) -> Result<Value> {
+        let result = self
+            .cdp("Runtime.evaluate", json!({ "expression": expression, "returnByValue": true }))
+            .await?;
+        if let Some(exception) = result.get("exceptionDetails") {
+            bail!("Script failed: {}", exception["text"].as_str().unwrap_or("exception"));
+        }
+        Ok(result.pointer("/result/value").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Center of the element's content box, in CSS pixels.
+    async fn center(&self, node: i64) -> Result<(f64, f64)> {
+        let model = self.cdp("DOM.getBoxModel", json!({ "backendNodeId": node })).await?;
+        let quad: Vec<f64> = model
+            .pointer("/model/content")
+            .and_then(|q| q.as_array())
+            .map(|q| q.iter().filter_map(|v| v.as_f64()).collect())
+            .unwrap_or_default();
+        if quad.len() < 8 {
+            bail!("Element is not rendered");
+        }
+        let x = (quad[0] + quad[2] + quad[4] + quad[6]) / 4.0;
+        let y = (quad[1] + quad[3] + quad[5] + quad[7]) / 4.0;
+        Ok((x, y))
+    }
+}
+
+/// Interesting nodes of an `Accessibility.getFullAXTree` result.
+pub fn elements_from_ax_tree(tree: &Value, refs: &mut ElementRefs) -> (Vec<SnapshotElement>, bool) {
+    let Some(nodes) = tree.get("nodes").and_then(|n| n.as_array()) else {
+        return (Vec::new(), false);
+    };
+    let mut elements = Vec::new();
+    for node in nodes {
+        if node["ignored"].as_bool() == Some(true) {
+            continue;
+        }
+        let role = node.pointer("/role/value").and_then(|r| r.as_str()).unwrap_or_default();
+        if !SNAPSHOT_ROLES.contains(&role) {
+            continue;
+        }
+        let Some(backend) = node["backendDOMNodeId"].as_i64() else { continue };
+        if elements.len() == MAX_SNAPSHOT_ELEMENTS {
+            return (elements, true);
+        }
+        let property = |name: &str| {
+            node["properties"]
+                .as_array()
+                .and_then(|props| props.iter().find(|p| p["name"] == name))
+                .and_then(|p| p.pointer("/value/value").cloned())
+        };
+        elements.push(SnapshotElement {
+            id: refs.id_for(backend),
+            role: role.to_string(),
+            name: node.pointer("/name/value").and_then(|n| n.as_str()).unwrap_or_default().trim().to_string(),
+            value: node.pointer("/value/value").map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string())),
+            disabled: property("disabled").and_then(|d| d.as_bool()).unwrap_or(false),
+        });
+    }
+    (elements, false)
+}
+
+fn js_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(role: &str, name: &str, backend: i64) -> Value {
+        json!({ "role": { "value": role }, "name": { "value": name }, "backendDOMNodeId": backend })
+    }
+
+    #[test]
+    fn snapshot_keeps_interesting_nodes_with_stable_ids() {
+        let mut refs = ElementRefs::default();
+        let mut field = node("textbox", "Email", 12);
+        field["value"] = json!({ "value": "me@example.com" });
+        let mut submit = node("button", " Sign in ", 14);
+        submit["properties"] = json!([{ "name": "disabled", "value": { "value": true } }]);
+        let mut hidden = node("link", "Skip", 15);
+        hidden["ignored"] = json!(true);
+        let tree = json!({ "nodes": [node("RootWebArea", "Login", 1), field, node("StaticText", "Hi", 13), submit, hidden, { "role": { "value": "link" } }] });
+
+        let (elements, truncated) = elements_from_ax_tree(&tree, &mut refs);
+        assert!(!truncated);
+        let lines: Vec<_> = elements.iter().map(|e| (e.id.as_str(), e.role.as_str(), e.name.as_str(), e.value.as_deref(), e.disabled)).collect();
+        assert_eq!(lines, [("e1", "textbox", "Email", Some("me@example.com"), false), ("e2", "button", "Sign in", None, true)]);
+
+        // The same DOM node keeps its id in the next snapshot; new ones get fresh ids.
+        let tree = json!({ "nodes": [node("link", "Help", 20), node("button", "Sign in", 14)] });
+        let ids: Vec<_> = elements_from_ax_tree(&tree, &mut refs).0.into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, ["e3", "e2"]);
+        assert_eq!(refs.node("e2").unwrap(), 14);
+        assert!(refs.node("e9").is_err());
+
+        let many: Vec<Value> = (0..MAX_SNAPSHOT_ELEMENTS as i64 + 1).map(|i| node("link", "x", 100 + i)).collect();
+        let (elements, truncated) = elements_from_ax_tree(&json!({ "nodes": many }), &mut refs);
+        assert_eq!((elements.len(), truncated), (MAX_SNAPSHOT_ELEMENTS, true));
+        assert_eq!(elements_from_ax_tree(&json!({}), &mut refs), (Vec::new(), false));
+    }
+}
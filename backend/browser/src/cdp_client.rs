@@ -1,30 +1,198 @@
 //! Chrome DevTools Protocol Client
 //!
-//! Exposes a WebSocket binding to attach to a running Chromium headless instance
-//! to issue high-level debug commands.
+//! Speaks CDP over the browser's DevTools WebSocket. Commands are matched to
+//! their responses by id, so any number can be in flight at once. After
+//! `connect`, the client attaches to the first page target in flat mode and
+//! sends page-level domains (`Page`, `Runtime`, `DOM`, `Input`, ...) on that
+//! session; browser-level domains (`Browser`, `Target`, `Storage`) go to the
+//! browser itself. Events are not consumed.
 
-use anyhow::Result;
-use tracing::info;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tracing::{debug, info, warn};
+
+/// How long a command may wait for its response.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+/// Domains served by the browser target rather than the page.
+const BROWSER_DOMAINS: &[&str] = &["Browser", "Target", "Storage", "SystemInfo"];
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value>>>>>;
 
 pub struct CdpClient {
     ws_endpoint: String,
+    next_id: AtomicU64,
+    pending: Pending,
+    /// Frames for the writer task; set by `connect`.
+    outgoing: OnceLock<mpsc::UnboundedSender<String>>,
+    /// Flat session of the attached page.
+    page_session: OnceLock<String>,
 }
 
 impl CdpClient {
     pub fn new(ws_endpoint: &str) -> Self {
-        Self { ws_endpoint: ws_endpoint.into() }
+        Self {
+            ws_endpoint: ws_endpoint.into(),
+            next_id: AtomicU64::new(1),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            outgoing: OnceLock::new(),
+            page_session: OnceLock::new(),
+        }
     }
 
-    /// Attaches onto the Chrome endpoint and negotiates a session.
+    /// Open the WebSocket and attach to a page, creating a blank one when
+    /// the browser has none.
     pub async fn connect(&self) -> Result<()> {
         info!("Connecting to CDP websocket at {}", self.ws_endpoint);
-        // MOCK: tokio-tungstenite WebSocket connection loop
+        let (socket, _) = tokio_tungstenite::connect_async(self.ws_endpoint.as_str())
+            .await
+            .with_context(|| format!("Cannot reach DevTools at {}", self.ws_endpoint))?;
+        let (mut sink, mut stream) = socket.split();
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        if self.outgoing.set(tx).is_err() {
+            bail!("CDP client is already connected");
+        }
+        tokio::spawn(async move {
+            while let Some(frame) = rx.recv().await {
+                if sink.send(WsMessage::Text(frame)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let pending = Arc::clone(&self.pending);
+        tokio::spawn(async move {
+            while let Some(Ok(message)) = stream.next().await {
+                let WsMessage::Text(text) = message else { continue };
+                let Ok(frame) = serde_json::from_str::<Value>(&text) else { continue };
+                let Some(id) = frame.get("id").and_then(Value::as_u64) else { continue };
+                let Some(reply) = pending.lock().unwrap().remove(&id) else { continue };
+                let _ = reply.send(response(frame));
+            }
+            // Dropping the senders fails every command still waiting.
+            pending.lock().unwrap().clear();
+            debug!("CDP connection closed");
+        });
+        self.attach_page().await
+    }
+
+    async fn attach_page(&self) -> Result<()> {
+        let targets = self.send_command("Target.getTargets", json!({})).await?;
+        let page = targets["targetInfos"]
+            .as_array()
+            .and_then(|infos| infos.iter().find(|info| info["type"] == "page"))
+            .and_then(|info| info["targetId"].as_str())
+            .map(str::to_string);
+        let target_id = match page {
+            Some(id) => id,
+            None => {
+                let created = self.send_command("Target.createTarget", json!({ "url": "about:blank" })).await?;
+                created["targetId"].as_str().ok_or_else(|| anyhow!("Target.createTarget returned no targetId"))?.to_string()
+            }
+        };
+        let attached = self.send_command("Target.attachToTarget", json!({ "targetId": target_id, "flatten": true })).await?;
+        let session = attached["sessionId"].as_str().ok_or_else(|| anyhow!("Target.attachToTarget returned no sessionId"))?;
+        let _ = self.page_session.set(session.to_string());
+        debug!(target = %target_id, "Attached to page");
         Ok(())
     }
 
-    /// Dispatches a raw JSON-RPC payload into the headless browser session.
-    pub async fn send_command(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
-        info!("Sending CDP Command: {}", method);
-        Ok(serde_json::json!({ "mock": "success" }))
+    /// Send a command and wait for its result; a CDP error becomes `Err`.
+    pub async fn send_command(&self, method: &str, params: Value) -> Result<Value> {
+        let outgoing = self.outgoing.get().ok_or_else(|| anyhow!("CDP client is not connected"))?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut frame = json!({ "id": id, "method": method, "params": params });
+        let domain = method.split('.').next().unwrap_or_default();
+        if let Some(session) = self.page_session.get().filter(|_| !BROWSER_DOMAINS.contains(&domain)) {
+            frame["sessionId"] = session.as_str().into();
+        }
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        debug!("Sending CDP Command: {}", method);
+        if outgoing.send(frame.to_string()).is_err() {
+            self.pending.lock().unwrap().remove(&id);
+            bail!("CDP connection closed");
+        }
+        match tokio::time::timeout(COMMAND_TIMEOUT, rx).await {
+            Ok(Ok(result)) => result.with_context(|| format!("CDP {} failed", method)),
+            Ok(Err(_)) => bail!("CDP connection closed during {}", method),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                warn!("CDP {} timed out", method);
+                bail!("CDP {} timed out after {:?}", method, COMMAND_TIMEOUT)
+            }
+        }
+    }
+}
+
+fn response(mut frame: Value) -> Result<Value> {
+    match frame.get("error") {
+        Some(error) => Err(anyhow!("{}", error["message"].as_str().unwrap_or("unknown error"))),
+        None => Ok(frame.get_mut("result").map(Value::take).unwrap_or(Value::Null)),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use base64::Engine;
+    use tokio::net::TcpListener;
+
+    /// A DevTools endpoint with one 800x20000 page: answers `Target.*` and
+    /// `Page.getLayoutMetrics`, returns the params of captures as their
+    /// base64 `data`, fails `Fail.now`, and echoes anything else with the
+    /// session it came on.
+    pub(crate) async fn fake_devtools() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(WsMessage::Text(text))) = socket.next().await {
+                        let request: Value = serde_json::from_str(&text).unwrap();
+                        let result = match request["method"].as_str().unwrap() {
+                            "Target.getTargets" => json!({ "targetInfos": [{ "targetId": "W1", "type": "worker" }, { "targetId": "P1", "type": "page" }] }),
+                            "Target.attachToTarget" => json!({ "sessionId": format!("S-{}", request["params"]["targetId"].as_str().unwrap()) }),
+                            "Page.getLayoutMetrics" => json!({ "cssContentSize": { "width": 800, "height": 20000 } }),
+                            "Page.captureScreenshot" | "Page.printToPDF" => {
+                                json!({ "data": base64::engine::general_purpose::STANDARD.encode(request["params"].to_string()) })
+                            }
+                            "Fail.now" => {
+                                let error = json!({ "id": request["id"], "error": { "code": -32000, "message": "nope" } });
+                                socket.send(WsMessage::Text(error.to_string())).await.unwrap();
+                                continue;
+                            }
+                            method => json!({ "method": method, "sessionId": request["sessionId"] }),
+                        };
+                        let reply = json!({ "id": request["id"], "result": result });
+                        socket.send(WsMessage::Text(reply.to_string())).await.unwrap();
+                    }
+                });
+            }
+        });
+        format!("ws://{}/devtools/browser/test", addr)
+    }
+
+    #[tokio::test]
+    async fn attaches_to_the_page_and_routes_commands() {
+        let cdp = CdpClient::new(&fake_devtools().await);
+        assert!(cdp.send_command("Page.enable", json!({})).await.is_err());
+        cdp.connect().await.unwrap();
+
+        let (page, cookies) = tokio::join!(
+            cdp.send_command("Runtime.evaluate", json!({ "expression": "1" })),
+            cdp.send_command("Storage.getCookies", json!({})),
+        );
+        assert_eq!(page.unwrap(), json!({ "method": "Runtime.evaluate", "sessionId": "S-P1" }));
+        assert_eq!(cookies.unwrap(), json!({ "method": "Storage.getCookies", "sessionId": null }));
+        let err = cdp.send_command("Fail.now", json!({})).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("nope"), "{:#}", err);
     }
 }
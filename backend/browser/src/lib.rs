@@ -1,9 +1,15 @@
+pub mod actions;
 pub mod cdp_client;
 pub mod page_control;
 pub mod element_query;
 pub mod screenshot;
+pub mod profile;
+pub mod session;
 
 pub use cdp_client::CdpClient;
 pub use page_control::PageControl;
 pub use element_query::ElementQuery;
-pub use screenshot::ScreenshotCapturer;
+pub use screenshot::{PdfOptions, ScreenshotCapturer};
+pub use profile::{BrowserProfile, ProfileStore, StorageState};
+pub use session::{BrowserSession, LaunchOptions, SessionPool};
+pub use actions::{BrowserActions, ElementRefs, PageSnapshot, ScrollTarget, SnapshotElement, WaitTarget};
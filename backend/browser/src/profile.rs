@@ -0,0 +1,187 @@
+//! Persistent Browser Profiles
+//!
+//! Each agent gets its own Chromium user-data directory so cookies, logins and
+//! localStorage survive between runs. Chromium keeps persistent cookies there
+//! by itself; session cookies and localStorage are additionally snapshotted to
+//! `clawforge-state.json` when a session closes and replayed when the next one
+//! opens, so a "remember me"-less login still carries over.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const STATE_FILE: &str = "clawforge-state.json";
+
+/// Cookies and per-origin localStorage captured from a session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageState {
+    /// `Network.Cookie` objects as returned by `Storage.getCookies`.
+    #[serde(default)]
+    pub cookies: Vec<Value>,
+    /// origin → key → value
+    #[serde(default)]
+    pub local_storage: HashMap<String, HashMap<String, String>>,
+}
+
+impl StorageState {
+    /// Script for `Page.addScriptToEvaluateOnNewDocument` that seeds each
+    /// origin's localStorage with the saved entries it doesn't have yet.
+    pub fn restore_script(&self) -> Option<String> {
+        if self.local_storage.is_empty() {
+            return None;
+        }
+        let data = serde_json::to_string(&self.local_storage).ok()?;
+        Some(format!(
+            "(() => {{ const saved = {}[location.origin]; if (!saved) return; \
+             for (const [k, v] of Object.entries(saved)) {{ if (localStorage.getItem(k) === null) localStorage.setItem(k, v); }} }})();",
+            data
+        ))
+    }
+}
+
+/// One agent's profile directory.
+#[derive(Debug, Clone)]
+pub struct BrowserProfile {
+    pub agent: String,
+    pub dir: PathBuf,
+}
+
+impl BrowserProfile {
+    /// Chromium `--user-data-dir`.
+    pub fn user_data_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn load_state(&self) -> Result<StorageState> {
+        let path = self.dir.join(STATE_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).with_context(|| format!("Corrupt browser state {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(StorageState::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save_state(&self, state: &StorageState) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let tmp = self.dir.join(format!("{}.tmp", STATE_FILE));
+        std::fs::write(&tmp, serde_json::to_vec_pretty(state)?)?;
+        std::fs::rename(&tmp, self.dir.join(STATE_FILE))?;
+        Ok(())
+    }
+}
+
+/// Root directory holding one profile per agent.
+#[derive(Debug, Clone)]
+pub struct ProfileStore {
+    root: PathBuf,
+}
+
+impl ProfileStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// `$CLAWFORGE_BROWSER_PROFILES`, else `<data dir>/clawforge/browser-profiles`.
+    pub fn default_location() -> Self {
+        let root = std::env::var("CLAWFORGE_BROWSER_PROFILES").map(PathBuf::from).unwrap_or_else(|_| {
+            dirs::data_dir().unwrap_or_else(std::env::temp_dir).join("clawforge").join("browser-profiles")
+        });
+        Self::new(root)
+    }
+
+    /// The agent's profile, created on first use.
+    pub fn profile(&self, agent: &str) -> Result<BrowserProfile> {
+        let dir = self.root.join(dir_name(agent)?);
+        std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create browser profile {}", dir.display()))?;
+        Ok(BrowserProfile { agent: agent.to_string(), dir })
+    }
+
+    /// Agents that have a profile.
+    pub fn list(&self) -> Result<Vec<String>> {
+        let entries = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut agents: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| agent_name(&e.file_name().to_string_lossy()))
+            .collect();
+        agents.sort();
+        Ok(agents)
+    }
+
+    /// Forget everything the agent's browser knew (logins included).
+    pub fn delete(&self, agent: &str) -> Result<bool> {
+        let dir = self.root.join(dir_name(agent)?);
+        match std::fs::remove_dir_all(&dir) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Agent ids become directory names: ASCII letters, digits, `-` and `_`
+/// are kept and every other byte is written as `%XX`, so distinct agents
+/// never share a profile.
+fn dir_name(agent: &str) -> Result<String> {
+    if agent.is_empty() {
+        anyhow::bail!("Agent id is empty");
+    }
+    Ok(agent
+        .bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect())
+}
+
+/// The agent a profile directory belongs to; `None` for names `dir_name`
+/// never produces.
+fn agent_name(dir: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(dir.len());
+    let mut rest = dir.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok().filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_dirs_are_distinct_and_reversible() {
+        let agents = ["a.b", "a_b", "a%2Eb", "research-bot", "ops/…"];
+        let dirs: Vec<String> = agents.iter().map(|a| dir_name(a).unwrap()).collect();
+        assert_eq!(dirs[..4], ["a%2Eb", "a_b", "a%252Eb", "research-bot"]);
+        assert!(dirs.iter().all(|d| d.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_%".contains(&b))));
+        for (agent, dir) in agents.iter().zip(&dirs) {
+            assert_eq!(agent_name(dir).as_deref(), Some(*agent));
+        }
+        assert!(dir_name("").is_err());
+
+        let root = std::env::temp_dir().join(format!("clawforge-profiles-{}", std::process::id()));
+        let store = ProfileStore::new(&root);
+        let a = store.profile("a.b").unwrap();
+        let b = store.profile("a_b").unwrap();
+        assert_ne!(a.dir, b.dir);
+        assert_eq!(store.list().unwrap(), ["a.b", "a_b"]);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
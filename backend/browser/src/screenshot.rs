@@ -1,17 +1,150 @@
-//! Viewport Screenshot Generator
+//! Page Screenshots and PDFs
 //!
-//! Emits raw PNG/WebP bytes from the remote CDP layout compositor.
+//! Captures the viewport, the whole scrollable page (via CDP's
+//! `captureBeyondViewport`) or a print-to-PDF rendering, and hands each back
+//! as a [`MediaPayload`] ready for the `MediaPipeline`.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use bytes::Bytes;
+use media::MediaPayload;
+use serde_json::{json, Value};
 use tracing::info;
 
-pub struct ScreenshotCapturer;
+use crate::cdp_client::CdpClient;
 
-impl ScreenshotCapturer {
-    /// Halts painting and snags a viewport buffer encoded as PNG format.
-    pub async fn capture_viewport() -> Result<Vec<u8>> {
-        info!("Capturing Browser Viewport Screenshot to bytebuffer.");
-        // MOCK: Issue Page.captureScreenshot over CDP
-        Ok(vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]) // PNG magic bytes
+/// Tallest single capture; Chromium's compositor fails on bigger textures,
+/// so longer pages come back as several consecutive tiles.
+const MAX_CAPTURE_HEIGHT: f64 = 16_384.0;
+
+/// `Page.printToPDF` settings; sizes are in inches.
+#[derive(Debug, Clone)]
+pub struct PdfOptions {
+    pub landscape: bool,
+    pub print_background: bool,
+    pub paper_width: f64,
+    pub paper_height: f64,
+    pub scale: f64,
+    /// e.g. `1-3, 5`; empty prints every page.
+    pub page_ranges: String,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        // US Letter, as Chromium does.
+        Self { landscape: false, print_background: true, paper_width: 8.5, paper_height: 11.0, scale: 1.0, page_ranges: String::new() }
+    }
+}
+
+pub struct ScreenshotCapturer<'a> {
+    cdp: &'a CdpClient,
+    source: String,
+}
+
+impl<'a> ScreenshotCapturer<'a> {
+    pub fn new(cdp: &'a CdpClient) -> Self {
+        Self { cdp, source: "browser".to_string() }
+    }
+
+    /// Where the payloads say they came from, typically the page URL.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// The visible viewport as PNG.
+    pub async fn capture_viewport(&self) -> Result<MediaPayload> {
+        info!(source = %self.source, "Capturing viewport screenshot");
+        let result = self.cdp.send_command("Page.captureScreenshot", json!({ "format": "png" })).await?;
+        Ok(self.payload("image/png", decode_data(&result)?))
+    }
+
+    /// The entire scrollable page as PNG: one payload, or top-to-bottom tiles
+    /// when the page is taller than a single capture allows.
+    pub async fn capture_full_page(&self) -> Result<Vec<MediaPayload>> {
+        let metrics = self.cdp.send_command("Page.getLayoutMetrics", json!({})).await?;
+        let size = metrics.get("cssContentSize").or_else(|| metrics.get("contentSize"));
+        let dimension = |name: &str| size.and_then(|s| s[name].as_f64()).filter(|v| *v > 0.0);
+        let (Some(width), Some(height)) = (dimension("width"), dimension("height")) else {
+            // Nothing to measure (blank page or odd target): settle for the viewport.
+            return Ok(vec![self.capture_viewport().await?]);
+        };
+        info!(source = %self.source, width, height, "Capturing full-page screenshot");
+
+        let mut tiles = Vec::new();
+        let mut y = 0.0;
+        while y < height {
+            let tile_height = (height - y).min(MAX_CAPTURE_HEIGHT);
+            let result = self
+                .cdp
+                .send_command(
+                    "Page.captureScreenshot",
+                    json!({
+                        "format": "png",
+                        "captureBeyondViewport": true,
+                        "clip": { "x": 0, "y": y, "width": width, "height": tile_height, "scale": 1 }
+                    }),
+                )
+                .await?;
+            tiles.push(self.payload("image/png", decode_data(&result)?));
+            y += tile_height;
+        }
+        Ok(tiles)
+    }
+
+    /// The page rendered for print.
+    pub async fn print_pdf(&self, options: &PdfOptions) -> Result<MediaPayload> {
+        info!(source = %self.source, "Printing page to PDF");
+        let result = self
+            .cdp
+            .send_command(
+                "Page.printToPDF",
+                json!({
+                    "landscape": options.landscape,
+                    "printBackground": options.print_background,
+                    "paperWidth": options.paper_width,
+                    "paperHeight": options.paper_height,
+                    "scale": options.scale,
+                    "pageRanges": options.page_ranges,
+                }),
+            )
+            .await?;
+        Ok(self.payload("application/pdf", decode_data(&result)?))
+    }
+
+    fn payload(&self, mime_type: &str, data: Vec<u8>) -> MediaPayload {
+        MediaPayload { source: self.source.clone(), mime_type: mime_type.to_string(), data: Bytes::from(data) }
+    }
+}
+
+/// Decode the base64 `data` field CDP returns for captures and PDFs.
+fn decode_data(result: &Value) -> Result<Vec<u8>> {
+    let data = result["data"].as_str().ok_or_else(|| anyhow!("CDP returned no image data"))?;
+    Ok(base64::engine::general_purpose::STANDARD.decode(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdp_client::tests::fake_devtools;
+
+    fn params(payload: &MediaPayload) -> Value {
+        serde_json::from_slice(&payload.data).unwrap()
+    }
+
+    #[tokio::test]
+    async fn tiles_tall_pages_and_prints_pdfs() {
+        let cdp = CdpClient::new(&fake_devtools().await);
+        cdp.connect().await.unwrap();
+        let capturer = ScreenshotCapturer::new(&cdp).with_source("https://example.com/");
+
+        let tiles = capturer.capture_full_page().await.unwrap();
+        let clips: Vec<_> = tiles.iter().map(|t| (params(t)["clip"]["y"].as_f64().unwrap(), params(t)["clip"]["height"].as_f64().unwrap())).collect();
+        assert_eq!(clips, [(0.0, MAX_CAPTURE_HEIGHT), (MAX_CAPTURE_HEIGHT, 20_000.0 - MAX_CAPTURE_HEIGHT)]);
+        assert!(tiles.iter().all(|t| t.mime_type == "image/png" && t.source == "https://example.com/" && params(t)["captureBeyondViewport"] == true));
+
+        let pdf = capturer.print_pdf(&PdfOptions { landscape: true, page_ranges: "1-2".into(), ..Default::default() }).await.unwrap();
+        assert_eq!(pdf.mime_type, "application/pdf");
+        assert_eq!((params(&pdf)["landscape"].clone(), params(&pdf)["pageRanges"].clone()), (json!(true), json!("1-2")));
     }
 }
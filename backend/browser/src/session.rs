@@ -0,0 +1,437 @@
+//! Browser Sessions
+//!
+//! Launches Chromium against an agent's persistent profile, optionally headful
+//! and with automation fingerprints masked, and keeps it running between tool
+//! calls. [`SessionPool`] hands the same session back to the same agent until
+//! it sits idle too long or is closed, saving storage state on the way out.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::actions::ElementRefs;
+use crate::cdp_client::CdpClient;
+use crate::profile::{BrowserProfile, ProfileStore};
+
+/// How long to wait for Chromium to print its DevTools endpoint.
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Masks the usual headless/automation tells before any page script runs.
+const STEALTH_SCRIPT: &str = "Object.defineProperty(navigator, 'webdriver', { get: () => undefined }); \
+     window.chrome = window.chrome || { runtime: {} }; \
+     Object.defineProperty(navigator, 'languages', { get: () => ['en-US', 'en'] }); \
+     Object.defineProperty(navigator, 'plugins', { get: () => [1, 2, 3, 4, 5] });";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOptions {
+    /// Show a window instead of running headless.
+    #[serde(default)]
+    pub headful: bool,
+    /// Hide automation markers (`navigator.webdriver`, the headless UA, …).
+    #[serde(default)]
+    pub stealth: bool,
+    /// Chromium binary; defaults to `$CLAWFORGE_CHROME` or the first one on PATH.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executable: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Viewport as (width, height).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_size: Option<(u32, u32)>,
+    /// Save downloads here instead of the profile's default folder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_dir: Option<PathBuf>,
+}
+
+impl LaunchOptions {
+    /// Chromium command-line flags for `profile`.
+    pub fn args(&self, profile: &BrowserProfile) -> Vec<String> {
+        let mut args = vec![
+            format!("--user-data-dir={}", profile.user_data_dir().display()),
+            "--remote-debugging-port=0".to_string(),
+            "--no-first-run".to_string(),
+            "--no-default-browser-check".to_string(),
+            "--disable-background-networking".to_string(),
+        ];
+        if !self.headful {
+            args.push("--headless=new".to_string());
+        }
+        if self.stealth {
+            args.push("--disable-blink-features=AutomationControlled".to_string());
+        }
+        if let Some(ua) = &self.user_agent {
+            args.push(format!("--user-agent={}", ua));
+        }
+        let (width, height) = self.window_size.unwrap_or((1280, 800));
+        args.push(format!("--window-size={},{}", width, height));
+        args.push("about:blank".to_string());
+        args
+    }
+
+    fn executable(&self) -> Result<String> {
+        if let Some(exe) = self.executable.clone().or_else(|| std::env::var("CLAWFORGE_CHROME").ok()) {
+            return Ok(exe);
+        }
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        ["google-chrome", "google-chrome-stable", "chromium", "chromium-browser", "chrome"]
+            .iter()
+            .find(|name| std::env::split_paths(&path).any(|dir| dir.join(name).is_file()))
+            .map(|name| name.to_string())
+            .ok_or_else(|| anyhow!("No Chromium found; install one or set CLAWFORGE_CHROME"))
+    }
+}
+
+/// A running browser bound to one agent's profile.
+pub struct BrowserSession {
+    pub profile: BrowserProfile,
+    pub options: LaunchOptions,
+    pub cdp: CdpClient,
+    /// Element ids handed out by snapshots of this session's pages.
+    pub elements: ElementRefs,
+    child: Child,
+    last_used: Instant,
+}
+
+impl BrowserSession {
+    /// Start Chromium on `profile` and replay its saved storage state.
+    pub async fn launch(profile: BrowserProfile, options: LaunchOptions) -> Result<Self> {
+        let exe = options.executable()?;
+        let mut child = Command::new(&exe)
+            .args(options.args(&profile))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", exe))?;
+
+        let stderr = child.stderr.take().ok_or_else(|| anyhow!("Chromium stderr not captured"))?;
+        let endpoint = tokio::time::timeout(LAUNCH_TIMEOUT, async {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Some(line) = lines.next_line().await? {
+                if let Some(ws) = line.trim().strip_prefix("DevTools listening on ") {
+                    return Ok(ws.to_string());
+                }
+            }
+            bail!("Chromium exited before exposing DevTools")
+        })
+        .await
+        .map_err(|_| anyhow!("Chromium did not expose DevTools within {:?}", LAUNCH_TIMEOUT))??;
+
+        let cdp = CdpClient::new(&endpoint);
+        cdp.connect().await?;
+        let session = Self { profile, options, cdp, elements: ElementRefs::default(), child, last_used: Instant::now() };
+        session.prepare().await?;
+        info!(agent = %session.profile.agent, headful = session.options.headful, "Browser session started");
+        Ok(session)
+    }
+
+    async fn prepare(&self) -> Result<()> {
+        if self.options.stealth {
+            self.add_init_script(STEALTH_SCRIPT).await?;
+        }
+        if let Some(dir) = &self.options.download_dir {
+            self.cdp
+                .send_command(
+                    "Browser.setDownloadBehavior",
+                    serde_json::json!({ "behavior": "allow", "downloadPath": dir }),
+                )
+                .await?;
+        }
+        let state = self.profile.load_state()?;
+        if !state.cookies.is_empty() {
+            self.cdp.send_command("Storage.setCookies", serde_json::json!({ "cookies": state.cookies })).await?;
+        }
+        if let Some(script) = state.restore_script() {
+            self.add_init_script(&script).await?;
+        }
+        Ok(())
+    }
+
+    async fn add_init_script(&self, source: &str) -> Result<()> {
+        self.cdp
+            .send_command("Page.addScriptToEvaluateOnNewDocument", serde_json::json!({ "source": source }))
+            .await?;
+        Ok(())
+    }
+
+    /// Capture cookies and the current page's localStorage into the profile,
+    /// keeping other origins' saved entries.
+    pub async fn save_state(&self) -> Result<()> {
+        let mut state = self.profile.load_state()?;
+        let cookies = self.cdp.send_command("Storage.getCookies", serde_json::json!({})).await?;
+        if let Some(cookies) = cookies.get("cookies").and_then(|c| c.as_array()) {
+            state.cookies = cookies.clone();
+        }
+        let page = self
+            .cdp
+            .send_command(
+                "Runtime.evaluate",
+                serde_json::json!({
+                    "expression": "JSON.stringify({ origin: location.origin, items: Object.assign({}, localStorage) })",
+                    "returnByValue": true
+                }),
+            )
+            .await?;
+        if let Some(text) = page.pointer("/result/value").and_then(|v| v.as_str()) {
+            if let Ok(snapshot) = serde_json::from_str::<serde_json::Value>(text) {
+                let origin = snapshot["origin"].as_str().unwrap_or_default();
+                if origin.starts_with("http") {
+                    if let Ok(items) = serde_json::from_value(snapshot["items"].clone()) {
+                        state.local_storage.insert(origin.to_string(), items);
+                    }
+                }
+            }
+        }
+        self.profile.save_state(&state)
+    }
+
+    pub fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    pub fn idle_for(&self) -> Duration {
+        self.last_used.elapsed()
+    }
+
+    /// Save state and stop the browser.
+    pub async fn close(mut self) -> Result<()> {
+        if let Err(e) = self.save_state().await {
+            warn!(agent = %self.profile.agent, error = %e, "Failed to save browser state");
+        }
+        self.child.kill().await.ok();
+        info!(agent = %self.profile.agent, "Browser session closed");
+        Ok(())
+    }
+}
+
+/// Reuses one browser session per agent across tool calls.
+///
+/// The pool's map is only locked to look sessions up; launches are
+/// serialised per agent, so one agent's slow start never blocks another's.
+pub struct SessionPool {
+    profiles: ProfileStore,
+    sessions: Mutex<HashMap<String, Arc<Mutex<BrowserSession>>>>,
+    /// Held while an agent's session is checked or launched.
+    launching: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+    max_idle: Duration,
+}
+
+impl SessionPool {
+    pub fn new(profiles: ProfileStore, max_idle: Duration) -> Self {
+        Self { profiles, sessions: Mutex::new(HashMap::new()), launching: Mutex::new(HashMap::new()), max_idle }
+    }
+
+    /// The agent's live session, launching one when there is none or the old
+    /// browser died. `options` only apply to a newly launched session.
+    pub async fn acquire(&self, agent: &str, options: LaunchOptions) -> Result<Arc<Mutex<BrowserSession>>> {
+        let gate = Arc::clone(self.launching.lock().await.entry(agent.to_string()).or_default());
+        let _launching = gate.lock().await;
+        let existing = self.sessions.lock().await.get(agent).cloned();
+        if let Some(session) = existing {
+            let alive = match session.try_lock() {
+                // In use by another call, so alive.
+                Err(_) => true,
+                Ok(mut guard) => {
+                    let alive = guard.is_alive();
+                    if alive {
+                        guard.last_used = Instant::now();
+                    }
+                    alive
+                }
+            };
+            if alive {
+                return Ok(session);
+            }
+            warn!(agent = %agent, "Browser session died; relaunching");
+        }
+        let session = BrowserSession::launch(self.profiles.profile(agent)?, options).await?;
+        let session = Arc::new(Mutex::new(session));
+        self.sessions.lock().await.insert(agent.to_string(), session.clone());
+        Ok(session)
+    }
+
+    /// Close the agent's session, saving its state. Returns false when none was open.
+    pub async fn close(&self, agent: &str) -> Result<bool> {
+        let Some(session) = self.sessions.lock().await.remove(agent) else {
+            return Ok(false);
+        };
+        Self::shutdown(session).await?;
+        Ok(true)
+    }
+
+    /// Close sessions idle longer than the pool's limit. Returns their agents.
+    pub async fn close_idle(&self) -> Result<Vec<String>> {
+        let mut idle = Vec::new();
+        {
+            let mut sessions = self.sessions.lock().await;
+            // A session busy with a call is not idle.
+            let expired: Vec<String> = sessions
+                .iter()
+                .filter(|(_, session)| session.try_lock().is_ok_and(|s| s.idle_for() >= self.max_idle))
+                .map(|(agent, _)| agent.clone())
+                .collect();
+            for agent in expired {
+                if let Some(session) = sessions.remove(&agent) {
+                    idle.push((agent, session));
+                }
+            }
+        }
+        let mut closed = Vec::new();
+        for (agent, session) in idle {
+            Self::shutdown(session).await?;
+            closed.push(agent);
+        }
+        Ok(closed)
+    }
+
+    pub async fn close_all(&self) -> Result<()> {
+        let sessions: Vec<_> = self.sessions.lock().await.drain().map(|(_, s)| s).collect();
+        for session in sessions {
+            Self::shutdown(session).await?;
+        }
+        Ok(())
+    }
+
+    async fn shutdown(session: Arc<Mutex<BrowserSession>>) -> Result<()> {
+        match Arc::try_unwrap(session) {
+            Ok(session) => session.into_inner().close().await,
+            // Still borrowed by an in-flight call; save what we can, the
+            // process goes when the last handle drops.
+            Err(shared) => shared.lock().await.save_state().await,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// A "Chromium" that announces `endpoint` and idles.
+    fn fake_chrome(dir: &std::path::Path, endpoint: &str) -> String {
+        let path = dir.join("chrome");
+        std::fs::write(&path, format!("#!/bin/sh\necho 'DevTools listening on {}' >&2\nexec sleep 30\n", endpoint)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.display().to_string()
+    }
+
+    /// A scratch directory and launch options for a fake Chromium in it.
+    async fn fake_setup(name: &str) -> (PathBuf, LaunchOptions) {
+        let root = std::env::temp_dir().join(format!("clawforge-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let endpoint = crate::cdp_client::tests::fake_devtools().await;
+        let options = LaunchOptions { executable: Some(fake_chrome(&root, &endpoint)), ..Default::default() };
+        (root, options)
+    }
+
+    fn saved_state(pool: &SessionPool, agent: &str) -> bool {
+        pool.profiles.profile(agent).unwrap().dir.join("clawforge-state.json").exists()
+    }
+
+    #[tokio::test]
+    async fn sessions_are_reused_until_their_browser_dies() {
+        let (root, options) = fake_setup("lifecycle").await;
+        let pool = SessionPool::new(ProfileStore::new(root.join("profiles")), Duration::from_secs(600));
+
+        let first = pool.acquire("researcher", options.clone()).await.unwrap();
+        let again = pool.acquire("researcher", options.clone()).await.unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        first.lock().await.child.start_kill().unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while first.lock().await.is_alive() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let relaunched = pool.acquire("researcher", options).await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &relaunched));
+        assert!(relaunched.lock().await.is_alive());
+
+        drop((first, again, relaunched));
+        assert!(pool.close("researcher").await.unwrap());
+        assert!(saved_state(&pool, "researcher"));
+        assert!(!pool.close("researcher").await.unwrap());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn idle_sessions_are_closed_and_their_state_saved() {
+        let (root, options) = fake_setup("idle").await;
+        let pool = SessionPool::new(ProfileStore::new(root.join("profiles")), Duration::ZERO);
+
+        let researcher = pool.acquire("researcher", options.clone()).await.unwrap();
+        let writer = pool.acquire("writer", options).await.unwrap();
+        let busy = writer.lock().await;
+        drop(researcher);
+
+        // The writer is mid-call, so only the researcher counts as idle.
+        assert_eq!(pool.close_idle().await.unwrap(), vec!["researcher".to_string()]);
+        assert!(saved_state(&pool, "researcher"));
+        assert!(!saved_state(&pool, "writer"));
+        drop(busy);
+        drop(writer);
+
+        pool.close_all().await.unwrap();
+        assert!(saved_state(&pool, "writer"));
+        assert!(pool.close_idle().await.unwrap().is_empty());
+        assert!(!pool.close("writer").await.unwrap());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn launch_fails_when_chromium_exits_without_devtools() {
+        let root = std::env::temp_dir().join(format!("clawforge-nochrome-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let exe = root.join("chrome");
+        std::fs::write(&exe, "#!/bin/sh
+echo 'crashed' >&2
+exit 1
+").unwrap();
+        std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let options = LaunchOptions { executable: Some(exe.display().to_string()), ..Default::default() };
+        let pool = SessionPool::new(ProfileStore::new(root.join("profiles")), Duration::from_secs(600));
+
+        let err = pool.acquire("researcher", options).await.err().unwrap();
+        assert!(err.to_string().contains("exited before exposing DevTools"), "{:#}", err);
+        assert!(!pool.close("researcher").await.unwrap());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn busy_sessions_do_not_block_other_agents() {
+        let root = std::env::temp_dir().join(format!("clawforge-pool-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let options = LaunchOptions {
+            executable: Some(fake_chrome(&root, &crate::cdp_client::tests::fake_devtools().await)),
+            ..Default::default()
+        };
+        let pool = SessionPool::new(ProfileStore::new(root.join("profiles")), Duration::from_secs(600));
+
+        let first = pool.acquire("researcher", options.clone()).await.unwrap();
+        let busy = first.lock().await;
+        let within = |f| tokio::time::timeout(Duration::from_secs(5), f);
+        let again = within(pool.acquire("researcher", options.clone())).await.unwrap().unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        let other = within(pool.acquire("writer", options.clone())).await.unwrap().unwrap();
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert!(pool.close_idle().await.unwrap().is_empty());
+        drop(busy);
+
+        drop((first, again, other));
+        pool.close_all().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+//! Adapter lifecycle supervision.
+//!
+//! `status_relay` sits between an adapter and the supervisor bus: it counts
+//! inbound messages and reports how many are waiting. `supervise` runs an
+//! adapter's `start`, reports it connected once it is up, and restarts it
+//! with exponential backoff when it fails. Both report into the shared
+//! `AdapterStatusRegistry` behind `/status` and `GET /api/health`.
+
+use std::time::Duration;
+
+use clawforge_core::Message;
+use infra::AdapterReporter;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+use crate::ChannelAdapter;
+
+/// Inbound messages buffered per adapter before it has to wait.
+const RELAY_CAPACITY: usize = 256;
+/// A `start` still running after this long counts as connected.
+const CONNECT_GRACE: Duration = Duration::from_secs(5);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Sender to hand an adapter instead of the supervisor bus; messages are
+/// forwarded to `supervisor_tx` and counted as inbound for `reporter`.
+pub fn status_relay(reporter: AdapterReporter, supervisor_tx: mpsc::Sender<Message>) -> mpsc::Sender<Message> {
+    let (tx, mut rx) = mpsc::channel::<Message>(RELAY_CAPACITY);
+    tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            reporter.inbound();
+            reporter.queue_depth(rx.len());
+            if supervisor_tx.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+    tx
+}
+
+/// Run `adapter` until it finishes cleanly, restarting it after failures.
+pub async fn supervise<A: ChannelAdapter>(adapter: A, inbound_tx: mpsc::Sender<Message>, reporter: AdapterReporter) {
+    let mut attempt = 0u32;
+    loop {
+        reporter.starting();
+        let start = adapter.start(inbound_tx.clone());
+        tokio::pin!(start);
+        let result = tokio::select! {
+            result = &mut start => result,
+            _ = tokio::time::sleep(CONNECT_GRACE) => {
+                reporter.connected();
+                attempt = 0;
+                start.await
+            }
+        };
+        match result {
+            // Webhook adapters return as soon as their routes are ready.
+            Ok(()) => {
+                reporter.connected();
+                info!(adapter = reporter.name(), "Channel adapter ready");
+                return;
+            }
+            Err(e) => {
+                attempt += 1;
+                let delay = INITIAL_BACKOFF.saturating_mul(1 << (attempt - 1).min(9)).min(MAX_BACKOFF);
+                error!(adapter = reporter.name(), attempt, error = %e, "Channel adapter failed; retrying in {:?}", delay);
+                reporter.error(&e);
+                reporter.backoff(attempt, delay);
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use infra::{AdapterState, AdapterStatusRegistry};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct Flaky {
+        failures: AtomicU32,
+    }
+
+    #[async_trait]
+    impl ChannelAdapter for Flaky {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn start(&self, _supervisor_tx: mpsc::Sender<Message>) -> anyhow::Result<()> {
+            if self.failures.fetch_add(1, Ordering::SeqCst) == 0 {
+                anyhow::bail!("connection refused");
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_adapters_back_off_then_connect() {
+        let registry = AdapterStatusRegistry::new();
+        let reporter = registry.reporter("flaky");
+        let (bus_tx, _bus_rx) = mpsc::channel(8);
+        let relay = status_relay(reporter.clone(), bus_tx);
+
+        let task = tokio::spawn(supervise(Flaky { failures: AtomicU32::new(0) }, relay, reporter));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let status = registry.get("flaky").unwrap();
+        assert!(matches!(status.state, AdapterState::Backoff { attempt: 1, .. }));
+        assert_eq!(status.last_error.as_deref(), Some("connection refused"));
+
+        task.await.unwrap();
+        assert_eq!(registry.get("flaky").unwrap().state, AdapterState::Connected);
+    }
+}
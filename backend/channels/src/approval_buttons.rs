@@ -0,0 +1,180 @@
+//! Native approval buttons
+//!
+//! Transport-neutral model for rendering pending approvals (exec, pairing,
+//! dangerous tools) as tappable buttons, and for decoding the callback payload
+//! a button press sends back into an `ApprovalResponse` for the approval socket.
+//!
+//! The text fallback `/approve yes <id>` keeps working; buttons are an extra path.
+
+use clawforge_sandbox::ApprovalResponse;
+use serde::{Deserialize, Serialize};
+
+/// Prefix identifying callback payloads produced by this module.
+const CALLBACK_PREFIX: &str = "cfap";
+
+/// Telegram rejects `callback_data` longer than 64 bytes.
+pub const TELEGRAM_CALLBACK_MAX: usize = 64;
+
+/// Discord rejects `custom_id` longer than 100 characters.
+pub const DISCORD_CUSTOM_ID_MAX: usize = 100;
+
+/// What kind of action is waiting on a human.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalKind {
+    Exec,
+    Pairing,
+    DangerousTool,
+}
+
+impl ApprovalKind {
+    fn title(self) -> &'static str {
+        match self {
+            ApprovalKind::Exec => "Command approval required",
+            ApprovalKind::Pairing => "Pairing request",
+            ApprovalKind::DangerousTool => "Dangerous tool call",
+        }
+    }
+}
+
+/// A button the user can press on an approval prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalChoice {
+    Allow,
+    AllowSession,
+    Deny,
+}
+
+impl ApprovalChoice {
+    /// Verdict string understood by `ApprovalSocketServer`.
+    pub fn verdict(self) -> &'static str {
+        match self {
+            ApprovalChoice::Allow => "allow",
+            ApprovalChoice::AllowSession => "allow-session",
+            ApprovalChoice::Deny => "deny",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ApprovalChoice::Allow => "Allow once",
+            ApprovalChoice::AllowSession => "Allow for session",
+            ApprovalChoice::Deny => "Deny",
+        }
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            ApprovalChoice::Allow => "a",
+            ApprovalChoice::AllowSession => "s",
+            ApprovalChoice::Deny => "d",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "a" => Some(ApprovalChoice::Allow),
+            "s" => Some(ApprovalChoice::AllowSession),
+            "d" => Some(ApprovalChoice::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// A pending approval to render as a message with buttons.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalPrompt {
+    /// Approval request ID (matches `ApprovalRequest::id`).
+    pub id: String,
+    pub kind: ApprovalKind,
+    /// One-line description: the command, device label or tool name.
+    pub summary: String,
+    /// Optional risk reasons shown under the summary.
+    #[serde(default)]
+    pub reasons: Vec<String>,
+}
+
+impl ApprovalPrompt {
+    pub fn new(id: impl Into<String>, kind: ApprovalKind, summary: impl Into<String>) -> Self {
+        Self { id: id.into(), kind, summary: summary.into(), reasons: vec![] }
+    }
+
+    /// Buttons offered for this prompt. Pairing has no session scope.
+    pub fn choices(&self) -> &'static [ApprovalChoice] {
+        match self.kind {
+            ApprovalKind::Pairing => &[ApprovalChoice::Allow, ApprovalChoice::Deny],
+            _ => &[ApprovalChoice::Allow, ApprovalChoice::AllowSession, ApprovalChoice::Deny],
+        }
+    }
+
+    /// Plain-text body of the prompt message, including the text fallback.
+    pub fn text(&self) -> String {
+        let mut out = format!("{}\n\n{}", self.kind.title(), self.summary);
+        for reason in &self.reasons {
+            out.push_str(&format!("\n• {}", reason));
+        }
+        out.push_str(&format!("\n\nOr reply: /approve yes {} | /approve no {}", self.id, self.id));
+        out
+    }
+
+    /// Callback payload for a button: `cfap:<choice>:<id>`.
+    pub fn callback_data(&self, choice: ApprovalChoice) -> String {
+        encode_callback(choice, &self.id)
+    }
+}
+
+pub fn encode_callback(choice: ApprovalChoice, id: &str) -> String {
+    format!("{}:{}:{}", CALLBACK_PREFIX, choice.code(), id)
+}
+
+/// Decode a button payload. Returns `None` for payloads not produced here.
+pub fn decode_callback(data: &str) -> Option<(ApprovalChoice, String)> {
+    let mut parts = data.splitn(3, ':');
+    if parts.next()? != CALLBACK_PREFIX {
+        return None;
+    }
+    let choice = ApprovalChoice::from_code(parts.next()?)?;
+    let id = parts.next().filter(|id| !id.is_empty())?;
+    Some((choice, id.to_string()))
+}
+
+/// Decode a button payload straight into a response for the approval socket.
+pub fn callback_to_response(data: &str) -> Option<ApprovalResponse> {
+    decode_callback(data).map(|(choice, id)| ApprovalResponse {
+        id,
+        verdict: choice.verdict().to_string(),
+    })
+}
+
+/// Text shown in place of the buttons once a decision has been recorded.
+pub fn resolved_text(choice: ApprovalChoice, id: &str, by: &str) -> String {
+    format!("{} — {} (by {})", id, choice.label(), by)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callback_roundtrip() {
+        let prompt = ApprovalPrompt::new("req-42", ApprovalKind::Exec, "rm -rf build/");
+        let data = prompt.callback_data(ApprovalChoice::AllowSession);
+        assert_eq!(data, "cfap:s:req-42");
+        let resp = callback_to_response(&data).unwrap();
+        assert_eq!(resp.id, "req-42");
+        assert_eq!(resp.verdict, "allow-session");
+    }
+
+    #[test]
+    fn foreign_payloads_are_ignored() {
+        assert!(decode_callback("confirm_yes").is_none());
+        assert!(decode_callback("cfap:x:req").is_none());
+        assert!(decode_callback("cfap:a:").is_none());
+    }
+
+    #[test]
+    fn pairing_has_no_session_scope() {
+        let prompt = ApprovalPrompt::new("p1", ApprovalKind::Pairing, "iPhone");
+        assert!(!prompt.choices().contains(&ApprovalChoice::AllowSession));
+    }
+}
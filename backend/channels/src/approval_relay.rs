@@ -0,0 +1,228 @@
+//! Approval prompts in chat
+//!
+//! Posts pending approvals to the chat the run was started from, and turns
+//! `/approve yes <id>` replies in that chat back into verdicts on the broker.
+//! Replies from any other chat are refused.
+//! Channels with native buttons get the prompt with Allow/Deny buttons; their
+//! presses come back through `approval_sink`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use clawforge_sandbox::ApprovalResponse;
+use clawforge_security::{ApprovalBroker, ApprovalNotifier, ApprovalVerdict, PendingApproval};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::approval_buttons::{ApprovalKind, ApprovalPrompt};
+use crate::stream_edit::EditableChannel;
+
+/// A channel that can show an approval prompt with native buttons.
+#[async_trait]
+pub trait ApprovalButtons: Send + Sync {
+    async fn send_approval_prompt(&self, chat_id: &str, prompt: &ApprovalPrompt) -> Result<()>;
+}
+
+/// Delivers approval prompts through the adapters registered per channel.
+#[derive(Default)]
+pub struct ChatApprovalNotifier {
+    channels: HashMap<String, Arc<dyn EditableChannel>>,
+    buttons: HashMap<String, Arc<dyn ApprovalButtons>>,
+}
+
+impl ChatApprovalNotifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Post prompts for runs started from `channel` through `adapter`.
+    pub fn with_channel(mut self, channel: impl Into<String>, adapter: Arc<dyn EditableChannel>) -> Self {
+        self.channels.insert(channel.into(), adapter);
+        self
+    }
+
+    /// Post prompts for runs started from `channel` with buttons, through
+    /// `adapter`.
+    pub fn with_buttons(mut self, channel: impl Into<String>, adapter: Arc<dyn ApprovalButtons>) -> Self {
+        self.buttons.insert(channel.into(), adapter);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty() && self.buttons.is_empty()
+    }
+}
+
+#[async_trait]
+impl ApprovalNotifier for ChatApprovalNotifier {
+    async fn deliver(&self, request: &PendingApproval) -> Result<()> {
+        let (Some(channel), Some(chat_id)) = (&request.channel, &request.chat_id) else {
+            // Not started from a chat; the Control UI and other notifiers cover it.
+            return Ok(());
+        };
+        if let Some(adapter) = self.buttons.get(channel) {
+            let kind = if request.tool == "exec" { ApprovalKind::Exec } else { ApprovalKind::DangerousTool };
+            let mut prompt = ApprovalPrompt::new(&request.id, kind, format!("{}: {}", request.tool, request.summary));
+            prompt.reasons = request.reasons.clone();
+            return adapter.send_approval_prompt(chat_id, &prompt).await;
+        }
+        let Some(adapter) = self.channels.get(channel) else {
+            bail!("no adapter for channel '{}'", channel);
+        };
+        adapter.post(chat_id, &request.prompt_text()).await?;
+        Ok(())
+    }
+}
+
+/// Answer to a message from `sender` in `chat_id` on `channel` that is an
+/// `/approve` reply, or `None` when the message is ordinary text for the
+/// agent. Only requests started from that chat can be answered there.
+pub async fn approval_reply(
+    broker: &ApprovalBroker,
+    channel: &str,
+    chat_id: &str,
+    sender: &str,
+    text: &str,
+) -> Option<&'static str> {
+    match broker.handle_reply(text.trim(), channel, chat_id).await? {
+        true => {
+            info!("[Approval] {} answered an approval in {}:{}", sender, channel, chat_id);
+            Some("Approval recorded.")
+        }
+        false => {
+            warn!("[Approval] {} sent an approval reply in {}:{} that matched no request from that chat", sender, channel, chat_id);
+            Some("No pending approval with that id in this chat.")
+        }
+    }
+}
+
+/// Sender for adapters' `with_approval_sink`: button presses sent to it
+/// resolve requests on `broker`.
+pub fn approval_sink(broker: Arc<ApprovalBroker>) -> mpsc::Sender<ApprovalResponse> {
+    let (tx, mut rx) = mpsc::channel::<ApprovalResponse>(32);
+    tokio::spawn(async move {
+        while let Some(response) = rx.recv().await {
+            let Some(verdict) = ApprovalVerdict::parse(&response.verdict) else {
+                warn!("[Approval] Unknown verdict '{}' for {}", response.verdict, response.id);
+                continue;
+            };
+            if !broker.resolve(&response.id, verdict).await {
+                warn!("[Approval] Button press for {} matched no pending approval", response.id);
+            }
+        }
+    });
+    tx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Outbox(Mutex<Vec<(String, String)>>);
+
+    #[async_trait]
+    impl EditableChannel for Outbox {
+        async fn post(&self, chat_id: &str, text: &str) -> Result<String> {
+            self.0.lock().unwrap().push((chat_id.to_string(), text.to_string()));
+            Ok("1".to_string())
+        }
+
+        async fn edit(&self, _chat_id: &str, _message_id: &str, _text: &str) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn prompt_goes_to_the_originating_chat_and_reply_resolves_it() {
+        let outbox = Arc::new(Outbox::default());
+        let notifier = ChatApprovalNotifier::new().with_channel("slack", outbox.clone());
+        let broker = Arc::new(ApprovalBroker::default().with_notifier(Arc::new(notifier)));
+
+        let waiting = tokio::spawn({
+            let broker = broker.clone();
+            async move { broker.request_in_chat("slack:U1", Some("slack"), Some("C42"), "shell", "rm -rf build", vec![]).await }
+        });
+        let (chat, prompt) = loop {
+            if let Some(sent) = outbox.0.lock().unwrap().first().cloned() {
+                break sent;
+            }
+            tokio::task::yield_now().await;
+        };
+        assert_eq!(chat, "C42");
+        let id = broker.pending().await[0].id.clone();
+        assert!(prompt.contains(&id));
+        assert_eq!(id.len(), 32);
+
+        assert_eq!(approval_reply(&broker, "slack", "C42", "U1", "hello").await, None);
+        assert_eq!(
+            approval_reply(&broker, "slack", "C42", "U1", "/approve yes nope").await,
+            Some("No pending approval with that id in this chat.")
+        );
+        assert_eq!(approval_reply(&broker, "slack", "C42", "U1", &format!("/approve yes {}", id)).await, Some("Approval recorded."));
+        assert!(waiting.await.unwrap().is_approved());
+    }
+
+    #[tokio::test]
+    async fn replies_from_another_chat_are_refused() {
+        let outbox = Arc::new(Outbox::default());
+        let notifier = ChatApprovalNotifier::new().with_channel("slack", outbox.clone());
+        let broker = Arc::new(ApprovalBroker::default().with_notifier(Arc::new(notifier)));
+
+        let waiting = tokio::spawn({
+            let broker = broker.clone();
+            async move { broker.request_in_chat("slack:U1", Some("slack"), Some("C42"), "shell", "rm -rf build", vec![]).await }
+        });
+        while outbox.0.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        let id = broker.pending().await[0].id.clone();
+        let approve = format!("/approve yes {}", id);
+
+        let refused = Some("No pending approval with that id in this chat.");
+        assert_eq!(approval_reply(&broker, "slack", "C99", "U2", &approve).await, refused);
+        assert_eq!(approval_reply(&broker, "matrix", "C42", "U2", &approve).await, refused);
+        assert_eq!(broker.pending().await.len(), 1);
+
+        assert_eq!(approval_reply(&broker, "slack", "C42", "U1", &format!("/approve no {}", id)).await, Some("Approval recorded."));
+        assert!(!waiting.await.unwrap().is_approved());
+    }
+
+    #[derive(Default)]
+    struct ButtonOutbox(Mutex<Vec<(String, ApprovalPrompt)>>);
+
+    #[async_trait]
+    impl ApprovalButtons for ButtonOutbox {
+        async fn send_approval_prompt(&self, chat_id: &str, prompt: &ApprovalPrompt) -> Result<()> {
+            self.0.lock().unwrap().push((chat_id.to_string(), prompt.clone()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn button_prompts_resolve_through_the_sink() {
+        let outbox = Arc::new(ButtonOutbox::default());
+        let notifier = ChatApprovalNotifier::new().with_buttons("telegram", outbox.clone());
+        let broker = Arc::new(ApprovalBroker::default().with_notifier(Arc::new(notifier)));
+        let sink = approval_sink(broker.clone());
+
+        let waiting = tokio::spawn({
+            let broker = broker.clone();
+            async move { broker.request_in_chat("telegram:42", Some("telegram"), Some("42"), "shell", "rm -rf build", vec![]).await }
+        });
+        let (chat, prompt) = loop {
+            if let Some(sent) = outbox.0.lock().unwrap().first().cloned() {
+                break sent;
+            }
+            tokio::task::yield_now().await;
+        };
+        assert_eq!(chat, "42");
+        assert_eq!(prompt.kind, ApprovalKind::DangerousTool);
+
+        sink.send(ApprovalResponse { id: prompt.id, verdict: "deny".into() }).await.unwrap();
+        assert!(!waiting.await.unwrap().is_approved());
+    }
+}
@@ -0,0 +1,72 @@
+//! Artifact links
+//!
+//! Transport-neutral model for announcing an artifact published by the
+//! `artifact` tool. Adapters with image support post the preview (when the
+//! tool produced one) with the link as caption; every other channel gets the
+//! plain-text link from `text()`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactLink {
+    pub name: String,
+    /// `html`, `svg`, `csv` or `mermaid`.
+    pub kind: String,
+    pub url: String,
+    /// PNG rendering of the artifact, if one is available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_url: Option<String>,
+}
+
+impl ArtifactLink {
+    /// Read the `artifact` object out of an `artifact` tool result.
+    pub fn from_tool_output(output: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(output).ok()?;
+        serde_json::from_value(value.get("artifact")?.clone()).ok()
+    }
+
+    fn label(&self) -> &str {
+        match self.kind.as_str() {
+            "html" => "page",
+            "svg" => "chart",
+            "csv" => "table",
+            "mermaid" => "diagram",
+            _ => "artifact",
+        }
+    }
+
+    /// Plain-text announcement with the link.
+    pub fn text(&self) -> String {
+        format!("📎 {} ({}): {}", self.name, self.label(), self.url)
+    }
+
+    /// Slack Block Kit blocks: a linked title, plus the preview image if any.
+    pub fn slack_blocks(&self) -> Value {
+        let mut blocks = vec![json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("📎 <{}|{}> ({})", self.url, self.name, self.label()) }
+        })];
+        if let Some(preview) = &self.preview_url {
+            blocks.push(json!({ "type": "image", "image_url": preview, "alt_text": self.name }));
+        }
+        Value::Array(blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_output_becomes_link_and_blocks() {
+        let output = r#"{"artifact":{"id":"ab12","name":"Flow","kind":"mermaid","url":"https://claw.example.com/artifacts/ab12","preview_url":"https://mermaid.ink/img/Z3JhcGg"}}"#;
+        let link = ArtifactLink::from_tool_output(output).unwrap();
+        assert_eq!(link.text(), "📎 Flow (diagram): https://claw.example.com/artifacts/ab12");
+        let blocks = link.slack_blocks();
+        assert_eq!(blocks.as_array().unwrap().len(), 2);
+        assert_eq!(blocks[1]["image_url"], "https://mermaid.ink/img/Z3JhcGg");
+
+        assert!(ArtifactLink::from_tool_output(r#"{"ok":true}"#).is_none());
+    }
+}
@@ -10,6 +10,7 @@ use crate::ChannelAdapter;
 use clawforge_core::{
     Message, EventKind, Event, AuditEventPayload
 };
+use infra::AdapterReporter;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -65,6 +66,7 @@ pub struct BlueBubblesAdapter {
     config: BlueBubblesConfig,
     supervisor_tx: mpsc::Sender<Message>,
     http_client: Client,
+    status: Option<AdapterReporter>,
 }
 
 impl BlueBubblesAdapter {
@@ -73,9 +75,16 @@ impl BlueBubblesAdapter {
             config,
             supervisor_tx,
             http_client: Client::new(),
+            status: None,
         }
     }
 
+    /// Report outbound sends to the adapter status registry.
+    pub fn with_status(mut self, status: AdapterReporter) -> Self {
+        self.status = Some(status);
+        self
+    }
+
     pub fn build_router(&self) -> Router {
         let state = AppState {
             config: self.config.clone(),
@@ -191,6 +200,9 @@ impl BlueBubblesAdapter {
 
         match self.http_client.post(&url).json(&payload).send().await {
             Ok(res) if res.status().is_success() => {
+                if let Some(status) = &self.status {
+                    status.outbound();
+                }
                 info!("[BlueBubbles] Sent message to {}", chat_guid);
             }
             Ok(res) => {
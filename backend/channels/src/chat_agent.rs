@@ -0,0 +1,19 @@
+//! Agent replies to inbound chat messages
+//!
+//! Adapters that answer messages themselves hand each delivered message to
+//! a `ChatAgent` and post what it returns back into the same chat. Every
+//! chat runs in its own session, `<channel>:<chat_id>`.
+
+use async_trait::async_trait;
+
+/// Answers inbound chat messages, usually by running an agent.
+#[async_trait]
+pub trait ChatAgent: Send + Sync {
+    /// The reply to `text` from `sender` in `chat_id` on `channel`.
+    async fn reply(&self, channel: &str, chat_id: &str, sender: &str, text: &str) -> anyhow::Result<String>;
+}
+
+/// Session a chat's messages run in.
+pub fn chat_session(channel: &str, chat_id: &str) -> String {
+    format!("{}:{}", channel, chat_id)
+}
@@ -0,0 +1,300 @@
+//! "Run this" affordances for code blocks
+//!
+//! When a reply contains a block in a language the sandbox can execute,
+//! channels offer a Run button (or the `/run <id>` text fallback). Pressing
+//! it never executes anything directly: the block goes through the approval
+//! broker as an `exec` request first, prompted in the chat the block was
+//! offered in, and only an approved block is piped into the session's
+//! sandbox. A block can only be run from the chat it was offered in, and
+//! offers nobody presses expire after `OFFER_TTL`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use clawforge_sandbox::{analyze_argv, ContainerExecResult, SandboxRegistry};
+use clawforge_security::ApprovalBroker;
+use markdown::{CodeBlock, CodeBlockAnalyzer, IrParser};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::info;
+use uuid::Uuid;
+
+/// Prefix identifying run-button callback payloads.
+const RUN_CALLBACK_PREFIX: &str = "cfrun";
+
+/// Output longer than this is cut from the front when reported back.
+const RESULT_OUTPUT_MAX: usize = 3000;
+
+/// Offers nobody pressed within this long are dropped.
+const OFFER_TTL: Duration = Duration::from_secs(3600);
+
+/// A runnable block offered to the user, waiting for a press.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunAffordance {
+    pub id: String,
+    pub session_id: String,
+    pub channel: String,
+    /// Chat within `channel` the block was offered in.
+    pub chat_id: String,
+    pub block: CodeBlock,
+}
+
+impl RunAffordance {
+    pub fn label(&self) -> String {
+        format!("▶ Run {}", self.block.language.as_deref().unwrap_or("code"))
+    }
+
+    /// Callback payload for the button: `cfrun:<id>`.
+    pub fn callback_data(&self) -> String {
+        format!("{}:{}", RUN_CALLBACK_PREFIX, self.id)
+    }
+
+    /// Text fallback for channels without buttons.
+    pub fn command(&self) -> String {
+        format!("/run {}", self.id)
+    }
+}
+
+/// Decode a run-button payload into the affordance ID.
+pub fn decode_run_callback(data: &str) -> Option<String> {
+    data.strip_prefix(RUN_CALLBACK_PREFIX)?
+        .strip_prefix(':')
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+}
+
+pub struct CodeRunner {
+    sandboxes: Arc<SandboxRegistry>,
+    approvals: Arc<ApprovalBroker>,
+    /// Offers by id, with when they expire.
+    offered: Mutex<HashMap<String, (RunAffordance, Instant)>>,
+    timeout_secs: u64,
+}
+
+impl CodeRunner {
+    pub fn new(sandboxes: Arc<SandboxRegistry>, approvals: Arc<ApprovalBroker>) -> Self {
+        Self { sandboxes, approvals, offered: Mutex::new(HashMap::new()), timeout_secs: 60 }
+    }
+
+    pub fn with_timeout(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    /// Register every runnable block in `markdown`, a reply to `chat_id`,
+    /// and return the affordances to attach to it.
+    pub async fn offer(&self, session_id: &str, channel: &str, chat_id: &str, markdown: &str) -> Vec<RunAffordance> {
+        let affordances: Vec<RunAffordance> = CodeBlockAnalyzer::analyze(&IrParser::parse(markdown))
+            .into_iter()
+            .filter(CodeBlock::is_runnable)
+            .map(|block| RunAffordance {
+                // The id alone names the block in a press, so it must not be guessable.
+                id: Uuid::new_v4().simple().to_string(),
+                session_id: session_id.to_string(),
+                channel: channel.to_string(),
+                chat_id: chat_id.to_string(),
+                block,
+            })
+            .collect();
+        let now = Instant::now();
+        let mut offered = self.offered.lock().await;
+        offered.retain(|_, (_, expires)| *expires > now);
+        for affordance in &affordances {
+            offered.insert(affordance.id.clone(), (affordance.clone(), now + OFFER_TTL));
+        }
+        affordances
+    }
+
+    /// Ask for approval in `chat_id`, then run the block in the session's
+    /// sandbox. Only the chat the block was offered in may run it, and only
+    /// once; a denied one is discarded.
+    pub async fn run(&self, id: &str, chat_id: &str) -> Result<ContainerExecResult> {
+        let affordance = {
+            let mut offered = self.offered.lock().await;
+            // A press from another chat leaves the offer in place for its own chat.
+            if offered.get(id).is_some_and(|(affordance, _)| affordance.chat_id != chat_id) {
+                bail!("Block {} was not offered in this chat", id);
+            }
+            match offered.remove(id) {
+                Some((affordance, expires)) if expires > Instant::now() => affordance,
+                _ => bail!("No runnable block {}", id),
+            }
+        };
+        let argv = affordance.block.run_command().context("Block is not runnable")?;
+        let argv: Vec<&str> = argv.iter().map(String::as_str).collect();
+
+        let summary = format!(
+            "Run {} block from chat:\n{}",
+            affordance.block.language.as_deref().unwrap_or("code"),
+            affordance.block.content
+        );
+        let reasons = analyze_argv(&argv).reasons;
+        let outcome = self
+            .approvals
+            .request_in_chat(
+                &affordance.session_id,
+                Some(&affordance.channel),
+                Some(&affordance.chat_id),
+                "exec",
+                &summary,
+                reasons,
+            )
+            .await;
+        if !outcome.is_approved() {
+            bail!("Run of block {} was not approved", id);
+        }
+
+        info!("[CodeRun] Running block {} in session {}", id, affordance.session_id);
+        self.sandboxes.exec(&affordance.session_id, &argv, Some(self.timeout_secs)).await
+    }
+
+    /// Handle a `/run <id>` reply in `chat_id`. Returns `None` when the text
+    /// is not a run command.
+    pub async fn handle_reply(&self, text: &str, chat_id: &str) -> Option<Result<ContainerExecResult>> {
+        let mut parts = text.split_whitespace();
+        if parts.next()? != "/run" {
+            return None;
+        }
+        let id = parts.next()?;
+        Some(self.run(id, chat_id).await)
+    }
+
+    /// Markdown summary of a run, for posting back into the chat.
+    pub fn format_result(result: &ContainerExecResult) -> String {
+        let status = if result.timed_out {
+            "Timed out".to_string()
+        } else {
+            format!("Exited with {}", result.exit_code)
+        };
+        let mut out = format!("**{}** in {} ms", status, result.wall_time_ms);
+        for (name, stream) in [("stdout", &result.stdout), ("stderr", &result.stderr)] {
+            if stream.trim().is_empty() {
+                continue;
+            }
+            let start = stream.len().saturating_sub(RESULT_OUTPUT_MAX);
+            let start = (start..stream.len()).find(|&i| stream.is_char_boundary(i)).unwrap_or(stream.len());
+            out.push_str(&format!("\n\n{}:\n```\n{}\n```", name, stream[start..].trim_end()));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawforge_sandbox::{ResourceUsage, SandboxDriver};
+    use clawforge_security::{ApprovalNotifier, ApprovalVerdict, PendingApproval};
+    use std::sync::Mutex as StdMutex;
+
+    /// Echoes the script it was asked to run.
+    struct EchoDriver;
+
+    #[async_trait::async_trait]
+    impl SandboxDriver for EchoDriver {
+        fn kind(&self) -> &str {
+            "echo"
+        }
+        async fn start(&mut self, session_id: &str) -> Result<String> {
+            Ok(session_id.to_string())
+        }
+        async fn exec(&self, command: &[&str], _timeout_secs: Option<u64>) -> Result<ContainerExecResult> {
+            Ok(ContainerExecResult { stdout: command.last().unwrap_or(&"").to_string(), ..Default::default() })
+        }
+        async fn copy_in(&self, _host_path: &str, _sandbox_path: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn copy_out(&self, _sandbox_path: &str, _host_path: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn stop(&mut self) -> Result<()> {
+            Ok(())
+        }
+        async fn resource_usage(&self) -> Result<ResourceUsage> {
+            Ok(ResourceUsage::default())
+        }
+    }
+
+    /// Answers every prompt with a fixed verdict and remembers where it went.
+    struct AutoAnswer {
+        verdict: ApprovalVerdict,
+        broker: StdMutex<Option<Arc<ApprovalBroker>>>,
+        chats: StdMutex<Vec<Option<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ApprovalNotifier for AutoAnswer {
+        async fn deliver(&self, request: &PendingApproval) -> Result<()> {
+            self.chats.lock().unwrap().push(request.chat_id.clone());
+            let broker = self.broker.lock().unwrap().clone().unwrap();
+            let (id, verdict) = (request.id.clone(), self.verdict);
+            tokio::spawn(async move { broker.resolve(&id, verdict).await });
+            Ok(())
+        }
+    }
+
+    async fn runner(verdict: ApprovalVerdict) -> (CodeRunner, Arc<AutoAnswer>) {
+        let answer = Arc::new(AutoAnswer { verdict, broker: StdMutex::new(None), chats: StdMutex::new(vec![]) });
+        let broker = Arc::new(ApprovalBroker::default().with_notifier(answer.clone()));
+        *answer.broker.lock().unwrap() = Some(broker.clone());
+        let sandboxes = Arc::new(SandboxRegistry::new());
+        sandboxes.register("telegram:42".into(), Box::new(EchoDriver)).await;
+        (CodeRunner::new(sandboxes, broker), answer)
+    }
+
+    const REPLY: &str = "Try this:\n\n```bash\necho hi\n```\n\nand this is just text:\n\n```text\nnot code\n```\n";
+
+    #[tokio::test]
+    async fn approved_block_runs_once_after_a_prompt_in_its_chat() {
+        let (runner, answer) = runner(ApprovalVerdict::Allow).await;
+        let offered = runner.offer("telegram:42", "telegram", "42", REPLY).await;
+        assert_eq!(offered.len(), 1);
+        let id = offered[0].id.clone();
+        assert_eq!(id.len(), 32);
+        assert_eq!(decode_run_callback(&offered[0].callback_data()), Some(id.clone()));
+
+        let result = runner.run(&id, "42").await.unwrap();
+        assert_eq!(result.stdout.trim(), "echo hi");
+        assert_eq!(*answer.chats.lock().unwrap(), vec![Some("42".to_string())]);
+        assert!(runner.run(&id, "42").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn denied_block_is_discarded() {
+        let (runner, _) = runner(ApprovalVerdict::Deny).await;
+        let id = runner.offer("telegram:42", "telegram", "42", REPLY).await[0].id.clone();
+        let err = runner.handle_reply(&format!("/run {}", id), "42").await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("not approved"));
+        assert!(runner.run(&id, "42").await.unwrap_err().to_string().contains("No runnable block"));
+    }
+
+    #[tokio::test]
+    async fn presses_from_other_chats_and_unknown_ids_are_refused() {
+        let (runner, answer) = runner(ApprovalVerdict::Allow).await;
+        let id = runner.offer("telegram:42", "telegram", "42", REPLY).await[0].id.clone();
+
+        let err = runner.run(&id, "666").await.unwrap_err();
+        assert!(err.to_string().contains("not offered in this chat"));
+        assert!(runner.run("0123456789abcdef0123456789abcdef", "42").await.is_err());
+        assert!(runner.handle_reply("hello", "42").await.is_none());
+        assert!(answer.chats.lock().unwrap().is_empty());
+
+        // The refused press left the offer for its own chat.
+        assert!(runner.run(&id, "42").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn stale_offers_expire() {
+        let (runner, _) = runner(ApprovalVerdict::Allow).await;
+        let id = runner.offer("telegram:42", "telegram", "42", REPLY).await[0].id.clone();
+        let stale = Instant::now();
+        runner.offered.lock().await.get_mut(&id).unwrap().1 = stale;
+        assert!(runner.run(&id, "42").await.is_err());
+
+        let old = runner.offer("telegram:42", "telegram", "42", REPLY).await[0].id.clone();
+        runner.offered.lock().await.get_mut(&old).unwrap().1 = stale;
+        runner.offer("telegram:42", "telegram", "42", REPLY).await;
+        assert!(!runner.offered.lock().await.contains_key(&old));
+    }
+}
@@ -1,15 +1,33 @@
+use crate::approval_buttons::{decode_callback, resolved_text, ApprovalPrompt};
+use crate::approval_relay::ApprovalButtons;
+use crate::chat_agent::ChatAgent;
+use crate::discord_components::DiscordComponents;
+use crate::dm_gate::{DmDecision, DmGate};
+use crate::media_upload::MediaChannel;
+use crate::outbound::OutboundMessage;
+use crate::stream_edit::EditableChannel;
 use crate::ChannelAdapter;
 use async_trait::async_trait;
+use clawforge_sandbox::ApprovalResponse;
+use serenity::builder::{CreateAttachment, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage};
+use serenity::http::Http;
 use serenity::prelude::*;
+use serenity::model::application::Interaction;
 use serenity::model::channel::Message as DiscordMessage;
 use serenity::model::gateway::Ready;
+use serenity::model::id::{ChannelId, MessageId};
+use std::sync::Arc;
 use tokio::sync::mpsc;
-use tracing::{error, info};
-use clawforge_core::{Message, EventKind, Event};
-use uuid::Uuid;
+use tracing::{error, info, warn};
+use clawforge_core::{Message, OutboundMedia};
+
+/// Discord rejects messages longer than this many characters.
+const DISCORD_MAX_CHARS: usize = 2000;
 
 struct Handler {
-    supervisor_tx: mpsc::Sender<Message>,
+    approval_tx: Option<mpsc::Sender<ApprovalResponse>>,
+    dm_gate: Option<Arc<DmGate>>,
+    agent: Option<Arc<dyn ChatAgent>>,
 }
 
 #[async_trait]
@@ -19,25 +37,91 @@ impl EventHandler for Handler {
             return;
         }
 
+        // Direct messages have no guild; only those go through the pairing gate.
+        if let (Some(gate), true) = (&self.dm_gate, msg.guild_id.is_none()) {
+            match gate.check(&msg.author.id.to_string(), &msg.content) {
+                DmDecision::Deliver => {}
+                DmDecision::Reply(reply) => {
+                    if let Err(e) = msg.channel_id.say(&ctx.http, reply).await {
+                        error!("Error sending pairing reply: {:?}", e);
+                    }
+                    return;
+                }
+                DmDecision::Drop => return,
+            }
+        }
+
         let channel_id = msg.channel_id.to_string();
-        info!("Received message from Discord channel {}: {}", channel_id, msg.content);
-
-        let event = Event::new(
-            Uuid::new_v4(), // Dummy Run ID for now
-            Uuid::new_v4(), // Dummy Agent ID
-            EventKind::RunStarted,
-            serde_json::json!({
-                "source": "discord",
-                "channel_id": channel_id,
-                "author": msg.author.name,
-                "text": msg.content
-            })
-        );
+        let Some(agent) = self.agent.clone() else {
+            warn!("Discord message from channel {} dropped: no agent answers this channel", channel_id);
+            return;
+        };
+        info!("Received message from Discord channel {}", channel_id);
+
+        // A run can take minutes; don't hold up the gateway connection.
+        tokio::spawn(async move {
+            let reply = match agent.reply("discord", &channel_id, &msg.author.id.to_string(), &msg.content).await {
+                Ok(reply) => reply,
+                Err(e) => {
+                    error!("Discord run for channel {} failed: {:#}", channel_id, e);
+                    format!("Sorry, that didn't work: {:#}", e)
+                }
+            };
+            if let Err(e) = send_text(&ctx.http, msg.channel_id, &reply).await {
+                error!("Error sending Discord reply to {}: {:#}", channel_id, e);
+            }
+        });
+    }
 
-        let _ = self.supervisor_tx.send(Message::AuditEvent(clawforge_core::AuditEventPayload { event })).await;
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let Interaction::Component(component) = interaction else {
+            return;
+        };
+        let Some((choice, id)) = decode_callback(&component.data.custom_id) else {
+            return;
+        };
+        let who = component.user.name.clone();
+        // Buttons are visible to everyone in the channel; only known contacts
+        // may press them.
+        if !self.dm_gate.as_ref().is_some_and(|gate| gate.may_approve(&component.user.id.to_string())) {
+            warn!("Discord approval for {} from {} refused: not an approver", id, who);
+            let response = CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content("You are not allowed to approve this")
+                    .ephemeral(true),
+            );
+            if let Err(e) = component.create_response(&ctx.http, response).await {
+                error!("Error answering approval interaction: {:?}", e);
+            }
+            return;
+        }
+        info!("Discord approval {} for {} from {}", choice.verdict(), id, who);
 
-        if let Err(e) = msg.channel_id.say(&ctx.http, format!("Received: {}", msg.content)).await {
-            error!("Error sending message: {:?}", e);
+        let delivered = match &self.approval_tx {
+            Some(tx) => tx
+                .send(ApprovalResponse { id: id.clone(), verdict: choice.verdict().into() })
+                .await
+                .is_ok(),
+            None => false,
+        };
+
+        let response = if delivered {
+            // Edit the prompt in place: drop the buttons so they can't be pressed twice.
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(resolved_text(choice, &id, &who))
+                    .components(vec![]),
+            )
+        } else {
+            warn!("No approval sink accepted Discord decision for {}", id);
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content("Approval could not be recorded")
+                    .ephemeral(true),
+            )
+        };
+        if let Err(e) = component.create_response(&ctx.http, response).await {
+            error!("Error answering approval interaction: {:?}", e);
         }
     }
 
@@ -48,11 +132,35 @@ impl EventHandler for Handler {
 
 pub struct DiscordAdapter {
     token: String,
+    approval_tx: Option<mpsc::Sender<ApprovalResponse>>,
+    dm_gate: Option<Arc<DmGate>>,
+    agent: Option<Arc<dyn ChatAgent>>,
 }
 
 impl DiscordAdapter {
     pub fn new(token: String) -> Self {
-        Self { token }
+        Self { token, approval_tx: None, dm_gate: None, agent: None }
+    }
+
+    /// Answer delivered messages with `agent`'s replies. Without one,
+    /// inbound messages are dropped.
+    pub fn with_agent(mut self, agent: Arc<dyn ChatAgent>) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+
+    /// Gate direct messages through the DM pairing flow.
+    pub fn with_dm_gate(mut self, gate: Arc<DmGate>) -> Self {
+        self.dm_gate = Some(gate);
+        self
+    }
+
+    /// Forward approval button presses to the given channel.
+    /// Only presses from contacts the DM gate knows count, so buttons need
+    /// `with_dm_gate` too.
+    pub fn with_approval_sink(mut self, tx: mpsc::Sender<ApprovalResponse>) -> Self {
+        self.approval_tx = Some(tx);
+        self
     }
 }
 
@@ -60,7 +168,7 @@ impl DiscordAdapter {
 impl ChannelAdapter for DiscordAdapter {
     fn name(&self) -> &str { "discord" }
 
-    async fn start(&self, supervisor_tx: mpsc::Sender<Message>) -> anyhow::Result<()> {
+    async fn start(&self, _supervisor_tx: mpsc::Sender<Message>) -> anyhow::Result<()> {
         info!("Starting Discord adapter");
         
         let intents = GatewayIntents::GUILD_MESSAGES
@@ -68,7 +176,11 @@ impl ChannelAdapter for DiscordAdapter {
             | GatewayIntents::MESSAGE_CONTENT;
 
         let mut client = Client::builder(&self.token, intents)
-            .event_handler(Handler { supervisor_tx })
+            .event_handler(Handler {
+                approval_tx: self.approval_tx.clone(),
+                dm_gate: self.dm_gate.clone(),
+                agent: self.agent.clone(),
+            })
             .await?;
 
         if let Err(why) = client.start().await {
@@ -80,9 +192,104 @@ impl ChannelAdapter for DiscordAdapter {
     }
 }
 
+#[async_trait]
+impl EditableChannel for DiscordAdapter {
+    async fn post(&self, chat_id: &str, text: &str) -> anyhow::Result<String> {
+        let channel_id = ChannelId::new(chat_id.parse()?);
+        let sent = channel_id.say(&Http::new(&self.token), text).await?;
+        Ok(sent.id.to_string())
+    }
+
+    async fn edit(&self, chat_id: &str, message_id: &str, text: &str) -> anyhow::Result<()> {
+        let channel_id = ChannelId::new(chat_id.parse()?);
+        let message_id = MessageId::new(message_id.parse()?);
+        channel_id
+            .edit_message(&Http::new(&self.token), message_id, EditMessage::new().content(text))
+            .await?;
+        Ok(())
+    }
+}
+#[async_trait]
+impl ApprovalButtons for DiscordAdapter {
+    async fn send_approval_prompt(&self, chat_id: &str, prompt: &ApprovalPrompt) -> anyhow::Result<()> {
+        DiscordAdapter::send_approval_prompt(self, chat_id, prompt).await
+    }
+}
+
+#[async_trait]
+impl MediaChannel for DiscordAdapter {
+    async fn send_media(&self, chat_id: &str, media: &OutboundMedia, bytes: Vec<u8>) -> anyhow::Result<()> {
+        DiscordAdapter::send_media(self, chat_id, media, bytes).await
+    }
+}
+
+
 impl DiscordAdapter {
-    pub async fn send_message(&self, _chat_id: &str, _text: &str) -> anyhow::Result<()> {
-        info!("Discord send_message not fully implemented in adapter yet.");
+    /// Send a Markdown reply to a channel, split into as many messages as
+    /// Discord's length limit needs.
+    pub async fn send_message(&self, chat_id: &str, text: &str) -> anyhow::Result<()> {
+        send_text(&Http::new(&self.token), ChannelId::new(chat_id.parse()?), text).await
+    }
+
+    /// Upload a generated attachment to a channel, captioned with the message content.
+    pub async fn send_media(&self, channel_id: &str, media: &OutboundMedia, bytes: Vec<u8>) -> anyhow::Result<()> {
+        let channel_id = ChannelId::new(channel_id.parse()?);
+        let mut message = CreateMessage::new().add_file(CreateAttachment::bytes(bytes, media.file_name.clone()));
+        if let Some(caption) = &media.caption {
+            message = message.content(caption);
+        }
+        channel_id.send_message(&Http::new(&self.token), message).await?;
+        info!("[Discord] Uploaded {} to {}", media.file_name, channel_id);
+        Ok(())
+    }
+
+    /// Post an approval request with Allow/Deny buttons to a channel.
+    pub async fn send_approval_prompt(&self, channel_id: &str, prompt: &ApprovalPrompt) -> anyhow::Result<()> {
+        let channel_id = ChannelId::new(channel_id.parse()?);
+        let http = Http::new(&self.token);
+        channel_id
+            .send_message(&http, DiscordComponents::build_approval_message(prompt))
+            .await?;
         Ok(())
     }
 }
+
+/// Render a Markdown reply for Discord and post it in messages of at most
+/// `DISCORD_MAX_CHARS`.
+async fn send_text(http: &Http, channel: ChannelId, text: &str) -> anyhow::Result<()> {
+    let message = OutboundMessage::render("discord", text);
+    for part in split_message(&message.text, DISCORD_MAX_CHARS) {
+        channel.say(http, part).await?;
+    }
+    Ok(())
+}
+
+/// Split `text` into pieces of at most `max` characters, breaking after a
+/// newline where there is one.
+fn split_message(text: &str, max: usize) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while rest.chars().count() > max {
+        let limit = rest.char_indices().nth(max).map_or(rest.len(), |(i, _)| i);
+        let cut = rest[..limit].rfind('\n').map_or(limit, |i| i + 1);
+        parts.push(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    if !rest.is_empty() {
+        parts.push(rest);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_replies_split_at_line_breaks_within_the_limit() {
+        assert_eq!(split_message("short", 10), vec!["short"]);
+        assert_eq!(split_message("aaaa\nbbbb\ncc", 10), vec!["aaaa\nbbbb\n", "cc"]);
+        assert_eq!(split_message("ééééééé", 3), vec!["ééé", "ééé", "é"]);
+        assert!(split_message("", 10).is_empty());
+    }
+}
@@ -0,0 +1,49 @@
+//! Discord Message Components
+//!
+//! Renders approval prompts as button action rows and maps component
+//! interactions back onto approval decisions.
+
+use serenity::builder::{CreateActionRow, CreateButton, CreateMessage};
+use serenity::model::application::ButtonStyle;
+
+use crate::approval_buttons::{ApprovalChoice, ApprovalPrompt, DISCORD_CUSTOM_ID_MAX};
+
+pub struct DiscordComponents;
+
+impl DiscordComponents {
+    /// Builds a single action row holding one button per approval choice.
+    ///
+    /// Returns `None` when the request ID is too long for a `custom_id`.
+    pub fn build_approval_row(prompt: &ApprovalPrompt) -> Option<CreateActionRow> {
+        let buttons = prompt
+            .choices()
+            .iter()
+            .map(|&choice| {
+                let custom_id = prompt.callback_data(choice);
+                (custom_id.len() <= DISCORD_CUSTOM_ID_MAX).then(|| {
+                    CreateButton::new(custom_id)
+                        .label(choice.label())
+                        .style(Self::style_for(choice))
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(CreateActionRow::Buttons(buttons))
+    }
+
+    /// Full message payload for an approval prompt.
+    pub fn build_approval_message(prompt: &ApprovalPrompt) -> CreateMessage {
+        let message = CreateMessage::new().content(prompt.text());
+        match Self::build_approval_row(prompt) {
+            Some(row) => message.components(vec![row]),
+            None => message,
+        }
+    }
+
+    fn style_for(choice: ApprovalChoice) -> ButtonStyle {
+        match choice {
+            ApprovalChoice::Allow => ButtonStyle::Success,
+            ApprovalChoice::AllowSession => ButtonStyle::Primary,
+            ApprovalChoice::Deny => ButtonStyle::Danger,
+        }
+    }
+}
@@ -4,6 +4,7 @@
 //! and pipe TTS buffers into the UDP streams.
 
 use anyhow::Result;
+use clawforge_tts::{pcm_to_wav, SttProvider, SttRequest};
 use tracing::info;
 
 pub struct DiscordVoice;
@@ -22,6 +23,14 @@ impl DiscordVoice {
         Ok(())
     }
 
+    /// Transcribe a buffer of received voice audio (48 kHz stereo 16-bit PCM,
+    /// as decoded from Discord's Opus stream) with the shared STT provider.
+    pub async fn transcribe_pcm(stt: &dyn SttProvider, audio_pcm: &[u8]) -> Result<String> {
+        let wav = pcm_to_wav(audio_pcm, 48_000, 2);
+        let transcript = stt.transcribe(SttRequest::new(wav, "audio/wav")).await?;
+        Ok(transcript.text)
+    }
+
     /// Cleanly exits the active voice session.
     pub async fn leave_voice_channel(guild_id: u64) -> Result<()> {
         info!("Leaving voice channel in guild {}", guild_id);
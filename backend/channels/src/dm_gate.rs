@@ -0,0 +1,212 @@
+//! DM pairing gate
+//!
+//! Decides whether an inbound direct message reaches the agent. Allowlisted
+//! and previously paired senders pass; unknown senders on a `pairing` channel
+//! get a prompt asking for a one-time code, which is checked against the
+//! shared `PairingStore`. Group traffic is not gated here.
+//!
+//! With an `IdentityRegistry` attached, a sender linked to a person who is
+//! paired or allowlisted on any channel passes too, and an unknown sender can
+//! join an existing person by sending a link code (`/link <code>`) issued to
+//! one of that person's accounts.
+
+use clawforge_security::{is_link_code, AccountRef, DmPolicy, IdentityRegistry, PairingStore};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+/// Failed code attempts allowed per sender before the gate stops replying.
+pub const MAX_PAIRING_ATTEMPTS: u32 = 5;
+
+const PAIRING_PROMPT: &str = "This assistant only talks to paired contacts. \
+Ask the owner for a pairing code and send it here as: /pair <code>";
+const PAIRING_OK: &str = "Paired. You can now message the assistant.";
+const PAIRING_BAD_CODE: &str = "That pairing code is invalid or has expired.";
+const LINK_OK: &str = "Linked to your other accounts. You can now message the assistant.";
+
+/// What the adapter should do with an inbound DM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DmDecision {
+    /// Forward to the agent as usual.
+    Deliver,
+    /// Do not forward; send this text back to the sender instead.
+    Reply(String),
+    /// Do not forward and stay silent.
+    Drop,
+}
+
+/// Per-channel DM gate backed by a shared pairing store.
+pub struct DmGate {
+    channel: String,
+    policy: DmPolicy,
+    store: Arc<PairingStore>,
+    identities: Option<Arc<IdentityRegistry>>,
+    failures: Mutex<HashMap<String, u32>>,
+}
+
+impl DmGate {
+    pub fn new(channel: impl Into<String>, policy: DmPolicy, store: Arc<PairingStore>) -> Self {
+        Self { channel: channel.into(), policy, store, identities: None, failures: Mutex::new(HashMap::new()) }
+    }
+
+    /// Let senders inherit access from the accounts they are linked to.
+    pub fn with_identities(mut self, identities: Arc<IdentityRegistry>) -> Self {
+        self.identities = Some(identities);
+        self
+    }
+
+    /// Pairing-store device ID for a sender on this channel.
+    pub fn device_id(&self, sender: &str) -> String {
+        format!("{}:{}", self.channel, sender)
+    }
+
+    /// Evaluate a DM from `sender` whose text body is `text`.
+    pub fn check(&self, sender: &str, text: &str) -> DmDecision {
+        if self.policy.block_all {
+            return DmDecision::Drop;
+        }
+        let device_id = self.device_id(sender);
+        if self.store.is_paired(&device_id) || self.policy.is_allowed(sender) || self.linked_is_known(sender) {
+            return DmDecision::Deliver;
+        }
+        if !self.policy.pairing {
+            return DmDecision::Drop;
+        }
+
+        let mut failures = self.failures.lock().unwrap();
+        let attempts = failures.get(sender).copied().unwrap_or(0);
+        if attempts >= MAX_PAIRING_ATTEMPTS {
+            return DmDecision::Drop;
+        }
+
+        if let (Some(identities), Some(code)) = (&self.identities, parse_link_code(text)) {
+            return match identities.complete_link(code, &AccountRef::new(&self.channel, sender)) {
+                Ok(person) => {
+                    failures.remove(sender);
+                    info!("[DmGate] {} on {} linked to {}", sender, self.channel, person.id);
+                    DmDecision::Reply(LINK_OK.to_string())
+                }
+                Err(e) => {
+                    failures.insert(sender.to_string(), attempts + 1);
+                    warn!("[DmGate] Link attempt from {} on {} failed: {}", sender, self.channel, e);
+                    DmDecision::Reply(PAIRING_BAD_CODE.to_string())
+                }
+            };
+        }
+        let Some(code) = parse_pairing_code(text) else {
+            return DmDecision::Reply(PAIRING_PROMPT.to_string());
+        };
+        match self.store.verify_code(code, &device_id) {
+            Ok(_) => {
+                failures.remove(sender);
+                info!("[DmGate] {} paired on {}", sender, self.channel);
+                DmDecision::Reply(PAIRING_OK.to_string())
+            }
+            Err(e) => {
+                failures.insert(sender.to_string(), attempts + 1);
+                warn!("[DmGate] Pairing attempt from {} on {} failed: {}", sender, self.channel, e);
+                DmDecision::Reply(PAIRING_BAD_CODE.to_string())
+            }
+        }
+    }
+
+    /// Whether `sender` may answer approval buttons: a paired or allowlisted
+    /// contact, directly or through a linked account. An open DM policy lets
+    /// anyone talk to the agent, but not approve its tool calls.
+    pub fn may_approve(&self, sender: &str) -> bool {
+        !self.policy.block_all
+            && (self.store.is_paired(&self.device_id(sender))
+                || self.policy.allowlist.contains(sender)
+                || self.linked_is_known(sender))
+    }
+
+    /// Whether the person `sender` is linked to is paired or allowed
+    /// through any of their accounts.
+    fn linked_is_known(&self, sender: &str) -> bool {
+        let Some(person) = self.identities.as_ref().and_then(|ids| ids.person_for(&self.channel, sender)) else {
+            return false;
+        };
+        self.policy.allowlist.contains(&person.id)
+            || person.accounts.iter().any(|account| {
+                self.store.is_paired(&account.key())
+                    || (account.channel == self.channel && self.policy.allowlist.contains(&account.sender))
+            })
+    }
+}
+
+/// Accepts `/link K7MPX2QD`.
+fn parse_link_code(text: &str) -> Option<&str> {
+    let code = text.trim().strip_prefix("/link")?.trim();
+    is_link_code(code).then_some(code)
+}
+
+/// Accepts `/pair 123456` or a bare 6-digit code.
+fn parse_pairing_code(text: &str) -> Option<&str> {
+    let text = text.trim();
+    let code = text.strip_prefix("/pair").map(str::trim).unwrap_or(text);
+    (code.len() == 6 && code.bytes().all(|b| b.is_ascii_digit())).then_some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gate(mode: &str) -> (DmGate, Arc<PairingStore>) {
+        let store = Arc::new(PairingStore::new(300));
+        let policy = DmPolicy::from_mode(mode, vec!["owner".to_string()]);
+        (DmGate::new("telegram", policy, store.clone()), store)
+    }
+
+    #[test]
+    fn unknown_sender_pairs_with_code() {
+        let (gate, store) = gate("pairing");
+        assert_eq!(gate.check("owner", "hi"), DmDecision::Deliver);
+        assert_eq!(gate.check("42", "hi"), DmDecision::Reply(PAIRING_PROMPT.into()));
+
+        let code = store.generate_code(Some("friend")).code;
+        assert_eq!(gate.check("42", &format!("/pair {}", code)), DmDecision::Reply(PAIRING_OK.into()));
+        assert_eq!(gate.check("42", "hi again"), DmDecision::Deliver);
+        assert!(store.is_paired("telegram:42"));
+    }
+
+    #[test]
+    fn repeated_bad_codes_go_silent() {
+        let (gate, _) = gate("pairing");
+        for _ in 0..MAX_PAIRING_ATTEMPTS {
+            assert_eq!(gate.check("42", "000000"), DmDecision::Reply(PAIRING_BAD_CODE.into()));
+        }
+        assert_eq!(gate.check("42", "000000"), DmDecision::Drop);
+    }
+
+    #[test]
+    fn allowlist_and_disabled_never_prompt() {
+        assert_eq!(gate("allowlist").0.check("42", "hi"), DmDecision::Drop);
+        assert_eq!(gate("disabled").0.check("owner", "hi"), DmDecision::Drop);
+        assert_eq!(gate("open").0.check("42", "hi"), DmDecision::Deliver);
+    }
+
+    #[test]
+    fn only_known_contacts_may_approve() {
+        let (open, store) = gate("open");
+        assert_eq!(open.check("42", "hi"), DmDecision::Deliver);
+        assert!(!open.may_approve("42"));
+        assert!(open.may_approve("owner"));
+        store.register_device("telegram:42", None).unwrap();
+        assert!(open.may_approve("42"));
+        assert!(!gate("disabled").0.may_approve("owner"));
+    }
+
+    #[test]
+    fn linked_accounts_inherit_access() {
+        let (gate, store) = gate("pairing");
+        let identities = Arc::new(IdentityRegistry::new(300));
+        let gate = gate.with_identities(identities.clone());
+        store.register_device("slack:U1", Some("alice".into())).unwrap();
+        assert_eq!(gate.check("42", "hi"), DmDecision::Reply(PAIRING_PROMPT.into()));
+
+        let code = identities.begin_link(AccountRef::new("slack", "U1"));
+        assert_eq!(gate.check("42", &format!("/link {}", code)), DmDecision::Reply(LINK_OK.into()));
+        assert_eq!(gate.check("42", "hi"), DmDecision::Deliver);
+        assert!(!store.is_paired("telegram:42"));
+    }
+}
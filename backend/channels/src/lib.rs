@@ -9,6 +9,7 @@ pub mod telegram_groups;
 pub mod telegram_inline;
 pub mod telegram_media;
 pub mod discord;
+pub mod discord_components;
 pub mod discord_embeds;
 pub mod discord_slash;
 pub mod discord_threads;
@@ -43,6 +44,52 @@ pub mod signal;
 pub mod rate_limiter;
 pub use rate_limiter::{ChannelRateLimiter, RateLimitPolicy, RateLimitResult};
 
+// --------------- Shared webhook verification ---------------
+pub mod webhook_verify;
+pub use webhook_verify::{verified, SignatureScheme, WebhookVerifier};
+
+// --------------- Native approval buttons ---------------
+pub mod approval_buttons;
+pub use approval_buttons::{ApprovalChoice, ApprovalKind, ApprovalPrompt};
+pub mod approval_relay;
+pub use approval_relay::{approval_reply, approval_sink, ApprovalButtons, ChatApprovalNotifier};
+
+// --------------- Artifact links ---------------
+pub mod artifact_links;
+pub use artifact_links::ArtifactLink;
+
+// --------------- Outbound media uploads ---------------
+pub mod media_upload;
+pub use media_upload::{load_media, ChatMediaRelay, MediaChannel, MAX_UPLOAD_BYTES};
+
+// --------------- Push notifications ---------------
+pub mod push;
+pub use push::{PushNotification, PushNotifier, PushPriority, PushProvider};
+
+// --------------- Edit-in-place streaming ---------------
+pub mod stream_edit;
+pub use stream_edit::{stream_reply, EditBudget, EditableChannel, StreamingReply};
+
+// --------------- Adapter lifecycle status ---------------
+pub mod adapter_supervisor;
+pub use adapter_supervisor::{status_relay, supervise};
+
+// --------------- DM pairing gate ---------------
+pub mod dm_gate;
+pub use dm_gate::{DmDecision, DmGate};
+
+// --------------- Per-channel Markdown rendering ---------------
+pub mod outbound;
+pub use outbound::{CodeImage, OutboundMessage};
+
+// --------------- Agent replies ---------------
+pub mod chat_agent;
+pub use chat_agent::{chat_session, ChatAgent};
+
+// --------------- Code block run affordances ---------------
+pub mod code_actions;
+pub use code_actions::{CodeRunner, RunAffordance};
+
 /// All channel adapters implement this trait.
 #[async_trait]
 pub trait ChannelAdapter: Send + Sync {
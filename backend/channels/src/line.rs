@@ -17,6 +17,7 @@ use uuid::Uuid;
 
 use clawforge_core::{AuditEventPayload, Event, EventKind, Message};
 
+use crate::webhook_verify::{verified, SignatureScheme, WebhookVerifier};
 use crate::ChannelAdapter;
 
 pub struct LineConfig {
@@ -100,7 +101,14 @@ impl ChannelAdapter for LineAdapter {
     fn name(&self) -> &str { "line" }
     fn build_router(&self) -> Router {
         let state = AppState { supervisor_tx: self.supervisor_tx.clone() };
-        Router::new().route(&self.config.webhook_path, post(webhook_handler)).with_state(state)
+        let verifier = WebhookVerifier::new(
+            "line",
+            SignatureScheme::Line { channel_secret: self.config.channel_secret.clone() },
+        );
+        verified(
+            Router::new().route(&self.config.webhook_path, post(webhook_handler)).with_state(state),
+            verifier,
+        )
     }
     async fn start(&self, _supervisor_tx: mpsc::Sender<Message>) -> Result<()> {
         info!("[LINE] Adapter ready at {}", self.config.webhook_path);
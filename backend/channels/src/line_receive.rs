@@ -4,16 +4,25 @@
 //! including signature validation and event deserialization.
 
 use anyhow::Result;
+use axum::http::HeaderMap;
 use tracing::info;
 
+use crate::webhook_verify::{SignatureScheme, WebhookVerifier};
+
 pub struct LineReceive;
 
 impl LineReceive {
     /// Validates the `x-line-signature` against the local channel secret.
     pub fn verify_signature(secret: &str, signature: &str, body: &str) -> bool {
         info!("Verifying LINE message signature...");
-        // MOCK: compute base64-encoded HMAC-SHA256
-        true
+        let Ok(value) = signature.parse() else {
+            return false;
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("x-line-signature", value);
+        WebhookVerifier::new("line", SignatureScheme::Line { channel_secret: secret.to_string() })
+            .verify(&headers, body.as_bytes())
+            .is_ok()
     }
 
     /// Primary router for parsed webhook events.
@@ -8,10 +8,16 @@
 ///   MATRIX_HOMESERVER_URL — e.g. https://matrix.org
 ///   MATRIX_ACCESS_TOKEN   — user access token
 ///   MATRIX_USER_ID        — @bot:matrix.org (used to filter self-messages)
+use crate::approval_relay::approval_reply;
+use crate::outbound::OutboundMessage;
+use crate::stream_edit::EditableChannel;
 use crate::ChannelAdapter;
 use anyhow::Result;
 use async_trait::async_trait;
 use clawforge_core::{AuditEventPayload, Event, EventKind, Message};
+use clawforge_security::ApprovalBroker;
+use std::sync::Arc;
+use infra::AdapterReporter;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
@@ -67,6 +73,10 @@ struct RoomEvent {
 struct SendMessageBody<'a> {
     msgtype: &'a str,
     body: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formatted_body: Option<&'a str>,
 }
 
 // ---------------------------------------------------------------------------
@@ -77,6 +87,8 @@ pub struct MatrixAdapter {
     config: MatrixConfig,
     supervisor_tx: mpsc::Sender<Message>,
     http_client: Client,
+    status: Option<AdapterReporter>,
+    approvals: Option<Arc<ApprovalBroker>>,
 }
 
 impl MatrixAdapter {
@@ -85,9 +97,24 @@ impl MatrixAdapter {
             config,
             supervisor_tx,
             http_client: Client::new(),
+            status: None,
+            approvals: None,
         }
     }
 
+    /// Resolve pending approvals from `/approve` replies instead of passing
+    /// them to the agent.
+    pub fn with_approvals(mut self, broker: Arc<ApprovalBroker>) -> Self {
+        self.approvals = Some(broker);
+        self
+    }
+
+    /// Report outbound sends to the adapter status registry.
+    pub fn with_status(mut self, status: AdapterReporter) -> Self {
+        self.status = Some(status);
+        self
+    }
+
     fn sync_url(&self, since: Option<&str>) -> String {
         let base = format!(
             "{}_matrix/client/v3/sync?timeout=30000&access_token={}",
@@ -215,6 +242,16 @@ impl MatrixAdapter {
             return;
         }
 
+        if let Some(broker) = &self.approvals {
+            if let Some(reply) = approval_reply(broker, "matrix", room_id, &sender, &body).await {
+                info!("[Matrix] Approval reply from {} in {}", sender, room_id);
+                if let Err(e) = self.send_message(room_id, reply).await {
+                    error!("[Matrix] Failed to confirm approval to {}: {}", sender, e);
+                }
+                return;
+            }
+        }
+
         info!("[Matrix] {} in {}: {}", sender, room_id, body);
 
         let event = Event::new(
@@ -254,9 +291,12 @@ impl MatrixAdapter {
     pub async fn send_message(&self, room_id: &str, text: &str) -> anyhow::Result<()> {
         let txn_id = Uuid::new_v4().to_string();
         let url = self.send_url(room_id, &txn_id);
+        let message = OutboundMessage::render("matrix", text);
         let body = SendMessageBody {
             msgtype: "m.text",
-            body: text,
+            body: &message.plain,
+            format: Some("org.matrix.custom.html"),
+            formatted_body: Some(&message.text),
         };
 
         let res = self.http_client.put(&url).json(&body).send().await?;
@@ -266,7 +306,40 @@ impl MatrixAdapter {
             error!("[Matrix] send failed to {}: {}", room_id, err);
             anyhow::bail!("Matrix send failed: {}", err);
         }
+        if let Some(status) = &self.status {
+            status.outbound();
+        }
         info!("[Matrix] Sent message to room {}", room_id);
         Ok(())
     }
 }
+
+#[derive(Deserialize)]
+struct SendResponse {
+    event_id: String,
+}
+
+#[async_trait]
+impl EditableChannel for MatrixAdapter {
+    async fn post(&self, chat_id: &str, text: &str) -> Result<String> {
+        let url = self.send_url(chat_id, &Uuid::new_v4().to_string());
+        let body = SendMessageBody { msgtype: "m.text", body: text, format: None, formatted_body: None };
+        let sent: SendResponse = self.http_client.put(&url).json(&body).send().await?.error_for_status()?.json().await?;
+        if let Some(status) = &self.status {
+            status.outbound();
+        }
+        Ok(sent.event_id)
+    }
+
+    async fn edit(&self, chat_id: &str, message_id: &str, text: &str) -> Result<()> {
+        let url = self.send_url(chat_id, &Uuid::new_v4().to_string());
+        let body = serde_json::json!({
+            "msgtype": "m.text",
+            "body": format!("* {}", text),
+            "m.new_content": { "msgtype": "m.text", "body": text },
+            "m.relates_to": { "rel_type": "m.replace", "event_id": message_id },
+        });
+        self.http_client.put(&url).json(&body).send().await?.error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+//! Media uploads
+//!
+//! Adapters upload generated attachments (`OutboundMedia`) natively rather
+//! than posting the signed link, so the file shows inline and outlives the
+//! URL. The bytes come from the media store when it is on this host, and
+//! from the signed URL otherwise. Attachments are named by tool output, so
+//! only files inside the store and URLs on public addresses are read.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use clawforge_core::{OutboundMedia, OutboundMediaSink};
+use clawforge_security::is_internal_address;
+use reqwest::{Client, Url};
+use tokio::io::AsyncReadExt;
+
+/// Largest attachment an adapter will upload, in bytes.
+pub const MAX_UPLOAD_BYTES: u64 = 50 * 1024 * 1024;
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Read the attachment's bytes: the stored file when `path` is inside
+/// `store_dir`, otherwise the signed URL.
+pub async fn load_media(media: &OutboundMedia, store_dir: &Path) -> Result<Vec<u8>> {
+    if let Some(path) = &media.path {
+        match tokio::fs::canonicalize(path).await {
+            Ok(path) => return read_stored(&path, store_dir).await,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path)),
+        }
+    }
+    download(&media.url).await.with_context(|| format!("Failed to download {}", media.file_name))
+}
+
+async fn read_stored(path: &Path, store_dir: &Path) -> Result<Vec<u8>> {
+    let store_dir = tokio::fs::canonicalize(store_dir).await.with_context(|| format!("Media store {} is missing", store_dir.display()))?;
+    if !path.starts_with(&store_dir) {
+        bail!("{} is outside the media store", path.display());
+    }
+    let file = tokio::fs::File::open(path).await.with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut bytes = Vec::new();
+    file.take(MAX_UPLOAD_BYTES + 1).read_to_end(&mut bytes).await?;
+    if bytes.len() as u64 > MAX_UPLOAD_BYTES {
+        bail!("{} is too large to upload", path.display());
+    }
+    Ok(bytes)
+}
+
+/// Fetch `url` from a public address, connecting to the address that was
+/// checked and refusing redirects and bodies over `MAX_UPLOAD_BYTES`.
+async fn download(url: &str) -> Result<Vec<u8>> {
+    let url = Url::parse(url)?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("Only http and https media URLs are allowed");
+    }
+    let host = url.host_str().context("Media URL has no host")?.trim_matches(['[', ']']).to_string();
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addrs: Vec<_> = tokio::net::lookup_host((host.as_str(), port)).await?.collect();
+    if let Some(addr) = addrs.iter().find(|a| is_internal_address(a.ip())) {
+        bail!("Media host '{}' resolves to internal address {}", host, addr.ip());
+    }
+    if addrs.is_empty() {
+        bail!("Cannot resolve '{}'", host);
+    }
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(DOWNLOAD_TIMEOUT)
+        .resolve_to_addrs(&host, &addrs)
+        .build()?;
+    let mut response = client.get(url).send().await?.error_for_status()?;
+    if response.content_length().is_some_and(|len| len > MAX_UPLOAD_BYTES) {
+        bail!("Attachment is too large to upload");
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if (bytes.len() + chunk.len()) as u64 > MAX_UPLOAD_BYTES {
+            bail!("Attachment is too large to upload");
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// An adapter that can upload an attachment natively.
+#[async_trait]
+pub trait MediaChannel: Send + Sync {
+    async fn send_media(&self, chat_id: &str, media: &OutboundMedia, bytes: Vec<u8>) -> Result<()>;
+}
+
+/// Uploads tool-result attachments through the adapter of the chat a run
+/// came from, reading files only from the media store at `store_dir`.
+pub struct ChatMediaRelay {
+    store_dir: PathBuf,
+    channels: HashMap<String, Arc<dyn MediaChannel>>,
+}
+
+impl ChatMediaRelay {
+    pub fn new(store_dir: impl Into<PathBuf>) -> Self {
+        Self { store_dir: store_dir.into(), channels: HashMap::new() }
+    }
+
+    /// Upload attachments for runs started from `channel` through `adapter`.
+    pub fn with_channel(mut self, channel: impl Into<String>, adapter: Arc<dyn MediaChannel>) -> Self {
+        self.channels.insert(channel.into(), adapter);
+        self
+    }
+}
+
+#[async_trait]
+impl OutboundMediaSink for ChatMediaRelay {
+    async fn deliver(&self, channel: &str, chat_id: &str, media: &OutboundMedia) -> Result<()> {
+        let Some(adapter) = self.channels.get(channel) else {
+            bail!("no media uploads for channel '{}'", channel);
+        };
+        let bytes = load_media(media, &self.store_dir).await?;
+        adapter.send_media(chat_id, media, bytes).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawforge_core::OutboundMediaKind;
+
+    fn media(path: Option<&Path>, url: &str) -> OutboundMedia {
+        OutboundMedia {
+            kind: OutboundMediaKind::File,
+            mime_type: "text/plain".into(),
+            file_name: "a.txt".into(),
+            url: url.into(),
+            path: path.map(|p| p.display().to_string()),
+            caption: None,
+            size: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_only_the_store_and_public_urls() {
+        let root = std::env::temp_dir().join(format!("clawforge-upload-{}", uuid::Uuid::new_v4()));
+        let store = root.join("media");
+        std::fs::create_dir_all(&store).unwrap();
+        std::fs::write(store.join("a.txt"), b"stored").unwrap();
+        std::fs::write(root.join("secret.txt"), b"secret").unwrap();
+
+        assert_eq!(load_media(&media(Some(&store.join("a.txt")), ""), &store).await.unwrap(), b"stored");
+        let escape = store.join("..").join("secret.txt");
+        assert!(load_media(&media(Some(&escape), ""), &store).await.unwrap_err().to_string().contains("outside the media store"));
+
+        for url in ["http://127.0.0.1:9/a.txt", "http://169.254.169.254/latest/", "http://[::1]/a", "file:///etc/passwd"] {
+            let err = load_media(&media(None, url), &store).await.unwrap_err();
+            assert!(format!("{:#}", err).contains("internal address") || format!("{:#}", err).contains("Only http"), "{}: {:#}", url, err);
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
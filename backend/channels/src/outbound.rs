@@ -0,0 +1,59 @@
+//! Outbound formatting
+//!
+//! Agents answer in Markdown; every channel parses its own dialect with its
+//! own escaping rules. `OutboundMessage::render` picks the dialect for the
+//! channel and renders the reply once, keeping a plain-text fallback for
+//! notifications and for retrying when a platform still rejects the markup.
+//! Channels without any code formatting also get each code block as a
+//! syntax-highlighted image to attach.
+
+use markdown::{CodeBlockAnalyzer, IrParser, RenderTarget, Renderer};
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct OutboundMessage {
+    pub target: RenderTarget,
+    /// The reply in the channel's dialect.
+    pub text: String,
+    /// The reply without formatting.
+    pub plain: String,
+    /// Block Kit blocks for Slack; empty for other channels.
+    pub blocks: Vec<Value>,
+    /// Highlighted code blocks for channels that can only show them as images.
+    pub code_images: Vec<CodeImage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CodeImage {
+    pub mime_type: &'static str,
+    pub data: String,
+    /// Caption naming the language, e.g. "python code".
+    pub alt: String,
+}
+
+impl OutboundMessage {
+    pub fn render(channel: &str, markdown: &str) -> Self {
+        let target = RenderTarget::for_channel(channel);
+        let nodes = IrParser::parse(markdown);
+        let blocks = if target == RenderTarget::Slack { Renderer::to_slack_blocks(&nodes) } else { Vec::new() };
+        let code_images = if target == RenderTarget::Plain {
+            CodeBlockAnalyzer::analyze(&nodes)
+                .iter()
+                .map(|block| CodeImage {
+                    mime_type: "image/svg+xml",
+                    data: CodeBlockAnalyzer::render_svg(block),
+                    alt: format!("{} code", block.language.as_deref().unwrap_or("source")),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            target,
+            text: Renderer::render(&nodes, target),
+            plain: Renderer::to_plain_text(&nodes),
+            blocks,
+            code_images,
+        }
+    }
+}
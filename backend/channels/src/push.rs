@@ -0,0 +1,230 @@
+//! Push notifications
+//!
+//! Outbound-only channel for reaching the user outside chat apps: alerts,
+//! approval prompts and cron digests go to ntfy, Pushover, or a relay that
+//! forwards to APNs/FCM. Configured from the environment:
+//!
+//! - `NTFY_URL` (topic URL, e.g. `https://ntfy.sh/my-claw`), optional `NTFY_TOKEN`
+//! - `PUSHOVER_TOKEN` + `PUSHOVER_USER`
+//! - `PUSH_RELAY_URL`, optional `PUSH_RELAY_TOKEN`
+//!
+//! Cron jobs select it with `delivery_target: push`.
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use clawforge_core::Message;
+use clawforge_scheduler::PushSink;
+use clawforge_security::{ApprovalNotifier, PendingApproval};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::info;
+
+use crate::ChannelAdapter;
+
+const PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+/// Pushover caps messages at 1024 characters, ntfy at 4 KB; keep both happy.
+const MAX_BODY_CHARS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushPriority {
+    Low,
+    #[default]
+    Default,
+    High,
+    Urgent,
+}
+
+impl PushPriority {
+    /// ntfy priority, 1-5.
+    fn ntfy(self) -> u8 {
+        match self {
+            Self::Low => 2,
+            Self::Default => 3,
+            Self::High => 4,
+            Self::Urgent => 5,
+        }
+    }
+
+    /// Pushover priority, -1..=1 (2 needs retry/expire parameters).
+    fn pushover(self) -> i8 {
+        match self {
+            Self::Low => -1,
+            Self::Default => 0,
+            Self::High | Self::Urgent => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PushNotification {
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub priority: PushPriority,
+    /// Opened when the notification is tapped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub click_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl PushNotification {
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self { title: title.into(), body: body.into(), ..Default::default() }
+    }
+
+    pub fn with_priority(mut self, priority: PushPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    fn body(&self) -> String {
+        if self.body.chars().count() <= MAX_BODY_CHARS {
+            return self.body.clone();
+        }
+        let mut body: String = self.body.chars().take(MAX_BODY_CHARS - 1).collect();
+        body.push('…');
+        body
+    }
+}
+
+/// Where notifications are sent.
+#[derive(Debug, Clone)]
+pub enum PushProvider {
+    Ntfy { topic_url: String, token: Option<String> },
+    Pushover { app_token: String, user_key: String },
+    /// Service forwarding to APNs/FCM; receives the notification as JSON.
+    Relay { url: String, token: Option<String> },
+}
+
+pub struct PushNotifier {
+    provider: PushProvider,
+    http: Client,
+}
+
+impl PushNotifier {
+    pub fn new(provider: PushProvider) -> Self {
+        Self { provider, http: Client::builder().timeout(Duration::from_secs(15)).build().unwrap_or_default() }
+    }
+
+    /// The first provider configured in the environment, if any.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let provider = if let Some(topic_url) = var("NTFY_URL") {
+            PushProvider::Ntfy { topic_url, token: var("NTFY_TOKEN") }
+        } else if let (Some(app_token), Some(user_key)) = (var("PUSHOVER_TOKEN"), var("PUSHOVER_USER")) {
+            PushProvider::Pushover { app_token, user_key }
+        } else if let Some(url) = var("PUSH_RELAY_URL") {
+            PushProvider::Relay { url, token: var("PUSH_RELAY_TOKEN") }
+        } else {
+            return None;
+        };
+        Some(Self::new(provider))
+    }
+
+    pub fn provider(&self) -> &PushProvider {
+        &self.provider
+    }
+
+    pub async fn send(&self, notification: &PushNotification) -> Result<()> {
+        let request = match &self.provider {
+            PushProvider::Ntfy { topic_url, token } => {
+                // Publishing as JSON needs the server root and the topic in the body.
+                let (server, topic) = topic_url.trim_end_matches('/').rsplit_once('/').unwrap_or((topic_url, ""));
+                let mut body = json!({
+                    "topic": topic,
+                    "title": notification.title,
+                    "message": notification.body(),
+                    "priority": notification.priority.ntfy(),
+                    "tags": notification.tags,
+                });
+                if let Some(url) = &notification.click_url {
+                    body["click"] = json!(url);
+                }
+                let request = self.http.post(server).json(&body);
+                match token {
+                    Some(token) => request.bearer_auth(token),
+                    None => request,
+                }
+            }
+            PushProvider::Pushover { app_token, user_key } => {
+                let mut form = vec![
+                    ("token", app_token.clone()),
+                    ("user", user_key.clone()),
+                    ("title", notification.title.clone()),
+                    ("message", notification.body()),
+                    ("priority", notification.priority.pushover().to_string()),
+                ];
+                if let Some(url) = &notification.click_url {
+                    form.push(("url", url.clone()));
+                }
+                self.http.post(PUSHOVER_URL).form(&form)
+            }
+            PushProvider::Relay { url, token } => {
+                let request = self.http.post(url).json(notification);
+                match token {
+                    Some(token) => request.bearer_auth(token),
+                    None => request,
+                }
+            }
+        };
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            bail!("Push delivery failed: HTTP {}", response.status());
+        }
+        info!("[Push] Sent \"{}\"", notification.title);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ChannelAdapter for PushNotifier {
+    fn name(&self) -> &str {
+        "push"
+    }
+
+    /// Outbound only: there is nothing to listen to.
+    async fn start(&self, _supervisor_tx: mpsc::Sender<Message>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ApprovalNotifier for PushNotifier {
+    async fn deliver(&self, request: &PendingApproval) -> Result<()> {
+        let notification = PushNotification {
+            title: format!("Approval needed: {}", request.tool),
+            body: request.prompt_text(),
+            priority: PushPriority::High,
+            tags: vec!["warning".to_string()],
+            ..Default::default()
+        };
+        self.send(&notification).await
+    }
+}
+
+#[async_trait]
+impl PushSink for PushNotifier {
+    async fn push(&self, title: &str, body: &str) -> Result<()> {
+        self.send(&PushNotification::new(title, body)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_bodies_are_truncated_and_priorities_mapped() {
+        let notification = PushNotification::new("Digest", "x".repeat(1500)).with_priority(PushPriority::Urgent);
+        let body = notification.body();
+        assert_eq!(body.chars().count(), MAX_BODY_CHARS);
+        assert!(body.ends_with('…'));
+        assert_eq!((notification.priority.ntfy(), notification.priority.pushover()), (5, 1));
+        assert_eq!(PushNotification::new("t", "short").body(), "short");
+    }
+}
@@ -4,23 +4,34 @@
 /// Slack Web API (`chat.postMessage`).
 ///
 /// Required env vars:
-///   SLACK_SIGNING_SECRET  — used to verify X-Slack-Signature HMAC
+///   SLACK_SIGNING_SECRET  — used to verify X-Slack-Signature HMAC (see `webhook_verify`)
 ///   SLACK_BOT_TOKEN       — Bot User OAuth Token (xoxb-...)
 ///   SLACK_WEBHOOK_PATH    — path to mount the webhook (default: /webhooks/slack)
+use crate::approval_relay::approval_reply;
+use crate::artifact_links::ArtifactLink;
+use crate::dm_gate::{DmDecision, DmGate};
+use crate::media_upload::MediaChannel;
+use crate::outbound::OutboundMessage;
+use crate::stream_edit::EditableChannel;
+use crate::webhook_verify::{verified, SignatureScheme, WebhookVerifier};
 use crate::ChannelAdapter;
+use markdown::SLACK_MAX_BLOCKS;
 use anyhow::Result;
 use async_trait::async_trait;
 use axum::{
     body::Bytes,
-    extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    extract::State,
+    http::StatusCode,
     response::IntoResponse,
     routing::post,
     Router,
 };
-use clawforge_core::{AuditEventPayload, Event, EventKind, Message};
+use clawforge_core::{AuditEventPayload, Event, EventKind, Message, OutboundMedia};
+use clawforge_security::ApprovalBroker;
+use infra::AdapterReporter;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 use uuid::Uuid;
@@ -42,9 +53,24 @@ pub struct SlackConfig {
 
 #[derive(Clone)]
 struct AppState {
-    config: SlackConfig,
     supervisor_tx: mpsc::Sender<Message>,
     http_client: Client,
+    bot_token: String,
+    dm_gate: Option<Arc<DmGate>>,
+    approvals: Option<Arc<ApprovalBroker>>,
+}
+
+impl AppState {
+    async fn post_text(&self, channel: &str, text: &str) -> Result<()> {
+        let body = SlackPostMessage { channel, text, thread_ts: None, blocks: &[] };
+        self.http_client
+            .post("https://slack.com/api/chat.postMessage")
+            .bearer_auth(&self.bot_token)
+            .json(&body)
+            .send()
+            .await?;
+        Ok(())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -77,6 +103,8 @@ struct SlackEvent {
     bot_id: Option<String>,
     /// Thread timestamp for threading replies.
     thread_ts: Option<String>,
+    /// `im` for direct messages.
+    channel_type: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -85,6 +113,33 @@ struct SlackPostMessage<'a> {
     text: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     thread_ts: Option<&'a str>,
+    /// Block Kit layout; `text` is then the notification fallback.
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    blocks: &'a [serde_json::Value],
+}
+
+#[derive(Serialize)]
+struct SlackUpdateMessage<'a> {
+    channel: &'a str,
+    ts: &'a str,
+    text: &'a str,
+}
+
+/// `chat.*` Web API reply; errors come back as 200 with `ok: false`.
+#[derive(Deserialize)]
+struct SlackApiResponse {
+    ok: bool,
+    ts: Option<String>,
+    error: Option<String>,
+}
+
+/// Reply to `files.getUploadURLExternal`.
+#[derive(Deserialize, Debug)]
+struct SlackUploadUrl {
+    ok: bool,
+    upload_url: Option<String>,
+    file_id: Option<String>,
+    error: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -95,6 +150,9 @@ pub struct SlackAdapter {
     config: SlackConfig,
     supervisor_tx: mpsc::Sender<Message>,
     http_client: Client,
+    status: Option<AdapterReporter>,
+    dm_gate: Option<Arc<DmGate>>,
+    approvals: Option<Arc<ApprovalBroker>>,
 }
 
 impl SlackAdapter {
@@ -103,18 +161,47 @@ impl SlackAdapter {
             config,
             supervisor_tx,
             http_client: Client::new(),
+            status: None,
+            dm_gate: None,
+            approvals: None,
         }
     }
 
+    /// Resolve pending approvals from `/approve` replies instead of passing
+    /// them to the agent.
+    pub fn with_approvals(mut self, broker: Arc<ApprovalBroker>) -> Self {
+        self.approvals = Some(broker);
+        self
+    }
+
+    /// Pass direct messages through `gate` before they reach the agent.
+    pub fn with_dm_gate(mut self, gate: Arc<DmGate>) -> Self {
+        self.dm_gate = Some(gate);
+        self
+    }
+
+    /// Report outbound sends to the adapter status registry.
+    pub fn with_status(mut self, status: AdapterReporter) -> Self {
+        self.status = Some(status);
+        self
+    }
+
     pub fn build_router(&self) -> Router {
         let state = AppState {
-            config: self.config.clone(),
             supervisor_tx: self.supervisor_tx.clone(),
             http_client: self.http_client.clone(),
+            bot_token: self.config.bot_token.clone(),
+            dm_gate: self.dm_gate.clone(),
+            approvals: self.approvals.clone(),
         };
-        Router::new()
+        let verifier = WebhookVerifier::new(
+            "slack",
+            SignatureScheme::Slack { signing_secret: self.config.signing_secret.clone() },
+        );
+        let router = Router::new()
             .route(&self.config.webhook_path, post(handle_slack_event))
-            .with_state(state)
+            .with_state(state);
+        verified(router, verifier)
     }
 }
 
@@ -122,18 +209,12 @@ impl SlackAdapter {
 // Webhook handler
 // ---------------------------------------------------------------------------
 
+/// The signature has already been checked by the `webhook_verify` layer.
 async fn handle_slack_event(
     State(state): State<AppState>,
-    headers: HeaderMap,
     body: Bytes,
 ) -> impl IntoResponse {
-    // 1. Verify Slack signature (HMAC-SHA256 over timestamp + body)
-    if !verify_slack_signature(&headers, &body, &state.config.signing_secret) {
-        warn!("[Slack] Invalid signature — rejecting webhook");
-        return (StatusCode::UNAUTHORIZED, "invalid_signature").into_response();
-    }
-
-    // 2. Parse JSON
+    // 1. Parse JSON
     let envelope: SlackEnvelope = match serde_json::from_slice(&body) {
         Ok(e) => e,
         Err(err) => {
@@ -142,14 +223,14 @@ async fn handle_slack_event(
         }
     };
 
-    // 3. URL-verification challenge (required at initial setup)
+    // 2. URL-verification challenge (required at initial setup)
     if envelope.event_type == "url_verification" {
         if let Some(challenge) = envelope.challenge {
             return (StatusCode::OK, challenge).into_response();
         }
     }
 
-    // 4. Handle event callbacks
+    // 3. Handle event callbacks
     if envelope.event_type != "event_callback" {
         return (StatusCode::OK, "ignored").into_response();
     }
@@ -158,7 +239,7 @@ async fn handle_slack_event(
         return (StatusCode::OK, "no_event").into_response();
     };
 
-    // 5. Only handle real user messages (message type, no bot_id)
+    // 4. Only handle real user messages (message type, no bot_id)
     if slack_event.event_type != "message" || slack_event.bot_id.is_some() {
         return (StatusCode::OK, "ignored").into_response();
     }
@@ -171,6 +252,29 @@ async fn handle_slack_event(
     let user = slack_event.user.unwrap_or_else(|| "unknown_user".into());
     let ts = slack_event.ts.unwrap_or_default();
 
+    if let (Some(gate), Some("im")) = (&state.dm_gate, slack_event.channel_type.as_deref()) {
+        match gate.check(&user, &text) {
+            DmDecision::Deliver => {}
+            DmDecision::Reply(reply) => {
+                if let Err(e) = state.post_text(&channel, &reply).await {
+                    error!("[Slack] Failed to answer {} at the DM gate: {}", user, e);
+                }
+                return (StatusCode::OK, "gated").into_response();
+            }
+            DmDecision::Drop => return (StatusCode::OK, "gated").into_response(),
+        }
+    }
+
+    if let Some(broker) = &state.approvals {
+        if let Some(reply) = approval_reply(broker, "slack", &channel, &user, &text).await {
+            info!("[Slack] Approval reply from {} in {}", user, channel);
+            if let Err(e) = state.post_text(&channel, reply).await {
+                error!("[Slack] Failed to confirm approval to {}: {}", user, e);
+            }
+            return (StatusCode::OK, "approval").into_response();
+        }
+    }
+
     info!("[Slack] Message from {} in {}: {}", user, channel, text);
 
     let event = Event::new(
@@ -196,36 +300,6 @@ async fn handle_slack_event(
     (StatusCode::OK, "ok").into_response()
 }
 
-/// Verify the `X-Slack-Signature` header using HMAC-SHA256.
-fn verify_slack_signature(headers: &HeaderMap, body: &[u8], signing_secret: &str) -> bool {
-    use hmac::{Hmac, Mac};
-    use sha2::Sha256;
-
-    let sig = match headers
-        .get("x-slack-signature")
-        .and_then(|v| v.to_str().ok())
-    {
-        Some(s) => s.to_owned(),
-        None => return false,
-    };
-    let ts = match headers
-        .get("x-slack-request-timestamp")
-        .and_then(|v| v.to_str().ok())
-    {
-        Some(s) => s.to_owned(),
-        None => return false,
-    };
-
-    let base = format!("v0:{}:{}", ts, std::str::from_utf8(body).unwrap_or(""));
-    let mut mac = match Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes()) {
-        Ok(m) => m,
-        Err(_) => return false,
-    };
-    mac.update(base.as_bytes());
-    let computed = format!("v0={}", hex::encode(mac.finalize().into_bytes()));
-    computed == sig
-}
-
 // ---------------------------------------------------------------------------
 // ChannelAdapter impl
 // ---------------------------------------------------------------------------
@@ -240,28 +314,147 @@ impl ChannelAdapter for SlackAdapter {
     }
 }
 
+#[async_trait]
+impl EditableChannel for SlackAdapter {
+    async fn post(&self, chat_id: &str, text: &str) -> Result<String> {
+        let body = SlackPostMessage { channel: chat_id, text, thread_ts: None, blocks: &[] };
+        let reply = self.call("chat.postMessage", &body).await?;
+        reply.ts.ok_or_else(|| anyhow::anyhow!("Slack chat.postMessage returned no ts"))
+    }
+
+    async fn edit(&self, chat_id: &str, message_id: &str, text: &str) -> Result<()> {
+        let body = SlackUpdateMessage { channel: chat_id, ts: message_id, text };
+        self.call("chat.update", &body).await?;
+        Ok(())
+    }
+}
+#[async_trait]
+impl MediaChannel for SlackAdapter {
+    async fn send_media(&self, chat_id: &str, media: &OutboundMedia, bytes: Vec<u8>) -> Result<()> {
+        SlackAdapter::send_media(self, chat_id, media, bytes).await
+    }
+}
+
+
 impl SlackAdapter {
-    pub async fn send_message(&self, channel: &str, text: &str) -> anyhow::Result<()> {
-        let url = "https://slack.com/api/chat.postMessage";
-        let body = SlackPostMessage {
-            channel,
-            text,
-            thread_ts: None,
-        };
-        let res = self
+    async fn call(&self, method: &str, body: &impl Serialize) -> Result<SlackApiResponse> {
+        let reply = self.post_json(method, body).await?;
+        if !reply.ok {
+            anyhow::bail!("Slack {} failed: {}", method, reply.error.as_deref().unwrap_or("unknown error"));
+        }
+        self.count_outbound();
+        Ok(reply)
+    }
+
+    /// Call a Web API method and return its reply, `ok` or not.
+    async fn post_json(&self, method: &str, body: &impl Serialize) -> Result<SlackApiResponse> {
+        Ok(self
             .http_client
-            .post(url)
+            .post(format!("https://slack.com/api/{}", method))
             .bearer_auth(&self.config.bot_token)
-            .json(&body)
+            .json(body)
             .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    fn count_outbound(&self) {
+        if let Some(status) = &self.status {
+            status.outbound();
+        }
+    }
+
+    /// Announce an artifact as a linked title, with an image block for its preview.
+    pub async fn send_artifact(&self, channel: &str, artifact: &ArtifactLink) -> Result<()> {
+        let body = serde_json::json!({
+            "channel": channel,
+            "text": artifact.text(),
+            "blocks": artifact.slack_blocks(),
+        });
+        self.call("chat.postMessage", &body).await?;
+        Ok(())
+    }
+
+    /// Upload a generated attachment to a channel: reserve an upload URL,
+    /// send the bytes there, then share the file with its caption.
+    pub async fn send_media(&self, channel: &str, media: &OutboundMedia, bytes: Vec<u8>) -> Result<()> {
+        let length = bytes.len().to_string();
+        let reserved: SlackUploadUrl = self
+            .http_client
+            .post("https://slack.com/api/files.getUploadURLExternal")
+            .bearer_auth(&self.config.bot_token)
+            .form(&[("filename", media.file_name.as_str()), ("length", length.as_str())])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
             .await?;
+        let (Some(upload_url), Some(file_id), true) = (reserved.upload_url, reserved.file_id, reserved.ok) else {
+            anyhow::bail!(
+                "Slack files.getUploadURLExternal failed: {}",
+                reserved.error.as_deref().unwrap_or("no upload URL")
+            );
+        };
+        self.http_client
+            .post(&upload_url)
+            .header(reqwest::header::CONTENT_TYPE, media.mime_type.as_str())
+            .body(bytes)
+            .send()
+            .await?
+            .error_for_status()?;
+        let mut body = serde_json::json!({
+            "channel_id": channel,
+            "files": [{ "id": file_id, "title": media.file_name }],
+        });
+        if let Some(caption) = &media.caption {
+            body["initial_comment"] = caption.clone().into();
+        }
+        self.call("files.completeUploadExternal", &body).await?;
+        info!("[Slack] Uploaded {} to {}", media.file_name, channel);
+        Ok(())
+    }
 
-        if !res.status().is_success() {
-            let err = res.text().await.unwrap_or_default();
+    /// Send a Markdown reply as Block Kit, split into messages of at most
+    /// `SLACK_MAX_BLOCKS` blocks. A message whose blocks Slack rejects is
+    /// resent as text.
+    pub async fn send_message(&self, channel: &str, text: &str) -> anyhow::Result<()> {
+        let message = OutboundMessage::render("slack", text);
+        if message.blocks.len() <= SLACK_MAX_BLOCKS {
+            self.post_blocks(channel, &message.plain, &message.blocks).await?;
+        } else {
+            for blocks in message.blocks.chunks(SLACK_MAX_BLOCKS) {
+                self.post_blocks(channel, &blocks_text(blocks), blocks).await?;
+            }
+        }
+        info!("[Slack] Sent message to channel {}", channel);
+        Ok(())
+    }
+
+    /// Post one message; `text` is its notification fallback, and the whole
+    /// message when Slack answers `invalid_blocks`.
+    async fn post_blocks(&self, channel: &str, text: &str, blocks: &[serde_json::Value]) -> Result<()> {
+        let body = SlackPostMessage { channel, text, thread_ts: None, blocks };
+        let reply = self.post_json("chat.postMessage", &body).await?;
+        if reply.ok {
+            self.count_outbound();
+            return Ok(());
+        }
+        if blocks.is_empty() || reply.error.as_deref() != Some("invalid_blocks") {
+            let err = reply.error.unwrap_or_else(|| "unknown error".to_string());
             error!("[Slack] chat.postMessage failed: {}", err);
             anyhow::bail!("Slack send failed: {}", err);
         }
-        info!("[Slack] Sent message to channel {}", channel);
+        warn!("[Slack] Blocks rejected in {}, resending as text", channel);
+        let body = SlackPostMessage { channel, text, thread_ts: None, blocks: &[] };
+        self.call("chat.postMessage", &body).await?;
         Ok(())
     }
 }
+
+/// The text of a message's blocks, for its notification fallback.
+fn blocks_text(blocks: &[serde_json::Value]) -> String {
+    let texts: Vec<&str> = blocks.iter().filter_map(|block| block["text"]["text"].as_str()).collect();
+    texts.join("\n\n")
+}
@@ -0,0 +1,192 @@
+//! Edit-in-place streaming replies.
+//!
+//! On platforms that can edit a sent message (Telegram, Discord, Slack) the
+//! reply starts as a placeholder that is rewritten as tokens arrive. Edits
+//! are spaced to the platform's edit budget; text past the per-message limit
+//! continues in follow-up messages, which are then edited in turn.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Shown at the end of the text while the reply is still streaming.
+const CURSOR: &str = " ▍";
+
+/// A channel that can post a message and later replace its text.
+#[async_trait]
+pub trait EditableChannel: Send + Sync {
+    /// Post `text` to `chat_id`, returning the platform message id.
+    async fn post(&self, chat_id: &str, text: &str) -> Result<String>;
+
+    /// Replace the text of a message posted earlier.
+    async fn edit(&self, chat_id: &str, message_id: &str, text: &str) -> Result<()>;
+}
+
+/// How often and how much a platform lets us edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditBudget {
+    /// Minimum gap between edits of one reply.
+    pub min_interval: Duration,
+    /// Longest text a single message may hold, in characters.
+    pub max_chars: usize,
+}
+
+impl EditBudget {
+    /// Budget for a channel that supports edit-in-place, `None` otherwise.
+    pub fn for_channel(channel: &str) -> Option<Self> {
+        let (interval_ms, max_chars) = match channel {
+            // ~20 edits/minute per group chat before 429s.
+            "telegram" => (3_000, 4_096),
+            // 5 edits per 5 seconds per channel.
+            "discord" => (1_200, 2_000),
+            // chat.update is Tier 3: ~50 calls/minute.
+            "slack" => (1_500, 4_000),
+            _ => return None,
+        };
+        Some(Self { min_interval: Duration::from_millis(interval_ms), max_chars })
+    }
+}
+
+/// One reply being streamed into a chat.
+pub struct StreamingReply<'a> {
+    channel: &'a dyn EditableChannel,
+    chat_id: String,
+    budget: EditBudget,
+    text: String,
+    /// Message ids and the text each currently shows.
+    messages: Vec<(String, String)>,
+    last_edit: Instant,
+}
+
+impl<'a> StreamingReply<'a> {
+    /// Post `placeholder` and return the reply that will replace it.
+    pub async fn start(channel: &'a dyn EditableChannel, chat_id: &str, budget: EditBudget, placeholder: &str) -> Result<Self> {
+        let id = channel.post(chat_id, placeholder).await?;
+        Ok(Self {
+            channel,
+            chat_id: chat_id.to_string(),
+            budget,
+            text: String::new(),
+            messages: vec![(id, placeholder.to_string())],
+            last_edit: Instant::now(),
+        })
+    }
+
+    /// Append a token delta, editing the chat if the budget allows.
+    pub async fn push(&mut self, delta: &str) {
+        self.text.push_str(delta);
+        if self.last_edit.elapsed() < self.budget.min_interval {
+            return;
+        }
+        // A failed interim edit only delays the update; `finish` retries.
+        if let Err(e) = self.render(true).await {
+            warn!(chat_id = %self.chat_id, error = %e, "Streaming edit failed");
+        }
+    }
+
+    /// Write the final text (waiting out the edit budget first) and return it.
+    pub async fn finish(mut self) -> Result<String> {
+        if let Some(wait) = self.budget.min_interval.checked_sub(self.last_edit.elapsed()) {
+            tokio::time::sleep(wait).await;
+        }
+        self.render(false).await?;
+        Ok(self.text)
+    }
+
+    async fn render(&mut self, streaming: bool) -> Result<()> {
+        let cursor = if streaming { CURSOR } else { "" };
+        let limit = self.budget.max_chars.saturating_sub(cursor.chars().count()).max(1);
+        let mut chunks = split_chars(self.text.trim_end(), limit);
+        if let Some(last) = chunks.last_mut() {
+            last.push_str(cursor);
+        }
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            match self.messages.get_mut(i) {
+                Some((_, shown)) if *shown == chunk => {}
+                Some((id, shown)) => {
+                    self.channel.edit(&self.chat_id, id, &chunk).await?;
+                    *shown = chunk;
+                }
+                None => {
+                    let id = self.channel.post(&self.chat_id, &chunk).await?;
+                    self.messages.push((id, chunk));
+                }
+            }
+        }
+        self.last_edit = Instant::now();
+        Ok(())
+    }
+}
+
+/// Stream `deltas` into `chat_id` until the sender closes; returns the full text.
+pub async fn stream_reply(
+    channel: &dyn EditableChannel,
+    chat_id: &str,
+    budget: EditBudget,
+    mut deltas: mpsc::Receiver<String>,
+) -> Result<String> {
+    let mut reply = StreamingReply::start(channel, chat_id, budget, "…").await?;
+    while let Some(delta) = deltas.recv().await {
+        reply.push(&delta).await;
+    }
+    reply.finish().await
+}
+
+/// Split into pieces of at most `limit` characters, preferring line breaks.
+fn split_chars(text: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while rest.chars().count() > limit {
+        let cut = rest.char_indices().nth(limit).map(|(i, _)| i).unwrap_or(rest.len());
+        let at = rest[..cut].rfind('\n').filter(|i| *i > 0).unwrap_or(cut);
+        chunks.push(rest[..at].to_string());
+        rest = rest[at..].trim_start_matches('\n');
+    }
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest.to_string());
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl EditableChannel for Recorder {
+        async fn post(&self, _chat_id: &str, text: &str) -> Result<String> {
+            let mut ops = self.0.lock().unwrap();
+            ops.push(format!("post {}", text));
+            Ok(ops.len().to_string())
+        }
+
+        async fn edit(&self, _chat_id: &str, message_id: &str, text: &str) -> Result<()> {
+            self.0.lock().unwrap().push(format!("edit {} {}", message_id, text));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn edits_are_budgeted_and_overflow_continues_in_new_messages() {
+        let chat = Recorder::default();
+        let budget = EditBudget { min_interval: Duration::from_millis(200), max_chars: 12 };
+        let mut reply = StreamingReply::start(&chat, "c1", budget, "…").await.unwrap();
+        reply.push("Hello").await;
+        reply.push(" world").await;
+        // Still inside the first interval: nothing but the placeholder.
+        assert_eq!(*chat.0.lock().unwrap(), ["post …"]);
+
+        reply.push("\nand more").await;
+        assert_eq!(reply.finish().await.unwrap(), "Hello world\nand more");
+        assert_eq!(*chat.0.lock().unwrap(), ["post …", "edit 1 Hello world", "post and more"]);
+
+        assert_eq!(EditBudget::for_channel("irc"), None);
+    }
+}
@@ -1,66 +1,204 @@
+use crate::approval_buttons::{decode_callback, resolved_text, ApprovalPrompt};
+use crate::approval_relay::ApprovalButtons;
+use crate::artifact_links::ArtifactLink;
+use crate::chat_agent::{chat_session, ChatAgent};
+use crate::code_actions::{decode_run_callback, CodeRunner};
+use crate::dm_gate::{DmDecision, DmGate};
+use crate::media_upload::MediaChannel;
+use crate::outbound::OutboundMessage;
+use crate::stream_edit::EditableChannel;
+use crate::telegram_inline::TelegramInline;
 use crate::ChannelAdapter;
 use async_trait::async_trait;
+use clawforge_sandbox::ApprovalResponse;
 use teloxide::prelude::*;
+use teloxide::types::{InputFile, MessageId, ParseMode};
+use teloxide::{ApiError, RequestError};
+use std::sync::Arc;
 use tokio::sync::mpsc;
-use tracing::{error, info};
-use clawforge_core::{Message, EventKind, Event};
-use uuid::Uuid;
+use tracing::{error, info, warn};
+use clawforge_core::{Message, OutboundMedia, OutboundMediaKind};
+
+/// Where decoded approval button presses are forwarded (usually the approval socket).
+type ApprovalSink = Option<mpsc::Sender<ApprovalResponse>>;
+
+/// Pairing gate applied to private chats, if configured.
+type DmGateDep = Option<Arc<DmGate>>;
+
+/// Runs approved code blocks when a Run button is pressed, if configured.
+type CodeRunnerDep = Option<Arc<CodeRunner>>;
+
+/// Answers inbound messages, if configured.
+type ChatAgentDep = Option<Arc<dyn ChatAgent>>;
 
 pub struct TelegramAdapter {
     bot: Bot,
+    approval_tx: ApprovalSink,
+    dm_gate: DmGateDep,
+    agent: ChatAgentDep,
+    code_runner: CodeRunnerDep,
 }
 
 impl TelegramAdapter {
     pub fn new(token: String) -> Self {
         Self {
             bot: Bot::new(token),
+            approval_tx: None,
+            dm_gate: None,
+            agent: None,
+            code_runner: None,
         }
     }
+
+    /// Answer delivered messages with `agent`'s replies. Without one,
+    /// inbound messages are dropped.
+    pub fn with_agent(mut self, agent: Arc<dyn ChatAgent>) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+
+    /// Offer Run buttons on runnable code blocks and execute approved ones.
+    pub fn with_code_runner(mut self, runner: Arc<CodeRunner>) -> Self {
+        self.code_runner = Some(runner);
+        self
+    }
+
+    /// Gate private chats through the DM pairing flow.
+    pub fn with_dm_gate(mut self, gate: Arc<DmGate>) -> Self {
+        self.dm_gate = Some(gate);
+        self
+    }
+
+    /// Forward inline-keyboard approval decisions to the given channel.
+    /// Only presses from contacts the DM gate knows count, so buttons need
+    /// `with_dm_gate` too.
+    pub fn with_approval_sink(mut self, tx: mpsc::Sender<ApprovalResponse>) -> Self {
+        self.approval_tx = Some(tx);
+        self
+    }
 }
 
 #[async_trait]
 impl ChannelAdapter for TelegramAdapter {
     fn name(&self) -> &str { "telegram" }
 
-    async fn start(&self, supervisor_tx: mpsc::Sender<Message>) -> anyhow::Result<()> {
+    async fn start(&self, _supervisor_tx: mpsc::Sender<Message>) -> anyhow::Result<()> {
         info!("Starting Telegram adapter");
-        
-        // This is a simplified listener. A full version would handle commands, media, etc.
+
         let bot = self.bot.clone();
-        let tx = supervisor_tx.clone();
-        
-        let handler = Update::filter_message().endpoint(
-            |bot: Bot, msg: teloxide::types::Message, tx: mpsc::Sender<Message>| async move {
-                if let Some(text) = msg.text() {
-                    let chat_id = msg.chat.id.to_string();
-                    info!("Received message from Telegram chat {}: {}", chat_id, text);
-                    
-                    // We need to map this to a Run. For simplicity in this iteration,
-                    // we'll emit an audit event that the Supervisor could use to auto-create a Run.
-                    // In a production app, we'd lookup active sessions/runs for this channel+chat_id.
-                    
-                    let event = Event::new(
-                        Uuid::new_v4(), // Dummy Run ID for now
-                        Uuid::new_v4(), // Dummy Agent ID
-                        EventKind::RunStarted,
-                        serde_json::json!({
-                            "source": "telegram",
-                            "chat_id": chat_id,
-                            "text": text
-                        })
-                    );
-                    
-                    let _ = tx.send(Message::AuditEvent(clawforge_core::AuditEventPayload { event })).await;
-                    
-                    // Echo back for testing
-                    let _ = bot.send_message(msg.chat.id, format!("Received: {}", text)).await;
+        let approvals: ApprovalSink = self.approval_tx.clone();
+        let dm_gate: DmGateDep = self.dm_gate.clone();
+        let agent: ChatAgentDep = self.agent.clone();
+        let code_runner: CodeRunnerDep = self.code_runner.clone();
+
+        let messages = Update::filter_message().endpoint(
+            |bot: Bot, msg: teloxide::types::Message, dm_gate: DmGateDep, agent: ChatAgentDep, code_runner: CodeRunnerDep| async move {
+                let Some(text) = msg.text() else {
+                    return respond(());
+                };
+                let chat_id = msg.chat.id.to_string();
+
+                if let (Some(gate), true) = (&dm_gate, msg.chat.is_private()) {
+                    match gate.check(&chat_id, text) {
+                        DmDecision::Deliver => {}
+                        DmDecision::Reply(reply) => {
+                            let _ = bot.send_message(msg.chat.id, reply).await;
+                            return respond(());
+                        }
+                        DmDecision::Drop => return respond(()),
+                    }
+                }
+                let Some(agent) = agent else {
+                    warn!("Telegram message from chat {} dropped: no agent answers this channel", chat_id);
+                    return respond(());
+                };
+                info!("Received message from Telegram chat {}", chat_id);
+
+                let sender = msg.from.as_ref().map_or_else(|| chat_id.clone(), |user| user.id.to_string());
+                let text = text.to_string();
+                // A run can take minutes; don't hold up the dispatcher.
+                tokio::spawn(async move {
+                    let reply = match agent.reply("telegram", &chat_id, &sender, &text).await {
+                        Ok(reply) => reply,
+                        Err(e) => {
+                            error!("Telegram run for chat {} failed: {:#}", chat_id, e);
+                            format!("Sorry, that didn't work: {:#}", e)
+                        }
+                    };
+                    if let Err(e) = send_reply(&bot, code_runner.as_deref(), &chat_id, &reply).await {
+                        error!("Failed to send Telegram reply to {}: {:#}", chat_id, e);
+                    }
+                });
+                respond(())
+            }
+        );
+
+        let callbacks = Update::filter_callback_query().endpoint(
+            |bot: Bot, q: CallbackQuery, approvals: ApprovalSink, dm_gate: DmGateDep, code_runner: CodeRunnerDep| async move {
+                // Buttons are visible to everyone in the chat; only known
+                // contacts may press them.
+                if !dm_gate.as_ref().is_some_and(|gate| gate.may_approve(&q.from.id.to_string())) {
+                    warn!("Telegram button press from {} refused: not an approver", q.from.id);
+                    let _ = bot.answer_callback_query(q.id).text("You are not allowed to approve this").await;
+                    return respond(());
+                }
+                if let (Some(id), Some(runner)) = (q.data.as_deref().and_then(decode_run_callback), &code_runner) {
+                    let _ = bot.answer_callback_query(q.id).text("Waiting for approval").await;
+                    let Some(chat) = q.message.as_ref().map(|m| m.chat().id) else {
+                        return respond(());
+                    };
+                    // Approval can take minutes; don't hold up the dispatcher.
+                    let runner = runner.clone();
+                    tokio::spawn(async move {
+                        let reply = match runner.run(&id, &chat.to_string()).await {
+                            Ok(result) => CodeRunner::format_result(&result),
+                            Err(e) => format!("Run failed: {:#}", e),
+                        };
+                        let message = OutboundMessage::render("telegram", &reply);
+                        if let Err(e) = bot.send_message(chat, &message.text).parse_mode(ParseMode::MarkdownV2).await {
+                            if is_parse_error(&e) {
+                                let _ = bot.send_message(chat, message.plain).await;
+                            }
+                        }
+                    });
+                    return respond(());
+                }
+                let Some((choice, id)) = q.data.as_deref().and_then(decode_callback) else {
+                    return respond(());
+                };
+                let who = q.from.username.clone().unwrap_or_else(|| q.from.id.to_string());
+                info!("Telegram approval {} for {} from {}", choice.verdict(), id, who);
+
+                let delivered = match &approvals {
+                    Some(approval_tx) => {
+                        let response = ApprovalResponse { id: id.clone(), verdict: choice.verdict().into() };
+                        approval_tx.send(response).await.is_ok()
+                    }
+                    None => false,
+                };
+
+                if !delivered {
+                    warn!("No approval sink accepted Telegram decision for {}", id);
+                    let _ = bot.answer_callback_query(q.id).text("Approval could not be recorded").await;
+                    return respond(());
+                }
+
+                let _ = bot.answer_callback_query(q.id).text(choice.label()).await;
+                // Replace the keyboard with the outcome so the buttons can't be pressed twice.
+                if let Some(prompt_msg) = &q.message {
+                    let text = resolved_text(choice, &id, &who);
+                    if let Err(e) = bot.edit_message_text(prompt_msg.chat().id, prompt_msg.id(), text).await {
+                        error!("Failed to update Telegram approval message: {}", e);
+                    }
                 }
                 respond(())
             }
         );
 
+        let handler = dptree::entry().branch(messages).branch(callbacks);
+
         Dispatcher::builder(bot.clone(), handler)
-            .dependencies(dptree::deps![tx])
+            .dependencies(dptree::deps![approvals, dm_gate, agent, code_runner])
             .enable_ctrlc_handler()
             .build()
             .dispatch()
@@ -71,10 +209,143 @@ impl ChannelAdapter for TelegramAdapter {
 
 }
 
+#[async_trait]
+impl EditableChannel for TelegramAdapter {
+    async fn post(&self, chat_id: &str, text: &str) -> anyhow::Result<String> {
+        let sent = self.bot.send_message(ChatId(chat_id.parse()?), text).await?;
+        Ok(sent.id.0.to_string())
+    }
+
+    async fn edit(&self, chat_id: &str, message_id: &str, text: &str) -> anyhow::Result<()> {
+        self.bot
+            .edit_message_text(ChatId(chat_id.parse()?), MessageId(message_id.parse()?), text)
+            .await?;
+        Ok(())
+    }
+}
+#[async_trait]
+impl ApprovalButtons for TelegramAdapter {
+    async fn send_approval_prompt(&self, chat_id: &str, prompt: &ApprovalPrompt) -> anyhow::Result<()> {
+        TelegramAdapter::send_approval_prompt(self, chat_id, prompt).await
+    }
+}
+
+#[async_trait]
+impl MediaChannel for TelegramAdapter {
+    async fn send_media(&self, chat_id: &str, media: &OutboundMedia, bytes: Vec<u8>) -> anyhow::Result<()> {
+        TelegramAdapter::send_media(self, chat_id, media, bytes).await
+    }
+}
+
+
 impl TelegramAdapter {
+    /// Send a Markdown reply as MarkdownV2, falling back to plain text if
+    /// Telegram still rejects the entities.
     pub async fn send_message(&self, chat_id: &str, text: &str) -> anyhow::Result<()> {
+        let chat_id = ChatId(chat_id.parse()?);
+        let message = OutboundMessage::render("telegram", text);
+        match self.bot.send_message(chat_id, &message.text).parse_mode(ParseMode::MarkdownV2).await {
+            Err(e) if is_parse_error(&e) => {
+                warn!("[Telegram] MarkdownV2 rejected ({}), resending as plain text", e);
+                self.bot.send_message(chat_id, message.plain).await?;
+            }
+            sent => {
+                sent?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send a Markdown reply with a Run button under each runnable code
+    /// block. Without a code runner this is `send_message`.
+    pub async fn send_reply(&self, chat_id: &str, text: &str) -> anyhow::Result<()> {
+        send_reply(&self.bot, self.code_runner.as_deref(), chat_id, text).await
+    }
+
+    /// Announce an artifact: its preview as a photo when there is one, else a link.
+    pub async fn send_artifact(&self, chat_id: &str, artifact: &ArtifactLink) -> anyhow::Result<()> {
+        let chat = ChatId(chat_id.parse()?);
+        match artifact.preview_url.as_deref().and_then(|url| url.parse().ok()) {
+            Some(preview) => {
+                self.bot.send_photo(chat, InputFile::url(preview)).caption(artifact.text()).await?;
+            }
+            None => {
+                self.bot.send_message(chat, artifact.text()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Upload a generated attachment with the matching Bot API method:
+    /// `sendPhoto`, `sendAudio`, `sendVideo` or `sendDocument`.
+    pub async fn send_media(&self, chat_id: &str, media: &OutboundMedia, bytes: Vec<u8>) -> anyhow::Result<()> {
+        let chat = ChatId(chat_id.parse()?);
+        let file = InputFile::memory(bytes).file_name(media.file_name.clone());
+        let caption = media.caption.clone().unwrap_or_default();
+        match media.kind {
+            OutboundMediaKind::Image => {
+                self.bot.send_photo(chat, file).caption(caption).await?;
+            }
+            OutboundMediaKind::Audio => {
+                self.bot.send_audio(chat, file).caption(caption).await?;
+            }
+            OutboundMediaKind::Video => {
+                self.bot.send_video(chat, file).caption(caption).await?;
+            }
+            OutboundMediaKind::File => {
+                self.bot.send_document(chat, file).caption(caption).await?;
+            }
+        }
+        info!("[Telegram] Uploaded {} to {}", media.file_name, chat_id);
+        Ok(())
+    }
+
+    /// Send an approval request with Allow/Deny inline buttons.
+    pub async fn send_approval_prompt(&self, chat_id: &str, prompt: &ApprovalPrompt) -> anyhow::Result<()> {
         let chat_id: i64 = chat_id.parse()?;
-        self.bot.send_message(ChatId(chat_id), text).await?;
+        let request = self.bot.send_message(ChatId(chat_id), prompt.text());
+        match TelegramInline::build_approval_keyboard(prompt) {
+            Some(keyboard) => request.reply_markup(keyboard).await?,
+            None => request.await?,
+        };
         Ok(())
     }
 }
+
+/// Send a Markdown reply to `chat_id`, with a Run button under each
+/// runnable code block when there is a code runner.
+async fn send_reply(bot: &Bot, code_runner: Option<&CodeRunner>, chat_id: &str, text: &str) -> anyhow::Result<()> {
+    let affordances = match code_runner {
+        Some(runner) => runner.offer(&chat_session("telegram", chat_id), "telegram", chat_id, text).await,
+        None => Vec::new(),
+    };
+    let keyboard = TelegramInline::build_run_keyboard(&affordances);
+    let chat_id = ChatId(chat_id.parse()?);
+    let message = OutboundMessage::render("telegram", text);
+    let request = bot.send_message(chat_id, &message.text).parse_mode(ParseMode::MarkdownV2);
+    let sent = match keyboard.clone() {
+        Some(keyboard) => request.reply_markup(keyboard).await,
+        None => request.await,
+    };
+    match sent {
+        Err(e) if is_parse_error(&e) => {
+            warn!("[Telegram] MarkdownV2 rejected ({}), resending as plain text", e);
+            let request = bot.send_message(chat_id, message.plain);
+            match keyboard {
+                Some(keyboard) => request.reply_markup(keyboard).await?,
+                None => request.await?,
+            };
+        }
+        sent => {
+            sent?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether Telegram refused a message only because its entities didn't
+/// parse. Any other failure, a timeout in particular, may have delivered it,
+/// so resending would post it twice.
+fn is_parse_error(e: &RequestError) -> bool {
+    matches!(e, RequestError::Api(ApiError::CantParseEntities(_)))
+}
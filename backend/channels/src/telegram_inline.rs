@@ -3,8 +3,12 @@
 //! Exposes inline keyboard builder utilities and callback query pipelines.
 
 use anyhow::Result;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
 use tracing::info;
 
+use crate::approval_buttons::{ApprovalPrompt, TELEGRAM_CALLBACK_MAX};
+use crate::code_actions::RunAffordance;
+
 pub struct TelegramInline;
 
 impl TelegramInline {
@@ -14,6 +18,33 @@ impl TelegramInline {
         r#"{"inline_keyboard": [[{"text": "Yes", "callback_data": "confirm_yes"}, {"text": "No", "callback_data": "confirm_no"}]]}"#.into()
     }
 
+    /// Builds the approval keyboard for a pending exec/pairing/tool request.
+    ///
+    /// Returns `None` when the request ID is too long for `callback_data`;
+    /// the prompt text still carries the `/approve` fallback in that case.
+    pub fn build_approval_keyboard(prompt: &ApprovalPrompt) -> Option<InlineKeyboardMarkup> {
+        let row = prompt
+            .choices()
+            .iter()
+            .map(|&choice| {
+                let data = prompt.callback_data(choice);
+                (data.len() <= TELEGRAM_CALLBACK_MAX)
+                    .then(|| InlineKeyboardButton::callback(choice.label(), data))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(InlineKeyboardMarkup::new(vec![row]))
+    }
+
+    /// One Run button per runnable code block, one row each.
+    pub fn build_run_keyboard(affordances: &[RunAffordance]) -> Option<InlineKeyboardMarkup> {
+        let rows: Vec<Vec<InlineKeyboardButton>> = affordances
+            .iter()
+            .filter(|a| a.callback_data().len() <= TELEGRAM_CALLBACK_MAX)
+            .map(|a| vec![InlineKeyboardButton::callback(a.label(), a.callback_data())])
+            .collect();
+        (!rows.is_empty()).then(|| InlineKeyboardMarkup::new(rows))
+    }
+
     /// Processes a callback query generated by a user clicking an inline button.
     pub async fn handle_callback_query(callback_id: &str, data: &str, user_id: i64) -> Result<()> {
         info!("Processing callback query {} from {}: {}", callback_id, user_id, data);
@@ -0,0 +1,389 @@
+//! Shared webhook signature verification.
+//!
+//! One verification layer for every adapter router instead of bespoke
+//! per-adapter checks. Supports Slack HMAC, Telegram secret tokens, LINE
+//! signatures, Meta/WhatsApp `X-Hub-Signature-256`, Twilio
+//! `X-Twilio-Signature` and generic HMAC-SHA256.
+//! All comparisons are constant-time, and a replay window rejects stale
+//! timestamps and re-delivered signatures.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+type HmacSha256 = Hmac<Sha256>;
+type HmacSha1 = Hmac<Sha1>;
+
+/// Largest webhook body the verifier will buffer.
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// Default tolerance for timestamped signatures and duplicate deliveries.
+const DEFAULT_REPLAY_WINDOW_SECS: u64 = 300;
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// How the signature is encoded in the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureEncoding {
+    Hex,
+    Base64,
+}
+
+/// Per-channel signature scheme.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "scheme", rename_all = "snake_case")]
+pub enum SignatureScheme {
+    /// `X-Slack-Signature: v0=<hex>` over `v0:<timestamp>:<body>`.
+    Slack { signing_secret: String },
+    /// `X-Telegram-Bot-Api-Secret-Token` set via `setWebhook(secret_token)`.
+    TelegramSecretToken { secret_token: String },
+    /// `x-line-signature: base64(HMAC-SHA256(channel_secret, body))`.
+    Line { channel_secret: String },
+    /// `X-Hub-Signature-256: sha256=<hex>` (Meta / WhatsApp Cloud API).
+    MetaSha256 { app_secret: String },
+    /// `X-Twilio-Signature: base64(HMAC-SHA1(auth_token, url + sorted form
+    /// params))`. `url` is the public webhook URL exactly as configured in Twilio.
+    Twilio { auth_token: String, url: String },
+    /// Any other HMAC-SHA256 header, optionally with a signed timestamp.
+    GenericHmac {
+        secret: String,
+        header: String,
+        #[serde(default)]
+        prefix: Option<String>,
+        encoding: SignatureEncoding,
+        /// When set, the MAC covers `<timestamp>.<body>` and the timestamp is age-checked.
+        #[serde(default)]
+        timestamp_header: Option<String>,
+    },
+}
+
+/// Why a request was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyFailure {
+    MissingSignature,
+    MissingTimestamp,
+    StaleTimestamp,
+    Replayed,
+    InvalidSignature,
+}
+
+impl VerifyFailure {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VerifyFailure::MissingSignature => "missing_signature",
+            VerifyFailure::MissingTimestamp => "missing_timestamp",
+            VerifyFailure::StaleTimestamp => "stale_timestamp",
+            VerifyFailure::Replayed => "replayed_request",
+            VerifyFailure::InvalidSignature => "invalid_signature",
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Verifier
+// ---------------------------------------------------------------------------
+
+/// Verifies inbound webhooks for one channel.
+#[derive(Clone)]
+pub struct WebhookVerifier {
+    channel: String,
+    scheme: SignatureScheme,
+    replay_window: Duration,
+    /// Decoded MACs accepted inside the replay window → first-seen time.
+    seen: Arc<Mutex<HashMap<Vec<u8>, Instant>>>,
+}
+
+impl WebhookVerifier {
+    pub fn new(channel: impl Into<String>, scheme: SignatureScheme) -> Self {
+        Self {
+            channel: channel.into(),
+            scheme,
+            replay_window: Duration::from_secs(DEFAULT_REPLAY_WINDOW_SECS),
+            seen: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn with_replay_window(mut self, window: Duration) -> Self {
+        self.replay_window = window;
+        self
+    }
+
+    /// Check a request's headers and raw body against the configured scheme.
+    pub fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), VerifyFailure> {
+        let mac = match &self.scheme {
+            SignatureScheme::Slack { signing_secret } => {
+                let sig = header(headers, "x-slack-signature").ok_or(VerifyFailure::MissingSignature)?;
+                let ts = header(headers, "x-slack-request-timestamp").ok_or(VerifyFailure::MissingTimestamp)?;
+                self.check_timestamp(ts)?;
+                let hex_sig = sig.strip_prefix("v0=").ok_or(VerifyFailure::InvalidSignature)?;
+                let expected = hex::decode(hex_sig).map_err(|_| VerifyFailure::InvalidSignature)?;
+                let mut signed = format!("v0:{}:", ts).into_bytes();
+                signed.extend_from_slice(body);
+                verify_mac(signing_secret, &signed, &expected)?;
+                expected
+            }
+            SignatureScheme::TelegramSecretToken { secret_token } => {
+                let token = header(headers, "x-telegram-bot-api-secret-token")
+                    .ok_or(VerifyFailure::MissingSignature)?;
+                if !constant_time_eq(token.as_bytes(), secret_token.as_bytes()) {
+                    return Err(VerifyFailure::InvalidSignature);
+                }
+                // The token is static per bot, so there is nothing to de-duplicate.
+                return Ok(());
+            }
+            SignatureScheme::Line { channel_secret } => {
+                let sig = header(headers, "x-line-signature").ok_or(VerifyFailure::MissingSignature)?;
+                let expected = base64::engine::general_purpose::STANDARD
+                    .decode(sig)
+                    .map_err(|_| VerifyFailure::InvalidSignature)?;
+                verify_mac(channel_secret, body, &expected)?;
+                expected
+            }
+            SignatureScheme::MetaSha256 { app_secret } => {
+                let sig = header(headers, "x-hub-signature-256").ok_or(VerifyFailure::MissingSignature)?;
+                let hex_sig = sig.strip_prefix("sha256=").ok_or(VerifyFailure::InvalidSignature)?;
+                let expected = hex::decode(hex_sig).map_err(|_| VerifyFailure::InvalidSignature)?;
+                verify_mac(app_secret, body, &expected)?;
+                expected
+            }
+            SignatureScheme::Twilio { auth_token, url } => {
+                let sig = header(headers, "x-twilio-signature").ok_or(VerifyFailure::MissingSignature)?;
+                let expected = base64::engine::general_purpose::STANDARD
+                    .decode(sig)
+                    .map_err(|_| VerifyFailure::InvalidSignature)?;
+                let mut params: Vec<(String, String)> = form_urlencoded::parse(body).into_owned().collect();
+                params.sort();
+                let mut signed = url.clone();
+                for (name, value) in params {
+                    signed.push_str(&name);
+                    signed.push_str(&value);
+                }
+                let mut mac = HmacSha1::new_from_slice(auth_token.as_bytes()).map_err(|_| VerifyFailure::InvalidSignature)?;
+                mac.update(signed.as_bytes());
+                mac.verify_slice(&expected).map_err(|_| VerifyFailure::InvalidSignature)?;
+                expected
+            }
+            SignatureScheme::GenericHmac { secret, header: name, prefix, encoding, timestamp_header } => {
+                let raw = header(headers, name).ok_or(VerifyFailure::MissingSignature)?;
+                let encoded = match prefix {
+                    Some(p) => raw.strip_prefix(p.as_str()).ok_or(VerifyFailure::InvalidSignature)?,
+                    None => raw,
+                };
+                let expected = match encoding {
+                    SignatureEncoding::Hex => hex::decode(encoded).ok(),
+                    SignatureEncoding::Base64 => base64::engine::general_purpose::STANDARD.decode(encoded).ok(),
+                }
+                .ok_or(VerifyFailure::InvalidSignature)?;
+                let signed = match timestamp_header {
+                    Some(ts_name) => {
+                        let ts = header(headers, ts_name).ok_or(VerifyFailure::MissingTimestamp)?;
+                        self.check_timestamp(ts)?;
+                        let mut signed = format!("{}.", ts).into_bytes();
+                        signed.extend_from_slice(body);
+                        signed
+                    }
+                    None => body.to_vec(),
+                };
+                verify_mac(secret, &signed, &expected)?;
+                expected
+            }
+        };
+        self.check_replay(mac)
+    }
+
+    fn check_timestamp(&self, ts: &str) -> Result<(), VerifyFailure> {
+        let ts: u64 = ts.trim().parse().map_err(|_| VerifyFailure::MissingTimestamp)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        if now.abs_diff(ts) > self.replay_window.as_secs() {
+            return Err(VerifyFailure::StaleTimestamp);
+        }
+        Ok(())
+    }
+
+    /// Reject a verified MAC already accepted inside the replay window.
+    /// Keyed on the decoded bytes, so re-encoding the header (hex case,
+    /// base64 padding) does not get a delivery past it.
+    fn check_replay(&self, mac: Vec<u8>) -> Result<(), VerifyFailure> {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap();
+        seen.retain(|_, first| now.duration_since(*first) < self.replay_window);
+        if seen.contains_key(&mac) {
+            return Err(VerifyFailure::Replayed);
+        }
+        seen.insert(mac, now);
+        Ok(())
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+fn verify_mac(secret: &str, signed: &[u8], expected: &[u8]) -> Result<(), VerifyFailure> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).map_err(|_| VerifyFailure::InvalidSignature)?;
+    mac.update(signed);
+    // `verify_slice` compares in constant time.
+    mac.verify_slice(expected).map_err(|_| VerifyFailure::InvalidSignature)
+}
+
+/// Length-revealing but otherwise constant-time byte comparison.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ---------------------------------------------------------------------------
+// Axum middleware
+// ---------------------------------------------------------------------------
+
+async fn verify_middleware(State(verifier): State<WebhookVerifier>, request: Request, next: Next) -> Response {
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(b) => b,
+        Err(_) => return (StatusCode::PAYLOAD_TOO_LARGE, "body_too_large").into_response(),
+    };
+    if let Err(failure) = verifier.verify(&parts.headers, &bytes) {
+        warn!("[{}] Webhook rejected: {}", verifier.channel, failure.as_str());
+        return (StatusCode::UNAUTHORIZED, failure.as_str()).into_response();
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
+
+/// Wrap an adapter router so every route is signature-checked first.
+pub fn verified<S>(router: Router<S>, verifier: WebhookVerifier) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(middleware::from_fn_with_state(verifier, verify_middleware))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hmac_hex(secret: &str, data: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(data);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn slack_signature_roundtrip_and_replay() {
+        let v = WebhookVerifier::new("slack", SignatureScheme::Slack { signing_secret: "s3cret".into() });
+        let body = br#"{"type":"event_callback"}"#;
+        let ts = now().to_string();
+        let mut signed = format!("v0:{}:", ts).into_bytes();
+        signed.extend_from_slice(body);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-slack-request-timestamp", ts.parse().unwrap());
+        headers.insert("x-slack-signature", format!("v0={}", hmac_hex("s3cret", &signed)).parse().unwrap());
+
+        assert_eq!(v.verify(&headers, body), Ok(()));
+        assert_eq!(v.verify(&headers, body), Err(VerifyFailure::Replayed));
+    }
+
+    #[test]
+    fn slack_rejects_stale_timestamp() {
+        let v = WebhookVerifier::new("slack", SignatureScheme::Slack { signing_secret: "s".into() });
+        let mut headers = HeaderMap::new();
+        headers.insert("x-slack-request-timestamp", (now() - 3600).to_string().parse().unwrap());
+        headers.insert("x-slack-signature", "v0=00".parse().unwrap());
+        assert_eq!(v.verify(&headers, b"{}"), Err(VerifyFailure::StaleTimestamp));
+    }
+
+    #[test]
+    fn twilio_signature_covers_url_and_sorted_params() {
+        let url = "https://claw.example.com/voice/twilio/answer";
+        let v = WebhookVerifier::new("twilio", SignatureScheme::Twilio { auth_token: "12345".into(), url: url.into() });
+        let mut mac = HmacSha1::new_from_slice(b"12345").unwrap();
+        mac.update(format!("{}CallSidCA1From+15550100To+15550199", url).as_bytes());
+        let sig = base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-twilio-signature", sig.parse().unwrap());
+        assert_eq!(v.verify(&headers, b"To=%2B15550199&From=%2B15550666&CallSid=CA1"), Err(VerifyFailure::InvalidSignature));
+        assert_eq!(v.verify(&headers, b"To=%2B15550199&From=%2B15550100&CallSid=CA1"), Ok(()));
+    }
+
+    #[test]
+    fn meta_signature_checks_body() {
+        let v = WebhookVerifier::new("whatsapp", SignatureScheme::MetaSha256 { app_secret: "app".into() });
+        let mut headers = HeaderMap::new();
+        headers.insert("x-hub-signature-256", format!("sha256={}", hmac_hex("app", b"hello")).parse().unwrap());
+        assert_eq!(v.verify(&headers, b"tampered"), Err(VerifyFailure::InvalidSignature));
+        assert_eq!(v.verify(&headers, b"hello"), Ok(()));
+    }
+
+    #[test]
+    fn replays_with_a_re_encoded_signature_are_caught() {
+        let v = WebhookVerifier::new("whatsapp", SignatureScheme::MetaSha256 { app_secret: "app".into() });
+        let sig = hmac_hex("app", b"hello");
+        let mut headers = HeaderMap::new();
+        headers.insert("x-hub-signature-256", format!("sha256={}", sig).parse().unwrap());
+        assert_eq!(v.verify(&headers, b"hello"), Ok(()));
+        headers.insert("x-hub-signature-256", format!("sha256={}", sig.to_uppercase()).parse().unwrap());
+        assert_eq!(v.verify(&headers, b"hello"), Err(VerifyFailure::Replayed));
+    }
+
+    #[test]
+    fn line_signature_is_base64() {
+        let v = WebhookVerifier::new("line", SignatureScheme::Line { channel_secret: "line".into() });
+        let mut mac = HmacSha256::new_from_slice(b"line").unwrap();
+        mac.update(b"{}");
+        let sig = base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-line-signature", sig.parse().unwrap());
+        assert_eq!(v.verify(&headers, b"{}"), Ok(()));
+    }
+
+    #[test]
+    fn telegram_secret_token() {
+        let v = WebhookVerifier::new("telegram", SignatureScheme::TelegramSecretToken { secret_token: "tok".into() });
+        let mut headers = HeaderMap::new();
+        assert_eq!(v.verify(&headers, b""), Err(VerifyFailure::MissingSignature));
+        headers.insert("x-telegram-bot-api-secret-token", "nope".parse().unwrap());
+        assert_eq!(v.verify(&headers, b""), Err(VerifyFailure::InvalidSignature));
+        headers.insert("x-telegram-bot-api-secret-token", "tok".parse().unwrap());
+        assert_eq!(v.verify(&headers, b""), Ok(()));
+    }
+
+    #[test]
+    fn generic_hmac_with_timestamp() {
+        let scheme = SignatureScheme::GenericHmac {
+            secret: "g".into(),
+            header: "x-signature".into(),
+            prefix: None,
+            encoding: SignatureEncoding::Hex,
+            timestamp_header: Some("x-timestamp".into()),
+        };
+        let v = WebhookVerifier::new("custom", scheme);
+        let ts = now().to_string();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-timestamp", ts.parse().unwrap());
+        headers.insert("x-signature", hmac_hex("g", format!("{}.body", ts).as_bytes()).parse().unwrap());
+        assert_eq!(v.verify(&headers, b"body"), Ok(()));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::webhook_verify::{verified, SignatureScheme, WebhookVerifier};
 use crate::ChannelAdapter;
 use async_trait::async_trait;
 use axum::{
@@ -8,7 +9,7 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
 use tokio::sync::mpsc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use clawforge_core::{Message, EventKind, Event};
 use uuid::Uuid;
 use std::sync::Arc;
@@ -82,11 +83,18 @@ pub struct VerifyRequest {
 pub struct WhatsAppAdapter {
     port: u16,
     verify_token: String,
+    /// Meta app secret for `X-Hub-Signature-256`; unsigned deliveries are rejected when set.
+    app_secret: Option<String>,
 }
 
 impl WhatsAppAdapter {
     pub fn new(port: u16, verify_token: String) -> Self {
-        Self { port, verify_token }
+        Self { port, verify_token, app_secret: None }
+    }
+
+    pub fn with_app_secret(mut self, app_secret: String) -> Self {
+        self.app_secret = Some(app_secret);
+        self
     }
 }
 
@@ -102,9 +110,24 @@ impl ChannelAdapter for WhatsAppAdapter {
             verify_token: self.verify_token.clone(),
         };
 
-        let app = Router::new()
-            .route("/webhook/whatsapp", axum::routing::get(verify_webhook).post(handle_webhook))
+        let mut app = Router::new()
+            .route("/webhook/whatsapp", axum::routing::get(verify_webhook))
+            .with_state(state.clone());
+
+        // Only the POST delivery is signed; the GET subscribe handshake uses verify_token.
+        let deliveries = Router::new()
+            .route("/webhook/whatsapp", post(handle_webhook))
             .with_state(state);
+        app = match &self.app_secret {
+            Some(secret) => app.merge(verified(
+                deliveries,
+                WebhookVerifier::new("whatsapp", SignatureScheme::MetaSha256 { app_secret: secret.clone() }),
+            )),
+            None => {
+                warn!("WhatsApp app secret not set — webhook deliveries are not signature-checked");
+                app.merge(deliveries)
+            }
+        };
 
         let addr = std::net::SocketAddr::from(([0, 0, 0, 0], self.port));
         let listener = tokio::net::TcpListener::bind(&addr).await?;
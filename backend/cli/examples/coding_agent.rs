@@ -52,6 +52,9 @@ async fn main() -> Result<()> {
         workflow: vec![],
         allowed_tools: vec!["file_write".to_string()],
         allowed_skills: vec![],
+        timezone: None,
+        locale: None,
+        execution_window: None,
     };
 
     // 3. Wiring
@@ -63,10 +63,14 @@ async fn main() -> Result<()> {
                     args: vec!["fetch".to_string(), "origin".to_string(), "pull/123/head:pr-123".to_string()],
                 },
                 on_failure: FailurePolicy::Stop,
+                output_contract: None,
             },
         ],
         allowed_tools: vec![],
         allowed_skills: vec![],
+        timezone: None,
+        locale: None,
+        execution_window: None,
     };
 
     info!(agent_id = %pr_reviewer.id, "Defined PR Reviewer agent");
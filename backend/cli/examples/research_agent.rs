@@ -160,6 +160,9 @@ async fn main() -> Result<()> {
         workflow: vec![],
         allowed_tools: vec![],
         allowed_skills: vec![],
+        timezone: None,
+        locale: None,
+        execution_window: None,
     };
 
     // 3. Wiring
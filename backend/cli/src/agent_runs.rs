@@ -0,0 +1,97 @@
+//! Agent runs started in serve outside the gateway: fan-out sub-agent tasks
+//! and messages from chat adapters.
+//!
+//! Each one is a plan request for a named agent. Like a cron run, it is
+//! matched back from the supervisor's event stream: the first executed
+//! action is its output, a failed or denied action its error.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use clawforge_channels::{chat_session, ChatAgent};
+use clawforge_core::{Event, Message, PlanRequest};
+use clawforge_scheduler::{run_outcome, AgentLookup};
+use tokio::sync::{broadcast, mpsc};
+use tracing::debug;
+use uuid::Uuid;
+
+/// A run with no outcome after this long fails.
+const RUN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Starts runs for agents by name and waits for their outcome.
+#[derive(Clone)]
+pub struct AgentRuns {
+    agents: AgentLookup,
+    planner_tx: mpsc::Sender<Message>,
+    events: broadcast::Sender<Event>,
+}
+
+impl AgentRuns {
+    pub fn new(agents: AgentLookup, planner_tx: mpsc::Sender<Message>, events: broadcast::Sender<Event>) -> Self {
+        Self { agents, planner_tx, events }
+    }
+
+    /// Run `agent` with `context` and return its output.
+    pub async fn run(&self, agent: &str, context: serde_json::Value) -> Result<String> {
+        let spec = (self.agents)(agent).ok_or_else(|| anyhow!("Agent '{}' not found", agent))?;
+        let run_id = Uuid::new_v4();
+        // Subscribe before the request goes out so no event is missed.
+        let mut events = self.events.subscribe();
+        self.planner_tx
+            .send(Message::PlanRequest(PlanRequest { run_id, agent: spec, context }))
+            .await
+            .map_err(|_| anyhow!("Planner channel closed"))?;
+        debug!(%run_id, %agent, "Agent run queued");
+
+        let outcome = tokio::time::timeout(RUN_TIMEOUT, async {
+            loop {
+                match events.recv().await {
+                    Ok(event) if event.run_id == run_id => {
+                        if let Some(outcome) = run_outcome(&event) {
+                            return outcome;
+                        }
+                    }
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return Err("Event stream closed".to_string()),
+                }
+            }
+        })
+        .await;
+        match outcome {
+            Ok(Ok(output)) => Ok(output),
+            Ok(Err(e)) => bail!(e),
+            Err(_) => bail!("No result after {}s", RUN_TIMEOUT.as_secs()),
+        }
+    }
+}
+
+/// Answers a chat adapter's messages with runs of one agent, each chat in
+/// its own session.
+pub struct ChannelAgent {
+    agent: String,
+    runs: AgentRuns,
+}
+
+impl ChannelAgent {
+    pub fn new(agent: String, runs: AgentRuns) -> Self {
+        Self { agent, runs }
+    }
+}
+
+#[async_trait]
+impl ChatAgent for ChannelAgent {
+    async fn reply(&self, channel: &str, chat_id: &str, sender: &str, text: &str) -> Result<String> {
+        let session = chat_session(channel, chat_id);
+        let context = serde_json::json!({
+            "trigger": "message",
+            "channel": channel,
+            "chat_id": chat_id,
+            "sender": sender,
+            "text": text,
+            "session_id": session,
+            "session_key": session,
+        });
+        self.runs.run(&self.agent, context).await
+    }
+}
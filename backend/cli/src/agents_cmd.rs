@@ -2,8 +2,13 @@
 //!
 //! Subcommands for managing agent lifecycles in the runtime.
 
-use anyhow::Result;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
 use clap::Subcommand;
+use clawforge_config::{config_dir, config_file_path, load_config, resolve_env_vars, write_config, AgentPack, PackCronJob};
+use clawforge_scheduler::cron_store::{CronJob, CronStore};
+use clawforge_supervisor::EventStore;
 
 #[derive(Subcommand)]
 pub enum AgentCommands {
@@ -24,6 +29,31 @@ pub enum AgentCommands {
         #[arg(short, long)]
         agent_id: String,
     },
+    /// Export an agent as a self-contained YAML pack; secrets become ${VAR} placeholders
+    Export {
+        /// Agent name
+        name: String,
+        /// Write the pack here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Runtime database path (defaults to $CLAWFORGE_DB or clawforge.db)
+        #[arg(long)]
+        db: Option<String>,
+    },
+    /// Import an agent pack: the agent, its config overrides, routes and cron jobs
+    Import {
+        /// Pack file produced by `agents export`
+        file: PathBuf,
+        /// Import under a different agent name
+        #[arg(long)]
+        rename: Option<String>,
+        /// Replace an existing agent with the same name
+        #[arg(long)]
+        overwrite: bool,
+        /// Runtime database path (defaults to $CLAWFORGE_DB or clawforge.db)
+        #[arg(long)]
+        db: Option<String>,
+    },
 }
 
 pub async fn run(cmd: AgentCommands) -> Result<()> {
@@ -43,6 +73,121 @@ pub async fn run(cmd: AgentCommands) -> Result<()> {
             println!("Streaming logs for {}... (Press Ctrl+C to exit)", agent_id);
             // mocked streaming
         }
+        AgentCommands::Export { name, output, db } => {
+            let db = db_path(db);
+            let store = EventStore::open(&db)?;
+            let Some(agent) = store.list_agents()?.into_iter().find(|a| a.name == name) else {
+                bail!("No agent named '{}' in {}", name, db);
+            };
+            let cron_jobs = CronStore::open(&db)?
+                .list_for_agent(&agent.id.to_string())?
+                .into_iter()
+                .map(|job| PackCronJob {
+                    schedule: job.schedule,
+                    prompt: job.prompt,
+                    channel: job.channel,
+                    delivery_target: job.delivery_target,
+                    timezone: job.timezone,
+                    delivery_template: job.delivery_template,
+                    stagger_secs: job.stagger_secs,
+                    max_runs: job.max_runs,
+                    enabled: job.enabled,
+                })
+                .collect();
+            let config = load_config(&config_file_path(&config_dir())).await?;
+            let pack = AgentPack::export(agent, &config, cron_jobs)?;
+            let yaml = pack.to_yaml()?;
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, yaml).with_context(|| format!("Failed to write {}", path.display()))?;
+                    println!("Exported '{}' to {}", name, path.display());
+                    if !pack.env.is_empty() {
+                        println!("Secrets were replaced by placeholders; importers must set: {}", pack.env.join(", "));
+                    }
+                }
+                None => print!("{}", yaml),
+            }
+        }
+        AgentCommands::Import { file, rename, overwrite, db } => {
+            let yaml = std::fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+            let mut pack = AgentPack::from_yaml(&yaml)?;
+            if let Some(name) = rename {
+                pack.agent.name = name;
+            }
+            let missing = pack.missing_env();
+            if !missing.is_empty() {
+                bail!("Set these env vars before importing: {}", missing.join(", "));
+            }
+
+            let db = db_path(db);
+            let store = EventStore::open(&db)?;
+            let existing = store.list_agents()?.into_iter().find(|a| a.name == pack.agent.name);
+            let cron = CronStore::open(&db)?;
+            pack.agent.id = match existing {
+                Some(_) if !overwrite => {
+                    bail!("An agent named '{}' exists; pass --overwrite or --rename", pack.agent.name)
+                }
+                Some(agent) => {
+                    cron.delete_for_agent(&agent.id.to_string())?;
+                    agent.id
+                }
+                None => uuid::Uuid::new_v4(),
+            };
+
+            // The stored spec holds real values; config overrides keep their
+            // placeholders and resolve when the config loads.
+            let agent = resolve_env_vars(&serde_json::to_value(&pack.agent)?).context("Failed to resolve pack secrets")?;
+            store.save_agent(&serde_json::from_value(agent)?)?;
+
+            let agent_id = pack.agent.id.to_string();
+            let now = chrono::Utc::now().timestamp();
+            for job in &pack.cron_jobs {
+                cron.upsert(&CronJob {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    agent_id: agent_id.clone(),
+                    channel: job.channel.clone(),
+                    schedule: job.schedule.clone(),
+                    delivery_target: job.delivery_target.clone(),
+                    prompt: job.prompt.clone(),
+                    enabled: job.enabled,
+                    stagger_secs: job.stagger_secs,
+                    max_runs: job.max_runs,
+                    run_count: 0,
+                    created_at: now,
+                    timezone: job.timezone.clone(),
+                    delivery_template: job.delivery_template.clone(),
+                })?;
+            }
+
+            let path = config_file_path(&config_dir());
+            let mut config = load_config(&path).await?;
+            let rerouted = pack.apply_to_config(&mut config);
+            if pack.overrides.is_some() || !pack.routes.is_empty() {
+                write_config(&config, &path).await?;
+            }
+
+            println!("Imported '{}' ({})", pack.agent.name, agent_id);
+            if !pack.skills.is_empty() {
+                println!("  Skills referenced: {}", pack.skills.join(", "));
+            }
+            if !pack.routes.is_empty() {
+                println!("  Routes: {}", pack.routes.join(", "));
+            }
+            for channel in rerouted {
+                println!("  Note: {} was routed to another agent and now goes to '{}'", channel, pack.agent.name);
+            }
+            if !pack.cron_jobs.is_empty() {
+                println!("  Cron jobs: {}", pack.cron_jobs.len());
+            }
+            if !pack.env.is_empty() {
+                println!("  Env vars used: {}", pack.env.join(", "));
+            }
+        }
     }
     Ok(())
 }
+
+fn db_path(db: Option<String>) -> String {
+    db.or_else(|| std::env::var("CLAWFORGE_DB").ok()).unwrap_or_else(|| "clawforge.db".to_string())
+}
@@ -4,7 +4,7 @@ use axum::{
     extract::{State, Query, ws::{WebSocketUpgrade, WebSocket, Message}},
     http::StatusCode,
     response::{Json, IntoResponse, Response},
-    routing::get,
+    routing::{delete, get, post},
     Router,
 };
 use serde::Deserialize;
@@ -29,9 +29,16 @@ fn api_error(status: StatusCode, code: &str, message: &str) -> Response {
 }
 
 // Removed duplicate import
-use clawforge_core::{Event, AgentSpec, Message as CoreMessage};
+use clawforge_core::{BusProbe, ContextLog, Event, AgentSpec, Message as CoreMessage, Template, TemplateError, Topology};
+use clawforge_commands::{detect_command, CommandContext, CommandDispatcher, CommandRegistry};
 use clawforge_core::message::JobTrigger;
-use clawforge_supervisor::Supervisor;
+use clawforge_security::PreferenceStore;
+use clawforge_scheduler::{sample_delivery_context, RunLog, Tz};
+use clawforge_supervisor::{AgentStateStore, EventForwarder, Supervisor};
+use clawforge_tools::ArtifactStore;
+use infra::AdapterStatusRegistry;
+
+use crate::archive::{AgentArchive, DEFAULT_GRACE_DAYS};
 
 /// Shared application state for API handlers.
 pub struct AppState {
@@ -39,6 +46,28 @@ pub struct AppState {
     pub broadcast_tx: broadcast::Sender<Event>,
     pub scheduler_tx: mpsc::Sender<CoreMessage>,
     pub supervisor_tx: mpsc::Sender<CoreMessage>,
+    /// Cron run history — None when the scheduler run log is unavailable.
+    pub run_log: Option<Arc<std::sync::Mutex<RunLog>>>,
+    /// Per-agent inter-run state — None when the state store is unavailable.
+    pub agent_state: Option<Arc<AgentStateStore>>,
+    /// Live bus queue depths for the topology endpoint.
+    pub bus: BusProbe,
+    /// Adapters, hooks, plugins and nodes registered at startup.
+    pub wiring: Topology,
+    /// Soft-delete, restore and purge of agents.
+    pub archive: Arc<AgentArchive>,
+    /// Latest prompt token breakdown per session, recorded by the planner.
+    pub context_log: ContextLog,
+    /// Per-person preferences injected into system prompts.
+    pub preferences: Arc<PreferenceStore>,
+    /// Webhook event forwarding — None when no webhooks are configured.
+    pub forwarder: Option<Arc<EventForwarder>>,
+    /// Artifacts published by the `artifact` tool.
+    pub artifacts: Arc<ArtifactStore>,
+    /// Lifecycle state of each channel adapter.
+    pub adapter_status: AdapterStatusRegistry,
+    /// Slash command handlers for `/api/commands`.
+    pub commands: Arc<CommandDispatcher>,
 }
 
 /// Build the Axum router with all API routes.
@@ -46,12 +75,29 @@ pub fn build_router(state: Arc<AppState>, bluebubbles_router: Option<Router>) ->
     let mut app = Router::new()
         .route("/api/health", get(health))
         .route("/api/runs", get(get_runs))
-        .route("/api/runs/{id}", get(get_run_details))
+        .route("/api/runs/:id", get(get_run_details))
         .route("/api/agents", get(list_agents).post(create_agent))
-        .route("/api/agents/{id}/run", get(run_agent).post(run_agent)) // Allow GET for easy testing, POST for correctness
-        .route("/api/runs/{id}/cancel", get(cancel_run).post(cancel_run))
-        .route("/api/runs/{id}/input", get(provide_input).post(provide_input))
+        .route("/api/agents/archived", get(list_archived_agents))
+        .route("/api/agents/:id", delete(archive_agent))
+        .route("/api/agents/:id/restore", post(restore_agent))
+        .route("/api/agents/:id/purge", post(purge_agent))
+        .route("/api/agents/:id/run", get(run_agent).post(run_agent)) // Allow GET for easy testing, POST for correctness
+        .route("/api/runs/:id/cancel", get(cancel_run).post(cancel_run))
+        .route("/api/runs/:id/input", get(provide_input).post(provide_input))
         .route("/api/status", get(get_status))
+        .route("/api/commands", post(run_command))
+        .route("/api/diagnostics/topology", get(get_topology))
+        .route("/api/sessions/:key/context", get(get_session_context))
+        .route("/api/cron/:id/runs", get(get_cron_runs))
+        .route("/api/templates/preview", post(preview_template))
+        .route("/api/agents/:id/state", get(list_agent_state))
+        .route("/api/agents/:id/state/:key", get(get_agent_state).put(set_agent_state).delete(delete_agent_state))
+        .route("/api/preferences/:principal", get(get_preferences).put(update_preferences).delete(clear_preferences))
+        .route("/api/forwarding/dead-letters", get(list_dead_letters))
+        .route("/api/forwarding/dead-letters/:id", delete(delete_dead_letter))
+        .route("/api/forwarding/dead-letters/:id/replay", post(replay_dead_letter))
+        .route("/api/artifacts", get(list_artifacts))
+        .route("/artifacts/:id", get(view_artifact))
         .route("/api/ws", get(ws_handler))
         .with_state(state);
         
@@ -94,11 +140,12 @@ async fn handle_socket(mut socket: WebSocket, state: Arc<AppState>) {
 }
 
 /// Health check endpoint.
-async fn health() -> Json<Value> {
+async fn health(State(state): State<Arc<AppState>>) -> Json<Value> {
     Json(json!({
-        "status": "ok",
+        "status": if state.adapter_status.is_degraded() { "degraded" } else { "ok" },
         "service": "clawforge",
         "version": env!("CARGO_PKG_VERSION"),
+        "adapters": state.adapter_status.snapshot(),
     }))
 }
 
@@ -181,6 +228,75 @@ async fn create_agent(
     }
 }
 
+#[derive(Deserialize)]
+struct ArchiveParams {
+    /// Days to keep the archived agent before it is purged.
+    #[serde(default)]
+    grace_days: Option<i64>,
+}
+
+/// Soft-delete an agent (?grace_days=30): its cron jobs are disabled, its
+/// runs frozen, and everything is purged once the grace period ends.
+async fn archive_agent(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(agent_id): axum::extract::Path<uuid::Uuid>,
+    Query(params): Query<ArchiveParams>,
+) -> Response {
+    let grace_days = params.grace_days.unwrap_or(DEFAULT_GRACE_DAYS);
+    if !(0..=3650).contains(&grace_days) {
+        return api_error(StatusCode::BAD_REQUEST, "invalid_grace_days", "grace_days must be between 0 and 3650");
+    }
+    match state.archive.archive(&agent_id, chrono::Duration::days(grace_days)) {
+        Ok(Some(archived)) => Json(json!({ "status": "archived", "agent": archived })).into_response(),
+        Ok(None) => api_error(StatusCode::NOT_FOUND, "agent_not_found", &format!("Agent {} not found", agent_id)),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to archive agent");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "archive_agent_failed", "Could not archive agent")
+        }
+    }
+}
+
+/// Archived agents awaiting purge, soonest first.
+async fn list_archived_agents(State(state): State<Arc<AppState>>) -> Response {
+    match state.supervisor.list_archived_agents() {
+        Ok(agents) => Json(json!({ "agents": agents })).into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to list archived agents");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "list_agents_failed", "Could not retrieve archived agents")
+        }
+    }
+}
+
+/// Undo an archive and re-enable the agent's cron jobs.
+async fn restore_agent(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(agent_id): axum::extract::Path<uuid::Uuid>,
+) -> Response {
+    match state.archive.restore(&agent_id) {
+        Ok(Some(agent)) => Json(json!({ "status": "restored", "agent": agent })).into_response(),
+        Ok(None) => api_error(StatusCode::NOT_FOUND, "agent_not_archived", &format!("Agent {} is not archived", agent_id)),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to restore agent");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "restore_agent_failed", "Could not restore agent")
+        }
+    }
+}
+
+/// Purge an archived agent now instead of waiting for the grace period.
+async fn purge_agent(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(agent_id): axum::extract::Path<uuid::Uuid>,
+) -> Response {
+    match state.archive.purge(&agent_id) {
+        Ok(true) => Json(json!({ "status": "purged", "id": agent_id })).into_response(),
+        Ok(false) => api_error(StatusCode::NOT_FOUND, "agent_not_archived", &format!("Agent {} is not archived", agent_id)),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to purge agent");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "purge_agent_failed", "Could not purge agent")
+        }
+    }
+}
+
 /// Trigger a run for an agent.
 async fn run_agent(
     State(state): State<Arc<AppState>>,
@@ -188,6 +304,9 @@ async fn run_agent(
 ) -> Response {
     let agent = match state.supervisor.get_agent(&agent_id) {
         Ok(Some(a)) => a,
+        Ok(None) if matches!(state.supervisor.get_archived_agent(&agent_id), Ok(Some(_))) => {
+            return api_error(StatusCode::GONE, "agent_archived", &format!("Agent {} is archived", agent_id))
+        }
         Ok(None) => return api_error(StatusCode::NOT_FOUND, "agent_not_found", &format!("Agent {} not found", agent_id)),
         Err(e) => {
             tracing::error!(error = %e, "Failed to get agent");
@@ -235,6 +354,10 @@ async fn provide_input(
         Some(s) => s.to_string(),
         None => return api_error(StatusCode::BAD_REQUEST, "empty_input", "input field must be a non-empty string"),
     };
+    // Runs of archived agents are frozen.
+    if matches!(state.supervisor.is_run_frozen(&run_id), Ok(true)) {
+        return api_error(StatusCode::GONE, "run_frozen", "The run's agent is archived");
+    }
     match state.supervisor_tx.send(CoreMessage::ProvideInput { run_id, input }).await {
         Ok(_) => Json(json!({ "status": "input_provided", "run_id": run_id })).into_response(),
         Err(e) => {
@@ -244,8 +367,336 @@ async fn provide_input(
     }
 }
 
+#[derive(Deserialize)]
+struct CommandRequest {
+    text: String,
+    #[serde(default = "default_command_source")]
+    session_id: String,
+    #[serde(default = "default_command_source")]
+    channel: String,
+    #[serde(default = "default_command_source")]
+    sender_id: String,
+}
+
+fn default_command_source() -> String {
+    "api".to_string()
+}
+
+/// Run a slash command such as `/status` or `/exec host ssh:build`.
+async fn run_command(State(state): State<Arc<AppState>>, Json(request): Json<CommandRequest>) -> Response {
+    let Some(invocation) = detect_command(&request.text, &CommandRegistry::new()) else {
+        return api_error(StatusCode::BAD_REQUEST, "not_a_command", "text is not a known slash command");
+    };
+    let ctx = CommandContext { session_id: request.session_id, channel: request.channel, sender_id: request.sender_id };
+    match state.commands.dispatch(&ctx, &invocation).await {
+        Ok(response) => Json(json!({ "text": response.text, "ephemeral": response.ephemeral })).into_response(),
+        Err(e) => api_error(StatusCode::UNPROCESSABLE_ENTITY, "command_failed", &format!("{e:#}")),
+    }
+}
+
+/// Get a cron job's run history plus aggregate stats (?limit=20&offset=0).
+async fn get_cron_runs(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(job_id): axum::extract::Path<String>,
+    Query(page): Query<PaginationParams>,
+) -> Response {
+    let Some(run_log) = &state.run_log else {
+        return api_error(StatusCode::SERVICE_UNAVAILABLE, "run_log_unavailable", "Cron run log is not enabled");
+    };
+    let limit = page.limit.min(200);
+    let run_log = match run_log.lock() {
+        Ok(guard) => guard,
+        Err(_) => return api_error(StatusCode::INTERNAL_SERVER_ERROR, "run_log_poisoned", "Cron run log is unavailable"),
+    };
+    let result = run_log
+        .stats(&job_id)
+        .and_then(|stats| run_log.page(&job_id, limit, page.offset).map(|runs| (stats, runs)));
+    match result {
+        Ok((stats, runs)) => Json(json!({
+            "job_id": job_id,
+            "stats": stats,
+            "runs": runs,
+            "limit": limit,
+            "offset": page.offset,
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to fetch cron runs");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "fetch_cron_runs_failed", "Could not retrieve cron runs")
+        }
+    }
+}
+
+
+#[derive(Deserialize)]
+struct TemplatePreviewRequest {
+    template: String,
+    /// Variables to render with; defaults to a sample cron delivery.
+    #[serde(default)]
+    context: Option<Value>,
+    /// Zone for the sample delivery's times (default UTC).
+    #[serde(default)]
+    timezone: Option<String>,
+}
+
+fn template_error(e: &TemplateError) -> Response {
+    let body = Json(json!({ "error": "invalid_template", "message": e.message, "line": e.line, "column": e.column }));
+    (StatusCode::UNPROCESSABLE_ENTITY, body).into_response()
+}
+
+/// Render a message template against a sample cron delivery, or a given
+/// context, so it can be checked before a job or hook uses it. Either way
+/// the template may only read variables the context provides.
+async fn preview_template(Json(request): Json<TemplatePreviewRequest>) -> Response {
+    let template = match Template::parse(&request.template) {
+        Ok(template) => template,
+        Err(e) => return template_error(&e),
+    };
+    let context = match request.context {
+        Some(context @ Value::Object(_)) => context,
+        Some(_) => return api_error(StatusCode::BAD_REQUEST, "invalid_context", "Context must be a JSON object"),
+        None => {
+            let tz = match request.timezone.as_deref().map(Tz::load).transpose() {
+                Ok(tz) => tz.unwrap_or_default(),
+                Err(e) => return api_error(StatusCode::BAD_REQUEST, "invalid_timezone", &e.to_string()),
+            };
+            sample_delivery_context(chrono::Utc::now(), &tz)
+        }
+    };
+    if let Some(unknown) = template.variables().into_iter().find(|v| context.get(v).is_none()) {
+        let available: Vec<&str> = context.as_object().map(|vars| vars.keys().map(String::as_str).collect()).unwrap_or_default();
+        let message = format!("Unknown variable '{}' (available: {})", unknown, available.join(", "));
+        return api_error(StatusCode::UNPROCESSABLE_ENTITY, "invalid_template", &message);
+    }
+    match template.render(&context) {
+        Ok(rendered) => Json(json!({ "rendered": rendered, "variables": template.variables() })).into_response(),
+        Err(e) => template_error(&e),
+    }
+}
+
+/// List an agent's saved inter-run state.
+async fn list_agent_state(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(agent_id): axum::extract::Path<uuid::Uuid>,
+) -> Response {
+    let Some(store) = &state.agent_state else {
+        return api_error(StatusCode::SERVICE_UNAVAILABLE, "agent_state_unavailable", "Agent state store is not enabled");
+    };
+    match store.list_entries(&agent_id) {
+        Ok(entries) => Json(json!({ "agent_id": agent_id, "entries": entries })).into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to list agent state");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "fetch_agent_state_failed", "Could not retrieve agent state")
+        }
+    }
+}
+
+/// Get one key of an agent's inter-run state.
+async fn get_agent_state(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path((agent_id, key)): axum::extract::Path<(uuid::Uuid, String)>,
+) -> Response {
+    let Some(store) = &state.agent_state else {
+        return api_error(StatusCode::SERVICE_UNAVAILABLE, "agent_state_unavailable", "Agent state store is not enabled");
+    };
+    match store.get_entry(&agent_id, &key) {
+        Ok(Some(entry)) => Json(entry).into_response(),
+        Ok(None) => api_error(StatusCode::NOT_FOUND, "state_key_not_found", &format!("No state key '{}'", key)),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to fetch agent state");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "fetch_agent_state_failed", "Could not retrieve agent state")
+        }
+    }
+}
+
+/// Set one key of an agent's inter-run state; the body is the JSON value.
+async fn set_agent_state(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path((agent_id, key)): axum::extract::Path<(uuid::Uuid, String)>,
+    Json(value): Json<Value>,
+) -> Response {
+    let Some(store) = &state.agent_state else {
+        return api_error(StatusCode::SERVICE_UNAVAILABLE, "agent_state_unavailable", "Agent state store is not enabled");
+    };
+    if let Err(e) = clawforge_tools::validate_entry(&key, &value) {
+        return api_error(StatusCode::BAD_REQUEST, "invalid_state_entry", &e.to_string());
+    }
+    match store.set_entry(&agent_id, &key, value) {
+        Ok(entry) => Json(entry).into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to save agent state");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "save_agent_state_failed", "Could not save agent state")
+        }
+    }
+}
+
+/// Delete one key of an agent's inter-run state.
+async fn delete_agent_state(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path((agent_id, key)): axum::extract::Path<(uuid::Uuid, String)>,
+) -> Response {
+    let Some(store) = &state.agent_state else {
+        return api_error(StatusCode::SERVICE_UNAVAILABLE, "agent_state_unavailable", "Agent state store is not enabled");
+    };
+    match store.delete_entry(&agent_id, &key) {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => api_error(StatusCode::NOT_FOUND, "state_key_not_found", &format!("No state key '{}'", key)),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to delete agent state");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "delete_agent_state_failed", "Could not delete agent state")
+        }
+    }
+}
+
+/// Preferences of a principal (`person:<id>` or `channel:sender`).
+async fn get_preferences(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(principal): axum::extract::Path<String>,
+) -> Response {
+    Json(state.preferences.get(&principal)).into_response()
+}
+
+/// Update preferences from a JSON object; `null` values clear a key.
+async fn update_preferences(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(principal): axum::extract::Path<String>,
+    Json(body): Json<serde_json::Map<String, Value>>,
+) -> Response {
+    for (key, value) in &body {
+        let result = match value {
+            Value::Null => state.preferences.unset(&principal, key),
+            Value::String(text) => state.preferences.set(&principal, key, text),
+            Value::Number(number) => state.preferences.set(&principal, key, &number.to_string()),
+            _ => return api_error(StatusCode::BAD_REQUEST, "invalid_preference", &format!("'{}' must be a string, number or null", key)),
+        };
+        if let Err(e) = result {
+            return api_error(StatusCode::BAD_REQUEST, "invalid_preference", &e.to_string());
+        }
+    }
+    Json(state.preferences.get(&principal)).into_response()
+}
+
+/// Remove all preferences of a principal.
+async fn clear_preferences(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(principal): axum::extract::Path<String>,
+) -> Response {
+    state.preferences.clear(&principal);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Published artifacts, newest first, without content.
+async fn list_artifacts(State(state): State<Arc<AppState>>) -> Response {
+    Json(state.artifacts.list()).into_response()
+}
+
+/// Serve an artifact under the CSP its kind calls for.
+async fn view_artifact(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Response {
+    let Some(artifact) = state.artifacts.get(&id) else {
+        return api_error(StatusCode::NOT_FOUND, "artifact_not_found", "Artifact not found");
+    };
+    (
+        [
+            (axum::http::header::CONTENT_TYPE, artifact.kind.content_type().to_string()),
+            (axum::http::header::CONTENT_SECURITY_POLICY, artifact.kind.content_security_policy().to_string()),
+            (axum::http::header::CONTENT_DISPOSITION, artifact.content_disposition()),
+            (axum::http::header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        artifact.render(),
+    )
+        .into_response()
+}
+
+#[derive(Deserialize)]
+struct DeadLetterParams {
+    limit: Option<usize>,
+}
+
+/// Failed webhook deliveries, newest first.
+async fn list_dead_letters(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DeadLetterParams>,
+) -> Response {
+    let Some(forwarder) = &state.forwarder else {
+        return api_error(StatusCode::SERVICE_UNAVAILABLE, "forwarding_disabled", "Event forwarding is not configured");
+    };
+    match forwarder.dead_letters().list(params.limit.unwrap_or(100).min(1000)) {
+        Ok(letters) => Json(letters).into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to list dead letters");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "list_dead_letters_failed", "Could not list dead letters")
+        }
+    }
+}
+
+/// Redeliver one dead letter; it is removed once the webhook accepts it.
+async fn replay_dead_letter(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(id): axum::extract::Path<i64>,
+) -> Response {
+    let Some(forwarder) = &state.forwarder else {
+        return api_error(StatusCode::SERVICE_UNAVAILABLE, "forwarding_disabled", "Event forwarding is not configured");
+    };
+    match forwarder.replay(id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => api_error(StatusCode::BAD_GATEWAY, "replay_failed", &e.to_string()),
+    }
+}
+
+/// Drop a dead letter without redelivering it.
+async fn delete_dead_letter(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(id): axum::extract::Path<i64>,
+) -> Response {
+    let Some(forwarder) = &state.forwarder else {
+        return api_error(StatusCode::SERVICE_UNAVAILABLE, "forwarding_disabled", "Event forwarding is not configured");
+    };
+    match forwarder.dead_letters().remove(id) {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => api_error(StatusCode::NOT_FOUND, "dead_letter_not_found", &format!("No dead letter {}", id)),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to delete dead letter");
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "delete_dead_letter_failed", "Could not delete dead letter")
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TopologyParams {
+    /// `json` (default) or `dot`.
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// How the deployment is wired — components, bus channels with live queue
+/// depths, adapters, hooks and nodes — as JSON or Graphviz DOT.
+async fn get_topology(State(state): State<Arc<AppState>>, Query(params): Query<TopologyParams>) -> Response {
+    let mut graph = state.bus.topology();
+    graph.merge(&state.wiring);
+    match params.format.as_deref().unwrap_or("json") {
+        "json" => Json(json!(graph)).into_response(),
+        "dot" => ([(axum::http::header::CONTENT_TYPE, "text/vnd.graphviz")], graph.to_dot()).into_response(),
+        other => api_error(StatusCode::BAD_REQUEST, "invalid_format", &format!("Unknown format '{}'. Valid: json, dot", other)),
+    }
+}
+
+/// The last prompt assembled for a session (run id or channel session key),
+/// broken down by segment with token counts.
+async fn get_session_context(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(key): axum::extract::Path<String>,
+) -> Response {
+    match state.context_log.get(&key) {
+        Some(breakdown) => Json(json!(*breakdown)).into_response(),
+        None => api_error(StatusCode::NOT_FOUND, "context_not_found", &format!("No assembled context for session '{}'", key)),
+    }
+}
+
 /// Get runtime status.
-async fn get_status(State(_state): State<Arc<AppState>>) -> Json<Value> {
+async fn get_status(State(state): State<Arc<AppState>>) -> Json<Value> {
     Json(json!({
         "status": "running",
         "components": {
@@ -254,6 +705,7 @@ async fn get_status(State(_state): State<Arc<AppState>>) -> Json<Value> {
             "executor": "active",
             "supervisor": "active",
         },
+        "adapters": state.adapter_status.snapshot(),
         "uptime_seconds": 0,
     }))
 }
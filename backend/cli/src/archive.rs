@@ -0,0 +1,94 @@
+//! Agent soft-delete: archive, restore and purge.
+//!
+//! Deleting an agent archives it instead of removing it. The archive cascades:
+//! the agent's cron jobs are disabled, its runs stop accepting input, and its
+//! events and inter-run state are kept until the grace period ends. The purge
+//! job then removes the agent together with everything that referenced it.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use clawforge_core::AgentSpec;
+use clawforge_scheduler::cron_store::CronStore;
+use clawforge_supervisor::{AgentStateStore, ArchivedAgent, Supervisor};
+
+/// Days an archived agent is kept before the purge job removes it.
+pub const DEFAULT_GRACE_DAYS: i64 = 30;
+
+pub struct AgentArchive {
+    supervisor: Arc<Supervisor>,
+    agent_state: Option<Arc<AgentStateStore>>,
+    /// Cron jobs share the runtime DB; the store is opened per operation.
+    db_path: String,
+}
+
+impl AgentArchive {
+    pub fn new(supervisor: Arc<Supervisor>, agent_state: Option<Arc<AgentStateStore>>, db_path: impl Into<String>) -> Self {
+        Self { supervisor, agent_state, db_path: db_path.into() }
+    }
+
+    /// Archive an active agent for `grace`. Returns None when the agent is
+    /// unknown or already archived.
+    pub fn archive(&self, id: &Uuid, grace: Duration) -> Result<Option<ArchivedAgent>> {
+        if self.supervisor.get_agent(id)?.is_none() {
+            return Ok(None);
+        }
+        let cron = CronStore::open(&self.db_path)?;
+        let cron_jobs = cron.disable_for_agent(&id.to_string())?;
+        if !self.supervisor.archive_agent(id, Utc::now() + grace, &cron_jobs)? {
+            // Archived concurrently; put the jobs back as they were.
+            for job in &cron_jobs {
+                cron.enable(job)?;
+            }
+            return Ok(None);
+        }
+        info!(agent_id = %id, cron_jobs = cron_jobs.len(), grace_days = grace.num_days(), "Agent archived");
+        self.supervisor.get_archived_agent(id)
+    }
+
+    /// Bring an archived agent back and re-enable the cron jobs its archive
+    /// disabled. Returns None when the agent is not archived.
+    pub fn restore(&self, id: &Uuid) -> Result<Option<AgentSpec>> {
+        let Some(cron_jobs) = self.supervisor.restore_agent(id)? else {
+            return Ok(None);
+        };
+        let cron = CronStore::open(&self.db_path)?;
+        for job in &cron_jobs {
+            cron.enable(job)?;
+        }
+        info!(agent_id = %id, cron_jobs = cron_jobs.len(), "Agent restored");
+        self.supervisor.get_agent(id)
+    }
+
+    /// Permanently remove an archived agent, its events, cron jobs and
+    /// state. Returns false when the agent is not archived.
+    pub fn purge(&self, id: &Uuid) -> Result<bool> {
+        if !self.supervisor.purge_agent(id)? {
+            return Ok(false);
+        }
+        let cron_jobs = CronStore::open(&self.db_path)?.delete_for_agent(&id.to_string())?;
+        let state = match &self.agent_state {
+            Some(store) => store.clear_agent(id)?,
+            None => 0,
+        };
+        info!(agent_id = %id, cron_jobs, state_entries = state, "Agent purged");
+        Ok(true)
+    }
+
+    /// Purge every archived agent whose grace period ended by `now`.
+    pub fn purge_due(&self, now: DateTime<Utc>) -> Result<Vec<Uuid>> {
+        let mut purged = Vec::new();
+        for id in self.supervisor.agents_due_for_purge(now)? {
+            match self.purge(&id) {
+                Ok(true) => purged.push(id),
+                Ok(false) => {}
+                Err(e) => warn!(agent_id = %id, error = %e, "Agent purge failed"),
+            }
+        }
+        Ok(purged)
+    }
+}
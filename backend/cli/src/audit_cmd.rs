@@ -0,0 +1,74 @@
+//! CLI Audit Subcommands
+//!
+//! Checks the tamper-evident audit log from the terminal.
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use clawforge_security::{verify_audit_log, AuditProblem};
+
+use crate::config::Config;
+
+#[derive(Subcommand)]
+pub enum AuditCommands {
+    /// Verify the audit log's hash chain and signed checkpoints
+    Verify {
+        /// Audit database path (defaults to the runtime database, $CLAWFORGE_DB)
+        #[arg(long)]
+        db: Option<String>,
+        /// Checkpoint signing key file (defaults to $CLAWFORGE_AUDIT_KEY or the
+        /// server's `audit.key`)
+        #[arg(long)]
+        key: Option<String>,
+    },
+}
+
+pub async fn run(cmd: AuditCommands, config: &Config) -> Result<()> {
+    match cmd {
+        AuditCommands::Verify { db, key } => {
+            let db = db.unwrap_or_else(|| config.db_path.clone());
+            // A key named on the command line must be readable; the server's
+            // default key may not exist on this machine.
+            let key = match key {
+                Some(path) => Some(std::fs::read_to_string(&path)?),
+                None => std::fs::read_to_string(config.audit_key_file()).ok(),
+            };
+            let key = key.map(|hex_key| hex::decode(hex_key.trim())).transpose()?;
+
+            let report = verify_audit_log(&db, key.as_deref())?;
+            println!("Audit log: {}", db);
+            println!("  Chained entries: {}", report.entries);
+            if report.legacy_entries > 0 {
+                println!("  Legacy entries (unverifiable): {}", report.legacy_entries);
+            }
+            println!(
+                "  Checkpoints: {}{}",
+                report.checkpoints,
+                if report.signatures_checked { "" } else { " (signatures not checked, no key)" }
+            );
+
+            if report.is_intact() {
+                println!("OK: no tampering detected");
+                return Ok(());
+            }
+            for problem in &report.problems {
+                let line = match problem {
+                    AuditProblem::HashMismatch { seq } => format!("entry #{} was modified", seq),
+                    AuditProblem::BrokenLink { seq } => format!("entry #{} does not link to its predecessor", seq),
+                    AuditProblem::MissingEntries { after, next } => {
+                        format!("entries between #{} and #{} are missing", after, next)
+                    }
+                    AuditProblem::CheckpointMismatch { seq } => {
+                        format!("checkpoint #{} no longer matches the log", seq)
+                    }
+                    AuditProblem::BadSignature { seq } => format!("checkpoint #{} has an invalid signature", seq),
+                    AuditProblem::MissingHead => "the chain head anchor was deleted".to_string(),
+                    AuditProblem::HeadMismatch { seq } => format!("entries up to #{} were removed or replaced", seq),
+                    AuditProblem::BadHeadSignature => "the chain head anchor has an invalid signature".to_string(),
+                    AuditProblem::MissingCheckpoint { seq } => format!("checkpoint #{} was deleted", seq),
+                };
+                println!("  TAMPERED: {}", line);
+            }
+            bail!("Audit log verification failed with {} problem(s)", report.problems.len());
+        }
+    }
+}
@@ -8,15 +8,65 @@ pub struct Config {
     pub bind_address: String,
     /// HTTP server port
     pub port: u16,
+    /// Port of the gateway (pairing, approvals, nodes, federation, ...);
+    /// the gateway only runs when set
+    pub gateway_port: Option<u16>,
     /// SQLite database path
     pub db_path: String,
+    /// Hex key file audit checkpoints are signed with; created on first
+    /// start (None = `audit.key` in the config directory)
+    pub audit_key_path: Option<String>,
     /// OpenRouter API key
     pub openrouter_api_key: Option<String>,
     /// Ollama base URL
     pub ollama_url: Option<String>,
     /// Log level
     pub log_level: String,
+    /// IANA time zone for cron triggers of agents without their own
+    /// (None = UTC)
+    pub timezone: Option<String>,
+    /// YAML file declaring data source connectors (weather, RSS, ...)
+    pub connectors_path: Option<String>,
+    /// Directory agents may search with `grep`/`glob` and manage with `git`
+    pub workspace_dir: Option<String>,
+    /// Show each `file_write` in the workspace's git work tree as a diff
+    /// to approve before it is applied
+    pub preview_writes: bool,
+    /// OpenAPI document whose operations the `http` tool offers as typed
+    /// calls (None = raw requests only)
+    pub openapi_spec_path: Option<String>,
+    /// What `web_fetch` does with pages that look like prompt injection:
+    /// `block`, `warn` (default) or `allow`
+    pub external_content_policy: Option<String>,
+    /// SSH exec hosts for `/exec node`, as `name=[user@]host[:port]`
+    pub exec_hosts: Vec<String>,
+    /// Node hosts reached over HTTP, as `id=http://host:port`; each is also
+    /// an exec host for `/exec node <id>`
+    pub node_hosts: Vec<String>,
+    /// Bearer token presented to node hosts
+    pub node_token: Option<String>,
+    /// Remote sub-agent hosts that may dial `/api/acp/ws`, as `id=token`
+    pub acp_hosts: Vec<String>,
+    /// Sandbox driver for the `python` tool: `docker`, `bwrap`,
+    /// `ssh:<name>` or `node:<id>` (None = no `python` tool)
+    pub python_sandbox: Option<String>,
+    /// YAML allowlist of AppleScript / Shortcuts automations for Mac nodes
+    pub automation_scripts_path: Option<String>,
+    /// YAML gateway settings watched for changes; its `providers:` are
+    /// hot-reloaded into the model provider registry
+    pub gateway_config_path: Option<String>,
+    /// Shell output kept per stream (and streamed to the session), in bytes
+    pub max_output_bytes: usize,
     
+    /// Who may DM the assistant on gated channels: `open`, `pairing`,
+    /// `allowlist` or `disabled` (None = no gate)
+    pub dm_policy: Option<String>,
+    /// Senders or `person:<uuid>` principals let through by the DM gate
+    pub dm_allow_from: Vec<String>,
+    /// USD a person may spend per rolling day, across all their linked
+    /// accounts (None = unlimited)
+    pub principal_daily_budget_usd: Option<f64>,
+
     // BlueBubbles
     pub bluebubbles_server_url: Option<String>,
     pub bluebubbles_password: Option<String>,
@@ -27,10 +77,39 @@ pub struct Config {
     pub slack_bot_token: Option<String>,
     pub slack_webhook_path: String,
     
+    // Telegram
+    pub telegram_bot_token: Option<String>,
+
+    // Discord
+    pub discord_bot_token: Option<String>,
+
     // Matrix
     pub matrix_homeserver_url: Option<String>,
     pub matrix_access_token: Option<String>,
     pub matrix_user_id: Option<String>,
+
+    // SIEM export
+    /// `cef` (default) or `ocsf`
+    pub siem_format: Option<String>,
+    /// File security records are appended to
+    pub siem_file: Option<String>,
+    /// Syslog collector, `udp://host:514` or `tcp://host:6514`
+    pub siem_syslog: Option<String>,
+
+    // Artifacts
+    /// Public origin used in artifact links, e.g. `https://claw.example.com`
+    pub public_url: Option<String>,
+    /// Mermaid rendering service for diagram previews, e.g. `https://mermaid.ink`
+    pub mermaid_renderer: Option<String>,
+
+    // Event forwarding
+    /// YAML file of webhooks that selected events are POSTed to
+    pub forwarding_path: Option<String>,
+
+    // Notifications
+    /// Template cron deliveries are wrapped in before they are sent, with
+    /// the variables in `clawforge_hooks::NOTIFICATION_VARIABLES`
+    pub notification_template: Option<String>,
 }
 
 impl Default for Config {
@@ -38,19 +117,47 @@ impl Default for Config {
         Self {
             bind_address: "0.0.0.0".to_string(),
             port: 8080,
+            gateway_port: None,
+            max_output_bytes: clawforge_sandbox::DEFAULT_MAX_OUTPUT_BYTES,
             db_path: "clawforge.db".to_string(),
+            audit_key_path: None,
             openrouter_api_key: None,
             ollama_url: Some("http://localhost:11434".to_string()),
             log_level: "info".to_string(),
+            timezone: None,
+            connectors_path: None,
+            workspace_dir: None,
+            preview_writes: false,
+            openapi_spec_path: None,
+            external_content_policy: None,
+            exec_hosts: Vec::new(),
+            node_hosts: Vec::new(),
+            node_token: None,
+            acp_hosts: Vec::new(),
+            python_sandbox: None,
+            automation_scripts_path: None,
+            gateway_config_path: None,
+            dm_policy: None,
+            dm_allow_from: Vec::new(),
+            principal_daily_budget_usd: None,
             bluebubbles_server_url: None,
             bluebubbles_password: None,
             bluebubbles_webhook_path: "/webhooks/bluebubbles".to_string(),
             slack_signing_secret: None,
             slack_bot_token: None,
             slack_webhook_path: "/webhooks/slack".to_string(),
+            telegram_bot_token: None,
+            discord_bot_token: None,
             matrix_homeserver_url: None,
             matrix_access_token: None,
             matrix_user_id: None,
+            siem_format: None,
+            siem_file: None,
+            siem_syslog: None,
+            public_url: None,
+            mermaid_renderer: None,
+            forwarding_path: None,
+            notification_template: None,
         }
     }
 }
@@ -61,9 +168,117 @@ impl Config {
         if self.port == 0 {
             bail!("CLAWFORGE_PORT must be between 1 and 65535");
         }
+        if self.gateway_port == Some(0) || self.gateway_port == Some(self.port) {
+            bail!("CLAWFORGE_GATEWAY_PORT must be between 1 and 65535 and differ from CLAWFORGE_PORT");
+        }
+        if self.max_output_bytes == 0 {
+            bail!("CLAWFORGE_MAX_OUTPUT_BYTES must be greater than 0");
+        }
         if self.db_path.trim().is_empty() {
             bail!("CLAWFORGE_DB must not be empty");
         }
+        if let Some(tz) = &self.timezone {
+            if let Err(e) = clawforge_scheduler::Tz::load(tz) {
+                bail!("CLAWFORGE_TZ is not a valid time zone: {}", e);
+            }
+        }
+        if let Some(path) = &self.connectors_path {
+            let yaml = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("CLAWFORGE_CONNECTORS could not be read: {}", e))?;
+            if let Err(e) = clawforge_tools::ConnectorSet::from_yaml(&yaml) {
+                bail!("CLAWFORGE_CONNECTORS is invalid: {:#}", e);
+            }
+        }
+        if let Some(dir) = &self.workspace_dir {
+            if !std::path::Path::new(dir).is_dir() {
+                bail!("CLAWFORGE_WORKSPACE is not a directory: {}", dir);
+            }
+        }
+        if let Some(path) = &self.openapi_spec_path {
+            if let Err(e) = clawforge_tools::OpenApiSpec::load(path) {
+                bail!("CLAWFORGE_OPENAPI_SPEC is invalid: {:#}", e);
+            }
+        }
+        if let Some(policy) = &self.external_content_policy {
+            if !matches!(policy.as_str(), "block" | "warn" | "allow") {
+                bail!("CLAWFORGE_EXTERNAL_CONTENT must be block, warn or allow");
+            }
+        }
+        for host in &self.exec_hosts {
+            if let Err(e) = Self::parse_exec_host(host) {
+                bail!("CLAWFORGE_EXEC_HOSTS entry '{}' is invalid: {}", host, e);
+            }
+        }
+        for node in &self.node_hosts {
+            if let Err(e) = Self::parse_node_host(node) {
+                bail!("CLAWFORGE_NODES entry '{}' is invalid: {}", node, e);
+            }
+        }
+        for host in &self.acp_hosts {
+            if let Err(e) = Self::parse_acp_host(host) {
+                bail!("CLAWFORGE_ACP_HOSTS entry '{}' is invalid: {}", host, e);
+            }
+        }
+        if let Some(driver) = &self.python_sandbox {
+            let known = matches!(driver.as_str(), "docker" | "bwrap" | "bubblewrap")
+                || driver.strip_prefix("ssh:").is_some_and(|name| self.exec_hosts.iter().any(|h| h.split('=').next() == Some(name)))
+                || driver.strip_prefix("node:").is_some_and(|id| self.node_hosts.iter().any(|n| n.split('=').next() == Some(id)));
+            if !known {
+                bail!("CLAWFORGE_PYTHON_SANDBOX must be docker, bwrap, or a configured ssh:<name> / node:<id> host");
+            }
+        }
+        if let Some(path) = &self.automation_scripts_path {
+            let yaml = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("CLAWFORGE_AUTOMATION_SCRIPTS could not be read: {}", e))?;
+            if let Err(e) = clawforge_companion::ScriptAllowlist::from_yaml(&yaml) {
+                bail!("CLAWFORGE_AUTOMATION_SCRIPTS is invalid: {:#}", e);
+            }
+        }
+        if let Some(path) = &self.gateway_config_path {
+            let yaml = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("CLAWFORGE_GATEWAY_CONFIG could not be read: {}", e))?;
+            if let Err(e) = serde_yaml::from_str::<clawforge_gateway::GatewayConfig>(&yaml) {
+                bail!("CLAWFORGE_GATEWAY_CONFIG is invalid: {}", e);
+            }
+        }
+        if let Some(format) = &self.siem_format {
+            if clawforge_security::SiemFormat::parse(format).is_none() {
+                bail!("CLAWFORGE_SIEM_FORMAT must be cef or ocsf");
+            }
+        }
+        if let Some(target) = &self.siem_syslog {
+            if let Err(e) = clawforge_security::SiemExporter::new(Default::default()).with_syslog(target) {
+                bail!("CLAWFORGE_SIEM_SYSLOG is invalid: {}", e);
+            }
+        }
+        for (var, url) in [("CLAWFORGE_PUBLIC_URL", &self.public_url), ("CLAWFORGE_MERMAID_RENDERER", &self.mermaid_renderer)] {
+            if let Some(url) = url {
+                if !(url.starts_with("https://") || url.starts_with("http://")) {
+                    bail!("{} must be an http(s) URL", var);
+                }
+            }
+        }
+        if let Some(path) = &self.forwarding_path {
+            let yaml = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("CLAWFORGE_FORWARDING could not be read: {}", e))?;
+            let dead_letters = clawforge_supervisor::DeadLetterQueue::in_memory()?;
+            if let Err(e) = clawforge_supervisor::EventForwarder::from_yaml(&yaml, dead_letters) {
+                bail!("CLAWFORGE_FORWARDING is invalid: {:#}", e);
+            }
+        }
+        if let Some(template) = &self.notification_template {
+            if let Err(e) = clawforge_hooks::NotificationTemplateHook::new(template) {
+                bail!("CLAWFORGE_NOTIFICATION_TEMPLATE is invalid: {}", e);
+            }
+        }
+        if let Some(policy) = &self.dm_policy {
+            if !matches!(policy.as_str(), "open" | "pairing" | "allowlist" | "disabled") {
+                bail!("CLAWFORGE_DM_POLICY must be open, pairing, allowlist or disabled");
+            }
+        }
+        if self.principal_daily_budget_usd.is_some_and(|usd| usd.is_nan() || usd <= 0.0) {
+            bail!("CLAWFORGE_PRINCIPAL_BUDGET_USD must be a positive number");
+        }
         if !self.bluebubbles_webhook_path.starts_with('/') {
             bail!("BLUEBUBBLES_WEBHOOK_PATH must start with '/'");
         }
@@ -73,6 +288,48 @@ impl Config {
         Ok(())
     }
 
+    /// Where the audit checkpoint signing key lives.
+    pub fn audit_key_file(&self) -> std::path::PathBuf {
+        match &self.audit_key_path {
+            Some(path) => std::path::PathBuf::from(path),
+            None => clawforge_config::config_dir().join("audit.key"),
+        }
+    }
+
+    /// Split an exec host entry into its name and SSH target.
+    pub fn parse_exec_host(entry: &str) -> Result<(String, clawforge_sandbox::SshTarget)> {
+        let (name, spec) = entry.split_once('=').unwrap_or((entry, entry));
+        if name.trim().is_empty() {
+            bail!("missing host name");
+        }
+        Ok((name.trim().to_string(), clawforge_sandbox::SshTarget::parse(spec.trim())?))
+    }
+
+    /// Split a node host entry into its node id and base URL.
+    pub fn parse_node_host(entry: &str) -> Result<(String, String)> {
+        let Some((id, url)) = entry.split_once('=') else { bail!("expected id=url") };
+        if id.trim().is_empty() {
+            bail!("missing node id");
+        }
+        let url = url.trim();
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            bail!("node URL must be http(s)");
+        }
+        Ok((id.trim().to_string(), url.to_string()))
+    }
+
+    /// Split an ACP host entry into its host id and token.
+    pub fn parse_acp_host(entry: &str) -> Result<(String, String)> {
+        let Some((id, token)) = entry.split_once('=') else { bail!("expected id=token") };
+        if id.trim().is_empty() {
+            bail!("missing host id");
+        }
+        if token.trim().is_empty() {
+            bail!("missing token");
+        }
+        Ok((id.trim().to_string(), token.trim().to_string()))
+    }
+
     /// Load configuration from environment variables with sensible defaults.
     pub fn from_env() -> Self {
         Self {
@@ -82,12 +339,42 @@ impl Config {
                 .ok()
                 .and_then(|p| p.parse().ok())
                 .unwrap_or(8080),
+            gateway_port: std::env::var("CLAWFORGE_GATEWAY_PORT").ok().and_then(|p| p.parse().ok()),
+            max_output_bytes: std::env::var("CLAWFORGE_MAX_OUTPUT_BYTES")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(clawforge_sandbox::DEFAULT_MAX_OUTPUT_BYTES),
             db_path: std::env::var("CLAWFORGE_DB")
                 .unwrap_or_else(|_| "clawforge.db".to_string()),
+            audit_key_path: std::env::var("CLAWFORGE_AUDIT_KEY").ok(),
             openrouter_api_key: std::env::var("OPENROUTER_API_KEY").ok(),
             ollama_url: std::env::var("OLLAMA_URL").ok().or(Some("http://localhost:11434".to_string())),
             log_level: std::env::var("RUST_LOG")
                 .unwrap_or_else(|_| "info".to_string()),
+            timezone: std::env::var("CLAWFORGE_TZ").ok(),
+            connectors_path: std::env::var("CLAWFORGE_CONNECTORS").ok(),
+            workspace_dir: std::env::var("CLAWFORGE_WORKSPACE").ok(),
+            preview_writes: std::env::var("CLAWFORGE_PREVIEW_WRITES").is_ok_and(|v| matches!(v.as_str(), "1" | "true")),
+            openapi_spec_path: std::env::var("CLAWFORGE_OPENAPI_SPEC").ok(),
+            external_content_policy: std::env::var("CLAWFORGE_EXTERNAL_CONTENT").ok(),
+            exec_hosts: std::env::var("CLAWFORGE_EXEC_HOSTS")
+                .map(|v| v.split(',').map(str::trim).filter(|h| !h.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            node_hosts: std::env::var("CLAWFORGE_NODES")
+                .map(|v| v.split(',').map(str::trim).filter(|n| !n.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            node_token: std::env::var("CLAWFORGE_NODE_TOKEN").ok(),
+            acp_hosts: std::env::var("CLAWFORGE_ACP_HOSTS")
+                .map(|v| v.split(',').map(str::trim).filter(|h| !h.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            python_sandbox: std::env::var("CLAWFORGE_PYTHON_SANDBOX").ok(),
+            automation_scripts_path: std::env::var("CLAWFORGE_AUTOMATION_SCRIPTS").ok(),
+            gateway_config_path: std::env::var("CLAWFORGE_GATEWAY_CONFIG").ok(),
+            dm_policy: std::env::var("CLAWFORGE_DM_POLICY").ok(),
+            dm_allow_from: std::env::var("CLAWFORGE_DM_ALLOW_FROM")
+                .map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            principal_daily_budget_usd: std::env::var("CLAWFORGE_PRINCIPAL_BUDGET_USD").ok().and_then(|v| v.parse().ok()),
             bluebubbles_server_url: std::env::var("BLUEBUBBLES_SERVER_URL").ok(),
             bluebubbles_password: std::env::var("BLUEBUBBLES_PASSWORD").ok(),
             bluebubbles_webhook_path: std::env::var("BLUEBUBBLES_WEBHOOK_PATH")
@@ -96,9 +383,18 @@ impl Config {
             slack_bot_token: std::env::var("SLACK_BOT_TOKEN").ok(),
             slack_webhook_path: std::env::var("SLACK_WEBHOOK_PATH")
                 .unwrap_or_else(|_| "/webhooks/slack".to_string()),
+            telegram_bot_token: std::env::var("TELEGRAM_BOT_TOKEN").ok(),
+            discord_bot_token: std::env::var("DISCORD_BOT_TOKEN").ok(),
             matrix_homeserver_url: std::env::var("MATRIX_HOMESERVER_URL").ok(),
             matrix_access_token: std::env::var("MATRIX_ACCESS_TOKEN").ok(),
             matrix_user_id: std::env::var("MATRIX_USER_ID").ok(),
+            siem_format: std::env::var("CLAWFORGE_SIEM_FORMAT").ok(),
+            siem_file: std::env::var("CLAWFORGE_SIEM_FILE").ok(),
+            siem_syslog: std::env::var("CLAWFORGE_SIEM_SYSLOG").ok(),
+            public_url: std::env::var("CLAWFORGE_PUBLIC_URL").ok(),
+            mermaid_renderer: std::env::var("CLAWFORGE_MERMAID_RENDERER").ok(),
+            forwarding_path: std::env::var("CLAWFORGE_FORWARDING").ok(),
+            notification_template: std::env::var("CLAWFORGE_NOTIFICATION_TEMPLATE").ok(),
         }
     }
 }
@@ -0,0 +1,77 @@
+//! The queryable agent event log behind `/api/events`.
+//!
+//! Runtime events from the executor and the channels are recorded as agent
+//! events (tool calls, messages, errors) keyed by run and agent, and entries
+//! older than `logging.eventRetentionDays` are pruned hourly.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use clawforge_core::{Event, EventKind};
+use logging::{AgentEvent, EventLogger, EventStore};
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+const DEFAULT_RETENTION_DAYS: u64 = 30;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// The agent event a runtime event is logged as, if any.
+fn agent_event(event: &Event) -> Option<AgentEvent> {
+    let text = |key: &str| event.payload[key].as_str().map(str::to_string);
+    match event.kind {
+        EventKind::ActionApproved => Some(AgentEvent::ToolCall {
+            tool_name: text("tool").unwrap_or_else(|| "action".to_string()),
+            arguments_json: event.payload.to_string(),
+        }),
+        EventKind::ActionExecuted => Some(AgentEvent::Message {
+            role: "tool".to_string(),
+            content: text("output").or_else(|| text("content")).unwrap_or_else(|| event.payload.to_string()),
+        }),
+        EventKind::CallTurn | EventKind::MediaReceived | EventKind::MediaProcessed => Some(AgentEvent::Message {
+            role: "channel".to_string(),
+            content: event.payload.to_string(),
+        }),
+        EventKind::ActionDenied
+        | EventKind::ActionFailed
+        | EventKind::RunFailed
+        | EventKind::MediaRejected
+        | EventKind::EgressBlocked
+        | EventKind::SandboxResourceExceeded => Some(AgentEvent::Error {
+            error_msg: text("error").unwrap_or_else(|| format!("{}: {}", event.kind, event.payload)),
+        }),
+        _ => None,
+    }
+}
+
+/// Record `events` into `store` and prune it to `retention_days`, in the
+/// background.
+pub fn spawn(store: Arc<EventStore>, retention_days: Option<u64>, mut events: broadcast::Receiver<Event>) {
+    let logger = EventLogger::new().with_store(Arc::clone(&store));
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Some(agent_event) = agent_event(&event) {
+                        let (agent, run) = (event.agent_id.to_string(), event.run_id.to_string());
+                        tokio::task::block_in_place(|| logger.record(&agent, &run, agent_event));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => warn!(missed, "Event log lagged; agent events were skipped"),
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    let retention = chrono::Duration::days(retention_days.unwrap_or(DEFAULT_RETENTION_DAYS) as i64);
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            tick.tick().await;
+            match tokio::task::block_in_place(|| store.prune_before(chrono::Utc::now() - retention)) {
+                Ok(pruned) if pruned > 0 => info!(pruned, "Pruned expired agent events"),
+                Ok(_) => {}
+                Err(e) => error!(error = %e, "Agent event pruning failed"),
+            }
+        }
+    });
+}
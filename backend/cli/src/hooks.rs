@@ -0,0 +1,83 @@
+//! Hooks installed from `hooks.installed`.
+//!
+//! Script hooks run in a sandbox per hook and only execute commands the
+//! operator's exec allowlist allows; webhook hooks POST each phase to a
+//! signed HTTP endpoint. An entry's `when` trigger is checked as it loads,
+//! and the hook then runs only when the trigger fires.
+
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use clawforge_config::schema::HookEntry;
+use clawforge_core::Topology;
+use clawforge_hooks::{ConditionalHook, Hook, HookPhase, HookRegistry, HookTrigger, ScriptHook, ScriptHookConfig, WebhookHook, WebhookHookConfig};
+use clawforge_sandbox::{ExecAllowlist, SandboxRegistry};
+use tracing::error;
+
+const ALL_PHASES: [HookPhase; 9] = [
+    HookPhase::PreMessage,
+    HookPhase::PostMessage,
+    HookPhase::PreToolCall,
+    HookPhase::AfterToolCall,
+    HookPhase::PreCompaction,
+    HookPhase::PostCompaction,
+    HookPhase::SessionStart,
+    HookPhase::SessionEnd,
+    HookPhase::ModelOverride,
+];
+
+/// What the installed hooks need from serve.
+pub struct HookDeps {
+    pub sandboxes: Arc<SandboxRegistry>,
+    pub allowlist: ExecAllowlist,
+}
+
+/// Build an installed hook, wrapped in its `when` trigger, and the phases
+/// it runs on.
+pub fn build(entry: &HookEntry, deps: &HookDeps) -> Result<(Arc<dyn Hook>, Vec<HookPhase>)> {
+    let (hook, phases) = build_kind(entry, deps)?;
+    let Some(when) = &entry.when else { return Ok((hook, phases)) };
+    let trigger: HookTrigger = serde_json::from_value(when.clone()).context("invalid hook trigger")?;
+    Ok((Arc::new(ConditionalHook::new(trigger, hook)?), phases))
+}
+
+fn build_kind(entry: &HookEntry, deps: &HookDeps) -> Result<(Arc<dyn Hook>, Vec<HookPhase>)> {
+    let mut settings = entry.config.clone().unwrap_or_else(|| serde_json::json!({}));
+    if let Some(settings) = settings.as_object_mut() {
+        settings.entry("name").or_insert_with(|| entry.id.clone().into());
+    }
+    match entry.kind.as_deref() {
+        Some("script") => {
+            let config: ScriptHookConfig = serde_json::from_value(settings).context("invalid script hook config")?;
+            let phases = config.phases.clone();
+            let hook = ScriptHook::new(config, Arc::clone(&deps.sandboxes))?.with_allowlist(deps.allowlist.clone());
+            Ok((Arc::new(hook), phases))
+        }
+        Some("webhook") => {
+            let config: WebhookHookConfig = serde_json::from_value(settings).context("invalid webhook hook config")?;
+            let phases = config.phases.clone();
+            Ok((Arc::new(WebhookHook::new(config)?), phases))
+        }
+        Some(other) => bail!("unknown hook kind '{}'", other),
+        None => bail!("hook has no kind"),
+    }
+}
+
+/// Register every installed hook that has a kind, skipping broken entries.
+pub async fn register_installed(hooks: &HookRegistry, entries: &[HookEntry], deps: &HookDeps, wiring: &mut Topology) {
+    for entry in entries.iter().filter(|e| e.kind.is_some()) {
+        let (hook, phases) = match build(entry, deps) {
+            Ok(built) => built,
+            Err(e) => {
+                error!(hook = %entry.id, error = %format!("{:#}", e), "Hook not installed");
+                continue;
+            }
+        };
+        let phases = if phases.is_empty() { ALL_PHASES.to_vec() } else { phases };
+        for phase in phases {
+            let point = serde_json::to_value(&phase).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+            hooks.register(phase, Arc::clone(&hook)).await;
+            wiring.add_hook(&entry.id, "scheduler", &point);
+        }
+    }
+}
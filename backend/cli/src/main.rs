@@ -1,11 +1,23 @@
+mod agent_runs;
 mod api;
+mod archive;
+mod audit_cmd;
 mod config;
 mod doctor_cmd;
+mod event_log;
+mod hooks;
+mod media_intake;
 mod models_cmd;
+mod plugin_cmd;
 mod status_cmd;
+mod subagents;
 mod agents_cmd;
 mod memory_cmd;
 mod sessions_cmd;
+mod security_cmd;
+mod skills_cmd;
+mod tts_cmd;
+mod voice;
 
 use std::sync::Arc;
 
@@ -15,17 +27,18 @@ use tokio::net::TcpListener;
 use tokio::sync::broadcast;
 use tower_http::cors::CorsLayer;
 use tower_http::limit::RequestBodyLimitLayer;
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
 
-use clawforge_core::ClawBus;
+use clawforge_core::{ClawBus, ContextLog, NodeKind, Topology};
 use clawforge_executor::Executor;
 use clawforge_planner::providers::ProviderRegistry;
 use clawforge_planner::LlmPlanner;
-use clawforge_scheduler::Scheduler;
-use clawforge_supervisor::Supervisor;
+use clawforge_scheduler::{RetentionPolicy, RunLog, Scheduler, Tz};
+use clawforge_supervisor::{AgentStateStore, Supervisor};
 use clawforge_supervisor::store::EventStore;
 
 use api::AppState;
+use archive::AgentArchive;
 use config::Config;
 
 #[derive(Parser)]
@@ -66,6 +79,31 @@ enum Commands {
         #[command(subcommand)]
         command: memory_cmd::MemoryCommands,
     },
+    /// Security posture checks
+    Security {
+        #[command(subcommand)]
+        command: security_cmd::SecurityCommands,
+    },
+    /// Install and list skills
+    Skills {
+        #[command(subcommand)]
+        command: skills_cmd::SkillsCommands,
+    },
+    /// Inspect the audit log
+    Audit {
+        #[command(subcommand)]
+        command: audit_cmd::AuditCommands,
+    },
+    /// Search, install, update and remove plugins
+    Plugin {
+        #[command(subcommand)]
+        command: plugin_cmd::PluginCommands,
+    },
+    /// Manage offline (Piper) TTS voices
+    Tts {
+        #[command(subcommand)]
+        command: tts_cmd::TtsCommands,
+    },
 }
 
 #[tokio::main]
@@ -110,6 +148,21 @@ async fn main() -> Result<()> {
         Commands::Memory { command } => {
             memory_cmd::run(command).await?;
         }
+        Commands::Security { command } => {
+            security_cmd::run(command).await?;
+        }
+        Commands::Skills { command } => {
+            skills_cmd::run(command).await?;
+        }
+        Commands::Audit { command } => {
+            audit_cmd::run(command, &config).await?;
+        }
+        Commands::Plugin { command } => {
+            plugin_cmd::run(command).await?;
+        }
+        Commands::Tts { command } => {
+            tts_cmd::run(command).await?;
+        }
     }
 
     Ok(())
@@ -123,6 +176,15 @@ async fn run_server(config: Config) -> Result<()> {
         "Starting ClawForge runtime"
     );
 
+    // Agent, hook, security and gateway sections come from the config file.
+    let file_config = match clawforge_config::load_and_prepare(&clawforge_config::config_file_path(&clawforge_config::config_dir())).await {
+        Ok(file) => file,
+        Err(e) => {
+            debug!(error = %e, "No usable config file; using defaults");
+            clawforge_config::ClawForgeConfig::default()
+        }
+    };
+
     // Initialize event store
     let event_store = EventStore::open(&config.db_path)?;
     let supervisor = Arc::new(Supervisor::new(event_store));
@@ -132,8 +194,68 @@ async fn run_server(config: Config) -> Result<()> {
     let (broadcast_tx, _) = broadcast::channel(1024);
     supervisor.set_broadcast_tx(broadcast_tx.clone()).await;
 
+    // Ship security-relevant events to a SIEM when a sink is configured.
+    let mut siem_exporter = None;
+    if config.siem_file.is_some() || config.siem_syslog.is_some() {
+        let siem = clawforge_security::SiemExportConfig {
+            format: config.siem_format.as_deref().and_then(clawforge_security::SiemFormat::parse).unwrap_or_default(),
+            file: config.siem_file.as_ref().map(std::path::PathBuf::from),
+            syslog: config.siem_syslog.clone(),
+        };
+        let exporter = Arc::new(clawforge_security::SiemExporter::from_config(&siem)?);
+        siem_exporter = Some(Arc::clone(&exporter));
+        let mut events = broadcast_tx.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        let _ = exporter.export_event(&event).await;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!(missed, "SIEM exporter lagged; security events were skipped");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        info!(format = ?siem.format, "SIEM export enabled");
+    }
+    // Pairings and elevated-mode changes go to the tamper-evident audit log,
+    // and from there to the SIEM.
+    let audit = clawforge_security::AuditLog::open(&config.db_path)?
+        .with_signing_key(clawforge_security::load_or_create_signing_key(&config.audit_key_file())?);
+    let audit = Arc::new(match siem_exporter {
+        Some(exporter) => audit.with_siem(exporter),
+        None => audit,
+    });
+
+    // Forward selected events to user webhooks; failures are dead-lettered
+    // next to the event store.
+    let forwarder = match &config.forwarding_path {
+        Some(path) => {
+            let yaml = std::fs::read_to_string(path)?;
+            let dead_letters = clawforge_supervisor::DeadLetterQueue::open(&config.db_path)?;
+            let forwarder = Arc::new(clawforge_supervisor::EventForwarder::from_yaml(&yaml, dead_letters)?);
+            Arc::clone(&forwarder).spawn(broadcast_tx.subscribe());
+            info!(path = %path, "Event forwarding enabled");
+            Some(forwarder)
+        }
+        None => None,
+    };
+
+    // Keep the model catalog (and deprecation warnings for agents' models) current.
+    let models_in_use: Vec<String> = supervisor.list_agents().unwrap_or_default().into_iter().map(|a| a.llm_policy.model).collect();
+    let catalog = Arc::new(std::sync::RwLock::new(clawforge_tools::ModelCatalog::new()));
+    let catalog_sync = clawforge_tools::CatalogSync::from_env(Arc::clone(&catalog)).with_models_in_use(models_in_use);
+    Arc::new(catalog_sync).spawn(std::time::Duration::from_secs(6 * 60 * 60));
+
     // Initialize channel bus
     let mut bus = ClawBus::new();
+    // Wiring beyond the bus itself, for the topology endpoint.
+    let mut wiring = Topology::new();
+    let api_node = wiring.add_node(NodeKind::Component, "api", "HTTP API");
+    wiring.add_edge(&api_node, "channel:scheduler", Some("run"));
+    wiring.add_edge(&api_node, "channel:supervisor", Some("input, cancel"));
 
     // Initialize provider registry
     let mut registry = ProviderRegistry::new();
@@ -160,22 +282,430 @@ async fn run_server(config: Config) -> Result<()> {
     }
 
     let registry = Arc::new(registry);
+    // Providers in the gateway config are applied on every change to it;
+    // replaced clients finish their running requests before being released.
+    if let Some(path) = &config.gateway_config_path {
+        let reloader = clawforge_gateway::ConfigReloader::new(Arc::new(tokio::sync::RwLock::new(Default::default())))
+            .with_providers(Arc::clone(&registry));
+        if let Err(e) = reloader.watch(path).await {
+            error!(error = %e, "Gateway config reload unavailable");
+        }
+    }
+    // Phone calls come in through the gateway and go out through the
+    // `phone_call` tool; both need `talk.calls`.
+    let calls = match file_config.talk.as_ref().map(|talk| voice::voice_calls(talk, &registry, bus.supervisor_tx.clone())) {
+        Some(Ok(calls)) => calls,
+        Some(Err(e)) => {
+            error!(error = %e, "Voice calls unavailable");
+            None
+        }
+        None => None,
+    };
 
     // Wire up components
+    // Per-session prompt token breakdowns for the context endpoint.
+    let context_log = ContextLog::new();
+    // Token usage per session, for `/usage` footers on replies.
+    let costs = infra::CostTracker::new();
+    let usage_footer = infra::UsageFooter::new(costs.clone());
+    // Accounts linked into people; spend, approvals and DM access follow
+    // the person rather than the platform account.
+    let identities = Arc::new(clawforge_security::IdentityRegistry::open_default());
     let planner = LlmPlanner::new(
         registry,
         bus.executor_tx.clone(),
         bus.supervisor_tx.clone(),
         None, // Memory disabled in main CLI for now
+    )
+    .with_context_log(context_log.clone())
+    .with_context_limits(clawforge_planner::ContextLimitCache::open_default(Arc::clone(&catalog)))
+    .with_cost_tracker(costs.clone())
+    .with_identities(Arc::clone(&identities));
+    let planner = match config.principal_daily_budget_usd {
+        Some(usd) => planner.with_principal_budget(usd),
+        None => planner,
+    };
+    // Inter-run agent state shares the runtime DB.
+    let agent_state = match AgentStateStore::open(&config.db_path) {
+        Ok(store) => Some(Arc::new(store)),
+        Err(e) => {
+            error!(error = %e, "Agent state store unavailable");
+            None
+        }
+    };
+    // Sandboxes are shared by every session; SSH exec hosts are drivers
+    // that `/exec node <name>` routes a session's commands to.
+    let mut sandboxes = clawforge_sandbox::SandboxRegistry::new();
+    for entry in &config.exec_hosts {
+        // `validate` has already checked the entries parse.
+        let Ok((name, target)) = Config::parse_exec_host(entry) else { continue };
+        let transport = Arc::new(clawforge_sandbox::SshTransport::new(target));
+        sandboxes = sandboxes.with_remote_host(format!("ssh:{}", name), transport, clawforge_sandbox::ExecAllowlist::with_safe_defaults());
+        info!(host = %name, "Registered SSH exec host");
+    }
+    // Node hosts are reached over HTTP and double as `node:<id>` exec hosts;
+    // they are registered once they describe themselves.
+    let node_store = Arc::new(clawforge_companion::NodeStore::in_memory());
+    let node_transport = clawforge_companion::HttpNodeTransport::new(Arc::clone(&node_store));
+    let node_transport = match &config.node_token {
+        Some(token) => node_transport.with_token(token),
+        None => node_transport,
+    };
+    let nodes = Arc::new(clawforge_companion::NodeHostRegistry::new(node_transport).with_store(Arc::clone(&node_store)));
+    nodes.follow_approvals();
+    let mut node_urls = Vec::new();
+    for entry in &config.node_hosts {
+        let Ok((id, url)) = Config::parse_node_host(entry) else { continue };
+        let transport = Arc::new(clawforge_companion::NodeExecTransport::new(Arc::clone(&nodes), id.clone()));
+        sandboxes = sandboxes.with_remote_host(format!("node:{}", id), transport, clawforge_sandbox::ExecAllowlist::with_safe_defaults());
+        wiring.add_remote_node(&id, "executor");
+        node_urls.push((id, url));
+    }
+    let sandboxes = Arc::new(sandboxes);
+    {
+        let nodes = Arc::clone(&nodes);
+        tokio::spawn(async move {
+            for (id, url) in node_urls {
+                match nodes.transport().fetch_registration(&url).await {
+                    Ok(registration) if registration.node_id == id => nodes.register(registration).await,
+                    Ok(registration) => warn!(node = %id, reported = %registration.node_id, "Node host reported a different id"),
+                    Err(e) => warn!(node = %id, error = %e, "Node host unavailable"),
+                }
+            }
+        });
+    }
+    let slack_config = match (&config.slack_signing_secret, &config.slack_bot_token) {
+        (Some(secret), Some(token)) => Some(clawforge_channels::slack::SlackConfig {
+            signing_secret: secret.clone(),
+            bot_token: token.clone(),
+            webhook_path: config.slack_webhook_path.clone(),
+        }),
+        _ => None,
+    };
+    let matrix_config = match (&config.matrix_homeserver_url, &config.matrix_access_token, &config.matrix_user_id) {
+        (Some(hs), Some(token), Some(user)) => Some(clawforge_channels::matrix::MatrixConfig {
+            homeserver_url: hs.clone(),
+            access_token: token.clone(),
+            user_id: user.clone(),
+        }),
+        _ => None,
+    };
+    // Approval prompts are posted to the chat the run came from and resolved
+    // by `/approve` replies there, by buttons on Telegram and Discord, or
+    // through the gateway when it runs.
+    let slack_chat = slack_config.clone().map(|sc| Arc::new(clawforge_channels::slack::SlackAdapter::new(sc, bus.supervisor_tx.clone())));
+    let mut approval_chats = clawforge_channels::ChatApprovalNotifier::new();
+    if let Some(slack) = &slack_chat {
+        approval_chats = approval_chats.with_channel("slack", slack.clone());
+    }
+    if let Some(mc) = matrix_config.clone() {
+        let matrix = clawforge_channels::matrix::MatrixAdapter::new(mc, bus.supervisor_tx.clone());
+        approval_chats = approval_chats.with_channel("matrix", Arc::new(matrix));
+    }
+    // Telegram and Discord only run when `channels.<name>.agent` names the
+    // agent that answers them.
+    let telegram = match (&config.telegram_bot_token, file_config.channels.as_ref().and_then(|c| c.telegram.as_ref()?.agent.clone())) {
+        (Some(token), Some(agent)) => Some((token.clone(), agent)),
+        (Some(_), None) => {
+            warn!("TELEGRAM_BOT_TOKEN is set but channels.telegram.agent is not; Telegram stays off");
+            None
+        }
+        _ => None,
+    };
+    if let Some((token, _)) = &telegram {
+        let telegram = clawforge_channels::telegram::TelegramAdapter::new(token.clone());
+        approval_chats = approval_chats.with_buttons("telegram", Arc::new(telegram));
+    }
+    let discord = match (&config.discord_bot_token, file_config.channels.as_ref().and_then(|c| c.discord.as_ref()?.agent.clone())) {
+        (Some(token), Some(agent)) => Some((token.clone(), agent)),
+        (Some(_), None) => {
+            warn!("DISCORD_BOT_TOKEN is set but channels.discord.agent is not; Discord stays off");
+            None
+        }
+        _ => None,
+    };
+    if let Some((token, _)) = &discord {
+        let discord = clawforge_channels::discord::DiscordAdapter::new(token.clone());
+        approval_chats = approval_chats.with_buttons("discord", Arc::new(discord));
+    }
+    let approvals = Arc::new(clawforge_security::ApprovalBroker::default().with_notifier(Arc::new(approval_chats)));
+    let approval_sink = clawforge_channels::approval_sink(Arc::clone(&approvals));
+    // Agent file writes, per session, for `/undo`.
+    let edits = Arc::new(clawforge_tools::EditJournal::new());
+    // Sub-agent sessions, for `/subagents`; finished ones are dropped after a day.
+    let subagents = Arc::new(clawforge_acp::SubAgentRegistry::new());
+    {
+        let subagents = Arc::clone(&subagents);
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                tick.tick().await;
+                subagents.gc(24 * 3600).await;
+            }
+        });
+    }
+    let content_guard = clawforge_security::ExternalContentGuard::new(clawforge_security::ContentPolicy::from_config(
+        config.external_content_policy.as_deref(),
+    ));
+    // One browser per agent, launched on first use with its own profile.
+    let browsers = Arc::new(clawforge_browser::SessionPool::new(
+        clawforge_browser::ProfileStore::default_location(),
+        std::time::Duration::from_secs(15 * 60),
+    ));
+    {
+        let browsers = Arc::clone(&browsers);
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(std::time::Duration::from_secs(60));
+            loop {
+                tick.tick().await;
+                match browsers.close_idle().await {
+                    Ok(closed) if !closed.is_empty() => info!(agents = closed.len(), "Closed idle browsers"),
+                    Ok(_) => {}
+                    Err(e) => error!(error = %e, "Closing idle browsers failed"),
+                }
+            }
+        });
+    }
+    // Tool allow/deny lists per agent and per channel from `agents.*.tools`.
+    let tool_policy = clawforge_core::ToolPolicyEngine::from_agents_config(
+        &serde_json::to_value(file_config.agents.clone().unwrap_or_default()).unwrap_or_default(),
     );
+    let executor = Executor::new(bus.supervisor_tx.clone())
+        .with_planner(bus.planner_tx.clone())
+        .with_tool_policy(Arc::new(tool_policy))
+        .with_approvals(Arc::clone(&approvals))
+        .with_edit_journal(Arc::clone(&edits))
+        .with_sandbox_usage(Arc::clone(&sandboxes), clawforge_sandbox::ResourceLimits::default())
+        .with_max_output_bytes(config.max_output_bytes)
+        .with_web_fetch(content_guard.clone())
+        .with_browser(Arc::clone(&browsers))
+        .with_downloads(
+            clawforge_tools::DownloadManager::new(clawforge_tools::DownloadManager::default_root()).with_guard(content_guard),
+        );
+    // `agents.defaults.sandbox.driver: native | seatbelt | appcontainer` runs
+    // the shell tool under the OS sandbox, writing only to the workspace.
+    let sandbox_settings = file_config.agents.as_ref().and_then(|a| a.defaults.as_ref()).and_then(|d| d.sandbox.as_ref());
+    let executor = match sandbox_settings.and_then(|s| s.driver.as_deref().map(|driver| (s, driver))) {
+        Some((settings, driver @ ("native" | "seatbelt" | "appcontainer"))) => match clawforge_sandbox::NativeDriver::parse(driver) {
+            Some(native) => {
+                let policy = clawforge_sandbox::NativeSandboxPolicy {
+                    writable_paths: config.workspace_dir.iter().map(std::path::PathBuf::from).collect(),
+                    allow_network: settings.network.as_deref() != Some("none"),
+                    ..Default::default()
+                };
+                let sandbox = clawforge_sandbox::NativeSandbox::new(native, policy);
+                if !sandbox.is_available() {
+                    warn!(driver = native.as_str(), "Native sandbox is not available; shell commands will fail");
+                }
+                executor.with_native_sandbox(sandbox)
+            }
+            None => anyhow::bail!("Sandbox driver '{}' has no native implementation on this OS; use docker or bwrap", driver),
+        },
+        _ => executor,
+    };
+    let executor = match agent_state.clone() {
+        Some(store) => executor.with_state_store(store),
+        None => executor,
+    };
+    // `validate` has already checked the connectors file parses.
+    let executor = match config.connectors_path.as_deref().map(std::fs::read_to_string) {
+        Some(Ok(yaml)) => match clawforge_tools::ConnectorSet::from_yaml(&yaml) {
+            Ok(connectors) => executor.with_connectors(Arc::new(connectors)),
+            Err(e) => {
+                error!(error = %e, "Data source connectors unavailable");
+                executor
+            }
+        },
+        _ => executor,
+    };
+    let executor = match &config.workspace_dir {
+        Some(dir) => executor.with_search_tools(dir).with_git_tool(dir),
+        None => executor,
+    };
+    // Writes wait for approval with a diff of the change.
+    let executor = if config.preview_writes { executor.with_write_previews() } else { executor };
+    let executor = match &config.python_sandbox {
+        Some(driver) => executor.with_python_tool(
+            driver.as_str(),
+            clawforge_sandbox::DockerSandboxConfig { image: "python:3.12-slim".to_string(), ..Default::default() },
+        ),
+        None => executor,
+    };
+    // Credentials agents lease for sandboxed commands, from `security.sandboxSecrets`.
+    let sandbox_secrets = file_config.security.as_ref().map(|s| &s.sandbox_secrets).into_iter().flatten();
+    let secrets = sandbox_secrets.fold(clawforge_sandbox::SecretBroker::new(), |broker, (name, entry)| {
+        let value = match &entry.from_env {
+            Some(var) => std::env::var(var).ok(),
+            None => entry.secret.clone(),
+        };
+        let Some(value) = value else {
+            warn!(secret = %name, "Sandbox secret has no value; not offered");
+            return broker;
+        };
+        let def = clawforge_sandbox::SecretDef::new(name, value)
+            .with_env_var(entry.env_var.clone().unwrap_or_else(|| name.clone()))
+            .with_agents(entry.agents.clone())
+            .with_ttl(std::time::Duration::from_secs(entry.ttl_secs.unwrap_or(60)));
+        broker.with_secret(def)
+    });
+    let executor = if secrets.names().is_empty() { executor } else { executor.with_sandbox_secrets(Arc::new(secrets)) };
+    // Agents with `can_make_http_requests` get the `http` tool; `validate`
+    // has already checked the spec loads.
+    let executor = match config.openapi_spec_path.as_deref().map(clawforge_tools::OpenApiSpec::load) {
+        Some(Ok(spec)) => executor.with_openapi_spec(Arc::new(spec)),
+        _ => executor.with_http_tool(),
+    };
+
+    // Desktop tools act only on nodes the owner granted them, through the
+    // gateway's node permissions.
+    let executor = executor.with_nodes(Arc::clone(&nodes), Arc::clone(&node_store));
+    // `validate` has already checked the scripts file parses.
+    let executor = match config.automation_scripts_path.as_deref().map(std::fs::read_to_string) {
+        Some(Ok(yaml)) => match clawforge_companion::ScriptAllowlist::from_yaml(&yaml) {
+            Ok(allowlist) => executor.with_mac_automation(Arc::new(allowlist)),
+            Err(e) => {
+                error!(error = %e, "Mac automations unavailable");
+                executor
+            }
+        },
+        _ => executor,
+    };
+
+    // `web_search` goes through `agents.defaults.webSearch`, or an agent's
+    // own `webSearch` providers in `agents.list`.
+    let web_search = file_config.agents.as_ref().map(|agents| -> Result<Option<clawforge_tools::SearchProviders>> {
+        let router = |defaults: &clawforge_config::schema::AgentDefaults| {
+            defaults.web_search.as_ref().map(|search| clawforge_tools::SearchRouter::from_json(&search.providers)).transpose()
+        };
+        let mut by_agent = Vec::new();
+        for (name, entry) in &agents.list {
+            if let Some(search) = router(&entry.defaults)? {
+                by_agent.push((name.clone(), search));
+            }
+        }
+        let default = match &agents.defaults {
+            Some(defaults) => router(defaults)?,
+            None => None,
+        };
+        if default.is_none() && by_agent.is_empty() {
+            return Ok(None);
+        }
+        let providers = clawforge_tools::SearchProviders::new(default.unwrap_or_default());
+        Ok(Some(by_agent.into_iter().fold(providers, |providers, (name, search)| providers.with_agent(name, search))))
+    });
+    let executor = match web_search.transpose().map(Option::flatten) {
+        Ok(Some(providers)) => executor.with_web_search(Arc::new(providers)),
+        Ok(None) => executor,
+        Err(e) => {
+            error!(error = %format!("{:#}", e), "Web search unavailable");
+            executor
+        }
+    };
 
-    let executor = Executor::new(bus.supervisor_tx.clone());
+    // Agents message each other only when `agents.agentToAgent` says who may
+    // reach whom.
+    let executor = match file_config.agents.as_ref().and_then(|a| a.agent_to_agent.clone()) {
+        Some(agent_to_agent) => {
+            let supervisor = Arc::clone(&supervisor);
+            let directory: clawforge_executor::AgentDirectory = Arc::new(move || {
+                supervisor.list_agents().unwrap_or_default().into_iter().map(|agent| (agent.name, agent.id)).collect()
+            });
+            executor.with_agent_messages(clawforge_tools::AgentMessagePolicy::new(agent_to_agent.allow), directory, bus.scheduler_tx.clone())
+        }
+        None => executor,
+    };
+
+    let agents: clawforge_scheduler::AgentLookup = {
+        let supervisor = Arc::clone(&supervisor);
+        Arc::new(move |id: &str| {
+            supervisor.list_agents().ok()?.into_iter().find(|agent| agent.id.to_string() == id || agent.name == id)
+        })
+    };
+    let agent_runs = agent_runs::AgentRuns::new(Arc::clone(&agents), bus.planner_tx.clone(), broadcast_tx.clone());
+    // `fanout` runs its tasks as the agent named in
+    // `agents.defaults.subagents.agent`.
+    let executor = match file_config.agents.as_ref().and_then(|a| a.defaults.as_ref()?.subagents.as_ref()?.agent.clone()) {
+        Some(agent) => {
+            let runner = subagents::AgentRunner::new(agent, agent_runs.clone());
+            let orchestrator = clawforge_acp::Orchestrator::new(Arc::clone(&subagents), Arc::new(runner));
+            executor.with_tool(Arc::new(clawforge_acp::FanoutTool::new(Arc::new(orchestrator))))
+        }
+        None => executor,
+    };
+
+    let (executor, calls) = match calls {
+        Some(voice::VoiceCalls { bridge, twilio_webhook, stream_url, tool }) => {
+            (executor.with_tool(Arc::new(tool)), Some((bridge, twilio_webhook, stream_url)))
+        }
+        None => (executor, None),
+    };
+
+    // Artifacts are served by the API below at `/artifacts/{id}`.
+    let artifacts = Arc::new(clawforge_tools::ArtifactStore::new());
+    let public_url = config.public_url.clone().unwrap_or_else(|| format!("http://localhost:{}", config.port));
+    // Generated attachments are served at `/media/{name}` behind signed links
+    // and uploaded natively to the chat the run came from.
+    let media_store = Arc::new(media::MediaStore::new(
+        media::MediaStore::default_dir(),
+        format!("{}/media", public_url.trim_end_matches('/')),
+        clawforge_security::generate_session_token(),
+    ));
+    let mut media_relay = clawforge_channels::ChatMediaRelay::new(media_store.dir());
+    if let Some(slack) = &slack_chat {
+        media_relay = media_relay.with_channel("slack", slack.clone());
+    }
+    let executor = executor.with_outbound_media(Arc::new(media_relay));
+    // Generated images go to the media store and are uploaded like any
+    // other attachment.
+    let executor = match file_config.media.as_ref().and_then(|m| m.generation.clone()) {
+        Some(generation) if !generation.providers.is_empty() => {
+            let policy = clawforge_tools::ImageGenPolicy { agents: generation.agents };
+            // The executor binds the tool to the calling agent on each call.
+            let tool = clawforge_tools::ImageGenTool::new("", Arc::clone(&media_store), policy).with_cost_tracker(costs.clone());
+            let tool = generation.providers.into_iter().fold(tool, |tool, entry| {
+                match clawforge_tools::ImageProvider::from_settings(&entry.provider, entry.api_key, entry.model) {
+                    Ok(provider) => tool.with_provider(provider),
+                    Err(e) => {
+                        error!(error = %e, "Image provider unavailable");
+                        tool
+                    }
+                }
+            });
+            executor.with_image_generation(tool)
+        }
+        _ => executor,
+    };
+    // Media tools produce is size-checked, scanned and described before the
+    // session sees it; a misconfigured pipeline is left out entirely.
+    let executor = match media_intake::media_pipeline(file_config.media.as_ref(), file_config.talk.as_ref(), bus.supervisor_tx.clone()) {
+        Ok(pipeline) => {
+            if let Err(e) = pipeline.sweep_temp(std::time::Duration::from_secs(3600)) {
+                warn!(error = %e, "Could not sweep staged media");
+            }
+            executor.with_media_pipeline(Arc::new(pipeline))
+        }
+        Err(e) => {
+            error!(error = %format!("{:#}", e), "Media pipeline unavailable");
+            executor
+        }
+    };
+    let artifact_tool = clawforge_tools::ArtifactTool::new(Arc::clone(&artifacts), public_url);
+    let executor = executor.with_artifacts(match &config.mermaid_renderer {
+        Some(renderer) => artifact_tool.with_mermaid_renderer(renderer),
+        None => artifact_tool,
+    });
 
     let scheduler = Scheduler::new(
         vec![], // No agents registered yet — Phase 2 adds dynamic registration
         bus.planner_tx.clone(),
         bus.supervisor_tx.clone(),
-    );
+    )
+    .with_activity_probe(Arc::new(clawforge_daemon::SystemActivityProbe));
+    // `validate` has already checked the zone loads.
+    let scheduler = match config.timezone.as_deref().map(Tz::load) {
+        Some(Ok(tz)) => scheduler.with_timezone(tz),
+        _ => scheduler,
+    };
 
     // Take receivers and start component tasks
     let scheduler_rx = bus.take_scheduler_rx().expect("scheduler rx already taken");
@@ -211,11 +741,19 @@ async fn run_server(config: Config) -> Result<()> {
 
     info!("All components started");
 
-    // Initialize endpoints
+    // Initialize endpoints. Adapters report their lifecycle into
+    // `adapter_status` for /status and /api/health.
+    let adapter_status = infra::AdapterStatusRegistry::new();
+    // DM gates share the gateway's pairing codes.
+    let pairing = Arc::new(clawforge_security::PairingStore::open_default(600));
+    let dm_gate = |channel: &str| {
+        let policy = clawforge_security::DmPolicy::from_mode(config.dm_policy.as_deref()?, config.dm_allow_from.clone());
+        let gate = clawforge_channels::DmGate::new(channel, policy, Arc::clone(&pairing)).with_identities(Arc::clone(&identities));
+        Some(Arc::new(gate))
+    };
     let mut bb_router = None;
     if let (Some(url), Some(password)) = (&config.bluebubbles_server_url, &config.bluebubbles_password) {
         use clawforge_channels::bluebubbles::{BlueBubblesAdapter, BlueBubblesConfig};
-        use clawforge_channels::ChannelAdapter;
         
         let bb_config = BlueBubblesConfig {
             server_url: url.clone(),
@@ -223,68 +761,269 @@ async fn run_server(config: Config) -> Result<()> {
             webhook_path: config.bluebubbles_webhook_path.clone(),
         };
         
-        let bb_adapter = BlueBubblesAdapter::new(bb_config, bus.supervisor_tx.clone());
+        let reporter = adapter_status.reporter("bluebubbles");
+        let inbound_tx = clawforge_channels::status_relay(reporter.clone(), bus.supervisor_tx.clone());
+        let bb_adapter = BlueBubblesAdapter::new(bb_config, inbound_tx.clone()).with_status(reporter.clone());
         bb_router = Some(bb_adapter.build_router());
-        
-        let supervisor_tx_bb = bus.supervisor_tx.clone();
-        tokio::spawn(async move {
-            if let Err(e) = bb_adapter.start(supervisor_tx_bb).await {
-                error!("BlueBubbles adapter start failed: {}", e);
-            }
-        });
+        tokio::spawn(clawforge_channels::supervise(bb_adapter, inbound_tx, reporter));
+        wiring.add_adapter("bluebubbles", "supervisor");
         info!("Registered BlueBubbles channel adapter");
     }
 
     // Slack adapter
     let mut slack_router = None;
-    if let (Some(secret), Some(token)) = (&config.slack_signing_secret, &config.slack_bot_token) {
-        use clawforge_channels::slack::{SlackAdapter, SlackConfig};
-        use clawforge_channels::ChannelAdapter;
-        let sc = SlackConfig {
-            signing_secret: secret.clone(),
-            bot_token: token.clone(),
-            webhook_path: config.slack_webhook_path.clone(),
+    if let Some(sc) = slack_config {
+        use clawforge_channels::slack::SlackAdapter;
+        let reporter = adapter_status.reporter("slack");
+        let inbound_tx = clawforge_channels::status_relay(reporter.clone(), bus.supervisor_tx.clone());
+        let sa = SlackAdapter::new(sc, inbound_tx.clone())
+            .with_status(reporter.clone())
+            .with_approvals(Arc::clone(&approvals));
+        let sa = match dm_gate("slack") {
+            Some(gate) => sa.with_dm_gate(gate),
+            None => sa,
         };
-        let sa = SlackAdapter::new(sc, bus.supervisor_tx.clone());
         slack_router = Some(sa.build_router());
-        let sup_tx = bus.supervisor_tx.clone();
+        tokio::spawn(clawforge_channels::supervise(sa, inbound_tx, reporter));
+        wiring.add_adapter("slack", "supervisor");
+        info!("Registered Slack channel adapter");
+    }
+
+    // Matrix adapter
+    if let Some(mc) = matrix_config {
+        use clawforge_channels::matrix::MatrixAdapter;
+        let reporter = adapter_status.reporter("matrix");
+        let inbound_tx = clawforge_channels::status_relay(reporter.clone(), bus.supervisor_tx.clone());
+        let ma = MatrixAdapter::new(mc, inbound_tx.clone())
+            .with_status(reporter.clone())
+            .with_approvals(Arc::clone(&approvals));
+        tokio::spawn(clawforge_channels::supervise(ma, inbound_tx, reporter));
+        wiring.add_adapter("matrix", "supervisor");
+        info!("Registered Matrix channel adapter");
+    }
+
+    // Telegram adapter. Messages run as `channels.telegram.agent`, one
+    // session per chat. Run buttons on code blocks in its replies ask the
+    // approval broker before anything reaches the sandbox; approval buttons
+    // only count from contacts the DM gate knows.
+    if let Some((token, agent)) = telegram {
+        use clawforge_channels::telegram::TelegramAdapter;
+        let reporter = adapter_status.reporter("telegram");
+        let inbound_tx = clawforge_channels::status_relay(reporter.clone(), bus.supervisor_tx.clone());
+        let runner = clawforge_channels::CodeRunner::new(Arc::clone(&sandboxes), Arc::clone(&approvals));
+        let ta = TelegramAdapter::new(token)
+            .with_agent(Arc::new(agent_runs::ChannelAgent::new(agent, agent_runs.clone())))
+            .with_code_runner(Arc::new(runner))
+            .with_approval_sink(approval_sink.clone());
+        let ta = match dm_gate("telegram") {
+            Some(gate) => ta.with_dm_gate(gate),
+            None => ta,
+        };
+        tokio::spawn(clawforge_channels::supervise(ta, inbound_tx, reporter));
+        wiring.add_adapter("telegram", "supervisor");
+        info!("Registered Telegram channel adapter");
+    }
+
+    // Discord adapter. Messages run as `channels.discord.agent`, one session
+    // per channel.
+    if let Some((token, agent)) = discord {
+        use clawforge_channels::discord::DiscordAdapter;
+        let reporter = adapter_status.reporter("discord");
+        let inbound_tx = clawforge_channels::status_relay(reporter.clone(), bus.supervisor_tx.clone());
+        let da = DiscordAdapter::new(token)
+            .with_agent(Arc::new(agent_runs::ChannelAgent::new(agent, agent_runs.clone())))
+            .with_approval_sink(approval_sink.clone());
+        let da = match dm_gate("discord") {
+            Some(gate) => da.with_dm_gate(gate),
+            None => da,
+        };
+        tokio::spawn(clawforge_channels::supervise(da, inbound_tx, reporter));
+        wiring.add_adapter("discord", "supervisor");
+        info!("Registered Discord channel adapter");
+    }
+
+    // Cron run log shares the runtime DB; retention runs hourly.
+    let run_log = match RunLog::open(&config.db_path) {
+        Ok(log) => Some(Arc::new(std::sync::Mutex::new(log))),
+        Err(e) => {
+            error!(error = %e, "Cron run log unavailable");
+            None
+        }
+    };
+    if let Some(log) = run_log.clone() {
+        let policy = RetentionPolicy::default();
         tokio::spawn(async move {
-            if let Err(e) = sa.start(sup_tx).await {
-                error!("Slack adapter failed: {}", e);
+            let mut tick = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                tick.tick().await;
+                let result = log.lock().map(|l| l.apply_retention(&policy));
+                if let Ok(Err(e)) = result {
+                    error!(error = %e, "Cron run log retention failed");
+                }
             }
         });
-        info!("Registered Slack channel adapter");
     }
 
-    // Matrix adapter
-    if let (Some(hs), Some(token), Some(user)) = (
-        &config.matrix_homeserver_url,
-        &config.matrix_access_token,
-        &config.matrix_user_id,
-    ) {
-        use clawforge_channels::matrix::{MatrixAdapter, MatrixConfig};
-        use clawforge_channels::ChannelAdapter;
-        let mc = MatrixConfig {
-            homeserver_url: hs.clone(),
-            access_token: token.clone(),
-            user_id: user.clone(),
+    // Cron jobs fire from the store; each delivery passes the post-message
+    // hooks, which wrap it in the notification template when one is set.
+    let hooks = clawforge_hooks::HookRegistry::new();
+    // Off until `/hooks trace on`; traces are served by the gateway.
+    let hook_tracer = Arc::new(clawforge_hooks::HookTracer::new());
+    if let Some(source) = &config.notification_template {
+        // `validate` has already checked the template.
+        if let Ok(hook) = clawforge_hooks::NotificationTemplateHook::new(source) {
+            hooks.register(clawforge_hooks::HookPhase::PostMessage, Arc::new(hook)).await;
+            wiring.add_hook("notification_template", "scheduler", "post_message");
+        }
+    }
+    // Registered last so it scrubs deliveries after any template has wrapped
+    // them; the runtime's own credentials are caught verbatim.
+    let leak_guard = [&config.openrouter_api_key, &config.slack_bot_token, &config.telegram_bot_token, &config.discord_bot_token, &config.bluebubbles_password, &config.matrix_access_token, &config.node_token]
+        .into_iter()
+        .flatten()
+        .fold(clawforge_hooks::SecretLeakHook::new(clawforge_hooks::LeakAction::Redact).with_gateway_token_from_env(), |hook, secret| {
+            hook.with_known_secret(secret)
+        });
+    let leak_guard = Arc::new(leak_guard);
+    hooks.register(clawforge_hooks::HookPhase::PostMessage, leak_guard.clone()).await;
+    hooks.register(clawforge_hooks::HookPhase::AfterToolCall, leak_guard).await;
+    wiring.add_hook("secret_leak", "scheduler", "post_message");
+    // Script hooks run only what the operator's exec allowlist allows.
+    let exec_approvals = file_config.security.as_ref().and_then(|s| s.exec_approvals.as_ref());
+    let allowlist = match exec_approvals.and_then(|e| e.allowlist_path.as_deref()) {
+        Some(path) => clawforge_sandbox::ExecAllowlist::load(std::path::Path::new(path)).await.unwrap_or_else(|e| {
+            error!(error = %e, "Exec allowlist unavailable; using the safe defaults");
+            clawforge_sandbox::ExecAllowlist::with_safe_defaults()
+        }),
+        None => clawforge_sandbox::ExecAllowlist::with_safe_defaults(),
+    };
+    let hook_deps = hooks::HookDeps { sandboxes: Arc::clone(&sandboxes), allowlist };
+    if let Some(installed) = file_config.hooks.as_ref().map(|h| &h.installed) {
+        hooks::register_installed(&hooks, installed, &hook_deps, &mut wiring).await;
+    }
+    let cron = clawforge_scheduler::CronRunner::new(config.db_path.clone(), agents, bus.planner_tx.clone(), broadcast_tx.clone())
+        .with_hooks(clawforge_hooks::HookPipeline::new(hooks).with_tracer(Arc::clone(&hook_tracer)))
+        .with_usage_footer(usage_footer.clone());
+    let cron = match config.timezone.as_deref().map(Tz::load) {
+        Some(Ok(tz)) => cron.with_timezone(tz),
+        _ => cron,
+    };
+    let cron = match run_log.clone() {
+        Some(log) => cron.with_run_log(log),
+        None => cron,
+    };
+    tokio::spawn(cron.run());
+
+    // Archived agents are purged hourly once their grace period ends.
+    let archive = Arc::new(AgentArchive::new(Arc::clone(&supervisor), agent_state.clone(), config.db_path.clone()));
+    {
+        let archive = Arc::clone(&archive);
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                tick.tick().await;
+                match archive.purge_due(chrono::Utc::now()) {
+                    Ok(purged) if !purged.is_empty() => info!(agents = purged.len(), "Purged archived agents"),
+                    Ok(_) => {}
+                    Err(e) => error!(error = %e, "Archived agent purge failed"),
+                }
+            }
+        });
+    }
+
+    // The gateway serves pairing, approvals, nodes and the control UI on its
+    // own port, sharing the runtime's stores.
+    if let Some(port) = config.gateway_port {
+        let state = clawforge_gateway::GatewayState::new(Arc::clone(&artifacts), Arc::clone(&approvals), Arc::clone(&node_store), adapter_status.clone())
+            .with_scheduler(bus.scheduler_tx.clone())
+            .with_run_events(broadcast_tx.clone())
+            .with_pairing(Arc::clone(&pairing))
+            .with_audit(Arc::clone(&audit))
+            .with_hook_tracer(Arc::clone(&hook_tracer))
+            .with_config_sources(clawforge_config::ConfigSources::new(clawforge_config::config_file_path(&clawforge_config::config_dir())))
+            .with_sessions(Arc::new(clawforge_agent::SessionStore::new()), broadcast_tx.subscribe());
+        // Runtime events are kept queryable for the Control UI dashboard.
+        let state = match logging::EventStore::open(&config.db_path) {
+            Ok(events) => {
+                let events = Arc::new(events);
+                let retention = file_config.logging.as_ref().and_then(|l| l.event_retention_days);
+                event_log::spawn(Arc::clone(&events), retention, broadcast_tx.subscribe());
+                state.with_events(events)
+            }
+            Err(e) => {
+                error!(error = %format!("{:#}", e), "Agent event log disabled");
+                state
+            }
+        };
+        // The gateway log is rotated and shipped per `logging`, and streamed
+        // at the log endpoint.
+        let state = match clawforge_daemon::LogManager::for_gateway(file_config.logging.as_ref()) {
+            Ok(logs) => {
+                let logs = Arc::new(logs);
+                Arc::clone(&logs).spawn();
+                state.with_logs(logs)
+            }
+            Err(e) => {
+                error!(error = %format!("{:#}", e), "Gateway log management disabled");
+                state
+            }
         };
-        let ma = MatrixAdapter::new(mc, bus.supervisor_tx.clone());
-        let sup_tx = bus.supervisor_tx.clone();
+        let state = match calls {
+            Some((bridge, twilio_webhook, stream_url)) => state.with_calls(bridge, twilio_webhook, stream_url),
+            None => state,
+        };
+        // Peer gateways come from the config file's `gateway.federation`.
+        let state = match file_config.gateway.clone().and_then(|gateway| gateway.federation) {
+            Some(federation) => state.with_federation(federation),
+            None => state,
+        };
+        // Nodes found on the LAN wait in the gateway for approval.
+        clawforge_companion::MdnsBrowser::default().spawn(Arc::clone(&node_store), std::time::Duration::from_secs(60));
+        let addr: std::net::SocketAddr = format!("{}:{}", config.bind_address, port).parse()?;
         tokio::spawn(async move {
-            if let Err(e) = ma.start(sup_tx).await {
-                error!("Matrix adapter failed: {}", e);
+            if let Err(e) = clawforge_gateway::start_server(addr, state).await {
+                error!(error = %e, "Gateway failed");
             }
         });
-        info!("Registered Matrix channel adapter");
     }
 
+    // Slash commands sent through the API act on the runtime's own services.
+    let preferences = Arc::new(clawforge_security::PreferenceStore::open_default());
+    let commands = clawforge_commands::DispatcherBuilder::new()
+        .with_sandboxes(Arc::clone(&sandboxes))
+        .with_workspaces(Arc::new(clawforge_sandbox::WorkspaceManager::new(clawforge_sandbox::WorkspaceManager::default_root())))
+        .with_catalog(Arc::clone(&catalog))
+        .with_adapters(adapter_status.clone())
+        .with_identities(Arc::clone(&identities))
+        .with_preferences(Arc::clone(&preferences))
+        .with_audit(Arc::clone(&audit))
+        .with_hook_tracer(hook_tracer)
+        .with_subagents(Arc::clone(&subagents))
+        .with_edit_journal(edits)
+        .with_usage_footer(usage_footer)
+        .with_cron(config.db_path.clone(), match config.timezone.as_deref().map(Tz::load) {
+            Some(Ok(tz)) => tz,
+            _ => Tz::utc(),
+        })
+        .build();
+
     // Start HTTP API
     let app_state = Arc::new(AppState {
         supervisor: Arc::clone(&supervisor),
         broadcast_tx,
         scheduler_tx: bus.scheduler_tx.clone(),
         supervisor_tx: bus.supervisor_tx.clone(),
+        run_log,
+        agent_state,
+        bus: bus.probe(),
+        wiring,
+        archive,
+        context_log,
+        preferences,
+        forwarder,
+        artifacts,
+        adapter_status,
+        commands: Arc::new(commands),
     });
 
     // Merge all optional channel routers.
@@ -295,6 +1034,10 @@ async fn run_server(config: Config) -> Result<()> {
     if let Some(sr) = slack_router {
         app = app.merge(sr);
     }
+    // Sub-agent coordination, including `/api/acp/ws` for remote hosts.
+    let acp_hosts = config.acp_hosts.iter().filter_map(|entry| Config::parse_acp_host(entry).ok()).collect();
+    app = app.merge(clawforge_acp::build_acp_router(subagents::acp_state(subagents, acp_hosts)));
+    app = app.nest("/media", media::signed_media_router(media_store));
     let addr = format!("{}:{}", config.bind_address, config.port);
 
     info!(addr = %addr, "HTTP API listening");
@@ -0,0 +1,53 @@
+//! The media pipeline tool output goes through.
+//!
+//! Builds it from `media` (limits, antivirus scanner, image description) and
+//! `talk.stt` (audio transcription). Classes without a configured handler
+//! are rejected with a `MediaRejected` event rather than guessed at.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use clawforge_config::schema::{MediaConfig, TalkConfig};
+use clawforge_core::Message;
+use clawforge_tts::{create_stt, SttProviderKind};
+use media::image::VisionImageHandler;
+use media::{audio::SttHandler, DocumentHandler, MediaHandler, MediaLimits, MediaPayload, MediaPipeline, MediaScanner};
+use tokio::sync::mpsc;
+
+/// Refuses a media class nothing is configured to handle.
+struct Unconfigured(&'static str);
+
+#[async_trait]
+impl MediaHandler for Unconfigured {
+    async fn process(&self, payload: &MediaPayload) -> Result<String> {
+        bail!("no handler for {} ({}); configure {}", payload.mime_type, payload.source, self.0)
+    }
+}
+
+pub fn media_pipeline(config: Option<&MediaConfig>, talk: Option<&TalkConfig>, events: mpsc::Sender<Message>) -> Result<MediaPipeline> {
+    let audio: Box<dyn MediaHandler> = match talk.and_then(|t| t.stt.as_ref()) {
+        Some(stt) => Box::new(SttHandler::new(Arc::from(create_stt(SttProviderKind::from_settings(
+            &stt.provider,
+            stt.api_key.clone(),
+            stt.model.clone(),
+            stt.binary_path.as_ref().map(PathBuf::from),
+        )?)))),
+        None => Box::new(Unconfigured("talk.stt")),
+    };
+    let image: Box<dyn MediaHandler> = match config.and_then(|m| m.vision.as_ref()) {
+        Some(vision) => Box::new(VisionImageHandler::new(vision.api_key.clone(), vision.model.as_deref().unwrap_or("gpt-4o"))),
+        None => Box::new(Unconfigured("media.vision")),
+    };
+    let mut pipeline = MediaPipeline::new(audio, image, events).with_document_handler(Box::new(DocumentHandler::default()));
+    if let Some(limits) = config.and_then(|m| m.limits.clone()) {
+        let limits: MediaLimits = serde_json::from_value(limits).context("media.limits is invalid")?;
+        pipeline = pipeline.with_limits(limits);
+    }
+    if let Some(scanner) = config.and_then(|m| m.scanner.clone()) {
+        let scanner: MediaScanner = serde_json::from_value(scanner).context("media.scanner is invalid")?;
+        pipeline = pipeline.with_scanner(scanner);
+    }
+    Ok(pipeline)
+}
@@ -1,21 +1,44 @@
 //! CLI Models Command
 //!
-//! Lists available connected LLMs.
+//! Lists available LLMs from the model catalog, synced live from the
+//! providers whose API keys are set.
+
+use std::sync::{Arc, RwLock};
 
 use anyhow::Result;
+use clawforge_tools::{CatalogSync, ModelCatalog};
 
 pub async fn run() -> Result<()> {
-    println!("\n🧠 Configured LLM Providers & Models\n");
-    
-    // MOCK: Fetch from config or registry
-    
-    println!("Provider: OpenAI");
-    println!("  - gpt-4-turbo (Ctx: 128k, Price: $10/1M In)");
-    println!("  - gpt-3.5-turbo (Ctx: 16k, Price: $0.5/1M In)\n");
+    let sync = CatalogSync::from_env(Arc::new(RwLock::new(ModelCatalog::new())));
+    let report = sync.sync_once().await;
+    for (provider, error) in &report.errors {
+        println!("⚠️  Could not fetch {} models: {}", provider, error);
+    }
 
-    println!("Provider: Anthropic");
-    println!("  - claude-3-opus-20240229 (Ctx: 200k, Price: $15/1M In)");
-    println!("  - claude-3-haiku-20240307 (Ctx: 200k, Price: $0.25/1M In)\n");
+    println!("\n🧠 Available LLM Providers & Models\n");
+    let catalog = sync.catalog();
+    let catalog = catalog.read().unwrap();
+    let mut providers: Vec<&str> = catalog.list(None).iter().map(|m| m.provider.as_str()).collect();
+    providers.sort();
+    providers.dedup();
+    for provider in providers {
+        println!("Provider: {}", provider);
+        for model in catalog.list(Some(provider)) {
+            let mut line = format!("  - {}", model.id);
+            if model.context_window > 0 {
+                line.push_str(&format!(" (Ctx: {}k", model.context_window / 1000));
+                if let Some(pricing) = model.pricing {
+                    line.push_str(&format!(", Price: ${}/1M In", pricing.input_per_million));
+                }
+                line.push(')');
+            }
+            if model.deprecated {
+                line.push_str(" [deprecated]");
+            }
+            println!("{}", line);
+        }
+        println!();
+    }
 
     Ok(())
 }
@@ -0,0 +1,160 @@
+//! CLI Plugin Subcommands
+//!
+//! Searches the marketplace index (`plugins.index`), installs plugins from it
+//! or straight from a git/tarball URL, and records each install's source,
+//! version and checksum in `plugins.installed` so a setup can be reproduced.
+
+use anyhow::{anyhow, bail, Result};
+use clap::Subcommand;
+use clawforge_config::schema::PluginEntry;
+use clawforge_config::{config_dir, config_file_path, load_config, write_config, ClawForgeConfig};
+use clawforge_plugins::{compare_versions, InstalledPlugin, PluginIndex, PluginInstaller, PluginSource};
+use std::cmp::Ordering;
+
+#[derive(Subcommand)]
+pub enum PluginCommands {
+    /// Search the plugin index
+    Search {
+        /// Matched against id, name and description; empty lists everything
+        #[arg(default_value = "")]
+        query: String,
+        /// Index URL or file (defaults to plugins.index)
+        #[arg(long)]
+        index: Option<String>,
+    },
+    /// Install a plugin by index id, or from `git+<url>[#tag]`, a tarball URL or a directory
+    Install {
+        plugin: String,
+        /// Git tag to pin when installing from a git URL
+        #[arg(long)]
+        tag: Option<String>,
+        /// Expected SHA-256 of the plugin tree
+        #[arg(long)]
+        sha256: Option<String>,
+        /// Index URL or file (defaults to plugins.index)
+        #[arg(long)]
+        index: Option<String>,
+    },
+    /// Update installed plugins to the latest release in the index
+    Update {
+        /// Only update this plugin
+        id: Option<String>,
+        /// Index URL or file (defaults to plugins.index)
+        #[arg(long)]
+        index: Option<String>,
+    },
+    /// Remove an installed plugin
+    Remove { id: String },
+}
+
+fn looks_like_source(plugin: &str) -> bool {
+    plugin.contains("://") || plugin.starts_with("git+") || plugin.starts_with('.') || plugin.starts_with('/')
+}
+
+async fn fetch_index(config: &ClawForgeConfig, flag: Option<String>) -> Result<PluginIndex> {
+    let location = flag
+        .or_else(|| config.plugins.as_ref().and_then(|p| p.index.clone()))
+        .ok_or_else(|| anyhow!("No plugin index configured; set plugins.index or pass --index"))?;
+    PluginIndex::fetch(&location).await
+}
+
+fn record(config: &mut ClawForgeConfig, installed: &InstalledPlugin) {
+    let plugins = config.plugins.get_or_insert_with(Default::default);
+    let entry = PluginEntry {
+        id: installed.id.clone(),
+        source: Some(installed.source.to_string()),
+        version: Some(installed.version.clone()),
+        checksum: Some(installed.checksum.clone()),
+    };
+    match plugins.installed.iter_mut().find(|e| e.id == installed.id) {
+        Some(existing) => *existing = entry,
+        None => plugins.installed.push(entry),
+    }
+}
+
+fn installed_version<'a>(config: &'a ClawForgeConfig, id: &str) -> Option<&'a str> {
+    config.plugins.as_ref()?.installed.iter().find(|e| e.id == id)?.version.as_deref()
+}
+
+pub async fn run(cmd: PluginCommands) -> Result<()> {
+    let dir = config_dir();
+    let path = config_file_path(&dir);
+    let mut config = load_config(&path).await?;
+    let installer = PluginInstaller::new(dir.join("plugins"));
+
+    match cmd {
+        PluginCommands::Search { query, index } => {
+            let index = fetch_index(&config, index).await?;
+            let matches = index.search(&query);
+            if matches.is_empty() {
+                println!("No plugins match '{}'.", query);
+            }
+            for entry in matches {
+                let installed = installed_version(&config, &entry.id).map(|v| format!(" (installed {})", v)).unwrap_or_default();
+                println!("{:<24} {:<10} {}{}", entry.id, entry.version, entry.description, installed);
+            }
+        }
+        PluginCommands::Install { plugin, tag, sha256, index } => {
+            let (source, expected) = if looks_like_source(&plugin) {
+                let source = match (PluginSource::parse(&plugin), tag) {
+                    (PluginSource::Git { url, .. }, Some(tag)) => PluginSource::Git { url, tag: Some(tag) },
+                    (_, Some(_)) => bail!("--tag only applies to git sources"),
+                    (source, None) => source,
+                };
+                (source, sha256)
+            } else {
+                let index = fetch_index(&config, index).await?;
+                let entry = index.get(&plugin).ok_or_else(|| anyhow!("Plugin '{}' is not in the index", plugin))?;
+                (entry.plugin_source(), Some(entry.sha256.clone()))
+            };
+            if expected.is_none() {
+                println!("Warning: no --sha256 given; the checksum below is recorded but was not verified.");
+            }
+            let installed = installer.install_from_source(&source, expected.as_deref(), false).await?;
+            record(&mut config, &installed);
+            write_config(&config, &path).await?;
+            println!("Installed {} {} → {}", installed.id, installed.version, installed.path.display());
+            println!("Source:   {}", installed.source);
+            println!("SHA-256:  {}", installed.checksum);
+        }
+        PluginCommands::Update { id, index } => {
+            let index = fetch_index(&config, index).await?;
+            let ids: Vec<String> = match id {
+                Some(id) => vec![id],
+                None => config.plugins.as_ref().map(|p| p.installed.iter().map(|e| e.id.clone()).collect()).unwrap_or_default(),
+            };
+            if ids.is_empty() {
+                println!("No plugins installed.");
+            }
+            let mut updated = 0;
+            for id in ids {
+                let current = installed_version(&config, &id).ok_or_else(|| anyhow!("Plugin '{}' is not installed", id))?.to_string();
+                let Some(entry) = index.get(&id) else {
+                    println!("{:<24} not in the index; skipped", id);
+                    continue;
+                };
+                if compare_versions(&entry.version, &current) != Ordering::Greater {
+                    println!("{:<24} {} is up to date", id, current);
+                    continue;
+                }
+                let installed = installer.install_from_source(&entry.plugin_source(), Some(&entry.sha256), true).await?;
+                record(&mut config, &installed);
+                updated += 1;
+                println!("{:<24} {} → {}", id, current, installed.version);
+            }
+            if updated > 0 {
+                write_config(&config, &path).await?;
+            }
+        }
+        PluginCommands::Remove { id } => {
+            installer.uninstall(&id)?;
+            if let Some(plugins) = config.plugins.as_mut() {
+                plugins.installed.retain(|e| e.id != id);
+                plugins.disabled.retain(|d| d != &id);
+            }
+            write_config(&config, &path).await?;
+            println!("Removed {}", id);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! CLI Security Subcommands
+//!
+//! Prints the security posture report and applies safe automatic fixes.
+
+use anyhow::Result;
+use clap::Subcommand;
+use clawforge_config::{config_dir, config_file_path, load_config, write_config, ClawForgeConfig};
+use clawforge_security::{load_skill_sources, security_posture, PostureInput};
+
+#[derive(Subcommand)]
+pub enum SecurityCommands {
+    /// Audit channels, config, tools, skills and gateway auth
+    Audit {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        /// Apply automatic fixes to the config file
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+pub async fn run(cmd: SecurityCommands) -> Result<()> {
+    match cmd {
+        SecurityCommands::Audit { json, fix } => {
+            let dir = config_dir();
+            let path = config_file_path(&dir);
+            let config = load_config(&path).await?;
+            let input = PostureInput {
+                config: serde_json::to_value(&config)?,
+                skills: load_skill_sources(&dir.join("workspace").join("skills")),
+                gateway_api_key: std::env::var("CLAWFORGE_API_KEY").ok(),
+            };
+            let posture = security_posture(&input);
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&posture)?);
+            } else if posture.total_findings == 0 {
+                println!("No security findings.");
+            } else {
+                for group in &posture.groups {
+                    println!("{:?} ({})", group.severity, group.findings.len());
+                    for f in &group.findings {
+                        println!("  [{}] {} — {} ({})", f.finding.code, f.finding.title, f.finding.description, f.source);
+                        if let Some(fix) = &f.suggested_fix {
+                            let how = if fix.automatic { "auto-fix" } else { "fix" };
+                            println!("      {}: {}", how, fix.action);
+                        }
+                    }
+                }
+                println!();
+                println!("{}", if posture.passed { "PASSED" } else { "FAILED: high or critical findings present" });
+            }
+
+            if fix {
+                let mut value = input.config;
+                let applied: Vec<_> = posture.apply_auto_fixes(&mut value).into_iter().filter(|r| r.applied).collect();
+                if applied.is_empty() {
+                    println!("No automatic fixes to apply.");
+                } else {
+                    let fixed: ClawForgeConfig = serde_json::from_value(value)?;
+                    write_config(&fixed, &path).await?;
+                    for result in applied {
+                        println!("Applied {}: {}", result.finding_code, result.description);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
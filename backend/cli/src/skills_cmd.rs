@@ -0,0 +1,105 @@
+//! CLI Skills Subcommands
+//!
+//! Installs skills with signature verification against the trusted publisher
+//! keys in `security.trustedSkillKeys`, and lists what is installed.
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use clawforge_config::{config_dir, config_file_path, load_config};
+use clawforge_security::{SignatureStatus, TrustedKey};
+use clawforge_tools::{SkillInstaller, SkillSource};
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum SkillsCommands {
+    /// Install a skill from a URL, `github:owner/repo[/subdir]` or a local directory
+    Install {
+        source: String,
+        /// Skill name; defaults to the last path segment of the source
+        #[arg(long)]
+        name: Option<String>,
+        /// Install even if the skill is unsigned or signed by an untrusted key
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+    /// List installed skills and their signature status
+    List,
+    /// Remove an installed skill
+    Remove { name: String },
+}
+
+fn parse_source(source: &str) -> SkillSource {
+    if let Some(rest) = source.strip_prefix("github:") {
+        let mut parts = rest.splitn(3, '/');
+        let owner = parts.next().unwrap_or_default();
+        let repo = parts.next().unwrap_or_default();
+        return SkillSource::GitHub { repo: format!("{}/{}", owner, repo), subdir: parts.next().map(str::to_string) };
+    }
+    if source.starts_with("http://") || source.starts_with("https://") {
+        return SkillSource::Url(source.to_string());
+    }
+    SkillSource::Local(PathBuf::from(source))
+}
+
+fn default_name(source: &str) -> String {
+    let trimmed = source.trim_end_matches('/');
+    let last = trimmed.rsplit(['/', ':']).next().unwrap_or(trimmed);
+    last.trim_end_matches(".tar.gz").trim_end_matches(".tgz").to_string()
+}
+
+fn describe(status: &SignatureStatus) -> String {
+    match status {
+        SignatureStatus::Verified { key_id, publisher } => match publisher {
+            Some(p) => format!("verified ({}, key {})", p, key_id),
+            None => format!("verified (key {})", key_id),
+        },
+        SignatureStatus::Unsigned => "UNSIGNED".to_string(),
+        SignatureStatus::UntrustedKey { key_id } => format!("UNTRUSTED key {}", key_id),
+        SignatureStatus::Invalid { reason } => format!("INVALID: {}", reason),
+    }
+}
+
+pub async fn run(cmd: SkillsCommands) -> Result<()> {
+    let dir = config_dir();
+    let config = load_config(&config_file_path(&dir)).await?;
+    let trusted = config
+        .security
+        .as_ref()
+        .map(|s| s.trusted_skill_keys.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|k| TrustedKey::from_hex(&k.id, k.publisher.clone(), &k.public_key))
+        .collect::<Result<Vec<_>>>()?;
+    let installer = SkillInstaller::new(dir.join("workspace").join("skills")).with_trusted_keys(trusted);
+
+    match cmd {
+        SkillsCommands::Install { source, name, allow_unsigned } => {
+            let name = name.unwrap_or_else(|| default_name(&source));
+            if name.is_empty() {
+                bail!("Could not derive a skill name from '{}'; pass --name", source);
+            }
+            let result = installer.with_allow_unsigned(allow_unsigned).install(&name, parse_source(&source)).await?;
+            println!("Installed {} → {}", result.name, result.path.display());
+            println!("Signature: {}", describe(&result.verification.status));
+        }
+        SkillsCommands::List => {
+            let registry = installer.registry().await?;
+            let installed = installer.list_installed().await?;
+            if installed.is_empty() {
+                println!("No skills installed.");
+            }
+            for name in installed {
+                let status = registry
+                    .get(&name)
+                    .map(|r| describe(&r.verification.status))
+                    .unwrap_or_else(|| "not in registry (installed manually)".to_string());
+                println!("{:<24} {}", name, status);
+            }
+        }
+        SkillsCommands::Remove { name } => {
+            installer.uninstall(&name).await?;
+            println!("Removed {}", name);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Sub-agents in serve: the runner behind the `fanout` tool and the ACP
+//! routes for remote sub-agent hosts.
+//!
+//! Each fan-out task runs as a plan request for the agent named in
+//! `agents.defaults.subagents.agent`, in the task's sub-agent session, and
+//! its outcome is matched back like any `AgentRuns` run.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clawforge_acp::{AcpHosts, AcpServerState, SubAgentAnnouncement, SubAgentRegistry, SubAgentRunner};
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::agent_runs::AgentRuns;
+
+/// Runs sub-agent tasks as plan requests for one agent.
+pub struct AgentRunner {
+    agent: String,
+    runs: AgentRuns,
+}
+
+impl AgentRunner {
+    pub fn new(agent: String, runs: AgentRuns) -> Self {
+        Self { agent, runs }
+    }
+}
+
+#[async_trait]
+impl SubAgentRunner for AgentRunner {
+    async fn run(&self, session_id: Uuid, prompt: &str) -> Result<String> {
+        let context = serde_json::json!({
+            "trigger": "subagent",
+            "prompt": prompt,
+            "session_id": session_id.to_string(),
+            "session_key": format!("subagent:{}", session_id),
+        });
+        debug!(%session_id, agent = %self.agent, "Sub-agent task queued");
+        self.runs.run(&self.agent, context).await
+    }
+}
+
+/// State for the ACP routes. Hosts in `host_tokens` may dial
+/// `/api/acp/ws`; announcements from sub-agents are logged.
+pub fn acp_state(registry: Arc<SubAgentRegistry>, host_tokens: HashMap<String, String>) -> AcpServerState {
+    let (announce_tx, mut announcements) = mpsc::channel::<SubAgentAnnouncement>(256);
+    tokio::spawn(async move {
+        while let Some(ann) = announcements.recv().await {
+            info!(session = %ann.session_id, status = ?ann.status, message = ?ann.message, "Sub-agent announcement");
+        }
+    });
+    AcpServerState { registry, announce_tx, hosts: AcpHosts::new(host_tokens) }
+}
@@ -0,0 +1,72 @@
+//! CLI TTS Subcommands
+//!
+//! Manages the Piper voice models used by the offline `piper` TTS provider.
+//! Voices live in `talk.voicesDir`, or the data dir when that is unset.
+
+use anyhow::Result;
+use clap::Subcommand;
+use clawforge_config::{config_dir, config_file_path, load_config};
+use clawforge_tts::{voice_url, PiperVoices, DEFAULT_PIPER_VOICE};
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum TtsCommands {
+    /// List installed Piper voices
+    Voices,
+    /// Download a Piper voice, e.g. `en_US-lessac-medium`
+    Install {
+        #[arg(default_value = DEFAULT_PIPER_VOICE)]
+        name: String,
+    },
+    /// Install a voice from a local `.onnx` file (its `.onnx.json` must sit beside it)
+    Import {
+        model: PathBuf,
+        /// Voice name; defaults to the file name without `.onnx`
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Remove an installed voice
+    Remove { name: String },
+}
+
+pub async fn run(cmd: TtsCommands) -> Result<()> {
+    let config = load_config(&config_file_path(&config_dir())).await?;
+    let voices = match config.talk.as_ref().and_then(|t| t.voices_dir.as_deref()) {
+        Some(dir) => PiperVoices::new(dir),
+        None => PiperVoices::default_location(),
+    };
+
+    match cmd {
+        TtsCommands::Voices => {
+            let installed = voices.list()?;
+            if installed.is_empty() {
+                println!("No Piper voices in {}.", voices.dir().display());
+                println!("Install one with `clawforge tts install {}`.", DEFAULT_PIPER_VOICE);
+                return Ok(());
+            }
+            println!("{:<32} {:<8} {:>8}", "VOICE", "LANG", "RATE");
+            for voice in installed {
+                println!(
+                    "{:<32} {:<8} {:>8}",
+                    voice.name,
+                    voice.language.as_deref().unwrap_or("-"),
+                    voice.sample_rate
+                );
+            }
+        }
+        TtsCommands::Install { name } => {
+            println!("Downloading {}", voice_url(&name)?);
+            let voice = voices.install(&name).await?;
+            println!("Installed {} → {}", voice.name, voice.model.display());
+        }
+        TtsCommands::Import { model, name } => {
+            let voice = voices.import(&model, name.as_deref()).await?;
+            println!("Imported {} → {}", voice.name, voice.model.display());
+        }
+        TtsCommands::Remove { name } => {
+            voices.remove(&name).await?;
+            println!("Removed {}", name);
+        }
+    }
+    Ok(())
+}
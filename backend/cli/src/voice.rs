@@ -0,0 +1,100 @@
+//! Phone calls answered by a model.
+//!
+//! Builds the call bridge, the Twilio answer-webhook verifier and the
+//! `phone_call` tool from `talk.calls`.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use clawforge_channels::{SignatureScheme, WebhookVerifier};
+use clawforge_config::schema::TalkConfig;
+use clawforge_core::{LlmProvider, LlmRequest, Message};
+use clawforge_planner::providers::ProviderRegistry;
+use clawforge_tts::{
+    create_stt, create_tts, CallAgent, CallBridge, CallInfo, PhoneCallTool, SttProviderKind, TtsProviderKind, TwilioCalls,
+};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+const CALL_PROMPT: &str = "You are talking to someone on the phone. Your reply is read aloud, \
+so answer in a few short spoken sentences without Markdown, lists or links.";
+
+/// Answers each caller turn with one completion from the configured model.
+struct ModelCallAgent {
+    provider: Arc<dyn LlmProvider>,
+    model: String,
+}
+
+#[async_trait]
+impl CallAgent for ModelCallAgent {
+    async fn respond(&self, _call: &CallInfo, transcript: &str, reply: mpsc::Sender<String>) -> Result<()> {
+        let request = LlmRequest {
+            model: self.model.clone(),
+            system_prompt: CALL_PROMPT.to_string(),
+            user_prompt: transcript.to_string(),
+            max_tokens: 300,
+            temperature: 0.7,
+        };
+        let response = self.provider.complete(&request).await?;
+        reply.send(response.content).await?;
+        Ok(())
+    }
+}
+
+/// Everything serve needs to take and place calls.
+pub struct VoiceCalls {
+    pub bridge: Arc<CallBridge>,
+    pub twilio_webhook: WebhookVerifier,
+    /// Where answered calls stream their media, under `talk.calls.publicUrl`.
+    pub stream_url: String,
+    pub tool: PhoneCallTool,
+}
+
+/// Voice calls from `talk.calls`, or `None` when they are not configured.
+pub fn voice_calls(talk: &TalkConfig, registry: &ProviderRegistry, events: mpsc::Sender<Message>) -> Result<Option<VoiceCalls>> {
+    let Some(calls) = &talk.calls else { return Ok(None) };
+    if calls.public_url.trim().is_empty() {
+        bail!("talk.calls needs publicUrl");
+    }
+    let stt = talk.stt.as_ref().context("talk.calls needs talk.stt")?;
+    let stt = create_stt(SttProviderKind::from_settings(
+        &stt.provider,
+        stt.api_key.clone(),
+        stt.model.clone(),
+        stt.binary_path.as_ref().map(PathBuf::from),
+    )?);
+    let tts = create_tts(TtsProviderKind::from_settings(
+        talk.provider.as_deref().context("talk.calls needs talk.provider")?,
+        talk.api_key.clone(),
+        talk.voice.clone(),
+        talk.binary_path.as_ref().map(PathBuf::from),
+        talk.voices_dir.as_ref().map(PathBuf::from),
+    )?);
+    let provider = registry
+        .get_providers(std::slice::from_ref(&calls.provider))
+        .into_iter()
+        .next()
+        .with_context(|| format!("talk.calls provider '{}' is not registered", calls.provider))?;
+    let agent = ModelCallAgent { provider, model: calls.model.clone() };
+
+    let mut bridge = CallBridge::new(Arc::from(stt), Arc::from(tts), Arc::new(agent)).with_events(events, Uuid::nil());
+    if let Some(greeting) = &calls.greeting {
+        bridge = bridge.with_greeting(greeting);
+    }
+    if let Some(token) = &calls.livekit_token {
+        bridge = bridge.with_stream_token(token);
+    }
+    let bridge = Arc::new(bridge);
+
+    let origin = calls.public_url.trim_end_matches('/');
+    let twilio_webhook = WebhookVerifier::new(
+        "twilio",
+        SignatureScheme::Twilio { auth_token: calls.auth_token.clone(), url: format!("{}/voice/twilio/answer", origin) },
+    );
+    let stream_url = format!("{}/voice/twilio/stream", origin.replacen("https://", "wss://", 1));
+    let twilio = Arc::new(TwilioCalls::new(calls.account_sid.clone(), calls.auth_token.clone()));
+    let tool = PhoneCallTool::new(twilio, Arc::clone(&bridge), calls.from_number.clone(), stream_url.clone());
+    Ok(Some(VoiceCalls { bridge, twilio_webhook, stream_url, tool }))
+}
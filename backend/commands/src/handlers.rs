@@ -5,8 +5,18 @@
 /// the appropriate ClawForge subsystems (executor, session manager, etc.).
 use anyhow::Result;
 use async_trait::async_trait;
+use std::sync::Arc;
 use tracing::info;
 
+use clawforge_acp::{SubAgentNode, SubAgentRegistry, SubAgentStatus};
+use clawforge_hooks::{HookTracer, TraceMode, TraceOutcome};
+use clawforge_sandbox::{SandboxRegistry, WorkspaceManager};
+use clawforge_scheduler::cron_store::CronStore;
+use clawforge_scheduler::{RunLog, Tz};
+use clawforge_security::{new_event, AccountRef, AuditLog, IdentityRegistry, PreferenceStore, PREFERENCE_KEYS, SESSION_PREFERENCE_KEYS};
+use clawforge_tools::{EditJournal, ModelCatalog};
+use infra::{AdapterStatusRegistry, UsageFooter, UsageMode};
+
 use crate::dispatch::{CommandContext, CommandHandler, CommandResponse};
 use crate::registry::CommandRegistry;
 use crate::types::CommandInvocation;
@@ -35,15 +45,24 @@ impl CommandHandler for HelpHandler {
 // /status
 // ---------------------------------------------------------------------------
 
-pub struct StatusHandler;
+pub struct StatusHandler {
+    pub adapters: AdapterStatusRegistry,
+}
 
 #[async_trait]
 impl CommandHandler for StatusHandler {
     async fn handle(&self, ctx: &CommandContext, _inv: &CommandInvocation) -> Result<CommandResponse> {
-        Ok(CommandResponse::ephemeral(format!(
+        let mut lines = vec![format!(
             "✅ Session `{}` on channel `{}` — agent is running",
             ctx.session_id, ctx.channel
-        )))
+        )];
+        let adapters = self.adapters.snapshot();
+        if !adapters.is_empty() {
+            let now = chrono::Utc::now();
+            lines.push("*Channels:*".to_string());
+            lines.extend(adapters.iter().map(|a| format!("• {}", a.summary(now))));
+        }
+        Ok(CommandResponse::ephemeral(lines.join("\n")))
     }
 }
 
@@ -99,12 +118,18 @@ impl CommandHandler for StopHandler {
 // /reset
 // ---------------------------------------------------------------------------
 
-pub struct ResetHandler;
+pub struct ResetHandler {
+    /// Where the session's Python interpreter runs, if anywhere.
+    pub sandboxes: Option<Arc<SandboxRegistry>>,
+}
 
 #[async_trait]
 impl CommandHandler for ResetHandler {
     async fn handle(&self, ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
         info!("[Commands] Reset session {}", ctx.session_id);
+        if let Some(sandboxes) = &self.sandboxes {
+            sandboxes.remove(&clawforge_tools::PythonTool::sandbox_session(&ctx.session_id)).await?;
+        }
         let suffix = if inv.raw_args.is_empty() {
             String::new()
         } else {
@@ -145,6 +170,53 @@ impl CommandHandler for ModelHandler {
     }
 }
 
+// ---------------------------------------------------------------------------
+// /models
+// ---------------------------------------------------------------------------
+
+pub struct ModelsHandler {
+    pub catalog: Arc<std::sync::RwLock<ModelCatalog>>,
+}
+
+#[async_trait]
+impl CommandHandler for ModelsHandler {
+    async fn handle(&self, _ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
+        let catalog = self.catalog.read().unwrap();
+        let Some(provider) = inv.args.first() else {
+            let mut counts: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+            for model in catalog.list(None) {
+                *counts.entry(model.provider.as_str()).or_default() += 1;
+            }
+            let lines: Vec<String> = counts.iter().map(|(p, n)| format!("• {} ({} models)", p, n)).collect();
+            return Ok(CommandResponse::ephemeral(format!(
+                "🧠 Providers:\n{}\nUse /models <provider> to list models.",
+                lines.join("\n")
+            )));
+        };
+        let models = catalog.list(Some(provider));
+        if models.is_empty() {
+            return Ok(CommandResponse::ephemeral(format!("❌ No models known for `{}`", provider)));
+        }
+        let lines: Vec<String> = models
+            .iter()
+            .map(|m| {
+                let mut line = format!("• `{}`", m.id);
+                if m.context_window > 0 {
+                    line.push_str(&format!(" · {}k ctx", m.context_window / 1000));
+                }
+                if let Some(p) = m.pricing {
+                    line.push_str(&format!(" · ${:.2}/${:.2} per 1M in/out", p.input_per_million, p.output_per_million));
+                }
+                if m.deprecated {
+                    line.push_str(" · ⚠️ deprecated");
+                }
+                line
+            })
+            .collect();
+        Ok(CommandResponse::ephemeral(format!("🧠 {} models:\n{}", provider, lines.join("\n"))))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // /verbose, /reasoning
 // ---------------------------------------------------------------------------
@@ -159,16 +231,83 @@ impl CommandHandler for ToggleHandler {
     }
 }
 
+/// `/elevated on|off|ask|full`, recorded in the audit log so every change of
+/// mode is attributable.
+pub struct ElevatedHandler {
+    pub audit: Option<Arc<AuditLog>>,
+}
+
+#[async_trait]
+impl CommandHandler for ElevatedHandler {
+    async fn handle(&self, ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
+        let mode = inv.args.first().map(|s| s.as_str()).unwrap_or("on");
+        if !matches!(mode, "on" | "off" | "ask" | "full") {
+            return Ok(CommandResponse::ephemeral("Usage: `/elevated on|off|ask|full`"));
+        }
+        if let Some(audit) = &self.audit {
+            let mut event = new_event(&ctx.channel, &ctx.sender_id, &format!("elevated_mode_{}", mode));
+            event.detail = serde_json::json!({ "session": ctx.session_id, "mode": mode });
+            audit.record(event).await?;
+        }
+        Ok(CommandResponse::ephemeral(format!("🔧 Elevated set to `{}`", mode)))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // /kill, /steer, /subagents
 // ---------------------------------------------------------------------------
 
-pub struct SubagentHandler;
+pub struct SubagentHandler {
+    /// The runtime's sub-agent tree; `None` when sub-agents are not configured.
+    pub registry: Option<Arc<SubAgentRegistry>>,
+}
+
+impl SubagentHandler {
+    fn render(node: &SubAgentNode, indent: usize, lines: &mut Vec<String>) {
+        let session = &node.session;
+        let icon = match session.status {
+            SubAgentStatus::Starting | SubAgentStatus::Running => "⏳",
+            SubAgentStatus::AwaitingPermission => "🔐",
+            SubAgentStatus::Completed => "✅",
+            SubAgentStatus::Failed => "❌",
+            SubAgentStatus::Cancelled => "⏹️",
+        };
+        let prompt: String = session.prompt.chars().take(60).collect();
+        let result = session
+            .result
+            .as_deref()
+            .map(|r| format!(" — {}", r.chars().take(60).collect::<String>()))
+            .unwrap_or_default();
+        let id = session.session_id.to_string();
+        lines.push(format!("{}{} `{}` {}{}", "  ".repeat(indent), icon, &id[..8], prompt, result));
+        for child in &node.children {
+            Self::render(child, indent + 1, lines);
+        }
+    }
+
+    async fn list(&self, registry: &SubAgentRegistry) -> CommandResponse {
+        let tree = registry.tree().await;
+        if tree.is_empty() {
+            return CommandResponse::ephemeral("No sub-agents.");
+        }
+        let mut lines = vec!["*Sub-agents:*".to_string()];
+        for root in &tree {
+            Self::render(root, 0, &mut lines);
+        }
+        CommandResponse::ephemeral(lines.join("\n"))
+    }
+}
 
 #[async_trait]
 impl CommandHandler for SubagentHandler {
     async fn handle(&self, ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
+        let Some(registry) = &self.registry else {
+            return Ok(CommandResponse::ephemeral("Sub-agents are not configured."));
+        };
         let action = inv.args.first().map(|s| s.as_str()).unwrap_or("list");
+        if inv.key == "subagents" && action == "list" {
+            return Ok(self.list(registry).await);
+        }
         info!("[Commands] Subagent '{}' in session {}", action, ctx.session_id);
         Ok(CommandResponse::ephemeral(format!("🤖 Subagent action `{}` queued", action)))
     }
@@ -210,3 +349,456 @@ impl CommandHandler for TtsHandler {
         }
     }
 }
+
+// ---------------------------------------------------------------------------
+// /usage
+// ---------------------------------------------------------------------------
+
+pub struct UsageHandler {
+    pub footer: UsageFooter,
+}
+
+#[async_trait]
+impl CommandHandler for UsageHandler {
+    async fn handle(&self, ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
+        let Some(arg) = inv.args.first() else {
+            let mode = self.footer.mode(&ctx.session_id).await;
+            return Ok(CommandResponse::ephemeral(format!(
+                "📊 Usage footer: `{}` (off, tokens, cost or full)",
+                mode.as_str()
+            )));
+        };
+        if arg == "subagents" {
+            return Ok(CommandResponse::ephemeral(match self.footer.subagent_report(&ctx.session_id).await {
+                Some(report) => format!("📊 {}", report),
+                None => "📊 No sub-agent usage in this session.".to_string(),
+            }));
+        }
+        let Some(mode) = UsageMode::parse(arg) else {
+            return Ok(CommandResponse::ephemeral(format!(
+                "❌ Unknown usage mode `{}`. Valid: off, tokens, cost, full, subagents", arg
+            )));
+        };
+        self.footer.set_mode(&ctx.session_id, mode).await;
+        info!("[Commands] Usage footer for session {} set to {}", ctx.session_id, mode.as_str());
+        Ok(CommandResponse::ephemeral(match mode {
+            UsageMode::Off => "📊 Usage footer off".to_string(),
+            _ => format!("📊 Replies will end with a `{}` usage footer", mode.as_str()),
+        }))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// /link
+// ---------------------------------------------------------------------------
+
+pub struct LinkHandler {
+    pub identities: Arc<IdentityRegistry>,
+}
+
+#[async_trait]
+impl CommandHandler for LinkHandler {
+    async fn handle(&self, ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
+        let account = AccountRef::new(&ctx.channel, &ctx.sender_id);
+        let text = match inv.args.first().map(String::as_str) {
+            None => {
+                let code = self.identities.begin_link(account);
+                format!(
+                    "🔗 Send `/link {}` from your other account within {} minutes to link it to this one.",
+                    code,
+                    self.identities.code_ttl_secs / 60
+                )
+            }
+            Some("list") => match self.identities.person_for(&ctx.channel, &ctx.sender_id) {
+                Some(person) => {
+                    let accounts: Vec<String> = person.accounts.iter().map(|a| format!("• `{}`", a.key())).collect();
+                    format!("🔗 Linked accounts ({}):\n{}", person.id, accounts.join("\n"))
+                }
+                None => "🔗 This account is not linked to any other.".to_string(),
+            },
+            Some("remove") => {
+                if self.identities.unlink(&account) {
+                    "🔗 This account is no longer linked.".to_string()
+                } else {
+                    "🔗 This account is not linked to any other.".to_string()
+                }
+            }
+            Some("suggestions") => {
+                let suggestions = self.identities.suggest_links();
+                if suggestions.is_empty() {
+                    "🔗 No link suggestions.".to_string()
+                } else {
+                    let lines: Vec<String> = suggestions
+                        .iter()
+                        .map(|s| format!("• `{}` ↔ `{}` ({})", s.a.key(), s.b.key(), s.reason))
+                        .collect();
+                    format!("🔗 Possible links:\n{}", lines.join("\n"))
+                }
+            }
+            Some(code) => match self.identities.complete_link(code, &account) {
+                Ok(person) => {
+                    info!("[Commands] {} linked to {}", account.key(), person.id);
+                    format!("🔗 Linked. {} accounts now share memory, budgets and permissions.", person.accounts.len())
+                }
+                Err(e) => format!("❌ {}", e),
+            },
+        };
+        Ok(CommandResponse::ephemeral(text))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// /prefs
+// ---------------------------------------------------------------------------
+
+pub struct PrefsHandler {
+    pub identities: Arc<IdentityRegistry>,
+    pub preferences: Arc<PreferenceStore>,
+}
+
+#[async_trait]
+impl CommandHandler for PrefsHandler {
+    async fn handle(&self, ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
+        let principal = self.identities.principal(&ctx.channel, &ctx.sender_id);
+        let result = match inv.args.first().map(String::as_str) {
+            None => {
+                let prefs = serde_json::to_value(self.preferences.get(&principal))?;
+                let lines: Vec<String> = PREFERENCE_KEYS
+                    .iter()
+                    .map(|key| match &prefs[*key] {
+                        serde_json::Value::Null => format!("• {}: —", key),
+                        serde_json::Value::String(text) => format!("• {}: {}", key, text),
+                        other => format!("• {}: {}", key, other),
+                    })
+                    .collect();
+                return Ok(CommandResponse::ephemeral(format!("⚙️ Preferences:\n{}", lines.join("\n"))));
+            }
+            Some("set") if inv.args.len() >= 3 => {
+                self.preferences.set(&principal, &inv.args[1], &inv.args[2..].join(" ")).map(|_| format!("⚙️ {} set", inv.args[1]))
+            }
+            Some("unset") if inv.args.len() == 2 => {
+                self.preferences.unset(&principal, &inv.args[1]).map(|_| format!("⚙️ {} cleared", inv.args[1]))
+            }
+            Some("clear") => {
+                self.preferences.clear(&principal);
+                Ok("⚙️ All preferences cleared".to_string())
+            }
+            // Voice overrides for this session only, on top of the person's own.
+            Some("session") if inv.args.len() >= 4 && inv.args[1] == "set" => self
+                .preferences
+                .set_for_session(&ctx.session_id, &inv.args[2], &inv.args[3..].join(" "))
+                .map(|_| format!("⚙️ {} set for this session", inv.args[2])),
+            Some("session") if inv.args.len() == 3 && inv.args[1] == "unset" => self
+                .preferences
+                .unset_for_session(&ctx.session_id, &inv.args[2])
+                .map(|_| format!("⚙️ {} cleared for this session", inv.args[2])),
+            _ => Ok(format!(
+                "Usage: /prefs [set <key> <value> | unset <key> | clear | session set|unset <key> [value]]. Keys: {}; per session: {}",
+                PREFERENCE_KEYS.join(", "),
+                SESSION_PREFERENCE_KEYS.join(", ")
+            )),
+        };
+        Ok(CommandResponse::ephemeral(match result {
+            Ok(text) => {
+                info!("[Commands] Preferences for {} updated", principal);
+                text
+            }
+            Err(e) => format!("❌ {}", e),
+        }))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// /sandbox
+// ---------------------------------------------------------------------------
+
+pub struct SandboxHandler {
+    pub workspaces: Arc<WorkspaceManager>,
+}
+
+#[async_trait]
+impl CommandHandler for SandboxHandler {
+    async fn handle(&self, ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
+        let action = inv.args.first().map(|s| s.as_str()).unwrap_or("list");
+        let value = inv.args.get(1).map(|s| s.trim()).filter(|s| !s.is_empty());
+        match action {
+            "snapshot" => {
+                let snap = self.workspaces.snapshot(&ctx.session_id, value).await?;
+                Ok(CommandResponse::ok(format!(
+                    "📸 Workspace snapshot `{}` saved ({} KB)",
+                    snap.id,
+                    snap.size_bytes.div_ceil(1024)
+                )))
+            }
+            "restore" => {
+                let Some(id) = value else {
+                    return Ok(CommandResponse::ephemeral("❌ Usage: /sandbox restore <snapshot-id>"));
+                };
+                self.workspaces.restore(&ctx.session_id, id).await?;
+                Ok(CommandResponse::ok(format!("⏪ Workspace restored to `{}`", id)))
+            }
+            "list" => {
+                let snaps = self.workspaces.snapshots(&ctx.session_id).await?;
+                if snaps.is_empty() {
+                    return Ok(CommandResponse::ephemeral("No workspace snapshots yet. Use /sandbox snapshot [label]."));
+                }
+                let mut lines = vec!["*Workspace snapshots:*".to_string()];
+                for snap in snaps {
+                    let label = snap.label.map(|l| format!(" — {}", l)).unwrap_or_default();
+                    lines.push(format!("• `{}`{}", snap.id, label));
+                }
+                Ok(CommandResponse::ephemeral(lines.join("\n")))
+            }
+            other => Ok(CommandResponse::ephemeral(format!(
+                "❌ Unknown sandbox action `{}`. Valid: snapshot, restore, list", other
+            ))),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// /exec
+// ---------------------------------------------------------------------------
+
+pub struct ExecHandler {
+    pub sandboxes: Arc<SandboxRegistry>,
+}
+
+impl ExecHandler {
+    /// Remote hosts are registered as `node:<id>` / `ssh:<name>` drivers.
+    fn remote_driver(&self, target: &str) -> Option<String> {
+        let names = self.sandboxes.driver_names();
+        [format!("node:{}", target), format!("ssh:{}", target)]
+            .into_iter()
+            .find(|name| names.contains(&name.as_str()))
+    }
+
+    fn remote_hosts(&self) -> Vec<String> {
+        self.sandboxes
+            .driver_names()
+            .into_iter()
+            .filter(|name| name.starts_with("node:") || name.starts_with("ssh:"))
+            .map(|name| format!("`{}`", name.split_once(':').map_or(name, |(_, host)| host)))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl CommandHandler for ExecHandler {
+    async fn handle(&self, ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
+        // `target` comes after `security` and `ask`; typed as `/exec node <host>`
+        // it lands in the `security` slot instead.
+        let target = inv
+            .args
+            .get(3)
+            .or_else(|| inv.args.get(1).filter(|s| !matches!(s.as_str(), "deny" | "allowlist" | "full")))
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty());
+        match inv.args.first().map(|s| s.as_str()) {
+            None => {
+                let host = self.sandboxes.exec_host(&ctx.session_id).unwrap_or_else(|| "gateway".into());
+                Ok(CommandResponse::ephemeral(format!("Exec host: `{}`", host)))
+            }
+            Some("gateway") => {
+                self.sandboxes.set_exec_host(&ctx.session_id, None)?;
+                Ok(CommandResponse::ok("🖥️ Commands now run on the gateway"))
+            }
+            Some("sandbox") => {
+                self.sandboxes.set_exec_host(&ctx.session_id, Some("docker"))?;
+                Ok(CommandResponse::ok("📦 Commands now run in a Docker sandbox"))
+            }
+            Some("node") => {
+                let hosts = self.remote_hosts();
+                let Some(target) = target else {
+                    return Ok(CommandResponse::ephemeral(format!(
+                        "❌ Usage: /exec node <host>. Registered: {}",
+                        if hosts.is_empty() { "none".to_string() } else { hosts.join(", ") }
+                    )));
+                };
+                let Some(driver) = self.remote_driver(target) else {
+                    return Ok(CommandResponse::ephemeral(format!(
+                        "❌ Unknown host `{}`. Registered: {}",
+                        target,
+                        if hosts.is_empty() { "none".to_string() } else { hosts.join(", ") }
+                    )));
+                };
+                self.sandboxes.set_exec_host(&ctx.session_id, Some(&driver))?;
+                info!(session = %ctx.session_id, host = %driver, "Exec host changed");
+                Ok(CommandResponse::ok(format!("🛰️ Commands now run on `{}`", driver)))
+            }
+            Some(other) => Ok(CommandResponse::ephemeral(format!(
+                "❌ Unknown exec host `{}`. Valid: sandbox, gateway, node", other
+            ))),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// /undo
+// ---------------------------------------------------------------------------
+
+pub struct UndoHandler {
+    pub edits: Arc<EditJournal>,
+}
+
+#[async_trait]
+impl CommandHandler for UndoHandler {
+    async fn handle(&self, ctx: &CommandContext, _inv: &CommandInvocation) -> Result<CommandResponse> {
+        match self.edits.undo(&ctx.session_id).await? {
+            None => Ok(CommandResponse::ephemeral("Nothing to undo")),
+            Some(edit) => {
+                info!(session = %ctx.session_id, path = %edit.path.display(), "Agent edit reverted");
+                let what = if edit.previous.is_some() { "Restored" } else { "Removed" };
+                Ok(CommandResponse::ok(format!("↩️ {} `{}`", what, edit.path.display())))
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// /cron
+// ---------------------------------------------------------------------------
+
+pub struct CronHandler {
+    pub db_path: String,
+    /// Zone for jobs that don't set their own.
+    pub timezone: Tz,
+}
+
+impl CronHandler {
+    fn list(&self) -> Result<CommandResponse> {
+        let jobs = CronStore::open(&self.db_path)?.list_enabled()?;
+        if jobs.is_empty() {
+            return Ok(CommandResponse::ephemeral("No scheduled jobs."));
+        }
+        let now = chrono::Utc::now();
+        let mut lines = vec!["*Scheduled jobs:*".to_string()];
+        for job in jobs {
+            let line = match job.timezone_or(&self.timezone) {
+                Ok(tz) => {
+                    let next = match job.next_fire(now, &self.timezone) {
+                        Ok(Some(at)) => tz.format(at),
+                        Ok(None) => "never".to_string(),
+                        Err(e) => format!("invalid schedule ({})", e),
+                    };
+                    format!("• `{}` `{}` ({}) — next {}", job.id, job.schedule, tz.name(), next)
+                }
+                Err(e) => format!("• `{}` `{}` — {}", job.id, job.schedule, e),
+            };
+            lines.push(line);
+        }
+        Ok(CommandResponse::ephemeral(lines.join("\n")))
+    }
+
+    fn runs(&self, job_id: &str) -> Result<CommandResponse> {
+        let runs = RunLog::open(&self.db_path)?.recent(job_id, 10)?;
+        if runs.is_empty() {
+            return Ok(CommandResponse::ephemeral(format!("No runs recorded for `{}`.", job_id)));
+        }
+        let mut lines = vec![format!("*Recent runs of `{}`:*", job_id)];
+        for run in runs {
+            let when = run.fired_at_local.clone().unwrap_or_else(|| run.local_time(&Tz::utc()));
+            let duration = run.duration_ms.map(|ms| format!(" ({} ms)", ms)).unwrap_or_default();
+            let error = run.error.map(|e| format!(" — {}", e)).unwrap_or_default();
+            lines.push(format!("• {} {}{}{}", when, run.status, duration, error));
+        }
+        Ok(CommandResponse::ephemeral(lines.join("\n")))
+    }
+}
+
+#[async_trait]
+impl CommandHandler for CronHandler {
+    async fn handle(&self, _ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
+        match inv.args.first().map(|s| s.as_str()).unwrap_or("list") {
+            "list" => self.list(),
+            "runs" => match inv.args.get(1).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                Some(job_id) => self.runs(job_id),
+                None => Ok(CommandResponse::ephemeral("❌ Usage: /cron runs <job-id>")),
+            },
+            other => Ok(CommandResponse::ephemeral(format!(
+                "❌ Unknown cron action `{}`. Valid: list, runs", other
+            ))),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// /hooks
+// ---------------------------------------------------------------------------
+
+pub struct HooksHandler {
+    /// The hook pipeline's tracer; `None` when no pipeline is traced.
+    pub tracer: Option<Arc<HookTracer>>,
+}
+
+impl HooksHandler {
+    fn status(tracer: &HookTracer) -> CommandResponse {
+        let mode = match tracer.mode() {
+            TraceMode::Off => "off",
+            TraceMode::On => "on",
+            TraceMode::DryRun => "dry-run",
+        };
+        let runs = tracer.runs();
+        let recent = if runs.is_empty() {
+            "none".to_string()
+        } else {
+            runs.iter().take(5).map(|id| format!("`{}`", id)).collect::<Vec<_>>().join(", ")
+        };
+        CommandResponse::ephemeral(format!("Hook tracing: {}\nRecent traces: {}", mode, recent))
+    }
+
+    fn show(tracer: &HookTracer, run_id: &str) -> CommandResponse {
+        let Some(trace) = tracer.trace(run_id) else {
+            return CommandResponse::ephemeral(format!("No hook trace for `{}`.", run_id));
+        };
+        let mut lines = vec![format!("*Hooks for `{}`:*", run_id)];
+        for eval in trace {
+            let outcome = match eval.outcome {
+                TraceOutcome::Skipped => "skipped (no match)",
+                TraceOutcome::Passed => "passed",
+                TraceOutcome::Modified => "modified",
+                TraceOutcome::Aborted => "aborted",
+                TraceOutcome::Errored => "errored",
+            };
+            let reason = eval.reason.map(|r| format!(" — {}", r)).unwrap_or_default();
+            let changed: Vec<String> = eval.changes.iter().map(|c| c.field.clone()).collect();
+            let changed = if changed.is_empty() { String::new() } else { format!(" [{}]", changed.join(", ")) };
+            lines.push(format!(
+                "• {:?} `{}` {} ({:.1} ms){}{}",
+                eval.phase, eval.hook, outcome, eval.duration_ms, changed, reason
+            ));
+        }
+        CommandResponse::ephemeral(lines.join("\n"))
+    }
+}
+
+#[async_trait]
+impl CommandHandler for HooksHandler {
+    async fn handle(&self, ctx: &CommandContext, inv: &CommandInvocation) -> Result<CommandResponse> {
+        match inv.args.first().map(|s| s.as_str()) {
+            Some("trace") => {}
+            None => return Ok(CommandResponse::ephemeral("❌ Usage: /hooks trace [on|off|dry-run|<run-id>]")),
+            Some(other) => {
+                return Ok(CommandResponse::ephemeral(format!("❌ Unknown hooks action `{}`. Valid: trace", other)))
+            }
+        }
+        let Some(tracer) = &self.tracer else {
+            return Ok(CommandResponse::ephemeral("Hook tracing is not configured."));
+        };
+        let Some(arg) = inv.args.get(1).map(|s| s.trim()).filter(|s| !s.is_empty()) else {
+            return Ok(Self::status(tracer));
+        };
+        match TraceMode::parse(arg) {
+            Some(mode) => {
+                tracer.set_mode(mode);
+                info!(session = %ctx.session_id, mode = ?mode, "Hook tracing changed");
+                Ok(CommandResponse::ok(match mode {
+                    TraceMode::Off => "🪝 Hook tracing off",
+                    TraceMode::On => "🪝 Hook tracing on — /hooks trace <run-id> to inspect",
+                    TraceMode::DryRun => "🪝 Hook dry-run on — hooks are traced but nothing is blocked or rewritten",
+                }))
+            }
+            None => Ok(Self::show(tracer, arg)),
+        }
+    }
+}
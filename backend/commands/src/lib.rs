@@ -7,36 +7,168 @@ pub mod types;
 pub use detection::detect_command;
 pub use dispatch::{CommandContext, CommandDispatcher, CommandHandler, CommandResponse};
 pub use handlers::{
-    CompactHandler, HelpHandler, ModelHandler, ResetHandler, SkillHandler,
+    CompactHandler, CronHandler, ElevatedHandler, ExecHandler, HelpHandler, HooksHandler, LinkHandler, ModelHandler, ModelsHandler, PrefsHandler, ResetHandler, SandboxHandler, SkillHandler,
     SkillHandler as KillHandler, StatusHandler, StopHandler, SubagentHandler,
-    ThinkHandler, ToggleHandler, TtsHandler, WhoAmIHandler,
+    ThinkHandler, ToggleHandler, TtsHandler, UndoHandler, UsageHandler, WhoAmIHandler,
 };
 pub use registry::{builtin_commands, CommandRegistry};
 pub use types::{CommandArg, CommandCategory, CommandDef, CommandInvocation, CommandScope};
 
-/// Build a dispatcher pre-wired with all built-in handlers.
+use std::sync::Arc;
+
+/// Build a dispatcher with the built-in handlers that need no runtime services.
 pub fn build_default_dispatcher() -> CommandDispatcher {
-    let registry = CommandRegistry::new();
-    let mut dispatcher = CommandDispatcher::new();
-
-    use std::sync::Arc;
-    dispatcher.register("help", Arc::new(HelpHandler { registry: CommandRegistry::new() }));
-    dispatcher.register("commands", Arc::new(HelpHandler { registry: CommandRegistry::new() }));
-    dispatcher.register("status", Arc::new(StatusHandler));
-    dispatcher.register("whoami", Arc::new(WhoAmIHandler));
-    dispatcher.register("think", Arc::new(ThinkHandler));
-    dispatcher.register("stop", Arc::new(StopHandler));
-    dispatcher.register("reset", Arc::new(ResetHandler));
-    dispatcher.register("compact", Arc::new(CompactHandler));
-    dispatcher.register("model", Arc::new(ModelHandler));
-    dispatcher.register("verbose", Arc::new(ToggleHandler { label: "Verbose".into() }));
-    dispatcher.register("reasoning", Arc::new(ToggleHandler { label: "Reasoning".into() }));
-    dispatcher.register("elevated", Arc::new(ToggleHandler { label: "Elevated".into() }));
-    dispatcher.register("subagents", Arc::new(SubagentHandler));
-    dispatcher.register("kill", Arc::new(SubagentHandler));
-    dispatcher.register("steer", Arc::new(SubagentHandler));
-    dispatcher.register("skill", Arc::new(SkillHandler));
-    dispatcher.register("tts", Arc::new(TtsHandler));
-
-    dispatcher
+    DispatcherBuilder::new().build()
+}
+
+/// Runtime services for the built-in handlers. A command whose service was
+/// not supplied is left unregistered, so it answers "no handler" instead of
+/// acting on a throwaway copy nobody else reads. `/subagents`, `/kill`,
+/// `/steer` and `/hooks` are always registered and say when their service is
+/// not configured.
+#[derive(Default)]
+pub struct DispatcherBuilder {
+    sandboxes: Option<Arc<clawforge_sandbox::SandboxRegistry>>,
+    workspaces: Option<Arc<clawforge_sandbox::WorkspaceManager>>,
+    edits: Option<Arc<clawforge_tools::EditJournal>>,
+    usage: Option<infra::UsageFooter>,
+    identities: Option<Arc<clawforge_security::IdentityRegistry>>,
+    preferences: Option<Arc<clawforge_security::PreferenceStore>>,
+    catalog: Option<Arc<std::sync::RwLock<clawforge_tools::ModelCatalog>>>,
+    adapters: Option<infra::AdapterStatusRegistry>,
+    hook_tracer: Option<Arc<clawforge_hooks::HookTracer>>,
+    subagents: Option<Arc<clawforge_acp::SubAgentRegistry>>,
+    cron: Option<(String, clawforge_scheduler::Tz)>,
+    audit: Option<Arc<clawforge_security::AuditLog>>,
+}
+
+impl DispatcherBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `/exec` routes sessions through the runtime's sandbox registry.
+    pub fn with_sandboxes(mut self, sandboxes: Arc<clawforge_sandbox::SandboxRegistry>) -> Self {
+        self.sandboxes = Some(sandboxes);
+        self
+    }
+
+    /// `/sandbox` snapshots and restores session workspaces.
+    pub fn with_workspaces(mut self, workspaces: Arc<clawforge_sandbox::WorkspaceManager>) -> Self {
+        self.workspaces = Some(workspaces);
+        self
+    }
+
+    /// `/undo` reverts edits from the executor's edit journal.
+    pub fn with_edit_journal(mut self, edits: Arc<clawforge_tools::EditJournal>) -> Self {
+        self.edits = Some(edits);
+        self
+    }
+
+    /// `/usage` sets the reply footer mode.
+    pub fn with_usage_footer(mut self, usage: infra::UsageFooter) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// `/link` links senders in the identity registry; with preferences,
+    /// `/prefs` edits them per person.
+    pub fn with_identities(mut self, identities: Arc<clawforge_security::IdentityRegistry>) -> Self {
+        self.identities = Some(identities);
+        self
+    }
+
+    pub fn with_preferences(mut self, preferences: Arc<clawforge_security::PreferenceStore>) -> Self {
+        self.preferences = Some(preferences);
+        self
+    }
+
+    /// `/models` lists the synced model catalog.
+    pub fn with_catalog(mut self, catalog: Arc<std::sync::RwLock<clawforge_tools::ModelCatalog>>) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    /// `/status` lists the state of each channel adapter.
+    pub fn with_adapters(mut self, adapters: infra::AdapterStatusRegistry) -> Self {
+        self.adapters = Some(adapters);
+        self
+    }
+
+    /// `/hooks trace` switches the hook pipeline's tracer.
+    pub fn with_hook_tracer(mut self, tracer: Arc<clawforge_hooks::HookTracer>) -> Self {
+        self.hook_tracer = Some(tracer);
+        self
+    }
+
+    /// `/subagents`, `/kill` and `/steer` act on the sub-agent tree.
+    pub fn with_subagents(mut self, subagents: Arc<clawforge_acp::SubAgentRegistry>) -> Self {
+        self.subagents = Some(subagents);
+        self
+    }
+
+    /// `/cron` manages jobs in the database at `db_path`, in `timezone` unless
+    /// a job sets its own.
+    pub fn with_cron(mut self, db_path: impl Into<String>, timezone: clawforge_scheduler::Tz) -> Self {
+        self.cron = Some((db_path.into(), timezone));
+        self
+    }
+
+    /// `/elevated` records every change of mode in `audit`.
+    pub fn with_audit(mut self, audit: Arc<clawforge_security::AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    pub fn build(self) -> CommandDispatcher {
+        let mut dispatcher = CommandDispatcher::new();
+
+        dispatcher.register("help", Arc::new(HelpHandler { registry: CommandRegistry::new() }));
+        dispatcher.register("commands", Arc::new(HelpHandler { registry: CommandRegistry::new() }));
+        dispatcher.register("whoami", Arc::new(WhoAmIHandler));
+        dispatcher.register("think", Arc::new(ThinkHandler));
+        dispatcher.register("stop", Arc::new(StopHandler));
+        dispatcher.register("reset", Arc::new(ResetHandler { sandboxes: self.sandboxes.clone() }));
+        dispatcher.register("compact", Arc::new(CompactHandler));
+        dispatcher.register("model", Arc::new(ModelHandler));
+        dispatcher.register("verbose", Arc::new(ToggleHandler { label: "Verbose".into() }));
+        dispatcher.register("reasoning", Arc::new(ToggleHandler { label: "Reasoning".into() }));
+        dispatcher.register("elevated", Arc::new(ElevatedHandler { audit: self.audit.clone() }));
+        dispatcher.register("skill", Arc::new(SkillHandler));
+        dispatcher.register("tts", Arc::new(TtsHandler));
+        dispatcher.register("subagents", Arc::new(SubagentHandler { registry: self.subagents.clone() }));
+        dispatcher.register("kill", Arc::new(SubagentHandler { registry: self.subagents.clone() }));
+        dispatcher.register("steer", Arc::new(SubagentHandler { registry: self.subagents }));
+        dispatcher.register("hooks", Arc::new(HooksHandler { tracer: self.hook_tracer }));
+
+        if let Some(adapters) = self.adapters {
+            dispatcher.register("status", Arc::new(StatusHandler { adapters }));
+        }
+        if let Some(catalog) = self.catalog {
+            dispatcher.register("models", Arc::new(ModelsHandler { catalog }));
+        }
+        if let Some(sandboxes) = self.sandboxes {
+            dispatcher.register("exec", Arc::new(ExecHandler { sandboxes }));
+        }
+        if let Some(edits) = self.edits {
+            dispatcher.register("undo", Arc::new(UndoHandler { edits }));
+        }
+        if let Some(footer) = self.usage {
+            dispatcher.register("usage", Arc::new(UsageHandler { footer }));
+        }
+        if let Some(identities) = self.identities {
+            if let Some(preferences) = self.preferences {
+                dispatcher.register("prefs", Arc::new(PrefsHandler { identities: identities.clone(), preferences }));
+            }
+            dispatcher.register("link", Arc::new(LinkHandler { identities }));
+        }
+        if let Some((db_path, timezone)) = self.cron {
+            dispatcher.register("cron", Arc::new(CronHandler { db_path, timezone }));
+        }
+        if let Some(workspaces) = self.workspaces {
+            dispatcher.register("sandbox", Arc::new(SandboxHandler { workspaces }));
+        }
+
+        dispatcher
+    }
 }
@@ -75,6 +75,30 @@ pub fn builtin_commands() -> Vec<CommandDef> {
             args: vec![],
             accepts_args: false,
         },
+        CommandDef {
+            key: "link".into(),
+            native_name: Some("link".into()),
+            description: "Link this account to your accounts on other channels.".into(),
+            scope: CommandScope::Both,
+            category: CommandCategory::Status,
+            text_aliases: vec!["/link".into()],
+            args: vec![string_arg("code", "Code from your other account, or list, remove, suggestions")],
+            accepts_args: true,
+        },
+        CommandDef {
+            key: "prefs".into(),
+            native_name: Some("prefs".into()),
+            description: "Show or change your tone, language, units, working hours, nickname and voice.".into(),
+            scope: CommandScope::Both,
+            category: CommandCategory::Options,
+            text_aliases: vec!["/prefs".into(), "/preferences".into()],
+            args: vec![
+                choice_arg("action", "set, unset, clear, or session to change this session's voice", &["set", "unset", "clear", "session"]),
+                string_arg("key", "tone, language, units, working_hours, nickname, voice, voice_speed or voice_provider"),
+                remaining_arg("value", "New value"),
+            ],
+            accepts_args: true,
+        },
         CommandDef {
             key: "context".into(),
             native_name: Some("context".into()),
@@ -126,6 +150,16 @@ pub fn builtin_commands() -> Vec<CommandDef> {
             args: vec![remaining_arg("instructions", "Extra compaction instructions")],
             accepts_args: true,
         },
+        CommandDef {
+            key: "undo".into(),
+            native_name: Some("undo".into()),
+            description: "Revert the last file edit made by the agent.".into(),
+            scope: CommandScope::Both,
+            category: CommandCategory::Session,
+            text_aliases: vec!["/undo".into()],
+            args: vec![],
+            accepts_args: false,
+        },
         CommandDef {
             key: "export-session".into(),
             native_name: Some("export-session".into()),
@@ -205,7 +239,11 @@ pub fn builtin_commands() -> Vec<CommandDef> {
             scope: CommandScope::Both,
             category: CommandCategory::Options,
             text_aliases: vec!["/usage".into()],
-            args: vec![choice_arg("mode", "off, tokens, full, or cost", &["off", "tokens", "full", "cost"])],
+            args: vec![choice_arg(
+                "mode",
+                "off, tokens, full, cost, or subagents",
+                &["off", "tokens", "full", "cost", "subagents"],
+            )],
             accepts_args: true,
         },
         CommandDef {
@@ -235,6 +273,7 @@ pub fn builtin_commands() -> Vec<CommandDef> {
                 choice_arg("host", "sandbox, gateway, or node", &["sandbox", "gateway", "node"]),
                 choice_arg("security", "deny, allowlist, or full", &["deny", "allowlist", "full"]),
                 choice_arg("ask", "off, on-miss, or always", &["off", "on-miss", "always"]),
+                string_arg("target", "Node id or SSH host when host is node"),
             ],
             accepts_args: true,
         },
@@ -347,6 +386,45 @@ pub fn builtin_commands() -> Vec<CommandDef> {
             args: vec![],
             accepts_args: false,
         },
+        CommandDef {
+            key: "sandbox".into(),
+            native_name: Some("sandbox".into()),
+            description: "Snapshot or restore the session's sandbox workspace.".into(),
+            scope: CommandScope::Both,
+            category: CommandCategory::Tools,
+            text_aliases: vec!["/sandbox".into()],
+            args: vec![
+                choice_arg("action", "snapshot, restore, or list", &["snapshot", "restore", "list"]),
+                remaining_arg("value", "Snapshot label, or the snapshot id to restore"),
+            ],
+            accepts_args: true,
+        },
+        CommandDef {
+            key: "cron".into(),
+            native_name: Some("cron".into()),
+            description: "List scheduled jobs and their recent runs in local time.".into(),
+            scope: CommandScope::Both,
+            category: CommandCategory::Tools,
+            text_aliases: vec!["/cron".into()],
+            args: vec![
+                choice_arg("action", "list or runs", &["list", "runs"]),
+                string_arg("job", "Job id for runs"),
+            ],
+            accepts_args: true,
+        },
+        CommandDef {
+            key: "hooks".into(),
+            native_name: Some("hooks".into()),
+            description: "Trace hook evaluations to see why a hook did or didn't fire.".into(),
+            scope: CommandScope::Both,
+            category: CommandCategory::Tools,
+            text_aliases: vec!["/hooks".into()],
+            args: vec![
+                choice_arg("action", "trace", &["trace"]),
+                string_arg("mode", "on, off, dry-run, or a run id to show"),
+            ],
+            accepts_args: true,
+        },
         // Sub-agent management
         CommandDef {
             key: "subagents".into(),
@@ -0,0 +1,262 @@
+//! macOS automation scripts for node hosts.
+//!
+//! Agents never send raw AppleScript. They pick a script from an owner-written
+//! allowlist and fill in its declared parameters; values are substituted as
+//! escaped AppleScript string literals (or passed as Shortcut input), so a
+//! parameter can't change what the script does. Each script carries a
+//! description template that is rendered with the same values and shown at
+//! approval time as a dry run.
+//!
+//! ```yaml
+//! scripts:
+//!   - name: add_reminder
+//!     description: Add reminder "{title}" to list {list}
+//!     applescript: |
+//!       tell application "Reminders" to make new reminder at end of list {{list}} with properties {name:{{title}}}
+//!     params:
+//!       - name: title
+//!       - name: list
+//!         default: Reminders
+//!   - name: play_music
+//!     description: Start playback in Music
+//!     shortcut: Play Music
+//! ```
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Longest accepted parameter value, in characters.
+const MAX_PARAM_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptParam {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Used when the caller leaves the parameter out; without one it is required.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationScript {
+    pub name: String,
+    /// Dry-run text; `{param}` is replaced with the value.
+    pub description: String,
+    /// AppleScript source; `{{param}}` becomes a quoted string literal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applescript: Option<String>,
+    /// Name of a Shortcut to run with the parameters as JSON input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcut: Option<String>,
+    #[serde(default)]
+    pub params: Vec<ScriptParam>,
+}
+
+/// A script with its parameters filled in, ready for a node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RenderedScript {
+    pub script: String,
+    /// Rendered description for the approval prompt.
+    pub description: String,
+    /// Node task to invoke: `applescript.run` or `shortcuts.run`.
+    pub task: &'static str,
+    pub args: Value,
+}
+
+impl AutomationScript {
+    fn validate(&self) -> Result<()> {
+        match (&self.applescript, &self.shortcut) {
+            (Some(_), Some(_)) | (None, None) => bail!("Script '{}' needs exactly one of applescript or shortcut", self.name),
+            (Some(source), None) => {
+                for placeholder in placeholders(source, "{{", "}}") {
+                    if placeholder.is_empty() || placeholder.contains(char::is_whitespace) {
+                        bail!("Script '{}' has malformed placeholder '{{{{{}}}}}'", self.name, placeholder);
+                    }
+                    if !self.params.iter().any(|p| p.name == placeholder) {
+                        bail!("Script '{}' uses undeclared parameter '{}'", self.name, placeholder);
+                    }
+                }
+            }
+            (None, Some(_)) => {}
+        }
+        Ok(())
+    }
+
+    /// Fill in `values`, rejecting unknown, missing or oversized parameters.
+    pub fn render(&self, values: &Map<String, Value>) -> Result<RenderedScript> {
+        if let Some(unknown) = values.keys().find(|k| !self.params.iter().any(|p| &p.name == *k)) {
+            bail!("Script '{}' has no parameter '{}'", self.name, unknown);
+        }
+        let mut resolved = BTreeMap::new();
+        for param in &self.params {
+            let value = match values.get(&param.name) {
+                Some(Value::String(s)) => s.clone(),
+                Some(Value::Number(n)) => n.to_string(),
+                Some(Value::Bool(b)) => b.to_string(),
+                Some(Value::Null) | None => match &param.default {
+                    Some(default) => default.clone(),
+                    None => bail!("Script '{}' needs parameter '{}'", self.name, param.name),
+                },
+                Some(_) => bail!("Parameter '{}' must be a string, number or boolean", param.name),
+            };
+            if value.chars().count() > MAX_PARAM_CHARS {
+                bail!("Parameter '{}' is longer than {} characters", param.name, MAX_PARAM_CHARS);
+            }
+            resolved.insert(param.name.as_str(), value);
+        }
+
+        // Single pass, so a value that itself looks like a placeholder is
+        // never expanded again.
+        let description = substitute(&self.description, "{", "}", |name| resolved.get(name).cloned());
+        Ok(match (&self.applescript, &self.shortcut) {
+            (Some(source), _) => {
+                let script = substitute(source, "{{", "}}", |name| resolved.get(name).map(|v| applescript_string(v)));
+                RenderedScript { args: json!({ "script": script }), script, description, task: "applescript.run" }
+            }
+            (None, Some(shortcut)) => RenderedScript {
+                script: shortcut.clone(),
+                description,
+                task: "shortcuts.run",
+                args: json!({ "name": shortcut, "input": resolved }),
+            },
+            (None, None) => unreachable!("validated on load"),
+        })
+    }
+}
+
+/// AppleScript string literal for `value`.
+pub fn applescript_string(value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\r', "\\r").replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// Names between `open` and `close` in `source`, untrimmed.
+fn placeholders<'a>(source: &'a str, open: &str, close: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find(open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(close) else { break };
+        found.push(&after[..end]);
+        rest = &after[end + close.len()..];
+    }
+    found
+}
+
+/// Replace each `open name close` in `template` with `value(name)` in one
+/// left-to-right pass; placeholders `value` doesn't know are kept verbatim.
+fn substitute(template: &str, open: &str, close: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(close) else { break };
+        out.push_str(&rest[..start]);
+        match value(&after[..end]) {
+            Some(replacement) => out.push_str(&replacement),
+            None => out.push_str(&rest[start..start + open.len() + end + close.len()]),
+        }
+        rest = &after[end + close.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Deserialize)]
+struct ScriptsFile {
+    #[serde(default)]
+    scripts: Vec<AutomationScript>,
+}
+
+/// The scripts agents may run, by name.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptAllowlist {
+    scripts: BTreeMap<String, AutomationScript>,
+}
+
+impl ScriptAllowlist {
+    pub fn new(scripts: Vec<AutomationScript>) -> Result<Self> {
+        let mut list = Self::default();
+        for script in scripts {
+            script.validate()?;
+            if list.scripts.contains_key(&script.name) {
+                bail!("Duplicate script name '{}'", script.name);
+            }
+            list.scripts.insert(script.name.clone(), script);
+        }
+        Ok(list)
+    }
+
+    /// Load a YAML `scripts:` document.
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let file: ScriptsFile = serde_yaml::from_str(yaml).context("Invalid automation scripts YAML")?;
+        Self::new(file.scripts)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AutomationScript> {
+        self.scripts.get(name)
+    }
+
+    pub fn scripts(&self) -> impl Iterator<Item = &AutomationScript> {
+        self.scripts.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameters_are_quoted_and_described() {
+        let allowlist = ScriptAllowlist::from_yaml(
+            r#"
+scripts:
+  - name: add_reminder
+    description: Add reminder "{title}" to {list}
+    applescript: tell application "Reminders" to make new reminder at end of list {{list}} with properties {name:{{title}}}
+    params:
+      - name: title
+      - name: list
+        default: Reminders
+"#,
+        )
+        .unwrap();
+        let script = allowlist.get("add_reminder").unwrap();
+        let values = json!({ "title": "Call \"Bob\"\" & do shell script \"rm -rf ~\"" });
+        let rendered = script.render(values.as_object().unwrap()).unwrap();
+        assert_eq!(rendered.task, "applescript.run");
+        assert!(rendered.script.contains(r#"{name:"Call \"Bob\"\" & do shell script \"rm -rf ~\""}"#));
+        assert!(rendered.script.contains(r#"end of list "Reminders""#));
+        assert_eq!(rendered.description, r#"Add reminder "Call "Bob"" & do shell script "rm -rf ~"" to Reminders"#);
+
+        assert!(script.render(json!({}).as_object().unwrap()).is_err());
+        assert!(script.render(json!({ "title": "x", "extra": 1 }).as_object().unwrap()).is_err());
+        assert!(ScriptAllowlist::from_yaml("scripts:\n  - name: x\n    description: d\n    applescript: 'say {{what}}'\n").is_err());
+    }
+
+    #[test]
+    fn values_are_not_expanded_again() {
+        let allowlist = ScriptAllowlist::from_yaml(
+            r#"
+scripts:
+  - name: note
+    description: Note {a} then {b}
+    applescript: say {{a}} & {{b}}
+    params:
+      - name: a
+      - name: b
+"#,
+        )
+        .unwrap();
+        let script = allowlist.get("note").unwrap();
+        let rendered = script.render(json!({ "a": "{{b}} {b}", "b": "x" }).as_object().unwrap()).unwrap();
+        assert_eq!(rendered.script, r#"say "{{b}} {b}" & "x""#);
+        assert_eq!(rendered.description, "Note {{b}} {b} then x");
+
+        let spaced = "scripts:\n  - name: x\n    description: d\n    applescript: 'say {{ what }}'\n    params:\n      - name: what\n";
+        assert!(ScriptAllowlist::from_yaml(spaced).is_err());
+    }
+}
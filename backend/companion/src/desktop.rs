@@ -0,0 +1,66 @@
+//! Desktop access permissions for node hosts.
+//!
+//! Clipboard and screen capture are off for every node until an owner grants
+//! them one by one. A granted permission still asks the owner to approve its
+//! first use; after that the node is trusted for it until the grant is revoked.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DesktopPermission {
+    ClipboardRead,
+    ClipboardWrite,
+    ScreenCapture,
+}
+
+impl DesktopPermission {
+    pub const ALL: [DesktopPermission; 3] = [Self::ClipboardRead, Self::ClipboardWrite, Self::ScreenCapture];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == name || p.task() == name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ClipboardRead => "clipboard_read",
+            Self::ClipboardWrite => "clipboard_write",
+            Self::ScreenCapture => "screen_capture",
+        }
+    }
+
+    /// Node task implementing this permission; nodes advertise it in `capabilities`.
+    pub fn task(self) -> &'static str {
+        match self {
+            Self::ClipboardRead => "clipboard.read",
+            Self::ClipboardWrite => "clipboard.write",
+            Self::ScreenCapture => "screen.capture",
+        }
+    }
+}
+
+/// What a node has been allowed to do.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DesktopGrants {
+    #[serde(default)]
+    pub granted: BTreeSet<DesktopPermission>,
+    /// Granted permissions whose first use the owner has approved.
+    #[serde(default)]
+    pub confirmed: BTreeSet<DesktopPermission>,
+}
+
+impl DesktopGrants {
+    pub fn allows(&self, permission: DesktopPermission) -> bool {
+        self.granted.contains(&permission)
+    }
+
+    pub fn needs_confirmation(&self, permission: DesktopPermission) -> bool {
+        !self.confirmed.contains(&permission)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.granted.is_empty()
+    }
+}
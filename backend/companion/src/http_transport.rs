@@ -0,0 +1,147 @@
+//! HTTP transport for node hosts.
+//!
+//! A node serves three endpoints on its advertised address:
+//! `GET /node` returns its `NodeRegistration`, `GET /health` answers 2xx
+//! while it is up, and `POST /invoke` runs a `NodeInvocation` and returns
+//! the `NodeInvocationResult`. The address comes from the node's approved
+//! registration: a `url` metadata key, or the `host`/`port` mDNS recorded.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::node_host::{NodeInvocation, NodeInvocationResult, NodeRegistration, NodeTransport};
+use crate::node_store::NodeStore;
+
+/// Budget for a task that doesn't set its own timeout.
+const DEFAULT_INVOKE_TIMEOUT: Duration = Duration::from_secs(60);
+/// Extra time over the task's own timeout for the round trip.
+const INVOKE_GRACE: Duration = Duration::from_secs(5);
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct HttpNodeTransport {
+    client: reqwest::Client,
+    store: Arc<NodeStore>,
+    /// Bearer token presented to nodes.
+    token: Option<String>,
+}
+
+impl HttpNodeTransport {
+    pub fn new(store: Arc<NodeStore>) -> Self {
+        Self { client: reqwest::Client::new(), store, token: None }
+    }
+
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Ask the node at `base_url` to describe itself. The URL is kept in the
+    /// registration's `url` metadata so later calls can find it.
+    pub async fn fetch_registration(&self, base_url: &str) -> Result<NodeRegistration> {
+        let base_url = base_url.trim_end_matches('/');
+        let response = self
+            .authorize(self.client.get(format!("{}/node", base_url)))
+            .timeout(PING_TIMEOUT)
+            .send()
+            .await
+            .with_context(|| format!("Node at {} is unreachable", base_url))?;
+        if !response.status().is_success() {
+            bail!("Node at {} answered {}", base_url, response.status());
+        }
+        let mut registration: NodeRegistration = response.json().await.context("Invalid node registration")?;
+        if !registration.metadata.is_object() {
+            registration.metadata = serde_json::json!({});
+        }
+        registration.metadata["url"] = base_url.into();
+        Ok(registration)
+    }
+
+    async fn base_url(&self, node_id: &str) -> Result<String> {
+        let approved = self.store.approved().await;
+        let registration = approved
+            .iter()
+            .find(|reg| reg.node_id == node_id)
+            .ok_or_else(|| anyhow!("Node '{}' is not approved", node_id))?;
+        endpoint(registration).ok_or_else(|| anyhow!("Node '{}' has no known address", node_id))
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+/// Base URL of a registered node.
+fn endpoint(registration: &NodeRegistration) -> Option<String> {
+    let metadata = &registration.metadata;
+    if let Some(url) = metadata["url"].as_str() {
+        return Some(url.trim_end_matches('/').to_string());
+    }
+    let port = metadata["port"].as_u64()?;
+    let host = metadata["addresses"]
+        .as_array()
+        .and_then(|addresses| addresses.first())
+        .and_then(|a| a.as_str())
+        .map(|a| if a.contains(':') { format!("[{}]", a) } else { a.to_string() })
+        .or_else(|| metadata["host"].as_str().map(str::to_string))?;
+    Some(format!("http://{}:{}", host, port))
+}
+
+impl NodeTransport for HttpNodeTransport {
+    async fn invoke(&self, invocation: NodeInvocation) -> Result<NodeInvocationResult> {
+        let base_url = self.base_url(&invocation.node_id).await?;
+        let timeout = invocation.timeout_secs.map(Duration::from_secs).unwrap_or(DEFAULT_INVOKE_TIMEOUT) + INVOKE_GRACE;
+        debug!(node_id = %invocation.node_id, task = %invocation.task, "Sending invocation to node");
+        let response = self
+            .authorize(self.client.post(format!("{}/invoke", base_url)))
+            .json(&invocation)
+            .timeout(timeout)
+            .send()
+            .await
+            .with_context(|| format!("Node '{}' is unreachable", invocation.node_id))?;
+        if !response.status().is_success() {
+            bail!("Node '{}' answered {}", invocation.node_id, response.status());
+        }
+        let result: NodeInvocationResult = response.json().await.context("Invalid invocation result")?;
+        if result.invocation_id != invocation.invocation_id {
+            bail!("Node '{}' answered a different invocation", invocation.node_id);
+        }
+        Ok(result)
+    }
+
+    async fn ping(&self, node_id: &str) -> Result<bool> {
+        let base_url = self.base_url(node_id).await?;
+        let response = self.authorize(self.client.get(format!("{}/health", base_url))).timeout(PING_TIMEOUT).send().await;
+        Ok(response.is_ok_and(|r| r.status().is_success()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn registration(metadata: serde_json::Value) -> NodeRegistration {
+        NodeRegistration {
+            node_id: "studio-mac".into(),
+            display_name: "Studio".into(),
+            platform: "macos".into(),
+            capabilities: vec![],
+            accepts_tasks: true,
+            metadata,
+        }
+    }
+
+    #[test]
+    fn endpoint_prefers_url_then_discovered_address() {
+        assert_eq!(endpoint(&registration(json!({ "url": "http://10.0.0.5:7070/" }))).as_deref(), Some("http://10.0.0.5:7070"));
+        let discovered = json!({ "host": "studio.local", "addresses": ["fe80::1"], "port": 7070 });
+        assert_eq!(endpoint(&registration(discovered)).as_deref(), Some("http://[fe80::1]:7070"));
+        assert_eq!(endpoint(&registration(json!({ "host": "studio.local", "port": 7070 }))).as_deref(), Some("http://studio.local:7070"));
+        assert_eq!(endpoint(&registration(json!({ "host": "studio.local" }))), None);
+    }
+}
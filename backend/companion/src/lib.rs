@@ -1,11 +1,21 @@
+pub mod automation;
 pub mod clawdbot;
+pub mod desktop;
+pub mod http_transport;
+pub mod mdns;
 pub mod moltbot;
 pub mod node_host;
+pub mod node_store;
 pub mod registry;
 pub mod traits;
 
+pub use automation::{applescript_string, AutomationScript, RenderedScript, ScriptAllowlist, ScriptParam};
 pub use clawdbot::Clawdbot;
+pub use desktop::{DesktopGrants, DesktopPermission};
+pub use http_transport::HttpNodeTransport;
+pub use mdns::MdnsBrowser;
 pub use moltbot::Moltbot;
-pub use node_host::{NodeHostRegistry, NodeInvocation, NodeInvocationResult, NodeRegistration, NodeStatus, NodeTransport};
+pub use node_host::{NodeExecTransport, NodeHostRegistry, NodeInvocation, NodeInvocationResult, NodeRegistration, NodeStatus, NodeTransport};
+pub use node_store::{DiscoveredNode, NodeChange, NodeStore};
 pub use registry::CompanionRegistry;
 pub use traits::{CompanionBot, Persona};
@@ -0,0 +1,314 @@
+//! LAN discovery of node hosts over mDNS / DNS-SD.
+//!
+//! Nodes advertise `_clawforge-node._tcp.local` with TXT keys `id`, `name`,
+//! `platform` and `caps` (comma-separated). The browser sends a one-shot PTR
+//! query with the unicast-response bit set (RFC 6762 §5.4), so it needs no
+//! port 5353 socket and coexists with Avahi or Bonjour on the same host.
+//! Discovered nodes go into the `NodeStore` pending queue for approval.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tracing::{debug, warn};
+
+use crate::node_store::{now, DiscoveredNode, NodeStore};
+
+pub const SERVICE_TYPE: &str = "_clawforge-node._tcp.local";
+
+const MDNS_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(224, 0, 0, 251)), 5353);
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_AAAA: u16 = 28;
+const TYPE_SRV: u16 = 33;
+/// Class IN with the "unicast response" bit.
+const CLASS_IN_QU: u16 = 0x8001;
+
+pub struct MdnsBrowser {
+    service_type: String,
+    timeout: Duration,
+}
+
+impl Default for MdnsBrowser {
+    fn default() -> Self {
+        Self::new(SERVICE_TYPE)
+    }
+}
+
+impl MdnsBrowser {
+    pub fn new(service_type: impl Into<String>) -> Self {
+        Self { service_type: service_type.into(), timeout: Duration::from_secs(2) }
+    }
+
+    /// How long to collect responses per browse.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Query the LAN once and return every node that answered.
+    pub async fn browse(&self) -> Result<Vec<DiscoveredNode>> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0)).await.context("Failed to bind mDNS socket")?;
+        socket.send_to(&build_query(&self.service_type), MDNS_ADDR).await.context("Failed to send mDNS query")?;
+
+        let mut records = Vec::new();
+        let mut buf = vec![0u8; 9000];
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        while let Ok(received) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+            let (len, from) = received?;
+            match parse_message(&buf[..len]) {
+                Ok(mut parsed) => records.append(&mut parsed),
+                Err(e) => debug!(%from, "Ignoring malformed mDNS response: {e:#}"),
+            }
+        }
+        Ok(collect_nodes(&self.service_type, &records))
+    }
+
+    /// Browse every `interval` and offer what is found to `store`.
+    pub fn spawn(self, store: Arc<NodeStore>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.browse().await {
+                    Ok(nodes) => {
+                        for node in nodes {
+                            store.offer(node).await;
+                        }
+                    }
+                    Err(e) => warn!("mDNS browse failed: {e:#}"),
+                }
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Wire format
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum RData {
+    Ptr(String),
+    Srv { port: u16, target: String },
+    Txt(Vec<String>),
+    Addr(IpAddr),
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Record {
+    name: String,
+    data: RData,
+}
+
+fn encode_name(out: &mut Vec<u8>, name: &str) {
+    for label in name.trim_end_matches('.').split('.') {
+        out.push(label.len() as u8);
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(0);
+}
+
+/// A single PTR question for `service_type`, asking for unicast replies.
+fn build_query(service_type: &str) -> Vec<u8> {
+    // id 0, flags 0, one question, no records.
+    let mut out = vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    encode_name(&mut out, service_type);
+    out.extend_from_slice(&TYPE_PTR.to_be_bytes());
+    out.extend_from_slice(&CLASS_IN_QU.to_be_bytes());
+    out
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> Result<u16> {
+    match buf.get(pos..pos + 2) {
+        Some(b) => Ok(u16::from_be_bytes([b[0], b[1]])),
+        None => bail!("Truncated message"),
+    }
+}
+
+/// Read a possibly compressed name; returns it and the position after it.
+fn read_name(buf: &[u8], mut pos: usize) -> Result<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    for _ in 0..128 {
+        let len = *buf.get(pos).context("Truncated name")? as usize;
+        if len == 0 {
+            return Ok((labels.join("."), end.unwrap_or(pos + 1)));
+        }
+        if len & 0xC0 == 0xC0 {
+            let target = (read_u16(buf, pos)? & 0x3FFF) as usize;
+            end.get_or_insert(pos + 2);
+            pos = target;
+            continue;
+        }
+        let label = buf.get(pos + 1..pos + 1 + len).context("Truncated label")?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        pos += 1 + len;
+    }
+    bail!("Name compression loop")
+}
+
+fn parse_message(buf: &[u8]) -> Result<Vec<Record>> {
+    let questions = read_u16(buf, 4)?;
+    let count = read_u16(buf, 6)? as usize + read_u16(buf, 8)? as usize + read_u16(buf, 10)? as usize;
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = read_name(buf, pos)?.1 + 4;
+    }
+
+    let mut records = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (name, next) = read_name(buf, pos)?;
+        let rtype = read_u16(buf, next)?;
+        let rdlen = read_u16(buf, next + 8)? as usize;
+        let start = next + 10;
+        let rdata = buf.get(start..start + rdlen).context("Truncated record")?;
+        let data = match rtype {
+            TYPE_PTR => RData::Ptr(read_name(buf, start)?.0),
+            TYPE_SRV => RData::Srv { port: read_u16(buf, start + 4)?, target: read_name(buf, start + 6)?.0 },
+            TYPE_TXT => {
+                let mut entries = Vec::new();
+                let mut i = 0;
+                while i < rdata.len() {
+                    let len = rdata[i] as usize;
+                    let entry = rdata.get(i + 1..i + 1 + len).context("Truncated TXT entry")?;
+                    entries.push(String::from_utf8_lossy(entry).into_owned());
+                    i += 1 + len;
+                }
+                RData::Txt(entries)
+            }
+            TYPE_A if rdlen == 4 => RData::Addr(IpAddr::V4(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]))),
+            TYPE_AAAA if rdlen == 16 => {
+                let octets: [u8; 16] = rdata.try_into().expect("length checked");
+                RData::Addr(IpAddr::V6(Ipv6Addr::from(octets)))
+            }
+            _ => RData::Other,
+        };
+        records.push(Record { name, data });
+        pos = start + rdlen;
+    }
+    Ok(records)
+}
+
+/// Join PTR → SRV/TXT → A/AAAA records into nodes.
+fn collect_nodes(service_type: &str, records: &[Record]) -> Vec<DiscoveredNode> {
+    let service = service_type.trim_end_matches('.');
+    let mut srv: HashMap<&str, (u16, &str)> = HashMap::new();
+    let mut txt: HashMap<&str, HashMap<&str, &str>> = HashMap::new();
+    let mut addrs: HashMap<&str, Vec<IpAddr>> = HashMap::new();
+    let mut instances = Vec::new();
+    for record in records {
+        match &record.data {
+            RData::Ptr(instance)
+                if record.name.eq_ignore_ascii_case(service) && !instances.contains(&instance.as_str()) =>
+            {
+                instances.push(instance.as_str());
+            }
+            RData::Srv { port, target } => {
+                srv.insert(&record.name, (*port, target));
+            }
+            RData::Txt(entries) => {
+                let map = txt.entry(&record.name).or_default();
+                for entry in entries {
+                    if let Some((k, v)) = entry.split_once('=') {
+                        map.insert(k, v);
+                    }
+                }
+            }
+            RData::Addr(ip) => {
+                let list = addrs.entry(&record.name).or_default();
+                if !list.contains(ip) {
+                    list.push(*ip);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let seen = now();
+    instances
+        .into_iter()
+        .filter_map(|instance| {
+            let (port, host) = srv.get(instance).copied()?;
+            let label = instance.strip_suffix(service).unwrap_or(instance).trim_end_matches('.');
+            let props = txt.get(instance).cloned().unwrap_or_default();
+            Some(DiscoveredNode {
+                node_id: props.get("id").unwrap_or(&label).to_string(),
+                display_name: props.get("name").unwrap_or(&label).to_string(),
+                platform: props.get("platform").unwrap_or(&"unknown").to_string(),
+                capabilities: props
+                    .get("caps")
+                    .map(|c| c.split(',').map(str::trim).filter(|c| !c.is_empty()).map(String::from).collect())
+                    .unwrap_or_default(),
+                host: host.to_string(),
+                addresses: addrs.get(host).cloned().unwrap_or_default(),
+                port,
+                first_seen: seen,
+                last_seen: seen,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(out: &mut Vec<u8>, name: &[u8], rtype: u16, rdata: &[u8]) {
+        out.extend_from_slice(name);
+        out.extend_from_slice(&rtype.to_be_bytes());
+        out.extend_from_slice(&[0x80, 0x01, 0, 0, 0x11, 0x94]);
+        out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        out.extend_from_slice(rdata);
+    }
+
+    #[test]
+    fn parses_dns_sd_response() {
+        let mut msg = vec![0, 0, 0x84, 0, 0, 0, 0, 1, 0, 0, 0, 3];
+        let service_at = msg.len() as u8;
+        let mut service = Vec::new();
+        encode_name(&mut service, SERVICE_TYPE);
+
+        // PTR _clawforge-node._tcp.local -> "Mac mini" + pointer to the service name.
+        let mut ptr = vec![8];
+        ptr.extend_from_slice(b"Mac mini");
+        ptr.extend_from_slice(&[0xC0, service_at]);
+        record(&mut msg, &service, TYPE_PTR, &ptr);
+        let mut instance = vec![8];
+        instance.extend_from_slice(b"Mac mini");
+        instance.extend_from_slice(&[0xC0, service_at]);
+
+        let mut srv = vec![0, 0, 0, 0, 0x49, 0x66];
+        encode_name(&mut srv, "mac-mini.local");
+        record(&mut msg, &instance, TYPE_SRV, &srv);
+
+        let mut txt = Vec::new();
+        for entry in ["id=mini-1", "platform=macos", "caps=imessage, shortcuts"] {
+            txt.push(entry.len() as u8);
+            txt.extend_from_slice(entry.as_bytes());
+        }
+        record(&mut msg, &instance, TYPE_TXT, &txt);
+
+        let mut host = Vec::new();
+        encode_name(&mut host, "mac-mini.local");
+        record(&mut msg, &host, TYPE_A, &[192, 168, 1, 20]);
+
+        let records = parse_message(&msg).unwrap();
+        assert_eq!(records[1].name, "Mac mini._clawforge-node._tcp.local");
+        let nodes = collect_nodes(SERVICE_TYPE, &records);
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!((node.node_id.as_str(), node.display_name.as_str()), ("mini-1", "Mac mini"));
+        assert_eq!(node.capabilities, vec!["imessage", "shortcuts"]);
+        assert_eq!((node.host.as_str(), node.port), ("mac-mini.local", 18790));
+        assert_eq!(node.addresses, vec!["192.168.1.20".parse::<IpAddr>().unwrap()]);
+
+        let query = build_query(SERVICE_TYPE);
+        assert_eq!(&query[query.len() - 4..], &[0, 12, 0x80, 0x01]);
+    }
+}
@@ -11,6 +11,11 @@ use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
+use clawforge_sandbox::{truncate_result, ContainerExecResult, OutputChunk, OutputStream, RemoteTransport};
+use tokio::sync::mpsc;
+
+use crate::node_store::{NodeChange, NodeStore};
+
 /// A connected node (device/peer) registration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -76,6 +81,8 @@ pub trait NodeTransport: Send + Sync + 'static {
 pub struct NodeHostRegistry<T: NodeTransport> {
     nodes: Arc<RwLock<HashMap<String, (NodeRegistration, NodeStatus)>>>,
     transport: Arc<T>,
+    /// Where registrations are persisted, if anywhere.
+    store: Option<Arc<NodeStore>>,
 }
 
 impl<T: NodeTransport> NodeHostRegistry<T> {
@@ -83,19 +90,82 @@ impl<T: NodeTransport> NodeHostRegistry<T> {
         Self {
             nodes: Arc::new(RwLock::new(HashMap::new())),
             transport: Arc::new(transport),
+            store: None,
         }
     }
 
+    /// The transport invocations go through.
+    pub fn transport(&self) -> &Arc<T> {
+        &self.transport
+    }
+
+    /// Persist registrations to `store` so they survive restarts.
+    pub fn with_store(mut self, store: Arc<NodeStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Load previously approved nodes from the store with status `Unknown`
+    /// until the next `refresh_all`. Returns how many were restored.
+    pub async fn restore(&self) -> usize {
+        let Some(store) = &self.store else { return 0 };
+        let approved = store.approved().await;
+        let mut nodes = self.nodes.write().await;
+        for reg in &approved {
+            nodes.entry(reg.node_id.clone()).or_insert_with(|| (reg.clone(), NodeStatus::Unknown));
+        }
+        info!(count = approved.len(), "Restored node registrations");
+        approved.len()
+    }
+
+    /// Register nodes as they are approved in the store (e.g. from mDNS
+    /// discovery) and drop them when they are forgotten there.
+    pub fn follow_approvals(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let registry = Arc::clone(self);
+        let mut changes = self.store.as_ref().map(|s| s.subscribe());
+        tokio::spawn(async move {
+            let Some(changes) = changes.as_mut() else { return };
+            loop {
+                match changes.recv().await {
+                    Ok(NodeChange::Approved(registration)) => {
+                        let id = registration.node_id.clone();
+                        registry.nodes.write().await.insert(id.clone(), (registration, NodeStatus::Unknown));
+                        info!(node_id = %id, "Approved node registered");
+                    }
+                    Ok(NodeChange::Removed(id)) => {
+                        if registry.nodes.write().await.remove(&id).is_some() {
+                            info!(node_id = %id, "Forgotten node deregistered");
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        warn!(skipped = n, "Node approval stream lagged");
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
     /// Register a new node.
     pub async fn register(&self, registration: NodeRegistration) {
         let id = registration.node_id.clone();
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save_registration(registration.clone()).await {
+                warn!(node_id = %id, "Failed to persist node registration: {e:#}");
+            }
+        }
         self.nodes.write().await.insert(id.clone(), (registration, NodeStatus::Online));
         info!(node_id = %id, "Node registered");
     }
 
-    /// Remove a node from the registry.
+    /// Remove a node from the registry (and the store, so it stays gone).
     pub async fn deregister(&self, node_id: &str) {
         self.nodes.write().await.remove(node_id);
+        if let Some(store) = &self.store {
+            if let Err(e) = store.remove(node_id).await {
+                warn!(node_id = %node_id, "Failed to persist node removal: {e:#}");
+            }
+        }
         info!(node_id = %node_id, "Node deregistered");
     }
 
@@ -148,3 +218,84 @@ impl<T: NodeTransport> NodeHostRegistry<T> {
         self.nodes.read().await.values().cloned().collect()
     }
 }
+
+/// Runs sandbox commands on a node through its `system.run` task, so a node
+/// can back a `RemoteSandbox`. Node invocations don't stream; output lines
+/// are forwarded once the command finishes.
+pub struct NodeExecTransport<T: NodeTransport> {
+    registry: Arc<NodeHostRegistry<T>>,
+    node_id: String,
+}
+
+impl<T: NodeTransport> NodeExecTransport<T> {
+    pub fn new(registry: Arc<NodeHostRegistry<T>>, node_id: impl Into<String>) -> Self {
+        Self { registry, node_id: node_id.into() }
+    }
+
+    async fn run(&self, args: serde_json::Value, timeout_secs: Option<u64>) -> Result<serde_json::Value> {
+        let result = self.registry.invoke(&self.node_id, "system.run", args, timeout_secs).await?;
+        if !result.success {
+            anyhow::bail!(
+                "Node '{}' failed to run command: {}",
+                self.node_id,
+                result.error.unwrap_or_else(|| "unknown error".into())
+            );
+        }
+        Ok(result.output)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: NodeTransport> RemoteTransport for NodeExecTransport<T> {
+    fn describe(&self) -> String {
+        format!("node:{}", self.node_id)
+    }
+
+    async fn check(&self) -> Result<()> {
+        let nodes = self.registry.list().await;
+        match nodes.iter().find(|(reg, _)| reg.node_id == self.node_id) {
+            None => anyhow::bail!("Node '{}' not found", self.node_id),
+            Some((_, NodeStatus::Offline)) => anyhow::bail!("Node '{}' is offline", self.node_id),
+            Some((reg, _)) if !reg.accepts_tasks => anyhow::bail!("Node '{}' does not accept task invocations", self.node_id),
+            Some(_) => Ok(()),
+        }
+    }
+
+    async fn exec(
+        &self,
+        command: &[&str],
+        env: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+        max_output_bytes: usize,
+        output: Option<&mpsc::UnboundedSender<OutputChunk>>,
+    ) -> Result<ContainerExecResult> {
+        let started = std::time::Instant::now();
+        let args = serde_json::json!({ "command": command, "env": env, "timeoutSecs": timeout_secs });
+        let out = self.run(args, timeout_secs).await?;
+        let mut result = ContainerExecResult {
+            exit_code: out["exitCode"].as_i64().unwrap_or(-1),
+            stdout: out["stdout"].as_str().unwrap_or_default().to_string(),
+            stderr: out["stderr"].as_str().unwrap_or_default().to_string(),
+            timed_out: out["timedOut"].as_bool().unwrap_or(false),
+            wall_time_ms: started.elapsed().as_millis() as u64,
+            ..Default::default()
+        };
+        if let Some(tx) = output {
+            for (stream, text) in [(OutputStream::Stdout, &result.stdout), (OutputStream::Stderr, &result.stderr)] {
+                for line in text.lines() {
+                    let _ = tx.send(OutputChunk { stream, line: line.to_string() });
+                }
+            }
+        }
+        truncate_result(&mut result, max_output_bytes);
+        Ok(result)
+    }
+
+    async fn copy_in(&self, _host_path: &str, _remote_path: &str) -> Result<()> {
+        anyhow::bail!("Node '{}' does not support file transfer", self.node_id)
+    }
+
+    async fn copy_out(&self, _remote_path: &str, _host_path: &str) -> Result<()> {
+        anyhow::bail!("Node '{}' does not support file transfer", self.node_id)
+    }
+}
@@ -0,0 +1,311 @@
+//! Persistent node registrations and the discovery approval queue.
+//!
+//! Approved nodes are saved to a JSON file so they survive gateway restarts.
+//! Nodes found on the LAN (see `mdns`) wait in a pending queue until someone
+//! approves or rejects them from the Control UI; rejected node IDs are
+//! remembered so they do not reappear. Desktop permissions granted to a node
+//! are stored alongside its registration.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, warn};
+
+use crate::desktop::{DesktopGrants, DesktopPermission};
+use crate::node_host::NodeRegistration;
+
+/// A node seen on the LAN that has not been approved yet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredNode {
+    pub node_id: String,
+    pub display_name: String,
+    pub platform: String,
+    pub capabilities: Vec<String>,
+    /// mDNS host name, e.g. `mac-mini.local`.
+    pub host: String,
+    pub addresses: Vec<IpAddr>,
+    pub port: u16,
+    pub first_seen: i64,
+    pub last_seen: i64,
+}
+
+impl DiscoveredNode {
+    pub fn to_registration(&self) -> NodeRegistration {
+        NodeRegistration {
+            node_id: self.node_id.clone(),
+            display_name: self.display_name.clone(),
+            platform: self.platform.clone(),
+            capabilities: self.capabilities.clone(),
+            accepts_tasks: true,
+            metadata: serde_json::json!({
+                "discovery": "mdns",
+                "host": self.host,
+                "addresses": self.addresses,
+                "port": self.port,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredNodes {
+    #[serde(default)]
+    approved: Vec<NodeRegistration>,
+    #[serde(default)]
+    rejected: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    desktop: BTreeMap<String, DesktopGrants>,
+}
+
+/// A change to the approved set, for registries following the store.
+#[derive(Debug, Clone)]
+pub enum NodeChange {
+    Approved(NodeRegistration),
+    Removed(String),
+}
+
+pub struct NodeStore {
+    path: Option<PathBuf>,
+    approved: RwLock<HashMap<String, NodeRegistration>>,
+    rejected: RwLock<BTreeSet<String>>,
+    pending: RwLock<HashMap<String, DiscoveredNode>>,
+    desktop: RwLock<BTreeMap<String, DesktopGrants>>,
+    changes: broadcast::Sender<NodeChange>,
+}
+
+impl NodeStore {
+    /// A store that forgets everything on restart.
+    pub fn in_memory() -> Self {
+        let (changes, _) = broadcast::channel(16);
+        Self {
+            path: None,
+            approved: RwLock::new(HashMap::new()),
+            rejected: RwLock::new(BTreeSet::new()),
+            pending: RwLock::new(HashMap::new()),
+            desktop: RwLock::new(BTreeMap::new()),
+            changes,
+        }
+    }
+
+    /// Open (or create on first save) the store at `path`.
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let stored: StoredNodes = match tokio::fs::read_to_string(&path).await {
+            Ok(raw) => serde_json::from_str(&raw).with_context(|| format!("Corrupt node store {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => StoredNodes::default(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let store = Self { path: Some(path), ..Self::in_memory() };
+        *store.approved.write().await = stored.approved.into_iter().map(|r| (r.node_id.clone(), r)).collect();
+        *store.rejected.write().await = stored.rejected;
+        *store.desktop.write().await = stored.desktop;
+        Ok(store)
+    }
+
+    async fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let mut approved: Vec<NodeRegistration> = self.approved.read().await.values().cloned().collect();
+        approved.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        let stored = StoredNodes {
+            approved,
+            rejected: self.rejected.read().await.clone(),
+            desktop: self.desktop.read().await.clone(),
+        };
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec_pretty(&stored)?).await?;
+        tokio::fs::rename(&tmp, path).await.with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub async fn approved(&self) -> Vec<NodeRegistration> {
+        let mut list: Vec<_> = self.approved.read().await.values().cloned().collect();
+        list.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        list
+    }
+
+    /// Save a registration, e.g. a node that connected and registered itself.
+    pub async fn save_registration(&self, registration: NodeRegistration) -> Result<()> {
+        self.rejected.write().await.remove(&registration.node_id);
+        self.pending.write().await.remove(&registration.node_id);
+        self.approved.write().await.insert(registration.node_id.clone(), registration);
+        self.save().await
+    }
+
+    /// Forget an approved node. Returns false if it was unknown.
+    pub async fn remove(&self, node_id: &str) -> Result<bool> {
+        let removed = self.approved.write().await.remove(node_id).is_some();
+        self.desktop.write().await.remove(node_id);
+        if removed {
+            let _ = self.changes.send(NodeChange::Removed(node_id.to_string()));
+            self.save().await?;
+        }
+        Ok(removed)
+    }
+
+    /// Queue a discovered node for approval. Returns true when it is new;
+    /// approved and rejected nodes are ignored, known pending ones refreshed.
+    pub async fn offer(&self, node: DiscoveredNode) -> bool {
+        if self.approved.read().await.contains_key(&node.node_id) || self.rejected.read().await.contains(&node.node_id) {
+            return false;
+        }
+        let mut pending = self.pending.write().await;
+        match pending.get_mut(&node.node_id) {
+            Some(existing) => {
+                let first_seen = existing.first_seen;
+                *existing = DiscoveredNode { first_seen, ..node };
+                false
+            }
+            None => {
+                info!(node_id = %node.node_id, host = %node.host, "Discovered node awaiting approval");
+                pending.insert(node.node_id.clone(), node);
+                true
+            }
+        }
+    }
+
+    pub async fn pending(&self) -> Vec<DiscoveredNode> {
+        let mut list: Vec<_> = self.pending.read().await.values().cloned().collect();
+        list.sort_by(|a, b| a.first_seen.cmp(&b.first_seen).then_with(|| a.node_id.cmp(&b.node_id)));
+        list
+    }
+
+    /// Approve a pending node; registries following the store register it.
+    pub async fn approve(&self, node_id: &str) -> Result<NodeRegistration> {
+        let node = self.pending.write().await.remove(node_id).context("No pending node with that id")?;
+        let registration = node.to_registration();
+        self.save_registration(registration.clone()).await?;
+        info!(node_id = %node_id, "Discovered node approved");
+        let _ = self.changes.send(NodeChange::Approved(registration.clone()));
+        Ok(registration)
+    }
+
+    /// Reject a pending node and stop offering it.
+    pub async fn reject(&self, node_id: &str) -> Result<bool> {
+        if self.pending.write().await.remove(node_id).is_none() {
+            return Ok(false);
+        }
+        self.rejected.write().await.insert(node_id.to_string());
+        if let Err(e) = self.save().await {
+            warn!(node_id = %node_id, "Failed to persist rejection: {e:#}");
+        }
+        Ok(true)
+    }
+
+    /// Desktop permissions of a node (none unless granted).
+    pub async fn desktop_grants(&self, node_id: &str) -> DesktopGrants {
+        self.desktop.read().await.get(node_id).cloned().unwrap_or_default()
+    }
+
+    /// Allow an approved node to use `permission`. Its first use still needs
+    /// owner approval.
+    pub async fn grant(&self, node_id: &str, permission: DesktopPermission) -> Result<DesktopGrants> {
+        if !self.approved.read().await.contains_key(node_id) {
+            anyhow::bail!("Node '{}' is not approved", node_id);
+        }
+        self.update_grants(node_id, |grants| {
+            grants.granted.insert(permission);
+        })
+        .await
+    }
+
+    /// Withdraw a permission; granting it again asks for approval again.
+    pub async fn revoke(&self, node_id: &str, permission: DesktopPermission) -> Result<DesktopGrants> {
+        self.update_grants(node_id, |grants| {
+            grants.granted.remove(&permission);
+            grants.confirmed.remove(&permission);
+        })
+        .await
+    }
+
+    /// Record that the owner approved the first use of a granted permission.
+    pub async fn confirm(&self, node_id: &str, permission: DesktopPermission) -> Result<DesktopGrants> {
+        self.update_grants(node_id, |grants| {
+            if grants.granted.contains(&permission) {
+                grants.confirmed.insert(permission);
+            }
+        })
+        .await
+    }
+
+    async fn update_grants(&self, node_id: &str, change: impl FnOnce(&mut DesktopGrants)) -> Result<DesktopGrants> {
+        let updated = {
+            let mut desktop = self.desktop.write().await;
+            let grants = desktop.entry(node_id.to_string()).or_default();
+            change(grants);
+            let updated = grants.clone();
+            if updated.is_empty() {
+                desktop.remove(node_id);
+            }
+            updated
+        };
+        self.save().await?;
+        info!(node_id = %node_id, granted = ?updated.granted, "Desktop permissions changed");
+        Ok(updated)
+    }
+
+    /// Approvals and removals as they happen.
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeChange> {
+        self.changes.subscribe()
+    }
+}
+
+/// Current time for `first_seen` / `last_seen`.
+pub(crate) fn now() -> i64 {
+    Utc::now().timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> DiscoveredNode {
+        DiscoveredNode {
+            node_id: id.into(),
+            display_name: "Mac mini".into(),
+            platform: "macos".into(),
+            capabilities: vec!["imessage".into()],
+            host: "mac-mini.local".into(),
+            addresses: vec!["192.168.1.20".parse().unwrap()],
+            port: 18790,
+            first_seen: 1,
+            last_seen: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn approved_nodes_survive_reopen() {
+        let dir = std::env::temp_dir().join(format!("clawforge-nodes-{}", uuid::Uuid::new_v4().simple()));
+        let path = dir.join("nodes.json");
+        let store = NodeStore::open(&path).await.unwrap();
+        let mut approvals = store.subscribe();
+
+        assert!(store.offer(node("mini")).await);
+        assert!(!store.offer(DiscoveredNode { last_seen: 5, ..node("mini") }).await);
+        assert!(store.offer(node("pi")).await);
+        let pending = store.pending().await;
+        assert_eq!(pending.iter().find(|n| n.node_id == "mini").unwrap().last_seen, 5);
+
+        store.approve("mini").await.unwrap();
+        assert!(matches!(approvals.recv().await.unwrap(), NodeChange::Approved(reg) if reg.node_id == "mini"));
+        assert!(store.reject("pi").await.unwrap());
+
+        let reopened = NodeStore::open(&path).await.unwrap();
+        let approved = reopened.approved().await;
+        assert_eq!(approved.len(), 1);
+        assert_eq!(approved[0].metadata["host"], "mac-mini.local");
+        assert!(!reopened.offer(node("pi")).await);
+        assert!(!reopened.offer(node("mini")).await);
+
+        assert!(store.remove("mini").await.unwrap());
+        assert!(matches!(approvals.recv().await.unwrap(), NodeChange::Removed(id) if id == "mini"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
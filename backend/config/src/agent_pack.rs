@@ -0,0 +1,296 @@
+//! Agent packs: a self-contained, shareable YAML bundle of one agent.
+//!
+//! A pack carries the agent spec (with its system prompt), the skills it
+//! references, its config overrides (`agents.list.<name>`), the channels
+//! routed to it and its cron jobs. Secrets never leave the deployment: every
+//! sensitive field is swapped for a `${VAR}` placeholder on export, and the
+//! pack lists the env vars an importer has to set.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use clawforge_core::AgentSpec;
+
+use crate::env::collect_referenced_vars;
+use crate::redact::is_sensitive_key;
+use crate::schema::{AgentEntry, ChannelsConfig, ClawForgeConfig};
+
+/// Format version written by this build.
+pub const PACK_VERSION: u32 = 1;
+
+/// Channels a pack can route; matches the fields of `ChannelsConfig`.
+pub const ROUTABLE_CHANNELS: &[&str] = &["telegram", "discord", "slack", "whatsapp", "signal", "line"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentPack {
+    pub pack_version: u32,
+    /// The agent; its id is cleared on export and assigned on import.
+    pub agent: AgentSpec,
+    /// Skills the agent references by name; they are not bundled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skills: Vec<String>,
+    /// `agents.list.<name>` overrides from the exporting deployment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<AgentEntry>,
+    /// Channels whose inbound messages go to this agent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cron_jobs: Vec<PackCronJob>,
+    /// Env vars the pack's placeholders refer to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+}
+
+/// A cron job without deployment-specific ids or run counters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackCronJob {
+    pub schedule: String,
+    pub prompt: String,
+    pub channel: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery_target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery_template: Option<String>,
+    #[serde(default)]
+    pub stagger_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_runs: Option<u64>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl AgentPack {
+    /// Pack `agent` with its overrides and routes from `config`. Secrets are
+    /// replaced with placeholders and listed in `env`.
+    pub fn export(mut agent: AgentSpec, config: &ClawForgeConfig, cron_jobs: Vec<PackCronJob>) -> Result<Self> {
+        let name = agent.name.clone();
+        agent.id = Default::default();
+        let pack = Self {
+            pack_version: PACK_VERSION,
+            skills: agent.allowed_skills.clone(),
+            overrides: config.agents.as_ref().and_then(|a| a.list.get(&name)).cloned(),
+            routes: routes_for(config, &name),
+            agent,
+            cron_jobs,
+            env: Vec::new(),
+        };
+
+        let mut value = serde_json::to_value(&pack)?;
+        placeholder_secrets(&mut value, &env_prefix(&name), &mut Vec::new());
+        let mut pack: Self = serde_json::from_value(value)?;
+        pack.env = collect_referenced_vars(&serde_json::to_value(&pack)?);
+        Ok(pack)
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let pack: Self = serde_yaml::from_str(yaml)?;
+        if pack.pack_version == 0 || pack.pack_version > PACK_VERSION {
+            bail!("Unsupported agent pack version {} (this build reads up to {})", pack.pack_version, PACK_VERSION);
+        }
+        if pack.agent.name.trim().is_empty() {
+            bail!("Agent pack has no agent name");
+        }
+        if let Some(route) = pack.routes.iter().find(|r| !ROUTABLE_CHANNELS.contains(&r.as_str())) {
+            bail!("Agent pack routes unknown channel '{}'", route);
+        }
+        Ok(pack)
+    }
+
+    /// Env vars the pack needs that are not set in this environment.
+    pub fn missing_env(&self) -> Vec<String> {
+        self.env
+            .iter()
+            .filter(|var| std::env::var(var).map_or(true, |v| v.is_empty()))
+            .cloned()
+            .collect()
+    }
+
+    /// Write the pack's overrides and routes into `config` under the agent's
+    /// (possibly renamed) name. Placeholders are kept, so secrets resolve
+    /// from the environment when the config loads. Returns the channels that
+    /// were previously routed to another agent.
+    pub fn apply_to_config(&self, config: &mut ClawForgeConfig) -> Vec<String> {
+        let name = &self.agent.name;
+        if let Some(overrides) = &self.overrides {
+            config.agents.get_or_insert_with(Default::default).list.insert(name.clone(), overrides.clone());
+        }
+        let channels = config.channels.get_or_insert_with(ChannelsConfig::default);
+        let mut rerouted = Vec::new();
+        for route in &self.routes {
+            let Some(agent) = channel_agent_mut(channels, route) else { continue };
+            if agent.as_ref().is_some_and(|a| a != name) {
+                rerouted.push(route.clone());
+            }
+            *agent = Some(name.clone());
+        }
+        rerouted
+    }
+}
+
+/// Channels in `config` routed to `agent`.
+pub fn routes_for(config: &ClawForgeConfig, agent: &str) -> Vec<String> {
+    let Some(channels) = &config.channels else { return Vec::new() };
+    let agents = [
+        channels.telegram.as_ref().and_then(|c| c.agent.as_deref()),
+        channels.discord.as_ref().and_then(|c| c.agent.as_deref()),
+        channels.slack.as_ref().and_then(|c| c.agent.as_deref()),
+        channels.whatsapp.as_ref().and_then(|c| c.agent.as_deref()),
+        channels.signal.as_ref().and_then(|c| c.agent.as_deref()),
+        channels.line.as_ref().and_then(|c| c.agent.as_deref()),
+    ];
+    ROUTABLE_CHANNELS
+        .iter()
+        .zip(agents)
+        .filter(|(_, routed)| *routed == Some(agent))
+        .map(|(channel, _)| channel.to_string())
+        .collect()
+}
+
+fn channel_agent_mut<'a>(channels: &'a mut ChannelsConfig, channel: &str) -> Option<&'a mut Option<String>> {
+    Some(match channel {
+        "telegram" => &mut channels.telegram.get_or_insert_with(Default::default).agent,
+        "discord" => &mut channels.discord.get_or_insert_with(Default::default).agent,
+        "slack" => &mut channels.slack.get_or_insert_with(Default::default).agent,
+        "whatsapp" => &mut channels.whatsapp.get_or_insert_with(Default::default).agent,
+        "signal" => &mut channels.signal.get_or_insert_with(Default::default).agent,
+        "line" => &mut channels.line.get_or_insert_with(Default::default).agent,
+        _ => return None,
+    })
+}
+
+/// `research-bot` → `RESEARCH_BOT`.
+fn env_prefix(name: &str) -> String {
+    upper_snake(name)
+}
+
+/// `botToken` / `bot-token` / `bot_token` → `BOT_TOKEN`.
+fn upper_snake(s: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_uppercase());
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else if !out.ends_with('_') && !out.is_empty() {
+            out.push('_');
+            prev_lower = false;
+        }
+    }
+    let out = out.trim_end_matches('_').to_string();
+    match out.chars().next() {
+        Some(c) if c.is_ascii_digit() => format!("_{}", out),
+        _ => out,
+    }
+}
+
+/// Replace sensitive string fields with `${PREFIX_KEY}` placeholders.
+/// Values that already are placeholders are left alone.
+fn placeholder_secrets(value: &mut Value, prefix: &str, used: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                match child {
+                    Value::String(s) if is_sensitive_key(key) && !s.is_empty() && !s.trim_start().starts_with("${") => {
+                        let base = format!("{}_{}", prefix, upper_snake(key));
+                        let mut var = base.clone();
+                        let mut n = 2;
+                        while used.contains(&var) {
+                            var = format!("{}_{}", base, n);
+                            n += 1;
+                        }
+                        *s = format!("${{{}}}", var);
+                        used.push(var);
+                    }
+                    _ => placeholder_secrets(child, prefix, used),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                placeholder_secrets(item, prefix, used);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{AgentsConfig, SlackChannelCfg};
+    use clawforge_core::TriggerSpec;
+
+    #[test]
+    fn export_replaces_secrets_and_round_trips() {
+        let mut agent = AgentSpec::new("research-bot", TriggerSpec::Manual);
+        agent.llm_policy.system_prompt = "Summarize the news.".into();
+        agent.allowed_skills = vec!["web-search".into()];
+        let mut config = ClawForgeConfig {
+            channels: Some(ChannelsConfig {
+                slack: Some(SlackChannelCfg { bot_token: Some("xoxb-real".into()), agent: Some("research-bot".into()), ..Default::default() }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut overrides: AgentEntry = serde_json::from_value(serde_json::json!({
+            "description": "news",
+            "models": { "custom": { "apiKey": "sk-live-123" } }
+        }))
+        .unwrap();
+        overrides.name = Some("Research".into());
+        config.agents = Some(AgentsConfig { list: [("research-bot".to_string(), overrides)].into(), ..Default::default() });
+        let job = PackCronJob {
+            schedule: "0 8 * * *".into(),
+            prompt: "Morning brief".into(),
+            channel: "slack".into(),
+            delivery_target: None,
+            timezone: Some("Europe/Berlin".into()),
+            delivery_template: None,
+            stagger_secs: 0,
+            max_runs: None,
+            enabled: true,
+        };
+
+        let pack = AgentPack::export(agent, &config, vec![job.clone()]).unwrap();
+        assert!(pack.agent.id.is_nil());
+        assert_eq!(pack.routes, vec!["slack"]);
+        assert_eq!(pack.env, vec!["RESEARCH_BOT_API_KEY"]);
+        let yaml = pack.to_yaml().unwrap();
+        assert!(!yaml.contains("sk-live-123") && !yaml.contains("xoxb-real"));
+
+        let mut imported = AgentPack::from_yaml(&yaml).unwrap();
+        assert_eq!(imported.cron_jobs, vec![job]);
+        assert_eq!(imported.agent.llm_policy.system_prompt, "Summarize the news.");
+
+        imported.agent.name = "copy".into();
+        let mut target = ClawForgeConfig {
+            channels: Some(ChannelsConfig {
+                slack: Some(SlackChannelCfg { agent: Some("other".into()), ..Default::default() }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(imported.apply_to_config(&mut target), vec!["slack"]);
+        assert_eq!(routes_for(&target, "copy"), vec!["slack"]);
+        assert!(target.agents.unwrap().list.contains_key("copy"));
+
+        assert!(AgentPack::from_yaml(&yaml.replace("packVersion: 1", "packVersion: 9")).is_err());
+    }
+}
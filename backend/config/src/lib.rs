@@ -8,23 +8,29 @@
 //! - Config redaction for safe logging/display
 //! - Default value application
 //! - Deep schema validation
+//! - Per-field provenance across default/file/profile/env/runtime layers
+//! - Portable agent packs with secret placeholders
 
+pub mod agent_pack;
 pub mod defaults;
 pub mod env;
 pub mod io;
 pub mod migration;
+pub mod provenance;
 pub mod redact;
 pub mod schema;
 pub mod validation;
 
 // Re-export most-used types at crate root.
 pub use schema::ClawForgeConfig;
+pub use agent_pack::{AgentPack, PackCronJob};
 pub use io::{config_dir, config_file_path, load_config, write_config, apply_merge_patch};
 pub use env::{
     collect_referenced_vars, contains_env_var_reference, resolve_env_vars, resolve_env_vars_with,
     MissingEnvVarError,
 };
 pub use migration::{migrate, CURRENT_VERSION};
+pub use provenance::{merge_layers, ConfigLayer, ConfigSource, ConfigSources, ExplainedConfig};
 pub use redact::{redact, collect_redacted_paths};
 pub use defaults::apply_all_defaults;
 pub use validation::{validate, ValidationReport, ConfigValidationError};
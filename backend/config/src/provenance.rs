@@ -0,0 +1,268 @@
+//! Config value provenance: records which layer produced each effective value.
+//!
+//! Layers are merged in order with RFC 7396 semantics (later layers win, `null`
+//! removes a key): defaults → config file → profile overlay → runtime overrides.
+//! Values that came from `${VAR}` substitution are attributed to the env var.
+
+use crate::defaults::apply_all_defaults;
+use crate::env::{collect_referenced_vars, resolve_env_vars};
+use crate::io::load_config;
+use crate::migration::{migrate, CURRENT_VERSION};
+use crate::schema::ClawForgeConfig;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The layer an effective config value was resolved from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "layer", rename_all = "camelCase")]
+pub enum ConfigSource {
+    /// Built-in default from `apply_all_defaults`.
+    Default,
+    /// The main config file.
+    File { path: String },
+    /// A named profile overlay file (`config.<profile>.yaml`).
+    Profile { name: String, path: String },
+    /// `${VAR}` substitution inside a file or overlay value.
+    Env { var: String },
+    /// A runtime override applied via merge patch.
+    Runtime,
+}
+
+/// One input to the merge, in precedence order.
+#[derive(Debug, Clone)]
+pub struct ConfigLayer {
+    pub source: ConfigSource,
+    pub value: Value,
+}
+
+/// Effective config plus the source layer of every resolved leaf field.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExplainedConfig {
+    pub config: Value,
+    /// Dotted path (e.g. `agents.defaults.maxConcurrent`) → source layer.
+    pub provenance: BTreeMap<String, ConfigSource>,
+}
+
+impl ExplainedConfig {
+    /// Source layer for a dotted path, if it resolved to a value.
+    pub fn source_of(&self, path: &str) -> Option<&ConfigSource> {
+        self.provenance.get(path)
+    }
+}
+
+/// Merge layers in order, tracking which layer last wrote each leaf.
+pub fn merge_layers(layers: &[ConfigLayer]) -> ExplainedConfig {
+    let mut out = ExplainedConfig { config: Value::Object(Map::new()), ..Default::default() };
+    for layer in layers {
+        merge_tracked(&mut out.config, &layer.value, "", &layer.source, &mut out.provenance);
+    }
+    out
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Drop provenance for `path` and everything beneath it.
+fn forget_subtree(provenance: &mut BTreeMap<String, ConfigSource>, path: &str) {
+    let nested = format!("{}.", path);
+    provenance.retain(|k, _| k != path && !k.starts_with(&nested));
+}
+
+fn merge_tracked(
+    target: &mut Value,
+    patch: &Value,
+    path: &str,
+    source: &ConfigSource,
+    provenance: &mut BTreeMap<String, ConfigSource>,
+) {
+    let Value::Object(patch_map) = patch else {
+        // Scalars and arrays replace wholesale and are recorded as leaves.
+        forget_subtree(provenance, path);
+        *target = patch.clone();
+        provenance.insert(path.to_string(), source.clone());
+        return;
+    };
+
+    if !target.is_object() {
+        forget_subtree(provenance, path);
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target_map) = target else { unreachable!() };
+
+    for (key, patch_val) in patch_map {
+        let child = join(path, key);
+        if patch_val.is_null() {
+            target_map.remove(key);
+            forget_subtree(provenance, &child);
+        } else {
+            let entry = target_map.entry(key.clone()).or_insert(Value::Null);
+            merge_tracked(entry, patch_val, &child, source, provenance);
+        }
+    }
+}
+
+/// Re-attribute leaves that reference `${VAR}` to the env var they pull from.
+fn attribute_env_vars(value: &Value, path: &str, provenance: &mut BTreeMap<String, ConfigSource>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                attribute_env_vars(child, &join(path, key), provenance);
+            }
+        }
+        Value::String(_) | Value::Array(_) => {
+            if let Some(var) = collect_referenced_vars(value).into_iter().next() {
+                provenance.insert(path.to_string(), ConfigSource::Env { var });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Resolve the path of a named profile overlay next to the main config file.
+pub fn profile_file_path(config_file: &Path, profile: &str) -> PathBuf {
+    config_file.with_extension(format!("{}.yaml", profile))
+}
+
+/// Everything needed to rebuild the effective config with provenance.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    /// Main config file.
+    pub path: PathBuf,
+    /// Active profile overlay, if any.
+    pub profile: Option<String>,
+    /// Runtime overrides (JSON merge patch), applied last.
+    pub runtime_overrides: Value,
+}
+
+impl ConfigSources {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), profile: None, runtime_overrides: Value::Null }
+    }
+
+    /// Load every layer from disk and merge them with provenance.
+    pub async fn explain(&self) -> Result<ExplainedConfig> {
+        let mut layers = vec![ConfigLayer {
+            source: ConfigSource::Default,
+            value: serde_json::to_value(apply_all_defaults(ClawForgeConfig::default()))
+                .context("Failed to serialize default config")?,
+        }];
+
+        if self.path.exists() {
+            layers.push(ConfigLayer {
+                source: ConfigSource::File { path: self.path.display().to_string() },
+                value: load_layer_value(&self.path).await?,
+            });
+        }
+
+        if let Some(name) = &self.profile {
+            let overlay = profile_file_path(&self.path, name);
+            if overlay.exists() {
+                layers.push(ConfigLayer {
+                    source: ConfigSource::Profile {
+                        name: name.clone(),
+                        path: overlay.display().to_string(),
+                    },
+                    value: load_layer_value(&overlay).await?,
+                });
+            } else {
+                tracing::warn!(profile = %name, path = %overlay.display(), "Profile overlay not found");
+            }
+        }
+
+        if self.runtime_overrides.is_object() {
+            layers.push(ConfigLayer {
+                source: ConfigSource::Runtime,
+                value: self.runtime_overrides.clone(),
+            });
+        }
+
+        let mut explained = merge_layers(&layers);
+        attribute_env_vars(&explained.config, "", &mut explained.provenance);
+        explained.config = resolve_env_vars(&explained.config)
+            .context("Failed to resolve env vars in config")?;
+        Ok(explained)
+    }
+}
+
+/// Load one config file as a migrated JSON value, without applying defaults.
+async fn load_layer_value(path: &Path) -> Result<Value> {
+    let raw = load_config(path).await?;
+    let value = serde_json::to_value(&raw).context("Failed to serialize config layer")?;
+    let version = value.get("_version").and_then(|v| v.as_u64()).unwrap_or(1) as u32;
+    if version < CURRENT_VERSION {
+        let (migrated, _mutated) = migrate(value, version)?;
+        return Ok(migrated);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn layer(source: ConfigSource, value: Value) -> ConfigLayer {
+        ConfigLayer { source, value }
+    }
+
+    #[test]
+    fn later_layer_wins_and_is_recorded() {
+        let file = ConfigSource::File { path: "config.yaml".into() };
+        let merged = merge_layers(&[
+            layer(ConfigSource::Default, json!({ "logging": { "level": "info", "redactSensitive": "tools" } })),
+            layer(file.clone(), json!({ "logging": { "level": "debug" } })),
+            layer(ConfigSource::Runtime, json!({ "messages": { "ackReactionScope": "all" } })),
+        ]);
+        assert_eq!(merged.config["logging"]["level"], "debug");
+        assert_eq!(merged.source_of("logging.level"), Some(&file));
+        assert_eq!(merged.source_of("logging.redactSensitive"), Some(&ConfigSource::Default));
+        assert_eq!(merged.source_of("messages.ackReactionScope"), Some(&ConfigSource::Runtime));
+    }
+
+    #[test]
+    fn null_removes_value_and_provenance() {
+        let merged = merge_layers(&[
+            layer(ConfigSource::Default, json!({ "logging": { "level": "info" } })),
+            layer(ConfigSource::Runtime, json!({ "logging": null })),
+        ]);
+        assert!(merged.config.get("logging").is_none());
+        assert!(merged.source_of("logging.level").is_none());
+    }
+
+    #[test]
+    fn scalar_over_object_drops_nested_provenance() {
+        let merged = merge_layers(&[
+            layer(ConfigSource::Default, json!({ "talk": { "voice": "alloy" } })),
+            layer(ConfigSource::Runtime, json!({ "talk": "off" })),
+        ]);
+        assert!(merged.source_of("talk.voice").is_none());
+        assert_eq!(merged.source_of("talk"), Some(&ConfigSource::Runtime));
+    }
+
+    #[test]
+    fn env_references_are_attributed_to_the_var() {
+        let mut merged = merge_layers(&[layer(
+            ConfigSource::File { path: "config.yaml".into() },
+            json!({ "auth": { "token": "${CF_TOKEN}" } }),
+        )]);
+        attribute_env_vars(&merged.config, "", &mut merged.provenance);
+        assert_eq!(
+            merged.source_of("auth.token"),
+            Some(&ConfigSource::Env { var: "CF_TOKEN".into() })
+        );
+    }
+
+    #[test]
+    fn profile_path_sits_next_to_main_file() {
+        let p = profile_file_path(Path::new("/etc/clawforge/config.yaml"), "prod");
+        assert_eq!(p, PathBuf::from("/etc/clawforge/config.prod.yaml"));
+    }
+}
@@ -43,7 +43,7 @@ pub fn redact(value: &Value) -> Value {
     redact_recursive(value, "")
 }
 
-fn is_sensitive_key(key: &str) -> bool {
+pub(crate) fn is_sensitive_key(key: &str) -> bool {
     API_KEY_KEYS.iter().any(|k| k.eq_ignore_ascii_case(key))
 }
 
@@ -70,6 +70,59 @@ pub struct ClawForgeConfig {
     /// Security configuration
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub security: Option<SecurityConfig>,
+
+    /// Inbound media limits, scanning and image description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media: Option<MediaConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaConfig {
+    /// Max payload bytes per class: `{ image, audio, video, document, other }`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<serde_json::Value>,
+    /// Antivirus scan run before any handler, e.g.
+    /// `{ kind: clamd, address: /run/clamav/clamd.ctl }`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scanner: Option<serde_json::Value>,
+    /// OpenAI vision model used to describe images; images are rejected
+    /// without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vision: Option<MediaVisionConfig>,
+    /// Image providers behind the `generate_image` tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation: Option<MediaGenerationConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaVisionConfig {
+    pub api_key: String,
+    /// Defaults to gpt-4o
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaGenerationConfig {
+    /// Agents that may generate images; `"*"` matches any agent
+    #[serde(default)]
+    pub agents: Vec<String>,
+    /// The first provider is the default
+    #[serde(default)]
+    pub providers: Vec<ImageProviderConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageProviderConfig {
+    pub provider: String, // "dalle3" | "sdxl" | "gemini" | "replicate"
+    pub api_key: String,
+    /// Gemini model or Replicate model version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -151,6 +204,18 @@ pub struct AgentsConfig {
     /// Per-named-agent overrides
     #[serde(default)]
     pub list: HashMap<String, AgentEntry>,
+
+    /// Which agents may message which via `send_to_agent`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_to_agent: Option<AgentToAgentConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentToAgentConfig {
+    /// sender agent → agents it may message. `"*"` matches any agent.
+    #[serde(default)]
+    pub allow: HashMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -186,8 +251,36 @@ pub struct AgentDefaults {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sandbox: Option<SandboxConfig>,
 
+    /// Per-tool execution environments; overrides `sandbox.driver` per tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution: Option<ExecutionConfig>,
+
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub system_prompt: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_content: Option<ExternalContentConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_search: Option<WebSearchConfig>,
+
+    /// When repeated or alternating tool calls count as a loop, and how
+    /// many detections lead to escalation and abort
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_guard: Option<LoopGuardSettings>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoopGuardSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_identical: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oscillation_cycles: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalate_after: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abort_after: Option<usize>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -230,29 +323,91 @@ pub struct SubagentDefaults {
     pub max_concurrent: Option<u32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_depth: Option<u32>,
+    /// Agent (by name) that runs the tasks of a `fanout`; no `fanout` tool
+    /// without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AgentToolsConfig {
+    /// Tool name globs (`*`, `?`); empty means every tool not denied.
     #[serde(default)]
     pub allow: Vec<String>,
     #[serde(default)]
     pub deny: Vec<String>,
     #[serde(default)]
     pub also_allow: Vec<String>,
+    /// Per-channel overrides, e.g. `{"whatsapp": {"deny": ["shell"]}}`.
+    /// Only read from `agents.defaults.tools`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub by_channel: HashMap<String, ChannelToolsConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelToolsConfig {
+    /// When set, only these tools may be called from the channel.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalContentConfig {
+    pub policy: Option<String>, // "block" | "warn" | "allow"
+    /// Model used to classify fetched content the heuristics pass
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classifier_model: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSearchConfig {
+    /// Search backends in fallback order, e.g.
+    /// `{ provider: brave, api_key: ..., rate_limit: { max_calls: 50, window_secs: 60 } }`
+    #[serde(default)]
+    pub providers: Vec<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SandboxConfig {
-    pub driver: Option<String>, // "none" | "docker" | "bwrap"
+    pub driver: Option<String>, // "none" | "docker" | "bwrap" | "native" | "seatbelt" | "appcontainer"
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub image: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub network: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub memory_limit: Option<String>,
+    /// Restrict sandbox network access to an allowlist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub egress: Option<SandboxEgressConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxEgressConfig {
+    /// Domains (`example.com` includes subdomains) and CIDRs; empty falls back to the agent's `allowedDomains`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Also drop non-proxied traffic with iptables, applied from the host (needs root and nsenter; Docker only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub firewall: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionConfig {
+    /// Environment for tools not listed in `tools`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>, // "host" | "docker" | "bwrap" | "native"
+    /// Tool name glob -> environment, e.g. `{"shell": "docker", "browser_*": "host"}`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tools: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -356,6 +511,14 @@ pub struct GatewayConfig {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tailscale: Option<TailscaleConfig>,
+
+    /// LAN discovery of node hosts over mDNS.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discovery: Option<NodeDiscoveryConfig>,
+
+    /// Peer gateways that selected channels/agents are proxied to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub federation: Option<FederationConfig>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -383,6 +546,52 @@ pub struct TailscaleConfig {
     pub serve: Option<bool>,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeDiscoveryConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// DNS-SD service to browse (default `_clawforge-node._tcp.local`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    /// Where approved nodes are saved (default `~/.clawforge/nodes.json`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationConfig {
+    /// This gateway's id as its peers know it (e.g. `home`).
+    pub gateway_id: String,
+    #[serde(default)]
+    pub peers: Vec<FederationPeer>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationPeer {
+    /// The peer's `gatewayId`.
+    pub id: String,
+    /// `ws(s)://host:port/federation`; leave unset for peers that dial in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Shared secret; both gateways must list the same token for each other.
+    pub token: String,
+    /// Channels (session key prefix, e.g. `telegram`) whose sessions run on the peer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<String>,
+    /// Agent ids whose sessions run on the peer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<String>,
+    /// Agent ids the peer may run on this gateway; forwards for any other
+    /// agent are refused.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accept_agents: Vec<String>,
+}
+
 // ---------------------------------------------------------------------------
 // Messages, Logging, Memory, Talk, Session, Plugins, Skills, Hooks, Channels
 // ---------------------------------------------------------------------------
@@ -394,6 +603,10 @@ pub struct MessagesConfig {
     pub ack_reaction_scope: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_length: Option<u64>,
+    /// Named message templates (notification hooks, scheduled deliveries),
+    /// checked for syntax errors when the config is loaded.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub templates: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -405,6 +618,45 @@ pub struct LoggingConfig {
     pub redact_sensitive: Option<String>, // "none" | "tools" | "all"
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub subsystems: Option<HashMap<String, String>>,
+    /// When the daemon rotates gateway logs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<LogRotationConfig>,
+    /// Where the daemon ships structured logs, besides the local files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shipping: Option<LogShippingConfig>,
+    /// Days agent events stay queryable at `/api/events` (default 30).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_retention_days: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRotationConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u64>,
+    /// Rotated files to keep.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogShippingConfig {
+    pub target: String, // "syslog" | "journald" | "http"
+    /// Collector endpoint for `http`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// `host:port` of a remote syslog server (UDP); the local `/dev/log` otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_level: Option<String>, // "trace" | "debug" | "info" | "warn" | "error"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -435,10 +687,95 @@ pub struct MemoryCollection {
 pub struct TalkConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
+    /// "openai" | "elevenlabs" | "piper"
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub provider: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub voice: Option<String>,
+    /// Piper binary for the offline `piper` provider; defaults to `piper` on PATH
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<String>,
+    /// Piper voice models; defaults to `<data dir>/clawforge/tts-voices`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voices_dir: Option<String>,
+    /// Speech-to-text provider shared by media, voice calls and Discord voice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stt: Option<SttConfig>,
+    /// Wake word gating for always-on voice nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wake: Option<WakeWordConfig>,
+    /// Phone calls through Twilio; needs `stt` and a TTS provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calls: Option<VoiceCallConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceCallConfig {
+    pub account_sid: String,
+    pub auth_token: String,
+    /// Number calls are placed from, in E.164
+    pub from_number: String,
+    /// Public origin of the gateway, e.g. `https://claw.example.com`; Twilio
+    /// signs its webhooks against it
+    pub public_url: String,
+    /// Provider and model that answer on calls
+    pub provider: String,
+    pub model: String,
+    /// Spoken when a call connects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub greeting: Option<String>,
+    /// Token LiveKit egress presents on `/voice/livekit/stream`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub livekit_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WakeWordConfig {
+    pub engine: String, // "openwakeword" | "porcupine"
+    /// Default wake words, e.g. `["hey claw"]`
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// Per-node wake words, keyed by node id; replaces `words` for that node
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub nodes: HashMap<String, Vec<String>>,
+    /// Directory holding `<word>.onnx` / `<word>.ppn` models
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_dir: Option<String>,
+    /// Detection threshold / sensitivity, 0.0-1.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensitivity: Option<f32>,
+    /// Picovoice access key for `porcupine`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_key: Option<String>,
+    /// Runner binary; defaults to `openwakeword-runner` / `porcupine-runner` on PATH
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runner_path: Option<String>,
+    /// Seconds to keep listening after the wake word or the last turn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_secs: Option<u32>,
+}
+
+impl WakeWordConfig {
+    /// Wake words for `node_id`, falling back to the defaults.
+    pub fn words_for_node(&self, node_id: &str) -> &[String] {
+        self.nodes.get(node_id).map(Vec::as_slice).unwrap_or(&self.words)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SttConfig {
+    pub provider: String, // "openai" | "deepgram" | "local"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Model name, or the model file path for `local`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// whisper.cpp binary for `local`; defaults to `whisper-cli` on PATH
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -455,6 +792,9 @@ pub struct PluginsConfig {
     pub installed: Vec<PluginEntry>,
     #[serde(default)]
     pub disabled: Vec<String>,
+    /// Marketplace index (HTTPS URL or local file) used by `clawforge plugin`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -465,6 +805,9 @@ pub struct PluginEntry {
     pub source: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    /// SHA-256 of the installed plugin tree; reinstalls must match it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -497,6 +840,17 @@ pub struct HookEntry {
     pub id: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
+    /// Hook implementation: "script" | "webhook"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Settings for `kind`: a script hook's command or a webhook's url,
+    /// plus phases, timeout and failure policy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<serde_json::Value>,
+    /// Trigger the hook must match to run, e.g.
+    /// `{"type": "onCondition", "condition": {"op": "expr", "expr": "..."}}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -527,6 +881,9 @@ pub struct TelegramChannelCfg {
     pub agent: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub webhook_secret: Option<String>,
+    /// Stream replies by editing a placeholder message as tokens arrive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_edits: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -538,6 +895,9 @@ pub struct DiscordChannelCfg {
     pub allow_from: Option<Vec<String>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub agent: Option<String>,
+    /// Stream replies by editing a placeholder message as tokens arrive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_edits: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -547,15 +907,24 @@ pub struct SlackChannelCfg {
     pub bot_token: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub app_token: Option<String>,
+    /// Signing secret used to verify `X-Slack-Signature` on webhooks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub allow_from: Option<Vec<String>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub agent: Option<String>,
+    /// Stream replies by editing a placeholder message as tokens arrive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_edits: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WhatsAppChannelCfg {
+    /// Meta app secret used to verify `X-Hub-Signature-256` on webhooks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_secret: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub allow_from: Option<Vec<String>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -595,6 +964,55 @@ pub struct SecurityConfig {
     pub exec_approvals: Option<ExecApprovalsConfig>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pairing: Option<PairingConfig>,
+    /// Per-channel DM policy, keyed by adapter name (`telegram`, `discord`, ...)
+    #[serde(default)]
+    pub dm: HashMap<String, DmChannelPolicy>,
+    /// Publisher keys whose skill signatures are trusted at install time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_skill_keys: Vec<TrustedSkillKey>,
+    /// Credentials agents may lease into sandboxed execs, keyed by secret name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sandbox_secrets: HashMap<String, SandboxSecretConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxSecretConfig {
+    /// Read the value from this gateway env var
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_env: Option<String>,
+    /// Inline value, used when `fromEnv` is unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Env var name inside the sandbox (default: the secret name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_var: Option<String>,
+    /// Agents allowed to lease it; empty allows all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<String>,
+    /// Lease lifetime (default 60)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedSkillKey {
+    /// Key ID referenced by `SKILL.sig`
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    /// Hex-encoded Ed25519 public key
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DmChannelPolicy {
+    pub policy: Option<String>, // "pairing" | "allowlist" | "open" | "disabled"
+    /// Sender IDs that skip pairing
+    #[serde(default)]
+    pub allow_from: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -606,6 +1024,9 @@ pub struct ExecApprovalsConfig {
     pub socket_path: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub socket_token: Option<String>,
+    /// Exec allowlist file; script hooks run only commands it allows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowlist_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -1,6 +1,7 @@
 //! Config validation: deep schema checks with user-friendly error messages.
 
 use crate::schema::ClawForgeConfig;
+use clawforge_core::Template;
 use thiserror::Error;
 
 /// A config validation error with field path and message.
@@ -47,6 +48,8 @@ pub fn validate(config: &ClawForgeConfig) -> ValidationReport {
     validate_channels(config, &mut report);
     validate_agents(config, &mut report);
     validate_memory(config, &mut report);
+    validate_messages(config, &mut report);
+    validate_logging(config, &mut report);
     report
 }
 
@@ -139,10 +142,12 @@ fn validate_agents(config: &ClawForgeConfig, report: &mut ValidationReport) {
         }
         if let Some(sandbox) = &defaults.sandbox {
             if let Some(driver) = &sandbox.driver {
-                if !matches!(driver.as_str(), "none" | "docker" | "bwrap") {
+                if !matches!(driver.as_str(), "none" | "docker" | "bwrap" | "native" | "seatbelt" | "appcontainer") {
                     report.error(
                         "agents.defaults.sandbox.driver",
-                        format!("Unknown sandbox driver '{driver}'. Use 'none', 'docker', or 'bwrap'"),
+                        format!(
+                            "Unknown sandbox driver '{driver}'. Use 'none', 'docker', 'bwrap', 'native', 'seatbelt', or 'appcontainer'"
+                        ),
                     );
                 }
             }
@@ -160,6 +165,30 @@ fn validate_memory(config: &ClawForgeConfig, report: &mut ValidationReport) {
     }
 }
 
+/// Message templates must parse; errors carry the template's line and column.
+fn validate_messages(config: &ClawForgeConfig, report: &mut ValidationReport) {
+    let Some(messages) = &config.messages else { return };
+    for (name, source) in &messages.templates {
+        if let Err(e) = Template::parse(source) {
+            report.error(format!("messages.templates.{name}"), e.to_string());
+        }
+    }
+}
+
+/// Log shipping needs a known target, and `http` needs a collector URL.
+fn validate_logging(config: &ClawForgeConfig, report: &mut ValidationReport) {
+    let Some(shipping) = config.logging.as_ref().and_then(|l| l.shipping.as_ref()) else { return };
+    match shipping.target.as_str() {
+        "syslog" | "journald" => {}
+        "http" if shipping.url.is_none() => report.error("logging.shipping.url", "HTTP log shipping needs a collector URL"),
+        "http" => {}
+        other => report.error(
+            "logging.shipping.target",
+            format!("Unknown log shipping target '{other}' (syslog, journald or http)"),
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,4 +214,30 @@ mod tests {
         assert!(!report.is_valid());
         assert!(report.errors[0].path.contains("tls"));
     }
+
+    #[test]
+    fn broken_message_template_is_error() {
+        let mut cfg = ClawForgeConfig::default();
+        let mut messages = crate::schema::MessagesConfig::default();
+        messages.templates.insert("briefing".to_string(), "{{ run.output }}".to_string());
+        messages.templates.insert("alert".to_string(), "{% if run.ok %}ok".to_string());
+        cfg.messages = Some(messages);
+        let report = validate(&cfg);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].path, "messages.templates.alert");
+        assert!(report.errors[0].message.contains("line 1"));
+    }
+
+    #[test]
+    fn http_log_shipping_needs_url() {
+        let cfg = ClawForgeConfig {
+            logging: Some(crate::schema::LoggingConfig {
+                shipping: Some(crate::schema::LogShippingConfig { target: "http".to_string(), ..Default::default() }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let report = validate(&cfg);
+        assert_eq!(report.errors[0].path, "logging.shipping.url");
+    }
 }
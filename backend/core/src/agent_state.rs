@@ -0,0 +1,48 @@
+//! Inter-run agent state: the entry type and backend trait shared by the
+//! `state_get` / `state_set` tools and the stores that persist them.
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Longest accepted key, in bytes.
+pub const MAX_KEY_LEN: usize = 256;
+/// Largest accepted value, in bytes of serialized JSON.
+pub const MAX_VALUE_BYTES: usize = 64 * 1024;
+
+/// One stored value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateEntry {
+    pub key: String,
+    pub value: Value,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Backend trait for per-agent state persistence.
+#[async_trait]
+pub trait StateBackend: Send + Sync {
+    async fn get(&self, agent_id: Uuid, key: &str) -> Result<Option<StateEntry>>;
+    async fn set(&self, agent_id: Uuid, key: &str, value: Value) -> Result<StateEntry>;
+    async fn delete(&self, agent_id: Uuid, key: &str) -> Result<bool>;
+    /// All of the agent's entries, ordered by key.
+    async fn list(&self, agent_id: Uuid) -> Result<Vec<StateEntry>>;
+}
+
+/// Reject empty or oversized keys and oversized values before storing.
+pub fn validate_entry(key: &str, value: &Value) -> Result<()> {
+    if key.trim().is_empty() {
+        bail!("State key must not be empty");
+    }
+    if key.len() > MAX_KEY_LEN {
+        bail!("State key is longer than {} bytes", MAX_KEY_LEN);
+    }
+    let size = serde_json::to_vec(value)?.len();
+    if size > MAX_VALUE_BYTES {
+        bail!("State value is {} bytes (max {})", size, MAX_VALUE_BYTES);
+    }
+    Ok(())
+}
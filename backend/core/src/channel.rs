@@ -2,6 +2,7 @@ use tokio::sync::mpsc;
 use tracing::{debug, info};
 
 use crate::message::Message;
+use crate::topology::BusProbe;
 
 /// Default channel buffer size for inter-component messaging.
 const DEFAULT_BUFFER_SIZE: usize = 256;
@@ -51,6 +52,16 @@ impl ClawBus {
         }
     }
 
+    /// Handle for reading queue depths once the receivers are taken.
+    pub fn probe(&self) -> BusProbe {
+        BusProbe::new(vec![
+            ("scheduler", self.scheduler_tx.clone()),
+            ("planner", self.planner_tx.clone()),
+            ("executor", self.executor_tx.clone()),
+            ("supervisor", self.supervisor_tx.clone()),
+        ])
+    }
+
     /// Take the scheduler receiver (can only be called once).
     pub fn take_scheduler_rx(&mut self) -> Option<mpsc::Receiver<Message>> {
         debug!("Scheduler receiver taken");
@@ -0,0 +1,170 @@
+//! Where a prompt's tokens go.
+//!
+//! The planner records a [`ContextBreakdown`] each time it assembles a prompt:
+//! the system prompt, skills, tool schemas, memory, history and pending media,
+//! each with an estimated token count. [`ContextLog`] keeps the latest
+//! breakdown per session so the API can show what fills the context window.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Sessions kept in the log; the oldest is evicted first.
+const MAX_SESSIONS: usize = 256;
+
+/// Rough token estimate: ~4 characters per token for English text and code,
+/// never less than one token per word.
+pub fn estimate_tokens(text: &str) -> usize {
+    let chars = text.chars().count();
+    let words = text.split_whitespace().count();
+    chars.div_ceil(4).max(words)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    SystemPrompt,
+    Skills,
+    Tools,
+    Memory,
+    History,
+    PendingMedia,
+}
+
+/// A named part of a segment, e.g. one skill or one tool.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SegmentItem {
+    pub name: String,
+    pub tokens: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContextSegment {
+    pub kind: SegmentKind,
+    pub tokens: usize,
+    pub chars: usize,
+    /// Percentage of the whole prompt.
+    pub share: f64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<SegmentItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextBreakdown {
+    pub session: String,
+    pub agent: String,
+    pub model: String,
+    pub total_tokens: usize,
+    /// Output tokens reserved by the agent's policy.
+    pub max_output_tokens: u32,
+    pub segments: Vec<ContextSegment>,
+    pub assembled_at: DateTime<Utc>,
+}
+
+impl ContextBreakdown {
+    pub fn new(session: impl Into<String>, agent: impl Into<String>, model: impl Into<String>, max_output_tokens: u32) -> Self {
+        Self {
+            session: session.into(),
+            agent: agent.into(),
+            model: model.into(),
+            total_tokens: 0,
+            max_output_tokens,
+            segments: Vec::new(),
+            assembled_at: Utc::now(),
+        }
+    }
+
+    /// Add a segment made of `items` (name, text). An empty segment is still
+    /// listed so every kind shows up with its zero.
+    pub fn add<'a>(&mut self, kind: SegmentKind, items: impl IntoIterator<Item = (&'a str, &'a str)>) -> &mut Self {
+        let mut segment = ContextSegment { kind, tokens: 0, chars: 0, share: 0.0, items: Vec::new() };
+        for (name, text) in items {
+            let tokens = estimate_tokens(text);
+            segment.tokens += tokens;
+            segment.chars += text.chars().count();
+            if !name.is_empty() {
+                segment.items.push(SegmentItem { name: name.to_string(), tokens });
+            }
+        }
+        segment.items.sort_by_key(|item| std::cmp::Reverse(item.tokens));
+        self.total_tokens += segment.tokens;
+        self.segments.push(segment);
+        let total = self.total_tokens.max(1) as f64;
+        for segment in &mut self.segments {
+            segment.share = (segment.tokens as f64 * 1000.0 / total).round() / 10.0;
+        }
+        self
+    }
+
+    pub fn segment(&self, kind: SegmentKind) -> Option<&ContextSegment> {
+        self.segments.iter().find(|s| s.kind == kind)
+    }
+}
+
+/// Latest breakdown per session, shared between the planner and the API.
+#[derive(Clone, Default)]
+pub struct ContextLog {
+    inner: Arc<RwLock<LogInner>>,
+}
+
+#[derive(Default)]
+struct LogInner {
+    by_session: HashMap<String, Arc<ContextBreakdown>>,
+    order: VecDeque<String>,
+}
+
+impl ContextLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `breakdown` under its session and any `aliases` (e.g. a
+    /// channel session key next to the run id).
+    pub fn record(&self, breakdown: ContextBreakdown, aliases: &[String]) {
+        let breakdown = Arc::new(breakdown);
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        for key in std::iter::once(&breakdown.session).chain(aliases) {
+            if inner.by_session.insert(key.clone(), breakdown.clone()).is_none() {
+                inner.order.push_back(key.clone());
+            }
+        }
+        while inner.order.len() > MAX_SESSIONS {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.by_session.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, session: &str) -> Option<Arc<ContextBreakdown>> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).by_session.get(session).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakdown_totals_shares_and_log_lookup() {
+        let mut breakdown = ContextBreakdown::new("run-1", "writer", "gpt-4o", 1024);
+        breakdown
+            .add(SegmentKind::SystemPrompt, [("", "You are a careful writer.")])
+            .add(SegmentKind::Skills, [("style", "Use short sentences. Prefer active voice over passive voice."), ("empty", "")])
+            .add(SegmentKind::Memory, []);
+
+        assert_eq!(estimate_tokens("You are a careful writer."), 7);
+        assert_eq!(breakdown.total_tokens, 7 + 15);
+        let skills = breakdown.segment(SegmentKind::Skills).unwrap();
+        assert_eq!(skills.items[0], SegmentItem { name: "style".into(), tokens: 15 });
+        assert_eq!(skills.share, 68.2);
+        assert_eq!(breakdown.segment(SegmentKind::Memory).unwrap().tokens, 0);
+
+        let log = ContextLog::new();
+        log.record(breakdown, &["telegram:42".to_string()]);
+        assert_eq!(log.get("telegram:42").unwrap().session, "run-1");
+        assert!(log.get("run-2").is_none());
+    }
+}
@@ -30,10 +30,18 @@ pub enum EventKind {
     ActionApproved,
     /// An action was denied by capability check
     ActionDenied,
+    /// A dangerous action is waiting on human approval
+    ApprovalRequested,
+    /// A line of stdout/stderr from an action still running
+    ActionOutput,
     /// An action was executed
     ActionExecuted,
     /// An action failed
     ActionFailed,
+    /// An agent repeated or oscillated between tool calls and was intervened on
+    ToolLoopDetected,
+    /// The `http` tool made a request (method, URL, status and sizes only)
+    HttpExchange,
     /// A run completed successfully
     RunCompleted,
     /// A run failed
@@ -42,6 +50,28 @@ pub enum EventKind {
     BudgetWarning,
     /// Budget limit was exceeded
     BudgetExceeded,
+    /// Resource usage sampled from the run's sandbox
+    ResourceUsage,
+    /// The run's sandbox went over a resource limit
+    ResourceLimitExceeded,
+    /// A step's output did not match its output contract
+    OutputContractViolated,
+    /// A sandbox tried to reach a host outside its egress policy
+    EgressBlocked,
+    /// A sandbox exec was OOM-killed or timed out, or the sandbox outlived its lifetime
+    SandboxResourceExceeded,
+    /// A phone call was connected to an agent
+    CallStarted,
+    /// The caller or the agent finished a turn on a call
+    CallTurn,
+    /// A phone call hung up
+    CallEnded,
+    /// A channel delivered an inbound media payload
+    MediaReceived,
+    /// A media payload was turned into text for the session
+    MediaProcessed,
+    /// A media payload was refused (unsupported, too large, infected) or failed processing
+    MediaRejected,
 }
 
 impl Event {
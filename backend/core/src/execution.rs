@@ -0,0 +1,164 @@
+//! Tool execution matrix: which environment each tool runs in, per agent.
+//!
+//! Configured under `agents.defaults.execution` and `agents.list.<name>.execution`:
+//!
+//! ```json
+//! { "default": "docker", "tools": { "shell": "docker", "python*": "bwrap", "browser_*": "host" } }
+//! ```
+//!
+//! Within a `tools` map an exact tool name beats globs, and a longer glob beats
+//! a shorter one. Across layers, first match wins: the agent's `tools`, the
+//! defaults' `tools`, the agent's `default` (or its `sandbox.driver`), the
+//! defaults' `default` (or `sandbox.driver`), then the host.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::tool_policy::glob_match;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionEnv {
+    /// Directly on the gateway host.
+    Host,
+    Docker,
+    Bwrap,
+    /// The OS sandbox: seatbelt on macOS, AppContainer on Windows.
+    Native,
+}
+
+impl ExecutionEnv {
+    /// `none` is the legacy spelling of `host` in `sandbox.driver`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "host" | "none" => Some(ExecutionEnv::Host),
+            "docker" => Some(ExecutionEnv::Docker),
+            "bwrap" | "bubblewrap" => Some(ExecutionEnv::Bwrap),
+            "native" | "seatbelt" | "appcontainer" => Some(ExecutionEnv::Native),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExecutionEnv::Host => "host",
+            ExecutionEnv::Docker => "docker",
+            ExecutionEnv::Bwrap => "bwrap",
+            ExecutionEnv::Native => "native",
+        }
+    }
+}
+
+/// Execution rules for one layer (defaults or one agent).
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionRules {
+    pub default: Option<ExecutionEnv>,
+    /// Tool name globs and their environments.
+    pub tools: Vec<(String, ExecutionEnv)>,
+}
+
+impl ExecutionRules {
+    /// Parse an agent entry (`execution` plus the legacy `sandbox.driver`).
+    fn from_agent_json(entry: &Value, scope: &str) -> Self {
+        let parse = |v: &Value, what: &str| -> Option<ExecutionEnv> {
+            let s = v.as_str()?;
+            let env = ExecutionEnv::parse(s);
+            if env.is_none() {
+                warn!("Unknown execution environment '{}' for {} in {}", s, what, scope);
+            }
+            env
+        };
+        let execution = entry.get("execution");
+        let default = execution
+            .and_then(|e| e.get("default"))
+            .and_then(|v| parse(v, "default"))
+            .or_else(|| entry.pointer("/sandbox/driver").and_then(|v| parse(v, "sandbox.driver")));
+        let tools = execution
+            .and_then(|e| e.get("tools"))
+            .and_then(Value::as_object)
+            .map(|map| map.iter().filter_map(|(glob, v)| Some((glob.clone(), parse(v, glob)?))).collect())
+            .unwrap_or_default();
+        Self { default, tools }
+    }
+
+    fn tool_env(&self, tool: &str) -> Option<ExecutionEnv> {
+        self.tools
+            .iter()
+            .filter(|(glob, _)| glob_match(glob, tool))
+            .max_by_key(|(glob, _)| (glob.eq_ignore_ascii_case(tool), glob.len()))
+            .map(|(_, env)| *env)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionMatrix {
+    defaults: ExecutionRules,
+    agents: HashMap<String, ExecutionRules>,
+}
+
+impl ExecutionMatrix {
+    pub fn new(defaults: ExecutionRules) -> Self {
+        Self { defaults, agents: HashMap::new() }
+    }
+
+    pub fn with_agent(mut self, agent: impl Into<String>, rules: ExecutionRules) -> Self {
+        self.agents.insert(agent.into(), rules);
+        self
+    }
+
+    /// Build from the `agents` section of the config (camelCase JSON).
+    pub fn from_agents_config(agents: &Value) -> Self {
+        let defaults = agents
+            .get("defaults")
+            .map(|d| ExecutionRules::from_agent_json(d, "defaults"))
+            .unwrap_or_default();
+        let mut matrix = Self::new(defaults);
+        if let Some(list) = agents.get("list").and_then(Value::as_object) {
+            for (name, entry) in list {
+                matrix.agents.insert(name.clone(), ExecutionRules::from_agent_json(entry, name));
+            }
+        }
+        matrix
+    }
+
+    /// Environment `tool` runs in when called by `agent`.
+    pub fn resolve(&self, tool: &str, agent: Option<&str>) -> ExecutionEnv {
+        let agent = agent.and_then(|a| self.agents.get(a));
+        agent
+            .and_then(|a| a.tool_env(tool))
+            .or_else(|| self.defaults.tool_env(tool))
+            .or_else(|| agent.and_then(|a| a.default))
+            .or(self.defaults.default)
+            .unwrap_or(ExecutionEnv::Host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn resolves_per_agent_tool_environments() {
+        let matrix = ExecutionMatrix::from_agents_config(&json!({
+            "defaults": {
+                "sandbox": { "driver": "docker" },
+                "execution": { "tools": { "browser_*": "host", "python*": "bwrap" } }
+            },
+            "list": {
+                "ops": { "execution": { "default": "host", "tools": { "shell": "docker" } } },
+                "legacy": { "sandbox": { "driver": "none" } },
+                "laptop": { "sandbox": { "driver": "seatbelt" } }
+            }
+        }));
+        assert_eq!(matrix.resolve("shell", Some("ops")), ExecutionEnv::Docker);
+        assert_eq!(matrix.resolve("file_write", Some("ops")), ExecutionEnv::Host);
+        assert_eq!(matrix.resolve("python_exec", Some("ops")), ExecutionEnv::Bwrap);
+        assert_eq!(matrix.resolve("BROWSER_open", None), ExecutionEnv::Host);
+        assert_eq!(matrix.resolve("shell", None), ExecutionEnv::Docker);
+        assert_eq!(matrix.resolve("shell", Some("legacy")), ExecutionEnv::Host);
+        assert_eq!(matrix.resolve("shell", Some("laptop")), ExecutionEnv::Native);
+    }
+}
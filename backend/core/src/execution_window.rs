@@ -0,0 +1,140 @@
+//! Execution windows: host conditions a scheduled agent waits for.
+//!
+//! On laptops and home servers, heavy agents shouldn't start while the machine
+//! is on battery, busy, or in use. When a trigger fires outside its window the
+//! scheduler defers the run and re-checks until the window opens (or until
+//! `max_deferral_secs` passes, after which it runs anyway).
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+fn default_recheck_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionWindow {
+    /// Only run on mains power.
+    #[serde(default)]
+    pub require_ac_power: bool,
+    /// On battery, only run at or above this charge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_battery_percent: Option<u8>,
+    /// Only run while the 1-minute load average per CPU is at or below this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_load_per_cpu: Option<f32>,
+    /// Only run once the user has been idle this long.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_idle_secs: Option<u64>,
+    /// Run anyway after deferring this long (None = wait indefinitely).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_deferral_secs: Option<u64>,
+    /// How often to re-check while deferred.
+    #[serde(default = "default_recheck_secs")]
+    pub recheck_secs: u64,
+}
+
+impl Default for ExecutionWindow {
+    fn default() -> Self {
+        Self {
+            require_ac_power: false,
+            min_battery_percent: None,
+            max_load_per_cpu: None,
+            min_idle_secs: None,
+            max_deferral_secs: None,
+            recheck_secs: default_recheck_secs(),
+        }
+    }
+}
+
+/// A sample of host state. `None` means the host can't report it (e.g. a
+/// desktop has no battery); unknown readings never block a run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HostActivity {
+    pub on_ac_power: Option<bool>,
+    pub battery_percent: Option<u8>,
+    pub load_per_cpu: Option<f32>,
+    /// Seconds since the last keyboard/mouse/terminal input.
+    pub idle_secs: Option<u64>,
+}
+
+/// Source of `HostActivity` readings for the scheduler.
+#[async_trait]
+pub trait ActivityProbe: Send + Sync {
+    async fn sample(&self) -> HostActivity;
+}
+
+/// A probe that can't read anything, so no window ever blocks.
+pub struct UnknownActivity;
+
+#[async_trait]
+impl ActivityProbe for UnknownActivity {
+    async fn sample(&self) -> HostActivity {
+        HostActivity::default()
+    }
+}
+
+impl ExecutionWindow {
+    /// Why the window is closed for `host`; empty when the agent may run.
+    pub fn blockers(&self, host: &HostActivity) -> Vec<String> {
+        let mut blockers = Vec::new();
+        let on_battery = host.on_ac_power == Some(false);
+        if self.require_ac_power && on_battery {
+            blockers.push("on battery power".to_string());
+        }
+        if let (Some(min), Some(level), true) = (self.min_battery_percent, host.battery_percent, on_battery) {
+            if level < min {
+                blockers.push(format!("battery at {}% (needs {}%)", level, min));
+            }
+        }
+        if let (Some(max), Some(load)) = (self.max_load_per_cpu, host.load_per_cpu) {
+            if load > max {
+                blockers.push(format!("load {:.2} per CPU (max {:.2})", load, max));
+            }
+        }
+        if let (Some(min), Some(idle)) = (self.min_idle_secs, host.idle_secs) {
+            if idle < min {
+                blockers.push(format!("user active {}s ago (needs {}s idle)", idle, min));
+            }
+        }
+        blockers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_on_battery_load_and_activity() {
+        let window = ExecutionWindow {
+            require_ac_power: true,
+            max_load_per_cpu: Some(0.5),
+            min_idle_secs: Some(300),
+            ..Default::default()
+        };
+        let busy_laptop = HostActivity {
+            on_ac_power: Some(false),
+            battery_percent: Some(80),
+            load_per_cpu: Some(1.25),
+            idle_secs: Some(12),
+        };
+        assert_eq!(
+            window.blockers(&busy_laptop),
+            vec!["on battery power", "load 1.25 per CPU (max 0.50)", "user active 12s ago (needs 300s idle)"]
+        );
+
+        // A headless server reports neither power nor idle time.
+        let server = HostActivity { load_per_cpu: Some(0.1), ..Default::default() };
+        assert!(window.blockers(&server).is_empty());
+
+        let battery_floor = ExecutionWindow { min_battery_percent: Some(30), ..Default::default() };
+        assert_eq!(
+            battery_floor.blockers(&HostActivity { on_ac_power: Some(false), battery_percent: Some(18), ..Default::default() }),
+            vec!["battery at 18% (needs 30%)"]
+        );
+        assert!(battery_floor
+            .blockers(&HostActivity { on_ac_power: Some(true), battery_percent: Some(18), ..Default::default() })
+            .is_empty());
+    }
+}
@@ -1,21 +1,39 @@
+pub mod agent_state;
 pub mod channel;
+pub mod context_breakdown;
 pub mod error;
 pub mod event;
+pub mod execution;
+pub mod execution_window;
 pub mod message;
+pub mod outbound_media;
+pub mod output_contract;
 pub mod session_export;
 pub mod session_policy;
+pub mod template;
+pub mod tool_policy;
 pub mod tools;
+pub mod topology;
 pub mod traits;
 pub mod types;
 
 pub use channel::ClawBus;
+pub use context_breakdown::{estimate_tokens, ContextBreakdown, ContextLog, ContextSegment, SegmentItem, SegmentKind};
 pub use error::ClawError;
 pub use event::{Event, EventKind};
+pub use execution::{ExecutionEnv, ExecutionMatrix, ExecutionRules};
+pub use execution_window::{ActivityProbe, ExecutionWindow, HostActivity, UnknownActivity};
 pub use message::{
     ActionProposal, AuditEventPayload, JobTrigger, Message, PlanRequest, ProposedAction, MemoryQueryRequest, MemoryQueryResponse, MemorySearchResult,
+    RepairRequest,
 };
+pub use outbound_media::{OutboundMedia, OutboundMediaKind, OutboundMediaSink};
+pub use output_contract::{ContractViolation, OutputContract};
 pub use traits::{Component, Tool, LlmProvider, LlmRequest, LlmResponse};
 pub use types::{
     ActionType, AgentSpec, Capabilities, FailurePolicy, LlmPolicy, TriggerSpec, WorkflowStep, MemoryConfig, Role,
 };
+pub use tool_policy::{glob_match, MatchedRule, RuleList, ToolPolicyDecision, ToolPolicyEngine, ToolRules};
 pub use session_export::{session_slug, SessionExporter, SessionMessage};
+pub use template::{Template, TemplateError};
+pub use topology::{BusProbe, NodeKind, Topology, TopologyEdge, TopologyNode};
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::event::Event;
+use crate::output_contract::OutputContract;
 use crate::types::{AgentSpec, Capabilities};
 
 /// Messages exchanged between components via the ClawBus.
@@ -32,6 +33,8 @@ pub enum Message {
         run_id: Uuid,
         prompt: String,
     },
+    /// Executor → Planner: a step's output broke its contract, re-plan it
+    RepairOutput(RepairRequest),
 }
 
 /// A trigger event from the scheduler.
@@ -59,6 +62,53 @@ pub struct ActionProposal {
     pub action: ProposedAction,
     /// Agent's declared capabilities — the executor enforces these; never trusts defaults.
     pub capabilities: Capabilities,
+    /// Agent name, used to look up per-agent tool policy.
+    #[serde(default)]
+    pub agent_name: Option<String>,
+    /// Channel the run was triggered from, for per-channel tool policy.
+    #[serde(default)]
+    pub channel: Option<String>,
+    /// Output schema for this step, checked by the executor.
+    #[serde(default)]
+    pub output_contract: Option<OutputContract>,
+    /// Repair prompts already spent on this step.
+    #[serde(default)]
+    pub repair_attempt: u32,
+    /// Chat session the run serves; sandboxes are kept per session.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Person the run acts for (a linked `person:<uuid>`, or
+    /// `channel:sender`); approvals are remembered per principal.
+    #[serde(default)]
+    pub principal: Option<String>,
+    /// Chat the run was triggered from; approval prompts are posted there.
+    #[serde(default)]
+    pub chat_id: Option<String>,
+}
+
+impl ActionProposal {
+    /// Key for per-session state such as sandboxes: the session, or the run
+    /// itself when it was not started from a chat session.
+    pub fn session_key(&self) -> String {
+        self.session_id.clone().unwrap_or_else(|| self.run_id.to_string())
+    }
+
+    /// Key for per-person state such as approvals: the principal, or the
+    /// session key when the run has none.
+    pub fn principal_key(&self) -> String {
+        self.principal.clone().unwrap_or_else(|| self.session_key())
+    }
+}
+
+/// Ask the planner to redo a step whose output broke its contract.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairRequest {
+    pub run_id: Uuid,
+    pub agent_id: Uuid,
+    pub step_index: usize,
+    /// Attempt number of the repaired proposal (1 for the first repair).
+    pub attempt: u32,
+    pub prompt: String,
 }
 
 /// The specific action to execute.
@@ -104,6 +154,9 @@ pub struct MemoryQueryRequest {
     pub query_vector: Vec<f32>,
     pub min_score: f32,
     pub limit: usize,
+    /// Search only memories of this person, when the run has one.
+    #[serde(default)]
+    pub principal: Option<String>,
 }
 
 /// Response from a memory query.
@@ -133,6 +186,7 @@ impl Message {
             Message::CancelRun(id) => *id,
             Message::ProvideInput { run_id, .. } => *run_id,
             Message::RequestInput { run_id, .. } => *run_id,
+            Message::RepairOutput(r) => r.run_id,
         }
     }
 }
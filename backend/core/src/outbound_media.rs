@@ -0,0 +1,99 @@
+//! Outbound media
+//!
+//! Transport-neutral description of a generated file attached to a reply:
+//! an image from a tool, synthesized speech from TTS, an exported document.
+//! The media crate stores the bytes and issues a signed URL; each channel
+//! adapter then uploads the file natively (Telegram `sendPhoto`, Slack file
+//! uploads, Discord attachments) and falls back to `text()` elsewhere.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutboundMediaKind {
+    Image,
+    Audio,
+    Video,
+    File,
+}
+
+impl OutboundMediaKind {
+    pub fn from_mime(mime: &str) -> Self {
+        match mime.split('/').next().unwrap_or_default() {
+            "image" => Self::Image,
+            "audio" => Self::Audio,
+            "video" => Self::Video,
+            _ => Self::File,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboundMedia {
+    pub kind: OutboundMediaKind,
+    pub mime_type: String,
+    pub file_name: String,
+    /// Signed URL the media server serves the file at.
+    pub url: String,
+    /// Location in the media store, for adapters running next to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    #[serde(default)]
+    pub size: u64,
+}
+
+impl OutboundMedia {
+    /// Read the `media` attachments out of a tool result; `media` may be a
+    /// single object or an array.
+    pub fn from_tool_output(output: &str) -> Vec<Self> {
+        let Ok(value) = serde_json::from_str::<Value>(output) else {
+            return Vec::new();
+        };
+        match value.get("media") {
+            Some(Value::Array(items)) => items.iter().filter_map(|item| serde_json::from_value(item.clone()).ok()).collect(),
+            Some(item) => serde_json::from_value(item.clone()).ok().into_iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Plain-text fallback for channels without native uploads.
+    pub fn text(&self) -> String {
+        match &self.caption {
+            Some(caption) => format!("📎 {} — {}: {}", self.file_name, caption, self.url),
+            None => format!("📎 {}: {}", self.file_name, self.url),
+        }
+    }
+}
+
+/// Delivers attachments from tool results to the chat a run came from.
+#[async_trait]
+pub trait OutboundMediaSink: Send + Sync {
+    async fn deliver(&self, channel: &str, chat_id: &str, media: &OutboundMedia) -> anyhow::Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_output_yields_attachments() {
+        let single = r#"{"media":{"kind":"image","mime_type":"image/png","file_name":"cat.png","url":"https://claw.example.com/media/ab.png?exp=1&sig=00","size":42}}"#;
+        let media = OutboundMedia::from_tool_output(single);
+        assert_eq!(media.len(), 1);
+        assert_eq!(media[0].kind, OutboundMediaKind::Image);
+        assert_eq!(media[0].text(), "📎 cat.png: https://claw.example.com/media/ab.png?exp=1&sig=00");
+
+        let many = r#"{"media":[{"kind":"audio","mime_type":"audio/mpeg","file_name":"a.mp3","url":"u1"},{"kind":"file","mime_type":"text/csv","file_name":"b.csv","url":"u2","caption":"Totals"}]}"#;
+        let media = OutboundMedia::from_tool_output(many);
+        assert_eq!(media.len(), 2);
+        assert_eq!(media[1].text(), "📎 b.csv — Totals: u2");
+
+        assert!(OutboundMedia::from_tool_output(r#"{"ok":true}"#).is_empty());
+        assert_eq!(OutboundMediaKind::from_mime("video/mp4"), OutboundMediaKind::Video);
+        assert_eq!(OutboundMediaKind::from_mime("application/pdf"), OutboundMediaKind::File);
+    }
+}
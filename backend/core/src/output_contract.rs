@@ -0,0 +1,259 @@
+//! Output contracts for workflow steps.
+//!
+//! A step may declare the shape its output must have as a JSON Schema. The
+//! executor checks each output against it before later steps consume it; on a
+//! mismatch the planner gets a repair prompt listing the exact violations, and
+//! once `max_repairs` is used up the run fails with those violations.
+//!
+//! Supported schema keywords: `type`, `enum`, `const`, `properties`,
+//! `required`, `additionalProperties` (boolean), `items`, `minItems`,
+//! `maxItems`, `minLength`, `maxLength`, `minimum`, `maximum`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+fn default_max_repairs() -> u32 {
+    2
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputContract {
+    /// JSON Schema the step output must satisfy.
+    pub schema: Value,
+    /// Repair prompts to try before failing the run.
+    #[serde(default = "default_max_repairs")]
+    pub max_repairs: u32,
+}
+
+/// One way an output breaks its contract, at a `$.a.b[0]` style path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractViolation {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ContractViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl OutputContract {
+    pub fn new(schema: Value) -> Self {
+        Self { schema, max_repairs: default_max_repairs() }
+    }
+
+    pub fn with_max_repairs(mut self, max_repairs: u32) -> Self {
+        self.max_repairs = max_repairs;
+        self
+    }
+
+    /// Pull the step's output out of an executor result and validate it.
+    /// Returns the extracted value when it conforms.
+    pub fn check(&self, result: &Value) -> Result<Value, Vec<ContractViolation>> {
+        let output = extract_output(result);
+        let mut violations = Vec::new();
+        validate(&self.schema, &output, "$", &mut violations);
+        if violations.is_empty() {
+            Ok(output)
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Prompt asking the model to fix its previous output.
+    pub fn repair_prompt(&self, violations: &[ContractViolation]) -> String {
+        let mut prompt = String::from("Your previous output does not match the required output schema:\n");
+        for v in violations {
+            prompt.push_str(&format!("- {}\n", v));
+        }
+        prompt.push_str("\nRequired schema:\n");
+        prompt.push_str(&serde_json::to_string_pretty(&self.schema).unwrap_or_else(|_| self.schema.to_string()));
+        prompt.push_str("\n\nReply with only the corrected JSON, no commentary.");
+        prompt
+    }
+}
+
+/// The part of an executor result a contract applies to: the LLM content,
+/// shell stdout, HTTP body or tool output. Text is parsed as JSON when it is
+/// JSON (optionally inside a ``` fence), otherwise kept as a string.
+pub fn extract_output(result: &Value) -> Value {
+    let inner = if result.get("type").and_then(Value::as_str) == Some("llm_response") {
+        result.get("content")
+    } else if result.get("tool").is_some() {
+        result.get("output")
+    } else {
+        result.get("stdout").or_else(|| result.get("body"))
+    };
+    match inner.unwrap_or(result) {
+        Value::String(text) => parse_text(text),
+        other => other.clone(),
+    }
+}
+
+fn parse_text(text: &str) -> Value {
+    let trimmed = text.trim();
+    let unfenced = match trimmed.strip_prefix("```").and_then(|rest| rest.strip_suffix("```")) {
+        // Drop the language tag, e.g. ```json
+        Some(body) => body.trim_start_matches(|c: char| c.is_ascii_alphanumeric()),
+        None => trimmed,
+    };
+    serde_json::from_str(unfenced.trim()).unwrap_or_else(|_| Value::String(text.to_string()))
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    let actual = type_name(value);
+    expected == actual || (expected == "number" && actual == "integer")
+}
+
+fn validate(schema: &Value, value: &Value, path: &str, out: &mut Vec<ContractViolation>) {
+    let mut fail = |message: String| out.push(ContractViolation { path: path.to_string(), message });
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| type_matches(t, value)) {
+            fail(format!("expected {}, got {}", types.join(" or "), type_name(value)));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            fail(format!("must be one of {}", Value::Array(allowed.clone())));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            fail(format!("must equal {}", constant));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(key) {
+                        fail(format!("missing required property '{}'", key));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                for key in map.keys() {
+                    if !properties.is_some_and(|p| p.contains_key(key)) {
+                        fail(format!("unexpected property '{}'", key));
+                    }
+                }
+            }
+            if let Some(properties) = properties {
+                for (key, sub) in properties {
+                    if let Some(v) = map.get(key) {
+                        validate(sub, v, &format!("{}.{}", path, key), out);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if (items.len() as u64) < min {
+                    fail(format!("expected at least {} items, got {}", min, items.len()));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if items.len() as u64 > max {
+                    fail(format!("expected at most {} items, got {}", max, items.len()));
+                }
+            }
+            if let Some(sub) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate(sub, item, &format!("{}[{}]", path, i), out);
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if len < min {
+                    fail(format!("expected at least {} characters, got {}", min, len));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if len > max {
+                    fail(format!("expected at most {} characters, got {}", max, len));
+                }
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if n < min {
+                    fail(format!("must be >= {}", min));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if n > max {
+                    fail(format!("must be <= {}", max));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn contract() -> OutputContract {
+        OutputContract::new(json!({
+            "type": "object",
+            "required": ["verdict", "issues"],
+            "additionalProperties": false,
+            "properties": {
+                "verdict": {"enum": ["approve", "request_changes"]},
+                "issues": {"type": "array", "items": {"type": "object", "required": ["line"], "properties": {"line": {"type": "integer", "minimum": 1}}}}
+            }
+        }))
+    }
+
+    #[test]
+    fn reports_precise_violations() {
+        let result = json!({"type": "llm_response", "content": "```json\n{\"verdict\": \"lgtm\", \"issues\": [{\"line\": 0}, {}], \"extra\": 1}\n```"});
+        let violations: Vec<String> = contract().check(&result).unwrap_err().iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            violations,
+            vec![
+                "$: unexpected property 'extra'",
+                "$.issues[0].line: must be >= 1",
+                "$.issues[1]: missing required property 'line'",
+                "$.verdict: must be one of [\"approve\",\"request_changes\"]",
+            ]
+        );
+        assert!(contract().repair_prompt(&contract().check(&result).unwrap_err()).contains("$.issues[0].line"));
+    }
+
+    #[test]
+    fn accepts_conforming_shell_output() {
+        let result = json!({"exit_code": 0, "stdout": "{\"verdict\": \"approve\", \"issues\": []}\n", "success": true});
+        assert_eq!(contract().check(&result).unwrap()["verdict"], "approve");
+        let text = OutputContract::new(json!({"type": "string", "minLength": 3}));
+        assert!(text.check(&json!({"type": "llm_response", "content": "ok"})).is_err());
+    }
+}
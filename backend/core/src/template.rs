@@ -0,0 +1,199 @@
+//! Message templates for scheduled deliveries and notifications.
+//!
+//! Templates are Jinja syntax, rendered by `minijinja` against a JSON
+//! context: `{{ run.output }}`, `{% if run.ok %}...{% endif %}`,
+//! `{% for item in items %}{{ loop.index }}. {{ item }}{% endfor %}`,
+//! `{# comments #}` and `{%-` / `-%}` trimming all work as in Jinja. A
+//! missing value renders as nothing, even through a chain like
+//! `{{ usage.success_rate }}` when there is no `usage`.
+//!
+//! Besides minijinja's builtin filters (`upper`, `default`, `round`,
+//! `join`, `length`, `tojson`, ...), templates get `truncate(n)` and
+//! `date(format)`; see `FILTERS`.
+//!
+//! Templates are parsed up front, so syntax errors and unknown filters
+//! surface with a line and column when config is loaded, not at send time.
+
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use minijinja::value::Value as JinjaValue;
+use minijinja::{Environment, ErrorKind, UndefinedBehavior};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+/// Filters added on top of minijinja's builtins.
+pub const FILTERS: &[&str] = &["truncate", "date"];
+
+/// Name the template is stored under in its environment.
+const NAME: &str = "message";
+
+/// Why a template failed to parse or render.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl TemplateError {
+    fn new(source: &str, e: &minijinja::Error) -> Self {
+        let line = e.line().unwrap_or(1);
+        let column = e
+            .range()
+            .filter(|range| range.start <= source.len() && source.is_char_boundary(range.start))
+            .map_or(1, |range| {
+                let before = &source[..range.start];
+                before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1
+            });
+        let message = match e.detail() {
+            Some(detail) => format!("{}: {}", e.kind(), detail),
+            None => e.kind().to_string(),
+        };
+        Self { line, column, message }
+    }
+}
+
+/// A parsed template, ready to render any number of times.
+#[derive(Debug, Clone)]
+pub struct Template {
+    env: Environment<'static>,
+    source: String,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut env = Environment::new();
+        env.set_undefined_behavior(UndefinedBehavior::Chainable);
+        env.set_keep_trailing_newline(true);
+        env.add_filter("truncate", truncate);
+        env.add_filter("date", date);
+        env.add_template_owned(NAME, source.to_string()).map_err(|e| TemplateError::new(source, &e))?;
+        let template = Self { env, source: source.to_string() };
+        template.check_filters()?;
+        Ok(template)
+    }
+
+    pub fn render(&self, context: &Value) -> Result<String, TemplateError> {
+        self.env
+            .get_template(NAME)
+            .and_then(|template| template.render(context))
+            .map_err(|e| TemplateError::new(&self.source, &e))
+    }
+
+    /// Top-level names the template reads (loop and `set` variables
+    /// excluded), sorted.
+    pub fn variables(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .env
+            .get_template(NAME)
+            .map(|template| template.undeclared_variables(false).into_iter().collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// minijinja resolves filters when rendering; render once against an
+    /// empty context so an unknown one is a parse error here instead.
+    fn check_filters(&self) -> Result<(), TemplateError> {
+        match self.render(&Value::Object(Default::default())) {
+            Err(e) if e.message.starts_with(&ErrorKind::UnknownFilter.to_string()) => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parse and render in one step.
+pub fn render(source: &str, context: &Value) -> Result<String, TemplateError> {
+    Template::parse(source)?.render(context)
+}
+
+/// `truncate(n)`: at most `n` characters (default 80), ending in `…` when cut.
+fn truncate(value: JinjaValue, max: Option<usize>) -> String {
+    let max = max.unwrap_or(80);
+    let text = display(&value);
+    if text.chars().count() <= max {
+        return text;
+    }
+    let kept: String = text.chars().take(max.saturating_sub(1)).collect();
+    format!("{}…", kept.trim_end())
+}
+
+/// `date(format)`: an RFC 3339 string (kept in its own offset) or a Unix
+/// timestamp in seconds or milliseconds (in UTC), formatted with strftime.
+fn date(value: JinjaValue, format: Option<String>) -> Result<JinjaValue, minijinja::Error> {
+    if value.is_undefined() || value.is_none() {
+        return Ok(JinjaValue::from(()));
+    }
+    let format = format.as_deref().unwrap_or("%Y-%m-%d %H:%M");
+    let parsed = match value.as_str() {
+        Some(s) => DateTime::parse_from_rfc3339(s).ok(),
+        None => i64::try_from(value.clone()).ok().and_then(from_timestamp),
+    };
+    match parsed {
+        Some(dt) => Ok(JinjaValue::from(dt.format(format).to_string())),
+        None => Err(minijinja::Error::new(ErrorKind::InvalidOperation, format!("date: '{}' is not a timestamp", value))),
+    }
+}
+
+fn from_timestamp(n: i64) -> Option<DateTime<FixedOffset>> {
+    let utc = if n.abs() >= 100_000_000_000 { Utc.timestamp_millis_opt(n).single() } else { Utc.timestamp_opt(n, 0).single() };
+    utc.map(|dt| dt.fixed_offset())
+}
+
+fn display(value: &JinjaValue) -> String {
+    if value.is_undefined() || value.is_none() {
+        String::new()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_values_filters_and_blocks() {
+        let context = json!({
+            "job": {"name": "morning briefing"},
+            "run": {"ok": true, "finished_at": "2025-06-10T07:30:00Z", "outputs": ["Sunny, 24°C", "3 meetings"]},
+            "usage": {"success_rate": 0.9667, "total_runs": 30},
+        });
+        let template = Template::parse(
+            "{{ job.name | upper }} — {{ run.finished_at | date(\"%d %b\") }}\n\
+             {%- for line in run.outputs %}\n{{ loop.index }}. {{ line }}{% endfor %}\n\
+             {% if not run.ok %}FAILED{% else %}ok: {{ usage.success_rate | round(2) }} of {{ usage.total_runs }}{% endif %}\
+             {# internal #}{{ missing | default('-') }}{{ missing.deeper.still }}",
+        )
+        .unwrap();
+        assert_eq!(
+            template.render(&context).unwrap(),
+            "MORNING BRIEFING — 10 Jun\n1. Sunny, 24°C\n2. 3 meetings\nok: 0.97 of 30-"
+        );
+        assert_eq!(template.variables(), ["job", "missing", "run", "usage"]);
+        assert_eq!(render("{{ 'a|b' | truncate(2) }}", &json!({})).unwrap(), "a…");
+        assert_eq!(render("{{ t | date('%H:%M') }}", &json!({"t": "2025-06-10T09:15:00+02:00"})).unwrap(), "09:15");
+        assert_eq!(render("{{ t | date('%Y') }}", &json!({"t": 1_749_542_400})).unwrap(), "2025");
+    }
+
+    #[test]
+    fn reports_errors_with_positions() {
+        let err = Template::parse("Hi\n  {{ name | shout }}").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("unknown filter"), "{}", err.message);
+        assert!(Template::parse("{% if x %}open").is_err());
+        assert!(Template::parse("{{ x").is_err());
+        assert!(Template::parse("{% endfor %}").is_err());
+        assert!(render("{% for x in n %}{% endfor %}", &json!({"n": 3})).is_err());
+        assert!(render("{{ t | date }}", &json!({"t": "yesterday"})).is_err());
+    }
+}
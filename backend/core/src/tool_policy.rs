@@ -0,0 +1,239 @@
+//! Tool-call policy: which tools an agent may call, optionally narrowed per channel.
+//!
+//! Rules come from `agents.defaults.tools`, `agents.list.<name>.tools` and
+//! `agents.defaults.tools.byChannel.<channel>`. Patterns are globs (`*`, `?`)
+//! matched case-insensitively against the tool name.
+//!
+//! Resolution order:
+//! 1. A `deny` match at any layer (channel, agent, defaults) blocks the call.
+//! 2. The agent's `allow` list replaces the defaults' `allow` list; when neither
+//!    is set every tool is allowed. `alsoAllow` at either layer adds to it.
+//! 3. A channel `allow` list further restricts what step 2 allowed.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Allow/deny globs for one layer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolRules {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+    #[serde(default)]
+    pub also_allow: Vec<String>,
+}
+
+/// Which list produced a decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleList {
+    Allow,
+    Deny,
+    AlsoAllow,
+    /// No allow list configured, so the tool is allowed implicitly.
+    Implicit,
+    /// An allow list exists and the tool is not on it.
+    NotAllowed,
+}
+
+/// The rule that decided a tool call, for audit events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MatchedRule {
+    /// `defaults`, `agent:<name>` or `channel:<name>`.
+    pub scope: String,
+    pub list: RuleList,
+    /// The glob that matched, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolPolicyDecision {
+    pub tool: String,
+    pub allowed: bool,
+    pub rule: MatchedRule,
+}
+
+impl ToolPolicyDecision {
+    pub fn reason(&self) -> String {
+        let pattern = self.rule.pattern.as_deref().map(|p| format!(" '{}'", p)).unwrap_or_default();
+        match self.rule.list {
+            RuleList::Deny => format!("tool '{}' denied by {} deny rule{}", self.tool, self.rule.scope, pattern),
+            RuleList::NotAllowed => format!("tool '{}' not in {} allow list", self.tool, self.rule.scope),
+            _ => format!("tool '{}' allowed by {}{}", self.tool, self.rule.scope, pattern),
+        }
+    }
+}
+
+/// Case-insensitive glob match supporting `*` and `?`.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let n: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let (mut star, mut mark) = (None, 0);
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some(pi);
+            mark = ni;
+            pi += 1;
+        } else if let Some(s) = star {
+            pi = s + 1;
+            mark += 1;
+            ni = mark;
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+fn first_match<'a>(patterns: &'a [String], tool: &str) -> Option<&'a String> {
+    patterns.iter().find(|p| glob_match(p, tool))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ToolPolicyEngine {
+    defaults: ToolRules,
+    agents: HashMap<String, ToolRules>,
+    channels: HashMap<String, ToolRules>,
+}
+
+impl ToolPolicyEngine {
+    pub fn new(defaults: ToolRules) -> Self {
+        Self { defaults, ..Default::default() }
+    }
+
+    pub fn with_agent(mut self, agent: impl Into<String>, rules: ToolRules) -> Self {
+        self.agents.insert(agent.into(), rules);
+        self
+    }
+
+    pub fn with_channel(mut self, channel: impl Into<String>, rules: ToolRules) -> Self {
+        self.channels.insert(channel.into(), rules);
+        self
+    }
+
+    /// Build from the `agents` section of the config (camelCase JSON).
+    pub fn from_agents_config(agents: &Value) -> Self {
+        let rules = |v: Option<&Value>| -> ToolRules {
+            v.cloned().and_then(|v| serde_json::from_value(v).ok()).unwrap_or_default()
+        };
+        let defaults_tools = agents.pointer("/defaults/tools");
+        let mut engine = Self::new(rules(defaults_tools));
+        if let Some(by_channel) = defaults_tools.and_then(|t| t.get("byChannel")).and_then(Value::as_object) {
+            for (channel, cfg) in by_channel {
+                engine.channels.insert(channel.clone(), rules(Some(cfg)));
+            }
+        }
+        if let Some(list) = agents.get("list").and_then(Value::as_object) {
+            for (name, entry) in list {
+                if let Some(tools) = entry.get("tools") {
+                    engine.agents.insert(name.clone(), rules(Some(tools)));
+                }
+            }
+        }
+        engine
+    }
+
+    /// Decide whether `agent` may call `tool` from `channel`.
+    pub fn evaluate(&self, tool: &str, agent: Option<&str>, channel: Option<&str>) -> ToolPolicyDecision {
+        let decide = |allowed: bool, scope: String, list: RuleList, pattern: Option<&String>| ToolPolicyDecision {
+            tool: tool.to_string(),
+            allowed,
+            rule: MatchedRule { scope, list, pattern: pattern.cloned() },
+        };
+
+        let channel_rules = channel.and_then(|c| self.channels.get(c).map(|r| (format!("channel:{}", c), r)));
+        let agent_rules = agent.and_then(|a| self.agents.get(a).map(|r| (format!("agent:{}", a), r)));
+        let defaults = ("defaults".to_string(), &self.defaults);
+
+        // 1. Deny wins at every layer, most specific first.
+        for (scope, rules) in channel_rules.iter().chain(agent_rules.iter()).chain([&defaults]) {
+            if let Some(p) = first_match(&rules.deny, tool) {
+                return decide(false, scope.clone(), RuleList::Deny, Some(p));
+            }
+        }
+
+        // 2. Agent allow replaces the defaults' allow; also_allow adds to either.
+        let (base_scope, base) = match &agent_rules {
+            Some((scope, rules)) if !rules.allow.is_empty() => (scope.clone(), *rules),
+            _ => defaults.clone(),
+        };
+        let mut granted = if base.allow.is_empty() {
+            Some(decide(true, base_scope.clone(), RuleList::Implicit, None))
+        } else {
+            first_match(&base.allow, tool).map(|p| decide(true, base_scope.clone(), RuleList::Allow, Some(p)))
+        };
+        if granted.is_none() {
+            granted = agent_rules.iter().chain([&defaults]).find_map(|(scope, rules)| {
+                first_match(&rules.also_allow, tool).map(|p| decide(true, scope.clone(), RuleList::AlsoAllow, Some(p)))
+            });
+        }
+        let Some(granted) = granted else {
+            return decide(false, base_scope, RuleList::NotAllowed, None);
+        };
+
+        // 3. A channel allow list narrows further.
+        if let Some((scope, rules)) = &channel_rules {
+            if !rules.allow.is_empty() {
+                return match first_match(&rules.allow, tool) {
+                    Some(p) if granted.rule.list == RuleList::Implicit => decide(true, scope.clone(), RuleList::Allow, Some(p)),
+                    Some(_) => granted,
+                    None => decide(false, scope.clone(), RuleList::NotAllowed, None),
+                };
+            }
+        }
+        granted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn engine() -> ToolPolicyEngine {
+        ToolPolicyEngine::from_agents_config(&json!({
+            "defaults": { "tools": {
+                "deny": ["secret_*"],
+                "byChannel": { "whatsapp": { "deny": ["shell", "bash", "exec*"] } }
+            }},
+            "list": {
+                "coder": { "tools": { "allow": ["file_*", "shell"], "alsoAllow": ["web_search"] } }
+            }
+        }))
+    }
+
+    #[test]
+    fn globs_match_case_insensitively() {
+        assert!(glob_match("file_*", "FILE_read"));
+        assert!(glob_match("http_?et", "http_get"));
+        assert!(!glob_match("file_*", "profile_read"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn channel_deny_beats_agent_allow() {
+        let d = engine().evaluate("shell", Some("coder"), Some("whatsapp"));
+        assert!(!d.allowed);
+        assert_eq!(d.rule.scope, "channel:whatsapp");
+        assert_eq!(d.rule.pattern.as_deref(), Some("shell"));
+
+        assert!(engine().evaluate("shell", Some("coder"), Some("telegram")).allowed);
+    }
+
+    #[test]
+    fn agent_allow_list_replaces_defaults() {
+        let e = engine();
+        assert_eq!(e.evaluate("web_fetch", Some("coder"), None).rule.list, RuleList::NotAllowed);
+        assert_eq!(e.evaluate("web_search", Some("coder"), None).rule.list, RuleList::AlsoAllow);
+        assert_eq!(e.evaluate("web_fetch", Some("writer"), None).rule.list, RuleList::Implicit);
+        assert_eq!(e.evaluate("secret_read", Some("writer"), None).rule.list, RuleList::Deny);
+    }
+}
@@ -0,0 +1,236 @@
+//! Deployment topology — how a message flows through the runtime.
+//!
+//! A [`Topology`] is a small directed graph of components, bus channels,
+//! channel adapters, hooks and remote nodes. [`BusProbe`] supplies
+//! the component/channel core with live queue depths; the rest is
+//! registered at startup as each piece is wired in. The graph serializes to
+//! JSON and renders to Graphviz DOT for diagnostics.
+
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::message::Message;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Component,
+    Channel,
+    Adapter,
+    Hook,
+    Node,
+}
+
+impl NodeKind {
+    fn shape(self) -> &'static str {
+        match self {
+            NodeKind::Component => "box",
+            NodeKind::Channel => "cds",
+            NodeKind::Adapter => "component",
+            NodeKind::Hook => "diamond",
+            NodeKind::Node => "box3d",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopologyNode {
+    /// `<kind>:<name>`, unique in the graph.
+    pub id: String,
+    pub kind: NodeKind,
+    pub label: String,
+    /// Messages waiting in a bus channel.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_depth: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopologyEdge {
+    pub from: String,
+    pub to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Topology {
+    pub nodes: Vec<TopologyNode>,
+    pub edges: Vec<TopologyEdge>,
+}
+
+impl Topology {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node and return its id. Adding an existing id is a no-op.
+    pub fn add_node(&mut self, kind: NodeKind, name: &str, label: &str) -> String {
+        let id = node_id(kind, name);
+        if !self.nodes.iter().any(|n| n.id == id) {
+            self.nodes.push(TopologyNode { id: id.clone(), kind, label: label.to_string(), queue_depth: None, capacity: None });
+        }
+        id
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str, label: Option<&str>) {
+        let edge = TopologyEdge { from: from.to_string(), to: to.to_string(), label: label.map(str::to_string) };
+        if !self.edges.contains(&edge) {
+            self.edges.push(edge);
+        }
+    }
+
+    /// A channel adapter that feeds inbound messages to `component`.
+    pub fn add_adapter(&mut self, name: &str, component: &str) {
+        let id = self.add_node(NodeKind::Adapter, name, name);
+        self.add_edge(&id, &node_id(NodeKind::Channel, component), Some("inbound"));
+    }
+
+    /// A hook run by `component` at `point` (e.g. `pre_message`).
+    pub fn add_hook(&mut self, name: &str, component: &str, point: &str) {
+        let id = self.add_node(NodeKind::Hook, name, name);
+        self.add_edge(&node_id(NodeKind::Component, component), &id, Some(point));
+    }
+
+    /// A remote host `component` sends work to.
+    pub fn add_remote_node(&mut self, name: &str, component: &str) {
+        let id = self.add_node(NodeKind::Node, name, name);
+        self.add_edge(&node_id(NodeKind::Component, component), &id, Some("exec"));
+    }
+
+    /// Add `other`'s nodes and edges, keeping existing ones.
+    pub fn merge(&mut self, other: &Topology) {
+        for node in &other.nodes {
+            if !self.nodes.iter().any(|n| n.id == node.id) {
+                self.nodes.push(node.clone());
+            }
+        }
+        for edge in &other.edges {
+            self.add_edge(&edge.from, &edge.to, edge.label.as_deref());
+        }
+    }
+
+    /// Graphviz DOT; channel labels carry `depth/capacity`.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph clawforge {\n    rankdir=LR;\n    node [fontname=\"Helvetica\"];\n");
+        for node in &self.nodes {
+            let label = match (node.queue_depth, node.capacity) {
+                (Some(depth), Some(capacity)) => format!("{}\\n{}/{}", node.label, depth, capacity),
+                (Some(depth), None) => format!("{}\\n{}", node.label, depth),
+                _ => node.label.clone(),
+            };
+            out.push_str(&format!(
+                "    {} [label={}, shape={}];\n",
+                dot_quote(&node.id),
+                dot_quote(&label),
+                node.kind.shape()
+            ));
+        }
+        for edge in &self.edges {
+            let label = edge.label.as_deref().map(|l| format!(" [label={}]", dot_quote(l))).unwrap_or_default();
+            out.push_str(&format!("    {} -> {}{};\n", dot_quote(&edge.from), dot_quote(&edge.to), label));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+pub fn node_id(kind: NodeKind, name: &str) -> String {
+    let kind = match kind {
+        NodeKind::Component => "component",
+        NodeKind::Channel => "channel",
+        NodeKind::Adapter => "adapter",
+        NodeKind::Hook => "hook",
+        NodeKind::Node => "node",
+    };
+    format!("{}:{}", kind, name)
+}
+
+/// Quote a DOT identifier; `\n` sequences already in `s` are kept as line breaks.
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\\\""))
+}
+
+// ---------------------------------------------------------------------------
+// Bus probe
+// ---------------------------------------------------------------------------
+
+/// Who sends into each component's inbox, per the standard wiring.
+const BUS_ROUTES: &[(&str, &str, &str)] = &[
+    ("scheduler", "planner", "schedule"),
+    ("planner", "executor", "actions"),
+    ("executor", "planner", "repairs"),
+    ("scheduler", "supervisor", "audit"),
+    ("planner", "supervisor", "audit"),
+    ("executor", "supervisor", "audit"),
+];
+
+/// Cloneable handle on the bus senders, for reading queue depths after the
+/// receivers have been handed to the components.
+#[derive(Clone)]
+pub struct BusProbe {
+    queues: Vec<(&'static str, mpsc::Sender<Message>)>,
+}
+
+impl BusProbe {
+    pub(crate) fn new(queues: Vec<(&'static str, mpsc::Sender<Message>)>) -> Self {
+        Self { queues }
+    }
+
+    /// Messages waiting in `component`'s inbox.
+    pub fn queue_depth(&self, component: &str) -> Option<usize> {
+        let (_, tx) = self.queues.iter().find(|(name, _)| *name == component)?;
+        Some(tx.max_capacity() - tx.capacity())
+    }
+
+    /// The components, their inbox channels with live depths, and the
+    /// standard routes between them.
+    pub fn topology(&self) -> Topology {
+        let mut graph = Topology::new();
+        for (name, tx) in &self.queues {
+            let component = graph.add_node(NodeKind::Component, name, name);
+            let channel = graph.add_node(NodeKind::Channel, name, &format!("{} inbox", name));
+            if let Some(node) = graph.nodes.iter_mut().find(|n| n.id == channel) {
+                node.queue_depth = Some(tx.max_capacity() - tx.capacity());
+                node.capacity = Some(tx.max_capacity());
+            }
+            graph.add_edge(&channel, &component, None);
+        }
+        for (from, to, label) in BUS_ROUTES {
+            graph.add_edge(&node_id(NodeKind::Component, from), &node_id(NodeKind::Channel, to), Some(label));
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::ClawBus;
+    use crate::message::JobTrigger;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn reports_queue_depths_and_renders_dot() {
+        let bus = ClawBus::with_buffer_size(4);
+        let probe = bus.probe();
+        bus.planner_tx
+            .send(Message::ScheduleJob(JobTrigger { run_id: Uuid::new_v4(), agent_id: Uuid::new_v4(), trigger_reason: "t".into() }))
+            .await
+            .unwrap();
+        assert_eq!(probe.queue_depth("planner"), Some(1));
+        assert_eq!(probe.queue_depth("executor"), Some(0));
+
+        let mut graph = probe.topology();
+        graph.add_adapter("slack", "supervisor");
+        let planner = graph.nodes.iter().find(|n| n.id == "channel:planner").unwrap();
+        assert_eq!((planner.queue_depth, planner.capacity), (Some(1), Some(4)));
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("\"channel:planner\" [label=\"planner inbox\\n1/4\", shape=cds];"));
+        assert!(dot.contains("\"adapter:slack\" -> \"channel:supervisor\" [label=\"inbound\"];"));
+        assert!(dot.contains("\"component:scheduler\" -> \"channel:planner\" [label=\"schedule\"];"));
+    }
+}
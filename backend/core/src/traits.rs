@@ -59,5 +59,8 @@ pub struct LlmResponse {
     pub provider: String,
     pub model: String,
     pub tokens_used: u64,
+    /// Input and output tokens; zero when the provider doesn't report them.
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
     pub latency_ms: u64,
 }
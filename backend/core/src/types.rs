@@ -3,6 +3,9 @@ use std::fmt;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::execution_window::ExecutionWindow;
+use crate::output_contract::OutputContract;
+
 /// Specification of an agent's capabilities and behavior.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentSpec {
@@ -19,6 +22,15 @@ pub struct AgentSpec {
     pub allowed_tools: Vec<String>, // List of tool names
     #[serde(default)]
     pub allowed_skills: Vec<String>, // List of skill names to inject
+    /// IANA time zone (e.g. "Europe/Berlin") for cron triggers and local time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// BCP 47 locale (e.g. "de-DE") the agent should write in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Host conditions (power, load, user idle) scheduled runs wait for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_window: Option<ExecutionWindow>,
 }
 
 /// The role an agent plays in the system.
@@ -52,6 +64,9 @@ impl AgentSpec {
             workflow: Vec::new(),
             allowed_tools: Vec::new(),
             allowed_skills: Vec::new(),
+            timezone: None,
+            locale: None,
+            execution_window: None,
         }
     }
 }
@@ -135,6 +150,9 @@ pub struct WorkflowStep {
     pub name: String,
     pub action: ActionType,
     pub on_failure: FailurePolicy,
+    /// Schema the step's output must match before later steps use it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_contract: Option<OutputContract>,
 }
 
 /// The type of action a workflow step performs.
@@ -0,0 +1,141 @@
+//! Host activity probe: power source, battery, load and user idle time.
+//!
+//! Feeds execution windows so heavy scheduled agents can wait until the
+//! machine is plugged in and idle. Readings a platform can't provide are
+//! left `None` and never block a run.
+
+use async_trait::async_trait;
+use clawforge_core::{ActivityProbe, HostActivity};
+use tracing::debug;
+
+/// Reads the live state of this machine.
+pub struct SystemActivityProbe;
+
+#[async_trait]
+impl ActivityProbe for SystemActivityProbe {
+    async fn sample(&self) -> HostActivity {
+        let activity = sample_host().await;
+        debug!(?activity, "Sampled host activity");
+        activity
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn sample_host() -> HostActivity {
+    let (on_ac_power, battery_percent) = linux_power();
+    let load_per_cpu = std::fs::read_to_string("/proc/loadavg")
+        .ok()
+        .and_then(|text| text.split_whitespace().next()?.parse::<f32>().ok())
+        .map(per_cpu);
+    let idle_secs = match x11_idle_secs().await {
+        Some(secs) => Some(secs),
+        None => terminal_idle_secs(),
+    };
+    HostActivity { on_ac_power, battery_percent, load_per_cpu, idle_secs }
+}
+
+#[cfg(target_os = "macos")]
+async fn sample_host() -> HostActivity {
+    let pmset = command_output("pmset", &["-g", "batt"]).await.unwrap_or_default();
+    let on_ac_power = if pmset.contains("'AC Power'") {
+        Some(true)
+    } else if pmset.contains("'Battery Power'") {
+        Some(false)
+    } else {
+        None
+    };
+    let battery_percent = pmset
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .find_map(|word| word.strip_suffix('%')?.parse().ok());
+    // "{ 1.52 1.71 1.80 }"
+    let load_per_cpu = command_output("sysctl", &["-n", "vm.loadavg"])
+        .await
+        .and_then(|text| text.trim_matches(|c| c == '{' || c == '}' || c == ' ' || c == '\n').split_whitespace().next()?.parse::<f32>().ok())
+        .map(per_cpu);
+    // `"HIDIdleTime" = 1234567890` in nanoseconds.
+    let idle_secs = command_output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])
+        .await
+        .and_then(|text| {
+            let line = text.lines().find(|l| l.contains("\"HIDIdleTime\""))?;
+            line.rsplit('=').next()?.trim().parse::<u64>().ok()
+        })
+        .map(|ns| ns / 1_000_000_000);
+    HostActivity { on_ac_power, battery_percent, load_per_cpu, idle_secs }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+async fn sample_host() -> HostActivity {
+    HostActivity::default()
+}
+
+fn per_cpu(load: f32) -> f32 {
+    let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    load / cpus as f32
+}
+
+#[cfg(unix)]
+async fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = tokio::process::Command::new(program).args(args).output().await.ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `/sys/class/power_supply`: any online `Mains` supply means AC; otherwise
+/// fall back to the battery's charging status.
+#[cfg(target_os = "linux")]
+fn linux_power() -> (Option<bool>, Option<u8>) {
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).map(|s| s.trim().to_string()).ok();
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return (None, None);
+    };
+    let (mut mains_online, mut battery_percent, mut battery_status) = (None, None, None);
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        match read(dir.join("type")).as_deref() {
+            Some("Mains") => {
+                let online = read(dir.join("online")).as_deref() == Some("1");
+                mains_online = Some(mains_online.unwrap_or(false) || online);
+            }
+            Some("Battery") if battery_percent.is_none() => {
+                battery_percent = read(dir.join("capacity")).and_then(|c| c.parse().ok());
+                battery_status = read(dir.join("status"));
+            }
+            _ => {}
+        }
+    }
+    let on_ac_power = mains_online.or(match battery_status.as_deref() {
+        Some("Discharging") => Some(false),
+        Some(_) => Some(true),
+        None => None,
+    });
+    (on_ac_power, battery_percent)
+}
+
+/// Idle time of the X session via `xprintidle` (milliseconds), when available.
+#[cfg(target_os = "linux")]
+async fn x11_idle_secs() -> Option<u64> {
+    std::env::var_os("DISPLAY")?;
+    command_output("xprintidle", &[]).await?.trim().parse::<u64>().ok().map(|ms| ms / 1000)
+}
+
+/// Like `w`: terminal devices' access times track the last keystroke, so the
+/// most recently touched one gives the user's idle time.
+#[cfg(target_os = "linux")]
+fn terminal_idle_secs() -> Option<u64> {
+    let mut newest: Option<std::time::SystemTime> = None;
+    for dir in ["/dev/pts", "/dev"] {
+        let Ok(entries) = std::fs::read_dir(dir) else { continue };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let is_terminal = dir == "/dev/pts" && name.chars().all(|c| c.is_ascii_digit())
+                || dir == "/dev" && name.strip_prefix("tty").is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+            if !is_terminal {
+                continue;
+            }
+            if let Ok(accessed) = entry.metadata().and_then(|m| m.accessed()) {
+                newest = Some(newest.map_or(accessed, |n| n.max(accessed)));
+            }
+        }
+    }
+    newest.map(|at| std::time::SystemTime::now().duration_since(at).map(|d| d.as_secs()).unwrap_or(0))
+}
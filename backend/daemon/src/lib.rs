@@ -1,11 +1,15 @@
 pub mod env_manager;
+pub mod host_activity;
 pub mod launchd;
+pub mod log_manager;
 pub mod schtasks;
 pub mod service;
 pub mod service_inspector;
 pub mod systemd;
 
 pub use env_manager::{EnvStore, EnvVar};
+pub use host_activity::SystemActivityProbe;
+pub use log_manager::{follow, LogLevel, LogManager, LogRotation, LogShipper, ShipTarget};
 pub use service::{
     current_platform, install_service, uninstall_service, start_service, stop_service,
     restart_service, status_service, service_audit, Platform,
@@ -0,0 +1,568 @@
+//! Gateway log management: rotation, live tailing and shipping.
+//!
+//! Rotation copies the live file to `<name>.1` (shifting older rotations up)
+//! and truncates it in place: launchd keeps the gateway's stdout open, so a
+//! rename would leave the gateway writing into the rotated file. Tailing
+//! follows the file across those truncations, for `/api/logs/stream`.
+//! Shipping forwards new lines — parsed as JSON when the structured logger
+//! wrote them — to syslog, journald or an HTTP collector.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context, Result};
+use clawforge_config::schema::{LogRotationConfig, LogShippingConfig, LoggingConfig};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+const ROTATION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const TAIL_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How far back from the end the tail looks for its backlog.
+const BACKLOG_WINDOW: u64 = 64 * 1024;
+const SHIP_FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+// ---------------------------------------------------------------------------
+// Rotation
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct LogRotation {
+    pub max_bytes: u64,
+    pub max_age: Option<Duration>,
+    /// Rotated files to keep (`<name>.1` … `<name>.N`).
+    pub max_files: usize,
+}
+
+impl Default for LogRotation {
+    fn default() -> Self {
+        Self { max_bytes: 50 * 1024 * 1024, max_age: Some(Duration::from_secs(7 * 86_400)), max_files: 5 }
+    }
+}
+
+impl LogRotation {
+    pub fn from_config(config: &LogRotationConfig) -> Self {
+        let defaults = Self::default();
+        Self {
+            max_bytes: config.max_size_mb.map_or(defaults.max_bytes, |mb| mb * 1024 * 1024),
+            max_age: config.max_age_days.map(|days| Duration::from_secs(days * 86_400)).or(defaults.max_age),
+            max_files: config.max_files.unwrap_or(defaults.max_files),
+        }
+    }
+
+    fn rotated(path: &Path, n: usize) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    /// When the live file was started: the last rotation, or its creation.
+    fn started_at(&self, path: &Path, meta: &std::fs::Metadata) -> Option<SystemTime> {
+        std::fs::metadata(Self::rotated(path, 1)).and_then(|m| m.modified()).or_else(|_| meta.created()).ok()
+    }
+
+    /// Rotate `path` when it is over the size limit or older than the age
+    /// limit. Returns whether it rotated.
+    pub fn rotate_if_needed(&self, path: &Path) -> Result<bool> {
+        let Ok(meta) = std::fs::metadata(path) else { return Ok(false) };
+        if meta.len() == 0 {
+            return Ok(false);
+        }
+        let too_big = meta.len() >= self.max_bytes;
+        let too_old = match (self.max_age, self.started_at(path, &meta)) {
+            (Some(max_age), Some(started)) => started.elapsed().is_ok_and(|age| age > max_age),
+            _ => false,
+        };
+        if !too_big && !too_old {
+            return Ok(false);
+        }
+        self.rotate(path)?;
+        Ok(true)
+    }
+
+    /// Copy-truncate `path`, dropping the oldest rotation beyond `max_files`.
+    pub fn rotate(&self, path: &Path) -> Result<()> {
+        if self.max_files > 0 {
+            let _ = std::fs::remove_file(Self::rotated(path, self.max_files));
+            for n in (1..self.max_files).rev() {
+                let from = Self::rotated(path, n);
+                if from.exists() {
+                    std::fs::rename(&from, Self::rotated(path, n + 1))
+                        .with_context(|| format!("Failed to shift {}", from.display()))?;
+                }
+            }
+            std::fs::copy(path, Self::rotated(path, 1))
+                .with_context(|| format!("Failed to copy {}", path.display()))?;
+        }
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_len(0))
+            .with_context(|| format!("Failed to truncate {}", path.display()))?;
+        info!(path = %path.display(), "Rotated log");
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tailing
+// ---------------------------------------------------------------------------
+
+/// Follow `path` like `tail -F`: up to `backlog` recent lines, then new
+/// lines as they are written, across truncation and rotation. The task stops
+/// when the receiver is dropped.
+pub fn follow(path: PathBuf, backlog: usize) -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel(256);
+    tokio::spawn(async move {
+        let mut pos = 0u64;
+        let mut partial = String::new();
+        if let Ok(meta) = tokio::fs::metadata(&path).await {
+            let start = meta.len().saturating_sub(BACKLOG_WINDOW);
+            let Ok(chunk) = read_from(&path, start).await else { return };
+            pos = start + chunk.len() as u64;
+            let mut lines: Vec<&str> = chunk.split('\n').collect();
+            partial = lines.pop().unwrap_or_default().to_string();
+            if start > 0 && !lines.is_empty() {
+                // The window probably starts mid-line.
+                lines.remove(0);
+            }
+            for line in &lines[lines.len().saturating_sub(backlog)..] {
+                if tx.send(line.to_string()).await.is_err() {
+                    return;
+                }
+            }
+        }
+
+        loop {
+            tokio::time::sleep(TAIL_POLL_INTERVAL).await;
+            if tx.is_closed() {
+                return;
+            }
+            let len = match tokio::fs::metadata(&path).await {
+                Ok(meta) => meta.len(),
+                Err(_) => continue,
+            };
+            if len < pos {
+                debug!(path = %path.display(), "Log truncated; following from the start");
+                pos = 0;
+                partial.clear();
+            }
+            if len == pos {
+                continue;
+            }
+            let Ok(chunk) = read_from(&path, pos).await else { continue };
+            pos += chunk.len() as u64;
+            partial.push_str(&chunk);
+            while let Some(end) = partial.find('\n') {
+                let line: String = partial.drain(..=end).collect();
+                if tx.send(line.trim_end().to_string()).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    rx
+}
+
+async fn read_from(path: &Path, offset: u64) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).await?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+// ---------------------------------------------------------------------------
+// Shipping
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "trace" => Some(Self::Trace),
+            "debug" => Some(Self::Debug),
+            "info" => Some(Self::Info),
+            "warn" | "warning" => Some(Self::Warn),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    /// Syslog severity.
+    fn severity(self) -> u8 {
+        match self {
+            Self::Error => 3,
+            Self::Warn => 4,
+            Self::Info => 6,
+            Self::Debug | Self::Trace => 7,
+        }
+    }
+}
+
+/// A log line as a structured record: the JSON the structured logger
+/// wrote, or the plain text wrapped as `{"message": ...}`.
+pub fn structured(line: &str) -> Value {
+    match serde_json::from_str::<Value>(line) {
+        Ok(value @ Value::Object(_)) => value,
+        _ => json!({ "message": line }),
+    }
+}
+
+fn level_of(record: &Value) -> LogLevel {
+    record["level"].as_str().and_then(LogLevel::parse).unwrap_or(LogLevel::Info)
+}
+
+fn message_of(record: &Value) -> String {
+    record["fields"]["message"]
+        .as_str()
+        .or_else(|| record["message"].as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| record.to_string())
+}
+
+#[derive(Debug, Clone)]
+pub enum ShipTarget {
+    /// Remote `host:port` over UDP, or the local `/dev/log`.
+    Syslog { address: Option<String> },
+    Journald,
+    Http { url: String, headers: HashMap<String, String> },
+}
+
+pub struct LogShipper {
+    target: ShipTarget,
+    min_level: LogLevel,
+    pub batch_size: usize,
+    client: reqwest::Client,
+}
+
+impl LogShipper {
+    pub fn new(target: ShipTarget) -> Self {
+        Self { target, min_level: LogLevel::Info, batch_size: 100, client: reqwest::Client::new() }
+    }
+
+    pub fn from_config(config: &LogShippingConfig) -> Result<Self> {
+        let target = match config.target.as_str() {
+            "syslog" => ShipTarget::Syslog { address: config.address.clone() },
+            "journald" => ShipTarget::Journald,
+            "http" => ShipTarget::Http {
+                url: config.url.clone().context("HTTP log shipping needs a collector URL")?,
+                headers: config.headers.clone(),
+            },
+            other => bail!("Unknown log shipping target '{}'", other),
+        };
+        let mut shipper = Self::new(target);
+        if let Some(level) = &config.min_level {
+            shipper.min_level = LogLevel::parse(level).with_context(|| format!("Unknown log level '{}'", level))?;
+        }
+        if let Some(batch_size) = config.batch_size {
+            shipper.batch_size = batch_size.max(1);
+        }
+        Ok(shipper)
+    }
+
+    /// Ship the lines at or above the minimum level.
+    pub async fn ship(&self, lines: &[String]) -> Result<()> {
+        let records: Vec<Value> =
+            lines.iter().map(|line| structured(line)).filter(|r| level_of(r) >= self.min_level).collect();
+        if records.is_empty() {
+            return Ok(());
+        }
+        match &self.target {
+            ShipTarget::Http { url, headers } => {
+                let mut request = self.client.post(url).json(&records);
+                for (name, value) in headers {
+                    request = request.header(name, value);
+                }
+                let status = request.send().await?.status();
+                if !status.is_success() {
+                    bail!("Log collector answered HTTP {}", status);
+                }
+            }
+            ShipTarget::Syslog { address } => {
+                let datagrams = records.iter().map(syslog_datagram);
+                match address {
+                    Some(address) => {
+                        let socket = tokio::net::UdpSocket::bind("0.0.0.0:0").await?;
+                        for datagram in datagrams {
+                            socket.send_to(datagram.as_bytes(), address).await?;
+                        }
+                    }
+                    None => send_local("/dev/log", datagrams.map(String::into_bytes)).await?,
+                }
+            }
+            ShipTarget::Journald => {
+                send_local("/run/systemd/journal/socket", records.iter().map(journald_datagram)).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// RFC 3164 line from the `daemon` facility.
+fn syslog_datagram(record: &Value) -> String {
+    let priority = 3 * 8 + level_of(record).severity();
+    let timestamp = chrono::Local::now().format("%b %e %H:%M:%S");
+    format!("<{}>{} clawforge[{}]: {}", priority, timestamp, std::process::id(), message_of(record))
+}
+
+/// journald native protocol: `KEY=value` lines, with values that contain
+/// newlines length-prefixed.
+fn journald_datagram(record: &Value) -> Vec<u8> {
+    let mut fields = vec![
+        ("MESSAGE", message_of(record)),
+        ("PRIORITY", level_of(record).severity().to_string()),
+        ("SYSLOG_IDENTIFIER", "clawforge".to_string()),
+    ];
+    if let Some(target) = record["target"].as_str() {
+        fields.push(("CLAWFORGE_TARGET", target.to_string()));
+    }
+    let mut datagram = Vec::new();
+    for (key, value) in fields {
+        datagram.extend_from_slice(key.as_bytes());
+        if value.contains('\n') {
+            datagram.push(b'\n');
+            datagram.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            datagram.push(b'=');
+        }
+        datagram.extend_from_slice(value.as_bytes());
+        datagram.push(b'\n');
+    }
+    datagram
+}
+
+#[cfg(unix)]
+async fn send_local(socket_path: &str, datagrams: impl Iterator<Item = Vec<u8>>) -> Result<()> {
+    let socket = tokio::net::UnixDatagram::unbound()?;
+    for datagram in datagrams {
+        socket.send_to(&datagram, socket_path).await.with_context(|| format!("Failed to write to {}", socket_path))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn send_local(socket_path: &str, _datagrams: impl Iterator<Item = Vec<u8>>) -> Result<()> {
+    bail!("{} is only available on Unix", socket_path)
+}
+
+// ---------------------------------------------------------------------------
+// Manager
+// ---------------------------------------------------------------------------
+
+/// Rotation, tailing and shipping for one log file.
+pub struct LogManager {
+    path: PathBuf,
+    rotation: Option<LogRotation>,
+    shipper: Option<LogShipper>,
+}
+
+impl LogManager {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), rotation: None, shipper: None }
+    }
+
+    /// The launchd gateway log (`gateway.log`), managed per `config`.
+    pub fn for_gateway(config: Option<&LoggingConfig>) -> Result<Self> {
+        let (stdout, _) = crate::launchd::gateway_log_paths();
+        let mut manager = Self::new(stdout).with_rotation(LogRotation::default());
+        if let Some(config) = config {
+            if let Some(rotation) = &config.rotation {
+                manager = manager.with_rotation(LogRotation::from_config(rotation));
+            }
+            if let Some(shipping) = &config.shipping {
+                manager = manager.with_shipper(LogShipper::from_config(shipping)?);
+            }
+        }
+        Ok(manager)
+    }
+
+    pub fn with_rotation(mut self, rotation: LogRotation) -> Self {
+        self.rotation = Some(rotation);
+        self
+    }
+
+    pub fn with_shipper(mut self, shipper: LogShipper) -> Self {
+        self.shipper = Some(shipper);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Recent and new lines of the log; see [`follow`].
+    pub fn tail(&self, backlog: usize) -> mpsc::Receiver<String> {
+        follow(self.path.clone(), backlog)
+    }
+
+    /// Rotate on schedule and ship new lines in the background.
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut lines = self.shipper.as_ref().map(|_| self.tail(0));
+            let mut batch = Vec::new();
+            let mut rotation_check = tokio::time::interval(ROTATION_CHECK_INTERVAL);
+            let mut flush = tokio::time::interval(SHIP_FLUSH_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = rotation_check.tick() => {
+                        if let Some(rotation) = &self.rotation {
+                            if let Err(e) = rotation.rotate_if_needed(&self.path) {
+                                warn!(error = %e, "Log rotation failed");
+                            }
+                        }
+                    }
+                    _ = flush.tick(), if !batch.is_empty() => self.ship(&mut batch).await,
+                    line = next_line(&mut lines) => match line {
+                        Some(line) => {
+                            batch.push(line);
+                            if self.shipper.as_ref().is_some_and(|s| batch.len() >= s.batch_size) {
+                                self.ship(&mut batch).await;
+                            }
+                        }
+                        None => lines = None,
+                    },
+                }
+            }
+        })
+    }
+
+    async fn ship(&self, batch: &mut Vec<String>) {
+        let Some(shipper) = &self.shipper else { return };
+        if let Err(e) = shipper.ship(batch).await {
+            // Keep the local file authoritative; a collector outage drops the batch.
+            warn!(error = %e, lines = batch.len(), "Log shipping failed");
+        }
+        batch.clear();
+    }
+}
+
+async fn next_line(lines: &mut Option<mpsc::Receiver<String>>) -> Option<String> {
+    match lines {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory holding `gateway.log` with `contents`.
+    fn log_file(contents: &str) -> PathBuf {
+        let nanos = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("clawforge-logs-{}-{}", std::process::id(), nanos));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gateway.log");
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn read(path: &Path) -> String {
+        std::fs::read_to_string(path).unwrap_or_default()
+    }
+
+    fn by_size(max_bytes: u64, max_files: usize) -> LogRotation {
+        LogRotation { max_bytes, max_age: None, max_files }
+    }
+
+    #[test]
+    fn files_rotate_only_once_they_reach_the_size_limit() {
+        let path = log_file("0123456789");
+        let rotation = by_size(11, 3);
+        assert!(!rotation.rotate_if_needed(&path).unwrap());
+        assert_eq!(read(&path), "0123456789");
+
+        std::fs::write(&path, "0123456789a").unwrap();
+        assert!(rotation.rotate_if_needed(&path).unwrap());
+        assert_eq!(read(&path), "");
+        assert_eq!(read(&LogRotation::rotated(&path, 1)), "0123456789a");
+
+        // An empty or missing file is never rotated.
+        assert!(!by_size(0, 3).rotate_if_needed(&path).unwrap());
+        assert!(!rotation.rotate_if_needed(&path.with_file_name("missing.log")).unwrap());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn rotations_shift_up_and_the_oldest_beyond_max_files_is_dropped() {
+        let path = log_file("");
+        let rotation = by_size(1, 2);
+        for generation in ["first", "second", "third"] {
+            std::fs::write(&path, generation).unwrap();
+            rotation.rotate(&path).unwrap();
+        }
+        assert_eq!(read(&LogRotation::rotated(&path, 1)), "third");
+        assert_eq!(read(&LogRotation::rotated(&path, 2)), "second");
+        assert!(!LogRotation::rotated(&path, 3).exists());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn keeping_no_rotations_just_truncates() {
+        let path = log_file("line\n");
+        by_size(1, 0).rotate(&path).unwrap();
+        assert_eq!(read(&path), "");
+        assert!(!LogRotation::rotated(&path, 1).exists());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn files_past_the_age_limit_rotate_even_when_small() {
+        let path = log_file("line\n");
+        std::fs::write(LogRotation::rotated(&path, 1), "older\n").unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        let rotation = LogRotation { max_bytes: u64::MAX, max_age: Some(Duration::from_millis(1)), max_files: 2 };
+        assert!(rotation.rotate_if_needed(&path).unwrap());
+        assert_eq!(read(&LogRotation::rotated(&path, 1)), "line\n");
+        assert_eq!(read(&LogRotation::rotated(&path, 2)), "older\n");
+
+        // The rotation just written restarts the clock.
+        std::fs::write(&path, "line\n").unwrap();
+        let rotation = LogRotation { max_age: Some(Duration::from_secs(3600)), ..rotation };
+        assert!(!rotation.rotate_if_needed(&path).unwrap());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn config_limits_are_in_megabytes_and_days() {
+        let config = LogRotationConfig { max_size_mb: Some(2), max_age_days: Some(1), max_files: Some(9) };
+        let rotation = LogRotation::from_config(&config);
+        assert_eq!(rotation.max_bytes, 2 * 1024 * 1024);
+        assert_eq!(rotation.max_age, Some(Duration::from_secs(86_400)));
+        assert_eq!(rotation.max_files, 9);
+
+        let rotation = LogRotation::from_config(&LogRotationConfig::default());
+        assert_eq!(rotation.max_bytes, LogRotation::default().max_bytes);
+        assert_eq!(rotation.max_files, 5);
+    }
+
+    async fn next(lines: &mut mpsc::Receiver<String>) -> String {
+        tokio::time::timeout(Duration::from_secs(5), lines.recv()).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn tails_follow_the_file_across_a_rotation() {
+        let path = log_file("one\ntwo\nthree\n");
+        let mut lines = follow(path.clone(), 2);
+        assert_eq!(next(&mut lines).await, "two");
+        assert_eq!(next(&mut lines).await, "three");
+
+        by_size(1, 1).rotate(&path).unwrap();
+        std::fs::write(&path, "four\n").unwrap();
+        assert_eq!(next(&mut lines).await, "four");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Stdio;
+use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -10,20 +12,509 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use clawforge_core::{
-    AuditEventPayload, Capabilities, ClawError, Component, Event, EventKind,
-    Message, ProposedAction,
+    ActionProposal, AuditEventPayload, Capabilities, ClawError, Component, Event, EventKind,
+    Message, OutboundMedia, OutboundMediaSink, ProposedAction, RepairRequest, Tool, ToolPolicyDecision, ToolPolicyEngine,
     tools::ToolRegistry,
 };
+use clawforge_companion::{DesktopPermission, HttpNodeTransport, NodeHostRegistry, NodeStore, ScriptAllowlist};
+use clawforge_sandbox::{analyze_argv, truncate_tail, DockerSandboxConfig, NativeSandbox, ResourceLimits, SandboxRegistry, SecretBroker, DEFAULT_MAX_OUTPUT_BYTES};
+use clawforge_security::{ApprovalBroker, ApprovalOutcome, ExternalContentGuard};
+use clawforge_tools::{preview_write, AgentMessagePolicy, ConnectorSet, EditJournal, SendToAgentTool, StateBackend, StateGetTool, StateSetTool};
+
+/// Diff lines shown in a chat approval prompt; the event carries the whole diff.
+const MAX_PREVIEW_LINES: usize = 60;
+
+/// Agent name → agent ID for every agent `send_to_agent` can address,
+/// listed when the tool is called.
+pub type AgentDirectory = Arc<dyn Fn() -> HashMap<String, Uuid> + Send + Sync>;
 
 /// The Executor component receives ActionProposals, validates capabilities,
 /// and executes approved actions.
 pub struct Executor {
     supervisor_tx: mpsc::Sender<Message>,
+    tool_policy: Option<Arc<ToolPolicyEngine>>,
+    approvals: Option<Arc<ApprovalBroker>>,
+    native_sandbox: Option<NativeSandbox>,
+    sandboxes: Option<(Arc<SandboxRegistry>, ResourceLimits)>,
+    /// Where repair requests for contract violations go.
+    planner_tx: Option<mpsc::Sender<Message>>,
+    /// Backs `state_get` / `state_set`, bound per call to the run's agent.
+    state: Option<Arc<dyn StateBackend>>,
+    /// Data sources offered through `connector_fetch`.
+    connectors: Option<Arc<ConnectorSet>>,
+    /// Cap on streamed output events and on each stream kept in a sandbox result.
+    max_output_bytes: usize,
+    /// Records `file_write` / `file_edit` changes for `/undo`.
+    edits: Option<Arc<EditJournal>>,
+    /// Put a unified diff of `file_write` changes in git work trees into the approval.
+    preview_writes: bool,
+    /// Work tree the `git` tool operates on.
+    git_workspace: Option<PathBuf>,
+    /// Workspace the `grep` / `glob` tools search.
+    search_workspace: Option<PathBuf>,
+    /// Per-agent browser sessions behind the `browser` tool.
+    browsers: Option<Arc<clawforge_browser::SessionPool>>,
+    /// Quarantine for files the browser and `web_fetch` download.
+    downloads: Option<clawforge_tools::DownloadManager>,
+    /// Per-agent web search providers.
+    web_search: Option<Arc<clawforge_tools::SearchProviders>>,
+    /// Prompt-injection guard `web_fetch` runs fetched pages through.
+    web_fetch: Option<ExternalContentGuard>,
+    /// Offer the `http` tool, bound per call to the run's allowed domains.
+    http_tool: bool,
+    /// API whose operations the `http` tool offers as typed calls.
+    openapi: Option<Arc<clawforge_tools::OpenApiSpec>>,
+    /// Driver and settings for `python` sessions, run in `sandboxes`.
+    python: Option<(String, DockerSandboxConfig)>,
+    /// Secrets agents lease for `secret_exec` commands in `sandboxes`.
+    secrets: Option<Arc<SecretBroker>>,
+    /// Where tools send images and documents they produce.
+    media: Option<Arc<media::MediaPipeline>>,
+    /// Uploads attachments in tool results to the chat the run came from.
+    media_sink: Option<Arc<dyn OutboundMediaSink>>,
+    /// `generate_image`, bound per call to the calling agent and session.
+    image_gen: Option<Arc<clawforge_tools::ImageGenTool>>,
+    /// Publishes HTML, SVG, CSV and Mermaid outputs as linkable artifacts.
+    artifacts: Option<Arc<clawforge_tools::ArtifactTool>>,
+    /// Node hosts behind the desktop tools, and the store holding their grants.
+    nodes: Option<(Arc<NodeHostRegistry<HttpNodeTransport>>, Arc<NodeStore>)>,
+    /// Scripts `mac_automation` may run on `nodes`.
+    automation: Option<Arc<ScriptAllowlist>>,
+    /// Who may message whom with `send_to_agent`, the agents it addresses
+    /// and the scheduler their runs are queued on.
+    agent_messages: Option<(AgentMessagePolicy, AgentDirectory, mpsc::Sender<Message>)>,
+    /// Tools registered by the embedder, e.g. plugins or test fixtures.
+    extra_tools: Vec<Arc<dyn Tool>>,
 }
 
 impl Executor {
     pub fn new(supervisor_tx: mpsc::Sender<Message>) -> Self {
-        Self { supervisor_tx }
+        Self {
+            supervisor_tx,
+            tool_policy: None,
+            approvals: None,
+            native_sandbox: None,
+            sandboxes: None,
+            planner_tx: None,
+            state: None,
+            connectors: None,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            edits: None,
+            preview_writes: false,
+            git_workspace: None,
+            search_workspace: None,
+            browsers: None,
+            downloads: None,
+            web_search: None,
+            web_fetch: None,
+            http_tool: false,
+            openapi: None,
+            python: None,
+            secrets: None,
+            media: None,
+            media_sink: None,
+            image_gen: None,
+            artifacts: None,
+            nodes: None,
+            automation: None,
+            agent_messages: None,
+            extra_tools: Vec::new(),
+        }
+    }
+
+    /// Send steps whose output breaks their output contract back to the
+    /// planner with a repair prompt. Without it, a violation fails the run.
+    pub fn with_planner(mut self, planner_tx: mpsc::Sender<Message>) -> Self {
+        self.planner_tx = Some(planner_tx);
+        self
+    }
+
+    /// Enforce per-agent / per-channel tool allowlists on top of capabilities.
+    pub fn with_tool_policy(mut self, policy: Arc<ToolPolicyEngine>) -> Self {
+        self.tool_policy = Some(policy);
+        self
+    }
+
+    /// Ask a human before running dangerous tools (shell, writes, mutating HTTP).
+    pub fn with_approvals(mut self, broker: Arc<ApprovalBroker>) -> Self {
+        self.approvals = Some(broker);
+        self
+    }
+
+    /// Run the shell tool under the OS sandbox (seatbelt / AppContainer).
+    pub fn with_native_sandbox(mut self, sandbox: NativeSandbox) -> Self {
+        self.native_sandbox = Some(sandbox);
+        self
+    }
+
+    /// Attach sandbox resource usage to run events after each action, and
+    /// flag runs whose sandbox goes over `limits` or has egress blocked.
+    /// Sandboxes are looked up in `registry` by the run's session. Also
+    /// starts the registry's lifetime watchdog when called inside a Tokio
+    /// runtime.
+    pub fn with_sandbox_usage(mut self, registry: Arc<SandboxRegistry>, limits: ResourceLimits) -> Self {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            registry.spawn_watchdog(std::time::Duration::from_secs(5));
+        }
+        self.sandboxes = Some((registry, limits));
+        self
+    }
+
+    /// Offer agents durable key-value state between runs via `state_get` / `state_set`.
+    pub fn with_state_store(mut self, backend: Arc<dyn StateBackend>) -> Self {
+        self.state = Some(backend);
+        self
+    }
+
+    /// Let agents read declared data sources (weather, feeds, calendar, ...)
+    /// through the `connector_fetch` tool.
+    pub fn with_connectors(mut self, connectors: Arc<ConnectorSet>) -> Self {
+        self.connectors = Some(connectors);
+        self
+    }
+
+    /// Limit how much shell output is streamed as `ActionOutput` events and
+    /// kept (from the tail) in the step result, locally or on an exec host.
+    pub fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Record every `file_write` / `file_edit` so `/undo` can revert a session's last agent edit.
+    pub fn with_edit_journal(mut self, journal: Arc<EditJournal>) -> Self {
+        self.edits = Some(journal);
+        self
+    }
+
+    /// Preview & approve: a `file_write` inside a git work tree is shown as a
+    /// unified diff in its approval request and only applied once approved.
+    /// Needs `with_approvals`.
+    pub fn with_write_previews(mut self) -> Self {
+        self.preview_writes = true;
+        self
+    }
+
+    /// Offer the `git` tool (status, diff, commit, branch) on `workspace`.
+    pub fn with_git_tool(mut self, workspace: impl Into<PathBuf>) -> Self {
+        self.git_workspace = Some(workspace.into());
+        self
+    }
+
+    /// Offer the `grep` and `glob` code search tools on `workspace`.
+    pub fn with_search_tools(mut self, workspace: impl Into<PathBuf>) -> Self {
+        self.search_workspace = Some(workspace.into());
+        self
+    }
+
+    /// Offer the `browser.control` tool; each agent drives its own session from `pool`.
+    pub fn with_browser(mut self, pool: Arc<clawforge_browser::SessionPool>) -> Self {
+        self.browsers = Some(pool);
+        self
+    }
+
+    /// Quarantine, size-limit and scan what the browser and `web_fetch` download.
+    pub fn with_downloads(mut self, downloads: clawforge_tools::DownloadManager) -> Self {
+        self.downloads = Some(downloads);
+        self
+    }
+
+    /// Offer the `web_search` tool through each agent's configured providers.
+    pub fn with_web_search(mut self, providers: Arc<clawforge_tools::SearchProviders>) -> Self {
+        self.web_search = Some(providers);
+        self
+    }
+
+    /// Offer the `web_fetch` tool, quarantining pages under `guard`'s policy.
+    pub fn with_web_fetch(mut self, guard: ExternalContentGuard) -> Self {
+        self.web_fetch = Some(guard);
+        self
+    }
+
+    /// Offer the structured `http` tool; requests are audited as `HttpExchange` events.
+    pub fn with_http_tool(mut self) -> Self {
+        self.http_tool = true;
+        self
+    }
+
+    /// Offer the `http` tool with `spec`'s operations as typed calls.
+    pub fn with_openapi_spec(mut self, spec: Arc<clawforge_tools::OpenApiSpec>) -> Self {
+        self.http_tool = true;
+        self.openapi = Some(spec);
+        self
+    }
+
+    /// Offer the `python` tool, one interpreter session per conversation in a sandbox
+    /// from `with_sandbox_usage` using `driver`.
+    pub fn with_python_tool(mut self, driver: impl Into<String>, config: DockerSandboxConfig) -> Self {
+        self.python = Some((driver.into(), config));
+        self
+    }
+
+    /// Offer `secret_lease` and `secret_exec`, running in `sandboxes`.
+    pub fn with_sandbox_secrets(mut self, broker: Arc<SecretBroker>) -> Self {
+        self.secrets = Some(broker);
+        self
+    }
+
+    /// Hand figures and other media produced by tools to `pipeline`.
+    pub fn with_media_pipeline(mut self, pipeline: Arc<media::MediaPipeline>) -> Self {
+        self.media = Some(pipeline);
+        self
+    }
+
+    /// Upload the `media` attachments of tool results to the originating chat.
+    pub fn with_outbound_media(mut self, sink: Arc<dyn OutboundMediaSink>) -> Self {
+        self.media_sink = Some(sink);
+        self
+    }
+
+    /// Offer `generate_image` through `tool`'s providers, acting as the
+    /// calling agent.
+    pub fn with_image_generation(mut self, tool: clawforge_tools::ImageGenTool) -> Self {
+        self.image_gen = Some(Arc::new(tool));
+        self
+    }
+
+    /// Offer the `artifact` tool for outputs too big for a chat message.
+    pub fn with_artifacts(mut self, tool: clawforge_tools::ArtifactTool) -> Self {
+        self.artifacts = Some(Arc::new(tool));
+        self
+    }
+
+    /// Offer the desktop tools (`clipboard_read`, `clipboard_write`,
+    /// `screen_capture`) on `nodes`, limited to what `store` has granted.
+    pub fn with_nodes(mut self, nodes: Arc<NodeHostRegistry<HttpNodeTransport>>, store: Arc<NodeStore>) -> Self {
+        self.nodes = Some((nodes, store));
+        self
+    }
+
+    /// Offer the `mac_automation` tool for `allowlist`'s scripts on the
+    /// `with_nodes` hosts; runs are approved through `with_approvals`.
+    pub fn with_mac_automation(mut self, allowlist: Arc<ScriptAllowlist>) -> Self {
+        self.automation = Some(allowlist);
+        self
+    }
+
+    /// Offer `send_to_agent`, queuing runs on `scheduler_tx` for the agents
+    /// `policy` lets the caller message.
+    pub fn with_agent_messages(mut self, policy: AgentMessagePolicy, agents: AgentDirectory, scheduler_tx: mpsc::Sender<Message>) -> Self {
+        self.agent_messages = Some((policy, agents, scheduler_tx));
+        self
+    }
+
+    /// Offer `tool` alongside the built-in ones; it replaces a built-in of the same name.
+    pub fn with_tool(mut self, tool: Arc<dyn Tool>) -> Self {
+        self.extra_tools.push(tool);
+        self
+    }
+
+    /// Tools that must act as the calling agent, built per call rather than
+    /// shared through the registry.
+    fn agent_scoped_tool(&self, name: &str, proposal: &ActionProposal) -> Option<Arc<dyn Tool>> {
+        let agent_id = proposal.agent_id;
+        match name {
+            "state_get" => Some(Arc::new(StateGetTool::new(agent_id, self.state.clone()?))),
+            "state_set" => Some(Arc::new(StateSetTool::new(agent_id, self.state.clone()?))),
+            "browser.control" => {
+                let mut tool = clawforge_tools::BrowserTool::new(self.browsers.clone()?, agent_id.to_string());
+                if let Some(downloads) = &self.downloads {
+                    tool = tool.with_downloads(downloads.clone());
+                }
+                if let Some(pipeline) = &self.media {
+                    tool = tool.with_media(pipeline.clone(), proposal.run_id, agent_id);
+                }
+                Some(Arc::new(tool))
+            }
+            // Journaled per session, so `/undo` only reverts the caller's edits.
+            "file_write" => {
+                let journal = self.edits.clone()?;
+                Some(Arc::new(clawforge_tools::FileWriteTool::default().with_journal(journal, proposal.session_key())))
+            }
+            "file_edit" => {
+                let journal = self.edits.clone()?;
+                Some(Arc::new(clawforge_tools::EditTool::default().with_journal(journal, proposal.session_key())))
+            }
+            "web_search" => {
+                let agent = proposal.agent_name.clone().unwrap_or_else(|| agent_id.to_string());
+                let router = self.web_search.as_ref()?.for_agent(&agent);
+                Some(Arc::new(clawforge_tools::WebSearchTool::new(router)))
+            }
+            "http" if self.http_tool => {
+                let (supervisor_tx, run_id) = (self.supervisor_tx.clone(), proposal.run_id);
+                let audit: clawforge_tools::HttpAuditSink = Arc::new(move |exchange| {
+                    let event = Event::new(run_id, agent_id, EventKind::HttpExchange, serde_json::json!(exchange));
+                    let tx = supervisor_tx.clone();
+                    tokio::spawn(async move {
+                        let _ = tx.send(Message::AuditEvent(AuditEventPayload { event })).await;
+                    });
+                });
+                let mut tool = clawforge_tools::HttpTool::new(&proposal.capabilities).with_audit(audit);
+                if let Some(spec) = &self.openapi {
+                    tool = tool.with_spec(spec.clone());
+                }
+                Some(Arc::new(tool))
+            }
+            // Leases are bound to the session and agent that take them.
+            "secret_lease" => {
+                let broker = self.secrets.clone()?;
+                Some(Arc::new(clawforge_tools::SecretLeaseTool::new(broker, proposal.session_key(), proposal.agent_name.clone())))
+            }
+            "secret_exec" => {
+                let broker = self.secrets.clone()?;
+                let (sandboxes, _) = self.sandboxes.as_ref()?;
+                Some(Arc::new(clawforge_tools::SecretExecTool::new(
+                    sandboxes.clone(),
+                    broker,
+                    proposal.session_key(),
+                    proposal.agent_name.clone(),
+                    proposal.capabilities.clone(),
+                )))
+            }
+            // The allow matrix is checked against the calling agent's name.
+            "send_to_agent" => {
+                let (policy, agents, scheduler_tx) = self.agent_messages.clone()?;
+                let sender = proposal.agent_name.clone().unwrap_or_else(|| agent_id.to_string());
+                Some(Arc::new(SendToAgentTool::new(sender, policy, agents(), scheduler_tx)))
+            }
+            "generate_image" => {
+                let agent = proposal.agent_name.clone().unwrap_or_else(|| agent_id.to_string());
+                Some(Arc::new(self.image_gen.as_ref()?.bound_to(agent, proposal.session_key())))
+            }
+            "python" => {
+                let (driver, config) = self.python.clone()?;
+                let (sandboxes, _) = self.sandboxes.as_ref()?;
+                let mut tool = clawforge_tools::PythonTool::new(sandboxes.clone(), &proposal.session_key(), proposal.capabilities.clone())
+                    .with_sandbox(driver, config);
+                if let Some(approvals) = &self.approvals {
+                    tool = tool.with_approvals(approvals.clone(), proposal.channel.clone());
+                }
+                if let Some(pipeline) = &self.media {
+                    tool = tool.with_media(pipeline.clone(), proposal.run_id, agent_id);
+                }
+                Some(Arc::new(tool))
+            }
+            "mac_automation" => {
+                let (nodes, _) = self.nodes.clone()?;
+                let mut tool = clawforge_tools::MacAutomationTool::new(nodes, self.automation.clone()?);
+                if let Some(approvals) = &self.approvals {
+                    tool = tool.with_approvals(approvals.clone(), proposal.principal_key(), proposal.channel.clone());
+                }
+                Some(Arc::new(tool))
+            }
+            "clipboard_read" | "clipboard_write" | "screen_capture" => {
+                let (nodes, store) = self.nodes.clone()?;
+                let mut tool = clawforge_tools::DesktopTool::new(DesktopPermission::parse(name)?, nodes, store);
+                if let Some(approvals) = &self.approvals {
+                    tool = tool.with_approvals(approvals.clone(), proposal.principal_key(), proposal.channel.clone());
+                }
+                if let Some(pipeline) = &self.media {
+                    tool = tool.with_vision(pipeline.clone(), proposal.run_id, agent_id);
+                }
+                Some(Arc::new(tool))
+            }
+            _ => None,
+        }
+    }
+
+    fn shell_tool(&self) -> clawforge_tools::ShellTool {
+        let tool = clawforge_tools::ShellTool::default();
+        match &self.native_sandbox {
+            Some(sandbox) => tool.with_native_sandbox(sandbox.clone()),
+            None => tool,
+        }
+    }
+
+    /// Tool name the policy engine sees for an action. Shell commands are the
+    /// `shell` tool and raw HTTP requests are `http_<method>`; LLM responses
+    /// have no side effects and are not subject to tool policy.
+    fn policy_tool_name(action: &ProposedAction) -> Option<String> {
+        match action {
+            ProposedAction::ShellCommand { .. } => Some("shell".to_string()),
+            ProposedAction::HttpRequest { method, .. } => Some(format!("http_{}", method.to_lowercase())),
+            ProposedAction::ToolCall { name, .. } => Some(name.clone()),
+            ProposedAction::LlmResponse { .. } => None,
+        }
+    }
+
+    /// One-line description of an action for approval prompts.
+    fn describe_action(action: &ProposedAction) -> String {
+        match action {
+            ProposedAction::ShellCommand { command, args, .. } => {
+                std::iter::once(command.as_str()).chain(args.iter().map(String::as_str)).collect::<Vec<_>>().join(" ")
+            }
+            ProposedAction::HttpRequest { method, url, .. } => format!("{} {}", method.to_uppercase(), url),
+            ProposedAction::ToolCall { name, args } => {
+                let args = args.to_string();
+                let args: String = args.chars().take(200).collect();
+                format!("{}({})", name, args)
+            }
+            ProposedAction::LlmResponse { .. } => String::new(),
+        }
+    }
+
+    /// Park a dangerous tool call until a human answers: the verdict is
+    /// awaited in its own task and the proposal comes back through `parked`,
+    /// so other runs keep executing meanwhile. Returns false when no approval
+    /// is needed.
+    async fn park_for_approval(&self, proposal: &ActionProposal, parked: &mpsc::UnboundedSender<(ActionProposal, ApprovalOutcome)>) -> bool {
+        let Some(broker) = self.approvals.clone() else { return false };
+        let Some(tool) = Self::policy_tool_name(&proposal.action) else { return false };
+        if !broker.needs_approval(&tool) {
+            return false;
+        }
+        let mut payload = serde_json::json!({"step": proposal.step_index, "tool": tool});
+        let mut reasons = Vec::new();
+        let mut summary = Self::describe_action(&proposal.action);
+        if let ProposedAction::ShellCommand { command, args, .. } = &proposal.action {
+            let argv: Vec<&str> = std::iter::once(command.as_str()).chain(args.iter().map(String::as_str)).collect();
+            let analysis = analyze_argv(&argv);
+            payload["risk"] = serde_json::json!(analysis.risk);
+            payload["findings"] = serde_json::json!(analysis.findings);
+            payload["segments"] = serde_json::json!(analysis.segments);
+            reasons = analysis.reasons;
+        }
+        if let Some(preview) = self.write_preview(&proposal.action).await {
+            reasons.push(format!(
+                "{} {} in git workspace {}",
+                if preview.created { "Creates" } else { "Modifies" },
+                preview.path,
+                preview.workspace.display()
+            ));
+            summary = preview.summary(MAX_PREVIEW_LINES);
+            payload["diff"] = serde_json::json!(preview.diff);
+            payload["path"] = serde_json::json!(preview.path);
+        }
+        self.emit_event(proposal.run_id, proposal.agent_id, EventKind::ApprovalRequested, payload).await;
+        let (proposal, parked) = (proposal.clone(), parked.clone());
+        // "Allow for the session" verdicts follow the person across chats.
+        tokio::spawn(async move {
+            let outcome = broker
+                .request_in_chat(
+                    &proposal.principal_key(),
+                    proposal.channel.as_deref(),
+                    proposal.chat_id.as_deref(),
+                    &tool,
+                    &summary,
+                    reasons,
+                )
+                .await;
+            let _ = parked.send((proposal, outcome));
+        });
+        true
+    }
+
+    async fn write_preview(&self, action: &ProposedAction) -> Option<clawforge_tools::WritePreview> {
+        let ProposedAction::ToolCall { name, args } = action else { return None };
+        if !self.preview_writes || name != "file_write" {
+            return None;
+        }
+        preview_write(args["path"].as_str()?, args["content"].as_str()?).await
+    }
+
+    fn check_tool_policy(&self, proposal: &ActionProposal) -> Option<ToolPolicyDecision> {
+        let policy = self.tool_policy.as_ref()?;
+        let tool = Self::policy_tool_name(&proposal.action)?;
+        Some(policy.evaluate(&tool, proposal.agent_name.as_deref(), proposal.channel.as_deref()))
     }
 
     /// Check if the proposed action is allowed by the agent's capabilities.
@@ -73,6 +564,11 @@ impl Executor {
                         "tool execution not allowed".to_string(),
                     ));
                 }
+                if name == "http" && !capabilities.can_make_http_requests {
+                    return Err(ClawError::CapabilityDenied(
+                        "HTTP requests not allowed".to_string(),
+                    ));
+                }
                 if !capabilities.allowed_tools.is_empty()
                     && !capabilities.allowed_tools.iter().any(|t| t == name)
                 {
@@ -86,11 +582,13 @@ impl Executor {
         Ok(())
     }
 
-    /// Execute a shell command and return its output.
+    /// Execute a shell command and return the last `max_output_bytes` of
+    /// each stream.
     async fn execute_shell(
         command: &str,
         args: &[String],
         working_dir: &Option<String>,
+        max_output_bytes: usize,
     ) -> Result<serde_json::Value> {
         let mut cmd = Command::new(command);
         cmd.args(args)
@@ -104,14 +602,72 @@ impl Executor {
         info!(command = %command, args = ?args, "Executing shell command");
 
         let output = cmd.output().await?;
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let mut stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let mut stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let truncated = truncate_tail(&mut stdout, max_output_bytes) | truncate_tail(&mut stderr, max_output_bytes);
 
         Ok(serde_json::json!({
             "exit_code": output.status.code(),
             "stdout": stdout,
             "stderr": stderr,
             "success": output.status.success(),
+            "truncated": truncated,
+        }))
+    }
+
+    /// The sandbox registry, when the session's commands were routed to an
+    /// exec host (`/exec host ...`) instead of this machine.
+    fn exec_host_registry(&self, session: &str) -> Option<&Arc<SandboxRegistry>> {
+        let (registry, _) = self.sandboxes.as_ref()?;
+        registry.exec_host(session).map(|_| registry)
+    }
+
+    /// Run a shell command through the session's exec host sandbox, emitting
+    /// its output lines as `ActionOutput` events while it runs.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_on_host(
+        &self,
+        registry: &SandboxRegistry,
+        session_id: &str,
+        run_id: Uuid,
+        agent_id: Uuid,
+        step: usize,
+        command: &str,
+        args: &[String],
+    ) -> Result<serde_json::Value> {
+        let argv: Vec<&str> = std::iter::once(command).chain(args.iter().map(String::as_str)).collect();
+        let host = registry.exec_host(session_id).unwrap_or_default();
+        info!(command = %command, args = ?args, host = %host, "Executing shell command on exec host");
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let exec = async move {
+            let config = DockerSandboxConfig::default();
+            registry.exec_routed(session_id, &argv, None, &config, self.max_output_bytes, &tx).await
+        };
+        let forward = async {
+            let mut streamed = 0;
+            while let Some(chunk) = rx.recv().await {
+                if streamed > self.max_output_bytes {
+                    continue;
+                }
+                streamed += chunk.line.len() + 1;
+                let payload = if streamed > self.max_output_bytes {
+                    serde_json::json!({ "step": step, "truncated": true })
+                } else {
+                    serde_json::json!({ "step": step, "stream": chunk.stream, "line": chunk.line })
+                };
+                self.emit_event(run_id, agent_id, EventKind::ActionOutput, payload).await;
+            }
+        };
+        let (result, ()) = tokio::join!(exec, forward);
+        let result = result?;
+        Ok(serde_json::json!({
+            "exit_code": result.exit_code,
+            "stdout": result.stdout,
+            "stderr": result.stderr,
+            "success": result.exit_code == 0 && !result.timed_out,
+            "timed_out": result.timed_out,
+            "truncated": result.truncated,
         }))
     }
 
@@ -176,7 +732,10 @@ impl Executor {
         let tool = registry.get(name).ok_or_else(|| {
             anyhow::anyhow!("Tool '{}' not found", name)
         })?;
-        
+        Self::run_tool(tool.as_ref(), name, args).await
+    }
+
+    async fn run_tool(tool: &dyn Tool, name: &str, args: serde_json::Value) -> Result<serde_json::Value> {
         info!(tool = %name, "Executing tool");
         let output = tool.execute(args).await?;
         
@@ -186,6 +745,286 @@ impl Executor {
         }))
     }
 
+    /// Check the step output against its contract. A violation is repaired
+    /// through the planner while attempts remain, otherwise it fails the run.
+    /// Returns whether the output may be used.
+    async fn enforce_contract(&self, proposal: &ActionProposal, output: &serde_json::Value) -> bool {
+        let Some(contract) = &proposal.output_contract else { return true };
+        let violations = match contract.check(output) {
+            Ok(_) => return true,
+            Err(violations) => violations,
+        };
+        let (run_id, agent_id, step) = (proposal.run_id, proposal.agent_id, proposal.step_index);
+        let attempt = proposal.repair_attempt;
+        warn!(run_id = %run_id, step, attempt, violations = violations.len(), "Step output violates its contract");
+        self.emit_event(
+            run_id,
+            agent_id,
+            EventKind::OutputContractViolated,
+            serde_json::json!({"step": step, "attempt": attempt, "violations": violations}),
+        )
+        .await;
+
+        if attempt < contract.max_repairs {
+            if let Some(planner_tx) = &self.planner_tx {
+                let repair = RepairRequest {
+                    run_id,
+                    agent_id,
+                    step_index: step,
+                    attempt: attempt + 1,
+                    prompt: contract.repair_prompt(&violations),
+                };
+                if planner_tx.send(Message::RepairOutput(repair)).await.is_ok() {
+                    return false;
+                }
+                error!(run_id = %run_id, "Failed to send repair request to planner");
+            }
+        }
+
+        let details: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+        self.emit_event(
+            run_id,
+            agent_id,
+            EventKind::RunFailed,
+            serde_json::json!({
+                "error": format!(
+                    "Step {} output violates its contract after {} repair attempt(s): {}",
+                    step,
+                    attempt,
+                    details.join("; ")
+                ),
+                "step": step,
+                "violations": violations,
+            }),
+        )
+        .await;
+        false
+    }
+
+    /// Emit an audit event for each connection the run's sandbox had refused.
+    async fn report_blocked_egress(&self, session: &str, run_id: Uuid, agent_id: Uuid, step: usize) {
+        let Some((registry, _)) = &self.sandboxes else { return };
+        for attempt in registry.take_blocked_egress(session).await {
+            self.emit_event(
+                run_id,
+                agent_id,
+                EventKind::EgressBlocked,
+                serde_json::json!({ "step": step, "host": attempt.host, "port": attempt.port, "method": attempt.method, "at": attempt.at }),
+            )
+            .await;
+        }
+    }
+
+    /// Emit an event for each limit the run's sandbox hit (OOM kill, timeout, lifetime).
+    async fn report_resource_exceeded(&self, session: &str, run_id: Uuid, agent_id: Uuid, step: usize) {
+        let Some((registry, _)) = &self.sandboxes else { return };
+        for exceeded in registry.take_resource_exceeded(session) {
+            self.emit_event(
+                run_id,
+                agent_id,
+                EventKind::SandboxResourceExceeded,
+                serde_json::json!({ "step": step, "kind": exceeded.kind, "detail": exceeded.detail, "at": exceeded.at }),
+            )
+            .await;
+        }
+    }
+
+    /// Emit the run's sandbox usage, and a limit event if it is running away.
+    async fn report_sandbox_usage(&self, session: &str, run_id: Uuid, agent_id: Uuid, step: usize) {
+        let Some((registry, limits)) = &self.sandboxes else { return };
+        let usage = match registry.sample(session).await {
+            Some(Ok(usage)) => usage,
+            Some(Err(e)) => {
+                warn!(run_id = %run_id, "Failed to sample sandbox usage: {e:#}");
+                return;
+            }
+            None => return,
+        };
+        self.emit_event(run_id, agent_id, EventKind::ResourceUsage, serde_json::json!({ "step": step, "usage": usage }))
+            .await;
+        let exceeded = usage.exceeded(limits);
+        if !exceeded.is_empty() {
+            warn!(run_id = %run_id, ?exceeded, "Sandbox over resource limits");
+            self.emit_event(
+                run_id,
+                agent_id,
+                EventKind::ResourceLimitExceeded,
+                serde_json::json!({ "step": step, "usage": usage, "exceeded": exceeded }),
+            )
+            .await;
+        }
+    }
+
+    /// Check, execute and report one proposal. `approval` is the verdict of
+    /// a proposal coming back from `park_for_approval`; the checks run again
+    /// on the way back in case the policy changed meanwhile.
+    async fn handle_proposal(
+        &self,
+        registry: &ToolRegistry,
+        proposal: ActionProposal,
+        approval: Option<ApprovalOutcome>,
+        parked: &mpsc::UnboundedSender<(ActionProposal, ApprovalOutcome)>,
+    ) {
+        let run_id = proposal.run_id;
+        let agent_id = proposal.agent_id;
+        let session = proposal.session_key();
+
+        if let Some(outcome) = approval.as_ref().filter(|outcome| !outcome.is_approved()) {
+            warn!(run_id = %run_id, "Dangerous tool call not approved");
+            self.emit_event(
+                run_id,
+                agent_id,
+                EventKind::ActionDenied,
+                serde_json::json!({"error": "not approved", "approval": outcome}),
+            )
+            .await;
+            return;
+        }
+
+        info!(
+            run_id = %proposal.run_id,
+            step = proposal.step_index,
+            "Executing action"
+        );
+
+        // Capability check against the agent's declared spec — enforced here,
+        // not assumed. Defaults (Capabilities::default) are all-false (deny).
+        if let Err(e) = Self::check_capability(&proposal.capabilities, &proposal.action) {
+            warn!(run_id = %run_id, error = %e, "Capability denied");
+            self.emit_event(
+                run_id,
+                agent_id,
+                EventKind::ActionDenied,
+                serde_json::json!({"error": e.to_string()}),
+            )
+            .await;
+            return;
+        }
+
+        // Tool policy — the matched rule goes into the audit event either way.
+        match self.check_tool_policy(&proposal) {
+            Some(decision) if !decision.allowed => {
+                warn!(run_id = %run_id, tool = %decision.tool, "Tool denied by policy");
+                self.emit_event(
+                    run_id,
+                    agent_id,
+                    EventKind::ActionDenied,
+                    serde_json::json!({
+                        "error": decision.reason(),
+                        "tool": decision.tool,
+                        "rule": decision.rule,
+                    }),
+                )
+                .await;
+                return;
+            }
+            decision => {
+                // Dangerous tools pause the run until a human answers.
+                if approval.is_none() && self.park_for_approval(&proposal, parked).await {
+                    return;
+                }
+                let mut payload = serde_json::json!({"step": proposal.step_index});
+                if let Some(decision) = decision {
+                    payload["tool"] = serde_json::json!(decision.tool);
+                    payload["rule"] = serde_json::json!(decision.rule);
+                }
+                self.emit_event(run_id, agent_id, EventKind::ActionApproved, payload).await;
+            }
+        }
+
+        // Execute the action
+        let result = match &proposal.action {
+            ProposedAction::ShellCommand {
+                command,
+                args,
+                working_dir,
+            } => match self.exec_host_registry(&session) {
+                Some(registry) => {
+                    self.execute_on_host(registry, &session, run_id, agent_id, proposal.step_index, command, args).await
+                }
+                None => Self::execute_shell(command, args, working_dir, self.max_output_bytes).await,
+            },
+            ProposedAction::HttpRequest {
+                method,
+                url,
+                headers,
+                body,
+            } => Self::execute_http(method, url, headers, body).await,
+            ProposedAction::LlmResponse {
+                content,
+                provider,
+                model,
+                tokens_used,
+            } => {
+                info!(
+                    provider = %provider,
+                    model = %model,
+                    tokens = tokens_used,
+                    "LLM response received (no execution needed)"
+                );
+                Ok(serde_json::json!({
+                    "type": "llm_response",
+                    "content": content,
+                    "provider": provider,
+                    "model": model,
+                    "tokens_used": tokens_used,
+                }))
+            },
+             ProposedAction::ToolCall {
+                name,
+                args,
+            } => match self.agent_scoped_tool(name, &proposal) {
+                Some(tool) => Self::run_tool(tool.as_ref(), name, args.clone()).await,
+                None => Self::execute_tool(registry, name, args.clone()).await,
+            },
+        };
+
+        match result {
+            // Output that breaks the step's contract is never reported as executed.
+            Ok(output) if self.enforce_contract(&proposal, &output).await => {
+                info!(run_id = %run_id, step = proposal.step_index, "Action executed successfully");
+                self.emit_event(
+                    run_id,
+                    agent_id,
+                    EventKind::ActionExecuted,
+                    output.clone(),
+                )
+                .await;
+                self.deliver_media(&proposal, &output).await;
+                // RunCompleted is emitted by the Supervisor once all steps
+                // are finished, not here after each individual action.
+            }
+            // Sent back for repair or failed by `enforce_contract`.
+            Ok(_) => {}
+            Err(e) => {
+                error!(run_id = %run_id, error = %e, "Action execution failed");
+                self.emit_event(
+                    run_id,
+                    agent_id,
+                    EventKind::ActionFailed,
+                    serde_json::json!({"error": e.to_string()}),
+                )
+                .await;
+            }
+        }
+        self.report_sandbox_usage(&session, run_id, agent_id, proposal.step_index).await;
+        self.report_blocked_egress(&session, run_id, agent_id, proposal.step_index).await;
+        self.report_resource_exceeded(&session, run_id, agent_id, proposal.step_index).await;
+    }
+
+    /// Upload the attachments a tool result names to the chat the run came from.
+    async fn deliver_media(&self, proposal: &ActionProposal, output: &serde_json::Value) {
+        let (Some(sink), Some(channel), Some(chat_id)) = (&self.media_sink, &proposal.channel, &proposal.chat_id) else {
+            return;
+        };
+        let Some(text) = output["output"].as_str() else { return };
+        for media in OutboundMedia::from_tool_output(text) {
+            if let Err(e) = sink.deliver(channel, chat_id, &media).await {
+                warn!(run_id = %proposal.run_id, file = %media.file_name, error = %e, "Failed to upload attachment");
+            }
+        }
+    }
+
     /// Send an audit event to the supervisor.
     async fn emit_event(&self, run_id: Uuid, agent_id: Uuid, kind: EventKind, payload: serde_json::Value) {
         let _ = self
@@ -208,116 +1047,50 @@ impl Component for Executor {
         
         // Initialize standard tools
         let mut registry = ToolRegistry::new();
-        registry.register(std::sync::Arc::new(clawforge_tools::ShellTool));
+        registry.register(std::sync::Arc::new(self.shell_tool()));
         registry.register(std::sync::Arc::new(clawforge_tools::FileReadTool));
-        registry.register(std::sync::Arc::new(clawforge_tools::FileWriteTool));
+        registry.register(std::sync::Arc::new(clawforge_tools::FileWriteTool::default()));
+        registry.register(std::sync::Arc::new(clawforge_tools::EditTool::default()));
+        if let Some(workspace) = &self.git_workspace {
+            registry.register(std::sync::Arc::new(clawforge_tools::GitTool::new(workspace.clone())));
+        }
+        if let Some(workspace) = &self.search_workspace {
+            registry.register(std::sync::Arc::new(clawforge_tools::GrepTool::new(workspace.clone())));
+            registry.register(std::sync::Arc::new(clawforge_tools::GlobTool::new(workspace.clone())));
+        }
+        if let Some(guard) = &self.web_fetch {
+            let tool = clawforge_tools::WebFetchTool::new(guard.clone());
+            registry.register(std::sync::Arc::new(match &self.downloads {
+                Some(downloads) => tool.with_downloads(downloads.clone()),
+                None => tool,
+            }));
+        }
+        if let Some(connectors) = &self.connectors {
+            registry.register(std::sync::Arc::new(clawforge_tools::ConnectorTool::new(connectors.clone())));
+        }
+        if let Some(artifacts) = &self.artifacts {
+            registry.register(artifacts.clone());
+        }
+        for tool in &self.extra_tools {
+            registry.register(tool.clone());
+        }
         // Simple HTTP tool wrapper could be added here or we rely on built-in capability for now
 
-        while let Some(msg) = rx.recv().await {
-            match msg {
-                Message::ExecuteAction(proposal) => {
-                    let run_id = proposal.run_id;
-                    let agent_id = proposal.agent_id;
-
-                    info!(
-                        run_id = %proposal.run_id,
-                        step = proposal.step_index,
-                        "Executing action"
-                    );
-
-                    // Capability check against the agent's declared spec — enforced here,
-                    // not assumed. Defaults (Capabilities::default) are all-false (deny).
-                    match Self::check_capability(&proposal.capabilities, &proposal.action) {
-                        Ok(()) => {
-                            self.emit_event(
-                                run_id,
-                                agent_id,
-                                EventKind::ActionApproved,
-                                serde_json::json!({"step": proposal.step_index}),
-                            )
-                            .await;
-                        }
-                        Err(e) => {
-                            warn!(run_id = %run_id, error = %e, "Capability denied");
-                            self.emit_event(
-                                run_id,
-                                agent_id,
-                                EventKind::ActionDenied,
-                                serde_json::json!({"error": e.to_string()}),
-                            )
-                            .await;
-                            continue;
-                        }
-                    }
-
-                    // Execute the action
-                    let result = match &proposal.action {
-                        ProposedAction::ShellCommand {
-                            command,
-                            args,
-                            working_dir,
-                        } => Self::execute_shell(command, args, working_dir).await,
-                        ProposedAction::HttpRequest {
-                            method,
-                            url,
-                            headers,
-                            body,
-                        } => Self::execute_http(method, url, headers, body).await,
-                        ProposedAction::LlmResponse {
-                            content,
-                            provider,
-                            model,
-                            tokens_used,
-                        } => {
-                            info!(
-                                provider = %provider,
-                                model = %model,
-                                tokens = tokens_used,
-                                "LLM response received (no execution needed)"
-                            );
-                            Ok(serde_json::json!({
-                                "type": "llm_response",
-                                "content": content,
-                                "provider": provider,
-                                "model": model,
-                                "tokens_used": tokens_used,
-                            }))
-                        },
-                         ProposedAction::ToolCall {
-                            name,
-                            args,
-                        } => Self::execute_tool(&registry, name, args.clone()).await,
-                    };
-
-                    match result {
-                        Ok(output) => {
-                            info!(run_id = %run_id, step = proposal.step_index, "Action executed successfully");
-                            self.emit_event(
-                                run_id,
-                                agent_id,
-                                EventKind::ActionExecuted,
-                                output,
-                            )
-                            .await;
-                            // RunCompleted is emitted by the Supervisor once all steps
-                            // are finished, not here after each individual action.
-                        }
-                        Err(e) => {
-                            error!(run_id = %run_id, error = %e, "Action execution failed");
-                            self.emit_event(
-                                run_id,
-                                agent_id,
-                                EventKind::ActionFailed,
-                                serde_json::json!({"error": e.to_string()}),
-                            )
-                            .await;
-                        }
+        // Proposals parked on an approval come back here with the verdict.
+        let (parked_tx, mut parked_rx) = mpsc::unbounded_channel();
+        loop {
+            let (proposal, approval) = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Some(Message::ExecuteAction(proposal)) => (proposal, None),
+                    Some(other) => {
+                        debug!(msg_type = ?other, "Executor ignoring non-execute message");
+                        continue;
                     }
-                }
-                other => {
-                    debug!(msg_type = ?other, "Executor ignoring non-execute message");
-                }
-            }
+                    None => break,
+                },
+                Some((proposal, outcome)) = parked_rx.recv() => (proposal, Some(outcome)),
+            };
+            self.handle_proposal(&registry, proposal, approval, &parked_tx).await;
         }
 
         info!("Executor channel closed, shutting down");
@@ -397,4 +1170,177 @@ mod tests {
         };
         assert!(Executor::check_capability(&caps, &action).is_ok());
     }
+
+    #[test]
+    fn test_tool_policy_maps_shell_and_http() {
+        let (tx, _rx) = mpsc::channel(1);
+        let policy = ToolPolicyEngine::default().with_channel(
+            "whatsapp",
+            clawforge_core::ToolRules { deny: vec!["shell".into()], ..Default::default() },
+        );
+        let executor = Executor::new(tx).with_tool_policy(Arc::new(policy));
+        let proposal = |channel: &str| ActionProposal {
+            run_id: Uuid::new_v4(),
+            agent_id: Uuid::new_v4(),
+            step_index: 0,
+            action: ProposedAction::ShellCommand { command: "ls".into(), args: vec![], working_dir: None },
+            capabilities: Capabilities::default(),
+            agent_name: None,
+            channel: Some(channel.into()),
+            output_contract: None,
+            repair_attempt: 0,
+            session_id: None,
+            principal: None,
+            chat_id: None,
+        };
+        let denied = executor.check_tool_policy(&proposal("whatsapp")).unwrap();
+        assert!(!denied.allowed);
+        assert_eq!(denied.rule.scope, "channel:whatsapp");
+        assert!(executor.check_tool_policy(&proposal("slack")).unwrap().allowed);
+        assert_eq!(
+            Executor::policy_tool_name(&ProposedAction::HttpRequest {
+                method: "POST".into(),
+                url: "https://x".into(),
+                headers: HashMap::new(),
+                body: None,
+            }),
+            Some("http_post".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn send_to_agent_acts_as_the_calling_agent() {
+        let (supervisor_tx, _supervisor_rx) = mpsc::channel(1);
+        let (scheduler_tx, mut scheduler_rx) = mpsc::channel(1);
+        let coder = Uuid::new_v4();
+        let policy = AgentMessagePolicy::new(HashMap::from([("planner".to_string(), vec!["coder".to_string()])]));
+        let directory: AgentDirectory = Arc::new(move || HashMap::from([("coder".to_string(), coder)]));
+        let executor = Executor::new(supervisor_tx).with_agent_messages(policy, directory, scheduler_tx);
+        let proposal = |agent_name: &str| ActionProposal {
+            run_id: Uuid::new_v4(),
+            agent_id: Uuid::new_v4(),
+            step_index: 0,
+            action: ProposedAction::ToolCall { name: "send_to_agent".into(), args: serde_json::json!({}) },
+            capabilities: Capabilities::default(),
+            agent_name: Some(agent_name.into()),
+            channel: None,
+            output_contract: None,
+            repair_attempt: 0,
+            session_id: None,
+            principal: None,
+            chat_id: None,
+        };
+        let args = serde_json::json!({ "agent": "coder", "message": "fix the build" });
+
+        let tool = executor.agent_scoped_tool("send_to_agent", &proposal("planner")).unwrap();
+        tool.execute(args.clone()).await.unwrap();
+        let Some(Message::ScheduleJob(job)) = scheduler_rx.recv().await else { panic!("no run queued") };
+        assert_eq!(job.agent_id, coder);
+        assert!(job.trigger_reason.contains("agent 'planner'"));
+
+        let tool = executor.agent_scoped_tool("send_to_agent", &proposal("coder")).unwrap();
+        assert!(tool.execute(args).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn contract_violation_is_not_reported_as_executed() {
+        let (supervisor_tx, mut supervisor_rx) = mpsc::channel(16);
+        let (planner_tx, mut planner_rx) = mpsc::channel(4);
+        let executor = Executor::new(supervisor_tx).with_planner(planner_tx);
+        let (tx, rx) = mpsc::channel(4);
+        tokio::spawn(async move { executor.start(rx).await });
+
+        let contract = clawforge_core::OutputContract::new(serde_json::json!({
+            "type": "object",
+            "required": ["summary"],
+        }))
+        .with_max_repairs(1);
+        let proposal = |step_index, repair_attempt| ActionProposal {
+            run_id: Uuid::nil(),
+            agent_id: Uuid::nil(),
+            step_index,
+            action: ProposedAction::LlmResponse { content: "hi".into(), provider: "test".into(), model: "test".into(), tokens_used: 1 },
+            capabilities: Capabilities::default(),
+            agent_name: None,
+            channel: None,
+            output_contract: Some(contract.clone()),
+            repair_attempt,
+            session_id: None,
+            principal: None,
+            chat_id: None,
+        };
+        // A later step is checked too, and repaired rather than executed.
+        tx.send(Message::ExecuteAction(proposal(2, 0))).await.unwrap();
+        let Some(Message::RepairOutput(repair)) = planner_rx.recv().await else { panic!("no repair requested") };
+        assert_eq!((repair.step_index, repair.attempt), (2, 1));
+        let mut kinds = Vec::new();
+        while let Ok(Message::AuditEvent(AuditEventPayload { event })) = supervisor_rx.try_recv() {
+            kinds.push(event.kind);
+        }
+        assert!(kinds.contains(&EventKind::OutputContractViolated));
+        assert!(!kinds.contains(&EventKind::ActionExecuted));
+
+        // Out of repairs: the run fails, still without ActionExecuted.
+        tx.send(Message::ExecuteAction(proposal(2, 1))).await.unwrap();
+        let mut kinds = Vec::new();
+        while !kinds.contains(&EventKind::RunFailed) {
+            match tokio::time::timeout(std::time::Duration::from_secs(5), supervisor_rx.recv()).await.unwrap() {
+                Some(Message::AuditEvent(AuditEventPayload { event })) => kinds.push(event.kind),
+                _ => panic!("executor stopped"),
+            }
+        }
+        assert!(!kinds.contains(&EventKind::ActionExecuted));
+    }
+
+    #[tokio::test]
+    async fn pending_approval_does_not_block_other_runs() {
+        let (supervisor_tx, mut supervisor_rx) = mpsc::channel(16);
+        let broker = Arc::new(ApprovalBroker::default());
+        let executor = Executor::new(supervisor_tx).with_approvals(broker.clone());
+        let (tx, rx) = mpsc::channel(4);
+        tokio::spawn(async move { executor.start(rx).await });
+
+        let proposal = |action| ActionProposal {
+            run_id: Uuid::new_v4(),
+            agent_id: Uuid::new_v4(),
+            step_index: 0,
+            action,
+            capabilities: Capabilities { can_execute_commands: true, ..Default::default() },
+            agent_name: None,
+            channel: None,
+            output_contract: None,
+            repair_attempt: 0,
+            session_id: None,
+            principal: None,
+            chat_id: None,
+        };
+        let dangerous = proposal(ProposedAction::ShellCommand { command: "rm".into(), args: vec!["-rf".into(), "/tmp/x".into()], working_dir: None });
+        let harmless = proposal(ProposedAction::LlmResponse { content: "hi".into(), provider: "test".into(), model: "test".into(), tokens_used: 1 });
+        let (parked_run, other_run) = (dangerous.run_id, harmless.run_id);
+        tx.send(Message::ExecuteAction(dangerous)).await.unwrap();
+        tx.send(Message::ExecuteAction(harmless)).await.unwrap();
+
+        let mut seen = Vec::new();
+        while !seen.iter().any(|(run, kind)| *run == other_run && *kind == EventKind::ActionExecuted) {
+            match tokio::time::timeout(std::time::Duration::from_secs(5), supervisor_rx.recv()).await.unwrap() {
+                Some(Message::AuditEvent(AuditEventPayload { event })) => seen.push((event.run_id, event.kind)),
+                _ => panic!("executor stopped"),
+            }
+        }
+        assert!(seen.contains(&(parked_run, EventKind::ApprovalRequested)));
+        assert!(!seen.iter().any(|(run, kind)| *run == parked_run && *kind != EventKind::ApprovalRequested));
+
+        let pending = broker.pending().await;
+        assert!(broker.resolve(&pending[0].id, clawforge_security::ApprovalVerdict::Deny).await);
+        loop {
+            match tokio::time::timeout(std::time::Duration::from_secs(5), supervisor_rx.recv()).await.unwrap() {
+                Some(Message::AuditEvent(AuditEventPayload { event })) if event.run_id == parked_run => {
+                    assert_eq!(event.kind, EventKind::ActionDenied);
+                    break;
+                }
+                Some(_) => {}
+                None => panic!("executor stopped"),
+            }
+        }
+    }
 }
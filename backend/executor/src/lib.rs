@@ -1,3 +1,3 @@
 pub mod executor;
 
-pub use executor::Executor;
+pub use executor::{AgentDirectory, Executor};
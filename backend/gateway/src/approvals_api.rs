@@ -0,0 +1,46 @@
+//! Tool Approvals API
+//!
+//! Lets the Control UI list dangerous tool calls waiting on a human and
+//! approve or deny them. Paused runs resume as soon as a verdict lands.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use tracing::info;
+
+use clawforge_security::{ApprovalVerdict, PendingApproval};
+
+use crate::auth::RequireAuth;
+use crate::server::GatewayState;
+
+#[derive(Debug, Deserialize)]
+pub struct VerdictRequest {
+    /// "allow" | "allow-session" | "deny" | "deny-session"
+    pub verdict: String,
+}
+
+/// Endpoint: `GET /api/approvals`
+pub async fn list_approvals(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+) -> Json<Vec<PendingApproval>> {
+    Json(state.approvals.pending().await)
+}
+
+/// Endpoint: `POST /api/approvals/:id`
+pub async fn resolve_approval(
+    RequireAuth(user): RequireAuth,
+    State(state): State<GatewayState>,
+    Path(id): Path<String>,
+    Json(req): Json<VerdictRequest>,
+) -> Result<StatusCode, (StatusCode, &'static str)> {
+    let verdict = ApprovalVerdict::parse(&req.verdict).ok_or((StatusCode::BAD_REQUEST, "Unknown verdict"))?;
+    if !state.approvals.resolve(&id, verdict).await {
+        return Err((StatusCode::NOT_FOUND, "No pending approval with that id"));
+    }
+    info!("Approval {} resolved as {} by {}", id, verdict.as_str(), user.key_id);
+    Ok(StatusCode::NO_CONTENT)
+}
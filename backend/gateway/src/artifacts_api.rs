@@ -0,0 +1,45 @@
+//! Artifacts API
+//!
+//! Serves artifacts published by the `artifact` tool. `/artifacts/:id` is
+//! public — the id is the only secret, as with share links — so agent-written
+//! markup is served under the restrictive CSP its kind calls for.
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use clawforge_tools::Artifact;
+
+use crate::auth::RequireAuth;
+use crate::server::GatewayState;
+
+/// Endpoint: `GET /api/artifacts`
+pub async fn list_artifacts(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+) -> Json<Vec<Artifact>> {
+    Json(state.artifacts.list())
+}
+
+/// Endpoint: `GET /artifacts/:id` — public, no auth.
+pub async fn view_artifact(
+    State(state): State<GatewayState>,
+    Path(id): Path<String>,
+) -> Response {
+    let Some(artifact) = state.artifacts.get(&id) else {
+        return (StatusCode::NOT_FOUND, "Artifact not found").into_response();
+    };
+    (
+        [
+            (header::CONTENT_TYPE, artifact.kind.content_type().to_string()),
+            (header::CONTENT_SECURITY_POLICY, artifact.kind.content_security_policy().to_string()),
+            (header::CONTENT_DISPOSITION, artifact.content_disposition()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        artifact.render(),
+    )
+        .into_response()
+}
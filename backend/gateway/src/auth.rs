@@ -1,39 +1,59 @@
 //! Gateway Authentication Module
 //!
-//! Validates Bearer tokens against the CLAWFORGE_API_KEY environment variable.
+//! Validates Bearer tokens against the CLAWFORGE_API_KEY environment variable,
+//! or against device tokens issued by the pairing flow.
 //! Set CLAWFORGE_API_KEY to a strong random secret before deployment.
-//! If the env var is unset the gateway rejects all authenticated requests.
+//! If the env var is unset only previously paired devices are accepted.
+//!
+//! The API key carries the `admin` role and device tokens the `device` role.
+//! Routes that change who or what may reach the gateway, or expose its
+//! configuration and logs, take `RequireAdmin`; everything else a paired
+//! client needs (chat, sessions, approvals, artifacts, events) takes
+//! `RequireAuth`.
 
 use axum::{
     async_trait,
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts},
     http::request::Parts,
     http::StatusCode,
 };
+use std::sync::Arc;
 use tracing::warn;
 
+use clawforge_security::PairingStore;
+
+/// Role of the operator's API key.
+pub const ADMIN_ROLE: &str = "admin";
+
+/// Role of tokens issued to paired devices.
+pub const DEVICE_ROLE: &str = "device";
+
 pub struct AuthenticatedUser {
     pub key_id: String,
     pub roles: Vec<String>,
 }
 
+impl AuthenticatedUser {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+
 pub struct RequireAuth(pub AuthenticatedUser);
 
+/// Like `RequireAuth`, but only for callers with the admin role.
+pub struct RequireAdmin(pub AuthenticatedUser);
+
 #[async_trait]
 impl<S> FromRequestParts<S> for RequireAuth
 where
     S: Send + Sync,
+    Arc<PairingStore>: FromRef<S>,
 {
     type Rejection = (StatusCode, &'static str);
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let expected = match std::env::var("CLAWFORGE_API_KEY") {
-            Ok(k) if !k.is_empty() => k,
-            _ => {
-                warn!("CLAWFORGE_API_KEY is not set — all authenticated requests will be rejected");
-                return Err((StatusCode::UNAUTHORIZED, "Server not configured for auth"));
-            }
-        };
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let expected = std::env::var("CLAWFORGE_API_KEY").ok().filter(|k| !k.is_empty());
 
         let auth_header = parts
             .headers
@@ -43,15 +63,25 @@ where
         match auth_header {
             Some(header) if header.starts_with("Bearer ") => {
                 let token = &header["Bearer ".len()..];
-                if token == expected {
-                    Ok(RequireAuth(AuthenticatedUser {
+                if expected.as_deref() == Some(token) {
+                    return Ok(RequireAuth(AuthenticatedUser {
                         key_id: "api_key".into(),
-                        roles: vec!["admin".into()],
-                    }))
-                } else {
-                    warn!("Invalid Bearer token presented");
-                    Err((StatusCode::UNAUTHORIZED, "Invalid token"))
+                        roles: vec![ADMIN_ROLE.into()],
+                    }));
+                }
+                // Long-lived tokens issued to paired devices via `/api/pair`.
+                if let Some(device_id) = Arc::<PairingStore>::from_ref(state).validate_token(token) {
+                    return Ok(RequireAuth(AuthenticatedUser {
+                        key_id: format!("device:{}", device_id),
+                        roles: vec![DEVICE_ROLE.into()],
+                    }));
+                }
+                if expected.is_none() {
+                    warn!("CLAWFORGE_API_KEY is not set — all authenticated requests will be rejected");
+                    return Err((StatusCode::UNAUTHORIZED, "Server not configured for auth"));
                 }
+                warn!("Invalid Bearer token presented");
+                Err((StatusCode::UNAUTHORIZED, "Invalid token"))
             }
             _ => {
                 warn!("Missing or invalid Authorization header");
@@ -60,3 +90,49 @@ where
         }
     }
 }
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RequireAdmin
+where
+    S: Send + Sync,
+    Arc<PairingStore>: FromRef<S>,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let RequireAuth(user) = RequireAuth::from_request_parts(parts, state).await?;
+        if !user.has_role(ADMIN_ROLE) {
+            warn!("{} denied an operator route", user.key_id);
+            return Err((StatusCode::FORBIDDEN, "Operator access required"));
+        }
+        Ok(RequireAdmin(user))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    async fn parts_with(token: &str) -> Parts {
+        let request = Request::builder().header("authorization", format!("Bearer {}", token)).body(()).unwrap();
+        request.into_parts().0
+    }
+
+    #[tokio::test]
+    async fn device_tokens_reach_client_routes_but_not_operator_routes() {
+        let store = Arc::new(PairingStore::new(60));
+        let device = store.register_device("phone", None).unwrap();
+
+        let RequireAuth(user) = RequireAuth::from_request_parts(&mut parts_with(&device.token).await, &store).await.ok().unwrap();
+        assert_eq!(user.key_id, "device:phone");
+        assert!(user.has_role(DEVICE_ROLE));
+
+        let denied = RequireAdmin::from_request_parts(&mut parts_with(&device.token).await, &store).await;
+        assert_eq!(denied.err().map(|(status, _)| status), Some(StatusCode::FORBIDDEN));
+
+        store.revoke("phone");
+        let revoked = RequireAuth::from_request_parts(&mut parts_with(&device.token).await, &store).await;
+        assert_eq!(revoked.err().map(|(status, _)| status), Some(StatusCode::UNAUTHORIZED));
+    }
+}
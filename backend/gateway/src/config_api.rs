@@ -0,0 +1,55 @@
+//! Effective Config API
+//!
+//! Exposes the merged runtime config, optionally annotated with the layer
+//! (default, file, profile, env, runtime) each value was resolved from.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tracing::error;
+
+use clawforge_config::{redact, ConfigSource};
+
+use crate::auth::RequireAdmin;
+use crate::server::GatewayState;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EffectiveConfigQuery {
+    #[serde(default)]
+    pub explain: bool,
+}
+
+#[derive(Serialize)]
+pub struct EffectiveConfigResponse {
+    /// Merged config with secrets redacted.
+    pub config: Value,
+    /// Present only when `?explain=true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<BTreeMap<String, ConfigSource>>,
+}
+
+/// Endpoint: `GET /api/config/effective?explain=true`
+pub async fn get_effective_config(
+    _auth: RequireAdmin,
+    State(state): State<GatewayState>,
+    Query(query): Query<EffectiveConfigQuery>,
+) -> Result<Json<EffectiveConfigResponse>, (StatusCode, &'static str)> {
+    let Some(sources) = &state.config_sources else {
+        return Err((StatusCode::NOT_FOUND, "No config sources attached to gateway"));
+    };
+
+    let explained = sources.read().await.explain().await.map_err(|e| {
+        error!("Failed to resolve effective config: {:#}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve effective config")
+    })?;
+
+    Ok(Json(EffectiveConfigResponse {
+        config: redact(&explained.config),
+        provenance: query.explain.then_some(explained.provenance),
+    }))
+}
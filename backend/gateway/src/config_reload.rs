@@ -2,15 +2,26 @@
 //!
 //! Watches a YAML config file for modifications and applies changes to the
 //! shared `GatewayConfig` without restarting the process.
+//!
+//! Model providers listed under `providers:` are applied to the planner's
+//! `ProviderRegistry` on each reload. A rotated key or removed entry does not
+//! cut requests off: they finish on the old client while new requests use the
+//! new one, and the old client is released after the drain grace period.
 
 use anyhow::Result;
+use clawforge_planner::providers::{catalog, ProviderRegistry};
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
 use tracing::{error, info, warn};
 
+/// How long a replaced provider may keep serving in-flight requests.
+const DEFAULT_DRAIN_GRACE: Duration = Duration::from_secs(120);
+
 /// Runtime-adjustable gateway settings loaded from YAML.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GatewayConfig {
@@ -23,6 +34,23 @@ pub struct GatewayConfig {
     /// Maximum requests per rate-limit window.
     #[serde(default = "default_rate_limit")]
     pub rate_limit: u32,
+    /// Catalog provider id → credentials, applied to the provider registry.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub providers: BTreeMap<String, ProviderKey>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProviderKey {
+    /// API key (the server URL for Ollama).
+    pub api_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+impl std::fmt::Debug for ProviderKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProviderKey").field("api_key", &"<redacted>").field("base_url", &self.base_url).finish()
+    }
 }
 
 fn default_max_connections() -> usize { 1000 }
@@ -35,17 +63,50 @@ impl Default for GatewayConfig {
             max_connections: default_max_connections(),
             rate_window_secs: default_rate_window_secs(),
             rate_limit: default_rate_limit(),
+            providers: BTreeMap::new(),
         }
     }
 }
 
+/// Apply `providers` to `registry`, draining replaced and removed clients.
+pub fn apply_providers(registry: &ProviderRegistry, providers: &BTreeMap<String, ProviderKey>) {
+    let specs = providers
+        .iter()
+        .filter_map(|(id, key)| {
+            let spec = catalog::provider_spec(id, &key.api_key, key.base_url.as_deref());
+            if spec.is_none() {
+                warn!(provider = %id, "Unknown provider in config; skipped");
+            }
+            spec
+        })
+        .collect();
+    let summary = registry.reload(specs);
+    if summary != Default::default() {
+        info!(added = ?summary.added, replaced = ?summary.replaced, removed = ?summary.removed, "Model providers reloaded");
+    }
+}
+
 pub struct ConfigReloader {
     config: Arc<RwLock<GatewayConfig>>,
+    providers: Option<Arc<ProviderRegistry>>,
+    drain_grace: Duration,
 }
 
 impl ConfigReloader {
     pub fn new(config: Arc<RwLock<GatewayConfig>>) -> Self {
-        Self { config }
+        Self { config, providers: None, drain_grace: DEFAULT_DRAIN_GRACE }
+    }
+
+    /// Apply `providers:` to `registry` on every reload.
+    pub fn with_providers(mut self, registry: Arc<ProviderRegistry>) -> Self {
+        self.providers = Some(registry);
+        self
+    }
+
+    /// How long replaced providers may finish in-flight requests (default 2 minutes).
+    pub fn with_drain_grace(mut self, grace: Duration) -> Self {
+        self.drain_grace = grace;
+        self
     }
 
     /// Watch the specified YAML configuration file for changes and reload on modify.
@@ -63,6 +124,16 @@ impl ConfigReloader {
 
         let config = Arc::clone(&self.config);
         let path_owned = path.as_ref().to_path_buf();
+        match std::fs::read_to_string(&path_owned).map(|raw| serde_yaml::from_str::<GatewayConfig>(&raw)) {
+            Ok(Ok(initial)) => *config.write().await = initial,
+            Ok(Err(e)) => warn!("Config parse error — starting with defaults: {}", e),
+            Err(e) => warn!("Could not read config file: {}", e),
+        }
+        let providers = self.providers.clone();
+        if let Some(registry) = &providers {
+            apply_providers(registry, &config.read().await.providers);
+            registry.spawn_drain_sweeper(self.drain_grace);
+        }
 
         tokio::spawn(async move {
             // keep watcher alive for the lifetime of the task
@@ -76,6 +147,9 @@ impl ConfigReloader {
                             Ok(contents) => match serde_yaml::from_str::<GatewayConfig>(&contents) {
                                 Err(e) => warn!("Config parse error — keeping old config: {}", e),
                                 Ok(new_cfg) => {
+                                    if let Some(registry) = &providers {
+                                        apply_providers(registry, &new_cfg.providers);
+                                    }
                                     *config.write().await = new_cfg;
                                     info!("Gateway config reloaded successfully");
                                 }
@@ -0,0 +1,48 @@
+//! Events API
+//!
+//! Queries the structured agent event log for the Control UI dashboard:
+//! raw entries filtered by agent, session, kind and time, and the hourly
+//! per-kind counts and error rates computed over the same filters.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::warn;
+
+use logging::{EventLogEntry, EventQuery, EventStats, EventStore};
+
+use crate::auth::RequireAuth;
+use crate::server::GatewayState;
+
+type ApiError = (StatusCode, &'static str);
+
+fn store(state: &GatewayState) -> Result<&Arc<EventStore>, ApiError> {
+    state.events.as_ref().ok_or((StatusCode::NOT_FOUND, "The event log is not available"))
+}
+
+fn query_failed(e: anyhow::Error) -> ApiError {
+    warn!("Event log query failed: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, "Event log query failed")
+}
+
+/// Endpoint: `GET /api/events`
+pub async fn list_events(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+    Query(query): Query<EventQuery>,
+) -> Result<Json<Vec<EventLogEntry>>, ApiError> {
+    store(&state)?.query(&query).map(Json).map_err(query_failed)
+}
+
+/// Endpoint: `GET /api/events/stats`
+pub async fn event_stats(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+    Query(query): Query<EventQuery>,
+) -> Result<Json<EventStats>, ApiError> {
+    store(&state)?.stats(&query).map(Json).map_err(query_failed)
+}
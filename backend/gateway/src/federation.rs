@@ -0,0 +1,771 @@
+//! Gateway Federation
+//!
+//! Lets one gateway (say, at home) proxy selected channels and agents to a
+//! peer gateway (say, on a VPS) over an authenticated WebSocket link at
+//! `/federation`. Either side may dial; the first frame is a `hello` carrying
+//! the shared token both gateways list for each other.
+//!
+//! Every federated session has one owner, tracked as `(gateway, epoch)` on
+//! both sides. A claim with a higher epoch wins, ties go to the smaller
+//! gateway id, and a peer that is asked to run a session it knows belongs to
+//! someone else declines with the current owner. When the peer is offline —
+//! or drops mid-request — the local gateway takes the session over with a
+//! bumped epoch and keeps serving it, so clients never notice; once the link
+//! is back those sessions are handed back to the peer with another bump.
+//!
+//! A forwarded Invoke is answered twice: a `reply` as soon as the peer has
+//! scheduled it, and a `result` with the run's output or error once it ends,
+//! which this gateway hands to the client that asked.
+//!
+//! A peer may only run the agents its `acceptAgents` entry lists here;
+//! forwards for anything else are answered with an error. Only the most
+//! recently used `MAX_TRACKED_SESSIONS` owners are remembered.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::{future, Sink, SinkExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message as PeerMessage;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use clawforge_config::schema::{FederationConfig, FederationPeer};
+
+use crate::auth::RequireAdmin;
+use crate::server::GatewayState;
+use crate::ws_protocol::WsMessage;
+use crate::ws_server::schedule_invoke;
+
+/// How long a peer gets to answer a forwarded Invoke before we serve it locally.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(15);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Session owners remembered; the least recently used are forgotten first.
+const MAX_TRACKED_SESSIONS: usize = 10_000;
+
+/// Frames exchanged on a peer link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PeerFrame {
+    /// Dialer -> acceptor: first frame on a new link.
+    Hello { gateway_id: String, token: String },
+    /// Acceptor -> dialer: the link is authenticated.
+    Welcome { gateway_id: String },
+    /// Assert ownership of a session.
+    Claim { session_id: String, owner: String, epoch: u64 },
+    /// Answer to a claim: who owns the session now.
+    Owner { session_id: String, owner: String, epoch: u64 },
+    /// Run an Invoke on the receiving gateway, which becomes the owner.
+    Forward {
+        request_id: String,
+        session_id: String,
+        agent_id: String,
+        content: String,
+        epoch: u64,
+    },
+    /// The receiving gateway's reply to a forwarded Invoke.
+    Reply { request_id: String, message: WsMessage },
+    /// The outcome of a forwarded Invoke's run, after its reply.
+    Result { request_id: String, message: WsMessage },
+    /// The receiving gateway knows a newer owner and did not run the Invoke.
+    Declined {
+        request_id: String,
+        session_id: String,
+        owner: String,
+        epoch: u64,
+    },
+}
+
+/// Who runs a federated session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ownership {
+    pub owner: String,
+    pub epoch: u64,
+}
+
+/// Where an Invoke should run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Route {
+    Local,
+    Peer(String),
+}
+
+struct Link {
+    id: u64,
+    tx: mpsc::UnboundedSender<PeerFrame>,
+}
+
+struct Tracked {
+    ownership: Ownership,
+    /// The peer this gateway took the session over from, until it is handed back.
+    taken_from: Option<String>,
+    /// Tick of the last proposal; the smallest is evicted first.
+    used: u64,
+}
+
+struct Inner {
+    config: FederationConfig,
+    ownership: Mutex<HashMap<String, Tracked>>,
+    links: Mutex<HashMap<String, Link>>,
+    /// request id -> (peer, waiter)
+    pending: Mutex<HashMap<String, (String, oneshot::Sender<PeerFrame>)>>,
+    /// request id -> (peer, client awaiting the run's result)
+    results: Mutex<HashMap<String, (String, mpsc::UnboundedSender<WsMessage>)>>,
+    next_link: AtomicU64,
+    next_use: AtomicU64,
+}
+
+/// Shared federation state: peer links, session owners and in-flight forwards.
+#[derive(Clone)]
+pub struct Federation {
+    inner: Arc<Inner>,
+}
+
+impl Federation {
+    pub fn new(config: FederationConfig) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                config,
+                ownership: Mutex::new(HashMap::new()),
+                links: Mutex::new(HashMap::new()),
+                pending: Mutex::new(HashMap::new()),
+                results: Mutex::new(HashMap::new()),
+                next_link: AtomicU64::new(1),
+                next_use: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn gateway_id(&self) -> &str {
+        &self.inner.config.gateway_id
+    }
+
+    pub fn is_online(&self, peer: &str) -> bool {
+        self.inner.links.lock().unwrap_or_else(|e| e.into_inner()).contains_key(peer)
+    }
+
+    pub fn owner(&self, session_id: &str) -> Option<Ownership> {
+        self.lock_ownership().get(session_id).map(|tracked| tracked.ownership.clone())
+    }
+
+    fn lock_ownership(&self) -> std::sync::MutexGuard<'_, HashMap<String, Tracked>> {
+        self.inner.ownership.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether `peer` may run `agent_id` on this gateway.
+    pub fn accepts(&self, peer: &str, agent_id: &str) -> bool {
+        self.inner.config.peers.iter().any(|p| p.id == peer && p.accept_agents.iter().any(|a| a == agent_id))
+    }
+
+    /// The peer configured to run this session: by agent id, or by channel
+    /// (the session key's prefix before `:`).
+    pub fn peer_for(&self, session_id: &str, agent_id: &str) -> Option<&FederationPeer> {
+        let channel = session_id.split_once(':').map(|(channel, _)| channel);
+        self.inner.config.peers.iter().find(|peer| {
+            peer.agents.iter().any(|a| a == agent_id) || channel.is_some_and(|c| peer.channels.iter().any(|p| p == c))
+        })
+    }
+
+    /// Record `owner` at `epoch` unless a stronger claim is already known.
+    /// Returns the owner after the proposal.
+    pub fn propose(&self, session_id: &str, owner: &str, epoch: u64) -> Ownership {
+        self.record(session_id, Ownership { owner: owner.to_string(), epoch }, None)
+    }
+
+    fn record(&self, session_id: &str, claim: Ownership, taken_from: Option<String>) -> Ownership {
+        let used = self.inner.next_use.fetch_add(1, Ordering::Relaxed);
+        let mut table = self.lock_ownership();
+        if !table.contains_key(session_id) && table.len() >= MAX_TRACKED_SESSIONS {
+            let oldest = table.iter().min_by_key(|(_, tracked)| tracked.used).map(|(session, _)| session.clone());
+            if let Some(oldest) = oldest {
+                table.remove(&oldest);
+            }
+        }
+        let current = table
+            .entry(session_id.to_string())
+            .or_insert_with(|| Tracked { ownership: claim.clone(), taken_from: taken_from.clone(), used });
+        current.used = used;
+        let known = &current.ownership;
+        if claim.epoch > known.epoch || (claim.epoch == known.epoch && claim.owner < known.owner) {
+            current.ownership = claim;
+            current.taken_from = taken_from;
+        }
+        current.ownership.clone()
+    }
+
+    pub fn route(&self, session_id: &str, agent_id: &str) -> Route {
+        let Some(peer) = self.peer_for(session_id, agent_id) else {
+            return Route::Local;
+        };
+        match self.owner(session_id) {
+            // Taken over while the peer was away; stays here.
+            Some(o) if o.owner == self.gateway_id() => Route::Local,
+            _ if self.is_online(&peer.id) => Route::Peer(peer.id.clone()),
+            _ => {
+                self.take_over(session_id, &peer.id);
+                Route::Local
+            }
+        }
+    }
+
+    /// Make this gateway the session's owner with a bumped epoch.
+    fn take_over(&self, session_id: &str, peer: &str) {
+        let epoch = match self.owner(session_id) {
+            Some(o) if o.owner == self.gateway_id() => return,
+            Some(o) => o.epoch + 1,
+            None => 1,
+        };
+        let claim = Ownership { owner: self.gateway_id().to_string(), epoch };
+        self.record(session_id, claim, Some(peer.to_string()));
+        warn!(session_id = %session_id, peer = %peer, epoch, "Peer unavailable — serving federated session locally");
+    }
+
+    /// Run an Invoke wherever its session lives, falling back to this
+    /// gateway when the peer cannot take it. The run's result follows on
+    /// `results` either way.
+    pub async fn invoke(
+        &self,
+        state: &GatewayState,
+        session_id: String,
+        agent_id: String,
+        content: String,
+        results: mpsc::UnboundedSender<WsMessage>,
+    ) -> WsMessage {
+        if let Route::Peer(peer) = self.route(&session_id, &agent_id) {
+            match self.forward(&peer, &session_id, &agent_id, &content, results.clone()).await {
+                Ok(reply) => return reply,
+                Err(e) => {
+                    warn!(peer = %peer, session_id = %session_id, error = %e, "Forward failed");
+                    self.take_over(&session_id, &peer);
+                }
+            }
+        }
+        schedule_invoke(state, session_id, agent_id, content, results).await
+    }
+
+    async fn forward(
+        &self,
+        peer: &str,
+        session_id: &str,
+        agent_id: &str,
+        content: &str,
+        results: mpsc::UnboundedSender<WsMessage>,
+    ) -> Result<WsMessage> {
+        let epoch = match self.owner(session_id) {
+            Some(o) if o.owner == peer => o.epoch,
+            Some(o) => o.epoch + 1,
+            None => 1,
+        };
+        self.propose(session_id, peer, epoch);
+
+        let request_id = Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        self.lock_pending().insert(request_id.clone(), (peer.to_string(), tx));
+        self.lock_results().insert(request_id.clone(), (peer.to_string(), results));
+        let frame = PeerFrame::Forward {
+            request_id: request_id.clone(),
+            session_id: session_id.to_string(),
+            agent_id: agent_id.to_string(),
+            content: content.to_string(),
+            epoch,
+        };
+        if !self.send(peer, frame) {
+            self.lock_pending().remove(&request_id);
+            self.lock_results().remove(&request_id);
+            bail!("link to {} is down", peer);
+        }
+        debug!(peer = %peer, session_id = %session_id, "Invoke forwarded");
+
+        let answer = tokio::time::timeout(FORWARD_TIMEOUT, rx).await;
+        self.lock_pending().remove(&request_id);
+        // Only a scheduled run has a result still to come.
+        if !matches!(answer, Ok(Ok(PeerFrame::Reply { message: WsMessage::StateChange { .. }, .. }))) {
+            self.lock_results().remove(&request_id);
+        }
+        match answer {
+            Ok(Ok(PeerFrame::Reply { message, .. })) => Ok(message),
+            Ok(Ok(PeerFrame::Declined { owner, epoch, .. })) => {
+                self.propose(session_id, &owner, epoch);
+                bail!("{} declined: session is owned by {} (epoch {})", peer, owner, epoch)
+            }
+            Ok(Ok(other)) => bail!("unexpected answer from {}: {:?}", peer, other),
+            Ok(Err(_)) => bail!("link to {} dropped", peer),
+            Err(_) => bail!("{} did not answer within {:?}", peer, FORWARD_TIMEOUT),
+        }
+    }
+
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, HashMap<String, (String, oneshot::Sender<PeerFrame>)>> {
+        self.inner.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_results(&self) -> std::sync::MutexGuard<'_, HashMap<String, (String, mpsc::UnboundedSender<WsMessage>)>> {
+        self.inner.results.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn send(&self, peer: &str, frame: PeerFrame) -> bool {
+        let links = self.inner.links.lock().unwrap_or_else(|e| e.into_inner());
+        links.get(peer).is_some_and(|link| link.tx.send(frame).is_ok())
+    }
+
+    /// Register a live link to `peer`, replacing any older one. Sessions
+    /// taken over from `peer` are handed back; the rest this gateway owns
+    /// are announced.
+    fn attach(&self, peer: &str) -> (u64, mpsc::UnboundedReceiver<PeerFrame>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let id = self.inner.next_link.fetch_add(1, Ordering::Relaxed);
+        let mut claims = Vec::new();
+        for (session_id, tracked) in self.lock_ownership().iter_mut() {
+            if tracked.ownership.owner != self.gateway_id() {
+                continue;
+            }
+            if tracked.taken_from.as_deref() == Some(peer) {
+                tracked.ownership = Ownership { owner: peer.to_string(), epoch: tracked.ownership.epoch + 1 };
+                tracked.taken_from = None;
+                debug!(session_id = %session_id, peer = %peer, "Handing federated session back");
+            }
+            let Ownership { owner, epoch } = tracked.ownership.clone();
+            claims.push(PeerFrame::Claim { session_id: session_id.clone(), owner, epoch });
+        }
+        for claim in claims {
+            let _ = tx.send(claim);
+        }
+        self.inner.links.lock().unwrap_or_else(|e| e.into_inner()).insert(peer.to_string(), Link { id, tx });
+        info!(peer = %peer, "Federation link up");
+        (id, rx)
+    }
+
+    /// Drop the link (unless a newer one replaced it) and fail its in-flight
+    /// forwards, telling clients still waiting on a result that it is lost.
+    fn detach(&self, peer: &str, link_id: u64) {
+        let mut links = self.inner.links.lock().unwrap_or_else(|e| e.into_inner());
+        if links.get(peer).is_some_and(|link| link.id == link_id) {
+            links.remove(peer);
+            drop(links);
+            self.lock_pending().retain(|_, (p, _)| p != peer);
+            self.lock_results().retain(|_, (p, client)| {
+                if p != peer {
+                    return true;
+                }
+                let _ = client.send(WsMessage::Error {
+                    session_id: None,
+                    error_code: "peer_unavailable".to_string(),
+                    message: format!("Link to gateway {} dropped before the run finished", peer),
+                });
+                false
+            });
+            warn!(peer = %peer, "Federation link down");
+        }
+    }
+
+    async fn handle_frame(&self, state: &GatewayState, peer: &str, frame: PeerFrame) {
+        match frame {
+            PeerFrame::Claim { session_id, owner, epoch } => {
+                let current = self.propose(&session_id, &owner, epoch);
+                self.send(peer, PeerFrame::Owner { session_id, owner: current.owner, epoch: current.epoch });
+            }
+            PeerFrame::Owner { session_id, owner, epoch } => {
+                self.propose(&session_id, &owner, epoch);
+            }
+            PeerFrame::Forward { request_id, session_id, agent_id, .. } if !self.accepts(peer, &agent_id) => {
+                warn!(peer = %peer, agent_id = %agent_id, "Refused forward for an agent the peer may not run");
+                let message = WsMessage::Error {
+                    session_id: Some(session_id),
+                    error_code: "agent_not_federated".to_string(),
+                    message: format!("Gateway {} does not run agent '{}' for {}", self.gateway_id(), agent_id, peer),
+                };
+                self.send(peer, PeerFrame::Reply { request_id, message });
+            }
+            PeerFrame::Forward { request_id, session_id, agent_id, content, epoch } => {
+                let current = self.propose(&session_id, self.gateway_id(), epoch);
+                if current.owner != self.gateway_id() {
+                    self.send(peer, PeerFrame::Declined { request_id, session_id, owner: current.owner, epoch: current.epoch });
+                    return;
+                }
+                let (results_tx, mut results) = mpsc::unbounded_channel();
+                let message = schedule_invoke(state, session_id, agent_id, content, results_tx).await;
+                self.send(peer, PeerFrame::Reply { request_id: request_id.clone(), message });
+                let (federation, peer) = (self.clone(), peer.to_string());
+                tokio::spawn(async move {
+                    if let Some(message) = results.recv().await {
+                        federation.send(&peer, PeerFrame::Result { request_id, message });
+                    }
+                });
+            }
+            PeerFrame::Reply { ref request_id, .. } | PeerFrame::Declined { ref request_id, .. } => {
+                let waiter = self.lock_pending().remove(request_id);
+                match waiter {
+                    Some((_, tx)) => {
+                        let _ = tx.send(frame);
+                    }
+                    None => debug!(peer = %peer, "Late answer to a forward that already gave up"),
+                }
+            }
+            PeerFrame::Result { request_id, message } => {
+                let client = self.lock_results().remove(&request_id).filter(|(p, _)| p == peer);
+                match client {
+                    Some((_, client)) => {
+                        let _ = client.send(message);
+                    }
+                    None => debug!(peer = %peer, "Result for a forward nobody is waiting on"),
+                }
+            }
+            PeerFrame::Hello { .. } | PeerFrame::Welcome { .. } => {
+                warn!(peer = %peer, "Unexpected handshake frame on an open link");
+            }
+        }
+    }
+
+    fn authenticate(&self, gateway_id: &str, token: &str) -> Option<&FederationPeer> {
+        self.inner
+            .config
+            .peers
+            .iter()
+            .find(|peer| peer.id == gateway_id && bool::from(peer.token.as_bytes().ct_eq(token.as_bytes())))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Links
+// ---------------------------------------------------------------------------
+
+/// Pump frames between a link's socket and the federation until either side closes.
+async fn run_link<Tx, Rx>(state: GatewayState, federation: Federation, peer: String, mut tx: Tx, mut rx: Rx)
+where
+    Tx: Sink<String> + Unpin,
+    Rx: Stream<Item = String> + Unpin,
+{
+    let (link_id, mut outbound) = federation.attach(&peer);
+    loop {
+        tokio::select! {
+            frame = outbound.recv() => {
+                let Some(frame) = frame else { break };
+                let text = match serde_json::to_string(&frame) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        warn!(error = %e, "Failed to serialize peer frame");
+                        continue;
+                    }
+                };
+                if tx.send(text).await.is_err() {
+                    break;
+                }
+            }
+            text = rx.next() => {
+                let Some(text) = text else { break };
+                match serde_json::from_str::<PeerFrame>(&text) {
+                    Ok(frame) => federation.handle_frame(&state, &peer, frame).await,
+                    Err(e) => warn!(peer = %peer, error = %e, "Invalid peer frame"),
+                }
+            }
+        }
+    }
+    federation.detach(&peer, link_id);
+}
+
+/// Endpoint: `GET /federation` (WebSocket) — peers dial in here.
+pub async fn federation_handler(ws: WebSocketUpgrade, State(state): State<GatewayState>) -> Response {
+    if state.federation.is_none() {
+        return (StatusCode::NOT_FOUND, "Federation is not enabled").into_response();
+    }
+    ws.on_upgrade(move |socket| accept_peer(socket, state)).into_response()
+}
+
+async fn accept_peer(socket: WebSocket, state: GatewayState) {
+    let Some(federation) = state.federation.clone() else { return };
+    let (mut sink, stream) = socket.split();
+    let mut texts = stream
+        .take_while(|msg| future::ready(matches!(msg, Ok(m) if !matches!(m, Message::Close(_)))))
+        .filter_map(|msg| future::ready(match msg {
+            Ok(Message::Text(text)) => Some(text.to_string()),
+            _ => None,
+        }));
+
+    let peer = match tokio::time::timeout(HANDSHAKE_TIMEOUT, texts.next()).await {
+        Ok(Some(text)) => match serde_json::from_str::<PeerFrame>(&text) {
+            Ok(PeerFrame::Hello { gateway_id, token }) => federation.authenticate(&gateway_id, &token).map(|p| p.id.clone()),
+            _ => None,
+        },
+        _ => None,
+    };
+    let Some(peer) = peer else {
+        warn!("Rejected federation link: bad or missing hello");
+        let _ = sink.send(Message::Close(None)).await;
+        return;
+    };
+    let welcome = PeerFrame::Welcome { gateway_id: federation.gateway_id().to_string() };
+    let Ok(text) = serde_json::to_string(&welcome) else { return };
+    if sink.send(Message::Text(text)).await.is_err() {
+        return;
+    }
+    let tx = sink.with(|text: String| future::ready(Ok::<_, axum::Error>(Message::Text(text))));
+    run_link(state, federation, peer, Box::pin(tx), Box::pin(texts)).await;
+}
+
+/// Keep a link open to every peer that has a `url`, redialing with backoff.
+pub fn connect_peers(state: &GatewayState) {
+    let Some(federation) = state.federation.clone() else { return };
+    for peer in federation.inner.config.peers.iter().filter(|p| p.url.is_some()).cloned() {
+        let state = state.clone();
+        let federation = federation.clone();
+        tokio::spawn(async move {
+            let mut backoff = MIN_BACKOFF;
+            loop {
+                match dial(&state, &federation, &peer).await {
+                    Ok(()) => backoff = MIN_BACKOFF,
+                    Err(e) => warn!(peer = %peer.id, error = %e, "Federation dial failed"),
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        });
+    }
+}
+
+async fn dial(state: &GatewayState, federation: &Federation, peer: &FederationPeer) -> Result<()> {
+    let url = peer.url.as_deref().ok_or_else(|| anyhow!("peer {} has no url", peer.id))?;
+    let (socket, _) = tokio_tungstenite::connect_async(url).await?;
+    let (mut sink, stream) = socket.split();
+    let mut texts = stream
+        .take_while(|msg| future::ready(matches!(msg, Ok(m) if !m.is_close())))
+        .filter_map(|msg| future::ready(match msg {
+            Ok(PeerMessage::Text(text)) => Some(text),
+            _ => None,
+        }));
+
+    let hello = PeerFrame::Hello { gateway_id: federation.gateway_id().to_string(), token: peer.token.clone() };
+    sink.send(PeerMessage::Text(serde_json::to_string(&hello)?)).await?;
+    let first = tokio::time::timeout(HANDSHAKE_TIMEOUT, texts.next())
+        .await
+        .map_err(|_| anyhow!("no welcome within {:?}", HANDSHAKE_TIMEOUT))?
+        .ok_or_else(|| anyhow!("peer closed the link during the handshake"))?;
+    match serde_json::from_str::<PeerFrame>(&first)? {
+        PeerFrame::Welcome { gateway_id } if gateway_id == peer.id => {}
+        PeerFrame::Welcome { gateway_id } => bail!("expected gateway {}, reached {}", peer.id, gateway_id),
+        other => bail!("expected welcome, got {:?}", other),
+    }
+
+    let tx = sink.with(|text: String| future::ready(Ok::<_, tokio_tungstenite::tungstenite::Error>(PeerMessage::Text(text))));
+    run_link(state.clone(), federation.clone(), peer.id.clone(), Box::pin(tx), Box::pin(texts)).await;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Status API
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerStatus {
+    pub id: String,
+    pub online: bool,
+    pub dials: bool,
+    pub channels: Vec<String>,
+    pub agents: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionOwner {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub ownership: Ownership,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationStatus {
+    pub gateway_id: String,
+    pub peers: Vec<PeerStatus>,
+    pub sessions: Vec<SessionOwner>,
+}
+
+/// Endpoint: `GET /api/federation`
+pub async fn get_federation_status(
+    _auth: RequireAdmin,
+    State(state): State<GatewayState>,
+) -> Result<Json<FederationStatus>, (StatusCode, &'static str)> {
+    let federation = state.federation.as_ref().ok_or((StatusCode::NOT_FOUND, "Federation is not enabled"))?;
+    let peers = federation
+        .inner
+        .config
+        .peers
+        .iter()
+        .map(|peer| PeerStatus {
+            id: peer.id.clone(),
+            online: federation.is_online(&peer.id),
+            dials: peer.url.is_some(),
+            channels: peer.channels.clone(),
+            agents: peer.agents.clone(),
+        })
+        .collect();
+    let mut sessions: Vec<SessionOwner> = federation
+        .lock_ownership()
+        .iter()
+        .map(|(session_id, tracked)| SessionOwner { session_id: session_id.clone(), ownership: tracked.ownership.clone() })
+        .collect();
+    sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+    Ok(Json(FederationStatus { gateway_id: federation.gateway_id().to_string(), peers, sessions }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawforge_companion::NodeStore;
+    use clawforge_security::ApprovalBroker;
+    use clawforge_tools::ArtifactStore;
+
+    const AGENT: &str = "7f1d2c3b-0000-4000-8000-000000000001";
+
+    fn federation() -> Federation {
+        Federation::new(FederationConfig {
+            gateway_id: "home".into(),
+            peers: vec![FederationPeer {
+                id: "vps".into(),
+                token: "secret".into(),
+                channels: vec!["telegram".into()],
+                accept_agents: vec![AGENT.into()],
+                ..Default::default()
+            }],
+        })
+    }
+
+    fn state() -> GatewayState {
+        GatewayState::new(
+            Arc::new(ArtifactStore::new()),
+            Arc::new(ApprovalBroker::default()),
+            Arc::new(NodeStore::in_memory()),
+            infra::AdapterStatusRegistry::new(),
+        )
+    }
+
+    fn forward(request_id: &str, agent_id: &str) -> PeerFrame {
+        PeerFrame::Forward {
+            request_id: request_id.into(),
+            session_id: format!("webchat:{}", request_id),
+            agent_id: agent_id.into(),
+            content: "hi".into(),
+            epoch: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn peers_only_run_the_agents_they_are_allowed() {
+        let federation = federation();
+        let (_, mut link) = federation.attach("vps");
+
+        federation.handle_frame(&state(), "vps", forward("r1", "some-other-agent")).await;
+        match link.recv().await.unwrap() {
+            PeerFrame::Reply { request_id, message: WsMessage::Error { error_code, .. } } => {
+                assert_eq!((request_id.as_str(), error_code.as_str()), ("r1", "agent_not_federated"));
+            }
+            other => panic!("unexpected frame {:?}", other),
+        }
+        assert_eq!(federation.owner("webchat:r1"), None);
+
+        federation.handle_frame(&state(), "vps", forward("r2", AGENT)).await;
+        assert!(matches!(link.recv().await.unwrap(), PeerFrame::Reply { request_id, .. } if request_id == "r2"));
+        assert_eq!(federation.owner("webchat:r2").unwrap().owner, "home");
+        assert!(!federation.accepts("elsewhere", AGENT));
+    }
+
+    #[test]
+    fn taken_over_sessions_fail_back_when_the_peer_returns() {
+        let federation = federation();
+        assert_eq!(federation.route("telegram:42", AGENT), Route::Local);
+        assert_eq!(federation.owner("telegram:42"), Some(Ownership { owner: "home".into(), epoch: 1 }));
+        // Sessions this gateway owns in its own right stay here.
+        federation.propose("webchat:1", "home", 3);
+
+        let (_, mut link) = federation.attach("vps");
+        let mut claims = Vec::new();
+        while let Ok(PeerFrame::Claim { session_id, owner, epoch }) = link.try_recv() {
+            claims.push((session_id, owner, epoch));
+        }
+        claims.sort();
+        assert_eq!(claims, [("telegram:42".into(), "vps".into(), 2), ("webchat:1".into(), "home".into(), 3)]);
+        assert_eq!(federation.route("telegram:42", AGENT), Route::Peer("vps".into()));
+    }
+
+    #[test]
+    fn ownership_table_forgets_least_recently_used_sessions() {
+        let federation = federation();
+        for n in 0..MAX_TRACKED_SESSIONS {
+            federation.propose(&format!("s{}", n), "vps", 1);
+        }
+        federation.propose("s0", "vps", 1);
+        federation.propose("one-more", "vps", 1);
+        assert_eq!(federation.lock_ownership().len(), MAX_TRACKED_SESSIONS);
+        assert!(federation.owner("s0").is_some());
+        assert!(federation.owner("s1").is_none());
+        assert!(federation.owner("one-more").is_some());
+    }
+
+    #[tokio::test]
+    async fn forwarded_invokes_relay_their_run_result_to_the_origin() {
+        use clawforge_core::{Event, EventKind, Message as CoreMessage};
+        use futures::channel::mpsc as pipe;
+        use tokio::sync::broadcast;
+
+        let peer = |id: &str, channels: Vec<String>, accept_agents: Vec<String>| FederationPeer {
+            id: id.into(),
+            token: "secret".into(),
+            channels,
+            accept_agents,
+            ..Default::default()
+        };
+        let home = state().with_federation(FederationConfig {
+            gateway_id: "home".into(),
+            peers: vec![peer("vps", vec!["telegram".into()], vec![])],
+        });
+        let (scheduler_tx, mut scheduled) = mpsc::channel(4);
+        let (events, _) = broadcast::channel(16);
+        let vps = state().with_scheduler(scheduler_tx).with_run_events(events.clone()).with_federation(FederationConfig {
+            gateway_id: "vps".into(),
+            peers: vec![peer("home", vec![], vec![AGENT.into()])],
+        });
+
+        // The VPS runtime answers every scheduled job.
+        tokio::spawn(async move {
+            while let Some(CoreMessage::ScheduleJob(job)) = scheduled.recv().await {
+                let output = serde_json::json!({ "content": "Sunny." });
+                let _ = events.send(Event::new(job.run_id, Uuid::nil(), EventKind::ActionExecuted, output));
+            }
+        });
+
+        let (home_fed, vps_fed) = (home.federation.clone().unwrap(), vps.federation.clone().unwrap());
+        let (to_vps, from_home) = pipe::unbounded::<String>();
+        let (to_home, from_vps) = pipe::unbounded::<String>();
+        tokio::spawn(run_link(home.clone(), home_fed.clone(), "vps".into(), to_vps, from_vps));
+        tokio::spawn(run_link(vps, vps_fed, "home".into(), to_home, from_home));
+        while !home_fed.is_online("vps") {
+            tokio::task::yield_now().await;
+        }
+
+        let (client, mut results) = mpsc::unbounded_channel();
+        let reply = home_fed.invoke(&home, "telegram:42".into(), AGENT.into(), "weather?".into(), client).await;
+        assert!(matches!(reply, WsMessage::StateChange { ref state, .. } if state.starts_with("scheduled:")));
+        match tokio::time::timeout(Duration::from_secs(5), results.recv()).await.unwrap().unwrap() {
+            WsMessage::Result { session_id, content } => assert_eq!((session_id.as_str(), content.as_str()), ("telegram:42", "Sunny.")),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(home_fed.owner("telegram:42").unwrap().owner, "vps");
+        assert!(home_fed.lock_results().is_empty());
+    }
+}
@@ -5,6 +5,7 @@
 use axum::{extract::State, Json};
 use serde::Serialize;
 use chrono::{DateTime, Utc};
+use infra::AdapterStatus;
 
 use crate::server::GatewayState;
 use crate::health_monitor::ChannelHealth;
@@ -14,17 +15,20 @@ pub struct GlobalHealthReport {
     pub status: String,
     pub uptime_seconds: u64,
     pub channels: Vec<ChannelHealth>,
+    /// Lifecycle state of each channel adapter.
+    pub adapters: Vec<AdapterStatus>,
     pub timestamp: DateTime<Utc>,
 }
 
 /// Handler for `GET /api/health`
 pub async fn get_health(State(state): State<GatewayState>) -> Json<GlobalHealthReport> {
     let channels = state.health_monitor.get_report().await;
-    let degraded = channels.iter().any(|c| c.status != "healthy");
+    let degraded = channels.iter().any(|c| c.status != "healthy") || state.adapters.is_degraded();
     Json(GlobalHealthReport {
         status: if degraded { "degraded" } else { "ok" }.into(),
         uptime_seconds: state.started_at.elapsed().as_secs(),
         channels,
+        adapters: state.adapters.snapshot(),
         timestamp: Utc::now(),
     })
 }
@@ -0,0 +1,25 @@
+//! Hooks API
+//!
+//! Serves hook traces so users can see why a hook did or didn't fire for a
+//! run. Tracing is switched on with `/hooks trace on`.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use clawforge_hooks::HookEvaluation;
+
+use crate::auth::RequireAuth;
+use crate::server::GatewayState;
+
+/// Endpoint: `GET /api/hooks/trace/:run_id`
+pub async fn get_hook_trace(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+    Path(run_id): Path<String>,
+) -> Result<Json<Vec<HookEvaluation>>, (StatusCode, &'static str)> {
+    let tracer = state.hook_tracer.as_ref().ok_or((StatusCode::NOT_FOUND, "Hook tracing is not available"))?;
+    tracer.trace(&run_id).map(Json).ok_or((StatusCode::NOT_FOUND, "No hook trace for that run"))
+}
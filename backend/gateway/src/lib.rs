@@ -2,20 +2,34 @@
 //!
 //! Provides the REST API, OpenAI compatibility layer, and Control UI static hosting.
 
+pub mod approvals_api;
+pub mod artifacts_api;
 pub mod attachments;
 pub mod auth;
 pub mod auth_health;
+pub mod config_api;
 pub mod config_reload;
 pub mod control_ui;
+pub mod events_api;
+pub mod federation;
 pub mod health_api;
 pub mod health_monitor;
+pub mod hooks_api;
+pub mod logs_api;
+pub mod nodes_api;
 pub mod openai_compat;
+pub mod pairing_api;
 pub mod rate_limit;
 pub mod responses_api;
+pub mod security_api;
 pub mod server;
 pub mod session_registry;
+pub mod sessions_api;
+pub mod share_links;
+pub mod voice_api;
 pub mod ws_protocol;
 pub mod ws_server;
 
-pub use config_reload::{ConfigReloader, GatewayConfig};
+pub use config_reload::{apply_providers, ConfigReloader, GatewayConfig, ProviderKey};
+pub use federation::Federation;
 pub use server::{start_server, GatewayState};
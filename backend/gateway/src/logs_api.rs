@@ -0,0 +1,41 @@
+//! Logs API
+//!
+//! Streams the gateway log to the Control UI as Server-Sent Events: the
+//! most recent lines first, then new lines as they are written, surviving
+//! rotation.
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::stream::{self, Stream};
+use serde::Deserialize;
+
+use crate::auth::RequireAdmin;
+use crate::server::GatewayState;
+
+const DEFAULT_BACKLOG: usize = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct LogStreamQuery {
+    /// Recent lines to send before following.
+    pub backlog: Option<usize>,
+}
+
+/// Endpoint: `GET /api/logs/stream`
+pub async fn stream_logs(
+    _auth: RequireAdmin,
+    State(state): State<GatewayState>,
+    Query(query): Query<LogStreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, &'static str)> {
+    let logs = state.logs.as_ref().ok_or((StatusCode::NOT_FOUND, "Log streaming is not available"))?;
+    let lines = logs.tail(query.backlog.unwrap_or(DEFAULT_BACKLOG));
+    let events = stream::unfold(lines, |mut lines| async move {
+        let line = lines.recv().await?;
+        Some((Ok(Event::default().data(line)), lines))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
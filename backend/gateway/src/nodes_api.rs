@@ -0,0 +1,130 @@
+//! Node Hosts API
+//!
+//! Lists approved node hosts and the ones found on the LAN by mDNS discovery,
+//! and lets the Control UI approve, reject or forget them, and grant or revoke
+//! their desktop permissions (clipboard, screen capture).
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use clawforge_companion::{DesktopGrants, DesktopPermission, DiscoveredNode, NodeRegistration};
+
+use crate::auth::{RequireAdmin, RequireAuth};
+use crate::server::GatewayState;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodesResponse {
+    pub approved: Vec<NodeRegistration>,
+    /// Discovered nodes waiting for approval.
+    pub pending: Vec<DiscoveredNode>,
+}
+
+/// Endpoint: `GET /api/nodes`
+pub async fn list_nodes(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+) -> Json<NodesResponse> {
+    Json(NodesResponse {
+        approved: state.nodes.approved().await,
+        pending: state.nodes.pending().await,
+    })
+}
+
+/// Endpoint: `POST /api/nodes/:id/approve`
+pub async fn approve_node(
+    RequireAdmin(user): RequireAdmin,
+    State(state): State<GatewayState>,
+    Path(id): Path<String>,
+) -> Result<Json<NodeRegistration>, (StatusCode, &'static str)> {
+    if !state.nodes.pending().await.iter().any(|n| n.node_id == id) {
+        return Err((StatusCode::NOT_FOUND, "No pending node with that id"));
+    }
+    let registration = state.nodes.approve(&id).await.map_err(|e| {
+        warn!("Failed to approve node {}: {e:#}", id);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to save node")
+    })?;
+    info!("Node {} approved by {}", id, user.key_id);
+    Ok(Json(registration))
+}
+
+/// Endpoint: `POST /api/nodes/:id/reject`
+pub async fn reject_node(
+    RequireAdmin(user): RequireAdmin,
+    State(state): State<GatewayState>,
+    Path(id): Path<String>,
+) -> StatusCode {
+    match state.nodes.reject(&id).await {
+        Ok(true) => {
+            info!("Node {} rejected by {}", id, user.key_id);
+            StatusCode::NO_CONTENT
+        }
+        Ok(false) => StatusCode::NOT_FOUND,
+        Err(e) => {
+            warn!("Failed to reject node {}: {e:#}", id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Endpoint: `DELETE /api/nodes/:id`
+pub async fn forget_node(
+    _auth: RequireAdmin,
+    State(state): State<GatewayState>,
+    Path(id): Path<String>,
+) -> StatusCode {
+    match state.nodes.remove(&id).await {
+        Ok(true) => StatusCode::NO_CONTENT,
+        Ok(false) => StatusCode::NOT_FOUND,
+        Err(e) => {
+            warn!("Failed to forget node {}: {e:#}", id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePermissionsRequest {
+    #[serde(default)]
+    pub grant: Vec<DesktopPermission>,
+    #[serde(default)]
+    pub revoke: Vec<DesktopPermission>,
+}
+
+/// Endpoint: `GET /api/nodes/:id/permissions`
+pub async fn get_permissions(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+    Path(id): Path<String>,
+) -> Json<DesktopGrants> {
+    Json(state.nodes.desktop_grants(&id).await)
+}
+
+/// Endpoint: `PUT /api/nodes/:id/permissions`
+pub async fn update_permissions(
+    RequireAdmin(user): RequireAdmin,
+    State(state): State<GatewayState>,
+    Path(id): Path<String>,
+    Json(req): Json<UpdatePermissionsRequest>,
+) -> Result<Json<DesktopGrants>, (StatusCode, &'static str)> {
+    if !state.nodes.approved().await.iter().any(|n| n.node_id == id) {
+        return Err((StatusCode::NOT_FOUND, "No approved node with that id"));
+    }
+    let failed = |e: anyhow::Error| {
+        warn!("Failed to update permissions of node {}: {e:#}", id);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to save node permissions")
+    };
+    for permission in req.revoke {
+        state.nodes.revoke(&id, permission).await.map_err(failed)?;
+    }
+    for permission in req.grant {
+        state.nodes.grant(&id, permission).await.map_err(failed)?;
+    }
+    info!("Desktop permissions of node {} changed by {}", id, user.key_id);
+    Ok(Json(state.nodes.desktop_grants(&id).await))
+}
@@ -0,0 +1,212 @@
+//! Device Pairing API
+//!
+//! An authenticated client asks for a pairing offer (setup code plus QR deep
+//! link), the new device exchanges the code for a long-lived device token, and
+//! paired devices can be listed and revoked.
+
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Path, State},
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use infra::PairingOffer;
+
+use crate::auth::RequireAdmin;
+use crate::server::GatewayState;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct OfferRequest {
+    /// Origin the device should reach the gateway at; defaults to the request's `Host`.
+    #[serde(default)]
+    pub gateway_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PairRequest {
+    pub code: String,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PairResponse {
+    pub device_id: String,
+    /// Bearer token for all further requests. Shown only once.
+    pub token: String,
+}
+
+/// A paired device as listed to operators; the token is never returned.
+#[derive(Serialize)]
+pub struct DeviceSummary {
+    pub device_id: String,
+    pub label: Option<String>,
+    pub paired_at: u64,
+}
+
+/// Endpoint: `POST /api/pair/offer`
+pub async fn create_offer(
+    _auth: RequireAdmin,
+    State(state): State<GatewayState>,
+    headers: HeaderMap,
+    body: Option<Json<OfferRequest>>,
+) -> Result<Json<PairingOffer>, (StatusCode, &'static str)> {
+    let requested = body.and_then(|Json(req)| req.gateway_url);
+    let gateway_url = match requested {
+        Some(url) => url,
+        None => {
+            let host = headers
+                .get(header::HOST)
+                .and_then(|h| h.to_str().ok())
+                .ok_or((StatusCode::BAD_REQUEST, "Missing Host header; pass gateway_url"))?;
+            format!("http://{}", host)
+        }
+    };
+
+    let setup = state.setup_codes.create().await;
+    Ok(Json(PairingOffer::new(&gateway_url, &setup.code, setup.expires_at)))
+}
+
+/// Endpoint: `POST /api/pair` — no auth; the setup code is the credential.
+/// Wrong codes are counted per client address, and the device ID is
+/// always assigned here.
+pub async fn pair_device(
+    State(state): State<GatewayState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Json(req): Json<PairRequest>,
+) -> Result<Json<PairResponse>, (StatusCode, &'static str)> {
+    if let Err(e) = state.setup_codes.consume(req.code.trim(), &peer.ip().to_string()).await {
+        warn!("Pairing attempt from {} rejected: {:#}", peer.ip(), e);
+        audit(&state, &peer.ip().to_string(), "pairing_rejected", Some(false)).await;
+        return Err((StatusCode::UNAUTHORIZED, "Invalid, used or expired setup code"));
+    }
+
+    let device_id = Uuid::new_v4().to_string();
+    let device = state.pairing.register_device(&device_id, req.label).map_err(|e| {
+        warn!("Pairing failed: {:#}", e);
+        (StatusCode::CONFLICT, "Device already paired")
+    })?;
+    info!("Paired device {} via setup code", device.device_id);
+    audit(&state, &device.device_id, "device_paired", Some(true)).await;
+    Ok(Json(PairResponse { device_id: device.device_id, token: device.token }))
+}
+
+/// Endpoint: `GET /api/devices`
+pub async fn list_devices(_auth: RequireAdmin, State(state): State<GatewayState>) -> Json<Vec<DeviceSummary>> {
+    let mut devices: Vec<DeviceSummary> = state
+        .pairing
+        .list_devices()
+        .into_iter()
+        .map(|d| DeviceSummary { device_id: d.device_id, label: d.label, paired_at: d.paired_at })
+        .collect();
+    devices.sort_by_key(|d| d.paired_at);
+    Json(devices)
+}
+
+/// Endpoint: `DELETE /api/devices/:id`
+pub async fn revoke_device(
+    _auth: RequireAdmin,
+    State(state): State<GatewayState>,
+    Path(device_id): Path<String>,
+) -> StatusCode {
+    if state.pairing.revoke(&device_id) {
+        audit(&state, &device_id, "device_unpaired", None).await;
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// Record a pairing change in the audit log, when one is kept.
+async fn audit(state: &GatewayState, actor: &str, action: &str, approved: Option<bool>) {
+    let Some(log) = &state.audit else { return };
+    let mut event = clawforge_security::new_event("gateway", actor, action);
+    event.approved = approved;
+    if let Err(e) = log.record(event).await {
+        warn!("Failed to audit {}: {:#}", action, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use clawforge_companion::NodeStore;
+    use clawforge_security::ApprovalBroker;
+    use clawforge_tools::ArtifactStore;
+
+    use crate::auth::{AuthenticatedUser, ADMIN_ROLE};
+
+    fn state() -> GatewayState {
+        GatewayState::new(
+            Arc::new(ArtifactStore::new()),
+            Arc::new(ApprovalBroker::default()),
+            Arc::new(NodeStore::in_memory()),
+            infra::AdapterStatusRegistry::new(),
+        )
+    }
+
+    fn operator() -> RequireAdmin {
+        RequireAdmin(AuthenticatedUser { key_id: "api_key".into(), roles: vec![ADMIN_ROLE.into()] })
+    }
+
+    fn peer(ip: &str) -> ConnectInfo<SocketAddr> {
+        ConnectInfo(format!("{}:50000", ip).parse().unwrap())
+    }
+
+    async fn offer(state: &GatewayState) -> PairingOffer {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, "gateway.local:8080".parse().unwrap());
+        let Json(offer) = create_offer(operator(), State(state.clone()), headers, None).await.ok().unwrap();
+        offer
+    }
+
+    async fn pair(state: &GatewayState, ip: &str, code: &str) -> Result<PairResponse, StatusCode> {
+        let req = PairRequest { code: code.into(), label: Some("Phone".into()) };
+        pair_device(State(state.clone()), peer(ip), Json(req)).await.map(|Json(r)| r).map_err(|(status, _)| status)
+    }
+
+    #[tokio::test]
+    async fn an_offered_code_pairs_one_device_once() {
+        let state = state();
+        let offer = offer(&state).await;
+        assert!(offer.pairing_url.starts_with("http://gateway.local:8080"));
+
+        let paired = pair(&state, "10.0.0.2", &offer.code).await.ok().unwrap();
+        assert_eq!(state.pairing.validate_token(&paired.token), Some(paired.device_id.clone()));
+        assert_eq!(pair(&state, "10.0.0.3", &offer.code).await.err(), Some(StatusCode::UNAUTHORIZED));
+
+        let Json(devices) = list_devices(operator(), State(state.clone())).await;
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].label.as_deref(), Some("Phone"));
+    }
+
+    #[tokio::test]
+    async fn wrong_codes_are_refused_and_lock_out_their_address() {
+        let state = state();
+        let offer = offer(&state).await;
+        for _ in 0..clawforge_security::setup_code::MAX_FAILED_ATTEMPTS {
+            assert_eq!(pair(&state, "10.0.0.9", "AAAA-BBBB-CCCC-DDDD").await.err(), Some(StatusCode::UNAUTHORIZED));
+        }
+        assert_eq!(pair(&state, "10.0.0.9", &offer.code).await.err(), Some(StatusCode::UNAUTHORIZED));
+        assert!(pair(&state, "10.0.0.2", &offer.code).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn revoked_devices_lose_their_token() {
+        let state = state();
+        let paired = pair(&state, "10.0.0.2", &offer(&state).await.code).await.ok().unwrap();
+
+        let path = Path(paired.device_id.clone());
+        assert_eq!(revoke_device(operator(), State(state.clone()), path).await, StatusCode::NO_CONTENT);
+        assert_eq!(state.pairing.validate_token(&paired.token), None);
+        let path = Path(paired.device_id);
+        assert_eq!(revoke_device(operator(), State(state.clone()), path).await, StatusCode::NOT_FOUND);
+    }
+}
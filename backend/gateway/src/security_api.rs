@@ -0,0 +1,43 @@
+//! Security Posture API
+//!
+//! Runs every security check against the live config and returns the findings
+//! grouped by severity, with machine-readable codes and suggested fixes.
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde_json::Value;
+use tracing::error;
+
+use clawforge_security::{load_skill_sources, security_posture, PostureInput, SecurityPosture};
+
+use crate::auth::RequireAdmin;
+use crate::server::GatewayState;
+
+/// Endpoint: `GET /api/security/posture`
+pub async fn get_security_posture(
+    _auth: RequireAdmin,
+    State(state): State<GatewayState>,
+) -> Result<Json<SecurityPosture>, (StatusCode, &'static str)> {
+    // Without config sources the report still covers gateway auth and skills.
+    let config = match &state.config_sources {
+        Some(sources) => {
+            sources
+                .read()
+                .await
+                .explain()
+                .await
+                .map_err(|e| {
+                    error!("Failed to resolve config for security posture: {:#}", e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve effective config")
+                })?
+                .config
+        }
+        None => Value::Null,
+    };
+
+    let input = PostureInput {
+        config,
+        skills: load_skill_sources(&clawforge_config::config_dir().join("workspace").join("skills")),
+        gateway_api_key: std::env::var("CLAWFORGE_API_KEY").ok(),
+    };
+    Ok(Json(security_posture(&input)))
+}
@@ -4,27 +4,57 @@
 
 use anyhow::Result;
 use axum::{
-    routing::{get, post},
+    extract::FromRef,
+    routing::{delete, get, post},
     Router,
 };
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio::net::TcpListener;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tower_http::cors::CorsLayer;
 use tracing::{info, instrument};
 
-use clawforge_core::Message as CoreMessage;
+use clawforge_agent::SessionStore;
+use clawforge_channels::WebhookVerifier;
+use clawforge_companion::NodeStore;
+use clawforge_config::ConfigSources;
+use clawforge_daemon::LogManager;
+use clawforge_core::{Event, Message as CoreMessage};
+use clawforge_hooks::HookTracer;
+use clawforge_security::{ApprovalBroker, AuditLog, PairingStore, SetupCodeStore};
+use clawforge_tools::ArtifactStore;
+use clawforge_tts::CallBridge;
+use infra::AdapterStatusRegistry;
+use logging::EventStore;
 
+use crate::approvals_api;
+use crate::artifacts_api;
 use crate::control_ui;
+use crate::events_api;
+use crate::federation::{self, Federation};
 use crate::openai_compat;
+use crate::sessions_api::SessionRecorder;
 use crate::ws_server;
 use crate::session_registry::SessionRegistry;
 use crate::rate_limit::RateLimiter;
 use crate::auth_health;
 use crate::health_api;
 use crate::health_monitor::HealthMonitor;
+use crate::hooks_api;
+use crate::logs_api;
 use crate::responses_api;
 use crate::attachments;
+use crate::config_api;
+use crate::nodes_api;
+use crate::pairing_api;
+use crate::security_api;
+use crate::sessions_api;
+use crate::share_links::{self, ShareLinks};
+use crate::voice_api;
+
+/// How long a pairing setup code stays valid.
+const SETUP_CODE_MINUTES: i64 = 10;
 
 /// Application state shared across routes.
 #[derive(Clone)]
@@ -32,14 +62,175 @@ pub struct GatewayState {
     pub session_registry: SessionRegistry,
     pub rate_limiter: RateLimiter,
     pub health_monitor: HealthMonitor,
+    /// Channel adapter lifecycle states reported by the adapters.
+    pub adapters: AdapterStatusRegistry,
     pub started_at: std::time::Instant,
     /// Channel to the scheduler — None when the gateway runs standalone.
     pub scheduler_tx: Option<mpsc::Sender<CoreMessage>>,
+    /// Runtime events that Invoke results are taken from — None when results aren't relayed.
+    pub run_events: Option<broadcast::Sender<Event>>,
+    /// Layered config inputs for `/api/config/effective` — None when no config file is wired.
+    pub config_sources: Option<Arc<RwLock<ConfigSources>>>,
+    /// Live agent sessions for fork/compare — None when no agent runtime is attached.
+    pub sessions: Option<Arc<SessionStore>>,
+    /// Records Invokes and their run output into `sessions`.
+    pub session_recorder: Option<SessionRecorder>,
+    /// Public read-only transcript links.
+    pub share_links: ShareLinks,
+    /// Artifacts published by the `artifact` tool, served at `/artifacts/:id`.
+    pub artifacts: Arc<ArtifactStore>,
+    /// One-time setup codes handed out by `/api/pair/offer`.
+    pub setup_codes: Arc<SetupCodeStore>,
+    /// Paired devices and their long-lived tokens.
+    pub pairing: Arc<PairingStore>,
+    /// Dangerous tool calls waiting on a human verdict.
+    pub approvals: Arc<ApprovalBroker>,
+    /// Approved node hosts and those awaiting approval from LAN discovery.
+    pub nodes: Arc<NodeStore>,
+    /// Peer gateways for proxied channels/agents — None when federation is off.
+    pub federation: Option<Federation>,
+    /// Recorded hook evaluations — None when no hook pipeline is attached.
+    pub hook_tracer: Option<Arc<HookTracer>>,
+    /// Gateway log rotation and tailing — None when the daemon doesn't manage the log.
+    pub logs: Option<Arc<LogManager>>,
+    /// Stored agent events for `/api/events` — None when the event log isn't persisted.
+    pub events: Option<Arc<EventStore>>,
+    /// Phone call media bridge — None when voice calls are not configured.
+    pub calls: Option<Arc<CallBridge>>,
+    /// Checks `X-Twilio-Signature` on the answer webhook; calls are refused without it.
+    pub twilio_webhook: Option<WebhookVerifier>,
+    /// Media stream URL answered calls are sent to, from the configured public URL.
+    pub twilio_stream_url: Option<String>,
+    /// Tamper-evident log that device pairings are recorded in — None when not kept.
+    pub audit: Option<Arc<AuditLog>>,
+}
+
+impl GatewayState {
+    /// State for a gateway serving `artifacts`, `approvals` and `nodes` shared
+    /// with the runtime; optional subsystems are attached with the `with_*`
+    /// builders.
+    pub fn new(
+        artifacts: Arc<ArtifactStore>,
+        approvals: Arc<ApprovalBroker>,
+        nodes: Arc<NodeStore>,
+        adapters: AdapterStatusRegistry,
+    ) -> Self {
+        Self {
+            session_registry: SessionRegistry::new(),
+            rate_limiter: RateLimiter::default(),
+            health_monitor: HealthMonitor::new(),
+            adapters,
+            started_at: std::time::Instant::now(),
+            scheduler_tx: None,
+            run_events: None,
+            config_sources: None,
+            sessions: None,
+            session_recorder: None,
+            share_links: ShareLinks::new(),
+            artifacts,
+            setup_codes: Arc::new(SetupCodeStore::new(SETUP_CODE_MINUTES)),
+            pairing: Arc::new(PairingStore::new(SETUP_CODE_MINUTES as u64 * 60)),
+            approvals,
+            nodes,
+            federation: None,
+            hook_tracer: None,
+            logs: None,
+            events: None,
+            calls: None,
+            twilio_webhook: None,
+            twilio_stream_url: None,
+            audit: None,
+        }
+    }
+
+    /// Share `pairing` with the channels' DM gates, so codes issued here
+    /// pair chat senders too.
+    pub fn with_pairing(mut self, pairing: Arc<PairingStore>) -> Self {
+        self.pairing = pairing;
+        self
+    }
+
+    /// Record device pairings and revocations in `audit`.
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Proxy the channels and agents `config` lists to peer gateways.
+    pub fn with_federation(mut self, config: clawforge_config::schema::FederationConfig) -> Self {
+        self.federation = Some(Federation::new(config));
+        self
+    }
+
+    /// Connect phone calls to the agent through `bridge`; Twilio's answer
+    /// webhook must pass `twilio_webhook`, and answered calls stream their
+    /// media to `stream_url`.
+    pub fn with_calls(mut self, bridge: Arc<CallBridge>, twilio_webhook: WebhookVerifier, stream_url: impl Into<String>) -> Self {
+        self.calls = Some(bridge);
+        self.twilio_webhook = Some(twilio_webhook);
+        self.twilio_stream_url = Some(stream_url.into());
+        self
+    }
+
+    /// Explain the config layered from `sources` at `/api/config/effective`.
+    pub fn with_config_sources(mut self, sources: ConfigSources) -> Self {
+        self.config_sources = Some(Arc::new(RwLock::new(sources)));
+        self
+    }
+
+    /// Keep WebSocket sessions in `store` for fork, compare and sharing,
+    /// taking run output from `events`.
+    pub fn with_sessions(mut self, store: Arc<SessionStore>, events: broadcast::Receiver<Event>) -> Self {
+        let recorder = SessionRecorder::new(Arc::clone(&store));
+        recorder.clone().spawn(events);
+        self.sessions = Some(store);
+        self.session_recorder = Some(recorder);
+        self
+    }
+
+    /// Stream `logs` at the log endpoint.
+    pub fn with_logs(mut self, logs: Arc<LogManager>) -> Self {
+        self.logs = Some(logs);
+        self
+    }
+
+    /// Serve hook traces recorded by `tracer` at `/api/hooks/trace`.
+    pub fn with_hook_tracer(mut self, tracer: Arc<HookTracer>) -> Self {
+        self.hook_tracer = Some(tracer);
+        self
+    }
+
+    /// Serve the agent events in `events` at `/api/events`.
+    pub fn with_events(mut self, events: Arc<EventStore>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Hand chat completions and WebSocket runs to the scheduler.
+    pub fn with_scheduler(mut self, scheduler_tx: mpsc::Sender<CoreMessage>) -> Self {
+        self.scheduler_tx = Some(scheduler_tx);
+        self
+    }
+
+    /// Send each WebSocket Invoke's result, taken from `events`, to the
+    /// client (or peer gateway) that asked for it.
+    pub fn with_run_events(mut self, events: broadcast::Sender<Event>) -> Self {
+        self.run_events = Some(events);
+        self
+    }
+}
+
+impl FromRef<GatewayState> for Arc<PairingStore> {
+    fn from_ref(state: &GatewayState) -> Self {
+        Arc::clone(&state.pairing)
+    }
 }
 
 /// Starts the main Axum HTTP server for the gateway.
 #[instrument(skip(state))]
 pub async fn start_server(addr: SocketAddr, state: GatewayState) -> Result<()> {
+    federation::connect_peers(&state);
+
     // Build our application with routes
     let app = Router::new()
         // API Endpoints
@@ -48,8 +239,41 @@ pub async fn start_server(addr: SocketAddr, state: GatewayState) -> Result<()> {
         .route("/v1/attachments", post(attachments::upload_attachment))
         .route("/api/health", get(health_api::get_health))
         .route("/api/v1/auth/health", get(auth_health::check_auth_health))
+        .route("/api/config/effective", get(config_api::get_effective_config))
+        .route("/api/security/posture", get(security_api::get_security_posture))
+        .route("/api/sessions/compare", get(sessions_api::compare_sessions))
+        .route("/api/sessions/:id/fork", post(sessions_api::fork_session))
+        .route("/api/pair/offer", post(pairing_api::create_offer))
+        .route("/api/devices", get(pairing_api::list_devices))
+        .route("/api/devices/:id", delete(pairing_api::revoke_device))
+        .route("/api/approvals", get(approvals_api::list_approvals))
+        .route("/api/approvals/:id", post(approvals_api::resolve_approval))
+        .route("/api/nodes", get(nodes_api::list_nodes))
+        .route("/api/nodes/:id", delete(nodes_api::forget_node))
+        .route("/api/nodes/:id/approve", post(nodes_api::approve_node))
+        .route("/api/nodes/:id/reject", post(nodes_api::reject_node))
+        .route("/api/nodes/:id/permissions", get(nodes_api::get_permissions).put(nodes_api::update_permissions))
+        .route("/api/federation", get(federation::get_federation_status))
+        .route("/api/share", post(share_links::create_share))
+        .route("/api/share/:token", delete(share_links::revoke_share))
+        .route("/api/artifacts", get(artifacts_api::list_artifacts))
+        .route("/api/hooks/trace/:run_id", get(hooks_api::get_hook_trace))
+        .route("/api/logs/stream", get(logs_api::stream_logs))
+        .route("/api/events", get(events_api::list_events))
+        .route("/api/events/stats", get(events_api::event_stats))
+        // Device pairing: the setup code is the credential
+        .route("/api/pair", post(pairing_api::pair_device))
+        // Public share links and artifacts (no auth)
+        .route("/share/:token", get(share_links::view_share))
+        .route("/artifacts/:id", get(artifacts_api::view_artifact))
         // WebSocket Endpoint
         .route("/ws", get(ws_server::ws_handler))
+        // Peer gateway links (authenticated by the hello frame)
+        .route("/federation", get(federation::federation_handler))
+        // Phone calls (Twilio-signed webhook, per-call or LiveKit stream tokens)
+        .route("/voice/twilio/answer", post(voice_api::twilio_answer))
+        .route("/voice/twilio/stream", get(voice_api::twilio_stream))
+        .route("/voice/livekit/stream", get(voice_api::livekit_stream))
         // Control UI Static Files
         .nest("/ui", control_ui::ui_router())
         .with_state(state)
@@ -57,7 +281,7 @@ pub async fn start_server(addr: SocketAddr, state: GatewayState) -> Result<()> {
 
     info!("Gateway HTTP server listening on {}", addr);
     let listener = TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
     
     Ok(())
 }
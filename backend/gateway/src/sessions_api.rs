@@ -0,0 +1,219 @@
+//! Session Forking API
+//!
+//! Fork a live session at a message and compare two branches side by side.
+//! Sessions are recorded from WebSocket Invokes by `SessionRecorder`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tracing::warn;
+use uuid::Uuid;
+
+use clawforge_agent::chat::ChatMessage;
+use clawforge_agent::{BranchComparison, ForkOrigin, SessionState, SessionStore};
+use clawforge_core::{Event, EventKind};
+
+use crate::auth::RequireAuth;
+use crate::server::GatewayState;
+
+#[derive(Debug, Deserialize)]
+pub struct ForkRequest {
+    /// Last message the fork keeps from the parent.
+    pub message_id: Uuid,
+    /// Model to continue the fork with; defaults to the parent's.
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ForkResponse {
+    pub session_id: String,
+    pub forked_from: Option<ForkOrigin>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareQuery {
+    pub left: String,
+    pub right: String,
+}
+
+fn session_store(state: &GatewayState) -> Result<&Arc<SessionStore>, (StatusCode, &'static str)> {
+    state
+        .sessions
+        .as_ref()
+        .ok_or((StatusCode::NOT_FOUND, "No session store attached to gateway"))
+}
+
+/// Endpoint: `POST /api/sessions/:id/fork`
+pub async fn fork_session(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+    Path(session_id): Path<String>,
+    Json(req): Json<ForkRequest>,
+) -> Result<Json<ForkResponse>, (StatusCode, &'static str)> {
+    let store = session_store(&state)?;
+    let new_id = store.fork(&session_id, req.message_id, req.model).await.map_err(|e| {
+        warn!("Fork of session {} failed: {:#}", session_id, e);
+        (StatusCode::NOT_FOUND, "Session or message not found")
+    })?;
+
+    let forked_from = match store.get(&new_id).await {
+        Some(session) => session.read().await.forked_from.clone(),
+        None => None,
+    };
+    Ok(Json(ForkResponse { session_id: new_id, forked_from }))
+}
+
+/// Endpoint: `GET /api/sessions/compare?left=<id>&right=<id>`
+pub async fn compare_sessions(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+    Query(query): Query<CompareQuery>,
+) -> Result<Json<BranchComparison>, (StatusCode, &'static str)> {
+    let store = session_store(&state)?;
+    store
+        .compare(&query.left, &query.right)
+        .await
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "Session not found"))
+}
+
+/// Finished runs whose turns are kept for run share links.
+const MAX_FINISHED_RUNS: usize = 1024;
+
+/// Session a run was started from and the turns it added there.
+struct RecordedRun {
+    session_id: String,
+    turns: Vec<Uuid>,
+    finished: bool,
+}
+
+#[derive(Default)]
+struct RunIndex {
+    runs: HashMap<Uuid, RecordedRun>,
+    /// Finished runs, oldest first.
+    finished: VecDeque<Uuid>,
+}
+
+/// Keeps gateway sessions in a `SessionStore`: each Invoke is a user turn,
+/// and the output of the run it started is appended as assistant turns.
+#[derive(Clone)]
+pub struct SessionRecorder {
+    store: Arc<SessionStore>,
+    runs: Arc<Mutex<RunIndex>>,
+}
+
+impl SessionRecorder {
+    pub fn new(store: Arc<SessionStore>) -> Self {
+        Self { store, runs: Arc::new(Mutex::new(RunIndex::default())) }
+    }
+
+    pub fn store(&self) -> &Arc<SessionStore> {
+        &self.store
+    }
+
+    /// Record `content` as a user turn of `session_id`, creating the
+    /// session, and attribute `run_id`'s output to it.
+    pub async fn record_invoke(&self, session_id: &str, agent_id: &str, run_id: Uuid, content: &str) {
+        let session = match self.store.get(session_id).await {
+            Some(session) => session,
+            None => self.store.insert(SessionState::new(session_id, agent_id)).await,
+        };
+        let turn = ChatMessage::user(content);
+        let run = RecordedRun { session_id: session_id.to_string(), turns: vec![turn.id], finished: false };
+        session.write().await.transcript.push(turn);
+        self.runs.lock().unwrap().runs.insert(run_id, run);
+    }
+
+    /// Apply one runtime event to the session its run belongs to.
+    pub async fn record_event(&self, event: &Event) {
+        let session_id = match event.kind {
+            EventKind::RunCompleted | EventKind::RunFailed => {
+                self.finish(event.run_id);
+                return;
+            }
+            EventKind::ActionExecuted => {
+                let runs = self.runs.lock().unwrap();
+                runs.runs.get(&event.run_id).filter(|run| !run.finished).map(|run| run.session_id.clone())
+            }
+            _ => None,
+        };
+        let (Some(session_id), Some(output)) = (session_id, event.payload["output"].as_str()) else { return };
+        let Some(session) = self.store.get(&session_id).await else { return };
+        let turn = ChatMessage::assistant(output);
+        if let Some(run) = self.runs.lock().unwrap().runs.get_mut(&event.run_id) {
+            run.turns.push(turn.id);
+        }
+        session.write().await.transcript.push(turn);
+    }
+
+    fn finish(&self, run_id: Uuid) {
+        let mut index = self.runs.lock().unwrap();
+        let Some(run) = index.runs.get_mut(&run_id).filter(|run| !run.finished) else { return };
+        run.finished = true;
+        index.finished.push_back(run_id);
+        if index.finished.len() > MAX_FINISHED_RUNS {
+            if let Some(oldest) = index.finished.pop_front() {
+                index.runs.remove(&oldest);
+            }
+        }
+    }
+
+    /// The turns `run_id` added to its session, or `None` for a run this
+    /// recorder didn't see or has since forgotten.
+    pub async fn run_transcript(&self, run_id: Uuid) -> Option<Vec<ChatMessage>> {
+        let (session_id, turns) = {
+            let runs = self.runs.lock().unwrap();
+            let run = runs.runs.get(&run_id)?;
+            (run.session_id.clone(), run.turns.clone())
+        };
+        let session = self.store.get(&session_id).await?;
+        let session = session.read().await;
+        Some(session.transcript.iter().filter(|m| turns.contains(&m.id)).cloned().collect())
+    }
+
+    /// Record run output from `events` in the background.
+    pub fn spawn(self, mut events: broadcast::Receiver<Event>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => self.record_event(&event).await,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => warn!(missed, "Session recorder lagged; run output was skipped"),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn invokes_and_their_run_output_become_turns() {
+        let recorder = SessionRecorder::new(Arc::new(SessionStore::new()));
+        let (run, other) = (Uuid::new_v4(), Uuid::new_v4());
+        recorder.record_invoke("s1", "agent", run, "what's the weather?").await;
+        let output = |run_id, text: &str| Event::new(run_id, Uuid::nil(), EventKind::ActionExecuted, serde_json::json!({ "output": text }));
+        recorder.record_event(&output(other, "unrelated")).await;
+        recorder.record_event(&output(run, "Sunny.")).await;
+        recorder.record_event(&Event::new(run, Uuid::nil(), EventKind::RunCompleted, serde_json::json!({}))).await;
+        recorder.record_event(&output(run, "late")).await;
+
+        let session = recorder.store().get("s1").await.unwrap();
+        let contents: Vec<String> = session.read().await.transcript.iter().map(|m| m.content.clone()).collect();
+        assert_eq!(contents, ["what's the weather?", "Sunny."]);
+
+        recorder.record_invoke("s1", "agent", other, "and tomorrow?").await;
+        let turns: Vec<String> = recorder.run_transcript(run).await.unwrap().into_iter().map(|m| m.content).collect();
+        assert_eq!(turns, ["what's the weather?", "Sunny."]);
+        assert!(recorder.run_transcript(Uuid::new_v4()).await.is_none());
+    }
+}
@@ -0,0 +1,281 @@
+//! Public Share Links
+//!
+//! Read-only, expiring links to a redacted transcript snapshot. Creating a
+//! link requires auth; viewing one does not, so the token is the only secret.
+//! The transcript is frozen and redacted when the link is created: later
+//! messages never leak through an old link, and system prompts are dropped.
+//! Links live in the gateway's memory only, so a restart ends every link.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::info;
+use uuid::Uuid;
+
+use clawforge_agent::chat::{ChatMessage, MessageRole};
+use logging::redact_sensitive_data;
+
+use crate::auth::RequireAuth;
+use crate::server::GatewayState;
+
+const DEFAULT_TTL_SECS: u64 = 7 * 24 * 3600;
+const MAX_TTL_SECS: u64 = 30 * 24 * 3600;
+
+/// What a share link points at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ShareTarget {
+    Session { session_id: String },
+    Run { run_id: Uuid },
+}
+
+/// One transcript entry as shown to link viewers.
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedMessage {
+    pub role: MessageRole,
+    pub content: String,
+    /// Names of tools the assistant called; arguments are never shared.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedTranscript {
+    pub target: ShareTarget,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub messages: Vec<SharedMessage>,
+}
+
+/// Redacted, system-prompt-free copy of a transcript.
+pub fn redact_transcript(messages: &[ChatMessage]) -> Vec<SharedMessage> {
+    messages
+        .iter()
+        .filter(|m| m.role != MessageRole::System)
+        .map(|m| SharedMessage {
+            role: m.role.clone(),
+            content: redact_sensitive_data(&m.content),
+            tools: m
+                .tool_calls
+                .iter()
+                .flatten()
+                .map(|call| call.name.clone())
+                .collect(),
+        })
+        .collect()
+}
+
+/// In-memory store of live share links, keyed by token.
+#[derive(Clone, Default)]
+pub struct ShareLinks {
+    links: Arc<RwLock<HashMap<String, SharedTranscript>>>,
+}
+
+impl ShareLinks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot `messages` behind a new unguessable token.
+    /// `ttl_secs` is clamped to 30 days.
+    pub async fn create(&self, target: ShareTarget, messages: &[ChatMessage], ttl_secs: Option<u64>) -> (String, DateTime<Utc>) {
+        // Two v4 UUIDs: 244 random bits from the OS RNG.
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let ttl = ttl_secs.unwrap_or(DEFAULT_TTL_SECS).min(MAX_TTL_SECS);
+        let created_at = Utc::now();
+        let expires_at = created_at + Duration::seconds(ttl as i64);
+
+        let mut links = self.links.write().await;
+        links.retain(|_, link| link.expires_at > created_at);
+        links.insert(
+            token.clone(),
+            SharedTranscript { target, created_at, expires_at, messages: redact_transcript(messages) },
+        );
+        (token, expires_at)
+    }
+
+    /// Look up a link, treating expired ones as missing.
+    pub async fn get(&self, token: &str) -> Option<SharedTranscript> {
+        let links = self.links.read().await;
+        links.get(token).filter(|link| link.expires_at > Utc::now()).cloned()
+    }
+
+    pub async fn revoke(&self, token: &str) -> bool {
+        self.links.write().await.remove(token).is_some()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Handlers
+// ---------------------------------------------------------------------------
+
+/// Share a session with `session_id`, or just the turns of one run with
+/// `run_id`.
+#[derive(Debug, Deserialize)]
+pub struct CreateShareRequest {
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub run_id: Option<Uuid>,
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct CreateShareResponse {
+    pub token: String,
+    /// Path to hand out; prefix with the gateway's public origin.
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Endpoint: `POST /api/share`
+pub async fn create_share(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+    Json(req): Json<CreateShareRequest>,
+) -> Result<Json<CreateShareResponse>, (StatusCode, &'static str)> {
+    let (target, messages) = match (req.session_id, req.run_id) {
+        (Some(session_id), None) => {
+            let Some(sessions) = &state.sessions else {
+                return Err((StatusCode::NOT_FOUND, "No session store attached to gateway"));
+            };
+            let session = sessions.get(&session_id).await.ok_or((StatusCode::NOT_FOUND, "Session not found"))?;
+            let messages = session.read().await.transcript.to_vec();
+            (ShareTarget::Session { session_id }, messages)
+        }
+        (None, Some(run_id)) => {
+            let Some(recorder) = &state.session_recorder else {
+                return Err((StatusCode::NOT_FOUND, "No session store attached to gateway"));
+            };
+            let messages = recorder.run_transcript(run_id).await.ok_or((StatusCode::NOT_FOUND, "Run not found"))?;
+            (ShareTarget::Run { run_id }, messages)
+        }
+        _ => return Err((StatusCode::BAD_REQUEST, "Give exactly one of session_id and run_id")),
+    };
+
+    let (token, expires_at) = state.share_links.create(target.clone(), &messages, req.ttl_secs).await;
+    info!("Created share link for {:?} (expires {})", target, expires_at);
+
+    Ok(Json(CreateShareResponse { url: format!("/share/{}", token), token, expires_at }))
+}
+
+/// Endpoint: `GET /share/:token` — public, no auth.
+pub async fn view_share(
+    State(state): State<GatewayState>,
+    Path(token): Path<String>,
+) -> Result<Json<SharedTranscript>, (StatusCode, &'static str)> {
+    state
+        .share_links
+        .get(&token)
+        .await
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "Share link not found or expired"))
+}
+
+/// Endpoint: `DELETE /api/share/:token`
+pub async fn revoke_share(
+    _auth: RequireAuth,
+    State(state): State<GatewayState>,
+    Path(token): Path<String>,
+) -> StatusCode {
+    if state.share_links.revoke(&token).await {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawforge_agent::chat::ToolCallRequest;
+    use clawforge_agent::{SessionState, SessionStore};
+    use clawforge_companion::NodeStore;
+    use clawforge_security::ApprovalBroker;
+    use clawforge_tools::ArtifactStore;
+    use tokio::sync::broadcast;
+
+    use crate::auth::AuthenticatedUser;
+
+    fn auth() -> RequireAuth {
+        RequireAuth(AuthenticatedUser { key_id: "api_key".into(), roles: vec!["admin".into()] })
+    }
+
+    /// A gateway with one session holding a system prompt, a question, a tool
+    /// call with a secret in its arguments, and an answer carrying a token.
+    async fn state() -> (GatewayState, Arc<SessionStore>) {
+        let sessions = Arc::new(SessionStore::new());
+        let session = sessions.insert(SessionState::new("s1", "agent")).await;
+        let mut call = ChatMessage::assistant("Looking it up.");
+        call.tool_calls = Some(vec![ToolCallRequest {
+            id: "c1".into(),
+            name: "http".into(),
+            arguments: serde_json::json!({ "headers": { "Authorization": "hunter2" } }),
+        }]);
+        let messages = [
+            ChatMessage::system("You are a secret internal prompt."),
+            ChatMessage::user("What's my key?"),
+            call,
+            ChatMessage::assistant("It is Bearer abc.def.ghi"),
+        ];
+        let mut session = session.write().await;
+        for message in messages {
+            session.transcript.push(message);
+        }
+        drop(session);
+        let (_, events) = broadcast::channel(1);
+        let state = GatewayState::new(
+            Arc::new(ArtifactStore::new()),
+            Arc::new(ApprovalBroker::default()),
+            Arc::new(NodeStore::in_memory()),
+            infra::AdapterStatusRegistry::new(),
+        )
+        .with_sessions(Arc::clone(&sessions), events);
+        (state, sessions)
+    }
+
+    async fn share(state: &GatewayState, ttl_secs: Option<u64>) -> String {
+        let req = CreateShareRequest { session_id: Some("s1".into()), run_id: None, ttl_secs };
+        let Json(created) = create_share(auth(), State(state.clone()), Json(req)).await.ok().unwrap();
+        created.token
+    }
+
+    async fn view(state: &GatewayState, token: &str) -> Result<SharedTranscript, StatusCode> {
+        view_share(State(state.clone()), Path(token.to_string())).await.map(|Json(t)| t).map_err(|(status, _)| status)
+    }
+
+    #[tokio::test]
+    async fn links_show_a_redacted_snapshot_without_system_prompts_or_tool_arguments() {
+        let (state, sessions) = state().await;
+        let token = share(&state, None).await;
+        sessions.get("s1").await.unwrap().write().await.transcript.push(ChatMessage::user("A later message"));
+
+        let shared = view(&state, &token).await.unwrap();
+        let contents: Vec<&str> = shared.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["What's my key?", "Looking it up.", "It is [REDACTED_TOKEN]"]);
+        assert_eq!(shared.messages[1].tools, ["http"]);
+        let json = serde_json::to_string(&shared).unwrap();
+        assert!(!json.contains("hunter2") && !json.contains("secret internal prompt"));
+    }
+
+    #[tokio::test]
+    async fn expired_and_revoked_links_are_not_found() {
+        let (state, _) = state().await;
+        let expired = share(&state, Some(0)).await;
+        assert_eq!(view(&state, &expired).await.err(), Some(StatusCode::NOT_FOUND));
+
+        let token = share(&state, None).await;
+        assert!(view(&state, &token).await.is_ok());
+        assert_eq!(revoke_share(auth(), State(state.clone()), Path(token.clone())).await, StatusCode::NO_CONTENT);
+        assert_eq!(view(&state, &token).await.err(), Some(StatusCode::NOT_FOUND));
+        assert_eq!(revoke_share(auth(), State(state.clone()), Path(token)).await, StatusCode::NOT_FOUND);
+    }
+}
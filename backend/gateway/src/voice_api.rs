@@ -0,0 +1,273 @@
+//! Voice Call Endpoints
+//!
+//! Connects phone calls to the agent through the TTS crate's `CallBridge`.
+//! Twilio calls `/voice/twilio/answer` when a call comes in and gets TwiML
+//! that opens a media stream back to `/voice/twilio/stream`. LiveKit track
+//! egress connects to `/voice/livekit/stream` with the call details in the
+//! query string. Neither Twilio nor LiveKit can send a gateway API key, so
+//! the answer webhook is checked against `X-Twilio-Signature` and hands each
+//! call a single-use stream token; LiveKit streams present the bridge's
+//! configured stream token and are refused when there is none. The stream
+//! URL in the TwiML comes from the configured public URL, never from the
+//! request's `Host`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::{sink::SinkExt, stream::StreamExt};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use clawforge_tts::{stream_twiml, CallBridge, CallDirection, CallFrame, CallInfo, MediaProtocol};
+
+use crate::server::GatewayState;
+
+/// LiveKit egress default: 48 kHz mono.
+const LIVEKIT_SAMPLE_RATE: u32 = 48_000;
+
+fn bridge(state: &GatewayState) -> Result<Arc<CallBridge>, (StatusCode, &'static str)> {
+    state.calls.clone().ok_or((StatusCode::SERVICE_UNAVAILABLE, "Voice calls are not configured"))
+}
+
+/// Endpoint: `POST /voice/twilio/answer` — Twilio's incoming-call webhook.
+pub async fn twilio_answer(
+    State(state): State<GatewayState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, (StatusCode, &'static str)> {
+    let bridge = bridge(&state)?;
+    let verifier = state.twilio_webhook.as_ref().ok_or((StatusCode::SERVICE_UNAVAILABLE, "Voice calls are not configured"))?;
+    if let Err(failure) = verifier.verify(&headers, &body) {
+        warn!("Twilio answer webhook rejected: {}", failure.as_str());
+        return Err((StatusCode::FORBIDDEN, "Invalid Twilio signature"));
+    }
+    let stream_url = state
+        .twilio_stream_url
+        .as_deref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "Voice calls have no public URL"))?;
+    let form: HashMap<String, String> = form_urlencoded::parse(&body).into_owned().collect();
+
+    let token = bridge.issue_call_token();
+    let mut parameters = vec![("direction", "inbound"), ("token", token.as_str())];
+    for (param, field) in [("from", "From"), ("to", "To")] {
+        if let Some(value) = form.get(field) {
+            parameters.push((param, value.as_str()));
+        }
+    }
+    info!("Answering call {} from {}", form.get("CallSid").map_or("?", String::as_str), form.get("From").map_or("?", String::as_str));
+    Ok(([(header::CONTENT_TYPE, "text/xml")], stream_twiml(stream_url, &parameters)).into_response())
+}
+
+/// Endpoint: `GET /voice/twilio/stream` — Twilio Media Streams socket. The
+/// call token is redeemed by the bridge when the stream's `start` event arrives.
+pub async fn twilio_stream(
+    ws: WebSocketUpgrade,
+    State(state): State<GatewayState>,
+) -> Result<Response, (StatusCode, &'static str)> {
+    let bridge = bridge(&state)?;
+    Ok(ws.on_upgrade(move |socket| run_call(socket, bridge, MediaProtocol::Twilio, None)))
+}
+
+/// Endpoint: `GET /voice/livekit/stream?call_id=&from=&to=&sample_rate=&token=`
+pub async fn livekit_stream(
+    ws: WebSocketUpgrade,
+    State(state): State<GatewayState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response, (StatusCode, &'static str)> {
+    let (bridge, protocol, info) = livekit_call(&state, &query)?;
+    Ok(ws.on_upgrade(move |socket| run_call(socket, bridge, protocol, Some(info))))
+}
+
+/// Authorize a LiveKit stream and read its call details from the query.
+fn livekit_call(
+    state: &GatewayState,
+    query: &HashMap<String, String>,
+) -> Result<(Arc<CallBridge>, MediaProtocol, CallInfo), (StatusCode, &'static str)> {
+    let bridge = bridge(state)?;
+    bridge
+        .authorize(query.get("token").map(String::as_str))
+        .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid stream token"))?;
+    let sample_rate = match query.get("sample_rate") {
+        Some(rate) => rate.parse().map_err(|_| (StatusCode::BAD_REQUEST, "Invalid sample_rate"))?,
+        None => LIVEKIT_SAMPLE_RATE,
+    };
+    let info = CallInfo {
+        call_id: query.get("call_id").cloned().unwrap_or_else(|| Uuid::new_v4().to_string()),
+        direction: CallDirection::Inbound,
+        from: query.get("from").cloned(),
+        to: query.get("to").cloned(),
+    };
+    Ok((bridge, MediaProtocol::LiveKit { sample_rate }, info))
+}
+
+async fn run_call(socket: WebSocket, bridge: Arc<CallBridge>, protocol: MediaProtocol, info: Option<CallInfo>) {
+    let (mut sender, mut receiver) = socket.split();
+    let (in_tx, in_rx) = mpsc::channel::<CallFrame>(256);
+    let (out_tx, mut out_rx) = mpsc::channel::<CallFrame>(256);
+
+    let send_task = tokio::spawn(async move {
+        while let Some(frame) = out_rx.recv().await {
+            let msg = match frame {
+                CallFrame::Text(text) => Message::Text(text),
+                CallFrame::Binary(data) => Message::Binary(data.to_vec()),
+            };
+            if sender.send(msg).await.is_err() {
+                debug!("Media socket send failed — call disconnected");
+                break;
+            }
+        }
+    });
+    let recv_task = tokio::spawn(async move {
+        while let Some(Ok(msg)) = receiver.next().await {
+            let frame = match msg {
+                Message::Text(text) => CallFrame::Text(text),
+                Message::Binary(data) => CallFrame::Binary(data.into()),
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            if in_tx.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    match bridge.run(protocol, info, in_rx, out_tx).await {
+        Ok(call) => info!("Call {} finished after {}s", call.call_id, call.duration_secs.unwrap_or(0)),
+        Err(e) => warn!("Call bridge failed: {e:#}"),
+    }
+    recv_task.abort();
+    send_task.abort();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::async_trait;
+    use clawforge_channels::{SignatureScheme, WebhookVerifier};
+    use clawforge_companion::NodeStore;
+    use clawforge_security::ApprovalBroker;
+    use clawforge_tools::ArtifactStore;
+    use clawforge_tts::{CallAgent, SttProvider, SttRequest, SttTranscript, TtsProvider, TtsRequest};
+
+    /// Hears nothing, says nothing.
+    struct Mute;
+
+    #[async_trait]
+    impl SttProvider for Mute {
+        fn name(&self) -> &str {
+            "mute"
+        }
+        async fn transcribe(&self, _req: SttRequest) -> anyhow::Result<SttTranscript> {
+            Ok(SttTranscript::default())
+        }
+    }
+
+    #[async_trait]
+    impl TtsProvider for Mute {
+        async fn synthesize(&self, _req: TtsRequest) -> anyhow::Result<Bytes> {
+            Ok(Bytes::new())
+        }
+    }
+
+    #[async_trait]
+    impl CallAgent for Mute {
+        async fn respond(&self, _call: &CallInfo, _transcript: &str, _reply: mpsc::Sender<String>) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    const STREAM_URL: &str = "wss://claw.example.com/voice/twilio/stream";
+
+    fn state(bridge: CallBridge) -> GatewayState {
+        // A static-token scheme keeps the signature out of the way; the
+        // Twilio scheme itself is covered in `webhook_verify`.
+        let verifier = WebhookVerifier::new("twilio", SignatureScheme::TelegramSecretToken { secret_token: "sig".into() });
+        GatewayState::new(
+            Arc::new(ArtifactStore::new()),
+            Arc::new(ApprovalBroker::default()),
+            Arc::new(NodeStore::in_memory()),
+            infra::AdapterStatusRegistry::new(),
+        )
+        .with_calls(Arc::new(bridge), verifier, STREAM_URL)
+    }
+
+    fn bridge() -> CallBridge {
+        CallBridge::new(Arc::new(Mute), Arc::new(Mute), Arc::new(Mute))
+    }
+
+    fn signed(host: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-telegram-bot-api-secret-token", "sig".parse().unwrap());
+        headers.insert(header::HOST, host.parse().unwrap());
+        headers
+    }
+
+    async fn text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn answer_streams_to_the_configured_url_whatever_the_host() {
+        let state = state(bridge());
+        let body = Bytes::from_static(b"CallSid=CA1&From=%2B15550100&To=%2B15550199");
+        let twiml = text(twilio_answer(State(state.clone()), signed("evil.example"), body).await.unwrap()).await;
+        assert!(twiml.contains(STREAM_URL));
+        assert!(!twiml.contains("evil.example"));
+        assert!(twiml.contains("+15550100"));
+
+        // The call token in the TwiML opens one Twilio stream.
+        let token = twiml.split("name=\"token\" value=\"").nth(1).unwrap().split('"').next().unwrap();
+        let (in_tx, in_rx) = mpsc::channel(4);
+        let (out_tx, _out_rx) = mpsc::channel(4);
+        let start = serde_json::json!({
+            "event": "start",
+            "start": { "streamSid": "MZ1", "callSid": "CA1", "customParameters": { "token": token } }
+        });
+        in_tx.send(CallFrame::Text(start.to_string())).await.unwrap();
+        drop(in_tx);
+        assert!(state.calls.unwrap().run(MediaProtocol::Twilio, None, in_rx, out_tx).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn answer_needs_a_signature_and_a_public_url() {
+        let state = state(bridge());
+        let err = twilio_answer(State(state.clone()), HeaderMap::new(), Bytes::new()).await.unwrap_err();
+        assert_eq!(err.0, StatusCode::FORBIDDEN);
+
+        let unconfigured = GatewayState { twilio_stream_url: None, ..state };
+        let err = twilio_answer(State(unconfigured), signed("claw.example.com"), Bytes::new()).await.unwrap_err();
+        assert_eq!(err.0, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn livekit_streams_need_the_configured_token() {
+        let query = |token: Option<&str>| -> HashMap<String, String> {
+            let mut query = HashMap::from([("call_id".to_string(), "room-1".to_string())]);
+            if let Some(token) = token {
+                query.insert("token".into(), token.into());
+            }
+            query
+        };
+        let refused = |state: &GatewayState, token| livekit_call(state, &query(token)).err().map(|(status, _)| status);
+        let unprotected = state(bridge());
+        assert_eq!(refused(&unprotected, Some("")), Some(StatusCode::UNAUTHORIZED));
+        assert_eq!(refused(&unprotected, None), Some(StatusCode::UNAUTHORIZED));
+
+        let protected = state(bridge().with_stream_token("s3cret"));
+        assert_eq!(refused(&protected, Some("guess")), Some(StatusCode::UNAUTHORIZED));
+        let (_, protocol, info) = livekit_call(&protected, &query(Some("s3cret"))).unwrap();
+        assert_eq!(protocol, MediaProtocol::LiveKit { sample_rate: LIVEKIT_SAMPLE_RATE });
+        assert_eq!(info.call_id, "room-1");
+    }
+}
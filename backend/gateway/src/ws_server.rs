@@ -11,14 +11,18 @@ use axum::{
 };
 use tracing::{debug, error, info, warn};
 
-use clawforge_core::{Message as CoreMessage, message::JobTrigger};
+use clawforge_core::{Event, Message as CoreMessage, message::JobTrigger};
+use clawforge_scheduler::run_outcome;
 use uuid::Uuid;
 
 use crate::server::GatewayState;
 use crate::ws_protocol::WsMessage;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use futures::{sink::SinkExt, stream::StreamExt};
 
+/// An Invoke with no result after this long is reported as timed out.
+const RESULT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(600);
+
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<GatewayState>,
@@ -88,56 +92,111 @@ async fn handle_incoming_message(
             }
         }
         WsMessage::Invoke { session_id, agent_id, content } => {
-            info!(session_id = %session_id, agent_id = %agent_id, "Received Invoke — dispatching to scheduler");
-            let parsed_agent_id = match Uuid::parse_str(&agent_id) {
-                Ok(id) => id,
-                Err(_) => {
-                    if reply_tx.send(WsMessage::Error {
-                        session_id: Some(session_id),
-                        error_code: "invalid_agent_id".to_string(),
-                        message: format!("agent_id '{}' is not a valid UUID", agent_id),
-                    }).is_err() {
-                        warn!("Failed to send Error — receiver dropped");
-                    }
-                    return;
-                }
+            let reply = match &state.federation {
+                Some(federation) => federation.invoke(state, session_id, agent_id, content, reply_tx.clone()).await,
+                None => schedule_invoke(state, session_id, agent_id, content, reply_tx.clone()).await,
             };
-            match &state.scheduler_tx {
-                Some(tx) => {
-                    let run_id = Uuid::new_v4();
-                    let trigger = JobTrigger {
-                        run_id,
-                        agent_id: parsed_agent_id,
-                        trigger_reason: format!("WebSocket Invoke from session {}: {}", session_id, content),
-                    };
-                    if let Err(e) = tx.send(CoreMessage::ScheduleJob(trigger)).await {
-                        error!(error = %e, "Failed to dispatch Invoke to scheduler");
-                        if reply_tx.send(WsMessage::Error {
-                            session_id: Some(session_id),
-                            error_code: "scheduler_unavailable".to_string(),
-                            message: "Scheduler is not reachable".to_string(),
-                        }).is_err() {
-                            warn!("Failed to send Error — receiver dropped");
-                        }
-                    } else if reply_tx.send(WsMessage::StateChange {
-                        session_id,
-                        state: format!("scheduled:{}", run_id),
-                    }).is_err() {
-                        warn!("Failed to send StateChange — receiver dropped");
-                    }
+            if reply_tx.send(reply).is_err() {
+                warn!("Failed to send Invoke reply — receiver dropped");
+            }
+        }
+        _ => warn!("Received unexpected message type from client"),
+    }
+}
+
+/// Run an Invoke on this gateway: hand it to the scheduler and describe the
+/// outcome as the reply for the client. When run events are attached, the
+/// run's result or error follows on `results` once it ends.
+pub(crate) async fn schedule_invoke(
+    state: &GatewayState,
+    session_id: String,
+    agent_id: String,
+    content: String,
+    results: mpsc::UnboundedSender<WsMessage>,
+) -> WsMessage {
+    info!(session_id = %session_id, agent_id = %agent_id, "Received Invoke — dispatching to scheduler");
+    let parsed_agent_id = match Uuid::parse_str(&agent_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return WsMessage::Error {
+                session_id: Some(session_id),
+                error_code: "invalid_agent_id".to_string(),
+                message: format!("agent_id '{}' is not a valid UUID", agent_id),
+            };
+        }
+    };
+    match &state.scheduler_tx {
+        Some(tx) => {
+            let run_id = Uuid::new_v4();
+            // Subscribe before the job goes out so no event is missed.
+            let events = state.run_events.as_ref().map(|events| events.subscribe());
+            let trigger = JobTrigger {
+                run_id,
+                agent_id: parsed_agent_id,
+                trigger_reason: format!("WebSocket Invoke from session {}: {}", session_id, content),
+            };
+            if let Err(e) = tx.send(CoreMessage::ScheduleJob(trigger)).await {
+                error!(error = %e, "Failed to dispatch Invoke to scheduler");
+                WsMessage::Error {
+                    session_id: Some(session_id),
+                    error_code: "scheduler_unavailable".to_string(),
+                    message: "Scheduler is not reachable".to_string(),
+                }
+            } else {
+                if let Some(recorder) = &state.session_recorder {
+                    recorder.record_invoke(&session_id, &agent_id, run_id, &content).await;
+                }
+                if let Some(events) = events {
+                    tokio::spawn(relay_result(events, run_id, session_id.clone(), results));
+                }
+                WsMessage::StateChange {
+                    session_id,
+                    state: format!("scheduled:{}", run_id),
                 }
-                None => {
-                    warn!(agent_id = %agent_id, "No scheduler connected — Invoke ignored");
-                    if reply_tx.send(WsMessage::Error {
-                        session_id: Some(session_id),
-                        error_code: "scheduler_unavailable".to_string(),
-                        message: "No scheduler is connected to this gateway".to_string(),
-                    }).is_err() {
-                        warn!("Failed to send Error — receiver dropped");
+            }
+        }
+        None => {
+            warn!(agent_id = %agent_id, "No scheduler connected — Invoke ignored");
+            WsMessage::Error {
+                session_id: Some(session_id),
+                error_code: "scheduler_unavailable".to_string(),
+                message: "No scheduler is connected to this gateway".to_string(),
+            }
+        }
+    }
+}
+
+/// Wait for `run_id` to produce output or fail, and send that to `results`.
+async fn relay_result(
+    mut events: broadcast::Receiver<Event>,
+    run_id: Uuid,
+    session_id: String,
+    results: mpsc::UnboundedSender<WsMessage>,
+) {
+    let outcome = tokio::time::timeout(RESULT_TIMEOUT, async {
+        loop {
+            match events.recv().await {
+                Ok(event) if event.run_id == run_id => {
+                    if let Some(outcome) = run_outcome(&event) {
+                        return outcome;
                     }
                 }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return Err("Event stream closed".to_string()),
             }
         }
-        _ => warn!("Received unexpected message type from client"),
+    })
+    .await;
+    let message = match outcome {
+        Ok(Ok(content)) => WsMessage::Result { session_id, content },
+        Ok(Err(message)) => WsMessage::Error { session_id: Some(session_id), error_code: "run_failed".to_string(), message },
+        Err(_) => WsMessage::Error {
+            session_id: Some(session_id),
+            error_code: "run_timeout".to_string(),
+            message: format!("No result after {}s", RESULT_TIMEOUT.as_secs()),
+        },
+    };
+    if results.send(message).is_err() {
+        debug!(%run_id, "Invoke result dropped — client disconnected");
     }
 }
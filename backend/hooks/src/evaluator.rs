@@ -67,6 +67,8 @@ fn evaluate_condition(cond: &HookCondition, ctx: &HookContext) -> bool {
         }
 
         HookCondition::Not { condition } => !evaluate_condition(condition, ctx),
+
+        HookCondition::Expr { expr } => expr.evaluate(ctx),
     }
 }
 
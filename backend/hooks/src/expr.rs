@@ -0,0 +1,542 @@
+//! Hook condition expressions.
+//!
+//! A small expression language for `HookCondition::Expr`:
+//!
+//! ```text
+//! payload.tool == "shell" && session.channel == "telegram" && contains(payload.args.command, "rm")
+//! ```
+//!
+//! - Paths start at `payload`, `session`, `event`, `message` or `extra` and
+//!   walk with `.field` or `[index]`; a missing field is `null`.
+//! - Literals: strings, numbers, `true`, `false`, `null`, lists `[a, b]`.
+//! - Operators: `==`, `!=`, `<`, `<=`, `>`, `>=`, `in`, `!`, `&&`, `||`, parentheses.
+//! - Functions: `contains`, `startsWith`, `endsWith`, `matches` (literal regex),
+//!   `lower`, `upper`, `len`, `exists`.
+//!
+//! Expressions are compiled when they are deserialized, so a hook with a
+//! syntax error, an unknown root or function, a wrong argument count or an
+//! invalid regex fails to load rather than silently never firing.
+
+use anyhow::{anyhow, bail, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+use crate::types::HookContext;
+
+const ROOTS: &[&str] = &["payload", "session", "event", "message", "extra"];
+
+/// A compiled condition expression.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Expression {
+    source: String,
+    ast: Expr,
+}
+
+impl Expression {
+    pub fn compile(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let ast = parser.expr()?;
+        if let Some((token, col)) = parser.tokens.get(parser.pos) {
+            bail!("unexpected {} at column {}", token, col);
+        }
+        Ok(Self { source: source.to_string(), ast })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the expression holds for `ctx`.
+    pub fn evaluate(&self, ctx: &HookContext) -> bool {
+        truthy(&self.value(&environment(ctx)))
+    }
+
+    /// Value of the expression against an environment whose top-level keys
+    /// are the path roots.
+    pub fn value(&self, env: &Value) -> Value {
+        eval(&self.ast, env)
+    }
+}
+
+impl TryFrom<String> for Expression {
+    type Error = anyhow::Error;
+
+    fn try_from(source: String) -> Result<Self> {
+        Self::compile(&source).map_err(|e| anyhow!("invalid hook expression `{}`: {}", source, e))
+    }
+}
+
+impl From<Expression> for String {
+    fn from(expr: Expression) -> String {
+        expr.source
+    }
+}
+
+impl fmt::Debug for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Expression({:?})", self.source)
+    }
+}
+
+fn environment(ctx: &HookContext) -> Value {
+    let extra = |key: &str| ctx.extra.get(key).cloned().unwrap_or(Value::Null);
+    serde_json::json!({
+        "payload": extra("payload"),
+        "session": extra("session"),
+        "event": ctx.event_name,
+        "message": ctx.message_text,
+        "extra": ctx.extra,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tokenizer
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Op(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "`{}`", name),
+            Token::Str(s) => write!(f, "{:?}", s),
+            Token::Num(n) => write!(f, "{}", n),
+            Token::Op(op) => write!(f, "`{}`", op),
+        }
+    }
+}
+
+const OPERATORS: &[&str] = &["==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "(", ")", "[", "]", ",", "."];
+
+/// Tokens with their 1-based column.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let col = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '"' || c == '\'' {
+            let mut s = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => bail!("unterminated string starting at column {}", col),
+                    Some(&q) if q == c => break,
+                    Some('\\') => {
+                        let escaped = chars.get(i + 1).ok_or_else(|| anyhow!("unterminated string starting at column {}", col))?;
+                        s.push(match escaped {
+                            'n' => '\n',
+                            't' => '\t',
+                            other => *other,
+                        });
+                        i += 2;
+                    }
+                    Some(&other) => {
+                        s.push(other);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            tokens.push((Token::Str(s), col));
+        } else if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) {
+            let start = i;
+            i += 1;
+            while chars.get(i).is_some_and(|c| c.is_ascii_digit() || *c == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text.parse().map_err(|_| anyhow!("invalid number `{}` at column {}", text, col))?;
+            tokens.push((Token::Num(n), col));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while chars.get(i).is_some_and(|c| c.is_alphanumeric() || *c == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), col));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| anyhow!("unexpected character `{}` at column {}", c, col))?;
+            i += op.len();
+            tokens.push((Token::Op(op), col));
+        }
+    }
+    Ok(tokens)
+}
+
+// ---------------------------------------------------------------------------
+// Parser
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Func {
+    Contains,
+    StartsWith,
+    EndsWith,
+    Lower,
+    Upper,
+    Len,
+    Exists,
+}
+
+impl Func {
+    fn lookup(name: &str) -> Option<(Self, usize)> {
+        Some(match name {
+            "contains" => (Self::Contains, 2),
+            "startsWith" => (Self::StartsWith, 2),
+            "endsWith" => (Self::EndsWith, 2),
+            "lower" => (Self::Lower, 1),
+            "upper" => (Self::Upper, 1),
+            "len" => (Self::Len, 1),
+            "exists" => (Self::Exists, 1),
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    List(Vec<Expr>),
+    Path(Vec<Segment>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(CmpOp, Box<Expr>, Box<Expr>),
+    Call(Func, Vec<Expr>),
+    Matches(Box<Expr>, Regex),
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, op: &str) -> Result<()> {
+        if self.eat(op) {
+            return Ok(());
+        }
+        match self.tokens.get(self.pos) {
+            Some((token, col)) => bail!("expected `{}` at column {}, found {}", op, col, token),
+            None => bail!("expected `{}` at end of expression", op),
+        }
+    }
+
+    fn next(&mut self) -> Result<(Token, usize)> {
+        let token = self.tokens.get(self.pos).cloned().ok_or_else(|| anyhow!("unexpected end of expression"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let mut left = self.and()?;
+        while self.eat("||") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut left = self.not()?;
+        while self.eat("&&") {
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = self.primary()?;
+        let op = match self.peek() {
+            Some(Token::Op("==")) => CmpOp::Eq,
+            Some(Token::Op("!=")) => CmpOp::Ne,
+            Some(Token::Op("<")) => CmpOp::Lt,
+            Some(Token::Op("<=")) => CmpOp::Le,
+            Some(Token::Op(">")) => CmpOp::Gt,
+            Some(Token::Op(">=")) => CmpOp::Ge,
+            Some(Token::Ident(word)) if word == "in" => CmpOp::In,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        Ok(Expr::Compare(op, Box::new(left), Box::new(self.primary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let (token, col) = self.next()?;
+        match token {
+            Token::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Token::Num(n) => Ok(Expr::Literal(serde_json::json!(n))),
+            Token::Op("(") => {
+                let inner = self.expr()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Token::Op("[") => {
+                let mut items = Vec::new();
+                if !self.eat("]") {
+                    loop {
+                        items.push(self.expr()?);
+                        if self.eat("]") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                Ok(Expr::List(items))
+            }
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ if self.eat("(") => self.call(&name, col),
+                _ if ROOTS.contains(&name.as_str()) => self.path(name),
+                _ => bail!("unknown name `{}` at column {}; paths start with one of {}", name, col, ROOTS.join(", ")),
+            },
+            other => bail!("unexpected {} at column {}", other, col),
+        }
+    }
+
+    fn call(&mut self, name: &str, col: usize) -> Result<Expr> {
+        let mut args = Vec::new();
+        if !self.eat(")") {
+            loop {
+                args.push(self.expr()?);
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+        if name == "matches" {
+            let [text, Expr::Literal(Value::String(pattern))] = <[Expr; 2]>::try_from(args)
+                .map_err(|_| anyhow!("matches() at column {} takes 2 arguments", col))?
+            else {
+                bail!("matches() at column {} needs a string literal pattern", col);
+            };
+            let regex = Regex::new(&pattern).map_err(|e| anyhow!("invalid regex in matches() at column {}: {}", col, e))?;
+            return Ok(Expr::Matches(Box::new(text), regex));
+        }
+        let (func, arity) = Func::lookup(name).ok_or_else(|| anyhow!("unknown function `{}` at column {}", name, col))?;
+        if args.len() != arity {
+            bail!("{}() at column {} takes {} argument(s), got {}", name, col, arity, args.len());
+        }
+        Ok(Expr::Call(func, args))
+    }
+
+    fn path(&mut self, root: String) -> Result<Expr> {
+        let mut segments = vec![Segment::Field(root)];
+        loop {
+            if self.eat(".") {
+                match self.next()? {
+                    (Token::Ident(field), _) => segments.push(Segment::Field(field)),
+                    (other, col) => bail!("expected a field name at column {}, found {}", col, other),
+                }
+            } else if self.eat("[") {
+                match self.next()? {
+                    (Token::Str(field), _) => segments.push(Segment::Field(field)),
+                    (Token::Num(n), _) if n >= 0.0 && n.fract() == 0.0 => segments.push(Segment::Index(n as usize)),
+                    (other, col) => bail!("expected a string or index at column {}, found {}", col, other),
+                }
+                self.expect("]")?;
+            } else {
+                return Ok(Expr::Path(segments));
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Evaluation
+// ---------------------------------------------------------------------------
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+/// Equality that treats `1` and `1.0` as the same number.
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn as_text(value: &Value) -> Option<&str> {
+    value.as_str()
+}
+
+fn eval(expr: &Expr, env: &Value) -> Value {
+    match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::List(items) => Value::Array(items.iter().map(|e| eval(e, env)).collect()),
+        Expr::Path(segments) => {
+            let mut current = env;
+            for segment in segments {
+                let next = match segment {
+                    Segment::Field(field) => current.get(field),
+                    Segment::Index(index) => current.get(index),
+                };
+                match next {
+                    Some(value) => current = value,
+                    None => return Value::Null,
+                }
+            }
+            current.clone()
+        }
+        Expr::Not(inner) => Value::Bool(!truthy(&eval(inner, env))),
+        Expr::And(a, b) => Value::Bool(truthy(&eval(a, env)) && truthy(&eval(b, env))),
+        Expr::Or(a, b) => Value::Bool(truthy(&eval(a, env)) || truthy(&eval(b, env))),
+        Expr::Compare(op, a, b) => {
+            let (a, b) = (eval(a, env), eval(b, env));
+            let ordering = match (&a, &b) {
+                (Value::Number(x), Value::Number(y)) => x.as_f64().partial_cmp(&y.as_f64()),
+                (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+                _ => None,
+            };
+            Value::Bool(match op {
+                CmpOp::Eq => equal(&a, &b),
+                CmpOp::Ne => !equal(&a, &b),
+                CmpOp::Lt => ordering.is_some_and(|o| o.is_lt()),
+                CmpOp::Le => ordering.is_some_and(|o| o.is_le()),
+                CmpOp::Gt => ordering.is_some_and(|o| o.is_gt()),
+                CmpOp::Ge => ordering.is_some_and(|o| o.is_ge()),
+                CmpOp::In => match &b {
+                    Value::Array(items) => items.iter().any(|item| equal(item, &a)),
+                    Value::String(s) => as_text(&a).is_some_and(|needle| s.contains(needle)),
+                    Value::Object(map) => as_text(&a).is_some_and(|key| map.contains_key(key)),
+                    _ => false,
+                },
+            })
+        }
+        Expr::Matches(text, regex) => Value::Bool(as_text(&eval(text, env)).is_some_and(|s| regex.is_match(s))),
+        Expr::Call(func, args) => {
+            let args: Vec<Value> = args.iter().map(|e| eval(e, env)).collect();
+            match func {
+                Func::Contains => Value::Bool(match (&args[0], &args[1]) {
+                    (Value::String(s), Value::String(needle)) => s.contains(needle.as_str()),
+                    (Value::Array(items), needle) => items.iter().any(|item| equal(item, needle)),
+                    _ => false,
+                }),
+                Func::StartsWith => Value::Bool(matches!((&args[0], &args[1]), (Value::String(s), Value::String(p)) if s.starts_with(p.as_str()))),
+                Func::EndsWith => Value::Bool(matches!((&args[0], &args[1]), (Value::String(s), Value::String(p)) if s.ends_with(p.as_str()))),
+                Func::Lower => as_text(&args[0]).map(|s| Value::String(s.to_lowercase())).unwrap_or(Value::Null),
+                Func::Upper => as_text(&args[0]).map(|s| Value::String(s.to_uppercase())).unwrap_or(Value::Null),
+                Func::Len => match &args[0] {
+                    Value::String(s) => serde_json::json!(s.chars().count()),
+                    Value::Array(items) => serde_json::json!(items.len()),
+                    Value::Object(map) => serde_json::json!(map.len()),
+                    _ => Value::Null,
+                },
+                Func::Exists => Value::Bool(!args[0].is_null()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{HookPayload, ToolCallPayload};
+
+    #[test]
+    fn expressions_compile_once_and_evaluate_against_hook_payloads() {
+        let payload = HookPayload::PreToolCall(ToolCallPayload {
+            session_id: "s1".into(),
+            tool_name: "shell".into(),
+            tool_input: serde_json::json!({"command": "rm -rf /tmp/x", "args": ["-v"]}),
+            tool_output: None,
+            is_error: false,
+        });
+        let ctx = HookContext::from_payload(&payload).with_session("channel", "telegram");
+
+        let expr = Expression::compile(
+            r#"payload.tool == "shell" && session.channel == "telegram" && contains(payload.args.command, "rm")"#,
+        )
+        .unwrap();
+        assert!(expr.evaluate(&ctx));
+        for (source, expected) in [
+            (r#"session.channel in ["slack", "discord"]"#, false),
+            (r#"!(payload.is_error) && len(payload.args.args) >= 1"#, true),
+            (r#"matches(payload.args.command, "^rm\\s+-rf")"#, true),
+            (r#"payload.args["command"] != null && !exists(payload.output)"#, true),
+            (r#"startsWith(lower(payload.tool), "sh") || event == "never""#, true),
+        ] {
+            assert_eq!(Expression::compile(source).unwrap().evaluate(&ctx), expected, "{}", source);
+        }
+
+        for (source, error) in [
+            (r#"payload.tool == "#, "end of expression"),
+            (r#"tool == "shell""#, "unknown name `tool`"),
+            (r#"startswith(payload.tool, "s")"#, "unknown function"),
+            (r#"contains(payload.tool)"#, "takes 2 argument(s)"),
+            (r#"matches(payload.tool, "(")"#, "invalid regex"),
+        ] {
+            let err = Expression::compile(source).unwrap_err().to_string();
+            assert!(err.contains(error), "{}: {}", source, err);
+        }
+
+        // Loading a hook condition validates its expression.
+        let condition: Result<crate::types::HookCondition, _> =
+            serde_json::from_value(serde_json::json!({"op": "expr", "expr": "payload.tool = \"shell\""}));
+        assert!(condition.unwrap_err().to_string().contains("invalid hook expression"));
+    }
+}
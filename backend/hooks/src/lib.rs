@@ -1,14 +1,26 @@
 pub mod builtin;
 pub mod evaluator;
+pub mod expr;
 pub mod pipeline;
 pub mod registry;
+pub mod script;
+pub mod trace;
 pub mod types;
+pub mod webhook;
 
-pub use builtin::{ChannelModelOverrideHook, ContentFilterHook, LoggingHook, ToolPolicyHook};
+pub use builtin::{
+    ChannelModelOverrideHook, ContentFilterHook, LeakAction, LoggingHook, NotificationTemplateHook, SecretLeakHook,
+    ToolPolicyHook, NOTIFICATION_VARIABLES,
+};
 pub use pipeline::HookPipeline;
-pub use registry::{Hook, HookRegistry};
+pub use registry::{ConditionalHook, Hook, HookRegistry};
 pub use evaluator::should_fire;
+pub use expr::Expression;
+pub use script::{ScriptHook, ScriptHookConfig};
+pub use trace::{HookChange, HookEvaluation, HookTracer, TraceMode, TraceOutcome};
+pub use webhook::{WebhookHook, WebhookHookConfig};
 pub use types::{
-    CompactionPayload, HookCondition, HookContext, HookPayload, HookPhase, HookResult, HookTrigger,
+    CompactionPayload, FailurePolicy, HookCondition, HookContext, HookPayload, HookPhase, HookResponse, HookResult,
+    HookTrigger,
     MessagePayload, ModelOverridePayload, SessionPayload, ToolCallPayload,
 };
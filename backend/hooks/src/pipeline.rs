@@ -7,9 +7,14 @@
 /// 3. Tool finishes → `pipeline.after_tool_call(...)`
 /// 4. Compaction starts → `pipeline.pre_compaction(...)`
 /// etc.
+///
+/// With a tracer attached, runs are traced while its mode is on; use
+/// `for_run` to key the trace by run id instead of session id.
+use std::sync::Arc;
 use tracing::debug;
 
 use crate::registry::HookRegistry;
+use crate::trace::{HookTracer, TraceMode};
 use crate::types::{
     CompactionPayload, HookPayload, HookResult, MessagePayload, ModelOverridePayload,
     SessionPayload, ToolCallPayload,
@@ -19,21 +24,43 @@ use crate::types::{
 #[derive(Clone)]
 pub struct HookPipeline {
     pub registry: HookRegistry,
+    tracer: Option<Arc<HookTracer>>,
+    run_id: Option<String>,
 }
 
 impl HookPipeline {
     pub fn new(registry: HookRegistry) -> Self {
-        Self { registry }
+        Self { registry, tracer: None, run_id: None }
+    }
+
+    pub fn with_tracer(mut self, tracer: Arc<HookTracer>) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    /// A pipeline whose traces are recorded under `run_id`.
+    pub fn for_run(&self, run_id: impl Into<String>) -> Self {
+        Self { run_id: Some(run_id.into()), ..self.clone() }
+    }
+
+    async fn run(&self, payload: HookPayload) -> HookResult {
+        match self.tracer.as_deref().filter(|tracer| tracer.mode() != TraceMode::Off) {
+            Some(tracer) => {
+                let run_id = self.run_id.clone().unwrap_or_else(|| session_of(&payload).to_string());
+                self.registry.run_traced(&payload, tracer, &run_id).await
+            }
+            None => self.registry.run(&payload).await,
+        }
     }
 
     pub async fn pre_message(&self, payload: MessagePayload) -> HookResult {
         debug!("[Pipeline] pre_message session={}", payload.session_id);
-        self.registry.run(&HookPayload::PreMessage(payload)).await
+        self.run(HookPayload::PreMessage(payload)).await
     }
 
     pub async fn post_message(&self, payload: MessagePayload) -> HookResult {
         debug!("[Pipeline] post_message session={}", payload.session_id);
-        self.registry.run(&HookPayload::PostMessage(payload)).await
+        self.run(HookPayload::PostMessage(payload)).await
     }
 
     pub async fn pre_tool_call(&self, payload: ToolCallPayload) -> HookResult {
@@ -41,7 +68,7 @@ impl HookPipeline {
             "[Pipeline] pre_tool_call tool={} session={}",
             payload.tool_name, payload.session_id
         );
-        self.registry.run(&HookPayload::PreToolCall(payload)).await
+        self.run(HookPayload::PreToolCall(payload)).await
     }
 
     pub async fn after_tool_call(&self, payload: ToolCallPayload) -> HookResult {
@@ -49,31 +76,41 @@ impl HookPipeline {
             "[Pipeline] after_tool_call tool={} session={}",
             payload.tool_name, payload.session_id
         );
-        self.registry.run(&HookPayload::AfterToolCall(payload)).await
+        self.run(HookPayload::AfterToolCall(payload)).await
     }
 
     pub async fn pre_compaction(&self, payload: CompactionPayload) -> HookResult {
         debug!("[Pipeline] pre_compaction session={}", payload.session_id);
-        self.registry.run(&HookPayload::PreCompaction(payload)).await
+        self.run(HookPayload::PreCompaction(payload)).await
     }
 
     pub async fn post_compaction(&self, payload: CompactionPayload) -> HookResult {
         debug!("[Pipeline] post_compaction session={}", payload.session_id);
-        self.registry.run(&HookPayload::PostCompaction(payload)).await
+        self.run(HookPayload::PostCompaction(payload)).await
     }
 
     pub async fn session_start(&self, payload: SessionPayload) -> HookResult {
         debug!("[Pipeline] session_start session={}", payload.session_id);
-        self.registry.run(&HookPayload::SessionStart(payload)).await
+        self.run(HookPayload::SessionStart(payload)).await
     }
 
     pub async fn session_end(&self, payload: SessionPayload) -> HookResult {
         debug!("[Pipeline] session_end session={}", payload.session_id);
-        self.registry.run(&HookPayload::SessionEnd(payload)).await
+        self.run(HookPayload::SessionEnd(payload)).await
     }
 
     pub async fn model_override(&self, payload: ModelOverridePayload) -> HookResult {
         debug!("[Pipeline] model_override session={}", payload.session_id);
-        self.registry.run(&HookPayload::ModelOverride(payload)).await
+        self.run(HookPayload::ModelOverride(payload)).await
+    }
+}
+
+fn session_of(payload: &HookPayload) -> &str {
+    match payload {
+        HookPayload::PreMessage(p) | HookPayload::PostMessage(p) => &p.session_id,
+        HookPayload::PreToolCall(p) | HookPayload::AfterToolCall(p) => &p.session_id,
+        HookPayload::PreCompaction(p) | HookPayload::PostCompaction(p) => &p.session_id,
+        HookPayload::SessionStart(p) | HookPayload::SessionEnd(p) => &p.session_id,
+        HookPayload::ModelOverride(p) => &p.session_id,
     }
 }
@@ -2,15 +2,19 @@
 ///
 /// Hooks are async functions that observe or transform pipeline data.
 /// Multiple hooks can be registered per phase; they run sequentially in
-/// registration order. The first hook to return `abort: true` halts the chain.
+/// registration order, each seeing the content as rewritten by the ones
+/// before it. The first hook to return `abort: true` halts the chain.
 use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::RwLock;
 use tracing::{debug, warn};
 
-use crate::types::{HookPayload, HookPhase, HookResult};
+use crate::evaluator::should_fire;
+use crate::trace::{HookEvaluation, HookTracer, TraceMode};
+use crate::types::{HookContext, HookPayload, HookPhase, HookResult, HookTrigger};
 
 // ---------------------------------------------------------------------------
 // Hook trait
@@ -24,6 +28,49 @@ pub trait Hook: Send + Sync {
 
     /// Run the hook. Return `HookResult::pass()` to continue normally.
     async fn run(&self, payload: &HookPayload) -> Result<HookResult>;
+
+    /// Whether the hook would act on this payload. Only tracing asks; `run`
+    /// still has to pass on payloads it doesn't match.
+    fn matches(&self, _payload: &HookPayload) -> bool {
+        true
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Conditional hooks
+// ---------------------------------------------------------------------------
+
+/// Runs `inner` only when `trigger` fires for the payload (see `should_fire`).
+pub struct ConditionalHook {
+    trigger: HookTrigger,
+    inner: Arc<dyn Hook>,
+}
+
+impl ConditionalHook {
+    /// Fails if the trigger has an invalid regex; expressions are already
+    /// compiled when the trigger is deserialized.
+    pub fn new(trigger: HookTrigger, inner: Arc<dyn Hook>) -> Result<Self> {
+        trigger.validate()?;
+        Ok(Self { trigger, inner })
+    }
+}
+
+#[async_trait]
+impl Hook for ConditionalHook {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn run(&self, payload: &HookPayload) -> Result<HookResult> {
+        if !self.matches(payload) {
+            return Ok(HookResult::pass());
+        }
+        self.inner.run(payload).await
+    }
+
+    fn matches(&self, payload: &HookPayload) -> bool {
+        should_fire(&self.trigger, &HookContext::from_payload(payload)) && self.inner.matches(payload)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -53,19 +100,47 @@ impl HookRegistry {
     /// Run all hooks registered for the phase in the given payload.
     /// Returns the merged `HookResult` after running the chain.
     pub async fn run(&self, payload: &HookPayload) -> HookResult {
+        self.run_chain(payload, None).await
+    }
+
+    /// Like `run`, recording each hook's evaluation under `run_id`. In
+    /// dry-run mode the whole chain runs and the merged result is discarded.
+    pub async fn run_traced(&self, payload: &HookPayload, tracer: &HookTracer, run_id: &str) -> HookResult {
+        self.run_chain(payload, Some((tracer, run_id))).await
+    }
+
+    async fn run_chain(&self, payload: &HookPayload, trace: Option<(&HookTracer, &str)>) -> HookResult {
         let phase = payload.phase();
+        let dry_run = trace.is_some_and(|(tracer, _)| tracer.mode() == TraceMode::DryRun);
         let map = self.hooks.read().await;
         let Some(chain) = map.get(&phase) else {
             return HookResult::pass();
         };
 
         let mut merged = HookResult::pass();
+        let mut rewritten: Option<HookPayload> = None;
         for hook in chain.iter() {
+            let payload = rewritten.as_ref().unwrap_or(payload);
+            if let Some((tracer, run_id)) = trace {
+                if !hook.matches(payload) {
+                    tracer.record(run_id, HookEvaluation::skipped(hook.name(), phase.clone()));
+                    continue;
+                }
+            }
             debug!("[Hooks] Running {} for phase {:?}", hook.name(), phase);
-            match hook.run(payload).await {
+            let started = Instant::now();
+            let outcome = hook.run(payload).await;
+            if let Some((tracer, run_id)) = trace {
+                tracer.record(run_id, HookEvaluation::of(hook.name(), payload, &outcome, started.elapsed()));
+            }
+            if dry_run {
+                continue;
+            }
+            match outcome {
                 Ok(result) => {
                     // Propagate content transform
                     if let Some(content) = &result.modified_content {
+                        rewritten = Some(payload.with_content(content));
                         merged.modified_content = Some(content.clone());
                     }
                     // Propagate model override
@@ -85,6 +160,10 @@ impl HookRegistry {
                 }
             }
         }
-        merged
+        if dry_run {
+            HookResult::pass()
+        } else {
+            merged
+        }
     }
 }
@@ -0,0 +1,289 @@
+//! Script hook — runs a program in a sandbox for each configured phase.
+//!
+//! The `HookPayload` is written to the program's stdin as JSON, with the
+//! phase also in `CLAWFORGE_HOOK_PHASE`. Its stdout is read as the same
+//! response webhooks answer (`{"decision": "deny", "reason": ...}`,
+//! `{"content": ...}`, `{"model": ...}`); empty stdout allows the call.
+//!
+//! Scripts run through the `SandboxRegistry`, in a sandbox per hook
+//! (`bwrap` unless configured otherwise) with `cwd` mounted. Before each
+//! run the command line goes through the same gate as sandboxed exec:
+//! dangerous patterns are refused, and so is anything the operator's exec
+//! allowlist does not explicitly allow, since nobody is there to ask. A
+//! refusal, non-zero exit, timeout or unparseable output is a failure and
+//! follows `on_failure`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use clawforge_sandbox::{ApprovalLevel, ApprovalVerdict, DockerSandboxConfig, ExecAllowlist, ExecApprovalAnalyzer, SandboxRegistry};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::registry::Hook;
+use crate::types::{FailurePolicy, HookPayload, HookPhase, HookResponse, HookResult};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptHookConfig {
+    pub name: String,
+    /// Program and arguments; no shell is involved.
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// Phases to run on; empty runs on every phase.
+    #[serde(default)]
+    pub phases: Vec<HookPhase>,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default)]
+    pub on_failure: FailurePolicy,
+    /// Sandbox driver the script runs under.
+    #[serde(default = "default_driver")]
+    pub driver: String,
+}
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_driver() -> String {
+    "bwrap".to_string()
+}
+
+/// Feeds the payload to the script's stdin from the environment, after
+/// changing to the hook's directory (`$1`).
+const RUNNER: &str = r#"cd "$1" || exit 1; shift; printf '%s' "$CLAWFORGE_HOOK_PAYLOAD" | "$@""#;
+
+pub struct ScriptHook {
+    config: ScriptHookConfig,
+    sandboxes: Arc<SandboxRegistry>,
+    /// Registry key of this hook's sandbox.
+    session: String,
+    /// Held while checking for and starting the sandbox.
+    starting: Mutex<()>,
+    allowlist: ExecAllowlist,
+    analyzer: ExecApprovalAnalyzer,
+}
+
+impl ScriptHook {
+    pub fn new(config: ScriptHookConfig, sandboxes: Arc<SandboxRegistry>) -> Result<Self> {
+        if config.command.is_empty() {
+            bail!("script hook '{}' has an empty command", config.name);
+        }
+        Ok(Self {
+            session: format!("hook:{}", config.name),
+            config,
+            sandboxes,
+            starting: Mutex::new(()),
+            allowlist: ExecAllowlist::with_safe_defaults(),
+            analyzer: ExecApprovalAnalyzer::default(),
+        })
+    }
+
+    /// Only scripts this allows are run; denied and unlisted ones are refused.
+    pub fn with_allowlist(mut self, allowlist: ExecAllowlist) -> Self {
+        self.allowlist = allowlist;
+        self
+    }
+
+    fn wants(&self, phase: &HookPhase) -> bool {
+        self.config.phases.is_empty() || self.config.phases.contains(phase)
+    }
+
+    fn check_command(&self) -> Result<()> {
+        let line = self.config.command.join(" ");
+        if let ApprovalVerdict::Blocked { reason } = self.analyzer.analyze(&line) {
+            bail!("refusing to run `{}`: {}", line, reason);
+        }
+        match self.allowlist.evaluate(&line) {
+            ApprovalLevel::Allow => Ok(()),
+            ApprovalLevel::Deny => bail!("refusing to run `{}`: denied by the exec allowlist", line),
+            ApprovalLevel::Ask => bail!("refusing to run `{}`: not allowed by the exec allowlist", line),
+        }
+    }
+
+    async fn ensure_sandbox(&self) -> Result<()> {
+        let _starting = self.starting.lock().await;
+        if self.sandboxes.has_sandbox(&self.session).await {
+            return Ok(());
+        }
+        let dir = self.config.cwd.as_ref().map(|d| d.display().to_string());
+        let config = DockerSandboxConfig { workspace_mount: dir.map(|d| (d.clone(), d)), ..Default::default() };
+        self.sandboxes.start_session(&self.session, &self.config.driver, &config).await?;
+        Ok(())
+    }
+
+    async fn call(&self, payload: &HookPayload) -> Result<HookResult> {
+        self.check_command()?;
+        self.ensure_sandbox().await.context("failed to start the hook sandbox")?;
+        let phase = serde_json::to_value(payload.phase())?;
+        let env = HashMap::from([
+            ("CLAWFORGE_HOOK_PAYLOAD".to_string(), serde_json::to_string(payload)?),
+            ("CLAWFORGE_HOOK_PHASE".to_string(), phase.as_str().unwrap_or_default().to_string()),
+        ]);
+        let dir = self.config.cwd.as_ref().map(|d| d.display().to_string()).unwrap_or_else(|| ".".to_string());
+        let mut argv = vec!["sh", "-c", RUNNER, "hook", dir.as_str()];
+        argv.extend(self.config.command.iter().map(String::as_str));
+
+        let secs = self.config.timeout_ms.div_ceil(1000).max(1);
+        let run = self.sandboxes.exec_with_env(&self.session, &argv, &env, Some(secs));
+        let output = tokio::time::timeout(Duration::from_millis(self.config.timeout_ms), run)
+            .await
+            .map_err(|_| anyhow!("timed out after {}ms", self.config.timeout_ms))??;
+        if output.timed_out {
+            bail!("timed out after {}ms", self.config.timeout_ms);
+        }
+        if output.exit_code != 0 {
+            bail!("exited with {}: {}", output.exit_code, output.stderr.trim());
+        }
+        if output.stdout.trim().is_empty() {
+            return Ok(HookResult::pass());
+        }
+        serde_json::from_str::<HookResponse>(&output.stdout).context("invalid hook output")?.into_result()
+    }
+}
+
+#[async_trait]
+impl Hook for ScriptHook {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn matches(&self, payload: &HookPayload) -> bool {
+        self.wants(&payload.phase())
+    }
+
+    async fn run(&self, payload: &HookPayload) -> Result<HookResult> {
+        if !self.wants(&payload.phase()) {
+            return Ok(HookResult::pass());
+        }
+        match self.call(payload).await {
+            Ok(result) => Ok(result),
+            Err(e) => Ok(self.config.on_failure.apply(&self.config.name, &e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MessagePayload;
+    use async_trait::async_trait;
+    use clawforge_sandbox::{AllowlistEntry, ContainerExecResult, ResourceUsage, SandboxDriver};
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    /// Runs commands on the host, with the exec's env vars set.
+    struct HostDriver;
+
+    #[async_trait]
+    impl SandboxDriver for HostDriver {
+        fn kind(&self) -> &str {
+            "host"
+        }
+        async fn start(&mut self, session_id: &str) -> Result<String> {
+            Ok(session_id.to_string())
+        }
+        async fn exec(&self, command: &[&str], timeout_secs: Option<u64>) -> Result<ContainerExecResult> {
+            self.exec_with_env(command, &HashMap::new(), timeout_secs).await
+        }
+        async fn exec_with_env(
+            &self,
+            command: &[&str],
+            env: &HashMap<String, String>,
+            _timeout_secs: Option<u64>,
+        ) -> Result<ContainerExecResult> {
+            let output = tokio::process::Command::new(command[0]).args(&command[1..]).envs(env).kill_on_drop(true).output().await?;
+            Ok(ContainerExecResult {
+                exit_code: output.status.code().unwrap_or(-1).into(),
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                ..Default::default()
+            })
+        }
+        async fn copy_in(&self, _host_path: &str, _sandbox_path: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn copy_out(&self, _sandbox_path: &str, _host_path: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn stop(&mut self) -> Result<()> {
+            Ok(())
+        }
+        async fn resource_usage(&self) -> Result<ResourceUsage> {
+            Ok(ResourceUsage::default())
+        }
+    }
+
+    fn allow(pattern: String, level: ApprovalLevel) -> AllowlistEntry {
+        AllowlistEntry { pattern, level, reason: None, added_at: None, scope: "session".into() }
+    }
+
+    fn hook(dir: &Path, name: &str, script: &str, on_failure: FailurePolicy) -> ScriptHook {
+        let path = dir.join(format!("{name}.sh"));
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let sandboxes = SandboxRegistry::new().with_driver("host", Arc::new(|_: &DockerSandboxConfig| Box::new(HostDriver) as Box<dyn SandboxDriver>));
+        let mut allowlist = ExecAllowlist::default();
+        allowlist.upsert(allow(format!("{}/*", dir.display()), ApprovalLevel::Allow));
+        ScriptHook::new(
+            ScriptHookConfig {
+                name: name.into(),
+                command: vec![path.display().to_string()],
+                cwd: Some(dir.to_path_buf()),
+                phases: vec![HookPhase::PreMessage],
+                timeout_ms: 500,
+                on_failure,
+                driver: "host".into(),
+            },
+            Arc::new(sandboxes),
+        )
+        .unwrap()
+        .with_allowlist(allowlist)
+    }
+
+    #[tokio::test]
+    async fn scripts_read_payloads_and_answer_through_the_exec_gate() {
+        let dir = std::env::temp_dir().join(format!("clawforge-script-hook-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let message = HookPayload::PreMessage(MessagePayload {
+            session_id: "s1".into(),
+            channel: "telegram".into(),
+            role: "user".into(),
+            content: "hello".into(),
+            metadata: serde_json::Value::Null,
+        });
+
+        let rewrite = hook(
+            &dir,
+            "rewrite",
+            r#"grep -q '"content":"hello"' && [ "$CLAWFORGE_HOOK_PHASE" = pre_message ] && echo '{"content":"HELLO"}'"#,
+            FailurePolicy::Closed,
+        );
+        assert_eq!(rewrite.run(&message).await.unwrap().modified_content.as_deref(), Some("HELLO"));
+
+        let deny = hook(&dir, "deny", r#"echo '{"decision":"deny","reason":"quiet hours"}'"#, FailurePolicy::Open);
+        assert_eq!(deny.run(&message).await.unwrap().reason.as_deref(), Some("quiet hours"));
+
+        assert!(!hook(&dir, "drain", "cat >/dev/null", FailurePolicy::Closed).run(&message).await.unwrap().abort);
+        assert!(hook(&dir, "fail", "exit 3", FailurePolicy::Closed).run(&message).await.unwrap().abort);
+        assert!(!hook(&dir, "fail", "exit 3", FailurePolicy::Open).run(&message).await.unwrap().abort);
+        assert!(hook(&dir, "slow", "sleep 5", FailurePolicy::Closed).run(&message).await.unwrap().abort);
+
+        // The exec gate refuses before anything runs.
+        let mut blocked = hook(&dir, "noop", "true", FailurePolicy::Closed);
+        blocked.config.command = vec!["sudo".into(), "touch".into(), "/tmp/never".into()];
+        assert!(blocked.run(&message).await.unwrap().reason.unwrap().contains("Dangerous pattern"));
+        let mut denylist = ExecAllowlist::default();
+        denylist.upsert(allow(format!("{}/*", dir.display()), ApprovalLevel::Deny));
+        let denied = hook(&dir, "noop", "true", FailurePolicy::Closed).with_allowlist(denylist).run(&message).await.unwrap();
+        assert!(denied.reason.unwrap().contains("denied by the exec allowlist"));
+        let unlisted = hook(&dir, "noop", "true", FailurePolicy::Closed).with_allowlist(ExecAllowlist::with_safe_defaults()).run(&message).await.unwrap();
+        assert!(unlisted.reason.unwrap().contains("not allowed by the exec allowlist"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+//! Hook tracing — records every hook evaluation per run.
+//!
+//! With tracing on, the registry records for each hook in the chain whether
+//! it matched the payload, how long it took, what it did and how it changed
+//! the payload, keyed by run id. Dry-run mode records the same but discards
+//! the merged result, so hooks can be tried against live traffic without
+//! blocking or rewriting anything (side effects such as webhook calls still
+//! happen). Only the most recent runs are kept.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::types::{HookPayload, HookPhase, HookResult};
+
+const DEFAULT_CAPACITY: usize = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TraceMode {
+    #[default]
+    Off,
+    On,
+    /// Trace, but let every message and tool call through unchanged.
+    DryRun,
+}
+
+impl TraceMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "on" => Some(Self::On),
+            "dry-run" | "dryrun" => Some(Self::DryRun),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceOutcome {
+    /// The hook's trigger didn't match; it never ran.
+    Skipped,
+    Passed,
+    Modified,
+    Aborted,
+    Errored,
+}
+
+/// One field a hook rewrote.
+#[derive(Debug, Clone, Serialize)]
+pub struct HookChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HookEvaluation {
+    pub hook: String,
+    pub phase: HookPhase,
+    pub matched: bool,
+    pub duration_ms: f64,
+    pub outcome: TraceOutcome,
+    /// Abort reason or error message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<HookChange>,
+    pub at: DateTime<Utc>,
+}
+
+impl HookEvaluation {
+    pub fn skipped(hook: &str, phase: HookPhase) -> Self {
+        Self {
+            hook: hook.to_string(),
+            phase,
+            matched: false,
+            duration_ms: 0.0,
+            outcome: TraceOutcome::Skipped,
+            reason: None,
+            changes: Vec::new(),
+            at: Utc::now(),
+        }
+    }
+
+    pub fn of(hook: &str, payload: &HookPayload, result: &anyhow::Result<HookResult>, took: Duration) -> Self {
+        let mut changes = Vec::new();
+        let (outcome, reason) = match result {
+            Err(e) => (TraceOutcome::Errored, Some(e.to_string())),
+            Ok(result) => {
+                if let Some(content) = &result.modified_content {
+                    changes.push(HookChange { field: "content".into(), before: content_of(payload), after: content.clone() });
+                }
+                if let Some(model) = &result.model_override {
+                    let before = match payload {
+                        HookPayload::ModelOverride(p) => Some(p.requested_model.clone()),
+                        _ => None,
+                    };
+                    changes.push(HookChange { field: "model".into(), before, after: model.clone() });
+                }
+                let outcome = if result.abort {
+                    TraceOutcome::Aborted
+                } else if changes.is_empty() {
+                    TraceOutcome::Passed
+                } else {
+                    TraceOutcome::Modified
+                };
+                (outcome, result.reason.clone())
+            }
+        };
+        Self {
+            hook: hook.to_string(),
+            phase: payload.phase(),
+            matched: true,
+            duration_ms: took.as_secs_f64() * 1000.0,
+            outcome,
+            reason,
+            changes,
+            at: Utc::now(),
+        }
+    }
+}
+
+fn content_of(payload: &HookPayload) -> Option<String> {
+    match payload {
+        HookPayload::PreMessage(p) | HookPayload::PostMessage(p) => Some(p.content.clone()),
+        HookPayload::PreCompaction(p) | HookPayload::PostCompaction(p) => p.summary.clone(),
+        _ => None,
+    }
+}
+
+/// Recent hook traces, shared by the pipeline, the API and `/hooks trace`.
+pub struct HookTracer {
+    mode: Mutex<TraceMode>,
+    runs: Mutex<VecDeque<(String, Vec<HookEvaluation>)>>,
+    capacity: usize,
+}
+
+impl Default for HookTracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HookTracer {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Keep traces for at most `capacity` runs.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { mode: Mutex::new(TraceMode::Off), runs: Mutex::new(VecDeque::new()), capacity: capacity.max(1) }
+    }
+
+    pub fn mode(&self) -> TraceMode {
+        *self.mode.lock().unwrap()
+    }
+
+    pub fn set_mode(&self, mode: TraceMode) {
+        *self.mode.lock().unwrap() = mode;
+    }
+
+    pub fn record(&self, run_id: &str, evaluation: HookEvaluation) {
+        let mut runs = self.runs.lock().unwrap();
+        if let Some((_, trace)) = runs.iter_mut().find(|(id, _)| id == run_id) {
+            trace.push(evaluation);
+            return;
+        }
+        if runs.len() == self.capacity {
+            runs.pop_front();
+        }
+        runs.push_back((run_id.to_string(), vec![evaluation]));
+    }
+
+    pub fn trace(&self, run_id: &str) -> Option<Vec<HookEvaluation>> {
+        self.runs.lock().unwrap().iter().find(|(id, _)| id == run_id).map(|(_, trace)| trace.clone())
+    }
+
+    /// Traced run ids, most recent first.
+    pub fn runs(&self) -> Vec<String> {
+        self.runs.lock().unwrap().iter().rev().map(|(id, _)| id.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builtin::ContentFilterHook;
+    use crate::pipeline::HookPipeline;
+    use crate::registry::{ConditionalHook, HookRegistry};
+    use crate::types::{HookTrigger, MessagePayload};
+    use std::sync::Arc;
+
+    fn message(content: &str) -> MessagePayload {
+        MessagePayload {
+            session_id: "s1".into(),
+            channel: "telegram".into(),
+            role: "user".into(),
+            content: content.into(),
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn traces_matches_skips_and_dry_runs() {
+        let registry = HookRegistry::new();
+        let slack_only: HookTrigger =
+            serde_json::from_value(serde_json::json!({"type": "onCondition", "condition": {"op": "expr", "expr": "payload.channel == \"slack\""}}))
+                .unwrap();
+        let filter = Arc::new(ContentFilterHook::new(vec!["secret".into()]));
+        registry.register(HookPhase::PreMessage, Arc::new(ConditionalHook::new(slack_only, filter.clone()).unwrap())).await;
+        registry.register(HookPhase::PreMessage, filter).await;
+
+        let tracer = Arc::new(HookTracer::new());
+        let pipeline = HookPipeline::new(registry).with_tracer(tracer.clone());
+
+        // Off: nothing is recorded.
+        assert!(pipeline.for_run("r0").pre_message(message("the secret")).await.abort);
+        assert!(tracer.trace("r0").is_none());
+
+        tracer.set_mode(TraceMode::On);
+        assert!(pipeline.for_run("r1").pre_message(message("the secret")).await.abort);
+        let trace = tracer.trace("r1").unwrap();
+        assert_eq!(trace.len(), 2);
+        assert!(!trace[0].matched && trace[0].outcome == TraceOutcome::Skipped);
+        assert!(trace[1].matched && trace[1].outcome == TraceOutcome::Aborted);
+
+        tracer.set_mode(TraceMode::DryRun);
+        assert!(!pipeline.for_run("r2").pre_message(message("the secret")).await.abort);
+        assert_eq!(tracer.trace("r2").unwrap()[1].outcome, TraceOutcome::Aborted);
+
+        // Without a run id, traces are keyed by session.
+        pipeline.pre_message(message("hi")).await;
+        assert_eq!(tracer.runs(), ["s1", "r2", "r1"]);
+    }
+}
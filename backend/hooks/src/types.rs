@@ -4,6 +4,8 @@
 /// Hooks fire at specific points in the agent run pipeline.
 use serde::{Deserialize, Serialize};
 
+use crate::expr::Expression;
+
 // ---------------------------------------------------------------------------
 // Hook phases
 // ---------------------------------------------------------------------------
@@ -115,6 +117,19 @@ impl HookPayload {
             Self::ModelOverride(_) => HookPhase::ModelOverride,
         }
     }
+
+    /// The payload with its message text (or tool output) replaced by
+    /// `content`, so later hooks in a chain see what earlier ones rewrote.
+    /// Payloads without content are returned unchanged.
+    pub fn with_content(&self, content: &str) -> Self {
+        let mut payload = self.clone();
+        match &mut payload {
+            Self::PreMessage(p) | Self::PostMessage(p) => p.content = content.to_string(),
+            Self::PreToolCall(p) | Self::AfterToolCall(p) => p.tool_output = Some(serde_json::Value::String(content.to_string())),
+            _ => {}
+        }
+        payload
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -148,6 +163,55 @@ impl HookResult {
     }
 }
 
+/// What an external hook (webhook or script) answers; every field optional,
+/// so an empty object allows the call unchanged.
+#[derive(Debug, Default, Deserialize)]
+pub struct HookResponse {
+    /// `allow` (default) or `deny`.
+    #[serde(default)]
+    pub decision: Option<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Replacement message text.
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Model override for the run.
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl HookResponse {
+    pub fn into_result(self) -> anyhow::Result<HookResult> {
+        let abort = match self.decision.as_deref() {
+            None | Some("allow") => false,
+            Some("deny") => true,
+            Some(other) => anyhow::bail!("unknown decision `{}`", other),
+        };
+        Ok(HookResult { modified_content: self.content, model_override: self.model, abort, reason: self.reason })
+    }
+}
+
+/// What an external hook's failure (timeout, error, bad output) means.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Continue as if the hook passed.
+    #[default]
+    Open,
+    /// Abort the pipeline (block the message or tool call).
+    Closed,
+}
+
+impl FailurePolicy {
+    pub fn apply(self, hook: &str, error: &anyhow::Error) -> HookResult {
+        tracing::warn!("[Hooks] {} failed ({:?}): {}", hook, self, error);
+        match self {
+            FailurePolicy::Open => HookResult::pass(),
+            FailurePolicy::Closed => HookResult::abort(format!("Hook '{}' unavailable: {}", hook, error)),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Hook trigger (used by evaluator)
 // ---------------------------------------------------------------------------
@@ -173,6 +237,33 @@ pub enum HookCondition {
     And { conditions: Vec<HookCondition> },
     Or { conditions: Vec<HookCondition> },
     Not { condition: Box<HookCondition> },
+    /// Expression such as `payload.tool == "shell" && contains(payload.args.command, "rm")`;
+    /// see `crate::expr`.
+    Expr { expr: Expression },
+}
+
+impl HookTrigger {
+    /// Check what deserializing a trigger does not: `FieldMatches` regexes.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Self::OnCondition { condition } => condition.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl HookCondition {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Self::FieldMatches { regex, .. } => {
+                regex::Regex::new(regex).map_err(|e| anyhow::anyhow!("invalid regex `{}`: {}", regex, e))?;
+                Ok(())
+            }
+            Self::And { conditions } | Self::Or { conditions } => conditions.iter().try_for_each(Self::validate),
+            Self::Not { condition } => condition.validate(),
+            Self::FieldEquals { .. } | Self::FieldContains { .. } | Self::Expr { .. } => Ok(()),
+        }
+    }
 }
 
 /// Runtime context passed to the hook evaluator.
@@ -183,3 +274,50 @@ pub struct HookContext {
     #[serde(default)]
     pub extra: std::collections::HashMap<String, serde_json::Value>,
 }
+
+impl HookContext {
+    /// Context for a pipeline payload: `extra.payload` holds the payload
+    /// fields (tool calls also as `tool`, `args` and `output`) and
+    /// `extra.session` the session id and, where known, channel and agent.
+    pub fn from_payload(payload: &HookPayload) -> Self {
+        let mut fields = serde_json::to_value(payload).unwrap_or_default();
+        let event_name = fields.get("phase").and_then(|p| p.as_str()).map(str::to_string);
+        let mut session = serde_json::Map::new();
+        if let serde_json::Value::Object(map) = &mut fields {
+            map.remove("phase");
+            if let Some(id) = map.get("session_id") {
+                session.insert("id".to_string(), id.clone());
+            }
+            for key in ["channel", "agent_id"] {
+                if let Some(value) = map.get(key) {
+                    session.insert(key.to_string(), value.clone());
+                }
+            }
+        }
+        let mut message_text = None;
+        match payload {
+            HookPayload::PreToolCall(call) | HookPayload::AfterToolCall(call) => {
+                fields["tool"] = serde_json::json!(call.tool_name);
+                fields["args"] = call.tool_input.clone();
+                fields["output"] = call.tool_output.clone().unwrap_or_default();
+            }
+            HookPayload::PreMessage(message) | HookPayload::PostMessage(message) => {
+                message_text = Some(message.content.clone());
+            }
+            _ => {}
+        }
+        let mut extra = std::collections::HashMap::new();
+        extra.insert("payload".to_string(), fields);
+        extra.insert("session".to_string(), serde_json::Value::Object(session));
+        Self { event_name, message_text, extra }
+    }
+
+    /// Add a `session` field the payload does not carry, e.g. the channel of a tool call.
+    pub fn with_session(mut self, key: &str, value: impl Into<serde_json::Value>) -> Self {
+        let session = self.extra.entry("session".to_string()).or_insert_with(|| serde_json::json!({}));
+        if let serde_json::Value::Object(map) = session {
+            map.insert(key.to_string(), value.into());
+        }
+        self
+    }
+}
@@ -0,0 +1,259 @@
+//! Webhook hook — hands lifecycle phases to an external HTTP service.
+//!
+//! On each configured phase the `HookPayload` is POSTed as JSON to `url`,
+//! signed like forwarded events: `X-ClawForge-Timestamp` plus
+//! `X-ClawForge-Signature: sha256=<hex>`, an HMAC-SHA256 of
+//! `"<timestamp>.<body>"`. A non-blocking hook fires and forgets. A blocking
+//! hook waits up to `timeout_ms` and applies the response:
+//!
+//! ```json
+//! { "decision": "deny", "reason": "rm is not allowed here" }
+//! { "content": "rewritten message" }
+//! { "model": "anthropic/claude-sonnet-4" }
+//! ```
+//!
+//! An empty body allows the call unchanged. When the service times out,
+//! errors or answers with something unparseable, `on_failure: open` lets the
+//! pipeline continue and `on_failure: closed` aborts it.
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::registry::Hook;
+use crate::types::{FailurePolicy, HookPayload, HookPhase, HookResponse, HookResult};
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookHookConfig {
+    pub name: String,
+    pub url: String,
+    /// HMAC key. Prefer `secret_env` to keep it out of config files.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Environment variable holding the HMAC key.
+    #[serde(default)]
+    pub secret_env: Option<String>,
+    /// Phases to send; empty sends every phase.
+    #[serde(default)]
+    pub phases: Vec<HookPhase>,
+    /// Wait for the response and apply it.
+    #[serde(default)]
+    pub blocking: bool,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default)]
+    pub on_failure: FailurePolicy,
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+/// `sha256=<hex>` signature of a request body.
+pub fn sign_payload(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn phase_name(phase: &HookPhase) -> String {
+    serde_json::to_value(phase).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+}
+
+pub struct WebhookHook {
+    config: WebhookHookConfig,
+    secret: Option<Vec<u8>>,
+    client: reqwest::Client,
+}
+
+impl WebhookHook {
+    pub fn new(config: WebhookHookConfig) -> Result<Self> {
+        let secret = match (&config.secret, &config.secret_env) {
+            (Some(secret), _) => Some(secret.clone().into_bytes()),
+            (None, Some(var)) => {
+                Some(std::env::var(var).map_err(|_| anyhow!("webhook hook '{}': ${} is not set", config.name, var))?.into_bytes())
+            }
+            (None, None) => None,
+        };
+        let client = reqwest::Client::builder().timeout(Duration::from_millis(config.timeout_ms)).build()?;
+        Ok(Self { config, secret, client })
+    }
+
+    fn wants(&self, phase: &HookPhase) -> bool {
+        self.config.phases.is_empty() || self.config.phases.contains(phase)
+    }
+
+    fn request(&self, payload: &HookPayload) -> Result<reqwest::RequestBuilder> {
+        let body = serde_json::to_vec(payload)?;
+        let timestamp = Utc::now().timestamp();
+        let mut request = self
+            .client
+            .post(&self.config.url)
+            .header("Content-Type", "application/json")
+            .header("X-ClawForge-Hook", &self.config.name)
+            .header("X-ClawForge-Phase", phase_name(&payload.phase()))
+            .header("X-ClawForge-Timestamp", timestamp.to_string());
+        if let Some(secret) = &self.secret {
+            request = request.header("X-ClawForge-Signature", sign_payload(secret, timestamp, &body));
+        }
+        Ok(request.body(body))
+    }
+
+    async fn call(&self, request: reqwest::RequestBuilder) -> Result<HookResult> {
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("HTTP {}", status);
+        }
+        let body = response.bytes().await?;
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(HookResult::pass());
+        }
+        serde_json::from_slice::<HookResponse>(&body)?.into_result()
+    }
+}
+
+#[async_trait]
+impl Hook for WebhookHook {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn matches(&self, payload: &HookPayload) -> bool {
+        self.wants(&payload.phase())
+    }
+
+    async fn run(&self, payload: &HookPayload) -> Result<HookResult> {
+        if !self.wants(&payload.phase()) {
+            return Ok(HookResult::pass());
+        }
+        let request = self.request(payload)?;
+
+        if !self.config.blocking {
+            let name = self.config.name.clone();
+            tokio::spawn(async move {
+                match request.send().await {
+                    Ok(response) => debug!("[Webhook] {} answered {}", name, response.status()),
+                    Err(e) => warn!("[Webhook] {} delivery failed: {}", name, e),
+                }
+            });
+            return Ok(HookResult::pass());
+        }
+
+        match self.call(request).await {
+            Ok(result) => Ok(result),
+            Err(e) => Ok(self.config.on_failure.apply(&self.config.name, &e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ToolCallPayload;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer each connection with the next body (None: never answer), echoing requests back.
+    async fn serve(replies: Vec<Option<&'static str>>) -> (String, tokio::sync::mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            for reply in replies {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = String::new();
+                let mut buf = [0u8; 4096];
+                while request.split_once("\r\n\r\n").is_none_or(|(head, body)| body.len() < content_length(head)) {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.push_str(&String::from_utf8_lossy(&buf[..n]));
+                }
+                tx.send(request).await.unwrap();
+                match reply {
+                    Some(body) => {
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                            body.len(),
+                            body
+                        );
+                        socket.write_all(response.as_bytes()).await.unwrap();
+                    }
+                    None => held.push(socket),
+                }
+            }
+        });
+        (url, rx)
+    }
+
+    fn content_length(head: &str) -> usize {
+        head.lines()
+            .find_map(|line| line.split_once(": ").filter(|(k, _)| k.eq_ignore_ascii_case("content-length")))
+            .map_or(0, |(_, v)| v.parse().unwrap())
+    }
+
+    fn header<'a>(request: &'a str, name: &str) -> &'a str {
+        request
+            .lines()
+            .find_map(|line| line.split_once(": ").filter(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v))
+            .unwrap()
+    }
+
+    fn hook(url: &str, on_failure: FailurePolicy) -> WebhookHook {
+        WebhookHook::new(WebhookHookConfig {
+            name: "guard".into(),
+            url: url.into(),
+            secret: Some("s3cret".into()),
+            secret_env: None,
+            phases: vec![HookPhase::PreToolCall],
+            blocking: true,
+            timeout_ms: 200,
+            on_failure,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn blocking_webhooks_apply_decisions_and_failure_policy() {
+        let shell = HookPayload::PreToolCall(ToolCallPayload {
+            session_id: "s1".into(),
+            tool_name: "shell".into(),
+            tool_input: serde_json::json!({"command": "rm -rf /"}),
+            tool_output: None,
+            is_error: false,
+        });
+        let (url, mut requests) = serve(vec![Some(r#"{"decision":"deny","reason":"no rm"}"#), Some(""), None, None]).await;
+
+        let result = hook(&url, FailurePolicy::Open).run(&shell).await.unwrap();
+        assert!(result.abort);
+        assert_eq!(result.reason.as_deref(), Some("no rm"));
+        let request = requests.recv().await.unwrap();
+        let timestamp: i64 = header(&request, "x-clawforge-timestamp").parse().unwrap();
+        let body = request.split("\r\n\r\n").nth(1).unwrap();
+        assert_eq!(header(&request, "x-clawforge-signature"), sign_payload(b"s3cret", timestamp, body.as_bytes()));
+        assert_eq!(header(&request, "x-clawforge-phase"), "pre_tool_call");
+
+        assert!(!hook(&url, FailurePolicy::Closed).run(&shell).await.unwrap().abort);
+
+        // Phases the hook doesn't subscribe to never reach the service.
+        let session = HookPayload::ModelOverride(crate::types::ModelOverridePayload {
+            session_id: "s1".into(),
+            requested_model: "gpt".into(),
+        });
+        assert!(!hook(&url, FailurePolicy::Closed).run(&session).await.unwrap().abort);
+
+        // Timeouts: open passes, closed blocks.
+        assert!(!hook(&url, FailurePolicy::Open).run(&shell).await.unwrap().abort);
+        let closed = hook(&url, FailurePolicy::Closed).run(&shell).await.unwrap();
+        assert!(closed.abort && closed.reason.unwrap().contains("unavailable"));
+    }
+}
@@ -0,0 +1,238 @@
+//! Channel Adapter Status
+//!
+//! Live lifecycle state of every channel adapter, for `/status` and
+//! `GET /api/health`. Adapters report through an `AdapterReporter` handed out
+//! by the shared `AdapterStatusRegistry`: connection state, reconnect backoff,
+//! the last error, last inbound/outbound message times and how many inbound
+//! messages are waiting to be picked up.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "state")]
+pub enum AdapterState {
+    Starting,
+    Connected,
+    /// Waiting to reconnect after a failure.
+    Backoff { attempt: u32, retry_at: DateTime<Utc> },
+    Error,
+    Stopped,
+}
+
+impl AdapterState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Starting => "starting",
+            Self::Connected => "connected",
+            Self::Backoff { .. } => "backoff",
+            Self::Error => "error",
+            Self::Stopped => "stopped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdapterStatus {
+    pub name: String,
+    #[serde(flatten)]
+    pub state: AdapterState,
+    /// When the adapter entered its current state.
+    pub since: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub last_inbound_at: Option<DateTime<Utc>>,
+    pub last_outbound_at: Option<DateTime<Utc>>,
+    /// Inbound messages received but not yet handed to the supervisor.
+    pub queue_depth: usize,
+    pub inbound_count: u64,
+    pub outbound_count: u64,
+}
+
+impl AdapterStatus {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            state: AdapterState::Starting,
+            since: Utc::now(),
+            last_error: None,
+            last_inbound_at: None,
+            last_outbound_at: None,
+            queue_depth: 0,
+            inbound_count: 0,
+            outbound_count: 0,
+        }
+    }
+
+    /// One line for chat: `slack: connected · in 2m ago · out 10s ago · queue 0`.
+    pub fn summary(&self, now: DateTime<Utc>) -> String {
+        let ago = |at: Option<DateTime<Utc>>| match at {
+            Some(at) => format_ago(now - at),
+            None => "never".to_string(),
+        };
+        let mut line = format!(
+            "{}: {} · in {} · out {} · queue {}",
+            self.name,
+            self.state.label(),
+            ago(self.last_inbound_at),
+            ago(self.last_outbound_at),
+            self.queue_depth
+        );
+        if let AdapterState::Backoff { attempt, retry_at } = &self.state {
+            line.push_str(&format!(" · retry #{} in {}s", attempt, (*retry_at - now).num_seconds().max(0)));
+        }
+        if let (Some(error), AdapterState::Backoff { .. } | AdapterState::Error) = (&self.last_error, &self.state) {
+            line.push_str(&format!(" · {}", error));
+        }
+        line
+    }
+}
+
+fn format_ago(elapsed: chrono::Duration) -> String {
+    let secs = elapsed.num_seconds().max(0);
+    match secs {
+        0..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// Shared registry every adapter reports into.
+#[derive(Clone, Default)]
+pub struct AdapterStatusRegistry {
+    adapters: Arc<RwLock<BTreeMap<String, AdapterStatus>>>,
+}
+
+impl AdapterStatusRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `name` (state `starting`) and return its reporter.
+    pub fn reporter(&self, name: &str) -> AdapterReporter {
+        self.adapters.write().unwrap().entry(name.to_string()).or_insert_with(|| AdapterStatus::new(name));
+        AdapterReporter { name: name.to_string(), registry: self.clone() }
+    }
+
+    /// All adapters, by name.
+    pub fn snapshot(&self) -> Vec<AdapterStatus> {
+        self.adapters.read().unwrap().values().cloned().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<AdapterStatus> {
+        self.adapters.read().unwrap().get(name).cloned()
+    }
+
+    /// True when any adapter is in backoff or error.
+    pub fn is_degraded(&self) -> bool {
+        self.adapters
+            .read()
+            .unwrap()
+            .values()
+            .any(|a| matches!(a.state, AdapterState::Backoff { .. } | AdapterState::Error))
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut AdapterStatus)) {
+        let mut adapters = self.adapters.write().unwrap();
+        f(adapters.entry(name.to_string()).or_insert_with(|| AdapterStatus::new(name)));
+    }
+}
+
+/// Handle one adapter uses to report its state.
+#[derive(Clone)]
+pub struct AdapterReporter {
+    name: String,
+    registry: AdapterStatusRegistry,
+}
+
+impl AdapterReporter {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn set_state(&self, state: AdapterState, error: Option<String>) {
+        self.registry.update(&self.name, |s| {
+            if s.state != state {
+                s.since = Utc::now();
+            }
+            s.state = state;
+            if error.is_some() {
+                s.last_error = error;
+            }
+        });
+    }
+
+    pub fn starting(&self) {
+        self.set_state(AdapterState::Starting, None);
+    }
+
+    pub fn connected(&self) {
+        self.set_state(AdapterState::Connected, None);
+    }
+
+    pub fn backoff(&self, attempt: u32, delay: Duration) {
+        let retry_at = Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default();
+        self.set_state(AdapterState::Backoff { attempt, retry_at }, None);
+    }
+
+    pub fn error(&self, error: impl ToString) {
+        self.set_state(AdapterState::Error, Some(error.to_string()));
+    }
+
+    pub fn stopped(&self) {
+        self.set_state(AdapterState::Stopped, None);
+    }
+
+    pub fn inbound(&self) {
+        self.registry.update(&self.name, |s| {
+            s.last_inbound_at = Some(Utc::now());
+            s.inbound_count += 1;
+        });
+    }
+
+    pub fn outbound(&self) {
+        self.registry.update(&self.name, |s| {
+            s.last_outbound_at = Some(Utc::now());
+            s.outbound_count += 1;
+        });
+    }
+
+    pub fn queue_depth(&self, depth: usize) {
+        self.registry.update(&self.name, |s| s.queue_depth = depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reporters_update_the_shared_registry() {
+        let registry = AdapterStatusRegistry::new();
+        let slack = registry.reporter("slack");
+        let matrix = registry.reporter("matrix");
+        assert!(!registry.is_degraded());
+
+        slack.connected();
+        slack.inbound();
+        slack.outbound();
+        slack.queue_depth(3);
+        matrix.error("sync failed: 401");
+        matrix.backoff(2, Duration::from_secs(30));
+        assert!(registry.is_degraded());
+
+        let statuses = registry.snapshot();
+        assert_eq!(statuses.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["matrix", "slack"]);
+        let now = Utc::now();
+        let matrix = &statuses[0];
+        assert!(matrix.summary(now).contains("backoff"));
+        assert!(matrix.summary(now).contains("sync failed: 401"));
+        assert_eq!(serde_json::to_value(matrix).unwrap()["state"], "backoff");
+        let slack = registry.get("slack").unwrap();
+        assert_eq!((slack.inbound_count, slack.queue_depth), (1, 3));
+        assert!(slack.summary(now).starts_with("slack: connected · in 0s ago"));
+    }
+}
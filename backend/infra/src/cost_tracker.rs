@@ -2,10 +2,15 @@
 //!
 //! Accumulates per-session LLM token cost in memory with a capped ring buffer.
 //! Records are kept up to MAX_RECORDS; oldest entries are dropped when full.
+//! Prices come from the synced model catalog when set, otherwise from a small
+//! built-in table. Sub-agent usage is recorded under the run it rolls up
+//! into, tagged with the sub-agent's depth and label for breakdowns.
+//! Usage can also be attributed to a principal (a person linked across
+//! channels), so spend follows the person rather than the chat.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -26,17 +31,62 @@ pub struct CostRecord {
     pub usage: TokenUsage,
     pub cost_usd: f64,
     pub timestamp: DateTime<Utc>,
+    /// Set when a sub-agent incurred the usage; `session_id` is then the parent run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subagent: Option<SubAgentOrigin>,
+    /// Person the usage was incurred for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub principal: Option<String>,
+}
+
+/// The sub-agent behind a rolled-up record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubAgentOrigin {
+    pub session_id: String,
+    pub depth: usize,
+    pub label: String,
+    pub tool_calls: u32,
+}
+
+/// Sub-agent usage of one run, summed per depth and label.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SubAgentRollup {
+    pub depth: usize,
+    pub label: String,
+    pub sessions: usize,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+    pub tool_calls: u64,
 }
 
 #[derive(Clone)]
 pub struct CostTracker {
     records: Arc<RwLock<VecDeque<CostRecord>>>,
+    /// model → (input, output) USD per million tokens
+    prices: Arc<std::sync::RwLock<HashMap<String, (f64, f64)>>>,
 }
 
 impl CostTracker {
     pub fn new() -> Self {
         Self {
             records: Arc::new(RwLock::new(VecDeque::with_capacity(MAX_RECORDS))),
+            prices: Arc::new(std::sync::RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Replace the price table with `(model, input, output)` USD per million tokens.
+    pub fn set_prices(&self, prices: impl IntoIterator<Item = (String, f64, f64)>) {
+        *self.prices.write().unwrap() = prices.into_iter().map(|(model, input, output)| (model, (input, output))).collect();
+    }
+
+    /// Cost of `usage` on `model`, from the price table when it knows the model.
+    pub fn cost_for(&self, model_name: &str, usage: &TokenUsage) -> f64 {
+        match self.prices.read().unwrap().get(model_name) {
+            Some((input, output)) => {
+                (usage.prompt_tokens as f64 * input + usage.completion_tokens as f64 * output) / 1_000_000.0
+            }
+            None => Self::calculate_cost(model_name, usage),
         }
     }
 
@@ -61,7 +111,43 @@ impl CostTracker {
         model_name: &str,
         usage: TokenUsage,
     ) -> anyhow::Result<CostRecord> {
-        let cost_usd = Self::calculate_cost(model_name, &usage);
+        self.push(session_id, None, agent_id, model_name, usage, None).await
+    }
+
+    /// Record usage in `session_id` on behalf of `principal`.
+    pub async fn record_principal_usage(
+        &self,
+        session_id: &str,
+        principal: &str,
+        agent_id: &str,
+        model_name: &str,
+        usage: TokenUsage,
+    ) -> anyhow::Result<CostRecord> {
+        self.push(session_id, Some(principal), agent_id, model_name, usage, None).await
+    }
+
+    /// Record a sub-agent's usage against the parent run `session_id`.
+    pub async fn record_subagent_usage(
+        &self,
+        session_id: &str,
+        agent_id: &str,
+        model_name: &str,
+        usage: TokenUsage,
+        origin: SubAgentOrigin,
+    ) -> anyhow::Result<CostRecord> {
+        self.push(session_id, None, agent_id, model_name, usage, Some(origin)).await
+    }
+
+    async fn push(
+        &self,
+        session_id: &str,
+        principal: Option<&str>,
+        agent_id: &str,
+        model_name: &str,
+        usage: TokenUsage,
+        subagent: Option<SubAgentOrigin>,
+    ) -> anyhow::Result<CostRecord> {
+        let cost_usd = self.cost_for(model_name, &usage);
         let record = CostRecord {
             session_id: session_id.into(),
             agent_id: agent_id.into(),
@@ -69,16 +155,42 @@ impl CostTracker {
             usage,
             cost_usd,
             timestamp: Utc::now(),
+            subagent,
+            principal: principal.map(str::to_string),
         };
+        Ok(self.insert(record).await)
+    }
+
+    /// Record a priced-per-call charge with no token usage, such as a
+    /// generated image.
+    pub async fn record_fixed_cost(
+        &self,
+        session_id: &str,
+        agent_id: &str,
+        model_name: &str,
+        cost_usd: f64,
+    ) -> anyhow::Result<CostRecord> {
+        let record = CostRecord {
+            session_id: session_id.into(),
+            agent_id: agent_id.into(),
+            model_name: model_name.into(),
+            usage: TokenUsage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 0 },
+            cost_usd,
+            timestamp: Utc::now(),
+            subagent: None,
+            principal: None,
+        };
+        Ok(self.insert(record).await)
+    }
 
+    async fn insert(&self, record: CostRecord) -> CostRecord {
         let mut records = self.records.write().await;
         if records.len() >= MAX_RECORDS {
             records.pop_front();
         }
         records.push_back(record.clone());
         tracing::debug!(cost_usd = record.cost_usd, session_id = %record.session_id, "Usage recorded");
-
-        Ok(record)
+        record
     }
 
     /// Return a snapshot of all stored records (most recent last).
@@ -86,6 +198,47 @@ impl CostTracker {
         self.records.read().await.iter().cloned().collect()
     }
 
+    /// Sub-agent usage rolled up into `session_id`, by depth then label.
+    pub async fn subagent_breakdown(&self, session_id: &str) -> Vec<SubAgentRollup> {
+        let records = self.records.read().await;
+        let mut rollups: Vec<(SubAgentRollup, Vec<&str>)> = Vec::new();
+        for record in records.iter().filter(|r| r.session_id == session_id) {
+            let Some(origin) = &record.subagent else { continue };
+            let index = match rollups.iter().position(|(r, _)| r.depth == origin.depth && r.label == origin.label) {
+                Some(index) => index,
+                None => {
+                    let rollup = SubAgentRollup { depth: origin.depth, label: origin.label.clone(), ..Default::default() };
+                    rollups.push((rollup, Vec::new()));
+                    rollups.len() - 1
+                }
+            };
+            let (rollup, sessions) = &mut rollups[index];
+            if !sessions.contains(&origin.session_id.as_str()) {
+                sessions.push(&origin.session_id);
+            }
+            rollup.sessions = sessions.len();
+            rollup.prompt_tokens += record.usage.prompt_tokens as u64;
+            rollup.completion_tokens += record.usage.completion_tokens as u64;
+            rollup.cost_usd += record.cost_usd;
+            rollup.tool_calls += origin.tool_calls as u64;
+        }
+        let mut rollups: Vec<SubAgentRollup> = rollups.into_iter().map(|(r, _)| r).collect();
+        rollups.sort_by(|a, b| a.depth.cmp(&b.depth).then_with(|| a.label.cmp(&b.label)));
+        rollups
+    }
+
+    /// What `principal` has spent since `since`, in USD, across every
+    /// session and channel.
+    pub async fn principal_cost_usd(&self, principal: &str, since: DateTime<Utc>) -> f64 {
+        self.records
+            .read()
+            .await
+            .iter()
+            .filter(|r| r.timestamp >= since && r.principal.as_deref() == Some(principal))
+            .map(|r| r.cost_usd)
+            .sum()
+    }
+
     /// Return the sum of all recorded costs in USD.
     pub async fn total_cost_usd(&self) -> f64 {
         self.records.read().await.iter().map(|r| r.cost_usd).sum()
@@ -109,6 +262,27 @@ mod tests {
         assert_eq!(tracker.get_records().await.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_fixed_cost() {
+        let tracker = CostTracker::new();
+        let record = tracker.record_fixed_cost("s1", "artist", "dall-e-3", 0.04).await.unwrap();
+        assert_eq!(record.usage.total_tokens, 0);
+        assert_eq!(tracker.total_cost_usd().await, 0.04);
+    }
+
+    #[tokio::test]
+    async fn test_principal_cost() {
+        let tracker = CostTracker::new();
+        let usage = || TokenUsage { prompt_tokens: 1000, completion_tokens: 0, total_tokens: 1000 };
+        tracker.record_principal_usage("telegram:1", "person:a", "a1", "gpt-4", usage()).await.unwrap();
+        tracker.record_principal_usage("slack:D1", "person:a", "a1", "gpt-4", usage()).await.unwrap();
+        tracker.record_principal_usage("slack:D2", "person:b", "a1", "gpt-4", usage()).await.unwrap();
+        tracker.record_usage("cron", "a1", "gpt-4", usage()).await.unwrap();
+        let since = Utc::now() - chrono::Duration::hours(1);
+        assert!((tracker.principal_cost_usd("person:a", since).await - 0.06).abs() < 1e-9);
+        assert_eq!(tracker.principal_cost_usd("person:a", Utc::now() + chrono::Duration::hours(1)).await, 0.0);
+    }
+
     #[tokio::test]
     async fn test_ring_buffer_cap() {
         let tracker = CostTracker::new();
@@ -118,4 +292,28 @@ mod tests {
         }
         assert_eq!(tracker.get_records().await.len(), MAX_RECORDS);
     }
+
+    #[tokio::test]
+    async fn test_subagent_breakdown() {
+        let tracker = CostTracker::new();
+        let usage = || TokenUsage { prompt_tokens: 100, completion_tokens: 50, total_tokens: 150 };
+        let origin = |id: &str, depth, label: &str| SubAgentOrigin {
+            session_id: id.into(),
+            depth,
+            label: label.into(),
+            tool_calls: 2,
+        };
+        tracker.record_usage("run", "a", "gpt-4", usage()).await.unwrap();
+        tracker.record_subagent_usage("run", "a", "gpt-4", usage(), origin("x1", 1, "research")).await.unwrap();
+        tracker.record_subagent_usage("run", "a", "gpt-4", usage(), origin("x1", 1, "research")).await.unwrap();
+        tracker.record_subagent_usage("run", "a", "gpt-4", usage(), origin("x2", 1, "research")).await.unwrap();
+        tracker.record_subagent_usage("run", "a", "gpt-4", usage(), origin("y1", 2, "fetch")).await.unwrap();
+        tracker.record_subagent_usage("other", "a", "gpt-4", usage(), origin("z1", 1, "research")).await.unwrap();
+
+        let breakdown = tracker.subagent_breakdown("run").await;
+        assert_eq!(breakdown.len(), 2);
+        assert_eq!((breakdown[0].depth, breakdown[0].sessions, breakdown[0].prompt_tokens), (1, 2, 300));
+        assert_eq!(breakdown[0].tool_calls, 6);
+        assert_eq!((breakdown[1].depth, breakdown[1].label.as_str()), (2, "fetch"));
+    }
 }
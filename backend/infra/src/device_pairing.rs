@@ -30,3 +30,48 @@ impl PairingProtocol {
         Ok(())
     }
 }
+
+// ---------------------------------------------------------------------------
+// Pairing offers
+// ---------------------------------------------------------------------------
+
+/// Everything a new device needs to pair: the Control UI encodes
+/// `deep_link` as a QR code, and `pairing_url` works when opened directly.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PairingOffer {
+    /// Human-readable setup code, also shown for manual entry.
+    pub code: String,
+    /// Control UI page that completes pairing in a browser.
+    pub pairing_url: String,
+    /// `clawforge://pair?...` link for the mobile client.
+    pub deep_link: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl PairingOffer {
+    /// Build an offer for `code` against the gateway reachable at `gateway_url`.
+    pub fn new(gateway_url: &str, code: &str, expires_at: chrono::DateTime<chrono::Utc>) -> Self {
+        let gateway_url = gateway_url.trim_end_matches('/');
+        Self {
+            code: code.to_string(),
+            pairing_url: format!("{}/ui/pair?code={}", gateway_url, percent_encode(code)),
+            deep_link: format!(
+                "clawforge://pair?gateway={}&code={}",
+                percent_encode(gateway_url),
+                percent_encode(code)
+            ),
+            expires_at,
+        }
+    }
+}
+
+/// Percent-encode everything outside RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
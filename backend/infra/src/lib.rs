@@ -3,13 +3,18 @@
 //! Provides operational support metrics, cost tracking, log analysis utilities,
 //! and usage metrics required for auditing and dashboard representations.
 
+pub mod adapter_status;
 pub mod channel_activity;
 pub mod cost_tracker;
 pub mod usage_scanner;
+pub mod usage_footer;
 pub mod device_identity;
 pub mod device_auth_store;
 pub mod device_pairing;
 
+pub use adapter_status::{AdapterReporter, AdapterState, AdapterStatus, AdapterStatusRegistry};
 pub use channel_activity::{ChannelActivity, ChannelActivityMonitor};
-pub use cost_tracker::{CostRecord, CostTracker, TokenUsage};
+pub use cost_tracker::{CostRecord, CostTracker, SubAgentOrigin, SubAgentRollup, TokenUsage};
 pub use usage_scanner::{UsageReport, UsageScanner};
+pub use usage_footer::{ReplyUsage, UsageFooter, UsageMode};
+pub use device_pairing::PairingOffer;
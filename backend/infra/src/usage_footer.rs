@@ -0,0 +1,235 @@
+//! Per-Message Usage Footer
+//!
+//! Appends a compact usage line such as
+//! `3.2k in / 840 out · $0.012 · claude-sonnet` to agent replies on chat
+//! channels, according to the session's `/usage` mode. Numbers come from the
+//! cost tracker records made while the reply was generated.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::cost_tracker::CostTracker;
+
+/// Channels that render a trailing text footer sensibly.
+const FOOTER_CHANNELS: &[&str] = &[
+    "telegram", "discord", "slack", "whatsapp", "signal", "matrix", "mattermost", "msteams", "googlechat", "line",
+    "imessage", "bluebubbles", "irc", "webchat",
+];
+
+/// `/usage` setting for a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageMode {
+    #[default]
+    Off,
+    /// `3.2k in / 840 out`
+    Tokens,
+    /// `$0.012`
+    Cost,
+    /// `3.2k in / 840 out · $0.012 · claude-sonnet`
+    Full,
+}
+
+impl UsageMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "tokens" => Some(Self::Tokens),
+            "cost" => Some(Self::Cost),
+            "full" | "on" => Some(Self::Full),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Tokens => "tokens",
+            Self::Cost => "cost",
+            Self::Full => "full",
+        }
+    }
+}
+
+/// Usage summed over one reply.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplyUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+    /// Model of the last call in the reply.
+    pub model: String,
+}
+
+impl ReplyUsage {
+    pub fn render(&self, mode: UsageMode) -> Option<String> {
+        let tokens = format!("{} in / {} out", compact_count(self.prompt_tokens), compact_count(self.completion_tokens));
+        match mode {
+            UsageMode::Off => None,
+            UsageMode::Tokens => Some(tokens),
+            UsageMode::Cost => Some(format_cost(self.cost_usd)),
+            UsageMode::Full => Some(format!("{} · {} · {}", tokens, format_cost(self.cost_usd), short_model(&self.model))),
+        }
+    }
+}
+
+/// Session `/usage` modes plus the cost tracker they read from.
+#[derive(Clone, Default)]
+pub struct UsageFooter {
+    modes: Arc<RwLock<HashMap<String, UsageMode>>>,
+    costs: CostTracker,
+}
+
+impl UsageFooter {
+    pub fn new(costs: CostTracker) -> Self {
+        Self { modes: Arc::default(), costs }
+    }
+
+    pub async fn mode(&self, session_id: &str) -> UsageMode {
+        self.modes.read().await.get(session_id).copied().unwrap_or_default()
+    }
+
+    pub async fn set_mode(&self, session_id: &str, mode: UsageMode) {
+        let mut modes = self.modes.write().await;
+        if mode == UsageMode::Off {
+            modes.remove(session_id);
+        } else {
+            modes.insert(session_id.to_string(), mode);
+        }
+    }
+
+    /// Usage recorded for the session since `since`, or `None` without records.
+    pub async fn reply_usage(&self, session_id: &str, since: DateTime<Utc>) -> Option<ReplyUsage> {
+        let records = self.costs.get_records().await;
+        let mut usage: Option<ReplyUsage> = None;
+        for record in records.iter().filter(|r| r.session_id == session_id && r.timestamp >= since) {
+            let total = usage.get_or_insert_with(|| ReplyUsage {
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                cost_usd: 0.0,
+                model: String::new(),
+            });
+            total.prompt_tokens += record.usage.prompt_tokens as u64;
+            total.completion_tokens += record.usage.completion_tokens as u64;
+            total.cost_usd += record.cost_usd;
+            total.model = record.model_name.clone();
+        }
+        usage
+    }
+
+    /// Sub-agent usage rolled up into the session, one line per depth and
+    /// label, or `None` when its runs spawned no sub-agents.
+    pub async fn subagent_report(&self, session_id: &str) -> Option<String> {
+        let rollups = self.costs.subagent_breakdown(session_id).await;
+        if rollups.is_empty() {
+            return None;
+        }
+        let total: f64 = rollups.iter().map(|r| r.cost_usd).sum();
+        let mut lines = vec![format!("Sub-agents: {} total", format_cost(total))];
+        for r in &rollups {
+            lines.push(format!(
+                "{}depth {} · {} ×{} — {} in / {} out · {} · {} tool calls",
+                "  ".repeat(r.depth.saturating_sub(1)),
+                r.depth,
+                r.label,
+                r.sessions,
+                compact_count(r.prompt_tokens),
+                compact_count(r.completion_tokens),
+                format_cost(r.cost_usd),
+                r.tool_calls
+            ));
+        }
+        Some(lines.join("\n"))
+    }
+
+    /// `reply` with the session's footer appended when the mode and channel
+    /// allow it; `started_at` is when the reply's run began.
+    pub async fn annotate(&self, session_id: &str, channel: &str, reply: &str, started_at: DateTime<Utc>) -> String {
+        if !FOOTER_CHANNELS.contains(&channel) {
+            return reply.to_string();
+        }
+        let mode = self.mode(session_id).await;
+        if mode == UsageMode::Off {
+            return reply.to_string();
+        }
+        match self.reply_usage(session_id, started_at).await.and_then(|u| u.render(mode)) {
+            Some(footer) => format!("{}\n\n{}", reply.trim_end(), footer),
+            None => reply.to_string(),
+        }
+    }
+}
+
+/// `840`, `3.2k`, `12k`, `1.5M`
+fn compact_count(n: u64) -> String {
+    if n < 1_000 {
+        return n.to_string();
+    }
+    let (mut value, mut unit) = (n as f64 / 1_000.0, "k");
+    for larger in ["M", "B"] {
+        // Move up a unit before rounding would print `1000k`.
+        if value < 999.5 {
+            break;
+        }
+        value /= 1_000.0;
+        unit = larger;
+    }
+    if value >= 10.0 {
+        format!("{:.0}{}", value, unit)
+    } else {
+        format!("{:.1}{}", value, unit).replace(".0", "")
+    }
+}
+
+fn format_cost(usd: f64) -> String {
+    if usd >= 1.0 {
+        format!("${:.2}", usd)
+    } else {
+        format!("${:.3}", usd)
+    }
+}
+
+/// Drop the provider prefix and a trailing date stamp:
+/// `anthropic/claude-sonnet-4-20250514` → `claude-sonnet-4`.
+fn short_model(model: &str) -> &str {
+    let name = model.rsplit('/').next().unwrap_or(model);
+    match name.rsplit_once('-') {
+        Some((base, stamp)) if stamp.len() == 8 && stamp.bytes().all(|b| b.is_ascii_digit()) => base,
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost_tracker::TokenUsage;
+
+    #[tokio::test]
+    async fn footer_sums_reply_usage_per_mode_and_channel() {
+        let costs = CostTracker::new();
+        let footer = UsageFooter::new(costs.clone());
+        let start = Utc::now();
+        for (prompt, completion) in [(2_000, 300), (1_200, 540)] {
+            let usage = TokenUsage { prompt_tokens: prompt, completion_tokens: completion, total_tokens: prompt + completion };
+            costs.record_usage("s1", "a1", "anthropic/claude-sonnet-20250514", usage).await.unwrap();
+        }
+
+        assert_eq!(footer.annotate("s1", "telegram", "Hi", start).await, "Hi");
+        footer.set_mode("s1", UsageMode::Full).await;
+        assert_eq!(
+            footer.annotate("s1", "telegram", "Hi\n", start).await,
+            "Hi\n\n3.2k in / 840 out · $0.004 · claude-sonnet"
+        );
+        assert_eq!(footer.annotate("s1", "api", "Hi", start).await, "Hi");
+        footer.set_mode("s1", UsageMode::Tokens).await;
+        assert_eq!(footer.annotate("s1", "slack", "Hi", start).await, "Hi\n\n3.2k in / 840 out");
+        assert_eq!(compact_count(12_400), "12k");
+        assert_eq!(compact_count(1_000), "1k");
+        assert_eq!(compact_count(999_499), "999k");
+        assert_eq!(compact_count(999_950), "1M");
+        assert_eq!(compact_count(9_960), "10k");
+    }
+}
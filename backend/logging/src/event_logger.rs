@@ -1,14 +1,21 @@
 //! Agent Event Logger
 //!
-//! Structured events (tool_call, message, error) written to rolling NDJSON logs.
+//! Structured events (tool_call, message, error) written to rolling NDJSON logs,
+//! and optionally to a SQLite `EventStore` that the Control UI queries: filter
+//! by agent, session, kind and time, and aggregate into hourly counts per kind
+//! and error rates per agent.
 
-use chrono::{DateTime, Utc};
-use serde::Serialize;
-use tracing::info;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, params_from_iter, types::Value as SqlValue, Connection};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
 
 use crate::redact::redact_sensitive_data;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum AgentEvent {
     ToolCall {
@@ -24,19 +31,59 @@ pub enum AgentEvent {
     }
 }
 
-#[derive(Debug, Serialize)]
+impl AgentEvent {
+    /// Name the event is filtered and aggregated by.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ToolCall { .. } => "tool_call",
+            Self::Message { .. } => "message",
+            Self::Error { .. } => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventLogEntry {
     pub session_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
     pub timestamp: DateTime<Utc>,
     pub event: AgentEvent,
 }
 
-pub struct EventLogger;
+#[derive(Default)]
+pub struct EventLogger {
+    store: Option<Arc<EventStore>>,
+}
 
 impl EventLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also persist events for querying.
+    pub fn with_store(mut self, store: Arc<EventStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
     /// Logs an agent's runtime event securely, immediately serializing it to the tracing system.
-    pub fn log_event(session_id: &str, mut event: AgentEvent) {
-        
+    pub fn log_event(session_id: &str, event: AgentEvent) {
+        Self::trace(Self::entry(session_id, None, event));
+    }
+
+    /// Log an event for `agent_id` and store it when a store is attached.
+    pub fn record(&self, agent_id: &str, session_id: &str, event: AgentEvent) {
+        let entry = Self::entry(session_id, Some(agent_id), event);
+        if let Some(store) = &self.store {
+            if let Err(e) = store.insert(&entry) {
+                warn!("Failed to store agent event: {}", e);
+            }
+        }
+        Self::trace(entry);
+    }
+
+    fn entry(session_id: &str, agent_id: Option<&str>, mut event: AgentEvent) -> EventLogEntry {
         // Redact any string contents before logging
         match &mut event {
             AgentEvent::ToolCall { arguments_json, .. } => {
@@ -50,13 +97,257 @@ impl EventLogger {
             }
         }
 
-        let entry = EventLogEntry {
+        EventLogEntry {
             session_id: session_id.into(),
+            agent_id: agent_id.map(str::to_string),
             timestamp: Utc::now(),
             event,
-        };
+        }
+    }
 
+    fn trace(entry: EventLogEntry) {
         // Leverage tracing to output NDJSON correctly wrapped
         info!(target: "agent_events", event = ?entry, "Agent trace event");
     }
 }
+
+// ---------------------------------------------------------------------------
+// Queryable storage
+// ---------------------------------------------------------------------------
+
+/// Most entries one query returns.
+const MAX_QUERY_LIMIT: usize = 1000;
+
+/// Filters shared by `EventStore::query` and `EventStore::stats`; every field is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EventQuery {
+    pub agent_id: Option<String>,
+    pub session_id: Option<String>,
+    /// `tool_call`, `message` or `error`.
+    pub kind: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Newest entries returned by `query`; defaults to 100.
+    pub limit: Option<usize>,
+}
+
+impl EventQuery {
+    fn where_clause(&self) -> (String, Vec<SqlValue>) {
+        let mut clauses = Vec::new();
+        let mut values = Vec::new();
+        for (column, value) in [("agent_id", &self.agent_id), ("session_id", &self.session_id), ("kind", &self.kind)] {
+            if let Some(value) = value {
+                clauses.push(format!("{} = ?", column));
+                values.push(SqlValue::Text(value.clone()));
+            }
+        }
+        if let Some(since) = self.since {
+            clauses.push("ts >= ?".to_string());
+            values.push(SqlValue::Integer(since.timestamp()));
+        }
+        if let Some(until) = self.until {
+            clauses.push("ts < ?".to_string());
+            values.push(SqlValue::Integer(until.timestamp()));
+        }
+        let clause = if clauses.is_empty() { String::new() } else { format!("WHERE {}", clauses.join(" AND ")) };
+        (clause, values)
+    }
+}
+
+/// Events of one kind within one hour.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HourlyCount {
+    /// Start of the hour.
+    pub hour: DateTime<Utc>,
+    pub kind: String,
+    pub count: u64,
+}
+
+/// Share of an agent's events that were errors.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AgentErrorRate {
+    pub agent_id: Option<String>,
+    pub events: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+}
+
+/// Aggregations served to the Control UI dashboard.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EventStats {
+    pub total: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub hourly: Vec<HourlyCount>,
+    pub agents: Vec<AgentErrorRate>,
+}
+
+fn rate(errors: u64, events: u64) -> f64 {
+    if events == 0 { 0.0 } else { errors as f64 / events as f64 }
+}
+
+/// SQLite table of logged events; the full entry is kept as JSON next to
+/// the indexed columns.
+pub struct EventStore {
+    conn: Mutex<Connection>,
+}
+
+impl EventStore {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path).context("Failed to open event log database")?;
+        Self::init(conn)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory().context("Failed to open in-memory SQLite")?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS agent_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                ts INTEGER NOT NULL,
+                session_id TEXT NOT NULL,
+                agent_id TEXT,
+                kind TEXT NOT NULL,
+                entry TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS agent_events_ts ON agent_events (ts);
+            CREATE INDEX IF NOT EXISTS agent_events_agent ON agent_events (agent_id, ts);
+            CREATE INDEX IF NOT EXISTS agent_events_session ON agent_events (session_id, ts);",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))
+    }
+
+    pub fn insert(&self, entry: &EventLogEntry) -> Result<()> {
+        self.lock()?.execute(
+            "INSERT INTO agent_events (ts, session_id, agent_id, kind, entry) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                entry.timestamp.timestamp(),
+                entry.session_id,
+                entry.agent_id,
+                entry.event.kind(),
+                serde_json::to_string(entry)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Matching entries, newest first.
+    pub fn query(&self, query: &EventQuery) -> Result<Vec<EventLogEntry>> {
+        let (clause, mut values) = query.where_clause();
+        values.push(SqlValue::Integer(query.limit.unwrap_or(100).min(MAX_QUERY_LIMIT) as i64));
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(&format!("SELECT entry FROM agent_events {} ORDER BY ts DESC, id DESC LIMIT ?", clause))?;
+        let rows = stmt.query_map(params_from_iter(values), |row| row.get::<_, String>(0))?;
+        Ok(rows.filter_map(|r| r.ok()).filter_map(|json| serde_json::from_str(&json).ok()).collect())
+    }
+
+    /// Hourly counts per kind and error rates per agent over the matching entries.
+    pub fn stats(&self, query: &EventQuery) -> Result<EventStats> {
+        let (clause, values) = query.where_clause();
+        let conn = self.lock()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT (ts / 3600) * 3600 AS hour, kind, COUNT(*) FROM agent_events {} GROUP BY hour, kind ORDER BY hour, kind",
+            clause
+        ))?;
+        let hourly: Vec<HourlyCount> = stmt
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(hour, kind, count)| {
+                Some(HourlyCount { hour: Utc.timestamp_opt(hour, 0).single()?, kind, count: count as u64 })
+            })
+            .collect();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT agent_id, COUNT(*), SUM(kind = 'error') FROM agent_events {} GROUP BY agent_id ORDER BY agent_id",
+            clause
+        ))?;
+        let agents: Vec<AgentErrorRate> = stmt
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok((row.get::<_, Option<String>>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+            })?
+            .filter_map(|r| r.ok())
+            .map(|(agent_id, events, errors)| AgentErrorRate {
+                agent_id,
+                events: events as u64,
+                errors: errors as u64,
+                error_rate: rate(errors as u64, events as u64),
+            })
+            .collect();
+
+        let total = agents.iter().map(|a| a.events).sum();
+        let errors = agents.iter().map(|a| a.errors).sum();
+        Ok(EventStats { total, errors, error_rate: rate(errors, total), hourly, agents })
+    }
+
+    /// Delete entries older than `cutoff`; returns how many were removed.
+    pub fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        Ok(self.lock()?.execute("DELETE FROM agent_events WHERE ts < ?1", params![cutoff.timestamp()])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn entry(agent: &str, session: &str, at: DateTime<Utc>, event: AgentEvent) -> EventLogEntry {
+        EventLogEntry { session_id: session.into(), agent_id: Some(agent.into()), timestamp: at, event }
+    }
+
+    #[test]
+    fn store_filters_and_aggregates() {
+        let store = EventStore::open_in_memory().unwrap();
+        let hour = Utc.with_ymd_and_hms(2026, 5, 1, 10, 0, 0).unwrap();
+        let tool = || AgentEvent::ToolCall { tool_name: "shell".into(), arguments_json: "{}".into() };
+        let error = || AgentEvent::Error { error_msg: "boom".into() };
+        store.insert(&entry("a", "s1", hour + Duration::minutes(5), tool())).unwrap();
+        store.insert(&entry("a", "s1", hour + Duration::minutes(10), error())).unwrap();
+        store.insert(&entry("a", "s2", hour + Duration::minutes(70), tool())).unwrap();
+        store.insert(&entry("b", "s3", hour + Duration::minutes(20), tool())).unwrap();
+
+        let a = store.query(&EventQuery { agent_id: Some("a".into()), ..Default::default() }).unwrap();
+        assert_eq!(a.len(), 3);
+        assert_eq!(a[0].session_id, "s2");
+        let errors = store.query(&EventQuery { kind: Some("error".into()), ..Default::default() }).unwrap();
+        assert_eq!(errors.len(), 1);
+        let first_hour = EventQuery { until: Some(hour + Duration::hours(1)), ..Default::default() };
+        assert_eq!(store.query(&first_hour).unwrap().len(), 3);
+
+        let stats = store.stats(&EventQuery::default()).unwrap();
+        assert_eq!((stats.total, stats.errors), (4, 1));
+        assert_eq!(stats.error_rate, 0.25);
+        assert_eq!(
+            stats.hourly,
+            vec![
+                HourlyCount { hour, kind: "error".into(), count: 1 },
+                HourlyCount { hour, kind: "tool_call".into(), count: 2 },
+                HourlyCount { hour: hour + Duration::hours(1), kind: "tool_call".into(), count: 1 },
+            ]
+        );
+        let agent_a = stats.agents.iter().find(|r| r.agent_id.as_deref() == Some("a")).unwrap();
+        assert!((agent_a.error_rate - 1.0 / 3.0).abs() < 1e-9);
+
+        assert_eq!(store.prune_before(hour + Duration::hours(1)).unwrap(), 3);
+        assert_eq!(store.stats(&EventQuery::default()).unwrap().total, 1);
+    }
+
+    #[test]
+    fn recorded_events_are_redacted_and_stored() {
+        let store = Arc::new(EventStore::open_in_memory().unwrap());
+        let logger = EventLogger::new().with_store(store.clone());
+        logger.record("a", "s1", AgentEvent::Message { role: "user".into(), content: "hi".into() });
+        let stored = store.query(&EventQuery::default()).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].agent_id.as_deref(), Some("a"));
+        assert_eq!(stored[0].event.kind(), "message");
+    }
+}
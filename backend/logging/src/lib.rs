@@ -6,6 +6,6 @@ pub mod event_logger;
 pub mod logger;
 pub mod redact;
 
-pub use event_logger::{AgentEvent, EventLogEntry, EventLogger};
+pub use event_logger::{AgentErrorRate, AgentEvent, EventLogEntry, EventLogger, EventQuery, EventStats, EventStore, HourlyCount};
 pub use logger::init_logger;
 pub use redact::redact_sensitive_data;
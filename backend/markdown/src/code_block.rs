@@ -1,22 +1,203 @@
 //! Code Block Semantic Utility
 //!
 //! Detects source code language and filters specific programming artifacts.
+//! Fenced blocks without an info string get a best-effort language guess;
+//! blocks in a runnable language expose the interpreter command a "run this"
+//! affordance hands to the sandbox, and any block can be drawn as a
+//! syntax-highlighted SVG for channels that only show images.
+
+use serde::{Deserialize, Serialize};
 
 use crate::ir::MarkdownNode;
 
+/// Width of one monospace character in the SVG, in pixels.
+const SVG_CHAR_WIDTH: usize = 8;
+const SVG_LINE_HEIGHT: usize = 18;
+const SVG_PADDING: usize = 12;
+/// Longer lines are cut so the image stays legible on a phone.
+const SVG_MAX_COLUMNS: usize = 120;
+
+const KEYWORDS: &[&str] = &[
+    "fn", "let", "mut", "pub", "use", "mod", "impl", "struct", "enum", "trait", "match", "if", "else", "for",
+    "while", "loop", "return", "async", "await", "def", "class", "import", "from", "as", "in", "not", "and", "or",
+    "function", "const", "var", "new", "this", "self", "Self", "true", "false", "True", "False", "None", "null",
+    "nil", "end", "do", "then", "fi", "done", "case", "esac", "echo", "export", "package", "func", "go", "type",
+    "interface", "SELECT", "FROM", "WHERE", "INSERT", "UPDATE", "DELETE", "JOIN",
+];
+
+/// A fenced or indented code block found in a reply.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeBlock {
+    /// Normalized language, declared or detected.
+    pub language: Option<String>,
+    /// Whether `language` was guessed rather than declared on the fence.
+    pub detected: bool,
+    pub content: String,
+}
+
+impl CodeBlock {
+    pub fn new(info: &str, content: impl Into<String>) -> Self {
+        let content = content.into();
+        let declared = info.split(|c: char| c.is_whitespace() || c == ',').next().unwrap_or_default();
+        match normalize_language(declared) {
+            Some(language) => Self { language: Some(language), detected: false, content },
+            None => Self { language: CodeBlockAnalyzer::detect_language(&content).map(str::to_string), detected: true, content },
+        }
+    }
+
+    /// Interpreter argv that runs this block, or `None` when the language
+    /// isn't one the sandbox can execute directly.
+    pub fn run_command(&self) -> Option<Vec<String>> {
+        let (program, flag) = match self.language.as_deref()? {
+            "bash" => ("bash", "-c"),
+            "sh" => ("sh", "-c"),
+            "python" => ("python3", "-c"),
+            "javascript" => ("node", "-e"),
+            "ruby" => ("ruby", "-e"),
+            "perl" => ("perl", "-e"),
+            _ => return None,
+        };
+        if self.content.trim().is_empty() {
+            return None;
+        }
+        Some(vec![program.to_string(), flag.to_string(), self.content.clone()])
+    }
+
+    pub fn is_runnable(&self) -> bool {
+        self.run_command().is_some()
+    }
+}
+
+/// Lexical class of a highlighted span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    Plain,
+    Keyword,
+    String,
+    Number,
+    Comment,
+}
+
+impl TokenKind {
+    /// Fill colour on the dark SVG background.
+    fn color(self) -> &'static str {
+        match self {
+            TokenKind::Plain => "#d4d4d4",
+            TokenKind::Keyword => "#569cd6",
+            TokenKind::String => "#ce9178",
+            TokenKind::Number => "#b5cea8",
+            TokenKind::Comment => "#6a9955",
+        }
+    }
+}
+
 pub struct CodeBlockAnalyzer;
 
 impl CodeBlockAnalyzer {
     /// Extracts all code blocks from an AST.
     pub fn extract_blocks(nodes: &[MarkdownNode]) -> Vec<(String, String)> {
         let mut blocks = Vec::new();
-        for node in nodes {
-            if let MarkdownNode::CodeBlock(lang, content) = node {
-                blocks.push((lang.clone(), content.clone()));
+        collect(nodes, &mut |lang, content| blocks.push((lang.to_string(), content.to_string())));
+        blocks
+    }
+
+    /// Every code block in the AST, in document order, with its language
+    /// resolved.
+    pub fn analyze(nodes: &[MarkdownNode]) -> Vec<CodeBlock> {
+        let mut blocks = Vec::new();
+        collect(nodes, &mut |lang, content| blocks.push(CodeBlock::new(lang, content)));
+        blocks
+    }
+
+    /// Guess a block's language from its content.
+    pub fn detect_language(content: &str) -> Option<&'static str> {
+        let first = content.lines().find(|l| !l.trim().is_empty())?.trim();
+        if let Some(shebang) = first.strip_prefix("#!") {
+            let interpreter = shebang.split_whitespace().last().unwrap_or_default();
+            let interpreter = interpreter.rsplit('/').next().unwrap_or_default();
+            return normalize_language(interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.'))
+                .and_then(|l| static_language(&l));
+        }
+        let trimmed = content.trim_start();
+        if (trimmed.starts_with('{') || trimmed.starts_with('[')) && serde_json::from_str::<serde_json::Value>(content).is_ok() {
+            return Some("json");
+        }
+        if trimmed.starts_with("<?php") {
+            return Some("php");
+        }
+        if trimmed.starts_with('<') && trimmed.contains("</") {
+            return Some("html");
+        }
+
+        let has = |needle: &str| content.contains(needle);
+        if has("fn ") && (has("let ") || has("->") || has("::")) {
+            Some("rust")
+        } else if has("package main") || (has("func ") && has(":=")) {
+            Some("go")
+        } else if (has("def ") && has(":")) || (has("import ") && !has(";") && !has("from '")) || (has("print(") && !has(";")) {
+            Some("python")
+        } else if has("function ") || (has("const ") && has("=>")) || has("console.log") || has("require(") {
+            Some("javascript")
+        } else if has("#include") {
+            Some("c")
+        } else if content.to_uppercase().starts_with("SELECT ") || has("CREATE TABLE") {
+            Some("sql")
+        } else if content.lines().any(|l| l.trim_start().starts_with("$ ")) || ["cd ", "ls", "echo ", "export ", "sudo ", "apt ", "brew ", "git ", "cargo ", "npm ", "curl "]
+            .iter()
+            .any(|cmd| first.starts_with(cmd))
+        {
+            Some("bash")
+        } else if looks_like_yaml(content) {
+            Some("yaml")
+        } else {
+            None
+        }
+    }
+
+    /// Split `content` into highlighted spans, one list per line.
+    pub fn highlight(language: Option<&str>, content: &str) -> Vec<Vec<(TokenKind, String)>> {
+        let comment = match language {
+            Some("python" | "bash" | "sh" | "ruby" | "perl" | "yaml" | "toml") => "#",
+            Some("sql" | "lua") => "--",
+            _ => "//",
+        };
+        content.lines().map(|line| highlight_line(line, comment)).collect()
+    }
+
+    /// Draw the block as a syntax-highlighted SVG image.
+    pub fn render_svg(block: &CodeBlock) -> String {
+        let lines = Self::highlight(block.language.as_deref(), &block.content);
+        let columns = lines
+            .iter()
+            .map(|spans| spans.iter().map(|(_, text)| text.chars().count()).sum::<usize>())
+            .max()
+            .unwrap_or(0)
+            .clamp(20, SVG_MAX_COLUMNS);
+        let width = columns * SVG_CHAR_WIDTH + 2 * SVG_PADDING;
+        let height = lines.len().max(1) * SVG_LINE_HEIGHT + 2 * SVG_PADDING;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\
+             <rect width=\"100%\" height=\"100%\" rx=\"6\" fill=\"#1e1e1e\"/>\
+             <g font-family=\"Menlo, Consolas, monospace\" font-size=\"13\" xml:space=\"preserve\">"
+        );
+        for (i, spans) in lines.iter().enumerate() {
+            let y = SVG_PADDING + (i + 1) * SVG_LINE_HEIGHT - 5;
+            svg.push_str(&format!("<text x=\"{}\" y=\"{}\">", SVG_PADDING, y));
+            let mut remaining = SVG_MAX_COLUMNS;
+            for (kind, text) in spans {
+                if remaining == 0 {
+                    break;
+                }
+                let text: String = text.chars().take(remaining).collect();
+                remaining -= text.chars().count();
+                svg.push_str(&format!("<tspan fill=\"{}\">{}</tspan>", kind.color(), xml_escape(&text)));
             }
-            // Recurse mock
+            svg.push_str("</text>");
         }
-        blocks
+        svg.push_str("</g></svg>");
+        svg
     }
 
     /// Replaces code blocks with a descriptive "code example" label for TTS pipelines.
@@ -31,3 +212,101 @@ impl CodeBlockAnalyzer {
         }).collect()
     }
 }
+
+/// Visit every code block under `nodes`, depth first.
+fn collect(nodes: &[MarkdownNode], visit: &mut impl FnMut(&str, &str)) {
+    for node in nodes {
+        match node {
+            MarkdownNode::CodeBlock(lang, content) => visit(lang, content),
+            MarkdownNode::List(children)
+            | MarkdownNode::OrderedList(_, children)
+            | MarkdownNode::ListItem(children)
+            | MarkdownNode::TaskItem(_, children)
+            | MarkdownNode::Blockquote(children)
+            | MarkdownNode::FootnoteDefinition(_, children) => collect(children, visit),
+            _ => {}
+        }
+    }
+}
+
+/// Two or more `key: value`, `key:` or `- item` lines and nothing else.
+fn looks_like_yaml(content: &str) -> bool {
+    let lines: Vec<&str> = content.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')).collect();
+    lines.len() >= 2
+        && lines.iter().all(|line| {
+            line.starts_with("- ")
+                || line.split_once(':').is_some_and(|(key, value)| {
+                    !key.is_empty()
+                        && key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+                        && (value.is_empty() || value.starts_with(' '))
+                })
+        })
+}
+
+/// Canonical name for a fence info string or interpreter.
+fn normalize_language(name: &str) -> Option<String> {
+    let name = name.trim().to_lowercase();
+    let canonical = match name.as_str() {
+        "" => return None,
+        "shell" | "zsh" | "console" | "shell-session" => "bash",
+        "py" | "python3" => "python",
+        "js" | "node" | "nodejs" | "mjs" => "javascript",
+        "ts" => "typescript",
+        "rs" => "rust",
+        "rb" => "ruby",
+        "yml" => "yaml",
+        "golang" => "go",
+        other => other,
+    };
+    Some(canonical.to_string())
+}
+
+/// `language` as one of the names `detect_language` returns.
+fn static_language(language: &str) -> Option<&'static str> {
+    ["bash", "sh", "python", "javascript", "ruby", "perl", "php"].into_iter().find(|l| *l == language)
+}
+
+fn highlight_line(line: &str, comment: &str) -> Vec<(TokenKind, String)> {
+    let mut spans: Vec<(TokenKind, String)> = Vec::new();
+    let mut push = |kind: TokenKind, text: &str| match spans.last_mut() {
+        Some((last, existing)) if *last == kind => existing.push_str(text),
+        _ => spans.push((kind, text.to_string())),
+    };
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let rest: String = chars[i..].iter().collect();
+        let c = chars[i];
+        if rest.starts_with(comment) {
+            push(TokenKind::Comment, &rest);
+            break;
+        }
+        if c == '"' || c == '\'' || c == '`' {
+            let mut j = i + 1;
+            while j < chars.len() && chars[j] != c {
+                j += if chars[j] == '\\' { 2 } else { 1 };
+            }
+            let end = (j + 1).min(chars.len());
+            push(TokenKind::String, &chars[i..end].iter().collect::<String>());
+            i = end;
+        } else if c.is_ascii_digit() {
+            let end = chars[i..].iter().position(|c| !(c.is_ascii_alphanumeric() || *c == '.' || *c == '_')).map_or(chars.len(), |n| i + n);
+            push(TokenKind::Number, &chars[i..end].iter().collect::<String>());
+            i = end;
+        } else if c.is_alphabetic() || c == '_' {
+            let end = chars[i..].iter().position(|c| !(c.is_alphanumeric() || *c == '_')).map_or(chars.len(), |n| i + n);
+            let word: String = chars[i..end].iter().collect();
+            let kind = if KEYWORDS.contains(&word.as_str()) { TokenKind::Keyword } else { TokenKind::Plain };
+            push(kind, &word);
+            i = end;
+        } else {
+            push(TokenKind::Plain, &c.to_string());
+            i += 1;
+        }
+    }
+    spans
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
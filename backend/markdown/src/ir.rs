@@ -1,10 +1,10 @@
 //! Markdown Intermediate Representation
 //!
-//! Parses markdown syntax into a strongly-typed AST, enabling abstract 
+//! Parses markdown syntax into a strongly-typed AST, enabling abstract
 //! rendering paths for multimodal output scenarios.
 
 use serde::{Deserialize, Serialize};
-use pulldown_cmark::{Parser, Event, Tag};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, Options, Parser, Tag};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MarkdownNode {
@@ -13,10 +13,93 @@ pub enum MarkdownNode {
     Text(String),
     CodeBlock(String, String), // language, content
     List(Vec<MarkdownNode>),
+    OrderedList(u64, Vec<MarkdownNode>), // first number, items
     ListItem(Vec<MarkdownNode>),
     Blockquote(Vec<MarkdownNode>),
     Link(String, String), // url, text
     Image(String, String), // url, alt_text
+    Emphasis(Vec<MarkdownNode>),
+    Strong(Vec<MarkdownNode>),
+    Strikethrough(Vec<MarkdownNode>),
+    InlineCode(String),
+    LineBreak,
+    Rule,
+    /// Column alignments and rows of `TableRow`s; the first row is the header.
+    Table(Vec<TableAlignment>, Vec<MarkdownNode>),
+    TableRow(Vec<MarkdownNode>),
+    TableCell(Vec<MarkdownNode>),
+    /// A `- [ ]` / `- [x]` list item.
+    TaskItem(bool, Vec<MarkdownNode>),
+    FootnoteReference(String),
+    FootnoteDefinition(String, Vec<MarkdownNode>), // label, content
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TableAlignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+impl From<Alignment> for TableAlignment {
+    fn from(alignment: Alignment) -> Self {
+        match alignment {
+            Alignment::None => Self::None,
+            Alignment::Left => Self::Left,
+            Alignment::Center => Self::Center,
+            Alignment::Right => Self::Right,
+        }
+    }
+}
+
+impl MarkdownNode {
+    /// The node's text with all formatting dropped.
+    pub fn text_content(&self) -> String {
+        match self {
+            Self::Text(text) | Self::InlineCode(text) => text.clone(),
+            Self::CodeBlock(_, content) => content.clone(),
+            Self::Link(_, text) | Self::Image(_, text) => text.clone(),
+            Self::LineBreak => "\n".to_string(),
+            Self::Rule => String::new(),
+            Self::FootnoteReference(label) => format!("[{}]", label),
+            Self::Table(_, rows) => rows.iter().map(Self::text_content).collect::<Vec<_>>().join("\n"),
+            Self::TableRow(cells) => cells.iter().map(Self::text_content).collect::<Vec<_>>().join(" | "),
+            Self::Heading(_, children)
+            | Self::Paragraph(children)
+            | Self::List(children)
+            | Self::OrderedList(_, children)
+            | Self::ListItem(children)
+            | Self::Blockquote(children)
+            | Self::Emphasis(children)
+            | Self::Strong(children)
+            | Self::Strikethrough(children)
+            | Self::TableCell(children)
+            | Self::TaskItem(_, children)
+            | Self::FootnoteDefinition(_, children) => children.iter().map(Self::text_content).collect(),
+        }
+    }
+}
+
+/// An open container while the event stream is folded into a tree.
+enum Frame {
+    Root,
+    Heading(u32),
+    Paragraph,
+    CodeBlock(String),
+    List(Option<u64>),
+    /// Checked state once a task list marker was seen.
+    Item(Option<bool>),
+    Blockquote,
+    Emphasis,
+    Strong,
+    Strikethrough,
+    Link(String),
+    Image(String),
+    Table(Vec<TableAlignment>),
+    Row,
+    Cell,
+    Footnote(String),
 }
 
 pub struct IrParser;
@@ -24,13 +107,93 @@ pub struct IrParser;
 impl IrParser {
     /// Tokenizes and processes standard Markdown into an Intermediate Representation.
     pub fn parse(markdown: &str) -> Vec<MarkdownNode> {
-        let _parser = Parser::new(markdown);
-        // MOCK: Actually build tree using a parser state machine mapping `pulldown-cmark::Event`s.
-        
-        vec![
-            MarkdownNode::Paragraph(vec![
-                MarkdownNode::Text("Mocked parsed markdown paragraph.".to_string()),
-            ]),
-        ]
+        let options =
+            Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_FOOTNOTES;
+        let mut stack: Vec<(Frame, Vec<MarkdownNode>)> = vec![(Frame::Root, Vec::new())];
+
+        for event in Parser::new_ext(markdown, options) {
+            match event {
+                Event::Start(tag) => {
+                    let frame = match tag {
+                        Tag::Paragraph => Frame::Paragraph,
+                        Tag::Heading(level, _, _) => Frame::Heading(level as u32),
+                        Tag::BlockQuote => Frame::Blockquote,
+                        Tag::CodeBlock(CodeBlockKind::Fenced(lang)) => Frame::CodeBlock(lang.to_string()),
+                        Tag::CodeBlock(CodeBlockKind::Indented) => Frame::CodeBlock(String::new()),
+                        Tag::List(start) => Frame::List(start),
+                        Tag::Item => Frame::Item(None),
+                        Tag::Emphasis => Frame::Emphasis,
+                        Tag::Strong => Frame::Strong,
+                        Tag::Strikethrough => Frame::Strikethrough,
+                        Tag::Link(_, url, _) => Frame::Link(url.to_string()),
+                        Tag::Image(_, url, _) => Frame::Image(url.to_string()),
+                        Tag::Table(alignments) => Frame::Table(alignments.into_iter().map(Into::into).collect()),
+                        Tag::TableHead | Tag::TableRow => Frame::Row,
+                        Tag::TableCell => Frame::Cell,
+                        Tag::FootnoteDefinition(label) => Frame::Footnote(label.to_string()),
+                    };
+                    stack.push((frame, Vec::new()));
+                }
+                Event::End(_) => {
+                    let Some((frame, children)) = stack.pop() else { break };
+                    let parent = &mut stack.last_mut().expect("root frame is never closed").1;
+                    match frame {
+                        Frame::Root => {}
+                        Frame::Heading(level) => parent.push(MarkdownNode::Heading(level, children)),
+                        Frame::Paragraph => parent.push(MarkdownNode::Paragraph(children)),
+                        Frame::CodeBlock(lang) => {
+                            let content: String = children.iter().map(MarkdownNode::text_content).collect();
+                            parent.push(MarkdownNode::CodeBlock(lang, content.trim_end_matches('\n').to_string()));
+                        }
+                        Frame::List(None) => parent.push(MarkdownNode::List(children)),
+                        Frame::List(Some(start)) => parent.push(MarkdownNode::OrderedList(start, children)),
+                        Frame::Item(None) => parent.push(MarkdownNode::ListItem(children)),
+                        Frame::Item(Some(checked)) => parent.push(MarkdownNode::TaskItem(checked, children)),
+                        Frame::Blockquote => parent.push(MarkdownNode::Blockquote(children)),
+                        Frame::Emphasis => parent.push(MarkdownNode::Emphasis(children)),
+                        Frame::Strong => parent.push(MarkdownNode::Strong(children)),
+                        Frame::Strikethrough => parent.push(MarkdownNode::Strikethrough(children)),
+                        Frame::Link(url) => {
+                            let text = children.iter().map(MarkdownNode::text_content).collect();
+                            parent.push(MarkdownNode::Link(url, text));
+                        }
+                        Frame::Image(url) => {
+                            let alt = children.iter().map(MarkdownNode::text_content).collect();
+                            parent.push(MarkdownNode::Image(url, alt));
+                        }
+                        Frame::Table(alignments) => parent.push(MarkdownNode::Table(alignments, children)),
+                        Frame::Row => parent.push(MarkdownNode::TableRow(children)),
+                        Frame::Cell => parent.push(MarkdownNode::TableCell(children)),
+                        Frame::Footnote(label) => parent.push(MarkdownNode::FootnoteDefinition(label, children)),
+                    }
+                }
+                Event::Text(text) | Event::Html(text) => {
+                    Self::push_text(&mut stack.last_mut().expect("root frame").1, &text)
+                }
+                Event::Code(code) => stack.last_mut().expect("root frame").1.push(MarkdownNode::InlineCode(code.to_string())),
+                Event::SoftBreak => Self::push_text(&mut stack.last_mut().expect("root frame").1, " "),
+                Event::HardBreak => stack.last_mut().expect("root frame").1.push(MarkdownNode::LineBreak),
+                Event::Rule => stack.last_mut().expect("root frame").1.push(MarkdownNode::Rule),
+                Event::TaskListMarker(checked) => {
+                    // Loose items put the marker inside their first paragraph.
+                    if let Some((Frame::Item(state), _)) = stack.iter_mut().rev().find(|(f, _)| matches!(f, Frame::Item(_))) {
+                        *state = Some(checked);
+                    }
+                }
+                Event::FootnoteReference(label) => {
+                    stack.last_mut().expect("root frame").1.push(MarkdownNode::FootnoteReference(label.to_string()))
+                }
+            }
+        }
+
+        stack.into_iter().next().map(|(_, nodes)| nodes).unwrap_or_default()
+    }
+
+    /// Append text, merging with a preceding text node.
+    fn push_text(nodes: &mut Vec<MarkdownNode>, text: &str) {
+        match nodes.last_mut() {
+            Some(MarkdownNode::Text(last)) => last.push_str(text),
+            _ => nodes.push(MarkdownNode::Text(text.to_string())),
+        }
     }
 }
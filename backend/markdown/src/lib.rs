@@ -6,7 +6,9 @@
 pub mod code_block;
 pub mod ir;
 pub mod renderer;
+pub mod ssml;
 
-pub use code_block::CodeBlockAnalyzer;
-pub use ir::{MarkdownNode, IrParser};
-pub use renderer::Renderer;
+pub use code_block::{CodeBlock, CodeBlockAnalyzer, TokenKind};
+pub use ir::{IrParser, MarkdownNode, TableAlignment};
+pub use renderer::{RenderTarget, Renderer, SLACK_MAX_BLOCKS};
+pub use ssml::SpeechRenderer;
@@ -1,31 +1,76 @@
 //! Renderers for the Markdown IR
 //!
-//! Transforms the AST into text (TTS), ANSI (Terminal), HTML, and WhatsApp syntax.
+//! Transforms the AST into text (TTS), ANSI (Terminal), and the chat
+//! dialects: Slack mrkdwn (plus Block Kit sections), Telegram MarkdownV2,
+//! Discord Markdown, Matrix HTML and WhatsApp. Each dialect escapes text by
+//! its own rules, so literal `*`, `_` or `.` in a reply never breaks the
+//! platform's parser. Only Matrix can show a real table; everywhere else a
+//! table becomes an aligned monospace grid, or one `Header: value` record per
+//! row when the grid would be too wide for a phone.
 
-use crate::ir::MarkdownNode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::ir::{IrParser, MarkdownNode, TableAlignment};
+
+/// Slack rejects section text over 3000 characters.
+const SLACK_SECTION_LIMIT: usize = 3000;
+/// Slack header blocks hold at most 150 characters of plain text.
+const SLACK_HEADER_LIMIT: usize = 150;
+/// Slack rejects a message with more than 50 blocks.
+pub const SLACK_MAX_BLOCKS: usize = 50;
+
+/// Widest monospace table, in characters, before rows become records.
+const TABLE_MAX_WIDTH: usize = 60;
+
+/// Characters Telegram MarkdownV2 requires escaped in ordinary text.
+const TELEGRAM_SPECIAL: &str = "_*[]()~`>#+-=|{}.!\\";
+/// Characters Discord would read as formatting.
+const DISCORD_SPECIAL: &str = "\\*_~`|>#";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderTarget {
+    Plain,
+    Slack,
+    Telegram,
+    Discord,
+    MatrixHtml,
+    WhatsApp,
+}
+
+impl RenderTarget {
+    /// The dialect a channel's messages are written in.
+    pub fn for_channel(channel: &str) -> Self {
+        match channel.to_ascii_lowercase().as_str() {
+            "slack" => Self::Slack,
+            "telegram" => Self::Telegram,
+            // Mattermost speaks the same standard Markdown as Discord.
+            "discord" | "mattermost" => Self::Discord,
+            "matrix" => Self::MatrixHtml,
+            "whatsapp" | "googlechat" | "signal" => Self::WhatsApp,
+            _ => Self::Plain,
+        }
+    }
+}
 
 pub struct Renderer;
 
 impl Renderer {
+    /// Parse `markdown` and render it for `target`.
+    pub fn render_markdown(markdown: &str, target: RenderTarget) -> String {
+        Self::render(&IrParser::parse(markdown), target)
+    }
+
+    pub fn render(nodes: &[MarkdownNode], target: RenderTarget) -> String {
+        let blocks: Vec<String> = nodes.iter().map(|node| block(node, target)).filter(|b| !b.is_empty()).collect();
+        let separator = if target == RenderTarget::MatrixHtml { "" } else { "\n\n" };
+        blocks.join(separator)
+    }
+
     /// Renders AST to plain text, stripping all formatting. Ideal for TTS engines.
     pub fn to_plain_text(nodes: &[MarkdownNode]) -> String {
-        let mut output = String::new();
-        for node in nodes {
-            match node {
-                MarkdownNode::Text(text) => output.push_str(text),
-                MarkdownNode::Paragraph(children) => {
-                    output.push_str(&Self::to_plain_text(children));
-                    output.push('\n');
-                }
-                MarkdownNode::CodeBlock(_, content) => {
-                    output.push_str("Code Example:\n");
-                    output.push_str(content);
-                    output.push('\n');
-                }
-                _ => {} // Handle others recursively
-            }
-        }
-        output
+        Self::render(nodes, RenderTarget::Plain)
     }
 
     /// Renders AST to ANSI terminal codes (Mock).
@@ -36,7 +81,473 @@ impl Renderer {
 
     /// Renders AST to WhatsApp compatible markdown.
     pub fn to_whatsapp(nodes: &[MarkdownNode]) -> String {
-        // MOCK: Replace ** with * and map formatting strictly to WA syntax
-        Self::to_plain_text(nodes)
+        Self::render(nodes, RenderTarget::WhatsApp)
+    }
+
+    pub fn to_slack(nodes: &[MarkdownNode]) -> String {
+        Self::render(nodes, RenderTarget::Slack)
+    }
+
+    pub fn to_telegram(nodes: &[MarkdownNode]) -> String {
+        Self::render(nodes, RenderTarget::Telegram)
     }
+
+    pub fn to_discord(nodes: &[MarkdownNode]) -> String {
+        Self::render(nodes, RenderTarget::Discord)
+    }
+
+    pub fn to_matrix_html(nodes: &[MarkdownNode]) -> String {
+        Self::render(nodes, RenderTarget::MatrixHtml)
+    }
+
+    /// Renders AST to Slack Block Kit: headings become `header` blocks,
+    /// rules `divider`s, and everything else is packed into mrkdwn `section`s
+    /// under Slack's size limit, splitting long code blocks between fences.
+    /// A long reply can exceed `SLACK_MAX_BLOCKS`; senders split it.
+    pub fn to_slack_blocks(nodes: &[MarkdownNode]) -> Vec<Value> {
+        let mut blocks = Vec::new();
+        let mut section = String::new();
+        let flush = |section: &mut String, blocks: &mut Vec<Value>| {
+            if !section.is_empty() {
+                blocks.push(json!({ "type": "section", "text": { "type": "mrkdwn", "text": std::mem::take(section) } }));
+            }
+        };
+        for node in nodes {
+            match node {
+                MarkdownNode::Heading(_, children) => {
+                    flush(&mut section, &mut blocks);
+                    let text: String = children.iter().map(MarkdownNode::text_content).collect();
+                    let text: String = text.chars().take(SLACK_HEADER_LIMIT).collect();
+                    blocks.push(json!({ "type": "header", "text": { "type": "plain_text", "text": text } }));
+                }
+                MarkdownNode::Rule => {
+                    flush(&mut section, &mut blocks);
+                    blocks.push(json!({ "type": "divider" }));
+                }
+                node => {
+                    for piece in slack_pieces(node) {
+                        if !section.is_empty() && section.len() + 2 + piece.len() > SLACK_SECTION_LIMIT {
+                            flush(&mut section, &mut blocks);
+                        }
+                        if !section.is_empty() {
+                            section.push_str("\n\n");
+                        }
+                        section.push_str(&piece);
+                    }
+                }
+            }
+        }
+        flush(&mut section, &mut blocks);
+        blocks
+    }
+}
+
+/// One top-level node as mrkdwn, in pieces that each fit a section.
+fn slack_pieces(node: &MarkdownNode) -> Vec<String> {
+    let rendered = block(node, RenderTarget::Slack);
+    if rendered.len() <= SLACK_SECTION_LIMIT {
+        return vec![rendered];
+    }
+    let fenced = match node {
+        MarkdownNode::CodeBlock(..) | MarkdownNode::Table(..) => {
+            rendered.strip_prefix("```\n").and_then(|r| r.strip_suffix("\n```")).map(str::to_string)
+        }
+        _ => None,
+    };
+    let (fence, body) = match fenced {
+        Some(body) => ("```", body),
+        None => ("", rendered),
+    };
+    let budget = SLACK_SECTION_LIMIT - 2 * (fence.len() + 1);
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for line in body.lines() {
+        for chunk in split_chars(line, budget) {
+            if !current.is_empty() && current.len() + 1 + chunk.len() > budget {
+                pieces.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(&chunk);
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    if fence.is_empty() {
+        pieces
+    } else {
+        pieces.into_iter().map(|p| format!("{fence}\n{p}\n{fence}")).collect()
+    }
+}
+
+/// `line` in pieces of at most `max` bytes, on character boundaries.
+fn split_chars(line: &str, max: usize) -> Vec<String> {
+    let mut pieces = vec![String::new()];
+    for c in line.chars() {
+        if pieces.last().is_some_and(|p| p.len() + c.len_utf8() > max) {
+            pieces.push(String::new());
+        }
+        pieces.last_mut().expect("never empty").push(c);
+    }
+    pieces
+}
+
+// ---------------------------------------------------------------------------
+// Emitters
+// ---------------------------------------------------------------------------
+
+fn block(node: &MarkdownNode, target: RenderTarget) -> String {
+    use RenderTarget::*;
+    match node {
+        MarkdownNode::Paragraph(children) => match target {
+            MatrixHtml => format!("<p>{}</p>", inline(children, target)),
+            _ => inline(children, target),
+        },
+        MarkdownNode::Heading(level, children) => {
+            let text = inline(children, target);
+            match target {
+                Plain => text,
+                Discord => format!("{} {}", "#".repeat((*level).clamp(1, 3) as usize), text),
+                MatrixHtml => format!("<h{level}>{text}</h{level}>"),
+                Slack | Telegram | WhatsApp => format!("*{}*", text),
+            }
+        }
+        MarkdownNode::CodeBlock(lang, content) => match target {
+            Plain => content.clone(),
+            Slack => format!("```\n{}\n```", escape(content, target)),
+            WhatsApp => format!("```{}```", content),
+            Telegram => format!("```{}\n{}\n```", lang, escape_telegram_code(content)),
+            Discord => format!("```{}\n{}\n```", lang, content.replace("```", "`\u{200b}``")),
+            MatrixHtml if lang.is_empty() => format!("<pre><code>{}</code></pre>", escape(content, target)),
+            MatrixHtml => format!("<pre><code class=\"language-{}\">{}</code></pre>", escape(lang, target), escape(content, target)),
+        },
+        MarkdownNode::List(items) => list(items, None, target),
+        MarkdownNode::OrderedList(start, items) => list(items, Some(*start), target),
+        MarkdownNode::Blockquote(children) => {
+            let body = Renderer::render(children, target);
+            match target {
+                MatrixHtml => format!("<blockquote>{}</blockquote>", body),
+                Plain => body.lines().map(|l| format!("  {}", l)).collect::<Vec<_>>().join("\n"),
+                _ => body.lines().map(|l| format!(">{}{}", if l.is_empty() { "" } else { " " }, l)).collect::<Vec<_>>().join("\n"),
+            }
+        }
+        MarkdownNode::Rule => match target {
+            MatrixHtml => "<hr>".to_string(),
+            Telegram => "——————".to_string(),
+            _ => "———".to_string(),
+        },
+        MarkdownNode::ListItem(_) | MarkdownNode::TaskItem(..) => list_item(node, target),
+        MarkdownNode::Table(alignments, rows) => table(alignments, rows, target),
+        MarkdownNode::TableRow(cells) => cells.iter().map(|cell| block(cell, target)).collect::<Vec<_>>().join(" | "),
+        MarkdownNode::TableCell(children) => inline(children, target),
+        MarkdownNode::FootnoteDefinition(label, children) => {
+            let body = list_item(&MarkdownNode::ListItem(children.clone()), target);
+            match target {
+                MatrixHtml => format!("<p>{} {}</p>", footnote_marker(label, target), body),
+                _ => format!("{} {}", footnote_marker(label, target), body),
+            }
+        }
+        inline_node => inline(std::slice::from_ref(inline_node), target),
+    }
+}
+
+fn list(items: &[MarkdownNode], start: Option<u64>, target: RenderTarget) -> String {
+    if target == RenderTarget::MatrixHtml {
+        let items: String = items.iter().map(|item| format!("<li>{}</li>", list_item(item, target))).collect();
+        return match start {
+            None => format!("<ul>{}</ul>", items),
+            Some(1) => format!("<ol>{}</ol>", items),
+            Some(n) => format!("<ol start=\"{}\">{}</ol>", n, items),
+        };
+    }
+    let mut lines = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let marker = match start {
+            None if target == RenderTarget::Discord => "-".to_string(),
+            None => "•".to_string(),
+            Some(n) => escape(&format!("{}.", n + i as u64), target),
+        };
+        let body = list_item(item, target);
+        let indent = " ".repeat(marker.chars().count() + 1);
+        for (j, line) in body.lines().enumerate() {
+            if j == 0 {
+                lines.push(format!("{} {}", marker, line));
+            } else if line.is_empty() {
+                lines.push(String::new());
+            } else {
+                lines.push(format!("{}{}", indent, line));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// An item's blocks, kept tight: loose-list paragraphs don't add blank lines.
+fn list_item(item: &MarkdownNode, target: RenderTarget) -> String {
+    let (checked, children) = match item {
+        MarkdownNode::ListItem(children) => (None, children),
+        MarkdownNode::TaskItem(checked, children) => (Some(*checked), children),
+        _ => return block(item, target),
+    };
+    let mut out = match checked {
+        Some(true) => "☑ ".to_string(),
+        Some(false) => "☐ ".to_string(),
+        None => String::new(),
+    };
+    let mut inline_run = Vec::new();
+    let flush = |run: &mut Vec<MarkdownNode>, out: &mut String| {
+        if !run.is_empty() {
+            out.push_str(&inline(run, target));
+            run.clear();
+        }
+    };
+    for child in children {
+        match child {
+            MarkdownNode::Paragraph(inner) => {
+                flush(&mut inline_run, &mut out);
+                push_line(&mut out, &inline(inner, target), target);
+            }
+            MarkdownNode::List(_) | MarkdownNode::OrderedList(..) | MarkdownNode::CodeBlock(..) | MarkdownNode::Blockquote(_) => {
+                flush(&mut inline_run, &mut out);
+                push_line(&mut out, &block(child, target), target);
+            }
+            other => inline_run.push(other.clone()),
+        }
+    }
+    flush(&mut inline_run, &mut out);
+    out
+}
+
+fn push_line(out: &mut String, text: &str, target: RenderTarget) {
+    if !out.is_empty() && !out.ends_with(' ') && target != RenderTarget::MatrixHtml {
+        out.push('\n');
+    }
+    out.push_str(text);
+}
+
+fn inline(nodes: &[MarkdownNode], target: RenderTarget) -> String {
+    use RenderTarget::*;
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            MarkdownNode::Text(text) => out.push_str(&escape(text, target)),
+            MarkdownNode::Strong(children) => {
+                let inner = inline(children, target);
+                out.push_str(&match target {
+                    Plain => inner,
+                    Discord => format!("**{}**", inner),
+                    MatrixHtml => format!("<strong>{}</strong>", inner),
+                    Slack | Telegram | WhatsApp => format!("*{}*", inner),
+                });
+            }
+            MarkdownNode::Emphasis(children) => {
+                let inner = inline(children, target);
+                out.push_str(&match target {
+                    Plain => inner,
+                    MatrixHtml => format!("<em>{}</em>", inner),
+                    _ => format!("_{}_", inner),
+                });
+            }
+            MarkdownNode::Strikethrough(children) => {
+                let inner = inline(children, target);
+                out.push_str(&match target {
+                    Plain => inner,
+                    Discord => format!("~~{}~~", inner),
+                    MatrixHtml => format!("<del>{}</del>", inner),
+                    Slack | Telegram | WhatsApp => format!("~{}~", inner),
+                });
+            }
+            MarkdownNode::InlineCode(code) => out.push_str(&match target {
+                Plain => code.clone(),
+                Telegram => format!("`{}`", escape_telegram_code(code)),
+                Slack => format!("`{}`", escape(code, target)),
+                MatrixHtml => format!("<code>{}</code>", escape(code, target)),
+                Discord | WhatsApp => format!("`{}`", code.replace('`', "'")),
+            }),
+            MarkdownNode::Link(url, text) => out.push_str(&link(url, text, target)),
+            MarkdownNode::Image(url, alt) => {
+                let alt = if alt.is_empty() { "image" } else { alt.as_str() };
+                out.push_str(&link(url, alt, target));
+            }
+            MarkdownNode::FootnoteReference(label) => out.push_str(&footnote_marker(label, target)),
+            MarkdownNode::LineBreak => out.push_str(if target == MatrixHtml { "<br>" } else { "\n" }),
+            block_node => out.push_str(&block(block_node, target)),
+        }
+    }
+    out
+}
+
+fn footnote_marker(label: &str, target: RenderTarget) -> String {
+    let marker = escape(&format!("[{}]", label), target);
+    match target {
+        RenderTarget::MatrixHtml => format!("<sup>{}</sup>", marker),
+        _ => marker,
+    }
+}
+
+fn table(alignments: &[TableAlignment], rows: &[MarkdownNode], target: RenderTarget) -> String {
+    let rows: Vec<&[MarkdownNode]> = rows
+        .iter()
+        .filter_map(|row| match row {
+            MarkdownNode::TableRow(cells) => Some(cells.as_slice()),
+            _ => None,
+        })
+        .collect();
+    if target == RenderTarget::MatrixHtml {
+        return table_html(alignments, &rows);
+    }
+
+    let grid: Vec<Vec<String>> = rows
+        .iter()
+        .map(|cells| cells.iter().map(|cell| cell.text_content().replace('\n', " ")).collect())
+        .collect();
+    let columns = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|j| grid.iter().filter_map(|row| row.get(j)).map(|cell| cell.chars().count()).max().unwrap_or(0))
+        .collect();
+    if widths.iter().sum::<usize>() + 3 * columns.saturating_sub(1) > TABLE_MAX_WIDTH {
+        return table_records(&rows, target);
+    }
+
+    let mut lines = Vec::new();
+    for (i, row) in grid.iter().enumerate() {
+        let line: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(j, width)| {
+                let alignment = alignments.get(j).copied().unwrap_or(TableAlignment::None);
+                pad(row.get(j).map_or("", String::as_str), *width, alignment)
+            })
+            .collect();
+        lines.push(line.join(" | ").trim_end().to_string());
+        if i == 0 {
+            lines.push(widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().join("-+-"));
+        }
+    }
+    let grid = lines.join("\n");
+    match target {
+        RenderTarget::Plain => grid,
+        _ => block(&MarkdownNode::CodeBlock(String::new(), grid), target),
+    }
+}
+
+fn pad(text: &str, width: usize, alignment: TableAlignment) -> String {
+    let gap = width.saturating_sub(text.chars().count());
+    match alignment {
+        TableAlignment::Right => format!("{}{}", " ".repeat(gap), text),
+        TableAlignment::Center => format!("{}{}{}", " ".repeat(gap / 2), text, " ".repeat(gap - gap / 2)),
+        TableAlignment::None | TableAlignment::Left => format!("{}{}", text, " ".repeat(gap)),
+    }
+}
+
+/// Each body row as `Header: value` lines, rows separated by a blank line.
+fn table_records(rows: &[&[MarkdownNode]], target: RenderTarget) -> String {
+    let Some((header, body)) = rows.split_first() else { return String::new() };
+    if body.is_empty() {
+        return header.iter().map(|cell| block(cell, target)).collect::<Vec<_>>().join(" | ");
+    }
+    let cell_children = |cell: &MarkdownNode| match cell {
+        MarkdownNode::TableCell(children) => children.clone(),
+        other => vec![other.clone()],
+    };
+    let records: Vec<String> = body
+        .iter()
+        .map(|cells| {
+            let mut fields = Vec::new();
+            for (j, cell) in cells.iter().enumerate() {
+                if j > 0 {
+                    fields.push(MarkdownNode::LineBreak);
+                }
+                if let Some(name) = header.get(j).filter(|h| !h.text_content().trim().is_empty()) {
+                    fields.push(MarkdownNode::Strong(cell_children(name)));
+                    fields.push(MarkdownNode::Text(": ".to_string()));
+                }
+                fields.extend(cell_children(cell));
+            }
+            inline(&fields, target)
+        })
+        .collect();
+    let separator = if target == RenderTarget::MatrixHtml { "<br><br>" } else { "\n\n" };
+    records.join(separator)
+}
+
+fn table_html(alignments: &[TableAlignment], rows: &[&[MarkdownNode]]) -> String {
+    let target = RenderTarget::MatrixHtml;
+    let mut html = String::from("<table>");
+    for (i, cells) in rows.iter().enumerate() {
+        let tag = if i == 0 { "th" } else { "td" };
+        match i {
+            0 => html.push_str("<thead>"),
+            1 => html.push_str("<tbody>"),
+            _ => {}
+        }
+        html.push_str("<tr>");
+        for (j, cell) in cells.iter().enumerate() {
+            let align = match alignments.get(j) {
+                Some(TableAlignment::Left) => " align=\"left\"",
+                Some(TableAlignment::Center) => " align=\"center\"",
+                Some(TableAlignment::Right) => " align=\"right\"",
+                _ => "",
+            };
+            html.push_str(&format!("<{tag}{align}>{}</{tag}>", block(cell, target)));
+        }
+        html.push_str("</tr>");
+        if i == 0 {
+            html.push_str("</thead>");
+        }
+    }
+    if rows.len() > 1 {
+        html.push_str("</tbody>");
+    }
+    html.push_str("</table>");
+    html
+}
+
+fn link(url: &str, text: &str, target: RenderTarget) -> String {
+    use RenderTarget::*;
+    if text.is_empty() || text == url {
+        return match target {
+            Slack => format!("<{}>", url),
+            MatrixHtml => format!("<a href=\"{0}\">{0}</a>", escape(url, target)),
+            _ => escape(url, target),
+        };
+    }
+    match target {
+        Plain | WhatsApp => format!("{} ({})", escape(text, target), url),
+        Slack => format!("<{}|{}>", url.replace('|', "%7C"), escape(text, target)),
+        Telegram => format!("[{}]({})", escape(text, target), url.replace('\\', "\\\\").replace(')', "\\)")),
+        Discord => format!("[{}](<{}>)", escape(text, target), url),
+        MatrixHtml => format!("<a href=\"{}\">{}</a>", escape(url, target), escape(text, target)),
+    }
+}
+
+/// Escape literal text so the target reads it verbatim.
+fn escape(text: &str, target: RenderTarget) -> String {
+    match target {
+        RenderTarget::Plain | RenderTarget::WhatsApp => text.to_string(),
+        RenderTarget::Slack => text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"),
+        RenderTarget::MatrixHtml => {
+            text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+        }
+        RenderTarget::Telegram => escape_chars(text, TELEGRAM_SPECIAL),
+        RenderTarget::Discord => escape_chars(text, DISCORD_SPECIAL),
+    }
+}
+
+fn escape_chars(text: &str, special: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special.contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Inside Telegram code entities only `` ` `` and `\` are escaped.
+fn escape_telegram_code(code: &str) -> String {
+    code.replace('\\', "\\\\").replace('`', "\\`")
 }
@@ -0,0 +1,303 @@
+//! Speech Renderer
+//!
+//! Turns the IR into something a TTS voice can read: SSML for providers that
+//! parse it, or bare speech text for those that don't. Markdown syntax is
+//! never spoken; code blocks and tables are replaced by a one-sentence
+//! summary, common abbreviations are expanded, and headings and rules get
+//! prosody breaks so structure is audible.
+
+use crate::code_block::CodeBlock;
+use crate::ir::MarkdownNode;
+
+const HEADING_BREAK_MS: u32 = 600;
+const RULE_BREAK_MS: u32 = 800;
+const ITEM_BREAK_MS: u32 = 250;
+
+/// Written abbreviations and how to say them.
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("e.g.", "for example"),
+    ("i.e.", "that is"),
+    ("etc.", "et cetera"),
+    ("vs.", "versus"),
+    ("vs", "versus"),
+    ("approx.", "approximately"),
+    ("incl.", "including"),
+    ("w/", "with"),
+    ("w/o", "without"),
+    ("&", "and"),
+    ("->", "to"),
+    ("=>", "to"),
+    ("~", "about"),
+    ("TL;DR", "in short"),
+    ("FYI", "for your information"),
+    ("ASAP", "as soon as possible"),
+    ("IMO", "in my opinion"),
+];
+
+/// Acronyms spelled out letter by letter rather than read as a word.
+const SPELLED: &[&str] = &["API", "CLI", "CPU", "GPU", "URL", "HTML", "CSS", "JSON", "YAML", "SQL", "SSH", "HTTP", "HTTPS", "UI", "PR", "CI", "ID"];
+
+pub struct SpeechRenderer;
+
+impl SpeechRenderer {
+    /// Renders AST to an SSML `<speak>` document.
+    pub fn to_ssml(nodes: &[MarkdownNode]) -> String {
+        let body: String = nodes.iter().map(|node| block(node, true)).collect();
+        format!("<speak>{}</speak>", body)
+    }
+
+    /// Renders AST to plain speech text with the same substitutions as
+    /// `to_ssml`, for providers that would read SSML tags aloud.
+    pub fn to_speech_text(nodes: &[MarkdownNode]) -> String {
+        let blocks: Vec<String> = nodes.iter().map(|node| block(node, false)).filter(|b| !b.trim().is_empty()).collect();
+        blocks.join("\n\n")
+    }
+
+    /// Expand abbreviations and symbols in `text` to their spoken form, and
+    /// shorten bare URLs to their domain.
+    pub fn expand_abbreviations(text: &str) -> String {
+        text.split_inclusive(char::is_whitespace).map(|word| {
+            let (core, space) = word.split_at(word.trim_end().len());
+            let end = core.trim_end_matches([',', ';', ':', '!', '?', ')']).len();
+            let (token, tail) = core.split_at(end);
+            if token.starts_with("http://") || token.starts_with("https://") {
+                return format!("a link to {}{tail}{space}", domain(token));
+            }
+            match ABBREVIATIONS.iter().find(|(abbr, _)| abbr.eq_ignore_ascii_case(token)) {
+                Some((_, spoken)) => format!("{spoken}{tail}{space}"),
+                None => word.to_string(),
+            }
+        }).collect()
+    }
+}
+
+fn block(node: &MarkdownNode, ssml: bool) -> String {
+    match node {
+        MarkdownNode::Paragraph(children) => paragraph(&inline(children, ssml), ssml),
+        MarkdownNode::Heading(_, children) => {
+            let text = inline(children, ssml);
+            if ssml {
+                format!("{}<p><emphasis level=\"moderate\">{}</emphasis></p>", pause(HEADING_BREAK_MS), text)
+            } else {
+                format!("{}.", text.trim_end_matches(['.', ':']))
+            }
+        }
+        MarkdownNode::CodeBlock(lang, content) => paragraph(&escape(&code_summary(&CodeBlock::new(lang, content.as_str())), ssml), ssml),
+        MarkdownNode::List(items) | MarkdownNode::OrderedList(_, items) => {
+            let spoken: Vec<String> = items.iter().map(|item| block(item, ssml)).filter(|i| !i.trim().is_empty()).collect();
+            if ssml {
+                spoken.iter().map(|item| format!("<s>{}</s>{}", item, pause(ITEM_BREAK_MS))).collect()
+            } else {
+                spoken.iter().map(|item| sentence(item)).collect::<Vec<_>>().join(" ")
+            }
+        }
+        MarkdownNode::ListItem(children) => item_text(children, ssml),
+        MarkdownNode::TaskItem(checked, children) => {
+            let status = if *checked { "Done" } else { "To do" };
+            format!("{}: {}", status, item_text(children, ssml))
+        }
+        MarkdownNode::Blockquote(children) => {
+            let body: Vec<String> = children.iter().map(|child| block(child, ssml)).collect();
+            if ssml {
+                format!("<p>Quote.</p>{}<p>End quote.</p>", body.concat())
+            } else {
+                format!("Quote. {} End quote.", body.join(" "))
+            }
+        }
+        MarkdownNode::Table(_, rows) => paragraph(&escape(&table_summary(rows), ssml), ssml),
+        MarkdownNode::TableRow(children) | MarkdownNode::TableCell(children) => inline(children, ssml),
+        MarkdownNode::Rule => if ssml { pause(RULE_BREAK_MS) } else { String::new() },
+        // Footnotes are reference material, not part of the spoken reply.
+        MarkdownNode::FootnoteDefinition(..) => String::new(),
+        inline_node => inline(std::slice::from_ref(inline_node), ssml),
+    }
+}
+
+fn inline(nodes: &[MarkdownNode], ssml: bool) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            MarkdownNode::Text(text) => out.push_str(&spoken_text(text, ssml)),
+            MarkdownNode::InlineCode(code) => out.push_str(&escape(code, ssml)),
+            MarkdownNode::Strong(children) | MarkdownNode::Emphasis(children) => {
+                let inner = inline(children, ssml);
+                if ssml {
+                    let level = if matches!(node, MarkdownNode::Strong(_)) { "strong" } else { "moderate" };
+                    out.push_str(&format!("<emphasis level=\"{}\">{}</emphasis>", level, inner));
+                } else {
+                    out.push_str(&inner);
+                }
+            }
+            // Struck-out text was retracted; reading it would say the opposite.
+            MarkdownNode::Strikethrough(_) | MarkdownNode::FootnoteReference(_) => {}
+            MarkdownNode::Link(url, text) if text.is_empty() || text == url => {
+                out.push_str(&escape(&format!("a link to {}", domain(url)), ssml));
+            }
+            MarkdownNode::Link(_, text) => out.push_str(&spoken_text(text, ssml)),
+            MarkdownNode::Image(_, alt) if alt.is_empty() => out.push_str("an image"),
+            MarkdownNode::Image(_, alt) => out.push_str(&format!("an image of {}", spoken_text(alt, ssml))),
+            MarkdownNode::LineBreak => out.push_str(if ssml { "<break strength=\"weak\"/>" } else { ", " }),
+            block_node => out.push_str(&block(block_node, ssml)),
+        }
+    }
+    out
+}
+
+/// A list item's blocks run together as one sentence.
+fn item_text(children: &[MarkdownNode], ssml: bool) -> String {
+    let parts: Vec<String> = children
+        .iter()
+        .map(|child| match child {
+            MarkdownNode::Paragraph(inner) => inline(inner, ssml),
+            other => block(other, ssml),
+        })
+        .filter(|p| !p.trim().is_empty())
+        .collect();
+    parts.join(" ")
+}
+
+fn spoken_text(text: &str, ssml: bool) -> String {
+    let expanded = SpeechRenderer::expand_abbreviations(text);
+    if !ssml {
+        return expanded;
+    }
+    expanded
+        .split_inclusive(|c: char| !c.is_alphanumeric())
+        .map(|piece| {
+            let end = piece.trim_end_matches(|c: char| !c.is_alphanumeric()).len();
+            let (word, rest) = piece.split_at(end);
+            if SPELLED.contains(&word) {
+                format!("<say-as interpret-as=\"characters\">{}</say-as>{}", word, escape(rest, true))
+            } else {
+                escape(piece, true)
+            }
+        })
+        .collect()
+}
+
+fn code_summary(block: &CodeBlock) -> String {
+    let lines = block.content.lines().count();
+    let language = block.language.as_deref().map(language_name).unwrap_or_default();
+    let noun = if lines == 1 { "line" } else { "lines" };
+    format!("There's a {}{}code example, {} {} long, in the written reply.", language, if language.is_empty() { "" } else { " " }, lines, noun)
+}
+
+fn language_name(language: &str) -> String {
+    match language {
+        "javascript" => "JavaScript".to_string(),
+        "typescript" => "TypeScript".to_string(),
+        "bash" | "sh" => "shell".to_string(),
+        "json" | "yaml" | "sql" | "html" | "css" | "php" => language.to_uppercase(),
+        other => {
+            let mut chars = other.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+        }
+    }
+}
+
+fn table_summary(rows: &[MarkdownNode]) -> String {
+    let columns = rows.first().map(|header| match header {
+        MarkdownNode::TableRow(cells) => cells.iter().map(MarkdownNode::text_content).filter(|c| !c.trim().is_empty()).collect::<Vec<_>>(),
+        _ => Vec::new(),
+    }).unwrap_or_default();
+    let body_rows = rows.len().saturating_sub(1);
+    let noun = if body_rows == 1 { "row" } else { "rows" };
+    match columns.as_slice() {
+        [] => format!("There's a table with {} {} in the written reply.", body_rows, noun),
+        [only] => format!("There's a table of {} with {} {} in the written reply.", only, body_rows, noun),
+        [init @ .., last] => format!(
+            "There's a table in the written reply with {} {} and columns {} and {}.",
+            body_rows,
+            noun,
+            init.join(", "),
+            last
+        ),
+    }
+}
+
+fn domain(url: &str) -> &str {
+    let rest = url.split("://").nth(1).unwrap_or(url);
+    let host = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+fn paragraph(text: &str, ssml: bool) -> String {
+    if ssml { format!("<p>{}</p>", text) } else { text.to_string() }
+}
+
+fn sentence(text: &str) -> String {
+    let text = text.trim();
+    if text.ends_with(['.', '!', '?', ':']) { text.to_string() } else { format!("{}.", text) }
+}
+
+fn pause(ms: u32) -> String {
+    format!("<break time=\"{}ms\"/>", ms)
+}
+
+fn escape(text: &str, ssml: bool) -> String {
+    if !ssml {
+        return text.to_string();
+    }
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::IrParser;
+
+    fn ssml(markdown: &str) -> String {
+        SpeechRenderer::to_ssml(&IrParser::parse(markdown))
+    }
+
+    fn speech(markdown: &str) -> String {
+        SpeechRenderer::to_speech_text(&IrParser::parse(markdown))
+    }
+
+    #[test]
+    fn markup_characters_are_escaped_in_ssml() {
+        let out = ssml("Use a<b AT&T \"quoted\" and it's `x > 1 && y`");
+        assert_eq!(
+            out,
+            "<speak><p>Use a&lt;b AT&amp;T &quot;quoted&quot; and it&apos;s x &gt; 1 &amp;&amp; y</p></speak>"
+        );
+        // Plain speech text is left as written.
+        assert_eq!(speech("AT&T says \"hi\""), "AT&T says \"hi\"");
+    }
+
+    #[test]
+    fn code_and_tables_are_summarised_not_read() {
+        let out = speech("```rust\nfn main() {}\nlet x = 1;\n```\n\n| Name | Age |\n|---|---|\n| Ann | 3 |\n");
+        assert_eq!(
+            out,
+            "There's a Rust code example, 2 lines long, in the written reply.\n\n\
+             There's a table in the written reply with 1 row and columns Name and Age."
+        );
+    }
+
+    #[test]
+    fn headings_items_and_rules_get_breaks() {
+        let out = ssml("# Plan\n\n- one\n- two\n\n---\n");
+        assert_eq!(
+            out,
+            "<speak><break time=\"600ms\"/><p><emphasis level=\"moderate\">Plan</emphasis></p>\
+             <s>one</s><break time=\"250ms\"/><s>two</s><break time=\"250ms\"/>\
+             <break time=\"800ms\"/></speak>"
+        );
+        assert_eq!(speech("# Plan:\n\n- one\n- two\n"), "Plan.\n\none. two.");
+    }
+
+    #[test]
+    fn abbreviations_acronyms_and_urls_are_spoken() {
+        assert_eq!(
+            SpeechRenderer::expand_abbreviations("see https://www.example.com/docs, e.g. now & later"),
+            "see a link to example.com, for example now and later"
+        );
+        assert_eq!(ssml("Call the API"), "<speak><p>Call the <say-as interpret-as=\"characters\">API</say-as></p></speak>");
+    }
+
+    #[test]
+    fn struck_text_and_footnotes_are_dropped() {
+        assert_eq!(speech("Ship it ~~not~~ today[^1]\n\n[^1]: A note.\n"), "Ship it  today");
+    }
+}
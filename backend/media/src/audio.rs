@@ -1,17 +1,61 @@
+use crate::audio_preprocess::{merge_transcripts, AudioPreprocessor};
 use crate::{MediaHandler, MediaPayload};
 use async_trait::async_trait;
+use clawforge_tts::{OpenAiWhisperStt, SttProvider, SttRequest};
+use std::sync::Arc;
 use tracing::info;
 
+/// Transcribes audio with whichever STT provider is configured in `talk.stt`.
+pub struct SttHandler {
+    provider: Arc<dyn SttProvider>,
+    preprocessor: Option<AudioPreprocessor>,
+}
+
+impl SttHandler {
+    pub fn new(provider: Arc<dyn SttProvider>) -> Self {
+        Self { provider, preprocessor: None }
+    }
+
+    /// Convert, trim, normalize and chunk audio before it reaches the provider.
+    pub fn with_preprocessor(mut self, preprocessor: AudioPreprocessor) -> Self {
+        self.preprocessor = Some(preprocessor);
+        self
+    }
+}
+
+#[async_trait]
+impl MediaHandler for SttHandler {
+    async fn process(&self, payload: &MediaPayload) -> anyhow::Result<String> {
+        info!(
+            "Transcribing audio payload of {} bytes using {}",
+            payload.data.len(),
+            self.provider.name()
+        );
+        let Some(preprocessor) = &self.preprocessor else {
+            let transcript = self
+                .provider
+                .transcribe(SttRequest::new(payload.data.clone(), payload.mime_type.clone()))
+                .await?;
+            return Ok(transcript.text);
+        };
+
+        let mut parts = Vec::new();
+        for chunk in preprocessor.process(&payload.data).await? {
+            parts.push(self.provider.transcribe(SttRequest::new(chunk, "audio/wav")).await?.text);
+        }
+        Ok(merge_transcripts(&parts))
+    }
+}
+
+/// OpenAI Whisper API handler, kept for callers that construct it directly.
 pub struct WhisperSttHandler {
-    api_key: String,
-    model: String,
+    inner: SttHandler,
 }
 
 impl WhisperSttHandler {
     pub fn new(api_key: String, model: impl Into<String>) -> Self {
         Self {
-            api_key,
-            model: model.into(),
+            inner: SttHandler::new(Arc::new(OpenAiWhisperStt::new(api_key).with_model(model))),
         }
     }
 }
@@ -19,14 +63,6 @@ impl WhisperSttHandler {
 #[async_trait]
 impl MediaHandler for WhisperSttHandler {
     async fn process(&self, payload: &MediaPayload) -> anyhow::Result<String> {
-        info!("Transcribing audio payload of {} bytes using model {}", payload.data.len(), self.model);
-        
-        // In reality, this would make an API call to OpenAI Whisper API 
-        // using reqwest and the provided api_key.
-        // For the stub implementation:
-        
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-        
-        Ok(format!("[Transcribed using {}] ...audio content...", self.model))
+        self.inner.process(payload).await
     }
 }
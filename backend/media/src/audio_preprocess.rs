@@ -0,0 +1,283 @@
+//! Audio Preprocessing
+//!
+//! Turns inbound voice notes into what STT providers handle best: 16 kHz mono
+//! 16-bit PCM with leading/trailing silence trimmed, loudness normalized, and
+//! long recordings split into overlapping chunks. Decoding goes through ffmpeg
+//! unless the input is already canonical WAV.
+
+use anyhow::{bail, Context, Result};
+use bytes::Bytes;
+use clawforge_tts::pcm_to_wav;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, info};
+
+/// Sample rate every chunk is converted to.
+pub const CANONICAL_SAMPLE_RATE: u32 = 16_000;
+
+/// Analysis window used for silence detection (10 ms).
+const FRAME_SAMPLES: usize = CANONICAL_SAMPLE_RATE as usize / 100;
+
+#[derive(Debug, Clone)]
+pub struct PreprocessConfig {
+    /// Frames quieter than this (dBFS RMS) count as silence.
+    pub silence_threshold_dbfs: f32,
+    /// Loudness target (dBFS RMS). `None` leaves levels untouched.
+    pub target_rms_dbfs: Option<f32>,
+    /// Longest chunk handed to STT in one request.
+    pub max_chunk_secs: u32,
+    /// Audio repeated at the start of each chunk so words at a cut are not lost.
+    pub overlap_secs: u32,
+    /// ffmpeg binary used for decoding.
+    pub ffmpeg_path: String,
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        Self {
+            silence_threshold_dbfs: -45.0,
+            target_rms_dbfs: Some(-20.0),
+            max_chunk_secs: 600,
+            overlap_secs: 2,
+            ffmpeg_path: "ffmpeg".to_string(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Decoding
+// ---------------------------------------------------------------------------
+
+/// Samples of a 16 kHz mono 16-bit WAV, or `None` if `data` is anything else.
+fn parse_canonical_wav(data: &[u8]) -> Option<Vec<i16>> {
+    if data.len() < 44 || &data[..4] != b"RIFF" || &data[8..16] != b"WAVEfmt " {
+        return None;
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+    let u32_at = |i: usize| u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+    let canonical = u16_at(20) == 1 && u16_at(22) == 1 && u32_at(24) == CANONICAL_SAMPLE_RATE && u16_at(34) == 16;
+    if !canonical || u32_at(16) != 16 || &data[36..40] != b"data" {
+        return None;
+    }
+    let end = (44 + u32_at(40) as usize).min(data.len());
+    Some(pcm_from_bytes(&data[44..end]))
+}
+
+fn pcm_from_bytes(bytes: &[u8]) -> Vec<i16> {
+    bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect()
+}
+
+fn pcm_to_bytes(samples: &[i16]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+/// Decode any container/codec ffmpeg understands into canonical samples.
+async fn decode_with_ffmpeg(ffmpeg: &str, data: &[u8]) -> Result<Vec<i16>> {
+    let mut child = tokio::process::Command::new(ffmpeg)
+        .args(["-hide_banner", "-loglevel", "error", "-i", "pipe:0"])
+        .args(["-f", "s16le", "-acodec", "pcm_s16le", "-ac", "1", "-ar"])
+        .arg(CANONICAL_SAMPLE_RATE.to_string())
+        .arg("pipe:1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start {}", ffmpeg))?;
+
+    let mut stdin = child.stdin.take().context("ffmpeg stdin unavailable")?;
+    let input = data.to_vec();
+    let writer = tokio::spawn(async move {
+        // ffmpeg may stop reading early on bad input; the exit status reports that.
+        let _ = stdin.write_all(&input).await;
+    });
+    let output = child.wait_with_output().await?;
+    let _ = writer.await;
+
+    if !output.status.success() {
+        bail!("ffmpeg exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(pcm_from_bytes(&output.stdout))
+}
+
+// ---------------------------------------------------------------------------
+// Signal processing
+// ---------------------------------------------------------------------------
+
+fn rms_dbfs(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+    let mean_sq = samples.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / samples.len() as f64;
+    (20.0 * (mean_sq.sqrt() / i16::MAX as f64).log10()) as f32
+}
+
+/// Drop leading and trailing 10 ms frames whose RMS is below `threshold_dbfs`.
+pub fn trim_silence(samples: &[i16], threshold_dbfs: f32) -> &[i16] {
+    let loud = |frame: &[i16]| rms_dbfs(frame) >= threshold_dbfs;
+    let frames: Vec<&[i16]> = samples.chunks(FRAME_SAMPLES).collect();
+    let Some(first) = frames.iter().position(|f| loud(f)) else {
+        return &[];
+    };
+    let last = frames.iter().rposition(|f| loud(f)).unwrap_or(first);
+    let start = first * FRAME_SAMPLES;
+    let end = ((last + 1) * FRAME_SAMPLES).min(samples.len());
+    &samples[start..end]
+}
+
+/// Scale to `target_dbfs` RMS, capping the gain so the peak does not clip.
+pub fn normalize_loudness(samples: &mut [i16], target_dbfs: f32) {
+    let current = rms_dbfs(samples);
+    if !current.is_finite() {
+        return;
+    }
+    let peak = samples.iter().map(|&s| (s as i32).abs()).max().unwrap_or(0).max(1) as f32;
+    let max_gain = i16::MAX as f32 / peak;
+    let gain = 10f32.powf((target_dbfs - current) / 20.0).min(max_gain);
+    if (gain - 1.0).abs() < 0.01 {
+        return;
+    }
+    for s in samples.iter_mut() {
+        *s = (*s as f32 * gain).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    }
+}
+
+/// Split into windows of at most `max_secs`, each starting `overlap_secs`
+/// before the previous one ended.
+pub fn split_chunks(samples: &[i16], max_secs: u32, overlap_secs: u32) -> Vec<&[i16]> {
+    let rate = CANONICAL_SAMPLE_RATE as usize;
+    let max = (max_secs as usize * rate).max(rate);
+    let overlap = (overlap_secs as usize * rate).min(max / 2);
+    if samples.len() <= max {
+        return vec![samples];
+    }
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + max).min(samples.len());
+        chunks.push(&samples[start..end]);
+        if end == samples.len() {
+            break;
+        }
+        start = end - overlap;
+    }
+    chunks
+}
+
+/// Join chunk transcripts, dropping words repeated because of chunk overlap.
+pub fn merge_transcripts(parts: &[String]) -> String {
+    let mut words: Vec<&str> = Vec::new();
+    for part in parts {
+        let next: Vec<&str> = part.split_whitespace().collect();
+        let max_overlap = words.len().min(next.len()).min(20);
+        let same = |a: &str, b: &str| {
+            let clean = |w: &str| w.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+            clean(a) == clean(b)
+        };
+        let overlap = (1..=max_overlap)
+            .rev()
+            .find(|&n| words[words.len() - n..].iter().zip(&next[..n]).all(|(a, b)| same(a, b)))
+            .unwrap_or(0);
+        words.extend_from_slice(&next[overlap..]);
+    }
+    words.join(" ")
+}
+
+// ---------------------------------------------------------------------------
+// Pipeline
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Default)]
+pub struct AudioPreprocessor {
+    config: PreprocessConfig,
+}
+
+impl AudioPreprocessor {
+    pub fn new(config: PreprocessConfig) -> Self {
+        Self { config }
+    }
+
+    /// Decode, trim, normalize and chunk `data`. Each returned chunk is a
+    /// standalone canonical WAV; an all-silent input yields no chunks.
+    pub async fn process(&self, data: &[u8]) -> Result<Vec<Bytes>> {
+        let decoded = match parse_canonical_wav(data) {
+            Some(samples) => samples,
+            None => decode_with_ffmpeg(&self.config.ffmpeg_path, data).await?,
+        };
+        let mut samples = trim_silence(&decoded, self.config.silence_threshold_dbfs).to_vec();
+
+        if let Some(target) = self.config.target_rms_dbfs {
+            normalize_loudness(&mut samples, target);
+        }
+
+        let chunks = split_chunks(&samples, self.config.max_chunk_secs, self.config.overlap_secs);
+        info!(
+            "[Audio] Preprocessed {:.1}s -> {:.1}s in {} chunk(s)",
+            decoded.len() as f32 / CANONICAL_SAMPLE_RATE as f32,
+            samples.len() as f32 / CANONICAL_SAMPLE_RATE as f32,
+            chunks.len()
+        );
+        if samples.is_empty() {
+            debug!("[Audio] Input is silent after trimming");
+            return Ok(Vec::new());
+        }
+        Ok(chunks
+            .into_iter()
+            .map(|chunk| pcm_to_wav(&pcm_to_bytes(chunk), CANONICAL_SAMPLE_RATE, 1))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(secs: f32, amplitude: i16) -> Vec<i16> {
+        let n = (secs * CANONICAL_SAMPLE_RATE as f32) as usize;
+        (0..n).map(|i| if i % 2 == 0 { amplitude } else { -amplitude }).collect()
+    }
+
+    #[test]
+    fn trims_leading_and_trailing_silence() {
+        let mut samples = vec![0i16; 8_000];
+        samples.extend(tone(1.0, 8_000));
+        samples.extend(vec![0i16; 8_000]);
+        let trimmed = trim_silence(&samples, -45.0);
+        assert_eq!(trimmed.len(), CANONICAL_SAMPLE_RATE as usize);
+        assert!(trim_silence(&[0i16; 4_000], -45.0).is_empty());
+    }
+
+    #[test]
+    fn normalizes_without_clipping() {
+        let mut quiet = tone(0.5, 300);
+        normalize_loudness(&mut quiet, -20.0);
+        assert!((rms_dbfs(&quiet) + 20.0).abs() < 0.5);
+
+        let mut spiky = tone(0.5, 100);
+        spiky[0] = 30_000;
+        normalize_loudness(&mut spiky, -3.0);
+        assert!(spiky.iter().all(|&s| s > i16::MIN));
+    }
+
+    #[test]
+    fn chunks_overlap() {
+        let samples = tone(25.0, 1_000);
+        let chunks = split_chunks(&samples, 10, 2);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].len(), 160_000);
+        let total: usize = chunks.iter().map(|c| c.len()).sum();
+        assert_eq!(total, samples.len() + 2 * 2 * CANONICAL_SAMPLE_RATE as usize);
+    }
+
+    #[test]
+    fn merges_overlapping_words() {
+        let parts = vec!["the quick brown fox".to_string(), "Brown fox jumps over".to_string()];
+        assert_eq!(merge_transcripts(&parts), "the quick brown fox jumps over");
+    }
+
+    #[test]
+    fn canonical_wav_skips_ffmpeg() {
+        let wav = pcm_to_wav(&pcm_to_bytes(&[1, 2, 3]), CANONICAL_SAMPLE_RATE, 1);
+        assert_eq!(parse_canonical_wav(&wav), Some(vec![1, 2, 3]));
+        assert!(parse_canonical_wav(&pcm_to_wav(&[0; 4], 48_000, 2)).is_none());
+    }
+}
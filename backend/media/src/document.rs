@@ -0,0 +1,31 @@
+use crate::{MediaHandler, MediaPayload};
+use async_trait::async_trait;
+use clawforge_understanding::DocumentPipeline;
+use tracing::info;
+
+/// Parses PDF, DOCX, XLSX, PPTX and CSV attachments and returns the summary
+/// the session sees. Indexing into memory is configured on the pipeline.
+pub struct DocumentHandler {
+    pipeline: DocumentPipeline,
+}
+
+impl DocumentHandler {
+    pub fn new(pipeline: DocumentPipeline) -> Self {
+        Self { pipeline }
+    }
+}
+
+impl Default for DocumentHandler {
+    fn default() -> Self {
+        Self::new(DocumentPipeline::new())
+    }
+}
+
+#[async_trait]
+impl MediaHandler for DocumentHandler {
+    async fn process(&self, payload: &MediaPayload) -> anyhow::Result<String> {
+        info!("Parsing document payload of {} bytes ({})", payload.data.len(), payload.mime_type);
+        let digest = self.pipeline.process(&payload.data, &payload.mime_type, None, None).await?;
+        Ok(digest.summary)
+    }
+}
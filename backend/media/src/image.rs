@@ -1,7 +1,10 @@
 use crate::{MediaHandler, MediaPayload};
 use async_trait::async_trait;
+use clawforge_understanding::{describe_image, VisionProvider};
 use tracing::info;
 
+const DESCRIBE_PROMPT: &str = "Describe this image for someone who cannot see it, including any text in it.";
+
 pub struct VisionImageHandler {
     api_key: String,
     model: String,
@@ -20,12 +23,7 @@ impl VisionImageHandler {
 impl MediaHandler for VisionImageHandler {
     async fn process(&self, payload: &MediaPayload) -> anyhow::Result<String> {
         info!("Describing image payload of {} bytes using model {}", payload.data.len(), self.model);
-        
-        // In reality, this would make an API call to GPT-4o-vision or similar
-        // For the stub implementation:
-        
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-        
-        Ok(format!("[Analyzed using {}] ...a descriptive summary of the image...", self.model))
+        let provider = VisionProvider::OpenAI { api_key: self.api_key.clone(), model: self.model.clone() };
+        describe_image(&provider, &payload.data, &payload.mime_type, DESCRIBE_PROMPT).await
     }
 }
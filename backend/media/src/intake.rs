@@ -0,0 +1,242 @@
+//! Media intake checks: per-MIME-class size limits, temporary storage and
+//! antivirus scanning, all applied before any handler touches the bytes.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::{debug, warn};
+
+use crate::mime_detect::{is_audio, is_document, is_image, is_video};
+
+const MIB: u64 = 1024 * 1024;
+
+/// Maximum payload size per MIME class, in bytes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MediaLimits {
+    pub image: u64,
+    pub audio: u64,
+    pub video: u64,
+    pub document: u64,
+    /// Everything else.
+    pub other: u64,
+}
+
+impl Default for MediaLimits {
+    fn default() -> Self {
+        Self { image: 20 * MIB, audio: 25 * MIB, video: 100 * MIB, document: 50 * MIB, other: 10 * MIB }
+    }
+}
+
+impl MediaLimits {
+    /// The MIME class name and its limit.
+    pub fn limit_for(&self, mime: &str) -> (&'static str, u64) {
+        if is_image(mime) {
+            ("image", self.image)
+        } else if is_audio(mime) {
+            ("audio", self.audio)
+        } else if is_video(mime) {
+            ("video", self.video)
+        } else if is_document(mime) {
+            ("document", self.document)
+        } else {
+            ("other", self.other)
+        }
+    }
+}
+
+/// A payload written to the intake directory; the file is removed on drop.
+pub struct TempMedia {
+    path: PathBuf,
+}
+
+impl TempMedia {
+    pub async fn write(dir: &Path, data: &[u8]) -> Result<Self> {
+        tokio::fs::create_dir_all(dir).await.with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("intake-{}", uuid::Uuid::new_v4()));
+        tokio::fs::write(&path, data).await.with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempMedia {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!(path = %self.path.display(), error = %e, "Failed to remove temporary media");
+        }
+    }
+}
+
+/// Remove intake files older than `max_age`, left behind by a crash.
+pub fn sweep_temp_dir(dir: &Path, max_age: Duration) -> Result<usize> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let stale = entry.file_name().to_string_lossy().starts_with("intake-")
+            && entry.metadata().and_then(|m| m.modified()).ok().and_then(|t| t.elapsed().ok()).is_some_and(|age| age > max_age);
+        if stale && std::fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    debug!(dir = %dir.display(), removed, "Swept temporary media");
+    Ok(removed)
+}
+
+/// Outcome of an antivirus scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    /// Signature or scanner message.
+    Infected(String),
+}
+
+/// Antivirus hook run on every payload before it is handled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MediaScanner {
+    /// clamd over its INSTREAM protocol; `address` is `host:port` or a unix socket path.
+    Clamd { address: String },
+    /// `<program> <args...> <file>`, clamscan-style: exit 0 is clean, exit 1
+    /// is infected (first stdout line is the reason), anything else an error.
+    Command { program: String, #[serde(default)] args: Vec<String> },
+}
+
+/// Longest a scan may take before the payload is rejected.
+const SCAN_TIMEOUT: Duration = Duration::from_secs(60);
+
+impl MediaScanner {
+    pub async fn scan(&self, file: &Path) -> Result<ScanVerdict> {
+        tokio::time::timeout(SCAN_TIMEOUT, self.scan_inner(file)).await.context("Antivirus scan timed out")?
+    }
+
+    async fn scan_inner(&self, file: &Path) -> Result<ScanVerdict> {
+        match self {
+            Self::Clamd { address } => {
+                let data = tokio::fs::read(file).await?;
+                #[cfg(unix)]
+                if address.starts_with('/') {
+                    let stream = tokio::net::UnixStream::connect(address)
+                        .await
+                        .with_context(|| format!("Failed to connect to clamd at {}", address))?;
+                    return clamd_instream(stream, &data).await;
+                }
+                let stream = tokio::net::TcpStream::connect(address)
+                    .await
+                    .with_context(|| format!("Failed to connect to clamd at {}", address))?;
+                clamd_instream(stream, &data).await
+            }
+            Self::Command { program, args } => {
+                let output = tokio::process::Command::new(program)
+                    .args(args)
+                    .arg(file)
+                    .kill_on_drop(true)
+                    .output()
+                    .await
+                    .with_context(|| format!("Failed to run scanner {}", program))?;
+                match output.status.code() {
+                    Some(0) => Ok(ScanVerdict::Clean),
+                    Some(1) => {
+                        let stdout = String::from_utf8_lossy(&output.stdout);
+                        let reason = stdout.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("rejected by scanner");
+                        Ok(ScanVerdict::Infected(reason.to_string()))
+                    }
+                    _ => bail!("Scanner {} exited with {}: {}", program, output.status, String::from_utf8_lossy(&output.stderr)),
+                }
+            }
+        }
+    }
+}
+
+async fn clamd_instream<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, data: &[u8]) -> Result<ScanVerdict> {
+    stream.write_all(b"zINSTREAM\0").await?;
+    for chunk in data.chunks(64 * 1024) {
+        stream.write_all(&(chunk.len() as u32).to_be_bytes()).await?;
+        stream.write_all(chunk).await?;
+    }
+    stream.write_all(&0u32.to_be_bytes()).await?;
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    parse_clamd_reply(&String::from_utf8_lossy(&reply))
+}
+
+/// `stream: OK`, `stream: <signature> FOUND`, or an error message.
+fn parse_clamd_reply(reply: &str) -> Result<ScanVerdict> {
+    let reply = reply.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    let result = reply.strip_prefix("stream:").map(str::trim).unwrap_or(reply);
+    if result == "OK" {
+        Ok(ScanVerdict::Clean)
+    } else if let Some(signature) = result.strip_suffix("FOUND") {
+        Ok(ScanVerdict::Infected(signature.trim().to_string()))
+    } else {
+        bail!("clamd error: {}", reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_follow_mime_class() {
+        let limits = MediaLimits { video: 5, ..Default::default() };
+        assert_eq!(limits.limit_for("video/mp4"), ("video", 5));
+        assert_eq!(limits.limit_for("application/pdf"), ("document", 50 * MIB));
+        assert_eq!(limits.limit_for("application/x-msdownload"), ("other", 10 * MIB));
+    }
+
+    #[test]
+    fn clamd_replies_are_parsed() {
+        assert_eq!(parse_clamd_reply("stream: OK\0").unwrap(), ScanVerdict::Clean);
+        assert_eq!(
+            parse_clamd_reply("stream: Eicar-Test-Signature FOUND\0").unwrap(),
+            ScanVerdict::Infected("Eicar-Test-Signature".into())
+        );
+        assert!(parse_clamd_reply("INSTREAM size limit exceeded. ERROR\0").is_err());
+    }
+
+    #[tokio::test]
+    async fn clamd_scanner_streams_file_in_chunks() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut command = [0u8; 10];
+            socket.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+            let mut received = Vec::new();
+            loop {
+                let len = socket.read_u32().await.unwrap() as usize;
+                if len == 0 {
+                    break;
+                }
+                let mut chunk = vec![0u8; len];
+                socket.read_exact(&mut chunk).await.unwrap();
+                received.extend(chunk);
+            }
+            let reply: &[u8] = if received.starts_with(b"X5O!") { b"stream: Eicar-Test-Signature FOUND\0" } else { b"stream: OK\0" };
+            socket.write_all(reply).await.unwrap();
+        });
+
+        let dir = std::env::temp_dir().join(format!("clawforge-intake-{}", uuid::Uuid::new_v4()));
+        let file = TempMedia::write(&dir, b"X5O!P%@AP[4\\PZX54(P^)7CC)7}$EICAR").await.unwrap();
+        let verdict = MediaScanner::Clamd { address }.scan(file.path()).await.unwrap();
+        assert_eq!(verdict, ScanVerdict::Infected("Eicar-Test-Signature".into()));
+        server.await.unwrap();
+
+        let path = file.path().to_path_buf();
+        drop(file);
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
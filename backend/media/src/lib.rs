@@ -1,17 +1,29 @@
 use async_trait::async_trait;
 use bytes::Bytes;
 use clawforge_core::{Message, EventKind, Event};
+use std::path::PathBuf;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{info, warn};
 use uuid::Uuid;
 
 pub mod audio;
+pub mod audio_preprocess;
+pub mod document;
 pub mod image;
+pub mod intake;
 pub mod media_server;
 pub mod mime_detect;
+pub mod outbound;
+pub mod video;
 
-pub use media_server::media_router;
-pub use mime_detect::{detect_mime_type, is_audio, is_image, is_inline_safe, is_video};
+pub use audio_preprocess::{AudioPreprocessor, PreprocessConfig};
+pub use document::DocumentHandler;
+pub use intake::{sweep_temp_dir, MediaLimits, MediaScanner, ScanVerdict, TempMedia};
+pub use media_server::{media_router, signed_media_router};
+pub use outbound::{MediaStore, DEFAULT_URL_TTL};
+pub use video::VideoHandler;
+pub use mime_detect::{content_mime_type, detect_mime_type, extension_for, sniff_mime_type, is_audio, is_document, is_image, is_inline_safe, is_video};
 
 #[derive(Debug, Clone)]
 pub struct MediaPayload {
@@ -30,6 +42,13 @@ pub trait MediaHandler: Send + Sync {
 pub struct MediaPipeline {
     audio_handler: Box<dyn MediaHandler>,
     image_handler: Box<dyn MediaHandler>,
+    /// PDFs, Office documents and CSVs; unsupported when unset.
+    document_handler: Option<Box<dyn MediaHandler>>,
+    /// Videos; unsupported when unset.
+    video_handler: Option<Box<dyn MediaHandler>>,
+    limits: MediaLimits,
+    scanner: Option<MediaScanner>,
+    temp_dir: PathBuf,
     supervisor_tx: mpsc::Sender<Message>,
 }
 
@@ -42,38 +61,154 @@ impl MediaPipeline {
         Self {
             audio_handler,
             image_handler,
+            document_handler: None,
+            video_handler: None,
+            limits: MediaLimits::default(),
+            scanner: None,
+            temp_dir: std::env::temp_dir().join("clawforge-media"),
             supervisor_tx,
         }
     }
 
-    pub async fn handle_media(&self, run_id: Uuid, agent_id: Uuid, payload: MediaPayload) -> anyhow::Result<()> {
-        info!("Received media payload: {} from {}", payload.mime_type, payload.source);
+    /// Handle document payloads, e.g. a [`DocumentHandler`] that parses,
+    /// indexes and summarizes them.
+    pub fn with_document_handler(mut self, handler: Box<dyn MediaHandler>) -> Self {
+        self.document_handler = Some(handler);
+        self
+    }
+
+    /// Handle `video/*` payloads, e.g. with a [`VideoHandler`] that merges
+    /// keyframe descriptions and the audio transcript into a timeline.
+    pub fn with_video_handler(mut self, handler: Box<dyn MediaHandler>) -> Self {
+        self.video_handler = Some(handler);
+        self
+    }
+
+    /// Per-MIME-class payload size limits; `MediaLimits::default()` otherwise.
+    pub fn with_limits(mut self, limits: MediaLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Scan every payload before it reaches a handler; infected payloads and
+    /// failed scans are rejected.
+    pub fn with_scanner(mut self, scanner: MediaScanner) -> Self {
+        self.scanner = Some(scanner);
+        self
+    }
 
-        let result = if payload.mime_type.starts_with("audio/") {
-            self.audio_handler.process(&payload).await
-        } else if payload.mime_type.starts_with("image/") {
-            self.image_handler.process(&payload).await
+    /// Where payloads are staged for scanning; the system temp dir otherwise.
+    pub fn with_temp_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.temp_dir = dir.into();
+        self
+    }
+
+    /// Remove staged payloads older than `max_age` left behind by a crash.
+    pub fn sweep_temp(&self, max_age: Duration) -> anyhow::Result<usize> {
+        sweep_temp_dir(&self.temp_dir, max_age)
+    }
+
+    fn handler_for(&self, mime: &str) -> Option<&dyn MediaHandler> {
+        if is_audio(mime) {
+            Some(self.audio_handler.as_ref())
+        } else if is_image(mime) {
+            Some(self.image_handler.as_ref())
+        } else if is_video(mime) {
+            self.video_handler.as_deref()
+        } else if is_document(mime) {
+            self.document_handler.as_deref()
         } else {
-            warn!("Unsupported media type: {}", payload.mime_type);
-            anyhow::bail!("Unsupported media type");
+            None
+        }
+    }
+
+    async fn emit(&self, run_id: Uuid, agent_id: Uuid, kind: EventKind, payload: serde_json::Value) {
+        let event = Event::new(run_id, agent_id, kind, payload);
+        let _ = self.supervisor_tx.send(Message::AuditEvent(clawforge_core::AuditEventPayload { event })).await;
+    }
+
+    async fn reject(&self, run_id: Uuid, agent_id: Uuid, payload: &MediaPayload, reason: &str, detail: serde_json::Value) {
+        warn!("Rejected media from {} ({}): {}", payload.source, payload.mime_type, reason);
+        let mut body = serde_json::json!({
+            "source": payload.source,
+            "mime": payload.mime_type,
+            "size": payload.data.len(),
+            "reason": reason,
+        });
+        if let (Some(body), serde_json::Value::Object(detail)) = (body.as_object_mut(), detail) {
+            body.extend(detail);
+        }
+        self.emit(run_id, agent_id, EventKind::MediaRejected, body).await;
+    }
+
+    /// Check, stage, scan and process one payload. Unsupported, oversized and
+    /// infected payloads are rejected with an error after a `MediaRejected`
+    /// event; handler failures are reported the same way but return `Ok`.
+    /// The payload's type is taken from its content, not the declared type.
+    pub async fn handle_media(&self, run_id: Uuid, agent_id: Uuid, mut payload: MediaPayload) -> anyhow::Result<()> {
+        let detected = content_mime_type(&payload.mime_type, &payload.data);
+        if detected != payload.mime_type {
+            warn!("Media from {} declared {} but is {}", payload.source, payload.mime_type, detected);
+            payload.mime_type = detected;
+        }
+        info!("Received media payload: {} from {}", payload.mime_type, payload.source);
+        self.emit(
+            run_id,
+            agent_id,
+            EventKind::MediaReceived,
+            serde_json::json!({ "source": payload.source, "mime": payload.mime_type, "size": payload.data.len() }),
+        )
+        .await;
+
+        let Some(handler) = self.handler_for(&payload.mime_type) else {
+            self.reject(run_id, agent_id, &payload, "unsupported_type", serde_json::json!({})).await;
+            anyhow::bail!("Unsupported media type: {}", payload.mime_type);
         };
 
-        match result {
+        let (class, limit) = self.limits.limit_for(&payload.mime_type);
+        if payload.data.len() as u64 > limit {
+            self.reject(run_id, agent_id, &payload, "too_large", serde_json::json!({ "class": class, "limit": limit }))
+                .await;
+            anyhow::bail!("{} payload of {} bytes exceeds the {} byte limit", class, payload.data.len(), limit);
+        }
+
+        if let Some(scanner) = &self.scanner {
+            let staged = TempMedia::write(&self.temp_dir, &payload.data).await?;
+            match scanner.scan(staged.path()).await {
+                Ok(ScanVerdict::Clean) => {}
+                Ok(ScanVerdict::Infected(signature)) => {
+                    self.reject(run_id, agent_id, &payload, "infected", serde_json::json!({ "signature": signature }))
+                        .await;
+                    anyhow::bail!("Media rejected by antivirus: {}", signature);
+                }
+                Err(e) => {
+                    self.reject(run_id, agent_id, &payload, "scan_failed", serde_json::json!({ "error": e.to_string() }))
+                        .await;
+                    return Err(e.context("Antivirus scan failed"));
+                }
+            }
+        }
+
+        match handler.process(&payload).await {
             Ok(text) => {
-                let event = Event::new(
+                self.emit(
                     run_id,
                     agent_id,
-                    EventKind::RunStarted, // Map to appropriate run event
+                    EventKind::MediaProcessed,
                     serde_json::json!({
                         "source": payload.source,
-                        "type": "media_processed",
                         "mime": payload.mime_type,
+                        "size": payload.data.len(),
                         "extracted_text": text
-                    })
-                );
-                let _ = self.supervisor_tx.send(Message::AuditEvent(clawforge_core::AuditEventPayload { event })).await;
+                    }),
+                )
+                .await;
+            }
+            Err(e) => {
+                warn!("Failed to process media: {}", e);
+                self.reject(run_id, agent_id, &payload, "processing_failed", serde_json::json!({ "error": e.to_string() }))
+                    .await;
             }
-            Err(e) => warn!("Failed to process media: {}", e),
         }
 
         Ok(())
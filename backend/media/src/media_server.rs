@@ -1,26 +1,37 @@
 //! Local media server: serves stored media files over HTTP.
 //!
 //! Provides a simple Axum router that serves media by ID from the local store,
-//! with content-type headers and range request support.
+//! with content-type headers and range request support. The signed variant
+//! serves the outbound [`MediaStore`] and only answers valid `exp`/`sig` URLs.
 
 use anyhow::Result;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     routing::get,
     Router,
 };
+use serde::Deserialize;
 use std::{path::PathBuf, sync::Arc};
 use tokio::fs;
 use tracing::{debug, warn};
 
 use crate::mime_detect::{detect_mime_type, is_inline_safe};
+use crate::outbound::MediaStore;
 
 /// State shared by media server routes.
 #[derive(Clone)]
 pub struct MediaServerState {
     pub media_dir: Arc<PathBuf>,
+    /// When set, every request must carry a valid signature from this store.
+    pub signer: Option<Arc<MediaStore>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SignedQuery {
+    exp: Option<i64>,
+    sig: Option<String>,
 }
 
 /// Build the media server Axum router.
@@ -30,6 +41,20 @@ pub struct MediaServerState {
 pub fn media_router(media_dir: PathBuf) -> Router {
     let state = MediaServerState {
         media_dir: Arc::new(media_dir),
+        signer: None,
+    };
+    Router::new()
+        .route("/:filename", get(serve_media))
+        .with_state(state)
+}
+
+/// Build the router for outbound media, mounted at the store's base URL.
+///
+///   GET /media/:filename?exp=..&sig=..  — serve a stored file
+pub fn signed_media_router(store: Arc<MediaStore>) -> Router {
+    let state = MediaServerState {
+        media_dir: Arc::new(store.dir().to_path_buf()),
+        signer: Some(store),
     };
     Router::new()
         .route("/:filename", get(serve_media))
@@ -39,6 +64,7 @@ pub fn media_router(media_dir: PathBuf) -> Router {
 /// GET /:filename — stream a media file from the local store.
 async fn serve_media(
     Path(filename): Path<String>,
+    Query(query): Query<SignedQuery>,
     State(state): State<MediaServerState>,
 ) -> Response {
     // Basic path sanitization: reject traversal.
@@ -47,6 +73,13 @@ async fn serve_media(
         return (StatusCode::BAD_REQUEST, "Invalid filename").into_response();
     }
 
+    if let Some(signer) = &state.signer {
+        let valid = matches!((query.exp, query.sig.as_deref()), (Some(exp), Some(sig)) if signer.verify(&filename, exp, sig));
+        if !valid {
+            return (StatusCode::FORBIDDEN, "Invalid or expired media link").into_response();
+        }
+    }
+
     let path = state.media_dir.join(&filename);
     debug!(path = %path.display(), "Serving media file");
 
@@ -62,10 +95,8 @@ async fn serve_media(
             let mut headers = HeaderMap::new();
             headers.insert(header::CONTENT_TYPE, mime.parse().unwrap());
             headers.insert(header::CONTENT_DISPOSITION, disposition.parse().unwrap());
-            headers.insert(
-                header::CACHE_CONTROL,
-                "public, max-age=86400".parse().unwrap(),
-            );
+            let cache = if state.signer.is_some() { "private, max-age=3600" } else { "public, max-age=86400" };
+            headers.insert(header::CACHE_CONTROL, cache.parse().unwrap());
             headers.insert(
                 header::CONTENT_LENGTH,
                 bytes.len().to_string().parse().unwrap(),
@@ -49,11 +49,110 @@ pub fn detect_mime_type(path: &Path) -> &'static str {
         "json"         => "application/json",
         "xml"          => "application/xml",
         "csv"          => "text/csv",
+        "docx"         => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx"         => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx"         => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
 
         _              => "application/octet-stream",
     }
 }
 
+/// File extension to store a payload of type `mime` under.
+pub fn extension_for(mime: &str) -> &'static str {
+    match mime.split(';').next().unwrap_or_default().trim() {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "audio/mpeg" => "mp3",
+        "audio/ogg" => "ogg",
+        "audio/opus" => "opus",
+        "audio/wav" | "audio/x-wav" => "wav",
+        "audio/flac" => "flac",
+        "audio/aac" => "aac",
+        "audio/mp4" => "m4a",
+        "video/mp4" => "mp4",
+        "video/webm" => "webm",
+        "application/pdf" => "pdf",
+        "text/plain" => "txt",
+        "text/markdown" => "md",
+        "text/csv" => "csv",
+        "application/json" => "json",
+        _ => "bin",
+    }
+}
+
+/// Detect MIME type from a file's leading bytes; `None` when nothing matches.
+///
+/// Content wins over extensions for untrusted files: a download named
+/// `report.pdf` that starts with `MZ` is an executable.
+pub fn sniff_mime_type(head: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"MZ", "application/x-msdownload"),
+        (b"\x7fELF", "application/x-executable"),
+        (b"ID3", "audio/mpeg"),
+        (b"OggS", "audio/ogg"),
+        (b"fLaC", "audio/flac"),
+        (b"\x1a\x45\xdf\xa3", "video/webm"),
+    ];
+    if let Some((_, mime)) = SIGNATURES.iter().find(|(magic, _)| head.starts_with(magic)) {
+        return Some(mime);
+    }
+    if head.len() >= 12 && &head[..4] == b"RIFF" {
+        match &head[8..12] {
+            b"WEBP" => return Some("image/webp"),
+            b"WAVE" => return Some("audio/wav"),
+            _ => {}
+        }
+    }
+    // MPEG audio frame sync, for MP3s without an ID3 tag.
+    if head.len() >= 2 && head[0] == 0xff && head[1] & 0xe0 == 0xe0 {
+        return Some("audio/mpeg");
+    }
+    if head.len() >= 12 && &head[4..8] == b"ftyp" {
+        return Some(if &head[8..12] == b"M4A " { "audio/mp4" } else { "video/mp4" });
+    }
+    // A sample may end mid-character; only invalid bytes before that mean binary.
+    let text = match std::str::from_utf8(head) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&head[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    let lower: String = text.chars().take(64).collect::<String>().to_ascii_lowercase();
+    if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        Some("text/html")
+    } else if lower.starts_with("<?xml") {
+        Some("application/xml")
+    } else if lower.starts_with('{') || lower.starts_with('[') {
+        Some("application/json")
+    } else {
+        Some("text/plain")
+    }
+}
+
+/// The type of `data`, judged by its content rather than the `declared`
+/// type. The declaration is kept only where content cannot tell it apart:
+/// OOXML documents (ZIP archives) and text formats. Unrecognised binary
+/// content is `application/octet-stream`.
+pub fn content_mime_type(declared: &str, data: &[u8]) -> String {
+    let head = &data[..data.len().min(512)];
+    match sniff_mime_type(head) {
+        Some("application/zip") if declared.starts_with("application/vnd.openxmlformats-officedocument.") => declared.to_string(),
+        Some("text/plain") if declared.starts_with("text/") => declared.to_string(),
+        Some(sniffed) => sniffed.to_string(),
+        None => "application/octet-stream".to_string(),
+    }
+}
+
 /// Whether a MIME type is for an image.
 pub fn is_image(mime: &str) -> bool {
     mime.starts_with("image/")
@@ -69,6 +168,11 @@ pub fn is_video(mime: &str) -> bool {
     mime.starts_with("video/")
 }
 
+/// Whether a MIME type is a document the document pipeline can parse.
+pub fn is_document(mime: &str) -> bool {
+    mime == "application/pdf" || mime == "text/csv" || mime.starts_with("application/vnd.openxmlformats-officedocument.")
+}
+
 /// Whether a file is safe to serve inline (not just download).
 pub fn is_inline_safe(mime: &str) -> bool {
     matches!(
@@ -95,6 +199,26 @@ mod tests {
         assert_eq!(detect_mime_type(&PathBuf::from("speech.mp3")), "audio/mpeg");
     }
 
+    #[test]
+    fn sniffs_content_over_extension() {
+        assert_eq!(sniff_mime_type(b"%PDF-1.7\n..."), Some("application/pdf"));
+        assert_eq!(sniff_mime_type(b"MZ\x90\x00\x03"), Some("application/x-msdownload"));
+        assert_eq!(sniff_mime_type(b"  <!DOCTYPE html><html>"), Some("text/html"));
+        assert_eq!(sniff_mime_type(b"plain notes"), Some("text/plain"));
+        assert_eq!(sniff_mime_type(&[0xde, 0xad, 0xbe, 0xef]), None);
+    }
+
+    #[test]
+    fn content_overrides_declared_types() {
+        assert_eq!(content_mime_type("image/png", b"MZ\x90\x00\x03"), "application/x-msdownload");
+        assert_eq!(content_mime_type("audio/mpeg", &[0xde, 0xad, 0xbe, 0xef]), "application/octet-stream");
+        assert_eq!(content_mime_type("audio/mpeg", &[0xff, 0xfb, 0x90, 0x00]), "audio/mpeg");
+        let docx = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+        assert_eq!(content_mime_type(docx, b"PK\x03\x04rest"), docx);
+        assert_eq!(content_mime_type("text/csv", b"a,b\n1,2\n"), "text/csv");
+        assert_eq!(content_mime_type("text/csv", b"%PDF-1.7"), "application/pdf");
+    }
+
     #[test]
     fn unknown_extension_fallback() {
         assert_eq!(detect_mime_type(&PathBuf::from("file.xyz")), "application/octet-stream");
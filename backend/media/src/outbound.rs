@@ -0,0 +1,154 @@
+//! Outbound media store: keeps files generated for replies (tool images,
+//! TTS audio, exports) and hands out expiring HMAC-signed URLs for them.
+//!
+//! URLs have the form `{base_url}/{stored_name}?exp={unix}&sig={hex}`, where
+//! `sig` is HMAC-SHA256 over `{stored_name}:{exp}`. The signed media router
+//! refuses requests whose signature is missing, wrong or expired.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use clawforge_core::{OutboundMedia, OutboundMediaKind};
+use clawforge_tts::TtsToolOutput;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::mime_detect::extension_for;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How long a signed URL stays valid by default.
+pub const DEFAULT_URL_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+pub struct MediaStore {
+    dir: PathBuf,
+    /// Public prefix the signed media router is mounted at, e.g. `https://claw.example.com/media`.
+    base_url: String,
+    secret: Vec<u8>,
+    ttl: Duration,
+}
+
+impl MediaStore {
+    pub fn new(dir: impl Into<PathBuf>, base_url: impl Into<String>, secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            dir: dir.into(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            secret: secret.into(),
+            ttl: DEFAULT_URL_TTL,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// `$CLAWFORGE_MEDIA_DIR`, or the platform data dir.
+    pub fn default_dir() -> PathBuf {
+        std::env::var_os("CLAWFORGE_MEDIA_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| dirs::data_dir().unwrap_or_else(std::env::temp_dir).join("clawforge").join("media"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Store `data` under a fresh unguessable name and return the attachment
+    /// with a signed URL.
+    pub async fn store(&self, data: &[u8], mime_type: &str, file_name: &str, caption: Option<String>) -> Result<OutboundMedia> {
+        tokio::fs::create_dir_all(&self.dir).await.with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let stored = format!("{}.{}", Uuid::new_v4().simple(), extension_for(mime_type));
+        let path = self.dir.join(&stored);
+        tokio::fs::write(&path, data).await.with_context(|| format!("Failed to write {}", path.display()))?;
+        info!("[Media] Stored outbound {} ({} bytes, {})", file_name, data.len(), mime_type);
+        Ok(OutboundMedia {
+            kind: OutboundMediaKind::from_mime(mime_type),
+            mime_type: mime_type.to_string(),
+            file_name: file_name.to_string(),
+            url: self.signed_url(&stored),
+            path: Some(path.display().to_string()),
+            caption,
+            size: data.len() as u64,
+        })
+    }
+
+    /// Store the audio returned by the TTS tool as a voice attachment.
+    pub async fn store_tts(&self, output: &TtsToolOutput) -> Result<OutboundMedia> {
+        let audio = general_purpose::STANDARD.decode(&output.audio_base64).context("TTS output is not valid base64")?;
+        let file_name = format!("reply.{}", extension_for(&output.mime_type));
+        self.store(&audio, &output.mime_type, &file_name, None).await
+    }
+
+    pub fn signed_url(&self, stored_name: &str) -> String {
+        let exp = Utc::now().timestamp() + self.ttl.as_secs() as i64;
+        format!("{}/{}?exp={}&sig={}", self.base_url, stored_name, exp, self.signature(stored_name, exp))
+    }
+
+    fn signature(&self, stored_name: &str, exp: i64) -> String {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}:{}", stored_name, exp).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// Whether `sig` is a valid, unexpired signature for `stored_name`.
+    pub fn verify(&self, stored_name: &str, exp: i64, sig: &str) -> bool {
+        if exp < Utc::now().timestamp() {
+            debug!(file = stored_name, "Signed media URL expired");
+            return false;
+        }
+        let Ok(sig) = hex::decode(sig) else {
+            return false;
+        };
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}:{}", stored_name, exp).as_bytes());
+        mac.verify_slice(&sig).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(url: &str) -> (String, i64, String) {
+        let (path, query) = url.split_once('?').unwrap();
+        let name = path.rsplit('/').next().unwrap().to_string();
+        let mut exp = 0;
+        let mut sig = String::new();
+        for pair in query.split('&') {
+            match pair.split_once('=').unwrap() {
+                ("exp", v) => exp = v.parse().unwrap(),
+                ("sig", v) => sig = v.to_string(),
+                _ => {}
+            }
+        }
+        (name, exp, sig)
+    }
+
+    #[tokio::test]
+    async fn stored_media_gets_verifiable_url() {
+        let dir = std::env::temp_dir().join(format!("clawforge-outbound-{}", Uuid::new_v4()));
+        let store = MediaStore::new(&dir, "https://claw.example.com/media/", "secret");
+        let media = store.store(b"\x89PNG....", "image/png", "chart.png", Some("Q3".into())).await.unwrap();
+        assert_eq!(media.kind, OutboundMediaKind::Image);
+        assert!(media.url.starts_with("https://claw.example.com/media/"));
+        assert!(Path::new(media.path.as_deref().unwrap()).exists());
+
+        let (name, exp, sig) = query(&media.url);
+        assert!(name.ends_with(".png"));
+        assert!(store.verify(&name, exp, &sig));
+        assert!(!store.verify(&name, exp + 1, &sig));
+        assert!(!store.verify("other.png", exp, &sig));
+        assert!(!MediaStore::new(&dir, "", "other").verify(&name, exp, &sig));
+
+        let expired = MediaStore::new(&dir, "", "secret");
+        let past = Utc::now().timestamp() - 1;
+        assert!(!expired.verify(&name, past, &expired.signature(&name, past)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{MediaHandler, MediaPayload};
+use async_trait::async_trait;
+use clawforge_understanding::VideoPipeline;
+use tracing::info;
+
+/// Runs videos through keyframe extraction, frame description and audio
+/// transcription, returning the merged timeline for the session.
+pub struct VideoHandler {
+    pipeline: VideoPipeline,
+}
+
+impl VideoHandler {
+    pub fn new(pipeline: VideoPipeline) -> Self {
+        Self { pipeline }
+    }
+}
+
+#[async_trait]
+impl MediaHandler for VideoHandler {
+    async fn process(&self, payload: &MediaPayload) -> anyhow::Result<String> {
+        info!("Processing video payload of {} bytes ({})", payload.data.len(), payload.mime_type);
+        // ffmpeg needs a seekable file, not a pipe.
+        let ext = payload.mime_type.strip_prefix("video/").unwrap_or("mp4").trim_start_matches("x-");
+        let path = std::env::temp_dir().join(format!("clawforge-video-{}.{}", uuid::Uuid::new_v4(), ext));
+        tokio::fs::write(&path, &payload.data).await?;
+        let result = self.pipeline.process(&path).await;
+        let _ = tokio::fs::remove_file(&path).await;
+        Ok(result?.to_context())
+    }
+}
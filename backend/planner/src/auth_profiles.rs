@@ -87,6 +87,25 @@ impl AuthProfileManager {
         }
     }
 
+    /// Replace the profiles after a config reload. Profiles whose id and key
+    /// are unchanged keep their cooldown state; rotated keys start fresh and
+    /// removed profiles are no longer handed out. Requests already holding a
+    /// key finish with it.
+    pub fn reload(&mut self, profiles: Vec<AuthProfile>) {
+        let previous = std::mem::take(&mut self.profiles);
+        self.profiles = profiles
+            .into_iter()
+            .map(|mut p| {
+                if let Some(old) = previous.iter().find(|o| o.id == p.id && o.api_key == p.api_key) {
+                    p.cooldown_until = old.cooldown_until;
+                    p.fail_count = old.fail_count;
+                }
+                p
+            })
+            .collect();
+        info!("[AuthProfile] Reloaded {} profiles", self.profiles.len());
+    }
+
     /// Mark a profile as succeeded — reset its cooldown.
     pub fn mark_success(&mut self, profile_id: &str) {
         if let Some(p) = self.profiles.iter_mut().find(|p| p.id == profile_id) {
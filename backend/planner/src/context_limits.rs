@@ -0,0 +1,225 @@
+//! Context-window limits learned from provider errors.
+//!
+//! A configured `context_window` can be stale or simply wrong (a provider
+//! serving a quantized variant, a router capping a model below its spec).
+//! When a provider rejects a request for being too long, the limit it reports
+//! — or, failing that, an estimate just under the rejected request — is
+//! recorded for that (provider, model) in the shared `ModelCatalog` and
+//! checked, together with the declared window, before later requests go out.
+//! Learned limits are persisted to `~/.clawforge/cache/model-limits.json` so
+//! they survive restarts.
+
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use clawforge_tools::{LearnedLimit, ModelCatalog};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Phrases providers use when a request exceeds the context window.
+const OVERFLOW_MARKERS: &[&str] = &[
+    "context_length_exceeded",
+    "maximum context length",
+    "context length",
+    "context window",
+    "prompt is too long",
+    "too many tokens",
+    "maximum number of tokens",
+    "reduce the length",
+];
+
+/// Phrases followed by the limit (`maximum context length is 8192`).
+const LIMIT_AFTER: &[&str] = &[
+    "maximum context length is",
+    "maximum number of tokens allowed",
+    "context length of",
+    "context window of",
+    "context window is",
+    "limit of",
+    "maximum of",
+];
+
+/// Phrases preceded by the limit (`210000 tokens > 200000 maximum`).
+const LIMIT_BEFORE: &[&str] = &["maximum", "token limit"];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    #[serde(default)]
+    limits: Vec<LearnedLimit>,
+}
+
+/// Learns limits into the model catalog and persists them.
+#[derive(Clone)]
+pub struct ContextLimitCache {
+    catalog: Arc<RwLock<ModelCatalog>>,
+    path: Option<PathBuf>,
+    /// Serializes saves so an older snapshot never lands after a newer one.
+    saving: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl ContextLimitCache {
+    /// In-memory only.
+    pub fn new(catalog: Arc<RwLock<ModelCatalog>>) -> Self {
+        Self { catalog, path: None, saving: Arc::default() }
+    }
+
+    /// Load learned limits from `path` into `catalog` (missing or unreadable
+    /// files add nothing) and save new ones back to it.
+    pub fn open(catalog: Arc<RwLock<ModelCatalog>>, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let file = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str::<CacheFile>(&raw).ok())
+            .unwrap_or_default();
+        {
+            let mut catalog = catalog.write().unwrap();
+            for limit in file.limits {
+                catalog.learn_limit(limit);
+            }
+        }
+        Self { catalog, path: Some(path), saving: Arc::default() }
+    }
+
+    /// `~/.clawforge/cache/model-limits.json`, or in-memory without a home dir.
+    pub fn open_default(catalog: Arc<RwLock<ModelCatalog>>) -> Self {
+        match dirs::home_dir() {
+            Some(home) => Self::open(catalog, home.join(".clawforge").join("cache").join("model-limits.json")),
+            None => Self::new(catalog),
+        }
+    }
+
+    /// The tightest known window for `model` on `provider`, declared or learned.
+    pub fn limit(&self, provider: &str, model: &str) -> Option<usize> {
+        self.catalog.read().unwrap().context_window(provider, model)
+    }
+
+    /// Learn from `provider`'s error for a request of about `requested`
+    /// tokens. Returns the new limit when the error was a context overflow
+    /// that lowered what we knew.
+    pub fn observe(&self, provider: &str, model: &str, error: &str, requested: usize) -> Option<usize> {
+        let lower = error.to_lowercase();
+        if !OVERFLOW_MARKERS.iter().any(|m| lower.contains(m)) {
+            return None;
+        }
+        let (context_window, reported) = match reported_limit(&lower) {
+            Some(limit) => (limit, true),
+            // Our estimate is rough; stay well under what was rejected.
+            None => (requested * 9 / 10, false),
+        };
+        if context_window == 0 {
+            return None;
+        }
+        let learned_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        let limit = LearnedLimit { provider: provider.to_string(), model: model.to_string(), context_window, reported, learned_at };
+        if !self.catalog.write().unwrap().learn_limit(limit) {
+            return None;
+        }
+        info!(%provider, %model, context_window, reported, "Learned context window from provider error");
+        self.persist();
+        Some(context_window)
+    }
+
+    /// Write the learned limits in the background, off the catalog lock.
+    fn persist(&self) {
+        let Some(path) = self.path.clone() else { return };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else { return };
+        let (catalog, saving) = (self.catalog.clone(), self.saving.clone());
+        runtime.spawn(async move {
+            let _saving = saving.lock().await;
+            let file = CacheFile { limits: catalog.read().unwrap().learned_limits() };
+            if let Err(e) = save(&path, &file).await {
+                warn!(error = %e, path = %path.display(), "Failed to persist learned context limits");
+            }
+        });
+    }
+}
+
+async fn save(path: &std::path::Path, file: &CacheFile) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    tokio::fs::write(path, serde_json::to_string_pretty(file)?).await?;
+    Ok(())
+}
+
+/// The limit stated in an (already lowercased) overflow message, if any.
+fn reported_limit(message: &str) -> Option<usize> {
+    let after = LIMIT_AFTER.iter().find_map(|phrase| {
+        let at = message.find(phrase)? + phrase.len();
+        leading_number(message[at..].trim_start_matches(|c: char| c.is_whitespace() || matches!(c, '(' | ':' | '=')))
+    });
+    after.or_else(|| {
+        LIMIT_BEFORE.iter().find_map(|phrase| {
+            let at = message.find(phrase)?;
+            trailing_number(message[..at].trim_end())
+        })
+    })
+}
+
+fn leading_number(s: &str) -> Option<usize> {
+    let digits: String = s.chars().take_while(|c| c.is_ascii_digit() || *c == ',').filter(|c| *c != ',').collect();
+    digits.parse().ok()
+}
+
+fn trailing_number(s: &str) -> Option<usize> {
+    let digits: String = s.chars().rev().take_while(|c| c.is_ascii_digit() || *c == ',').filter(|c| *c != ',').collect();
+    digits.chars().rev().collect::<String>().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> ContextLimitCache {
+        ContextLimitCache::new(Arc::new(RwLock::new(ModelCatalog::new())))
+    }
+
+    #[test]
+    fn learns_reported_and_estimated_limits() {
+        let cache = cache();
+        let openai = "This model's maximum context length is 8,192 tokens. However, you requested 9000 tokens (8000 in the messages, 1000 in the completion).";
+        assert_eq!(cache.observe("openai", "gpt-4", openai, 9_000), Some(8_192));
+        let anthropic = "invalid_request_error: prompt is too long: 210000 tokens > 200000 maximum";
+        assert_eq!(cache.observe("anthropic", "claude", anthropic, 215_000), Some(200_000));
+        let gemini = "The input token count (1048577) exceeds the maximum number of tokens allowed (1048576).";
+        assert_eq!(cache.observe("google", "gemini", gemini, 1_050_000), Some(1_048_576));
+        assert_eq!(cache.observe("ollama", "local", "error: context_length_exceeded", 10_000), Some(9_000));
+
+        // Never raised by a later, looser report; unrelated errors are ignored.
+        assert_eq!(cache.observe("openai", "gpt-4", "maximum context length is 16384 tokens", 20_000), None);
+        assert_eq!(cache.observe("openai", "gpt-4", "rate limit exceeded", 100), None);
+        assert_eq!(cache.limit("openai", "gpt-4"), Some(8_192));
+        // Another provider serving the same model keeps its own limit.
+        assert_eq!(cache.limit("openrouter", "gpt-4"), None);
+    }
+
+    #[test]
+    fn declared_windows_apply_until_a_provider_enforces_less() {
+        let cache = cache();
+        assert_eq!(cache.limit("openai", "gpt-4o"), Some(128_000));
+        cache.observe("openrouter", "gpt-4o", "maximum context length is 64000 tokens", 70_000);
+        assert_eq!(cache.limit("openrouter", "gpt-4o"), Some(64_000));
+        assert_eq!(cache.limit("openai", "gpt-4o"), Some(128_000));
+    }
+
+    #[tokio::test]
+    async fn learned_limits_persist_across_restarts() {
+        let path = std::env::temp_dir().join(format!("clawforge-limits-{}.json", uuid::Uuid::new_v4()));
+        let catalog = || Arc::new(RwLock::new(ModelCatalog::new()));
+        let cache = ContextLimitCache::open(catalog(), &path);
+        cache.observe("openai", "gpt-4", "maximum context length is 8192 tokens", 9_000);
+        // The save runs in the background.
+        let mut reopened = None;
+        for _ in 0..100 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            reopened = ContextLimitCache::open(catalog(), &path).limit("openai", "gpt-4");
+            if reopened.is_some() {
+                break;
+            }
+        }
+        assert_eq!(reopened, Some(8_192));
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -1,7 +1,9 @@
 pub mod auth_profiles;
+pub mod context_limits;
 pub mod planner;
 pub mod providers;
 pub mod skills;
 
 pub use auth_profiles::{AuthProfile, AuthProfileManager, FallbackChain};
+pub use context_limits::ContextLimitCache;
 pub use planner::LlmPlanner;
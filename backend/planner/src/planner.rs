@@ -1,5 +1,6 @@
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -7,13 +8,17 @@ use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 use clawforge_core::{
-    ActionProposal, AuditEventPayload, ClawError, Component, Event, EventKind,
-    LlmRequest, Message, PlanRequest, ProposedAction,
+    estimate_tokens, ActionProposal, AuditEventPayload, ClawError, Component, ContextBreakdown, ContextLog, Event, EventKind,
+    LlmRequest, LlmResponse, Message, OutputContract, PlanRequest, ProposedAction, SegmentKind,
     message::MemoryQueryRequest, // Add this
 };
 
+use crate::context_limits::ContextLimitCache;
 use crate::providers::ProviderRegistry;
 
+/// How long a run with an output contract stays eligible for repair re-plans.
+const CONTRACT_RUN_TTL: Duration = Duration::from_secs(3600);
+
 /// The Planner component receives PlanRequests and races multiple LLM providers
 /// to generate action proposals.
 pub struct LlmPlanner {
@@ -21,6 +26,16 @@ pub struct LlmPlanner {
     executor_tx: mpsc::Sender<Message>,
     supervisor_tx: mpsc::Sender<Message>,
     memory_tx: Option<mpsc::Sender<Message>>,
+    /// Where each assembled prompt's token breakdown is recorded.
+    context_log: Option<ContextLog>,
+    /// Context windows learned from provider overflow errors.
+    context_limits: Option<ContextLimitCache>,
+    /// Where each completion's token usage is recorded, per session.
+    costs: Option<infra::CostTracker>,
+    /// Resolves a request's `channel` and `sender` to the person behind them.
+    identities: Option<Arc<clawforge_security::IdentityRegistry>>,
+    /// USD a principal may spend per rolling day, across channels.
+    principal_budget_usd: Option<f64>,
     // We will inject tool definitions into the prompt, but the Executor actually runs them.
     // The planner needs to know ABOUT them.
 }
@@ -37,7 +52,68 @@ impl LlmPlanner {
             executor_tx,
             supervisor_tx,
             memory_tx,
+            context_log: None,
+            context_limits: None,
+            costs: None,
+            identities: None,
+            principal_budget_usd: None,
+        }
+    }
+
+    /// Record a per-segment token breakdown of every prompt in `log`.
+    pub fn with_context_log(mut self, log: ContextLog) -> Self {
+        self.context_log = Some(log);
+        self
+    }
+
+    /// Learn effective context windows from overflow errors and refuse
+    /// requests that would exceed them before they reach a provider.
+    pub fn with_context_limits(mut self, limits: ContextLimitCache) -> Self {
+        self.context_limits = Some(limits);
+        self
+    }
+
+    /// Record every completion's token usage under the request's session key
+    /// (its run id when it has none), for usage footers and budgets.
+    pub fn with_cost_tracker(mut self, costs: infra::CostTracker) -> Self {
+        self.costs = Some(costs);
+        self
+    }
+
+    /// Attribute requests from linked accounts to their person: the
+    /// `principal` context key is set from `channel` and `sender`.
+    pub fn with_identities(mut self, identities: Arc<clawforge_security::IdentityRegistry>) -> Self {
+        self.identities = Some(identities);
+        self
+    }
+
+    /// Refuse to plan for a principal who has spent `usd` in the last 24
+    /// hours. Needs `with_cost_tracker`.
+    pub fn with_principal_budget(mut self, usd: f64) -> Self {
+        self.principal_budget_usd = Some(usd);
+        self
+    }
+
+    /// The request's principal: set by the caller, or resolved from its
+    /// `channel` and `sender`.
+    fn principal_of(&self, request: &PlanRequest) -> Option<String> {
+        let context = &request.context;
+        if let Some(principal) = context.get("principal").and_then(|p| p.as_str()) {
+            return Some(principal.to_string());
         }
+        let channel = context.get("channel")?.as_str()?;
+        let sender = context.get("sender")?.as_str()?;
+        Some(match &self.identities {
+            Some(identities) => identities.principal(channel, sender),
+            None => format!("{}:{}", channel, sender),
+        })
+    }
+
+    /// What `principal` spent in the last 24 hours, when that uses up its budget.
+    async fn over_budget(&self, principal: &str) -> Option<f64> {
+        let (Some(budget), Some(costs)) = (self.principal_budget_usd, &self.costs) else { return None };
+        let spent = costs.principal_cost_usd(principal, chrono::Utc::now() - chrono::Duration::hours(24)).await;
+        (spent >= budget).then_some(spent)
     }
 
     /// Race all configured providers and return the first successful response.
@@ -48,40 +124,33 @@ impl LlmPlanner {
             return Err(ClawError::AllProvidersFailed);
         }
 
-        // Inject tool context if agent has tools
-        let mut system_prompt = request.agent.llm_policy.system_prompt.clone();
-        if !request.agent.allowed_tools.is_empty() {
-             system_prompt.push_str("\n\nYou have access to the following tools:\n");
-             for tool in &request.agent.allowed_tools {
-                 system_prompt.push_str(&format!("- {}\n", tool));
-                 // In a real implementation, we would look up the tool definition and inject schema here
-             }
-             system_prompt.push_str("\nTo use a tool, reply in the format:\nAction: ToolName(arg1=\"value\", arg2=\"value\")\n");
+        let (llm_request, breakdown) = Self::assemble(request).await;
+        if let Some(log) = &self.context_log {
+            let aliases: Vec<String> = request.context.get("session_key").and_then(|k| k.as_str()).map(str::to_string).into_iter().collect();
+            log.record(breakdown, &aliases);
         }
 
-        // Inject skills context if agent has skills
-        if !request.agent.allowed_skills.is_empty() {
-             system_prompt.push_str("\n\n=== AVAILABLE SKILLS ===\n");
-             for skill in &request.agent.allowed_skills {
-                 match crate::skills::load_skill(skill).await {
-                     Ok(content) => {
-                         system_prompt.push_str(&format!("\n--- SKILL: {} ---\n{}\n-------------------\n", skill, content));
-                     }
-                     Err(e) => {
-                         warn!(%skill, error = %e, "Failed to load skill for agent prompt");
-                     }
-                 }
-             }
-             system_prompt.push_str("========================\n");
-        }
-
-        let llm_request = LlmRequest {
-            model: request.agent.llm_policy.model.clone(),
-            system_prompt,
-            user_prompt: serde_json::to_string_pretty(&request.context)
-                .unwrap_or_else(|_| request.context.to_string()),
-            max_tokens: request.agent.llm_policy.max_tokens,
-            temperature: request.agent.llm_policy.temperature,
+        let requested = estimate_tokens(&llm_request.system_prompt) + estimate_tokens(&llm_request.user_prompt) + llm_request.max_tokens as usize;
+        // Only race providers whose window for the model fits the request.
+        let providers = match &self.context_limits {
+            Some(limits) => {
+                let limit = |p: &Arc<dyn clawforge_core::LlmProvider>| limits.limit(p.name(), &llm_request.model);
+                let largest = providers.iter().filter_map(limit).max();
+                let fitting: Vec<_> = providers.iter().filter(|p| limit(p).is_none_or(|l| requested <= l)).cloned().collect();
+                if fitting.is_empty() {
+                    return Err(ClawError::LlmError {
+                        provider: "preflight".to_string(),
+                        message: format!(
+                            "request needs ~{} tokens but {} accepts at most {}",
+                            requested,
+                            llm_request.model,
+                            largest.unwrap_or_default()
+                        ),
+                    });
+                }
+                fitting
+            }
+            None => providers,
         };
 
         info!(
@@ -109,11 +178,11 @@ impl LlmPlanner {
                                 latency_ms = response.latency_ms,
                                 "Provider responded"
                             );
-                            Ok(response)
+                            (name, Ok(response))
                         }
                         Err(e) => {
                             warn!(provider = %name, error = %e, "Provider failed");
-                            Err(e)
+                            (name, Err(e))
                         }
                     }
                 }
@@ -129,13 +198,14 @@ impl LlmPlanner {
 
         while let Some(result) = join_set.join_next().await {
             match result {
-                Ok(Ok(response)) => {
+                Ok((_, Ok(response))) => {
                     let elapsed = start.elapsed();
                     info!(
                         provider = %response.provider,
                         total_latency_ms = elapsed.as_millis(),
                         "Plan generated"
                     );
+                    self.record_usage(request, &response).await;
 
                     // Simple parser for "Action: ToolName(json_args)"
                     // Example: Action: file_write({"path": "foo.txt", "content": "bar"})
@@ -182,7 +252,10 @@ impl LlmPlanner {
                         tokens_used: response.tokens_used,
                     });
                 }
-                Ok(Err(e)) => {
+                Ok((provider, Err(e))) => {
+                    if let Some(limits) = &self.context_limits {
+                        limits.observe(&provider, &llm_request.model, &e.to_string(), requested);
+                    }
                     last_error = Some(e);
                 }
                 Err(e) => {
@@ -213,12 +286,18 @@ impl Component for LlmPlanner {
         
         // Track pending plan requests waiting for memory: run_id -> PlanRequest
         let mut pending_plans: std::collections::HashMap<uuid::Uuid, PlanRequest> = std::collections::HashMap::new();
+        // Runs whose first step has an output contract, kept so it can be re-planned
+        let mut contract_runs: HashMap<uuid::Uuid, (PlanRequest, Instant)> = HashMap::new();
 
         while let Some(msg) = rx.recv().await {
             match msg {
-                Message::PlanRequest(request) => {
+                Message::PlanRequest(mut request) => {
                     let run_id = request.run_id;
                     let agent_id = request.agent.id;
+                    let principal = self.principal_of(&request);
+                    if let (Some(principal), serde_json::Value::Object(map)) = (&principal, &mut request.context) {
+                        map.insert("principal".to_string(), principal.clone().into());
+                    }
 
                     info!(
                         run_id = %run_id,
@@ -231,6 +310,20 @@ impl Component for LlmPlanner {
                         event: Event::new(run_id, agent_id, EventKind::RunStarted, serde_json::json!({"source": "planner"}))
                     })).await;
 
+                    if let Some(spent) = match &principal {
+                        Some(principal) => self.over_budget(principal).await,
+                        None => None,
+                    } {
+                        warn!(run_id = %run_id, spent, "Principal is over its daily budget");
+                        let _ = self.supervisor_tx.send(Message::AuditEvent(AuditEventPayload {
+                            event: Event::new(run_id, agent_id, EventKind::RunFailed, serde_json::json!({
+                                "error": format!("daily budget reached (${:.2} spent in the last 24h)", spent),
+                                "principal": principal,
+                            })),
+                        })).await;
+                        continue;
+                    }
+
                     // 2. Check memory config
                     if let Some(_mem_config) = &request.agent.memory_config {
                          // Only query if we have a memory channel
@@ -247,6 +340,7 @@ impl Component for LlmPlanner {
                                 query_vector: mock_query,
                                 min_score: 0.7,
                                 limit: 3,
+                                principal: principal.clone(),
                             };
                             
                             // Store pending request
@@ -263,7 +357,9 @@ impl Component for LlmPlanner {
                     }
 
                     // 3. No memory or no config -> Plan immediately
-                    self.execute_planning(request).await;
+                    Self::track_contract_run(&mut contract_runs, &request);
+                    let step = Self::step_of(&request);
+                    self.execute_planning(request, step, 0).await;
                 }
                 Message::MemoryResponse(response) => {
                     if let Some(mut request) = pending_plans.remove(&response.run_id) {
@@ -274,11 +370,36 @@ impl Component for LlmPlanner {
                              map.insert("memory_context".to_string(), serde_json::json!(response.results));
                          }
 
-                         self.execute_planning(request).await;
+                         Self::track_contract_run(&mut contract_runs, &request);
+                         let step = Self::step_of(&request);
+                         self.execute_planning(request, step, 0).await;
                     } else {
                         warn!(run_id = %response.run_id, "Received memory response for unknown run");
                     }
                 }
+                Message::RepairOutput(repair) => {
+                    let Some((request, _)) = contract_runs.get(&repair.run_id) else {
+                        warn!(run_id = %repair.run_id, "Repair requested for unknown run");
+                        continue;
+                    };
+                    let mut request = request.clone();
+                    let last_attempt = Self::step_contract(&request, repair.step_index)
+                        .is_none_or(|contract| repair.attempt >= contract.max_repairs);
+                    if last_attempt {
+                        contract_runs.remove(&repair.run_id);
+                    }
+
+                    info!(run_id = %repair.run_id, attempt = repair.attempt, "Re-planning step to repair its output");
+                    match request.context {
+                        serde_json::Value::Object(ref mut map) => {
+                            map.insert("repair".to_string(), serde_json::json!(repair.prompt));
+                        }
+                        ref mut other => {
+                            *other = serde_json::json!({"input": other.take(), "repair": repair.prompt});
+                        }
+                    }
+                    self.execute_planning(request, repair.step_index, repair.attempt).await;
+                }
                 other => {
                     debug!(msg_type = ?other, "Planner ignoring non-plan message");
                 }
@@ -291,8 +412,125 @@ impl Component for LlmPlanner {
 }
 
 impl LlmPlanner {
+    /// Build the LLM request for `request`, and a breakdown of its tokens by
+    /// segment for the context endpoint.
+    async fn record_usage(&self, request: &PlanRequest, response: &LlmResponse) {
+        let Some(costs) = &self.costs else { return };
+        let session = match request.context.get("session_key").and_then(|k| k.as_str()) {
+            Some(key) => key.to_string(),
+            None => request.run_id.to_string(),
+        };
+        let clamp = |n: u64| u32::try_from(n).unwrap_or(u32::MAX);
+        let usage = infra::TokenUsage {
+            prompt_tokens: clamp(response.prompt_tokens),
+            completion_tokens: clamp(response.completion_tokens),
+            total_tokens: clamp(response.tokens_used),
+        };
+        let agent_id = request.agent.id.to_string();
+        let recorded = match request.context.get("principal").and_then(|p| p.as_str()) {
+            Some(principal) => costs.record_principal_usage(&session, principal, &agent_id, &response.model, usage).await,
+            None => costs.record_usage(&session, &agent_id, &response.model, usage).await,
+        };
+        if let Err(e) = recorded {
+            warn!(error = %e, "Failed to record token usage");
+        }
+    }
+
+    async fn assemble(request: &PlanRequest) -> (LlmRequest, ContextBreakdown) {
+        let policy = &request.agent.llm_policy;
+        let mut breakdown = ContextBreakdown::new(request.run_id.to_string(), &request.agent.name, &policy.model, policy.max_tokens);
+        let mut system_prompt = policy.system_prompt.clone();
+        breakdown.add(SegmentKind::SystemPrompt, [("", system_prompt.as_str())]);
+
+        // Inject tool context if agent has tools
+        let mut tools: Vec<(String, String)> = Vec::new();
+        if !request.agent.allowed_tools.is_empty() {
+             tools.push((String::new(), "\n\nYou have access to the following tools:\n".to_string()));
+             for tool in &request.agent.allowed_tools {
+                 tools.push((tool.clone(), format!("- {}\n", tool)));
+                 // In a real implementation, we would look up the tool definition and inject schema here
+             }
+             tools.push((String::new(), "\nTo use a tool, reply in the format:\nAction: ToolName(arg1=\"value\", arg2=\"value\")\n".to_string()));
+        }
+
+        // Inject skills context if agent has skills
+        let mut skills: Vec<(String, String)> = Vec::new();
+        if !request.agent.allowed_skills.is_empty() {
+             skills.push((String::new(), "\n\n=== AVAILABLE SKILLS ===\n".to_string()));
+             for skill in &request.agent.allowed_skills {
+                 match crate::skills::load_skill(skill).await {
+                     Ok(content) => {
+                         skills.push((skill.clone(), format!("\n--- SKILL: {} ---\n{}\n-------------------\n", skill, content)));
+                     }
+                     Err(e) => {
+                         warn!(%skill, error = %e, "Failed to load skill for agent prompt");
+                     }
+                 }
+             }
+             skills.push((String::new(), "========================\n".to_string()));
+        }
+
+        for (_, text) in tools.iter().chain(&skills) {
+            system_prompt.push_str(text);
+        }
+        breakdown.add(SegmentKind::Skills, skills.iter().map(|(name, text)| (name.as_str(), text.as_str())));
+        breakdown.add(SegmentKind::Tools, tools.iter().map(|(name, text)| (name.as_str(), text.as_str())));
+
+        // The user prompt is the run context; memory results and media
+        // waiting to be sent are broken out of the rest.
+        let pretty = |value: &serde_json::Value| serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string());
+        let mut history = request.context.clone();
+        let mut memory = Vec::new();
+        let mut media = Vec::new();
+        if let serde_json::Value::Object(map) = &mut history {
+            if let Some(value) = map.remove("memory_context") {
+                memory.push(("memory_context".to_string(), pretty(&value)));
+            }
+            for key in ["attachments", "media"] {
+                if let Some(value) = map.remove(key) {
+                    media.push((key.to_string(), pretty(&value)));
+                }
+            }
+        }
+        let history = match &history {
+            serde_json::Value::Object(map) if map.is_empty() => String::new(),
+            other => pretty(other),
+        };
+        breakdown.add(SegmentKind::Memory, memory.iter().map(|(name, text)| (name.as_str(), text.as_str())));
+        breakdown.add(SegmentKind::History, [("", history.as_str())]);
+        breakdown.add(SegmentKind::PendingMedia, media.iter().map(|(name, text)| (name.as_str(), text.as_str())));
+
+        let llm_request = LlmRequest {
+            model: policy.model.clone(),
+            system_prompt,
+            user_prompt: pretty(&request.context),
+            max_tokens: policy.max_tokens,
+            temperature: policy.temperature,
+        };
+        (llm_request, breakdown)
+    }
+
+    /// Workflow step a plan request is for: `step_index` in its context, or
+    /// the first step.
+    fn step_of(request: &PlanRequest) -> usize {
+        request.context.get("step_index").and_then(|s| s.as_u64()).unwrap_or(0) as usize
+    }
+
+    /// Output contract of workflow step `step`, if it declares one.
+    fn step_contract(request: &PlanRequest, step: usize) -> Option<&OutputContract> {
+        request.agent.workflow.get(step).and_then(|step| step.output_contract.as_ref())
+    }
+
+    /// Remember a run with any step under an output contract, for repairs.
+    fn track_contract_run(runs: &mut HashMap<uuid::Uuid, (PlanRequest, Instant)>, request: &PlanRequest) {
+        runs.retain(|_, (_, at)| at.elapsed() < CONTRACT_RUN_TTL);
+        if request.agent.workflow.iter().any(|step| step.output_contract.is_some()) {
+            runs.insert(request.run_id, (request.clone(), Instant::now()));
+        }
+    }
+
     /// Run the planning logic and dispatch to executor.
-    async fn execute_planning(&self, request: PlanRequest) {
+    async fn execute_planning(&self, request: PlanRequest, step_index: usize, repair_attempt: u32) {
         let run_id = request.run_id;
         let agent_id = request.agent.id;
 
@@ -306,9 +544,16 @@ impl LlmPlanner {
                 let proposal = Message::ExecuteAction(ActionProposal {
                     run_id,
                     agent_id,
-                    step_index: 0,
+                    step_index,
                     action,
                     capabilities: request.agent.capabilities.clone(),
+                    agent_name: Some(request.agent.name.clone()),
+                    channel: request.context.get("channel").and_then(|c| c.as_str()).map(str::to_string),
+                    output_contract: Self::step_contract(&request, step_index).cloned(),
+                    repair_attempt,
+                    session_id: request.context.get("session_id").and_then(|s| s.as_str()).map(str::to_string),
+                    principal: request.context.get("principal").and_then(|p| p.as_str()).map(str::to_string),
+                    chat_id: request.context.get("chat_id").and_then(|c| c.as_str()).map(str::to_string),
                 });
 
                 if let Err(e) = self.executor_tx.send(proposal).await {
@@ -324,3 +569,41 @@ impl LlmPlanner {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawforge_core::{AgentSpec, TriggerSpec};
+    use clawforge_security::{AccountRef, IdentityRegistry};
+
+    fn request(context: serde_json::Value) -> PlanRequest {
+        PlanRequest { run_id: uuid::Uuid::new_v4(), agent: AgentSpec::new("assistant", TriggerSpec::Manual), context }
+    }
+
+    #[tokio::test]
+    async fn linked_senders_share_a_principal_and_its_budget() {
+        let (tx, _rx) = mpsc::channel(1);
+        let identities = Arc::new(IdentityRegistry::new(300));
+        let person = identities.link(&AccountRef::new("telegram", "42"), &AccountRef::new("slack", "U1")).unwrap();
+        let costs = infra::CostTracker::new();
+        let planner = LlmPlanner::new(Arc::new(ProviderRegistry::new()), tx.clone(), tx, None)
+            .with_identities(identities)
+            .with_cost_tracker(costs.clone())
+            .with_principal_budget(0.05);
+
+        let from = |channel: &str, sender: &str| planner.principal_of(&request(serde_json::json!({ "channel": channel, "sender": sender })));
+        assert_eq!(from("telegram", "42").as_deref(), Some(person.as_str()));
+        assert_eq!(from("slack", "U1").as_deref(), Some(person.as_str()));
+        assert_eq!(from("slack", "U2").as_deref(), Some("slack:U2"));
+        assert_eq!(planner.principal_of(&request(serde_json::json!({ "principal": "owner", "channel": "slack", "sender": "U1" }))).as_deref(), Some("owner"));
+        assert_eq!(planner.principal_of(&request(serde_json::json!({ "trigger": "cron" }))), None);
+
+        let usage = infra::TokenUsage { prompt_tokens: 1000, completion_tokens: 0, total_tokens: 1000 };
+        costs.record_principal_usage("telegram:42", &person, "a", "gpt-4", usage).await.unwrap();
+        assert!(planner.over_budget(&person).await.is_none());
+        let usage = infra::TokenUsage { prompt_tokens: 1000, completion_tokens: 0, total_tokens: 1000 };
+        costs.record_principal_usage("slack:U1", &person, "a", "gpt-4", usage).await.unwrap();
+        assert!(planner.over_budget(&person).await.is_some());
+        assert!(planner.over_budget("slack:U2").await.is_none());
+    }
+}
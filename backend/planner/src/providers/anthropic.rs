@@ -126,17 +126,19 @@ impl LlmProvider for AnthropicProvider {
             .collect::<Vec<_>>()
             .join("");
 
-        let tokens_used = parsed
+        let (prompt_tokens, completion_tokens) = parsed
             .usage
-            .map(|u| u.input_tokens + u.output_tokens)
-            .unwrap_or(0);
+            .map(|u| (u.input_tokens, u.output_tokens))
+            .unwrap_or_default();
         let latency_ms = start.elapsed().as_millis() as u64;
 
         Ok(LlmResponse {
             content,
             provider: "anthropic".to_string(),
             model: request.model.clone(),
-            tokens_used,
+            tokens_used: prompt_tokens + completion_tokens,
+            prompt_tokens,
+            completion_tokens,
             latency_ms,
         })
     }
@@ -6,13 +6,17 @@
 //! variables, and the governance layer uses to reason about model origin and
 //! data residency.
 
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
+use clawforge_core::LlmProvider;
 use tracing::info;
 
 use super::anthropic::AnthropicProvider;
+use super::ollama::OllamaProvider;
 use super::openai_compatible::OpenAiCompatibleProvider;
-use super::ProviderRegistry;
+use super::{ProviderRegistry, ProviderSpec};
 
 /// Wire protocol a provider speaks.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -164,6 +168,27 @@ pub fn chinese() -> Vec<&'static ProviderInfo> {
     by_region(Region::China)
 }
 
+/// Build a client for catalog provider `id` with `api_key` (the server URL
+/// for Ollama), optionally pointed at `base_url` instead of the catalog's.
+pub fn build_provider(id: &str, api_key: &str, base_url: Option<&str>) -> Option<Arc<dyn LlmProvider>> {
+    let p = get(id)?;
+    let base_url = base_url.unwrap_or(p.base_url);
+    Some(match p.wire {
+        Wire::Anthropic => Arc::new(AnthropicProvider::new(api_key).with_base_url(base_url)),
+        Wire::OpenAiCompatible => Arc::new(OpenAiCompatibleProvider::new(p.id, base_url, api_key)),
+        Wire::Ollama => Arc::new(OllamaProvider::new().with_base_url(api_key)),
+    })
+}
+
+/// A reloadable `ProviderSpec` for catalog provider `id`. The fingerprint
+/// covers the key and base URL, so a rotated key replaces the client.
+pub fn provider_spec(id: &str, api_key: &str, base_url: Option<&str>) -> Option<ProviderSpec> {
+    let provider = build_provider(id, api_key, base_url)?;
+    let mut hasher = DefaultHasher::new();
+    (api_key, base_url).hash(&mut hasher);
+    Some(ProviderSpec { name: id.to_string(), fingerprint: format!("{:016x}", hasher.finish()), provider })
+}
+
 /// Register every catalog provider whose credential environment variable is set.
 ///
 /// `openrouter` and `ollama` are skipped here because the CLI wires them from
@@ -179,15 +204,8 @@ pub fn register_from_env(registry: &mut ProviderRegistry) -> Vec<String> {
             Ok(v) if !v.trim().is_empty() => v,
             _ => continue,
         };
-        match p.wire {
-            Wire::Anthropic => {
-                registry.register(p.id, Arc::new(AnthropicProvider::new(key).with_base_url(p.base_url)));
-            }
-            Wire::OpenAiCompatible => {
-                registry.register(p.id, Arc::new(OpenAiCompatibleProvider::new(p.id, p.base_url, key)));
-            }
-            Wire::Ollama => continue,
-        }
+        let Some(provider) = build_provider(p.id, &key, None) else { continue };
+        registry.register(p.id, provider);
         info!(provider = p.id, region = ?p.region, "Registered model provider from env");
         registered.push(p.id.to_string());
     }
@@ -34,6 +34,8 @@ impl LlmProvider for MockProvider {
             provider: self.name.clone(),
             model: "mock".to_string(),
             tokens_used: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
             latency_ms: 0,
         })
     }
@@ -6,39 +6,247 @@ pub mod anthropic;
 pub mod catalog;
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
-use clawforge_core::LlmProvider;
+use anyhow::Result;
+use async_trait::async_trait;
+use clawforge_core::{LlmProvider, LlmRequest, LlmResponse};
+use tracing::{info, warn};
+
+/// How often retired providers are checked for disposal.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Hands out a provider while counting the requests running on it.
+struct Tracked {
+    inner: Arc<dyn LlmProvider>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+/// Decrements the in-flight count when a request ends, however it ends.
+struct InFlightGuard(Arc<AtomicUsize>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl LlmProvider for Tracked {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn complete(&self, request: &LlmRequest) -> Result<LlmResponse> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let _guard = InFlightGuard(Arc::clone(&self.in_flight));
+        self.inner.complete(request).await
+    }
+}
+
+struct Entry {
+    provider: Arc<dyn LlmProvider>,
+    /// Requests currently running on `provider`.
+    in_flight: Arc<AtomicUsize>,
+    /// Fingerprint of the config the provider was built from; `None` for
+    /// providers registered at startup rather than from config.
+    fingerprint: Option<String>,
+}
+
+impl Entry {
+    fn new(provider: Arc<dyn LlmProvider>, fingerprint: Option<String>) -> Self {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let provider = Arc::new(Tracked { inner: provider, in_flight: Arc::clone(&in_flight) });
+        Self { provider, in_flight, fingerprint }
+    }
+}
+
+/// A provider replaced or removed by a reload, kept until its in-flight
+/// requests finish or the grace period ends.
+struct Retired {
+    name: String,
+    /// Kept alive until the drain ends.
+    _provider: Arc<dyn LlmProvider>,
+    in_flight: Arc<AtomicUsize>,
+    retired_at: Instant,
+}
+
+/// A retired provider that is still draining.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrainingProvider {
+    pub name: String,
+    /// Requests still running on the old client.
+    pub in_flight: usize,
+    pub retired_for: Duration,
+}
+
+/// A provider built from config, for `ProviderRegistry::reload`.
+pub struct ProviderSpec {
+    pub name: String,
+    /// Changes whenever the config behind the provider does (key, base URL…).
+    pub fingerprint: String,
+    pub provider: Arc<dyn LlmProvider>,
+}
+
+/// What a reload changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadSummary {
+    pub added: Vec<String>,
+    pub replaced: Vec<String>,
+    pub removed: Vec<String>,
+}
 
 /// Registry of LLM providers, looked up by name.
+///
+/// Providers can be swapped while requests are running: callers hold their
+/// own `Arc` to the client they started with, so a replaced or removed
+/// provider keeps serving those requests while new lookups get the new one.
+/// Every request through a handed-out client is counted, and the registry
+/// keeps retired clients in a draining list until no request is running on
+/// them or the grace period passes (`sweep`).
 pub struct ProviderRegistry {
-    providers: HashMap<String, Arc<dyn LlmProvider>>,
+    providers: RwLock<HashMap<String, Entry>>,
+    retired: Mutex<Vec<Retired>>,
 }
 
 impl ProviderRegistry {
     pub fn new() -> Self {
         Self {
-            providers: HashMap::new(),
+            providers: RwLock::new(HashMap::new()),
+            retired: Mutex::new(Vec::new()),
         }
     }
 
     /// Register a provider by name.
     pub fn register(&mut self, name: impl Into<String>, provider: Arc<dyn LlmProvider>) {
-        self.providers.insert(name.into(), provider);
+        self.providers.get_mut().unwrap().insert(name.into(), Entry::new(provider, None));
     }
 
     /// Get providers matching the given names (in order).
     /// Unknown names are silently skipped.
     pub fn get_providers(&self, names: &[String]) -> Vec<Arc<dyn LlmProvider>> {
+        let providers = self.providers.read().unwrap();
         names
             .iter()
-            .filter_map(|name| self.providers.get(name).cloned())
+            .filter_map(|name| providers.get(name).map(|e| e.provider.clone()))
             .collect()
     }
 
     /// Get all registered provider names.
     pub fn list(&self) -> Vec<String> {
-        self.providers.keys().cloned().collect()
+        self.providers.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Swap in `provider` for new requests; the previous one drains.
+    pub fn replace(&self, name: impl Into<String>, provider: Arc<dyn LlmProvider>) {
+        let name = name.into();
+        let old = self.providers.write().unwrap().insert(name.clone(), Entry::new(provider, None));
+        if let Some(old) = old {
+            self.retire(name, old);
+        }
+    }
+
+    /// Stop handing out `name`; requests already using it finish.
+    pub fn remove(&self, name: &str) -> bool {
+        let old = self.providers.write().unwrap().remove(name);
+        match old {
+            Some(old) => {
+                self.retire(name.to_string(), old);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Apply the providers defined in config. Providers whose fingerprint is
+    /// unchanged are kept; changed ones are replaced and config-defined ones
+    /// missing from `specs` are removed, both draining. Providers registered
+    /// at startup are left alone unless `specs` names them.
+    pub fn reload(&self, specs: Vec<ProviderSpec>) -> ReloadSummary {
+        let mut summary = ReloadSummary::default();
+        let mut retired = Vec::new();
+        {
+            let mut providers = self.providers.write().unwrap();
+            let names: Vec<&str> = specs.iter().map(|s| s.name.as_str()).collect();
+            let removed: Vec<String> = providers
+                .iter()
+                .filter(|(name, e)| e.fingerprint.is_some() && !names.contains(&name.as_str()))
+                .map(|(name, _)| name.clone())
+                .collect();
+            for name in removed {
+                if let Some(old) = providers.remove(&name) {
+                    retired.push((name.clone(), old));
+                    summary.removed.push(name);
+                }
+            }
+            for spec in specs {
+                match providers.get(&spec.name) {
+                    Some(e) if e.fingerprint.as_deref() == Some(spec.fingerprint.as_str()) => continue,
+                    Some(_) => summary.replaced.push(spec.name.clone()),
+                    None => summary.added.push(spec.name.clone()),
+                }
+                let entry = Entry::new(spec.provider, Some(spec.fingerprint));
+                if let Some(old) = providers.insert(spec.name.clone(), entry) {
+                    retired.push((spec.name, old));
+                }
+            }
+        }
+        for (name, entry) in retired {
+            self.retire(name, entry);
+        }
+        summary
+    }
+
+    fn retire(&self, name: String, entry: Entry) {
+        info!(provider = %name, "Provider retired; draining in-flight requests");
+        self.retired.lock().unwrap().push(Retired { name, _provider: entry.provider, in_flight: entry.in_flight, retired_at: Instant::now() });
+    }
+
+    /// Retired providers that have not been disposed yet.
+    pub fn draining(&self) -> Vec<DrainingProvider> {
+        self.retired
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| DrainingProvider {
+                name: r.name.clone(),
+                in_flight: r.in_flight.load(Ordering::SeqCst),
+                retired_for: r.retired_at.elapsed(),
+            })
+            .collect()
+    }
+
+    /// Dispose of retired providers that are idle or past `grace`. Returns
+    /// how many were disposed.
+    pub fn sweep(&self, grace: Duration) -> usize {
+        let mut retired = self.retired.lock().unwrap();
+        let before = retired.len();
+        retired.retain(|r| {
+            let in_flight = r.in_flight.load(Ordering::SeqCst);
+            if in_flight == 0 {
+                return false;
+            }
+            if r.retired_at.elapsed() >= grace {
+                warn!(provider = %r.name, in_flight, "Drain grace period over; releasing old provider");
+                return false;
+            }
+            true
+        });
+        before - retired.len()
+    }
+
+    /// Sweep retired providers in the background.
+    pub fn spawn_drain_sweeper(self: &Arc<Self>, grace: Duration) -> tokio::task::JoinHandle<()> {
+        let registry = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                tick.tick().await;
+                registry.sweep(grace);
+            }
+        })
     }
 }
 
@@ -57,6 +265,8 @@ mod tests {
 
     struct MockProvider {
         name: String,
+        /// Holds `complete` until notified.
+        gate: Option<Arc<tokio::sync::Notify>>,
     }
 
     #[async_trait]
@@ -65,11 +275,16 @@ mod tests {
             &self.name
         }
         async fn complete(&self, _req: &LlmRequest) -> Result<LlmResponse> {
+            if let Some(gate) = &self.gate {
+                gate.notified().await;
+            }
             Ok(LlmResponse {
                 content: "mock response".into(),
                 provider: self.name.clone(),
                 model: "mock".into(),
                 tokens_used: 10,
+                prompt_tokens: 6,
+                completion_tokens: 4,
                 latency_ms: 5,
             })
         }
@@ -82,12 +297,14 @@ mod tests {
             "mock1",
             Arc::new(MockProvider {
                 name: "mock1".into(),
+                gate: None,
             }),
         );
         registry.register(
             "mock2",
             Arc::new(MockProvider {
                 name: "mock2".into(),
+                gate: None,
             }),
         );
 
@@ -95,4 +312,60 @@ mod tests {
             registry.get_providers(&["mock1".into(), "mock2".into(), "missing".into()]);
         assert_eq!(providers.len(), 2);
     }
+
+    fn spec(name: &str, fingerprint: &str, gate: &Arc<tokio::sync::Notify>) -> ProviderSpec {
+        ProviderSpec {
+            name: "openai".into(),
+            fingerprint: fingerprint.into(),
+            provider: Arc::new(MockProvider { name: name.into(), gate: Some(Arc::clone(gate)) }),
+        }
+    }
+
+    fn request() -> LlmRequest {
+        LlmRequest { model: "m".into(), system_prompt: String::new(), user_prompt: "hi".into(), max_tokens: 1, temperature: 0.0 }
+    }
+
+    #[tokio::test]
+    async fn reload_drains_replaced_providers() {
+        let registry = ProviderRegistry::new();
+        let names = vec!["openai".to_string()];
+        let gate = Arc::new(tokio::sync::Notify::new());
+        registry.reload(vec![spec("old-key", "a", &gate)]);
+        assert_eq!(registry.reload(vec![spec("old-key", "a", &gate)]), ReloadSummary::default());
+
+        // A request in flight finishes on its client across the rotation;
+        // merely holding a client is not a request.
+        let held = registry.get_providers(&names).remove(0);
+        let running = tokio::spawn({
+            let provider = Arc::clone(&held);
+            async move { provider.complete(&request()).await.map(|r| r.provider) }
+        });
+        while registry.providers.read().unwrap()["openai"].in_flight.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        let summary = registry.reload(vec![spec("new-key", "b", &gate)]);
+        assert_eq!(summary.replaced, vec!["openai".to_string()]);
+        assert_eq!(registry.get_providers(&names)[0].name(), "new-key");
+
+        assert_eq!(registry.sweep(Duration::from_secs(60)), 0);
+        assert_eq!(registry.draining()[0].in_flight, 1);
+        gate.notify_one();
+        assert_eq!(running.await.unwrap().unwrap(), "old-key");
+        assert_eq!(registry.draining()[0].in_flight, 0);
+        assert_eq!(registry.sweep(Duration::from_secs(60)), 1);
+        assert_eq!(held.name(), "old-key");
+
+        // Removing the config entry drains it too; a stuck request is
+        // released once the grace period is over.
+        let stuck = registry.get_providers(&names).remove(0);
+        let stuck = tokio::spawn(async move { stuck.complete(&request()).await.map(|r| r.provider) });
+        while registry.providers.read().unwrap()["openai"].in_flight.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(registry.reload(vec![]).removed, vec!["openai".to_string()]);
+        assert!(registry.get_providers(&names).is_empty());
+        assert_eq!(registry.sweep(Duration::ZERO), 1);
+        gate.notify_one();
+        assert_eq!(stuck.await.unwrap().unwrap(), "new-key");
+    }
 }
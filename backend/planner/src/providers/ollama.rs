@@ -121,8 +121,8 @@ impl LlmProvider for OllamaProvider {
             .await
             .context("Failed to parse Ollama response")?;
 
-        let tokens_used = chat_response.eval_count.unwrap_or(0)
-            + chat_response.prompt_eval_count.unwrap_or(0);
+        let prompt_tokens = chat_response.prompt_eval_count.unwrap_or(0);
+        let completion_tokens = chat_response.eval_count.unwrap_or(0);
 
         let latency_ms = start.elapsed().as_millis() as u64;
 
@@ -130,7 +130,9 @@ impl LlmProvider for OllamaProvider {
             content: chat_response.message.content,
             provider: "ollama".to_string(),
             model,
-            tokens_used,
+            tokens_used: prompt_tokens + completion_tokens,
+            prompt_tokens,
+            completion_tokens,
             latency_ms,
         })
     }
@@ -58,6 +58,10 @@ struct Choice {
 #[derive(Deserialize)]
 struct Usage {
     total_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
 }
 
 #[async_trait]
@@ -120,10 +124,10 @@ impl LlmProvider for OpenRouterProvider {
             .map(|c| c.message.content.clone())
             .unwrap_or_default();
 
-        let tokens_used = chat_response
+        let (tokens_used, prompt_tokens, completion_tokens) = chat_response
             .usage
-            .and_then(|u| u.total_tokens)
-            .unwrap_or(0);
+            .map(|u| (u.total_tokens.unwrap_or(u.prompt_tokens + u.completion_tokens), u.prompt_tokens, u.completion_tokens))
+            .unwrap_or_default();
 
         let latency_ms = start.elapsed().as_millis() as u64;
 
@@ -132,6 +136,8 @@ impl LlmProvider for OpenRouterProvider {
             provider: "openrouter".to_string(),
             model: request.model.clone(),
             tokens_used,
+            prompt_tokens,
+            completion_tokens,
             latency_ms,
         })
     }
@@ -1,38 +1,284 @@
 //! Event Bus
 //!
 //! Implements a publish-subscribe router allowing plugins to listen to global ClawForge events.
+//!
+//! Plugins subscribe with the filter from their manifest's `subscribe` block
+//! and get a bounded queue of their own. When it is full the subscription's
+//! overflow policy applies: `drop` discards the event for that plugin (and
+//! counts it), `propagate` makes the publisher wait for room, so a slow
+//! exporter can hold back the runtime rather than lose data.
+//!
+//! ```json
+//! "subscribe": { "events": ["run.*", "tool.called"], "tools": ["http"],
+//!                "queue": 256, "overflow": "drop" }
+//! ```
 
 use anyhow::Result;
-use tokio::sync::broadcast;
-use tracing::info;
+use async_trait::async_trait;
+use clawforge_core::EventKind;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+const DEFAULT_QUEUE: usize = 64;
 
 #[derive(Debug, Clone)]
 pub enum SystemEvent {
     SessionStarted(String),
     MessageReceived(String, String), // session, content
     AgentThoughts(String, String),  // session, structured_thought
+    PluginFailed(String, String),   // plugin_id, reason
+    RunStarted { run_id: String, agent_id: String },
+    RunCompleted { run_id: String, agent_id: String },
+    RunFailed { run_id: String, agent_id: String, error: String },
+    ToolCalled { run_id: String, tool: String, ok: bool },
+    ChannelMessage { channel: String, chat_id: String, inbound: bool, text: String },
+}
+
+impl SystemEvent {
+    /// Dotted name matched by subscription filters, e.g. `run.started`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::SessionStarted(_) => "session.started",
+            Self::MessageReceived(..) => "message.received",
+            Self::AgentThoughts(..) => "agent.thoughts",
+            Self::PluginFailed(..) => "plugin.failed",
+            Self::RunStarted { .. } => "run.started",
+            Self::RunCompleted { .. } => "run.completed",
+            Self::RunFailed { .. } => "run.failed",
+            Self::ToolCalled { .. } => "tool.called",
+            Self::ChannelMessage { .. } => "channel.message",
+        }
+    }
+
+    /// Plugin-facing view of a runtime audit event, if it has one.
+    pub fn from_runtime(event: &clawforge_core::Event) -> Option<Self> {
+        let (run_id, agent_id) = (event.run_id.to_string(), event.agent_id.to_string());
+        let text = |key: &str| event.payload.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        match event.kind {
+            // Channel adapters report inbound chat messages as run starts.
+            EventKind::RunStarted => match (text("source"), text("text")) {
+                (Some(channel), Some(body)) => Some(Self::ChannelMessage {
+                    channel,
+                    chat_id: text("chat_id").unwrap_or_default(),
+                    inbound: true,
+                    text: body,
+                }),
+                _ => Some(Self::RunStarted { run_id, agent_id }),
+            },
+            EventKind::RunCompleted => Some(Self::RunCompleted { run_id, agent_id }),
+            EventKind::RunFailed => Some(Self::RunFailed { run_id, agent_id, error: text("error").unwrap_or_default() }),
+            EventKind::ActionExecuted | EventKind::ActionFailed => Some(Self::ToolCalled {
+                run_id,
+                tool: text("tool")?,
+                ok: event.kind == EventKind::ActionExecuted,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Implemented by plugins that listen to bus events. Handlers run on a
+/// supervised task (see `PluginHost`): an error is logged, a panic disables
+/// the plugin.
+#[async_trait]
+pub trait PluginEventHandler: Send + Sync {
+    async fn on_event(&self, event: &SystemEvent) -> Result<()>;
+}
+
+// ---------------------------------------------------------------------------
+// Subscriptions
+// ---------------------------------------------------------------------------
+
+/// What to do when a plugin's queue is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverflowPolicy {
+    /// Discard the event for this plugin.
+    #[default]
+    Drop,
+    /// Make the publisher wait until the plugin catches up.
+    Propagate,
+}
+
+/// The `subscribe` block of a plugin manifest. Empty lists match everything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSubscription {
+    /// Event kinds: exact (`tool.called`), prefix (`run.*`) or `*`.
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Only channel messages from these channels.
+    #[serde(default)]
+    pub channels: Vec<String>,
+    /// Only calls of these tools.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default = "default_queue")]
+    pub queue: usize,
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+}
+
+fn default_queue() -> usize {
+    DEFAULT_QUEUE
+}
+
+impl Default for EventSubscription {
+    fn default() -> Self {
+        Self { events: Vec::new(), channels: Vec::new(), tools: Vec::new(), queue: DEFAULT_QUEUE, overflow: OverflowPolicy::Drop }
+    }
+}
+
+impl EventSubscription {
+    pub fn matches(&self, event: &SystemEvent) -> bool {
+        let kind = event.kind();
+        let kind_ok = self.events.is_empty()
+            || self.events.iter().any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => kind.starts_with(prefix),
+                None => pattern == kind,
+            });
+        let channel_ok = match event {
+            SystemEvent::ChannelMessage { channel, .. } => self.channels.is_empty() || self.channels.contains(channel),
+            _ => true,
+        };
+        let tool_ok = match event {
+            SystemEvent::ToolCalled { tool, .. } => self.tools.is_empty() || self.tools.contains(tool),
+            _ => true,
+        };
+        kind_ok && channel_ok && tool_ok
+    }
 }
 
+#[derive(Default)]
+struct Counters {
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+}
+
+struct Subscriber {
+    plugin_id: String,
+    subscription: EventSubscription,
+    tx: mpsc::Sender<SystemEvent>,
+    counters: Arc<Counters>,
+}
+
+/// Queue state of one plugin's subscription.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionStatus {
+    pub plugin_id: String,
+    pub overflow: OverflowPolicy,
+    pub capacity: usize,
+    pub queued: usize,
+    pub delivered: u64,
+    pub dropped: u64,
+}
+
+// ---------------------------------------------------------------------------
+// Bus
+// ---------------------------------------------------------------------------
+
 pub struct EventBus {
     sender: broadcast::Sender<SystemEvent>,
+    subscribers: Mutex<Vec<Subscriber>>,
 }
 
 impl EventBus {
     pub fn new() -> Self {
         let (tx, _) = broadcast::channel(100);
-        Self { sender: tx }
+        Self { sender: tx, subscribers: Mutex::new(Vec::new()) }
     }
 
     /// Dispatches a high-level system event to all subscribed plugins.
-    pub fn publish(&self, event: SystemEvent) {
+    /// Waits only on full queues of `propagate` subscriptions.
+    pub async fn publish(&self, event: SystemEvent) {
         info!("Publishing SystemEvent to plugin bus: {:?}", event);
-        let _ = self.sender.send(event);
+        let _ = self.sender.send(event.clone());
+
+        let mut waiting = Vec::new();
+        {
+            let subscribers = self.subscribers.lock().unwrap();
+            for sub in subscribers.iter().filter(|s| s.subscription.matches(&event)) {
+                match sub.tx.try_send(event.clone()) {
+                    Ok(()) => {
+                        sub.counters.delivered.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(mpsc::error::TrySendError::Full(event)) => match sub.subscription.overflow {
+                        OverflowPolicy::Drop => {
+                            sub.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                            debug!(plugin = %sub.plugin_id, kind = event.kind(), "Plugin queue full; event dropped");
+                        }
+                        OverflowPolicy::Propagate => waiting.push((sub.tx.clone(), event, Arc::clone(&sub.counters))),
+                    },
+                    Err(mpsc::error::TrySendError::Closed(_)) => {}
+                }
+            }
+        }
+        for (tx, event, counters) in waiting {
+            if tx.send(event).await.is_ok() {
+                counters.delivered.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.subscribers.lock().unwrap().retain(|s| !s.tx.is_closed());
     }
 
     /// Provides a reciever stream for a plugin to await events.
     pub fn subscribe(&self) -> broadcast::Receiver<SystemEvent> {
         self.sender.subscribe()
     }
+
+    /// Queue of the events `subscription` matches, for `plugin_id`. Replaces
+    /// the plugin's previous subscription.
+    pub fn subscribe_filtered(&self, plugin_id: &str, subscription: EventSubscription) -> mpsc::Receiver<SystemEvent> {
+        let (tx, rx) = mpsc::channel(subscription.queue.max(1));
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|s| s.plugin_id != plugin_id);
+        subscribers.push(Subscriber { plugin_id: plugin_id.to_string(), subscription, tx, counters: Arc::default() });
+        rx
+    }
+
+    pub fn unsubscribe(&self, plugin_id: &str) {
+        self.subscribers.lock().unwrap().retain(|s| s.plugin_id != plugin_id);
+    }
+
+    pub fn subscriptions(&self) -> Vec<SubscriptionStatus> {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|s| SubscriptionStatus {
+                plugin_id: s.plugin_id.clone(),
+                overflow: s.subscription.overflow,
+                capacity: s.tx.max_capacity(),
+                queued: s.tx.max_capacity() - s.tx.capacity(),
+                delivered: s.counters.delivered.load(Ordering::Relaxed),
+                dropped: s.counters.dropped.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Publish the plugin-facing view of runtime audit events (the
+    /// supervisor's broadcast) until that stream closes.
+    pub fn forward_runtime_events(self: &Arc<Self>, mut events: broadcast::Receiver<clawforge_core::Event>) -> JoinHandle<()> {
+        let bus = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if let Some(event) = SystemEvent::from_runtime(&event) {
+                            bus.publish(event).await;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!(missed, "Plugin bus fell behind runtime events");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
 }
 
 impl Default for EventBus {
@@ -40,3 +286,45 @@ impl Default for EventBus {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn tool_call(tool: &str) -> SystemEvent {
+        SystemEvent::ToolCalled { run_id: "r1".into(), tool: tool.into(), ok: true }
+    }
+
+    #[tokio::test]
+    async fn filtered_subscriptions_drop_or_hold_back_when_full() {
+        let bus = Arc::new(EventBus::new());
+        let tools_only = EventSubscription { events: vec!["tool.*".into()], tools: vec!["http".into()], queue: 1, ..Default::default() };
+        let mut analytics = bus.subscribe_filtered("analytics", tools_only);
+        let mut exporter = bus.subscribe_filtered(
+            "exporter",
+            EventSubscription { events: vec!["run.*".into()], queue: 1, overflow: OverflowPolicy::Propagate, ..Default::default() },
+        );
+
+        bus.publish(tool_call("shell")).await;
+        bus.publish(tool_call("http")).await;
+        bus.publish(tool_call("http")).await;
+        assert!(matches!(analytics.recv().await, Some(SystemEvent::ToolCalled { tool, .. }) if tool == "http"));
+        assert!(analytics.try_recv().is_err());
+        let analytics_status = bus.subscriptions().into_iter().find(|s| s.plugin_id == "analytics").unwrap();
+        assert_eq!((analytics_status.delivered, analytics_status.dropped), (1, 1));
+
+        // The exporter's queue holds one event; the second publish waits for it.
+        let started = |id: &str| SystemEvent::RunStarted { run_id: id.into(), agent_id: "a".into() };
+        bus.publish(started("r1")).await;
+        let publisher = tokio::spawn({
+            let bus = Arc::clone(&bus);
+            async move { bus.publish(started("r2")).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!publisher.is_finished());
+        assert!(matches!(exporter.recv().await, Some(SystemEvent::RunStarted { run_id, .. }) if run_id == "r1"));
+        publisher.await.unwrap();
+        assert!(matches!(exporter.recv().await, Some(SystemEvent::RunStarted { run_id, .. }) if run_id == "r2"));
+    }
+}
@@ -0,0 +1,122 @@
+/// Plugin index — the marketplace catalogue of installable plugins.
+///
+/// An index is a JSON document served over HTTPS (or read from a local file
+/// for self-hosted mirrors). Every entry pins a source and the SHA-256 of the
+/// plugin tree, so `install` gets exactly the bytes the index describes:
+///
+/// ```json
+/// { "plugins": [
+///   { "id": "weather", "name": "Weather", "description": "Forecasts",
+///     "version": "1.2.0", "source": "git+https://github.com/acme/cf-weather.git",
+///     "tag": "v1.2.0", "sha256": "9f86d08..." }
+/// ] }
+/// ```
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::path::Path;
+
+use crate::installer::PluginSource;
+
+/// One installable plugin release.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub version: String,
+    /// Git URL (`git+https://…` or ending in `.git`), tarball URL or path.
+    pub source: String,
+    /// Git tag to check out; overrides a `#tag` suffix on `source`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Checksum of the installed plugin tree (see `tree_checksum`).
+    pub sha256: String,
+}
+
+impl IndexEntry {
+    pub fn plugin_source(&self) -> PluginSource {
+        match (PluginSource::parse(&self.source), &self.tag) {
+            (PluginSource::Git { url, .. }, Some(tag)) => PluginSource::Git { url, tag: Some(tag.clone()) },
+            (source, _) => source,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginIndex {
+    #[serde(default)]
+    pub plugins: Vec<IndexEntry>,
+}
+
+impl PluginIndex {
+    pub fn from_json(json: &str) -> Result<Self> {
+        let index: Self = serde_json::from_str(json).context("Invalid plugin index JSON")?;
+        for entry in &index.plugins {
+            if entry.id.is_empty() || entry.sha256.len() != 64 {
+                bail!("Plugin index entry '{}' needs an id and a 64-character sha256", entry.id);
+            }
+        }
+        Ok(index)
+    }
+
+    /// Fetch an index from an `https://` URL or a local file.
+    pub async fn fetch(location: &str) -> Result<Self> {
+        let json = if location.starts_with("https://") {
+            let resp = reqwest::get(location).await.with_context(|| format!("fetch plugin index {}", location))?;
+            if !resp.status().is_success() {
+                bail!("Plugin index fetch failed ({}): {}", resp.status(), location);
+            }
+            resp.text().await?
+        } else if location.contains("://") {
+            bail!("Plugin index must be served over HTTPS: {}", location);
+        } else {
+            tokio::fs::read_to_string(Path::new(location))
+                .await
+                .with_context(|| format!("read plugin index {}", location))?
+        };
+        Self::from_json(&json)
+    }
+
+    /// Latest release of `id`.
+    pub fn get(&self, id: &str) -> Option<&IndexEntry> {
+        self.plugins
+            .iter()
+            .filter(|e| e.id == id)
+            .max_by(|a, b| compare_versions(&a.version, &b.version))
+    }
+
+    /// Latest releases whose id, name or description contains `query` (case-insensitive).
+    pub fn search(&self, query: &str) -> Vec<&IndexEntry> {
+        let query = query.to_lowercase();
+        let mut ids: Vec<&str> = self
+            .plugins
+            .iter()
+            .filter(|e| [&e.id, &e.name, &e.description].iter().any(|f| f.to_lowercase().contains(&query)))
+            .map(|e| e.id.as_str())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids.into_iter().filter_map(|id| self.get(id)).collect()
+    }
+}
+
+/// Compare dotted versions numerically (`1.10.0` > `1.9.2`); a leading `v`
+/// and non-numeric suffixes are ignored.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|p| p.chars().take_while(char::is_ascii_digit).collect::<String>().parse().unwrap_or(0))
+            .collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+    for i in 0..a.len().max(b.len()) {
+        match a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    Ordering::Equal
+}
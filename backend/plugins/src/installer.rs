@@ -1,10 +1,84 @@
 /// Plugin installer — download and install plugin packages.
 ///
 /// Mirrors `src/plugins/install.ts` from OpenClaw.
-/// Plugins are distributed as tarballs or directories.
+/// Plugins are distributed as git repositories, tarballs or directories.
+/// Remote sources are fetched into a staging directory, checked against the
+/// expected SHA-256 of the plugin tree, and only then moved into place under
+/// the id from their manifest.
 use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use std::fmt;
 use std::path::{Path, PathBuf};
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::manifest::PluginManifest;
+
+const MANIFEST_FILE: &str = "clawforge-plugin.json";
+
+// ---------------------------------------------------------------------------
+// Plugin source
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginSource {
+    /// Git repository, optionally pinned to a tag.
+    Git { url: String, tag: Option<String> },
+    /// URL to a `.tar.gz` archive.
+    Url(String),
+    /// Local directory path.
+    Local(PathBuf),
+}
+
+impl PluginSource {
+    /// Parse `git+<url>[#tag]`, `<url>.git[#tag]`, an `http(s)://` tarball URL
+    /// or a local path. This is also the form recorded in `plugins.installed`.
+    pub fn parse(source: &str) -> Self {
+        let git = |rest: &str| {
+            let (url, tag) = match rest.split_once('#') {
+                Some((url, tag)) if !tag.is_empty() => (url, Some(tag.to_string())),
+                Some((url, _)) => (url, None),
+                None => (rest, None),
+            };
+            PluginSource::Git { url: url.to_string(), tag }
+        };
+        if let Some(rest) = source.strip_prefix("git+") {
+            return git(rest);
+        }
+        if source.split('#').next().is_some_and(|url| url.ends_with(".git")) {
+            return git(source);
+        }
+        if source.starts_with("http://") || source.starts_with("https://") {
+            return PluginSource::Url(source.to_string());
+        }
+        PluginSource::Local(PathBuf::from(source))
+    }
+}
+
+impl fmt::Display for PluginSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginSource::Git { url, tag: Some(tag) } => write!(f, "git+{}#{}", url, tag),
+            PluginSource::Git { url, tag: None } => write!(f, "git+{}", url),
+            PluginSource::Url(url) => f.write_str(url),
+            PluginSource::Local(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// A plugin installed from a source, with what to record for reproducible setups.
+#[derive(Debug, Clone)]
+pub struct InstalledPlugin {
+    pub id: String,
+    pub version: String,
+    pub source: PluginSource,
+    /// SHA-256 of the plugin tree as installed.
+    pub checksum: String,
+    pub path: PathBuf,
+}
+
+// ---------------------------------------------------------------------------
+// Installer
+// ---------------------------------------------------------------------------
 
 pub struct PluginInstaller {
     pub plugins_dir: PathBuf,
@@ -34,6 +108,75 @@ impl PluginInstaller {
         Ok(dest)
     }
 
+    /// Fetch `source`, verify it against `expected_sha256` when given, and
+    /// install it under its manifest id. An existing install of the same id is
+    /// only replaced when `replace` is set (updates).
+    pub async fn install_from_source(&self, source: &PluginSource, expected_sha256: Option<&str>, replace: bool) -> Result<InstalledPlugin> {
+        std::fs::create_dir_all(&self.plugins_dir)?;
+        let staging = self.plugins_dir.join(format!(".staging-{}", staging_suffix()));
+        let result = self.stage(source, &staging, expected_sha256, replace).await;
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging).ok();
+        }
+        result
+    }
+
+    async fn stage(&self, source: &PluginSource, staging: &Path, expected_sha256: Option<&str>, replace: bool) -> Result<InstalledPlugin> {
+        match source {
+            PluginSource::Git { url, tag } => {
+                info!("[Installer] Cloning {} ({})", url, tag.as_deref().unwrap_or("default branch"));
+                let mut cmd = tokio::process::Command::new("git");
+                cmd.args(["clone", "--quiet", "--depth", "1"]);
+                if let Some(tag) = tag {
+                    cmd.args(["--branch", tag]);
+                }
+                let status = cmd.arg("--").arg(url).arg(staging).status().await.context("run git")?;
+                if !status.success() {
+                    bail!("git clone failed for {}", url);
+                }
+                std::fs::remove_dir_all(staging.join(".git")).ok();
+                if tag.is_none() {
+                    warn!("[Installer] {} is not pinned to a tag", url);
+                }
+            }
+            PluginSource::Url(url) => {
+                info!("[Installer] Downloading {}", url);
+                download_and_extract(url, staging).await?;
+            }
+            PluginSource::Local(path) => {
+                if !path.is_dir() {
+                    bail!("Source is not a directory: {:?}", path);
+                }
+                copy_dir(path, staging).context("copy plugin dir")?;
+            }
+        }
+
+        let checksum = tree_checksum(staging)?;
+        if let Some(expected) = expected_sha256 {
+            if !checksum.eq_ignore_ascii_case(expected) {
+                bail!("Checksum mismatch for {}: expected {}, got {}", source, expected, checksum);
+            }
+        }
+
+        let raw = std::fs::read_to_string(staging.join(MANIFEST_FILE)).with_context(|| format!("{} has no {}", source, MANIFEST_FILE))?;
+        let manifest: PluginManifest = serde_json::from_str(&raw).with_context(|| format!("parse {}", MANIFEST_FILE))?;
+        manifest.validate()?;
+        if manifest.id.contains(['/', '\\']) || manifest.id.starts_with('.') {
+            bail!("Invalid plugin id '{}'", manifest.id);
+        }
+
+        let dest = self.plugins_dir.join(&manifest.id);
+        if dest.exists() {
+            if !replace {
+                bail!("Plugin '{}' is already installed", manifest.id);
+            }
+            std::fs::remove_dir_all(&dest).with_context(|| format!("remove plugin dir {:?}", dest))?;
+        }
+        std::fs::rename(staging, &dest)?;
+        info!("[Installer] Installed plugin '{}' {} → {:?}", manifest.id, manifest.version, dest);
+        Ok(InstalledPlugin { id: manifest.id, version: manifest.version, source: source.clone(), checksum, path: dest })
+    }
+
     /// Uninstall a plugin by ID (removes its directory).
     pub fn uninstall(&self, plugin_id: &str) -> Result<()> {
         let path = self.plugins_dir.join(plugin_id);
@@ -53,13 +196,75 @@ impl PluginInstaller {
         }
         let names = std::fs::read_dir(&self.plugins_dir)?
             .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_dir())
+            .filter(|e| e.path().is_dir() && !e.file_name().to_string_lossy().starts_with('.'))
             .map(|e| e.file_name().to_string_lossy().to_string())
             .collect();
         Ok(names)
     }
 }
 
+/// SHA-256 over every file in `dir` (except `.git`), in path order, covering
+/// relative paths and contents. This is the `sha256` a plugin index pins.
+pub fn tree_checksum(dir: &Path) -> Result<String> {
+    fn collect(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_name() == ".git" {
+                continue;
+            }
+            if entry.file_type()?.is_dir() {
+                collect(root, &path, files)?;
+            } else {
+                let rel = path.strip_prefix(root)?.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+                files.push((rel, path));
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect(dir, dir, &mut files)?;
+    files.sort();
+    let mut hasher = Sha256::new();
+    for (rel, path) in files {
+        let content = std::fs::read(&path)?;
+        hasher.update(rel.as_bytes());
+        hasher.update([0]);
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(&content);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+async fn download_and_extract(url: &str, dest: &Path) -> Result<()> {
+    let resp = reqwest::get(url).await?;
+    if !resp.status().is_success() {
+        bail!("Download failed ({}): {}", resp.status(), url);
+    }
+    let bytes = resp.bytes().await?;
+    std::fs::create_dir_all(dest)?;
+    let archive = dest.with_extension("tar.gz");
+    tokio::fs::write(&archive, &bytes).await?;
+    let status = tokio::process::Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(dest)
+        .arg("--strip-components=1")
+        .status()
+        .await;
+    tokio::fs::remove_file(&archive).await.ok();
+    if !status?.success() {
+        bail!("tar extraction failed for {}", url);
+    }
+    Ok(())
+}
+
+fn staging_suffix() -> u128 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos()
+}
+
 fn copy_dir(src: &Path, dst: &Path) -> Result<()> {
     std::fs::create_dir_all(dst)?;
     for entry in std::fs::read_dir(src)? {
@@ -73,3 +278,50 @@ fn copy_dir(src: &Path, dst: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::PluginIndex;
+
+    #[tokio::test]
+    async fn index_installs_are_checksum_verified() {
+        let root = std::env::temp_dir().join(format!("cf-plugin-install-{}", staging_suffix()));
+        let src = root.join("weather-src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(
+            src.join(MANIFEST_FILE),
+            r#"{"id":"weather","name":"Weather","version":"1.2.0","description":"Forecasts","main":"index.js","permissions":{"network":true,"filesystem":false,"shell":false}}"#,
+        )
+        .unwrap();
+        std::fs::write(src.join("index.js"), "export default {}").unwrap();
+        let sha = tree_checksum(&src).unwrap();
+
+        let index = PluginIndex::from_json(&serde_json::json!({ "plugins": [
+            { "id": "weather", "name": "Weather", "description": "Forecasts", "version": "1.10.0", "source": src, "sha256": sha },
+            { "id": "weather", "name": "Weather", "version": "1.9.0", "source": src, "sha256": "0".repeat(64) },
+        ] }).to_string())
+        .unwrap();
+        let entry = index.get("weather").unwrap();
+        assert_eq!(entry.version, "1.10.0");
+        assert_eq!(index.search("FORECAST").len(), 1);
+
+        let installer = PluginInstaller::new(root.join("plugins"));
+        let installed = installer.install_from_source(&entry.plugin_source(), Some(&entry.sha256), false).await.unwrap();
+        assert_eq!((installed.id.as_str(), installed.version.as_str()), ("weather", "1.2.0"));
+        assert_eq!(installed.checksum, sha);
+        assert!(installer.install_from_source(&entry.plugin_source(), None, false).await.is_err());
+
+        std::fs::write(src.join("index.js"), "tampered").unwrap();
+        let err = installer.install_from_source(&entry.plugin_source(), Some(&sha), true).await.unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"));
+        assert_eq!(installer.list_installed().unwrap(), vec!["weather".to_string()]);
+
+        assert_eq!(
+            PluginSource::parse("https://github.com/acme/cf-weather.git#v1.2.0"),
+            PluginSource::Git { url: "https://github.com/acme/cf-weather.git".into(), tag: Some("v1.2.0".into()) }
+        );
+        assert_eq!(PluginSource::parse("git+https://example.com/p#v1").to_string(), "git+https://example.com/p#v1");
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -1,3 +1,4 @@
+pub mod index;
 pub mod installer;
 pub mod lifecycle;
 pub mod manifest;
@@ -8,8 +9,10 @@ pub mod sdk;
 pub mod permissions;
 pub mod event_bus;
 
-pub use installer::PluginInstaller;
-pub use lifecycle::{DefaultPluginLifecycle, PluginLifecycle, PluginLifecycleContext, PluginState, run_load_sequence, run_unload_sequence};
+pub use index::{compare_versions, IndexEntry, PluginIndex};
+pub use installer::{tree_checksum, InstalledPlugin, PluginInstaller, PluginSource};
+pub use event_bus::{EventBus, EventSubscription, OverflowPolicy, PluginEventHandler, SubscriptionStatus, SystemEvent};
+pub use lifecycle::{DefaultPluginLifecycle, HostedPluginStatus, PluginHost, PluginLifecycle, PluginLifecycleContext, PluginState, run_load_sequence, run_unload_sequence};
 pub use manifest::{PluginHookEntry, PluginManifest, PluginPermissions, PluginToolSlot};
 pub use registry::PluginRegistry;
 pub use slots::{collect_plugin_tools, ResolvedPluginTool};
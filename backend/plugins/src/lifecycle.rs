@@ -1,11 +1,30 @@
 //! Plugin lifecycle hooks: before_load, after_load, before_unload, after_unload.
 //!
 //! Mirrors `src/plugins/lifecycle.ts`.
+//!
+//! `PluginHost` keeps loaded plugins running inside the gateway: it reloads a
+//! plugin (unload then load sequence) when its files change, and runs each
+//! plugin's event handler on a supervised task fed by the subscription in its
+//! manifest, so a plugin that panics is marked failed and reported on the bus
+//! while everything else keeps going.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use notify::{RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
-use tracing::{debug, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use crate::event_bus::{EventBus, EventSubscription, PluginEventHandler, SystemEvent};
+use crate::manifest::PluginManifest;
+
+/// Quiet period after the last file change before a plugin is reloaded.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
 
 /// Current state of a plugin.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -90,3 +109,270 @@ pub async fn run_unload_sequence(
     lifecycle.after_unload(ctx).await;
     PluginState::Unloaded
 }
+
+// ---------------------------------------------------------------------------
+// Plugin host
+// ---------------------------------------------------------------------------
+
+struct HostedPlugin {
+    path: PathBuf,
+    ctx: PluginLifecycleContext,
+    lifecycle: Arc<dyn PluginLifecycle>,
+    handler: Option<Arc<dyn PluginEventHandler>>,
+    state: PluginState,
+    last_error: Option<String>,
+    task: Option<JoinHandle<()>>,
+}
+
+/// Status of a hosted plugin.
+#[derive(Debug, Clone, Serialize)]
+pub struct HostedPluginStatus {
+    pub id: String,
+    pub version: String,
+    pub state: PluginState,
+    pub last_error: Option<String>,
+}
+
+/// Runs loaded plugins: hot reload on file changes and crash isolation for
+/// their event handlers.
+pub struct PluginHost {
+    bus: Arc<EventBus>,
+    plugins: Arc<Mutex<HashMap<String, HostedPlugin>>>,
+}
+
+impl PluginHost {
+    pub fn new(bus: Arc<EventBus>) -> Self {
+        Self { bus, plugins: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// Load the plugin installed at `path` and start its event handler.
+    pub async fn load(
+        &self,
+        path: impl Into<PathBuf>,
+        lifecycle: Arc<dyn PluginLifecycle>,
+        handler: Option<Arc<dyn PluginEventHandler>>,
+        config: serde_json::Value,
+    ) -> Result<PluginState> {
+        let path = path.into();
+        let manifest = PluginManifest::read_from_dir(&path)?;
+        let ctx = PluginLifecycleContext { plugin_id: manifest.id.clone(), plugin_version: manifest.version, config };
+        let (state, last_error) = match run_load_sequence(lifecycle.as_ref(), &ctx).await {
+            Ok(state) => (state, None),
+            Err(e) => (PluginState::Failed, Some(e.to_string())),
+        };
+        let subscription = manifest.subscribe.unwrap_or_default();
+        let task = match (&handler, &state) {
+            (Some(handler), PluginState::Active) => Some(self.supervise(&ctx.plugin_id, subscription, Arc::clone(handler))),
+            _ => None,
+        };
+        let plugin = HostedPlugin { path, ctx, lifecycle, handler, state: state.clone(), last_error, task };
+        if let Some(old) = self.plugins.lock().unwrap().insert(manifest.id, plugin) {
+            if let Some(task) = old.task {
+                task.abort();
+            }
+        }
+        Ok(state)
+    }
+
+    /// Stop the handler and run the unload sequence.
+    pub async fn unload(&self, id: &str) -> Result<()> {
+        let plugin = self.plugins.lock().unwrap().remove(id).ok_or_else(|| anyhow!("Plugin '{}' is not loaded", id))?;
+        if let Some(task) = plugin.task {
+            task.abort();
+        }
+        self.bus.unsubscribe(id);
+        run_unload_sequence(plugin.lifecycle.as_ref(), &plugin.ctx).await;
+        Ok(())
+    }
+
+    /// Run the unload then load sequence with the manifest currently on disk.
+    /// Also brings a failed plugin back.
+    pub async fn reload(&self, id: &str) -> Result<PluginState> {
+        let (path, ctx, lifecycle, handler) = {
+            let mut plugins = self.plugins.lock().unwrap();
+            let plugin = plugins.get_mut(id).ok_or_else(|| anyhow!("Plugin '{}' is not loaded", id))?;
+            if let Some(task) = plugin.task.take() {
+                task.abort();
+            }
+            plugin.state = PluginState::Unloading;
+            (plugin.path.clone(), plugin.ctx.clone(), Arc::clone(&plugin.lifecycle), plugin.handler.clone())
+        };
+        self.bus.unsubscribe(id);
+        info!(plugin = %id, "Reloading plugin");
+        run_unload_sequence(lifecycle.as_ref(), &ctx).await;
+        let result = self.load(&path, Arc::clone(&lifecycle), handler.clone(), ctx.config.clone()).await;
+        if let Err(e) = &result {
+            // Unreadable manifest mid-edit: keep the plugin listed as failed
+            // so the next change retries.
+            error!(plugin = %id, error = %e, "Plugin reload failed");
+            let plugin = HostedPlugin { path, ctx, lifecycle, handler, state: PluginState::Failed, last_error: Some(e.to_string()), task: None };
+            self.plugins.lock().unwrap().insert(id.to_string(), plugin);
+        }
+        result
+    }
+
+    pub fn status(&self, id: &str) -> Option<HostedPluginStatus> {
+        self.plugins.lock().unwrap().get(id).map(|p| status_of(id, p))
+    }
+
+    pub fn statuses(&self) -> Vec<HostedPluginStatus> {
+        let plugins = self.plugins.lock().unwrap();
+        let mut statuses: Vec<_> = plugins.iter().map(|(id, p)| status_of(id, p)).collect();
+        statuses.sort_by(|a, b| a.id.cmp(&b.id));
+        statuses
+    }
+
+    /// Feed the events `subscription` matches to `handler`, each on its own
+    /// task. A panic disables the plugin and publishes `SystemEvent::PluginFailed`.
+    fn supervise(&self, id: &str, subscription: EventSubscription, handler: Arc<dyn PluginEventHandler>) -> JoinHandle<()> {
+        let id = id.to_string();
+        let bus = Arc::clone(&self.bus);
+        let plugins = Arc::clone(&self.plugins);
+        let mut events = bus.subscribe_filtered(&id, subscription);
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                let handler = Arc::clone(&handler);
+                let outcome = tokio::spawn(async move { handler.on_event(&event).await }).await;
+                match outcome {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => warn!(plugin = %id, error = %e, "Plugin event handler failed"),
+                    Err(e) => {
+                        let reason = panic_message(e);
+                        error!(plugin = %id, reason = %reason, "Plugin panicked; disabling it");
+                        if let Some(plugin) = plugins.lock().unwrap().get_mut(&id) {
+                            plugin.state = PluginState::Failed;
+                            plugin.last_error = Some(reason.clone());
+                            plugin.task = None;
+                        }
+                        bus.unsubscribe(&id);
+                        bus.publish(SystemEvent::PluginFailed(id, reason)).await;
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Reload plugins under `plugins_dir` when their files change.
+    pub fn watch(self: &Arc<Self>, plugins_dir: impl AsRef<Path>) -> Result<JoinHandle<()>> {
+        let plugins_dir = plugins_dir.as_ref().to_path_buf();
+        let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+            Ok(event) if event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove() => {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Plugin watch error: {:?}", e),
+        })?;
+        watcher.watch(&plugins_dir, RecursiveMode::Recursive)?;
+        info!("Watching plugins for changes: {:?}", plugins_dir);
+
+        let host = Arc::clone(self);
+        Ok(tokio::spawn(async move {
+            let _watcher = watcher;
+            while let Some(first) = rx.recv().await {
+                let mut changed = vec![first];
+                while let Ok(Some(path)) = tokio::time::timeout(RELOAD_DEBOUNCE, rx.recv()).await {
+                    changed.push(path);
+                }
+                let ids: HashSet<String> = {
+                    let plugins = host.plugins.lock().unwrap();
+                    changed
+                        .iter()
+                        .filter_map(|path| plugins.iter().find(|(_, p)| path.starts_with(&p.path)).map(|(id, _)| id.clone()))
+                        .collect()
+                };
+                for id in ids {
+                    if let Err(e) = host.reload(&id).await {
+                        warn!(plugin = %id, error = %e, "Hot reload failed");
+                    }
+                }
+            }
+        }))
+    }
+}
+
+fn status_of(id: &str, plugin: &HostedPlugin) -> HostedPluginStatus {
+    HostedPluginStatus {
+        id: id.to_string(),
+        version: plugin.ctx.plugin_version.clone(),
+        state: plugin.state.clone(),
+        last_error: plugin.last_error.clone(),
+    }
+}
+
+fn panic_message(error: tokio::task::JoinError) -> String {
+    if !error.is_panic() {
+        return error.to_string();
+    }
+    let payload = error.into_panic();
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "plugin panicked".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fragile;
+
+    #[async_trait]
+    impl PluginEventHandler for Fragile {
+        async fn on_event(&self, event: &SystemEvent) -> Result<()> {
+            if let SystemEvent::MessageReceived(_, text) = event {
+                if text == "boom" {
+                    panic!("cannot handle boom");
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn write_manifest(dir: &Path, version: &str) {
+        std::fs::write(
+            dir.join("clawforge-plugin.json"),
+            format!(
+                r#"{{"id":"fragile","name":"Fragile","version":"{}","description":"","main":"index.js","permissions":{{"network":false,"filesystem":false,"shell":false}}}}"#,
+                version
+            ),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn panicking_plugins_are_disabled_and_reloadable() {
+        let dir = std::env::temp_dir().join(format!("cf-plugin-host-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_manifest(&dir, "1.0.0");
+
+        let bus = Arc::new(EventBus::new());
+        let mut observer = bus.subscribe();
+        let host = PluginHost::new(Arc::clone(&bus));
+        let state = host.load(&dir, Arc::new(DefaultPluginLifecycle), Some(Arc::new(Fragile)), serde_json::json!({})).await.unwrap();
+        assert_eq!(state, PluginState::Active);
+
+        bus.publish(SystemEvent::MessageReceived("s1".into(), "boom".into())).await;
+        let reported = loop {
+            if let SystemEvent::PluginFailed(id, reason) = observer.recv().await.unwrap() {
+                break (id, reason);
+            }
+        };
+        assert_eq!(reported, ("fragile".to_string(), "cannot handle boom".to_string()));
+        let status = host.status("fragile").unwrap();
+        assert_eq!(status.state, PluginState::Failed);
+
+        // The bus survives and a reload picks up the edited manifest.
+        write_manifest(&dir, "1.0.1");
+        assert_eq!(host.reload("fragile").await.unwrap(), PluginState::Active);
+        assert_eq!(host.status("fragile").unwrap().version, "1.0.1");
+        bus.publish(SystemEvent::SessionStarted("s2".into())).await;
+        host.unload("fragile").await.unwrap();
+        assert!(host.statuses().is_empty());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
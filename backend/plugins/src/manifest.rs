@@ -3,6 +3,8 @@
 /// Mirrors `src/plugins/manifest.ts` from OpenClaw.
 use serde::{Deserialize, Serialize};
 
+use crate::event_bus::EventSubscription;
+
 /// The permissions a plugin requests.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PluginPermissions {
@@ -45,9 +47,22 @@ pub struct PluginManifest {
     /// Channels this plugin is compatible with (empty = all).
     #[serde(default)]
     pub channels: Vec<String>,
+    /// Bus events delivered to the plugin's handler (absent = all, queue of 64, drop on overflow).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscribe: Option<EventSubscription>,
 }
 
 impl PluginManifest {
+    /// Read and validate `clawforge-plugin.json` in `dir`.
+    pub fn read_from_dir(dir: &std::path::Path) -> anyhow::Result<Self> {
+        use anyhow::Context;
+        let path = dir.join("clawforge-plugin.json");
+        let raw = std::fs::read_to_string(&path).with_context(|| format!("read manifest at {:?}", path))?;
+        let manifest: Self = serde_json::from_str(&raw).context("parse plugin manifest")?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
     /// Validate the manifest for required fields.
     pub fn validate(&self) -> anyhow::Result<()> {
         if self.id.is_empty() {
@@ -58,13 +58,7 @@ impl PluginRegistry {
     }
 
     fn load_from_path(&self, path: &Path) -> Result<PluginManifest> {
-        let manifest_path = path.join("clawforge-plugin.json");
-        let raw = std::fs::read_to_string(&manifest_path)
-            .with_context(|| format!("read manifest at {:?}", manifest_path))?;
-        let manifest: PluginManifest = serde_json::from_str(&raw)
-            .context("parse plugin manifest")?;
-        manifest.validate()?;
-        Ok(manifest)
+        PluginManifest::read_from_dir(path)
     }
 
     pub fn get(&self, id: &str) -> Option<&LoadedPlugin> {
@@ -6,6 +6,8 @@ use std::path::{Path, PathBuf};
 use tokio::fs;
 use tracing::{debug, info};
 
+use crate::analysis::analyze_command;
+
 /// Security level for an approval entry.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -100,14 +102,31 @@ impl ExecAllowlist {
         self.entries.len() < before
     }
 
-    /// Evaluate a command string against the allowlist.
+    /// Evaluate a command line against the allowlist.
     ///
-    /// Returns the first matching entry's level, or `Ask` if no match.
+    /// Every simple command in the line is matched on its own, so an allowed
+    /// first word can't carry others along (`ls; rm -rf ~`). Any denied
+    /// segment denies the line; it is allowed only if every segment is.
+    /// Scripts run through `sh -c`, `eval`, `xargs`, substitutions or
+    /// `$VAR` expansion always ask.
     pub fn evaluate(&self, command: &str) -> ApprovalLevel {
+        let analysis = analyze_command(command);
+        let levels: Vec<ApprovalLevel> = analysis.segments.iter().map(|s| self.evaluate_segment(&s.line())).collect();
+        if levels.contains(&ApprovalLevel::Deny) {
+            ApprovalLevel::Deny
+        } else if analysis.has_indirection() || levels.is_empty() || levels.contains(&ApprovalLevel::Ask) {
+            ApprovalLevel::Ask
+        } else {
+            ApprovalLevel::Allow
+        }
+    }
+
+    /// The first matching entry's level for one simple command, or `Ask`.
+    fn evaluate_segment(&self, segment: &str) -> ApprovalLevel {
         for entry in &self.entries {
-            if glob_matches(&entry.pattern, command) {
+            if glob_matches(&entry.pattern, segment) {
                 debug!(
-                    command = %command,
+                    command = %segment,
                     pattern = %entry.pattern,
                     level = ?entry.level,
                     "Allowlist match"
@@ -235,4 +254,30 @@ mod tests {
         let list = ExecAllowlist::with_safe_defaults();
         assert_eq!(list.evaluate("git status"), ApprovalLevel::Allow);
     }
+
+    #[test]
+    fn evaluates_each_segment() {
+        let mut list = ExecAllowlist::with_safe_defaults();
+        list.upsert(AllowlistEntry {
+            pattern: "rm -rf*".to_string(),
+            level: ApprovalLevel::Deny,
+            reason: None,
+            added_at: None,
+            scope: "persistent".to_string(),
+        });
+        assert_eq!(list.evaluate("ls -la | grep src && git status"), ApprovalLevel::Allow);
+        assert_eq!(list.evaluate("ls; rm -rf ~"), ApprovalLevel::Deny);
+        assert_eq!(list.evaluate("ls\nrm -rf ~"), ApprovalLevel::Deny);
+        assert_eq!(list.evaluate("ls; make"), ApprovalLevel::Ask);
+        assert_eq!(list.evaluate("echo $(whoami)"), ApprovalLevel::Ask);
+    }
+
+    #[test]
+    fn variable_expansion_asks() {
+        let list = ExecAllowlist::with_safe_defaults();
+        assert_eq!(list.evaluate("ls $TARGET"), ApprovalLevel::Ask);
+        assert_eq!(list.evaluate("cat \"${HOME}/.ssh/id_rsa\""), ApprovalLevel::Ask);
+        assert_eq!(list.evaluate("echo '$HOME'"), ApprovalLevel::Allow);
+        assert_eq!(list.evaluate("echo \"price: 5$\""), ApprovalLevel::Allow);
+    }
 }
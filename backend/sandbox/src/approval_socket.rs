@@ -3,8 +3,14 @@
 //! When the agent needs to execute a command and the allowlist says "Ask",
 //! it sends a request to this socket where a connected client (TUI, desktop app)
 //! can grant/deny the approval in real-time.
+//!
+//! The server is also an `ApprovalNotifier`, so the security `ApprovalBroker`
+//! can fan dangerous tool approvals out to socket clients alongside chat
+//! channels; verdicts read back from the socket go to `ApprovalBroker::resolve`.
 
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use clawforge_security::{ApprovalNotifier, PendingApproval};
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::{UnixListener, UnixStream};
@@ -125,6 +131,23 @@ impl ApprovalSocketServer {
     }
 }
 
+#[async_trait]
+impl ApprovalNotifier for ApprovalSocketServer {
+    async fn deliver(&self, request: &PendingApproval) -> Result<()> {
+        self.request_tx
+            .send(ApprovalRequest {
+                id: request.id.clone(),
+                command: request.summary.clone(),
+                session_id: request.session_id.clone(),
+                cwd: None,
+                risk_level: "high".to_string(),
+                risk_reasons: request.reasons.clone(),
+            })
+            .await
+            .context("Approval socket request channel closed")
+    }
+}
+
 impl Drop for ApprovalSocketServer {
     fn drop(&mut self) {
         let _ = std::fs::remove_file(&self.socket_path);
@@ -0,0 +1,241 @@
+//! Bubblewrap sandbox for Linux hosts without Docker.
+//!
+//! Each exec runs in fresh namespaces: the host root is mounted read-only,
+//! the workspace is bind-mounted read-write, and the network is unshared
+//! unless enabled. `/tmp` is a per-session scratch dir, so files left there
+//! persist between execs like in a long-lived container. Bubblewrap has no
+//! memory or CPU limits; use Docker where those matter.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+use crate::docker::{ContainerExecResult, DockerSandboxConfig};
+use crate::driver::SandboxDriver;
+use crate::usage::ResourceUsage;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BwrapSandboxConfig {
+    /// Path to the `bwrap` binary.
+    pub bwrap_path: String,
+    /// Share the host network namespace.
+    pub network: bool,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Workspace directory mount: (host_path, sandbox_path).
+    pub workspace_mount: Option<(String, String)>,
+    /// Extra read-only binds: (host_path, sandbox_path).
+    #[serde(default)]
+    pub ro_binds: Vec<(String, String)>,
+}
+
+impl Default for BwrapSandboxConfig {
+    fn default() -> Self {
+        Self {
+            bwrap_path: "bwrap".to_string(),
+            network: false,
+            env: HashMap::new(),
+            workspace_mount: None,
+            ro_binds: Vec::new(),
+        }
+    }
+}
+
+impl From<&DockerSandboxConfig> for BwrapSandboxConfig {
+    /// Network, env and workspace carry over; image and limits have no bwrap
+    /// equivalent. bwrap has no egress proxy, so an egress policy means no network.
+    fn from(docker: &DockerSandboxConfig) -> Self {
+        Self {
+            network: docker.network_mode != "none" && docker.egress.is_none(),
+            env: docker.env.clone(),
+            workspace_mount: docker.workspace_mount.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+pub struct BwrapSandbox {
+    config: BwrapSandboxConfig,
+    /// Host directory mounted at `/tmp`; set while the session is started.
+    scratch: Option<PathBuf>,
+}
+
+impl BwrapSandbox {
+    pub fn new(config: BwrapSandboxConfig) -> Self {
+        Self { config, scratch: None }
+    }
+
+    fn args(&self, scratch: &Path, command: &[&str]) -> Vec<String> {
+        let mut args: Vec<String> = [
+            "--ro-bind", "/", "/",
+            "--dev", "/dev",
+            "--proc", "/proc",
+            "--unshare-all",
+            "--die-with-parent",
+            "--new-session",
+            "--hostname", "clawforge-sandbox",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        if self.config.network {
+            args.push("--share-net".to_string());
+        }
+        args.extend(["--bind".to_string(), scratch.display().to_string(), "/tmp".to_string()]);
+        for (host, target) in &self.config.ro_binds {
+            args.extend(["--ro-bind".to_string(), host.clone(), target.clone()]);
+        }
+        if let Some((host, target)) = &self.config.workspace_mount {
+            args.extend(["--bind".to_string(), host.clone(), target.clone(), "--chdir".to_string(), target.clone()]);
+        }
+        for (key, val) in &self.config.env {
+            args.extend(["--setenv".to_string(), key.clone(), val.clone()]);
+        }
+        args.push("--".to_string());
+        args.extend(command.iter().map(|s| s.to_string()));
+        args
+    }
+
+    /// Host path backing a sandbox path; only `/tmp` and the workspace are writable.
+    fn host_path(&self, sandbox_path: &str) -> Result<PathBuf> {
+        let path = Path::new(sandbox_path);
+        if let Some((host, target)) = &self.config.workspace_mount {
+            if let Ok(rest) = path.strip_prefix(target) {
+                return Ok(Path::new(host).join(rest));
+            }
+        }
+        if let (Some(scratch), Ok(rest)) = (&self.scratch, path.strip_prefix("/tmp")) {
+            return Ok(scratch.join(rest));
+        }
+        bail!("'{}' is not in the sandbox workspace or /tmp", sandbox_path)
+    }
+}
+
+#[async_trait]
+impl SandboxDriver for BwrapSandbox {
+    fn kind(&self) -> &str {
+        "bwrap"
+    }
+
+    /// Check that bwrap works and create the session's scratch dir.
+    async fn start(&mut self, session_id: &str) -> Result<String> {
+        let output = tokio::process::Command::new(&self.config.bwrap_path)
+            .arg("--version")
+            .output()
+            .await
+            .with_context(|| format!("bubblewrap not found at '{}'", self.config.bwrap_path))?;
+        if !output.status.success() {
+            bail!("bwrap --version failed: {}", String::from_utf8_lossy(&output.stderr));
+        }
+
+        let name = format!("clawforge-bwrap-{}", crate::docker::sanitize_id(session_id));
+        let scratch = std::env::temp_dir().join(&name);
+        tokio::fs::create_dir_all(&scratch).await.context("Failed to create sandbox scratch dir")?;
+        info!(sandbox = %name, "Bubblewrap sandbox ready");
+        self.scratch = Some(scratch);
+        Ok(name)
+    }
+
+    /// Execute a command inside the sandbox.
+    async fn exec(&self, command: &[&str], timeout_secs: Option<u64>) -> Result<ContainerExecResult> {
+        self.exec_with_env(command, &HashMap::new(), timeout_secs).await
+    }
+
+    /// bwrap passes its own environment through, so values never reach a command line.
+    async fn exec_with_env(
+        &self,
+        command: &[&str],
+        env: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+    ) -> Result<ContainerExecResult> {
+        let scratch = self.scratch.as_deref().context("Sandbox not started")?;
+        debug!(cmd = ?command, "Executing in bubblewrap sandbox");
+
+        let mut cmd = tokio::process::Command::new(&self.config.bwrap_path);
+        cmd.args(self.args(scratch, command)).envs(env).kill_on_drop(true);
+        let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(30));
+        let started = std::time::Instant::now();
+        let result = tokio::time::timeout(timeout, cmd.output()).await;
+        let wall_time_ms = started.elapsed().as_millis() as u64;
+        match result {
+            Ok(Ok(output)) => Ok(ContainerExecResult {
+                exit_code: output.status.code().unwrap_or(-1) as i64,
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                wall_time_ms,
+                ..Default::default()
+            }),
+            Ok(Err(e)) => bail!("bwrap exec failed: {e}"),
+            Err(_) => Ok(ContainerExecResult {
+                exit_code: -1,
+                stderr: format!("Command timed out after {}s", timeout_secs.unwrap_or(30)),
+                timed_out: true,
+                wall_time_ms,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Remove the scratch dir.
+    async fn stop(&mut self) -> Result<()> {
+        if let Some(scratch) = self.scratch.take() {
+            info!(scratch = %scratch.display(), "Stopping bubblewrap sandbox");
+            let _ = tokio::fs::remove_dir_all(&scratch).await;
+        }
+        Ok(())
+    }
+
+    /// Copy a file from the host into the sandbox.
+    async fn copy_in(&self, host_path: &str, sandbox_path: &str) -> Result<()> {
+        tokio::fs::copy(host_path, self.host_path(sandbox_path)?).await.context("copy into sandbox failed")?;
+        Ok(())
+    }
+
+    /// Copy a file from the sandbox to the host.
+    async fn copy_out(&self, sandbox_path: &str, host_path: &str) -> Result<()> {
+        tokio::fs::copy(self.host_path(sandbox_path)?, host_path).await.context("copy out of sandbox failed")?;
+        Ok(())
+    }
+
+    async fn resource_usage(&self) -> Result<ResourceUsage> {
+        bail!("The bwrap driver does not report resource usage")
+    }
+}
+
+impl Drop for BwrapSandbox {
+    fn drop(&mut self) {
+        if let Some(scratch) = &self.scratch {
+            let _ = std::fs::remove_dir_all(scratch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_isolated_command_line() {
+        let docker = DockerSandboxConfig {
+            workspace_mount: Some(("/home/me/ws".into(), "/workspace".into())),
+            ..Default::default()
+        };
+        let mut sandbox = BwrapSandbox::new((&docker).into());
+        sandbox.scratch = Some(PathBuf::from("/tmp/scratch"));
+        let args = sandbox.args(Path::new("/tmp/scratch"), &["sh", "-c", "ls"]).join(" ");
+        assert!(args.starts_with("--ro-bind / / "));
+        assert!(args.contains("--unshare-all"));
+        assert!(!args.contains("--share-net"));
+        assert!(args.contains("--bind /home/me/ws /workspace --chdir /workspace"));
+        assert!(args.ends_with("-- sh -c ls"));
+
+        assert_eq!(sandbox.host_path("/workspace/a.txt").unwrap(), PathBuf::from("/home/me/ws/a.txt"));
+        assert_eq!(sandbox.host_path("/tmp/out.bin").unwrap(), PathBuf::from("/tmp/scratch/out.bin"));
+        assert!(sandbox.host_path("/etc/passwd").is_err());
+        sandbox.scratch = None;
+    }
+}
@@ -3,10 +3,24 @@
 //! Manages per-session sandboxed Docker containers for safe code execution.
 
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
+use crate::driver::{forward_lines, OutputChunk, OutputStream, SandboxDriver};
+use crate::egress::{EgressAttempt, EgressPolicy, EgressProxy};
+use crate::usage::{self, ResourceUsage};
+
+/// Name the container uses to reach the host-side egress proxy.
+const PROXY_HOST: &str = "host.docker.internal";
+/// Execs run as `nobody` unless the config names another user.
+const DEFAULT_EXEC_USER: &str = "65534:65534";
+
 /// Configuration for a sandbox container.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -26,6 +40,16 @@ pub struct DockerSandboxConfig {
     pub workspace_mount: Option<(String, String)>,
     /// Max container lifetime in seconds before forced kill.
     pub max_lifetime_secs: Option<u64>,
+    /// Route network access through an allowlisting proxy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub egress: Option<EgressPolicy>,
+    /// User (`uid:gid` or name) execs run as; never root.
+    #[serde(default = "default_exec_user")]
+    pub user: String,
+}
+
+fn default_exec_user() -> String {
+    DEFAULT_EXEC_USER.to_string()
 }
 
 impl Default for DockerSandboxConfig {
@@ -38,18 +62,31 @@ impl Default for DockerSandboxConfig {
             env: HashMap::new(),
             workspace_mount: None,
             max_lifetime_secs: Some(3600),
+            egress: None,
+            user: default_exec_user(),
         }
     }
 }
 
 /// Result of executing a command inside a container.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ContainerExecResult {
     pub exit_code: i64,
     pub stdout: String,
     pub stderr: String,
     pub timed_out: bool,
+    pub wall_time_ms: u64,
+    /// CPU time spent by the sandbox during the exec, when measurable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_time_ms: Option<u64>,
+    /// Peak memory of the sandbox so far, when measurable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_peak_bytes: Option<u64>,
+    /// The kernel OOM killer fired during the exec.
+    pub oom_killed: bool,
+    /// The head of stdout or stderr was dropped to stay under the output cap.
+    pub truncated: bool,
 }
 
 /// Lightweight Docker client wrapper.
@@ -57,27 +94,112 @@ pub struct ContainerExecResult {
 pub struct DockerSandbox {
     config: DockerSandboxConfig,
     container_id: Option<String>,
+    /// Egress proxy and its serving task, while the container runs.
+    egress: Option<(EgressProxy, tokio::task::JoinHandle<()>)>,
+    /// Execs started so far, numbering their PID files.
+    execs: AtomicU64,
 }
 
 impl DockerSandbox {
     pub fn new(config: DockerSandboxConfig) -> Self {
-        Self { config, container_id: None }
+        Self { config, container_id: None, egress: None, execs: AtomicU64::new(0) }
+    }
+
+    /// Where the next exec records its PID inside the container.
+    fn next_pid_file(&self) -> String {
+        format!("/tmp/.clawforge-exec-{}.pid", self.execs.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn container_id(&self) -> Option<&str> {
+        self.container_id.as_deref()
+    }
+
+    /// Pin the proxy to the container's address and apply the firewall rules.
+    /// The rules go in from the host through the container's network
+    /// namespace, since the container itself holds no capabilities.
+    async fn lock_down_egress(&self, container_id: &str, proxy_addr: SocketAddr) -> Result<()> {
+        let Some((proxy, _)) = &self.egress else { return Ok(()) };
+        let output = tokio::process::Command::new("docker")
+            .args(["inspect", "-f", "{{range .NetworkSettings.Networks}}{{.IPAddress}} {{end}}", container_id])
+            .output()
+            .await?;
+        let ip = String::from_utf8_lossy(&output.stdout).split_whitespace().next().and_then(|ip| ip.parse().ok());
+        let ip = ip.with_context(|| format!("Could not find the IP of container {container_id} for its egress proxy"))?;
+        proxy.restrict_peer(ip);
+
+        if proxy.policy().firewall {
+            let output = tokio::process::Command::new("docker")
+                .args(["inspect", "-f", "{{.State.Pid}}", container_id])
+                .output()
+                .await?;
+            let pid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if pid.parse::<u32>().map_or(true, |pid| pid == 0) {
+                anyhow::bail!("Could not find the PID of container {container_id} for its egress firewall");
+            }
+            let script = proxy.policy().firewall_script(proxy_addr.ip(), proxy_addr.port());
+            let output = tokio::process::Command::new("nsenter")
+                .args(["--target", &pid, "--net", "sh", "-c", &script])
+                .output()
+                .await
+                .context("Failed to run nsenter for the egress firewall")?;
+            if !output.status.success() {
+                anyhow::bail!("Failed to apply egress firewall: {}", String::from_utf8_lossy(&output.stderr).trim());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SandboxDriver for DockerSandbox {
+    fn kind(&self) -> &str {
+        "docker"
     }
 
     /// Start a new container for this sandbox session.
-    pub async fn start(&mut self, session_id: &str) -> Result<String> {
+    async fn start(&mut self, session_id: &str) -> Result<String> {
         let container_name = format!("clawforge-sandbox-{}", sanitize_id(session_id));
         info!(container = %container_name, image = %self.config.image, "Starting sandbox container");
 
+        // A container under an egress policy only gets a network when the
+        // proxy is its sole way out: something is allowed and the firewall
+        // drops everything else. Otherwise it gets no network at all.
+        let proxied = self.config.egress.as_ref().filter(|p| p.firewall && !p.denies_all());
+        let network = match &self.config.egress {
+            Some(_) if proxied.is_some() => "bridge".to_string(),
+            Some(_) => "none".to_string(),
+            None => self.config.network_mode.clone(),
+        };
+
         // Build docker run args.
         let mut args = vec![
             "docker".to_string(),
             "run".to_string(),
             "-d".to_string(),
+            // Reaps the processes of execs killed on timeout.
+            "--init".to_string(),
+            // Sandboxed code gets no capabilities and cannot regain any, so
+            // it cannot touch the egress firewall either.
+            "--cap-drop=ALL".to_string(),
+            "--security-opt=no-new-privileges".to_string(),
             "--name".to_string(), container_name.clone(),
-            "--network".to_string(), self.config.network_mode.clone(),
+            "--network".to_string(), network,
         ];
 
+        let mut proxy_addr = None;
+        if let Some(policy) = proxied {
+            // Listen only where `host-gateway` points, not on every interface.
+            let proxy = EgressProxy::new(policy.clone());
+            let (addr, task) = proxy.start((bridge_gateway().await?, 0).into()).await?;
+            proxy_addr = Some(addr);
+            self.egress = Some((proxy, task));
+            args.push(format!("--add-host={PROXY_HOST}:host-gateway"));
+            for (key, val) in EgressPolicy::proxy_env(&format!("http://{PROXY_HOST}:{}", addr.port())) {
+                args.push("-e".to_string());
+                args.push(format!("{key}={val}"));
+            }
+        }
+
         if let Some(mem) = &self.config.memory_limit {
             args.push("-m".to_string());
             args.push(mem.clone());
@@ -124,13 +246,30 @@ impl DockerSandbox {
         let container_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
         info!(container_id = %container_id, "Sandbox container started");
         self.container_id = Some(container_id.clone());
+        if let Some(proxy_addr) = proxy_addr {
+            if let Err(e) = self.lock_down_egress(&container_id, proxy_addr).await {
+                self.stop().await.ok();
+                return Err(e);
+            }
+        }
         Ok(container_id)
     }
 
     /// Execute a command inside the running container.
-    pub async fn exec(
+    async fn exec(
+        &self,
+        command: &[&str],
+        timeout_secs: Option<u64>,
+    ) -> Result<ContainerExecResult> {
+        self.exec_with_env(command, &HashMap::new(), timeout_secs).await
+    }
+
+    /// Values are passed through the docker CLI's environment (`-e KEY`),
+    /// so they never appear on a command line.
+    async fn exec_with_env(
         &self,
         command: &[&str],
+        env: &HashMap<String, String>,
         timeout_secs: Option<u64>,
     ) -> Result<ContainerExecResult> {
         let container_id = self
@@ -138,42 +277,101 @@ impl DockerSandbox {
             .as_deref()
             .context("Container not started")?;
 
-        let mut args = vec!["docker", "exec", container_id];
-        args.extend_from_slice(command);
-
+        let pid_file = self.next_pid_file();
+        let args = exec_args(container_id, &self.config.user, env, command, &pid_file);
         debug!(container = %container_id, cmd = ?command, "Executing in sandbox");
 
+        let before = usage::sample_container(container_id).await.ok();
+        let started = std::time::Instant::now();
         let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(30));
-        let result = tokio::time::timeout(timeout, async {
-            tokio::process::Command::new(args[0])
-                .args(&args[1..])
-                .output()
-                .await
-        })
+        let result = tokio::time::timeout(
+            timeout,
+            tokio::process::Command::new("docker")
+                .args(&args)
+                .envs(env)
+                .kill_on_drop(true)
+                .output(),
+        )
         .await;
 
-        match result {
-            Ok(Ok(output)) => {
-                let exit_code = output.status.code().unwrap_or(-1) as i64;
-                Ok(ContainerExecResult {
-                    exit_code,
-                    stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                    stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-                    timed_out: false,
-                })
+        let exec = match result {
+            Ok(Ok(output)) => ContainerExecResult {
+                exit_code: output.status.code().unwrap_or(-1) as i64,
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                ..Default::default()
+            },
+            Ok(Err(e)) => anyhow::bail!("docker exec failed: {e}"),
+            Err(_) => {
+                kill_exec(container_id, &self.config.user, &pid_file).await;
+                timed_out(timeout_secs)
             }
+        };
+        Ok(finish_exec(container_id, command, exec, started, before).await)
+    }
+
+    /// Pipes `docker exec` and forwards its lines while it runs, so long
+    /// builds show progress before they finish.
+    async fn exec_streaming(
+        &self,
+        command: &[&str],
+        env: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+        max_output_bytes: usize,
+        output: &mpsc::UnboundedSender<OutputChunk>,
+    ) -> Result<ContainerExecResult> {
+        let container_id = self
+            .container_id
+            .as_deref()
+            .context("Container not started")?;
+        let pid_file = self.next_pid_file();
+        let args = exec_args(container_id, &self.config.user, env, command, &pid_file);
+        debug!(container = %container_id, cmd = ?command, "Streaming exec in sandbox");
+
+        let before = usage::sample_container(container_id).await.ok();
+        let started = std::time::Instant::now();
+        let mut child = tokio::process::Command::new("docker")
+            .args(&args)
+            .envs(env)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("docker exec failed")?;
+        let stdout = child.stdout.take().context("docker exec stdout unavailable")?;
+        let stderr = child.stderr.take().context("docker exec stderr unavailable")?;
+        let run = async {
+            let (stdout, stderr) = tokio::join!(
+                forward_lines(stdout, OutputStream::Stdout, Some(output), max_output_bytes),
+                forward_lines(stderr, OutputStream::Stderr, Some(output), max_output_bytes),
+            );
+            let status = child.wait().await?;
+            Ok::<_, anyhow::Error>((status, stdout, stderr))
+        };
+        let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(30));
+
+        let exec = match tokio::time::timeout(timeout, run).await {
+            Ok(Ok((status, (stdout, stdout_cut), (stderr, stderr_cut)))) => ContainerExecResult {
+                exit_code: status.code().unwrap_or(-1) as i64,
+                stdout,
+                stderr,
+                truncated: stdout_cut || stderr_cut,
+                ..Default::default()
+            },
             Ok(Err(e)) => anyhow::bail!("docker exec failed: {e}"),
-            Err(_) => Ok(ContainerExecResult {
-                exit_code: -1,
-                stdout: String::new(),
-                stderr: format!("Command timed out after {}s", timeout_secs.unwrap_or(30)),
-                timed_out: true,
-            }),
-        }
+            Err(_) => {
+                kill_exec(container_id, &self.config.user, &pid_file).await;
+                timed_out(timeout_secs)
+            }
+        };
+        Ok(finish_exec(container_id, command, exec, started, before).await)
     }
 
     /// Stop and remove the container.
-    pub async fn stop(&mut self) -> Result<()> {
+    async fn stop(&mut self) -> Result<()> {
+        if let Some((_, task)) = self.egress.take() {
+            task.abort();
+        }
         let Some(id) = self.container_id.take() else {
             return Ok(());
         };
@@ -186,7 +384,7 @@ impl DockerSandbox {
     }
 
     /// Copy a file from the host into the container.
-    pub async fn copy_in(&self, host_path: &str, container_path: &str) -> Result<()> {
+    async fn copy_in(&self, host_path: &str, container_path: &str) -> Result<()> {
         let id = self.container_id.as_deref().context("Container not started")?;
         let output = tokio::process::Command::new("docker")
             .args(["cp", host_path, &format!("{id}:{container_path}")])
@@ -202,7 +400,7 @@ impl DockerSandbox {
     }
 
     /// Copy a file from the container to the host.
-    pub async fn copy_out(&self, container_path: &str, host_path: &str) -> Result<()> {
+    async fn copy_out(&self, container_path: &str, host_path: &str) -> Result<()> {
         let id = self.container_id.as_deref().context("Container not started")?;
         let output = tokio::process::Command::new("docker")
             .args(["cp", &format!("{id}:{container_path}"), host_path])
@@ -217,13 +415,21 @@ impl DockerSandbox {
         Ok(())
     }
 
-    pub fn container_id(&self) -> Option<&str> {
-        self.container_id.as_deref()
+    async fn resource_usage(&self) -> Result<ResourceUsage> {
+        let id = self.container_id.as_deref().context("Container not started")?;
+        usage::sample_container(id).await
+    }
+
+    fn take_blocked_egress(&self) -> Vec<EgressAttempt> {
+        self.egress.as_ref().map(|(proxy, _)| proxy.take_blocked()).unwrap_or_default()
     }
 }
 
 impl Drop for DockerSandbox {
     fn drop(&mut self) {
+        if let Some((_, task)) = &self.egress {
+            task.abort();
+        }
         if let Some(id) = &self.container_id {
             let id = id.clone();
             warn!(container = %id, "DockerSandbox dropped without explicit stop; removing container");
@@ -234,8 +440,103 @@ impl Drop for DockerSandbox {
     }
 }
 
-fn sanitize_id(s: &str) -> String {
-    s.chars()
-        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '-' })
-        .collect()
+/// Host address of the default bridge network, which `host-gateway` maps to.
+async fn bridge_gateway() -> Result<IpAddr> {
+    let output = tokio::process::Command::new("docker")
+        .args(["network", "inspect", "bridge", "-f", "{{range .IPAM.Config}}{{.Gateway}} {{end}}"])
+        .output()
+        .await
+        .context("Failed to inspect the docker bridge network")?;
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .find_map(|ip| ip.parse().ok())
+        .context("Could not find the docker bridge gateway address")
+}
+
+/// `docker exec` arguments, naming each env var with `-e KEY`. The command
+/// runs as `user` under a shell that records its PID in `pid_file` before
+/// `exec`ing it, so a timeout can kill it inside the container.
+fn exec_args(container_id: &str, user: &str, env: &HashMap<String, String>, command: &[&str], pid_file: &str) -> Vec<String> {
+    let mut args = vec!["exec".to_string(), "--user".to_string(), user.to_string()];
+    for key in env.keys() {
+        args.extend(["-e".to_string(), key.clone()]);
+    }
+    args.push(container_id.to_string());
+    args.extend(["sh".to_string(), "-c".to_string(), format!("echo $$ > {pid_file} && exec \"$@\""), "sh".to_string()]);
+    args.extend(command.iter().map(|arg| arg.to_string()));
+    args
+}
+
+/// Kill a timed-out exec inside the container. Killing the local `docker
+/// exec` client alone leaves the process running there. Runs as the exec's
+/// own user, since the container holds no `CAP_KILL`.
+async fn kill_exec(container_id: &str, user: &str, pid_file: &str) {
+    let script = format!(
+        "pid=$(cat {pid_file} 2>/dev/null) && {{ kill -KILL -- -$pid 2>/dev/null || kill -KILL $pid; }}; rm -f {pid_file}"
+    );
+    match tokio::process::Command::new("docker").args(["exec", "--user", user, container_id, "sh", "-c", &script]).output().await {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => warn!(container = %container_id, "Failed to kill timed-out exec: {}", String::from_utf8_lossy(&output.stderr).trim()),
+        Err(e) => warn!(container = %container_id, "Failed to kill timed-out exec: {e}"),
+    }
+}
+
+fn timed_out(timeout_secs: Option<u64>) -> ContainerExecResult {
+    ContainerExecResult {
+        exit_code: -1,
+        stderr: format!("Command timed out after {}s", timeout_secs.unwrap_or(30)),
+        timed_out: true,
+        ..Default::default()
+    }
+}
+
+/// Fill in the wall time and resource usage measured around an exec.
+async fn finish_exec(
+    container_id: &str,
+    command: &[&str],
+    mut exec: ContainerExecResult,
+    started: std::time::Instant,
+    before: Option<ResourceUsage>,
+) -> ContainerExecResult {
+    exec.wall_time_ms = started.elapsed().as_millis() as u64;
+    let after = usage::sample_container(container_id).await.ok();
+    let (cpu_time_ms, memory_peak_bytes, oom_killed) = usage::exec_delta(before, after);
+    exec.cpu_time_ms = cpu_time_ms;
+    exec.memory_peak_bytes = memory_peak_bytes;
+    exec.oom_killed = oom_killed;
+    if oom_killed {
+        warn!(container = %container_id, cmd = ?command, "Sandbox exec was OOM-killed");
+    }
+    exec
+}
+
+/// Session id as a container / directory name: ASCII letters, digits and
+/// `-` are kept, any other byte becomes `_XX` hex, so distinct ids (say
+/// `chat:42` and `chat-42`) never share a name.
+pub(crate) fn sanitize_id(s: &str) -> String {
+    let mut name = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("_{:02X}", byte));
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exec_args_record_the_pid_before_running_the_command() {
+        let env = HashMap::from([("TOKEN".to_string(), "secret".to_string())]);
+        let args = exec_args("c1", DEFAULT_EXEC_USER, &env, &["ls", "-la"], "/tmp/.clawforge-exec-0.pid");
+        assert_eq!(
+            args,
+            ["exec", "--user", "65534:65534", "-e", "TOKEN", "c1", "sh", "-c", "echo $$ > /tmp/.clawforge-exec-0.pid && exec \"$@\"", "sh", "ls", "-la"]
+        );
+        assert!(!args.iter().any(|arg| arg.contains("secret")));
+    }
 }
@@ -0,0 +1,213 @@
+//! Sandbox driver interface.
+//!
+//! Every backend (Docker, bubblewrap, and later podman, firecracker or remote
+//! hosts) implements `SandboxDriver`. `SandboxRegistry` builds drivers by the
+//! `sandbox.driver` name through registered factories, so adding a backend
+//! means registering a factory rather than touching callers.
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc;
+
+use crate::docker::{ContainerExecResult, DockerSandboxConfig};
+use crate::egress::EgressAttempt;
+use crate::usage::ResourceUsage;
+
+/// Output kept per stream by streaming execs unless the caller asks otherwise.
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// One line of exec output, forwarded as it arrives.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputChunk {
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+/// A per-session sandbox.
+#[async_trait]
+pub trait SandboxDriver: Send + Sync {
+    /// Driver name as used in `sandbox.driver`.
+    fn kind(&self) -> &str;
+
+    /// Bring the sandbox up for `session_id`; returns its container or instance name.
+    async fn start(&mut self, session_id: &str) -> Result<String>;
+
+    async fn exec(&self, command: &[&str], timeout_secs: Option<u64>) -> Result<ContainerExecResult>;
+
+    /// `exec` with extra env vars for this exec only, used to inject leased
+    /// secrets. Drivers must not put the values on a command line.
+    async fn exec_with_env(
+        &self,
+        command: &[&str],
+        env: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+    ) -> Result<ContainerExecResult> {
+        if !env.is_empty() {
+            bail!("Sandbox driver '{}' cannot inject environment variables", self.kind());
+        }
+        self.exec(command, timeout_secs).await
+    }
+
+    /// `exec_with_env`, forwarding stdout/stderr lines to `output` as they are
+    /// produced. Each stream in the result keeps only its last
+    /// `max_output_bytes`. Drivers that cannot stream send the lines when the
+    /// command finishes.
+    async fn exec_streaming(
+        &self,
+        command: &[&str],
+        env: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+        max_output_bytes: usize,
+        output: &mpsc::UnboundedSender<OutputChunk>,
+    ) -> Result<ContainerExecResult> {
+        let mut result = self.exec_with_env(command, env, timeout_secs).await?;
+        for (stream, text) in [(OutputStream::Stdout, &result.stdout), (OutputStream::Stderr, &result.stderr)] {
+            for line in text.lines() {
+                let _ = output.send(OutputChunk { stream, line: line.to_string() });
+            }
+        }
+        truncate_result(&mut result, max_output_bytes);
+        Ok(result)
+    }
+
+    /// Copy a file from the host into the sandbox.
+    async fn copy_in(&self, host_path: &str, sandbox_path: &str) -> Result<()>;
+
+    /// Copy a file from the sandbox to the host.
+    async fn copy_out(&self, sandbox_path: &str, host_path: &str) -> Result<()>;
+
+    async fn stop(&mut self) -> Result<()>;
+
+    /// Cumulative CPU, memory, disk and network usage.
+    async fn resource_usage(&self) -> Result<ResourceUsage>;
+
+    /// Connections refused by the egress policy since the last call.
+    fn take_blocked_egress(&self) -> Vec<EgressAttempt> {
+        Vec::new()
+    }
+}
+
+/// Builds a driver from the shared sandbox settings (image, network, env, workspace, limits).
+pub type DriverFactory = Arc<dyn Fn(&DockerSandboxConfig) -> Box<dyn SandboxDriver> + Send + Sync>;
+
+// ---------------------------------------------------------------------------
+// Output capture
+// ---------------------------------------------------------------------------
+
+/// Keep the last `max_bytes` of each stream, marking the result truncated.
+pub fn truncate_result(result: &mut ContainerExecResult, max_bytes: usize) {
+    let stdout = truncate_tail(&mut result.stdout, max_bytes);
+    let stderr = truncate_tail(&mut result.stderr, max_bytes);
+    result.truncated |= stdout || stderr;
+}
+
+/// Drop the head of `text` so at most `max_bytes` remain, cutting at a line
+/// start where possible. Returns whether anything was dropped.
+pub fn truncate_tail(text: &mut String, max_bytes: usize) -> bool {
+    if text.len() <= max_bytes {
+        return false;
+    }
+    let mut cut = text.len() - max_bytes;
+    while !text.is_char_boundary(cut) {
+        cut += 1;
+    }
+    if let Some(newline) = text[cut..].find('\n').filter(|&i| cut + i + 1 < text.len()) {
+        cut += newline + 1;
+    }
+    text.drain(..cut);
+    true
+}
+
+/// Lines of a stream, keeping only the most recent `max_bytes` of them.
+struct TailBuffer {
+    lines: VecDeque<String>,
+    bytes: usize,
+    max_bytes: usize,
+    truncated: bool,
+}
+
+impl TailBuffer {
+    fn new(max_bytes: usize) -> Self {
+        Self { lines: VecDeque::new(), bytes: 0, max_bytes, truncated: false }
+    }
+
+    fn push(&mut self, line: String) {
+        self.bytes += line.len() + 1;
+        self.lines.push_back(line);
+        while self.bytes > self.max_bytes && self.lines.len() > 1 {
+            let dropped = self.lines.pop_front().unwrap_or_default();
+            self.bytes -= dropped.len() + 1;
+            self.truncated = true;
+        }
+    }
+
+    fn finish(self) -> (String, bool) {
+        let mut text: String = self.lines.into_iter().flat_map(|line| [line, "\n".to_string()]).collect();
+        // A single line longer than the cap is cut too.
+        let cut = truncate_tail(&mut text, self.max_bytes);
+        (text, self.truncated || cut)
+    }
+}
+
+/// Read a stream line by line, forwarding each line to `output` and
+/// returning the last `max_bytes` of it and whether the head was dropped.
+pub(crate) async fn forward_lines<R: tokio::io::AsyncRead + Unpin>(
+    reader: R,
+    stream: OutputStream,
+    output: Option<&mpsc::UnboundedSender<OutputChunk>>,
+    max_bytes: usize,
+) -> (String, bool) {
+    let mut lines = BufReader::new(reader).lines();
+    let mut tail = TailBuffer::new(max_bytes);
+    while let Ok(Some(line)) = lines.next_line().await {
+        if let Some(tx) = output {
+            let _ = tx.send(OutputChunk { stream, line: line.clone() });
+        }
+        tail.push(line);
+    }
+    tail.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncation_keeps_the_tail() {
+        let mut text = "one\ntwo\nthree\n".to_string();
+        assert!(!truncate_tail(&mut text, 64));
+        assert!(truncate_tail(&mut text, 8));
+        assert_eq!(text, "three\n");
+
+        let mut tail = TailBuffer::new(10);
+        for line in ["compiling a", "compiling b", "done"] {
+            tail.push(line.to_string());
+        }
+        assert_eq!(tail.finish(), ("done\n".to_string(), true));
+    }
+
+    #[tokio::test]
+    async fn forwards_every_line_but_keeps_the_tail() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let input: &[u8] = b"step 1\nstep 2\nstep 3\n";
+        let (text, truncated) = forward_lines(input, OutputStream::Stdout, Some(&tx), 7).await;
+        assert_eq!((text.as_str(), truncated), ("step 3\n", true));
+        drop(tx);
+        let mut forwarded = Vec::new();
+        while let Some(chunk) = rx.recv().await {
+            forwarded.push(chunk.line);
+        }
+        assert_eq!(forwarded, ["step 1", "step 2", "step 3"]);
+    }
+}
@@ -0,0 +1,427 @@
+//! Network egress policy for sandboxed execs.
+//!
+//! A sandbox with an `EgressPolicy` reaches the network only through an
+//! in-process HTTP(S) proxy that checks every destination against an
+//! allowlist of domains and CIDRs. With `firewall` set, the container also
+//! gets iptables rules that drop all other outbound traffic, so tools that
+//! ignore `HTTPS_PROXY` cannot go around it. The rules are applied from the
+//! host inside the container's network namespace; the container itself runs
+//! without capabilities and cannot change them.
+//!
+//! Blocked attempts are kept on the proxy until the executor collects them
+//! as `EgressBlocked` audit events.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, warn};
+
+use clawforge_core::Capabilities;
+
+/// Largest request head the proxy reads before giving up.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// Blocked attempts kept between collections.
+const MAX_PENDING_ATTEMPTS: usize = 1000;
+
+/// An IP network such as `10.0.0.0/8`; a bare address is a single host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::str::FromStr for Cidr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (addr, prefix) = s.split_once('/').unwrap_or((s, ""));
+        let addr: IpAddr = addr.parse().with_context(|| format!("Invalid CIDR '{}'", s))?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = if prefix.is_empty() { max } else { prefix.parse().with_context(|| format!("Invalid CIDR '{}'", s))? };
+        if prefix > max {
+            bail!("Invalid CIDR '{}': prefix over /{}", s, max);
+        }
+        Ok(Self { addr, prefix })
+    }
+}
+
+impl TryFrom<String> for Cidr {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Cidr> for String {
+    fn from(c: Cidr) -> String {
+        format!("{}/{}", c.addr, c.prefix)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EgressPolicy {
+    /// Allowed host names; `example.com` also allows its subdomains.
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    #[serde(default)]
+    pub allowed_cidrs: Vec<Cidr>,
+    /// Also drop non-proxied traffic with iptables inside the container.
+    #[serde(default)]
+    pub firewall: bool,
+}
+
+impl EgressPolicy {
+    /// Split allowlist entries into CIDRs and domains (`*.` prefixes are dropped).
+    pub fn from_allowlist(entries: &[String]) -> Self {
+        let mut policy = Self::default();
+        for entry in entries.iter().map(|e| e.trim()).filter(|e| !e.is_empty()) {
+            match entry.parse::<Cidr>() {
+                Ok(cidr) => policy.allowed_cidrs.push(cidr),
+                Err(_) => policy.allowed_domains.push(entry.trim_start_matches("*.").to_lowercase()),
+            }
+        }
+        policy
+    }
+
+    /// The policy implied by an agent's capabilities: no network without
+    /// `can_make_http_requests`, `allowed_domains` when set, and `None`
+    /// (unrestricted) otherwise — the same rules the executor applies to
+    /// HTTP actions.
+    pub fn from_capabilities(capabilities: &Capabilities) -> Option<Self> {
+        if !capabilities.can_make_http_requests {
+            return Some(Self::default());
+        }
+        if capabilities.allowed_domains.is_empty() {
+            return None;
+        }
+        Some(Self::from_allowlist(&capabilities.allowed_domains))
+    }
+
+    pub fn with_firewall(mut self, firewall: bool) -> Self {
+        self.firewall = firewall;
+        self
+    }
+
+    /// Nothing is allowed: the sandbox gets no network.
+    pub fn denies_all(&self) -> bool {
+        self.allowed_domains.is_empty() && self.allowed_cidrs.is_empty()
+    }
+
+    fn allows_name(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_lowercase();
+        self.allowed_domains
+            .iter()
+            .any(|d| host == *d || host.strip_suffix(d.as_str()).is_some_and(|rest| rest.ends_with('.')))
+    }
+
+    /// Whether `ip` falls in an allowed CIDR.
+    pub fn allows_ip(&self, ip: IpAddr) -> bool {
+        self.allowed_cidrs.iter().any(|c| c.contains(ip))
+    }
+
+    /// Whether `host` may be reached. Names outside the domain list are
+    /// allowed only if every address they resolve to is in an allowed CIDR.
+    pub async fn allows(&self, host: &str, port: u16) -> bool {
+        if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+            return self.allows_ip(ip);
+        }
+        if self.allows_name(host) {
+            return true;
+        }
+        if self.allowed_cidrs.is_empty() {
+            return false;
+        }
+        match tokio::net::lookup_host((host, port)).await {
+            Ok(addrs) => {
+                let ips: Vec<IpAddr> = addrs.map(|a| a.ip()).collect();
+                !ips.is_empty() && ips.into_iter().all(|ip| self.allows_ip(ip))
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// The addresses to connect to for `host`, if it may be reached. The
+    /// proxy dials exactly these, so a name cannot resolve to an allowed
+    /// address for the check and to another one for the connection.
+    pub async fn allowed_addrs(&self, host: &str, port: u16) -> Option<Vec<SocketAddr>> {
+        if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+            return self.allows_ip(ip).then(|| vec![SocketAddr::new(ip, port)]);
+        }
+        let by_name = self.allows_name(host);
+        if !by_name && self.allowed_cidrs.is_empty() {
+            return None;
+        }
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await.ok()?.collect();
+        let allowed = !addrs.is_empty() && (by_name || addrs.iter().all(|a| self.allows_ip(a.ip())));
+        allowed.then_some(addrs)
+    }
+
+    /// Env vars that point HTTP clients in the sandbox at the proxy.
+    pub fn proxy_env(proxy_url: &str) -> Vec<(String, String)> {
+        ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy", "ALL_PROXY"]
+            .iter()
+            .map(|k| (k.to_string(), proxy_url.to_string()))
+            .chain(["NO_PROXY", "no_proxy"].iter().map(|k| (k.to_string(), "localhost,127.0.0.1".to_string())))
+            .collect()
+    }
+
+    /// Shell script that limits outbound traffic to loopback, the proxy at
+    /// `proxy` and the allowed CIDRs.
+    pub fn firewall_script(&self, proxy: IpAddr, proxy_port: u16) -> String {
+        let mut lines = vec!["set -e".to_string()];
+        // IPv6 may be disabled in the container; only IPv4 failures abort.
+        for (tool, tail) in [("iptables", ""), ("ip6tables", " || true")] {
+            lines.push(format!("{tool} -F OUTPUT{tail}"));
+            lines.push(format!("{tool} -A OUTPUT -o lo -j ACCEPT{tail}"));
+            lines.push(format!("{tool} -A OUTPUT -m conntrack --ctstate ESTABLISHED,RELATED -j ACCEPT{tail}"));
+        }
+        let tool = if proxy.is_ipv4() { "iptables" } else { "ip6tables" };
+        lines.push(format!("{tool} -A OUTPUT -p tcp -d {proxy} --dport {proxy_port} -j ACCEPT"));
+        for cidr in &self.allowed_cidrs {
+            let tool = if cidr.addr.is_ipv4() { "iptables" } else { "ip6tables" };
+            lines.push(format!("{tool} -A OUTPUT -d {} -j ACCEPT", String::from(*cidr)));
+        }
+        lines.push("iptables -P OUTPUT DROP".to_string());
+        lines.push("ip6tables -P OUTPUT DROP || true".to_string());
+        lines.join("\n")
+    }
+}
+
+/// A connection the proxy refused.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EgressAttempt {
+    pub host: String,
+    pub port: u16,
+    /// `CONNECT` for HTTPS tunnels, otherwise the HTTP method.
+    pub method: String,
+    /// Unix seconds.
+    pub at: u64,
+}
+
+/// Allowlisting forward proxy for one sandbox.
+#[derive(Clone)]
+pub struct EgressProxy {
+    policy: Arc<EgressPolicy>,
+    blocked: Arc<Mutex<Vec<EgressAttempt>>>,
+    /// Only this client may use the proxy once set (the sandbox's address).
+    peer: Arc<Mutex<Option<IpAddr>>>,
+}
+
+impl EgressProxy {
+    pub fn new(policy: EgressPolicy) -> Self {
+        Self { policy: Arc::new(policy), blocked: Arc::default(), peer: Arc::default() }
+    }
+
+    pub fn policy(&self) -> &EgressPolicy {
+        &self.policy
+    }
+
+    /// Refuse clients other than `ip`.
+    pub fn restrict_peer(&self, ip: IpAddr) {
+        *self.peer.lock().unwrap() = Some(ip);
+    }
+
+    /// Blocked attempts since the last call.
+    pub fn take_blocked(&self) -> Vec<EgressAttempt> {
+        std::mem::take(&mut *self.blocked.lock().unwrap())
+    }
+
+    /// Listen on `bind` and serve until the task is aborted.
+    pub async fn start(&self, bind: SocketAddr) -> Result<(SocketAddr, tokio::task::JoinHandle<()>)> {
+        let listener = TcpListener::bind(bind).await.context("Failed to bind egress proxy")?;
+        let addr = listener.local_addr()?;
+        let proxy = self.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let (client, from) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        warn!("Egress proxy accept failed: {e}");
+                        continue;
+                    }
+                };
+                let allowed_peer = *proxy.peer.lock().unwrap();
+                if allowed_peer.is_some_and(|peer| peer != from.ip()) {
+                    debug!(%from, "Egress proxy refused foreign client");
+                    continue;
+                }
+                let proxy = proxy.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = proxy.serve(client).await {
+                        debug!("Egress proxy connection ended: {e:#}");
+                    }
+                });
+            }
+        });
+        Ok((addr, task))
+    }
+
+    async fn serve(&self, mut client: TcpStream) -> Result<()> {
+        let mut buf = Vec::with_capacity(1024);
+        let head_end = loop {
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            if buf.len() > MAX_HEAD_BYTES {
+                bail!("Request head too large");
+            }
+            let mut chunk = [0u8; 4096];
+            let n = client.read(&mut chunk).await?;
+            if n == 0 {
+                bail!("Client closed before sending a request");
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        };
+
+        let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+        let target = parse_request(&head)?;
+        let Some(addrs) = self.policy.allowed_addrs(&target.host, target.port).await else {
+            warn!(host = %target.host, port = target.port, "Sandbox egress blocked");
+            {
+                let mut blocked = self.blocked.lock().unwrap();
+                if blocked.len() < MAX_PENDING_ATTEMPTS {
+                    blocked.push(EgressAttempt {
+                        host: target.host.clone(),
+                        port: target.port,
+                        method: target.method.clone(),
+                        at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default(),
+                    });
+                }
+            }
+            client
+                .write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 25\r\nConnection: close\r\n\r\nBlocked by egress policy\n")
+                .await?;
+            return Ok(());
+        };
+
+        debug!(host = %target.host, port = target.port, "Sandbox egress allowed");
+        let mut upstream = TcpStream::connect(&addrs[..]).await?;
+        match &target.origin_head {
+            None => client.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n").await?,
+            Some(origin_head) => {
+                upstream.write_all(origin_head.as_bytes()).await?;
+                upstream.write_all(&buf[head_end..]).await?;
+            }
+        }
+        tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct ProxyTarget {
+    method: String,
+    host: String,
+    port: u16,
+    /// Request head rewritten to origin form; `None` for CONNECT tunnels.
+    origin_head: Option<String>,
+}
+
+fn split_host_port(authority: &str, default_port: u16) -> Result<(String, u16)> {
+    if let Some(rest) = authority.strip_prefix('[') {
+        let (host, after) = rest.split_once(']').context("Bad IPv6 authority")?;
+        let port = after.strip_prefix(':').map(str::parse).transpose()?.unwrap_or(default_port);
+        return Ok((host.to_string(), port));
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) => Ok((host.to_string(), port.parse().context("Bad port")?)),
+        None => Ok((authority.to_string(), default_port)),
+    }
+}
+
+fn parse_request(head: &str) -> Result<ProxyTarget> {
+    let line = head.lines().next().context("Empty request")?;
+    let mut parts = line.split_whitespace();
+    let (method, target, version) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(m), Some(t), Some(v)) => (m, t, v),
+        _ => bail!("Malformed request line"),
+    };
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let (host, port) = split_host_port(target, 443)?;
+        return Ok(ProxyTarget { method: "CONNECT".into(), host, port, origin_head: None });
+    }
+
+    let rest = target.strip_prefix("http://").context("Only http:// URLs can be proxied without CONNECT")?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = split_host_port(authority, 80)?;
+    let origin_head = format!("{} {} {}{}", method, path, version, &head[line.len()..]);
+    Ok(ProxyTarget { method: method.to_string(), host, port, origin_head: Some(origin_head) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn policy_matches_domains_and_cidrs() {
+        let policy = EgressPolicy::from_allowlist(&["*.github.com".into(), "pypi.org".into(), "10.0.0.0/8".into()]);
+        assert_eq!(policy.allowed_domains, vec!["github.com", "pypi.org"]);
+        assert!(policy.allows("api.github.com", 443).await);
+        assert!(policy.allows("PyPI.org", 443).await);
+        assert!(!policy.allows("evilgithub.com", 443).await);
+        assert!(policy.allows("10.1.2.3", 80).await);
+        assert!(!policy.allows("192.168.1.1", 80).await);
+        assert_eq!(policy.allowed_addrs("10.1.2.3", 80).await, Some(vec!["10.1.2.3:80".parse().unwrap()]));
+        assert_eq!(policy.allowed_addrs("192.168.1.1", 80).await, None);
+        assert!(EgressPolicy::default().denies_all());
+
+        let caps = Capabilities { can_make_http_requests: false, ..Default::default() };
+        assert_eq!(EgressPolicy::from_capabilities(&caps), Some(EgressPolicy::default()));
+        let v6: Cidr = "fd00::/8".parse().unwrap();
+        assert!(v6.contains("fd12::1".parse().unwrap()));
+        assert_eq!(String::from("192.168.1.5".parse::<Cidr>().unwrap()), "192.168.1.5/32");
+    }
+
+    #[test]
+    fn parses_proxy_requests() {
+        let connect = parse_request("CONNECT api.github.com:443 HTTP/1.1\r\nHost: api.github.com\r\n\r\n").unwrap();
+        assert_eq!((connect.host.as_str(), connect.port, connect.origin_head), ("api.github.com", 443, None));
+
+        let get = parse_request("GET http://pypi.org:8080/simple/ HTTP/1.1\r\nHost: pypi.org\r\n\r\n").unwrap();
+        assert_eq!((get.host.as_str(), get.port), ("pypi.org", 8080));
+        assert_eq!(get.origin_head.unwrap(), "GET /simple/ HTTP/1.1\r\nHost: pypi.org\r\n\r\n");
+    }
+
+    #[tokio::test]
+    async fn blocks_and_records_disallowed_hosts() {
+        let proxy = EgressProxy::new(EgressPolicy::from_allowlist(&["example.com".into()]));
+        let (addr, task) = proxy.start("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(b"CONNECT evil.test:443 HTTP/1.1\r\n\r\n").await.unwrap();
+        let mut reply = String::new();
+        client.read_to_string(&mut reply).await.unwrap();
+        assert!(reply.starts_with("HTTP/1.1 403"));
+
+        let blocked = proxy.take_blocked();
+        assert_eq!((blocked[0].host.as_str(), blocked[0].method.as_str()), ("evil.test", "CONNECT"));
+        assert!(proxy.take_blocked().is_empty());
+        task.abort();
+    }
+}
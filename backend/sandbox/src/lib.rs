@@ -1,15 +1,34 @@
 pub mod allowlist;
 pub mod analysis;
+#[cfg(windows)]
+pub mod appcontainer;
 pub mod approval_socket;
+pub mod bwrap;
 pub mod docker;
+pub mod driver;
+pub mod egress;
 pub mod exec_approval;
 pub mod fs_bridge;
+pub mod native;
+pub mod remote;
 pub mod sandbox_registry;
+pub mod seatbelt;
+pub mod secrets;
+pub mod usage;
+pub mod workspace;
 
 pub use allowlist::{AllowlistEntry, ApprovalLevel, ExecAllowlist};
-pub use analysis::{analyze_command, CommandAnalysis, CommandRisk};
+pub use analysis::{analyze_argv, analyze_command, CommandAnalysis, CommandRisk, Finding, FindingKind, Redirect, Segment};
 pub use approval_socket::{ApprovalRequest, ApprovalResponse, ApprovalSocketServer};
+pub use bwrap::{BwrapSandbox, BwrapSandboxConfig};
 pub use docker::{ContainerExecResult, DockerSandbox, DockerSandboxConfig};
+pub use driver::{truncate_result, truncate_tail, DriverFactory, OutputChunk, OutputStream, SandboxDriver, DEFAULT_MAX_OUTPUT_BYTES};
+pub use egress::{Cidr, EgressAttempt, EgressPolicy, EgressProxy};
 pub use exec_approval::{ApprovalVerdict, ExecApprovalAnalyzer};
 pub use fs_bridge::FsBridge;
+pub use native::{NativeDriver, NativeSandbox, NativeSandboxPolicy};
+pub use remote::{RemoteSandbox, RemoteTransport, SshTarget, SshTransport};
 pub use sandbox_registry::{SandboxEntry, SandboxRegistry};
+pub use secrets::{SecretBroker, SecretDef, SecretLease};
+pub use usage::{ExceededKind, ResourceExceeded, ResourceLimits, ResourceUsage};
+pub use workspace::{SnapshotInfo, WorkspaceManager};
@@ -0,0 +1,201 @@
+//! Native OS sandboxing for hosts without Docker.
+//!
+//! Two drivers, each giving at least filesystem and network restrictions:
+//! - `seatbelt` — macOS `sandbox-exec` with a generated profile.
+//! - `appcontainer` — Windows AppContainer; writable paths are granted to the
+//!   container SID and network access is the `internetClient` capability.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::debug;
+
+use crate::docker::ContainerExecResult;
+use crate::seatbelt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NativeDriver {
+    Seatbelt,
+    AppContainer,
+}
+
+impl NativeDriver {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "seatbelt" | "sandbox-exec" => Some(NativeDriver::Seatbelt),
+            "appcontainer" => Some(NativeDriver::AppContainer),
+            "native" => Self::for_host(),
+            _ => None,
+        }
+    }
+
+    /// The driver for the current OS, if it has one.
+    pub fn for_host() -> Option<Self> {
+        if cfg!(target_os = "macos") {
+            Some(NativeDriver::Seatbelt)
+        } else if cfg!(windows) {
+            Some(NativeDriver::AppContainer)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NativeDriver::Seatbelt => "seatbelt",
+            NativeDriver::AppContainer => "appcontainer",
+        }
+    }
+}
+
+/// What a sandboxed command may touch.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeSandboxPolicy {
+    /// Directories the command may write (the temp dir is always writable).
+    #[serde(default)]
+    pub writable_paths: Vec<PathBuf>,
+    /// Directories the command may read besides system roots; empty allows
+    /// reads everywhere. Only enforced by seatbelt: AppContainers can only
+    /// read what is granted to them or to all application packages.
+    #[serde(default)]
+    pub readable_paths: Vec<PathBuf>,
+    #[serde(default)]
+    pub allow_network: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Runs shell commands under a native OS sandbox.
+#[derive(Debug, Clone)]
+pub struct NativeSandbox {
+    driver: NativeDriver,
+    policy: NativeSandboxPolicy,
+    /// AppContainer profile name.
+    container_name: String,
+}
+
+impl NativeSandbox {
+    pub fn new(driver: NativeDriver, policy: NativeSandboxPolicy) -> Self {
+        Self { driver, policy, container_name: "clawforge.sandbox".to_string() }
+    }
+
+    /// The driver for the current OS; fails on hosts without one.
+    pub fn for_host(policy: NativeSandboxPolicy) -> Result<Self> {
+        let driver = NativeDriver::for_host().context("No native sandbox driver for this OS; use docker or bwrap")?;
+        Ok(Self::new(driver, policy))
+    }
+
+    pub fn with_container_name(mut self, name: impl Into<String>) -> Self {
+        self.container_name = name.into();
+        self
+    }
+
+    pub fn driver(&self) -> NativeDriver {
+        self.driver
+    }
+
+    pub fn policy(&self) -> &NativeSandboxPolicy {
+        &self.policy
+    }
+
+    pub fn is_available(&self) -> bool {
+        match self.driver {
+            NativeDriver::Seatbelt => seatbelt::is_available(),
+            NativeDriver::AppContainer => cfg!(windows),
+        }
+    }
+
+    /// Run `command` through the platform shell inside the sandbox.
+    pub async fn exec(&self, command: &str, cwd: Option<&Path>) -> Result<ContainerExecResult> {
+        if !self.is_available() {
+            bail!("Sandbox driver '{}' is not available on this host", self.driver.as_str());
+        }
+        debug!(driver = self.driver.as_str(), "Running sandboxed command");
+        let timeout = self.policy.timeout_secs.map(Duration::from_secs);
+        match self.driver {
+            NativeDriver::Seatbelt => {
+                let mut cmd = seatbelt::command(&self.policy, command);
+                if let Some(dir) = cwd {
+                    cmd.current_dir(dir);
+                }
+                cmd.kill_on_drop(true);
+                let started = std::time::Instant::now();
+                let output = cmd.output();
+                let output = match timeout {
+                    Some(limit) => match tokio::time::timeout(limit, output).await {
+                        Ok(output) => output,
+                        Err(_) => {
+                            return Ok(ContainerExecResult {
+                                exit_code: -1,
+                                stderr: format!("Command timed out after {}s", limit.as_secs()),
+                                timed_out: true,
+                                wall_time_ms: started.elapsed().as_millis() as u64,
+                                ..Default::default()
+                            })
+                        }
+                    },
+                    None => output.await,
+                }
+                .context("Failed to run sandbox-exec")?;
+                Ok(ContainerExecResult {
+                    exit_code: output.status.code().unwrap_or(-1) as i64,
+                    stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                    stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                    wall_time_ms: started.elapsed().as_millis() as u64,
+                    ..Default::default()
+                })
+            }
+            NativeDriver::AppContainer => self.exec_appcontainer(command, cwd, timeout).await,
+        }
+    }
+
+    #[cfg(windows)]
+    async fn exec_appcontainer(
+        &self,
+        command: &str,
+        cwd: Option<&Path>,
+        timeout: Option<Duration>,
+    ) -> Result<ContainerExecResult> {
+        let (policy, name, command) = (self.policy.clone(), self.container_name.clone(), command.to_string());
+        let cwd = cwd.map(Path::to_path_buf);
+        tokio::task::spawn_blocking(move || {
+            crate::appcontainer::run(&name, &policy, &command, cwd.as_deref(), timeout)
+        })
+        .await
+        .context("AppContainer task panicked")?
+    }
+
+    #[cfg(not(windows))]
+    async fn exec_appcontainer(
+        &self,
+        _command: &str,
+        _cwd: Option<&Path>,
+        _timeout: Option<Duration>,
+    ) -> Result<ContainerExecResult> {
+        bail!("The appcontainer driver only runs on Windows")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_driver_names() {
+        assert_eq!(NativeDriver::parse("Seatbelt"), Some(NativeDriver::Seatbelt));
+        assert_eq!(NativeDriver::parse("appcontainer"), Some(NativeDriver::AppContainer));
+        assert_eq!(NativeDriver::parse("docker"), None);
+        assert_eq!(NativeDriver::parse("native"), NativeDriver::for_host());
+    }
+
+    #[tokio::test]
+    async fn unavailable_driver_errors() {
+        let sandbox = NativeSandbox::new(NativeDriver::AppContainer, NativeSandboxPolicy::default());
+        if !cfg!(windows) {
+            assert!(sandbox.exec("echo hi", None).await.is_err());
+        }
+    }
+}
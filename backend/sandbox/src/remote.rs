@@ -0,0 +1,433 @@
+//! Remote sandbox driver: run a session's commands on another machine.
+//!
+//! `RemoteSandbox` is a `SandboxDriver` over a `RemoteTransport` — SSH here,
+//! node hosts in `clawforge-companion`. Commands go through the same exec
+//! allowlist and dangerous-pattern checks as local execution before they
+//! leave this host, and stdout/stderr are streamed back line by line.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+
+use crate::allowlist::{ApprovalLevel, ExecAllowlist};
+use crate::docker::ContainerExecResult;
+use crate::driver::{forward_lines, OutputChunk, OutputStream, SandboxDriver, DEFAULT_MAX_OUTPUT_BYTES};
+use crate::exec_approval::{ApprovalVerdict, ExecApprovalAnalyzer};
+use crate::usage::ResourceUsage;
+
+// ---------------------------------------------------------------------------
+// Transport
+// ---------------------------------------------------------------------------
+
+/// How commands reach a remote host.
+#[async_trait]
+pub trait RemoteTransport: Send + Sync {
+    /// Human-readable target, e.g. `ssh:ci@build-01` or `node:studio-mac`.
+    fn describe(&self) -> String;
+
+    /// Check the host is reachable and accepts commands.
+    async fn check(&self) -> Result<()>;
+
+    /// Run `command` remotely. Output lines are sent to `output` as they arrive
+    /// (transports that can't stream send them once the command finishes).
+    /// The result keeps the last `max_output_bytes` of each stream.
+    async fn exec(
+        &self,
+        command: &[&str],
+        env: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+        max_output_bytes: usize,
+        output: Option<&mpsc::UnboundedSender<OutputChunk>>,
+    ) -> Result<ContainerExecResult>;
+
+    async fn copy_in(&self, host_path: &str, remote_path: &str) -> Result<()>;
+
+    async fn copy_out(&self, remote_path: &str, host_path: &str) -> Result<()>;
+}
+
+// ---------------------------------------------------------------------------
+// SSH
+// ---------------------------------------------------------------------------
+
+/// An SSH host reachable with key auth (no password prompts).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SshTarget {
+    pub host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity_file: Option<PathBuf>,
+    /// Remote directory commands run in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir: Option<String>,
+}
+
+impl SshTarget {
+    pub fn new(host: impl Into<String>) -> Self {
+        Self { host: host.into(), ..Default::default() }
+    }
+
+    /// Parse `[user@]host[:port]`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (user, rest) = match spec.split_once('@') {
+            Some((user, rest)) => (Some(user.to_string()), rest),
+            None => (None, spec),
+        };
+        let (host, port) = match rest.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port.parse().with_context(|| format!("Invalid SSH port in '{}'", spec))?)),
+            None => (rest, None),
+        };
+        if host.is_empty() || host.starts_with('-') {
+            bail!("Invalid SSH host '{}'", spec);
+        }
+        Ok(Self { host: host.to_string(), user, port, ..Default::default() })
+    }
+
+    fn destination(&self) -> String {
+        match &self.user {
+            Some(user) => format!("{}@{}", user, self.host),
+            None => self.host.clone(),
+        }
+    }
+}
+
+/// Runs commands with the system `ssh` / `scp` clients.
+pub struct SshTransport {
+    target: SshTarget,
+}
+
+impl SshTransport {
+    pub fn new(target: SshTarget) -> Self {
+        Self { target }
+    }
+
+    fn ssh(&self) -> Command {
+        let mut cmd = Command::new("ssh");
+        cmd.args(["-T", "-o", "BatchMode=yes"]);
+        if let Some(port) = self.target.port {
+            cmd.arg("-p").arg(port.to_string());
+        }
+        if let Some(identity) = &self.target.identity_file {
+            cmd.arg("-i").arg(identity);
+        }
+        cmd.arg(self.target.destination()).arg("--");
+        cmd
+    }
+
+    async fn scp(&self, from: &str, to: &str) -> Result<()> {
+        let mut cmd = Command::new("scp");
+        cmd.args(["-q", "-o", "BatchMode=yes"]);
+        if let Some(port) = self.target.port {
+            cmd.arg("-P").arg(port.to_string());
+        }
+        if let Some(identity) = &self.target.identity_file {
+            cmd.arg("-i").arg(identity);
+        }
+        let output = cmd.arg(from).arg(to).output().await.context("Failed to run scp")?;
+        if !output.status.success() {
+            bail!("scp {} -> {} failed: {}", from, to, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(())
+    }
+
+    /// The remote shell line. Env vars are not put on it; they arrive on stdin
+    /// as `KEY=VALUE` lines and are exported before the command is exec'd.
+    fn remote_line(&self, command: &[&str], with_env: bool) -> String {
+        let mut line = String::new();
+        if let Some(dir) = &self.target.workdir {
+            line.push_str(&format!("cd {} && ", shell_quote(dir)));
+        }
+        if with_env {
+            line.push_str(r#"while IFS= read -r kv && [ -n "$kv" ]; do export "$kv"; done; "#);
+        }
+        line.push_str("exec ");
+        line.push_str(&command.iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>().join(" "));
+        line
+    }
+}
+
+#[async_trait]
+impl RemoteTransport for SshTransport {
+    fn describe(&self) -> String {
+        format!("ssh:{}", self.target.destination())
+    }
+
+    async fn check(&self) -> Result<()> {
+        let output = self.ssh().arg("true").output().await.context("Failed to run ssh")?;
+        if !output.status.success() {
+            bail!("Cannot reach {}: {}", self.describe(), String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(())
+    }
+
+    async fn exec(
+        &self,
+        command: &[&str],
+        env: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+        max_output_bytes: usize,
+        output: Option<&mpsc::UnboundedSender<OutputChunk>>,
+    ) -> Result<ContainerExecResult> {
+        if command.is_empty() {
+            bail!("Empty command");
+        }
+        for (key, value) in env {
+            let valid_key = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid_key || value.contains('\n') {
+                bail!("Env var '{}' cannot be sent over ssh", key);
+            }
+        }
+
+        let started = Instant::now();
+        let mut child = self
+            .ssh()
+            .arg(self.remote_line(command, !env.is_empty()))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to run ssh")?;
+
+        let mut stdin = child.stdin.take().context("ssh stdin unavailable")?;
+        if !env.is_empty() {
+            let mut lines: String = env.iter().map(|(k, v)| format!("{}={}\n", k, v)).collect();
+            lines.push('\n');
+            stdin.write_all(lines.as_bytes()).await?;
+        }
+        drop(stdin);
+
+        let stdout = child.stdout.take().context("ssh stdout unavailable")?;
+        let stderr = child.stderr.take().context("ssh stderr unavailable")?;
+        let run = async {
+            let (stdout, stderr) = tokio::join!(
+                forward_lines(stdout, OutputStream::Stdout, output, max_output_bytes),
+                forward_lines(stderr, OutputStream::Stderr, output, max_output_bytes),
+            );
+            let status = child.wait().await?;
+            Ok::<_, anyhow::Error>((status, stdout, stderr))
+        };
+        let (status, (stdout, stdout_cut), (stderr, stderr_cut)) = match timeout_secs {
+            Some(secs) => match tokio::time::timeout(Duration::from_secs(secs), run).await {
+                Ok(result) => result?,
+                Err(_) => {
+                    return Ok(ContainerExecResult {
+                        exit_code: -1,
+                        stderr: format!("Timed out after {}s on {}", secs, self.describe()),
+                        timed_out: true,
+                        wall_time_ms: started.elapsed().as_millis() as u64,
+                        ..Default::default()
+                    })
+                }
+            },
+            None => run.await?,
+        };
+
+        // ssh reserves 255 for its own failures (unreachable host, auth).
+        let exit_code = status.code().unwrap_or(-1);
+        if exit_code == 255 {
+            bail!("ssh to {} failed: {}", self.describe(), stderr.trim());
+        }
+        Ok(ContainerExecResult {
+            exit_code: exit_code as i64,
+            stdout,
+            stderr,
+            wall_time_ms: started.elapsed().as_millis() as u64,
+            truncated: stdout_cut || stderr_cut,
+            ..Default::default()
+        })
+    }
+
+    async fn copy_in(&self, host_path: &str, remote_path: &str) -> Result<()> {
+        self.scp(host_path, &format!("{}:{}", self.target.destination(), remote_path)).await
+    }
+
+    async fn copy_out(&self, remote_path: &str, host_path: &str) -> Result<()> {
+        self.scp(&format!("{}:{}", self.target.destination(), remote_path), host_path).await
+    }
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c)) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+// ---------------------------------------------------------------------------
+// Driver
+// ---------------------------------------------------------------------------
+
+/// A sandbox whose commands run on a remote host.
+pub struct RemoteSandbox {
+    transport: Arc<dyn RemoteTransport>,
+    allowlist: ExecAllowlist,
+    analyzer: ExecApprovalAnalyzer,
+    output: Option<mpsc::UnboundedSender<OutputChunk>>,
+}
+
+impl RemoteSandbox {
+    pub fn new(transport: Arc<dyn RemoteTransport>) -> Self {
+        Self {
+            transport,
+            allowlist: ExecAllowlist::with_safe_defaults(),
+            analyzer: ExecApprovalAnalyzer::default(),
+            output: None,
+        }
+    }
+
+    /// Commands denied here are refused before they reach the host.
+    pub fn with_allowlist(mut self, allowlist: ExecAllowlist) -> Self {
+        self.allowlist = allowlist;
+        self
+    }
+
+    /// Stream remote stdout/stderr lines to `tx` while commands run.
+    pub fn with_output(mut self, tx: mpsc::UnboundedSender<OutputChunk>) -> Self {
+        self.output = Some(tx);
+        self
+    }
+
+    /// Same gate as local exec: dangerous patterns and allowlist denials are
+    /// refused. `Ask` commands pass; approval happens before the driver.
+    fn check_command(&self, command: &[&str]) -> Result<()> {
+        let line = command.join(" ");
+        if let ApprovalVerdict::Blocked { reason } = self.analyzer.analyze(&line) {
+            bail!("Refusing to run on {}: {}", self.transport.describe(), reason);
+        }
+        if self.allowlist.evaluate(&line) == ApprovalLevel::Deny {
+            bail!("Refusing to run on {}: denied by the exec allowlist", self.transport.describe());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SandboxDriver for RemoteSandbox {
+    fn kind(&self) -> &str {
+        "remote"
+    }
+
+    async fn start(&mut self, session_id: &str) -> Result<String> {
+        self.transport.check().await?;
+        let name = self.transport.describe();
+        info!(session_id = %session_id, host = %name, "Remote sandbox ready");
+        Ok(name)
+    }
+
+    async fn exec(&self, command: &[&str], timeout_secs: Option<u64>) -> Result<ContainerExecResult> {
+        self.exec_with_env(command, &HashMap::new(), timeout_secs).await
+    }
+
+    async fn exec_with_env(
+        &self,
+        command: &[&str],
+        env: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+    ) -> Result<ContainerExecResult> {
+        self.check_command(command)?;
+        debug!(host = %self.transport.describe(), ?command, "Remote exec");
+        self.transport.exec(command, env, timeout_secs, DEFAULT_MAX_OUTPUT_BYTES, self.output.as_ref()).await
+    }
+
+    async fn exec_streaming(
+        &self,
+        command: &[&str],
+        env: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+        max_output_bytes: usize,
+        output: &mpsc::UnboundedSender<OutputChunk>,
+    ) -> Result<ContainerExecResult> {
+        self.check_command(command)?;
+        debug!(host = %self.transport.describe(), ?command, "Remote streaming exec");
+        self.transport.exec(command, env, timeout_secs, max_output_bytes, Some(output)).await
+    }
+
+    async fn copy_in(&self, host_path: &str, sandbox_path: &str) -> Result<()> {
+        self.transport.copy_in(host_path, sandbox_path).await
+    }
+
+    async fn copy_out(&self, sandbox_path: &str, host_path: &str) -> Result<()> {
+        self.transport.copy_out(sandbox_path, host_path).await
+    }
+
+    /// Nothing is provisioned remotely, so there is nothing to tear down.
+    async fn stop(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Usage isn't measured on remote hosts.
+    async fn resource_usage(&self) -> Result<ResourceUsage> {
+        Ok(ResourceUsage::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct LoopbackTransport;
+
+    #[async_trait]
+    impl RemoteTransport for LoopbackTransport {
+        fn describe(&self) -> String {
+            "loopback".into()
+        }
+        async fn check(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn exec(
+            &self,
+            command: &[&str],
+            _env: &HashMap<String, String>,
+            _timeout_secs: Option<u64>,
+            _max_output_bytes: usize,
+            output: Option<&mpsc::UnboundedSender<OutputChunk>>,
+        ) -> Result<ContainerExecResult> {
+            let line = command.join(" ");
+            if let Some(tx) = output {
+                tx.send(OutputChunk { stream: OutputStream::Stdout, line: line.clone() })?;
+            }
+            Ok(ContainerExecResult { stdout: line, ..Default::default() })
+        }
+        async fn copy_in(&self, _: &str, _: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn copy_out(&self, _: &str, _: &str) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn checks_commands_and_streams_output() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut sandbox = RemoteSandbox::new(Arc::new(LoopbackTransport)).with_output(tx);
+        assert_eq!(sandbox.start("s1").await.unwrap(), "loopback");
+
+        assert_eq!(sandbox.exec(&["ls", "-la"], None).await.unwrap().stdout, "ls -la");
+        assert_eq!(rx.recv().await.unwrap().line, "ls -la");
+        assert!(sandbox.exec(&["sudo", "reboot"], None).await.is_err());
+    }
+
+    #[test]
+    fn parses_targets_and_quotes_remote_line() {
+        let target = SshTarget::parse("ci@build-01:2222").unwrap();
+        assert_eq!((target.user.as_deref(), target.host.as_str(), target.port), (Some("ci"), "build-01", Some(2222)));
+        assert!(SshTarget::parse("-oProxyCommand=x").is_err());
+
+        let ssh = SshTransport::new(SshTarget { workdir: Some("/srv/app".into()), ..target });
+        assert_eq!(ssh.remote_line(&["echo", "it's here"], false), r"cd /srv/app && exec echo 'it'\''s here'");
+    }
+}
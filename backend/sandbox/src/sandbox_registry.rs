@@ -1,50 +1,393 @@
-//! Sandbox container registry: tracks active containers per session.
+//! Sandbox registry: tracks active sandboxes per session, whatever their driver.
 
-use crate::docker::DockerSandbox;
-use anyhow::Result;
+use crate::allowlist::ExecAllowlist;
+use crate::bwrap::{BwrapSandbox, BwrapSandboxConfig};
+use crate::docker::{ContainerExecResult, DockerSandbox, DockerSandboxConfig};
+use crate::driver::{DriverFactory, OutputChunk, SandboxDriver};
+use crate::egress::{EgressAttempt, EgressPolicy};
+use crate::remote::{RemoteSandbox, RemoteTransport};
+use crate::secrets::SecretBroker;
+use crate::usage::{ExceededKind, ResourceExceeded, ResourceUsage};
+use crate::workspace::WorkspaceManager;
+use anyhow::{Context, Result};
+use clawforge_core::Capabilities;
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock};
 use tracing::{info, warn};
 
 /// An active sandbox entry.
 pub struct SandboxEntry {
     pub session_id: String,
-    pub sandbox: DockerSandbox,
+    pub sandbox: Box<dyn SandboxDriver>,
+    /// Usage as of the last sample, with the peak memory seen so far.
+    pub usage: ResourceUsage,
+    /// When the watchdog kills the sandbox (`max_lifetime_secs`).
+    pub deadline: Option<Instant>,
 }
 
-/// Global registry of active sandbox containers (keyed by session_id).
+/// A registered sandbox. Execs hold its read lock, so the registry map is
+/// only locked long enough to look the slot up.
+type SandboxSlot = Arc<RwLock<SandboxEntry>>;
+
+/// Limit records kept per session until drained; older ones are dropped.
+const MAX_EXCEEDED_PER_SESSION: usize = 32;
+/// Undrained limit records older than this are pruned by the watchdog.
+const EXCEEDED_TTL_SECS: u64 = 3600;
+
+/// Global registry of active sandboxes (keyed by session_id).
 pub struct SandboxRegistry {
-    entries: Arc<RwLock<HashMap<String, SandboxEntry>>>,
+    entries: Arc<RwLock<HashMap<String, SandboxSlot>>>,
+    /// Driver factories by `sandbox.driver` name.
+    drivers: HashMap<String, DriverFactory>,
+    /// Persistent session workspaces mounted into new sandboxes.
+    workspaces: Option<Arc<WorkspaceManager>>,
+    /// Limits hit per session, kept after the sandbox is gone until drained.
+    exceeded: Arc<Mutex<HashMap<String, Vec<ResourceExceeded>>>>,
+    /// Driver chosen per session with `/exec host`, started on first exec.
+    exec_hosts: Mutex<HashMap<String, String>>,
 }
 
 impl SandboxRegistry {
+    /// A registry with the built-in `docker` and `bwrap` drivers.
     pub fn new() -> Self {
         Self {
             entries: Arc::new(RwLock::new(HashMap::new())),
+            drivers: HashMap::new(),
+            workspaces: None,
+            exceeded: Arc::new(Mutex::new(HashMap::new())),
+            exec_hosts: Mutex::new(HashMap::new()),
         }
+        .with_driver("docker", Arc::new(|config: &DockerSandboxConfig| {
+            Box::new(DockerSandbox::new(config.clone())) as Box<dyn SandboxDriver>
+        }))
+        .with_driver("bwrap", Arc::new(|config: &DockerSandboxConfig| {
+            Box::new(BwrapSandbox::new(BwrapSandboxConfig::from(config))) as Box<dyn SandboxDriver>
+        }))
     }
 
-    /// Register a started sandbox for a session.
-    pub async fn register(&self, session_id: String, sandbox: DockerSandbox) {
+    /// Add or replace a driver backend.
+    pub fn with_driver(mut self, name: impl Into<String>, factory: DriverFactory) -> Self {
+        self.drivers.insert(name.into(), factory);
+        self
+    }
+
+    /// Add a remote host as driver `name`. Its sandboxes run commands on the
+    /// host through `transport`, refusing anything `allowlist` denies.
+    pub fn with_remote_host(self, name: impl Into<String>, transport: Arc<dyn RemoteTransport>, allowlist: ExecAllowlist) -> Self {
+        self.with_driver(name, Arc::new(move |_: &DockerSandboxConfig| {
+            Box::new(RemoteSandbox::new(transport.clone()).with_allowlist(allowlist.clone())) as Box<dyn SandboxDriver>
+        }))
+    }
+
+    /// Mount each session's persistent workspace into its sandbox.
+    pub fn with_workspaces(mut self, workspaces: Arc<WorkspaceManager>) -> Self {
+        self.workspaces = Some(workspaces);
+        self
+    }
+
+    pub fn workspaces(&self) -> Option<&Arc<WorkspaceManager>> {
+        self.workspaces.as_ref()
+    }
+
+    pub fn driver_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.drivers.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    /// Route the session's commands to `driver` from its next exec on
+    /// (`None` goes back to running them locally).
+    pub fn set_exec_host(&self, session_id: &str, driver: Option<&str>) -> Result<()> {
+        let mut hosts = self.exec_hosts.lock().map_err(|_| anyhow::anyhow!("Exec host table poisoned"))?;
+        match driver {
+            Some(driver) => {
+                if !self.drivers.contains_key(driver) {
+                    anyhow::bail!("Unknown sandbox driver '{}'", driver);
+                }
+                hosts.insert(session_id.to_string(), driver.to_string());
+            }
+            None => {
+                hosts.remove(session_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Driver the session's commands are routed to, if any.
+    pub fn exec_host(&self, session_id: &str) -> Option<String> {
+        self.exec_hosts.lock().ok()?.get(session_id).cloned()
+    }
+
+    /// Start a sandbox for `session_id` with the named driver and register it.
+    pub async fn start_session(&self, session_id: &str, driver: &str, config: &DockerSandboxConfig) -> Result<String> {
+        let driver = if driver == "bubblewrap" { "bwrap" } else { driver };
+        let factory = self
+            .drivers
+            .get(driver)
+            .with_context(|| format!("Unknown sandbox driver '{}'", driver))?;
+        let mut sandbox = match &self.workspaces {
+            Some(workspaces) => factory(&workspaces.sandbox_config(session_id, config).await?),
+            None => factory(config),
+        };
+        let name = sandbox.start(session_id).await?;
+        self.register(session_id.to_string(), sandbox).await;
+        if let Some(secs) = config.max_lifetime_secs {
+            if let Ok(slot) = self.slot(session_id).await {
+                slot.write().await.deadline = Some(Instant::now() + Duration::from_secs(secs));
+            }
+        }
+        Ok(name)
+    }
+
+    /// Start a sandbox whose network access follows the agent's capabilities
+    /// (`can_make_http_requests` and `allowed_domains`). An egress policy
+    /// already in `config` takes precedence; its `firewall` flag is kept.
+    pub async fn start_agent_session(
+        &self,
+        session_id: &str,
+        driver: &str,
+        config: &DockerSandboxConfig,
+        capabilities: &Capabilities,
+    ) -> Result<String> {
+        let mut config = config.clone();
+        if config.egress.as_ref().is_none_or(EgressPolicy::denies_all) {
+            let firewall = config.egress.as_ref().is_some_and(|p| p.firewall);
+            config.egress = EgressPolicy::from_capabilities(capabilities).map(|p| p.with_firewall(firewall));
+        }
+        self.start_session(session_id, driver, &config).await
+    }
+
+    /// The session's slot, releasing the map lock before the caller uses it.
+    async fn slot(&self, session_id: &str) -> Result<SandboxSlot> {
         self.entries
-            .write()
+            .read()
+            .await
+            .get(session_id)
+            .cloned()
+            .with_context(|| format!("No sandbox for session {}", session_id))
+    }
+
+    /// Run a command in the session's sandbox.
+    pub async fn exec(&self, session_id: &str, command: &[&str], timeout_secs: Option<u64>) -> Result<ContainerExecResult> {
+        let slot = self.slot(session_id).await?;
+        let entry = slot.read().await;
+        let result = entry.sandbox.exec(command, timeout_secs).await?;
+        self.note_exec(session_id, command, &result);
+        Ok(result)
+    }
+
+    /// Run a command in the session's sandbox with extra env vars for this
+    /// exec only.
+    pub async fn exec_with_env(
+        &self,
+        session_id: &str,
+        command: &[&str],
+        env: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+    ) -> Result<ContainerExecResult> {
+        let slot = self.slot(session_id).await?;
+        let entry = slot.read().await;
+        let result = entry.sandbox.exec_with_env(command, env, timeout_secs).await?;
+        self.note_exec(session_id, command, &result);
+        Ok(result)
+    }
+
+    /// Run a command in the session's sandbox, forwarding stdout/stderr
+    /// lines to `output` as they are produced. The result keeps the last
+    /// `max_output_bytes` of each stream.
+    pub async fn exec_streaming(
+        &self,
+        session_id: &str,
+        command: &[&str],
+        timeout_secs: Option<u64>,
+        max_output_bytes: usize,
+        output: &mpsc::UnboundedSender<OutputChunk>,
+    ) -> Result<ContainerExecResult> {
+        let slot = self.slot(session_id).await?;
+        let entry = slot.read().await;
+        let result = entry
+            .sandbox
+            .exec_streaming(command, &HashMap::new(), timeout_secs, max_output_bytes, output)
+            .await?;
+        self.note_exec(session_id, command, &result);
+        Ok(result)
+    }
+
+    /// Stream a command in the session's sandbox, first starting one on the
+    /// session's exec host if it was routed there but has none yet.
+    pub async fn exec_routed(
+        &self,
+        session_id: &str,
+        command: &[&str],
+        timeout_secs: Option<u64>,
+        config: &DockerSandboxConfig,
+        max_output_bytes: usize,
+        output: &mpsc::UnboundedSender<OutputChunk>,
+    ) -> Result<ContainerExecResult> {
+        if !self.has_sandbox(session_id).await {
+            let driver = self
+                .exec_host(session_id)
+                .with_context(|| format!("No sandbox or exec host for session {}", session_id))?;
+            self.start_session(session_id, &driver, config).await?;
+        }
+        self.exec_streaming(session_id, command, timeout_secs, max_output_bytes, output).await
+    }
+
+    /// Run a command with secrets `agent` leased in this session injected
+    /// as env vars. The leases are consumed and secret values are masked in
+    /// the output and errors.
+    pub async fn exec_with_secrets(
+        &self,
+        session_id: &str,
+        agent: Option<&str>,
+        command: &[&str],
+        secrets: &SecretBroker,
+        lease_ids: &[String],
+        timeout_secs: Option<u64>,
+    ) -> Result<ContainerExecResult> {
+        let slot = self.slot(session_id).await?;
+        let entry = slot.read().await;
+        let env = secrets.redeem(lease_ids, session_id, agent)?;
+        let mut result = entry
+            .sandbox
+            .exec_with_env(command, &env, timeout_secs)
             .await
-            .insert(session_id.clone(), SandboxEntry { session_id, sandbox });
-        info!(count = self.entries.read().await.len(), "Sandbox registered");
+            .map_err(|e| anyhow::anyhow!(secrets.mask(&format!("{e:#}"))))?;
+        result.stdout = secrets.mask(&result.stdout);
+        result.stderr = secrets.mask(&result.stderr);
+        self.note_exec(session_id, command, &result);
+        Ok(result)
+    }
+
+    /// Record an exec's OOM kill or timeout against the session.
+    fn note_exec(&self, session_id: &str, command: &[&str], result: &ContainerExecResult) {
+        let program = command.first().copied().unwrap_or_default();
+        if result.oom_killed {
+            self.record_exceeded(session_id, ResourceExceeded::new(
+                ExceededKind::OutOfMemory,
+                format!("`{}` was killed for running out of memory", program),
+            ));
+        }
+        if result.timed_out {
+            self.record_exceeded(session_id, ResourceExceeded::new(
+                ExceededKind::Timeout,
+                format!("`{}` timed out after {} ms", program, result.wall_time_ms),
+            ));
+        }
+    }
+
+    fn record_exceeded(&self, session_id: &str, exceeded: ResourceExceeded) {
+        warn!(session_id = %session_id, kind = ?exceeded.kind, detail = %exceeded.detail, "Sandbox resource exceeded");
+        if let Ok(mut map) = self.exceeded.lock() {
+            let records = map.entry(session_id.to_string()).or_default();
+            records.push(exceeded);
+            if records.len() > MAX_EXCEEDED_PER_SESSION {
+                records.drain(..records.len() - MAX_EXCEEDED_PER_SESSION);
+            }
+        }
+    }
+
+    /// Drop limit records nobody drained within `EXCEEDED_TTL_SECS`.
+    fn prune_exceeded(&self) {
+        let cutoff = ResourceExceeded::new(ExceededKind::Timeout, "").at.saturating_sub(EXCEEDED_TTL_SECS);
+        if let Ok(mut map) = self.exceeded.lock() {
+            map.retain(|_, records| {
+                records.retain(|r| r.at >= cutoff);
+                !records.is_empty()
+            });
+        }
+    }
+
+    /// Drain the limits the session's sandbox hit, for audit events.
+    pub fn take_resource_exceeded(&self, session_id: &str) -> Vec<ResourceExceeded> {
+        self.exceeded
+            .lock()
+            .ok()
+            .and_then(|mut map| map.remove(session_id))
+            .unwrap_or_default()
+    }
+
+    /// Stop and remove every sandbox past its lifetime. Returns their session ids.
+    ///
+    /// Deadlines are read without blocking on running execs; an expired
+    /// sandbox leaves the map at once, so no new exec reaches it, and is
+    /// stopped when its current exec (bounded by its own timeout) returns.
+    pub async fn enforce_lifetimes(&self) -> Vec<String> {
+        let now = Instant::now();
+        let slots: Vec<(String, SandboxSlot)> =
+            self.entries.read().await.iter().map(|(id, slot)| (id.clone(), Arc::clone(slot))).collect();
+        let mut expired_ids = Vec::new();
+        for (id, slot) in slots {
+            let deadline = match slot.try_read() {
+                Ok(entry) => entry.deadline,
+                // Locked for writing means it is starting or stopping.
+                Err(_) => continue,
+            };
+            if deadline.is_some_and(|d| now >= d) {
+                expired_ids.push(id);
+            }
+        }
+        let expired: Vec<SandboxSlot> = {
+            let mut entries = self.entries.write().await;
+            expired_ids.iter().filter_map(|id| entries.remove(id)).collect()
+        };
+        let mut killed = Vec::with_capacity(expired.len());
+        for slot in expired {
+            let mut entry = slot.write().await;
+            if let Err(e) = entry.sandbox.stop().await {
+                warn!(session_id = %entry.session_id, "Failed to stop expired sandbox: {e:#}");
+            }
+            self.record_exceeded(&entry.session_id, ResourceExceeded::new(
+                ExceededKind::Lifetime,
+                "sandbox exceeded its maximum lifetime and was killed",
+            ));
+            killed.push(entry.session_id.clone());
+        }
+        self.prune_exceeded();
+        killed
+    }
+
+    /// Check lifetimes every `interval` until the registry is dropped.
+    pub fn spawn_watchdog(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let registry = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            loop {
+                tick.tick().await;
+                let Some(registry) = registry.upgrade() else { break };
+                registry.enforce_lifetimes().await;
+            }
+        })
+    }
+
+    /// Register a started sandbox for a session.
+    pub async fn register(&self, session_id: String, sandbox: Box<dyn SandboxDriver>) {
+        let entry = SandboxEntry { session_id: session_id.clone(), sandbox, usage: ResourceUsage::default(), deadline: None };
+        let count = {
+            let mut entries = self.entries.write().await;
+            entries.insert(session_id, Arc::new(RwLock::new(entry)));
+            entries.len()
+        };
+        info!(count, "Sandbox registered");
     }
 
-    /// Remove a sandbox entry.
-    pub async fn remove(&self, session_id: &str) -> Option<SandboxEntry> {
-        self.entries.write().await.remove(session_id)
+    /// Stop and remove a session's sandbox. Returns whether it had one.
+    pub async fn remove(&self, session_id: &str) -> Result<bool> {
+        let Some(slot) = self.entries.write().await.remove(session_id) else {
+            return Ok(false);
+        };
+        slot.write().await.sandbox.stop().await?;
+        Ok(true)
     }
 
     /// Stop and remove all sandboxes (called at shutdown).
     pub async fn stop_all(&self) -> Result<()> {
-        let mut entries = self.entries.write().await;
-        for (id, mut entry) in entries.drain() {
+        let drained: Vec<(String, SandboxSlot)> = self.entries.write().await.drain().collect();
+        for (id, slot) in drained {
             warn!(session_id = %id, "Force-stopping sandbox at shutdown");
-            entry.sandbox.stop().await.ok();
+            slot.write().await.sandbox.stop().await.ok();
         }
         Ok(())
     }
@@ -54,6 +397,41 @@ impl SandboxRegistry {
         self.entries.read().await.len()
     }
 
+    /// Sample a session's sandbox and fold it into the entry's totals.
+    /// Returns `None` when the session has no sandbox.
+    pub async fn sample(&self, session_id: &str) -> Option<Result<ResourceUsage>> {
+        let slot = self.slot(session_id).await.ok()?;
+        let sample = slot.read().await.sandbox.resource_usage().await;
+        let mut entry = slot.write().await;
+        Some(sample.map(|sample| {
+            entry.usage.update(sample);
+            entry.usage
+        }))
+    }
+
+    /// Drain the session's blocked egress attempts, for audit events.
+    pub async fn take_blocked_egress(&self, session_id: &str) -> Vec<EgressAttempt> {
+        match self.slot(session_id).await {
+            Ok(slot) => slot.read().await.sandbox.take_blocked_egress(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Last sampled usage of every active sandbox, for dashboards.
+    pub async fn usage(&self) -> Vec<(String, ResourceUsage)> {
+        let slots: Vec<(String, SandboxSlot)> =
+            self.entries.read().await.iter().map(|(id, slot)| (id.clone(), Arc::clone(slot))).collect();
+        let mut list = Vec::with_capacity(slots.len());
+        for (id, slot) in slots {
+            // Skip sandboxes busy starting or stopping rather than wait on them.
+            if let Ok(entry) = slot.try_read() {
+                list.push((id, entry.usage));
+            }
+        }
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        list
+    }
+
     /// Check whether a session has an active sandbox.
     pub async fn has_sandbox(&self, session_id: &str) -> bool {
         self.entries.read().await.contains_key(session_id)
@@ -65,3 +443,129 @@ impl Default for SandboxRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::SecretDef;
+    use async_trait::async_trait;
+
+    /// Echoes commands back and reports fixed usage.
+    struct EchoDriver {
+        started: bool,
+    }
+
+    #[async_trait]
+    impl SandboxDriver for EchoDriver {
+        fn kind(&self) -> &str {
+            "echo"
+        }
+        async fn start(&mut self, session_id: &str) -> Result<String> {
+            self.started = true;
+            Ok(format!("echo-{session_id}"))
+        }
+        async fn exec(&self, command: &[&str], _timeout_secs: Option<u64>) -> Result<ContainerExecResult> {
+            match command {
+                ["hog", ..] => Ok(ContainerExecResult { exit_code: 137, oom_killed: true, ..Default::default() }),
+                _ => Ok(ContainerExecResult { exit_code: 0, stdout: command.join(" "), ..Default::default() }),
+            }
+        }
+        async fn exec_with_env(
+            &self,
+            command: &[&str],
+            env: &HashMap<String, String>,
+            _timeout_secs: Option<u64>,
+        ) -> Result<ContainerExecResult> {
+            let mut result = self.exec(command, None).await?;
+            result.stdout.extend(env.values().map(|v| format!(" {v}")));
+            Ok(result)
+        }
+        async fn copy_in(&self, _host_path: &str, _sandbox_path: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn copy_out(&self, _sandbox_path: &str, _host_path: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn stop(&mut self) -> Result<()> {
+            self.started = false;
+            Ok(())
+        }
+        async fn resource_usage(&self) -> Result<ResourceUsage> {
+            Ok(ResourceUsage { cpu_time_ms: 7, memory_bytes: 64, ..Default::default() })
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_sessions_on_registered_drivers() {
+        let registry = SandboxRegistry::new().with_driver(
+            "echo",
+            Arc::new(|_: &DockerSandboxConfig| Box::new(EchoDriver { started: false }) as Box<dyn SandboxDriver>),
+        );
+        assert_eq!(registry.driver_names(), vec!["bwrap", "docker", "echo"]);
+
+        let config = DockerSandboxConfig::default();
+        assert_eq!(registry.start_session("s1", "echo", &config).await.unwrap(), "echo-s1");
+        assert!(registry.start_session("s2", "firecracker", &config).await.is_err());
+
+        assert_eq!(registry.exec("s1", &["ls", "-la"], None).await.unwrap().stdout, "ls -la");
+        let usage = registry.sample("s1").await.unwrap().unwrap();
+        assert_eq!((usage.cpu_time_ms, usage.memory_peak_bytes), (7, 64));
+        assert!(registry.sample("s2").await.is_none());
+
+        let secrets = SecretBroker::new().with_secret(SecretDef::new("deploy", "tok_live_abc123"));
+        let lease = secrets.lease("deploy", "s1", None).unwrap();
+        let out = registry.exec_with_secrets("s1", None, &["deploy"], &secrets, std::slice::from_ref(&lease.id), None).await.unwrap();
+        assert_eq!(out.stdout, "deploy [secret:deploy]");
+        assert!(registry.exec_with_secrets("s1", None, &["deploy"], &secrets, &[lease.id], None).await.is_err());
+
+        registry.stop_all().await.unwrap();
+        assert_eq!(registry.active_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn records_oom_kills_and_enforces_lifetimes() {
+        let registry = SandboxRegistry::new().with_driver(
+            "echo",
+            Arc::new(|_: &DockerSandboxConfig| Box::new(EchoDriver { started: false }) as Box<dyn SandboxDriver>),
+        );
+        let config = DockerSandboxConfig { max_lifetime_secs: Some(0), ..Default::default() };
+        registry.start_session("s1", "echo", &config).await.unwrap();
+
+        assert!(registry.exec("s1", &["hog"], None).await.unwrap().oom_killed);
+        let exceeded = registry.take_resource_exceeded("s1");
+        assert_eq!(exceeded.len(), 1);
+        assert_eq!(exceeded[0].kind, ExceededKind::OutOfMemory);
+        assert!(registry.take_resource_exceeded("s1").is_empty());
+
+        assert_eq!(registry.enforce_lifetimes().await, vec!["s1"]);
+        assert!(!registry.has_sandbox("s1").await);
+        assert_eq!(registry.take_resource_exceeded("s1")[0].kind, ExceededKind::Lifetime);
+    }
+
+    #[tokio::test]
+    async fn undrained_limit_records_are_capped_and_pruned() {
+        let registry = SandboxRegistry::new();
+        for _ in 0..MAX_EXCEEDED_PER_SESSION + 5 {
+            registry.record_exceeded("busy", ResourceExceeded::new(ExceededKind::Timeout, "slow"));
+        }
+        let mut stale = ResourceExceeded::new(ExceededKind::OutOfMemory, "hog");
+        stale.at -= EXCEEDED_TTL_SECS + 1;
+        registry.exceeded.lock().unwrap().insert("gone".into(), vec![stale]);
+
+        registry.enforce_lifetimes().await;
+        assert!(registry.take_resource_exceeded("gone").is_empty());
+        assert_eq!(registry.take_resource_exceeded("busy").len(), MAX_EXCEEDED_PER_SESSION);
+    }
+
+    #[tokio::test]
+    async fn remove_stops_the_sandbox() {
+        let registry = SandboxRegistry::new().with_driver(
+            "echo",
+            Arc::new(|_: &DockerSandboxConfig| Box::new(EchoDriver { started: false }) as Box<dyn SandboxDriver>),
+        );
+        registry.start_session("s1", "echo", &DockerSandboxConfig::default()).await.unwrap();
+        assert!(registry.remove("s1").await.unwrap());
+        assert!(!registry.remove("s1").await.unwrap());
+        assert!(registry.exec("s1", &["ls"], None).await.is_err());
+    }
+}
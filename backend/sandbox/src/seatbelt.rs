@@ -0,0 +1,113 @@
+//! macOS seatbelt driver: runs commands under `sandbox-exec` with a generated
+//! SBPL profile.
+//!
+//! The profile denies everything by default, then allows process execution,
+//! reads (everywhere, or only system roots plus the policy's readable paths),
+//! writes to the policy's writable paths and the temp dir, and network access
+//! only when the policy permits it.
+
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::native::NativeSandboxPolicy;
+
+/// Location of `sandbox-exec` on every supported macOS release.
+pub const SANDBOX_EXEC: &str = "/usr/bin/sandbox-exec";
+
+/// Roots a shell needs to read even when reads are restricted.
+const SYSTEM_READ_ROOTS: &[&str] = &[
+    "/bin",
+    "/sbin",
+    "/usr",
+    "/System",
+    "/Library",
+    "/opt/homebrew",
+    "/private/etc",
+    "/private/var/db",
+    "/dev",
+];
+
+pub fn is_available() -> bool {
+    cfg!(target_os = "macos") && Path::new(SANDBOX_EXEC).exists()
+}
+
+/// Seatbelt matches on resolved paths (`/tmp` is `/private/tmp`), so
+/// canonicalize where the path exists.
+fn resolve(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn quote(path: &Path) -> String {
+    let s = path.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", s)
+}
+
+fn subpaths(paths: &[PathBuf]) -> String {
+    paths.iter().map(|p| format!(" (subpath {})", quote(&resolve(p)))).collect()
+}
+
+/// Render the SBPL profile for `policy`.
+pub fn render_profile(policy: &NativeSandboxPolicy) -> String {
+    let mut writable = policy.writable_paths.clone();
+    writable.push(std::env::temp_dir());
+
+    let mut profile = String::from(
+        "(version 1)\n\
+         (deny default)\n\
+         (allow process-exec process-fork)\n\
+         (allow signal (target same-sandbox))\n\
+         (allow sysctl-read mach-lookup ipc-posix-shm)\n\
+         (allow file-read-metadata)\n",
+    );
+    if policy.readable_paths.is_empty() {
+        profile.push_str("(allow file-read*)\n");
+    } else {
+        let mut readable: Vec<PathBuf> = SYSTEM_READ_ROOTS.iter().map(PathBuf::from).collect();
+        readable.extend(policy.readable_paths.iter().cloned());
+        readable.extend(writable.iter().cloned());
+        profile.push_str(&format!("(allow file-read*{})\n", subpaths(&readable)));
+    }
+    profile.push_str(&format!(
+        "(allow file-write* (literal \"/dev/null\") (literal \"/dev/tty\"){})\n",
+        subpaths(&writable)
+    ));
+    if policy.allow_network {
+        profile.push_str("(allow network*)\n");
+    }
+    profile
+}
+
+/// `sandbox-exec -p <profile> /bin/sh -c <command>`.
+pub fn command(policy: &NativeSandboxPolicy, shell_command: &str) -> Command {
+    let mut cmd = Command::new(SANDBOX_EXEC);
+    cmd.arg("-p").arg(render_profile(policy)).arg("/bin/sh").arg("-c").arg(shell_command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_restricts_writes_and_network() {
+        let policy = NativeSandboxPolicy {
+            writable_paths: vec![PathBuf::from("/nonexistent/work \"space\"")],
+            ..Default::default()
+        };
+        let profile = render_profile(&policy);
+        assert!(profile.starts_with("(version 1)\n(deny default)"));
+        assert!(profile.contains("(allow file-read*)\n"));
+        assert!(profile.contains("(subpath \"/nonexistent/work \\\"space\\\"\")"));
+        assert!(!profile.contains("network"));
+
+        let open = NativeSandboxPolicy {
+            readable_paths: vec![PathBuf::from("/nonexistent/docs")],
+            allow_network: true,
+            ..Default::default()
+        };
+        let profile = render_profile(&open);
+        assert!(!profile.contains("(allow file-read*)"));
+        assert!(profile.contains("(subpath \"/nonexistent/docs\")"));
+        assert!(profile.contains("(allow network*)"));
+    }
+}
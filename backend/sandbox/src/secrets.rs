@@ -0,0 +1,205 @@
+//! Secret leasing for sandboxed execs.
+//!
+//! Agents never see credential values. A run asks for a lease on a named
+//! secret and gets back an opaque lease id, which it passes to
+//! `SandboxRegistry::exec_with_secrets`. The value is injected as an env var
+//! for that single exec and masked in everything the exec prints. Leases are
+//! bound to the session and agent that took them, consumed on use and expire
+//! after their TTL.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Values shorter than this are not masked; they would mangle ordinary output.
+const MIN_MASK_LEN: usize = 4;
+
+/// A credential agents may lease.
+#[derive(Clone)]
+pub struct SecretDef {
+    pub name: String,
+    value: String,
+    /// Env var the value is injected as (default: the secret name).
+    pub env_var: String,
+    /// Agents allowed to lease it; empty allows every agent.
+    pub agents: Vec<String>,
+    pub ttl: Duration,
+}
+
+impl SecretDef {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        Self { env_var: name.clone(), name, value: value.into(), agents: Vec::new(), ttl: Duration::from_secs(60) }
+    }
+
+    pub fn with_env_var(mut self, env_var: impl Into<String>) -> Self {
+        self.env_var = env_var.into();
+        self
+    }
+
+    pub fn with_agents(mut self, agents: Vec<String>) -> Self {
+        self.agents = agents;
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
+
+impl std::fmt::Debug for SecretDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretDef")
+            .field("name", &self.name)
+            .field("env_var", &self.env_var)
+            .field("agents", &self.agents)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+/// What the agent gets back: a handle, never the value.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretLease {
+    pub id: String,
+    pub secret: String,
+    pub env_var: String,
+    pub expires_in_secs: u64,
+}
+
+struct ActiveLease {
+    secret: String,
+    session: String,
+    agent: Option<String>,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+pub struct SecretBroker {
+    secrets: HashMap<String, SecretDef>,
+    leases: Mutex<HashMap<String, ActiveLease>>,
+}
+
+impl SecretBroker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_secret(mut self, secret: SecretDef) -> Self {
+        self.secrets.insert(secret.name.clone(), secret);
+        self
+    }
+
+    /// Names of the secrets agents can lease, for tool descriptions.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.secrets.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    /// Issue a single-use lease on `secret` for `agent` in `session`.
+    pub fn lease(&self, secret: &str, session: &str, agent: Option<&str>) -> Result<SecretLease> {
+        let def = self.secrets.get(secret).with_context(|| format!("Unknown secret '{}'", secret))?;
+        if !def.agents.is_empty() && !agent.is_some_and(|a| def.agents.iter().any(|x| x == a)) {
+            warn!(secret = %secret, agent = ?agent, "Secret lease refused");
+            bail!("Agent {} may not lease secret '{}'", agent.unwrap_or("(unknown)"), secret);
+        }
+        let id = Uuid::new_v4().simple().to_string();
+        let mut leases = self.leases.lock().unwrap();
+        let now = Instant::now();
+        leases.retain(|_, l| l.expires_at > now);
+        leases.insert(
+            id.clone(),
+            ActiveLease {
+                secret: secret.to_string(),
+                session: session.to_string(),
+                agent: agent.map(str::to_string),
+                expires_at: now + def.ttl,
+            },
+        );
+        info!(secret = %secret, agent = ?agent, "Secret leased");
+        Ok(SecretLease {
+            id,
+            secret: secret.to_string(),
+            env_var: def.env_var.clone(),
+            expires_in_secs: def.ttl.as_secs(),
+        })
+    }
+
+    /// Consume leases taken by `agent` in `session` and return the env vars
+    /// to inject. Fails without consuming anything if any lease is unknown,
+    /// used, expired or another caller's.
+    pub fn redeem(&self, lease_ids: &[String], session: &str, agent: Option<&str>) -> Result<HashMap<String, String>> {
+        let mut leases = self.leases.lock().unwrap();
+        let now = Instant::now();
+        for id in lease_ids {
+            match leases.get(id) {
+                Some(lease) if lease.session != session || lease.agent.as_deref() != agent => {
+                    warn!(secret = %lease.secret, session = %session, agent = ?agent, "Secret lease redeemed by another caller");
+                    bail!("Secret lease {} was not issued to this session and agent", id)
+                }
+                Some(lease) if lease.expires_at > now => {}
+                Some(_) => bail!("Secret lease {} has expired", id),
+                None => bail!("Unknown or already used secret lease {}", id),
+            }
+        }
+        let mut env = HashMap::new();
+        for id in lease_ids {
+            let lease = leases.remove(id).expect("checked above");
+            let def = self.secrets.get(&lease.secret).context("Secret removed while leased")?;
+            env.insert(def.env_var.clone(), def.value.clone());
+        }
+        Ok(env)
+    }
+
+    /// Replace every known secret value in `text` with `[secret:<name>]`.
+    pub fn mask(&self, text: &str) -> String {
+        let mut defs: Vec<&SecretDef> = self.secrets.values().filter(|d| d.value.len() >= MIN_MASK_LEN).collect();
+        // Longest first so a secret containing another is masked whole.
+        defs.sort_by_key(|d| std::cmp::Reverse(d.value.len()));
+        defs.iter().fold(text.to_string(), |out, d| out.replace(&d.value, &format!("[secret:{}]", d.name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn broker() -> SecretBroker {
+        SecretBroker::new()
+            .with_secret(SecretDef::new("deploy", "tok_live_abc123").with_env_var("DEPLOY_TOKEN").with_agents(vec!["ops".into()]))
+            .with_secret(SecretDef::new("npm", "npm_xyz789").with_ttl(Duration::ZERO))
+    }
+
+    #[test]
+    fn leases_are_scoped_and_single_use() {
+        let broker = broker();
+        assert!(broker.lease("deploy", "s1", Some("writer")).is_err());
+        let lease = broker.lease("deploy", "s1", Some("ops")).unwrap();
+        assert_eq!(lease.env_var, "DEPLOY_TOKEN");
+        assert!(!serde_json::to_string(&lease).unwrap().contains("tok_live"));
+
+        let ids = std::slice::from_ref(&lease.id);
+        assert!(broker.redeem(ids, "s2", Some("ops")).is_err());
+        assert!(broker.redeem(ids, "s1", Some("writer")).is_err());
+        let env = broker.redeem(ids, "s1", Some("ops")).unwrap();
+        assert_eq!(env["DEPLOY_TOKEN"], "tok_live_abc123");
+        assert!(broker.redeem(ids, "s1", Some("ops")).is_err());
+
+        let expired = broker.lease("npm", "s1", None).unwrap();
+        assert!(broker.redeem(&[expired.id], "s1", None).is_err());
+    }
+
+    #[test]
+    fn masks_secret_values() {
+        let out = broker().mask("pushed with tok_live_abc123 and npm_xyz789");
+        assert_eq!(out, "pushed with [secret:deploy] and [secret:npm]");
+        assert!(!format!("{:?}", SecretDef::new("k", "hunter22")).contains("hunter22"));
+    }
+}
@@ -0,0 +1,308 @@
+//! Resource usage sampling for sandbox containers.
+//!
+//! Reads the container's cgroup v2 files (`cpu.stat`, `memory.*`, `io.stat`)
+//! and its network namespace's `/proc/<pid>/net/dev` when the gateway runs on
+//! the Docker host, and falls back to `docker stats` otherwise (no CPU time).
+//! Samples taken around an exec give its CPU time and whether it hit the OOM
+//! killer.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::debug;
+
+/// Cumulative usage of one sandbox container.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceUsage {
+    /// User + system CPU time; 0 when sampled through `docker stats`.
+    pub cpu_time_ms: u64,
+    pub memory_bytes: u64,
+    pub memory_peak_bytes: u64,
+    pub disk_write_bytes: u64,
+    pub net_rx_bytes: u64,
+    pub net_tx_bytes: u64,
+    /// Cumulative `oom_kill` count from `memory.events`; 0 through `docker stats`.
+    #[serde(default)]
+    pub oom_kills: u64,
+}
+
+impl ResourceUsage {
+    pub fn net_bytes(&self) -> u64 {
+        self.net_rx_bytes + self.net_tx_bytes
+    }
+
+    /// Replace with a newer sample, keeping the highest memory seen so far.
+    pub fn update(&mut self, sample: ResourceUsage) {
+        let peak = self.memory_peak_bytes.max(sample.memory_peak_bytes).max(sample.memory_bytes);
+        *self = sample;
+        self.memory_peak_bytes = peak;
+    }
+
+    /// Descriptions of every limit this usage is over.
+    pub fn exceeded(&self, limits: &ResourceLimits) -> Vec<String> {
+        let checks = [
+            ("CPU time (ms)", self.cpu_time_ms, limits.max_cpu_time_ms),
+            ("peak memory (bytes)", self.memory_peak_bytes, limits.max_memory_bytes),
+            ("disk writes (bytes)", self.disk_write_bytes, limits.max_disk_write_bytes),
+            ("network (bytes)", self.net_bytes(), limits.max_net_bytes),
+        ];
+        checks
+            .into_iter()
+            .filter_map(|(what, used, max)| {
+                let max = max?;
+                (used > max).then(|| format!("{} {} over limit {}", what, used, max))
+            })
+            .collect()
+    }
+}
+
+/// Thresholds for flagging runaway sandboxes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cpu_time_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_disk_write_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_net_bytes: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_cpu_time_ms.is_none()
+            && self.max_memory_bytes.is_none()
+            && self.max_disk_write_bytes.is_none()
+            && self.max_net_bytes.is_none()
+    }
+}
+
+/// What a sandbox ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExceededKind {
+    /// The kernel OOM killer fired during an exec.
+    OutOfMemory,
+    /// An exec ran past its timeout.
+    Timeout,
+    /// The sandbox outlived `max_lifetime_secs` and was killed.
+    Lifetime,
+}
+
+/// A limit a sandbox hit, reported to the run as `SandboxResourceExceeded`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceExceeded {
+    pub kind: ExceededKind,
+    pub detail: String,
+    /// Unix seconds.
+    pub at: u64,
+}
+
+impl ResourceExceeded {
+    pub fn new(kind: ExceededKind, detail: impl Into<String>) -> Self {
+        let at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self { kind, detail: detail.into(), at }
+    }
+}
+
+/// Per-exec CPU time, sandbox peak memory and OOM kill from samples taken
+/// before and after the exec. CPU time is `None` without cgroup access.
+pub fn exec_delta(before: Option<ResourceUsage>, after: Option<ResourceUsage>) -> (Option<u64>, Option<u64>, bool) {
+    let cpu_time_ms = match (before, after) {
+        (Some(b), Some(a)) if a.cpu_time_ms > 0 => Some(a.cpu_time_ms.saturating_sub(b.cpu_time_ms)),
+        _ => None,
+    };
+    let memory_peak_bytes = after.map(|a| a.memory_peak_bytes.max(a.memory_bytes));
+    let oom_killed = matches!((before, after), (Some(b), Some(a)) if a.oom_kills > b.oom_kills);
+    (cpu_time_ms, memory_peak_bytes, oom_killed)
+}
+
+// ---------------------------------------------------------------------------
+// Sampling
+// ---------------------------------------------------------------------------
+
+/// Sample a running container's usage.
+pub async fn sample_container(container_id: &str) -> Result<ResourceUsage> {
+    match sample_cgroup(container_id).await {
+        Ok(usage) => Ok(usage),
+        Err(e) => {
+            debug!(container = %container_id, "cgroup sampling unavailable ({e:#}), using docker stats");
+            sample_docker_stats(container_id).await
+        }
+    }
+}
+
+async fn docker(args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new("docker")
+        .args(args)
+        .output()
+        .await
+        .context("Failed to run docker command")?;
+    if !output.status.success() {
+        bail!("docker {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+async fn sample_cgroup(container_id: &str) -> Result<ResourceUsage> {
+    let pid = docker(&["inspect", "-f", "{{.State.Pid}}", container_id]).await?;
+    let cgroups = tokio::fs::read_to_string(format!("/proc/{pid}/cgroup"))
+        .await
+        .context("Container process is not visible from this host")?;
+    let rel = cgroups
+        .lines()
+        .find_map(|l| l.strip_prefix("0::"))
+        .context("Container is not in a cgroup v2 hierarchy")?;
+    let dir = PathBuf::from("/sys/fs/cgroup").join(rel.trim_start_matches('/'));
+    let read = |name: &str| {
+        let path = dir.join(name);
+        async move { tokio::fs::read_to_string(path).await.ok() }
+    };
+
+    let cpu = read("cpu.stat").await.context("cpu.stat not readable")?;
+    let memory_bytes = read("memory.current").await.and_then(|s| s.trim().parse().ok()).unwrap_or(0);
+    let (net_rx_bytes, net_tx_bytes) = tokio::fs::read_to_string(format!("/proc/{pid}/net/dev"))
+        .await
+        .map(|s| parse_net_dev(&s))
+        .unwrap_or_default();
+    Ok(ResourceUsage {
+        cpu_time_ms: parse_cpu_stat(&cpu).unwrap_or(0),
+        memory_bytes,
+        // memory.peak needs Linux 5.19+; otherwise peaks come from repeated samples.
+        memory_peak_bytes: read("memory.peak").await.and_then(|s| s.trim().parse().ok()).unwrap_or(memory_bytes),
+        disk_write_bytes: read("io.stat").await.map(|s| parse_io_stat(&s)).unwrap_or(0),
+        net_rx_bytes,
+        net_tx_bytes,
+        oom_kills: read("memory.events").await.and_then(|s| parse_oom_kills(&s)).unwrap_or(0),
+    })
+}
+
+async fn sample_docker_stats(container_id: &str) -> Result<ResourceUsage> {
+    let out = docker(&["stats", "--no-stream", "--format", "{{json .}}", container_id]).await?;
+    let stats: serde_json::Value = serde_json::from_str(&out).context("Unexpected docker stats output")?;
+    let pair = |key: &str| -> (u64, u64) {
+        let s = stats[key].as_str().unwrap_or("");
+        let mut parts = s.split('/').map(|p| parse_size(p.trim()).unwrap_or(0));
+        (parts.next().unwrap_or(0), parts.next().unwrap_or(0))
+    };
+    let (memory_bytes, _) = pair("MemUsage");
+    let (net_rx_bytes, net_tx_bytes) = pair("NetIO");
+    let (_, disk_write_bytes) = pair("BlockIO");
+    Ok(ResourceUsage {
+        cpu_time_ms: 0,
+        memory_bytes,
+        memory_peak_bytes: memory_bytes,
+        disk_write_bytes,
+        net_rx_bytes,
+        net_tx_bytes,
+        oom_kills: 0,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Parsers
+// ---------------------------------------------------------------------------
+
+/// `usage_usec` from cgroup v2 `cpu.stat`, in milliseconds.
+fn parse_cpu_stat(text: &str) -> Option<u64> {
+    text.lines()
+        .find_map(|l| l.strip_prefix("usage_usec "))
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(|usec| usec / 1000)
+}
+
+/// `oom_kill` from cgroup v2 `memory.events`.
+fn parse_oom_kills(text: &str) -> Option<u64> {
+    text.lines()
+        .find_map(|l| l.strip_prefix("oom_kill "))
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// Total `wbytes` across devices in cgroup v2 `io.stat`.
+fn parse_io_stat(text: &str) -> u64 {
+    text.split_whitespace()
+        .filter_map(|field| field.strip_prefix("wbytes="))
+        .filter_map(|v| v.parse::<u64>().ok())
+        .sum()
+}
+
+/// Received and transmitted bytes across non-loopback interfaces.
+fn parse_net_dev(text: &str) -> (u64, u64) {
+    text.lines()
+        .skip(2)
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(iface, _)| iface.trim() != "lo")
+        .fold((0, 0), |(rx, tx), (_, counters)| {
+            let cols: Vec<u64> = counters.split_whitespace().filter_map(|c| c.parse().ok()).collect();
+            (rx + cols.first().copied().unwrap_or(0), tx + cols.get(8).copied().unwrap_or(0))
+        })
+}
+
+/// Sizes as printed by `docker stats`: `648B`, `1.2kB`, `12.5MiB`.
+fn parse_size(s: &str) -> Option<u64> {
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+    let (num, unit) = s.split_at(split);
+    let num: f64 = num.parse().ok()?;
+    let factor = match unit.trim() {
+        "B" => 1.0,
+        "kB" | "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((num * factor).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cgroup_and_proc_files() {
+        assert_eq!(parse_cpu_stat("usage_usec 2500000\nuser_usec 2000000\n"), Some(2500));
+        assert_eq!(parse_io_stat("8:0 rbytes=10 wbytes=4096 rios=1 wios=2\n8:16 rbytes=0 wbytes=100\n"), 4196);
+        let net_dev = "Inter-|   Receive |  Transmit\n face |bytes packets ...\n    lo: 500 5 0 0 0 0 0 0 500 5 0 0 0 0 0 0\n  eth0: 1200 10 0 0 0 0 0 0 648 6 0 0 0 0 0 0\n";
+        assert_eq!(parse_net_dev(net_dev), (1200, 648));
+        assert_eq!(parse_oom_kills("low 0\nhigh 0\nmax 3\noom 1\noom_kill 1\n"), Some(1));
+        assert_eq!(parse_size("1.5kB"), Some(1500));
+        assert_eq!(parse_size("2MiB"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_size("0B"), Some(0));
+        assert_eq!(parse_size("--"), None);
+    }
+
+    #[test]
+    fn tracks_peak_and_flags_limits() {
+        let mut usage = ResourceUsage::default();
+        usage.update(ResourceUsage { memory_bytes: 900, ..Default::default() });
+        usage.update(ResourceUsage { memory_bytes: 100, cpu_time_ms: 5_000, ..Default::default() });
+        assert_eq!(usage.memory_peak_bytes, 900);
+
+        let limits = ResourceLimits { max_memory_bytes: Some(512), max_cpu_time_ms: Some(10_000), ..Default::default() };
+        let over = usage.exceeded(&limits);
+        assert_eq!(over.len(), 1);
+        assert!(over[0].starts_with("peak memory"));
+    }
+
+    #[test]
+    fn exec_delta_measures_cpu_and_oom() {
+        let before = ResourceUsage { cpu_time_ms: 1_000, memory_bytes: 10, ..Default::default() };
+        let after = ResourceUsage { cpu_time_ms: 1_250, memory_bytes: 20, memory_peak_bytes: 500, oom_kills: 1, ..Default::default() };
+        assert_eq!(exec_delta(Some(before), Some(after)), (Some(250), Some(500), true));
+        // docker stats fallback: no CPU time, no OOM counter.
+        let stats = ResourceUsage { memory_bytes: 64, memory_peak_bytes: 64, ..Default::default() };
+        assert_eq!(exec_delta(Some(stats), Some(stats)), (None, Some(64), false));
+        assert_eq!(exec_delta(None, None), (None, None, false));
+    }
+}
@@ -0,0 +1,275 @@
+//! Persistent per-session sandbox workspaces with snapshot/restore.
+//!
+//! Each session gets a host directory that is bind-mounted as the sandbox
+//! workspace, so files survive sandbox restarts. `snapshot` captures the
+//! directory to a gzipped tarball; `restore` swaps its contents back in place
+//! (the directory itself is kept so a running container's mount stays valid).
+//!
+//! Layout under the root (default `~/.clawforge/workspaces`):
+//! `<session>/workspace/` and `<session>/snapshots/<id>.tar.gz` + `<id>.json`.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::process::Command;
+use tracing::info;
+
+use crate::docker::{sanitize_id, DockerSandboxConfig};
+
+/// Where workspaces are mounted inside the sandbox.
+pub const WORKSPACE_MOUNT: &str = "/workspace";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub session_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Unix seconds.
+    pub created_at: u64,
+    pub size_bytes: u64,
+}
+
+pub struct WorkspaceManager {
+    root: PathBuf,
+}
+
+impl WorkspaceManager {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// `~/.clawforge/workspaces`, or `.clawforge/workspaces` without a home dir.
+    pub fn default_root() -> PathBuf {
+        dirs::home_dir().unwrap_or_default().join(".clawforge").join("workspaces")
+    }
+
+    fn session_dir(&self, session_id: &str) -> PathBuf {
+        self.root.join(sanitize_id(session_id))
+    }
+
+    pub fn workspace_dir(&self, session_id: &str) -> PathBuf {
+        self.session_dir(session_id).join("workspace")
+    }
+
+    fn snapshot_dir(&self, session_id: &str) -> PathBuf {
+        self.session_dir(session_id).join("snapshots")
+    }
+
+    /// Create the session's workspace if needed and return its path.
+    pub async fn ensure(&self, session_id: &str) -> Result<PathBuf> {
+        let dir = self.workspace_dir(session_id);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Failed to create workspace {}", dir.display()))?;
+        Ok(dir)
+    }
+
+    /// `base` with the session workspace mounted at `/workspace`.
+    pub async fn sandbox_config(&self, session_id: &str, base: &DockerSandboxConfig) -> Result<DockerSandboxConfig> {
+        let dir = self.ensure(session_id).await?;
+        Ok(DockerSandboxConfig {
+            workspace_mount: Some((dir.display().to_string(), WORKSPACE_MOUNT.to_string())),
+            ..base.clone()
+        })
+    }
+
+    /// Capture the session workspace to a tarball.
+    pub async fn snapshot(&self, session_id: &str, label: Option<&str>) -> Result<SnapshotInfo> {
+        let workspace = self.ensure(session_id).await?;
+        let snapshots = self.snapshot_dir(session_id);
+        tokio::fs::create_dir_all(&snapshots).await?;
+
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        let id = format!("{}-{}", created_at, &uuid::Uuid::new_v4().simple().to_string()[..6]);
+        let archive = snapshots.join(format!("{id}.tar.gz"));
+        run_tar(Command::new("tar").arg("-czf").arg(&archive).arg("-C").arg(&workspace).arg(".")).await?;
+
+        let info = SnapshotInfo {
+            id,
+            session_id: session_id.to_string(),
+            label: label.map(str::to_string),
+            created_at,
+            size_bytes: tokio::fs::metadata(&archive).await?.len(),
+        };
+        tokio::fs::write(snapshots.join(format!("{}.json", info.id)), serde_json::to_vec_pretty(&info)?).await?;
+        info!(session_id = %session_id, snapshot = %info.id, bytes = info.size_bytes, "Workspace snapshot taken");
+        Ok(info)
+    }
+
+    /// Snapshots of a session, newest first.
+    pub async fn snapshots(&self, session_id: &str) -> Result<Vec<SnapshotInfo>> {
+        let mut list = Vec::new();
+        let mut entries = match tokio::fs::read_dir(self.snapshot_dir(session_id)).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(list),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.path().extension().is_some_and(|ext| ext == "json") {
+                let raw = tokio::fs::read(entry.path()).await?;
+                list.push(serde_json::from_slice::<SnapshotInfo>(&raw)?);
+            }
+        }
+        list.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.id.cmp(&a.id)));
+        Ok(list)
+    }
+
+    fn archive_path(&self, session_id: &str, snapshot_id: &str) -> Result<PathBuf> {
+        if snapshot_id.is_empty() || !snapshot_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            bail!("Invalid snapshot id '{}'", snapshot_id);
+        }
+        let archive = self.snapshot_dir(session_id).join(format!("{snapshot_id}.tar.gz"));
+        if !archive.exists() {
+            bail!("No snapshot '{}' for session {}", snapshot_id, session_id);
+        }
+        Ok(archive)
+    }
+
+    /// Replace the workspace contents with a snapshot. The archive is
+    /// checked and unpacked beside the workspace first, and the current
+    /// contents are set aside until the swap succeeds, so a failed restore
+    /// leaves the workspace as it was.
+    pub async fn restore(&self, session_id: &str, snapshot_id: &str) -> Result<()> {
+        let archive = self.archive_path(session_id, snapshot_id)?;
+        let workspace = self.ensure(session_id).await?;
+        check_archive(&archive).await?;
+
+        let staging = self.session_dir(session_id).join(".restore");
+        let _ = tokio::fs::remove_dir_all(&staging).await;
+        tokio::fs::create_dir_all(&staging).await?;
+        if let Err(e) = run_tar(Command::new("tar").arg("-xzf").arg(&archive).arg("-C").arg(&staging)).await {
+            let _ = tokio::fs::remove_dir_all(&staging).await;
+            return Err(e);
+        }
+
+        let previous = self.session_dir(session_id).join(".previous");
+        let _ = tokio::fs::remove_dir_all(&previous).await;
+        tokio::fs::create_dir_all(&previous).await?;
+        if let Err(e) = move_entries(&workspace, &previous).await {
+            // Put back whatever was already moved aside.
+            move_entries(&previous, &workspace).await?;
+            let _ = tokio::fs::remove_dir_all(&staging).await;
+            return Err(e);
+        }
+        if let Err(e) = move_entries(&staging, &workspace).await {
+            clear_dir(&workspace).await?;
+            move_entries(&previous, &workspace).await?;
+            let _ = tokio::fs::remove_dir_all(&staging).await;
+            return Err(e.context("Restore failed; workspace left unchanged"));
+        }
+        tokio::fs::remove_dir_all(&staging).await?;
+        tokio::fs::remove_dir_all(&previous).await?;
+        info!(session_id = %session_id, snapshot = %snapshot_id, "Workspace restored");
+        Ok(())
+    }
+
+    pub async fn delete_snapshot(&self, session_id: &str, snapshot_id: &str) -> Result<()> {
+        let archive = self.archive_path(session_id, snapshot_id)?;
+        tokio::fs::remove_file(&archive).await?;
+        let _ = tokio::fs::remove_file(archive.with_extension("").with_extension("json")).await;
+        Ok(())
+    }
+}
+
+async fn run_tar(cmd: &mut Command) -> Result<()> {
+    let output = cmd.output().await.context("Failed to run tar")?;
+    if !output.status.success() {
+        bail!("tar failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Refuse archives with absolute paths or `..` components.
+async fn check_archive(archive: &Path) -> Result<()> {
+    let output = Command::new("tar").arg("-tzf").arg(archive).output().await.context("Failed to run tar")?;
+    if !output.status.success() {
+        bail!("Snapshot is not a readable archive: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    for entry in String::from_utf8_lossy(&output.stdout).lines() {
+        let path = Path::new(entry);
+        if path.is_absolute() || path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+            bail!("Snapshot contains unsafe path '{}'", entry);
+        }
+    }
+    Ok(())
+}
+
+/// Move every entry of `from` into `to`.
+async fn move_entries(from: &Path, to: &Path) -> Result<()> {
+    let mut entries = tokio::fs::read_dir(from).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        tokio::fs::rename(entry.path(), to.join(entry.file_name()))
+            .await
+            .with_context(|| format!("Failed to move {}", entry.path().display()))?;
+    }
+    Ok(())
+}
+
+async fn clear_dir(dir: &Path) -> Result<()> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_type().await?.is_dir() {
+            tokio::fs::remove_dir_all(entry.path()).await?;
+        } else {
+            tokio::fs::remove_file(entry.path()).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn snapshot_and_restore_round_trip() {
+        let root = std::env::temp_dir().join(format!("clawforge-ws-{}", uuid::Uuid::new_v4().simple()));
+        let manager = WorkspaceManager::new(&root);
+        let config = manager.sandbox_config("chat:42", &DockerSandboxConfig::default()).await.unwrap();
+        let (host, target) = config.workspace_mount.unwrap();
+        assert_eq!(target, WORKSPACE_MOUNT);
+
+        let ws = PathBuf::from(host);
+        tokio::fs::create_dir_all(ws.join("src")).await.unwrap();
+        tokio::fs::write(ws.join("src/main.rs"), "fn main() {}").await.unwrap();
+        let snap = manager.snapshot("chat:42", Some("before refactor")).await.unwrap();
+
+        tokio::fs::write(ws.join("src/main.rs"), "broken").await.unwrap();
+        tokio::fs::write(ws.join("junk.txt"), "x").await.unwrap();
+        manager.restore("chat:42", &snap.id).await.unwrap();
+        assert_eq!(tokio::fs::read_to_string(ws.join("src/main.rs")).await.unwrap(), "fn main() {}");
+        assert!(!ws.join("junk.txt").exists());
+
+        assert_eq!(manager.snapshots("chat:42").await.unwrap(), vec![snap.clone()]);
+        assert!(manager.restore("chat:42", "../../etc").await.is_err());
+        manager.delete_snapshot("chat:42", &snap.id).await.unwrap();
+        assert!(manager.snapshots("chat:42").await.unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn similar_session_ids_get_distinct_dirs() {
+        let manager = WorkspaceManager::new("/ws");
+        assert_ne!(manager.workspace_dir("chat:42"), manager.workspace_dir("chat-42"));
+        assert_ne!(manager.workspace_dir("a_3A"), manager.workspace_dir("a:"));
+        assert_eq!(manager.workspace_dir("chat:42"), PathBuf::from("/ws/chat_3A42/workspace"));
+    }
+
+    #[tokio::test]
+    async fn bad_snapshot_leaves_workspace_untouched() {
+        let root = std::env::temp_dir().join(format!("clawforge-ws-{}", uuid::Uuid::new_v4().simple()));
+        let manager = WorkspaceManager::new(&root);
+        let ws = manager.ensure("s").await.unwrap();
+        tokio::fs::write(ws.join("keep.txt"), "mine").await.unwrap();
+        let snap = manager.snapshot("s", None).await.unwrap();
+        let archive = manager.archive_path("s", &snap.id).unwrap();
+        tokio::fs::write(&archive, b"not a tarball").await.unwrap();
+
+        assert!(manager.restore("s", &snap.id).await.is_err());
+        assert_eq!(tokio::fs::read_to_string(ws.join("keep.txt")).await.unwrap(), "mine");
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
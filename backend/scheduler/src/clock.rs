@@ -0,0 +1,89 @@
+//! Scheduler clock
+//!
+//! The scheduler reads the time through a `SchedulerClock` so tests can move
+//! it forward: `advance` adds an offset to both the monotonic and wall-clock
+//! readings and wakes the scheduler loop, which then fires whatever interval
+//! and cron triggers came due. A clock that is never advanced is the system
+//! clock. `running` tells tests when the scheduler has registered its
+//! triggers, so an advance can't land before they exist.
+
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{watch, Notify};
+use tokio::time::Instant;
+
+#[derive(Clone)]
+pub struct SchedulerClock {
+    offset: Arc<Mutex<Duration>>,
+    advanced: Arc<Notify>,
+    running: Arc<watch::Sender<bool>>,
+}
+
+impl Default for SchedulerClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SchedulerClock {
+    pub fn new() -> Self {
+        Self {
+            offset: Arc::new(Mutex::new(Duration::ZERO)),
+            advanced: Arc::new(Notify::new()),
+            running: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    pub fn now(&self) -> Instant {
+        Instant::now() + self.offset()
+    }
+
+    pub fn utc_now(&self) -> DateTime<Utc> {
+        Utc::now() + chrono::Duration::from_std(self.offset()).unwrap_or_default()
+    }
+
+    /// Total time the clock has been moved forward.
+    pub fn offset(&self) -> Duration {
+        *self.offset.lock().unwrap()
+    }
+
+    /// Move the clock forward and wake the scheduler.
+    pub fn advance(&self, by: Duration) {
+        *self.offset.lock().unwrap() += by;
+        self.advanced.notify_one();
+    }
+
+    /// Resolves after the next `advance`.
+    pub async fn advanced(&self) {
+        self.advanced.notified().await
+    }
+
+    pub(crate) fn mark_running(&self) {
+        self.running.send_replace(true);
+    }
+
+    /// Resolves once a scheduler using this clock has registered its triggers.
+    pub async fn running(&self) {
+        let _ = self.running.subscribe().wait_for(|running| *running).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn advancing_moves_both_clocks_and_wakes_waiters() {
+        let clock = SchedulerClock::new();
+        let (before, before_utc) = (clock.now(), clock.utc_now());
+        let waiter = tokio::spawn({
+            let clock = clock.clone();
+            async move { clock.advanced().await }
+        });
+        clock.advance(Duration::from_secs(3600));
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert!(clock.now() - before >= Duration::from_secs(3600));
+        assert!(clock.utc_now() - before_utc >= chrono::Duration::hours(1));
+    }
+}
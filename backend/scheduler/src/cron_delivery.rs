@@ -4,10 +4,31 @@
 /// A cron job can specify a `delivery_target` which is either:
 ///   - A session ID (send to that exact session)
 ///   - A channel string (send to that channel's active session)
+///   - `push` (send as a push notification through a `PushSink`)
 ///   - None (output is discarded / logged only)
-use anyhow::Result;
+///
+/// A job may also carry a `delivery_template`; the message is then rendered
+/// from it (see `clawforge_core::template`) with the variables listed in
+/// `DELIVERY_VARIABLES` instead of being the raw run output.
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clawforge_core::Template;
+use serde_json::{json, Value};
 use tracing::{info, warn};
 
+use crate::cron_store::CronJob;
+use crate::run_log::{JobRunStats, RunLogEntry};
+use crate::timezone::Tz;
+
+/// Variables available to delivery templates:
+///   - `job`: id, agent_id, channel, schedule, timezone, run_count
+///   - `run`: fired_at, status, ok, output, error, duration_ms
+///   - `output`: shorthand for `run.output`
+///   - `usage`: the job's run stats (total_runs, success_rate, avg_duration_ms, ...)
+///   - `now`, `date`: render time on the job's clock (RFC 3339, `YYYY-MM-DD`)
+pub const DELIVERY_VARIABLES: &[&str] = &["job", "run", "output", "usage", "now", "date"];
+
 /// Delivery target resolution result.
 #[derive(Debug, Clone)]
 pub enum DeliveryTarget {
@@ -15,6 +36,8 @@ pub enum DeliveryTarget {
     Session(String),
     /// Deliver to a channel (find or create the active session).
     Channel(String),
+    /// Send as a push notification.
+    Push,
     /// No delivery (log only).
     Discard,
 }
@@ -27,6 +50,7 @@ pub fn parse_delivery_target(raw: &Option<String>) -> DeliveryTarget {
         Some(s) if s.starts_with("session:") => {
             DeliveryTarget::Session(s.trim_start_matches("session:").to_string())
         }
+        Some(s) if s == "push" => DeliveryTarget::Push,
         Some(s) if s.starts_with("channel:") => {
             DeliveryTarget::Channel(s.trim_start_matches("channel:").to_string())
         }
@@ -35,10 +59,16 @@ pub fn parse_delivery_target(raw: &Option<String>) -> DeliveryTarget {
     }
 }
 
+/// Outbound push notification channel (ntfy, Pushover, APNs/FCM relay).
+#[async_trait]
+pub trait PushSink: Send + Sync {
+    async fn push(&self, title: &str, body: &str) -> Result<()>;
+}
+
 /// Deliver the cron result to the resolved target.
 /// In a real implementation this would call into the channel bus or session manager.
-/// Here we log the delivery for traceability.
-pub async fn deliver_result(target: &DeliveryTarget, content: &str, job_id: &str) -> Result<()> {
+/// Here we log the delivery for traceability. Push targets go to `push`.
+pub async fn deliver_result(target: &DeliveryTarget, content: &str, job_id: &str, push: Option<&dyn PushSink>) -> Result<()> {
     match target {
         DeliveryTarget::Session(id) => {
             info!("[CronDelivery] job={} → session={}: {}", job_id, id, content);
@@ -48,9 +78,142 @@ pub async fn deliver_result(target: &DeliveryTarget, content: &str, job_id: &str
             info!("[CronDelivery] job={} → channel={}: {}", job_id, ch, content);
             // TODO: call channel_adapter.send_message(ch, content)
         }
+        DeliveryTarget::Push => match push {
+            Some(sink) => {
+                info!("[CronDelivery] job={} → push", job_id);
+                sink.push(&format!("Cron: {}", job_id), content).await?;
+            }
+            None => bail!("Job {} delivers to push, but no push provider is configured", job_id),
+        },
         DeliveryTarget::Discard => {
             warn!("[CronDelivery] job={}: no delivery target, discarding output", job_id);
         }
     }
     Ok(())
 }
+
+/// Parse a delivery template, rejecting syntax errors and variables that
+/// deliveries don't provide.
+pub fn validate_delivery_template(source: &str) -> Result<Template> {
+    let template = Template::parse(source)?;
+    if let Some(unknown) = template.variables().into_iter().find(|v| !DELIVERY_VARIABLES.contains(&v.as_str())) {
+        bail!("Unknown variable '{}' (available: {})", unknown, DELIVERY_VARIABLES.join(", "));
+    }
+    Ok(template)
+}
+
+/// Template variables for one run of `job`. Times are on the job's clock.
+/// Names are snake_case throughout, including `usage`, which the API
+/// serializes in camelCase.
+pub fn delivery_context(job: &CronJob, run: &RunLogEntry, stats: Option<&JobRunStats>, now: DateTime<Utc>, tz: &Tz) -> Value {
+    let fired_at = DateTime::from_timestamp(run.fired_at, 0).unwrap_or(now);
+    let local_now = tz.to_local(now);
+    json!({
+        "job": {
+            "id": job.id,
+            "agent_id": job.agent_id,
+            "channel": job.channel,
+            "schedule": job.schedule,
+            "timezone": tz.name(),
+            "run_count": job.run_count,
+        },
+        "run": {
+            "fired_at": tz.to_local(fired_at).to_rfc3339(),
+            "status": run.status,
+            "ok": run.status == "ok",
+            "output": run.output_summary,
+            "error": run.error,
+            "duration_ms": run.duration_ms,
+        },
+        "output": run.output_summary,
+        "usage": stats.map(|stats| json!({
+            "total_runs": stats.total_runs,
+            "ok_runs": stats.ok_runs,
+            "error_runs": stats.error_runs,
+            "skipped_runs": stats.skipped_runs,
+            "success_rate": stats.success_rate,
+            "avg_duration_ms": stats.avg_duration_ms,
+            "last_run_at": stats.last_run_at,
+            "last_failure_at": stats.last_failure_at,
+            "last_failure_reason": stats.last_failure_reason,
+        })),
+        "now": local_now.to_rfc3339(),
+        "date": local_now.format("%Y-%m-%d").to_string(),
+    })
+}
+
+/// The message to deliver for a run: the job's template rendered against
+/// `delivery_context`, or the raw output (or error) without one.
+pub fn render_delivery(job: &CronJob, run: &RunLogEntry, stats: Option<&JobRunStats>, now: DateTime<Utc>, tz: &Tz) -> Result<String> {
+    match &job.delivery_template {
+        Some(source) => {
+            let context = delivery_context(job, run, stats, now, tz);
+            Ok(validate_delivery_template(source)?.render(&context)?)
+        }
+        None => Ok(run.output_summary.clone().or_else(|| run.error.clone()).unwrap_or_default()),
+    }
+}
+
+/// A plausible context for previewing templates without a real run.
+pub fn sample_delivery_context(now: DateTime<Utc>, tz: &Tz) -> Value {
+    let job = CronJob {
+        id: "daily-briefing".to_string(),
+        agent_id: "assistant".to_string(),
+        channel: "telegram".to_string(),
+        schedule: "0 7 * * *".to_string(),
+        delivery_target: Some("channel:telegram".to_string()),
+        prompt: "Summarize my day".to_string(),
+        enabled: true,
+        stagger_secs: 0,
+        max_runs: None,
+        run_count: 41,
+        created_at: now.timestamp() - 41 * 86_400,
+        timezone: Some(tz.name().to_string()),
+        delivery_template: None,
+    };
+    let run = RunLogEntry {
+        id: "sample".to_string(),
+        job_id: job.id.clone(),
+        fired_at: now.timestamp(),
+        status: "ok".to_string(),
+        output_summary: Some("Sunny, high of 24°C. Three meetings, first at 09:30.".to_string()),
+        error: None,
+        duration_ms: Some(4_210),
+        timezone: job.timezone.clone(),
+        fired_at_local: None,
+    };
+    let stats = JobRunStats {
+        job_id: job.id.clone(),
+        total_runs: 41,
+        ok_runs: 39,
+        error_runs: 1,
+        skipped_runs: 1,
+        success_rate: Some(0.975),
+        avg_duration_ms: Some(3_870.0),
+        last_run_at: Some(now.timestamp() - 86_400),
+        last_failure_at: Some(now.timestamp() - 9 * 86_400),
+        last_failure_reason: Some("provider timeout".to_string()),
+    };
+    delivery_context(&job, &run, Some(&stats), now, tz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_templates_against_run_context() {
+        let now = DateTime::parse_from_rfc3339("2025-06-10T05:00:00Z").unwrap().with_timezone(&Utc);
+        let context = sample_delivery_context(now, &Tz::utc());
+        let template = validate_delivery_template(
+            "{{ date }} {{ job.id }}: {% if run.ok %}{{ output | truncate(12) }}{% else %}failed{% endif %} \
+             ({{ usage.success_rate | round(2) }})",
+        )
+        .unwrap();
+        assert_eq!(template.render(&context).unwrap(), "2025-06-10 daily-briefing: Sunny, high… (0.98)");
+
+        let err = validate_delivery_template("{{ weather }}").unwrap_err();
+        assert!(err.to_string().contains("Unknown variable 'weather'"));
+        assert!(validate_delivery_template("{% if run.ok %}").is_err());
+    }
+}
@@ -2,10 +2,16 @@
 ///
 /// Supports 5-field (min hour dom mon dow) and 6-field (sec …) cron syntax.
 /// Returns the normalized expression string or an error.
-use anyhow::{bail, Result};
+///
+/// `parse_schedule` + `next_fire` evaluate expressions against a time zone's
+/// wall clock, so "0 9 * * *" means 09:00 local time all year round.
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use cron::Schedule;
 
-// For now we validate structure without a full cron parser dependency.
-// A production version would use the `cron` crate for full expansion.
+use crate::timezone::{LocalResolution, Tz};
 
 const VALID_RANGES: &[(u32, u32)] = &[
     (0, 59),  // minute
@@ -60,3 +66,110 @@ fn validate_field(field: &str, min: u32, max: u32) -> Result<()> {
     }
     Ok(())
 }
+
+/// Parse a 5-field cron expression, or the `cron` crate's native 6/7-field
+/// (seconds first, optional year) and `@daily`-style forms.
+pub fn parse_schedule(expr: &str) -> Result<Schedule> {
+    let parts: Vec<&str> = expr.split_whitespace().collect();
+    let native = if parts.len() == 5 {
+        validate_cron(expr)?;
+        format!(
+            "0 {} {} {} {} {}",
+            parts[0], parts[1], parts[2], parts[3], shift_day_of_week(parts[4])
+        )
+    } else {
+        parts.join(" ")
+    };
+    Schedule::from_str(&native).map_err(|e| anyhow!("Invalid cron expression '{}': {}", expr, e))
+}
+
+/// Standard cron numbers Sunday 0 (or 7) to Saturday 6; the `cron` crate
+/// numbers Sunday 1 to Saturday 7.
+fn shift_day_of_week(field: &str) -> String {
+    field
+        .split(',')
+        .map(|part| {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, Some(step)),
+                None => (part, None),
+            };
+            let shifted = match range.split_once('-') {
+                _ if range == "*" || range == "?" => range.to_string(),
+                Some((lo, hi)) => match (lo.parse::<u32>(), hi.parse::<u32>()) {
+                    // "5-7" runs Friday through Sunday, which wraps in the crate's numbering.
+                    (Ok(lo), Ok(7)) if lo > 0 && step.is_none() => format!("{}-7,1", lo + 1),
+                    (Ok(lo), Ok(hi)) => format!("{}-{}", lo + 1, hi.min(6) + 1),
+                    _ => range.to_string(),
+                },
+                None => match range.parse::<u32>() {
+                    Ok(day) => (day % 7 + 1).to_string(),
+                    Err(_) => range.to_string(),
+                },
+            };
+            match step {
+                Some(step) => format!("{}/{}", shifted, step),
+                None => shifted,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Next instant after `after` at which `schedule` fires on `tz`'s wall clock.
+///
+/// DST-safe: a time skipped when clocks spring forward fires once at the
+/// moment of the jump, and a time repeated when they fall back fires only on
+/// its first pass.
+pub fn next_fire(schedule: &Schedule, tz: &Tz, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    // The crate walks wall-clock fields; a UTC clock stands in for naive local time.
+    let wall = tz.to_local(after).naive_local().and_utc();
+    schedule.after(&wall).find_map(|candidate| {
+        let at = match tz.resolve(candidate.naive_utc()) {
+            LocalResolution::Single(at) | LocalResolution::Ambiguous(at, _) | LocalResolution::Gap(at) => at,
+        };
+        (at > after).then_some(at)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn next_fire_follows_local_wall_clock_across_dst() {
+        let tz = Tz::from_posix("America/New_York", "EST5EDT,M3.2.0,M11.1.0").unwrap();
+        let at = |d, h, m| Utc.with_ymd_and_hms(2026, 3, d, h, m, 0).unwrap();
+
+        // 09:00 local is 14:00Z before the switch and 13:00Z after it.
+        let daily = parse_schedule("0 9 * * *").unwrap();
+        assert_eq!(next_fire(&daily, &tz, at(7, 0, 0)), Some(at(7, 14, 0)));
+        assert_eq!(next_fire(&daily, &tz, at(7, 14, 0)), Some(at(8, 13, 0)));
+
+        // 02:30 doesn't exist on 2026-03-08: fire once when the clock jumps.
+        let skipped = parse_schedule("30 2 * * *").unwrap();
+        assert_eq!(next_fire(&skipped, &tz, at(8, 0, 0)), Some(at(8, 7, 0)));
+        assert_eq!(next_fire(&skipped, &tz, at(8, 7, 0)), Some(at(9, 6, 30)));
+
+        // 01:30 happens twice on 2026-11-01: only the first pass fires.
+        let repeated = parse_schedule("30 1 * * *").unwrap();
+        let nov = |d, h, m| Utc.with_ymd_and_hms(2026, 11, d, h, m, 0).unwrap();
+        assert_eq!(next_fire(&repeated, &tz, nov(1, 0, 0)), Some(nov(1, 5, 30)));
+        assert_eq!(next_fire(&repeated, &tz, nov(1, 5, 30)), Some(nov(2, 6, 30)));
+    }
+
+    #[test]
+    fn five_field_day_of_week_uses_standard_numbering() {
+        let utc = Tz::utc();
+        // 2026-10-16 is a Friday.
+        let from = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let weekdays = parse_schedule("0 9 * * 1-5").unwrap();
+        assert_eq!(next_fire(&weekdays, &utc, from), Some(Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap()));
+        let weekend = parse_schedule("0 9 * * 6-7").unwrap();
+        assert_eq!(next_fire(&weekend, &utc, from), Some(Utc.with_ymd_and_hms(2026, 10, 17, 9, 0, 0).unwrap()));
+        let sunday = parse_schedule("0 9 * * 0").unwrap();
+        assert_eq!(next_fire(&sunday, &utc, from), Some(Utc.with_ymd_and_hms(2026, 10, 18, 9, 0, 0).unwrap()));
+        assert!(parse_schedule("0 9 * *").is_err());
+        assert!(parse_schedule("0 0 9 * * *").is_ok());
+    }
+}
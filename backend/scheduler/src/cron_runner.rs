@@ -0,0 +1,390 @@
+/// Cron runner — fires the jobs in `CronStore` and delivers their results.
+///
+/// Each enabled job fires on its schedule (on its own clock, after its
+/// stagger) as a plan request for its agent. The run is matched back from
+/// the supervisor's event stream: the first executed action is its output,
+/// a failed or denied action its error. The run is then recorded in the
+/// `RunLog`, counted against the job's `max_runs`, and the message rendered
+/// from its delivery template goes through the post-message hooks to the
+/// job's delivery target. A job delivering to a session runs as that
+/// session, so its usage is counted there and its `/usage` footer applies.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::{self, Duration, Instant};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use clawforge_core::{AgentSpec, Event, EventKind, Message, PlanRequest};
+use clawforge_hooks::{HookPipeline, MessagePayload};
+use infra::UsageFooter;
+
+use crate::cron_delivery::{deliver_result, parse_delivery_target, render_delivery, DeliveryTarget, PushSink};
+use crate::cron_store::{CronJob, CronStore};
+use crate::run_log::{RunLog, RunLogEntry};
+use crate::stagger::apply_stagger;
+use crate::timezone::Tz;
+
+/// Finds the agent a job runs, by id or name.
+pub type AgentLookup = Arc<dyn Fn(&str) -> Option<AgentSpec> + Send + Sync>;
+
+/// How often the store is checked for due jobs.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// A run with no outcome after this long is recorded as failed.
+const RUN_TIMEOUT: Duration = Duration::from_secs(600);
+/// Longest output kept in the run log.
+const MAX_SUMMARY_CHARS: usize = 4_000;
+
+/// A fired run waiting for its outcome.
+struct PendingRun {
+    job: CronJob,
+    tz: Tz,
+    fired_at: DateTime<Utc>,
+    started: Instant,
+}
+
+pub struct CronRunner {
+    db_path: String,
+    agents: AgentLookup,
+    planner_tx: mpsc::Sender<Message>,
+    events: broadcast::Sender<Event>,
+    run_log: Option<Arc<Mutex<RunLog>>>,
+    /// Zone for jobs that don't set their own.
+    timezone: Tz,
+    push: Option<Arc<dyn PushSink>>,
+    hooks: Option<HookPipeline>,
+    usage: Option<UsageFooter>,
+    pending: HashMap<Uuid, PendingRun>,
+}
+
+impl CronRunner {
+    pub fn new(db_path: impl Into<String>, agents: AgentLookup, planner_tx: mpsc::Sender<Message>, events: broadcast::Sender<Event>) -> Self {
+        Self {
+            db_path: db_path.into(),
+            agents,
+            planner_tx,
+            events,
+            run_log: None,
+            timezone: Tz::utc(),
+            push: None,
+            hooks: None,
+            usage: None,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn with_run_log(mut self, run_log: Arc<Mutex<RunLog>>) -> Self {
+        self.run_log = Some(run_log);
+        self
+    }
+
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub fn with_push(mut self, push: Arc<dyn PushSink>) -> Self {
+        self.push = Some(push);
+        self
+    }
+
+    /// Run each delivered message through the post-message hooks, which may
+    /// rewrite it (e.g. a notification template) or hold it back.
+    pub fn with_hooks(mut self, hooks: HookPipeline) -> Self {
+        self.hooks = Some(hooks);
+        self
+    }
+
+    /// End messages delivered to a session with its `/usage` footer.
+    pub fn with_usage_footer(mut self, usage: UsageFooter) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Fire due jobs until the event stream closes. Jobs due before the
+    /// runner started are not caught up.
+    pub async fn run(mut self) {
+        info!("[Cron] Runner started");
+        let mut events = self.events.subscribe();
+        let mut tick = time::interval(POLL_INTERVAL);
+        let mut checked_until = Utc::now();
+        loop {
+            tokio::select! {
+                _ = tick.tick() => {
+                    let now = Utc::now();
+                    if let Err(e) = self.fire_due(checked_until, now).await {
+                        error!(error = %e, "[Cron] Could not load jobs");
+                    }
+                    checked_until = now;
+                    self.expire_pending().await;
+                }
+                event = events.recv() => match event {
+                    Ok(event) => self.observe(&event).await,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!(missed, "[Cron] Runner fell behind the event stream");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            }
+        }
+        info!("[Cron] Event stream closed, runner stopping");
+    }
+
+    /// Fire every enabled job with a fire time in `(after, until]`.
+    async fn fire_due(&mut self, after: DateTime<Utc>, until: DateTime<Utc>) -> Result<()> {
+        let jobs = CronStore::open(&self.db_path)?.list_enabled()?;
+        for job in jobs {
+            if job.max_runs.is_some_and(|max| job.run_count >= max) {
+                continue;
+            }
+            let tz = match job.timezone_or(&self.timezone) {
+                Ok(tz) => tz,
+                Err(e) => {
+                    warn!(job = %job.id, error = %e, "[Cron] Invalid job time zone, skipping");
+                    continue;
+                }
+            };
+            let fire_at = match job.next_fire(after, &self.timezone) {
+                Ok(Some(at)) if at <= until => at,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!(job = %job.id, error = %e, "[Cron] Invalid job schedule, skipping");
+                    continue;
+                }
+            };
+            let Some(agent) = (self.agents)(&job.agent_id) else {
+                warn!(job = %job.id, agent = %job.agent_id, "[Cron] Job's agent not found, skipping");
+                continue;
+            };
+            self.fire(job, agent, tz, fire_at);
+        }
+        Ok(())
+    }
+
+    fn fire(&mut self, job: CronJob, agent: AgentSpec, tz: Tz, fired_at: DateTime<Utc>) {
+        let run_id = Uuid::new_v4();
+        info!(job = %job.id, agent = %agent.name, %run_id, "[Cron] Firing job");
+        let mut context = serde_json::json!({
+            "trigger": "cron",
+            "cron_job_id": job.id,
+            "prompt": job.prompt,
+            "channel": job.channel,
+            "timestamp": fired_at.to_rfc3339(),
+            "timezone": tz.name(),
+            "local_time": tz.to_local(fired_at).to_rfc3339(),
+        });
+        if let Some(session) = target_session(&job) {
+            context["session_key"] = session.into();
+        }
+        let request = Message::PlanRequest(PlanRequest { run_id, agent, context });
+        let planner_tx = self.planner_tx.clone();
+        let stagger_secs = job.stagger_secs;
+        tokio::spawn(async move {
+            apply_stagger(stagger_secs).await;
+            if let Err(e) = planner_tx.send(request).await {
+                error!(error = %e, "[Cron] Failed to send plan request");
+            }
+        });
+        self.pending.insert(run_id, PendingRun { job, tz, fired_at, started: Instant::now() });
+    }
+
+    /// Finish a pending run once an event settles it.
+    async fn observe(&mut self, event: &Event) {
+        if !self.pending.contains_key(&event.run_id) {
+            return;
+        }
+        let Some(outcome) = run_outcome(event) else { return };
+        if let Some(run) = self.pending.remove(&event.run_id) {
+            self.finish(event.run_id, run, outcome).await;
+        }
+    }
+
+    async fn expire_pending(&mut self) {
+        let expired: Vec<Uuid> = self.pending.iter().filter(|(_, run)| run.started.elapsed() >= RUN_TIMEOUT).map(|(id, _)| *id).collect();
+        for run_id in expired {
+            if let Some(run) = self.pending.remove(&run_id) {
+                let outcome = Err(format!("No result after {}s", RUN_TIMEOUT.as_secs()));
+                self.finish(run_id, run, outcome).await;
+            }
+        }
+    }
+
+    async fn finish(&self, run_id: Uuid, run: PendingRun, outcome: Result<String, String>) {
+        let PendingRun { job, tz, fired_at, started } = run;
+        let (status, output_summary, error) = match outcome {
+            Ok(output) => ("ok", Some(output.chars().take(MAX_SUMMARY_CHARS).collect()), None),
+            Err(e) => ("error", None, Some(e)),
+        };
+        let entry = RunLogEntry {
+            id: run_id.to_string(),
+            job_id: job.id.clone(),
+            fired_at: fired_at.timestamp(),
+            status: status.to_string(),
+            output_summary,
+            error,
+            duration_ms: Some(started.elapsed().as_millis() as u64),
+            timezone: Some(tz.name().to_string()),
+            fired_at_local: None,
+        };
+        info!(job = %job.id, %run_id, status, "[Cron] Run finished");
+
+        let stats = self.run_log.as_ref().and_then(|log| {
+            let log = log.lock().ok()?;
+            if let Err(e) = log.record(&entry) {
+                error!(job = %job.id, error = %e, "[Cron] Could not record run");
+            }
+            log.stats(&job.id).ok()
+        });
+        if let Err(e) = self.count_run(&job) {
+            error!(job = %job.id, error = %e, "[Cron] Could not update run count");
+        }
+
+        let message = match render_delivery(&job, &entry, stats.as_ref(), Utc::now(), &tz) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!(job = %job.id, error = %e, "[Cron] Delivery template failed, delivering the raw result");
+                entry.output_summary.clone().or(entry.error.clone()).unwrap_or_default()
+            }
+        };
+        let Some(mut message) = self.apply_hooks(&job, message).await else { return };
+        if let (Some(usage), Some(session)) = (&self.usage, target_session(&job)) {
+            message = usage.annotate(&session, &job.channel, &message, fired_at).await;
+        }
+        let target = parse_delivery_target(&job.delivery_target);
+        if let Err(e) = deliver_result(&target, &message, &job.id, self.push.as_deref()).await {
+            error!(job = %job.id, error = %e, "[Cron] Delivery failed");
+        }
+    }
+
+    /// Count the run, disabling the job once it reaches `max_runs`.
+    fn count_run(&self, job: &CronJob) -> Result<()> {
+        let store = CronStore::open(&self.db_path)?;
+        store.increment_run_count(&job.id)?;
+        if job.max_runs.is_some_and(|max| job.run_count + 1 >= max) {
+            info!(job = %job.id, "[Cron] Job reached its run limit, disabling");
+            store.disable(&job.id)?;
+        }
+        Ok(())
+    }
+
+    /// The message after post-message hooks, or None if a hook held it back.
+    async fn apply_hooks(&self, job: &CronJob, message: String) -> Option<String> {
+        let Some(hooks) = &self.hooks else { return Some(message) };
+        let result = hooks
+            .post_message(MessagePayload {
+                session_id: job.delivery_target.clone().unwrap_or_default(),
+                channel: job.channel.clone(),
+                role: "assistant".to_string(),
+                content: message.clone(),
+                metadata: serde_json::json!({ "cron_job_id": job.id }),
+            })
+            .await;
+        if result.abort {
+            info!(job = %job.id, reason = ?result.reason, "[Cron] Delivery held back by a hook");
+            return None;
+        }
+        Some(result.modified_content.unwrap_or(message))
+    }
+}
+
+/// The session a job delivers to, if its target is one.
+fn target_session(job: &CronJob) -> Option<String> {
+    match parse_delivery_target(&job.delivery_target) {
+        DeliveryTarget::Session(session) => Some(session),
+        _ => None,
+    }
+}
+
+/// What an event says about its run: output when an action executed, an
+/// error when the run could not go on. Other events leave it pending.
+pub fn run_outcome(event: &Event) -> Option<Result<String, String>> {
+    let text = |key: &str| event.payload.get(key).and_then(Value::as_str).map(str::to_string);
+    match event.kind {
+        EventKind::ActionExecuted => Some(Ok(text("content").or_else(|| text("stdout")).unwrap_or_else(|| event.payload.to_string()))),
+        EventKind::ActionFailed | EventKind::ActionDenied | EventKind::RunFailed => {
+            Some(Err(text("error").or_else(|| text("reason")).unwrap_or_else(|| event.kind.to_string())))
+        }
+        _ => {
+            debug!(run_id = %event.run_id, kind = %event.kind, "Run still in progress");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawforge_core::{Capabilities, LlmPolicy, TriggerSpec};
+
+    fn agent() -> AgentSpec {
+        AgentSpec {
+            id: Uuid::new_v4(),
+            name: "briefer".to_string(),
+            description: "test".to_string(),
+            trigger: TriggerSpec::Manual,
+            capabilities: Capabilities::default(),
+            llm_policy: LlmPolicy::default(),
+            role: Default::default(),
+            memory_config: None,
+            workflow: vec![],
+            allowed_tools: vec![],
+            allowed_skills: vec![],
+            timezone: None,
+            locale: None,
+            execution_window: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn fires_due_jobs_and_records_their_results() {
+        let db = std::env::temp_dir().join(format!("cron-runner-{}.db", Uuid::new_v4()));
+        let db = db.to_string_lossy().to_string();
+        let store = CronStore::open(&db).unwrap();
+        store
+            .upsert(&CronJob {
+                id: "briefing".into(),
+                agent_id: "briefer".into(),
+                channel: "telegram".into(),
+                schedule: "0 7 * * *".into(),
+                delivery_target: None,
+                prompt: "Summarize my day".into(),
+                enabled: true,
+                stagger_secs: 0,
+                max_runs: Some(1),
+                run_count: 0,
+                created_at: 0,
+                timezone: None,
+                delivery_template: Some("{{ job.id }}: {{ output }}".into()),
+            })
+            .unwrap();
+
+        let spec = agent();
+        let agents: AgentLookup = Arc::new(move |id: &str| (id == spec.name).then(|| spec.clone()));
+        let (planner_tx, mut planner_rx) = mpsc::channel(4);
+        let (events, _) = broadcast::channel(4);
+        let run_log = Arc::new(Mutex::new(RunLog::open(&db).unwrap()));
+        let mut runner = CronRunner::new(db.clone(), agents, planner_tx, events).with_run_log(Arc::clone(&run_log));
+
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        runner.fire_due(at("2025-06-10T06:59:30Z"), at("2025-06-10T07:00:00Z")).await.unwrap();
+        let Some(Message::PlanRequest(request)) = planner_rx.recv().await else { panic!("expected a plan request") };
+        assert_eq!(request.context["prompt"], "Summarize my day");
+        assert_eq!(runner.pending.len(), 1);
+
+        // Events for other runs don't settle it.
+        runner.observe(&Event::new(Uuid::new_v4(), request.agent.id, EventKind::ActionExecuted, serde_json::json!({}))).await;
+        let content = serde_json::json!({ "type": "llm_response", "content": "Sunny" });
+        runner.observe(&Event::new(request.run_id, request.agent.id, EventKind::ActionExecuted, content)).await;
+        assert!(runner.pending.is_empty());
+
+        let runs = run_log.lock().unwrap().recent("briefing", 10).unwrap();
+        assert_eq!((runs[0].status.as_str(), runs[0].output_summary.as_deref()), ("ok", Some("Sunny")));
+        // The only allowed run is spent, so the job is off.
+        assert!(store.list_enabled().unwrap().is_empty());
+        let _ = std::fs::remove_file(&db);
+    }
+}
@@ -2,8 +2,13 @@
 ///
 /// Mirrors `src/cron/store.ts` from OpenClaw.
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::cron_delivery::validate_delivery_template;
+use crate::cron_parser::{next_fire, parse_schedule};
+use crate::timezone::Tz;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CronJob {
     pub id: String,
@@ -24,6 +29,30 @@ pub struct CronJob {
     /// Count of completed runs
     pub run_count: u64,
     pub created_at: i64,
+    /// IANA time zone the schedule is evaluated in (None = the agent's or
+    /// scheduler's default).
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Template the delivered message is rendered from (see
+    /// `cron_delivery::DELIVERY_VARIABLES`); None delivers the raw output.
+    #[serde(default)]
+    pub delivery_template: Option<String>,
+}
+
+impl CronJob {
+    /// The job's own zone, or `default` when it doesn't set one.
+    pub fn timezone_or(&self, default: &Tz) -> Result<Tz> {
+        match &self.timezone {
+            Some(name) => Tz::load(name),
+            None => Ok(default.clone()),
+        }
+    }
+
+    /// Next time the job fires after `after`, in its time zone.
+    pub fn next_fire(&self, after: DateTime<Utc>, default: &Tz) -> Result<Option<DateTime<Utc>>> {
+        let schedule = parse_schedule(&self.schedule)?;
+        Ok(next_fire(&schedule, &self.timezone_or(default)?, after))
+    }
 }
 
 pub struct CronStore {
@@ -48,44 +77,81 @@ impl CronStore {
                 stagger_secs    INTEGER NOT NULL DEFAULT 0,
                 max_runs        INTEGER,
                 run_count       INTEGER NOT NULL DEFAULT 0,
-                created_at      INTEGER NOT NULL
+                created_at      INTEGER NOT NULL,
+                timezone        TEXT,
+                delivery_template TEXT
             );
             "#,
         )?;
+        Self::migrate_column(&conn, "timezone")?;
+        Self::migrate_column(&conn, "delivery_template")?;
         Ok(Self { conn })
     }
 
+    /// Stores created before a TEXT column was introduced lack it.
+    fn migrate_column(conn: &rusqlite::Connection, column: &str) -> Result<()> {
+        let mut stmt = conn.prepare("PRAGMA table_info(cron_jobs)")?;
+        let has_column = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .any(|name| name == column);
+        if !has_column {
+            conn.execute_batch(&format!("ALTER TABLE cron_jobs ADD COLUMN {} TEXT;", column))?;
+        }
+        Ok(())
+    }
+
+    /// Insert or update a job. A delivery template that doesn't parse is
+    /// rejected here rather than failing when the job fires.
     pub fn upsert(&self, job: &CronJob) -> Result<()> {
+        if let Some(template) = &job.delivery_template {
+            validate_delivery_template(template).with_context(|| format!("Invalid delivery template for cron job {}", job.id))?;
+        }
         self.conn.execute(
             r#"INSERT INTO cron_jobs
                (id, agent_id, channel, schedule, delivery_target, prompt,
-                enabled, stagger_secs, max_runs, run_count, created_at)
-               VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11)
+                enabled, stagger_secs, max_runs, run_count, created_at, timezone,
+                delivery_template)
+               VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13)
                ON CONFLICT(id) DO UPDATE SET
                  schedule=excluded.schedule,
                  delivery_target=excluded.delivery_target,
                  prompt=excluded.prompt,
                  enabled=excluded.enabled,
                  stagger_secs=excluded.stagger_secs,
-                 max_runs=excluded.max_runs"#,
+                 max_runs=excluded.max_runs,
+                 timezone=excluded.timezone,
+                 delivery_template=excluded.delivery_template"#,
             rusqlite::params![
                 job.id, job.agent_id, job.channel, job.schedule,
                 job.delivery_target, job.prompt,
                 job.enabled as i32, job.stagger_secs as i64,
                 job.max_runs.map(|v| v as i64),
-                job.run_count as i64, job.created_at,
+                job.run_count as i64, job.created_at, job.timezone,
+                job.delivery_template,
             ],
         )?;
         Ok(())
     }
 
     pub fn list_enabled(&self) -> Result<Vec<CronJob>> {
-        let mut stmt = self.conn.prepare(
+        self.query_jobs("WHERE enabled = 1", rusqlite::params![])
+    }
+
+    /// Every job of `agent_id`, enabled or not, oldest first.
+    pub fn list_for_agent(&self, agent_id: &str) -> Result<Vec<CronJob>> {
+        self.query_jobs("WHERE agent_id = ?1 ORDER BY created_at", rusqlite::params![agent_id])
+    }
+
+    fn query_jobs(&self, filter: &str, params: &[&dyn rusqlite::ToSql]) -> Result<Vec<CronJob>> {
+        let mut stmt = self.conn.prepare(&format!(
             "SELECT id, agent_id, channel, schedule, delivery_target, prompt,
-                    enabled, stagger_secs, max_runs, run_count, created_at
-             FROM cron_jobs WHERE enabled = 1"
-        )?;
-        let jobs = stmt.query_map([], |row| {
+                    enabled, stagger_secs, max_runs, run_count, created_at, timezone,
+                    delivery_template
+             FROM cron_jobs {}",
+            filter
+        ))?;
+        let jobs = stmt.query_map(params, |row| {
             Ok(CronJob {
                 id: row.get(0)?,
                 agent_id: row.get(1)?,
@@ -98,6 +164,8 @@ impl CronStore {
                 max_runs: row.get::<_, Option<i64>>(8)?.map(|v| v as u64),
                 run_count: row.get::<_, i64>(9)? as u64,
                 created_at: row.get(10)?,
+                timezone: row.get(11)?,
+                delivery_template: row.get(12)?,
             })
         })?.filter_map(|r| r.ok()).collect();
         Ok(jobs)
@@ -123,4 +191,32 @@ impl CronStore {
         self.conn.execute("DELETE FROM cron_jobs WHERE id = ?1", rusqlite::params![id])?;
         Ok(())
     }
+
+    /// Disable an agent's enabled jobs, returning their ids so they can be
+    /// re-enabled later.
+    pub fn disable_for_agent(&self, agent_id: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT id FROM cron_jobs WHERE agent_id = ?1 AND enabled = 1")?;
+        let ids: Vec<String> = stmt
+            .query_map(rusqlite::params![agent_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        self.conn.execute(
+            "UPDATE cron_jobs SET enabled = 0 WHERE agent_id = ?1",
+            rusqlite::params![agent_id],
+        )?;
+        Ok(ids)
+    }
+
+    pub fn enable(&self, id: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE cron_jobs SET enabled = 1 WHERE id = ?1",
+            rusqlite::params![id],
+        )?;
+        Ok(())
+    }
+
+    /// Delete all of an agent's jobs; returns how many were removed.
+    pub fn delete_for_agent(&self, agent_id: &str) -> Result<usize> {
+        Ok(self.conn.execute("DELETE FROM cron_jobs WHERE agent_id = ?1", rusqlite::params![agent_id])?)
+    }
 }
@@ -1,3 +1,4 @@
+pub mod clock;
 pub mod heartbeat;
  pub mod retry;
 pub mod scheduler;
@@ -5,12 +6,18 @@ pub mod scheduler;
 // Phase 28: Cron enhancements
 pub mod cron_delivery;
 pub mod cron_parser;
+pub mod cron_runner;
 pub mod cron_store;
 pub mod run_log;
 pub mod session_reaper;
 pub mod stagger;
+pub mod timezone;
 
+pub use clock::SchedulerClock;
 pub use retry::{RetryPolicy, RetryState};
 pub use scheduler::Scheduler;
 pub use cron_store::CronJob;
-pub use run_log::RunLogEntry;
+pub use cron_runner::{run_outcome, AgentLookup, CronRunner};
+pub use cron_delivery::{deliver_result, parse_delivery_target, render_delivery, DeliveryTarget, PushSink, sample_delivery_context, validate_delivery_template, DELIVERY_VARIABLES};
+pub use run_log::{JobRunStats, RetentionPolicy, RunLog, RunLogEntry};
+pub use timezone::Tz;
@@ -2,8 +2,14 @@
 ///
 /// Mirrors `src/cron/run-log.ts` from OpenClaw.
 /// Every time a cron job fires, a row is written here with the result.
+/// Retention keeps the table bounded; `stats` summarises a job's history.
+use std::collections::HashMap;
+
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::timezone::Tz;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunLogEntry {
@@ -13,6 +19,60 @@ pub struct RunLogEntry {
     pub status: String, // "ok" | "error" | "skipped"
     pub output_summary: Option<String>,
     pub error: Option<String>,
+    /// Wall-clock run time in milliseconds, if measured.
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// IANA zone the job was scheduled in (None = UTC).
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// `fired_at` on the job's wall clock, e.g. "2026-03-29 09:00:00 CEST".
+    /// Filled in when reading; never stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fired_at_local: Option<String>,
+}
+
+impl RunLogEntry {
+    /// `fired_at` rendered in the entry's time zone.
+    pub fn local_time(&self, tz: &Tz) -> String {
+        let at = chrono::DateTime::from_timestamp(self.fired_at, 0).unwrap_or_default();
+        tz.format(at)
+    }
+}
+
+/// How long run log rows are kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    /// Drop rows older than this many seconds (None = keep forever).
+    pub max_age_secs: Option<i64>,
+    /// Keep at most this many of the newest rows per job (None = unlimited).
+    pub max_runs_per_job: Option<usize>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_secs: Some(30 * 24 * 3600),
+            max_runs_per_job: Some(500),
+        }
+    }
+}
+
+/// Aggregate statistics for one cron job's run history.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobRunStats {
+    pub job_id: String,
+    pub total_runs: u64,
+    pub ok_runs: u64,
+    pub error_runs: u64,
+    pub skipped_runs: u64,
+    /// ok / (ok + error); skipped runs don't count either way. None with no data.
+    pub success_rate: Option<f64>,
+    pub avg_duration_ms: Option<f64>,
+    pub last_run_at: Option<i64>,
+    pub last_failure_at: Option<i64>,
+    pub last_failure_reason: Option<String>,
 }
 
 pub struct RunLog {
@@ -31,33 +91,56 @@ impl RunLog {
                 fired_at       INTEGER NOT NULL,
                 status         TEXT NOT NULL,
                 output_summary TEXT,
-                error          TEXT
+                error          TEXT,
+                duration_ms    INTEGER,
+                timezone       TEXT
             );
             CREATE INDEX IF NOT EXISTS cron_run_log_job_id ON cron_run_log(job_id);
             "#,
         )?;
+        Self::migrate_column(&conn, "duration_ms", "INTEGER")?;
+        Self::migrate_column(&conn, "timezone", "TEXT")?;
         Ok(Self { conn })
     }
 
+    /// Databases created before `duration_ms` / `timezone` existed lack the column.
+    fn migrate_column(conn: &rusqlite::Connection, column: &str, decl: &str) -> Result<()> {
+        let mut stmt = conn.prepare("PRAGMA table_info(cron_run_log)")?;
+        let has_column = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .any(|name| name == column);
+        if !has_column {
+            conn.execute_batch(&format!("ALTER TABLE cron_run_log ADD COLUMN {} {};", column, decl))?;
+        }
+        Ok(())
+    }
+
     pub fn record(&self, entry: &RunLogEntry) -> Result<()> {
         self.conn.execute(
-            "INSERT INTO cron_run_log (id, job_id, fired_at, status, output_summary, error)
-             VALUES (?1,?2,?3,?4,?5,?6)",
+            "INSERT INTO cron_run_log (id, job_id, fired_at, status, output_summary, error, duration_ms, timezone)
+             VALUES (?1,?2,?3,?4,?5,?6,?7,?8)",
             rusqlite::params![
                 entry.id, entry.job_id, entry.fired_at,
                 entry.status, entry.output_summary, entry.error,
+                entry.duration_ms.map(|v| v as i64), entry.timezone,
             ],
         )?;
         Ok(())
     }
 
     pub fn recent(&self, job_id: &str, limit: usize) -> Result<Vec<RunLogEntry>> {
+        self.page(job_id, limit, 0)
+    }
+
+    /// Newest-first page of a job's runs, with `fired_at_local` filled in.
+    pub fn page(&self, job_id: &str, limit: usize, offset: usize) -> Result<Vec<RunLogEntry>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, job_id, fired_at, status, output_summary, error
+            "SELECT id, job_id, fired_at, status, output_summary, error, duration_ms, timezone
              FROM cron_run_log WHERE job_id = ?1
-             ORDER BY fired_at DESC LIMIT ?2",
+             ORDER BY fired_at DESC LIMIT ?2 OFFSET ?3",
         )?;
-        let entries = stmt.query_map(rusqlite::params![job_id, limit as i64], |row| {
+        let entries = stmt.query_map(rusqlite::params![job_id, limit as i64, offset as i64], |row| {
             Ok(RunLogEntry {
                 id: row.get(0)?,
                 job_id: row.get(1)?,
@@ -65,9 +148,72 @@ impl RunLog {
                 status: row.get(3)?,
                 output_summary: row.get(4)?,
                 error: row.get(5)?,
+                duration_ms: row.get::<_, Option<i64>>(6)?.map(|v| v as u64),
+                timezone: row.get(7)?,
+                fired_at_local: None,
+            })
+        })?.filter_map(|r| r.ok()).collect::<Vec<_>>();
+
+        let mut zones: HashMap<Option<String>, Option<Tz>> = HashMap::new();
+        Ok(entries
+            .into_iter()
+            .map(|mut entry| {
+                let tz = zones.entry(entry.timezone.clone()).or_insert_with(|| match &entry.timezone {
+                    Some(name) => Tz::load(name).ok(),
+                    None => Some(Tz::utc()),
+                });
+                entry.fired_at_local = tz.as_ref().map(|tz| entry.local_time(tz));
+                entry
             })
-        })?.filter_map(|r| r.ok()).collect();
-        Ok(entries)
+            .collect())
+    }
+
+    /// Aggregate success rate, average duration and last failure for a job.
+    pub fn stats(&self, job_id: &str) -> Result<JobRunStats> {
+        let mut stats = self.conn.query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(status = 'ok'), 0),
+                    COALESCE(SUM(status = 'error'), 0),
+                    COALESCE(SUM(status = 'skipped'), 0),
+                    AVG(duration_ms),
+                    MAX(fired_at)
+             FROM cron_run_log WHERE job_id = ?1",
+            rusqlite::params![job_id],
+            |row| {
+                Ok(JobRunStats {
+                    job_id: job_id.to_string(),
+                    total_runs: row.get::<_, i64>(0)? as u64,
+                    ok_runs: row.get::<_, i64>(1)? as u64,
+                    error_runs: row.get::<_, i64>(2)? as u64,
+                    skipped_runs: row.get::<_, i64>(3)? as u64,
+                    avg_duration_ms: row.get(4)?,
+                    last_run_at: row.get(5)?,
+                    ..Default::default()
+                })
+            },
+        )?;
+
+        let decided = stats.ok_runs + stats.error_runs;
+        if decided > 0 {
+            stats.success_rate = Some(stats.ok_runs as f64 / decided as f64);
+        }
+
+        let last_failure = self.conn.query_row(
+            "SELECT fired_at, error FROM cron_run_log
+             WHERE job_id = ?1 AND status = 'error'
+             ORDER BY fired_at DESC LIMIT 1",
+            rusqlite::params![job_id],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?)),
+        );
+        match last_failure {
+            Ok((at, reason)) => {
+                stats.last_failure_at = Some(at);
+                stats.last_failure_reason = reason;
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(stats)
     }
 
     /// Prune entries older than `max_age_secs`.
@@ -79,4 +225,111 @@ impl RunLog {
         )?;
         Ok(n)
     }
+
+    /// Keep only the newest `keep` rows for every job.
+    pub fn prune_per_job(&self, keep: usize) -> Result<usize> {
+        let n = self.conn.execute(
+            "DELETE FROM cron_run_log WHERE id IN (
+                SELECT id FROM (
+                    SELECT id, ROW_NUMBER() OVER (
+                        PARTITION BY job_id ORDER BY fired_at DESC
+                    ) AS rn
+                    FROM cron_run_log
+                ) WHERE rn > ?1
+             )",
+            rusqlite::params![keep as i64],
+        )?;
+        Ok(n)
+    }
+
+    /// Apply a retention policy. Returns the total number of rows removed.
+    pub fn apply_retention(&self, policy: &RetentionPolicy) -> Result<usize> {
+        let mut removed = 0;
+        if let Some(max_age) = policy.max_age_secs {
+            removed += self.prune(max_age)?;
+        }
+        if let Some(keep) = policy.max_runs_per_job {
+            removed += self.prune_per_job(keep)?;
+        }
+        if removed > 0 {
+            info!("[RunLog] Retention removed {} run log entries", removed);
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(job: &str, fired_at: i64, status: &str, duration_ms: u64, error: Option<&str>) -> RunLogEntry {
+        RunLogEntry {
+            id: format!("{}-{}", job, fired_at),
+            job_id: job.to_string(),
+            fired_at,
+            status: status.to_string(),
+            output_summary: None,
+            error: error.map(str::to_string),
+            duration_ms: Some(duration_ms),
+            timezone: None,
+            fired_at_local: None,
+        }
+    }
+
+    #[test]
+    fn stats_aggregate_success_rate_and_last_failure() {
+        let log = RunLog::open(":memory:").unwrap();
+        log.record(&entry("digest", 100, "ok", 1000, None)).unwrap();
+        log.record(&entry("digest", 200, "error", 3000, Some("feed timeout"))).unwrap();
+        log.record(&entry("digest", 300, "ok", 2000, None)).unwrap();
+        log.record(&entry("digest", 400, "skipped", 0, None)).unwrap();
+
+        let stats = log.stats("digest").unwrap();
+        assert_eq!(stats.total_runs, 4);
+        assert_eq!(stats.success_rate, Some(2.0 / 3.0));
+        assert_eq!(stats.avg_duration_ms, Some(1500.0));
+        assert_eq!(stats.last_run_at, Some(400));
+        assert_eq!(stats.last_failure_at, Some(200));
+        assert_eq!(stats.last_failure_reason.as_deref(), Some("feed timeout"));
+    }
+
+    #[test]
+    fn stats_for_unknown_job_are_empty() {
+        let log = RunLog::open(":memory:").unwrap();
+        let stats = log.stats("nope").unwrap();
+        assert_eq!(stats.total_runs, 0);
+        assert!(stats.success_rate.is_none());
+        assert!(stats.last_failure_reason.is_none());
+    }
+
+    #[test]
+    fn page_renders_fire_time_in_job_timezone() {
+        let log = RunLog::open(":memory:").unwrap();
+        // 2026-01-01T12:00:00Z
+        log.record(&entry("utc", 1_767_268_800, "ok", 10, None)).unwrap();
+        let runs = log.page("utc", 10, 0).unwrap();
+        assert_eq!(runs[0].fired_at_local.as_deref(), Some("2026-01-01 12:00:00 UTC"));
+
+        let tokyo = Tz::from_posix("Asia/Tokyo", "JST-9").unwrap();
+        assert_eq!(runs[0].local_time(&tokyo), "2026-01-01 21:00:00 JST");
+    }
+
+    #[test]
+    fn retention_keeps_newest_runs_per_job() {
+        let log = RunLog::open(":memory:").unwrap();
+        let now = chrono::Utc::now().timestamp();
+        for i in 0..5 {
+            log.record(&entry("a", now - i, "ok", 10, None)).unwrap();
+        }
+        log.record(&entry("b", now, "ok", 10, None)).unwrap();
+        log.record(&entry("b", now - 10_000, "ok", 10, None)).unwrap();
+
+        let removed = log
+            .apply_retention(&RetentionPolicy { max_age_secs: Some(3600), max_runs_per_job: Some(2) })
+            .unwrap();
+        assert_eq!(removed, 4);
+        assert_eq!(log.recent("a", 10).unwrap().len(), 2);
+        assert_eq!(log.recent("a", 10).unwrap()[0].fired_at, now);
+        assert_eq!(log.recent("b", 10).unwrap().len(), 1);
+    }
 }
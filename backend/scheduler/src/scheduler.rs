@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -11,14 +11,27 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use clawforge_core::{
-    AgentSpec, Component, Message, PlanRequest, TriggerSpec,
+    ActivityProbe, AgentSpec, Component, HostActivity, Message, PlanRequest, TriggerSpec, UnknownActivity,
 };
 
+use crate::clock::SchedulerClock;
+use crate::cron_parser::{next_fire, parse_schedule};
+use crate::timezone::Tz;
+
+/// Longest a host probe may take before the tick goes ahead without it.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// The Scheduler component evaluates agent triggers and dispatches PlanRequest messages.
 pub struct Scheduler {
     agents: Vec<AgentSpec>,
     planner_tx: mpsc::Sender<Message>,
     _supervisor_tx: mpsc::Sender<Message>,
+    /// Zone for agents that don't set their own.
+    timezone: Tz,
+    /// Host state checked against agents' execution windows. The default
+    /// reads nothing, so windows stay open until a real probe is supplied.
+    activity: Arc<dyn ActivityProbe>,
+    clock: SchedulerClock,
 }
 
 impl Scheduler {
@@ -31,10 +44,77 @@ impl Scheduler {
             agents,
             planner_tx,
             _supervisor_tx,
+            timezone: Tz::utc(),
+            activity: Arc::new(UnknownActivity),
+            clock: SchedulerClock::new(),
+        }
+    }
+
+    pub fn with_activity_probe(mut self, activity: Arc<dyn ActivityProbe>) -> Self {
+        self.activity = activity;
+        self
+    }
+
+    /// Read the time from `clock`; advancing it fires due triggers at once.
+    pub fn with_clock(mut self, clock: SchedulerClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Sample the host, giving up after `PROBE_TIMEOUT` so a hung probe
+    /// command can't stall the scheduler.
+    async fn sample_activity(&self) -> HostActivity {
+        match time::timeout(PROBE_TIMEOUT, self.activity.sample()).await {
+            Ok(activity) => activity,
+            Err(_) => {
+                warn!("Host activity probe timed out, treating readings as unknown");
+                HostActivity::default()
+            }
+        }
+    }
+
+    /// The agent's own zone, falling back to the scheduler default.
+    fn agent_timezone(&self, agent: &AgentSpec) -> Tz {
+        match agent.timezone.as_deref().map(Tz::load) {
+            Some(Ok(tz)) => tz,
+            Some(Err(e)) => {
+                warn!(agent = %agent.name, error = %e, "Invalid agent time zone, using scheduler default");
+                self.timezone.clone()
+            }
+            None => self.timezone.clone(),
+        }
+    }
+
+    /// Trigger context handed to the planner, with the agent's local time and locale.
+    fn trigger_context(&self, agent: &AgentSpec, trigger: &str) -> serde_json::Value {
+        let now = self.clock.utc_now();
+        let tz = self.agent_timezone(agent);
+        let mut context = serde_json::json!({
+            "trigger": trigger,
+            "timestamp": now.to_rfc3339(),
+            "timezone": tz.name(),
+            "local_time": tz.to_local(now).to_rfc3339(),
+        });
+        if let Some(locale) = &agent.locale {
+            context["locale"] = serde_json::json!(locale);
         }
+        context
     }
 }
 
+/// Tokio deadline for the next fire of a cron schedule.
+fn cron_deadline(schedule: &Schedule, tz: &Tz, clock: &SchedulerClock) -> Option<(tokio::time::Instant, chrono::DateTime<Utc>)> {
+    let now = clock.utc_now();
+    let next = next_fire(schedule, tz, now)?;
+    let until = (next - now).to_std().unwrap_or(Duration::from_secs(60));
+    Some((clock.now() + until, next))
+}
+
 #[async_trait]
 impl Component for Scheduler {
     fn name(&self) -> &str {
@@ -49,12 +129,15 @@ impl Component for Scheduler {
 
         // Track next fire time for each cron/interval agent
         let mut next_fires: HashMap<Uuid, tokio::time::Instant> = HashMap::new();
+        let mut cron_schedules: HashMap<Uuid, (Schedule, Tz)> = HashMap::new();
+        // When each deferred agent's trigger first fired outside its execution window.
+        let mut deferred_since: HashMap<Uuid, tokio::time::Instant> = HashMap::new();
 
         // Initialize interval agents
         for agent in &self.agents {
             match &agent.trigger {
                 TriggerSpec::Interval { seconds } => {
-                    let fire_at = tokio::time::Instant::now() + Duration::from_secs(*seconds);
+                    let fire_at = self.clock.now() + Duration::from_secs(*seconds);
                     next_fires.insert(agent.id, fire_at);
                     info!(
                         agent = %agent.name,
@@ -63,20 +146,19 @@ impl Component for Scheduler {
                     );
                 }
                 TriggerSpec::Cron { expression } => {
-                    match Schedule::from_str(expression) {
+                    match parse_schedule(expression) {
                         Ok(schedule) => {
-                            if let Some(next) = schedule.upcoming(Utc).next() {
-                                let until = (next - Utc::now())
-                                    .to_std()
-                                    .unwrap_or(Duration::from_secs(60));
-                                let fire_at = tokio::time::Instant::now() + until;
+                            let tz = self.agent_timezone(agent);
+                            if let Some((fire_at, next)) = cron_deadline(&schedule, &tz, &self.clock) {
                                 next_fires.insert(agent.id, fire_at);
                                 info!(
                                     agent = %agent.name,
-                                    next = %next,
+                                    timezone = %tz.name(),
+                                    next = %tz.format(next),
                                     "Registered cron trigger"
                                 );
                             }
+                            cron_schedules.insert(agent.id, (schedule, tz));
                         }
                         Err(e) => {
                             warn!(
@@ -96,17 +178,56 @@ impl Component for Scheduler {
             }
         }
 
+        self.clock.mark_running();
+
         // Main scheduling loop
         let tick_interval = Duration::from_secs(1);
         let mut ticker = time::interval(tick_interval);
 
         loop {
             tokio::select! {
-                _ = ticker.tick() => {
-                    let now = tokio::time::Instant::now();
+                _ = async { tokio::select! { _ = ticker.tick() => {}, _ = self.clock.advanced() => {} } } => {
+                    let now = self.clock.now();
+                    // Sampled at most once per tick, and only when a windowed agent is due.
+                    let mut host: Option<HostActivity> = None;
                     for agent in &self.agents {
                         if let Some(fire_at) = next_fires.get(&agent.id) {
                             if now >= *fire_at {
+                                if let Some(window) = &agent.execution_window {
+                                    if host.is_none() {
+                                        host = Some(self.sample_activity().await);
+                                    }
+                                    let blockers = window.blockers(host.as_ref().unwrap());
+                                    if !blockers.is_empty() {
+                                        let first = !deferred_since.contains_key(&agent.id);
+                                        let since = *deferred_since.entry(agent.id).or_insert(now);
+                                        let waited = now.duration_since(since);
+                                        let expired = window
+                                            .max_deferral_secs
+                                            .is_some_and(|max| waited >= Duration::from_secs(max));
+                                        if !expired {
+                                            if first {
+                                                info!(
+                                                    agent = %agent.name,
+                                                    reasons = %blockers.join("; "),
+                                                    "Outside execution window, deferring run"
+                                                );
+                                            }
+                                            next_fires.insert(
+                                                agent.id,
+                                                now + Duration::from_secs(window.recheck_secs.max(1)),
+                                            );
+                                            continue;
+                                        }
+                                        warn!(
+                                            agent = %agent.name,
+                                            waited_secs = waited.as_secs(),
+                                            reasons = %blockers.join("; "),
+                                            "Execution window still closed after max deferral, running anyway"
+                                        );
+                                    }
+                                }
+
                                 let run_id = Uuid::new_v4();
                                 info!(
                                     agent = %agent.name,
@@ -114,13 +235,14 @@ impl Component for Scheduler {
                                     "Trigger fired, dispatching plan request"
                                 );
 
+                                let mut context = self.trigger_context(agent, "scheduled");
+                                if let Some(since) = deferred_since.remove(&agent.id) {
+                                    context["deferred_secs"] = serde_json::json!(now.duration_since(since).as_secs());
+                                }
                                 let plan_request = Message::PlanRequest(PlanRequest {
                                     run_id,
                                     agent: agent.clone(),
-                                    context: serde_json::json!({
-                                        "trigger": "scheduled",
-                                        "timestamp": Utc::now().to_rfc3339(),
-                                    }),
+                                    context,
                                 });
 
                                 if let Err(e) = self.planner_tx.send(plan_request).await {
@@ -135,13 +257,16 @@ impl Component for Scheduler {
                                             now + Duration::from_secs(*seconds),
                                         );
                                     }
-                                    TriggerSpec::Cron { expression } => {
-                                        if let Ok(schedule) = Schedule::from_str(expression) {
-                                            if let Some(next) = schedule.upcoming(Utc).next() {
-                                                let until = (next - Utc::now())
-                                                    .to_std()
-                                                    .unwrap_or(Duration::from_secs(60));
-                                                next_fires.insert(agent.id, now + until);
+                                    TriggerSpec::Cron { .. } => {
+                                        let next = cron_schedules
+                                            .get(&agent.id)
+                                            .and_then(|(schedule, tz)| cron_deadline(schedule, tz, &self.clock));
+                                        match next {
+                                            Some((fire_at, _)) => {
+                                                next_fires.insert(agent.id, fire_at);
+                                            }
+                                            None => {
+                                                next_fires.remove(&agent.id);
                                             }
                                         }
                                     }
@@ -165,10 +290,7 @@ impl Component for Scheduler {
                                 let plan_request = Message::PlanRequest(PlanRequest {
                                     run_id: trigger.run_id,
                                     agent: agent.clone(),
-                                    context: serde_json::json!({
-                                        "trigger": trigger.trigger_reason,
-                                        "timestamp": Utc::now().to_rfc3339(),
-                                    }),
+                                    context: self.trigger_context(agent, &trigger.trigger_reason),
                                 });
                                 if let Err(e) = self.planner_tx.send(plan_request).await {
                                     error!(error = %e, "Failed to send plan request");
@@ -199,7 +321,20 @@ impl Component for Scheduler {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clawforge_core::{Capabilities, JobTrigger, LlmPolicy};
+    use clawforge_core::{Capabilities, ExecutionWindow, HostActivity, JobTrigger, LlmPolicy};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Reports battery power until plugged in.
+    struct FakeProbe {
+        plugged_in: AtomicBool,
+    }
+
+    #[async_trait]
+    impl ActivityProbe for FakeProbe {
+        async fn sample(&self) -> HostActivity {
+            HostActivity { on_ac_power: Some(self.plugged_in.load(Ordering::SeqCst)), ..Default::default() }
+        }
+    }
 
     fn test_agent(trigger: TriggerSpec) -> AgentSpec {
         AgentSpec {
@@ -214,6 +349,9 @@ mod tests {
             workflow: vec![],
             allowed_tools: vec![],
             allowed_skills: vec![],
+            timezone: None,
+            locale: None,
+            execution_window: None,
         }
     }
 
@@ -250,9 +388,44 @@ mod tests {
             .unwrap();
 
         assert_eq!(msg.run_id(), run_id);
+        let Message::PlanRequest(request) = msg else { panic!("expected a plan request") };
+        assert_eq!(request.context["timezone"], "UTC");
 
         // Cleanup
         drop(scheduler_tx);
         let _ = tokio::time::timeout(Duration::from_secs(1), handle).await;
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_execution_window_defers_until_open() {
+        let (planner_tx, mut planner_rx) = mpsc::channel(16);
+        let (supervisor_tx, _supervisor_rx) = mpsc::channel(16);
+        let (_scheduler_tx, scheduler_rx) = mpsc::channel(16);
+
+        let mut agent = test_agent(TriggerSpec::Interval { seconds: 1 });
+        agent.execution_window = Some(ExecutionWindow {
+            require_ac_power: true,
+            recheck_secs: 1,
+            ..Default::default()
+        });
+        let probe = Arc::new(FakeProbe { plugged_in: AtomicBool::new(false) });
+        let scheduler = Scheduler::new(vec![agent], planner_tx, supervisor_tx)
+            .with_activity_probe(probe.clone());
+        let handle = tokio::spawn(async move {
+            scheduler.start(scheduler_rx).await.unwrap();
+        });
+
+        // On battery: the trigger fires but nothing is dispatched.
+        assert!(tokio::time::timeout(Duration::from_millis(2500), planner_rx.recv()).await.is_err());
+
+        probe.plugged_in.store(true, Ordering::SeqCst);
+        let msg = tokio::time::timeout(Duration::from_secs(3), planner_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let Message::PlanRequest(request) = msg else { panic!("expected a plan request") };
+        assert!(request.context["deferred_secs"].as_u64().unwrap() >= 1);
+
+        handle.abort();
+    }
 }
@@ -0,0 +1,544 @@
+//! IANA time zones for cron evaluation.
+//!
+//! Zones are read from the system zoneinfo database (TZif files, RFC 8536),
+//! including the POSIX rule in the footer that covers instants after the last
+//! listed transition. Only what the scheduler needs is exposed: the local time
+//! of an instant, and how a wall-clock time maps back onto instants across DST
+//! transitions.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use std::path::PathBuf;
+
+const ZONEINFO_DIRS: &[&str] = &["/usr/share/zoneinfo", "/usr/lib/zoneinfo", "/usr/share/lib/zoneinfo"];
+
+/// Default DST switch time for POSIX rules without an explicit `/time`.
+const DEFAULT_RULE_TIME: i64 = 2 * 3600;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LocalType {
+    /// Seconds east of UTC.
+    offset: i64,
+    abbr: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleDate {
+    /// `Jn`: day 1..=365, February 29 is never counted.
+    Julian1(u32),
+    /// `n`: day 0..=365, February 29 is counted in leap years.
+    Julian0(u32),
+    /// `Mm.w.d`: weekday `d` (0 = Sunday) of week `w` (5 = last) of month `m`.
+    MonthWeekDay(u32, u32, u32),
+}
+
+#[derive(Debug, Clone)]
+struct DstRule {
+    dst: LocalType,
+    start: RuleDate,
+    /// Local standard time of the switch to DST.
+    start_time: i64,
+    end: RuleDate,
+    /// Local daylight time of the switch back.
+    end_time: i64,
+}
+
+#[derive(Debug, Clone)]
+struct PosixRule {
+    std: LocalType,
+    dst: Option<DstRule>,
+}
+
+/// How a wall-clock time maps onto real instants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalResolution {
+    Single(DateTime<Utc>),
+    /// Repeated when clocks fall back: (first pass, second pass).
+    Ambiguous(DateTime<Utc>, DateTime<Utc>),
+    /// Skipped when clocks spring forward; carries the instant of the jump.
+    Gap(DateTime<Utc>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Tz {
+    name: String,
+    /// (UTC seconds, index into `types`), ascending.
+    transitions: Vec<(i64, usize)>,
+    types: Vec<LocalType>,
+    rule: Option<PosixRule>,
+}
+
+impl Default for Tz {
+    fn default() -> Self {
+        Self::utc()
+    }
+}
+
+impl Tz {
+    pub fn utc() -> Self {
+        Self {
+            name: "UTC".to_string(),
+            transitions: Vec::new(),
+            types: vec![LocalType { offset: 0, abbr: "UTC".to_string() }],
+            rule: None,
+        }
+    }
+
+    /// Load an IANA zone such as `Europe/Berlin` from the zoneinfo database
+    /// (`$TZDIR` first, then the usual system locations).
+    pub fn load(name: &str) -> Result<Self> {
+        let name = name.trim();
+        if name == "UTC" || name == "Etc/UTC" {
+            return Ok(Self::utc());
+        }
+        let valid = !name.is_empty()
+            && !name.starts_with('/')
+            && name.split('/').all(|part| !part.is_empty() && part != "." && part != "..")
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || "/_-+".contains(c));
+        if !valid {
+            bail!("Invalid time zone name '{}'", name);
+        }
+        let dirs = std::env::var_os("TZDIR")
+            .map(PathBuf::from)
+            .into_iter()
+            .chain(ZONEINFO_DIRS.iter().map(PathBuf::from));
+        for dir in dirs {
+            let path = dir.join(name);
+            if let Ok(bytes) = std::fs::read(&path) {
+                return Self::from_tzif(name, &bytes).with_context(|| format!("Failed to parse {}", path.display()));
+            }
+        }
+        bail!("Unknown time zone '{}'", name)
+    }
+
+    /// A zone described only by a POSIX TZ rule, e.g. `CET-1CEST,M3.5.0,M10.5.0/3`.
+    pub fn from_posix(name: &str, rule: &str) -> Result<Self> {
+        let rule = parse_posix(rule).with_context(|| format!("Invalid POSIX TZ rule '{}'", rule))?;
+        Ok(Self {
+            name: name.to_string(),
+            transitions: Vec::new(),
+            types: vec![rule.std.clone()],
+            rule: Some(rule),
+        })
+    }
+
+    /// Parse the contents of a TZif file.
+    pub fn from_tzif(name: &str, data: &[u8]) -> Result<Self> {
+        let mut r = Reader { data, pos: 0 };
+        let mut header = r.header()?;
+        let mut time_size = 4;
+        if header.version >= b'2' {
+            // Skip the legacy 32-bit block; the 64-bit one repeats it in full.
+            r.take(header.block_len(4))?;
+            header = r.header()?;
+            time_size = 8;
+        }
+
+        let times = (0..header.timecnt).map(|_| r.time(time_size)).collect::<Result<Vec<_>>>()?;
+        let indices = r.take(header.timecnt)?.to_vec();
+        let mut raw = Vec::with_capacity(header.typecnt);
+        for _ in 0..header.typecnt {
+            let offset = r.time(4)?;
+            let _is_dst = r.take(1)?;
+            let abbr_index = r.take(1)?[0] as usize;
+            raw.push((offset, abbr_index));
+        }
+        let chars = r.take(header.charcnt)?;
+        r.take(header.leapcnt * (time_size + 4) + header.isstdcnt + header.isutcnt)?;
+
+        let types: Vec<LocalType> = raw
+            .into_iter()
+            .map(|(offset, i)| LocalType { offset, abbr: c_string(chars, i) })
+            .collect();
+        if types.is_empty() {
+            bail!("TZif file has no local time types");
+        }
+        let transitions = times
+            .into_iter()
+            .zip(indices)
+            .map(|(at, i)| match types.get(i as usize) {
+                Some(_) => Ok((at, i as usize)),
+                None => Err(anyhow!("transition refers to missing type {}", i)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // v2+ footer: "\n<POSIX TZ rule>\n", possibly empty.
+        let rule = match time_size {
+            8 => {
+                let footer = String::from_utf8_lossy(r.rest());
+                let footer = footer.trim_matches('\n');
+                if footer.is_empty() { None } else { Some(parse_posix(footer)?) }
+            }
+            _ => None,
+        };
+
+        Ok(Self { name: name.to_string(), transitions, types, rule })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn local_type(&self, ts: i64) -> &LocalType {
+        if let Some(rule) = &self.rule {
+            if self.transitions.last().is_none_or(|&(last, _)| ts >= last) {
+                return rule.local_type(ts);
+            }
+        }
+        match self.transitions.partition_point(|&(at, _)| at <= ts) {
+            0 => &self.types[0],
+            i => &self.types[self.transitions[i - 1].1],
+        }
+    }
+
+    /// The instant as wall-clock time in this zone.
+    pub fn to_local(&self, at: DateTime<Utc>) -> DateTime<FixedOffset> {
+        let offset = self.local_type(at.timestamp()).offset;
+        let offset = FixedOffset::east_opt(offset as i32).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        at.with_timezone(&offset)
+    }
+
+    /// Zone abbreviation in effect at `at`, e.g. `CEST`.
+    pub fn abbreviation(&self, at: DateTime<Utc>) -> &str {
+        &self.local_type(at.timestamp()).abbr
+    }
+
+    /// `2026-03-29 03:00:00 CEST`
+    pub fn format(&self, at: DateTime<Utc>) -> String {
+        format!("{} {}", self.to_local(at).format("%Y-%m-%d %H:%M:%S"), self.abbreviation(at))
+    }
+
+    /// Map a wall-clock time in this zone onto UTC.
+    pub fn resolve(&self, local: NaiveDateTime) -> LocalResolution {
+        let wall = local.and_utc().timestamp();
+        // UTC offsets stay well inside a day, so the offsets in effect a day
+        // either side cover every reading of `wall`.
+        let before = self.local_type(wall - 86_400).offset;
+        let after = self.local_type(wall + 86_400).offset;
+        let mut hits: Vec<i64> = [before, after]
+            .into_iter()
+            .map(|offset| wall - offset)
+            .filter(|&at| self.local_type(at).offset == wall - at)
+            .collect();
+        hits.sort_unstable();
+        hits.dedup();
+
+        match hits[..] {
+            [at] => LocalResolution::Single(utc(at)),
+            [first, second, ..] => LocalResolution::Ambiguous(utc(first), utc(second)),
+            [] => {
+                // Spring-forward gap: binary-search the jump between the two readings.
+                let (mut lo, mut hi) = (wall - after, wall - before);
+                while hi - lo > 1 {
+                    let mid = lo + (hi - lo) / 2;
+                    if self.local_type(mid).offset == before {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                LocalResolution::Gap(utc(hi))
+            }
+        }
+    }
+}
+
+fn utc(ts: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(ts, 0).unwrap_or_default()
+}
+
+fn c_string(chars: &[u8], start: usize) -> String {
+    let tail = chars.get(start..).unwrap_or_default();
+    let end = tail.iter().position(|&b| b == 0).unwrap_or(tail.len());
+    String::from_utf8_lossy(&tail[..end]).into_owned()
+}
+
+// ---------------------------------------------------------------------------
+// TZif reader
+// ---------------------------------------------------------------------------
+
+struct Header {
+    version: u8,
+    isutcnt: usize,
+    isstdcnt: usize,
+    leapcnt: usize,
+    timecnt: usize,
+    typecnt: usize,
+    charcnt: usize,
+}
+
+impl Header {
+    fn block_len(&self, time_size: usize) -> usize {
+        self.timecnt * (time_size + 1)
+            + self.typecnt * 6
+            + self.charcnt
+            + self.leapcnt * (time_size + 4)
+            + self.isstdcnt
+            + self.isutcnt
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| anyhow!("TZif data truncated"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        &self.data[self.pos.min(self.data.len())..]
+    }
+
+    fn time(&mut self, size: usize) -> Result<i64> {
+        let bytes = self.take(size)?;
+        Ok(match size {
+            8 => i64::from_be_bytes(bytes.try_into()?),
+            _ => i32::from_be_bytes(bytes.try_into()?) as i64,
+        })
+    }
+
+    fn count(&mut self) -> Result<usize> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?) as usize)
+    }
+
+    fn header(&mut self) -> Result<Header> {
+        if self.take(4)? != b"TZif" {
+            bail!("not a TZif file");
+        }
+        let version = self.take(1)?[0];
+        self.take(15)?;
+        Ok(Header {
+            version,
+            isutcnt: self.count()?,
+            isstdcnt: self.count()?,
+            leapcnt: self.count()?,
+            timecnt: self.count()?,
+            typecnt: self.count()?,
+            charcnt: self.count()?,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// POSIX TZ rules
+// ---------------------------------------------------------------------------
+
+impl PosixRule {
+    fn local_type(&self, ts: i64) -> &LocalType {
+        let Some(rule) = &self.dst else {
+            return &self.std;
+        };
+        let year = utc(ts + self.std.offset).year();
+        let start = rule.start.midnight(year) + rule.start_time - self.std.offset;
+        let end = rule.end.midnight(year) + rule.end_time - rule.dst.offset;
+        // Southern-hemisphere rules start DST late in the year and end it early.
+        let in_dst = if start <= end {
+            ts >= start && ts < end
+        } else {
+            ts >= start || ts < end
+        };
+        if in_dst { &rule.dst } else { &self.std }
+    }
+}
+
+impl RuleDate {
+    /// Local midnight of the rule's day in `year`, as seconds on a UTC clock.
+    fn midnight(self, year: i32) -> i64 {
+        let leap = NaiveDate::from_ymd_opt(year, 2, 29).is_some();
+        let date = match self {
+            RuleDate::Julian1(n) => NaiveDate::from_yo_opt(year, if leap && n >= 60 { n + 1 } else { n }),
+            RuleDate::Julian0(n) => NaiveDate::from_yo_opt(year, n + 1),
+            RuleDate::MonthWeekDay(month, week, weekday) => NaiveDate::from_ymd_opt(year, month, 1).and_then(|first| {
+                let mut day = 1 + (weekday + 7 - first.weekday().num_days_from_sunday()) % 7 + (week - 1) * 7;
+                while NaiveDate::from_ymd_opt(year, month, day).is_none() {
+                    day -= 7;
+                }
+                NaiveDate::from_ymd_opt(year, month, day)
+            }),
+        };
+        date.and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|dt| dt.and_utc().timestamp())
+            .unwrap_or_default()
+    }
+}
+
+fn parse_posix(spec: &str) -> Result<PosixRule> {
+    let mut p = PosixParser { s: spec.as_bytes(), pos: 0 };
+    // POSIX offsets count hours west of UTC.
+    let std = LocalType { abbr: p.abbr()?, offset: -p.duration()? };
+    if p.done() {
+        return Ok(PosixRule { std, dst: None });
+    }
+    let abbr = p.abbr()?;
+    let offset = if p.peek() == Some(b',') { std.offset + 3600 } else { -p.duration()? };
+    let dst = LocalType { abbr, offset };
+    p.expect(b',')?;
+    let start = p.date()?;
+    let start_time = p.time()?;
+    p.expect(b',')?;
+    let end = p.date()?;
+    let end_time = p.time()?;
+    if !p.done() {
+        bail!("trailing characters");
+    }
+    Ok(PosixRule { std, dst: Some(DstRule { dst, start, start_time, end, end_time }) })
+}
+
+struct PosixParser<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl PosixParser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.pos).copied()
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.s.len()
+    }
+
+    fn expect(&mut self, c: u8) -> Result<()> {
+        if self.peek() != Some(c) {
+            bail!("expected '{}' at {}", c as char, self.pos);
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn abbr(&mut self) -> Result<String> {
+        let start = self.pos;
+        let text = if self.peek() == Some(b'<') {
+            let end = self.s[start..].iter().position(|&c| c == b'>').ok_or_else(|| anyhow!("unterminated '<'"))?;
+            self.pos = start + end + 1;
+            &self.s[start + 1..start + end]
+        } else {
+            while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                self.pos += 1;
+            }
+            &self.s[start..self.pos]
+        };
+        if text.is_empty() {
+            bail!("missing zone abbreviation at {}", start);
+        }
+        Ok(String::from_utf8_lossy(text).into_owned())
+    }
+
+    fn number(&mut self) -> Result<u32> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.s[start..self.pos])?
+            .parse()
+            .map_err(|_| anyhow!("expected a number at {}", start))
+    }
+
+    /// `[+-]hh[:mm[:ss]]` in seconds.
+    fn duration(&mut self) -> Result<i64> {
+        let sign = match self.peek() {
+            Some(b'-') => { self.pos += 1; -1 }
+            Some(b'+') => { self.pos += 1; 1 }
+            _ => 1,
+        };
+        let mut secs = self.number()? as i64 * 3600;
+        for unit in [60, 1] {
+            if self.peek() != Some(b':') {
+                break;
+            }
+            self.pos += 1;
+            secs += self.number()? as i64 * unit;
+        }
+        Ok(sign * secs)
+    }
+
+    fn time(&mut self) -> Result<i64> {
+        if self.peek() == Some(b'/') {
+            self.pos += 1;
+            self.duration()
+        } else {
+            Ok(DEFAULT_RULE_TIME)
+        }
+    }
+
+    fn date(&mut self) -> Result<RuleDate> {
+        match self.peek() {
+            Some(b'M') => {
+                self.pos += 1;
+                let month = self.number()?;
+                self.expect(b'.')?;
+                let week = self.number()?;
+                self.expect(b'.')?;
+                let weekday = self.number()?;
+                if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+                    bail!("rule date M{}.{}.{} out of range", month, week, weekday);
+                }
+                Ok(RuleDate::MonthWeekDay(month, week, weekday))
+            }
+            Some(b'J') => {
+                self.pos += 1;
+                match self.number()? {
+                    n @ 1..=365 => Ok(RuleDate::Julian1(n)),
+                    n => bail!("rule date J{} out of range", n),
+                }
+            }
+            _ => match self.number()? {
+                n @ 0..=365 => Ok(RuleDate::Julian0(n)),
+                n => bail!("rule date {} out of range", n),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn new_york() -> Tz {
+        Tz::from_posix("America/New_York", "EST5EDT,M3.2.0,M11.1.0").unwrap()
+    }
+
+    fn naive(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn resolves_dst_transitions() {
+        let tz = new_york();
+        assert_eq!(tz.resolve(naive("2026-01-15 09:00")), LocalResolution::Single(Utc.with_ymd_and_hms(2026, 1, 15, 14, 0, 0).unwrap()));
+        // 2026-03-08 02:30 never happens; the clock jumps 02:00 EST -> 03:00 EDT at 07:00Z.
+        assert_eq!(tz.resolve(naive("2026-03-08 02:30")), LocalResolution::Gap(Utc.with_ymd_and_hms(2026, 3, 8, 7, 0, 0).unwrap()));
+        // 2026-11-01 01:30 happens twice.
+        assert_eq!(
+            tz.resolve(naive("2026-11-01 01:30")),
+            LocalResolution::Ambiguous(
+                Utc.with_ymd_and_hms(2026, 11, 1, 5, 30, 0).unwrap(),
+                Utc.with_ymd_and_hms(2026, 11, 1, 6, 30, 0).unwrap()
+            )
+        );
+        assert_eq!(tz.format(Utc.with_ymd_and_hms(2026, 7, 4, 16, 0, 0).unwrap()), "2026-07-04 12:00:00 EDT");
+
+        let sydney = Tz::from_posix("Australia/Sydney", "AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        assert_eq!(sydney.abbreviation(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()), "AEDT");
+        assert_eq!(sydney.abbreviation(Utc.with_ymd_and_hms(2026, 6, 1, 0, 0, 0).unwrap()), "AEST");
+    }
+
+    #[test]
+    fn loads_system_zoneinfo() {
+        assert!(Tz::load("../etc/passwd").is_err());
+        assert_eq!(Tz::load("UTC").unwrap().format(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()), "2026-01-01 00:00:00 UTC");
+        // Not every build host ships tzdata.
+        let Ok(tz) = Tz::load("Europe/Berlin") else { return };
+        assert_eq!(tz.format(Utc.with_ymd_and_hms(1990, 7, 1, 12, 0, 0).unwrap()), "1990-07-01 14:00:00 CEST");
+        assert_eq!(tz.format(Utc.with_ymd_and_hms(2040, 12, 1, 12, 0, 0).unwrap()), "2040-12-01 13:00:00 CET");
+    }
+}
@@ -0,0 +1,369 @@
+/// Approval broker — human-in-the-loop sign-off for dangerous tool calls.
+///
+/// When a run wants to call a tool that `is_dangerous` flags, the caller asks
+/// the broker for approval and awaits the outcome. The broker delivers the
+/// request to every registered notifier (the session's chat channel, the exec
+/// approval socket, ...) and to Control UI subscribers, then resolves the wait
+/// with the first verdict that comes back through `resolve` or an
+/// `/approve yes <id>` reply. If nobody answers in time the timeout policy
+/// decides. Session-scoped verdicts are remembered for the rest of the session.
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::dangerous_tools::is_dangerous;
+
+/// A human's answer to an approval request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApprovalVerdict {
+    Allow,
+    AllowSession,
+    Deny,
+    DenySession,
+}
+
+impl ApprovalVerdict {
+    /// Accepts the approval socket's verdict strings and `/approve` answers.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "allow" | "yes" | "y" | "approve" => Some(ApprovalVerdict::Allow),
+            "allow-session" | "always" => Some(ApprovalVerdict::AllowSession),
+            "deny" | "no" | "n" | "reject" => Some(ApprovalVerdict::Deny),
+            "deny-session" | "never" => Some(ApprovalVerdict::DenySession),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApprovalVerdict::Allow => "allow",
+            ApprovalVerdict::AllowSession => "allow-session",
+            ApprovalVerdict::Deny => "deny",
+            ApprovalVerdict::DenySession => "deny-session",
+        }
+    }
+
+    pub fn is_allow(self) -> bool {
+        matches!(self, ApprovalVerdict::Allow | ApprovalVerdict::AllowSession)
+    }
+
+    fn is_session_scoped(self) -> bool {
+        matches!(self, ApprovalVerdict::AllowSession | ApprovalVerdict::DenySession)
+    }
+}
+
+/// What happens when nobody answers in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutAction {
+    Deny,
+    Allow,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApprovalPolicy {
+    pub timeout: Duration,
+    pub on_timeout: TimeoutAction,
+}
+
+impl Default for ApprovalPolicy {
+    fn default() -> Self {
+        Self { timeout: Duration::from_secs(300), on_timeout: TimeoutAction::Deny }
+    }
+}
+
+/// An approval request waiting on a human.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingApproval {
+    pub id: String,
+    pub session_id: String,
+    /// Channel the run was started from; notifiers deliver the prompt there.
+    pub channel: Option<String>,
+    /// Chat within `channel` the run was started from.
+    #[serde(default)]
+    pub chat_id: Option<String>,
+    pub tool: String,
+    /// One-line description, e.g. the command line or target URL.
+    pub summary: String,
+    pub reasons: Vec<String>,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+impl PendingApproval {
+    /// Plain-text prompt for channels without buttons.
+    pub fn prompt_text(&self) -> String {
+        let mut out = format!("Dangerous tool call: {}\n\n{}", self.tool, self.summary);
+        for reason in &self.reasons {
+            out.push_str(&format!("\n• {}", reason));
+        }
+        out.push_str(&format!("\n\nReply: /approve yes {} | /approve no {}", self.id, self.id));
+        out
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ApprovalOutcome {
+    /// The tool is not dangerous; no approval needed.
+    NotRequired,
+    /// Answered earlier in this session with a session-scoped verdict.
+    Remembered { verdict: ApprovalVerdict },
+    Answered { id: String, verdict: ApprovalVerdict },
+    TimedOut { id: String, action: TimeoutAction },
+}
+
+impl ApprovalOutcome {
+    pub fn is_approved(&self) -> bool {
+        match self {
+            ApprovalOutcome::NotRequired => true,
+            ApprovalOutcome::Remembered { verdict } | ApprovalOutcome::Answered { verdict, .. } => verdict.is_allow(),
+            ApprovalOutcome::TimedOut { action, .. } => *action == TimeoutAction::Allow,
+        }
+    }
+}
+
+/// Live updates for the Control UI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApprovalEvent {
+    Requested(PendingApproval),
+    Resolved { id: String, verdict: Option<ApprovalVerdict>, timed_out: bool },
+}
+
+/// Delivers approval prompts somewhere a human will see them.
+#[async_trait]
+pub trait ApprovalNotifier: Send + Sync {
+    async fn deliver(&self, request: &PendingApproval) -> Result<()>;
+}
+
+struct Waiter {
+    request: PendingApproval,
+    reply: oneshot::Sender<ApprovalVerdict>,
+}
+
+pub struct ApprovalBroker {
+    policy: ApprovalPolicy,
+    notifiers: Vec<Arc<dyn ApprovalNotifier>>,
+    pending: Mutex<HashMap<String, Waiter>>,
+    /// Session-scoped verdicts, keyed by (session, tool).
+    remembered: Mutex<HashMap<(String, String), ApprovalVerdict>>,
+    events: broadcast::Sender<ApprovalEvent>,
+}
+
+impl Default for ApprovalBroker {
+    fn default() -> Self {
+        Self::new(ApprovalPolicy::default())
+    }
+}
+
+impl ApprovalBroker {
+    pub fn new(policy: ApprovalPolicy) -> Self {
+        let (events, _) = broadcast::channel(64);
+        Self {
+            policy,
+            notifiers: Vec::new(),
+            pending: Mutex::new(HashMap::new()),
+            remembered: Mutex::new(HashMap::new()),
+            events,
+        }
+    }
+
+    pub fn with_notifier(mut self, notifier: Arc<dyn ApprovalNotifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
+    pub fn needs_approval(&self, tool: &str) -> bool {
+        is_dangerous(tool)
+    }
+
+    /// Control UI feed of requests and resolutions.
+    pub fn subscribe(&self) -> broadcast::Receiver<ApprovalEvent> {
+        self.events.subscribe()
+    }
+
+    pub async fn pending(&self) -> Vec<PendingApproval> {
+        let mut list: Vec<_> = self.pending.lock().await.values().map(|w| w.request.clone()).collect();
+        list.sort_by_key(|r| r.created_at);
+        list
+    }
+
+    /// Ask for approval and wait for the answer. Returns immediately for
+    /// tools that are not dangerous or already decided for the session.
+    pub async fn request(
+        &self,
+        session_id: &str,
+        channel: Option<&str>,
+        tool: &str,
+        summary: &str,
+        reasons: Vec<String>,
+    ) -> ApprovalOutcome {
+        self.request_in_chat(session_id, channel, None, tool, summary, reasons).await
+    }
+
+    /// `request`, with the prompt addressed to the chat the run came from.
+    pub async fn request_in_chat(
+        &self,
+        session_id: &str,
+        channel: Option<&str>,
+        chat_id: Option<&str>,
+        tool: &str,
+        summary: &str,
+        reasons: Vec<String>,
+    ) -> ApprovalOutcome {
+        if !self.needs_approval(tool) {
+            return ApprovalOutcome::NotRequired;
+        }
+        let key = (session_id.to_string(), tool.to_lowercase());
+        if let Some(&verdict) = self.remembered.lock().await.get(&key) {
+            return ApprovalOutcome::Remembered { verdict };
+        }
+
+        let now = Utc::now().timestamp();
+        let request = PendingApproval {
+            // The id alone resolves the request, so it must not be guessable.
+            id: Uuid::new_v4().simple().to_string(),
+            session_id: session_id.to_string(),
+            channel: channel.map(str::to_string),
+            chat_id: chat_id.map(str::to_string),
+            tool: tool.to_string(),
+            summary: summary.to_string(),
+            reasons,
+            created_at: now,
+            expires_at: now + self.policy.timeout.as_secs() as i64,
+        };
+        let id = request.id.clone();
+        let (reply, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id.clone(), Waiter { request: request.clone(), reply });
+
+        info!("[Approval] {} waiting on approval for '{}' in session {}", id, tool, session_id);
+        let _ = self.events.send(ApprovalEvent::Requested(request.clone()));
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.deliver(&request).await {
+                warn!("[Approval] Failed to deliver {}: {:#}", id, e);
+            }
+        }
+
+        match tokio::time::timeout(self.policy.timeout, rx).await {
+            Ok(Ok(verdict)) => {
+                if verdict.is_session_scoped() {
+                    self.remembered.lock().await.insert(key, verdict);
+                }
+                ApprovalOutcome::Answered { id, verdict }
+            }
+            // Timed out, or the broker dropped the waiter.
+            _ => {
+                self.pending.lock().await.remove(&id);
+                let action = self.policy.on_timeout;
+                warn!("[Approval] {} timed out, applying {:?}", id, action);
+                let _ = self.events.send(ApprovalEvent::Resolved { id: id.clone(), verdict: None, timed_out: true });
+                ApprovalOutcome::TimedOut { id, action }
+            }
+        }
+    }
+
+    /// Record a verdict. Returns false when the request is unknown or already resolved.
+    pub async fn resolve(&self, id: &str, verdict: ApprovalVerdict) -> bool {
+        let Some(waiter) = self.pending.lock().await.remove(id) else {
+            return false;
+        };
+        self.finish(id, waiter, verdict)
+    }
+
+    /// Resolve `id` from a reply in `chat_id` on `channel`. A request started
+    /// from any other chat is left pending.
+    pub async fn resolve_in_chat(&self, id: &str, verdict: ApprovalVerdict, channel: &str, chat_id: &str) -> bool {
+        let waiter = {
+            let mut pending = self.pending.lock().await;
+            let from_chat = pending.get(id).is_some_and(|waiter| {
+                waiter.request.channel.as_deref() == Some(channel) && waiter.request.chat_id.as_deref() == Some(chat_id)
+            });
+            if !from_chat {
+                return false;
+            }
+            pending.remove(id)
+        };
+        waiter.is_some_and(|waiter| self.finish(id, waiter, verdict))
+    }
+
+    fn finish(&self, id: &str, waiter: Waiter, verdict: ApprovalVerdict) -> bool {
+        info!("[Approval] {} resolved: {}", id, verdict.as_str());
+        let _ = self.events.send(ApprovalEvent::Resolved { id: id.to_string(), verdict: Some(verdict), timed_out: false });
+        waiter.reply.send(verdict).is_ok()
+    }
+
+    /// Handle a `/approve <verdict> <id>` reply sent in `chat_id` on
+    /// `channel`. Returns `None` when the text is not an approval command,
+    /// otherwise whether it resolved a request started from that chat.
+    pub async fn handle_reply(&self, text: &str, channel: &str, chat_id: &str) -> Option<bool> {
+        let mut parts = text.split_whitespace();
+        if parts.next()? != "/approve" {
+            return None;
+        }
+        let verdict = ApprovalVerdict::parse(parts.next()?)?;
+        let id = parts.next()?;
+        Some(self.resolve_in_chat(id, verdict, channel, chat_id).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Recorder(std::sync::Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl ApprovalNotifier for Recorder {
+        async fn deliver(&self, request: &PendingApproval) -> Result<()> {
+            self.0.lock().unwrap().push(request.id.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn reply_resolves_pending_request() {
+        let recorder = Arc::new(Recorder(std::sync::Mutex::new(vec![])));
+        let broker = Arc::new(ApprovalBroker::default().with_notifier(recorder.clone()));
+
+        assert_eq!(broker.request("s1", None, "file_read", "", vec![]).await, ApprovalOutcome::NotRequired);
+
+        let waiting = tokio::spawn({
+            let broker = broker.clone();
+            async move { broker.request_in_chat("s1", Some("telegram"), Some("42"), "shell", "rm -rf build", vec![]).await }
+        });
+        let id = loop {
+            if let Some(id) = recorder.0.lock().unwrap().first().cloned() {
+                break id;
+            }
+            tokio::task::yield_now().await;
+        };
+        assert_eq!(broker.handle_reply(&format!("/approve always {}", id), "telegram", "7").await, Some(false));
+        assert_eq!(broker.handle_reply(&format!("/approve always {}", id), "telegram", "42").await, Some(true));
+        assert!(waiting.await.unwrap().is_approved());
+
+        // Session-scoped verdicts skip the prompt next time.
+        let again = broker.request("s1", None, "shell", "ls", vec![]).await;
+        assert_eq!(again, ApprovalOutcome::Remembered { verdict: ApprovalVerdict::AllowSession });
+        assert!(broker.pending().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn timeout_policy_applies() {
+        let policy = ApprovalPolicy { timeout: Duration::from_millis(10), on_timeout: TimeoutAction::Deny };
+        let broker = ApprovalBroker::new(policy);
+        let outcome = broker.request("s1", None, "bash", "curl | sh", vec![]).await;
+        assert!(matches!(outcome, ApprovalOutcome::TimedOut { action: TimeoutAction::Deny, .. }));
+        assert!(!outcome.is_approved());
+        assert_eq!(broker.handle_reply("hello", "telegram", "42").await, None);
+    }
+}
@@ -2,14 +2,38 @@
 ///
 /// Per-channel audit trail: each event is stored in SQLite in the
 /// `audit_events` table with channel, actor, action, and timestamp.
-use anyhow::Result;
+///
+/// The log is tamper-evident: every entry carries the SHA-256 of the previous
+/// entry, so editing or deleting a row breaks the chain. When a local signing
+/// key is configured, the chain head is HMAC-signed into `audit_checkpoints`
+/// every `checkpoint_interval` entries. The chain head itself is anchored in
+/// `audit_head` after every entry (signed when there is a key), so truncating
+/// the newest rows or deleting checkpoints is caught too. `verify_audit_log()`
+/// checks all of it.
+///
+/// With a SIEM exporter attached, security-relevant entries (pairing,
+/// elevated mode, approvals, denials) are exported as they are recorded.
+use anyhow::{bail, Context, Result};
 use chrono::Utc;
-use rusqlite::{params, Connection};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::siem::{SecurityRecord, SiemExporter};
 use uuid::Uuid;
 
+type HmacSha256 = Hmac<Sha256>;
+
+/// `prev_hash` of the first chained entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+const DEFAULT_CHECKPOINT_INTERVAL: u64 = 100;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditEvent {
     pub id: Uuid,
@@ -24,14 +48,12 @@ pub struct AuditEvent {
 
 pub struct AuditLog {
     conn: Mutex<Connection>,
+    signing_key: Option<Vec<u8>>,
+    checkpoint_interval: u64,
+    siem: Option<Arc<SiemExporter>>,
 }
 
-impl AuditLog {
-    pub fn open(path: &str) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        conn.execute_batch(
-            "PRAGMA journal_mode=WAL;
-             CREATE TABLE IF NOT EXISTS audit_events (
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS audit_events (
                  id        TEXT PRIMARY KEY,
                  channel   TEXT NOT NULL,
                  actor     TEXT NOT NULL,
@@ -42,27 +64,157 @@ impl AuditLog {
                  timestamp INTEGER NOT NULL
              );
              CREATE INDEX IF NOT EXISTS idx_audit_channel ON audit_events(channel);
-             CREATE INDEX IF NOT EXISTS idx_audit_ts ON audit_events(timestamp);",
-        )?;
-        Ok(Self { conn: Mutex::new(conn) })
+             CREATE INDEX IF NOT EXISTS idx_audit_ts ON audit_events(timestamp);
+             CREATE TABLE IF NOT EXISTS audit_checkpoints (
+                 seq        INTEGER PRIMARY KEY,
+                 hash       TEXT NOT NULL,
+                 signature  TEXT NOT NULL,
+                 created_at INTEGER NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS audit_head (
+                 id                  INTEGER PRIMARY KEY CHECK (id = 1),
+                 seq                 INTEGER NOT NULL,
+                 hash                TEXT NOT NULL,
+                 checkpoint_interval INTEGER NOT NULL,
+                 signature           TEXT NOT NULL
+             );";
+
+/// Add the chain columns to databases created before hash chaining.
+/// Rows written before the upgrade keep a NULL `seq` and are reported as legacy.
+fn migrate(conn: &Connection) -> Result<()> {
+    let columns: Vec<String> = conn
+        .prepare("PRAGMA table_info(audit_events)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<_>>()?;
+    for (name, ty) in [("seq", "INTEGER"), ("prev_hash", "TEXT"), ("hash", "TEXT")] {
+        if !columns.iter().any(|c| c == name) {
+            conn.execute_batch(&format!("ALTER TABLE audit_events ADD COLUMN {} {};", name, ty))?;
+        }
+    }
+    conn.execute_batch("CREATE UNIQUE INDEX IF NOT EXISTS idx_audit_seq ON audit_events(seq);")?;
+    Ok(())
+}
+
+/// Hash of one entry, covering its position, predecessor and every stored field.
+fn entry_hash(seq: i64, prev_hash: &str, event: &AuditEvent, detail: &str) -> String {
+    let canonical = serde_json::json!([
+        seq,
+        prev_hash,
+        event.id.to_string(),
+        event.channel,
+        event.actor,
+        event.action,
+        event.tool,
+        event.approved,
+        detail,
+        event.timestamp,
+    ]);
+    hex::encode(Sha256::digest(canonical.to_string().as_bytes()))
+}
+
+fn sign_checkpoint(key: &[u8], seq: i64, hash: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(format!("{}:{}", seq, hash).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn checkpoint_valid(key: &[u8], seq: i64, hash: &str, signature: &str) -> bool {
+    signature_valid(key, &format!("{}:{}", seq, hash), signature)
+}
+
+/// The head anchor also covers the checkpoint interval, so checkpoints the
+/// log should have written can be told apart from ones that were deleted.
+fn head_message(seq: i64, hash: &str, checkpoint_interval: u64) -> String {
+    format!("head:{}:{}:{}", seq, hash, checkpoint_interval)
+}
+
+fn signature_valid(key: &[u8], message: &str, signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(message.as_bytes());
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Read the hex-encoded checkpoint key at `path`, creating a random one
+/// (readable only by the owner) if it does not exist yet.
+pub fn load_or_create_signing_key(path: &Path) -> Result<Vec<u8>> {
+    if path.exists() {
+        let hex_key = std::fs::read_to_string(path)?;
+        return hex::decode(hex_key.trim()).with_context(|| format!("Invalid audit key in {}", path.display()));
+    }
+    let mut key = vec![0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, hex::encode(&key))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    info!("[Audit] Created checkpoint signing key at {}", path.display());
+    Ok(key)
+}
+
+impl AuditLog {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+        conn.execute_batch(SCHEMA)?;
+        migrate(&conn)?;
+        Ok(Self::with_connection(conn))
     }
 
     pub fn in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS audit_events (
-                 id TEXT, channel TEXT, actor TEXT, action TEXT,
-                 tool TEXT, approved INTEGER, detail TEXT, timestamp INTEGER
-             );",
-        )?;
-        Ok(Self { conn: Mutex::new(conn) })
+        conn.execute_batch(SCHEMA)?;
+        migrate(&conn)?;
+        Ok(Self::with_connection(conn))
+    }
+
+    fn with_connection(conn: Connection) -> Self {
+        Self { conn: Mutex::new(conn), signing_key: None, checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL, siem: None }
+    }
+
+    /// Sign a checkpoint of the chain head with `key` every `checkpoint_interval` entries.
+    pub fn with_signing_key(mut self, key: Vec<u8>) -> Self {
+        self.signing_key = Some(key);
+        self
+    }
+
+    pub fn with_checkpoint_interval(mut self, every: u64) -> Self {
+        self.checkpoint_interval = every.max(1);
+        self
+    }
+
+    /// Export security-relevant entries through `exporter` once recorded.
+    pub fn with_siem(mut self, exporter: Arc<SiemExporter>) -> Self {
+        self.siem = Some(exporter);
+        self
     }
 
     pub async fn record(&self, event: AuditEvent) -> Result<()> {
         let conn = self.conn.lock().await;
+        let head: Option<(i64, String)> = conn
+            .query_row(
+                "SELECT seq, hash FROM audit_events WHERE seq IS NOT NULL ORDER BY seq DESC LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let (seq, prev_hash) = match head {
+            Some((seq, hash)) => (seq + 1, hash),
+            None => (1, GENESIS_HASH.to_string()),
+        };
+        let detail = serde_json::to_string(&event.detail)?;
+        let hash = entry_hash(seq, &prev_hash, &event, &detail);
+
         conn.execute(
-            "INSERT INTO audit_events (id, channel, actor, action, tool, approved, detail, timestamp)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO audit_events (id, channel, actor, action, tool, approved, detail, timestamp, seq, prev_hash, hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
             params![
                 event.id.to_string(),
                 event.channel,
@@ -70,14 +222,82 @@ impl AuditLog {
                 event.action,
                 event.tool,
                 event.approved.map(|b| b as i32),
-                serde_json::to_string(&event.detail)?,
+                detail,
                 event.timestamp,
+                seq,
+                prev_hash,
+                hash,
             ],
         )?;
         info!("[Audit] {} {} in {}", event.actor, event.action, event.channel);
+
+        if let Some(key) = &self.signing_key {
+            if (seq as u64).is_multiple_of(self.checkpoint_interval) {
+                Self::write_checkpoint(&conn, key, seq, &hash)?;
+            }
+        }
+        self.write_head(&conn, seq, &hash)?;
+        drop(conn);
+
+        // The entry is stored either way; a failed export is only logged.
+        if let (Some(exporter), Some(record)) = (&self.siem, SecurityRecord::from_audit(&event)) {
+            let _ = exporter.export(&record).await;
+        }
+        Ok(())
+    }
+
+    /// Sign the current chain head now, e.g. on shutdown or from a timer.
+    /// Returns the checkpointed sequence number, if there is anything to sign.
+    pub async fn checkpoint(&self) -> Result<Option<i64>> {
+        let Some(key) = &self.signing_key else {
+            bail!("No audit signing key configured");
+        };
+        let conn = self.conn.lock().await;
+        let head: Option<(i64, String)> = conn
+            .query_row(
+                "SELECT seq, hash FROM audit_events WHERE seq IS NOT NULL ORDER BY seq DESC LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((seq, hash)) = head else {
+            return Ok(None);
+        };
+        Self::write_checkpoint(&conn, key, seq, &hash)?;
+        Ok(Some(seq))
+    }
+
+    /// Anchor the chain head; unsigned without a key.
+    fn write_head(&self, conn: &Connection, seq: i64, hash: &str) -> Result<()> {
+        let signature = match &self.signing_key {
+            Some(key) => {
+                let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+                mac.update(head_message(seq, hash, self.checkpoint_interval).as_bytes());
+                hex::encode(mac.finalize().into_bytes())
+            }
+            None => String::new(),
+        };
+        conn.execute(
+            "INSERT OR REPLACE INTO audit_head (id, seq, hash, checkpoint_interval, signature) VALUES (1, ?1, ?2, ?3, ?4)",
+            params![seq, hash, self.checkpoint_interval as i64, signature],
+        )?;
         Ok(())
     }
 
+    fn write_checkpoint(conn: &Connection, key: &[u8], seq: i64, hash: &str) -> Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO audit_checkpoints (seq, hash, signature, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![seq, hash, sign_checkpoint(key, seq, hash), Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    /// Walk the chain and checkpoints. Signatures are only checked when `key` is given.
+    pub async fn verify(&self, key: Option<&[u8]>) -> Result<AuditVerification> {
+        let conn = self.conn.lock().await;
+        verify_connection(&conn, key)
+    }
+
     pub async fn recent(&self, channel: &str, limit: usize) -> Result<Vec<AuditEvent>> {
         let conn = self.conn.lock().await;
         let mut stmt = conn.prepare(
@@ -131,6 +351,157 @@ impl AuditLog {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Verification
+// ---------------------------------------------------------------------------
+
+/// One inconsistency found while verifying the log.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditProblem {
+    /// The entry's contents no longer match its stored hash.
+    HashMismatch { seq: i64 },
+    /// The entry does not point at the hash of the entry before it.
+    BrokenLink { seq: i64 },
+    /// Sequence numbers skip, so entries were deleted.
+    MissingEntries { after: i64, next: i64 },
+    /// A checkpoint refers to an entry that is gone or has a different hash.
+    CheckpointMismatch { seq: i64 },
+    /// A checkpoint signature does not verify with the given key.
+    BadSignature { seq: i64 },
+    /// The log has entries but its head anchor is gone.
+    MissingHead,
+    /// The head anchor points past the newest entry or at a different hash,
+    /// so the newest entries were removed or replaced.
+    HeadMismatch { seq: i64 },
+    /// The head anchor's signature does not verify with the given key.
+    BadHeadSignature,
+    /// A checkpoint the signing log must have written is gone.
+    MissingCheckpoint { seq: i64 },
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditVerification {
+    /// Chained entries checked.
+    pub entries: u64,
+    /// Entries written before hash chaining, which cannot be verified.
+    pub legacy_entries: u64,
+    pub checkpoints: u64,
+    /// Whether checkpoint signatures were checked.
+    pub signatures_checked: bool,
+    pub problems: Vec<AuditProblem>,
+}
+
+impl AuditVerification {
+    pub fn is_intact(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Open the audit database at `path` and verify it.
+pub fn verify_audit_log(path: &str, key: Option<&[u8]>) -> Result<AuditVerification> {
+    let conn = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open audit log {}", path))?;
+    verify_connection(&conn, key)
+}
+
+fn verify_connection(conn: &Connection, key: Option<&[u8]>) -> Result<AuditVerification> {
+    let mut report = AuditVerification { signatures_checked: key.is_some(), ..Default::default() };
+    report.legacy_entries = conn.query_row("SELECT COUNT(*) FROM audit_events WHERE seq IS NULL", [], |row| row.get(0))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, channel, actor, action, tool, approved, detail, timestamp, seq, prev_hash, hash
+         FROM audit_events WHERE seq IS NOT NULL ORDER BY seq ASC",
+    )?;
+    let mut rows = stmt.query([])?;
+    let mut expected_prev = GENESIS_HASH.to_string();
+    let mut last_seq = 0i64;
+    while let Some(row) = rows.next()? {
+        let seq: i64 = row.get(8)?;
+        let prev_hash: String = row.get(9)?;
+        let hash: String = row.get(10)?;
+        let detail: String = row.get(6)?;
+        let event = AuditEvent {
+            id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap_or_default(),
+            channel: row.get(1)?,
+            actor: row.get(2)?,
+            action: row.get(3)?,
+            tool: row.get(4)?,
+            approved: row.get::<_, Option<i32>>(5)?.map(|v| v != 0),
+            detail: serde_json::Value::Null,
+            timestamp: row.get(7)?,
+        };
+
+        if seq != last_seq + 1 {
+            report.problems.push(AuditProblem::MissingEntries { after: last_seq, next: seq });
+        } else if prev_hash != expected_prev {
+            report.problems.push(AuditProblem::BrokenLink { seq });
+        }
+        if entry_hash(seq, &prev_hash, &event, &detail) != hash {
+            report.problems.push(AuditProblem::HashMismatch { seq });
+        }
+        report.entries += 1;
+        expected_prev = hash;
+        last_seq = seq;
+    }
+
+    let head: Option<(i64, String, i64, String)> = conn
+        .query_row("SELECT seq, hash, checkpoint_interval, signature FROM audit_head WHERE id = 1", [], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .optional()?;
+    // Checkpoints a signing log wrote at every multiple of its interval.
+    let mut expected_checkpoints = Vec::new();
+    match head {
+        None if report.entries > 0 => report.problems.push(AuditProblem::MissingHead),
+        None => {}
+        Some((seq, hash, interval, signature)) => {
+            let signed = !signature.is_empty();
+            if let Some(key) = key {
+                if !signature_valid(key, &head_message(seq, &hash, interval as u64), &signature) {
+                    report.problems.push(AuditProblem::BadHeadSignature);
+                }
+            }
+            if seq != last_seq || hash != expected_prev {
+                report.problems.push(AuditProblem::HeadMismatch { seq });
+            }
+            if signed && interval > 0 {
+                expected_checkpoints = (1..=seq / interval).map(|n| n * interval).collect();
+            }
+        }
+    }
+
+    let mut stmt = conn.prepare("SELECT seq, hash, signature FROM audit_checkpoints ORDER BY seq ASC")?;
+    let checkpoints = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for seq in expected_checkpoints {
+        if !checkpoints.iter().any(|(checkpointed, _, _)| *checkpointed == seq) {
+            report.problems.push(AuditProblem::MissingCheckpoint { seq });
+        }
+    }
+    for (seq, hash, signature) in checkpoints {
+        report.checkpoints += 1;
+        if let Some(key) = key {
+            if !checkpoint_valid(key, seq, &hash, &signature) {
+                report.problems.push(AuditProblem::BadSignature { seq });
+                continue;
+            }
+        }
+        let stored: Option<String> = conn
+            .query_row("SELECT hash FROM audit_events WHERE seq = ?1", params![seq], |row| row.get(0))
+            .optional()?;
+        if stored.as_deref() != Some(hash.as_str()) {
+            report.problems.push(AuditProblem::CheckpointMismatch { seq });
+        }
+    }
+
+    if !report.is_intact() {
+        warn!("[Audit] Verification found {} problem(s)", report.problems.len());
+    }
+    Ok(report)
+}
+
 /// Helper to create a new audit event with the current timestamp.
 pub fn new_event(channel: &str, actor: &str, action: &str) -> AuditEvent {
     AuditEvent {
@@ -144,3 +515,98 @@ pub fn new_event(channel: &str, actor: &str, action: &str) -> AuditEvent {
         timestamp: Utc::now().timestamp(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn log_with(n: usize) -> AuditLog {
+        let log = AuditLog::in_memory().unwrap().with_signing_key(b"k".to_vec()).with_checkpoint_interval(2);
+        for i in 0..n {
+            log.record(new_event("cli", "agent", &format!("action-{}", i))).await.unwrap();
+        }
+        log
+    }
+
+    #[tokio::test]
+    async fn untouched_log_verifies() {
+        let log = log_with(5).await;
+        let report = log.verify(Some(b"k")).await.unwrap();
+        assert!(report.is_intact(), "{:?}", report.problems);
+        assert_eq!((report.entries, report.checkpoints), (5, 2));
+    }
+
+    #[tokio::test]
+    async fn detects_edits_deletions_and_truncation() {
+        let log = log_with(5).await;
+        {
+            let conn = log.conn.lock().await;
+            conn.execute("UPDATE audit_events SET actor = 'someone-else' WHERE seq = 2", []).unwrap();
+        }
+        let problems = log.verify(Some(b"k")).await.unwrap().problems;
+        assert!(matches!(problems[..], [AuditProblem::HashMismatch { seq: 2 }]));
+
+        let log = log_with(5).await;
+        {
+            let conn = log.conn.lock().await;
+            conn.execute("DELETE FROM audit_events WHERE seq = 3", []).unwrap();
+            conn.execute("DELETE FROM audit_events WHERE seq = 5", []).unwrap();
+            conn.execute("DELETE FROM audit_events WHERE seq = 4", []).unwrap();
+        }
+        let problems = log.verify(Some(b"k")).await.unwrap().problems;
+        assert!(problems.iter().any(|p| matches!(p, AuditProblem::CheckpointMismatch { seq: 4 })));
+    }
+
+    #[tokio::test]
+    async fn detects_a_truncated_tail_and_deleted_anchors() {
+        let log = log_with(5).await;
+        {
+            let conn = log.conn.lock().await;
+            conn.execute("DELETE FROM audit_events WHERE seq = 5", []).unwrap();
+        }
+        let problems = log.verify(Some(b"k")).await.unwrap().problems;
+        assert!(matches!(problems[..], [AuditProblem::HeadMismatch { seq: 5 }]), "{:?}", problems);
+
+        let log = log_with(5).await;
+        {
+            let conn = log.conn.lock().await;
+            conn.execute("DELETE FROM audit_checkpoints WHERE seq = 2", []).unwrap();
+        }
+        let problems = log.verify(None).await.unwrap().problems;
+        assert!(matches!(problems[..], [AuditProblem::MissingCheckpoint { seq: 2 }]), "{:?}", problems);
+
+        let log = log_with(5).await;
+        {
+            let conn = log.conn.lock().await;
+            conn.execute("DELETE FROM audit_head", []).unwrap();
+        }
+        let problems = log.verify(Some(b"k")).await.unwrap().problems;
+        assert!(matches!(problems[..], [AuditProblem::MissingHead]), "{:?}", problems);
+    }
+
+    #[tokio::test]
+    async fn wrong_key_fails_signatures() {
+        let log = log_with(2).await;
+        let problems = log.verify(Some(b"other")).await.unwrap().problems;
+        assert!(matches!(problems[..], [AuditProblem::BadHeadSignature, AuditProblem::BadSignature { seq: 2 }]), "{:?}", problems);
+    }
+
+    #[tokio::test]
+    async fn security_entries_are_exported_to_the_siem() {
+        let path = std::env::temp_dir().join(format!("clawforge-audit-siem-{}.log", Uuid::new_v4()));
+        let exporter = Arc::new(SiemExporter::new(crate::siem::SiemFormat::Cef).with_file(&path));
+        let log = AuditLog::in_memory().unwrap().with_siem(exporter);
+        let mut paired = new_event("gateway", "device-1", "device_paired");
+        paired.approved = Some(true);
+        log.record(paired).await.unwrap();
+        log.record(new_event("telegram", "42", "elevated_mode_on")).await.unwrap();
+        log.record(new_event("cli", "agent", "file_read")).await.unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", written);
+        assert!(lines[0].contains("|pairing|") && lines[0].contains("device_paired"), "{}", lines[0]);
+        assert!(lines[1].contains("|elevated_mode|"), "{}", lines[1]);
+    }
+}
@@ -18,6 +18,9 @@ pub fn dangerous_tools() -> HashSet<&'static str> {
         "shell",
         "exec",
         "run_command",
+        "secret_exec",
+        // Package installation (runs third-party install scripts)
+        "pip_install",
         // Network + exfiltration risk
         "http_post",
         "http_put",
@@ -28,7 +31,15 @@ pub fn dangerous_tools() -> HashSet<&'static str> {
         "db_execute",
         // Credential / secret access
         "secret_read",
+        "secret_lease",
         "keychain_get",
+        // Desktop node access (clipboard contents, screen)
+        "clipboard_read",
+        "clipboard_write",
+        "screen_capture",
+        "mac_automation",
+        // Outbound phone calls (reach third parties, cost money)
+        "phone_call",
     ]
     .into_iter()
     .collect()
@@ -16,6 +16,10 @@ pub struct DmPolicy {
     pub allow_all: bool,
     /// If true, block all DMs entirely.
     pub block_all: bool,
+    /// If true, senders outside the allowlist may pair with a one-time code
+    /// instead of being dropped.
+    #[serde(default)]
+    pub pairing: bool,
 }
 
 impl Default for DmPolicy {
@@ -24,11 +28,29 @@ impl Default for DmPolicy {
             allowlist: HashSet::new(),
             allow_all: true, // open by default (same as OpenClaw)
             block_all: false,
+            pairing: false,
         }
     }
 }
 
 impl DmPolicy {
+    /// Build a policy from a config mode: `"open"`, `"pairing"`, `"allowlist"`
+    /// or `"disabled"`. Unknown modes fall back to `"pairing"`.
+    pub fn from_mode(mode: &str, allow_from: impl IntoIterator<Item = String>) -> Self {
+        let allowlist = allow_from.into_iter().collect();
+        match mode {
+            "open" => Self { allowlist, ..Default::default() },
+            "allowlist" => Self { allowlist, allow_all: false, ..Default::default() },
+            "disabled" => Self { allowlist, allow_all: false, block_all: true, pairing: false },
+            other => {
+                if other != "pairing" {
+                    warn!("[DmPolicy] Unknown DM policy '{}', using pairing", other);
+                }
+                Self { allowlist, allow_all: false, pairing: true, ..Default::default() }
+            }
+        }
+    }
+
     /// Returns `true` if the given sender is permitted to invoke the agent.
     pub fn is_allowed(&self, sender: &str) -> bool {
         if self.block_all {
@@ -69,6 +91,16 @@ mod tests {
         assert!(!policy.is_allowed("stranger@evil.com"));
     }
 
+    #[test]
+    fn test_from_mode() {
+        let policy = DmPolicy::from_mode("pairing", vec!["owner".to_string()]);
+        assert!(policy.pairing);
+        assert!(policy.is_allowed("owner"));
+        assert!(!policy.is_allowed("stranger"));
+        assert!(DmPolicy::from_mode("disabled", vec![]).block_all);
+        assert!(!DmPolicy::from_mode("allowlist", vec![]).pairing);
+    }
+
     #[test]
     fn test_block_all() {
         let policy = DmPolicy { block_all: true, ..Default::default() };
@@ -0,0 +1,476 @@
+//! Cross-channel identities.
+//!
+//! A person who talks to the assistant from Telegram, Slack and iMessage shows
+//! up as three unrelated sender ids. The registry links those accounts into a
+//! single `Person` so that everything keyed on "who is this" — memory, DM
+//! policy, budgets, permissions — can key on `principal()` instead of the raw
+//! platform account.
+//!
+//! Accounts are linked explicitly: a known account asks for a short-lived link
+//! code and the person repeats it from the other account. An account that
+//! sends too many wrong codes is locked out of linking for a while, and any
+//! code it sends meanwhile is burnt. The registry also
+//! collects display names and contact handles it sees and suggests links
+//! between accounts that look like the same person; suggestions are never
+//! applied on their own.
+
+use anyhow::{bail, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Length of a link code.
+pub const LINK_CODE_LEN: usize = 8;
+
+/// Wrong codes an account may send before it is locked out of linking.
+pub const MAX_LINK_FAILURES: u32 = 5;
+
+/// Link code characters: uppercase letters and digits, minus look-alikes.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+fn gen_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..LINK_CODE_LEN).map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char).collect()
+}
+
+/// Whether `code` looks like a link code, in either case.
+pub fn is_link_code(code: &str) -> bool {
+    code.len() == LINK_CODE_LEN && code.bytes().all(|b| CODE_ALPHABET.contains(&b.to_ascii_uppercase()))
+}
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// One platform account: a sender id on a channel.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct AccountRef {
+    pub channel: String,
+    pub sender: String,
+}
+
+impl AccountRef {
+    pub fn new(channel: impl Into<String>, sender: impl Into<String>) -> Self {
+        Self { channel: channel.into(), sender: sender.into() }
+    }
+
+    /// `channel:sender`, the same form the DM gate uses for device ids.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.channel, self.sender)
+    }
+}
+
+/// A human behind one or more linked accounts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Person {
+    /// Stable principal, `person:<uuid>`.
+    pub id: String,
+    pub display_name: Option<String>,
+    pub accounts: Vec<AccountRef>,
+    pub created_at: u64,
+}
+
+/// Two accounts that look like the same person.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkSuggestion {
+    pub a: AccountRef,
+    pub b: AccountRef,
+    /// Why they matched, e.g. `same email alice@example.com`.
+    pub reason: String,
+}
+
+struct PendingLink {
+    account: AccountRef,
+    expires_at: u64,
+}
+
+/// Wrong link codes an account has sent in the current window.
+struct LinkFailures {
+    count: u32,
+    window_ends: u64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    people: Vec<Person>,
+}
+
+#[derive(Default)]
+struct State {
+    people: HashMap<String, Person>,
+    /// Account key → person id.
+    index: HashMap<String, String>,
+    /// Link code → account that asked for it.
+    pending: HashMap<String, PendingLink>,
+    /// Account trying codes → its recent wrong ones.
+    failures: HashMap<AccountRef, LinkFailures>,
+    /// Account → display name last seen for it.
+    hints: BTreeMap<AccountRef, String>,
+}
+
+impl State {
+    fn linked_accounts(&self) -> impl Iterator<Item = &AccountRef> {
+        self.people.values().flat_map(|p| p.accounts.iter())
+    }
+
+    fn person_id(&self, account: &AccountRef) -> Option<&String> {
+        self.index.get(&account.key())
+    }
+
+    /// The person `account` belongs to, creating a single-account one if needed.
+    fn ensure_person(&mut self, account: &AccountRef) -> String {
+        if let Some(id) = self.person_id(account) {
+            return id.clone();
+        }
+        let id = format!("person:{}", Uuid::new_v4());
+        let person = Person {
+            id: id.clone(),
+            display_name: self.hints.get(account).cloned(),
+            accounts: vec![account.clone()],
+            created_at: now_secs(),
+        };
+        self.index.insert(account.key(), id.clone());
+        self.people.insert(id.clone(), person);
+        id
+    }
+
+    /// Move every account of `from` into `into` and drop `from`.
+    fn merge(&mut self, into: &str, from: &str) {
+        if into == from {
+            return;
+        }
+        let Some(from) = self.people.remove(from) else { return };
+        for account in &from.accounts {
+            self.index.insert(account.key(), into.to_string());
+        }
+        if let Some(person) = self.people.get_mut(into) {
+            person.accounts.extend(from.accounts);
+            if person.display_name.is_none() {
+                person.display_name = from.display_name;
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Registry
+// ---------------------------------------------------------------------------
+
+pub struct IdentityRegistry {
+    state: RwLock<State>,
+    path: Option<PathBuf>,
+    /// Link code validity window (seconds).
+    pub code_ttl_secs: u64,
+}
+
+impl IdentityRegistry {
+    /// In-memory registry.
+    pub fn new(code_ttl_secs: u64) -> Self {
+        Self { state: RwLock::new(State::default()), path: None, code_ttl_secs }
+    }
+
+    /// `~/.clawforge/identities.json` with ten-minute link codes, or
+    /// in-memory without a home dir.
+    pub fn open_default() -> Self {
+        const CODE_TTL_SECS: u64 = 600;
+        match std::env::var_os("HOME") {
+            Some(home) => Self::open(PathBuf::from(home).join(".clawforge").join("identities.json"), CODE_TTL_SECS),
+            None => Self::new(CODE_TTL_SECS),
+        }
+    }
+
+    /// Load linked people from `path` (missing or unreadable files start
+    /// empty) and save every change back to it.
+    pub fn open(path: impl Into<PathBuf>, code_ttl_secs: u64) -> Self {
+        let path = path.into();
+        let file: RegistryFile = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        let mut state = State::default();
+        for person in file.people {
+            for account in &person.accounts {
+                state.index.insert(account.key(), person.id.clone());
+            }
+            state.people.insert(person.id.clone(), person);
+        }
+        Self { state: RwLock::new(state), path: Some(path), code_ttl_secs }
+    }
+
+    /// Key to attribute `sender` on `channel` to: the linked person's id, or
+    /// `channel:sender` for accounts that are not linked to anything.
+    pub fn principal(&self, channel: &str, sender: &str) -> String {
+        let account = AccountRef::new(channel, sender);
+        self.state.read().unwrap().person_id(&account).cloned().unwrap_or_else(|| account.key())
+    }
+
+    /// The person `sender` on `channel` is linked to, if any.
+    pub fn person_for(&self, channel: &str, sender: &str) -> Option<Person> {
+        let state = self.state.read().unwrap();
+        let id = state.person_id(&AccountRef::new(channel, sender))?;
+        state.people.get(id).cloned()
+    }
+
+    pub fn person(&self, id: &str) -> Option<Person> {
+        self.state.read().unwrap().people.get(id).cloned()
+    }
+
+    pub fn people(&self) -> Vec<Person> {
+        let mut people: Vec<Person> = self.state.read().unwrap().people.values().cloned().collect();
+        people.sort_by_key(|p| p.created_at);
+        people
+    }
+
+    /// Link two accounts as the same person, merging whatever each was
+    /// already linked to. Returns the person id.
+    pub fn link(&self, a: &AccountRef, b: &AccountRef) -> Result<String> {
+        if a == b {
+            bail!("Cannot link {} to itself", a.key());
+        }
+        let id = {
+            let mut state = self.state.write().unwrap();
+            let into = state.ensure_person(a);
+            let from = state.ensure_person(b);
+            state.merge(&into, &from);
+            into
+        };
+        info!("[Identity] Linked {} and {} as {}", a.key(), b.key(), id);
+        self.save();
+        Ok(id)
+    }
+
+    /// Detach an account from its person. Returns false if it was not linked.
+    pub fn unlink(&self, account: &AccountRef) -> bool {
+        {
+            let mut state = self.state.write().unwrap();
+            let Some(id) = state.index.remove(&account.key()) else { return false };
+            let empty = state.people.get_mut(&id).map(|person| {
+                person.accounts.retain(|a| a != account);
+                person.accounts.is_empty()
+            });
+            if empty == Some(true) {
+                state.people.remove(&id);
+            }
+        }
+        info!("[Identity] Unlinked {}", account.key());
+        self.save();
+        true
+    }
+
+    pub fn set_display_name(&self, person_id: &str, name: impl Into<String>) -> Result<()> {
+        {
+            let mut state = self.state.write().unwrap();
+            let Some(person) = state.people.get_mut(person_id) else { bail!("Unknown person {}", person_id) };
+            person.display_name = Some(name.into());
+        }
+        self.save();
+        Ok(())
+    }
+
+    /// Issue a link code for `account`; repeating it from another account
+    /// links the two.
+    pub fn begin_link(&self, account: AccountRef) -> String {
+        let mut state = self.state.write().unwrap();
+        let now = now_secs();
+        state.pending.retain(|_, p| p.expires_at > now);
+        let code = loop {
+            let code = gen_code();
+            if !state.pending.contains_key(&code) {
+                break code;
+            }
+        };
+        let expires_at = now + self.code_ttl_secs;
+        state.pending.insert(code.clone(), PendingLink { account, expires_at });
+        code
+    }
+
+    /// Redeem a link code from `account`. Codes are single-use. After
+    /// `MAX_LINK_FAILURES` wrong codes the account is refused until a code
+    /// lifetime has passed, and the codes it sends are invalidated unused.
+    pub fn complete_link(&self, code: &str, account: &AccountRef) -> Result<Person> {
+        let code = code.trim().to_ascii_uppercase();
+        let pending = {
+            let mut state = self.state.write().unwrap();
+            let now = now_secs();
+            state.failures.retain(|_, f| f.window_ends > now);
+            let pending = state.pending.remove(&code);
+            if state.failures.get(account).is_some_and(|f| f.count >= MAX_LINK_FAILURES) {
+                warn!("[Identity] Link attempt from locked-out {}", account.key());
+                bail!("Too many wrong link codes; ask for a new code later");
+            }
+            if pending.is_none() {
+                let ttl = self.code_ttl_secs;
+                let failures = state.failures.entry(account.clone()).or_insert(LinkFailures { count: 0, window_ends: now + ttl });
+                failures.count += 1;
+            } else {
+                state.failures.remove(account);
+            }
+            pending
+        };
+        let Some(pending) = pending else {
+            warn!("[Identity] Unknown link code from {}", account.key());
+            bail!("Invalid or expired link code");
+        };
+        if pending.expires_at <= now_secs() {
+            bail!("Link code expired");
+        }
+        if pending.account == *account {
+            bail!("Send the code from the other account you want to link");
+        }
+        let id = self.link(&pending.account, account)?;
+        self.person(&id).ok_or_else(|| anyhow::anyhow!("Person {} vanished while linking", id))
+    }
+
+    /// Record the display name a channel reports for a sender, for suggestions.
+    pub fn observe(&self, channel: &str, sender: &str, display_name: &str) {
+        let display_name = display_name.trim();
+        if display_name.is_empty() {
+            return;
+        }
+        let mut state = self.state.write().unwrap();
+        let account = AccountRef::new(channel, sender);
+        if let Some(person) = state.person_id(&account).cloned().and_then(|id| state.people.get_mut(&id)) {
+            person.display_name.get_or_insert_with(|| display_name.to_string());
+        }
+        state.hints.insert(account, display_name.to_string());
+    }
+
+    /// Pairs of accounts on different channels, not yet linked, that share
+    /// an email, a phone number or a display name.
+    pub fn suggest_links(&self) -> Vec<LinkSuggestion> {
+        let state = self.state.read().unwrap();
+        let mut by_handle: BTreeMap<String, Vec<&AccountRef>> = BTreeMap::new();
+        for account in state.linked_accounts().chain(state.hints.keys()) {
+            for handle in handles(account, state.hints.get(account).map(String::as_str)) {
+                let accounts = by_handle.entry(handle).or_default();
+                if !accounts.contains(&account) {
+                    accounts.push(account);
+                }
+            }
+        }
+
+        let mut suggestions: Vec<LinkSuggestion> = Vec::new();
+        for (handle, accounts) in &by_handle {
+            for (i, a) in accounts.iter().enumerate() {
+                for b in &accounts[i + 1..] {
+                    let same_person = matches!((state.person_id(a), state.person_id(b)), (Some(x), Some(y)) if x == y);
+                    let seen = suggestions.iter().any(|s| (&s.a, &s.b) == (*a, *b) || (&s.a, &s.b) == (*b, *a));
+                    if a.channel == b.channel || same_person || seen {
+                        continue;
+                    }
+                    let (a, b) = if a <= b { (a, b) } else { (b, a) };
+                    suggestions.push(LinkSuggestion { a: (*a).clone(), b: (*b).clone(), reason: handle.replacen(':', " ", 1) });
+                }
+            }
+        }
+        suggestions
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else { return };
+        let mut people: Vec<Person> = self.state.read().unwrap().people.values().cloned().collect();
+        people.sort_by(|a, b| a.id.cmp(&b.id));
+        let result = serde_json::to_string_pretty(&RegistryFile { people }).map_err(anyhow::Error::from).and_then(|json| {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, json)?;
+            Ok(())
+        });
+        if let Err(e) = result {
+            warn!("[Identity] Failed to save {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Normalized handles an account can be matched on: `same email ...`,
+/// `same phone ...`, `same name ...`.
+fn handles(account: &AccountRef, display_name: Option<&str>) -> Vec<String> {
+    let mut out = Vec::new();
+    for raw in [Some(account.sender.as_str()), display_name].into_iter().flatten() {
+        let raw = raw.trim().trim_start_matches("mailto:").trim_start_matches("tel:");
+        if raw.contains('@') && raw.contains('.') && !raw.starts_with('@') {
+            out.push(format!("same email:{}", raw.to_lowercase()));
+            continue;
+        }
+        let digits: String = raw.chars().filter(char::is_ascii_digit).collect();
+        let phone_like = raw.chars().all(|c| c.is_ascii_digit() || " +-().".contains(c));
+        if phone_like && (7..=15).contains(&digits.len()) {
+            out.push(format!("same phone:{}", digits));
+        }
+    }
+    if let Some(name) = display_name {
+        let name: String = name
+            .split_whitespace()
+            .map(|w| w.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase())
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        // Single short words ("Al", "me") match too many strangers.
+        if name.contains(' ') || name.chars().count() >= 5 {
+            out.push(format!("same name:{}", name));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_codes_merge_accounts_and_suggestions_skip_linked_pairs() {
+        let registry = IdentityRegistry::new(300);
+        let telegram = AccountRef::new("telegram", "4242");
+        let slack = AccountRef::new("slack", "U01ALICE");
+        let imessage = AccountRef::new("imessage", "+1 (555) 010-9999");
+        assert_eq!(registry.principal("slack", "U01ALICE"), "slack:U01ALICE");
+
+        let code = registry.begin_link(telegram.clone());
+        assert!(registry.complete_link(&code, &telegram).is_err());
+        let code = registry.begin_link(telegram.clone());
+        let person = registry.complete_link(&code, &slack).unwrap();
+        assert_eq!(registry.principal("slack", "U01ALICE"), person.id);
+        assert_eq!(registry.principal("telegram", "4242"), person.id);
+        assert!(registry.complete_link(&code, &imessage).is_err(), "codes are single-use");
+        assert!(is_link_code(&code) && code.len() == 8);
+
+        registry.observe("slack", "U01ALICE", "Alice Liddell");
+        registry.observe("telegram", "4242", "alice liddell");
+        registry.observe("whatsapp", "15550109999", "Ally");
+        registry.observe("imessage", "+1 (555) 010-9999", "Alice L.");
+        let suggestions = registry.suggest_links();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!((&suggestions[0].a, &suggestions[0].b), (&imessage, &AccountRef::new("whatsapp", "15550109999")));
+        assert_eq!(suggestions[0].reason, "same phone 15550109999");
+
+        assert!(registry.unlink(&telegram));
+        assert_eq!(registry.principal("telegram", "4242"), "telegram:4242");
+        assert_eq!(registry.person(&person.id).unwrap().accounts, vec![slack]);
+    }
+
+    #[test]
+    fn wrong_codes_lock_the_account_out_and_burn_its_codes() {
+        let registry = IdentityRegistry::new(300);
+        let owner = AccountRef::new("telegram", "4242");
+        let guesser = AccountRef::new("slack", "U0MALLORY");
+        let code = registry.begin_link(owner.clone());
+        for _ in 0..MAX_LINK_FAILURES {
+            assert!(registry.complete_link("AAAAAAAA", &guesser).unwrap_err().to_string().contains("Invalid"));
+        }
+        let err = registry.complete_link(&code.to_lowercase(), &guesser).unwrap_err();
+        assert!(err.to_string().contains("Too many"));
+        assert!(registry.complete_link(&code, &AccountRef::new("imessage", "a@b.c")).is_err(), "the code was burnt");
+        assert_eq!(registry.principal("slack", "U0MALLORY"), "slack:U0MALLORY");
+    }
+}
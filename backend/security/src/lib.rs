@@ -1,19 +1,31 @@
+pub mod approval;
 pub mod audit;
 pub mod auto_fix;
 pub mod channel_audit;
 pub mod dangerous_tools;
 pub mod dm_policy;
 pub mod external_content;
+pub mod identity;
+pub mod network;
 pub mod pairing;
+pub mod posture;
+pub mod preferences;
 pub mod setup_code;
+pub mod siem;
 pub mod skill_scanner;
 
-pub use audit::{new_event, AuditEvent, AuditLog};
+pub use approval::{ApprovalBroker, ApprovalEvent, ApprovalNotifier, ApprovalOutcome, ApprovalPolicy, ApprovalVerdict, PendingApproval, TimeoutAction};
+pub use audit::{load_or_create_signing_key, new_event, verify_audit_log, AuditEvent, AuditLog, AuditProblem, AuditVerification};
 pub use auto_fix::{auto_fix, has_blocking_findings, AutoFixResult};
 pub use channel_audit::{audit_all_channels, audit_discord, audit_slack, audit_telegram, AuditFinding, AuditSeverity, ChannelAuditResult};
 pub use dangerous_tools::{dangerous_tools, is_dangerous, is_safe_kind};
 pub use dm_policy::DmPolicy;
-pub use external_content::scan_external_content;
+pub use external_content::{quarantine, scan_external_content, ContentPolicy, ContentVerdict, ExternalContentGuard, GuardedContent, InjectionClassifier, LlmInjectionClassifier};
+pub use identity::{is_link_code, AccountRef, IdentityRegistry, LinkSuggestion, Person};
+pub use posture::{load_skill_sources, security_posture, PostureFinding, PostureInput, SecurityPosture, SeverityGroup, SuggestedFix};
+pub use network::is_internal_address;
 pub use pairing::{PairedDevice, PairingStore, PendingCode};
+pub use preferences::{PreferenceStore, Preferences, PREFERENCE_KEYS, SESSION_PREFERENCE_KEYS};
 pub use setup_code::{generate_code, generate_session_token, SetupCode, SetupCodeStore};
-pub use skill_scanner::scan_skill;
+pub use siem::{to_cef, to_ocsf, SecurityCategory, SecurityOutcome, SecurityRecord, SiemExportConfig, SiemExporter, SiemFormat};
+pub use skill_scanner::{scan_skill, sign_skill_dir, skill_digest, verify_skill_dir, SignatureStatus, SkillSignature, SkillVerification, TrustedKey};
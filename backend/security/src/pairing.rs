@@ -1,9 +1,12 @@
 /// Device pairing system — one-time setup codes and device token issuance.
 ///
 /// Mirrors `src/pairing/pairing-store.ts` + `setup-code.ts` from OpenClaw.
+/// Paired devices and their tokens survive restarts when the store is
+/// opened on a file; pending codes are short-lived and stay in memory.
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{info, warn};
@@ -23,11 +26,7 @@ fn gen_code() -> String {
 }
 
 fn gen_token() -> String {
-    let h = {
-        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
-        format!("{:x}{:x}", nanos, nanos.wrapping_mul(0xdeadbeef))
-    };
-    format!("cf_{}", h)
+    format!("cf_{}", crate::setup_code::generate_session_token())
 }
 
 // ---------------------------------------------------------------------------
@@ -49,6 +48,12 @@ pub struct PairedDevice {
     pub paired_at: u64,
 }
 
+/// On-disk form of the paired devices.
+#[derive(Default, Serialize, Deserialize)]
+struct DevicesFile {
+    devices: Vec<PairedDevice>,
+}
+
 #[derive(Debug, Default)]
 pub struct PairingStore {
     pending: Arc<RwLock<HashMap<String, PendingCode>>>,     // code → PendingCode
@@ -56,13 +61,44 @@ pub struct PairingStore {
     tokens: Arc<RwLock<HashMap<String, String>>>,          // token → device_id
     /// Code validity window (seconds).
     pub code_ttl_secs: u64,
+    /// File paired devices are saved to, if any.
+    path: Option<PathBuf>,
 }
 
 impl PairingStore {
+    /// A store that keeps paired devices in memory only.
     pub fn new(code_ttl_secs: u64) -> Self {
         Self { code_ttl_secs, ..Default::default() }
     }
 
+    /// Open the store at `~/.clawforge/devices.json`, or in memory only when
+    /// there is no home directory.
+    pub fn open_default(code_ttl_secs: u64) -> Self {
+        match std::env::var_os("HOME") {
+            Some(home) => Self::open(PathBuf::from(home).join(".clawforge").join("devices.json"), code_ttl_secs),
+            None => Self::new(code_ttl_secs),
+        }
+    }
+
+    /// Load paired devices from `path` (missing or unreadable files start
+    /// empty) and save every pairing and revocation back to it.
+    pub fn open(path: impl Into<PathBuf>, code_ttl_secs: u64) -> Self {
+        let path = path.into();
+        let file: DevicesFile = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        let tokens = file.devices.iter().map(|d| (d.token.clone(), d.device_id.clone())).collect();
+        let devices = file.devices.into_iter().map(|d| (d.device_id.clone(), d)).collect();
+        Self {
+            devices: Arc::new(RwLock::new(devices)),
+            tokens: Arc::new(RwLock::new(tokens)),
+            code_ttl_secs,
+            path: Some(path),
+            ..Default::default()
+        }
+    }
+
     /// Generate a new one-time pairing code.
     pub fn generate_code(&self, label: Option<&str>) -> PendingCode {
         let code = gen_code();
@@ -78,6 +114,9 @@ impl PairingStore {
 
     /// Verify a submitted code and, if valid, issue a device token.
     pub fn verify_code(&self, code: &str, device_id: &str) -> Result<PairedDevice> {
+        if self.is_paired(device_id) {
+            bail!("Device '{}' is already paired", device_id);
+        }
         let mut pending = self.pending.write().unwrap();
         let entry = pending.remove(code).ok_or_else(|| anyhow::anyhow!("Invalid or expired code"))?;
 
@@ -93,8 +132,32 @@ impl PairingStore {
             paired_at: now_secs(),
         };
 
+        drop(pending);
         self.devices.write().unwrap().insert(device_id.to_string(), device.clone());
         self.tokens.write().unwrap().insert(token, device_id.to_string());
+        self.save();
+        info!("[Pairing] Device '{}' paired successfully", device_id);
+        Ok(device)
+    }
+
+    /// Issue a device token directly, for callers that verified the device
+    /// some other way (e.g. a consumed setup code). An already paired ID is
+    /// refused rather than having its token replaced.
+    pub fn register_device(&self, device_id: &str, label: Option<String>) -> Result<PairedDevice> {
+        let mut devices = self.devices.write().unwrap();
+        if devices.contains_key(device_id) {
+            bail!("Device '{}' is already paired", device_id);
+        }
+        let device = PairedDevice {
+            device_id: device_id.to_string(),
+            token: gen_token(),
+            label,
+            paired_at: now_secs(),
+        };
+        devices.insert(device_id.to_string(), device.clone());
+        self.tokens.write().unwrap().insert(device.token.clone(), device_id.to_string());
+        drop(devices);
+        self.save();
         info!("[Pairing] Device '{}' paired successfully", device_id);
         Ok(device)
     }
@@ -104,17 +167,66 @@ impl PairingStore {
         self.tokens.read().unwrap().get(token).cloned()
     }
 
-    /// Revoke a paired device.
-    pub fn revoke(&self, device_id: &str) {
-        let mut devices = self.devices.write().unwrap();
-        let mut tokens = self.tokens.write().unwrap();
-        if let Some(device) = devices.remove(device_id) {
-            tokens.remove(&device.token);
-            warn!("[Pairing] Revoked device '{}'", device_id);
-        }
+    /// Whether `device_id` has completed pairing and not been revoked.
+    pub fn is_paired(&self, device_id: &str) -> bool {
+        self.devices.read().unwrap().contains_key(device_id)
+    }
+
+    /// Revoke a paired device. Returns whether it was paired.
+    pub fn revoke(&self, device_id: &str) -> bool {
+        let Some(device) = self.devices.write().unwrap().remove(device_id) else {
+            return false;
+        };
+        self.tokens.write().unwrap().remove(&device.token);
+        self.save();
+        warn!("[Pairing] Revoked device '{}'", device_id);
+        true
     }
 
     pub fn list_devices(&self) -> Vec<PairedDevice> {
         self.devices.read().unwrap().values().cloned().collect()
     }
+
+    /// Write paired devices to the store's file, readable by the owner only
+    /// since it holds their tokens.
+    fn save(&self) {
+        let Some(path) = &self.path else { return };
+        let mut devices = self.list_devices();
+        devices.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        let result = serde_json::to_string_pretty(&DevicesFile { devices }).map_err(anyhow::Error::from).and_then(|json| {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, json)?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            warn!("[Pairing] Failed to save {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paired_devices_and_revocations_survive_reopening() {
+        let path = std::env::temp_dir().join(format!("clawforge-devices-{}.json", uuid::Uuid::new_v4()));
+        let store = PairingStore::open(&path, 60);
+        let phone = store.register_device("phone", Some("Phone".into())).unwrap();
+        store.register_device("laptop", None).unwrap();
+        assert!(store.revoke("laptop"));
+
+        let reopened = PairingStore::open(&path, 60);
+        assert_eq!(reopened.validate_token(&phone.token).as_deref(), Some("phone"));
+        assert!(!reopened.is_paired("laptop"));
+        assert!(reopened.register_device("phone", None).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
 }
@@ -1,22 +1,23 @@
 //! Setup code generation and validation for device pairing.
 //!
-//! Generates short, human-readable setup codes for first-time device pairing.
+//! Generates grouped, human-readable setup codes for first-time device
+//! pairing. Codes carry 80 random bits, so they cannot be guessed within
+//! their lifetime; wrong codes are still limited per source.
 //! Mirrors `src/pairing/setup-code.ts`.
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
-use once_cell::sync::Lazy;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// A generated setup code.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetupCode {
-    /// The display code shown to the user (e.g., "BLUE-TIGER-42").
+    /// The display code shown to the user (e.g., "K7QM-2XPD-RW9B-HT4N").
     pub code: String,
     /// The session token to use after verification.
     pub session_token: String,
@@ -35,24 +36,21 @@ impl SetupCode {
     }
 }
 
-/// Adjectives and nouns for memorable code generation.
-const ADJECTIVES: &[&str] = &[
-    "BLUE", "RED", "SWIFT", "BRIGHT", "CALM", "BOLD", "COOL", "DARK",
-    "FAST", "FINE", "GOLD", "HARD", "HIGH", "KEEN", "LOUD", "MILD",
-];
+/// Code characters: uppercase letters and digits, minus look-alikes.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
 
-const NOUNS: &[&str] = &[
-    "TIGER", "EAGLE", "SHARK", "WOLF", "BEAR", "HAWK", "LION", "RAVEN",
-    "FOX", "OWL", "SEAL", "STAG", "SWAN", "TOAD", "WREN", "LYNX",
-];
+/// Groups in a setup code, and characters per group.
+const CODE_GROUPS: usize = 4;
+const GROUP_LEN: usize = 4;
 
-/// Generate a human-readable code: ADJECTIVE-NOUN-NN (e.g., "BLUE-TIGER-42").
+/// Generate a setup code: four groups of four characters from a 32-symbol
+/// alphabet (e.g., "K7QM-2XPD-RW9B-HT4N"), 80 bits in all.
 pub fn generate_code() -> String {
     let mut rng = rand::thread_rng();
-    let adj = ADJECTIVES[rng.gen_range(0..ADJECTIVES.len())];
-    let noun = NOUNS[rng.gen_range(0..NOUNS.len())];
-    let num: u8 = rng.gen_range(10..99);
-    format!("{adj}-{noun}-{num}")
+    let groups: Vec<String> = (0..CODE_GROUPS)
+        .map(|_| (0..GROUP_LEN).map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char).collect())
+        .collect();
+    groups.join("-")
 }
 
 /// Generate a cryptographically-random session token.
@@ -66,10 +64,17 @@ pub fn generate_session_token() -> String {
     })
 }
 
+/// Wrong codes tolerated from one source (e.g. a client IP) per code
+/// lifetime. This stops a caller from hammering the endpoint without letting
+/// it lock everyone else out.
+pub const MAX_FAILED_ATTEMPTS: u32 = 10;
+
 /// In-memory store of pending setup codes.
 pub struct SetupCodeStore {
     codes: RwLock<HashMap<String, SetupCode>>,
     code_lifetime: Duration,
+    /// Failed attempts per source and when the source's window started.
+    failures: RwLock<HashMap<String, (u32, DateTime<Utc>)>>,
 }
 
 impl SetupCodeStore {
@@ -77,6 +82,7 @@ impl SetupCodeStore {
         Self {
             codes: RwLock::new(HashMap::new()),
             code_lifetime: Duration::minutes(lifetime_minutes),
+            failures: RwLock::new(HashMap::new()),
         }
     }
 
@@ -96,20 +102,30 @@ impl SetupCodeStore {
         entry
     }
 
-    /// Validate and consume a code. Returns the session token if valid.
-    pub async fn consume(&self, code: &str) -> Result<String> {
-        let mut codes = self.codes.write().await;
-        let entry = codes
-            .get_mut(code)
-            .with_context(|| format!("Setup code '{code}' not found"))?;
-
-        if entry.used {
-            anyhow::bail!("Setup code '{code}' has already been used");
-        }
-        if entry.is_expired() {
-            anyhow::bail!("Setup code '{code}' has expired");
+    /// Validate and consume a code presented by `source`, in either case.
+    /// Returns the session token if valid.
+    ///
+    /// A source that sent `MAX_FAILED_ATTEMPTS` wrong codes is refused, even
+    /// with a valid code, until a code lifetime has passed.
+    pub async fn consume(&self, code: &str, source: &str) -> Result<String> {
+        let now = Utc::now();
+        let mut failures = self.failures.write().await;
+        failures.retain(|_, (_, since)| now - *since < self.code_lifetime);
+        if failures.get(source).is_some_and(|(count, _)| *count >= MAX_FAILED_ATTEMPTS) {
+            anyhow::bail!("Too many invalid setup codes from {source}");
         }
 
+        let code = code.trim().to_ascii_uppercase();
+        let mut codes = self.codes.write().await;
+        let Some(entry) = codes.get_mut(&code).filter(|entry| entry.is_valid()) else {
+            let (count, _) = failures.entry(source.to_string()).or_insert((0, now));
+            *count += 1;
+            if *count >= MAX_FAILED_ATTEMPTS {
+                warn!(source = %source, "Too many invalid setup codes; refusing further attempts");
+            }
+            anyhow::bail!("Setup code '{code}' is unknown, used or expired");
+        };
+
         let token = entry.session_token.clone();
         entry.used = true;
         debug!(code = %code, "Setup code consumed successfully");
@@ -132,3 +148,31 @@ impl SetupCodeStore {
         self.codes.read().await.values().filter(|c| c.is_valid()).count()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn repeated_bad_codes_lock_out_only_their_source() {
+        let store = SetupCodeStore::new(5);
+        let code = store.create().await.code;
+        for _ in 0..MAX_FAILED_ATTEMPTS {
+            assert!(store.consume("AAAA-BBBB-CCCC-DDDD", "10.0.0.9").await.is_err());
+        }
+        assert!(store.consume(&code, "10.0.0.9").await.is_err());
+        assert_eq!(store.valid_count().await, 1);
+
+        assert!(store.consume(&code.to_lowercase(), "10.0.0.2").await.is_ok());
+        assert!(store.consume(&code, "10.0.0.2").await.is_err());
+    }
+
+    #[test]
+    fn codes_are_four_groups_from_the_alphabet() {
+        let code = generate_code();
+        let groups: Vec<&str> = code.split('-').collect();
+        assert_eq!(groups.len(), CODE_GROUPS);
+        assert!(groups.iter().all(|g| g.len() == GROUP_LEN && g.bytes().all(|b| CODE_ALPHABET.contains(&b))));
+        assert_ne!(generate_code(), code);
+    }
+}
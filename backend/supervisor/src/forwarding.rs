@@ -0,0 +1,438 @@
+//! Event forwarding to external webhooks.
+//!
+//! Selected events are POSTed as JSON to user-configured endpoints, so
+//! automations can react to runs without polling the API. Targets are
+//! declared in YAML:
+//!
+//! ```yaml
+//! webhooks:
+//!   - name: ops
+//!     url: https://hooks.example.com/clawforge
+//!     secret_env: OPS_WEBHOOK_SECRET
+//!     events: [run_failed, budget_exceeded]
+//!     max_attempts: 5
+//! ```
+//!
+//! Each delivery carries `X-ClawForge-Timestamp` and
+//! `X-ClawForge-Signature: sha256=<hex>`, an HMAC-SHA256 of
+//! `"<timestamp>.<body>"` keyed by the target's secret. Network errors, 429
+//! and 5xx responses are retried with exponential backoff; deliveries that
+//! still fail land in a SQLite dead-letter queue and can be replayed.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use clawforge_core::{Event, EventKind};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Longest wait between two attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// One webhook endpoint and the events it receives.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookTarget {
+    pub name: String,
+    pub url: String,
+    /// HMAC key. Prefer `secret_env` to keep it out of the file.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Environment variable holding the HMAC key.
+    #[serde(default)]
+    pub secret_env: Option<String>,
+    /// Event kinds to forward; empty forwards everything.
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+    /// Only forward events from these agents; empty means all agents.
+    #[serde(default)]
+    pub agents: Vec<Uuid>,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+fn default_max_attempts() -> u32 {
+    5
+}
+
+impl WebhookTarget {
+    pub fn matches(&self, event: &Event) -> bool {
+        (self.events.is_empty() || self.events.contains(&event.kind))
+            && (self.agents.is_empty() || self.agents.contains(&event.agent_id))
+    }
+}
+
+#[derive(Deserialize)]
+struct ForwardingFile {
+    #[serde(default)]
+    webhooks: Vec<WebhookTarget>,
+}
+
+/// `sha256=<hex>` signature of a delivery body.
+pub fn sign_payload(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// ---------------------------------------------------------------------------
+// Dead-letter queue
+// ---------------------------------------------------------------------------
+
+/// A delivery that exhausted its attempts.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetter {
+    pub id: i64,
+    pub target: String,
+    pub event: Event,
+    pub error: String,
+    pub attempts: u32,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// SQLite-backed store of failed deliveries.
+pub struct DeadLetterQueue {
+    conn: Mutex<Connection>,
+}
+
+impl DeadLetterQueue {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path).context("Failed to open dead-letter database")?;
+        Self::init(conn)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory().context("Failed to open in-memory SQLite")?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS event_dead_letters (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                target TEXT NOT NULL,
+                event TEXT NOT NULL,
+                error TEXT NOT NULL,
+                attempts INTEGER NOT NULL,
+                failed_at TEXT NOT NULL
+            );",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))
+    }
+
+    pub fn push(&self, target: &str, event: &Event, error: &str, attempts: u32) -> Result<i64> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO event_dead_letters (target, event, error, attempts, failed_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![target, serde_json::to_string(event)?, error, attempts, Utc::now().to_rfc3339()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Most recent failures first.
+    pub fn list(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, target, event, error, attempts, failed_at FROM event_dead_letters ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], Self::row)?;
+        Ok(rows.filter_map(|r| r.ok().flatten()).collect())
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<DeadLetter>> {
+        let conn = self.lock()?;
+        let letter = conn
+            .query_row(
+                "SELECT id, target, event, error, attempts, failed_at FROM event_dead_letters WHERE id = ?1",
+                params![id],
+                Self::row,
+            )
+            .optional()?;
+        Ok(letter.flatten())
+    }
+
+    pub fn remove(&self, id: i64) -> Result<bool> {
+        Ok(self.lock()?.execute("DELETE FROM event_dead_letters WHERE id = ?1", params![id])? > 0)
+    }
+
+    fn record_failure(&self, id: i64, error: &str) -> Result<()> {
+        self.lock()?.execute(
+            "UPDATE event_dead_letters SET error = ?2, attempts = attempts + 1, failed_at = ?3 WHERE id = ?1",
+            params![id, error, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    fn row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Option<DeadLetter>> {
+        let event: String = row.get(2)?;
+        let failed_at: String = row.get(5)?;
+        let (Ok(event), Ok(failed_at)) = (serde_json::from_str(&event), DateTime::parse_from_rfc3339(&failed_at)) else {
+            return Ok(None);
+        };
+        Ok(Some(DeadLetter {
+            id: row.get(0)?,
+            target: row.get(1)?,
+            event,
+            error: row.get(3)?,
+            attempts: row.get(4)?,
+            failed_at: failed_at.with_timezone(&Utc),
+        }))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Forwarder
+// ---------------------------------------------------------------------------
+
+/// Why a single attempt failed.
+struct AttemptError {
+    message: String,
+    retryable: bool,
+}
+
+pub struct EventForwarder {
+    targets: Vec<(WebhookTarget, Option<Vec<u8>>)>,
+    dead_letters: DeadLetterQueue,
+    client: reqwest::Client,
+    backoff: Duration,
+}
+
+impl EventForwarder {
+    pub fn new(targets: Vec<WebhookTarget>, dead_letters: DeadLetterQueue) -> Result<Self> {
+        let mut resolved: Vec<(WebhookTarget, Option<Vec<u8>>)> = Vec::new();
+        for target in targets {
+            if resolved.iter().any(|(t, _)| t.name == target.name) {
+                bail!("Duplicate webhook name '{}'", target.name);
+            }
+            if !(target.url.starts_with("https://") || target.url.starts_with("http://")) {
+                bail!("Webhook '{}' needs an http(s) URL", target.name);
+            }
+            if target.max_attempts == 0 {
+                bail!("Webhook '{}' needs max_attempts of at least 1", target.name);
+            }
+            let secret = match (&target.secret, &target.secret_env) {
+                (Some(secret), _) => Some(secret.clone().into_bytes()),
+                (None, Some(var)) => Some(
+                    std::env::var(var)
+                        .with_context(|| format!("Webhook '{}': {} is not set", target.name, var))?
+                        .into_bytes(),
+                ),
+                (None, None) => {
+                    warn!(webhook = %target.name, "Webhook has no secret; deliveries will be unsigned");
+                    None
+                }
+            };
+            resolved.push((target, secret));
+        }
+        Ok(Self {
+            targets: resolved,
+            dead_letters,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(15))
+                .user_agent(concat!("clawforge/", env!("CARGO_PKG_VERSION")))
+                .build()?,
+            backoff: Duration::from_secs(2),
+        })
+    }
+
+    /// Build from a YAML `webhooks:` document.
+    pub fn from_yaml(yaml: &str, dead_letters: DeadLetterQueue) -> Result<Self> {
+        let file: ForwardingFile = serde_yaml::from_str(yaml).context("Invalid event forwarding YAML")?;
+        Self::new(file.webhooks, dead_letters)
+    }
+
+    /// Delay before the first retry; doubles on each further attempt.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn dead_letters(&self) -> &DeadLetterQueue {
+        &self.dead_letters
+    }
+
+    /// Deliver `event` to every matching target, retrying in the background.
+    pub fn forward(self: &Arc<Self>, event: &Event) {
+        for index in 0..self.targets.len() {
+            if !self.targets[index].0.matches(event) {
+                continue;
+            }
+            let (forwarder, event) = (Arc::clone(self), event.clone());
+            tokio::spawn(async move { forwarder.deliver_with_retries(index, &event).await });
+        }
+    }
+
+    /// Forward every event from the broadcast channel until it closes.
+    pub fn spawn(self: Arc<Self>, mut events: broadcast::Receiver<Event>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => self.forward(&event),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!(missed, "Event forwarder lagged; events were not forwarded");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Redeliver a dead letter once. It is removed on success; on failure it
+    /// stays queued with the new error.
+    pub async fn replay(&self, id: i64) -> Result<()> {
+        let letter = self.dead_letters.get(id)?.ok_or_else(|| anyhow!("No dead letter {}", id))?;
+        let index = self
+            .targets
+            .iter()
+            .position(|(t, _)| t.name == letter.target)
+            .ok_or_else(|| anyhow!("Webhook '{}' is no longer configured", letter.target))?;
+        match self.attempt(index, &letter.event).await {
+            Ok(()) => {
+                self.dead_letters.remove(id)?;
+                Ok(())
+            }
+            Err(e) => {
+                self.dead_letters.record_failure(id, &e.message)?;
+                bail!("Replay to '{}' failed: {}", letter.target, e.message)
+            }
+        }
+    }
+
+    async fn deliver_with_retries(&self, index: usize, event: &Event) {
+        let target = &self.targets[index].0;
+        let mut delay = self.backoff;
+        for attempt in 1..=target.max_attempts {
+            let error = match self.attempt(index, event).await {
+                Ok(()) => return,
+                Err(e) => e,
+            };
+            if !error.retryable || attempt == target.max_attempts {
+                warn!(webhook = %target.name, event = %event.id, attempts = attempt, error = %error.message, "Event delivery dead-lettered");
+                if let Err(e) = self.dead_letters.push(&target.name, event, &error.message, attempt) {
+                    warn!(webhook = %target.name, error = %e, "Failed to store dead letter");
+                }
+                return;
+            }
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    async fn attempt(&self, index: usize, event: &Event) -> std::result::Result<(), AttemptError> {
+        let (target, secret) = &self.targets[index];
+        let body = serde_json::to_vec(event).map_err(|e| AttemptError { message: e.to_string(), retryable: false })?;
+        let timestamp = Utc::now().timestamp();
+        let mut request = self
+            .client
+            .post(&target.url)
+            .header("Content-Type", "application/json")
+            .header("X-ClawForge-Event", event.kind.to_string())
+            .header("X-ClawForge-Delivery", event.id.to_string())
+            .header("X-ClawForge-Timestamp", timestamp.to_string());
+        if let Some(secret) = secret {
+            request = request.header("X-ClawForge-Signature", sign_payload(secret, timestamp, &body));
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AttemptError { message: e.to_string(), retryable: true })?;
+        let status = response.status();
+        if status.is_success() {
+            info!(webhook = %target.name, event = %event.id, kind = %event.kind, "Event forwarded");
+            return Ok(());
+        }
+        Err(AttemptError {
+            message: format!("HTTP {}", status),
+            retryable: status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer each connection with the next status, echoing requests back.
+    async fn serve(statuses: Vec<u16>) -> (String, tokio::sync::mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = String::new();
+                let mut buf = [0u8; 4096];
+                while request.split_once("\r\n\r\n").is_none_or(|(head, body)| body.len() < content_length(head)) {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.push_str(&String::from_utf8_lossy(&buf[..n]));
+                }
+                tx.send(request).await.unwrap();
+                let reply = format!("HTTP/1.1 {} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                socket.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    #[tokio::test]
+    async fn failed_deliveries_are_retried_dead_lettered_and_replayed() {
+        let (url, mut requests) = serve(vec![503, 503, 200]).await;
+        let yaml = format!(
+            "webhooks:\n  - name: ops\n    url: {}\n    secret: s3cret\n    events: [run_failed]\n    max_attempts: 2\n",
+            url
+        );
+        let forwarder = EventForwarder::from_yaml(&yaml, DeadLetterQueue::in_memory().unwrap())
+            .unwrap()
+            .with_backoff(Duration::from_millis(1));
+
+        let ignored = Event::new(Uuid::new_v4(), Uuid::new_v4(), EventKind::RunStarted, serde_json::json!({}));
+        assert!(!forwarder.targets[0].0.matches(&ignored));
+
+        let event = Event::new(Uuid::new_v4(), Uuid::new_v4(), EventKind::RunFailed, serde_json::json!({"error": "boom"}));
+        forwarder.deliver_with_retries(0, &event).await;
+        let first = requests.recv().await.unwrap();
+        requests.recv().await.unwrap();
+
+        let timestamp: i64 = header(&first, "x-clawforge-timestamp").parse().unwrap();
+        let body = first.split("\r\n\r\n").nth(1).unwrap();
+        assert_eq!(header(&first, "x-clawforge-signature"), sign_payload(b"s3cret", timestamp, body.as_bytes()));
+        assert_eq!(header(&first, "x-clawforge-event"), "run_failed");
+
+        let letters = forwarder.dead_letters().list(10).unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!((letters[0].attempts, letters[0].error.as_str()), (2, "HTTP 503 Service Unavailable"));
+
+        forwarder.replay(letters[0].id).await.unwrap();
+        assert!(forwarder.dead_letters().list(10).unwrap().is_empty());
+    }
+
+    fn content_length(head: &str) -> usize {
+        head.lines()
+            .find_map(|line| line.split_once(": ").filter(|(k, _)| k.eq_ignore_ascii_case("content-length")))
+            .map_or(0, |(_, v)| v.parse().unwrap())
+    }
+
+    fn header<'a>(request: &'a str, name: &str) -> &'a str {
+        request
+            .lines()
+            .find_map(|line| line.split_once(": ").filter(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v))
+            .unwrap()
+    }
+}
@@ -1,3 +1,5 @@
+pub mod forwarding;
+pub mod state_store;
 pub mod store;
 pub mod supervisor;
 
@@ -5,4 +7,7 @@ pub mod kill_tree;
 pub mod pty_supervisor;
 pub mod timeout_kill;
 
+pub use forwarding::{DeadLetter, DeadLetterQueue, EventForwarder, WebhookTarget};
+pub use state_store::AgentStateStore;
+pub use store::{ArchivedAgent, EventStore};
 pub use supervisor::Supervisor;
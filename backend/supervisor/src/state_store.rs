@@ -0,0 +1,167 @@
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+use tracing::info;
+use uuid::Uuid;
+
+use clawforge_core::agent_state::{validate_entry, StateBackend, StateEntry};
+
+/// SQLite-backed per-agent key-value state that persists between runs.
+///
+/// Lives next to the event store but in its own table; it is mutable
+/// bookkeeping (cursors, last-seen ids), not part of the audit log.
+pub struct AgentStateStore {
+    conn: Mutex<Connection>,
+}
+
+impl AgentStateStore {
+    /// Open or create the state table in the database at `path`.
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path).context("Failed to open SQLite database")?;
+        let store = Self { conn: Mutex::new(conn) };
+        store.init_schema()?;
+        info!(path = %path, "Agent state store opened");
+        Ok(store)
+    }
+
+    /// Create an in-memory store (for testing).
+    pub fn in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open in-memory SQLite")?;
+        let store = Self { conn: Mutex::new(conn) };
+        store.init_schema()?;
+        Ok(store)
+    }
+
+    fn init_schema(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS agent_state (
+                agent_id TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (agent_id, key)
+            );",
+        )?;
+        Ok(())
+    }
+
+    /// Read one key.
+    pub fn get_entry(&self, agent_id: &Uuid, key: &str) -> Result<Option<StateEntry>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let row = conn
+            .query_row(
+                "SELECT value, updated_at FROM agent_state WHERE agent_id = ?1 AND key = ?2",
+                params![agent_id.to_string(), key],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?;
+        row.map(|(value, updated_at)| decode(key.to_string(), &value, &updated_at)).transpose()
+    }
+
+    /// Insert or replace one key.
+    pub fn set_entry(&self, agent_id: &Uuid, key: &str, value: serde_json::Value) -> Result<StateEntry> {
+        validate_entry(key, &value)?;
+        let entry = StateEntry { key: key.to_string(), value, updated_at: chrono::Utc::now() };
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        conn.execute(
+            "INSERT INTO agent_state (agent_id, key, value, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(agent_id, key) DO UPDATE SET
+                value = excluded.value,
+                updated_at = excluded.updated_at",
+            params![
+                agent_id.to_string(),
+                key,
+                serde_json::to_string(&entry.value)?,
+                entry.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(entry)
+    }
+
+    /// Remove one key. Returns whether it existed.
+    pub fn delete_entry(&self, agent_id: &Uuid, key: &str) -> Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let removed = conn.execute(
+            "DELETE FROM agent_state WHERE agent_id = ?1 AND key = ?2",
+            params![agent_id.to_string(), key],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// All of an agent's entries, ordered by key.
+    pub fn list_entries(&self, agent_id: &Uuid) -> Result<Vec<StateEntry>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let mut stmt = conn.prepare(
+            "SELECT key, value, updated_at FROM agent_state WHERE agent_id = ?1 ORDER BY key ASC",
+        )?;
+        let rows = stmt
+            .query_map(params![agent_id.to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.into_iter().map(|(key, value, updated_at)| decode(key, &value, &updated_at)).collect()
+    }
+
+    /// Drop all of an agent's entries (when the agent is purged).
+    pub fn clear_agent(&self, agent_id: &Uuid) -> Result<usize> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        Ok(conn.execute("DELETE FROM agent_state WHERE agent_id = ?1", params![agent_id.to_string()])?)
+    }
+}
+
+fn decode(key: String, value: &str, updated_at: &str) -> Result<StateEntry> {
+    Ok(StateEntry {
+        key,
+        value: serde_json::from_str(value)?,
+        updated_at: chrono::DateTime::parse_from_rfc3339(updated_at)?.with_timezone(&chrono::Utc),
+    })
+}
+
+#[async_trait]
+impl StateBackend for AgentStateStore {
+    async fn get(&self, agent_id: Uuid, key: &str) -> Result<Option<StateEntry>> {
+        self.get_entry(&agent_id, key)
+    }
+
+    async fn set(&self, agent_id: Uuid, key: &str, value: serde_json::Value) -> Result<StateEntry> {
+        self.set_entry(&agent_id, key, value)
+    }
+
+    async fn delete(&self, agent_id: Uuid, key: &str) -> Result<bool> {
+        self.delete_entry(&agent_id, key)
+    }
+
+    async fn list(&self, agent_id: Uuid) -> Result<Vec<StateEntry>> {
+        self.list_entries(&agent_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip() {
+        let store = AgentStateStore::in_memory().unwrap();
+        let (agent, other) = (Uuid::new_v4(), Uuid::new_v4());
+
+        store.set_entry(&agent, "cursor", serde_json::json!({"id": 17})).unwrap();
+        store.set_entry(&agent, "cursor", serde_json::json!({"id": 18})).unwrap();
+        store.set_entry(&agent, "etag", serde_json::json!("abc")).unwrap();
+        store.set_entry(&other, "cursor", serde_json::json!(1)).unwrap();
+
+        let entry = store.get_entry(&agent, "cursor").unwrap().unwrap();
+        assert_eq!(entry.value, serde_json::json!({"id": 18}));
+        let keys: Vec<String> = store.list_entries(&agent).unwrap().into_iter().map(|e| e.key).collect();
+        assert_eq!(keys, vec!["cursor", "etag"]);
+
+        assert!(store.delete_entry(&agent, "cursor").unwrap());
+        assert!(!store.delete_entry(&agent, "cursor").unwrap());
+        assert!(store.get_entry(&agent, "cursor").unwrap().is_none());
+        assert_eq!(store.get_entry(&other, "cursor").unwrap().unwrap().value, serde_json::json!(1));
+    }
+}
@@ -1,11 +1,23 @@
 use std::sync::Mutex;
 
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
 use tracing::info;
 
 use clawforge_core::{Event, AgentSpec};
 
+/// A soft-deleted agent, kept until `purge_after`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedAgent {
+    pub agent: AgentSpec,
+    pub deleted_at: DateTime<Utc>,
+    pub purge_after: DateTime<Utc>,
+    /// Cron jobs disabled by the archive, re-enabled on restore.
+    pub cron_jobs: Vec<String>,
+}
+
 /// SQLite-backed event store for immutable event-sourcing.
 pub struct EventStore {
     conn: Mutex<Connection>,
@@ -53,9 +65,28 @@ impl EventStore {
                 name TEXT NOT NULL,
                 spec TEXT NOT NULL,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                deleted_at TEXT,
+                purge_after TEXT,
+                archived_cron_jobs TEXT
             );",
         )?;
+        for column in ["deleted_at", "purge_after", "archived_cron_jobs"] {
+            Self::migrate_agent_column(&conn, column)?;
+        }
+        Ok(())
+    }
+
+    /// Stores created before soft-delete lack the archive columns.
+    fn migrate_agent_column(conn: &Connection, column: &str) -> Result<()> {
+        let mut stmt = conn.prepare("PRAGMA table_info(agents)")?;
+        let has_column = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .any(|name| name == column);
+        if !has_column {
+            conn.execute_batch(&format!("ALTER TABLE agents ADD COLUMN {} TEXT;", column))?;
+        }
         Ok(())
     }
 
@@ -180,10 +211,10 @@ impl EventStore {
         Ok(())
     }
 
-    /// Get an agent by ID.
+    /// Get an agent by ID. Archived agents are not returned.
     pub fn get_agent(&self, id: &uuid::Uuid) -> Result<Option<AgentSpec>> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-        let mut stmt = conn.prepare("SELECT spec FROM agents WHERE id = ?1")?;
+        let mut stmt = conn.prepare("SELECT spec FROM agents WHERE id = ?1 AND deleted_at IS NULL")?;
         
         let mut rows = stmt.query(params![id.to_string()])?;
         if let Some(row) = rows.next()? {
@@ -223,7 +254,7 @@ impl EventStore {
     /// List all agents (full list, used internally).
     pub fn list_agents(&self) -> Result<Vec<AgentSpec>> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-        let mut stmt = conn.prepare("SELECT spec FROM agents ORDER BY name ASC")?;
+        let mut stmt = conn.prepare("SELECT spec FROM agents WHERE deleted_at IS NULL ORDER BY name ASC")?;
         let agents = stmt
             .query_map([], |row| {
                 let spec_json: String = row.get(0)?;
@@ -239,7 +270,7 @@ impl EventStore {
     pub fn list_agents_page(&self, limit: usize, offset: usize) -> Result<Vec<AgentSpec>> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
         let mut stmt = conn.prepare(
-            "SELECT spec FROM agents ORDER BY name ASC LIMIT ?1 OFFSET ?2",
+            "SELECT spec FROM agents WHERE deleted_at IS NULL ORDER BY name ASC LIMIT ?1 OFFSET ?2",
         )?;
         let agents = stmt
             .query_map(params![limit, offset], |row| {
@@ -251,6 +282,106 @@ impl EventStore {
             .collect();
         Ok(agents)
     }
+
+    /// Soft-delete an active agent: hide it from lookups and schedule its
+    /// purge. Returns false when the agent is unknown or already archived.
+    pub fn archive_agent(&self, id: &uuid::Uuid, purge_after: DateTime<Utc>, cron_jobs: &[String]) -> Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let changed = conn.execute(
+            "UPDATE agents SET deleted_at = ?2, purge_after = ?3, archived_cron_jobs = ?4
+             WHERE id = ?1 AND deleted_at IS NULL",
+            params![id.to_string(), Utc::now().to_rfc3339(), purge_after.to_rfc3339(), serde_json::to_string(cron_jobs)?],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Undo an archive. Returns the cron jobs the archive disabled, or None
+    /// when the agent is not archived.
+    pub fn restore_agent(&self, id: &uuid::Uuid) -> Result<Option<Vec<String>>> {
+        let Some(archived) = self.get_archived_agent(id)? else {
+            return Ok(None);
+        };
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        conn.execute(
+            "UPDATE agents SET deleted_at = NULL, purge_after = NULL, archived_cron_jobs = NULL WHERE id = ?1",
+            params![id.to_string()],
+        )?;
+        Ok(Some(archived.cron_jobs))
+    }
+
+    pub fn get_archived_agent(&self, id: &uuid::Uuid) -> Result<Option<ArchivedAgent>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let row = conn
+            .query_row(
+                "SELECT spec, deleted_at, purge_after, archived_cron_jobs FROM agents
+                 WHERE id = ?1 AND deleted_at IS NOT NULL",
+                params![id.to_string()],
+                Self::archived_row,
+            )
+            .optional()?;
+        row.map(Self::parse_archived).transpose()
+    }
+
+    /// Archived agents, soonest purge first.
+    pub fn list_archived_agents(&self) -> Result<Vec<ArchivedAgent>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let mut stmt = conn.prepare(
+            "SELECT spec, deleted_at, purge_after, archived_cron_jobs FROM agents
+             WHERE deleted_at IS NOT NULL ORDER BY purge_after ASC",
+        )?;
+        let rows: Vec<_> = stmt.query_map([], Self::archived_row)?.filter_map(|r| r.ok()).collect();
+        Ok(rows.into_iter().filter_map(|row| Self::parse_archived(row).ok()).collect())
+    }
+
+    /// Archived agents whose grace period ended by `now`.
+    pub fn agents_due_for_purge(&self, now: DateTime<Utc>) -> Result<Vec<uuid::Uuid>> {
+        Ok(self
+            .list_archived_agents()?
+            .into_iter()
+            .filter(|a| a.purge_after <= now)
+            .map(|a| a.agent.id)
+            .collect())
+    }
+
+    /// Permanently delete an archived agent and its events. Active agents
+    /// are never purged; returns false for them and for unknown ids.
+    pub fn purge_agent(&self, id: &uuid::Uuid) -> Result<bool> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let tx = conn.transaction()?;
+        let deleted = tx.execute("DELETE FROM agents WHERE id = ?1 AND deleted_at IS NOT NULL", params![id.to_string()])?;
+        if deleted > 0 {
+            tx.execute("DELETE FROM events WHERE agent_id = ?1", params![id.to_string()])?;
+        }
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    /// The agent a run belongs to, from its events.
+    pub fn run_agent_id(&self, run_id: &uuid::Uuid) -> Result<Option<uuid::Uuid>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let agent: Option<String> = conn
+            .query_row("SELECT agent_id FROM events WHERE run_id = ?1 LIMIT 1", params![run_id.to_string()], |row| row.get(0))
+            .optional()?;
+        Ok(agent.and_then(|a| uuid::Uuid::parse_str(&a).ok()))
+    }
+
+    /// Whether `id` is a soft-deleted agent.
+    pub fn is_archived(&self, id: &uuid::Uuid) -> Result<bool> {
+        Ok(self.get_archived_agent(id)?.is_some())
+    }
+
+    fn archived_row(row: &rusqlite::Row) -> rusqlite::Result<(String, String, String, Option<String>)> {
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+    }
+
+    fn parse_archived((spec, deleted_at, purge_after, cron_jobs): (String, String, String, Option<String>)) -> Result<ArchivedAgent> {
+        Ok(ArchivedAgent {
+            agent: serde_json::from_str(&spec)?,
+            deleted_at: DateTime::parse_from_rfc3339(&deleted_at)?.with_timezone(&Utc),
+            purge_after: DateTime::parse_from_rfc3339(&purge_after)?.with_timezone(&Utc),
+            cron_jobs: cron_jobs.map(|j| serde_json::from_str(&j)).transpose()?.unwrap_or_default(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -313,4 +444,31 @@ mod tests {
         let recent = store.get_recent(3).unwrap();
         assert_eq!(recent.len(), 3);
     }
+
+    #[test]
+    fn test_archive_restore_and_purge() {
+        let store = EventStore::in_memory().unwrap();
+        let agent = AgentSpec::new("archivist", clawforge_core::TriggerSpec::Manual);
+        store.save_agent(&agent).unwrap();
+        let run_id = Uuid::new_v4();
+        store.insert(&Event::new(run_id, agent.id, EventKind::RunStarted, serde_json::json!({}))).unwrap();
+
+        let purge_after = Utc::now() + chrono::Duration::days(30);
+        assert!(store.archive_agent(&agent.id, purge_after, &["job-1".to_string()]).unwrap());
+        assert!(!store.archive_agent(&agent.id, purge_after, &[]).unwrap());
+        assert!(store.get_agent(&agent.id).unwrap().is_none());
+        assert!(store.list_agents().unwrap().is_empty());
+        assert_eq!(store.run_agent_id(&run_id).unwrap(), Some(agent.id));
+        assert!(store.agents_due_for_purge(Utc::now()).unwrap().is_empty());
+
+        assert_eq!(store.restore_agent(&agent.id).unwrap(), Some(vec!["job-1".to_string()]));
+        assert!(store.get_agent(&agent.id).unwrap().is_some());
+        assert!(!store.purge_agent(&agent.id).unwrap());
+
+        store.archive_agent(&agent.id, Utc::now(), &[]).unwrap();
+        assert_eq!(store.agents_due_for_purge(Utc::now()).unwrap(), vec![agent.id]);
+        assert!(store.purge_agent(&agent.id).unwrap());
+        assert!(store.get_archived_agent(&agent.id).unwrap().is_none());
+        assert_eq!(store.count().unwrap(), 0);
+    }
 }
@@ -3,11 +3,12 @@ use async_trait::async_trait;
 use tokio::sync::{mpsc, broadcast, RwLock};
 use tracing::{debug, error, info, warn};
 
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use clawforge_core::types::{AgentSpec, RunState};
 use clawforge_core::{Component, Event, EventKind, Message};
 
-use crate::store::EventStore;
+use crate::store::{ArchivedAgent, EventStore};
 
 /// The Supervisor component logs all audit events, enforces budget policies,
 /// and tracks run state.
@@ -102,6 +103,41 @@ impl Supervisor {
     pub fn list_agents_page(&self, limit: usize, offset: usize) -> Result<Vec<AgentSpec>> {
         tokio::task::block_in_place(|| self.event_store.list_agents_page(limit, offset))
     }
+
+    /// Soft-delete an agent until `purge_after`; see `EventStore::archive_agent`.
+    pub fn archive_agent(&self, id: &Uuid, purge_after: DateTime<Utc>, cron_jobs: &[String]) -> Result<bool> {
+        tokio::task::block_in_place(|| self.event_store.archive_agent(id, purge_after, cron_jobs))
+    }
+
+    /// Undo an archive, returning the cron jobs to re-enable.
+    pub fn restore_agent(&self, id: &Uuid) -> Result<Option<Vec<String>>> {
+        tokio::task::block_in_place(|| self.event_store.restore_agent(id))
+    }
+
+    pub fn get_archived_agent(&self, id: &Uuid) -> Result<Option<ArchivedAgent>> {
+        tokio::task::block_in_place(|| self.event_store.get_archived_agent(id))
+    }
+
+    pub fn list_archived_agents(&self) -> Result<Vec<ArchivedAgent>> {
+        tokio::task::block_in_place(|| self.event_store.list_archived_agents())
+    }
+
+    pub fn agents_due_for_purge(&self, now: DateTime<Utc>) -> Result<Vec<Uuid>> {
+        tokio::task::block_in_place(|| self.event_store.agents_due_for_purge(now))
+    }
+
+    /// Permanently delete an archived agent and its events.
+    pub fn purge_agent(&self, id: &Uuid) -> Result<bool> {
+        tokio::task::block_in_place(|| self.event_store.purge_agent(id))
+    }
+
+    /// Whether a run belongs to an archived agent; such runs are frozen.
+    pub fn is_run_frozen(&self, run_id: &Uuid) -> Result<bool> {
+        tokio::task::block_in_place(|| match self.event_store.run_agent_id(run_id)? {
+            Some(agent_id) => self.event_store.is_archived(&agent_id),
+            None => Ok(false),
+        })
+    }
 }
 
 #[async_trait]
@@ -133,6 +169,15 @@ impl Component for Supervisor {
                             states.insert(event.run_id, RunState::Failed);
                             warn!(run_id = %event.run_id, "Run failed");
                         }
+                        // A call is tracked as a run that lasts until hang-up.
+                        EventKind::CallStarted => {
+                            states.insert(event.run_id, RunState::Active);
+                            info!(run_id = %event.run_id, agent_id = %event.agent_id, "Call started");
+                        }
+                        EventKind::CallEnded => {
+                            states.insert(event.run_id, RunState::Completed);
+                            info!(run_id = %event.run_id, "Call ended");
+                        }
                         _ => {}
                     }
                     drop(states); // Release lock
@@ -0,0 +1,66 @@
+//! Mock chat channel
+//!
+//! Stands in for Telegram, Slack and the rest: inbound messages become plan
+//! requests carrying the same context keys a real adapter sets (`channel`,
+//! `chat_id`, `sender`, `text`, `session_key`), and the output of the run
+//! they start is delivered back to the chat as a `Reply`. Chats are direct
+//! messages, so the sender is the chat id.
+
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// A message the runtime sent to a chat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    pub channel: String,
+    pub chat_id: String,
+    pub run_id: Uuid,
+    pub text: String,
+}
+
+#[derive(Clone, Default)]
+pub struct MockChannel {
+    /// Run → (channel, chat) it was started from.
+    origins: Arc<Mutex<HashMap<Uuid, (String, String)>>>,
+    outbox: Arc<Mutex<Vec<Reply>>>,
+}
+
+impl MockChannel {
+    /// Plan request context for a message from `chat_id` on `channel`, and
+    /// remember where to deliver the reply to `run_id`.
+    pub(crate) fn inbound(&self, run_id: Uuid, channel: &str, chat_id: &str, text: &str) -> serde_json::Value {
+        self.origins.lock().unwrap().insert(run_id, (channel.to_string(), chat_id.to_string()));
+        json!({
+            "trigger": "message",
+            "channel": channel,
+            "chat_id": chat_id,
+            "sender": chat_id,
+            "text": text,
+            "session_key": format!("{}:{}", channel, chat_id),
+        })
+    }
+
+    /// Deliver a step's output to the chat the run came from, if any.
+    pub(crate) fn deliver(&self, run_id: Uuid, output: &serde_json::Value) {
+        let Some((channel, chat_id)) = self.origins.lock().unwrap().get(&run_id).cloned() else {
+            return;
+        };
+        let text = match output.get("content").or_else(|| output.get("output")) {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(other) => other.to_string(),
+            None => output.to_string(),
+        };
+        self.outbox.lock().unwrap().push(Reply { channel, chat_id, run_id, text });
+    }
+
+    /// Everything sent so far, oldest first.
+    pub fn replies(&self) -> Vec<Reply> {
+        self.outbox.lock().unwrap().clone()
+    }
+
+    pub fn replies_to(&self, chat_id: &str) -> Vec<Reply> {
+        self.replies().into_iter().filter(|r| r.chat_id == chat_id).collect()
+    }
+}
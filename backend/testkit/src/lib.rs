@@ -0,0 +1,24 @@
+//! ClawForge test kit
+//!
+//! Runs the whole runtime in-process for black-box tests of flows such as
+//! "chat message → plan → tool → reply", in this repository and in plugins:
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use clawforge_testkit::TestRuntime;
+//!
+//! let runtime = TestRuntime::start().await?;
+//! runtime.provider().reply("Hello!");
+//! let run_id = runtime.send_message("telegram", "42", "hi").await?;
+//! assert_eq!(runtime.wait_for_reply(run_id).await?.text, "Hello!");
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod channel;
+pub mod provider;
+pub mod runtime;
+
+pub use channel::{MockChannel, Reply};
+pub use provider::ScriptedProvider;
+pub use runtime::{test_agent, TestRuntime, TestRuntimeBuilder};
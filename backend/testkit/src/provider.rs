@@ -0,0 +1,78 @@
+//! Scripted model provider
+//!
+//! Answers completions from a queue of canned replies, in order, and records
+//! every request so tests can assert on the prompt the planner assembled.
+//! Once the queue is empty it keeps returning the fallback reply.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use clawforge_core::{LlmProvider, LlmRequest, LlmResponse};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+pub const PROVIDER_NAME: &str = "mock";
+
+pub struct ScriptedProvider {
+    replies: Mutex<VecDeque<Result<String, String>>>,
+    fallback: String,
+    requests: Mutex<Vec<LlmRequest>>,
+}
+
+impl Default for ScriptedProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptedProvider {
+    pub fn new() -> Self {
+        Self {
+            replies: Mutex::new(VecDeque::new()),
+            fallback: "Mock response".to_string(),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Queue a completion. `Action: tool({...})` replies become tool calls.
+    pub fn reply(&self, content: impl Into<String>) -> &Self {
+        self.replies.lock().unwrap().push_back(Ok(content.into()));
+        self
+    }
+
+    /// Queue a provider error.
+    pub fn fail(&self, error: impl Into<String>) -> &Self {
+        self.replies.lock().unwrap().push_back(Err(error.into()));
+        self
+    }
+
+    /// Every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<LlmRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ScriptedProvider {
+    fn name(&self) -> &str {
+        PROVIDER_NAME
+    }
+
+    async fn complete(&self, request: &LlmRequest) -> Result<LlmResponse> {
+        self.requests.lock().unwrap().push(request.clone());
+        let next = self.replies.lock().unwrap().pop_front();
+        let content = match next {
+            Some(Ok(content)) => content,
+            Some(Err(error)) => return Err(anyhow!(error)),
+            None => self.fallback.clone(),
+        };
+        Ok(LlmResponse {
+            content,
+            provider: PROVIDER_NAME.to_string(),
+            model: request.model.clone(),
+            tokens_used: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            latency_ms: 0,
+        })
+    }
+}
@@ -0,0 +1,346 @@
+//! In-process runtime
+//!
+//! `TestRuntime` wires the real scheduler, planner, executor and supervisor
+//! over a `ClawBus`, the way `clawforge serve` does, but with a scripted model
+//! provider, a mock chat channel, a manually advanced scheduler clock and a
+//! temporary directory holding the config file and event database. Every
+//! audit event the supervisor persists is also recorded for assertions.
+//!
+//! The supervisor writes to SQLite through `block_in_place`, so tests must
+//! run on the multi-threaded runtime: `#[tokio::test(flavor = "multi_thread")]`.
+
+use anyhow::{bail, Result};
+use clawforge_config::{config_file_path, write_config, ClawForgeConfig};
+use clawforge_core::{
+    AgentSpec, Capabilities, ClawBus, Component, Event, EventKind, JobTrigger, LlmPolicy, Message, PlanRequest, Tool,
+    TriggerSpec,
+};
+use clawforge_executor::Executor;
+use clawforge_planner::providers::ProviderRegistry;
+use clawforge_planner::LlmPlanner;
+use clawforge_scheduler::{Scheduler, SchedulerClock};
+use clawforge_supervisor::store::EventStore;
+use clawforge_supervisor::Supervisor;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::channel::{MockChannel, Reply};
+use crate::provider::{ScriptedProvider, PROVIDER_NAME};
+
+/// How long `wait_for_*` helpers wait before failing the test.
+const DEFAULT_WAIT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// An agent that plans with the scripted provider and may call any tool.
+pub fn test_agent(name: &str, trigger: TriggerSpec) -> AgentSpec {
+    AgentSpec {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        description: format!("{} (testkit)", name),
+        trigger,
+        capabilities: Capabilities { can_use_tools: true, ..Default::default() },
+        llm_policy: LlmPolicy { providers: vec![PROVIDER_NAME.to_string()], model: "mock".to_string(), ..Default::default() },
+        role: Default::default(),
+        memory_config: None,
+        workflow: vec![],
+        allowed_tools: vec![],
+        allowed_skills: vec![],
+        timezone: None,
+        locale: None,
+        execution_window: None,
+    }
+}
+
+#[derive(Default)]
+pub struct TestRuntimeBuilder {
+    agents: Vec<AgentSpec>,
+    tools: Vec<Arc<dyn Tool>>,
+    config: ClawForgeConfig,
+}
+
+impl TestRuntimeBuilder {
+    /// Register an agent with the scheduler. Without any, a manually
+    /// triggered `assistant` from `test_agent` is used.
+    pub fn with_agent(mut self, agent: AgentSpec) -> Self {
+        self.agents.push(agent);
+        self
+    }
+
+    /// Offer `tool` to agents through the executor.
+    pub fn with_tool(mut self, tool: Arc<dyn Tool>) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    /// Config written to the runtime's temporary config file.
+    pub fn with_config(mut self, config: ClawForgeConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub async fn start(self) -> Result<TestRuntime> {
+        let dir = tempfile::tempdir()?;
+        let config_path = config_file_path(dir.path());
+        write_config(&self.config, &config_path).await?;
+        let db_path = dir.path().join("clawforge.db");
+
+        let supervisor = Arc::new(Supervisor::new(EventStore::open(&db_path.to_string_lossy())?));
+        let (broadcast_tx, _) = broadcast::channel(1024);
+        supervisor.set_broadcast_tx(broadcast_tx.clone()).await;
+
+        let provider = Arc::new(ScriptedProvider::new());
+        let mut registry = ProviderRegistry::new();
+        registry.register(PROVIDER_NAME, provider.clone());
+
+        let agents = match self.agents.is_empty() {
+            true => vec![test_agent("assistant", TriggerSpec::Manual)],
+            false => self.agents,
+        };
+
+        let mut bus = ClawBus::new();
+        let planner = LlmPlanner::new(Arc::new(registry), bus.executor_tx.clone(), bus.supervisor_tx.clone(), None);
+        let executor = self
+            .tools
+            .into_iter()
+            .fold(Executor::new(bus.supervisor_tx.clone()).with_planner(bus.planner_tx.clone()), Executor::with_tool);
+        let clock = SchedulerClock::new();
+        let scheduler =
+            Scheduler::new(agents.clone(), bus.planner_tx.clone(), bus.supervisor_tx.clone()).with_clock(clock.clone());
+
+        // Record events before anything can emit them.
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let channel = MockChannel::default();
+        let mut tasks = vec![tokio::spawn(record(broadcast_tx.subscribe(), events.clone(), channel.clone()))];
+        tasks.push(spawn_component(supervisor.clone(), bus.take_supervisor_rx().expect("supervisor rx")));
+        tasks.push(spawn_component(Arc::new(scheduler), bus.take_scheduler_rx().expect("scheduler rx")));
+        tasks.push(spawn_component(Arc::new(planner), bus.take_planner_rx().expect("planner rx")));
+        tasks.push(spawn_component(Arc::new(executor), bus.take_executor_rx().expect("executor rx")));
+        // Triggers are registered against the clock; advancing before that
+        // would move their deadlines along with it.
+        clock.running().await;
+
+        Ok(TestRuntime { dir, config_path, db_path, agents, provider, channel, clock, supervisor, bus, events, tasks })
+    }
+}
+
+fn spawn_component<C: Component>(component: Arc<C>, rx: mpsc::Receiver<Message>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if let Err(e) = component.start(rx).await {
+            error!(component = component.name(), error = %e, "Test runtime component failed");
+        }
+    })
+}
+
+async fn record(mut rx: broadcast::Receiver<Event>, events: Arc<Mutex<Vec<Event>>>, channel: MockChannel) {
+    loop {
+        match rx.recv().await {
+            Ok(event) => {
+                if event.kind == EventKind::ActionExecuted {
+                    channel.deliver(event.run_id, &event.payload);
+                }
+                events.lock().unwrap().push(event);
+            }
+            Err(broadcast::error::RecvError::Lagged(missed)) => error!(missed, "Test runtime dropped events"),
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+pub struct TestRuntime {
+    dir: TempDir,
+    config_path: PathBuf,
+    db_path: PathBuf,
+    agents: Vec<AgentSpec>,
+    provider: Arc<ScriptedProvider>,
+    channel: MockChannel,
+    clock: SchedulerClock,
+    supervisor: Arc<Supervisor>,
+    bus: ClawBus,
+    events: Arc<Mutex<Vec<Event>>>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl TestRuntime {
+    pub fn builder() -> TestRuntimeBuilder {
+        TestRuntimeBuilder::default()
+    }
+
+    /// A runtime with the default agent and no extra tools.
+    pub async fn start() -> Result<Self> {
+        Self::builder().start().await
+    }
+
+    pub fn provider(&self) -> &ScriptedProvider {
+        &self.provider
+    }
+
+    pub fn channel(&self) -> &MockChannel {
+        &self.channel
+    }
+
+    pub fn supervisor(&self) -> &Supervisor {
+        &self.supervisor
+    }
+
+    pub fn agents(&self) -> &[AgentSpec] {
+        &self.agents
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+
+    /// A message arriving on `channel` from `chat_id`, handled by the first
+    /// agent. Returns the run it starts.
+    pub async fn send_message(&self, channel: &str, chat_id: &str, text: &str) -> Result<Uuid> {
+        let run_id = Uuid::new_v4();
+        let request = PlanRequest {
+            run_id,
+            agent: self.agents[0].clone(),
+            context: self.channel.inbound(run_id, channel, chat_id, text),
+        };
+        self.bus.planner_tx.send(Message::PlanRequest(request)).await?;
+        Ok(run_id)
+    }
+
+    /// Trigger `agent_id` by hand, as `POST /api/agents/{id}/run` does.
+    pub async fn trigger(&self, agent_id: Uuid) -> Result<Uuid> {
+        let run_id = Uuid::new_v4();
+        let trigger = JobTrigger { run_id, agent_id, trigger_reason: "manual".to_string() };
+        self.bus.scheduler_tx.send(Message::ScheduleJob(trigger)).await?;
+        Ok(run_id)
+    }
+
+    /// Move the scheduler clock forward, firing interval and cron triggers
+    /// that come due.
+    pub fn advance(&self, by: Duration) {
+        self.clock.advance(by);
+    }
+
+    /// Every event recorded so far, oldest first.
+    pub fn events(&self) -> Vec<Event> {
+        self.events.lock().unwrap().clone()
+    }
+
+    pub fn events_for(&self, run_id: Uuid) -> Vec<Event> {
+        self.events().into_iter().filter(|e| e.run_id == run_id).collect()
+    }
+
+    /// First recorded event matching `predicate`, waiting up to 5 seconds.
+    pub async fn wait_for_event(&self, predicate: impl Fn(&Event) -> bool) -> Result<Event> {
+        self.wait_until(|| self.events.lock().unwrap().iter().find(|e| predicate(e)).cloned()).await
+    }
+
+    /// Wait for `run_id` to emit `kind`.
+    pub async fn wait_for(&self, run_id: Uuid, kind: EventKind) -> Result<Event> {
+        self.wait_for_event(|e| e.run_id == run_id && e.kind == kind).await
+    }
+
+    /// Wait for the reply to `run_id`.
+    pub async fn wait_for_reply(&self, run_id: Uuid) -> Result<Reply> {
+        self.wait_until(|| self.channel.replies().into_iter().find(|r| r.run_id == run_id)).await
+    }
+
+    /// Fail unless `run_id` emitted `kinds` in this order (other events may
+    /// come in between).
+    pub fn assert_events(&self, run_id: Uuid, kinds: &[EventKind]) -> Result<()> {
+        let emitted: Vec<EventKind> = self.events_for(run_id).into_iter().map(|e| e.kind).collect();
+        let mut remaining = emitted.iter();
+        for kind in kinds {
+            if !remaining.any(|k| k == kind) {
+                bail!("run {} emitted {:?}, expected {:?} in order", run_id, emitted, kinds);
+            }
+        }
+        Ok(())
+    }
+
+    async fn wait_until<T>(&self, mut check: impl FnMut() -> Option<T>) -> Result<T> {
+        let deadline = tokio::time::Instant::now() + DEFAULT_WAIT;
+        loop {
+            if let Some(found) = check() {
+                return Ok(found);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                bail!("timed out after {:?}; events so far: {:?}", DEFAULT_WAIT, self.events().iter().map(|e| &e.kind).collect::<Vec<_>>());
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl Drop for TestRuntime {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct Weather;
+
+    #[async_trait]
+    impl Tool for Weather {
+        fn name(&self) -> &str {
+            "weather"
+        }
+
+        fn description(&self) -> &str {
+            "Current weather for a city"
+        }
+
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}})
+        }
+
+        async fn execute(&self, args: serde_json::Value) -> Result<String> {
+            Ok(format!("Sunny in {}", args["city"].as_str().unwrap_or("?")))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn chat_message_is_planned_run_through_a_tool_and_answered() {
+        let runtime = TestRuntime::builder().with_tool(Arc::new(Weather)).start().await.unwrap();
+        runtime.provider().reply(r#"Action: weather({"city": "Lisbon"})"#);
+
+        let run_id = runtime.send_message("telegram", "42", "weather in Lisbon?").await.unwrap();
+        let reply = runtime.wait_for_reply(run_id).await.unwrap();
+        assert_eq!((reply.chat_id.as_str(), reply.text.as_str()), ("42", "Sunny in Lisbon"));
+        runtime
+            .assert_events(run_id, &[EventKind::PlanGenerated, EventKind::ActionApproved, EventKind::ActionExecuted])
+            .unwrap();
+        assert!(runtime.provider().requests()[0].user_prompt.contains("weather in Lisbon?"));
+        assert!(runtime.config_path().exists());
+        assert!(runtime.supervisor().get_run_summary(&run_id).unwrap()["event_count"].as_u64().unwrap() >= 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn advancing_the_clock_fires_interval_agents() {
+        let agent = test_agent("digest", TriggerSpec::Interval { seconds: 3600 });
+        let agent_id = agent.id;
+        let runtime = TestRuntime::builder().with_agent(agent).start().await.unwrap();
+        runtime.provider().reply("Nothing new today.");
+
+        runtime.advance(Duration::from_secs(3600));
+        let event = runtime.wait_for_event(|e| e.agent_id == agent_id && e.kind == EventKind::ActionExecuted).await.unwrap();
+        assert_eq!(event.payload["content"], "Nothing new today.");
+    }
+}
@@ -0,0 +1,206 @@
+//! Agent-to-agent messaging — `send_to_agent` lets one agent hand a message to
+//! another agent, which picks it up as a fresh run.
+//!
+//! Lighter than a sub-agent spawn: there is no parent/child link, no depth
+//! tracking and no waiting. The sender is recorded in the run's trigger reason
+//! so the target (and the audit trail) knows who asked. Which agents may
+//! message which is governed by `agents.agentToAgent.allow` in config.
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use clawforge_core::{JobTrigger, Message, Tool};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tracing::info;
+use uuid::Uuid;
+
+/// Wildcard accepted on either side of the allow matrix.
+const ANY_AGENT: &str = "*";
+
+/// Sender → allowed targets. Empty matrix denies everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AgentMessagePolicy {
+    #[serde(default)]
+    pub allow: HashMap<String, Vec<String>>,
+}
+
+impl AgentMessagePolicy {
+    pub fn new(allow: HashMap<String, Vec<String>>) -> Self {
+        Self { allow }
+    }
+
+    /// Whether `sender` may message `target`. An agent never messages itself.
+    pub fn allows(&self, sender: &str, target: &str) -> bool {
+        if sender == target {
+            return false;
+        }
+        [sender, ANY_AGENT]
+            .iter()
+            .filter_map(|key| self.allow.get(*key))
+            .flatten()
+            .any(|t| t == target || t == ANY_AGENT)
+    }
+}
+
+/// Input for send-to-agent.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendToAgentInput {
+    /// Name of the target agent (from config).
+    pub agent: String,
+    pub message: String,
+    /// Target session to continue, if the caller knows one.
+    pub session_id: Option<String>,
+}
+
+/// Output from send-to-agent.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendToAgentOutput {
+    pub ok: bool,
+    pub agent: String,
+    pub run_id: String,
+}
+
+/// Trigger reason for a run created by another agent.
+pub fn agent_message_reason(sender: &str, input: &SendToAgentInput) -> String {
+    match &input.session_id {
+        Some(session) => format!("Message from agent '{}' (session {}): {}", sender, session, input.message),
+        None => format!("Message from agent '{}': {}", sender, input.message),
+    }
+}
+
+/// `send_to_agent` tool bound to the calling agent.
+pub struct SendToAgentTool {
+    sender: String,
+    policy: AgentMessagePolicy,
+    /// Agent name → agent ID, for every agent that can be addressed.
+    agents: HashMap<String, Uuid>,
+    scheduler_tx: mpsc::Sender<Message>,
+}
+
+impl SendToAgentTool {
+    pub fn new(
+        sender: impl Into<String>,
+        policy: AgentMessagePolicy,
+        agents: HashMap<String, Uuid>,
+        scheduler_tx: mpsc::Sender<Message>,
+    ) -> Self {
+        Self { sender: sender.into(), policy, agents, scheduler_tx }
+    }
+
+    /// Check the allow matrix and queue a run on the target agent.
+    pub async fn send(&self, input: SendToAgentInput) -> Result<SendToAgentOutput> {
+        if !self.policy.allows(&self.sender, &input.agent) {
+            bail!("Agent '{}' is not allowed to message agent '{}'", self.sender, input.agent);
+        }
+        let agent_id = *self
+            .agents
+            .get(&input.agent)
+            .ok_or_else(|| anyhow!("Unknown agent '{}'", input.agent))?;
+
+        let run_id = Uuid::new_v4();
+        self.scheduler_tx
+            .send(Message::ScheduleJob(JobTrigger {
+                run_id,
+                agent_id,
+                trigger_reason: agent_message_reason(&self.sender, &input),
+            }))
+            .await
+            .map_err(|_| anyhow!("Scheduler channel closed"))?;
+
+        info!(from = %self.sender, to = %input.agent, %run_id, "Queued agent-to-agent message");
+        Ok(SendToAgentOutput { ok: true, agent: input.agent, run_id: run_id.to_string() })
+    }
+}
+
+#[async_trait]
+impl Tool for SendToAgentTool {
+    fn name(&self) -> &str {
+        "send_to_agent"
+    }
+
+    fn description(&self) -> &str {
+        "Send a message to another agent. The target starts a new run with your message and works on it independently; this call does not wait for a reply."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "agent": {
+                    "type": "string",
+                    "description": "Name of the agent to message"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "What you want the other agent to do or know"
+                },
+                "sessionId": {
+                    "type": "string",
+                    "description": "Optional session of the target agent to continue"
+                }
+            },
+            "required": ["agent", "message"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let input: SendToAgentInput = serde_json::from_value(args)?;
+        let output = self.send(input).await?;
+        Ok(serde_json::to_string(&output)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(entries: &[(&str, &[&str])]) -> AgentMessagePolicy {
+        AgentMessagePolicy::new(
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn matrix_and_wildcards() {
+        let p = policy(&[("planner", &["coder"]), ("*", &["notifier"])]);
+        assert!(p.allows("planner", "coder"));
+        assert!(p.allows("coder", "notifier"));
+        assert!(!p.allows("coder", "planner"));
+        assert!(!p.allows("notifier", "notifier"));
+        assert!(!AgentMessagePolicy::default().allows("a", "b"));
+    }
+
+    #[tokio::test]
+    async fn queues_run_with_sender_in_reason() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let coder = Uuid::new_v4();
+        let tool = SendToAgentTool::new(
+            "planner",
+            policy(&[("planner", &["*"])]),
+            HashMap::from([("coder".to_string(), coder)]),
+            tx,
+        );
+
+        let out = tool
+            .execute(serde_json::json!({ "agent": "coder", "message": "fix the build" }))
+            .await
+            .unwrap();
+        assert!(out.contains("\"ok\":true"));
+
+        let Some(Message::ScheduleJob(trigger)) = rx.recv().await else { panic!("no job queued") };
+        assert_eq!(trigger.agent_id, coder);
+        assert_eq!(trigger.trigger_reason, "Message from agent 'planner': fix the build");
+
+        let denied = tool
+            .execute(serde_json::json!({ "agent": "planner", "message": "loop" }))
+            .await;
+        assert!(denied.is_err());
+    }
+}
@@ -0,0 +1,293 @@
+//! Artifacts — named outputs too big or too rich for a chat message.
+//!
+//! The `artifact` tool stores an HTML page, SVG chart, CSV table or Mermaid
+//! diagram in the shared `ArtifactStore` and returns the URL the gateway
+//! serves it at (`/artifacts/{id}`). Ids are unguessable because the route is
+//! public, like share links. Channels turn the tool output into an
+//! `ArtifactLink` and post a link, or an image preview where one exists.
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use clawforge_core::Tool;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::RwLock;
+use uuid::Uuid;
+
+/// Largest accepted artifact, in bytes.
+pub const MAX_ARTIFACT_BYTES: usize = 2 * 1024 * 1024;
+/// Artifacts kept before the oldest are evicted.
+const MAX_ARTIFACTS: usize = 500;
+const MAX_NAME_CHARS: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactKind {
+    Html,
+    Svg,
+    Csv,
+    Mermaid,
+}
+
+impl ArtifactKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.trim().to_lowercase().as_str() {
+            "html" => Some(Self::Html),
+            "svg" => Some(Self::Svg),
+            "csv" => Some(Self::Csv),
+            "mermaid" | "mmd" => Some(Self::Mermaid),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Svg => "svg",
+            Self::Csv => "csv",
+            Self::Mermaid => "mermaid",
+        }
+    }
+
+    /// Content type the artifact is served with. Mermaid is served as an
+    /// HTML page that renders the diagram.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Html | Self::Mermaid => "text/html; charset=utf-8",
+            Self::Svg => "image/svg+xml",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    /// CSP for serving agent-written markup on a public URL: pages run in a
+    /// sandboxed origin, SVG and CSV may not run scripts at all.
+    pub fn content_security_policy(self) -> &'static str {
+        match self {
+            Self::Html | Self::Mermaid => "sandbox allow-scripts",
+            Self::Svg => "default-src 'none'; style-src 'unsafe-inline'",
+            Self::Csv => "default-src 'none'",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Artifact {
+    pub id: String,
+    pub name: String,
+    pub kind: ArtifactKind,
+    #[serde(skip)]
+    pub content: String,
+    pub size_bytes: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Artifact {
+    /// `Content-Disposition` value; CSV gets a file name for downloads.
+    pub fn content_disposition(&self) -> String {
+        match self.kind {
+            ArtifactKind::Csv => format!("inline; filename=\"{}.csv\"", self.name.replace(['"', '\\'], "")),
+            _ => "inline".to_string(),
+        }
+    }
+
+    /// Body to serve: the content itself, or an HTML page for Mermaid.
+    pub fn render(&self) -> String {
+        match self.kind {
+            ArtifactKind::Mermaid => format!(
+                "<!doctype html>\n<html><head><meta charset=\"utf-8\"><title>{}</title>\
+                 <script type=\"module\">import mermaid from 'https://cdn.jsdelivr.net/npm/mermaid@11/dist/mermaid.esm.min.mjs'; mermaid.initialize({{ startOnLoad: true }});</script>\
+                 </head><body><pre class=\"mermaid\">\n{}\n</pre></body></html>\n",
+                escape_html(&self.name),
+                escape_html(&self.content)
+            ),
+            _ => self.content.clone(),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// ---------------------------------------------------------------------------
+// Store
+// ---------------------------------------------------------------------------
+
+/// In-memory artifact store shared by the tool and the gateway route.
+#[derive(Default)]
+pub struct ArtifactStore {
+    artifacts: RwLock<HashMap<String, Artifact>>,
+    order: RwLock<VecDeque<String>>,
+}
+
+impl ArtifactStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put(&self, name: &str, kind: ArtifactKind, content: String) -> Result<Artifact> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_CHARS || name.chars().any(char::is_control) {
+            bail!("Artifact name must be a single line of 1-{} characters", MAX_NAME_CHARS);
+        }
+        if content.trim().is_empty() {
+            bail!("Artifact content is empty");
+        }
+        if content.len() > MAX_ARTIFACT_BYTES {
+            bail!("Artifact is {} bytes (max {})", content.len(), MAX_ARTIFACT_BYTES);
+        }
+        if kind == ArtifactKind::Svg && !content.contains("<svg") {
+            bail!("SVG artifact has no <svg> element");
+        }
+
+        let artifact = Artifact {
+            id: format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+            name: name.to_string(),
+            kind,
+            size_bytes: content.len(),
+            content,
+            created_at: Utc::now(),
+        };
+        let mut artifacts = self.artifacts.write().unwrap();
+        let mut order = self.order.write().unwrap();
+        while order.len() >= MAX_ARTIFACTS {
+            if let Some(oldest) = order.pop_front() {
+                artifacts.remove(&oldest);
+            }
+        }
+        order.push_back(artifact.id.clone());
+        artifacts.insert(artifact.id.clone(), artifact.clone());
+        Ok(artifact)
+    }
+
+    pub fn get(&self, id: &str) -> Option<Artifact> {
+        self.artifacts.read().unwrap().get(id).cloned()
+    }
+
+    /// Newest first, without content.
+    pub fn list(&self) -> Vec<Artifact> {
+        let artifacts = self.artifacts.read().unwrap();
+        self.order.read().unwrap().iter().rev().filter_map(|id| artifacts.get(id).cloned()).collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tool
+// ---------------------------------------------------------------------------
+
+/// `artifact` tool writing into a shared store.
+pub struct ArtifactTool {
+    store: std::sync::Arc<ArtifactStore>,
+    /// Public origin of the gateway, e.g. `https://claw.example.com`.
+    base_url: String,
+    /// Mermaid rendering service for PNG previews (e.g. `https://mermaid.ink`).
+    mermaid_renderer: Option<String>,
+}
+
+impl ArtifactTool {
+    pub fn new(store: std::sync::Arc<ArtifactStore>, base_url: impl Into<String>) -> Self {
+        Self { store, base_url: base_url.into().trim_end_matches('/').to_string(), mermaid_renderer: None }
+    }
+
+    /// Offer PNG previews of Mermaid diagrams rendered by `renderer`. Off by
+    /// default because the diagram source is sent to that service.
+    pub fn with_mermaid_renderer(mut self, renderer: impl Into<String>) -> Self {
+        self.mermaid_renderer = Some(renderer.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    fn preview_url(&self, artifact: &Artifact) -> Option<String> {
+        let renderer = self.mermaid_renderer.as_ref().filter(|_| artifact.kind == ArtifactKind::Mermaid)?;
+        let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(artifact.content.as_bytes());
+        Some(format!("{}/img/{}?type=png", renderer, encoded))
+    }
+}
+
+#[async_trait]
+impl Tool for ArtifactTool {
+    fn name(&self) -> &str {
+        "artifact"
+    }
+
+    fn description(&self) -> &str {
+        "Publish a named artifact (HTML page, SVG chart, CSV table or Mermaid diagram) and get a link to it. Use this for output that is too long or too rich for a chat message, then share the link."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Short title, e.g. \"Q3 revenue by region\""
+                },
+                "kind": {
+                    "type": "string",
+                    "enum": ["html", "svg", "csv", "mermaid"]
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Full document: HTML, SVG markup, CSV text or Mermaid source"
+                }
+            },
+            "required": ["name", "kind", "content"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let name = args["name"].as_str().ok_or_else(|| anyhow!("Missing 'name' argument"))?;
+        let kind = args["kind"].as_str().ok_or_else(|| anyhow!("Missing 'kind' argument"))?;
+        let kind = ArtifactKind::parse(kind).ok_or_else(|| anyhow!("Unknown artifact kind '{}'. Valid: html, svg, csv, mermaid", kind))?;
+        let content = args["content"].as_str().ok_or_else(|| anyhow!("Missing 'content' argument"))?;
+
+        let artifact = self.store.put(name, kind, content.to_string())?;
+        let mut output = serde_json::json!({
+            "artifact": {
+                "id": artifact.id,
+                "name": artifact.name,
+                "kind": kind,
+                "url": format!("{}/artifacts/{}", self.base_url, artifact.id),
+            }
+        });
+        if let Some(preview) = self.preview_url(&artifact) {
+            output["artifact"]["preview_url"] = Value::String(preview);
+        }
+        Ok(output.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn artifacts_are_stored_and_linked() {
+        let store = Arc::new(ArtifactStore::new());
+        let tool = ArtifactTool::new(store.clone(), "https://claw.example.com/").with_mermaid_renderer("https://mermaid.ink");
+
+        let out: Value = serde_json::from_str(
+            &tool
+                .execute(serde_json::json!({ "name": "Flow", "kind": "mermaid", "content": "graph TD; A-->B" }))
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        let id = out["artifact"]["id"].as_str().unwrap();
+        assert_eq!(out["artifact"]["url"], format!("https://claw.example.com/artifacts/{}", id));
+        assert!(out["artifact"]["preview_url"].as_str().unwrap().starts_with("https://mermaid.ink/img/"));
+
+        let stored = store.get(id).unwrap();
+        assert!(stored.render().contains("A--&gt;B"));
+        assert_eq!(stored.kind.content_type(), "text/html; charset=utf-8");
+
+        let csv = tool.execute(serde_json::json!({ "name": "t", "kind": "csv", "content": "a,b\n1,2" })).await.unwrap();
+        assert!(!csv.contains("preview_url"));
+        assert!(tool.execute(serde_json::json!({ "name": "x", "kind": "svg", "content": "<div/>" })).await.is_err());
+        assert!(tool.execute(serde_json::json!({ "name": "x", "kind": "pdf", "content": "..." })).await.is_err());
+        assert_eq!(store.list().len(), 2);
+    }
+}
@@ -70,6 +70,8 @@ pub enum ExecHost {
     Gateway,
     /// Run inside a sandbox (Docker/ephemeral environment).
     Sandbox,
+    /// Run on a registered remote host (node host or SSH).
+    Node,
 }
 
 // ---------------------------------------------------------------------------
@@ -1,12 +1,70 @@
+//! `browser.control` tool: drive a real browser through element ids, not raw CDP.
+//!
+//! The model takes a `snapshot` (an accessibility outline where each element
+//! has an id like `e7`), then acts on ids: `click`, `type`, `press`,
+//! `scroll`, `wait_for`. Each agent keeps one browser session with its own
+//! persistent profile, reused across calls. With a download manager, files
+//! the page downloads are quarantined, scanned and listed in the result.
+//! `screenshot` (optionally the full page) and `pdf` hand the capture to the
+//! media pipeline, which describes images and summarizes documents.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail};
 use async_trait::async_trait;
+use clawforge_browser::{BrowserActions, LaunchOptions, PdfOptions, ScrollTarget, SessionPool, WaitTarget};
 use clawforge_core::traits::Tool;
+use media::{MediaPayload, MediaPipeline};
 use serde_json::json;
+use tracing::warn;
+use uuid::Uuid;
 
-pub struct BrowserTool {}
+use crate::downloads::DownloadManager;
+
+pub struct BrowserTool {
+    pool: Arc<SessionPool>,
+    agent: String,
+    options: LaunchOptions,
+    downloads: Option<DownloadManager>,
+    /// Pipeline, run and agent that screenshots and PDFs are handed to.
+    media: Option<(Arc<MediaPipeline>, Uuid, Uuid)>,
+}
 
 impl BrowserTool {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(pool: Arc<SessionPool>, agent: impl Into<String>) -> Self {
+        Self { pool, agent: agent.into(), options: LaunchOptions::default(), downloads: None, media: None }
+    }
+
+    /// Options for the agent's browser when this call has to launch it.
+    pub fn with_options(mut self, options: LaunchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Route the page's downloads through `downloads`.
+    pub fn with_downloads(mut self, downloads: DownloadManager) -> Self {
+        self.downloads = Some(downloads);
+        self
+    }
+
+    /// Send screenshots and PDFs to `pipeline` as media of the given run.
+    pub fn with_media(mut self, pipeline: Arc<MediaPipeline>, run_id: Uuid, agent_id: Uuid) -> Self {
+        self.media = Some((pipeline, run_id, agent_id));
+        self
+    }
+
+    /// Hand captures to the pipeline; returns how many it accepted.
+    async fn deliver(&self, payloads: Vec<MediaPayload>) -> anyhow::Result<usize> {
+        let (pipeline, run_id, agent_id) = self.media.as_ref().ok_or_else(|| anyhow!("Screenshots and PDFs need a media pipeline"))?;
+        let mut delivered = 0;
+        for payload in payloads {
+            match pipeline.handle_media(*run_id, *agent_id, payload).await {
+                Ok(()) => delivered += 1,
+                Err(e) => warn!(error = %e, "Failed to deliver browser capture"),
+            }
+        }
+        Ok(delivered)
     }
 }
 
@@ -17,7 +75,7 @@ impl Tool for BrowserTool {
     }
 
     fn description(&self) -> &str {
-        "Controls a headless Chrome browser using CDP to navigate, click, and evaluate JS."
+        "Control a web browser. Take a `snapshot` to list page elements with ids, then click/type/scroll by id. `screenshot` and `pdf` capture the page for archiving or summary. Logins persist between runs."
     }
 
     fn parameters(&self) -> serde_json::Value {
@@ -26,22 +84,103 @@ impl Tool for BrowserTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "description": "Action to perform (goto, click, evaluate)"
+                    "enum": ["navigate", "snapshot", "click", "type", "press", "scroll", "wait_for", "screenshot", "pdf"],
+                    "description": "What to do; most actions are followed by a new snapshot"
                 },
-                "url": { "type": "string" },
-                "selector": { "type": "string" },
-                "code": { "type": "string" }
+                "url": { "type": "string", "description": "For navigate" },
+                "element": { "type": "string", "description": "Element id from the latest snapshot (e.g. \"e7\") for click, type and scroll" },
+                "text": { "type": "string", "description": "Text to type, or text to wait for" },
+                "key": { "type": "string", "description": "Key to press, e.g. Enter, Tab, Escape" },
+                "selector": { "type": "string", "description": "CSS selector to wait for" },
+                "pixels": { "type": "integer", "description": "Scroll distance; negative scrolls up (default 600)" },
+                "timeout_secs": { "type": "integer", "description": "How long wait_for waits (default 10)" },
+                "full_page": { "type": "boolean", "description": "Screenshot the whole scrollable page, not just the viewport" },
+                "landscape": { "type": "boolean", "description": "Print the pdf in landscape" }
             },
             "required": ["action"]
         })
     }
 
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<String> {
-        let action = args.get("action")
-            .and_then(|a| a.as_str())
-            .unwrap_or("unknown");
+        let action = args["action"].as_str().ok_or_else(|| anyhow!("Missing 'action' argument"))?;
+        let arg = |name: &str| args[name].as_str().ok_or_else(|| anyhow!("'{}' needs '{}'", action, name));
+
+        let mut options = self.options.clone();
+        if let Some(downloads) = &self.downloads {
+            options.download_dir = Some(downloads.incoming_dir(&self.agent)?);
+        }
+        let session = self.pool.acquire(&self.agent, options).await?;
+        let mut session = session.lock().await;
+        let mut browser = BrowserActions::new(&mut session);
+
+        let mut done = match action {
+            "snapshot" => return Ok(browser.snapshot().await?.render()),
+            "screenshot" | "pdf" => {
+                let capturer = browser.capturer().await?;
+                let payloads = match action {
+                    "pdf" => {
+                        let options = PdfOptions { landscape: args["landscape"].as_bool().unwrap_or(false), ..Default::default() };
+                        vec![capturer.print_pdf(&options).await?]
+                    }
+                    _ if args["full_page"].as_bool() == Some(true) => capturer.capture_full_page().await?,
+                    _ => vec![capturer.capture_viewport().await?],
+                };
+                let captured = payloads.len();
+                let delivered = self.deliver(payloads).await?;
+                return Ok(format!("Captured {} {}; {} sent to the media pipeline", captured, if action == "pdf" { "PDF" } else { "image(s)" }, delivered));
+            }
+            "navigate" => {
+                let url = arg("url")?;
+                browser.navigate(url).await?;
+                format!("Opened {}", url)
+            }
+            "click" => {
+                let id = arg("element")?;
+                browser.click(id).await?;
+                format!("Clicked {}", id)
+            }
+            "type" => {
+                let id = arg("element")?;
+                browser.type_text(id, arg("text")?).await?;
+                format!("Typed into {}", id)
+            }
+            "press" => {
+                let key = arg("key")?;
+                browser.press(key).await?;
+                format!("Pressed {}", key)
+            }
+            "scroll" => match args["element"].as_str() {
+                Some(id) => {
+                    browser.scroll(ScrollTarget::Element(id)).await?;
+                    format!("Scrolled {} into view", id)
+                }
+                None => {
+                    let pixels = args["pixels"].as_i64().unwrap_or(600);
+                    browser.scroll(ScrollTarget::By(pixels)).await?;
+                    format!("Scrolled {} px", pixels)
+                }
+            },
+            "wait_for" => {
+                let target = match (args["selector"].as_str(), args["text"].as_str()) {
+                    (Some(selector), _) => WaitTarget::Selector(selector.to_string()),
+                    (None, Some(text)) => WaitTarget::Text(text.to_string()),
+                    (None, None) => bail!("'wait_for' needs 'selector' or 'text'"),
+                };
+                let timeout = args["timeout_secs"].as_u64().map(Duration::from_secs);
+                browser.wait_for(&target, timeout).await?;
+                "Found it".to_string()
+            }
+            other => bail!("Unknown browser action '{}'", other),
+        };
+
+        if let Some(downloads) = &self.downloads {
+            for download in downloads.collect_incoming(&self.agent).await? {
+                done.push('\n');
+                done.push_str(&download.summary());
+            }
+        }
 
-        // Real implementation would use `chromiumoxide` or `fantoccini`
-        Ok(format!("Browser Tool Mock: Performed action '{}'", action))
+        // Hand back the page as it is now so the next call has fresh ids.
+        Ok(format!("{}\n\n{}", done, browser.snapshot().await?.render()))
     }
 }
@@ -0,0 +1,267 @@
+//! Live model catalog sync.
+//!
+//! Fetches model lists (and, where the API exposes it, pricing) from
+//! OpenRouter, OpenAI and Anthropic, folds them into the shared
+//! `ModelCatalog`, and pushes prices into the `CostTracker`. Models that
+//! disappear from a provider's list are flagged deprecated; the ones agents
+//! still use are logged and reported.
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use infra::CostTracker;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::model_catalog::{ModelCatalog, ModelEntry, ModelPricing};
+
+const OPENROUTER_URL: &str = "https://openrouter.ai/api/v1";
+const OPENAI_URL: &str = "https://api.openai.com/v1";
+const ANTHROPIC_URL: &str = "https://api.anthropic.com/v1";
+
+/// OpenAI lists every model it serves; these are not chat models.
+const OPENAI_NON_CHAT: &[&str] = &["embedding", "tts", "whisper", "dall-e", "moderation", "transcribe", "image", "realtime"];
+
+/// One provider to pull models from.
+#[derive(Debug, Clone)]
+pub struct CatalogSource {
+    /// `openrouter`, `openai` or `anthropic`.
+    pub provider: String,
+    pub api_key: Option<String>,
+    /// Override the API base (proxies, tests).
+    pub base_url: Option<String>,
+}
+
+/// Outcome of one sync pass.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct SyncReport {
+    /// Models fetched per provider.
+    pub fetched: Vec<(String, usize)>,
+    /// Providers that failed, with the error.
+    pub errors: Vec<(String, String)>,
+    /// Models flagged deprecated by this pass.
+    pub newly_deprecated: Vec<String>,
+    /// Deprecated models that agents are configured to use.
+    pub deprecated_in_use: Vec<String>,
+}
+
+pub struct CatalogSync {
+    catalog: Arc<RwLock<ModelCatalog>>,
+    sources: Vec<CatalogSource>,
+    in_use: Vec<String>,
+    costs: Option<CostTracker>,
+    client: reqwest::Client,
+}
+
+impl CatalogSync {
+    pub fn new(catalog: Arc<RwLock<ModelCatalog>>) -> Self {
+        Self {
+            catalog,
+            sources: Vec::new(),
+            in_use: Vec::new(),
+            costs: None,
+            client: reqwest::Client::builder().timeout(Duration::from_secs(30)).build().unwrap_or_default(),
+        }
+    }
+
+    /// OpenRouter (public), plus OpenAI and Anthropic when their API keys are set.
+    pub fn from_env(catalog: Arc<RwLock<ModelCatalog>>) -> Self {
+        let mut sync = Self::new(catalog).with_source("openrouter", std::env::var("OPENROUTER_API_KEY").ok());
+        if let Ok(key) = std::env::var("OPENAI_API_KEY") {
+            sync = sync.with_source("openai", Some(key));
+        }
+        if let Ok(key) = std::env::var("ANTHROPIC_API_KEY") {
+            sync = sync.with_source("anthropic", Some(key));
+        }
+        sync
+    }
+
+    pub fn with_source(mut self, provider: impl Into<String>, api_key: Option<String>) -> Self {
+        self.sources.push(CatalogSource { provider: provider.into(), api_key, base_url: None });
+        self
+    }
+
+    /// Models agents are configured with, checked for deprecation after each sync.
+    pub fn with_models_in_use(mut self, models: Vec<String>) -> Self {
+        self.in_use = models;
+        self
+    }
+
+    /// Keep the tracker's price table in step with the catalog.
+    pub fn with_cost_tracker(mut self, costs: CostTracker) -> Self {
+        self.costs = Some(costs);
+        self
+    }
+
+    pub fn catalog(&self) -> Arc<RwLock<ModelCatalog>> {
+        Arc::clone(&self.catalog)
+    }
+
+    /// Fetch every source once. A failing provider leaves its models as they were.
+    pub async fn sync_once(&self) -> SyncReport {
+        let mut report = SyncReport::default();
+        for source in &self.sources {
+            match self.fetch(source).await {
+                Ok(models) => {
+                    report.fetched.push((source.provider.clone(), models.len()));
+                    let deprecated = self.catalog.write().unwrap().apply_live(&source.provider, models);
+                    report.newly_deprecated.extend(deprecated);
+                }
+                Err(e) => {
+                    warn!(provider = %source.provider, error = %e, "Model catalog sync failed");
+                    report.errors.push((source.provider.clone(), e.to_string()));
+                }
+            }
+        }
+
+        let catalog = self.catalog.read().unwrap();
+        report.deprecated_in_use = catalog.deprecated_in_use(self.in_use.iter().map(String::as_str)).into_iter().map(|m| m.id.clone()).collect();
+        for model in &report.deprecated_in_use {
+            warn!(%model, "Model in use is deprecated by its provider");
+        }
+        if let Some(costs) = &self.costs {
+            costs.set_prices(catalog.prices().into_iter().map(|(id, p)| (id, p.input_per_million, p.output_per_million)));
+        }
+        info!(fetched = ?report.fetched, deprecated = report.newly_deprecated.len(), "Model catalog synced");
+        report
+    }
+
+    /// Sync now and then every `interval`.
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.sync_once().await;
+            }
+        })
+    }
+
+    async fn fetch(&self, source: &CatalogSource) -> Result<Vec<ModelEntry>> {
+        let (default_url, parse): (&str, fn(&Value) -> Vec<ModelEntry>) = match source.provider.as_str() {
+            "openrouter" => (OPENROUTER_URL, parse_openrouter),
+            "openai" => (OPENAI_URL, parse_openai),
+            "anthropic" => (ANTHROPIC_URL, parse_anthropic),
+            other => bail!("No model list API known for provider '{}'", other),
+        };
+        let base = source.base_url.as_deref().unwrap_or(default_url).trim_end_matches('/');
+        let mut request = self.client.get(format!("{}/models", base));
+        if source.provider == "anthropic" {
+            request = request.query(&[("limit", "1000")]).header("anthropic-version", "2023-06-01");
+            if let Some(key) = &source.api_key {
+                request = request.header("x-api-key", key);
+            }
+        } else if let Some(key) = &source.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            bail!("{} returned HTTP {}", source.provider, response.status());
+        }
+        let body: Value = response.json().await?;
+        Ok(parse(&body))
+    }
+}
+
+fn data(body: &Value) -> &[Value] {
+    body["data"].as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// OpenRouter prices are USD per token, as strings.
+fn per_million(value: &Value) -> Option<f64> {
+    let per_token: f64 = match value {
+        Value::String(s) => s.parse().ok()?,
+        other => other.as_f64()?,
+    };
+    Some(per_token * 1_000_000.0)
+}
+
+pub(crate) fn parse_openrouter(body: &Value) -> Vec<ModelEntry> {
+    data(body)
+        .iter()
+        .filter_map(|m| {
+            let id = m["id"].as_str()?.to_string();
+            let has = |list: &Value, item: &str| list.as_array().is_some_and(|l| l.iter().any(|v| v == item));
+            let pricing = match (per_million(&m["pricing"]["prompt"]), per_million(&m["pricing"]["completion"])) {
+                (Some(input), Some(output)) => Some(ModelPricing { input_per_million: input, output_per_million: output }),
+                _ => None,
+            };
+            Some(ModelEntry {
+                display_name: m["name"].as_str().unwrap_or(&id).to_string(),
+                provider: "openrouter".into(),
+                context_window: m["context_length"].as_u64().unwrap_or_default() as usize,
+                supports_vision: has(&m["architecture"]["input_modalities"], "image"),
+                supports_tools: has(&m["supported_parameters"], "tools"),
+                pricing,
+                deprecated: m["expiration_date"].as_str().is_some_and(|d| !d.is_empty()),
+                id,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn parse_openai(body: &Value) -> Vec<ModelEntry> {
+    data(body)
+        .iter()
+        .filter_map(|m| m["id"].as_str())
+        .filter(|id| !OPENAI_NON_CHAT.iter().any(|skip| id.contains(skip)))
+        .map(|id| ModelEntry { id: id.to_string(), provider: "openai".into(), supports_tools: true, ..Default::default() })
+        .collect()
+}
+
+pub(crate) fn parse_anthropic(body: &Value) -> Vec<ModelEntry> {
+    data(body)
+        .iter()
+        .filter_map(|m| {
+            let id = m["id"].as_str()?.to_string();
+            Some(ModelEntry {
+                display_name: m["display_name"].as_str().unwrap_or_default().to_string(),
+                provider: "anthropic".into(),
+                supports_vision: true,
+                supports_tools: true,
+                id,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn live_lists_merge_and_flag_deprecations() {
+        let mut catalog = ModelCatalog::new();
+        let anthropic = parse_anthropic(&json!({"data": [
+            {"id": "claude-sonnet-4-5-20250929", "display_name": "Claude Sonnet 4.5", "type": "model"}
+        ]}));
+        // The alias survives through its dated snapshot; Opus is gone.
+        assert_eq!(catalog.apply_live("anthropic", anthropic), vec!["claude-opus-4-5".to_string()]);
+        assert!(!catalog.get("claude-sonnet-4-5").unwrap().deprecated);
+        let in_use = catalog.deprecated_in_use(["claude-opus-4-5", "gpt-4o"]);
+        assert_eq!(in_use.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), ["claude-opus-4-5"]);
+
+        let openrouter = parse_openrouter(&json!({"data": [{
+            "id": "openai/gpt-4o",
+            "name": "OpenAI: GPT-4o",
+            "context_length": 128000,
+            "pricing": {"prompt": "0.0000025", "completion": "0.00001"},
+            "architecture": {"input_modalities": ["text", "image"]},
+            "supported_parameters": ["tools", "temperature"],
+            "expiration_date": "2026-12-01"
+        }]}));
+        assert_eq!(catalog.apply_live("openrouter", openrouter), Vec::<String>::new());
+        let entry = catalog.get("openai/gpt-4o").unwrap();
+        assert!(entry.supports_vision && entry.supports_tools && entry.deprecated);
+        let pricing = entry.pricing.unwrap();
+        assert!((pricing.input_per_million - 2.5).abs() < 1e-9 && (pricing.output_per_million - 10.0).abs() < 1e-9);
+
+        let openai = parse_openai(&json!({"data": [{"id": "gpt-4o"}, {"id": "text-embedding-3-small"}]}));
+        assert_eq!(openai.len(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+//! Calendar connector: upcoming events from an iCalendar (ICS) feed, such as
+//! the private iCal address Google Calendar, Outlook and Fastmail publish.
+//!
+//! Recurring events are not expanded; a recurring event shows up when its
+//! first occurrence falls in the window. `TZID` times are read as UTC for
+//! windowing and reported as written.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{get_text, Connector, ConnectorContext};
+
+fn default_days_ahead() -> i64 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarConfig {
+    /// ICS feed URL (`webcal://` is accepted).
+    pub url: String,
+    /// How many days of events to include, starting now.
+    #[serde(default = "default_days_ahead")]
+    pub days_ahead: i64,
+}
+
+pub struct CalendarConnector {
+    config: CalendarConfig,
+    http: reqwest::Client,
+}
+
+impl CalendarConnector {
+    pub fn new(config: CalendarConfig, http: reqwest::Client) -> Self {
+        Self { config, http }
+    }
+}
+
+#[async_trait]
+impl Connector for CalendarConnector {
+    fn kind(&self) -> &str {
+        "calendar"
+    }
+
+    async fn fetch(&self, ctx: &ConnectorContext) -> Result<Value> {
+        let url = match self.config.url.strip_prefix("webcal://") {
+            Some(rest) => format!("https://{}", rest),
+            None => self.config.url.clone(),
+        };
+        let ics = get_text(self.http.get(&url), "calendar feed").await?;
+        let until = ctx.now + Duration::days(self.config.days_ahead.max(1));
+        let mut events: Vec<Event> = parse_events(&ics)
+            .into_iter()
+            .filter(|e| e.end.unwrap_or(e.start) >= ctx.now && e.start < until)
+            .collect();
+        events.sort_by_key(|e| e.start);
+        Ok(serde_json::json!({ "events": events.iter().map(Event::to_json).collect::<Vec<_>>() }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub summary: String,
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    pub all_day: bool,
+    pub location: Option<String>,
+    /// `TZID` the times were written in, when not UTC.
+    pub timezone: Option<String>,
+}
+
+impl Event {
+    fn to_json(&self) -> Value {
+        serde_json::json!({
+            "summary": self.summary,
+            "start": if self.all_day { self.start.format("%Y-%m-%d").to_string() } else { self.start.to_rfc3339() },
+            "end": self.end.map(|end| end.to_rfc3339()),
+            "all_day": self.all_day,
+            "location": self.location,
+            "timezone": self.timezone,
+        })
+    }
+}
+
+/// All `VEVENT`s with a parseable `DTSTART`.
+pub fn parse_events(ics: &str) -> Vec<Event> {
+    // Unfold continuation lines (RFC 5545 §3.1).
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<Vec<(String, String, String)>> = None;
+    for line in lines {
+        match line.trim_end() {
+            "BEGIN:VEVENT" => current = Some(Vec::new()),
+            "END:VEVENT" => {
+                if let Some(props) = current.take() {
+                    events.extend(build_event(&props));
+                }
+            }
+            line => {
+                let (Some(props), Some((key, value))) = (current.as_mut(), line.split_once(':')) else { continue };
+                let (name, params) = key.split_once(';').unwrap_or((key, ""));
+                props.push((name.to_ascii_uppercase(), params.to_string(), value.to_string()));
+            }
+        }
+    }
+    events
+}
+
+fn build_event(props: &[(String, String, String)]) -> Option<Event> {
+    let get = |name: &str| props.iter().find(|(n, _, _)| n == name);
+    let (_, start_params, start_raw) = get("DTSTART")?;
+    let (start, all_day) = parse_time(start_raw)?;
+    let end = get("DTEND").and_then(|(_, _, raw)| parse_time(raw)).map(|(t, _)| t);
+    let timezone = start_params
+        .split(';')
+        .find_map(|p| p.strip_prefix("TZID="))
+        .map(|tz| tz.trim_matches('"').to_string());
+    Some(Event {
+        summary: get("SUMMARY").map(|(_, _, v)| unescape(v)).unwrap_or_default(),
+        start,
+        end,
+        all_day,
+        location: get("LOCATION").map(|(_, _, v)| unescape(v)).filter(|l| !l.is_empty()),
+        timezone,
+    })
+}
+
+/// `20250610T090000Z`, `20250610T090000` or a `20250610` date.
+fn parse_time(raw: &str) -> Option<(DateTime<Utc>, bool)> {
+    let raw = raw.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y%m%d") {
+        return Some((date.and_hms_opt(0, 0, 0)?.and_utc(), true));
+    }
+    let naive = NaiveDateTime::parse_from_str(raw.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()?;
+    Some((naive.and_utc(), false))
+}
+
+fn unescape(value: &str) -> String {
+    value.replace("\\n", "\n").replace("\\N", "\n").replace("\\,", ",").replace("\\;", ";").replace("\\\\", "\\")
+}
@@ -0,0 +1,386 @@
+//! Declarative data source connectors for briefing-style agents.
+//!
+//! Each connector fetches one source (weather, stock quotes, an RSS feed, a
+//! calendar, a todo list) behind the same `fetch(context)` call and returns
+//! JSON. Connectors are declared in YAML, so an agent composes its sources
+//! from config instead of custom tools:
+//!
+//! ```yaml
+//! connectors:
+//!   - name: weather
+//!     kind: weather
+//!     latitude: 52.52
+//!     longitude: 13.41
+//!   - name: headlines
+//!     kind: rss
+//!     url: https://example.com/feed.xml
+//!     cache_secs: 1800
+//!     rate_limit: { max_calls: 4, window_secs: 3600 }
+//! ```
+//!
+//! Every connector is cached for `cache_secs` and rate-limited; a call over the
+//! limit gets the last cached value when there is one.
+
+pub mod calendar;
+pub mod rss;
+pub mod stocks;
+pub mod todo;
+pub mod weather;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clawforge_core::Tool;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+pub use calendar::CalendarConnector;
+pub use rss::RssConnector;
+pub use stocks::StocksConnector;
+pub use todo::TodoConnector;
+pub use weather::WeatherConnector;
+
+/// What a connector knows about the run it is fetching for.
+#[derive(Debug, Clone)]
+pub struct ConnectorContext {
+    pub now: DateTime<Utc>,
+    /// IANA zone of the agent, for local dates and forecasts.
+    pub timezone: Option<String>,
+    pub locale: Option<String>,
+}
+
+impl Default for ConnectorContext {
+    fn default() -> Self {
+        Self { now: Utc::now(), timezone: None, locale: None }
+    }
+}
+
+/// A data source.
+#[async_trait]
+pub trait Connector: Send + Sync {
+    /// Connector kind as used in `kind:` (e.g. "weather").
+    fn kind(&self) -> &str;
+
+    async fn fetch(&self, ctx: &ConnectorContext) -> Result<Value>;
+}
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub max_calls: u32,
+    pub window_secs: u64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self { max_calls: 30, window_secs: 3600 }
+    }
+}
+
+/// The source-specific part of a connector declaration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConnectorSource {
+    Weather(weather::WeatherConfig),
+    Stocks(stocks::StocksConfig),
+    Rss(rss::RssConfig),
+    Calendar(calendar::CalendarConfig),
+    Todo(todo::TodoConfig),
+}
+
+impl ConnectorSource {
+    fn default_cache_secs(&self) -> u64 {
+        match self {
+            ConnectorSource::Weather(_) => 900,
+            ConnectorSource::Stocks(_) => 300,
+            ConnectorSource::Rss(_) => 1800,
+            ConnectorSource::Calendar(_) | ConnectorSource::Todo(_) => 300,
+        }
+    }
+
+    fn build(&self, http: reqwest::Client) -> Result<Arc<dyn Connector>> {
+        Ok(match self {
+            ConnectorSource::Weather(c) => Arc::new(WeatherConnector::new(c.clone(), http)),
+            ConnectorSource::Stocks(c) => Arc::new(StocksConnector::new(c.clone(), http)),
+            ConnectorSource::Rss(c) => Arc::new(RssConnector::new(c.clone(), http)),
+            ConnectorSource::Calendar(c) => Arc::new(CalendarConnector::new(c.clone(), http)),
+            ConnectorSource::Todo(c) => Arc::new(TodoConnector::new(c.clone(), http)?),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectorConfig {
+    /// Name the agent refers to the source by.
+    pub name: String,
+    #[serde(flatten)]
+    pub source: ConnectorSource,
+    /// How long a fetched value is reused (defaults per kind).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConnectorsFile {
+    #[serde(default)]
+    pub connectors: Vec<ConnectorConfig>,
+}
+
+// ---------------------------------------------------------------------------
+// Cache + rate limit
+// ---------------------------------------------------------------------------
+
+#[derive(Default)]
+struct Throttle {
+    value: Option<(Instant, Value)>,
+    calls: VecDeque<Instant>,
+}
+
+/// A connector with its cache and rate limit.
+struct ManagedConnector {
+    connector: Arc<dyn Connector>,
+    ttl: Duration,
+    limit: RateLimit,
+    state: Mutex<Throttle>,
+}
+
+impl ManagedConnector {
+    async fn fetch(&self, name: &str, ctx: &ConnectorContext) -> Result<Value> {
+        // Held across the fetch so concurrent callers share one request.
+        let mut state = self.state.lock().await;
+        let now = Instant::now();
+        if let Some((at, value)) = &state.value {
+            if now.duration_since(*at) < self.ttl {
+                debug!(connector = %name, "Connector cache hit");
+                return Ok(value.clone());
+            }
+        }
+
+        let window = Duration::from_secs(self.limit.window_secs);
+        while state.calls.front().is_some_and(|t| now.duration_since(*t) >= window) {
+            state.calls.pop_front();
+        }
+        if state.calls.len() >= self.limit.max_calls as usize {
+            return match &state.value {
+                Some((_, stale)) => {
+                    warn!(connector = %name, "Connector rate limited; serving stale value");
+                    Ok(stale.clone())
+                }
+                None => bail!("Connector '{}' is rate limited ({} calls per {}s)", name, self.limit.max_calls, self.limit.window_secs),
+            };
+        }
+
+        state.calls.push_back(now);
+        let value = self.connector.fetch(ctx).await.with_context(|| format!("Connector '{}' failed", name))?;
+        state.value = Some((Instant::now(), value.clone()));
+        Ok(value)
+    }
+}
+
+/// The configured connectors, by name.
+#[derive(Default)]
+pub struct ConnectorSet {
+    connectors: HashMap<String, Arc<ManagedConnector>>,
+}
+
+impl ConnectorSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build every connector in a YAML `connectors:` document.
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let file: ConnectorsFile = serde_yaml::from_str(yaml).context("Invalid connectors YAML")?;
+        Self::from_configs(&file.connectors)
+    }
+
+    pub fn from_configs(configs: &[ConnectorConfig]) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(20))
+            .user_agent(concat!("clawforge/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        let mut set = Self::new();
+        for config in configs {
+            if set.connectors.contains_key(&config.name) {
+                bail!("Duplicate connector name '{}'", config.name);
+            }
+            let connector = config.source.build(http.clone())?;
+            let ttl = config.cache_secs.unwrap_or_else(|| config.source.default_cache_secs());
+            set.insert(&config.name, connector, ttl, config.rate_limit.unwrap_or_default());
+        }
+        Ok(set)
+    }
+
+    /// Add a connector with its cache TTL (seconds) and rate limit.
+    pub fn insert(&mut self, name: &str, connector: Arc<dyn Connector>, cache_secs: u64, limit: RateLimit) {
+        self.connectors.insert(
+            name.to_string(),
+            Arc::new(ManagedConnector {
+                connector,
+                ttl: Duration::from_secs(cache_secs),
+                limit,
+                state: Mutex::new(Throttle::default()),
+            }),
+        );
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.connectors.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    pub async fn fetch(&self, name: &str, ctx: &ConnectorContext) -> Result<Value> {
+        let managed = self.connectors.get(name).ok_or_else(|| anyhow!("Unknown connector '{}'", name))?;
+        managed.fetch(name, ctx).await
+    }
+
+    /// Fetch every connector concurrently. A failing source becomes
+    /// `{"error": ...}` so one outage doesn't sink the whole briefing.
+    pub async fn fetch_all(&self, ctx: &ConnectorContext) -> serde_json::Map<String, Value> {
+        let mut fetches = tokio::task::JoinSet::new();
+        for (name, managed) in &self.connectors {
+            let (name, managed, ctx) = (name.clone(), managed.clone(), ctx.clone());
+            fetches.spawn(async move {
+                let value = managed.fetch(&name, &ctx).await;
+                (name, value.unwrap_or_else(|e| serde_json::json!({ "error": format!("{e:#}") })))
+            });
+        }
+        let mut results = serde_json::Map::new();
+        while let Some(joined) = fetches.join_next().await {
+            if let Ok((name, value)) = joined {
+                results.insert(name, value);
+            }
+        }
+        results
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tool
+// ---------------------------------------------------------------------------
+
+/// `connector_fetch`: read configured data sources.
+pub struct ConnectorTool {
+    connectors: Arc<ConnectorSet>,
+}
+
+impl ConnectorTool {
+    pub fn new(connectors: Arc<ConnectorSet>) -> Self {
+        Self { connectors }
+    }
+}
+
+#[async_trait]
+impl Tool for ConnectorTool {
+    fn name(&self) -> &str {
+        "connector_fetch"
+    }
+
+    fn description(&self) -> &str {
+        "Fetch data from configured sources (weather, stock quotes, news feeds, calendar, todos). Omit the name to fetch every source."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "enum": self.connectors.names(),
+                    "description": "Connector to fetch"
+                },
+                "timezone": {
+                    "type": "string",
+                    "description": "IANA time zone for local dates"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let ctx = ConnectorContext {
+            timezone: args["timezone"].as_str().map(str::to_string),
+            ..Default::default()
+        };
+        let output = match args["name"].as_str() {
+            Some(name) => self.connectors.fetch(name, &ctx).await?,
+            None => Value::Object(self.connectors.fetch_all(&ctx).await),
+        };
+        Ok(output.to_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers shared by connectors
+// ---------------------------------------------------------------------------
+
+/// Send `request` and return the body, failing on a non-success status.
+async fn get_text(request: reqwest::RequestBuilder, what: &str) -> Result<String> {
+    let response = request.send().await.with_context(|| format!("Request to {} failed", what))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("{} returned HTTP {}", what, status);
+    }
+    Ok(response.text().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct Counter(AtomicU32);
+
+    #[async_trait]
+    impl Connector for Counter {
+        fn kind(&self) -> &str {
+            "counter"
+        }
+        async fn fetch(&self, _ctx: &ConnectorContext) -> Result<Value> {
+            Ok(serde_json::json!(self.0.fetch_add(1, Ordering::SeqCst)))
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_and_rate_limits() {
+        let ctx = ConnectorContext::default();
+        let mut set = ConnectorSet::new();
+        set.insert("cached", Arc::new(Counter(AtomicU32::new(0))), 3600, RateLimit::default());
+        set.insert("limited", Arc::new(Counter(AtomicU32::new(0))), 0, RateLimit { max_calls: 2, window_secs: 3600 });
+
+        assert_eq!(set.fetch("cached", &ctx).await.unwrap(), 0);
+        assert_eq!(set.fetch("cached", &ctx).await.unwrap(), 0);
+
+        assert_eq!(set.fetch("limited", &ctx).await.unwrap(), 0);
+        assert_eq!(set.fetch("limited", &ctx).await.unwrap(), 1);
+        // Over the limit: the last value is served instead of a third call.
+        assert_eq!(set.fetch("limited", &ctx).await.unwrap(), 1);
+
+        let all = set.fetch_all(&ctx).await;
+        assert_eq!(all.len(), 2);
+        assert!(set.fetch("missing", &ctx).await.is_err());
+    }
+
+    #[test]
+    fn parses_yaml_declarations() {
+        let file: ConnectorsFile = serde_yaml::from_str(
+            "connectors:\n  - name: weather\n    kind: weather\n    latitude: 52.52\n    longitude: 13.41\n  - name: news\n    kind: rss\n    url: https://example.com/feed.xml\n    cache_secs: 60\n    rate_limit: { max_calls: 4, window_secs: 3600 }\n",
+        )
+        .unwrap();
+        assert_eq!(file.connectors.len(), 2);
+        assert!(matches!(file.connectors[0].source, ConnectorSource::Weather(_)));
+        assert_eq!(file.connectors[1].rate_limit, Some(RateLimit { max_calls: 4, window_secs: 3600 }));
+        assert_eq!(ConnectorSet::from_configs(&file.connectors).unwrap().names(), vec!["news", "weather"]);
+    }
+}
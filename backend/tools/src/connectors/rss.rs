@@ -0,0 +1,187 @@
+//! RSS / Atom connector: the newest items of a feed.
+//!
+//! Feeds are read with a small tag scanner rather than a full XML parser; it
+//! handles RSS 2.0 `<item>` and Atom `<entry>` elements, CDATA and the
+//! predefined entities, which covers real-world news feeds.
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{get_text, Connector, ConnectorContext};
+
+fn default_max_items() -> usize {
+    10
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RssConfig {
+    pub url: String,
+    #[serde(default = "default_max_items")]
+    pub max_items: usize,
+}
+
+pub struct RssConnector {
+    config: RssConfig,
+    http: reqwest::Client,
+}
+
+impl RssConnector {
+    pub fn new(config: RssConfig, http: reqwest::Client) -> Self {
+        Self { config, http }
+    }
+}
+
+#[async_trait]
+impl Connector for RssConnector {
+    fn kind(&self) -> &str {
+        "rss"
+    }
+
+    async fn fetch(&self, _ctx: &ConnectorContext) -> Result<Value> {
+        let xml = get_text(self.http.get(&self.config.url), &self.config.url).await?;
+        let (title, items) = parse_feed(&xml, self.config.max_items)?;
+        Ok(serde_json::json!({ "feed": title, "items": items }))
+    }
+}
+
+/// Feed title and up to `max_items` items as `{title, link, published, summary}`.
+pub fn parse_feed(xml: &str, max_items: usize) -> Result<(Option<String>, Vec<Value>)> {
+    let (tag, atom) = if !elements(xml, "item").is_empty() {
+        ("item", false)
+    } else if !elements(xml, "entry").is_empty() {
+        ("entry", true)
+    } else if xml.contains("<rss") || xml.contains("<feed") {
+        return Ok((feed_title(xml), Vec::new()));
+    } else {
+        bail!("Not an RSS or Atom feed");
+    };
+
+    let items = elements(xml, tag)
+        .into_iter()
+        .take(max_items)
+        .map(|item| {
+            let link = if atom { attribute(item, "link", "href") } else { text(item, "link") };
+            let published = text(item, if atom { "updated" } else { "pubDate" })
+                .or_else(|| text(item, "published"))
+                .map(|raw| {
+                    DateTime::parse_from_rfc2822(&raw)
+                        .or_else(|_| DateTime::parse_from_rfc3339(&raw))
+                        .map(|dt| dt.to_rfc3339())
+                        .unwrap_or(raw)
+                });
+            let summary = text(item, if atom { "summary" } else { "description" }).map(|s| strip_tags(&s));
+            serde_json::json!({
+                "title": text(item, "title"),
+                "link": link,
+                "published": published,
+                "summary": summary,
+            })
+        })
+        .collect();
+    Ok((feed_title(xml), items))
+}
+
+fn feed_title(xml: &str) -> Option<String> {
+    // The first <title> precedes any item/entry in both formats.
+    let head_end = xml.find("<item").or_else(|| xml.find("<entry")).unwrap_or(xml.len());
+    text(&xml[..head_end], "title")
+}
+
+/// Inner content of every `<tag ...>...</tag>` (not nested in itself).
+fn elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}", tag), format!("</{}>", tag));
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // `<title` must not match `<titles>`.
+        if !after.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+            rest = after;
+            continue;
+        }
+        let Some(tag_end) = after.find('>') else { break };
+        if after[..tag_end].ends_with('/') {
+            found.push("");
+            rest = &after[tag_end + 1..];
+            continue;
+        }
+        let body = &after[tag_end + 1..];
+        let Some(end) = body.find(&close) else { break };
+        found.push(&body[..end]);
+        rest = &body[end + close.len()..];
+    }
+    found
+}
+
+/// Text of the first `<tag>`, with CDATA unwrapped and entities decoded.
+fn text(xml: &str, tag: &str) -> Option<String> {
+    let raw = elements(xml, tag).into_iter().next()?.trim();
+    let raw = raw.strip_prefix("<![CDATA[").and_then(|r| r.strip_suffix("]]>")).unwrap_or(raw);
+    let decoded = decode_entities(raw.trim());
+    (!decoded.is_empty()).then_some(decoded)
+}
+
+/// `attr` of the first `<tag ...>` (e.g. Atom's `<link href="..."/>`).
+fn attribute(xml: &str, tag: &str, attr: &str) -> Option<String> {
+    let open = format!("<{} ", tag);
+    let start = xml.find(&open)? + open.len();
+    let attrs = &xml[start..start + xml[start..].find('>')?];
+    let key = format!("{}=", attr);
+    let value = &attrs[attrs.find(&key)? + key.len()..];
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    Some(decode_entities(&value[..value.find(quote)?]))
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Drop HTML markup from item descriptions.
+fn strip_tags(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rss_and_atom() {
+        let rss = r#"<?xml version="1.0"?><rss><channel><title>Example News</title>
+            <item><title><![CDATA[Rates & markets]]></title><link>https://example.com/a</link>
+              <pubDate>Tue, 10 Jun 2025 07:30:00 GMT</pubDate><description>&lt;p&gt;Central bank holds.&lt;/p&gt;</description></item>
+            <item><title>Second</title></item>
+          </channel></rss>"#;
+        let (title, items) = parse_feed(rss, 1).unwrap();
+        assert_eq!(title.as_deref(), Some("Example News"));
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["title"], "Rates & markets");
+        assert_eq!(items[0]["published"], "2025-06-10T07:30:00+00:00");
+        assert_eq!(items[0]["summary"], "Central bank holds.");
+
+        let atom = r#"<feed><title>Blog</title><entry><title>Release</title>
+            <link rel="alternate" href="https://example.com/r?a=1&amp;b=2"/><updated>2025-06-01T12:00:00Z</updated></entry></feed>"#;
+        let (_, items) = parse_feed(atom, 10).unwrap();
+        assert_eq!(items[0]["link"], "https://example.com/r?a=1&b=2");
+        assert!(parse_feed("<html></html>", 5).is_err());
+    }
+}
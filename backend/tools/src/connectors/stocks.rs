@@ -0,0 +1,79 @@
+//! Stock quote connector: latest quotes from Stooq's CSV endpoint (no API key).
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{get_text, Connector, ConnectorContext};
+
+const STOOQ_URL: &str = "https://stooq.com/q/l/";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StocksConfig {
+    /// Stooq symbols, e.g. `aapl.us`, `^spx`.
+    pub symbols: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+pub struct StocksConnector {
+    config: StocksConfig,
+    http: reqwest::Client,
+}
+
+impl StocksConnector {
+    pub fn new(config: StocksConfig, http: reqwest::Client) -> Self {
+        Self { config, http }
+    }
+}
+
+#[async_trait]
+impl Connector for StocksConnector {
+    fn kind(&self) -> &str {
+        "stocks"
+    }
+
+    async fn fetch(&self, _ctx: &ConnectorContext) -> Result<Value> {
+        if self.config.symbols.is_empty() {
+            bail!("No stock symbols configured");
+        }
+        let url = self.config.base_url.as_deref().unwrap_or(STOOQ_URL);
+        let request = self.http.get(url).query(&[
+            ("s", self.config.symbols.join(" ")),
+            ("f", "sd2t2ohlcv".to_string()),
+            ("h", String::new()),
+            ("e", "csv".to_string()),
+        ]);
+        parse_quotes(&get_text(request, "Stooq").await?)
+    }
+}
+
+/// Parse `Symbol,Date,Time,Open,High,Low,Close,Volume` rows. Unknown symbols
+/// come back as `N/D` and are reported without prices.
+fn parse_quotes(csv_text: &str) -> Result<Value> {
+    let mut reader = csv::Reader::from_reader(csv_text.as_bytes());
+    let mut quotes = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let field = |i: usize| record.get(i).unwrap_or_default();
+        let number = |i: usize| field(i).parse::<f64>().ok();
+        let (open, close) = (number(3), number(6));
+        let change_percent = match (open, close) {
+            (Some(open), Some(close)) if open != 0.0 => Some(((close - open) / open * 10_000.0).round() / 100.0),
+            _ => None,
+        };
+        quotes.push(serde_json::json!({
+            "symbol": field(0),
+            "date": field(1),
+            "time": field(2),
+            "open": open,
+            "high": number(4),
+            "low": number(5),
+            "close": close,
+            "change_percent": change_percent,
+            "volume": number(7),
+        }));
+    }
+    Ok(serde_json::json!({ "quotes": quotes }))
+}
@@ -0,0 +1,109 @@
+//! Todo connector: open tasks from Todoist or a local `todo.txt` file.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+
+use super::{get_text, Connector, ConnectorContext};
+
+const TODOIST_URL: &str = "https://api.todoist.com/rest/v2/tasks";
+
+fn default_token_env() -> String {
+    "TODOIST_API_TOKEN".to_string()
+}
+
+fn default_filter() -> String {
+    "today | overdue".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum TodoConfig {
+    Todoist {
+        /// Env var holding the API token (never put the token in YAML).
+        #[serde(default = "default_token_env")]
+        token_env: String,
+        /// Todoist filter query.
+        #[serde(default = "default_filter")]
+        filter: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base_url: Option<String>,
+    },
+    TodoTxt {
+        path: PathBuf,
+    },
+}
+
+pub struct TodoConnector {
+    config: TodoConfig,
+    http: reqwest::Client,
+}
+
+impl TodoConnector {
+    pub fn new(config: TodoConfig, http: reqwest::Client) -> Result<Self> {
+        if let TodoConfig::Todoist { token_env, .. } = &config {
+            if token_env.trim().is_empty() {
+                bail!("Todoist connector needs a token_env");
+            }
+        }
+        Ok(Self { config, http })
+    }
+}
+
+#[async_trait]
+impl Connector for TodoConnector {
+    fn kind(&self) -> &str {
+        "todo"
+    }
+
+    async fn fetch(&self, _ctx: &ConnectorContext) -> Result<Value> {
+        let tasks = match &self.config {
+            TodoConfig::Todoist { token_env, filter, base_url } => {
+                // Read at fetch time so a rotated token is picked up.
+                let token = std::env::var(token_env).with_context(|| format!("{} is not set", token_env))?;
+                let request = self
+                    .http
+                    .get(base_url.as_deref().unwrap_or(TODOIST_URL))
+                    .bearer_auth(token)
+                    .query(&[("filter", filter)]);
+                let body: Vec<Value> = serde_json::from_str(&get_text(request, "Todoist").await?)?;
+                body.iter()
+                    .map(|task| {
+                        serde_json::json!({
+                            "title": task["content"],
+                            "due": task["due"]["datetime"].as_str().or(task["due"]["date"].as_str()),
+                            "priority": task["priority"],
+                            "url": task["url"],
+                        })
+                    })
+                    .collect()
+            }
+            TodoConfig::TodoTxt { path } => {
+                let text = tokio::fs::read_to_string(path)
+                    .await
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                parse_todo_txt(&text)
+            }
+        };
+        Ok(serde_json::json!({ "tasks": tasks }))
+    }
+}
+
+/// Open tasks from todo.txt lines: `(A) Call mom due:2025-06-10 +family`.
+/// Completed (`x `) and blank lines are skipped.
+fn parse_todo_txt(text: &str) -> Vec<Value> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("x "))
+        .map(|line| {
+            let (priority, rest) = match line.as_bytes() {
+                [b'(', p @ b'A'..=b'Z', b')', b' ', ..] => (Some((*p as char).to_string()), &line[4..]),
+                _ => (None, line),
+            };
+            let due = rest.split_whitespace().find_map(|word| word.strip_prefix("due:"));
+            serde_json::json!({ "title": rest, "due": due, "priority": priority })
+        })
+        .collect()
+}
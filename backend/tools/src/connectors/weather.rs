@@ -0,0 +1,104 @@
+//! Weather connector: current conditions and today's forecast from Open-Meteo
+//! (no API key needed).
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{get_text, Connector, ConnectorContext};
+
+const OPEN_METEO_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeatherConfig {
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(default)]
+    pub units: Units,
+    /// Override the API endpoint (for self-hosted Open-Meteo).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+pub struct WeatherConnector {
+    config: WeatherConfig,
+    http: reqwest::Client,
+}
+
+impl WeatherConnector {
+    pub fn new(config: WeatherConfig, http: reqwest::Client) -> Self {
+        Self { config, http }
+    }
+}
+
+#[async_trait]
+impl Connector for WeatherConnector {
+    fn kind(&self) -> &str {
+        "weather"
+    }
+
+    async fn fetch(&self, ctx: &ConnectorContext) -> Result<Value> {
+        let (temperature_unit, wind_unit) = match self.config.units {
+            Units::Metric => ("celsius", "kmh"),
+            Units::Imperial => ("fahrenheit", "mph"),
+        };
+        let url = self.config.base_url.as_deref().unwrap_or(OPEN_METEO_URL);
+        let request = self.http.get(url).query(&[
+            ("latitude", self.config.latitude.to_string()),
+            ("longitude", self.config.longitude.to_string()),
+            ("current", "temperature_2m,apparent_temperature,weather_code,wind_speed_10m".to_string()),
+            ("daily", "temperature_2m_max,temperature_2m_min,precipitation_probability_max,weather_code".to_string()),
+            ("forecast_days", "1".to_string()),
+            ("temperature_unit", temperature_unit.to_string()),
+            ("wind_speed_unit", wind_unit.to_string()),
+            ("timezone", ctx.timezone.clone().unwrap_or_else(|| "auto".to_string())),
+        ]);
+        let body: Value = serde_json::from_str(&get_text(request, "Open-Meteo").await?)?;
+        Ok(summarize(&body, self.config.units))
+    }
+}
+
+/// Flatten Open-Meteo's parallel arrays into one briefing-friendly object.
+fn summarize(body: &Value, units: Units) -> Value {
+    let current = &body["current"];
+    let daily = &body["daily"];
+    let today = |field: &str| daily[field].get(0).cloned().unwrap_or(Value::Null);
+    serde_json::json!({
+        "units": units,
+        "current": {
+            "temperature": current["temperature_2m"],
+            "feels_like": current["apparent_temperature"],
+            "wind_speed": current["wind_speed_10m"],
+            "conditions": describe_code(current["weather_code"].as_u64()),
+        },
+        "today": {
+            "high": today("temperature_2m_max"),
+            "low": today("temperature_2m_min"),
+            "precipitation_chance": today("precipitation_probability_max"),
+            "conditions": describe_code(today("weather_code").as_u64()),
+        },
+    })
+}
+
+/// WMO weather interpretation codes, as used by Open-Meteo.
+fn describe_code(code: Option<u64>) -> &'static str {
+    match code {
+        Some(0) => "clear",
+        Some(1..=3) => "partly cloudy",
+        Some(45 | 48) => "fog",
+        Some(51..=57) => "drizzle",
+        Some(61..=67 | 80..=82) => "rain",
+        Some(71..=77 | 85 | 86) => "snow",
+        Some(95..=99) => "thunderstorm",
+        _ => "unknown",
+    }
+}
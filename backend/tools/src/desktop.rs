@@ -0,0 +1,253 @@
+//! Desktop node tools: `clipboard_read`, `clipboard_write` and `screen_capture`.
+//!
+//! Each call runs the matching task (`clipboard.read`, ...) on a desktop node
+//! host. A node must advertise the task, the owner must have granted the
+//! permission for that node in the `NodeStore`, and the first use of each
+//! grant waits on owner approval. Screenshots go to the media pipeline so
+//! the vision handler can describe them.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use bytes::Bytes;
+use clawforge_companion::{DesktopPermission, NodeHostRegistry, NodeStore, NodeTransport};
+use clawforge_core::Tool;
+use clawforge_security::ApprovalBroker;
+use media::{MediaPayload, MediaPipeline};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Longest text accepted for `clipboard_write`, in bytes.
+const MAX_CLIPBOARD_BYTES: usize = 256 * 1024;
+
+/// Who approves first use, and where the prompt goes.
+struct Approvals {
+    broker: Arc<ApprovalBroker>,
+    session_id: String,
+    channel: Option<String>,
+}
+
+pub struct DesktopTool<T: NodeTransport> {
+    permission: DesktopPermission,
+    registry: Arc<NodeHostRegistry<T>>,
+    store: Arc<NodeStore>,
+    approvals: Option<Approvals>,
+    /// Pipeline, run and agent that screenshots are handed to.
+    vision: Option<(Arc<MediaPipeline>, Uuid, Uuid)>,
+}
+
+impl<T: NodeTransport> DesktopTool<T> {
+    pub fn new(permission: DesktopPermission, registry: Arc<NodeHostRegistry<T>>, store: Arc<NodeStore>) -> Self {
+        Self { permission, registry, store, approvals: None, vision: None }
+    }
+
+    /// Ask the owner through `broker` before a grant is used the first time.
+    /// Without it, unconfirmed grants are refused.
+    pub fn with_approvals(mut self, broker: Arc<ApprovalBroker>, session_id: impl Into<String>, channel: Option<String>) -> Self {
+        self.approvals = Some(Approvals { broker, session_id: session_id.into(), channel });
+        self
+    }
+
+    /// Send screenshots to `pipeline` as images of the given run.
+    pub fn with_vision(mut self, pipeline: Arc<MediaPipeline>, run_id: Uuid, agent_id: Uuid) -> Self {
+        self.vision = Some((pipeline, run_id, agent_id));
+        self
+    }
+
+    async fn authorize(&self, node_id: &str) -> Result<()> {
+        let task = self.permission.task();
+        let nodes = self.registry.list().await;
+        let (registration, _) = nodes
+            .iter()
+            .find(|(reg, _)| reg.node_id == node_id)
+            .ok_or_else(|| anyhow!("Node '{}' not found", node_id))?;
+        if !registration.capabilities.iter().any(|c| c == task) {
+            bail!("Node '{}' does not support {}", node_id, task);
+        }
+
+        let grants = self.store.desktop_grants(node_id).await;
+        if !grants.allows(self.permission) {
+            bail!("Node '{}' has not been granted {}; the owner can enable it in the node's permissions", node_id, self.permission.as_str());
+        }
+        if !grants.needs_confirmation(self.permission) {
+            return Ok(());
+        }
+
+        let approvals = self
+            .approvals
+            .as_ref()
+            .ok_or_else(|| anyhow!("First use of {} on '{}' needs owner approval", self.permission.as_str(), node_id))?;
+        let summary = format!("{} on {} ({})", task, registration.display_name, node_id);
+        let reasons = vec!["First use of this permission on this node".to_string()];
+        let outcome = approvals
+            .broker
+            .request(&approvals.session_id, approvals.channel.as_deref(), self.permission.as_str(), &summary, reasons)
+            .await;
+        if !outcome.is_approved() {
+            bail!("{} was not approved", summary);
+        }
+        self.store.confirm(node_id, self.permission).await?;
+        Ok(())
+    }
+
+    async fn run(&self, node_id: &str, args: Value) -> Result<Value> {
+        let result = self.registry.invoke(node_id, self.permission.task(), args, Some(30)).await?;
+        if !result.success {
+            bail!("{} failed on '{}': {}", self.permission.task(), node_id, result.error.unwrap_or_else(|| "unknown error".into()));
+        }
+        Ok(result.output)
+    }
+
+    async fn capture(&self, node_id: &str, args: &Value) -> Result<Value> {
+        let out = self.run(node_id, json!({ "display": args.get("display"), "window": args.get("window") })).await?;
+        let png = out["png"].as_str().ok_or_else(|| anyhow!("Node '{}' returned no image", node_id))?;
+        let data = base64::engine::general_purpose::STANDARD.decode(png)?;
+        let bytes = data.len();
+
+        let mut analyzed = false;
+        if let Some((pipeline, run_id, agent_id)) = &self.vision {
+            let payload = MediaPayload { source: format!("node:{}", node_id), mime_type: "image/png".to_string(), data: Bytes::from(data) };
+            match pipeline.handle_media(*run_id, *agent_id, payload).await {
+                Ok(()) => analyzed = true,
+                Err(e) => warn!(node_id = %node_id, error = %e, "Failed to hand screenshot to vision"),
+            }
+        }
+        Ok(json!({
+            "node_id": node_id,
+            "width": out["width"],
+            "height": out["height"],
+            "bytes": bytes,
+            "sent_to_vision": analyzed,
+        }))
+    }
+}
+
+#[async_trait]
+impl<T: NodeTransport> Tool for DesktopTool<T> {
+    fn name(&self) -> &str {
+        self.permission.as_str()
+    }
+
+    fn description(&self) -> &str {
+        match self.permission {
+            DesktopPermission::ClipboardRead => "Read the text on a desktop node's clipboard.",
+            DesktopPermission::ClipboardWrite => "Put text on a desktop node's clipboard.",
+            DesktopPermission::ScreenCapture => {
+                "Take a screenshot of a desktop node's display or of one window; the image is passed to vision for a description."
+            }
+        }
+    }
+
+    fn parameters(&self) -> Value {
+        let mut properties = json!({
+            "node_id": { "type": "string", "description": "Desktop node to use" }
+        });
+        let mut required = vec!["node_id"];
+        match self.permission {
+            DesktopPermission::ClipboardRead => {}
+            DesktopPermission::ClipboardWrite => {
+                properties["text"] = json!({ "type": "string" });
+                required.push("text");
+            }
+            DesktopPermission::ScreenCapture => {
+                properties["display"] = json!({ "type": "integer", "description": "Display index, 0 for the main display" });
+                properties["window"] = json!({ "type": "string", "description": "Capture only the window whose title contains this" });
+            }
+        }
+        json!({ "type": "object", "properties": properties, "required": required })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let node_id = args["node_id"].as_str().ok_or_else(|| anyhow!("Missing 'node_id' argument"))?;
+        let text = match self.permission {
+            DesktopPermission::ClipboardWrite => {
+                let text = args["text"].as_str().ok_or_else(|| anyhow!("Missing 'text' argument"))?;
+                if text.len() > MAX_CLIPBOARD_BYTES {
+                    bail!("Clipboard text is {} bytes (max {})", text.len(), MAX_CLIPBOARD_BYTES);
+                }
+                Some(text)
+            }
+            _ => None,
+        };
+        self.authorize(node_id).await?;
+        info!(node_id = %node_id, task = self.permission.task(), "Running desktop task");
+
+        let output = match self.permission {
+            DesktopPermission::ClipboardRead => {
+                let out = self.run(node_id, json!({})).await?;
+                json!({ "node_id": node_id, "text": out["text"].as_str().unwrap_or_default() })
+            }
+            DesktopPermission::ClipboardWrite => {
+                self.run(node_id, json!({ "text": text })).await?;
+                json!({ "ok": true, "node_id": node_id })
+            }
+            DesktopPermission::ScreenCapture => self.capture(node_id, &args).await?,
+        };
+        Ok(output.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawforge_companion::{NodeInvocation, NodeInvocationResult, NodeRegistration};
+    use clawforge_security::{ApprovalEvent, ApprovalPolicy, ApprovalVerdict};
+
+    struct Clipboard;
+
+    impl NodeTransport for Clipboard {
+        async fn invoke(&self, invocation: NodeInvocation) -> Result<NodeInvocationResult> {
+            Ok(NodeInvocationResult {
+                invocation_id: invocation.invocation_id,
+                node_id: invocation.node_id,
+                success: true,
+                output: json!({ "text": "copied text" }),
+                error: None,
+                duration_ms: 1,
+            })
+        }
+
+        async fn ping(&self, _node_id: &str) -> Result<bool> {
+            Ok(true)
+        }
+    }
+
+    #[tokio::test]
+    async fn clipboard_needs_grant_and_first_use_approval() {
+        let registration = NodeRegistration {
+            node_id: "laptop".into(),
+            display_name: "Laptop".into(),
+            platform: "macos".into(),
+            capabilities: vec!["clipboard.read".into()],
+            accepts_tasks: true,
+            metadata: json!({}),
+        };
+        let store = Arc::new(NodeStore::in_memory());
+        store.save_registration(registration.clone()).await.unwrap();
+        let registry = Arc::new(NodeHostRegistry::new(Clipboard));
+        registry.register(registration).await;
+
+        let broker = Arc::new(ApprovalBroker::new(ApprovalPolicy::default()));
+        let tool = DesktopTool::new(DesktopPermission::ClipboardRead, registry, store.clone()).with_approvals(broker.clone(), "s1", None);
+        let args = json!({ "node_id": "laptop" });
+        assert!(tool.execute(args.clone()).await.unwrap_err().to_string().contains("not been granted"));
+
+        store.grant("laptop", DesktopPermission::ClipboardRead).await.unwrap();
+        let mut events = broker.subscribe();
+        let approver = tokio::spawn(async move {
+            if let Ok(ApprovalEvent::Requested(request)) = events.recv().await {
+                broker.resolve(&request.id, ApprovalVerdict::Allow).await;
+            }
+        });
+        let out: Value = serde_json::from_str(&tool.execute(args.clone()).await.unwrap()).unwrap();
+        assert_eq!(out["text"], "copied text");
+        approver.await.unwrap();
+
+        // Confirmed: later calls do not ask again.
+        assert!(!store.desktop_grants("laptop").await.needs_confirmation(DesktopPermission::ClipboardRead));
+        tool.execute(args).await.unwrap();
+    }
+}
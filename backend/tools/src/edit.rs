@@ -0,0 +1,431 @@
+//! `file_edit` tool: targeted edits instead of whole-file rewrites.
+//!
+//! Two modes:
+//! - search/replace — `old` is replaced by `new`. An exact match is tried
+//!   first; failing that, lines are compared with whitespace collapsed so
+//!   re-indented or re-wrapped spacing still matches. `context_before` /
+//!   `context_after` lines pick one match out of several.
+//! - `patch` — a unified diff for this one file, applied hunk by hunk. Hunks
+//!   are located near their stated line, tolerating drift and whitespace
+//!   differences, so a patch made against a slightly older file still applies.
+//!
+//! An edit only lands if its target is found unambiguously in the file as it
+//! is now, which keeps concurrent edits from silently clobbering each other.
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use clawforge_core::Tool;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::fs;
+
+use crate::file::{unified_diff, EditJournal};
+
+#[derive(Default)]
+pub struct EditTool {
+    /// Journal and the session edits are recorded under.
+    journal: Option<(Arc<EditJournal>, String)>,
+}
+
+impl EditTool {
+    /// Record each edit under `session` so it can be reverted with `/undo`.
+    pub fn with_journal(mut self, journal: Arc<EditJournal>, session: impl Into<String>) -> Self {
+        self.journal = Some((journal, session.into()));
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for EditTool {
+    fn name(&self) -> &str {
+        "file_edit"
+    }
+
+    fn description(&self) -> &str {
+        "Edit part of a file: replace `old` with `new` (whitespace-tolerant), or apply a unified diff `patch`."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path to the file to edit"
+                },
+                "old": {
+                    "type": "string",
+                    "description": "Text to replace"
+                },
+                "new": {
+                    "type": "string",
+                    "description": "Replacement text"
+                },
+                "context_before": {
+                    "type": "string",
+                    "description": "Lines just above `old`, to pick one of several matches"
+                },
+                "context_after": {
+                    "type": "string",
+                    "description": "Lines just below `old`, to pick one of several matches"
+                },
+                "replace_all": {
+                    "type": "boolean",
+                    "description": "Replace every match instead of requiring exactly one"
+                },
+                "patch": {
+                    "type": "string",
+                    "description": "Unified diff for this file (instead of old/new)"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<String> {
+        let path_str = args["path"].as_str().ok_or_else(|| anyhow!("Missing 'path' argument"))?;
+        if path_str.contains("..") {
+            return Err(anyhow!("Security violation: Path cannot contain '..'"));
+        }
+
+        let existing = fs::read_to_string(path_str).await;
+        let (edited, how) = match args["patch"].as_str() {
+            Some(patch) => {
+                // A patch may create the file.
+                let text = match &existing {
+                    Ok(text) => text.as_str(),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => "",
+                    Err(e) => bail!("Failed to read {}: {}", path_str, e),
+                };
+                apply_patch(text, patch)?
+            }
+            None => {
+                let text = existing.as_ref().map_err(|e| anyhow!("Failed to read {}: {}", path_str, e))?;
+                let old = args["old"].as_str().ok_or_else(|| anyhow!("Missing 'old' argument (or 'patch')"))?;
+                let new = args["new"].as_str().ok_or_else(|| anyhow!("Missing 'new' argument"))?;
+                let replace = Replace {
+                    old,
+                    new,
+                    context_before: args["context_before"].as_str(),
+                    context_after: args["context_after"].as_str(),
+                    replace_all: args["replace_all"].as_bool().unwrap_or(false),
+                };
+                replace_in(text, &replace)?
+            }
+        };
+
+        // Any read error other than a missing file has already failed above.
+        let previous = existing.ok();
+        let diff = unified_diff(path_str.trim_start_matches("./"), previous.as_deref(), &edited);
+        if diff.is_empty() {
+            return Ok(format!("No changes to {}", path_str));
+        }
+        fs::write(path_str, &edited).await?;
+        if let Some((journal, session)) = &self.journal {
+            journal.record(session, PathBuf::from(path_str), previous.map(String::into_bytes));
+        }
+        Ok(format!("Edited {} ({})\n{}", path_str, how, diff))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Search / replace
+// ---------------------------------------------------------------------------
+
+pub struct Replace<'a> {
+    pub old: &'a str,
+    pub new: &'a str,
+    pub context_before: Option<&'a str>,
+    pub context_after: Option<&'a str>,
+    pub replace_all: bool,
+}
+
+/// Lines compared with runs of whitespace collapsed and ends trimmed.
+fn normalize(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn normalized_lines(text: &str) -> Vec<String> {
+    text.lines().map(normalize).collect()
+}
+
+/// Line-ranged match: `start..end` over the file's lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LineMatch {
+    start: usize,
+    end: usize,
+}
+
+/// Apply `replace` to `text`, returning the new text and how it matched.
+pub fn replace_in(text: &str, replace: &Replace) -> Result<(String, String)> {
+    if replace.old.is_empty() {
+        bail!("'old' must not be empty");
+    }
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let normalized: Vec<String> = lines.iter().map(|l| normalize(l)).collect();
+
+    // Exact substring matches, as byte offsets, filtered by context.
+    let exact: Vec<usize> = text
+        .match_indices(replace.old)
+        .map(|(at, _)| at)
+        .filter(|&at| {
+            let m = LineMatch { start: line_of(&lines, at), end: line_of(&lines, at + replace.old.len() - 1) + 1 };
+            context_fits(&normalized, m, replace)
+        })
+        .collect();
+    if !exact.is_empty() {
+        check_ambiguity(&exact.iter().map(|&at| line_of(&lines, at)).collect::<Vec<_>>(), replace.replace_all)?;
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for at in &exact {
+            out.push_str(&text[last..*at]);
+            out.push_str(replace.new);
+            last = at + replace.old.len();
+        }
+        out.push_str(&text[last..]);
+        return Ok((out, format!("{} exact match{}", exact.len(), if exact.len() == 1 { "" } else { "es" })));
+    }
+
+    // Whole-line matches with whitespace collapsed.
+    let wanted = trimmed_lines(replace.old);
+    if wanted.is_empty() {
+        bail!("'old' not found in file");
+    }
+    let mut matches: Vec<LineMatch> = Vec::new();
+    for i in 0..=lines.len().saturating_sub(wanted.len()) {
+        let m = LineMatch { start: i, end: i + wanted.len() };
+        let overlaps = matches.last().is_some_and(|prev| prev.end > i);
+        if !overlaps && m.end <= lines.len() && normalized[i..m.end] == wanted[..] && context_fits(&normalized, m, replace) {
+            matches.push(m);
+        }
+    }
+    if matches.is_empty() {
+        bail!("'old' not found in file, even ignoring whitespace");
+    }
+    check_ambiguity(&matches.iter().map(|m| m.start).collect::<Vec<_>>(), replace.replace_all)?;
+
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for m in &matches {
+        out.push_str(&lines[last..m.start].concat());
+        // Keep the file's indentation, and its line ending after the block.
+        let indent = leading_whitespace(lines[m.start]);
+        let old_indent = replace.old.lines().find(|l| !l.trim().is_empty()).map(leading_whitespace).unwrap_or("");
+        out.push_str(&reindent(replace.new, old_indent, indent));
+        if lines[m.end - 1].ends_with('\n') && !replace.new.ends_with('\n') {
+            out.push('\n');
+        }
+        last = m.end;
+    }
+    out.push_str(&lines[last..].concat());
+    let first = matches[0].start + 1;
+    Ok((out, format!("whitespace-insensitive match at line {}", first)))
+}
+
+/// `old`'s normalized lines without leading/trailing blank lines.
+fn trimmed_lines(text: &str) -> Vec<String> {
+    let lines = normalized_lines(text);
+    let start = lines.iter().position(|l| !l.is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !l.is_empty()).map_or(start, |i| i + 1);
+    lines[start..end].to_vec()
+}
+
+fn line_of(lines: &[&str], byte: usize) -> usize {
+    let mut offset = 0;
+    for (i, line) in lines.iter().enumerate() {
+        offset += line.len();
+        if byte < offset {
+            return i;
+        }
+    }
+    lines.len().saturating_sub(1)
+}
+
+fn context_fits(normalized: &[String], m: LineMatch, replace: &Replace) -> bool {
+    if let Some(before) = replace.context_before.map(trimmed_lines).filter(|c| !c.is_empty()) {
+        if m.start < before.len() || normalized[m.start - before.len()..m.start] != before[..] {
+            return false;
+        }
+    }
+    if let Some(after) = replace.context_after.map(trimmed_lines).filter(|c| !c.is_empty()) {
+        if m.end + after.len() > normalized.len() || normalized[m.end..m.end + after.len()] != after[..] {
+            return false;
+        }
+    }
+    true
+}
+
+fn check_ambiguity(starts: &[usize], replace_all: bool) -> Result<()> {
+    if starts.len() > 1 && !replace_all {
+        let at: Vec<String> = starts.iter().take(10).map(|l| (l + 1).to_string()).collect();
+        bail!(
+            "'old' matches {} places (lines {}); add context_before/context_after or set replace_all",
+            starts.len(),
+            at.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Swap `from` for `to` at the start of each line of `text`.
+fn reindent(text: &str, from: &str, to: &str) -> String {
+    if from == to {
+        return text.to_string();
+    }
+    text.split_inclusive('\n')
+        .map(|line| match line.strip_prefix(from) {
+            Some(rest) if !line.trim().is_empty() => format!("{}{}", to, rest),
+            _ => line.to_string(),
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Unified diff application
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct Hunk {
+    /// 1-based line the hunk claims to start at in the old file.
+    old_start: usize,
+    old: Vec<String>,
+    new: Vec<String>,
+}
+
+fn parse_hunks(patch: &str) -> Result<Vec<Hunk>> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for line in patch.lines() {
+        if let Some(header) = line.strip_prefix("@@ ") {
+            let old = header.split_whitespace().next().and_then(|r| r.strip_prefix('-')).ok_or_else(|| anyhow!("Malformed hunk header: {}", line))?;
+            let old_start = old.split(',').next().unwrap_or("0").parse().map_err(|_| anyhow!("Malformed hunk header: {}", line))?;
+            hunks.push(Hunk { old_start, ..Hunk::default() });
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            // File headers (`diff`, `index`, `---`, `+++`) before the first hunk.
+            continue;
+        };
+        match line.chars().next() {
+            Some(' ') => {
+                hunk.old.push(line[1..].to_string());
+                hunk.new.push(line[1..].to_string());
+            }
+            Some('-') if !line.starts_with("--- ") => hunk.old.push(line[1..].to_string()),
+            Some('+') if !line.starts_with("+++ ") => hunk.new.push(line[1..].to_string()),
+            // Some generators drop the space on empty context lines.
+            None => {
+                hunk.old.push(String::new());
+                hunk.new.push(String::new());
+            }
+            Some('\\') => {}
+            _ => {}
+        }
+    }
+    if hunks.is_empty() {
+        bail!("Patch has no hunks");
+    }
+    Ok(hunks)
+}
+
+/// Apply a single-file unified diff to `text`.
+pub fn apply_patch(text: &str, patch: &str) -> Result<(String, String)> {
+    let hunks = parse_hunks(patch)?;
+    let trailing_newline = text.is_empty() || text.ends_with('\n');
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let mut offset: isize = 0;
+    let mut floor = 0;
+    let mut fuzzy = 0;
+
+    for (n, hunk) in hunks.iter().enumerate() {
+        let expected = ((hunk.old_start.max(1) - 1) as isize + offset).max(0) as usize;
+        let at = match locate(&lines, &hunk.old, expected, floor, false) {
+            Some(at) => at,
+            None => {
+                fuzzy += 1;
+                locate(&lines, &hunk.old, expected, floor, true)
+                    .ok_or_else(|| anyhow!("Hunk {} (line {}) does not match the file", n + 1, hunk.old_start))?
+            }
+        };
+        lines.splice(at..at + hunk.old.len(), hunk.new.iter().cloned());
+        offset += hunk.new.len() as isize - hunk.old.len() as isize;
+        offset += at as isize - expected as isize;
+        floor = at + hunk.new.len();
+    }
+
+    let mut out = lines.join("\n");
+    if trailing_newline && !out.is_empty() {
+        out.push('\n');
+    }
+    let how = match fuzzy {
+        0 => format!("{} hunk{} applied", hunks.len(), if hunks.len() == 1 { "" } else { "s" }),
+        _ => format!("{} hunks applied, {} ignoring whitespace", hunks.len(), fuzzy),
+    };
+    Ok((out, how))
+}
+
+/// Where `old` occurs at or after `floor`, nearest to `expected` first.
+fn locate(lines: &[String], old: &[String], expected: usize, floor: usize, fuzzy: bool) -> Option<usize> {
+    if old.is_empty() {
+        return Some(expected.clamp(floor, lines.len()));
+    }
+    let last = lines.len().checked_sub(old.len())?;
+    let fits = |at: usize| {
+        lines[at..at + old.len()].iter().zip(old).all(|(have, want)| {
+            if fuzzy {
+                normalize(have) == normalize(want)
+            } else {
+                have == want
+            }
+        })
+    };
+    let mut candidates: Vec<usize> = (floor..=last).collect();
+    candidates.sort_by_key(|&at| at.abs_diff(expected));
+    candidates.into_iter().find(|&at| fits(at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replace<'a>(old: &'a str, new: &'a str) -> Replace<'a> {
+        Replace { old, new, context_before: None, context_after: None, replace_all: false }
+    }
+
+    #[test]
+    fn replaces_exact_fuzzy_and_disambiguated() {
+        let text = "fn a() {\n    let x = 1;\n}\nfn b() {\n    let x = 1;\n}\n";
+
+        let err = replace_in(text, &replace("let x = 1;", "let x = 2;")).unwrap_err().to_string();
+        assert!(err.contains("matches 2 places (lines 2, 5)"), "{}", err);
+
+        let mut r = replace("let x = 1;", "let x = 2;");
+        r.context_before = Some("fn b() {");
+        let (out, _) = replace_in(text, &r).unwrap();
+        assert_eq!(out, "fn a() {\n    let x = 1;\n}\nfn b() {\n    let x = 2;\n}\n");
+
+        // Different spacing and indentation still matches; the file's indent is kept.
+        let (out, how) = replace_in("if  ok {\n\t\trun( a,b );\n}\n", &replace("run(  a,b );", "run(a, b);\nlog();")).unwrap();
+        assert_eq!(out, "if  ok {\n\t\trun(a, b);\n\t\tlog();\n}\n");
+        assert!(how.starts_with("whitespace-insensitive"));
+
+        assert!(replace_in(text, &replace("missing", "x")).is_err());
+    }
+
+    #[test]
+    fn applies_drifted_patch() {
+        let text = "header\nextra\none\ntwo\nthree\nfour\n";
+        let patch = "--- a/f\n+++ b/f\n@@ -2,3 +2,3 @@\n one\n-two\n+TWO\n three\n";
+        let (out, how) = apply_patch(text, patch).unwrap();
+        assert_eq!(out, "header\nextra\none\nTWO\nthree\nfour\n");
+        assert_eq!(how, "1 hunk applied");
+
+        let (out, _) = apply_patch("", "--- /dev/null\n+++ b/new\n@@ -0,0 +1,2 @@\n+a\n+b\n").unwrap();
+        assert_eq!(out, "a\nb\n");
+        assert!(apply_patch(text, "@@ -1,1 +1,1 @@\n-nope\n+x\n").is_err());
+    }
+}
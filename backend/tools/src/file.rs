@@ -1,8 +1,20 @@
+//! File tools: `file_read`, `file_write` and `git`.
+//!
+//! Writes can be journaled in an [`EditJournal`] so `/undo` reverts a
+//! session's last agent edit, and inside a git work tree [`preview_write`] renders the change
+//! as a unified diff for the approval prompt before anything touches disk.
+
+use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use clawforge_core::Tool;
+use serde::Serialize;
 use serde_json::Value;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tokio::fs;
+use tokio::process::Command;
 
 pub struct FileReadTool;
 
@@ -42,7 +54,19 @@ impl Tool for FileReadTool {
     }
 }
 
-pub struct FileWriteTool;
+#[derive(Default)]
+pub struct FileWriteTool {
+    /// Journal and the session writes are recorded under.
+    journal: Option<(Arc<EditJournal>, String)>,
+}
+
+impl FileWriteTool {
+    /// Record each write under `session` so it can be reverted with `/undo`.
+    pub fn with_journal(mut self, journal: Arc<EditJournal>, session: impl Into<String>) -> Self {
+        self.journal = Some((journal, session.into()));
+        self
+    }
+}
 
 #[async_trait]
 impl Tool for FileWriteTool {
@@ -85,7 +109,461 @@ impl Tool for FileWriteTool {
             fs::create_dir_all(parent).await?;
         }
 
+        let previous = match &self.journal {
+            Some(_) => snapshot(Path::new(path_str)).await?,
+            None => None,
+        };
         fs::write(path_str, content).await?;
+        if let Some((journal, session)) = &self.journal {
+            journal.record(session, PathBuf::from(path_str), previous);
+        }
         Ok(format!("Successfully wrote to {}", path_str))
     }
 }
+
+// ---------------------------------------------------------------------------
+// Edit journal — what `/undo` reverts
+// ---------------------------------------------------------------------------
+
+/// Most edits kept per session; older ones can no longer be undone.
+pub const MAX_EDITS: usize = 100;
+/// Sessions with journaled edits; the one edited longest ago is dropped first.
+pub const MAX_SESSIONS: usize = 256;
+
+/// One agent write, with what the file held before.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Edit {
+    pub path: PathBuf,
+    /// Prior bytes; `None` when the write created the file.
+    #[serde(skip)]
+    pub previous: Option<Vec<u8>>,
+    pub at: DateTime<Utc>,
+}
+
+/// What `path` holds before a write: its bytes, or `None` if it doesn't
+/// exist. Any other read error fails the write rather than recording the
+/// file as new, which would make `/undo` delete it.
+pub async fn snapshot(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path).await {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => bail!("Failed to read {} before writing: {}", path.display(), e),
+    }
+}
+
+/// Stacks of recent agent writes per session, newest last.
+#[derive(Default)]
+pub struct EditJournal {
+    sessions: Mutex<HashMap<String, Vec<Edit>>>,
+}
+
+impl EditJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, session: &str, path: PathBuf, previous: Option<Vec<u8>>) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if !sessions.contains_key(session) && sessions.len() >= MAX_SESSIONS {
+            let stalest = sessions
+                .iter()
+                .min_by_key(|(_, edits)| edits.last().map(|e| e.at))
+                .map(|(key, _)| key.clone());
+            if let Some(key) = stalest {
+                sessions.remove(&key);
+            }
+        }
+        let edits = sessions.entry(session.to_string()).or_default();
+        if edits.len() == MAX_EDITS {
+            edits.remove(0);
+        }
+        edits.push(Edit { path, previous, at: Utc::now() });
+    }
+
+    pub fn last(&self, session: &str) -> Option<Edit> {
+        self.sessions.lock().unwrap().get(session)?.last().cloned()
+    }
+
+    pub fn len(&self, session: &str) -> usize {
+        self.sessions.lock().unwrap().get(session).map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self, session: &str) -> bool {
+        self.len(session) == 0
+    }
+
+    /// Revert `session`'s newest edit: restore the prior contents, or delete
+    /// the file if the edit created it. Returns the reverted edit, or `None`
+    /// when there is nothing to undo. A failed revert stays on the journal.
+    pub async fn undo(&self, session: &str) -> Result<Option<Edit>> {
+        let Some(edit) = self.pop(session) else {
+            return Ok(None);
+        };
+        let reverted = match &edit.previous {
+            Some(previous) => fs::write(&edit.path, previous).await,
+            None => match fs::remove_file(&edit.path).await {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                other => other,
+            },
+        };
+        if let Err(e) = reverted {
+            let message = format!("Failed to revert {}: {}", edit.path.display(), e);
+            self.sessions.lock().unwrap().entry(session.to_string()).or_default().push(edit);
+            bail!(message);
+        }
+        Ok(Some(edit))
+    }
+
+    fn pop(&self, session: &str) -> Option<Edit> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let edits = sessions.get_mut(session)?;
+        let edit = edits.pop();
+        if edits.is_empty() {
+            sessions.remove(session);
+        }
+        edit
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Write previews — unified diffs for approval
+// ---------------------------------------------------------------------------
+
+/// A pending `file_write` inside a git work tree, as a unified diff.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WritePreview {
+    /// Work tree root.
+    pub workspace: PathBuf,
+    /// Path relative to the work tree, as shown in the diff headers.
+    pub path: String,
+    pub created: bool,
+    /// Empty when the write would not change the file.
+    pub diff: String,
+}
+
+impl WritePreview {
+    /// The diff cut to `max_lines`, for chat prompts.
+    pub fn summary(&self, max_lines: usize) -> String {
+        let lines: Vec<&str> = self.diff.lines().collect();
+        if self.diff.is_empty() {
+            return format!("{} (no changes)", self.path);
+        }
+        if lines.len() <= max_lines {
+            return self.diff.trim_end().to_string();
+        }
+        format!("{}\n… {} more lines", lines[..max_lines].join("\n"), lines.len() - max_lines)
+    }
+}
+
+/// Top of the git work tree containing `path`, if any.
+pub async fn git_root(path: &Path) -> Option<PathBuf> {
+    // The file (or even its directory) may not exist yet.
+    let mut dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    while !dir.is_dir() {
+        dir = dir.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    }
+    let output = Command::new("git").arg("-C").arg(dir).args(["rev-parse", "--show-toplevel"]).output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Diff of writing `content` to `path`, or `None` outside a git work tree.
+pub async fn preview_write(path: &str, content: &str) -> Option<WritePreview> {
+    let workspace = git_root(Path::new(path)).await?;
+    let previous = fs::read_to_string(path).await.ok();
+    let relative = workspace_relative(&workspace, Path::new(path)).await.unwrap_or_else(|| path.trim_start_matches("./").to_string());
+    let diff = unified_diff(&relative, previous.as_deref(), content);
+    Some(WritePreview { workspace, path: relative, created: previous.is_none(), diff })
+}
+
+async fn workspace_relative(workspace: &Path, path: &Path) -> Option<String> {
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let parent = fs::canonicalize(parent).await.ok()?;
+    let workspace = fs::canonicalize(workspace).await.ok()?;
+    let relative = parent.strip_prefix(&workspace).ok()?.join(path.file_name()?);
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Lines of context around each change.
+const CONTEXT: usize = 3;
+/// Above this many LCS cells the changed region is shown as one replacement.
+const MAX_DIFF_CELLS: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Unified diff of `old` (`None` for a new file) to `new` with three lines
+/// of context, in the `a/` / `b/` form `git apply` accepts. Empty when the
+/// contents are equal.
+pub fn unified_diff(path: &str, old: Option<&str>, new: &str) -> String {
+    let a: Vec<&str> = old.unwrap_or("").lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&a, &b);
+    if ops.iter().all(|(op, _)| *op == Op::Equal) {
+        return String::new();
+    }
+
+    let mut out = match old {
+        Some(_) => format!("--- a/{}\n+++ b/{}\n", path, path),
+        None => format!("--- /dev/null\n+++ b/{}\n", path),
+    };
+    let changes: Vec<usize> = ops.iter().enumerate().filter(|(_, (op, _))| *op != Op::Equal).map(|(i, _)| i).collect();
+    let mut i = 0;
+    while i < changes.len() {
+        // Merge changes whose context would overlap into one hunk.
+        let first = changes[i];
+        let mut last = first;
+        while i + 1 < changes.len() && changes[i + 1] - last <= 2 * CONTEXT + 1 {
+            i += 1;
+            last = changes[i];
+        }
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + 1 + CONTEXT).min(ops.len());
+        let old_before = ops[..start].iter().filter(|(op, _)| *op != Op::Insert).count();
+        let new_before = ops[..start].iter().filter(|(op, _)| *op != Op::Delete).count();
+        let hunk = &ops[start..end];
+        let old_len = hunk.iter().filter(|(op, _)| *op != Op::Insert).count();
+        let new_len = hunk.iter().filter(|(op, _)| *op != Op::Delete).count();
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_before, old_len),
+            hunk_range(new_before, new_len)
+        ));
+        for (op, line) in hunk {
+            let marker = match op {
+                Op::Equal => ' ',
+                Op::Delete => '-',
+                Op::Insert => '+',
+            };
+            out.push(marker);
+            out.push_str(line);
+            out.push('\n');
+        }
+        i += 1;
+    }
+    out
+}
+
+/// `start,len` with 1-based start; an empty side points at the line before.
+fn hunk_range(before: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", before),
+        1 => format!("{}", before + 1),
+        _ => format!("{},{}", before + 1, len),
+    }
+}
+
+/// Line edit script from a longest common subsequence, after trimming the
+/// shared prefix and suffix.
+fn diff_lines<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops: Vec<(Op, &str)> = a[..prefix].iter().map(|l| (Op::Equal, *l)).collect();
+    let (n, m) = (mid_a.len(), mid_b.len());
+    if n * m > MAX_DIFF_CELLS {
+        ops.extend(mid_a.iter().map(|l| (Op::Delete, *l)));
+        ops.extend(mid_b.iter().map(|l| (Op::Insert, *l)));
+    } else {
+        // lcs[i][j] = LCS length of mid_a[i..] and mid_b[j..].
+        let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i * (m + 1) + j] = if mid_a[i] == mid_b[j] {
+                    lcs[(i + 1) * (m + 1) + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && mid_a[i] == mid_b[j] {
+                ops.push((Op::Equal, mid_a[i]));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[(i + 1) * (m + 1) + j] >= lcs[i * (m + 1) + j + 1]) {
+                ops.push((Op::Delete, mid_a[i]));
+                i += 1;
+            } else {
+                ops.push((Op::Insert, mid_b[j]));
+                j += 1;
+            }
+        }
+    }
+    ops.extend(a[a.len() - suffix..].iter().map(|l| (Op::Equal, *l)));
+    ops
+}
+
+// ---------------------------------------------------------------------------
+// Git tool
+// ---------------------------------------------------------------------------
+
+/// `git` tool: status, diff, commit and branch in one work tree.
+pub struct GitTool {
+    workspace: PathBuf,
+}
+
+impl GitTool {
+    pub fn new(workspace: impl Into<PathBuf>) -> Self {
+        Self { workspace: workspace.into() }
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        let output = Command::new("git").arg("-C").arg(&self.workspace).args(args).output().await?;
+        if !output.status.success() {
+            bail!("git {} failed: {}", args.first().unwrap_or(&""), String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Paths and names come from the model; keep them from being read as flags
+/// or escaping the work tree.
+fn checked_arg<'a>(value: &'a str, what: &str) -> Result<&'a str> {
+    if value.starts_with('-') || value.contains("..") {
+        bail!("Invalid {}: {}", what, value);
+    }
+    Ok(value)
+}
+
+#[async_trait]
+impl Tool for GitTool {
+    fn name(&self) -> &str {
+        "git"
+    }
+
+    fn description(&self) -> &str {
+        "Inspect and commit changes in the workspace git repository: status, diff, commit, branch."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["status", "diff", "commit", "branch"]
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Limit diff/commit to these paths (default: everything)"
+                },
+                "staged": {
+                    "type": "boolean",
+                    "description": "diff: show staged changes instead of unstaged"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "commit: the commit message"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "branch: branch to switch to (omit to list branches)"
+                },
+                "create": {
+                    "type": "boolean",
+                    "description": "branch: create the branch first"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<String> {
+        let action = args["action"].as_str().ok_or_else(|| anyhow!("Missing 'action' argument"))?;
+        let paths = args["paths"]
+            .as_array()
+            .map(|paths| paths.iter().filter_map(Value::as_str).map(|p| checked_arg(p, "path")).collect::<Result<Vec<_>>>())
+            .transpose()?
+            .unwrap_or_default();
+
+        match action {
+            "status" => self.git(&["status", "--short", "--branch"]).await,
+            "diff" => {
+                let mut cmd = vec!["diff", "--no-color"];
+                if args["staged"].as_bool().unwrap_or(false) {
+                    cmd.push("--staged");
+                }
+                cmd.push("--");
+                cmd.extend(&paths);
+                let diff = self.git(&cmd).await?;
+                Ok(if diff.is_empty() { "No changes".to_string() } else { diff })
+            }
+            "commit" => {
+                let message = args["message"].as_str().filter(|m| !m.trim().is_empty()).ok_or_else(|| anyhow!("Missing 'message' argument"))?;
+                let mut add = vec!["add", "-A", "--"];
+                add.extend(&paths);
+                self.git(&add).await?;
+                self.git(&["commit", "-m", message]).await
+            }
+            "branch" => match args["name"].as_str() {
+                None => self.git(&["branch", "--list"]).await,
+                Some(name) => {
+                    let name = checked_arg(name, "branch name")?;
+                    if args["create"].as_bool().unwrap_or(false) {
+                        self.git(&["switch", "-c", name]).await?;
+                    } else {
+                        self.git(&["switch", name]).await?;
+                    }
+                    Ok(format!("Switched to branch {}", name))
+                }
+            },
+            other => bail!("Unknown git action '{}'. Valid: status, diff, commit, branch", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_changes_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        let diff = unified_diff("src/x.txt", Some(old), new);
+        assert_eq!(
+            diff,
+            "--- a/src/x.txt\n+++ b/src/x.txt\n@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n@@ -8,3 +8,4 @@\n h\n i\n j\n+k\n"
+        );
+        assert_eq!(unified_diff("x", Some(old), old), "");
+        assert_eq!(unified_diff("new.txt", None, "hi\n"), "--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1 @@\n+hi\n");
+    }
+
+    #[tokio::test]
+    async fn undo_restores_or_removes() {
+        let dir = std::env::temp_dir().join(format!("clawforge-undo-{}", uuid::Uuid::new_v4()));
+        let (existing, created) = (dir.join("notes.txt"), dir.join("new.txt"));
+        fs::create_dir_all(&dir).await.unwrap();
+        // Not UTF-8: must still be restored, not taken for a new file.
+        let binary = [0xff, 0xfe, 0x00, 0x9f];
+        fs::write(&existing, binary).await.unwrap();
+
+        let journal = Arc::new(EditJournal::new());
+        let tool = FileWriteTool::default().with_journal(journal.clone(), "chat-1");
+        for path in [&existing, &created] {
+            tool.execute(serde_json::json!({"path": path.to_str().unwrap(), "content": "after"})).await.unwrap();
+        }
+        assert_eq!(journal.len("chat-1"), 2);
+        // Another session has nothing to undo.
+        assert!(journal.undo("chat-2").await.unwrap().is_none());
+
+        assert_eq!(journal.undo("chat-1").await.unwrap().unwrap().path, created);
+        assert!(!created.exists());
+        journal.undo("chat-1").await.unwrap();
+        assert_eq!(fs::read(&existing).await.unwrap(), binary);
+        assert!(journal.undo("chat-1").await.unwrap().is_none());
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
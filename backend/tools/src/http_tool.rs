@@ -0,0 +1,594 @@
+//! `http` tool: structured HTTP requests, optionally typed by an OpenAPI spec.
+//!
+//! Without a spec the model supplies `method`, `url`, `headers`, `query` and
+//! a JSON `body`. With one loaded, each operation becomes a variant of the
+//! parameter schema (`operation` + typed `args`) and the tool fills in the
+//! path, query, headers and body itself. Hosts are checked against the
+//! agent's `allowed_domains` (redirects included), and every exchange is
+//! summarised for the audit log.
+//!
+//! Loopback, private and link-local addresses are refused even without a
+//! domain list — an allowed CIDR is the only way to reach them — and each
+//! hop connects to exactly the addresses that were checked. A redirect to
+//! another origin carries only the headers in `REDIRECT_SAFE_HEADERS`, so
+//! credentials stay with the host they were meant for.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use clawforge_core::{Capabilities, Tool};
+use clawforge_security::is_internal_address;
+use clawforge_sandbox::EgressPolicy;
+use reqwest::{Client, Method};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tracing::info;
+use url::{Host, Url};
+
+const DEFAULT_MAX_BODY_BYTES: usize = 64 * 1024;
+const MAX_REDIRECTS: usize = 5;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Request headers still sent after a redirect to another origin.
+const REDIRECT_SAFE_HEADERS: &[&str] = &["accept", "accept-language", "content-type", "user-agent"];
+const HTTP_METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch"];
+
+/// Receives a summary of each request the tool makes.
+pub type HttpAuditSink = Arc<dyn Fn(&HttpExchange) + Send + Sync>;
+
+/// What went out and what came back; header values and bodies are left out.
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpExchange {
+    pub method: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation: Option<String>,
+    pub request_headers: Vec<String>,
+    pub request_bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    pub response_bytes: usize,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// OpenAPI
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamLocation {
+    Path,
+    Query,
+    Header,
+}
+
+#[derive(Debug, Clone)]
+pub struct OperationParam {
+    pub name: String,
+    pub location: ParamLocation,
+    pub required: bool,
+    pub schema: Value,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub id: String,
+    pub method: String,
+    pub path: String,
+    pub summary: Option<String>,
+    pub params: Vec<OperationParam>,
+    /// JSON request body schema, if the operation takes one.
+    pub body: Option<Value>,
+    pub body_required: bool,
+}
+
+impl Operation {
+    /// JSON schema for the operation's `args`.
+    pub fn args_schema(&self) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for param in &self.params {
+            let mut schema = param.schema.clone();
+            if let (Some(description), Some(obj)) = (&param.description, schema.as_object_mut()) {
+                obj.entry("description").or_insert_with(|| json!(description));
+            }
+            properties.insert(param.name.clone(), schema);
+            if param.required {
+                required.push(json!(param.name));
+            }
+        }
+        if let Some(body) = &self.body {
+            properties.insert("body".to_string(), body.clone());
+            if self.body_required {
+                required.push(json!("body"));
+            }
+        }
+        json!({ "type": "object", "properties": properties, "required": required })
+    }
+}
+
+/// The operations of an OpenAPI 3 document (JSON or YAML).
+#[derive(Debug, Clone)]
+pub struct OpenApiSpec {
+    pub base_url: String,
+    pub operations: Vec<Operation>,
+}
+
+impl OpenApiSpec {
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path).with_context(|| format!("Reading OpenAPI spec {}", path.display()))?;
+        Self::parse(&raw)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self> {
+        // YAML is a superset of JSON, so one parser covers both.
+        let doc: Value = serde_yaml::from_str(raw).context("Invalid OpenAPI document")?;
+        if doc.get("openapi").is_none() {
+            bail!("Not an OpenAPI 3 document (missing `openapi`)");
+        }
+        let base_url = doc.pointer("/servers/0/url").and_then(Value::as_str).unwrap_or_default().trim_end_matches('/').to_string();
+        let mut operations = Vec::new();
+        for (path, item) in doc["paths"].as_object().into_iter().flatten() {
+            let shared = item.get("parameters").and_then(Value::as_array).cloned().unwrap_or_default();
+            for method in HTTP_METHODS {
+                let Some(op) = item.get(*method) else { continue };
+                let mut params: Vec<OperationParam> = Vec::new();
+                for raw in shared.iter().chain(op.get("parameters").and_then(Value::as_array).into_iter().flatten()) {
+                    let Some(param) = parse_param(&resolve(&doc, raw, 0)) else { continue };
+                    // Operation-level parameters override path-level ones.
+                    params.retain(|p| !(p.name == param.name && p.location == param.location));
+                    params.push(param);
+                }
+                let body = op.get("requestBody").map(|b| resolve(&doc, b, 0));
+                let body_schema = body.as_ref().and_then(|b| b.pointer("/content/application~1json/schema")).map(|s| resolve(&doc, s, 0));
+                operations.push(Operation {
+                    id: op["operationId"].as_str().map(str::to_string).unwrap_or_else(|| fallback_id(method, path)),
+                    method: method.to_uppercase(),
+                    path: path.clone(),
+                    summary: op["summary"].as_str().or(op["description"].as_str()).map(str::to_string),
+                    params,
+                    body_required: body.as_ref().is_some_and(|b| b["required"].as_bool() == Some(true)),
+                    body: body_schema,
+                });
+            }
+        }
+        Ok(Self { base_url, operations })
+    }
+
+    /// Use `base_url` instead of the document's first `servers` entry.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn operation(&self, id: &str) -> Option<&Operation> {
+        self.operations.iter().find(|op| op.id == id)
+    }
+}
+
+/// Inline `$ref`s into `#/components/...`, following at most a few hops
+/// so recursive schemas stay finite.
+fn resolve(doc: &Value, value: &Value, depth: usize) -> Value {
+    if depth > 8 {
+        return value.clone();
+    }
+    match value {
+        Value::Object(obj) => {
+            if let Some(target) = obj.get("$ref").and_then(Value::as_str) {
+                return match target.strip_prefix('#').and_then(|p| doc.pointer(p)) {
+                    Some(found) => resolve(doc, found, depth + 1),
+                    None => json!({}),
+                };
+            }
+            Value::Object(obj.iter().map(|(k, v)| (k.clone(), resolve(doc, v, depth))).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| resolve(doc, v, depth)).collect()),
+        other => other.clone(),
+    }
+}
+
+fn parse_param(raw: &Value) -> Option<OperationParam> {
+    let location = match raw["in"].as_str()? {
+        "path" => ParamLocation::Path,
+        "query" => ParamLocation::Query,
+        "header" => ParamLocation::Header,
+        _ => return None,
+    };
+    Some(OperationParam {
+        name: raw["name"].as_str()?.to_string(),
+        required: location == ParamLocation::Path || raw["required"].as_bool() == Some(true),
+        location,
+        schema: raw.get("schema").cloned().unwrap_or_else(|| json!({ "type": "string" })),
+        description: raw["description"].as_str().map(str::to_string),
+    })
+}
+
+/// `get /pets/{petId}` → `get_pets_petId`.
+fn fallback_id(method: &str, path: &str) -> String {
+    let path: String = path.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    let path = path.split('_').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("_");
+    format!("{}_{}", method, path)
+}
+
+// ---------------------------------------------------------------------------
+// Tool
+// ---------------------------------------------------------------------------
+
+/// A request ready to send.
+#[derive(Debug, Clone, PartialEq)]
+struct PreparedRequest {
+    method: Method,
+    url: Url,
+    headers: Vec<(String, String)>,
+    body: Option<Value>,
+    operation: Option<String>,
+}
+
+pub struct HttpTool {
+    /// `None` lets any public host through.
+    egress: Option<EgressPolicy>,
+    spec: Option<Arc<OpenApiSpec>>,
+    audit: Option<HttpAuditSink>,
+    max_body_bytes: usize,
+}
+
+impl HttpTool {
+    /// Restricted the way the executor restricts HTTP actions: nothing without
+    /// `can_make_http_requests`, only `allowed_domains` when any are listed.
+    pub fn new(capabilities: &Capabilities) -> Self {
+        Self { egress: EgressPolicy::from_capabilities(capabilities), spec: None, audit: None, max_body_bytes: DEFAULT_MAX_BODY_BYTES }
+    }
+
+    pub fn with_spec(mut self, spec: Arc<OpenApiSpec>) -> Self {
+        self.spec = Some(spec);
+        self
+    }
+
+    pub fn with_audit(mut self, sink: HttpAuditSink) -> Self {
+        self.audit = Some(sink);
+        self
+    }
+
+    pub fn with_max_body_bytes(mut self, max: usize) -> Self {
+        self.max_body_bytes = max;
+        self
+    }
+
+    fn prepare(&self, args: &Value) -> Result<PreparedRequest> {
+        match args["operation"].as_str() {
+            Some(id) => {
+                let spec = self.spec.as_ref().ok_or_else(|| anyhow!("No OpenAPI spec is loaded; send a raw request"))?;
+                let op = spec.operation(id).ok_or_else(|| anyhow!("Unknown operation '{}'", id))?;
+                prepare_operation(&spec.base_url, op, args.get("args").unwrap_or(&Value::Null))
+            }
+            None => prepare_raw(args),
+        }
+    }
+
+    /// The addresses `url` may be fetched from: those the agent's domain
+    /// list allows, or any public address without one. Internal addresses
+    /// need an allowed CIDR.
+    async fn resolve(&self, url: &Url) -> Result<Vec<SocketAddr>> {
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("Only http and https URLs are allowed");
+        }
+        let host = url.host_str().unwrap_or_default();
+        let port = url.port_or_known_default().unwrap_or(443);
+        let addrs = match &self.egress {
+            Some(policy) => policy.allowed_addrs(host, port).await.ok_or_else(|| anyhow!("Host '{}' is not in the agent's allowed domains", host))?,
+            None => match url.host() {
+                Some(Host::Ipv4(ip)) => vec![SocketAddr::new(ip.into(), port)],
+                Some(Host::Ipv6(ip)) => vec![SocketAddr::new(ip.into(), port)],
+                Some(Host::Domain(name)) => tokio::net::lookup_host((name, port)).await.with_context(|| format!("Cannot resolve '{}'", name))?.collect(),
+                None => bail!("URL has no host"),
+            },
+        };
+        let listed = |ip: IpAddr| self.egress.as_ref().is_some_and(|p| p.allows_ip(ip));
+        if let Some(addr) = addrs.iter().find(|a| is_internal_address(a.ip()) && !listed(a.ip())) {
+            bail!("Host '{}' resolves to internal address {}; allow its CIDR to reach it", host, addr.ip());
+        }
+        if addrs.is_empty() {
+            bail!("Cannot resolve '{}'", host);
+        }
+        Ok(addrs)
+    }
+
+    async fn send(&self, request: &PreparedRequest, exchange: &mut HttpExchange) -> Result<Value> {
+        let (mut method, mut url, mut body) = (request.method.clone(), request.url.clone(), request.body.clone());
+        let origin = request.url.origin();
+        let mut cross_origin = false;
+        for _ in 0..=MAX_REDIRECTS {
+            let addrs = self.resolve(&url).await?;
+            // Connect to the checked addresses, not whatever a second lookup returns.
+            let mut client = Client::builder().redirect(reqwest::redirect::Policy::none()).timeout(REQUEST_TIMEOUT);
+            if let Some(Host::Domain(name)) = url.host() {
+                client = client.resolve_to_addrs(name, &addrs);
+            }
+            cross_origin |= url.origin() != origin;
+            let mut builder = client.build()?.request(method.clone(), url.clone());
+            for (name, value) in forwarded_headers(&request.headers, cross_origin) {
+                builder = builder.header(name, value);
+            }
+            if let Some(body) = &body {
+                builder = builder.json(body);
+            }
+            let mut response = builder.send().await?;
+            let status = response.status();
+            if status.is_redirection() {
+                if let Some(location) = response.headers().get("location").and_then(|l| l.to_str().ok()) {
+                    url = url.join(location)?;
+                    if !matches!(status.as_u16(), 307 | 308) {
+                        method = Method::GET;
+                        body = None;
+                    }
+                    continue;
+                }
+            }
+            exchange.url = url.to_string();
+            exchange.status = Some(status.as_u16());
+            let content_type = response.headers().get("content-type").and_then(|v| v.to_str().ok()).map(str::to_string);
+            exchange.content_type = content_type.clone();
+            // Read only up to the cap; the rest of the body is never buffered.
+            let content_length = response.content_length();
+            let mut shown = Vec::new();
+            let mut truncated = false;
+            while let Some(chunk) = response.chunk().await? {
+                let room = self.max_body_bytes - shown.len();
+                if chunk.len() > room {
+                    shown.extend_from_slice(&chunk[..room]);
+                    truncated = true;
+                    break;
+                }
+                shown.extend_from_slice(&chunk);
+            }
+            exchange.response_bytes = content_length.map_or(shown.len(), |len| len as usize);
+
+            let shown = shown.as_slice();
+            let is_json = content_type.as_deref().is_some_and(|t| t.contains("json"));
+            let body = match serde_json::from_slice::<Value>(shown) {
+                Ok(value) if is_json && !truncated => value,
+                _ => json!(String::from_utf8_lossy(shown)),
+            };
+            return Ok(json!({
+                "status": status.as_u16(),
+                "url": url.as_str(),
+                "content_type": content_type,
+                "truncated": truncated,
+                "body": body,
+            }));
+        }
+        bail!("Too many redirects")
+    }
+}
+
+/// The request headers for a hop; once a redirect has left the original
+/// origin only `REDIRECT_SAFE_HEADERS` go along.
+fn forwarded_headers(headers: &[(String, String)], cross_origin: bool) -> impl Iterator<Item = &(String, String)> {
+    headers.iter().filter(move |(name, _)| !cross_origin || REDIRECT_SAFE_HEADERS.contains(&name.to_ascii_lowercase().as_str()))
+}
+
+fn prepare_raw(args: &Value) -> Result<PreparedRequest> {
+    let method = args["method"].as_str().unwrap_or("GET").to_uppercase();
+    let url = args["url"].as_str().ok_or_else(|| anyhow!("Missing 'url' argument"))?;
+    let mut url = Url::parse(url).with_context(|| format!("Invalid URL '{}'", url))?;
+    for (name, value) in args["query"].as_object().into_iter().flatten() {
+        url.query_pairs_mut().append_pair(name, &scalar(value));
+    }
+    let headers = args["headers"].as_object().into_iter().flatten().map(|(k, v)| (k.clone(), scalar(v))).collect();
+    Ok(PreparedRequest {
+        method: Method::from_bytes(method.as_bytes()).map_err(|_| anyhow!("Invalid method '{}'", method))?,
+        url,
+        headers,
+        body: args.get("body").filter(|b| !b.is_null()).cloned(),
+        operation: None,
+    })
+}
+
+fn prepare_operation(base_url: &str, op: &Operation, args: &Value) -> Result<PreparedRequest> {
+    let mut path = op.path.clone();
+    let mut query = Vec::new();
+    let mut headers = Vec::new();
+    for param in &op.params {
+        let Some(value) = args.get(&param.name).filter(|v| !v.is_null()) else {
+            if param.required {
+                bail!("Operation '{}' needs '{}'", op.id, param.name);
+            }
+            continue;
+        };
+        let value = scalar(value);
+        match param.location {
+            ParamLocation::Path => path = path.replace(&format!("{{{}}}", param.name), &urlencoding::encode(&value)),
+            ParamLocation::Query => query.push((param.name.clone(), value)),
+            ParamLocation::Header => headers.push((param.name.clone(), value)),
+        }
+    }
+    let body = args.get("body").filter(|b| !b.is_null()).cloned();
+    if op.body_required && body.is_none() {
+        bail!("Operation '{}' needs a 'body'", op.id);
+    }
+    let mut url = Url::parse(&format!("{}{}", base_url, path)).with_context(|| format!("Invalid URL for operation '{}'", op.id))?;
+    for (name, value) in &query {
+        url.query_pairs_mut().append_pair(name, value);
+    }
+    Ok(PreparedRequest {
+        method: Method::from_bytes(op.method.as_bytes())?,
+        url,
+        headers,
+        body,
+        operation: Some(op.id.clone()),
+    })
+}
+
+/// A parameter value as it goes on the wire (strings unquoted).
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[async_trait]
+impl Tool for HttpTool {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    fn description(&self) -> &str {
+        "Make an HTTP request with method, url, headers, query and a JSON body, or call an operation from the loaded API spec by `operation` with typed `args`."
+    }
+
+    fn parameters(&self) -> Value {
+        let raw = json!({
+            "type": "object",
+            "properties": {
+                "method": { "type": "string", "enum": ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"] },
+                "url": { "type": "string" },
+                "headers": { "type": "object", "additionalProperties": { "type": "string" } },
+                "query": { "type": "object" },
+                "body": { "description": "JSON request body" }
+            },
+            "required": ["url"]
+        });
+        let Some(spec) = &self.spec else { return raw };
+        let mut variants: Vec<Value> = spec
+            .operations
+            .iter()
+            .map(|op| {
+                json!({
+                    "type": "object",
+                    "description": format!("{} {}{}", op.method, op.path, op.summary.as_ref().map(|s| format!(" — {}", s)).unwrap_or_default()),
+                    "properties": { "operation": { "const": op.id }, "args": op.args_schema() },
+                    "required": ["operation"]
+                })
+            })
+            .collect();
+        variants.push(raw);
+        json!({ "oneOf": variants })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let request = self.prepare(&args)?;
+        let started = Instant::now();
+        let mut exchange = HttpExchange {
+            method: request.method.to_string(),
+            url: request.url.to_string(),
+            operation: request.operation.clone(),
+            request_headers: request.headers.iter().map(|(name, _)| name.clone()).collect(),
+            request_bytes: request.body.as_ref().map(|b| b.to_string().len()).unwrap_or(0),
+            status: None,
+            content_type: None,
+            response_bytes: 0,
+            duration_ms: 0,
+            error: None,
+        };
+        let result = self.send(&request, &mut exchange).await;
+        exchange.duration_ms = started.elapsed().as_millis() as u64;
+        if let Err(e) = &result {
+            exchange.error = Some(e.to_string());
+        }
+        info!(method = %exchange.method, url = %exchange.url, status = ?exchange.status, "HTTP tool request");
+        if let Some(audit) = &self.audit {
+            audit(&exchange);
+        }
+        let mut output = result?;
+        output["exchange"] = serde_json::to_value(&exchange)?;
+        Ok(serde_json::to_string(&output)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+servers: [{ url: "https://api.example.com/v1/" }]
+paths:
+  /pets/{petId}:
+    parameters:
+      - { name: petId, in: path, schema: { type: integer } }
+    get:
+      operationId: getPet
+      parameters:
+        - { name: fields, in: query, schema: { type: string } }
+    put:
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Pet" }
+components:
+  schemas:
+    Pet: { type: object, properties: { name: { type: string } } }
+"##;
+
+    #[tokio::test]
+    async fn builds_operation_requests_and_enforces_domains() {
+        let spec = Arc::new(OpenApiSpec::parse(SPEC).unwrap());
+        let capabilities = Capabilities { can_make_http_requests: true, allowed_domains: vec!["example.com".into()], ..Default::default() };
+        let tool = HttpTool::new(&capabilities).with_spec(spec.clone());
+
+        let request = tool.prepare(&json!({ "operation": "getPet", "args": { "petId": 7, "fields": "name,age" } })).unwrap();
+        assert_eq!(request.url.as_str(), "https://api.example.com/v1/pets/7?fields=name%2Cage");
+        assert_eq!(request.method, Method::GET);
+
+        let put = spec.operation("put_pets_petId").unwrap();
+        assert_eq!(put.args_schema()["properties"]["body"]["properties"]["name"]["type"], "string");
+        assert!(tool.prepare(&json!({ "operation": "put_pets_petId", "args": { "petId": 7 } })).is_err());
+
+        let err = tool.execute(json!({ "url": "https://evil.test/steal" })).await.unwrap_err();
+        assert!(err.to_string().contains("not in the agent's allowed domains"));
+    }
+
+    #[tokio::test]
+    async fn refuses_internal_addresses_and_drops_credentials_across_origins() {
+        let open = HttpTool::new(&Capabilities { can_make_http_requests: true, ..Default::default() });
+        for url in ["http://127.0.0.1:9/", "http://169.254.169.254/latest/meta-data/", "http://[::1]/", "http://10.1.2.3/", "http://[::ffff:192.168.0.1]/"] {
+            let err = open.execute(json!({ "url": url })).await.unwrap_err();
+            assert!(err.to_string().contains("internal address"), "{}: {}", url, err);
+        }
+        assert!(!is_internal_address("93.184.216.34".parse().unwrap()));
+
+        // An allowed CIDR opts an internal range back in.
+        let lan = Capabilities { can_make_http_requests: true, allowed_domains: vec!["10.0.0.0/8".into()], ..Default::default() };
+        let url = Url::parse("http://10.1.2.3:8080/").unwrap();
+        assert_eq!(HttpTool::new(&lan).resolve(&url).await.unwrap(), vec!["10.1.2.3:8080".parse().unwrap()]);
+
+        let headers = vec![("Authorization".to_string(), "Bearer s3cret".to_string()), ("Accept".to_string(), "application/json".to_string())];
+        assert_eq!(forwarded_headers(&headers, false).count(), 2);
+        let kept: Vec<_> = forwarded_headers(&headers, true).map(|(name, _)| name.as_str()).collect();
+        assert_eq!(kept, ["Accept"]);
+    }
+
+    #[tokio::test]
+    async fn bodies_past_the_cap_are_cut_off() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            let body = "x".repeat(256 * 1024);
+            let head = format!("HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\ncontent-length: {}\r\n\r\n", body.len());
+            let _ = socket.write_all(head.as_bytes()).await;
+            let _ = socket.write_all(body.as_bytes()).await;
+        });
+
+        let local = Capabilities { can_make_http_requests: true, allowed_domains: vec!["127.0.0.0/8".into()], ..Default::default() };
+        let tool = HttpTool::new(&local).with_max_body_bytes(1024);
+        let output: Value = serde_json::from_str(&tool.execute(json!({ "url": format!("http://{}/", addr) })).await.unwrap()).unwrap();
+        assert_eq!(output["truncated"], true);
+        assert_eq!(output["body"].as_str().unwrap().len(), 1024);
+        assert_eq!(output["exchange"]["response_bytes"], 256 * 1024);
+    }
+}
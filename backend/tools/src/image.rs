@@ -1,9 +1,19 @@
 /// Image generation tool.
 ///
 /// Mirrors `src/agents/tools/image-tool.ts` from OpenClaw.
-/// Supports DALL·E 3 (OpenAI) and Stable Diffusion via Replicate.
-use anyhow::{bail, Result};
+/// Supports DALL·E 3 (OpenAI), SDXL (Stability AI), Gemini image models and
+/// Stable Diffusion via Replicate. `ImageGenTool` stores each result in the
+/// outbound `MediaStore` and returns it as `media`, so channels upload the
+/// picture natively; every image is charged to the agent's cost record.
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use clawforge_core::Tool;
+use infra::CostTracker;
+use media::{sniff_mime_type, MediaStore};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
 use tracing::info;
 
 // ---------------------------------------------------------------------------
@@ -14,8 +24,14 @@ use tracing::info;
 pub enum ImageProvider {
     DallE3 { api_key: String },
     Replicate { api_key: String, model_version: String },
+    /// Stability AI's SDXL 1.0 text-to-image endpoint.
+    Sdxl { api_key: String },
+    Gemini { api_key: String, model: String },
 }
 
+/// Gemini model used when none is configured.
+pub const DEFAULT_GEMINI_IMAGE_MODEL: &str = "gemini-2.5-flash-image";
+
 impl ImageProvider {
     pub fn dalle3(api_key: impl Into<String>) -> Self {
         Self::DallE3 { api_key: api_key.into() }
@@ -23,6 +39,63 @@ impl ImageProvider {
     pub fn replicate(api_key: impl Into<String>, model_version: impl Into<String>) -> Self {
         Self::Replicate { api_key: api_key.into(), model_version: model_version.into() }
     }
+    pub fn sdxl(api_key: impl Into<String>) -> Self {
+        Self::Sdxl { api_key: api_key.into() }
+    }
+    pub fn gemini(api_key: impl Into<String>) -> Self {
+        Self::Gemini { api_key: api_key.into(), model: DEFAULT_GEMINI_IMAGE_MODEL.to_string() }
+    }
+
+    /// Provider named `provider` in config; `model` is the Gemini model or
+    /// the Replicate model version.
+    pub fn from_settings(provider: &str, api_key: String, model: Option<String>) -> Result<Self> {
+        Ok(match provider {
+            "dalle3" => Self::dalle3(api_key),
+            "sdxl" => Self::sdxl(api_key),
+            "gemini" => Self::Gemini { api_key, model: model.unwrap_or_else(|| DEFAULT_GEMINI_IMAGE_MODEL.to_string()) },
+            "replicate" => Self::replicate(api_key, model.context("Image provider 'replicate' needs a model version")?),
+            other => bail!("Unknown image provider '{}'", other),
+        })
+    }
+
+    /// Name the tool's `provider` argument selects this provider by.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DallE3 { .. } => "dalle3",
+            Self::Replicate { .. } => "replicate",
+            Self::Sdxl { .. } => "sdxl",
+            Self::Gemini { .. } => "gemini",
+        }
+    }
+
+    /// Model name cost records are filed under.
+    pub fn model(&self) -> String {
+        match self {
+            Self::DallE3 { .. } => "dall-e-3".to_string(),
+            Self::Replicate { model_version, .. } => format!("replicate/{}", model_version),
+            Self::Sdxl { .. } => "stable-diffusion-xl-1024-v1-0".to_string(),
+            Self::Gemini { model, .. } => model.clone(),
+        }
+    }
+
+    /// List price of one image in USD. Replicate bills by run time, so its
+    /// figure is an estimate.
+    pub fn price_per_image(&self, input: &ImageGenInput) -> f64 {
+        match self {
+            Self::DallE3 { .. } => {
+                let hd = input.quality.as_deref() == Some("hd");
+                let square = input.size.as_deref().unwrap_or("1024x1024") == "1024x1024";
+                match (hd, square) {
+                    (false, true) => 0.04,
+                    (false, false) | (true, true) => 0.08,
+                    (true, false) => 0.12,
+                }
+            }
+            Self::Replicate { .. } => 0.01,
+            Self::Sdxl { .. } => 0.006,
+            Self::Gemini { .. } => 0.039,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -42,6 +115,9 @@ pub struct ImageGenOutput {
     pub url: Option<String>,
     pub b64_json: Option<String>,
     pub revised_prompt: Option<String>,
+    /// Type of `b64_json`, when the provider reports it.
+    #[serde(default)]
+    pub mime_type: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -57,6 +133,8 @@ pub async fn generate_image(
         ImageProvider::Replicate { api_key, model_version } => {
             generate_replicate(api_key, model_version, input).await
         }
+        ImageProvider::Sdxl { api_key } => generate_sdxl(api_key, input).await,
+        ImageProvider::Gemini { api_key, model } => generate_gemini(api_key, model, input).await,
     }
 }
 
@@ -86,6 +164,7 @@ async fn generate_dalle3(api_key: &str, input: &ImageGenInput) -> Result<ImageGe
         url: data["url"].as_str().map(str::to_string),
         b64_json: data["b64_json"].as_str().map(str::to_string),
         revised_prompt: data["revised_prompt"].as_str().map(str::to_string),
+        mime_type: None,
     })
 }
 
@@ -117,5 +196,296 @@ async fn generate_replicate(
         .and_then(|a| a.first())
         .and_then(|v| v.as_str())
         .map(str::to_string);
-    Ok(ImageGenOutput { url, b64_json: None, revised_prompt: None })
+    Ok(ImageGenOutput { url, b64_json: None, revised_prompt: None, mime_type: None })
+}
+
+/// `1024x1792` → (1024, 1792); square when unset or malformed.
+fn parse_size(size: Option<&str>) -> (u32, u32) {
+    size.and_then(|s| s.split_once('x'))
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+        .unwrap_or((1024, 1024))
+}
+
+/// Dimensions the SDXL 1.0 endpoint accepts.
+const SDXL_SIZES: &[(u32, u32)] =
+    &[(1024, 1024), (1152, 896), (896, 1152), (1216, 832), (832, 1216), (1344, 768), (768, 1344), (1536, 640), (640, 1536)];
+
+async fn generate_sdxl(api_key: &str, input: &ImageGenInput) -> Result<ImageGenOutput> {
+    info!("[ImageGen] SDXL — prompt: {:.80}", input.prompt);
+    let size = parse_size(input.size.as_deref());
+    let (width, height) = if SDXL_SIZES.contains(&size) { size } else { (1024, 1024) };
+    let mut body = serde_json::json!({
+        "text_prompts": [{ "text": input.prompt, "weight": 1 }],
+        "width": width,
+        "height": height,
+        "samples": 1,
+        "steps": if input.quality.as_deref() == Some("hd") { 50 } else { 30 },
+    });
+    if let Some(style) = &input.style {
+        body["style_preset"] = Value::String(style.clone());
+    }
+    let resp = reqwest::Client::new()
+        .post("https://api.stability.ai/v1/generation/stable-diffusion-xl-1024-v1-0/text-to-image")
+        .bearer_auth(api_key)
+        .header("Accept", "application/json")
+        .json(&body)
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        bail!("SDXL error: {}", resp.text().await.unwrap_or_default());
+    }
+    let json: Value = resp.json().await?;
+    let artifact = &json["artifacts"][0];
+    if artifact["finishReason"] == "CONTENT_FILTERED" {
+        bail!("SDXL refused the prompt (content filter)");
+    }
+    Ok(ImageGenOutput {
+        url: None,
+        b64_json: artifact["base64"].as_str().map(str::to_string),
+        revised_prompt: None,
+        mime_type: Some("image/png".to_string()),
+    })
+}
+
+async fn generate_gemini(api_key: &str, model: &str, input: &ImageGenInput) -> Result<ImageGenOutput> {
+    info!("[ImageGen] Gemini {} — prompt: {:.80}", model, input.prompt);
+    let mut prompt = input.prompt.clone();
+    if let Some(style) = &input.style {
+        prompt.push_str(&format!("\nStyle: {}", style));
+    }
+    let body = serde_json::json!({
+        "contents": [{ "parts": [{ "text": prompt }] }],
+        "generationConfig": { "responseModalities": ["TEXT", "IMAGE"] },
+    });
+    let resp = reqwest::Client::new()
+        .post(format!("https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent", model))
+        .header("x-goog-api-key", api_key)
+        .json(&body)
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        bail!("Gemini error: {}", resp.text().await.unwrap_or_default());
+    }
+    let json: Value = resp.json().await?;
+    parse_gemini(&json)
+}
+
+fn parse_gemini(json: &Value) -> Result<ImageGenOutput> {
+    let parts = json["candidates"][0]["content"]["parts"].as_array().cloned().unwrap_or_default();
+    let image = parts
+        .iter()
+        .find_map(|p| p.get("inlineData").or_else(|| p.get("inline_data")))
+        .ok_or_else(|| anyhow!("Gemini returned no image"))?;
+    let text: Vec<&str> = parts.iter().filter_map(|p| p["text"].as_str()).collect();
+    Ok(ImageGenOutput {
+        url: None,
+        b64_json: image["data"].as_str().map(str::to_string),
+        revised_prompt: (!text.is_empty()).then(|| text.join(" ")),
+        mime_type: image.get("mimeType").or_else(|| image.get("mime_type")).and_then(Value::as_str).map(str::to_string),
+    })
+}
+
+/// The generated image's bytes and MIME type, downloading it when the
+/// provider only returned a (short-lived) URL.
+async fn image_bytes(client: &reqwest::Client, output: &ImageGenOutput) -> Result<(Vec<u8>, String)> {
+    let (bytes, reported) = match (&output.b64_json, &output.url) {
+        (Some(b64), _) => {
+            let bytes = base64::engine::general_purpose::STANDARD.decode(b64).context("Provider returned invalid base64")?;
+            (bytes, output.mime_type.clone())
+        }
+        (None, Some(url)) => {
+            let resp = client.get(url).send().await?.error_for_status().context("Failed to download generated image")?;
+            let mime = resp
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.split(';').next().unwrap_or(v).trim().to_string());
+            (resp.bytes().await?.to_vec(), mime)
+        }
+        (None, None) => bail!("Provider returned no image"),
+    };
+    let mime = reported
+        .filter(|m| m.starts_with("image/"))
+        .or_else(|| sniff_mime_type(&bytes).filter(|m| m.starts_with("image/")).map(str::to_string))
+        .ok_or_else(|| anyhow!("Provider returned something other than an image"))?;
+    Ok((bytes, mime))
+}
+
+// ---------------------------------------------------------------------------
+// Tool
+// ---------------------------------------------------------------------------
+
+/// Wildcard in `ImageGenPolicy::agents`.
+const ANY_AGENT: &str = "*";
+
+/// Which agents may generate images. Empty disables the tool for everyone.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageGenPolicy {
+    #[serde(default)]
+    pub agents: Vec<String>,
+}
+
+impl ImageGenPolicy {
+    pub fn allows(&self, agent: &str) -> bool {
+        self.agents.iter().any(|a| a == agent || a == ANY_AGENT)
+    }
+}
+
+/// `generate_image` tool bound to the calling agent. The first configured
+/// provider is the default.
+#[derive(Clone)]
+pub struct ImageGenTool {
+    agent: String,
+    /// Session costs are charged to; taken from the call's `session_id` when unset.
+    session: Option<String>,
+    providers: Vec<ImageProvider>,
+    policy: ImageGenPolicy,
+    store: Arc<MediaStore>,
+    costs: Option<CostTracker>,
+    client: reqwest::Client,
+}
+
+impl ImageGenTool {
+    pub fn new(agent: impl Into<String>, store: Arc<MediaStore>, policy: ImageGenPolicy) -> Self {
+        Self { agent: agent.into(), session: None, providers: Vec::new(), policy, store, costs: None, client: reqwest::Client::new() }
+    }
+
+    /// This tool acting for `agent` in `session`.
+    pub fn bound_to(&self, agent: impl Into<String>, session: impl Into<String>) -> Self {
+        Self { agent: agent.into(), session: Some(session.into()), ..self.clone() }
+    }
+
+    pub fn with_provider(mut self, provider: ImageProvider) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    /// Charge each generated image to the agent.
+    pub fn with_cost_tracker(mut self, costs: CostTracker) -> Self {
+        self.costs = Some(costs);
+        self
+    }
+
+    fn provider(&self, name: Option<&str>) -> Result<&ImageProvider> {
+        match name {
+            Some(name) => self.providers.iter().find(|p| p.name() == name).ok_or_else(|| {
+                let names: Vec<&str> = self.providers.iter().map(ImageProvider::name).collect();
+                anyhow!("Image provider '{}' is not configured. Available: {}", name, names.join(", "))
+            }),
+            None => self.providers.first().ok_or_else(|| anyhow!("No image provider is configured")),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for ImageGenTool {
+    fn name(&self) -> &str {
+        "generate_image"
+    }
+
+    fn description(&self) -> &str {
+        "Generate an image from a text prompt. The image is attached to your reply automatically; describe it briefly rather than linking it."
+    }
+
+    fn parameters(&self) -> Value {
+        let providers: Vec<&str> = self.providers.iter().map(ImageProvider::name).collect();
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "prompt": { "type": "string", "description": "What to draw, in detail" },
+                "size": { "type": "string", "enum": ["1024x1024", "1024x1792", "1792x1024"] },
+                "quality": { "type": "string", "enum": ["standard", "hd"] },
+                "style": { "type": "string", "description": "e.g. \"vivid\", \"natural\", \"photographic\"" },
+                "provider": { "type": "string", "enum": providers },
+                "caption": { "type": "string", "description": "Caption shown with the image" },
+                "session_id": { "type": "string", "description": "Filled in by the runtime" }
+            },
+            "required": ["prompt"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        if !self.policy.allows(&self.agent) {
+            bail!("Image generation is not enabled for agent '{}'", self.agent);
+        }
+        let input: ImageGenInput = serde_json::from_value(args.clone()).context("Invalid image generation arguments")?;
+        if input.prompt.trim().is_empty() {
+            bail!("Missing 'prompt' argument");
+        }
+        let provider = self.provider(args["provider"].as_str())?;
+        let output = generate_image(provider, &input).await?;
+        let (bytes, mime) = image_bytes(&self.client, &output).await?;
+
+        let cost_usd = provider.price_per_image(&input);
+        if let Some(costs) = &self.costs {
+            let session = self.session.as_deref().or(args["session_id"].as_str()).unwrap_or_default();
+            costs.record_fixed_cost(session, &self.agent, &provider.model(), cost_usd).await?;
+        }
+
+        let file_name = format!("image.{}", media::extension_for(&mime));
+        let caption = args["caption"].as_str().map(str::to_string);
+        let attachment = self.store.store(&bytes, &mime, &file_name, caption).await?;
+        info!("[ImageGen] {} generated {} ({} bytes) for ${:.3}", self.agent, provider.name(), bytes.len(), cost_usd);
+        Ok(serde_json::json!({
+            "media": attachment,
+            "provider": provider.name(),
+            "revised_prompt": output.revised_prompt,
+            "cost_usd": cost_usd,
+        })
+        .to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gemini_inline_image_is_parsed() {
+        let json = serde_json::json!({
+            "candidates": [{ "content": { "parts": [
+                { "text": "A red fox in snow." },
+                { "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=" } }
+            ] } }]
+        });
+        let output = parse_gemini(&json).unwrap();
+        assert_eq!(output.b64_json.as_deref(), Some("iVBORw0KGgo="));
+        assert_eq!(output.mime_type.as_deref(), Some("image/png"));
+        assert_eq!(output.revised_prompt.as_deref(), Some("A red fox in snow."));
+        assert!(parse_gemini(&serde_json::json!({ "candidates": [] })).is_err());
+    }
+
+    #[test]
+    fn pricing_and_sizes() {
+        let input = |size: &str, quality: &str| ImageGenInput {
+            prompt: "x".into(),
+            size: Some(size.into()),
+            quality: Some(quality.into()),
+            style: None,
+        };
+        let dalle = ImageProvider::dalle3("k");
+        assert_eq!(dalle.price_per_image(&input("1024x1024", "standard")), 0.04);
+        assert_eq!(dalle.price_per_image(&input("1792x1024", "hd")), 0.12);
+        assert_eq!(parse_size(Some("1024x1792")), (1024, 1792));
+        assert_eq!(parse_size(Some("big")), (1024, 1024));
+
+        assert_eq!(ImageProvider::from_settings("gemini", "k".into(), None).unwrap().model(), DEFAULT_GEMINI_IMAGE_MODEL);
+        assert!(ImageProvider::from_settings("replicate", "k".into(), None).is_err());
+        assert!(ImageProvider::from_settings("midjourney", "k".into(), None).is_err());
+    }
+
+    #[tokio::test]
+    async fn disabled_agents_are_refused() {
+        let dir = std::env::temp_dir().join(format!("clawforge-imagegen-{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(MediaStore::new(&dir, "https://claw.example.com/media", "secret"));
+        let tool = ImageGenTool::new("writer", store.clone(), ImageGenPolicy { agents: vec!["artist".into()] })
+            .with_provider(ImageProvider::dalle3("k"));
+        let err = tool.execute(serde_json::json!({ "prompt": "a fox" })).await.unwrap_err();
+        assert!(err.to_string().contains("not enabled"));
+
+        let tool = ImageGenTool::new("writer", store, ImageGenPolicy { agents: vec!["artist".into()] }).bound_to("artist", "s1");
+        let err = tool.execute(serde_json::json!({ "prompt": "a fox" })).await.unwrap_err();
+        assert!(err.to_string().contains("No image provider"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
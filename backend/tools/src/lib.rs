@@ -1,35 +1,63 @@
+pub mod agent_message_tool;
 pub mod apply_patch;
+pub mod artifact_tool;
 pub mod bash_exec;
 pub mod patch_validator;
 pub mod browser;
+pub mod catalog_sync;
 pub mod compaction;
+pub mod connectors;
+pub mod downloads;
 pub mod cron_tool;
+pub mod desktop;
+pub mod edit;
 pub mod file;
+pub mod http_tool;
 pub mod image;
 pub mod loop_detection;
+pub mod mac_automation;
 pub mod memory_tool;
 pub mod message_tool;
 pub mod model_catalog;
 pub mod node;
 pub mod process_registry;
+pub mod python;
+pub mod search;
+pub mod search_providers;
+pub mod secrets;
 pub mod sessions_tool;
 pub mod shell;
 pub mod skill_install;
+pub mod state_tool;
 pub mod subagents_tool;
 pub mod web;
 
+pub use agent_message_tool::{AgentMessagePolicy, SendToAgentInput, SendToAgentOutput, SendToAgentTool};
+pub use artifact_tool::{Artifact, ArtifactKind, ArtifactStore, ArtifactTool};
 pub use browser::BrowserTool;
 pub use compaction::{compact_history, CompactionResult, Turn};
-pub use file::{FileReadTool, FileWriteTool};
-pub use loop_detection::{hash_input, LoopDetector, ToolCall};
+pub use connectors::{Connector, ConnectorConfig, ConnectorContext, ConnectorSet, ConnectorSource, ConnectorTool, RateLimit};
+pub use desktop::DesktopTool;
+pub use downloads::{Download, DownloadManager, DownloadStatus, DownloadWriter};
+pub use edit::EditTool;
+pub use http_tool::{HttpAuditSink, HttpExchange, HttpTool, OpenApiSpec, Operation};
+pub use file::{preview_write, unified_diff, Edit, EditJournal, FileReadTool, FileWriteTool, GitTool, WritePreview};
+pub use mac_automation::MacAutomationTool;
+pub use loop_detection::{hash_input, LoopDetector, LoopKind, ToolCall};
 pub use memory_tool::{MemoryAddInput, MemoryAddOutput, MemoryDeleteInput, MemoryDeleteOutput, MemoryHit, MemorySearchInput, MemorySearchOutput, MemoryToolBackend, MemoryCollection};
 pub use message_tool::{MessageChannel, MessageSender, MessageToolInput, MessageToolOutput, MessageToolRegistry};
-pub use model_catalog::{ModelCatalog, ModelEntry};
+pub use catalog_sync::{CatalogSource, CatalogSync, SyncReport};
+pub use model_catalog::{LearnedLimit, ModelCatalog, ModelEntry, ModelPricing};
+pub use search::{Glob, GlobTool, GrepMatch, GrepTool};
+pub use search_providers::{SearchBackend, SearchProvider, SearchProviderConfig, SearchProviders, SearchRouter, WebSearchTool};
 pub use sessions_tool::{ListSessionsInput, SendToSessionInput, SendToSessionOutput, SessionBackend, SessionEntry, SessionHistoryInput, SessionHistoryOutput, SessionStatus, SpawnSessionInput, SpawnSessionOutput, TranscriptEntry};
+pub use secrets::{SecretExecTool, SecretLeaseTool};
 pub use shell::ShellTool;
 pub use subagents_tool::{new_subagent_id, SpawnSubagentInput, SpawnSubagentOutput, SteerSubagentInput, StopSubagentInput, SubagentBackend, SubagentEntry, SubagentRegistry, SubagentStatus};
-pub use web::{web_fetch, web_search, WebFetchInput, WebFetchOutput, WebSearchInput, WebSearchOutput, SearchHit};
+pub use web::{web_fetch, web_fetch_guarded, web_fetch_managed, web_search, WebFetchInput, WebFetchTool, WebFetchOutput, WebSearchInput, WebSearchOutput, SearchHit};
 pub use cron_tool::{CronBackend, CronJob, CronToolInput, CronToolOutput, InMemoryCronBackend, run_cron_tool, CreateCronInput, UpdateCronInput};
-pub use image::{generate_image, ImageGenInput, ImageGenOutput, ImageProvider};
+pub use image::{generate_image, ImageGenInput, ImageGenOutput, ImageGenPolicy, ImageGenTool, ImageProvider, DEFAULT_GEMINI_IMAGE_MODEL};
 pub use process_registry::{ProcessEntry, ProcessRegistry};
-pub use skill_install::{SkillInstaller, SkillInstallResult, SkillSource};
+pub use python::PythonTool;
+pub use skill_install::{SkillInstaller, SkillInstallResult, SkillRecord, SkillSource};
+pub use state_tool::{validate_entry, InMemoryStateBackend, StateBackend, StateEntry, StateGetTool, StateSetTool};
@@ -2,7 +2,7 @@
 /// repeatedly in an infinite loop.
 ///
 /// Mirrors `src/agents/tool-loop-detection.ts`.
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use serde::{Deserialize, Serialize};
 
 /// A single tool invocation record.
@@ -12,6 +12,16 @@ pub struct ToolCall {
     pub input_hash: u64,
 }
 
+/// What kind of loop a call completed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LoopKind {
+    /// The same tool with the same input, `count` times.
+    Repeated { tool: String, count: usize },
+    /// Two calls alternating (edit, revert, edit, ...) for `cycles` rounds.
+    Oscillating { tools: (String, String), cycles: usize },
+}
+
 /// Detection state for a single agent run.
 #[derive(Default)]
 pub struct LoopDetector {
@@ -19,6 +29,10 @@ pub struct LoopDetector {
     call_counts: HashMap<(String, u64), usize>,
     /// Maximum allowed identical calls before a loop is declared.
     max_identical: usize,
+    /// Recent calls, newest last, for spotting A/B alternation.
+    recent: VecDeque<(String, u64)>,
+    /// A/B rounds that count as oscillation; 0 disables the check.
+    oscillation_cycles: usize,
 }
 
 impl LoopDetector {
@@ -26,7 +40,45 @@ impl LoopDetector {
         Self {
             call_counts: HashMap::new(),
             max_identical,
+            recent: VecDeque::new(),
+            oscillation_cycles: 0,
+        }
+    }
+
+    /// Also flag two calls alternating for `cycles` rounds.
+    pub fn with_oscillation(mut self, cycles: usize) -> Self {
+        self.oscillation_cycles = cycles;
+        self
+    }
+
+    /// Record a call and report the loop it completes, if any. The matching
+    /// counters start over afterwards, so a persisting loop is reported again
+    /// only after another full round.
+    pub fn check(&mut self, call: &ToolCall) -> Option<LoopKind> {
+        let key = (call.tool_name.clone(), call.input_hash);
+        if self.record(call) {
+            let count = self.call_counts.remove(&key).unwrap_or_default();
+            self.recent.clear();
+            return Some(LoopKind::Repeated { tool: call.tool_name.clone(), count });
+        }
+        if self.oscillation_cycles == 0 {
+            return None;
+        }
+        let window = self.oscillation_cycles * 2;
+        self.recent.push_back(key);
+        if self.recent.len() > window {
+            self.recent.pop_front();
+        }
+        let (a, b) = (&self.recent[0], self.recent.get(1)?);
+        let alternating = self.recent.len() == window
+            && a != b
+            && self.recent.iter().enumerate().all(|(i, c)| c == if i % 2 == 0 { a } else { b });
+        if !alternating {
+            return None;
         }
+        let tools = (a.0.clone(), b.0.clone());
+        self.recent.clear();
+        Some(LoopKind::Oscillating { tools, cycles: self.oscillation_cycles })
     }
 
     /// Record a tool call and return whether a loop was detected.
@@ -40,6 +92,7 @@ impl LoopDetector {
     /// Reset the detector for a new run.
     pub fn reset(&mut self) {
         self.call_counts.clear();
+        self.recent.clear();
     }
 }
 
@@ -79,4 +132,19 @@ mod tests {
             assert!(!detector.record(&call));
         }
     }
+
+    #[test]
+    fn test_edit_revert_oscillation_flagged() {
+        let mut detector = LoopDetector::new(5).with_oscillation(2);
+        let edit = |input: &str| ToolCall { tool_name: "edit".into(), input_hash: hash_input(input) };
+        assert_eq!(detector.check(&edit("a->b")), None);
+        assert_eq!(detector.check(&edit("b->a")), None);
+        assert_eq!(detector.check(&edit("a->b")), None);
+        assert_eq!(
+            detector.check(&edit("b->a")),
+            Some(LoopKind::Oscillating { tools: ("edit".into(), "edit".into()), cycles: 2 })
+        );
+        // Counters start over once a loop is reported.
+        assert_eq!(detector.check(&edit("a->b")), None);
+    }
 }
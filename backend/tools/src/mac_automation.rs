@@ -0,0 +1,150 @@
+//! `mac_automation` tool: run allowlisted AppleScript or Shortcuts on a macOS node.
+//!
+//! The agent names a script from the `ScriptAllowlist` and passes its
+//! parameters; the rendered description is the approval prompt, so the owner
+//! sees what will happen before anything runs. `dry_run` returns the rendered
+//! description and script without asking or running.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use clawforge_companion::{NodeHostRegistry, NodeTransport, ScriptAllowlist};
+use clawforge_core::Tool;
+use clawforge_security::ApprovalBroker;
+use serde_json::{json, Value};
+use tracing::info;
+
+const TOOL_NAME: &str = "mac_automation";
+
+pub struct MacAutomationTool<T: NodeTransport> {
+    registry: Arc<NodeHostRegistry<T>>,
+    allowlist: Arc<ScriptAllowlist>,
+    approvals: Option<(Arc<ApprovalBroker>, String, Option<String>)>,
+    description: String,
+}
+
+impl<T: NodeTransport> MacAutomationTool<T> {
+    pub fn new(registry: Arc<NodeHostRegistry<T>>, allowlist: Arc<ScriptAllowlist>) -> Self {
+        let names: Vec<String> = allowlist.scripts().map(|s| format!("{} ({})", s.name, s.description)).collect();
+        let description = format!(
+            "Run an approved macOS automation on a Mac node. Set dry_run to preview. Available scripts: {}",
+            if names.is_empty() { "none".to_string() } else { names.join("; ") }
+        );
+        Self { registry, allowlist, approvals: None, description }
+    }
+
+    /// Ask the owner through `broker` before every run. Without it, only dry runs work.
+    pub fn with_approvals(mut self, broker: Arc<ApprovalBroker>, session_id: impl Into<String>, channel: Option<String>) -> Self {
+        self.approvals = Some((broker, session_id.into(), channel));
+        self
+    }
+
+    async fn check_node(&self, node_id: &str, task: &str) -> Result<()> {
+        let nodes = self.registry.list().await;
+        let (registration, _) = nodes
+            .iter()
+            .find(|(reg, _)| reg.node_id == node_id)
+            .ok_or_else(|| anyhow!("Node '{}' not found", node_id))?;
+        if registration.platform != "macos" {
+            bail!("Node '{}' is not a Mac ({})", node_id, registration.platform);
+        }
+        if !registration.capabilities.iter().any(|c| c == task) {
+            bail!("Node '{}' does not support {}", node_id, task);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T: NodeTransport> Tool for MacAutomationTool<T> {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "node_id": { "type": "string", "description": "Mac node to run on" },
+                "script": { "type": "string", "description": "Name of an allowlisted script" },
+                "params": { "type": "object", "description": "Values for the script's parameters" },
+                "dry_run": { "type": "boolean", "description": "Describe what would run without running it" }
+            },
+            "required": ["node_id", "script"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let node_id = args["node_id"].as_str().ok_or_else(|| anyhow!("Missing 'node_id' argument"))?;
+        let name = args["script"].as_str().ok_or_else(|| anyhow!("Missing 'script' argument"))?;
+        let script = self.allowlist.get(name).ok_or_else(|| anyhow!("Script '{}' is not on the allowlist", name))?;
+        let empty = serde_json::Map::new();
+        let params = match args.get("params") {
+            Some(Value::Object(params)) => params,
+            None | Some(Value::Null) => &empty,
+            Some(_) => bail!("'params' must be an object"),
+        };
+        let rendered = script.render(params)?;
+
+        if args["dry_run"].as_bool().unwrap_or(false) {
+            return Ok(json!({ "dry_run": true, "description": rendered.description, "task": rendered.task, "script": rendered.script }).to_string());
+        }
+
+        self.check_node(node_id, rendered.task).await?;
+        let (broker, session_id, channel) = self.approvals.as_ref().ok_or_else(|| anyhow!("Automations need an approval broker"))?;
+        let summary = format!("{} on {}", rendered.description, node_id);
+        let reasons = vec![format!("Runs {} '{}':\n{}", if rendered.task == "shortcuts.run" { "Shortcut" } else { "AppleScript" }, name, rendered.script)];
+        if !broker.request(session_id, channel.as_deref(), TOOL_NAME, &summary, reasons).await.is_approved() {
+            bail!("{} was not approved", summary);
+        }
+
+        info!(node_id = %node_id, script = %name, "Running macOS automation");
+        let result = self.registry.invoke(node_id, rendered.task, rendered.args, Some(60)).await?;
+        if !result.success {
+            bail!("Script '{}' failed on '{}': {}", name, node_id, result.error.unwrap_or_else(|| "unknown error".into()));
+        }
+        Ok(json!({ "ok": true, "script": name, "description": rendered.description, "output": result.output }).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawforge_companion::{NodeInvocation, NodeInvocationResult};
+
+    struct Offline;
+
+    impl NodeTransport for Offline {
+        async fn invoke(&self, _invocation: NodeInvocation) -> Result<NodeInvocationResult> {
+            bail!("offline")
+        }
+
+        async fn ping(&self, _node_id: &str) -> Result<bool> {
+            Ok(false)
+        }
+    }
+
+    #[tokio::test]
+    async fn dry_run_describes_without_running() {
+        let allowlist = ScriptAllowlist::from_yaml(
+            "scripts:\n  - name: play\n    description: Play {playlist} in Music\n    shortcut: Play Playlist\n    params:\n      - name: playlist\n",
+        )
+        .unwrap();
+        let tool = MacAutomationTool::new(Arc::new(NodeHostRegistry::new(Offline)), Arc::new(allowlist));
+        assert!(tool.description().contains("play (Play {playlist} in Music)"));
+
+        let args = json!({ "node_id": "mac", "script": "play", "params": { "playlist": "Focus" }, "dry_run": true });
+        let out: Value = serde_json::from_str(&tool.execute(args).await.unwrap()).unwrap();
+        assert_eq!(out["description"], "Play Focus in Music");
+        assert_eq!(out["task"], "shortcuts.run");
+
+        assert!(tool.execute(json!({ "node_id": "mac", "script": "rm" })).await.is_err());
+        let live = json!({ "node_id": "mac", "script": "play", "params": { "playlist": "Focus" } });
+        assert!(tool.execute(live).await.unwrap_err().to_string().contains("not found"));
+    }
+}
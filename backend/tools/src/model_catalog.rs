@@ -2,11 +2,28 @@
 /// across all configured providers.
 ///
 /// Mirrors `src/agents/model-catalog.ts`.
+///
+/// Besides the built-in defaults, the catalog merges models declared in
+/// `ModelsConfig` and live lists fetched by `CatalogSync`. A model a provider
+/// stops listing (or lists with an expiration date) is kept but flagged
+/// deprecated, so agents still pinned to it can be warned about.
+///
+/// Context windows learned from provider overflow errors are kept per
+/// (provider, model): the same model can be capped differently by the
+/// providers serving it.
+use clawforge_config::schema::ModelsConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
 /// A model entry in the catalog.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModelEntry {
     pub id: String,
     pub provider: String,
@@ -15,12 +32,33 @@ pub struct ModelEntry {
     pub supports_vision: bool,
     pub supports_tools: bool,
     pub is_default: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+    /// No longer offered by the provider, or scheduled for removal.
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+/// A context window a provider enforced, learned from its overflow error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LearnedLimit {
+    pub provider: String,
+    pub model: String,
+    /// Prompt plus completion tokens the provider accepts for the model.
+    pub context_window: usize,
+    /// Whether the provider stated the number (vs. our estimate).
+    pub reported: bool,
+    /// Unix seconds.
+    pub learned_at: u64,
 }
 
 /// The full model catalog organized by provider.
 #[derive(Debug, Default)]
 pub struct ModelCatalog {
     models: HashMap<String, ModelEntry>,
+    /// Keyed by (provider, model).
+    learned: HashMap<(String, String), LearnedLimit>,
 }
 
 impl ModelCatalog {
@@ -41,6 +79,7 @@ impl ModelCatalog {
                 supports_vision: true,
                 supports_tools: true,
                 is_default: true,
+                ..Default::default()
             },
             ModelEntry {
                 id: "gpt-4o-mini".into(),
@@ -50,6 +89,7 @@ impl ModelCatalog {
                 supports_vision: true,
                 supports_tools: true,
                 is_default: false,
+                ..Default::default()
             },
             ModelEntry {
                 id: "claude-opus-4-5".into(),
@@ -59,6 +99,7 @@ impl ModelCatalog {
                 supports_vision: true,
                 supports_tools: true,
                 is_default: false,
+                ..Default::default()
             },
             ModelEntry {
                 id: "claude-sonnet-4-5".into(),
@@ -68,6 +109,7 @@ impl ModelCatalog {
                 supports_vision: true,
                 supports_tools: true,
                 is_default: false,
+                ..Default::default()
             },
             ModelEntry {
                 id: "gemini-2.0-flash".into(),
@@ -77,6 +119,7 @@ impl ModelCatalog {
                 supports_vision: true,
                 supports_tools: true,
                 is_default: false,
+                ..Default::default()
             },
             ModelEntry {
                 id: "gemini-2.5-pro".into(),
@@ -86,6 +129,7 @@ impl ModelCatalog {
                 supports_vision: true,
                 supports_tools: true,
                 is_default: false,
+                ..Default::default()
             },
             ModelEntry {
                 id: "llama3.3:70b".into(),
@@ -95,6 +139,7 @@ impl ModelCatalog {
                 supports_vision: false,
                 supports_tools: true,
                 is_default: false,
+                ..Default::default()
             },
         ];
         for m in defaults {
@@ -127,4 +172,108 @@ impl ModelCatalog {
     pub fn get(&self, id: &str) -> Option<&ModelEntry> {
         self.models.get(id)
     }
+
+    /// Apply models declared in config; declared values win over synced ones.
+    pub fn merge_config(&mut self, config: &ModelsConfig) {
+        for (provider, provider_config) in &config.providers {
+            if provider_config.disabled == Some(true) {
+                continue;
+            }
+            for def in &provider_config.models {
+                let entry = self.models.entry(def.id.clone()).or_insert_with(|| ModelEntry {
+                    id: def.id.clone(),
+                    provider: provider.clone(),
+                    supports_tools: true,
+                    ..Default::default()
+                });
+                entry.display_name = def.name.clone();
+                if let Some(window) = def.context_window {
+                    entry.context_window = window as usize;
+                }
+                if !def.input.is_empty() {
+                    entry.supports_vision = def.input.iter().any(|i| i == "image");
+                }
+                if let Some(cost) = &def.cost {
+                    entry.pricing = Some(ModelPricing { input_per_million: cost.input, output_per_million: cost.output });
+                }
+            }
+        }
+    }
+
+    /// Replace what we know from `provider` with a freshly fetched list.
+    /// Known fields the live list lacks (context window, pricing) are kept.
+    /// Returns ids of models that became deprecated with this sync.
+    pub fn apply_live(&mut self, provider: &str, live: Vec<ModelEntry>) -> Vec<String> {
+        let listed: std::collections::HashSet<String> = live.iter().map(|m| m.id.clone()).collect();
+        let mut newly_deprecated = Vec::new();
+        for mut model in live {
+            if let Some(known) = self.models.get(&model.id) {
+                if model.context_window == 0 {
+                    model.context_window = known.context_window;
+                }
+                model.pricing = model.pricing.or(known.pricing);
+                model.supports_vision |= known.supports_vision;
+                model.is_default = known.is_default;
+                if model.display_name.is_empty() {
+                    model.display_name = known.display_name.clone();
+                }
+                if model.deprecated && !known.deprecated {
+                    newly_deprecated.push(model.id.clone());
+                }
+            }
+            if model.display_name.is_empty() {
+                model.display_name = model.id.clone();
+            }
+            self.models.insert(model.id.clone(), model);
+        }
+        // An alias such as `claude-sonnet-4-5` is listed through its dated snapshots.
+        let is_listed = |id: &str| listed.contains(id) || listed.iter().any(|l| l.strip_prefix(id).is_some_and(|rest| rest.starts_with('-')));
+        for model in self.models.values_mut() {
+            if model.provider == provider && !is_listed(&model.id) && !model.deprecated {
+                model.deprecated = true;
+                newly_deprecated.push(model.id.clone());
+            }
+        }
+        newly_deprecated.sort();
+        newly_deprecated
+    }
+
+    /// Deprecated models among `in_use`.
+    pub fn deprecated_in_use<'a>(&self, in_use: impl IntoIterator<Item = &'a str>) -> Vec<&ModelEntry> {
+        in_use.into_iter().filter_map(|id| self.models.get(id)).filter(|m| m.deprecated).collect()
+    }
+
+    /// The tightest context window known for `model` on `provider`: the
+    /// declared or synced window, lowered by anything the provider enforced.
+    pub fn context_window(&self, provider: &str, model: &str) -> Option<usize> {
+        let declared = self.models.get(model).map(|m| m.context_window).filter(|w| *w > 0);
+        let learned = self.learned.get(&(provider.to_string(), model.to_string())).map(|l| l.context_window);
+        match (declared, learned) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Record an enforced limit. Only ever lowers what was learned before;
+    /// returns whether anything changed.
+    pub fn learn_limit(&mut self, limit: LearnedLimit) -> bool {
+        let key = (limit.provider.clone(), limit.model.clone());
+        if self.learned.get(&key).is_some_and(|known| known.context_window <= limit.context_window) {
+            return false;
+        }
+        self.learned.insert(key, limit);
+        true
+    }
+
+    /// Every learned limit, for persisting.
+    pub fn learned_limits(&self) -> Vec<LearnedLimit> {
+        let mut limits: Vec<LearnedLimit> = self.learned.values().cloned().collect();
+        limits.sort_by(|a, b| (&a.provider, &a.model).cmp(&(&b.provider, &b.model)));
+        limits
+    }
+
+    /// Known prices, keyed by model id.
+    pub fn prices(&self) -> HashMap<String, ModelPricing> {
+        self.models.iter().filter_map(|(id, m)| Some((id.clone(), m.pricing?))).collect()
+    }
 }
@@ -0,0 +1,313 @@
+//! `python` tool: run snippets in the conversation's sandbox.
+//!
+//! Each call is one exec of a small runner inside the sandbox (whichever
+//! driver is configured). The runner keeps an interpreter session alive
+//! across calls by replaying earlier `import`/`def`/`class` statements and
+//! unpickling the variables saved after the previous call, so state survives
+//! without a long-lived process. Matplotlib figures left open are saved as
+//! PNGs and handed to the media pipeline; `pip install` waits on approval.
+//!
+//! The sandbox lives as long as the conversation: `/reset` removes it, and
+//! the registry's watchdog stops it at the config's `max_lifetime_secs`.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use bytes::Bytes;
+use clawforge_core::{Capabilities, Tool};
+use clawforge_sandbox::{DockerSandboxConfig, SandboxRegistry};
+use clawforge_security::ApprovalBroker;
+use media::{MediaPayload, MediaPipeline};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Where the runner keeps the session's state inside the sandbox.
+const STATE_DIR: &str = "/tmp/clawforge-python";
+/// Prefix of the runner's result line on stdout.
+const RESULT_MARKER: &str = "__CLAWFORGE_RESULT__";
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+
+/// Runs one cell: restore state, execute with captured output, collect open
+/// figures, save state, then print the result as JSON after the marker.
+const RUNNER: &str = r#"
+def _clawforge_main():
+    import ast, base64, contextlib, io, json, os, pickle, sys, traceback, types
+    os.environ.setdefault("MPLBACKEND", "Agg")
+    code = base64.b64decode(sys.argv[1]).decode()
+    state_dir = sys.argv[2]
+    ns = sys.modules["__main__"].__dict__
+    defs_path = os.path.join(state_dir, "defs.json")
+    vars_path = os.path.join(state_dir, "vars.pkl")
+    defs = json.load(open(defs_path)) if os.path.exists(defs_path) else []
+    for src in defs:
+        try:
+            exec(compile(src, "<session>", "exec"), ns)
+        except Exception:
+            pass
+    if os.path.exists(vars_path):
+        try:
+            ns.update(pickle.load(open(vars_path, "rb")))
+        except Exception:
+            pass
+    out, err = io.StringIO(), io.StringIO()
+    result, error = None, None
+    try:
+        tree = ast.parse(code, "<cell>", "exec")
+        last = tree.body.pop() if tree.body and isinstance(tree.body[-1], ast.Expr) else None
+        with contextlib.redirect_stdout(out), contextlib.redirect_stderr(err):
+            exec(compile(tree, "<cell>", "exec"), ns)
+            if last is not None:
+                value = eval(compile(ast.Expression(last.value), "<cell>", "eval"), ns)
+                if value is not None:
+                    result = repr(value)
+        kinds = (ast.Import, ast.ImportFrom, ast.FunctionDef, ast.AsyncFunctionDef, ast.ClassDef)
+        defs += [ast.get_source_segment(code, node) for node in tree.body if isinstance(node, kinds)]
+    except BaseException:
+        error = traceback.format_exc()
+    images = []
+    plt = sys.modules.get("matplotlib.pyplot")
+    if plt is not None:
+        for num in plt.get_fignums():
+            buf = io.BytesIO()
+            plt.figure(num).savefig(buf, format="png", bbox_inches="tight")
+            images.append(base64.b64encode(buf.getvalue()).decode())
+        plt.close("all")
+    kept, dropped = {}, []
+    for name, value in list(ns.items()):
+        if name.startswith("_") or isinstance(value, (types.ModuleType, type)):
+            continue
+        if isinstance(value, types.FunctionType) and value.__name__ != "<lambda>":
+            continue
+        try:
+            pickle.dumps(value)
+            kept[name] = value
+        except Exception:
+            dropped.append(name)
+    os.makedirs(state_dir, exist_ok=True)
+    json.dump(defs, open(defs_path, "w"))
+    pickle.dump(kept, open(vars_path, "wb"))
+    print("__CLAWFORGE_RESULT__" + json.dumps({
+        "stdout": out.getvalue(), "stderr": err.getvalue(), "result": result,
+        "error": error, "images": images, "dropped": dropped,
+    }))
+
+_clawforge_main()
+"#;
+
+#[derive(Debug, Deserialize)]
+struct CellResult {
+    stdout: String,
+    stderr: String,
+    result: Option<String>,
+    error: Option<String>,
+    #[serde(default)]
+    images: Vec<String>,
+    #[serde(default)]
+    dropped: Vec<String>,
+}
+
+/// Where figures go, with the run they belong to.
+struct MediaTarget {
+    pipeline: Arc<MediaPipeline>,
+    run_id: Uuid,
+    agent_id: Uuid,
+}
+
+pub struct PythonTool {
+    sandboxes: Arc<SandboxRegistry>,
+    /// One interpreter session per conversation.
+    session_id: String,
+    driver: String,
+    config: DockerSandboxConfig,
+    capabilities: Capabilities,
+    approvals: Option<(Arc<ApprovalBroker>, Option<String>)>,
+    media: Option<MediaTarget>,
+    timeout_secs: u64,
+}
+
+impl PythonTool {
+    /// A tool for `conversation`, whose sandbox gets the network access
+    /// `capabilities` allow.
+    pub fn new(sandboxes: Arc<SandboxRegistry>, conversation: &str, capabilities: Capabilities) -> Self {
+        Self {
+            sandboxes,
+            session_id: Self::sandbox_session(conversation),
+            driver: "docker".to_string(),
+            config: DockerSandboxConfig { image: "python:3.12-slim".to_string(), ..Default::default() },
+            capabilities,
+            approvals: None,
+            media: None,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+        }
+    }
+
+    /// The sandbox registry key of `conversation`'s interpreter.
+    pub fn sandbox_session(conversation: &str) -> String {
+        format!("python-{}", conversation)
+    }
+
+    /// Sandbox driver (`docker`, `bwrap`, a remote host, ...) and its settings.
+    pub fn with_sandbox(mut self, driver: impl Into<String>, config: DockerSandboxConfig) -> Self {
+        self.driver = driver.into();
+        self.config = config;
+        self
+    }
+
+    /// Allow `packages` installs once a human approves them; `channel` is
+    /// where the prompt goes.
+    pub fn with_approvals(mut self, broker: Arc<ApprovalBroker>, channel: Option<String>) -> Self {
+        self.approvals = Some((broker, channel));
+        self
+    }
+
+    /// Send figures to `pipeline` as images of the given run.
+    pub fn with_media(mut self, pipeline: Arc<MediaPipeline>, run_id: Uuid, agent_id: Uuid) -> Self {
+        self.media = Some(MediaTarget { pipeline, run_id, agent_id });
+        self
+    }
+
+    pub fn with_timeout(mut self, secs: u64) -> Self {
+        self.timeout_secs = secs;
+        self
+    }
+
+    async fn ensure_sandbox(&self) -> Result<()> {
+        if !self.sandboxes.has_sandbox(&self.session_id).await {
+            self.sandboxes
+                .start_agent_session(&self.session_id, &self.driver, &self.config, &self.capabilities)
+                .await
+                .context("Starting the Python sandbox")?;
+        }
+        Ok(())
+    }
+
+    async fn install(&self, packages: &[String]) -> Result<String> {
+        if let Some(bad) = packages.iter().find(|p| p.starts_with('-') || p.chars().any(char::is_whitespace)) {
+            bail!("Invalid package spec '{}'", bad);
+        }
+        let (broker, channel) = self.approvals.as_ref().ok_or_else(|| anyhow!("pip installs need an approval broker"))?;
+        let summary = format!("pip install {}", packages.join(" "));
+        let reasons = vec!["Downloads and runs third-party package code in the sandbox".to_string()];
+        let outcome = broker.request(&self.session_id, channel.as_deref(), "pip_install", &summary, reasons).await;
+        if !outcome.is_approved() {
+            bail!("{} was not approved", summary);
+        }
+        info!(session = %self.session_id, packages = ?packages, "Installing Python packages");
+        let argv: Vec<&str> = ["python3", "-m", "pip", "install", "--quiet", "--disable-pip-version-check"]
+            .into_iter()
+            .chain(packages.iter().map(String::as_str))
+            .collect();
+        let result = self.sandboxes.exec(&self.session_id, &argv, Some(self.timeout_secs.max(300))).await?;
+        if result.exit_code != 0 {
+            bail!("pip install failed: {}", result.stderr.trim());
+        }
+        Ok(summary)
+    }
+
+    async fn run_cell(&self, code: &str) -> Result<CellResult> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(code);
+        let argv = ["python3", "-c", RUNNER, &encoded, STATE_DIR];
+        let result = self.sandboxes.exec(&self.session_id, &argv, Some(self.timeout_secs)).await?;
+        if result.timed_out {
+            bail!("Python cell timed out after {}s", self.timeout_secs);
+        }
+        parse_cell_output(&result.stdout).ok_or_else(|| {
+            anyhow!("Python runner failed (exit {}): {}", result.exit_code, result.stderr.trim())
+        })
+    }
+
+    /// Hand figures to the media pipeline; returns how many were delivered.
+    async fn deliver_images(&self, images: &[String]) -> usize {
+        let Some(target) = &self.media else { return 0 };
+        let mut delivered = 0;
+        for image in images {
+            let Ok(data) = base64::engine::general_purpose::STANDARD.decode(image) else { continue };
+            let payload = MediaPayload { source: "python".to_string(), mime_type: "image/png".to_string(), data: Bytes::from(data) };
+            match target.pipeline.handle_media(target.run_id, target.agent_id, payload).await {
+                Ok(()) => delivered += 1,
+                Err(e) => warn!(error = %e, "Failed to deliver Python figure"),
+            }
+        }
+        delivered
+    }
+}
+
+fn parse_cell_output(stdout: &str) -> Option<CellResult> {
+    let line = stdout.lines().rev().find_map(|l| l.strip_prefix(RESULT_MARKER))?;
+    serde_json::from_str(line).ok()
+}
+
+#[async_trait]
+impl Tool for PythonTool {
+    fn name(&self) -> &str {
+        "python"
+    }
+
+    fn description(&self) -> &str {
+        "Run Python in a sandboxed session where variables, imports and functions persist between calls. The value of a trailing expression is returned; open matplotlib figures are captured as images. List `packages` to pip install them first (requires approval)."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "code": { "type": "string" },
+                "packages": { "type": "array", "items": { "type": "string" } },
+                "reset": { "type": "boolean", "description": "Clear the session's variables first" }
+            },
+            "required": ["code"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let code = args["code"].as_str().ok_or_else(|| anyhow!("Missing 'code' argument"))?;
+        self.ensure_sandbox().await?;
+        if args["reset"].as_bool() == Some(true) {
+            self.sandboxes.exec(&self.session_id, &["rm", "-rf", STATE_DIR], Some(10)).await?;
+        }
+        let packages: Vec<String> = args["packages"]
+            .as_array()
+            .map(|items| items.iter().filter_map(|p| p.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+        let installed = if packages.is_empty() { None } else { Some(self.install(&packages).await?) };
+
+        let cell = self.run_cell(code).await?;
+        let delivered = self.deliver_images(&cell.images).await;
+        let mut output = json!({
+            "stdout": cell.stdout,
+            "stderr": cell.stderr,
+            "result": cell.result,
+            "error": cell.error,
+            "images": { "captured": cell.images.len(), "delivered": delivered },
+        });
+        if !cell.dropped.is_empty() {
+            output["not_persisted"] = json!(cell.dropped);
+        }
+        if let Some(installed) = installed {
+            output["installed"] = json!(installed);
+        }
+        Ok(serde_json::to_string(&output)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_line_is_found_after_stray_output() {
+        let stdout = format!(
+            "warning from site hook\n{}{}\n",
+            RESULT_MARKER,
+            r#"{"stdout":"hi\n","stderr":"","result":"42","error":null,"images":["iVBO"],"dropped":["conn"]}"#
+        );
+        let cell = parse_cell_output(&stdout).unwrap();
+        assert_eq!((cell.stdout.as_str(), cell.result.as_deref()), ("hi\n", Some("42")));
+        assert_eq!((cell.images.len(), cell.dropped), (1, vec!["conn".to_string()]));
+        assert!(parse_cell_output("Traceback ...").is_none());
+    }
+}
@@ -0,0 +1,431 @@
+//! `grep` and `glob` tools: workspace code search without shelling out.
+//!
+//! Both walk the workspace with the `ignore` crate, honoring `.gitignore`
+//! files (and skipping `.git`), ignore binary and oversized files, cap their
+//! output, and return JSON the model can act on directly.
+//!
+//! `grep` ranks matching files so the likely definition comes first: files
+//! where the match follows a definition keyword (`fn`, `struct`, `class`,
+//! `def`, …), whole-word hits and more hits score higher, shallow paths beat
+//! deep ones.
+//! `glob` lists the most recently modified files first.
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use clawforge_core::Tool;
+use globset::{GlobBuilder, GlobMatcher};
+use ignore::WalkBuilder;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// Default and hard cap on matches returned by `grep`.
+const DEFAULT_MAX_MATCHES: usize = 100;
+const MAX_MATCHES: usize = 500;
+/// Default and hard cap on paths returned by `glob`.
+const DEFAULT_MAX_FILES: usize = 200;
+const MAX_FILES: usize = 1000;
+/// Files larger than this are not searched.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Stop walking after this many files so a huge tree can't stall the run.
+const MAX_WALK_FILES: usize = 50_000;
+const MAX_SNIPPET_CHARS: usize = 200;
+
+// ---------------------------------------------------------------------------
+// Tools
+// ---------------------------------------------------------------------------
+
+pub struct GrepTool {
+    workspace: PathBuf,
+}
+
+impl GrepTool {
+    pub fn new(workspace: impl Into<PathBuf>) -> Self {
+        Self { workspace: workspace.into() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GrepMatch {
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub snippet: String,
+}
+
+#[async_trait]
+impl Tool for GrepTool {
+    fn name(&self) -> &str {
+        "grep"
+    }
+
+    fn description(&self) -> &str {
+        "Search file contents in the workspace with a regex. Respects .gitignore; returns ranked matches (path, line, snippet)."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "Regular expression to search for"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Directory or file to search, relative to the workspace (default: whole workspace)"
+                },
+                "glob": {
+                    "type": "string",
+                    "description": "Only search files matching this glob, e.g. `*.rs` or `src/**/*.ts`"
+                },
+                "case_insensitive": {
+                    "type": "boolean",
+                    "description": "Ignore case"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum matches to return (default 100, max 500)"
+                }
+            },
+            "required": ["pattern"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let pattern = args["pattern"].as_str().ok_or_else(|| anyhow!("Missing 'pattern' argument"))?;
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(args["case_insensitive"].as_bool().unwrap_or(false))
+            .size_limit(1 << 20)
+            .build()
+            .map_err(|e| anyhow!("Invalid pattern: {}", e))?;
+        let filter = args["glob"].as_str().map(Glob::new).transpose()?;
+        let root = scoped_path(&self.workspace, args["path"].as_str())?;
+        let limit = limit(&args, DEFAULT_MAX_MATCHES, MAX_MATCHES);
+        let workspace = self.workspace.clone();
+
+        let result = tokio::task::spawn_blocking(move || grep(&workspace, &root, &regex, filter.as_ref(), limit)).await??;
+        Ok(serde_json::to_string_pretty(&result)?)
+    }
+}
+
+pub struct GlobTool {
+    workspace: PathBuf,
+}
+
+impl GlobTool {
+    pub fn new(workspace: impl Into<PathBuf>) -> Self {
+        Self { workspace: workspace.into() }
+    }
+}
+
+#[async_trait]
+impl Tool for GlobTool {
+    fn name(&self) -> &str {
+        "glob"
+    }
+
+    fn description(&self) -> &str {
+        "Find files in the workspace by glob (`**/*.rs`, `src/{lib,main}.rs`). Respects .gitignore; newest first."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "Glob pattern; without a `/` it matches file names at any depth"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Directory to search, relative to the workspace (default: whole workspace)"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum paths to return (default 200, max 1000)"
+                }
+            },
+            "required": ["pattern"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let glob = Glob::new(args["pattern"].as_str().ok_or_else(|| anyhow!("Missing 'pattern' argument"))?)?;
+        let root = scoped_path(&self.workspace, args["path"].as_str())?;
+        let limit = limit(&args, DEFAULT_MAX_FILES, MAX_FILES);
+        let workspace = self.workspace.clone();
+
+        let result = tokio::task::spawn_blocking(move || glob_files(&workspace, &root, &glob, limit)).await??;
+        Ok(serde_json::to_string_pretty(&result)?)
+    }
+}
+
+fn limit(args: &Value, default: usize, max: usize) -> usize {
+    args["max_results"].as_u64().map_or(default, |n| (n as usize).clamp(1, max))
+}
+
+/// Resolve a model-supplied path inside the workspace.
+fn scoped_path(workspace: &Path, path: Option<&str>) -> Result<PathBuf> {
+    let Some(path) = path.filter(|p| !p.is_empty() && *p != ".") else {
+        return Ok(workspace.to_path_buf());
+    };
+    let relative = Path::new(path);
+    if relative.components().any(|c| matches!(c, Component::ParentDir | Component::RootDir | Component::Prefix(_))) {
+        bail!("Security violation: path must be relative to the workspace without '..'");
+    }
+    Ok(workspace.join(relative))
+}
+
+// ---------------------------------------------------------------------------
+// Search
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+pub struct GrepResult {
+    pub matches: Vec<GrepMatch>,
+    pub total_matches: usize,
+    pub files_matched: usize,
+    pub files_searched: usize,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GlobResult {
+    pub files: Vec<String>,
+    pub total: usize,
+    pub truncated: bool,
+}
+
+struct FileHits {
+    path: String,
+    score: f64,
+    matches: Vec<GrepMatch>,
+}
+
+pub fn grep(workspace: &Path, root: &Path, regex: &Regex, filter: Option<&Glob>, limit: usize) -> Result<GrepResult> {
+    let word = word_regex(regex);
+    let mut files = Vec::new();
+    let mut searched = 0;
+    let walk_truncated = walk(workspace, root, &mut |rel, path| {
+        if filter.is_some_and(|g| !g.is_match(rel)) {
+            return;
+        }
+        let Some(text) = read_text(path) else { return };
+        searched += 1;
+
+        let mut hits = FileHits { path: rel.to_string(), score: 0.0, matches: Vec::new() };
+        for (i, line) in text.lines().enumerate() {
+            let Some(m) = regex.find(line) else { continue };
+            hits.score += 1.0;
+            if word.as_ref().is_some_and(|w| w.is_match(line)) {
+                hits.score += 2.0;
+            }
+            if is_definition(&line[..m.start()]) {
+                hits.score += 5.0;
+            }
+            hits.matches.push(GrepMatch {
+                path: hits.path.clone(),
+                line: i + 1,
+                column: line[..m.start()].chars().count() + 1,
+                snippet: snippet(line),
+            });
+        }
+        if !hits.matches.is_empty() {
+            // Many hits in one file shouldn't drown out a single definition elsewhere.
+            hits.score = hits.score.sqrt() * 4.0 - hits.path.matches('/').count() as f64 * 0.5;
+            files.push(hits);
+        }
+    })?;
+
+    files.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    let total_matches = files.iter().map(|f| f.matches.len()).sum();
+    let files_matched = files.len();
+    let matches: Vec<GrepMatch> = files.into_iter().flat_map(|f| f.matches).take(limit).collect();
+    Ok(GrepResult {
+        truncated: walk_truncated || matches.len() < total_matches,
+        matches,
+        total_matches,
+        files_matched,
+        files_searched: searched,
+    })
+}
+
+pub fn glob_files(workspace: &Path, root: &Path, glob: &Glob, limit: usize) -> Result<GlobResult> {
+    let mut found: Vec<(SystemTime, String)> = Vec::new();
+    let walk_truncated = walk(workspace, root, &mut |rel, path| {
+        if glob.is_match(rel) {
+            let modified = std::fs::metadata(path).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+            found.push((modified, rel.to_string()));
+        }
+    })?;
+    found.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    let total = found.len();
+    let files: Vec<String> = found.into_iter().take(limit).map(|(_, path)| path).collect();
+    Ok(GlobResult { truncated: walk_truncated || files.len() < total, files, total })
+}
+
+/// `regex` wrapped in word boundaries, for ranking whole-word hits.
+fn word_regex(regex: &Regex) -> Option<Regex> {
+    Regex::new(&format!(r"\b(?:{})\b", regex.as_str())).ok()
+}
+
+/// Whether the text before a match ends in a definition keyword, i.e. the
+/// match is the name being defined (`pub fn <match>`, `class <match>`).
+fn is_definition(before: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "fn", "struct", "enum", "trait", "impl", "type", "mod", "const", "static", "class", "interface", "def",
+        "function", "func", "let", "var", "macro_rules!",
+    ];
+    before.ends_with(char::is_whitespace)
+        && before.split_whitespace().next_back().is_some_and(|token| KEYWORDS.contains(&token))
+}
+
+fn snippet(line: &str) -> String {
+    let line = line.trim();
+    match line.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line.to_string(),
+    }
+}
+
+/// File contents, or None for unreadable, oversized or binary files.
+fn read_text(path: &Path) -> Option<String> {
+    if std::fs::metadata(path).ok()?.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    if bytes.iter().take(8192).any(|&b| b == 0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+// ---------------------------------------------------------------------------
+// Walking with .gitignore
+// ---------------------------------------------------------------------------
+
+/// Visit every non-ignored file under `root` with its workspace-relative
+/// path. Returns true when the walk stopped at `MAX_WALK_FILES`.
+fn walk(workspace: &Path, root: &Path, visit: &mut dyn FnMut(&str, &Path)) -> Result<bool> {
+    let meta = std::fs::metadata(root).map_err(|e| anyhow!("Cannot search {}: {}", root.display(), e))?;
+    if meta.is_file() {
+        visit(&relative(workspace, root), root);
+        return Ok(false);
+    }
+    // Hidden files are searched like any other; only `.git` itself is
+    // skipped. Rules from `.gitignore` files above the search root apply
+    // too, whether or not the workspace is a git checkout.
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .parents(true)
+        .require_git(false)
+        .git_global(false)
+        .follow_links(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+
+    let mut count = 0;
+    for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        if count >= MAX_WALK_FILES {
+            return Ok(true);
+        }
+        count += 1;
+        visit(&relative(workspace, entry.path()), entry.path());
+    }
+    Ok(false)
+}
+
+fn relative(workspace: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(workspace).unwrap_or(path);
+    rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+// ---------------------------------------------------------------------------
+// Glob patterns
+// ---------------------------------------------------------------------------
+
+/// A glob over `/`-separated relative paths: `*`, `?`, `[...]`, `**` and
+/// `{a,b}`. A pattern without `/` matches the file name at any depth.
+pub struct Glob {
+    matcher: GlobMatcher,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim_start_matches("./");
+        let pattern = if pattern.contains('/') { pattern.to_string() } else { format!("**/{}", pattern) };
+        let glob = GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| anyhow!("Invalid glob '{}': {}", pattern, e.kind()))?;
+        Ok(Self { matcher: glob.compile_matcher() })
+    }
+
+    pub fn is_match(&self, path: &str) -> bool {
+        self.matcher.is_match(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("clawforge-search-{}", uuid::Uuid::new_v4()));
+        for (path, content) in [
+            (".gitignore", "target/\n*.log\n!keep.log\n"),
+            ("src/lib.rs", "mod parser;\npub fn parse_config() {}\n"),
+            ("src/deep/nested/usage.rs", "let a = parse_config();\nlet b = parse_config();\nparse_config_x();\n"),
+            ("target/debug/out.rs", "pub fn parse_config() {}\n"),
+            ("debug.log", "parse_config\n"),
+            ("keep.log", "parse_config\n"),
+            ("docs/.gitignore", "draft.md\n"),
+            ("docs/draft.md", "parse_config\n"),
+        ] {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn grep_ranks_definitions_and_respects_gitignore() {
+        let dir = workspace();
+        let regex = Regex::new("parse_config").unwrap();
+        let result = grep(&dir, &dir, &regex, None, 100).unwrap();
+        let files: Vec<_> = result.matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(files[0], "src/lib.rs");
+        assert!(files.contains(&"keep.log"));
+        assert!(!files.iter().any(|f| f.starts_with("target/") || *f == "debug.log" || *f == "docs/draft.md"));
+        assert_eq!((result.matches[0].line, result.matches[0].column), (2, 8));
+
+        let limited = grep(&dir, &dir, &regex, Some(&Glob::new("*.rs").unwrap()), 2).unwrap();
+        assert_eq!(limited.matches.len(), 2);
+        assert!(limited.truncated && limited.total_matches == 4);
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn glob_patterns() {
+        let dir = workspace();
+        let found = glob_files(&dir, &dir, &Glob::new("src/**/*.rs").unwrap(), 10).unwrap();
+        let mut files = found.files.clone();
+        files.sort();
+        assert_eq!(files, vec!["src/deep/nested/usage.rs", "src/lib.rs"]);
+
+        assert!(Glob::new("*.{rs,toml}").unwrap().is_match("a/b/Cargo.toml"));
+        assert!(Glob::new("src/[!x]*.rs").unwrap().is_match("src/lib.rs"));
+        assert!(!Glob::new("src/*.rs").unwrap().is_match("src/deep/usage.rs"));
+        assert!(Glob::new("src/[a-").is_err());
+        assert!(scoped_path(&dir, Some("../etc")).is_err());
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,475 @@
+//! Web search backends behind one `SearchProvider` trait.
+//!
+//! Brave, SerpAPI, Tavily, SearXNG and the keyless DuckDuckGo Instant Answer
+//! API all return the same normalized [`SearchHit`]s. A [`SearchRouter`]
+//! tries an agent's providers in order, skipping any that are over their
+//! rate limit or failing, so a quota running out degrades to the next backend:
+//!
+//! ```yaml
+//! webSearch:
+//!   providers:
+//!     - provider: brave
+//!       api_key: ${BRAVE_API_KEY}
+//!       rate_limit: { max_calls: 50, window_secs: 60 }
+//!     - provider: searxng
+//!       base_url: https://searx.example.org
+//! ```
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use clawforge_core::Tool;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::connectors::RateLimit;
+use crate::web::{strip_html, SearchHit, WebSearchInput, WebSearchOutput};
+
+const DEFAULT_RESULTS: usize = 5;
+const MAX_RESULTS: usize = 20;
+
+/// One web search backend.
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    /// Provider id as used in `provider:` (e.g. "brave").
+    fn name(&self) -> &str;
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>>;
+}
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum SearchBackend {
+    Brave { api_key: String },
+    Serpapi {
+        api_key: String,
+        /// SerpAPI engine (default `google`).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        engine: Option<String>,
+    },
+    Tavily { api_key: String },
+    Searxng { base_url: String },
+    Duckduckgo,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchProviderConfig {
+    #[serde(flatten)]
+    pub backend: SearchBackend,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl SearchBackend {
+    fn build(&self, http: Client) -> Arc<dyn SearchProvider> {
+        match self {
+            SearchBackend::Brave { api_key } => Arc::new(BraveSearch { api_key: api_key.clone(), http }),
+            SearchBackend::Serpapi { api_key, engine } => Arc::new(SerpApiSearch {
+                api_key: api_key.clone(),
+                engine: engine.clone().unwrap_or_else(|| "google".to_string()),
+                http,
+            }),
+            SearchBackend::Tavily { api_key } => Arc::new(TavilySearch { api_key: api_key.clone(), http }),
+            SearchBackend::Searxng { base_url } => Arc::new(SearxngSearch { base_url: base_url.trim_end_matches('/').to_string(), http }),
+            SearchBackend::Duckduckgo => Arc::new(DuckDuckGoSearch { http }),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Router
+// ---------------------------------------------------------------------------
+
+struct Limited {
+    provider: Arc<dyn SearchProvider>,
+    limit: Option<RateLimit>,
+    calls: Mutex<VecDeque<Instant>>,
+}
+
+impl Limited {
+    /// Take a slot in the provider's window, or `false` when it is used up.
+    async fn try_acquire(&self) -> bool {
+        let Some(limit) = self.limit else { return true };
+        let mut calls = self.calls.lock().await;
+        let now = Instant::now();
+        let window = Duration::from_secs(limit.window_secs);
+        while calls.front().is_some_and(|t| now.duration_since(*t) >= window) {
+            calls.pop_front();
+        }
+        if calls.len() >= limit.max_calls as usize {
+            return false;
+        }
+        calls.push_back(now);
+        true
+    }
+}
+
+/// An agent's providers in fallback order.
+pub struct SearchRouter {
+    providers: Vec<Limited>,
+}
+
+impl SearchRouter {
+    pub fn new() -> Self {
+        Self { providers: Vec::new() }
+    }
+
+    pub fn from_configs(configs: &[SearchProviderConfig]) -> Result<Self> {
+        let http = Client::builder()
+            .timeout(Duration::from_secs(15))
+            .user_agent(concat!("clawforge/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        let mut router = Self::new();
+        for config in configs {
+            router = router.with_provider(config.backend.build(http.clone()), config.rate_limit);
+        }
+        Ok(router)
+    }
+
+    /// Build from the raw `webSearch.providers` entries of an agent's config.
+    pub fn from_json(providers: &[Value]) -> Result<Self> {
+        let configs = providers
+            .iter()
+            .map(|v| serde_json::from_value(v.clone()).context("Invalid webSearch provider"))
+            .collect::<Result<Vec<SearchProviderConfig>>>()?;
+        Self::from_configs(&configs)
+    }
+
+    /// Append a provider to the fallback order.
+    pub fn with_provider(mut self, provider: Arc<dyn SearchProvider>, limit: Option<RateLimit>) -> Self {
+        self.providers.push(Limited { provider, limit, calls: Mutex::new(VecDeque::new()) });
+        self
+    }
+
+    pub fn provider_names(&self) -> Vec<&str> {
+        self.providers.iter().map(|p| p.provider.name()).collect()
+    }
+
+    /// Ask each provider in turn until one answers.
+    pub async fn search(&self, input: WebSearchInput) -> Result<WebSearchOutput> {
+        let limit = input.max_results.unwrap_or(DEFAULT_RESULTS).clamp(1, MAX_RESULTS);
+        let mut failures = Vec::new();
+        for entry in &self.providers {
+            let name = entry.provider.name();
+            if !entry.try_acquire().await {
+                debug!(provider = %name, "Search provider rate limited; trying next");
+                failures.push(format!("{}: rate limited", name));
+                continue;
+            }
+            match entry.provider.search(&input.query, limit).await {
+                Ok(hits) => {
+                    return Ok(WebSearchOutput { query: input.query, provider: name.to_string(), hits: normalize(hits, limit) });
+                }
+                Err(e) => {
+                    warn!(provider = %name, error = %e, "Search provider failed; trying next");
+                    failures.push(format!("{}: {:#}", name, e));
+                }
+            }
+        }
+        if failures.is_empty() {
+            bail!("No search providers configured");
+        }
+        bail!("All search providers failed ({})", failures.join("; "))
+    }
+}
+
+impl Default for SearchRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-agent routers with a shared default.
+pub struct SearchProviders {
+    default: Arc<SearchRouter>,
+    by_agent: HashMap<String, Arc<SearchRouter>>,
+}
+
+impl SearchProviders {
+    pub fn new(default: SearchRouter) -> Self {
+        Self { default: Arc::new(default), by_agent: HashMap::new() }
+    }
+
+    /// Give `agent` its own provider order (and rate limits).
+    pub fn with_agent(mut self, agent: impl Into<String>, router: SearchRouter) -> Self {
+        self.by_agent.insert(agent.into(), Arc::new(router));
+        self
+    }
+
+    pub fn for_agent(&self, agent: &str) -> Arc<SearchRouter> {
+        self.by_agent.get(agent).unwrap_or(&self.default).clone()
+    }
+}
+
+/// Trim, strip markup, drop empty/duplicate URLs and cap the count.
+fn normalize(hits: Vec<SearchHit>, limit: usize) -> Vec<SearchHit> {
+    let mut seen = HashSet::new();
+    hits.into_iter()
+        .filter_map(|hit| {
+            let url = hit.url.trim().to_string();
+            if url.is_empty() || !seen.insert(url.trim_end_matches('/').to_string()) {
+                return None;
+            }
+            Some(SearchHit { title: strip_html(&hit.title), url, snippet: strip_html(&hit.snippet) })
+        })
+        .take(limit)
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Providers
+// ---------------------------------------------------------------------------
+
+async fn get_json(request: reqwest::RequestBuilder, what: &str) -> Result<Value> {
+    let response = request.send().await.with_context(|| format!("Request to {} failed", what))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("{} returned {}", what, status);
+    }
+    response.json().await.with_context(|| format!("Invalid JSON from {}", what))
+}
+
+/// Map `results[*]` with the given title/url/snippet field names.
+fn hits_from(results: Option<&Value>, title: &str, url: &str, snippet: &str) -> Vec<SearchHit> {
+    results
+        .and_then(|r| r.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .map(|item| SearchHit {
+                    title: item[title].as_str().unwrap_or_default().to_string(),
+                    url: item[url].as_str().unwrap_or_default().to_string(),
+                    snippet: item[snippet].as_str().unwrap_or_default().to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub struct BraveSearch {
+    api_key: String,
+    http: Client,
+}
+
+#[async_trait]
+impl SearchProvider for BraveSearch {
+    fn name(&self) -> &str {
+        "brave"
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let request = self
+            .http
+            .get("https://api.search.brave.com/res/v1/web/search")
+            .header("X-Subscription-Token", &self.api_key)
+            .header("Accept", "application/json")
+            .query(&[("q", query), ("count", &limit.to_string())]);
+        let body = get_json(request, "Brave Search").await?;
+        Ok(hits_from(body.pointer("/web/results"), "title", "url", "description"))
+    }
+}
+
+pub struct SerpApiSearch {
+    api_key: String,
+    engine: String,
+    http: Client,
+}
+
+#[async_trait]
+impl SearchProvider for SerpApiSearch {
+    fn name(&self) -> &str {
+        "serpapi"
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let request = self.http.get("https://serpapi.com/search.json").query(&[
+            ("q", query),
+            ("engine", &self.engine),
+            ("num", &limit.to_string()),
+            ("api_key", &self.api_key),
+        ]);
+        let body = get_json(request, "SerpAPI").await?;
+        if let Some(error) = body["error"].as_str() {
+            bail!("SerpAPI: {}", error);
+        }
+        Ok(hits_from(body.get("organic_results"), "title", "link", "snippet"))
+    }
+}
+
+pub struct TavilySearch {
+    api_key: String,
+    http: Client,
+}
+
+#[async_trait]
+impl SearchProvider for TavilySearch {
+    fn name(&self) -> &str {
+        "tavily"
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let request = self
+            .http
+            .post("https://api.tavily.com/search")
+            .bearer_auth(&self.api_key)
+            .json(&json!({ "query": query, "max_results": limit }));
+        let body = get_json(request, "Tavily").await?;
+        Ok(hits_from(body.get("results"), "title", "url", "content"))
+    }
+}
+
+pub struct SearxngSearch {
+    base_url: String,
+    http: Client,
+}
+
+#[async_trait]
+impl SearchProvider for SearxngSearch {
+    fn name(&self) -> &str {
+        "searxng"
+    }
+
+    async fn search(&self, query: &str, _limit: usize) -> Result<Vec<SearchHit>> {
+        let request = self.http.get(format!("{}/search", self.base_url)).query(&[("q", query), ("format", "json")]);
+        let body = get_json(request, "SearXNG").await?;
+        Ok(hits_from(body.get("results"), "title", "url", "content"))
+    }
+}
+
+/// DuckDuckGo Instant Answer API — no key, but related topics rather than a full index.
+pub struct DuckDuckGoSearch {
+    http: Client,
+}
+
+impl DuckDuckGoSearch {
+    pub fn new(http: Client) -> Self {
+        Self { http }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for DuckDuckGoSearch {
+    fn name(&self) -> &str {
+        "duckduckgo"
+    }
+
+    async fn search(&self, query: &str, _limit: usize) -> Result<Vec<SearchHit>> {
+        let request = self
+            .http
+            .get("https://api.duckduckgo.com/")
+            .header("User-Agent", "ClawForge/1.0")
+            .query(&[("q", query), ("format", "json"), ("no_redirect", "1"), ("no_html", "1")]);
+        let body = get_json(request, "DuckDuckGo").await?;
+        Ok(body["RelatedTopics"]
+            .as_array()
+            .map(|topics| {
+                topics
+                    .iter()
+                    .filter_map(|t| {
+                        Some(SearchHit {
+                            title: query.to_string(),
+                            url: t["FirstURL"].as_str()?.to_string(),
+                            snippet: t["Text"].as_str().unwrap_or_default().to_string(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tool
+// ---------------------------------------------------------------------------
+
+/// `web_search`: query the agent's search providers.
+pub struct WebSearchTool {
+    router: Arc<SearchRouter>,
+}
+
+impl WebSearchTool {
+    pub fn new(router: Arc<SearchRouter>) -> Self {
+        Self { router }
+    }
+}
+
+#[async_trait]
+impl Tool for WebSearchTool {
+    fn name(&self) -> &str {
+        "web_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the web. Returns titles, URLs and snippets; use web_fetch to read a result."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "max_results": { "type": "integer", "description": "1-20, default 5" }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let query = args["query"].as_str().ok_or_else(|| anyhow!("Missing 'query' argument"))?;
+        let input = WebSearchInput { query: query.to_string(), max_results: args["max_results"].as_u64().map(|n| n as usize) };
+        Ok(serde_json::to_string(&self.router.search(input).await?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str, Result<Vec<(&'static str, &'static str)>, &'static str>);
+
+    #[async_trait]
+    impl SearchProvider for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        async fn search(&self, _query: &str, _limit: usize) -> Result<Vec<SearchHit>> {
+            match &self.1 {
+                Ok(hits) => Ok(hits
+                    .iter()
+                    .map(|(url, snippet)| SearchHit { title: "<b>T</b>".into(), url: url.to_string(), snippet: snippet.to_string() })
+                    .collect()),
+                Err(e) => bail!("{}", e),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn router_falls_back_and_normalizes() {
+        let router = SearchRouter::new()
+            .with_provider(Arc::new(Fixed("brave", Err("quota exceeded"))), None)
+            .with_provider(
+                Arc::new(Fixed("searxng", Ok(vec![("https://a.example/", "one <em>hit</em>"), ("https://a.example", "dup"), ("", "no url")]))),
+                Some(RateLimit { max_calls: 1, window_secs: 60 }),
+            );
+        let query = || WebSearchInput { query: "rust".into(), max_results: None };
+
+        let output = router.search(query()).await.unwrap();
+        assert_eq!(output.provider, "searxng");
+        assert_eq!(output.hits.len(), 1);
+        assert_eq!((output.hits[0].title.as_str(), output.hits[0].snippet.as_str()), ("T", "one hit"));
+
+        let err = router.search(query()).await.unwrap_err().to_string();
+        assert!(err.contains("brave: quota exceeded") && err.contains("searxng: rate limited"), "{}", err);
+    }
+}
@@ -0,0 +1,148 @@
+//! `secret_lease` and `secret_exec`: use credentials without seeing them.
+//!
+//! An agent leases a configured secret by name and gets an opaque lease id.
+//! It passes the id to `secret_exec`, which runs one command in the
+//! session's sandbox with the value injected as an env var and masked in the
+//! output. Both tools are built per call, bound to the calling session and
+//! agent, so a lease cannot be redeemed by anyone else.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use clawforge_core::{Capabilities, Tool};
+use clawforge_sandbox::{DockerSandboxConfig, SandboxRegistry, SecretBroker};
+use serde_json::{json, Value};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Leases a named secret for the calling session and agent.
+pub struct SecretLeaseTool {
+    broker: Arc<SecretBroker>,
+    session: String,
+    agent: Option<String>,
+    description: String,
+}
+
+impl SecretLeaseTool {
+    pub fn new(broker: Arc<SecretBroker>, session: impl Into<String>, agent: Option<String>) -> Self {
+        let description = format!(
+            "Lease a secret for one `secret_exec` command. Returns a lease id and the env var the value will be in; the value itself is never shown. Available secrets: {}.",
+            broker.names().join(", ")
+        );
+        Self { broker, session: session.into(), agent, description }
+    }
+}
+
+#[async_trait]
+impl Tool for SecretLeaseTool {
+    fn name(&self) -> &str {
+        "secret_lease"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "secret": { "type": "string" } },
+            "required": ["secret"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let secret = args["secret"].as_str().ok_or_else(|| anyhow!("Missing 'secret' argument"))?;
+        let lease = self.broker.lease(secret, &self.session, self.agent.as_deref())?;
+        Ok(serde_json::to_string(&lease)?)
+    }
+}
+
+/// Runs one command in the session's sandbox with leased secrets injected.
+pub struct SecretExecTool {
+    sandboxes: Arc<SandboxRegistry>,
+    broker: Arc<SecretBroker>,
+    session: String,
+    agent: Option<String>,
+    driver: String,
+    config: DockerSandboxConfig,
+    capabilities: Capabilities,
+}
+
+impl SecretExecTool {
+    pub fn new(
+        sandboxes: Arc<SandboxRegistry>,
+        broker: Arc<SecretBroker>,
+        session: impl Into<String>,
+        agent: Option<String>,
+        capabilities: Capabilities,
+    ) -> Self {
+        Self {
+            sandboxes,
+            broker,
+            session: session.into(),
+            agent,
+            driver: "docker".to_string(),
+            config: DockerSandboxConfig::default(),
+            capabilities,
+        }
+    }
+
+    /// Sandbox driver and settings used when the session has no sandbox yet.
+    pub fn with_sandbox(mut self, driver: impl Into<String>, config: DockerSandboxConfig) -> Self {
+        self.driver = driver.into();
+        self.config = config;
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for SecretExecTool {
+    fn name(&self) -> &str {
+        "secret_exec"
+    }
+
+    fn description(&self) -> &str {
+        "Run one command (argv array) in the session's sandbox with the secrets from `secret_lease` ids in `leases` set as env vars. Each lease works once; secret values are masked in the output."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": { "type": "array", "items": { "type": "string" } },
+                "leases": { "type": "array", "items": { "type": "string" } },
+                "timeout_secs": { "type": "integer" }
+            },
+            "required": ["command", "leases"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let command: Vec<String> = serde_json::from_value(args["command"].clone()).context("'command' must be an array of strings")?;
+        let leases: Vec<String> = serde_json::from_value(args["leases"].clone()).context("'leases' must be an array of lease ids")?;
+        if command.is_empty() {
+            bail!("'command' is empty");
+        }
+        if !self.sandboxes.has_sandbox(&self.session).await {
+            self.sandboxes
+                .start_agent_session(&self.session, &self.driver, &self.config, &self.capabilities)
+                .await
+                .context("Starting the sandbox")?;
+        }
+        let argv: Vec<&str> = command.iter().map(String::as_str).collect();
+        let timeout = args["timeout_secs"].as_u64().unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let result = self
+            .sandboxes
+            .exec_with_secrets(&self.session, self.agent.as_deref(), &argv, &self.broker, &leases, Some(timeout))
+            .await?;
+        Ok(json!({
+            "exit_code": result.exit_code,
+            "stdout": result.stdout,
+            "stderr": result.stderr,
+            "timed_out": result.timed_out,
+        })
+        .to_string())
+    }
+}
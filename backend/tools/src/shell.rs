@@ -1,9 +1,22 @@
 use async_trait::async_trait;
 use clawforge_core::Tool;
+use clawforge_sandbox::NativeSandbox;
 use serde_json::Value;
 use std::process::Command;
 
-pub struct ShellTool;
+#[derive(Default)]
+pub struct ShellTool {
+    /// Run commands under the OS sandbox (seatbelt / AppContainer) where
+    /// Docker isn't available.
+    native: Option<NativeSandbox>,
+}
+
+impl ShellTool {
+    pub fn with_native_sandbox(mut self, sandbox: NativeSandbox) -> Self {
+        self.native = Some(sandbox);
+        self
+    }
+}
 
 #[async_trait]
 impl Tool for ShellTool {
@@ -36,6 +49,11 @@ impl Tool for ShellTool {
         let command = args["command"].as_str().ok_or_else(|| anyhow::anyhow!("Missing 'command' argument"))?;
         let use_docker = args["use_docker"].as_bool().unwrap_or(false);
 
+        if let Some(sandbox) = self.native.as_ref().filter(|_| !use_docker) {
+            let result = sandbox.exec(command, None).await?;
+            return Ok(format!("Stdout:\n{}\nStderr:\n{}", result.stdout, result.stderr));
+        }
+
         let output = if use_docker {
             Command::new("docker")
                 .arg("run")
@@ -1,10 +1,21 @@
 /// Skill install pipeline — download and install skills from URLs or GitHub.
 ///
 /// Mirrors `src/agents/skills-install.ts` + `skills-install-download.ts` from OpenClaw.
+///
+/// Skills are fetched into a staging directory and their detached signature is
+/// checked against the trusted publisher keys before they are moved into place.
+/// Unsigned or untrusted skills are refused unless `allow_unsigned` is set; a
+/// signature that does not match the files is always refused. Each install's
+/// verification result is recorded in `registry.json` in the skills directory.
 use anyhow::{bail, Result};
+use clawforge_security::{verify_skill_dir, SignatureStatus, SkillVerification, TrustedKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
+const REGISTRY_FILE: &str = "registry.json";
+
 // ---------------------------------------------------------------------------
 // Skill source
 // ---------------------------------------------------------------------------
@@ -28,6 +39,17 @@ pub struct SkillInstallResult {
     pub name: String,
     pub path: PathBuf,
     pub source: String,
+    pub verification: SkillVerification,
+}
+
+/// One installed skill as recorded in the skills registry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillRecord {
+    pub name: String,
+    pub source: String,
+    pub installed_at: i64,
+    pub verification: SkillVerification,
 }
 
 // ---------------------------------------------------------------------------
@@ -37,39 +59,119 @@ pub struct SkillInstallResult {
 pub struct SkillInstaller {
     /// Root directory where skills are installed per-agent.
     pub skills_dir: PathBuf,
+    trusted_keys: Vec<TrustedKey>,
+    allow_unsigned: bool,
 }
 
 impl SkillInstaller {
     pub fn new(skills_dir: impl Into<PathBuf>) -> Self {
-        Self { skills_dir: skills_dir.into() }
+        Self { skills_dir: skills_dir.into(), trusted_keys: Vec::new(), allow_unsigned: false }
+    }
+
+    pub fn with_trusted_keys(mut self, keys: Vec<TrustedKey>) -> Self {
+        self.trusted_keys = keys;
+        self
+    }
+
+    /// Install skills that are unsigned or signed by an unknown key (`--allow-unsigned`).
+    pub fn with_allow_unsigned(mut self, allow: bool) -> Self {
+        self.allow_unsigned = allow;
+        self
     }
 
     pub async fn install(&self, name: &str, source: SkillSource) -> Result<SkillInstallResult> {
         let dest = self.skills_dir.join(name);
-        tokio::fs::create_dir_all(&dest).await?;
+        let staging = self.skills_dir.join(format!(".staging-{}", name));
+        if staging.exists() {
+            tokio::fs::remove_dir_all(&staging).await?;
+        }
+        tokio::fs::create_dir_all(&staging).await?;
+        let fetched = self.fetch(name, &source, &staging).await;
+        let verification = match fetched.and_then(|_| verify_skill_dir(&staging, &self.trusted_keys)) {
+            Ok(v) => v,
+            Err(e) => {
+                tokio::fs::remove_dir_all(&staging).await.ok();
+                return Err(e);
+            }
+        };
+
+        let refusal = match &verification.status {
+            SignatureStatus::Verified { .. } => None,
+            SignatureStatus::Invalid { reason } => Some(format!("invalid signature ({})", reason)),
+            _ if self.allow_unsigned => None,
+            SignatureStatus::Unsigned => Some("skill is unsigned; pass --allow-unsigned to install it anyway".to_string()),
+            SignatureStatus::UntrustedKey { key_id } => Some(format!(
+                "skill is signed by untrusted key '{}'; add it to security.trustedSkillKeys or pass --allow-unsigned",
+                key_id
+            )),
+        };
+        if let Some(reason) = refusal {
+            tokio::fs::remove_dir_all(&staging).await.ok();
+            bail!("Refusing to install skill '{}': {}", name, reason);
+        }
+        if !verification.status.is_verified() {
+            warn!("[SkillInstall] Installing unverified skill '{}' ({:?})", name, verification.status);
+        }
+
+        if dest.exists() {
+            tokio::fs::remove_dir_all(&dest).await?;
+        }
+        tokio::fs::rename(&staging, &dest).await?;
+        self.record(SkillRecord {
+            name: name.to_string(),
+            source: format!("{:?}", source),
+            installed_at: chrono::Utc::now().timestamp(),
+            verification: verification.clone(),
+        })
+        .await?;
+
+        info!("[SkillInstall] Installed skill '{}' → {}", name, dest.display());
+        Ok(SkillInstallResult {
+            name: name.to_string(),
+            path: dest,
+            source: format!("{:?}", source),
+            verification,
+        })
+    }
+
+    async fn fetch(&self, name: &str, source: &SkillSource, dest: &Path) -> Result<()> {
 
         match &source {
             SkillSource::Url(url) => {
                 info!("[SkillInstall] Downloading skill '{}' from {}", name, url);
-                self.download_and_extract(url, &dest).await?;
+                self.download_and_extract(url, dest).await?;
             }
             SkillSource::GitHub { repo, subdir } => {
                 let url = format!("https://github.com/{}/archive/refs/heads/main.tar.gz", repo);
                 info!("[SkillInstall] Cloning GitHub skill '{}' from {}", name, url);
-                self.download_and_extract(&url, &dest).await?;
+                self.download_and_extract(&url, dest).await?;
             }
             SkillSource::Local(path) => {
                 info!("[SkillInstall] Copying local skill '{}' from {}", name, path.display());
-                copy_dir_all(path, &dest).await?;
+                copy_dir_all(path, dest).await?;
             }
         }
+        Ok(())
+    }
 
-        info!("[SkillInstall] Installed skill '{}' → {}", name, dest.display());
-        Ok(SkillInstallResult {
-            name: name.to_string(),
-            path: dest,
-            source: format!("{:?}", source),
-        })
+    /// Installed skills and their verification results, keyed by name.
+    pub async fn registry(&self) -> Result<BTreeMap<String, SkillRecord>> {
+        match tokio::fs::read_to_string(self.skills_dir.join(REGISTRY_FILE)).await {
+            Ok(raw) => Ok(serde_json::from_str(&raw)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn write_registry(&self, registry: &BTreeMap<String, SkillRecord>) -> Result<()> {
+        tokio::fs::write(self.skills_dir.join(REGISTRY_FILE), serde_json::to_string_pretty(registry)?).await?;
+        Ok(())
+    }
+
+    async fn record(&self, record: SkillRecord) -> Result<()> {
+        let mut registry = self.registry().await?;
+        registry.insert(record.name.clone(), record);
+        self.write_registry(&registry).await
     }
 
     async fn download_and_extract(&self, url: &str, dest: &Path) -> Result<()> {
@@ -102,6 +204,10 @@ impl SkillInstaller {
             tokio::fs::remove_dir_all(&path).await?;
             info!("[SkillInstall] Uninstalled skill '{}'", name);
         }
+        let mut registry = self.registry().await?;
+        if registry.remove(name).is_some() {
+            self.write_registry(&registry).await?;
+        }
         Ok(())
     }
 
@@ -110,7 +216,8 @@ impl SkillInstaller {
         if !self.skills_dir.exists() { return Ok(names); }
         let mut dir = tokio::fs::read_dir(&self.skills_dir).await?;
         while let Some(entry) = dir.next_entry().await? {
-            if entry.file_type().await?.is_dir() {
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if entry.file_type().await?.is_dir() && !hidden {
                 names.push(entry.file_name().to_string_lossy().to_string());
             }
         }
@@ -139,3 +246,32 @@ fn uuid_v4() -> u64 {
         .unwrap_or_default()
         .subsec_nanos() as u64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn unsigned_skills_need_explicit_opt_in() {
+        let root = std::env::temp_dir().join(format!("cf-skill-install-{}", uuid::Uuid::new_v4()));
+        let src = root.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("SKILL.md"), "# Notes").unwrap();
+        let skills = root.join("skills");
+
+        let strict = SkillInstaller::new(&skills);
+        let err = strict.install("notes", SkillSource::Local(src.clone())).await.unwrap_err();
+        assert!(err.to_string().contains("--allow-unsigned"));
+        assert!(strict.list_installed().await.unwrap().is_empty());
+
+        let lenient = SkillInstaller::new(&skills).with_allow_unsigned(true);
+        lenient.install("notes", SkillSource::Local(src)).await.unwrap();
+        let registry = lenient.registry().await.unwrap();
+        assert_eq!(registry["notes"].verification.status, SignatureStatus::Unsigned);
+        assert_eq!(lenient.list_installed().await.unwrap(), vec!["notes".to_string()]);
+
+        lenient.uninstall("notes").await.unwrap();
+        assert!(lenient.registry().await.unwrap().is_empty());
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,192 @@
+//! Inter-run agent state — `state_get` / `state_set` give an agent a small
+//! durable key-value store that survives between runs.
+//!
+//! Meant for cursors and bookkeeping (last processed item id, last seen
+//! timestamp), not for recall: it is exact-key lookup, scoped to one agent,
+//! and separate from vector memory. Both tools are bound to the calling agent
+//! so a run can never read or write another agent's state.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use clawforge_core::Tool;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+pub use clawforge_core::agent_state::{validate_entry, StateBackend, StateEntry, MAX_KEY_LEN, MAX_VALUE_BYTES};
+
+/// In-memory state backend for testing.
+#[derive(Default)]
+pub struct InMemoryStateBackend {
+    entries: Arc<RwLock<HashMap<(Uuid, String), StateEntry>>>,
+}
+
+impl InMemoryStateBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StateBackend for InMemoryStateBackend {
+    async fn get(&self, agent_id: Uuid, key: &str) -> Result<Option<StateEntry>> {
+        Ok(self.entries.read().await.get(&(agent_id, key.to_string())).cloned())
+    }
+
+    async fn set(&self, agent_id: Uuid, key: &str, value: Value) -> Result<StateEntry> {
+        validate_entry(key, &value)?;
+        let entry = StateEntry { key: key.to_string(), value, updated_at: Utc::now() };
+        self.entries.write().await.insert((agent_id, key.to_string()), entry.clone());
+        Ok(entry)
+    }
+
+    async fn delete(&self, agent_id: Uuid, key: &str) -> Result<bool> {
+        Ok(self.entries.write().await.remove(&(agent_id, key.to_string())).is_some())
+    }
+
+    async fn list(&self, agent_id: Uuid) -> Result<Vec<StateEntry>> {
+        let mut entries: Vec<StateEntry> = self
+            .entries
+            .read()
+            .await
+            .iter()
+            .filter(|((owner, _), _)| *owner == agent_id)
+            .map(|(_, entry)| entry.clone())
+            .collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(entries)
+    }
+}
+
+/// `state_get` tool bound to the calling agent.
+pub struct StateGetTool {
+    agent_id: Uuid,
+    backend: Arc<dyn StateBackend>,
+}
+
+impl StateGetTool {
+    pub fn new(agent_id: Uuid, backend: Arc<dyn StateBackend>) -> Self {
+        Self { agent_id, backend }
+    }
+}
+
+#[async_trait]
+impl Tool for StateGetTool {
+    fn name(&self) -> &str {
+        "state_get"
+    }
+
+    fn description(&self) -> &str {
+        "Read a value you saved with state_set in this or an earlier run. Omit the key to get every saved value."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "key": {
+                    "type": "string",
+                    "description": "Key to read (e.g. \"last_processed_id\")"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let output = match args.get("key").and_then(Value::as_str) {
+            Some(key) => {
+                let entry = self.backend.get(self.agent_id, key).await?;
+                serde_json::json!({ "key": key, "value": entry.map(|e| e.value) })
+            }
+            None => {
+                let entries = self.backend.list(self.agent_id).await?;
+                let values: serde_json::Map<String, Value> = entries.into_iter().map(|e| (e.key, e.value)).collect();
+                serde_json::json!({ "values": values })
+            }
+        };
+        Ok(output.to_string())
+    }
+}
+
+/// `state_set` tool bound to the calling agent.
+pub struct StateSetTool {
+    agent_id: Uuid,
+    backend: Arc<dyn StateBackend>,
+}
+
+impl StateSetTool {
+    pub fn new(agent_id: Uuid, backend: Arc<dyn StateBackend>) -> Self {
+        Self { agent_id, backend }
+    }
+}
+
+#[async_trait]
+impl Tool for StateSetTool {
+    fn name(&self) -> &str {
+        "state_set"
+    }
+
+    fn description(&self) -> &str {
+        "Save a JSON value under a key so later runs can read it with state_get. Setting a key to null deletes it."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "key": {
+                    "type": "string",
+                    "description": "Key to write"
+                },
+                "value": {
+                    "description": "Any JSON value; null deletes the key"
+                }
+            },
+            "required": ["key", "value"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let key = args["key"].as_str().ok_or_else(|| anyhow!("Missing 'key' argument"))?;
+        let value = args.get("value").cloned().ok_or_else(|| anyhow!("Missing 'value' argument"))?;
+        let output = if value.is_null() {
+            let deleted = self.backend.delete(self.agent_id, key).await?;
+            serde_json::json!({ "ok": true, "key": key, "deleted": deleted })
+        } else {
+            let entry = self.backend.set(self.agent_id, key, value).await?;
+            serde_json::json!({ "ok": true, "key": key, "updatedAt": entry.updated_at })
+        };
+        Ok(output.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn state_is_scoped_to_the_bound_agent() {
+        let backend: Arc<dyn StateBackend> = Arc::new(InMemoryStateBackend::new());
+        let (me, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let set = StateSetTool::new(me, backend.clone());
+        let get = StateGetTool::new(me, backend.clone());
+
+        set.execute(serde_json::json!({ "key": "cursor", "value": 42 })).await.unwrap();
+        let out: Value = serde_json::from_str(&get.execute(serde_json::json!({ "key": "cursor" })).await.unwrap()).unwrap();
+        assert_eq!(out["value"], 42);
+        assert!(backend.list(other).await.unwrap().is_empty());
+
+        let all: Value = serde_json::from_str(&get.execute(serde_json::json!({})).await.unwrap()).unwrap();
+        assert_eq!(all["values"], serde_json::json!({ "cursor": 42 }));
+
+        set.execute(serde_json::json!({ "key": "cursor", "value": null })).await.unwrap();
+        assert!(backend.get(me, "cursor").await.unwrap().is_none());
+
+        let huge = "x".repeat(MAX_VALUE_BYTES);
+        assert!(set.execute(serde_json::json!({ "key": "blob", "value": huge })).await.is_err());
+        assert!(set.execute(serde_json::json!({ "key": " ", "value": 1 })).await.is_err());
+    }
+}
@@ -0,0 +1,326 @@
+/// TTS response cache — reuses audio for repeated (provider, voice, text).
+///
+/// Confirmations and digest intros are synthesized over and over; caching them
+/// saves a provider round-trip and its cost. The cache is bounded by total
+/// audio bytes and evicts the least recently used entries first. With a
+/// directory attached, clips are also kept on disk by content hash so
+/// greetings survive a restart without being billed again.
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+use crate::engine::{AudioFormat, TtsProvider, TtsRequest};
+
+/// Default cache budget: 64 MiB of audio.
+pub const DEFAULT_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Default budget of the on-disk tier: 512 MiB of audio.
+pub const DEFAULT_MAX_DISK_BYTES: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TtsCacheKey {
+    pub provider: String,
+    pub voice: String,
+    pub format: &'static str,
+    /// Speed in thousandths, so the key stays hashable.
+    pub speed_milli: i32,
+    /// `text` is SSML; the same words as plain text sound different.
+    pub ssml: bool,
+    /// SHA-256 of the text.
+    pub text_hash: String,
+}
+
+impl TtsCacheKey {
+    pub fn new(provider: &str, req: &TtsRequest) -> Self {
+        Self {
+            provider: provider.to_string(),
+            voice: req.voice.clone().unwrap_or_default(),
+            format: req.format.openai_str(),
+            speed_milli: (req.speed * 1000.0).round() as i32,
+            ssml: req.ssml,
+            text_hash: hex::encode(Sha256::digest(req.text.as_bytes())),
+        }
+    }
+
+    /// File name of the clip in the on-disk tier: a hash over the whole key.
+    pub fn file_name(&self) -> String {
+        let key = format!(
+            "{}\0{}\0{}\0{}\0{}\0{}",
+            self.provider, self.voice, self.format, self.speed_milli, self.ssml, self.text_hash
+        );
+        format!("{}.{}", hex::encode(Sha256::digest(key.as_bytes())), self.format)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TtsCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+struct Entry {
+    audio: Bytes,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<TtsCacheKey, Entry>,
+    bytes: usize,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// Size-bounded LRU cache of synthesized audio.
+pub struct TtsCache {
+    inner: Mutex<Inner>,
+    max_bytes: usize,
+    dir: Option<PathBuf>,
+}
+
+impl Default for TtsCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_BYTES)
+    }
+}
+
+impl TtsCache {
+    pub fn new(max_bytes: usize) -> Self {
+        Self { inner: Mutex::new(Inner::default()), max_bytes, dir: None }
+    }
+
+    /// Also keep clips in `dir`, trimmed to `DEFAULT_MAX_DISK_BYTES` by
+    /// removing the oldest files.
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        if let Err(e) = prune_dir(&dir, DEFAULT_MAX_DISK_BYTES) {
+            warn!("[TTS/Cache] Failed to prune {}: {}", dir.display(), e);
+        }
+        self.dir = Some(dir);
+        self
+    }
+
+    /// `$CLAWFORGE_TTS_CACHE`, else `<cache dir>/clawforge/tts`.
+    pub fn default_dir() -> PathBuf {
+        std::env::var("CLAWFORGE_TTS_CACHE").map(PathBuf::from).unwrap_or_else(|_| {
+            dirs::cache_dir().unwrap_or_else(std::env::temp_dir).join("clawforge").join("tts")
+        })
+    }
+
+    pub fn get(&self, key: &TtsCacheKey) -> Option<Bytes> {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.clock += 1;
+            let now = inner.clock;
+            if let Some(entry) = inner.entries.get_mut(key) {
+                entry.last_used = now;
+                let audio = entry.audio.clone();
+                inner.hits += 1;
+                return Some(audio);
+            }
+        }
+        let on_disk = self.dir.as_ref().and_then(|dir| std::fs::read(dir.join(key.file_name())).ok());
+        let mut inner = self.inner.lock().unwrap();
+        match on_disk {
+            Some(audio) => {
+                inner.hits += 1;
+                drop(inner);
+                let audio = Bytes::from(audio);
+                self.insert_memory(key.clone(), audio.clone());
+                Some(audio)
+            }
+            None => {
+                inner.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store `audio`, evicting least recently used entries to stay within budget.
+    /// Clips larger than the whole budget are not cached.
+    pub fn insert(&self, key: TtsCacheKey, audio: Bytes) {
+        if audio.len() > self.max_bytes {
+            return;
+        }
+        if let Some(dir) = &self.dir {
+            let path = dir.join(key.file_name());
+            if let Err(e) = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&path, &audio)) {
+                warn!("[TTS/Cache] Failed to write {}: {}", path.display(), e);
+            }
+        }
+        self.insert_memory(key, audio);
+    }
+
+    fn insert_memory(&self, key: TtsCacheKey, audio: Bytes) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.clock += 1;
+        let last_used = inner.clock;
+        inner.bytes += audio.len();
+        if let Some(old) = inner.entries.insert(key, Entry { audio, last_used }) {
+            inner.bytes -= old.audio.len();
+        }
+        while inner.bytes > self.max_bytes {
+            let Some(oldest) = inner.entries.iter().min_by_key(|(_, e)| e.last_used).map(|(k, _)| k.clone()) else {
+                break;
+            };
+            if let Some(evicted) = inner.entries.remove(&oldest) {
+                inner.bytes -= evicted.audio.len();
+            }
+        }
+    }
+
+    /// Drop every cached clip, on disk included.
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.entries.clear();
+        inner.bytes = 0;
+        if let Some(dir) = &self.dir {
+            if let Err(e) = prune_dir(dir, 0) {
+                warn!("[TTS/Cache] Failed to clear {}: {}", dir.display(), e);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> TtsCacheStats {
+        let inner = self.inner.lock().unwrap();
+        TtsCacheStats { hits: inner.hits, misses: inner.misses, entries: inner.entries.len(), bytes: inner.bytes }
+    }
+}
+
+/// Remove the oldest files in `dir` until the rest fit in `max_bytes`.
+fn prune_dir(dir: &Path, max_bytes: u64) -> std::io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let mut files: Vec<(std::time::SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((meta.modified().ok()?, meta.len(), entry.path()))
+        })
+        .collect();
+    files.sort_by_key(|(modified, ..)| std::cmp::Reverse(*modified));
+    let mut total = 0;
+    for (_, len, path) in files {
+        total += len;
+        if total > max_bytes {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Wraps a provider so identical requests are served from a shared cache.
+pub struct CachedTts {
+    inner: Arc<dyn TtsProvider>,
+    provider: String,
+    cache: Arc<TtsCache>,
+}
+
+impl CachedTts {
+    /// `provider` namespaces the cache so two providers never share audio.
+    pub fn new(inner: Arc<dyn TtsProvider>, provider: impl Into<String>, cache: Arc<TtsCache>) -> Self {
+        Self { inner, provider: provider.into(), cache }
+    }
+}
+
+#[async_trait]
+impl TtsProvider for CachedTts {
+    async fn synthesize(&self, req: TtsRequest) -> Result<Bytes> {
+        if req.bypass_cache {
+            return self.inner.synthesize(req).await;
+        }
+        let key = TtsCacheKey::new(&self.provider, &req);
+        if let Some(audio) = self.cache.get(&key) {
+            debug!("[TTS/Cache] Hit for {} ({} bytes)", self.provider, audio.len());
+            return Ok(audio);
+        }
+        let audio = self.inner.synthesize(req).await?;
+        self.cache.insert(key, audio.clone());
+        Ok(audio)
+    }
+
+    fn supports_ssml(&self) -> bool {
+        self.inner.supports_ssml()
+    }
+
+    fn output_format(&self, requested: &AudioFormat) -> AudioFormat {
+        self.inner.output_format(requested)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counting(AtomicUsize);
+
+    #[async_trait]
+    impl TtsProvider for Counting {
+        async fn synthesize(&self, req: TtsRequest) -> Result<Bytes> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(Bytes::from(req.text.into_bytes()))
+        }
+    }
+
+    fn request(text: &str) -> TtsRequest {
+        TtsRequest { text: text.to_string(), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn repeats_are_served_from_cache_unless_bypassed() {
+        let inner = Arc::new(Counting(AtomicUsize::new(0)));
+        let tts = CachedTts::new(inner.clone(), "openai", Arc::new(TtsCache::default()));
+
+        tts.synthesize(request("Done!")).await.unwrap();
+        tts.synthesize(request("Done!")).await.unwrap();
+        assert_eq!(inner.0.load(Ordering::SeqCst), 1);
+
+        tts.synthesize(TtsRequest { bypass_cache: true, ..request("Done!") }).await.unwrap();
+        tts.synthesize(TtsRequest { voice: Some("alloy".into()), ..request("Done!") }).await.unwrap();
+        assert_eq!(inner.0.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn disk_tier_survives_a_new_cache() {
+        let dir = std::env::temp_dir().join(format!("clawforge-tts-cache-{}", uuid::Uuid::new_v4()));
+        let inner = Arc::new(Counting(AtomicUsize::new(0)));
+
+        let first = CachedTts::new(inner.clone(), "openai", Arc::new(TtsCache::default().with_dir(&dir)));
+        first.synthesize(request("Good morning!")).await.unwrap();
+        let restarted = CachedTts::new(inner.clone(), "openai", Arc::new(TtsCache::default().with_dir(&dir)));
+        assert_eq!(restarted.synthesize(request("Good morning!")).await.unwrap(), Bytes::from_static(b"Good morning!"));
+        assert_eq!(inner.0.load(Ordering::SeqCst), 1);
+
+        restarted.synthesize(TtsRequest { ssml: true, ..request("Good morning!") }).await.unwrap();
+        assert_eq!(inner.0.load(Ordering::SeqCst), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn evicts_least_recently_used_within_budget() {
+        let cache = TtsCache::new(10);
+        let key = |text: &str| TtsCacheKey::new("p", &request(text));
+        cache.insert(key("a"), Bytes::from_static(b"aaaa"));
+        cache.insert(key("b"), Bytes::from_static(b"bbbb"));
+        assert!(cache.get(&key("a")).is_some());
+        cache.insert(key("c"), Bytes::from_static(b"cccc"));
+
+        assert!(cache.get(&key("b")).is_none());
+        assert!(cache.get(&key("a")).is_some());
+        assert_eq!(cache.stats().bytes, 8);
+        cache.insert(key("huge"), Bytes::from(vec![0u8; 11]));
+        assert_eq!(cache.stats().entries, 2);
+    }
+}
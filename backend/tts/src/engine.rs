@@ -1,10 +1,24 @@
 /// TTS provider trait and implementations (ElevenLabs + OpenAI TTS).
-use anyhow::Result;
+///
+/// Replies can also be spoken while they are still being generated:
+/// `synthesize_stream` cuts the token stream into sentences and sends each
+/// sentence's audio on as soon as it is ready.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
 use bytes::Bytes;
+use markdown::{IrParser, SpeechRenderer};
 use reqwest::Client;
 use serde::Serialize;
-use tracing::info;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+use crate::cache::{CachedTts, TtsCache};
+use crate::piper::{PiperTts, PiperVoices};
 
 // ---------------------------------------------------------------------------
 // Trait
@@ -19,6 +33,7 @@ pub enum AudioFormat {
     Aac,
     Flac,
     Pcm,
+    Wav,
 }
 
 impl AudioFormat {
@@ -29,6 +44,7 @@ impl AudioFormat {
             Self::Aac => "audio/aac",
             Self::Flac => "audio/flac",
             Self::Pcm => "audio/pcm",
+            Self::Wav => "audio/wav",
         }
     }
 
@@ -39,6 +55,7 @@ impl AudioFormat {
             Self::Aac => "aac",
             Self::Flac => "flac",
             Self::Pcm => "pcm",
+            Self::Wav => "wav",
         }
     }
 }
@@ -50,6 +67,10 @@ pub struct TtsRequest {
     pub voice: Option<String>,
     pub format: AudioFormat,
     pub speed: f32,
+    /// Skip the response cache, for dynamic content that will not repeat.
+    pub bypass_cache: bool,
+    /// `text` is an SSML document rather than plain text.
+    pub ssml: bool,
 }
 
 impl Default for TtsRequest {
@@ -59,14 +80,52 @@ impl Default for TtsRequest {
             voice: None,
             format: AudioFormat::Mp3,
             speed: 1.0,
+            bypass_cache: false,
+            ssml: false,
         }
     }
 }
 
+impl TtsRequest {
+    /// A request speaking a Markdown reply: SSML when `provider` parses it,
+    /// otherwise speech text, so the voice never reads out markup.
+    pub fn from_markdown(markdown: &str, provider: &dyn TtsProvider) -> Self {
+        let nodes = IrParser::parse(markdown);
+        let ssml = provider.supports_ssml();
+        let text = if ssml { SpeechRenderer::to_ssml(&nodes) } else { SpeechRenderer::to_speech_text(&nodes) };
+        Self { text, ssml, ..Default::default() }
+    }
+}
+
 /// Returns raw audio bytes.
 #[async_trait]
 pub trait TtsProvider: Send + Sync {
     async fn synthesize(&self, req: TtsRequest) -> Result<Bytes>;
+
+    /// Whether requests may carry SSML instead of plain text.
+    fn supports_ssml(&self) -> bool {
+        false
+    }
+
+    /// The format audio actually comes back in when `requested` is asked for.
+    fn output_format(&self, requested: &AudioFormat) -> AudioFormat {
+        requested.clone()
+    }
+
+    /// Synthesize `req`, sending audio to `sink` as the provider produces
+    /// it. The default sends the whole clip once it is done.
+    async fn synthesize_streaming(&self, req: TtsRequest, sink: &mpsc::Sender<Bytes>) -> Result<()> {
+        let audio = self.synthesize(req).await?;
+        sink.send(audio).await.map_err(|_| anyhow!("Audio sink closed"))
+    }
+}
+
+/// Forward a streamed HTTP body to `sink` chunk by chunk.
+async fn forward_body(mut response: reqwest::Response, sink: &mpsc::Sender<Bytes>) -> Result<()> {
+    while let Some(chunk) = response.chunk().await? {
+        sink.send(chunk).await.map_err(|_| anyhow!("Audio sink closed"))?;
+    }
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
@@ -110,9 +169,8 @@ struct OpenAiTtsBody {
     speed: f32,
 }
 
-#[async_trait]
-impl TtsProvider for OpenAiTts {
-    async fn synthesize(&self, req: TtsRequest) -> Result<Bytes> {
+impl OpenAiTts {
+    async fn send(&self, req: TtsRequest) -> Result<reqwest::Response> {
         let body = OpenAiTtsBody {
             model: self.model.clone(),
             input: req.text,
@@ -121,17 +179,26 @@ impl TtsProvider for OpenAiTts {
             speed: req.speed,
         };
         info!("[TTS/OpenAI] Synthesizing with model={}", body.model);
-        let bytes = self
+        let response = self
             .client
             .post("https://api.openai.com/v1/audio/speech")
             .bearer_auth(&self.api_key)
             .json(&body)
             .send()
             .await?
-            .error_for_status()?
-            .bytes()
-            .await?;
-        Ok(bytes)
+            .error_for_status()?;
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl TtsProvider for OpenAiTts {
+    async fn synthesize(&self, req: TtsRequest) -> Result<Bytes> {
+        Ok(self.send(req).await?.bytes().await?)
+    }
+
+    async fn synthesize_streaming(&self, req: TtsRequest, sink: &mpsc::Sender<Bytes>) -> Result<()> {
+        forward_body(self.send(req).await?, sink).await
     }
 }
 
@@ -169,9 +236,8 @@ struct ElevenLabsVoiceSettings {
     speed: f32,
 }
 
-#[async_trait]
-impl TtsProvider for ElevenLabsTts {
-    async fn synthesize(&self, req: TtsRequest) -> Result<Bytes> {
+impl ElevenLabsTts {
+    async fn send(&self, req: TtsRequest) -> Result<reqwest::Response> {
         let voice_id = req.voice.as_deref().unwrap_or(&self.default_voice_id);
         let url = format!(
             "https://api.elevenlabs.io/v1/text-to-speech/{}/stream",
@@ -187,17 +253,31 @@ impl TtsProvider for ElevenLabsTts {
             },
         };
         info!("[TTS/ElevenLabs] Synthesizing voice_id={}", voice_id);
-        let bytes = self
+        let response = self
             .client
             .post(&url)
+            .query(&[("enable_ssml_parsing", req.ssml)])
             .header("xi-api-key", &self.api_key)
             .json(&body)
             .send()
             .await?
-            .error_for_status()?
-            .bytes()
-            .await?;
-        Ok(bytes)
+            .error_for_status()?;
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl TtsProvider for ElevenLabsTts {
+    async fn synthesize(&self, req: TtsRequest) -> Result<Bytes> {
+        Ok(self.send(req).await?.bytes().await?)
+    }
+
+    async fn synthesize_streaming(&self, req: TtsRequest, sink: &mpsc::Sender<Bytes>) -> Result<()> {
+        forward_body(self.send(req).await?, sink).await
+    }
+
+    fn supports_ssml(&self) -> bool {
+        true
     }
 }
 
@@ -208,6 +288,35 @@ impl TtsProvider for ElevenLabsTts {
 pub enum TtsProviderKind {
     OpenAi { api_key: String },
     ElevenLabs { api_key: String, voice_id: Option<String> },
+    /// Offline synthesis with the Piper CLI; needs no key.
+    Piper { binary: PathBuf, voices_dir: Option<PathBuf>, voice: Option<String> },
+}
+
+impl TtsProviderKind {
+    /// Build from `talk` settings: provider name plus its credentials.
+    pub fn from_settings(
+        provider: &str,
+        api_key: Option<String>,
+        voice: Option<String>,
+        binary: Option<PathBuf>,
+        voices_dir: Option<PathBuf>,
+    ) -> Result<Self> {
+        Ok(match provider {
+            "openai" => TtsProviderKind::OpenAi {
+                api_key: api_key.context("TTS provider 'openai' needs an API key")?,
+            },
+            "elevenlabs" => TtsProviderKind::ElevenLabs {
+                api_key: api_key.context("TTS provider 'elevenlabs' needs an API key")?,
+                voice_id: voice,
+            },
+            "piper" | "local" => TtsProviderKind::Piper {
+                binary: binary.unwrap_or_else(|| PathBuf::from("piper")),
+                voices_dir,
+                voice,
+            },
+            other => bail!("Unknown TTS provider '{}'", other),
+        })
+    }
 }
 
 pub fn create_tts(kind: TtsProviderKind) -> Box<dyn TtsProvider> {
@@ -216,5 +325,197 @@ pub fn create_tts(kind: TtsProviderKind) -> Box<dyn TtsProvider> {
         TtsProviderKind::ElevenLabs { api_key, voice_id } => {
             Box::new(ElevenLabsTts::new(api_key, voice_id))
         }
+        TtsProviderKind::Piper { binary, voices_dir, voice } => {
+            let voices = voices_dir.map(PiperVoices::new).unwrap_or_else(PiperVoices::default_location);
+            let tts = PiperTts::new(binary, voices);
+            Box::new(match voice {
+                Some(v) => tts.with_voice(v),
+                None => tts,
+            })
+        }
+    }
+}
+
+/// Named TTS providers with a default, so voice preferences can pick one
+/// per user. Providers registered on a cached registry share its cache.
+#[derive(Clone, Default)]
+pub struct TtsRegistry {
+    providers: HashMap<String, Arc<dyn TtsProvider>>,
+    default: Option<String>,
+    cache: Option<Arc<TtsCache>>,
+}
+
+impl TtsRegistry {
+    pub fn new() -> Self {
+        Self::default()
     }
+
+    /// A registry whose providers are all wrapped in `CachedTts` over `cache`.
+    pub fn cached(cache: Arc<TtsCache>) -> Self {
+        Self { cache: Some(cache), ..Self::default() }
+    }
+
+    /// Register a provider under `name`. The first one becomes the default.
+    pub fn register(&mut self, name: &str, provider: Arc<dyn TtsProvider>) {
+        let name = name.to_lowercase();
+        let provider: Arc<dyn TtsProvider> = match &self.cache {
+            Some(cache) => Arc::new(CachedTts::new(provider, name.clone(), cache.clone())),
+            None => provider,
+        };
+        self.default.get_or_insert_with(|| name.clone());
+        self.providers.insert(name, provider);
+    }
+
+    pub fn set_default(&mut self, name: &str) -> Result<()> {
+        if !self.providers.contains_key(name) {
+            bail!("TTS provider '{}' is not registered", name);
+        }
+        self.default = Some(name.to_string());
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn TtsProvider>> {
+        self.providers.get(name).cloned()
+    }
+
+    pub fn default_provider(&self) -> Option<Arc<dyn TtsProvider>> {
+        self.default.as_deref().and_then(|name| self.get(name))
+    }
+
+    /// `name` when it is registered, else the default provider.
+    pub fn get_or_default(&self, name: Option<&str>) -> Result<Arc<dyn TtsProvider>> {
+        if let Some(provider) = name.and_then(|name| self.get(name)) {
+            return Ok(provider);
+        }
+        if let Some(name) = name {
+            debug!("[TTS] Provider '{}' is not registered, using the default", name);
+        }
+        self.default_provider().context("No TTS provider configured")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Streaming
+// ---------------------------------------------------------------------------
+
+/// Abbreviations whose trailing period does not end a sentence.
+const NON_TERMINAL: &[&str] = &["e.g.", "i.e.", "etc.", "vs.", "mr.", "mrs.", "ms.", "dr.", "st.", "approx.", "no."];
+
+/// Sentences shorter than this are merged into the next one, so a reply
+/// doesn't start with a string of one-word requests.
+const MIN_SENTENCE_CHARS: usize = 12;
+
+/// Cuts streamed Markdown into sentences that can be spoken on their own.
+/// Fenced code blocks are kept whole so the speech renderer can summarize
+/// them instead of reading half a block.
+#[derive(Debug, Default)]
+pub struct SentenceSplitter {
+    buffer: String,
+}
+
+impl SentenceSplitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a token delta and return every sentence it completed.
+    pub fn push(&mut self, delta: &str) -> Vec<String> {
+        self.buffer.push_str(delta);
+        let mut sentences = Vec::new();
+        while let Some(end) = self.sentence_end() {
+            let sentence: String = self.buffer.drain(..end).collect();
+            let sentence = sentence.trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence.to_string());
+            }
+        }
+        sentences
+    }
+
+    /// Whatever is left once the reply is complete.
+    pub fn finish(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = rest.trim();
+        (!rest.is_empty()).then(|| rest.to_string())
+    }
+
+    /// Byte offset just past the first complete sentence, if there is one.
+    fn sentence_end(&self) -> Option<usize> {
+        let text = &self.buffer;
+        let mut in_fence = false;
+        let mut line_start = 0;
+        for (i, c) in text.char_indices() {
+            if c == '\n' {
+                let line = text[line_start..i].trim();
+                if line.starts_with("```") {
+                    in_fence = !in_fence;
+                    if !in_fence {
+                        return Some(i + 1);
+                    }
+                } else if !in_fence && line.is_empty() && text[..line_start].trim().chars().count() >= MIN_SENTENCE_CHARS {
+                    // A blank line closes a paragraph, heading or list.
+                    return Some(i + 1);
+                }
+                line_start = i + 1;
+                continue;
+            }
+            if in_fence || !matches!(c, '.' | '!' | '?') {
+                continue;
+            }
+            // Terminal punctuation counts once whitespace follows it, so
+            // "3.14" and "example.com" are never split.
+            let next = text[i + 1..].chars().next();
+            if !next.is_some_and(char::is_whitespace) {
+                continue;
+            }
+            let candidate = &text[..i + 1];
+            let last_word = candidate.rsplit(char::is_whitespace).next().unwrap_or_default().to_lowercase();
+            if c == '.' && NON_TERMINAL.contains(&last_word.as_str()) {
+                continue;
+            }
+            if candidate.trim().chars().count() < MIN_SENTENCE_CHARS {
+                continue;
+            }
+            return Some(i + 1);
+        }
+        None
+    }
+}
+
+/// Speak a reply while it is being generated. Text deltas from `text` are
+/// cut into sentences and each is synthesized with `template`'s voice,
+/// format and speed; audio goes to `audio` in order as it arrives. The
+/// returned task ends once `text` closes and the last sentence is spoken,
+/// and aborting it (barge-in) stops synthesis mid-sentence.
+pub fn synthesize_stream(
+    provider: Arc<dyn TtsProvider>,
+    template: TtsRequest,
+    mut text: mpsc::Receiver<String>,
+    audio: mpsc::Sender<Bytes>,
+) -> JoinHandle<Result<()>> {
+    tokio::spawn(async move {
+        let mut splitter = SentenceSplitter::new();
+        let speak = |sentence: String| {
+            let req = TtsRequest {
+                voice: template.voice.clone(),
+                format: template.format.clone(),
+                speed: template.speed,
+                bypass_cache: template.bypass_cache,
+                ..TtsRequest::from_markdown(&sentence, provider.as_ref())
+            };
+            debug!("[TTS/Stream] Speaking {} chars", sentence.len());
+            let provider = provider.clone();
+            let audio = audio.clone();
+            async move { provider.synthesize_streaming(req, &audio).await }
+        };
+        while let Some(delta) = text.recv().await {
+            for sentence in splitter.push(&delta) {
+                speak(sentence).await?;
+            }
+        }
+        if let Some(rest) = splitter.finish() {
+            speak(rest).await?;
+        }
+        Ok(())
+    })
 }
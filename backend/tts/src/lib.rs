@@ -1,9 +1,22 @@
+pub mod cache;
 pub mod deepgram;
 pub mod engine;
+pub mod piper;
+pub mod stt;
 pub mod tool;
 pub mod voice_call;
+pub mod voice_session;
+pub mod wake_word;
 
+pub use cache::{CachedTts, TtsCache, TtsCacheKey, TtsCacheStats, DEFAULT_MAX_DISK_BYTES};
 pub use deepgram::{DeepgramTts, DeepgramTtsRequest, DeepgramTtsResponse, DeepgramVoice};
-pub use engine::{create_tts, AudioFormat, ElevenLabsTts, OpenAiTts, TtsProvider, TtsProviderKind, TtsRequest};
-pub use tool::{run_tts_tool, TtsToolInput, TtsToolOutput};
-pub use voice_call::{initiate_call, CallStatus, VoiceCall};
+pub use engine::{create_tts, synthesize_stream, AudioFormat, ElevenLabsTts, OpenAiTts, SentenceSplitter, TtsProvider, TtsProviderKind, TtsRegistry, TtsRequest};
+pub use piper::{voice_url, PiperTts, PiperVoice, PiperVoices, DEFAULT_PIPER_VOICE};
+pub use stt::{create_stt, pcm_to_wav, DeepgramStt, LocalWhisperStt, OpenAiWhisperStt, SttProvider, SttProviderKind, SttRegistry, SttRequest, SttTranscript};
+pub use tool::{run_tts_tool, TtsCaller, TtsToolInput, TtsToolOutput};
+pub use voice_call::{
+    mulaw_decode, mulaw_encode, resample, stream_twiml, CallAgent, CallBridge, CallDirection, CallFrame, CallInfo, CallStatus, MediaProtocol,
+    PhoneCallTool, TwilioCalls, VoiceCall,
+};
+pub use voice_session::{Vad, VadConfig, VadEvent, VoiceEvent, VoiceSession, VoiceState};
+pub use wake_word::{ProcessWakeWord, WakeGate, WakeGateOutput, WakeWordDetector, WakeWordEngineKind};
@@ -0,0 +1,279 @@
+//! Piper TTS provider for clawforge-tts.
+//!
+//! Offline speech synthesis by shelling out to the `piper` binary, so
+//! air-gapped deployments can speak without any cloud key. Voice models
+//! (`<voice>.onnx` plus its `<voice>.onnx.json` config) live under the data
+//! dir and are installed from the upstream voice repository or imported from
+//! a local file.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tracing::info;
+
+use crate::engine::{AudioFormat, TtsProvider, TtsRequest};
+use crate::stt::pcm_to_wav;
+
+pub const DEFAULT_PIPER_VOICE: &str = "en_US-lessac-medium";
+
+/// Upstream repository of Piper voice models.
+const VOICE_REPO: &str = "https://huggingface.co/rhasspy/piper-voices/resolve/main";
+
+/// An installed voice model.
+#[derive(Debug, Clone, Serialize)]
+pub struct PiperVoice {
+    pub name: String,
+    pub model: PathBuf,
+    pub sample_rate: u32,
+    pub language: Option<String>,
+}
+
+impl PiperVoice {
+    /// Read the voice's `.onnx.json` config next to `model`.
+    fn load(name: &str, model: PathBuf) -> Result<Self> {
+        let config_path = config_path(&model);
+        let raw = std::fs::read_to_string(&config_path)
+            .with_context(|| format!("Voice config {} is missing", config_path.display()))?;
+        let config: serde_json::Value = serde_json::from_str(&raw)
+            .with_context(|| format!("Voice config {} is not valid JSON", config_path.display()))?;
+        Ok(Self {
+            name: name.to_string(),
+            sample_rate: config["audio"]["sample_rate"].as_u64().unwrap_or(22_050) as u32,
+            language: config["language"]["code"].as_str().map(str::to_string),
+            model,
+        })
+    }
+}
+
+fn config_path(model: &Path) -> PathBuf {
+    let mut path = model.as_os_str().to_owned();
+    path.push(".json");
+    PathBuf::from(path)
+}
+
+/// Voice names become file names; keep them to the upstream alphabet.
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        bail!("Invalid voice name '{}'", name);
+    }
+    Ok(())
+}
+
+/// Download URL of an upstream voice, e.g. `en_US-lessac-medium` lives at
+/// `en/en_US/lessac/medium/en_US-lessac-medium.onnx`.
+pub fn voice_url(name: &str) -> Result<String> {
+    validate_name(name)?;
+    let mut parts = name.splitn(3, '-');
+    let (Some(locale), Some(speaker), Some(quality)) = (parts.next(), parts.next(), parts.next()) else {
+        bail!("Voice '{}' is not of the form <locale>-<speaker>-<quality>", name);
+    };
+    let family = locale.split('_').next().unwrap_or(locale);
+    Ok(format!("{}/{}/{}/{}/{}/{}.onnx", VOICE_REPO, family, locale, speaker, quality, name))
+}
+
+/// Voice models on disk, one `.onnx` + `.onnx.json` pair per voice.
+#[derive(Debug, Clone)]
+pub struct PiperVoices {
+    dir: PathBuf,
+}
+
+impl PiperVoices {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `$CLAWFORGE_TTS_VOICES`, else `<data dir>/clawforge/tts-voices`.
+    pub fn default_location() -> Self {
+        let dir = std::env::var("CLAWFORGE_TTS_VOICES").map(PathBuf::from).unwrap_or_else(|_| {
+            dirs::data_dir().unwrap_or_else(std::env::temp_dir).join("clawforge").join("tts-voices")
+        });
+        Self::new(dir)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn model_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.onnx", name))
+    }
+
+    /// Installed voices, sorted by name.
+    pub fn list(&self) -> Result<Vec<PiperVoice>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", self.dir.display())),
+        };
+        let mut voices = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()).and_then(|n| n.strip_suffix(".onnx")) else {
+                continue;
+            };
+            if let Ok(voice) = PiperVoice::load(name, path.clone()) {
+                voices.push(voice);
+            }
+        }
+        voices.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(voices)
+    }
+
+    pub fn get(&self, name: &str) -> Result<PiperVoice> {
+        validate_name(name)?;
+        let model = self.model_path(name);
+        if !model.exists() {
+            bail!("Piper voice '{}' is not installed; run `clawforge tts install {}`", name, name);
+        }
+        PiperVoice::load(name, model)
+    }
+
+    /// Download a voice and its config from the upstream repository.
+    pub async fn install(&self, name: &str) -> Result<PiperVoice> {
+        let url = voice_url(name)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let client = reqwest::Client::new();
+        for (from, to) in [(url.clone(), self.model_path(name)), (format!("{}.json", url), config_path(&self.model_path(name)))] {
+            info!("[TTS/Piper] Downloading {}", from);
+            let bytes = client.get(&from).send().await?.error_for_status()?.bytes().await?;
+            tokio::fs::write(&to, &bytes).await.with_context(|| format!("Failed to write {}", to.display()))?;
+        }
+        self.get(name)
+    }
+
+    /// Copy a voice model, and the `.onnx.json` beside it, into the voice
+    /// dir. For machines that can't reach the voice repository.
+    pub async fn import(&self, model: &Path, name: Option<&str>) -> Result<PiperVoice> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => model
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(".onnx"))
+                .with_context(|| format!("{} is not an .onnx model", model.display()))?
+                .to_string(),
+        };
+        validate_name(&name)?;
+        let source_config = config_path(model);
+        if !source_config.exists() {
+            bail!("Voice config {} is missing; Piper needs it next to the model", source_config.display());
+        }
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::copy(model, self.model_path(&name)).await?;
+        tokio::fs::copy(&source_config, config_path(&self.model_path(&name))).await?;
+        self.get(&name)
+    }
+
+    pub async fn remove(&self, name: &str) -> Result<()> {
+        let voice = self.get(name)?;
+        tokio::fs::remove_file(config_path(&voice.model)).await.ok();
+        tokio::fs::remove_file(&voice.model).await?;
+        Ok(())
+    }
+}
+
+/// Local synthesis with the Piper CLI. Piper produces 16-bit mono PCM:
+/// `AudioFormat::Pcm` requests get it raw, everything else gets WAV.
+pub struct PiperTts {
+    binary: PathBuf,
+    voices: PiperVoices,
+    default_voice: String,
+}
+
+impl PiperTts {
+    pub fn new(binary: impl Into<PathBuf>, voices: PiperVoices) -> Self {
+        Self { binary: binary.into(), voices, default_voice: DEFAULT_PIPER_VOICE.to_string() }
+    }
+
+    pub fn with_voice(mut self, voice: impl Into<String>) -> Self {
+        self.default_voice = voice.into();
+        self
+    }
+}
+
+#[async_trait]
+impl TtsProvider for PiperTts {
+    async fn synthesize(&self, req: TtsRequest) -> Result<Bytes> {
+        let voice = self.voices.get(req.voice.as_deref().unwrap_or(&self.default_voice))?;
+        info!("[TTS/Piper] Synthesizing with voice={}", voice.name);
+
+        let mut child = tokio::process::Command::new(&self.binary)
+            .arg("--model")
+            .arg(&voice.model)
+            .arg("--output-raw")
+            .arg("--length_scale")
+            .arg(format!("{:.3}", 1.0 / req.speed.max(0.1)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to run {}", self.binary.display()))?;
+        let mut stdin = child.stdin.take().context("piper stdin unavailable")?;
+        stdin.write_all(req.text.as_bytes()).await?;
+        drop(stdin);
+
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            bail!("piper exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr));
+        }
+        Ok(match self.output_format(&req.format) {
+            AudioFormat::Pcm => Bytes::from(output.stdout),
+            _ => pcm_to_wav(&output.stdout, voice.sample_rate, 1),
+        })
+    }
+
+    fn output_format(&self, requested: &AudioFormat) -> AudioFormat {
+        match requested {
+            AudioFormat::Pcm => AudioFormat::Pcm,
+            _ => AudioFormat::Wav,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voice_url_follows_repository_layout() {
+        assert_eq!(
+            voice_url("en_US-lessac-medium").unwrap(),
+            format!("{}/en/en_US/lessac/medium/en_US-lessac-medium.onnx", VOICE_REPO)
+        );
+        assert!(voice_url("../../etc-passwd-x").is_err());
+        assert!(voice_url("lessac").is_err());
+    }
+
+    #[tokio::test]
+    async fn imported_voice_is_listed_with_its_sample_rate() {
+        let root = std::env::temp_dir().join(format!("clawforge-piper-{}", uuid::Uuid::new_v4()));
+        let source = root.join("downloads");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("de_DE-thorsten-low.onnx"), b"model").unwrap();
+        std::fs::write(
+            source.join("de_DE-thorsten-low.onnx.json"),
+            r#"{"audio": {"sample_rate": 16000}, "language": {"code": "de_DE"}}"#,
+        )
+        .unwrap();
+
+        let voices = PiperVoices::new(root.join("voices"));
+        assert!(voices.list().unwrap().is_empty());
+        voices.import(&source.join("de_DE-thorsten-low.onnx"), None).await.unwrap();
+
+        let listed = voices.list().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "de_DE-thorsten-low");
+        assert_eq!(listed[0].sample_rate, 16_000);
+        assert_eq!(listed[0].language.as_deref(), Some("de_DE"));
+
+        voices.remove("de_DE-thorsten-low").await.unwrap();
+        assert!(voices.get("de_DE-thorsten-low").is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
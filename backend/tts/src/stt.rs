@@ -0,0 +1,366 @@
+/// STT provider trait and implementations (Deepgram, OpenAI Whisper API,
+/// local whisper.cpp), plus a registry shared by every audio consumer.
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::info;
+
+// ---------------------------------------------------------------------------
+// Trait
+// ---------------------------------------------------------------------------
+
+/// An STT request.
+#[derive(Debug, Clone)]
+pub struct SttRequest {
+    pub audio: Bytes,
+    /// MIME type of `audio`, e.g. `audio/ogg` or `audio/wav`.
+    pub mime_type: String,
+    /// BCP-47 language hint; providers auto-detect when `None`.
+    pub language: Option<String>,
+}
+
+impl SttRequest {
+    pub fn new(audio: impl Into<Bytes>, mime_type: impl Into<String>) -> Self {
+        Self { audio: audio.into(), mime_type: mime_type.into(), language: None }
+    }
+}
+
+/// Result of a transcription.
+#[derive(Debug, Clone, Default)]
+pub struct SttTranscript {
+    pub text: String,
+    /// Language reported by the provider, if any.
+    pub language: Option<String>,
+    pub confidence: Option<f32>,
+}
+
+#[async_trait]
+pub trait SttProvider: Send + Sync {
+    /// Provider name for logging and registry lookup.
+    fn name(&self) -> &str;
+
+    async fn transcribe(&self, req: SttRequest) -> Result<SttTranscript>;
+}
+
+fn file_extension(mime: &str) -> &'static str {
+    match mime.split(';').next().unwrap_or(mime).trim() {
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/ogg" | "audio/opus" => "ogg",
+        "audio/webm" => "webm",
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" => "m4a",
+        "audio/flac" => "flac",
+        _ => "wav",
+    }
+}
+
+/// Wrap little-endian 16-bit PCM in a WAV header so it can be sent to any provider.
+pub fn pcm_to_wav(pcm: &[u8], sample_rate: u32, channels: u16) -> Bytes {
+    let byte_rate = sample_rate * channels as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + pcm.len());
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + pcm.len() as u32).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&byte_rate.to_le_bytes());
+    wav.extend_from_slice(&(channels * 2).to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(pcm.len() as u32).to_le_bytes());
+    wav.extend_from_slice(pcm);
+    Bytes::from(wav)
+}
+
+// ---------------------------------------------------------------------------
+// OpenAI Whisper API
+// ---------------------------------------------------------------------------
+
+pub struct OpenAiWhisperStt {
+    api_key: String,
+    model: String,
+    client: Client,
+}
+
+impl OpenAiWhisperStt {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key, model: "whisper-1".to_string(), client: Client::new() }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+}
+
+#[async_trait]
+impl SttProvider for OpenAiWhisperStt {
+    fn name(&self) -> &str { "openai" }
+
+    async fn transcribe(&self, req: SttRequest) -> Result<SttTranscript> {
+        info!("[STT/OpenAI] Transcribing {} bytes with model={}", req.audio.len(), self.model);
+        let part = reqwest::multipart::Part::bytes(req.audio.to_vec())
+            .file_name(format!("audio.{}", file_extension(&req.mime_type)))
+            .mime_str(&req.mime_type)?;
+        let mut form = reqwest::multipart::Form::new()
+            .text("model", self.model.clone())
+            .text("response_format", "verbose_json")
+            .part("file", part);
+        if let Some(lang) = &req.language {
+            form = form.text("language", lang.clone());
+        }
+        let json: serde_json::Value = self
+            .client
+            .post("https://api.openai.com/v1/audio/transcriptions")
+            .bearer_auth(&self.api_key)
+            .multipart(form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(SttTranscript {
+            text: json["text"].as_str().unwrap_or("").trim().to_string(),
+            language: json["language"].as_str().map(str::to_string),
+            confidence: None,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Deepgram
+// ---------------------------------------------------------------------------
+
+pub struct DeepgramStt {
+    api_key: String,
+    model: String,
+    client: Client,
+}
+
+impl DeepgramStt {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key, model: "nova-2".to_string(), client: Client::new() }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+}
+
+#[async_trait]
+impl SttProvider for DeepgramStt {
+    fn name(&self) -> &str { "deepgram" }
+
+    async fn transcribe(&self, req: SttRequest) -> Result<SttTranscript> {
+        info!("[STT/Deepgram] Transcribing {} bytes with model={}", req.audio.len(), self.model);
+        let mut query = vec![("model", self.model.clone()), ("smart_format", "true".to_string())];
+        match &req.language {
+            Some(lang) => query.push(("language", lang.clone())),
+            None => query.push(("detect_language", "true".to_string())),
+        }
+        let json: serde_json::Value = self
+            .client
+            .post("https://api.deepgram.com/v1/listen")
+            .query(&query)
+            .header("Authorization", format!("Token {}", self.api_key))
+            .header("Content-Type", &req.mime_type)
+            .body(req.audio)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let channel = &json["results"]["channels"][0];
+        let best = &channel["alternatives"][0];
+        Ok(SttTranscript {
+            text: best["transcript"].as_str().unwrap_or("").to_string(),
+            language: channel["detected_language"].as_str().map(str::to_string),
+            confidence: best["confidence"].as_f64().map(|c| c as f32),
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Local whisper.cpp
+// ---------------------------------------------------------------------------
+
+/// Runs a local whisper.cpp binary. Input must be a format the binary accepts
+/// (16 kHz WAV for stock builds).
+pub struct LocalWhisperStt {
+    binary: PathBuf,
+    model_path: PathBuf,
+}
+
+impl LocalWhisperStt {
+    pub fn new(binary: impl Into<PathBuf>, model_path: impl Into<PathBuf>) -> Self {
+        Self { binary: binary.into(), model_path: model_path.into() }
+    }
+}
+
+#[async_trait]
+impl SttProvider for LocalWhisperStt {
+    fn name(&self) -> &str { "local" }
+
+    async fn transcribe(&self, req: SttRequest) -> Result<SttTranscript> {
+        let input = std::env::temp_dir().join(format!(
+            "clawforge-stt-{}.{}",
+            uuid::Uuid::new_v4(),
+            file_extension(&req.mime_type)
+        ));
+        tokio::fs::write(&input, &req.audio).await?;
+        info!("[STT/Local] Transcribing {} with {}", input.display(), self.binary.display());
+
+        let mut cmd = tokio::process::Command::new(&self.binary);
+        cmd.arg("-m").arg(&self.model_path).arg("-f").arg(&input).arg("--no-timestamps");
+        if let Some(lang) = &req.language {
+            cmd.arg("-l").arg(lang);
+        }
+        let output = cmd.output().await;
+        let _ = tokio::fs::remove_file(&input).await;
+        let output = output.with_context(|| format!("Failed to run {}", self.binary.display()))?;
+
+        if !output.status.success() {
+            bail!("whisper exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr));
+        }
+        let text = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Ok(SttTranscript { text, language: req.language, confidence: None })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Factory
+// ---------------------------------------------------------------------------
+
+pub enum SttProviderKind {
+    OpenAi { api_key: String, model: Option<String> },
+    Deepgram { api_key: String, model: Option<String> },
+    Local { binary: PathBuf, model_path: PathBuf },
+}
+
+impl SttProviderKind {
+    /// Build from `talk.stt` settings: provider name plus its credentials.
+    pub fn from_settings(
+        provider: &str,
+        api_key: Option<String>,
+        model: Option<String>,
+        binary: Option<PathBuf>,
+    ) -> Result<Self> {
+        Ok(match provider {
+            "openai" | "whisper" => SttProviderKind::OpenAi {
+                api_key: api_key.context("STT provider 'openai' needs an API key")?,
+                model,
+            },
+            "deepgram" => SttProviderKind::Deepgram {
+                api_key: api_key.context("STT provider 'deepgram' needs an API key")?,
+                model,
+            },
+            "local" => SttProviderKind::Local {
+                binary: binary.unwrap_or_else(|| PathBuf::from("whisper-cli")),
+                model_path: model.map(PathBuf::from).context("STT provider 'local' needs a model path")?,
+            },
+            other => bail!("Unknown STT provider '{}'", other),
+        })
+    }
+}
+
+pub fn create_stt(kind: SttProviderKind) -> Box<dyn SttProvider> {
+    match kind {
+        SttProviderKind::OpenAi { api_key, model } => {
+            let stt = OpenAiWhisperStt::new(api_key);
+            Box::new(match model {
+                Some(m) => stt.with_model(m),
+                None => stt,
+            })
+        }
+        SttProviderKind::Deepgram { api_key, model } => {
+            let stt = DeepgramStt::new(api_key);
+            Box::new(match model {
+                Some(m) => stt.with_model(m),
+                None => stt,
+            })
+        }
+        SttProviderKind::Local { binary, model_path } => Box::new(LocalWhisperStt::new(binary, model_path)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Registry
+// ---------------------------------------------------------------------------
+
+/// Named STT providers with a default, shared by the media pipeline, voice
+/// calls and Discord voice.
+#[derive(Clone, Default)]
+pub struct SttRegistry {
+    providers: HashMap<String, Arc<dyn SttProvider>>,
+    default: Option<String>,
+}
+
+impl SttRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a provider under its own name. The first one becomes the default.
+    pub fn register(&mut self, provider: Arc<dyn SttProvider>) {
+        let name = provider.name().to_string();
+        self.default.get_or_insert_with(|| name.clone());
+        self.providers.insert(name, provider);
+    }
+
+    pub fn set_default(&mut self, name: &str) -> Result<()> {
+        if !self.providers.contains_key(name) {
+            bail!("STT provider '{}' is not registered", name);
+        }
+        self.default = Some(name.to_string());
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn SttProvider>> {
+        self.providers.get(name).cloned()
+    }
+
+    pub fn default_provider(&self) -> Option<Arc<dyn SttProvider>> {
+        self.default.as_deref().and_then(|name| self.get(name))
+    }
+
+    /// Transcribe with the default provider.
+    pub async fn transcribe(&self, req: SttRequest) -> Result<SttTranscript> {
+        let provider = self.default_provider().context("No STT provider configured")?;
+        provider.transcribe(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wav_header_matches_pcm() {
+        let wav = pcm_to_wav(&[0u8; 8], 48_000, 2);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 8);
+        assert_eq!(wav.len(), 52);
+    }
+
+    #[test]
+    fn first_registered_provider_is_default() {
+        let mut registry = SttRegistry::new();
+        registry.register(Arc::new(DeepgramStt::new("k".into())));
+        registry.register(Arc::new(OpenAiWhisperStt::new("k".into())));
+        assert_eq!(registry.default_provider().unwrap().name(), "deepgram");
+        registry.set_default("openai").unwrap();
+        assert_eq!(registry.default_provider().unwrap().name(), "openai");
+        assert!(registry.set_default("local").is_err());
+    }
+}
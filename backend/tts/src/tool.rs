@@ -1,12 +1,17 @@
 /// TTS Tool — allows agents to reply with audio output.
 ///
 /// Wraps the TTS engine and returns a base64-encoded audio payload
-/// that can be sent to channel adapters that support audio.
+/// that can be sent to channel adapters that support audio. The voice,
+/// speed and provider come from the tool arguments first, then from the
+/// calling session's overrides and the caller's preferences in the
+/// `PreferenceStore`, then from the registry default. Who the caller is
+/// comes from the runtime's `TtsCaller`, never from the model's arguments.
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
+use clawforge_security::PreferenceStore;
 use serde::{Deserialize, Serialize};
 
-use crate::engine::{TtsProvider, TtsRequest};
+use crate::engine::{TtsRegistry, TtsRequest};
 
 /// Input parameters for the TTS tool.
 #[derive(Debug, Deserialize)]
@@ -15,6 +20,18 @@ pub struct TtsToolInput {
     pub voice: Option<String>,
     pub format: Option<String>,
     pub speed: Option<f32>,
+    /// Set for one-off text so it is not stored in the TTS cache.
+    #[serde(default)]
+    pub no_cache: bool,
+}
+
+/// The session a TTS call runs in, taken from the run's context.
+#[derive(Debug, Clone, Default)]
+pub struct TtsCaller {
+    /// Principal whose preferences apply.
+    pub principal: Option<String>,
+    /// Session whose voice overrides apply.
+    pub session_id: Option<String>,
 }
 
 /// Output from the TTS tool.
@@ -28,26 +45,35 @@ pub struct TtsToolOutput {
     pub char_count: usize,
 }
 
-/// Run the TTS tool with the given provider and input.
+/// Run the TTS tool for `caller` with the given providers, preferences and
+/// input.
 pub async fn run_tts_tool(
-    provider: &dyn TtsProvider,
+    providers: &TtsRegistry,
+    preferences: &PreferenceStore,
+    caller: &TtsCaller,
     input: TtsToolInput,
 ) -> Result<TtsToolOutput> {
     let char_count = input.text.len();
+    let prefs = preferences.resolve(caller.principal.as_deref(), caller.session_id.as_deref());
+    let provider = providers.get_or_default(prefs.voice_provider.as_deref())?;
+    let provider = provider.as_ref();
     let format = match input.format.as_deref().unwrap_or("mp3") {
         "opus" => crate::engine::AudioFormat::Opus,
         "aac" => crate::engine::AudioFormat::Aac,
         "flac" => crate::engine::AudioFormat::Flac,
         "pcm" => crate::engine::AudioFormat::Pcm,
+        "wav" => crate::engine::AudioFormat::Wav,
         _ => crate::engine::AudioFormat::Mp3,
     };
-    let mime = format.mime_type().to_string();
+    let mime = provider.output_format(&format).mime_type().to_string();
 
+    // Agents write Markdown; speak it rather than reading out the syntax.
     let req = TtsRequest {
-        text: input.text,
-        voice: input.voice,
+        speed: input.speed.or(prefs.voice_speed).unwrap_or(1.0),
+        voice: input.voice.or(prefs.voice),
         format,
-        speed: input.speed.unwrap_or(1.0),
+        bypass_cache: input.no_cache,
+        ..TtsRequest::from_markdown(&input.text, provider)
     };
 
     let bytes = provider.synthesize(req).await?;
@@ -59,3 +85,89 @@ pub async fn run_tts_tool(
         char_count,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::TtsCache;
+    use crate::engine::TtsProvider;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Returns "<name>|<voice>|<speed>" and counts calls.
+    struct Echo(&'static str, AtomicUsize);
+
+    #[async_trait]
+    impl TtsProvider for Echo {
+        async fn synthesize(&self, req: TtsRequest) -> Result<Bytes> {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            Ok(Bytes::from(format!("{}|{}|{}", self.0, req.voice.unwrap_or_default(), req.speed)))
+        }
+    }
+
+    fn input(text: &str) -> TtsToolInput {
+        TtsToolInput {
+            text: text.to_string(),
+            voice: None,
+            format: None,
+            speed: None,
+            no_cache: false,
+        }
+    }
+
+    fn caller(session: Option<&str>) -> TtsCaller {
+        TtsCaller { principal: Some("person:42".into()), session_id: session.map(str::to_string) }
+    }
+
+    fn decode(output: &TtsToolOutput) -> String {
+        String::from_utf8(general_purpose::STANDARD.decode(&output.audio_base64).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn preferences_pick_voice_and_provider_and_repeats_hit_the_cache() {
+        let openai = Arc::new(Echo("openai", AtomicUsize::new(0)));
+        let piper = Arc::new(Echo("piper", AtomicUsize::new(0)));
+        let mut providers = TtsRegistry::cached(Arc::new(TtsCache::default()));
+        providers.register("openai", openai.clone());
+        providers.register("piper", piper.clone());
+
+        let prefs = PreferenceStore::new();
+        prefs.set("person:42", "voice_provider", "piper").unwrap();
+        prefs.set("person:42", "voice", "en_GB-alan-low").unwrap();
+        prefs.set("person:42", "voice_speed", "1.5").unwrap();
+
+        let out = run_tts_tool(&providers, &prefs, &caller(None), input("Got it.")).await.unwrap();
+        assert_eq!(decode(&out), "piper|en_GB-alan-low|1.5");
+        run_tts_tool(&providers, &prefs, &caller(None), input("Got it.")).await.unwrap();
+        assert_eq!(piper.1.load(Ordering::SeqCst), 1);
+
+        let explicit = TtsToolInput { voice: Some("fable".into()), ..input("Got it.") };
+        assert_eq!(decode(&run_tts_tool(&providers, &prefs, &caller(None), explicit).await.unwrap()), "piper|fable|1.5");
+
+        // A principal in the model's arguments is ignored.
+        let spoofed: TtsToolInput = serde_json::from_value(serde_json::json!({ "text": "Got it.", "principal": "person:42" })).unwrap();
+        let stranger = TtsCaller::default();
+        assert_eq!(decode(&run_tts_tool(&providers, &prefs, &stranger, spoofed).await.unwrap()), "openai||1");
+        assert_eq!(openai.1.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn session_overrides_beat_the_person_and_lose_to_arguments() {
+        let mut providers = TtsRegistry::new();
+        providers.register("openai", Arc::new(Echo("openai", AtomicUsize::new(0))));
+
+        let prefs = PreferenceStore::new();
+        prefs.set("person:42", "voice", "nova").unwrap();
+        prefs.set("person:42", "voice_speed", "1.5").unwrap();
+        prefs.set_for_session("telegram:7", "voice", "onyx").unwrap();
+
+        let out = run_tts_tool(&providers, &prefs, &caller(Some("telegram:7")), input("Hi.")).await.unwrap();
+        assert_eq!(decode(&out), "openai|onyx|1.5");
+        let out = run_tts_tool(&providers, &prefs, &caller(Some("telegram:8")), input("Hi.")).await.unwrap();
+        assert_eq!(decode(&out), "openai|nova|1.5");
+        let explicit = TtsToolInput { speed: Some(0.75), ..input("Hi.") };
+        assert_eq!(decode(&run_tts_tool(&providers, &prefs, &caller(Some("telegram:7")), explicit).await.unwrap()), "openai|onyx|0.75");
+    }
+}
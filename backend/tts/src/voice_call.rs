@@ -1,7 +1,42 @@
-/// Voice-call stub — placeholder for PSTN/VoIP integration.
+/// Voice calls — Twilio dialing and the realtime media bridge.
 ///
-/// Full implementation would integrate with Twilio, Vonage, or SIP.
+/// Phone audio arrives over a Twilio Media Streams or LiveKit WebSocket.
+/// `CallBridge` feeds it through the voice session's VAD for turn detection,
+/// transcribes each turn as soon as the caller stops talking, streams the
+/// agent's reply back through TTS sentence by sentence, and cancels the reply
+/// when the caller talks over it. Call start, every turn and hang-up are sent
+/// to the supervisor as audit events, where a call is tracked like a run.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use bytes::Bytes;
+use clawforge_core::{AuditEventPayload, Event, EventKind, Message, Tool};
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::engine::{AudioFormat, TtsProvider, TtsRequest};
+use crate::stt::{pcm_to_wav, SttProvider, SttRequest};
+use crate::voice_session::{VadConfig, VoiceEvent, VoiceSession};
+
+const TWILIO_API: &str = "https://api.twilio.com/2010-04-01";
+
+/// Twilio Media Streams carry 8 kHz μ-law.
+const TWILIO_SAMPLE_RATE: u32 = 8_000;
+
+/// Sample rate of `AudioFormat::Pcm` from OpenAI TTS.
+const DEFAULT_TTS_SAMPLE_RATE: u32 = 24_000;
+
+/// How long a Twilio call's stream token stays redeemable: from answering
+/// (or dialing) until the media stream's `start` event.
+const CALL_TOKEN_TTL: Duration = Duration::from_secs(120);
 
 /// Status of a voice call.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -16,6 +51,21 @@ pub enum CallStatus {
     NoAnswer,
 }
 
+impl CallStatus {
+    /// Parse a Twilio call status (`in-progress`, `no-answer`, ...).
+    pub fn from_twilio(status: &str) -> Self {
+        match status {
+            "queued" | "initiated" => CallStatus::Queued,
+            "ringing" => CallStatus::Ringing,
+            "in-progress" => CallStatus::InProgress,
+            "completed" => CallStatus::Completed,
+            "busy" => CallStatus::Busy,
+            "no-answer" => CallStatus::NoAnswer,
+            _ => CallStatus::Failed,
+        }
+    }
+}
+
 /// An outbound voice call record.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VoiceCall {
@@ -26,18 +76,876 @@ pub struct VoiceCall {
     pub duration_secs: Option<u32>,
 }
 
-/// Stub: initiate an outbound call.
-/// In production, this would call the Twilio/Vonage API.
-pub async fn initiate_call(to: &str, from: &str, _twiml_url: &str) -> VoiceCall {
-    tracing::warn!(
-        "[VoiceCall] Voice call to {} is a stub — integrate Twilio/Vonage for production",
-        to
-    );
-    VoiceCall {
-        call_id: format!("stub-{}", uuid::Uuid::new_v4()),
-        to: to.to_string(),
-        from: from.to_string(),
-        status: CallStatus::Queued,
-        duration_secs: None,
+/// Transcribe a finished call's recording with the shared STT provider.
+pub async fn transcribe_recording(
+    stt: &dyn crate::stt::SttProvider,
+    recording: bytes::Bytes,
+    mime_type: &str,
+) -> anyhow::Result<String> {
+    let transcript = stt.transcribe(crate::stt::SttRequest::new(recording, mime_type)).await?;
+    Ok(transcript.text)
+}
+
+// ---------------------------------------------------------------------------
+// Twilio
+// ---------------------------------------------------------------------------
+
+/// TwiML connecting a call to the media bridge at `stream_url` (a `wss://`
+/// URL). `parameters` reach the bridge in the stream's `start` event, which
+/// is how the stream token and caller details get through.
+pub fn stream_twiml(stream_url: &str, parameters: &[(&str, &str)]) -> String {
+    let params: String = parameters
+        .iter()
+        .map(|(name, value)| format!("<Parameter name=\"{}\" value=\"{}\"/>", xml_escape(name), xml_escape(value)))
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Response><Connect><Stream url=\"{}\">{}</Stream></Connect></Response>",
+        xml_escape(stream_url),
+        params
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Twilio REST client for placing and ending calls.
+pub struct TwilioCalls {
+    account_sid: String,
+    auth_token: String,
+    client: reqwest::Client,
+}
+
+impl TwilioCalls {
+    pub fn new(account_sid: String, auth_token: String) -> Self {
+        Self { account_sid, auth_token, client: reqwest::Client::new() }
+    }
+
+    /// Dial `to` from `from` and connect the answered call to the media
+    /// bridge at `stream_url`.
+    pub async fn place_call(&self, to: &str, from: &str, stream_url: &str, token: Option<&str>) -> Result<VoiceCall> {
+        let mut parameters = vec![("direction", "outbound"), ("from", from), ("to", to)];
+        if let Some(token) = token {
+            parameters.push(("token", token));
+        }
+        let twiml = stream_twiml(stream_url, &parameters);
+        info!("[VoiceCall] Dialing {} from {}", to, from);
+        let response: serde_json::Value = self
+            .client
+            .post(format!("{}/Accounts/{}/Calls.json", TWILIO_API, self.account_sid))
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .form(&[("To", to), ("From", from), ("Twiml", twiml.as_str())])
+            .send()
+            .await?
+            .error_for_status()
+            .context("Twilio rejected the call")?
+            .json()
+            .await?;
+        Ok(VoiceCall {
+            call_id: response["sid"].as_str().context("Twilio response has no call sid")?.to_string(),
+            to: to.to_string(),
+            from: from.to_string(),
+            status: CallStatus::from_twilio(response["status"].as_str().unwrap_or("queued")),
+            duration_secs: None,
+        })
+    }
+
+    /// Hang up a call in progress.
+    pub async fn hang_up(&self, call_sid: &str) -> Result<()> {
+        self.client
+            .post(format!("{}/Accounts/{}/Calls/{}.json", TWILIO_API, self.account_sid, call_sid))
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .form(&[("Status", "completed")])
+            .send()
+            .await?
+            .error_for_status()
+            .context("Twilio refused to end the call")?;
+        Ok(())
+    }
+}
+
+/// The `phone_call` tool: dials a number and connects whoever answers to
+/// the agent through the media bridge.
+pub struct PhoneCallTool {
+    twilio: Arc<TwilioCalls>,
+    bridge: Arc<CallBridge>,
+    from: String,
+    stream_url: String,
+}
+
+impl PhoneCallTool {
+    /// Calls come from `from` and stream to the bridge at `stream_url`.
+    pub fn new(twilio: Arc<TwilioCalls>, bridge: Arc<CallBridge>, from: impl Into<String>, stream_url: impl Into<String>) -> Self {
+        Self { twilio, bridge, from: from.into(), stream_url: stream_url.into() }
+    }
+}
+
+/// `+` followed by 8 to 15 digits.
+fn is_e164(number: &str) -> bool {
+    number.strip_prefix('+').is_some_and(|digits| (8..=15).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[async_trait]
+impl Tool for PhoneCallTool {
+    fn name(&self) -> &str {
+        "phone_call"
+    }
+
+    fn description(&self) -> &str {
+        "Place a phone call and talk to whoever answers. The call runs in the background; this returns once it is dialing."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "to": { "type": "string", "description": "Number to call in E.164 format, e.g. +15550100" }
+            },
+            "required": ["to"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<String> {
+        let to = args["to"].as_str().context("'to' is required")?;
+        if !is_e164(to) {
+            bail!("'{}' is not an E.164 phone number", to);
+        }
+        let token = self.bridge.issue_call_token();
+        let call = self.twilio.place_call(to, &self.from, &self.stream_url, Some(&token)).await?;
+        Ok(serde_json::to_string(&call)?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum TwilioEvent {
+    Start { start: TwilioStart },
+    Media { media: TwilioMedia },
+    Mark { mark: TwilioMark },
+    Stop,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TwilioStart {
+    stream_sid: String,
+    call_sid: String,
+    #[serde(default)]
+    custom_parameters: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TwilioMedia {
+    payload: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TwilioMark {
+    name: String,
+}
+
+// ---------------------------------------------------------------------------
+// Audio
+// ---------------------------------------------------------------------------
+
+/// Decode one G.711 μ-law byte.
+pub fn mulaw_decode(byte: u8) -> i16 {
+    let byte = !byte;
+    let exponent = (byte >> 4) & 0x07;
+    let mantissa = (byte & 0x0F) as i32;
+    let magnitude = (((mantissa << 3) + 0x84) << exponent) - 0x84;
+    (if byte & 0x80 != 0 { -magnitude } else { magnitude }) as i16
+}
+
+/// Encode one sample as G.711 μ-law.
+pub fn mulaw_encode(sample: i16) -> u8 {
+    const BIAS: i32 = 0x84;
+    const CLIP: i32 = 32_635;
+    let sign = if sample < 0 { 0x80 } else { 0 };
+    let magnitude = (sample as i32).abs().min(CLIP) + BIAS;
+    let mut exponent = 7;
+    while exponent > 0 && magnitude & (0x80 << exponent) == 0 {
+        exponent -= 1;
+    }
+    let mantissa = (magnitude >> (exponent + 3)) & 0x0F;
+    !(sign | (exponent << 4) | mantissa) as u8
+}
+
+/// Convert mono PCM between sample rates: averaging when going down so
+/// the higher band doesn't alias into speech, interpolating when going up.
+pub fn resample(samples: &[i16], from: u32, to: u32) -> Vec<i16> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = from as f64 / to as f64;
+    let len = (samples.len() as f64 / ratio) as usize;
+    let last = samples.len() - 1;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            if ratio > 1.0 {
+                let start = pos as usize;
+                let end = (((i + 1) as f64 * ratio) as usize).clamp(start + 1, samples.len());
+                let sum: i64 = samples[start..end].iter().map(|&s| s as i64).sum();
+                (sum / (end - start) as i64) as i16
+            } else {
+                let idx = pos as usize;
+                let frac = pos - idx as f64;
+                let (a, b) = (samples[idx.min(last)] as f64, samples[(idx + 1).min(last)] as f64);
+                (a + (b - a) * frac).round() as i16
+            }
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Bridge
+// ---------------------------------------------------------------------------
+
+/// Wire format of the media WebSocket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaProtocol {
+    /// Twilio Media Streams: JSON events carrying base64 8 kHz μ-law.
+    Twilio,
+    /// LiveKit track egress/ingress: binary frames of 16-bit little-endian
+    /// mono PCM at `sample_rate`, both ways.
+    LiveKit { sample_rate: u32 },
+}
+
+impl MediaProtocol {
+    pub fn sample_rate(&self) -> u32 {
+        match self {
+            MediaProtocol::Twilio => TWILIO_SAMPLE_RATE,
+            MediaProtocol::LiveKit { sample_rate } => *sample_rate,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            MediaProtocol::Twilio => "twilio",
+            MediaProtocol::LiveKit { .. } => "livekit",
+        }
+    }
+}
+
+/// One WebSocket message, so the bridge stays independent of the server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallFrame {
+    Text(String),
+    Binary(Bytes),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallDirection {
+    Inbound,
+    Outbound,
+}
+
+/// Who is on the call. Twilio streams fill this in from their `start`
+/// event; LiveKit streams get it from whoever accepted the socket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallInfo {
+    pub call_id: String,
+    pub direction: CallDirection,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// The agent side of a call.
+#[async_trait]
+pub trait CallAgent: Send + Sync {
+    /// Answer the caller's `transcript`, sending the reply as text deltas
+    /// into `reply`. The turn ends when `reply` is dropped; if the caller
+    /// interrupts, sends start failing and the agent should stop.
+    async fn respond(&self, call: &CallInfo, transcript: &str, reply: mpsc::Sender<String>) -> Result<()>;
+}
+
+/// Audit events for one call, sent to the supervisor.
+#[derive(Clone)]
+struct CallEvents {
+    tx: Option<mpsc::Sender<Message>>,
+    run_id: Uuid,
+    agent_id: Uuid,
+}
+
+impl CallEvents {
+    async fn emit(&self, kind: EventKind, payload: serde_json::Value) {
+        let Some(tx) = &self.tx else { return };
+        let event = Event::new(self.run_id, self.agent_id, kind, payload);
+        if tx.send(Message::AuditEvent(AuditEventPayload { event })).await.is_err() {
+            debug!("[VoiceCall] Supervisor channel closed; dropping call event");
+        }
+    }
+}
+
+pub struct CallBridge {
+    stt: Arc<dyn SttProvider>,
+    tts: Arc<dyn TtsProvider>,
+    agent: Arc<dyn CallAgent>,
+    agent_id: Uuid,
+    events: Option<mpsc::Sender<Message>>,
+    voice: Option<String>,
+    greeting: Option<String>,
+    tts_sample_rate: u32,
+    vad: VadConfig,
+    stream_token: Option<String>,
+    /// Single-use Twilio stream tokens → when they were issued.
+    call_tokens: Mutex<HashMap<String, Instant>>,
+}
+
+impl CallBridge {
+    pub fn new(stt: Arc<dyn SttProvider>, tts: Arc<dyn TtsProvider>, agent: Arc<dyn CallAgent>) -> Self {
+        Self {
+            stt,
+            tts,
+            agent,
+            agent_id: Uuid::nil(),
+            events: None,
+            voice: None,
+            greeting: None,
+            tts_sample_rate: DEFAULT_TTS_SAMPLE_RATE,
+            vad: VadConfig::default(),
+            stream_token: None,
+            call_tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Report call lifecycle events to the supervisor through `tx`, on
+    /// behalf of `agent_id`.
+    pub fn with_events(mut self, tx: mpsc::Sender<Message>, agent_id: Uuid) -> Self {
+        self.events = Some(tx);
+        self.agent_id = agent_id;
+        self
+    }
+
+    pub fn with_voice(mut self, voice: impl Into<String>) -> Self {
+        self.voice = Some(voice.into());
+        self
+    }
+
+    /// Spoken as soon as the call connects.
+    pub fn with_greeting(mut self, greeting: impl Into<String>) -> Self {
+        self.greeting = Some(greeting.into());
+        self
+    }
+
+    /// Sample rate of the PCM the TTS provider returns (24 kHz for OpenAI,
+    /// the voice's rate for Piper).
+    pub fn with_tts_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.tts_sample_rate = sample_rate;
+        self
+    }
+
+    pub fn with_vad(mut self, vad: VadConfig) -> Self {
+        self.vad = vad;
+        self
+    }
+
+    /// Let LiveKit streams in when they present `token`, checked by the
+    /// server with `authorize`; without it they are refused. Twilio streams
+    /// use `issue_call_token` instead.
+    pub fn with_stream_token(mut self, token: impl Into<String>) -> Self {
+        self.stream_token = Some(token.into());
+        self
+    }
+
+    /// Mint a single-use token for one Twilio call. It goes into that call's
+    /// TwiML and must come back in the stream's `start` event within
+    /// `CALL_TOKEN_TTL`.
+    pub fn issue_call_token(&self) -> String {
+        let token = Uuid::new_v4().simple().to_string();
+        let mut tokens = self.call_tokens.lock().unwrap();
+        tokens.retain(|_, issued| issued.elapsed() < CALL_TOKEN_TTL);
+        tokens.insert(token.clone(), Instant::now());
+        token
+    }
+
+    fn redeem_call_token(&self, presented: Option<&str>) -> Result<()> {
+        let issued = presented.and_then(|token| self.call_tokens.lock().unwrap().remove(token));
+        match issued {
+            Some(issued) if issued.elapsed() < CALL_TOKEN_TTL => Ok(()),
+            _ => bail!("Media stream presented an invalid or expired call token"),
+        }
+    }
+
+    /// Check a LiveKit stream token. Without one configured, LiveKit streams
+    /// are refused.
+    pub fn authorize(&self, presented: Option<&str>) -> Result<()> {
+        let Some(expected) = &self.stream_token else {
+            bail!("LiveKit streams are off: no stream token is configured");
+        };
+        let presented = presented.unwrap_or_default();
+        if !bool::from(expected.as_bytes().ct_eq(presented.as_bytes())) {
+            bail!("Media stream presented an invalid token");
+        }
+        Ok(())
+    }
+
+    /// Run one call until the stream stops or the socket closes. `info` is
+    /// required for LiveKit and ignored for Twilio.
+    pub async fn run(
+        &self,
+        protocol: MediaProtocol,
+        info: Option<CallInfo>,
+        mut inbound: mpsc::Receiver<CallFrame>,
+        outbound: mpsc::Sender<CallFrame>,
+    ) -> Result<VoiceCall> {
+        let rate = protocol.sample_rate();
+        let frame_len = (rate / 50) as usize;
+        let mut call = Call {
+            protocol,
+            stream_sid: None,
+            session: VoiceSession::new(self.vad.clone()),
+            outbound,
+            carry: None,
+            marks: 0,
+        };
+        let mut info = match (protocol, info) {
+            (MediaProtocol::LiveKit { .. }, None) => bail!("LiveKit streams need the call details up front"),
+            (MediaProtocol::LiveKit { .. }, info) => info,
+            (MediaProtocol::Twilio, _) => None,
+        };
+        let events = CallEvents { tx: self.events.clone(), run_id: Uuid::new_v4(), agent_id: self.agent_id };
+        let mut started_at = None;
+        let template = TtsRequest { voice: self.voice.clone(), format: AudioFormat::Pcm, ..Default::default() };
+
+        // Utterance samples not yet cut into VAD frames.
+        let mut pending: Vec<i16> = Vec::new();
+        let (transcript_tx, mut transcripts) = mpsc::channel::<(u64, Result<String>)>(4);
+        let mut turn = 0u64;
+        // Text of a turn the caller talked over before it was answered.
+        let mut held = String::new();
+        let mut reply_audio: Option<mpsc::Receiver<Bytes>> = None;
+        let mut reply_tasks: Vec<AbortHandle> = Vec::new();
+        let (mut turns, mut barge_ins) = (0u32, 0u32);
+
+        if let Some(info) = &info {
+            started_at = Some(Instant::now());
+            self.call_started(&events, protocol, info).await;
+            reply_audio = self.greet(&mut call.session, &template);
+        }
+
+        loop {
+            tokio::select! {
+                frame = inbound.recv() => {
+                    let Some(frame) = frame else { break };
+                    let samples = match (protocol, frame) {
+                        (MediaProtocol::Twilio, CallFrame::Text(text)) => match serde_json::from_str::<TwilioEvent>(&text) {
+                            Ok(TwilioEvent::Start { start }) => {
+                                self.redeem_call_token(start.custom_parameters.get("token").map(String::as_str))?;
+                                let params = &start.custom_parameters;
+                                let started = CallInfo {
+                                    call_id: start.call_sid,
+                                    direction: if params.get("direction").map(String::as_str) == Some("outbound") {
+                                        CallDirection::Outbound
+                                    } else {
+                                        CallDirection::Inbound
+                                    },
+                                    from: params.get("from").cloned(),
+                                    to: params.get("to").cloned(),
+                                };
+                                call.stream_sid = Some(start.stream_sid);
+                                started_at = Some(Instant::now());
+                                self.call_started(&events, protocol, &started).await;
+                                info = Some(started);
+                                reply_audio = self.greet(&mut call.session, &template);
+                                continue;
+                            }
+                            Ok(TwilioEvent::Media { media }) => match general_purpose::STANDARD.decode(media.payload) {
+                                Ok(bytes) => bytes.into_iter().map(mulaw_decode).collect::<Vec<i16>>(),
+                                Err(e) => {
+                                    debug!("[VoiceCall] Dropping undecodable media payload: {}", e);
+                                    continue;
+                                }
+                            },
+                            Ok(TwilioEvent::Mark { mark }) => {
+                                if mark.name == format!("reply-{}", call.marks) {
+                                    call.session.playback_finished();
+                                }
+                                continue;
+                            }
+                            Ok(TwilioEvent::Stop) => break,
+                            Ok(TwilioEvent::Other) => continue,
+                            Err(e) => {
+                                debug!("[VoiceCall] Ignoring unparseable Twilio event: {}", e);
+                                continue;
+                            }
+                        },
+                        (MediaProtocol::LiveKit { .. }, CallFrame::Binary(bytes)) => {
+                            bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect()
+                        }
+                        _ => continue,
+                    };
+                    if info.is_none() {
+                        continue;
+                    }
+                    pending.extend(samples);
+                    let whole = pending.len() - pending.len() % frame_len;
+                    let frames: Vec<i16> = pending.drain(..whole).collect();
+                    for frame in frames.chunks(frame_len) {
+                        for event in call.session.on_audio_frame(frame) {
+                            match event {
+                                VoiceEvent::SpeechStarted => turn += 1,
+                                VoiceEvent::BargeIn => {
+                                    barge_ins += 1;
+                                    reply_audio = None;
+                                    reply_tasks.drain(..).for_each(|task| task.abort());
+                                    call.clear().await;
+                                }
+                                VoiceEvent::Utterance(samples) => {
+                                    let wav = pcm_to_wav(&samples.iter().flat_map(|s| s.to_le_bytes()).collect::<Vec<u8>>(), rate, 1);
+                                    let (stt, tx, this_turn) = (self.stt.clone(), transcript_tx.clone(), turn);
+                                    tokio::spawn(async move {
+                                        let text = stt.transcribe(SttRequest::new(wav, "audio/wav")).await.map(|t| t.text);
+                                        let _ = tx.send((this_turn, text)).await;
+                                    });
+                                }
+                                VoiceEvent::WakeWord(_) => {}
+                            }
+                        }
+                    }
+                }
+                Some((for_turn, result)) = transcripts.recv() => {
+                    let text = match result {
+                        Ok(text) => text.trim().to_string(),
+                        Err(e) => {
+                            warn!("[VoiceCall] Transcription failed: {:#}", e);
+                            String::new()
+                        }
+                    };
+                    if for_turn != turn {
+                        // The caller kept talking; answer both parts together.
+                        held = format!("{} {}", held, text).trim().to_string();
+                        continue;
+                    }
+                    let text = format!("{} {}", std::mem::take(&mut held), text).trim().to_string();
+                    let Some(call_info) = &info else { continue };
+                    if text.is_empty() {
+                        call.session.finish_turn();
+                        continue;
+                    }
+                    turns += 1;
+                    events.emit(EventKind::CallTurn, serde_json::json!({ "call_id": call_info.call_id, "role": "caller", "text": text })).await;
+                    let (audio_rx, tasks) = self.answer(&mut call.session, &template, call_info, text, events.clone());
+                    reply_audio = Some(audio_rx);
+                    reply_tasks = tasks;
+                }
+                chunk = recv_reply(&mut reply_audio) => match chunk {
+                    Some(chunk) => call.play(&chunk, self.tts_sample_rate).await,
+                    None => {
+                        reply_audio = None;
+                        call.reply_done().await;
+                    }
+                },
+            }
+        }
+
+        reply_tasks.iter().for_each(AbortHandle::abort);
+        let Some(info) = info else { bail!("Media stream closed before the call started") };
+        let duration_secs = started_at.map(|t| t.elapsed().as_secs() as u32).unwrap_or(0);
+        info!("[VoiceCall] Call {} ended after {}s and {} turns", info.call_id, duration_secs, turns);
+        events
+            .emit(
+                EventKind::CallEnded,
+                serde_json::json!({ "call_id": info.call_id, "duration_secs": duration_secs, "turns": turns, "barge_ins": barge_ins }),
+            )
+            .await;
+        Ok(VoiceCall {
+            call_id: info.call_id,
+            to: info.to.unwrap_or_default(),
+            from: info.from.unwrap_or_default(),
+            status: CallStatus::Completed,
+            duration_secs: Some(duration_secs),
+        })
+    }
+
+    async fn call_started(&self, events: &CallEvents, protocol: MediaProtocol, info: &CallInfo) {
+        info!("[VoiceCall] {:?} call {} connected over {}", info.direction, info.call_id, protocol.name());
+        events
+            .emit(
+                EventKind::CallStarted,
+                serde_json::json!({
+                    "call_id": info.call_id,
+                    "direction": info.direction,
+                    "from": info.from,
+                    "to": info.to,
+                    "protocol": protocol.name(),
+                }),
+            )
+            .await;
+    }
+
+    fn greet(&self, session: &mut VoiceSession, template: &TtsRequest) -> Option<mpsc::Receiver<Bytes>> {
+        let greeting = self.greeting.clone()?;
+        let (text_tx, text_rx) = mpsc::channel(1);
+        let _ = text_tx.try_send(greeting);
+        let (audio_tx, audio_rx) = mpsc::channel(32);
+        session.speak_stream(self.tts.clone(), template.clone(), text_rx, audio_tx);
+        Some(audio_rx)
+    }
+
+    /// Start the agent on `transcript` and speak its reply as it streams.
+    /// Returns the reply audio and the tasks to abort on barge-in.
+    fn answer(
+        &self,
+        session: &mut VoiceSession,
+        template: &TtsRequest,
+        info: &CallInfo,
+        transcript: String,
+        events: CallEvents,
+    ) -> (mpsc::Receiver<Bytes>, Vec<AbortHandle>) {
+        let (agent_tx, mut agent_rx) = mpsc::channel::<String>(64);
+        let (text_tx, text_rx) = mpsc::channel::<String>(64);
+        let (agent, call) = (self.agent.clone(), info.clone());
+        let respond = tokio::spawn(async move {
+            if let Err(e) = agent.respond(&call, &transcript, agent_tx).await {
+                warn!("[VoiceCall] Agent failed to answer on call {}: {:#}", call.call_id, e);
+            }
+        });
+        // Tee the reply into TTS and, once complete, into the call log. The
+        // event goes out before `text_tx` drops, so it precedes the end of
+        // the reply's audio.
+        let call_id = info.call_id.clone();
+        let tee = tokio::spawn(async move {
+            let mut reply = String::new();
+            while let Some(delta) = agent_rx.recv().await {
+                reply.push_str(&delta);
+                if text_tx.send(delta).await.is_err() {
+                    return;
+                }
+            }
+            events.emit(EventKind::CallTurn, serde_json::json!({ "call_id": call_id, "role": "agent", "text": reply })).await;
+        });
+        let (audio_tx, audio_rx) = mpsc::channel(32);
+        session.speak_stream(self.tts.clone(), template.clone(), text_rx, audio_tx);
+        (audio_rx, vec![respond.abort_handle(), tee.abort_handle()])
+    }
+}
+
+async fn recv_reply(audio: &mut Option<mpsc::Receiver<Bytes>>) -> Option<Bytes> {
+    match audio {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Per-call output state.
+struct Call {
+    protocol: MediaProtocol,
+    stream_sid: Option<String>,
+    session: VoiceSession,
+    outbound: mpsc::Sender<CallFrame>,
+    /// Odd byte left over from the last TTS chunk.
+    carry: Option<u8>,
+    marks: u64,
+}
+
+impl Call {
+    async fn send(&mut self, frame: CallFrame) {
+        if self.outbound.send(frame).await.is_err() {
+            debug!("[VoiceCall] Media socket closed while sending");
+        }
+    }
+
+    /// Send a chunk of TTS PCM to the caller.
+    async fn play(&mut self, chunk: &[u8], tts_rate: u32) {
+        let mut bytes = Vec::with_capacity(chunk.len() + 1);
+        bytes.extend(self.carry.take());
+        bytes.extend_from_slice(chunk);
+        if bytes.len() % 2 == 1 {
+            self.carry = bytes.pop();
+        }
+        let samples: Vec<i16> = bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect();
+        let samples = resample(&samples, tts_rate, self.protocol.sample_rate());
+        let frame = match self.protocol {
+            MediaProtocol::Twilio => {
+                let Some(sid) = &self.stream_sid else { return };
+                let payload = general_purpose::STANDARD.encode(samples.into_iter().map(mulaw_encode).collect::<Vec<u8>>());
+                CallFrame::Text(serde_json::json!({ "event": "media", "streamSid": sid, "media": { "payload": payload } }).to_string())
+            }
+            MediaProtocol::LiveKit { .. } => CallFrame::Binary(samples.iter().flat_map(|s| s.to_le_bytes()).collect::<Vec<u8>>().into()),
+        };
+        self.send(frame).await;
+    }
+
+    /// All reply audio was sent. Twilio tells us when it has been played
+    /// through a mark; LiveKit has no such signal, so playback counts as
+    /// finished once it has been handed over.
+    async fn reply_done(&mut self) {
+        self.carry = None;
+        match (&self.protocol, &self.stream_sid) {
+            (MediaProtocol::Twilio, Some(sid)) => {
+                self.marks += 1;
+                let mark = serde_json::json!({ "event": "mark", "streamSid": sid, "mark": { "name": format!("reply-{}", self.marks) } });
+                self.send(CallFrame::Text(mark.to_string())).await;
+            }
+            _ => self.session.playback_finished(),
+        }
+    }
+
+    /// Drop audio the caller talked over.
+    async fn clear(&mut self) {
+        self.carry = None;
+        if let (MediaProtocol::Twilio, Some(sid)) = (&self.protocol, &self.stream_sid) {
+            self.send(CallFrame::Text(serde_json::json!({ "event": "clear", "streamSid": sid }).to_string())).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stt::SttTranscript;
+
+    struct FixedStt;
+
+    #[async_trait]
+    impl SttProvider for FixedStt {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn transcribe(&self, _req: SttRequest) -> Result<SttTranscript> {
+            Ok(SttTranscript { text: "What's the weather like?".into(), ..Default::default() })
+        }
+    }
+
+    /// Two bytes of silence per character, so reply length is audible.
+    struct SilentTts;
+
+    #[async_trait]
+    impl TtsProvider for SilentTts {
+        async fn synthesize(&self, req: TtsRequest) -> Result<Bytes> {
+            Ok(Bytes::from(vec![0u8; req.text.len() * 2]))
+        }
+    }
+
+    struct Forecaster;
+
+    #[async_trait]
+    impl CallAgent for Forecaster {
+        async fn respond(&self, _call: &CallInfo, transcript: &str, reply: mpsc::Sender<String>) -> Result<()> {
+            assert_eq!(transcript, "What's the weather like?");
+            reply.send("Sunny all day, ".into()).await?;
+            reply.send("with a light breeze.".into()).await?;
+            Ok(())
+        }
+    }
+
+    fn media(sample: i16) -> CallFrame {
+        let payload = general_purpose::STANDARD.encode(vec![mulaw_encode(sample); 160]);
+        CallFrame::Text(serde_json::json!({ "event": "media", "streamSid": "MZ1", "media": { "payload": payload } }).to_string())
+    }
+
+    #[test]
+    fn phone_numbers_must_be_e164() {
+        assert!(is_e164("+15550100999"));
+        assert!(!is_e164("15550100999"));
+        assert!(!is_e164("+1555"));
+        assert!(!is_e164("+1555010099a"));
+    }
+
+    #[test]
+    fn mulaw_round_trips() {
+        for byte in 0..=255u8 {
+            let decoded = mulaw_decode(byte);
+            assert_eq!(mulaw_decode(mulaw_encode(decoded)), decoded, "byte {:#x}", byte);
+        }
+        assert!((mulaw_decode(mulaw_encode(8_000)) - 8_000).abs() < 300);
+        assert_eq!(resample(&[0, 100, 200, 300, 400, 500], 24_000, 8_000), vec![100, 400]);
+        assert_eq!(resample(&[0, 100], 8_000, 16_000), vec![0, 50, 100, 100]);
+    }
+
+    #[tokio::test]
+    async fn twilio_call_answers_a_turn_and_reports_lifecycle() {
+        let (events_tx, mut events_rx) = mpsc::channel(16);
+        let bridge = CallBridge::new(Arc::new(FixedStt), Arc::new(SilentTts), Arc::new(Forecaster)).with_events(events_tx, Uuid::new_v4());
+        let token = bridge.issue_call_token();
+        let (in_tx, in_rx) = mpsc::channel(128);
+        let (out_tx, mut out_rx) = mpsc::channel(128);
+        let call = tokio::spawn(async move { bridge.run(MediaProtocol::Twilio, None, in_rx, out_tx).await });
+
+        let start = serde_json::json!({
+            "event": "start",
+            "streamSid": "MZ1",
+            "start": { "streamSid": "MZ1", "callSid": "CA1", "customParameters": { "token": token, "from": "+15550100" } }
+        });
+        in_tx.send(CallFrame::Text(start.to_string())).await.unwrap();
+        for _ in 0..10 {
+            in_tx.send(media(8_000)).await.unwrap();
+        }
+        for _ in 0..40 {
+            in_tx.send(media(0)).await.unwrap();
+        }
+
+        let mut media_frames = 0;
+        loop {
+            let CallFrame::Text(text) = out_rx.recv().await.unwrap() else { panic!("Twilio frames are text") };
+            let frame: serde_json::Value = serde_json::from_str(&text).unwrap();
+            match frame["event"].as_str() {
+                Some("media") => media_frames += 1,
+                Some("mark") => {
+                    assert_eq!(frame["mark"]["name"], "reply-1");
+                    break;
+                }
+                other => panic!("unexpected frame {:?}", other),
+            }
+        }
+        assert!(media_frames > 0);
+        in_tx.send(CallFrame::Text(r#"{"event": "stop", "streamSid": "MZ1"}"#.into())).await.unwrap();
+
+        let summary = call.await.unwrap().unwrap();
+        assert_eq!(summary.call_id, "CA1");
+        assert_eq!(summary.from, "+15550100");
+        let mut kinds = Vec::new();
+        while let Ok(Message::AuditEvent(payload)) = events_rx.try_recv() {
+            kinds.push((payload.event.kind, payload.event.payload["role"].as_str().map(str::to_string)));
+        }
+        assert_eq!(
+            kinds,
+            vec![
+                (EventKind::CallStarted, None),
+                (EventKind::CallTurn, Some("caller".into())),
+                (EventKind::CallTurn, Some("agent".into())),
+                (EventKind::CallEnded, None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn call_tokens_are_single_use() {
+        let bridge = CallBridge::new(Arc::new(FixedStt), Arc::new(SilentTts), Arc::new(Forecaster)).with_stream_token("s3cret");
+        let token = bridge.issue_call_token();
+        let mut started = Vec::new();
+        // The shared LiveKit token does not open Twilio streams, and a call
+        // token only opens one.
+        for presented in ["guess", "s3cret", token.as_str(), token.as_str()] {
+            let (in_tx, in_rx) = mpsc::channel(4);
+            let (out_tx, _out_rx) = mpsc::channel(4);
+            let start = serde_json::json!({
+                "event": "start",
+                "start": { "streamSid": "MZ1", "callSid": "CA1", "customParameters": { "token": presented } }
+            });
+            in_tx.send(CallFrame::Text(start.to_string())).await.unwrap();
+            drop(in_tx);
+            started.push(bridge.run(MediaProtocol::Twilio, None, in_rx, out_tx).await.is_ok());
+        }
+        assert_eq!(started, vec![false, false, true, false]);
+    }
+
+    #[test]
+    fn livekit_streams_need_the_configured_token() {
+        let open = CallBridge::new(Arc::new(FixedStt), Arc::new(SilentTts), Arc::new(Forecaster));
+        assert!(open.authorize(None).is_err());
+        assert!(open.authorize(Some("")).is_err());
+
+        let bridge = CallBridge::new(Arc::new(FixedStt), Arc::new(SilentTts), Arc::new(Forecaster)).with_stream_token("s3cret");
+        assert!(bridge.authorize(None).is_err());
+        assert!(bridge.authorize(Some("s3cre")).is_err());
+        assert!(bridge.authorize(Some("S3CRET")).is_err());
+        assert!(bridge.authorize(Some("s3cret")).is_ok());
     }
 }
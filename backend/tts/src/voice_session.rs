@@ -0,0 +1,372 @@
+/// Real-time voice session — voice activity detection and barge-in.
+///
+/// Audio arrives as short PCM frames (10–30 ms). An energy-based VAD decides
+/// when the user starts and stops talking. If the user starts talking while
+/// the agent is thinking or speaking, the in-flight synthesis/playback task is
+/// aborted and the session goes straight back to capturing the user's turn.
+/// With a wake gate, audio is ignored until the node hears its wake word.
+use bytes::Bytes;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+use tracing::{debug, info, warn};
+
+use crate::engine::{synthesize_stream, TtsProvider, TtsRequest};
+use crate::wake_word::{WakeGate, WakeGateOutput};
+
+// ---------------------------------------------------------------------------
+// VAD
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct VadConfig {
+    /// Frames louder than this (dBFS RMS) count as speech.
+    pub threshold_dbfs: f32,
+    /// Consecutive speech frames needed to report the start of speech.
+    pub start_frames: usize,
+    /// Consecutive quiet frames needed to report the end of speech.
+    pub end_frames: usize,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        // With 20 ms frames: 60 ms to trigger, 600 ms of silence to end a turn.
+        Self { threshold_dbfs: -40.0, start_frames: 3, end_frames: 30 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VadEvent {
+    SpeechStart,
+    SpeechEnd,
+}
+
+/// Energy-based voice activity detector with start/end hysteresis.
+#[derive(Debug, Clone, Default)]
+pub struct Vad {
+    config: VadConfig,
+    speaking: bool,
+    run: usize,
+}
+
+impl Vad {
+    pub fn new(config: VadConfig) -> Self {
+        Self { config, speaking: false, run: 0 }
+    }
+
+    pub fn is_speaking(&self) -> bool {
+        self.speaking
+    }
+
+    /// Feed one frame of 16-bit PCM; returns a transition if one happened.
+    pub fn process_frame(&mut self, frame: &[i16]) -> Option<VadEvent> {
+        let loud = frame_dbfs(frame) >= self.config.threshold_dbfs;
+        // Count consecutive frames that disagree with the current state.
+        if loud != self.speaking {
+            self.run += 1;
+        } else {
+            self.run = 0;
+        }
+        let needed = if self.speaking { self.config.end_frames } else { self.config.start_frames };
+        if self.run < needed.max(1) {
+            return None;
+        }
+        self.run = 0;
+        self.speaking = loud;
+        Some(if loud { VadEvent::SpeechStart } else { VadEvent::SpeechEnd })
+    }
+}
+
+fn frame_dbfs(frame: &[i16]) -> f32 {
+    if frame.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+    let mean_sq = frame.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / frame.len() as f64;
+    (20.0 * (mean_sq.sqrt() / i16::MAX as f64).log10()) as f32
+}
+
+// ---------------------------------------------------------------------------
+// Session
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceState {
+    /// Waiting for the user to talk.
+    Listening,
+    /// Capturing the user's turn.
+    UserSpeaking,
+    /// User finished; waiting for the agent's reply.
+    Thinking,
+    /// Synthesizing or playing the agent's reply.
+    Speaking,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VoiceEvent {
+    /// The wake word was heard; the session now listens for a turn.
+    WakeWord(String),
+    SpeechStarted,
+    /// The user interrupted the agent; its reply was cancelled and any
+    /// audio already queued for playback should be dropped.
+    BargeIn,
+    /// A complete user turn, including the frames that triggered the VAD.
+    Utterance(Vec<i16>),
+}
+
+pub struct VoiceSession {
+    vad: Vad,
+    state: VoiceState,
+    /// Recent frames kept so the start of an utterance is not clipped.
+    preroll: VecDeque<Vec<i16>>,
+    utterance: Vec<i16>,
+    playback: Option<AbortHandle>,
+    wake: Option<WakeGate>,
+}
+
+impl VoiceSession {
+    pub fn new(config: VadConfig) -> Self {
+        Self {
+            vad: Vad::new(config),
+            state: VoiceState::Listening,
+            preroll: VecDeque::new(),
+            utterance: Vec::new(),
+            playback: None,
+            wake: None,
+        }
+    }
+
+    /// Only process audio after the node's wake word. The gate stays open
+    /// during a conversation and for its window after the last turn.
+    pub fn with_wake_gate(mut self, gate: WakeGate) -> Self {
+        self.wake = Some(gate);
+        self
+    }
+
+    pub fn state(&self) -> VoiceState {
+        self.state
+    }
+
+    /// The transport finished playing the reply to the user.
+    pub fn playback_finished(&mut self) {
+        self.playback = None;
+        if self.state == VoiceState::Speaking {
+            self.state = VoiceState::Listening;
+        }
+    }
+
+    /// Abort in-flight synthesis and playback.
+    pub fn cancel_speech(&mut self) -> bool {
+        match self.playback.take() {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Feed one microphone frame and get back what it changed.
+    pub fn on_audio_frame(&mut self, frame: &[i16]) -> Vec<VoiceEvent> {
+        let mut events = Vec::new();
+
+        if let Some(gate) = &mut self.wake {
+            if self.state != VoiceState::Listening {
+                gate.keep_awake();
+            } else {
+                match gate.on_frame(frame) {
+                    WakeGateOutput::Dormant => return events,
+                    WakeGateOutput::Woke(word) => {
+                        // Frames before the wake word must not leak into the turn.
+                        self.preroll.clear();
+                        events.push(VoiceEvent::WakeWord(word));
+                        return events;
+                    }
+                    WakeGateOutput::Active => {}
+                }
+            }
+        }
+
+        match self.vad.process_frame(frame) {
+            Some(VadEvent::SpeechStart) => {
+                if matches!(self.state, VoiceState::Speaking | VoiceState::Thinking) {
+                    self.cancel_speech();
+                    info!("[Voice] Barge-in: user interrupted while {:?}", self.state);
+                    events.push(VoiceEvent::BargeIn);
+                }
+                self.state = VoiceState::UserSpeaking;
+                self.utterance = self.preroll.drain(..).flatten().collect();
+                events.push(VoiceEvent::SpeechStarted);
+            }
+            Some(VadEvent::SpeechEnd) if self.state == VoiceState::UserSpeaking => {
+                self.utterance.extend_from_slice(frame);
+                self.state = VoiceState::Thinking;
+                debug!("[Voice] Utterance of {} samples complete", self.utterance.len());
+                events.push(VoiceEvent::Utterance(std::mem::take(&mut self.utterance)));
+                return events;
+            }
+            _ => {}
+        }
+
+        if self.state == VoiceState::UserSpeaking {
+            self.utterance.extend_from_slice(frame);
+        } else {
+            self.preroll.push_back(frame.to_vec());
+            while self.preroll.len() >= self.vad.config.start_frames {
+                self.preroll.pop_front();
+            }
+        }
+        events
+    }
+
+    /// Synthesize `req` and send the audio to `sink`, replacing any reply
+    /// already in progress. Barge-in aborts the task, which drops the
+    /// provider request mid-flight. The session stays `Speaking` until
+    /// `playback_finished`, so interrupting audio that is already playing
+    /// still yields `BargeIn` and the transport can flush its output.
+    pub fn speak(&mut self, provider: Arc<dyn TtsProvider>, req: TtsRequest, sink: mpsc::Sender<Bytes>) {
+        self.cancel_speech();
+        self.state = VoiceState::Speaking;
+        let task = tokio::spawn(async move {
+            match provider.synthesize(req).await {
+                Ok(audio) => {
+                    let _ = sink.send(audio).await;
+                }
+                Err(e) => warn!("[Voice] Synthesis failed: {:#}", e),
+            }
+        });
+        self.playback = Some(task.abort_handle());
+    }
+
+    /// Speak a reply as it streams in: `text` carries the agent's token
+    /// deltas and audio reaches `sink` sentence by sentence. Barge-in
+    /// aborts it like `speak`.
+    pub fn speak_stream(
+        &mut self,
+        provider: Arc<dyn TtsProvider>,
+        template: TtsRequest,
+        text: mpsc::Receiver<String>,
+        sink: mpsc::Sender<Bytes>,
+    ) {
+        self.cancel_speech();
+        self.state = VoiceState::Speaking;
+        let stream = synthesize_stream(provider, template, text, sink);
+        self.playback = Some(stream.abort_handle());
+        tokio::spawn(async move {
+            if let Ok(Err(e)) = stream.await {
+                warn!("[Voice] Streaming synthesis failed: {:#}", e);
+            }
+        });
+    }
+
+    /// The agent decided not to reply; go back to listening.
+    pub fn finish_turn(&mut self) {
+        if self.state == VoiceState::Thinking {
+            self.state = VoiceState::Listening;
+        }
+    }
+}
+
+impl Drop for VoiceSession {
+    fn drop(&mut self) {
+        self.cancel_speech();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use async_trait::async_trait;
+
+    const LOUD: [i16; 320] = [8_000; 320];
+    const QUIET: [i16; 320] = [0; 320];
+
+    struct SlowTts;
+
+    #[async_trait]
+    impl TtsProvider for SlowTts {
+        async fn synthesize(&self, _req: TtsRequest) -> Result<Bytes> {
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+            Ok(Bytes::new())
+        }
+    }
+
+    /// Speaks each request's text back as its audio.
+    struct EchoTts;
+
+    #[async_trait]
+    impl TtsProvider for EchoTts {
+        async fn synthesize(&self, req: TtsRequest) -> Result<Bytes> {
+            Ok(Bytes::from(req.text))
+        }
+    }
+
+    fn session() -> VoiceSession {
+        VoiceSession::new(VadConfig { start_frames: 2, end_frames: 3, ..Default::default() })
+    }
+
+    #[test]
+    fn captures_one_utterance_with_preroll() {
+        let mut s = session();
+        assert!(s.on_audio_frame(&QUIET).is_empty());
+        assert!(s.on_audio_frame(&LOUD).is_empty());
+        assert_eq!(s.on_audio_frame(&LOUD), vec![VoiceEvent::SpeechStarted]);
+        s.on_audio_frame(&QUIET);
+        s.on_audio_frame(&QUIET);
+        let events = s.on_audio_frame(&QUIET);
+        let [VoiceEvent::Utterance(audio)] = &events[..] else {
+            panic!("expected utterance, got {:?}", events);
+        };
+        assert_eq!(audio.len(), 5 * 320);
+        assert_eq!(s.state(), VoiceState::Thinking);
+    }
+
+    #[test]
+    fn wake_gate_drops_audio_until_wake_word() {
+        struct WakeOnSecondFrame(usize);
+        impl crate::wake_word::WakeWordDetector for WakeOnSecondFrame {
+            fn process_frame(&mut self, _frame: &[i16]) -> Result<Option<String>> {
+                self.0 += 1;
+                Ok((self.0 == 2).then(|| "hey_claw".to_string()))
+            }
+        }
+        let gate = WakeGate::new(Box::new(WakeOnSecondFrame(0)), &["hey claw".into()], 5);
+        let mut s = session().with_wake_gate(gate);
+        assert!(s.on_audio_frame(&LOUD).is_empty());
+        assert_eq!(s.on_audio_frame(&LOUD), vec![VoiceEvent::WakeWord("hey_claw".into())]);
+        assert!(s.on_audio_frame(&LOUD).is_empty());
+        assert_eq!(s.on_audio_frame(&LOUD), vec![VoiceEvent::SpeechStarted]);
+    }
+
+    #[tokio::test]
+    async fn speech_while_speaking_cancels_reply() {
+        let mut s = session();
+        let (tx, mut rx) = mpsc::channel(1);
+        s.speak(Arc::new(SlowTts), TtsRequest::default(), tx);
+        assert_eq!(s.state(), VoiceState::Speaking);
+
+        s.on_audio_frame(&LOUD);
+        let events = s.on_audio_frame(&LOUD);
+        assert_eq!(events, vec![VoiceEvent::BargeIn, VoiceEvent::SpeechStarted]);
+        assert_eq!(s.state(), VoiceState::UserSpeaking);
+        // The aborted task dropped its sender without sending audio.
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn streamed_reply_is_spoken_sentence_by_sentence() {
+        let mut s = session();
+        let (text_tx, text_rx) = mpsc::channel(8);
+        let (audio_tx, mut audio_rx) = mpsc::channel(8);
+        s.speak_stream(Arc::new(EchoTts), TtsRequest::default(), text_rx, audio_tx);
+
+        text_tx.send("Sure, e.g. the first one works. Then".into()).await.unwrap();
+        assert_eq!(audio_rx.recv().await.unwrap(), "Sure, for example the first one works.");
+        text_tx.send(" run **it**! Done".into()).await.unwrap();
+        assert_eq!(audio_rx.recv().await.unwrap(), "Then run it!");
+        drop(text_tx);
+        assert_eq!(audio_rx.recv().await.unwrap(), "Done");
+        assert!(audio_rx.recv().await.is_none());
+    }
+}
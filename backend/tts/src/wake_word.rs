@@ -0,0 +1,275 @@
+/// Wake word gating for always-on voice nodes.
+///
+/// A companion node with a microphone should not stream everything it hears
+/// to STT. The wake gate keeps the session dormant until a local detector
+/// hears one of the node's wake words ("hey claw"), then lets audio through
+/// for `active_secs` after the last speech before going dormant again.
+///
+/// Detection runs in a local runner process so the ONNX / Porcupine runtimes
+/// stay out of this crate. Runners read 16 kHz mono s16le PCM on stdin and
+/// print the name of each detected keyword on its own line of stdout.
+use anyhow::{bail, Context, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Sample rate runners expect.
+pub const WAKE_SAMPLE_RATE: u32 = 16_000;
+
+/// Anything that turns PCM frames into wake word detections.
+pub trait WakeWordDetector: Send {
+    /// Feed one 16 kHz mono frame; returns the keyword if one was detected.
+    fn process_frame(&mut self, frame: &[i16]) -> Result<Option<String>>;
+}
+
+// ---------------------------------------------------------------------------
+// Engines
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub enum WakeWordEngineKind {
+    /// openWakeWord ONNX models, one `<word>.onnx` per wake word.
+    OpenWakeWord { runner: PathBuf, models: Vec<PathBuf>, threshold: f32 },
+    /// Picovoice Porcupine, one `<word>.ppn` keyword file per wake word.
+    Porcupine { runner: PathBuf, access_key: String, keyword_paths: Vec<PathBuf>, sensitivity: f32 },
+}
+
+impl WakeWordEngineKind {
+    /// Build from `talk.wake` settings. Model files are looked up in
+    /// `model_dir` by wake word, with spaces replaced by underscores.
+    pub fn from_settings(
+        engine: &str,
+        words: &[String],
+        model_dir: Option<PathBuf>,
+        sensitivity: Option<f32>,
+        access_key: Option<String>,
+        runner: Option<PathBuf>,
+    ) -> Result<Self> {
+        if words.is_empty() {
+            bail!("Wake word engine '{}' needs at least one wake word", engine);
+        }
+        let model_dir = model_dir.unwrap_or_else(|| PathBuf::from("wakewords"));
+        let files = |ext: &str| words.iter().map(|w| model_dir.join(format!("{}.{}", normalize_word(w), ext))).collect();
+        Ok(match engine {
+            "openwakeword" => WakeWordEngineKind::OpenWakeWord {
+                runner: runner.unwrap_or_else(|| PathBuf::from("openwakeword-runner")),
+                models: files("onnx"),
+                threshold: sensitivity.unwrap_or(0.5),
+            },
+            "porcupine" => WakeWordEngineKind::Porcupine {
+                runner: runner.unwrap_or_else(|| PathBuf::from("porcupine-runner")),
+                access_key: access_key.context("Wake word engine 'porcupine' needs an access key")?,
+                keyword_paths: files("ppn"),
+                sensitivity: sensitivity.unwrap_or(0.5),
+            },
+            other => bail!("Unknown wake word engine '{}'", other),
+        })
+    }
+
+    fn command(&self) -> Command {
+        match self {
+            WakeWordEngineKind::OpenWakeWord { runner, models, threshold } => {
+                let mut cmd = Command::new(runner);
+                for model in models {
+                    cmd.arg("--model").arg(model);
+                }
+                cmd.arg("--threshold").arg(threshold.to_string());
+                cmd
+            }
+            WakeWordEngineKind::Porcupine { runner, access_key, keyword_paths, sensitivity } => {
+                let mut cmd = Command::new(runner);
+                cmd.arg("--access-key").arg(access_key);
+                for path in keyword_paths {
+                    cmd.arg("--keyword-path").arg(path).arg("--sensitivity").arg(sensitivity.to_string());
+                }
+                cmd
+            }
+        }
+    }
+}
+
+/// `"Hey Claw"` and `hey_claw.onnx` both name the `hey_claw` keyword.
+fn normalize_word(word: &str) -> String {
+    let stem = word.trim().rsplit(['/', '\\']).next().unwrap_or(word);
+    let stem = stem.strip_suffix(".onnx").or_else(|| stem.strip_suffix(".ppn")).unwrap_or(stem);
+    stem.to_lowercase().split([' ', '-', '_']).filter(|s| !s.is_empty()).collect::<Vec<_>>().join("_")
+}
+
+/// Detector backed by a runner process speaking the stdin/stdout protocol.
+pub struct ProcessWakeWord {
+    child: Child,
+    stdin: ChildStdin,
+    detections: mpsc::Receiver<String>,
+}
+
+impl ProcessWakeWord {
+    pub fn spawn(kind: &WakeWordEngineKind) -> Result<Self> {
+        let mut cmd = kind.command();
+        let program = cmd.get_program().to_string_lossy().into_owned();
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start wake word runner '{}'", program))?;
+        let stdin = child.stdin.take().context("wake word runner has no stdin")?;
+        let stdout = child.stdout.take().context("wake word runner has no stdout")?;
+
+        let (tx, detections) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(|l| l.ok()) {
+                let word = line.trim();
+                if !word.is_empty() && tx.send(word.to_string()).is_err() {
+                    break;
+                }
+            }
+        });
+        info!("[Wake] Started runner '{}'", program);
+        Ok(Self { child, stdin, detections })
+    }
+}
+
+impl WakeWordDetector for ProcessWakeWord {
+    fn process_frame(&mut self, frame: &[i16]) -> Result<Option<String>> {
+        let bytes: Vec<u8> = frame.iter().flat_map(|s| s.to_le_bytes()).collect();
+        self.stdin.write_all(&bytes).context("wake word runner exited")?;
+        Ok(self.detections.try_recv().ok())
+    }
+}
+
+impl Drop for ProcessWakeWord {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Gate
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WakeGateOutput {
+    /// Still dormant: drop the frame.
+    Dormant,
+    /// A wake word was just heard; audio flows from the next frame on.
+    Woke(String),
+    /// Awake: pass the frame on to VAD / STT.
+    Active,
+}
+
+/// Gates microphone audio on a wake word, per node.
+pub struct WakeGate {
+    detector: Box<dyn WakeWordDetector>,
+    /// Normalized wake words this node answers to; other keywords are ignored.
+    words: Vec<String>,
+    active_samples: usize,
+    /// Samples left before going dormant; zero while dormant.
+    remaining: usize,
+}
+
+impl WakeGate {
+    /// `active_secs` is how long the node stays awake after the wake word or
+    /// the end of the last utterance.
+    pub fn new(detector: Box<dyn WakeWordDetector>, words: &[String], active_secs: u32) -> Self {
+        Self {
+            detector,
+            words: words.iter().map(|w| normalize_word(w)).collect(),
+            active_samples: (active_secs * WAKE_SAMPLE_RATE) as usize,
+            remaining: 0,
+        }
+    }
+
+    pub fn is_awake(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Restart the awake window, e.g. while the user or agent is talking.
+    pub fn keep_awake(&mut self) {
+        self.remaining = self.active_samples.max(1);
+    }
+
+    pub fn sleep(&mut self) {
+        self.remaining = 0;
+    }
+
+    pub fn on_frame(&mut self, frame: &[i16]) -> WakeGateOutput {
+        if self.remaining > 0 {
+            self.remaining = self.remaining.saturating_sub(frame.len());
+            if self.remaining == 0 {
+                debug!("[Wake] Awake window elapsed, going dormant");
+            }
+            return WakeGateOutput::Active;
+        }
+        match self.detector.process_frame(frame) {
+            Ok(Some(word)) => {
+                let word = normalize_word(&word);
+                if self.words.is_empty() || self.words.contains(&word) {
+                    info!("[Wake] Heard '{}'", word);
+                    self.keep_awake();
+                    WakeGateOutput::Woke(word)
+                } else {
+                    debug!("[Wake] Ignoring keyword '{}' not configured for this node", word);
+                    WakeGateOutput::Dormant
+                }
+            }
+            Ok(None) => WakeGateOutput::Dormant,
+            Err(e) => {
+                warn!("[Wake] Detector failed: {:#}", e);
+                WakeGateOutput::Dormant
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Detects `word` on the n-th frame.
+    struct Scripted {
+        word: &'static str,
+        at: usize,
+        seen: usize,
+    }
+
+    impl WakeWordDetector for Scripted {
+        fn process_frame(&mut self, _frame: &[i16]) -> Result<Option<String>> {
+            self.seen += 1;
+            Ok((self.seen == self.at).then(|| self.word.to_string()))
+        }
+    }
+
+    #[test]
+    fn normalizes_wake_words() {
+        assert_eq!(normalize_word("Hey Claw"), "hey_claw");
+        assert_eq!(normalize_word("models/hey-claw.onnx"), "hey_claw");
+    }
+
+    #[test]
+    fn gate_opens_on_configured_word_then_times_out() {
+        let detector = Box::new(Scripted { word: "hey_claw", at: 2, seen: 0 });
+        let mut gate = WakeGate::new(detector, &["Hey Claw".to_string()], 1);
+        let frame = [0i16; 8_000];
+        assert_eq!(gate.on_frame(&frame), WakeGateOutput::Dormant);
+        assert_eq!(gate.on_frame(&frame), WakeGateOutput::Woke("hey_claw".into()));
+        assert_eq!(gate.on_frame(&frame), WakeGateOutput::Active);
+        assert_eq!(gate.on_frame(&frame), WakeGateOutput::Active);
+        assert_eq!(gate.on_frame(&frame), WakeGateOutput::Dormant);
+
+        let other = Box::new(Scripted { word: "alexa", at: 1, seen: 0 });
+        let mut gate = WakeGate::new(other, &["hey claw".to_string()], 1);
+        assert_eq!(gate.on_frame(&frame), WakeGateOutput::Dormant);
+    }
+
+    #[test]
+    fn builds_engine_model_paths() {
+        let kind = WakeWordEngineKind::from_settings("openwakeword", &["Hey Claw".into()], Some("/m".into()), None, None, None)
+            .unwrap();
+        let WakeWordEngineKind::OpenWakeWord { models, .. } = kind else { panic!() };
+        assert_eq!(models, vec![PathBuf::from("/m/hey_claw.onnx")]);
+        assert!(WakeWordEngineKind::from_settings("porcupine", &["x".into()], None, None, None, None).is_err());
+    }
+}
@@ -1,12 +1,19 @@
 /// Audio understanding — transcribe audio using STT providers.
 ///
 /// Mirrors `src/media-understanding/providers/deepgram.ts` from OpenClaw.
-use anyhow::{bail, Result};
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
+use crate::stt::WhisperCpp;
+
 pub enum AudioProvider {
     Whisper { api_key: String },
     Deepgram { api_key: String },
+    /// Offline whisper.cpp; `language: None` auto-detects.
+    Local { binary: PathBuf, model: PathBuf, language: Option<String> },
 }
 
 impl AudioProvider {
@@ -16,25 +23,143 @@ impl AudioProvider {
     pub fn deepgram(api_key: impl Into<String>) -> Self {
         Self::Deepgram { api_key: api_key.into() }
     }
+    /// whisper.cpp with `binary` (usually `whisper-cli`) and a GGML model file.
+    pub fn local(binary: impl Into<PathBuf>, model: impl Into<PathBuf>) -> Self {
+        Self::Local { binary: binary.into(), model: model.into(), language: None }
+    }
+}
+
+/// A stretch of speech with its position in the recording.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Speaker label when diarization ran, e.g. `Speaker 1` or `SPEAKER_00`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+/// Result of transcribing one recording.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Transcript {
+    pub text: String,
+    /// Language reported by the provider, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub segments: Vec<TranscriptSegment>,
+}
+
+impl Transcript {
+    /// Render for the session context: one timestamped line per speaker turn,
+    /// e.g. `[01:05] Speaker 2: Let's ship it.` Consecutive segments from the
+    /// same speaker are merged. Falls back to `text` without segments.
+    pub fn to_context(&self) -> String {
+        if self.segments.is_empty() {
+            return self.text.clone();
+        }
+        let mut lines: Vec<(u64, Option<&str>, String)> = Vec::new();
+        for seg in &self.segments {
+            let text = seg.text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            match lines.last_mut() {
+                Some((_, speaker, line)) if seg.speaker.is_some() && *speaker == seg.speaker.as_deref() => {
+                    line.push(' ');
+                    line.push_str(text);
+                }
+                _ => lines.push((seg.start_ms, seg.speaker.as_deref(), text.to_string())),
+            }
+        }
+        lines
+            .into_iter()
+            .map(|(start, speaker, line)| match speaker {
+                Some(speaker) => format!("[{}] {}: {}", format_timestamp(start), speaker, line),
+                None => format!("[{}] {}", format_timestamp(start), line),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Distinct speaker labels in order of first appearance.
+    pub fn speakers(&self) -> Vec<&str> {
+        let mut speakers = Vec::new();
+        for speaker in self.segments.iter().filter_map(|s| s.speaker.as_deref()) {
+            if !speakers.contains(&speaker) {
+                speakers.push(speaker);
+            }
+        }
+        speakers
+    }
+}
+
+/// `mm:ss`, or `h:mm:ss` past the first hour.
+pub(crate) fn format_timestamp(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// Options for [`transcribe_audio_with`].
+#[derive(Debug, Clone, Default)]
+pub struct TranscribeOptions {
+    /// Ask for speaker labels. Deepgram diarizes natively; other providers
+    /// need `diarizer`.
+    pub diarize: bool,
+    /// Local diarization pass applied to providers that can't diarize.
+    pub diarizer: Option<LocalDiarizer>,
 }
 
-/// Transcribe audio bytes to text.
+/// Transcribe audio bytes into text plus timestamped segments.
 pub async fn transcribe_audio(
     provider: &AudioProvider,
     audio_bytes: Vec<u8>,
     mime_type: &str,
-) -> Result<String> {
-    match provider {
+) -> Result<Transcript> {
+    transcribe_audio_with(provider, audio_bytes, mime_type, &TranscribeOptions::default()).await
+}
+
+/// Like [`transcribe_audio`], optionally labelling segments with speakers.
+pub async fn transcribe_audio_with(
+    provider: &AudioProvider,
+    audio_bytes: Vec<u8>,
+    mime_type: &str,
+    options: &TranscribeOptions,
+) -> Result<Transcript> {
+    let local_pass = match (&options.diarizer, provider) {
+        (Some(diarizer), AudioProvider::Whisper { .. } | AudioProvider::Local { .. }) if options.diarize => {
+            Some((diarizer, audio_bytes.clone()))
+        }
+        _ => None,
+    };
+    let mut transcript = match provider {
         AudioProvider::Whisper { api_key } => {
-            transcribe_whisper(api_key, audio_bytes, mime_type).await
+            transcribe_whisper(api_key, audio_bytes, mime_type).await?
         }
         AudioProvider::Deepgram { api_key } => {
-            transcribe_deepgram(api_key, audio_bytes, mime_type).await
+            transcribe_deepgram(api_key, audio_bytes, mime_type, options.diarize).await?
         }
+        AudioProvider::Local { binary, model, language } => {
+            let mut whisper = WhisperCpp::new(binary, model);
+            if let Some(language) = language {
+                whisper = whisper.with_language(language);
+            }
+            whisper.transcribe(&audio_bytes, mime_type).await?
+        }
+    };
+    if let Some((diarizer, audio)) = local_pass {
+        let turns = diarizer.diarize(&audio, mime_type).await?;
+        assign_speakers(&mut transcript.segments, &turns);
     }
+    Ok(transcript)
 }
 
-async fn transcribe_whisper(api_key: &str, audio: Vec<u8>, mime: &str) -> Result<String> {
+async fn transcribe_whisper(api_key: &str, audio: Vec<u8>, mime: &str) -> Result<Transcript> {
     info!("[Audio] Transcribing via OpenAI Whisper");
     let ext = if mime.contains("mp3") { "mp3" } else { "wav" };
     let part = reqwest::multipart::Part::bytes(audio)
@@ -42,6 +167,8 @@ async fn transcribe_whisper(api_key: &str, audio: Vec<u8>, mime: &str) -> Result
         .mime_str(mime)?;
     let form = reqwest::multipart::Form::new()
         .text("model", "whisper-1")
+        .text("response_format", "verbose_json")
+        .text("timestamp_granularities[]", "segment")
         .part("file", part);
     let client = reqwest::Client::new();
     let resp = client
@@ -54,14 +181,38 @@ async fn transcribe_whisper(api_key: &str, audio: Vec<u8>, mime: &str) -> Result
         bail!("Whisper error: {}", resp.text().await.unwrap_or_default());
     }
     let json: serde_json::Value = resp.json().await?;
-    Ok(json["text"].as_str().unwrap_or("").to_string())
+    Ok(parse_whisper_verbose(&json))
+}
+
+/// OpenAI `verbose_json`: `segments[]` with `start`/`end` in seconds.
+fn parse_whisper_verbose(json: &serde_json::Value) -> Transcript {
+    let segments = json["segments"]
+        .as_array()
+        .map(|segments| {
+            segments
+                .iter()
+                .map(|s| TranscriptSegment {
+                    start_ms: seconds_to_ms(&s["start"]),
+                    end_ms: seconds_to_ms(&s["end"]),
+                    text: s["text"].as_str().unwrap_or("").trim().to_string(),
+                    speaker: None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Transcript {
+        text: json["text"].as_str().unwrap_or("").trim().to_string(),
+        language: json["language"].as_str().map(str::to_string),
+        segments,
+    }
 }
 
-async fn transcribe_deepgram(api_key: &str, audio: Vec<u8>, mime: &str) -> Result<String> {
-    info!("[Audio] Transcribing via Deepgram");
+async fn transcribe_deepgram(api_key: &str, audio: Vec<u8>, mime: &str, diarize: bool) -> Result<Transcript> {
+    info!("[Audio] Transcribing via Deepgram (diarize={})", diarize);
     let client = reqwest::Client::new();
     let resp = client
-        .post("https://api.deepgram.com/v1/listen?model=nova-2")
+        .post("https://api.deepgram.com/v1/listen")
+        .query(&[("model", "nova-2"), ("utterances", "true"), ("diarize", if diarize { "true" } else { "false" })])
         .header("Authorization", format!("Token {}", api_key))
         .header("Content-Type", mime)
         .body(audio)
@@ -71,8 +222,157 @@ async fn transcribe_deepgram(api_key: &str, audio: Vec<u8>, mime: &str) -> Resul
         bail!("Deepgram error: {}", resp.text().await.unwrap_or_default());
     }
     let json: serde_json::Value = resp.json().await?;
-    Ok(json["results"]["channels"][0]["alternatives"][0]["transcript"]
-        .as_str()
-        .unwrap_or("")
-        .to_string())
+    Ok(parse_deepgram(&json, diarize))
+}
+
+/// Deepgram `utterances[]`, whose zero-based `speaker` becomes `Speaker N`.
+fn parse_deepgram(json: &serde_json::Value, diarize: bool) -> Transcript {
+    let channel = &json["results"]["channels"][0];
+    let segments = json["results"]["utterances"]
+        .as_array()
+        .map(|utterances| {
+            utterances
+                .iter()
+                .map(|u| TranscriptSegment {
+                    start_ms: seconds_to_ms(&u["start"]),
+                    end_ms: seconds_to_ms(&u["end"]),
+                    text: u["transcript"].as_str().unwrap_or("").to_string(),
+                    speaker: u["speaker"]
+                        .as_u64()
+                        .filter(|_| diarize)
+                        .map(|n| format!("Speaker {}", n + 1)),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Transcript {
+        text: channel["alternatives"][0]["transcript"].as_str().unwrap_or("").to_string(),
+        language: channel["detected_language"].as_str().map(str::to_string),
+        segments,
+    }
+}
+
+fn seconds_to_ms(value: &serde_json::Value) -> u64 {
+    (value.as_f64().unwrap_or(0.0).max(0.0) * 1000.0).round() as u64
+}
+
+// ---------------------------------------------------------------------------
+// Local diarization
+// ---------------------------------------------------------------------------
+
+/// A span attributed to one speaker by a diarization pass.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpeakerTurn {
+    /// Seconds from the start of the recording.
+    pub start: f64,
+    pub end: f64,
+    pub speaker: String,
+}
+
+/// Runs a local diarization command (e.g. a pyannote script) as
+/// `<command> <args...> <audio file>`. It must print a JSON array of
+/// `{"start": 0.0, "end": 4.2, "speaker": "SPEAKER_00"}` turns to stdout.
+#[derive(Debug, Clone)]
+pub struct LocalDiarizer {
+    pub command: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl LocalDiarizer {
+    pub fn new(command: impl Into<PathBuf>) -> Self {
+        Self { command: command.into(), args: Vec::new() }
+    }
+
+    pub fn with_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub async fn diarize(&self, audio: &[u8], mime_type: &str) -> Result<Vec<SpeakerTurn>> {
+        let ext = mime_type.split('/').nth(1).and_then(|s| s.split(';').next()).unwrap_or("wav");
+        let input = std::env::temp_dir().join(format!("clawforge-diarize-{}.{}", uuid::Uuid::new_v4(), ext));
+        tokio::fs::write(&input, audio).await?;
+        info!("[Audio] Diarizing {} with {}", input.display(), self.command.display());
+        let output = tokio::process::Command::new(&self.command)
+            .args(&self.args)
+            .arg(&input)
+            .kill_on_drop(true)
+            .output()
+            .await;
+        let _ = tokio::fs::remove_file(&input).await;
+        let output = output.with_context(|| format!("Failed to run {}", self.command.display()))?;
+        if !output.status.success() {
+            bail!("diarizer exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr));
+        }
+        serde_json::from_slice(&output.stdout).context("Diarizer output is not a JSON array of speaker turns")
+    }
+}
+
+/// Label each segment with the speaker whose turns overlap it the most.
+/// Segments no turn overlaps keep their current label.
+pub fn assign_speakers(segments: &mut [TranscriptSegment], turns: &[SpeakerTurn]) {
+    for seg in segments {
+        let mut best: Option<(&str, u64)> = None;
+        for turn in turns {
+            let start = (turn.start * 1000.0).round() as u64;
+            let end = (turn.end * 1000.0).round() as u64;
+            let overlap = end.min(seg.end_ms).saturating_sub(start.max(seg.start_ms));
+            if overlap > best.map_or(0, |(_, o)| o) {
+                best = Some((turn.speaker.as_str(), overlap));
+            }
+        }
+        if let Some((speaker, _)) = best {
+            seg.speaker = Some(speaker.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(start_ms: u64, end_ms: u64, text: &str) -> TranscriptSegment {
+        TranscriptSegment { start_ms, end_ms, text: text.into(), speaker: None }
+    }
+
+    #[test]
+    fn deepgram_utterances_become_labelled_segments() {
+        let json = serde_json::json!({
+            "results": {
+                "channels": [{"alternatives": [{"transcript": "hi there hello"}]}],
+                "utterances": [
+                    {"start": 0.0, "end": 1.25, "transcript": "hi there", "speaker": 0},
+                    {"start": 1.5, "end": 2.0, "transcript": "hello", "speaker": 1}
+                ]
+            }
+        });
+        let transcript = parse_deepgram(&json, true);
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[0].end_ms, 1250);
+        assert_eq!(transcript.speakers(), vec!["Speaker 1", "Speaker 2"]);
+        assert!(parse_deepgram(&json, false).segments.iter().all(|s| s.speaker.is_none()));
+    }
+
+    #[test]
+    fn speakers_follow_largest_overlap() {
+        let mut segments = vec![seg(0, 4000, "a"), seg(4000, 9000, "b"), seg(20_000, 21_000, "c")];
+        let turns = vec![
+            SpeakerTurn { start: 0.0, end: 4.5, speaker: "SPEAKER_00".into() },
+            SpeakerTurn { start: 4.5, end: 10.0, speaker: "SPEAKER_01".into() },
+        ];
+        assign_speakers(&mut segments, &turns);
+        assert_eq!(segments[0].speaker.as_deref(), Some("SPEAKER_00"));
+        assert_eq!(segments[1].speaker.as_deref(), Some("SPEAKER_01"));
+        assert_eq!(segments[2].speaker, None);
+    }
+
+    #[test]
+    fn context_merges_consecutive_speaker_segments() {
+        let mut segments = vec![seg(0, 1000, "Morning."), seg(1000, 2000, "Shall we start?"), seg(65_000, 66_000, "Yes.")];
+        segments[0].speaker = Some("Speaker 1".into());
+        segments[1].speaker = Some("Speaker 1".into());
+        segments[2].speaker = Some("Speaker 2".into());
+        let transcript = Transcript { segments, ..Default::default() };
+        assert_eq!(transcript.to_context(), "[00:00] Speaker 1: Morning. Shall we start?\n[01:05] Speaker 2: Yes.");
+    }
 }
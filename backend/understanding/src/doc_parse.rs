@@ -2,9 +2,25 @@
 //!
 //! Exposes routines to crack open PDFs, docx, and csv files to aggregate
 //! structured context blocks for the agent's memory banks.
+//!
+//! PDFs go through poppler's `pdftotext`, falling back to OCR of rendered
+//! pages for scans. DOCX, XLSX and PPTX are zip archives of XML and are read
+//! directly. [`DocumentPipeline`] chunks the result, optionally indexes the
+//! chunks into memory and produces the summary injected into the session.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use clawforge_memory::{chunk_text, MemoryManager};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use tracing::{info, warn};
 
-use anyhow::Result;
-use tracing::info;
+use crate::ocr::OcrService;
 
 pub struct DocParser;
 
@@ -19,18 +35,753 @@ impl DocParser {
     /// Opens a PDF document to strip its layout and extract plain contiguous text.
     pub async fn parse_pdf(file_path: &str) -> Result<DocumentMetadata> {
         info!("Parsing PDF document: {}", file_path);
-        
-        // MOCK: using lopdf or pdf-extract
+        let bytes = tokio::fs::read(file_path).await.with_context(|| format!("Failed to read {}", file_path))?;
+        let doc = parse_pdf(&bytes, true).await?;
         Ok(DocumentMetadata {
-            page_count: 5,
-            title: Some("Mock PDF Title".into()),
-            extracted_text: "Mock parsed PDF text data".into(),
+            page_count: doc.page_count,
+            extracted_text: doc.text(),
+            title: doc.title,
         })
     }
 
     /// Opens an Office document (docx) and transforms it into plain markdown.
     pub async fn parse_docx(file_path: &str) -> Result<String> {
         info!("Parsing DOCX file: {}", file_path);
-        Ok("Mock DOCX content as markdown".into())
+        let bytes = tokio::fs::read(file_path).await.with_context(|| format!("Failed to read {}", file_path))?;
+        Ok(tokio::task::spawn_blocking(move || parse_docx(&bytes)).await??.to_markdown())
+    }
+}
+
+/// Document formats the pipeline understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+    Xlsx,
+    Pptx,
+    Csv,
+    Text,
+}
+
+impl DocumentKind {
+    /// From a MIME type, falling back to the file extension for the generic
+    /// types channels often report (`application/octet-stream`, `application/zip`).
+    pub fn detect(mime_type: &str, file_name: Option<&str>) -> Option<Self> {
+        let by_mime = match mime_type.split(';').next().unwrap_or(mime_type).trim() {
+            "application/pdf" => Some(Self::Pdf),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => Some(Self::Docx),
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => Some(Self::Xlsx),
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation" => Some(Self::Pptx),
+            "text/csv" => Some(Self::Csv),
+            "text/plain" | "text/markdown" => Some(Self::Text),
+            _ => None,
+        };
+        by_mime.or_else(|| {
+            let ext = Path::new(file_name?).extension()?.to_str()?.to_ascii_lowercase();
+            match ext.as_str() {
+                "pdf" => Some(Self::Pdf),
+                "docx" => Some(Self::Docx),
+                "xlsx" => Some(Self::Xlsx),
+                "pptx" => Some(Self::Pptx),
+                "csv" => Some(Self::Csv),
+                "txt" | "md" => Some(Self::Text),
+                _ => None,
+            }
+        })
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Pdf => "PDF",
+            Self::Docx => "DOCX",
+            Self::Xlsx => "XLSX",
+            Self::Pptx => "PPTX",
+            Self::Csv => "CSV",
+            Self::Text => "text",
+        }
+    }
+
+    fn unit(&self) -> &'static str {
+        match self {
+            Self::Pdf => "page",
+            Self::Xlsx => "sheet",
+            Self::Pptx => "slide",
+            _ => "section",
+        }
+    }
+}
+
+/// A heading-delimited part of a document: a DOCX heading, PDF page, sheet or slide.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DocumentSection {
+    pub heading: Option<String>,
+    /// Heading depth (1 = top level); 0 for untitled sections.
+    pub level: u8,
+    pub text: String,
+}
+
+/// Text and structure extracted from one document.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParsedDocument {
+    pub kind: DocumentKind,
+    pub title: Option<String>,
+    /// Pages, sheets or slides; sections for flowing text.
+    pub page_count: usize,
+    pub sections: Vec<DocumentSection>,
+    /// Whether the text came from OCR of rendered pages.
+    pub ocr: bool,
+}
+
+impl ParsedDocument {
+    fn new(kind: DocumentKind) -> Self {
+        Self { kind, title: None, page_count: 0, sections: Vec::new(), ocr: false }
+    }
+
+    /// All section text, separated by blank lines.
+    pub fn text(&self) -> String {
+        self.sections.iter().map(|s| s.text.trim()).filter(|t| !t.is_empty()).collect::<Vec<_>>().join("\n\n")
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for section in &self.sections {
+            if let Some(heading) = &section.heading {
+                out.push_str(&"#".repeat(section.level.clamp(1, 6) as usize));
+                out.push(' ');
+                out.push_str(heading);
+                out.push_str("\n\n");
+            }
+            if !section.text.trim().is_empty() {
+                out.push_str(section.text.trim());
+                out.push_str("\n\n");
+            }
+        }
+        out.trim_end().to_string()
+    }
+
+    /// Short description for the session: kind, size, outline and an excerpt
+    /// of at most `max_chars` characters.
+    pub fn summary(&self, name: Option<&str>, max_chars: usize) -> String {
+        let unit = self.kind.unit();
+        let mut out = format!(
+            "[Document: {} ({}, {} {}{}{})]",
+            self.title.as_deref().or(name).unwrap_or("untitled"),
+            self.kind.label(),
+            self.page_count,
+            unit,
+            if self.page_count == 1 { "" } else { "s" },
+            if self.ocr { ", OCR" } else { "" },
+        );
+        let outline: Vec<&str> = self
+            .sections
+            .iter()
+            .filter(|s| s.level <= 2)
+            .filter_map(|s| s.heading.as_deref())
+            .take(12)
+            .collect();
+        if !outline.is_empty() {
+            out.push_str("\nOutline: ");
+            out.push_str(&outline.join("; "));
+        }
+        let text = self.text();
+        if !text.is_empty() {
+            out.push_str("\n\n");
+            if text.chars().count() > max_chars {
+                out.extend(text.chars().take(max_chars));
+                out.push_str(" […]");
+            } else {
+                out.push_str(&text);
+            }
+        }
+        out
+    }
+}
+
+/// Extract text and structure from a document's bytes.
+pub async fn parse_document(data: &[u8], kind: DocumentKind, ocr_fallback: bool) -> Result<ParsedDocument> {
+    match kind {
+        DocumentKind::Pdf => parse_pdf(data, ocr_fallback).await,
+        DocumentKind::Docx | DocumentKind::Xlsx | DocumentKind::Pptx => {
+            let data = data.to_vec();
+            tokio::task::spawn_blocking(move || match kind {
+                DocumentKind::Docx => parse_docx(&data),
+                DocumentKind::Xlsx => parse_xlsx(&data),
+                _ => parse_pptx(&data),
+            })
+            .await?
+        }
+        DocumentKind::Csv | DocumentKind::Text => {
+            let mut doc = ParsedDocument::new(kind);
+            doc.sections.push(DocumentSection { text: String::from_utf8_lossy(data).into_owned(), ..Default::default() });
+            doc.page_count = 1;
+            Ok(doc)
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PDF
+// ---------------------------------------------------------------------------
+
+/// Pages with fewer non-whitespace characters than this on average are
+/// treated as scans and OCR'd.
+const MIN_CHARS_PER_PAGE: usize = 20;
+
+async fn parse_pdf(data: &[u8], ocr_fallback: bool) -> Result<ParsedDocument> {
+    let work = std::env::temp_dir().join(format!("clawforge-doc-{}", uuid::Uuid::new_v4()));
+    tokio::fs::create_dir_all(&work).await?;
+    let result = parse_pdf_in(&work, data, ocr_fallback).await;
+    let _ = tokio::fs::remove_dir_all(&work).await;
+    result
+}
+
+async fn parse_pdf_in(work: &Path, data: &[u8], ocr_fallback: bool) -> Result<ParsedDocument> {
+    let input = work.join("input.pdf");
+    tokio::fs::write(&input, data).await?;
+
+    let output = tokio::process::Command::new("pdftotext")
+        .arg("-layout")
+        .arg(&input)
+        .arg("-")
+        .kill_on_drop(true)
+        .output()
+        .await
+        .context("Failed to run pdftotext; install poppler-utils to parse PDFs")?;
+    if !output.status.success() {
+        bail!("pdftotext exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr));
+    }
+    let mut doc = ParsedDocument::new(DocumentKind::Pdf);
+    doc.sections = pdf_pages(&String::from_utf8_lossy(&output.stdout));
+    doc.page_count = doc.sections.len();
+    doc.title = pdf_title(&input).await;
+
+    let chars: usize = doc.sections.iter().map(|s| s.text.chars().filter(|c| !c.is_whitespace()).count()).sum();
+    if ocr_fallback && chars < MIN_CHARS_PER_PAGE * doc.page_count.max(1) {
+        info!("PDF has {} characters of text over {} pages; falling back to OCR", chars, doc.page_count);
+        match ocr_pdf(work, &input).await {
+            Ok(pages) if !pages.is_empty() => {
+                doc.page_count = pages.len();
+                doc.sections = pages;
+                doc.ocr = true;
+            }
+            Ok(_) => {}
+            Err(e) => warn!("OCR fallback failed: {}", e),
+        }
+    }
+    Ok(doc)
+}
+
+/// `pdftotext` separates pages with form feeds; the output ends with one.
+fn pdf_pages(text: &str) -> Vec<DocumentSection> {
+    let mut pages: Vec<&str> = text.split('\x0c').collect();
+    if pages.len() > 1 && pages.last().is_some_and(|p| p.trim().is_empty()) {
+        pages.pop();
+    }
+    pages
+        .into_iter()
+        .enumerate()
+        .map(|(i, page)| DocumentSection {
+            heading: Some(format!("Page {}", i + 1)),
+            level: 2,
+            text: page.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim().to_string(),
+        })
+        .collect()
+}
+
+/// The `Title:` line of `pdfinfo`, if poppler knows one.
+async fn pdf_title(input: &Path) -> Option<String> {
+    let output = tokio::process::Command::new("pdfinfo").arg(input).output().await.ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|l| l.strip_prefix("Title:"))
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+/// Render every page with `pdftoppm` and OCR the images in page order.
+async fn ocr_pdf(work: &Path, input: &Path) -> Result<Vec<DocumentSection>> {
+    let prefix = work.join("page");
+    let output = tokio::process::Command::new("pdftoppm")
+        .args(["-r", "200", "-png"])
+        .arg(input)
+        .arg(&prefix)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .context("Failed to run pdftoppm")?;
+    if !output.status.success() {
+        bail!("pdftoppm exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr));
+    }
+    let mut images: Vec<(usize, String)> = Vec::new();
+    let mut entries = tokio::fs::read_dir(work).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if let Some(n) = name.strip_prefix("page-").and_then(|n| n.strip_suffix(".png")).and_then(|n| n.parse().ok()) {
+            images.push((n, entry.path().to_string_lossy().into_owned()));
+        }
+    }
+    images.sort();
+    let mut pages = Vec::with_capacity(images.len());
+    for (n, path) in images {
+        pages.push(DocumentSection {
+            heading: Some(format!("Page {}", n)),
+            level: 2,
+            text: OcrService::extract_text(&path).await?,
+        });
+    }
+    Ok(pages)
+}
+
+// ---------------------------------------------------------------------------
+// Office Open XML (DOCX / XLSX / PPTX)
+// ---------------------------------------------------------------------------
+
+static DOCX_PARAGRAPH: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<w:p\b[^>]*?(?:/>|>.*?</w:p>)").unwrap());
+static DOCX_RUN_TEXT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<w:t(?:\s[^>]*)?>(.*?)</w:t>|<w:tab/>|<w:br/>").unwrap());
+static DOCX_STYLE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<w:pStyle w:val="(Title|Heading(\d))""#).unwrap());
+static CORE_TITLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<dc:title>(.*?)</dc:title>").unwrap());
+static XLSX_SHARED: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<si>(.*?)</si>").unwrap());
+static XLSX_TEXT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<t(?:\s[^>]*)?>(.*?)</t>").unwrap());
+static XLSX_SHEET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<sheet [^>]*?name="([^"]*)"[^>]*?r:id="([^"]*)""#).unwrap());
+static XLSX_REL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<Relationship [^>]*?Id="([^"]*)"[^>]*?Target="([^"]*)""#).unwrap());
+static XLSX_ROW: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<row\b[^>]*?(?:/>|>.*?</row>)").unwrap());
+static XLSX_CELL: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<c ([^>]*?)(?:/>|>(.*?)</c>)").unwrap());
+static XLSX_VALUE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<v>(.*?)</v>").unwrap());
+static XLSX_TYPE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\bt="([^"]*)""#).unwrap());
+static XLSX_REF: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\br="([A-Z]+)"#).unwrap());
+static PPTX_PARAGRAPH: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<a:p\b[^>]*?(?:/>|>.*?</a:p>)").unwrap());
+static PPTX_TEXT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<a:t>(.*?)</a:t>").unwrap());
+static XML_ENTITY: Lazy<Regex> = Lazy::new(|| Regex::new(r"&(amp|lt|gt|quot|apos|#x[0-9a-fA-F]+|#[0-9]+);").unwrap());
+
+/// Rows rendered per sheet; large spreadsheets are summarized, not inlined.
+const MAX_SHEET_ROWS: usize = 500;
+/// Zip-bomb limits for Office archives: entries in the archive, inflated
+/// bytes per entry, and inflated bytes across every entry read.
+const MAX_ENTRIES: usize = 10_000;
+const MAX_ENTRY_BYTES: u64 = 32 * 1024 * 1024;
+const MAX_TOTAL_BYTES: u64 = 128 * 1024 * 1024;
+
+fn unescape_xml(s: &str) -> String {
+    XML_ENTITY
+        .replace_all(s, |caps: &regex::Captures| match &caps[1] {
+            "amp" => "&".to_string(),
+            "lt" => "<".to_string(),
+            "gt" => ">".to_string(),
+            "quot" => "\"".to_string(),
+            "apos" => "'".to_string(),
+            num => {
+                let code = match num.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => num[1..].parse().ok(),
+                };
+                code.and_then(char::from_u32).map(String::from).unwrap_or_default()
+            }
+        })
+        .into_owned()
+}
+
+/// An Office archive and the bytes inflated from it so far.
+struct Archive {
+    zip: zip::ZipArchive<Cursor<Vec<u8>>>,
+    inflated: u64,
+}
+
+fn open_archive(data: &[u8]) -> Result<Archive> {
+    let zip = zip::ZipArchive::new(Cursor::new(data.to_vec())).context("Document is not a valid Office Open XML archive")?;
+    if zip.len() > MAX_ENTRIES {
+        bail!("Document archive has {} entries; at most {} are read", zip.len(), MAX_ENTRIES);
+    }
+    Ok(Archive { zip, inflated: 0 })
+}
+
+/// Read one entry, refusing it when its declared or actual inflated size
+/// breaks the per-entry or total limit.
+fn read_entry(archive: &mut Archive, name: &str) -> Result<Option<String>> {
+    let file = match archive.zip.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let limit = MAX_ENTRY_BYTES.min(MAX_TOTAL_BYTES - archive.inflated);
+    if file.size() > limit {
+        bail!("{} inflates to {} bytes, over the {} byte limit", name, file.size(), limit);
+    }
+    // The declared size can lie; never inflate past the limit.
+    let mut bytes = Vec::new();
+    file.take(limit + 1).read_to_end(&mut bytes).with_context(|| format!("Failed to read {}", name))?;
+    if bytes.len() as u64 > limit {
+        bail!("{} inflates past the {} byte limit", name, limit);
+    }
+    archive.inflated += bytes.len() as u64;
+    String::from_utf8(bytes).map(Some).with_context(|| format!("{} is not UTF-8", name))
+}
+
+fn core_title(archive: &mut Archive) -> Result<Option<String>> {
+    Ok(read_entry(archive, "docProps/core.xml")?
+        .and_then(|xml| CORE_TITLE.captures(&xml).map(|c| unescape_xml(c[1].trim())))
+        .filter(|t| !t.is_empty()))
+}
+
+fn parse_docx(data: &[u8]) -> Result<ParsedDocument> {
+    let mut archive = open_archive(data)?;
+    let xml = read_entry(&mut archive, "word/document.xml")?.context("DOCX has no word/document.xml")?;
+    let mut doc = ParsedDocument::new(DocumentKind::Docx);
+    doc.title = core_title(&mut archive)?;
+
+    let mut current = DocumentSection::default();
+    for para in DOCX_PARAGRAPH.find_iter(&xml) {
+        let para = para.as_str();
+        let text: String = DOCX_RUN_TEXT
+            .captures_iter(para)
+            .map(|c| match c.get(1) {
+                Some(t) => unescape_xml(t.as_str()),
+                None if c[0].starts_with("<w:tab") => "\t".to_string(),
+                None => "\n".to_string(),
+            })
+            .collect();
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        match DOCX_STYLE.captures(para) {
+            Some(style) => {
+                if current.heading.is_some() || !current.text.is_empty() {
+                    doc.sections.push(std::mem::take(&mut current));
+                }
+                let level = style.get(2).and_then(|l| l.as_str().parse().ok()).unwrap_or(1);
+                if &style[1] == "Title" && doc.title.is_none() {
+                    doc.title = Some(text.to_string());
+                }
+                current.heading = Some(text.to_string());
+                current.level = level;
+            }
+            None => {
+                if !current.text.is_empty() {
+                    current.text.push_str("\n\n");
+                }
+                current.text.push_str(text);
+            }
+        }
+    }
+    if current.heading.is_some() || !current.text.is_empty() {
+        doc.sections.push(current);
+    }
+    doc.page_count = doc.sections.len();
+    Ok(doc)
+}
+
+/// `A1`-style reference to a zero-based column index.
+fn column_index(reference: &str) -> usize {
+    reference
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .fold(0, |acc, c| acc * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1))
+        .saturating_sub(1)
+}
+
+fn parse_xlsx(data: &[u8]) -> Result<ParsedDocument> {
+    let mut archive = open_archive(data)?;
+    let workbook = read_entry(&mut archive, "xl/workbook.xml")?.context("XLSX has no xl/workbook.xml")?;
+    let rels = read_entry(&mut archive, "xl/_rels/workbook.xml.rels")?.unwrap_or_default();
+    let targets: HashMap<&str, &str> = XLSX_REL
+        .captures_iter(&rels)
+        .filter_map(|c| Some((c.get(1)?.as_str(), c.get(2)?.as_str())))
+        .collect();
+    let shared: Vec<String> = read_entry(&mut archive, "xl/sharedStrings.xml")?
+        .map(|xml| {
+            XLSX_SHARED
+                .captures_iter(&xml)
+                .map(|si| XLSX_TEXT.captures_iter(&si[1]).map(|t| unescape_xml(&t[1])).collect())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut doc = ParsedDocument::new(DocumentKind::Xlsx);
+    doc.title = core_title(&mut archive)?;
+    for sheet in XLSX_SHEET.captures_iter(&workbook) {
+        let name = unescape_xml(&sheet[1]);
+        let Some(target) = targets.get(&sheet[2]) else { continue };
+        let path = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{}", target),
+        };
+        let Some(xml) = read_entry(&mut archive, &path)? else { continue };
+
+        let mut lines = Vec::new();
+        let mut total_rows = 0;
+        for row in XLSX_ROW.find_iter(&xml) {
+            let mut cells: Vec<String> = Vec::new();
+            for cell in XLSX_CELL.captures_iter(row.as_str()) {
+                let attrs = &cell[1];
+                let body = cell.get(2).map(|b| b.as_str()).unwrap_or("");
+                let value = match XLSX_TYPE.captures(attrs).map(|t| t[1].to_string()).as_deref() {
+                    Some("s") => XLSX_VALUE
+                        .captures(body)
+                        .and_then(|v| v[1].parse::<usize>().ok())
+                        .and_then(|i| shared.get(i).cloned())
+                        .unwrap_or_default(),
+                    Some("inlineStr") => XLSX_TEXT.captures_iter(body).map(|t| unescape_xml(&t[1])).collect(),
+                    _ => XLSX_VALUE.captures(body).map(|v| unescape_xml(&v[1])).unwrap_or_default(),
+                };
+                let column = XLSX_REF.captures(attrs).map(|r| column_index(&r[1])).unwrap_or(cells.len());
+                if column >= cells.len() {
+                    cells.resize(column + 1, String::new());
+                }
+                cells[column] = value;
+            }
+            if cells.iter().all(|c| c.trim().is_empty()) {
+                continue;
+            }
+            total_rows += 1;
+            if lines.len() < MAX_SHEET_ROWS {
+                lines.push(cells.join(" | "));
+            }
+        }
+        if total_rows > lines.len() {
+            lines.push(format!("… {} more rows", total_rows - lines.len()));
+        }
+        doc.sections.push(DocumentSection { heading: Some(name), level: 2, text: lines.join("\n") });
+    }
+    doc.page_count = doc.sections.len();
+    Ok(doc)
+}
+
+fn parse_pptx(data: &[u8]) -> Result<ParsedDocument> {
+    let mut archive = open_archive(data)?;
+    let mut slides: Vec<(usize, String)> = archive
+        .zip
+        .file_names()
+        .filter_map(|name| {
+            let n = name.strip_prefix("ppt/slides/slide")?.strip_suffix(".xml")?.parse().ok()?;
+            Some((n, name.to_string()))
+        })
+        .collect();
+    slides.sort();
+
+    let mut doc = ParsedDocument::new(DocumentKind::Pptx);
+    doc.title = core_title(&mut archive)?;
+    for (n, name) in slides {
+        let Some(xml) = read_entry(&mut archive, &name)? else { continue };
+        let paragraphs: Vec<String> = PPTX_PARAGRAPH
+            .find_iter(&xml)
+            .map(|p| PPTX_TEXT.captures_iter(p.as_str()).map(|t| unescape_xml(&t[1])).collect::<String>())
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect();
+        let heading = match paragraphs.first() {
+            Some(title) => format!("Slide {}: {}", n, title),
+            None => format!("Slide {}", n),
+        };
+        let body = paragraphs.iter().skip(1).cloned().collect::<Vec<_>>().join("\n");
+        doc.sections.push(DocumentSection { heading: Some(heading), level: 2, text: body });
+    }
+    doc.page_count = doc.sections.len();
+    Ok(doc)
+}
+
+// ---------------------------------------------------------------------------
+// Pipeline
+// ---------------------------------------------------------------------------
+
+/// A piece of a document sized for embedding.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentChunk {
+    pub index: usize,
+    /// Heading of the section the chunk came from.
+    pub section: Option<String>,
+    pub text: String,
+}
+
+/// Everything the pipeline produced for one attachment.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentDigest {
+    pub document: ParsedDocument,
+    pub chunks: Vec<DocumentChunk>,
+    /// Chunks written to memory; 0 when indexing is off.
+    pub indexed: usize,
+    /// Text injected into the session.
+    pub summary: String,
+}
+
+/// Parse, chunk, optionally index and summarize document attachments.
+pub struct DocumentPipeline {
+    chunk_size: usize,
+    overlap: usize,
+    summary_chars: usize,
+    ocr_fallback: bool,
+    memory: Option<(Arc<MemoryManager>, String)>,
+}
+
+impl Default for DocumentPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocumentPipeline {
+    pub fn new() -> Self {
+        Self { chunk_size: 1500, overlap: 200, summary_chars: 1200, ocr_fallback: true, memory: None }
+    }
+
+    pub fn with_chunking(mut self, chunk_size: usize, overlap: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self.overlap = overlap;
+        self
+    }
+
+    /// Maximum excerpt length in the session summary.
+    pub fn with_summary_chars(mut self, summary_chars: usize) -> Self {
+        self.summary_chars = summary_chars;
+        self
+    }
+
+    /// Skip OCR for PDFs without a text layer.
+    pub fn without_ocr(mut self) -> Self {
+        self.ocr_fallback = false;
+        self
+    }
+
+    /// Index every chunk into `collection`, which must already be open.
+    pub fn with_memory(mut self, manager: Arc<MemoryManager>, collection: impl Into<String>) -> Self {
+        self.memory = Some((manager, collection.into()));
+        self
+    }
+
+    pub async fn process(
+        &self,
+        data: &[u8],
+        mime_type: &str,
+        file_name: Option<&str>,
+        session_id: Option<String>,
+    ) -> Result<DocumentDigest> {
+        let kind = DocumentKind::detect(mime_type, file_name)
+            .with_context(|| format!("Unsupported document type {}", mime_type))?;
+        info!("Parsing {:?} document of {} bytes", kind, data.len());
+        let document = parse_document(data, kind, self.ocr_fallback).await?;
+        let chunks = chunk_document(&document, self.chunk_size, self.overlap);
+
+        let mut indexed = 0;
+        if let Some((manager, collection)) = &self.memory {
+            for chunk in &chunks {
+                let metadata = serde_json::json!({
+                    "source": "document",
+                    "file_name": file_name,
+                    "title": document.title,
+                    "kind": kind,
+                    "section": chunk.section,
+                    "chunk": chunk.index,
+                });
+                manager.insert(collection, &chunk.text, metadata, session_id.clone()).await?;
+                indexed += 1;
+            }
+            info!("Indexed {} document chunks into '{}'", indexed, collection);
+        }
+
+        let mut summary = document.summary(file_name, self.summary_chars);
+        if indexed > 0 {
+            summary.push_str(&format!("\n\n(Full text indexed into memory as {} chunks.)", indexed));
+        }
+        Ok(DocumentDigest { document, chunks, indexed, summary })
+    }
+}
+
+/// Chunk section by section so no chunk straddles two headings.
+pub fn chunk_document(doc: &ParsedDocument, chunk_size: usize, overlap: usize) -> Vec<DocumentChunk> {
+    let mut chunks = Vec::new();
+    for section in &doc.sections {
+        for text in chunk_text(section.text.trim(), chunk_size, overlap) {
+            let text = match &section.heading {
+                Some(heading) => format!("{}\n\n{}", heading, text),
+                None => text,
+            };
+            chunks.push(DocumentChunk { index: chunks.len(), section: section.heading.clone(), text });
+        }
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn archive(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, body) in entries {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(body.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn docx_headings_become_sections() {
+        let data = archive(&[(
+            "word/document.xml",
+            r#"<w:document><w:body>
+                <w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr><w:r><w:t>Q3 Report</w:t></w:r></w:p>
+                <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Revenue</w:t></w:r></w:p>
+                <w:p><w:r><w:t xml:space="preserve">Up 12% </w:t></w:r><w:r><w:t>&amp; rising.</w:t></w:r></w:p>
+            </w:body></w:document>"#,
+        )]);
+        let doc = parse_docx(&data).unwrap();
+        assert_eq!(doc.title.as_deref(), Some("Q3 Report"));
+        assert_eq!(doc.sections.len(), 2);
+        assert_eq!(doc.sections[1].heading.as_deref(), Some("Revenue"));
+        assert_eq!(doc.sections[1].text, "Up 12% & rising.");
+        assert_eq!(doc.to_markdown(), "# Q3 Report\n\n# Revenue\n\nUp 12% & rising.");
+    }
+
+    #[test]
+    fn oversized_entries_are_refused() {
+        let bomb = "a".repeat(MAX_ENTRY_BYTES as usize + 1);
+        let err = parse_docx(&archive(&[("word/document.xml", &bomb)])).unwrap_err();
+        assert!(err.to_string().contains("byte limit"), "{}", err);
+    }
+
+    #[test]
+    fn xlsx_sheets_resolve_shared_strings() {
+        let data = archive(&[
+            ("xl/workbook.xml", r#"<workbook><sheets><sheet name="Sales" sheetId="1" r:id="rId1"/></sheets></workbook>"#),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Type="worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#,
+            ),
+            ("xl/sharedStrings.xml", r#"<sst><si><t>Region</t></si><si><t>EMEA</t></si></sst>"#),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData>
+                    <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="inlineStr"><is><t>Total</t></is></c></row>
+                    <row r="2"><c r="A2" t="s"><v>1</v></c><c r="C2"><v>42</v></c></row>
+                </sheetData></worksheet>"#,
+            ),
+        ]);
+        let doc = parse_xlsx(&data).unwrap();
+        assert_eq!(doc.page_count, 1);
+        assert_eq!(doc.sections[0].heading.as_deref(), Some("Sales"));
+        assert_eq!(doc.sections[0].text, "Region | Total\nEMEA |  | 42");
+    }
+
+    #[test]
+    fn pdf_text_splits_on_form_feeds() {
+        let pages = pdf_pages("Intro\n\x0cBody   \n\x0c");
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1].heading.as_deref(), Some("Page 2"));
+        assert_eq!(pages[1].text, "Body");
+    }
+
+    #[test]
+    fn summary_lists_outline_and_truncates() {
+        let mut doc = ParsedDocument::new(DocumentKind::Pptx);
+        doc.sections.push(DocumentSection { heading: Some("Slide 1: Roadmap".into()), level: 2, text: "a".repeat(50) });
+        doc.page_count = 1;
+        let summary = doc.summary(Some("deck.pptx"), 10);
+        assert!(summary.starts_with("[Document: deck.pptx (PPTX, 1 slide)]\nOutline: Slide 1: Roadmap"));
+        assert!(summary.ends_with(&format!("{} […]", "a".repeat(10))));
+        assert_eq!(DocumentKind::detect("application/octet-stream", Some("deck.PPTX")), Some(DocumentKind::Pptx));
     }
 }
@@ -1,13 +1,27 @@
 pub mod audio;
 pub mod entity;
 pub mod link;
+pub mod link_extract;
 pub mod vision;
 pub mod stt;
 pub mod ocr;
 pub mod doc_parse;
 pub mod video_thumb;
 
-pub use audio::{transcribe_audio, AudioProvider};
+pub use audio::{
+    assign_speakers, transcribe_audio, transcribe_audio_with, AudioProvider, LocalDiarizer, SpeakerTurn,
+    TranscribeOptions, Transcript, TranscriptSegment,
+};
+pub use doc_parse::{
+    chunk_document, parse_document, DocumentChunk, DocumentDigest, DocumentKind, DocumentPipeline, DocumentSection,
+    ParsedDocument,
+};
 pub use entity::{extract_entities, extract_of_kind, Entity, EntityKind};
-pub use link::{detect_content_type, understand_link, LinkUnderstanding};
+pub use link::{
+    detect_content_type, understand_link, understand_link_guarded, LinkCache, LinkUnderstander, LinkUnderstanding,
+    DEFAULT_LINK_TTL,
+};
+pub use link_extract::{classify, ExtractorTokens, LinkExtraction, LinkSource, LinkTarget};
+pub use stt::{WhisperCpp, WhisperModel, WhisperModels};
+pub use video_thumb::{merge_timeline, Keyframe, TimelineEntry, TimelineKind, VideoPipeline, VideoUnderstanding};
 pub use vision::{describe_image, VisionProvider};
@@ -1,20 +1,189 @@
 /// Link understanding — extract metadata and content from URLs.
 ///
 /// Mirrors `src/link-understanding/` from OpenClaw.
-use anyhow::Result;
-use tracing::info;
+///
+/// Extractions are cached on disk by URL hash so a link pasted twice is only
+/// fetched once per TTL; the prompt-injection guard runs on every read, so a
+/// cached page is judged by the calling agent's policy.
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use clawforge_security::{quarantine, scan_external_content, ContentPolicy, ContentVerdict, ExternalContentGuard};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+
+use crate::link_extract::{classify, extract, ExtractorTokens, LinkExtraction, LinkSource};
 
 /// The result of understanding a URL.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct LinkUnderstanding {
     pub url: String,
     pub title: Option<String>,
     pub description: Option<String>,
     pub content_type: String,
-    /// Extracted plain-text body (if HTML).
+    /// Extracted plain-text body (if HTML), quarantined for prompt use.
     pub text: Option<String>,
+    /// Prompt-injection verdict for the title, description and body.
+    pub verdict: ContentVerdict,
+    /// Which extractor produced the content.
+    pub source: LinkSource,
+    /// Structured fields from domain extractors, e.g. repo stars or paper authors.
+    pub metadata: serde_json::Value,
+}
+
+/// How long a cached extraction stays fresh by default.
+pub const DEFAULT_LINK_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Serialize, Deserialize)]
+struct CachedLink {
+    url: String,
+    /// Unix seconds.
+    fetched_at: u64,
+    extraction: LinkExtraction,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// On-disk cache of link extractions: one `<sha256(url)>.json` file per URL.
+#[derive(Debug, Clone)]
+pub struct LinkCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl LinkCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self { dir: dir.into(), ttl }
+    }
+
+    /// `$CLAWFORGE_LINK_CACHE`, else `<cache dir>/clawforge/links`.
+    pub fn default_dir() -> PathBuf {
+        std::env::var("CLAWFORGE_LINK_CACHE").map(PathBuf::from).unwrap_or_else(|_| {
+            dirs::cache_dir().unwrap_or_else(std::env::temp_dir).join("clawforge").join("links")
+        })
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", hex::encode(Sha256::digest(url.as_bytes()))))
+    }
+
+    fn read(&self, path: &std::path::Path) -> Option<CachedLink> {
+        serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+    }
+
+    fn fresh(&self, entry: &CachedLink) -> bool {
+        now_secs().saturating_sub(entry.fetched_at) < self.ttl.as_secs()
+    }
+
+    /// The cached extraction for `url`, unless it has expired.
+    pub fn get(&self, url: &str) -> Option<LinkExtraction> {
+        let entry = self.read(&self.path(url))?;
+        (entry.url == url && self.fresh(&entry)).then_some(entry.extraction)
+    }
+
+    pub fn put(&self, url: &str, extraction: &LinkExtraction) -> Result<()> {
+        std::fs::create_dir_all(&self.dir).with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let entry = CachedLink { url: url.to_string(), fetched_at: now_secs(), extraction: extraction.clone() };
+        std::fs::write(self.path(url), serde_json::to_vec(&entry)?)?;
+        Ok(())
+    }
+
+    pub fn invalidate(&self, url: &str) {
+        let _ = std::fs::remove_file(self.path(url));
+    }
+
+    /// Delete expired entries; returns how many were removed.
+    pub fn prune(&self) -> Result<usize> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            if !self.read(&path).is_some_and(|e| self.fresh(&e)) && std::fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Fetches links through the domain extractors or the generic HTML path,
+/// with an optional cache in front.
+pub struct LinkUnderstander {
+    client: reqwest::Client,
+    cache: Option<LinkCache>,
+    tokens: ExtractorTokens,
+}
+
+impl Default for LinkUnderstander {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LinkUnderstander {
+    /// No cache; extractor tokens from the environment.
+    pub fn new() -> Self {
+        Self { client: reqwest::Client::new(), cache: None, tokens: ExtractorTokens::from_env() }
+    }
+
+    pub fn with_cache(mut self, cache: LinkCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn with_tokens(mut self, tokens: ExtractorTokens) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    pub async fn understand(&self, url: &str, guard: &ExternalContentGuard) -> Result<LinkUnderstanding> {
+        let cached = self.cache.as_ref().and_then(|c| c.get(url));
+        let extraction = match cached {
+            Some(extraction) => {
+                debug!("[LinkUnderstanding] Cache hit for {}", url);
+                extraction
+            }
+            None => {
+                let extraction = self.fetch(url).await?;
+                if let Some(cache) = &self.cache {
+                    if let Err(e) = cache.put(url, &extraction) {
+                        warn!("[LinkUnderstanding] Failed to cache {}: {}", url, e);
+                    }
+                }
+                extraction
+            }
+        };
+        Ok(guard_extraction(url, extraction, guard).await)
+    }
+
+    /// Domain extractor when one matches, the HTML page otherwise or when
+    /// the extractor fails.
+    async fn fetch(&self, url: &str) -> Result<LinkExtraction> {
+        let target = classify(url);
+        match extract(&self.client, &target, &self.tokens).await {
+            Ok(Some(extraction)) => return Ok(extraction),
+            Ok(None) => {}
+            Err(e) => warn!("[LinkUnderstanding] {:?} extractor failed for {}, using HTML: {}", target.source(), url, e),
+        }
+        fetch_html(&self.client, url).await
+    }
 }
 
+static DEFAULT_UNDERSTANDER: Lazy<LinkUnderstander> =
+    Lazy::new(|| LinkUnderstander::new().with_cache(LinkCache::new(LinkCache::default_dir(), DEFAULT_LINK_TTL)));
+
 /// Detect the content type from a URL extension or HTTP response headers.
 pub fn detect_content_type(url: &str) -> &'static str {
     let lower = url.to_lowercase();
@@ -33,9 +202,19 @@ pub fn detect_content_type(url: &str) -> &'static str {
 }
 
 /// Fetch and understand a URL — return a brief plain-text summary.
+/// Content is scanned for prompt injection under the default (warn) policy.
 pub async fn understand_link(url: &str) -> Result<LinkUnderstanding> {
+    understand_link_guarded(url, &ExternalContentGuard::default()).await
+}
+
+/// `understand_link` with the calling agent's external-content guard.
+/// Uses the default extractors and the on-disk cache with `DEFAULT_LINK_TTL`.
+pub async fn understand_link_guarded(url: &str, guard: &ExternalContentGuard) -> Result<LinkUnderstanding> {
+    DEFAULT_UNDERSTANDER.understand(url, guard).await
+}
+
+async fn fetch_html(client: &reqwest::Client, url: &str) -> Result<LinkExtraction> {
     info!("[LinkUnderstanding] Fetching {}", url);
-    let client = reqwest::Client::new();
     let resp = client.get(url).header("User-Agent", "ClawForge/1.0").send().await?;
     let content_type = resp
         .headers()
@@ -45,21 +224,57 @@ pub async fn understand_link(url: &str) -> Result<LinkUnderstanding> {
         .to_string();
     let body = resp.text().await?;
 
-    let text = if content_type.contains("html") {
-        Some(strip_html(&body))
-    } else {
-        None
-    };
-
-    Ok(LinkUnderstanding {
-        url: url.to_string(),
+    Ok(LinkExtraction {
+        source: LinkSource::Web,
         title: extract_html_tag(&body, "title"),
         description: extract_meta_description(&body),
+        text: content_type.contains("html").then(|| strip_html(&body)),
         content_type,
-        text,
+        metadata: serde_json::Value::Null,
     })
 }
 
+/// Scan and quarantine an extraction under `guard`'s policy.
+async fn guard_extraction(url: &str, extraction: LinkExtraction, guard: &ExternalContentGuard) -> LinkUnderstanding {
+    let LinkExtraction { source, title, description, content_type, text, metadata } = extraction;
+
+    // Title and description reach the prompt too, so they are scanned with the body.
+    let scanned = [title.as_deref(), description.as_deref(), text.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n");
+    let guarded = guard.inspect(url, &scanned).await;
+    if guarded.verdict == ContentVerdict::Blocked {
+        return LinkUnderstanding {
+            url: url.to_string(),
+            title: None,
+            description: None,
+            content_type,
+            text: Some(guarded.text),
+            verdict: guarded.verdict,
+            source,
+            metadata: serde_json::Value::Null,
+        };
+    }
+
+    let flagged = guarded.verdict == ContentVerdict::Flagged;
+    let redact = |s: String| match guard.policy {
+        ContentPolicy::Allow => s,
+        _ => scan_external_content(&s).sanitized,
+    };
+    LinkUnderstanding {
+        url: url.to_string(),
+        title: title.map(redact),
+        description: description.map(redact),
+        content_type,
+        text: text.map(|t| quarantine(url, &redact(t), flagged)),
+        verdict: guarded.verdict,
+        source,
+        metadata,
+    }
+}
+
 fn strip_html(html: &str) -> String {
     let mut out = String::new();
     let mut in_tag = false;
@@ -71,8 +286,13 @@ fn strip_html(html: &str) -> String {
             _ => {}
         }
     }
-    // Collapse whitespace
-    out.split_whitespace().collect::<Vec<_>>().join(" ")
+    // Collapse whitespace within lines; keep line breaks so injection
+    // redaction only removes the offending lines.
+    out.lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 fn extract_html_tag(html: &str, tag: &str) -> Option<String> {
@@ -91,3 +311,32 @@ fn extract_meta_description(html: &str) -> Option<String> {
     let end = html[start..].find('"')?;
     Some(html[start..start + end].to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_entries_expire_after_ttl() {
+        let dir = std::env::temp_dir().join(format!("clawforge-links-{}", uuid::Uuid::new_v4()));
+        let extraction = LinkExtraction {
+            source: LinkSource::Arxiv,
+            title: Some("Attention Is All You Need".into()),
+            content_type: "paper".into(),
+            ..Default::default()
+        };
+
+        let cache = LinkCache::new(&dir, DEFAULT_LINK_TTL);
+        assert!(cache.get("https://arxiv.org/abs/1706.03762").is_none());
+        cache.put("https://arxiv.org/abs/1706.03762", &extraction).unwrap();
+        let hit = cache.get("https://arxiv.org/abs/1706.03762").unwrap();
+        assert_eq!(hit.source, LinkSource::Arxiv);
+        assert_eq!(hit.title, extraction.title);
+        assert_eq!(cache.prune().unwrap(), 0);
+
+        let expired = LinkCache::new(&dir, Duration::ZERO);
+        assert!(expired.get("https://arxiv.org/abs/1706.03762").is_none());
+        assert_eq!(expired.prune().unwrap(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,472 @@
+/// Domain-specific link extractors.
+///
+/// Well-known sites expose far better context through their APIs than through
+/// their HTML: YouTube captions, GitHub repo/issue/PR metadata, tweet text and
+/// arXiv abstracts. Everything else goes through the generic HTML path in
+/// `link.rs`.
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// Longest body kept from any extractor; transcripts and READMEs can be huge.
+pub const MAX_TEXT_CHARS: usize = 20_000;
+
+/// Which extractor produced a link's content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkSource {
+    #[default]
+    Web,
+    YouTube,
+    GitHub,
+    Twitter,
+    Arxiv,
+}
+
+/// What a URL points at, as far as the extractors care.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    YouTube { video_id: String },
+    GitHubRepo { owner: String, repo: String },
+    GitHubIssue { owner: String, repo: String, number: u64, pull: bool },
+    Tweet { user: String, id: String },
+    Arxiv { id: String },
+    Web,
+}
+
+impl LinkTarget {
+    pub fn source(&self) -> LinkSource {
+        match self {
+            Self::YouTube { .. } => LinkSource::YouTube,
+            Self::GitHubRepo { .. } | Self::GitHubIssue { .. } => LinkSource::GitHub,
+            Self::Tweet { .. } => LinkSource::Twitter,
+            Self::Arxiv { .. } => LinkSource::Arxiv,
+            Self::Web => LinkSource::Web,
+        }
+    }
+}
+
+/// Classify a URL by host and path.
+pub fn classify(url: &str) -> LinkTarget {
+    let Ok(parsed) = Url::parse(url) else { return LinkTarget::Web };
+    let host = parsed.host_str().unwrap_or("").trim_start_matches("www.").trim_start_matches("m.");
+    let segments: Vec<&str> = parsed.path_segments().map(|s| s.filter(|p| !p.is_empty()).collect()).unwrap_or_default();
+    match (host, segments.as_slice()) {
+        ("youtube.com" | "music.youtube.com", ["watch"]) => parsed
+            .query_pairs()
+            .find(|(k, _)| k == "v")
+            .map(|(_, v)| LinkTarget::YouTube { video_id: v.into_owned() })
+            .unwrap_or(LinkTarget::Web),
+        ("youtube.com", ["shorts" | "live" | "embed", id, ..]) | ("youtu.be", [id, ..]) => {
+            LinkTarget::YouTube { video_id: id.to_string() }
+        }
+        ("github.com", [owner, repo]) => LinkTarget::GitHubRepo { owner: owner.to_string(), repo: repo.to_string() },
+        ("github.com", [owner, repo, kind @ ("issues" | "pull"), number, ..]) => match number.parse() {
+            Ok(number) => LinkTarget::GitHubIssue {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                number,
+                pull: *kind == "pull",
+            },
+            Err(_) => LinkTarget::Web,
+        },
+        ("twitter.com" | "x.com" | "mobile.twitter.com", [user, "status", id, ..]) if id.chars().all(|c| c.is_ascii_digit()) => {
+            LinkTarget::Tweet { user: user.to_string(), id: id.to_string() }
+        }
+        ("arxiv.org", ["abs" | "pdf", id @ ..]) if !id.is_empty() => {
+            LinkTarget::Arxiv { id: id.join("/").trim_end_matches(".pdf").to_string() }
+        }
+        _ => LinkTarget::Web,
+    }
+}
+
+/// Raw content pulled from a URL, before the prompt-injection guard runs.
+/// This is what the link cache stores.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinkExtraction {
+    pub source: LinkSource,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub content_type: String,
+    pub text: Option<String>,
+    /// Structured fields specific to the source, e.g. stars or authors.
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+}
+
+/// API credentials the extractors use when present.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractorTokens {
+    /// Raises the GitHub API rate limit and reaches private repos.
+    pub github: Option<String>,
+    /// X API v2 bearer token; without it only the linked tweet is fetched.
+    pub twitter: Option<String>,
+}
+
+impl ExtractorTokens {
+    /// `GITHUB_TOKEN` and `TWITTER_BEARER_TOKEN`.
+    pub fn from_env() -> Self {
+        Self {
+            github: std::env::var("GITHUB_TOKEN").ok().filter(|t| !t.is_empty()),
+            twitter: std::env::var("TWITTER_BEARER_TOKEN").ok().filter(|t| !t.is_empty()),
+        }
+    }
+}
+
+/// Run the extractor for `target`; `None` for plain web pages.
+pub async fn extract(client: &Client, target: &LinkTarget, tokens: &ExtractorTokens) -> Result<Option<LinkExtraction>> {
+    let extraction = match target {
+        LinkTarget::YouTube { video_id } => youtube(client, video_id).await?,
+        LinkTarget::GitHubRepo { owner, repo } => github_repo(client, owner, repo, tokens).await?,
+        LinkTarget::GitHubIssue { owner, repo, number, pull } => {
+            github_issue(client, owner, repo, *number, *pull, tokens).await?
+        }
+        LinkTarget::Tweet { user, id } => tweet(client, user, id, tokens).await?,
+        LinkTarget::Arxiv { id } => arxiv(client, id).await?,
+        LinkTarget::Web => return Ok(None),
+    };
+    Ok(Some(LinkExtraction { text: extraction.text.map(truncate), ..extraction }))
+}
+
+fn truncate(text: String) -> String {
+    if text.chars().count() <= MAX_TEXT_CHARS {
+        return text;
+    }
+    let mut out: String = text.chars().take(MAX_TEXT_CHARS).collect();
+    out.push_str("\n[…truncated]");
+    out
+}
+
+fn decode_entities(s: &str) -> String {
+    static NUMERIC: Lazy<Regex> = Lazy::new(|| Regex::new(r"&#(x[0-9a-fA-F]+|[0-9]+);").unwrap());
+    let named = s
+        .replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'");
+    NUMERIC
+        .replace_all(&named, |c: &regex::Captures| {
+            let code = match c[1].strip_prefix('x') {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => c[1].parse().ok(),
+            };
+            code.and_then(char::from_u32).map(String::from).unwrap_or_default()
+        })
+        .into_owned()
+}
+
+// ---------------------------------------------------------------------------
+// YouTube
+// ---------------------------------------------------------------------------
+
+static CAPTION_URL: Lazy<Regex> = Lazy::new(|| Regex::new(r#""captionTracks":\[\{"baseUrl":"([^"]+)""#).unwrap());
+static CAPTION_TEXT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?s)<text start="([0-9.]+)"[^>]*>(.*?)</text>"#).unwrap());
+
+async fn youtube(client: &Client, video_id: &str) -> Result<LinkExtraction> {
+    info!("[LinkUnderstanding] YouTube video {}", video_id);
+    let watch = format!("https://www.youtube.com/watch?v={}", video_id);
+    let oembed: serde_json::Value = client
+        .get("https://www.youtube.com/oembed")
+        .query(&[("url", watch.as_str()), ("format", "json")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // Captions are listed in the watch page's player config.
+    let page = client.get(&watch).header("Accept-Language", "en").send().await?.text().await?;
+    let transcript = match CAPTION_URL.captures(&page) {
+        Some(caps) => {
+            let url = caps[1].replace("\\u0026", "&");
+            let xml = client.get(&url).send().await?.text().await?;
+            Some(parse_timedtext(&xml))
+        }
+        None => None,
+    };
+
+    Ok(LinkExtraction {
+        source: LinkSource::YouTube,
+        title: oembed["title"].as_str().map(str::to_string),
+        description: oembed["author_name"].as_str().map(|a| format!("YouTube video by {}", a)),
+        content_type: "video".into(),
+        text: transcript.filter(|t| !t.is_empty()),
+        metadata: serde_json::json!({
+            "video_id": video_id,
+            "channel": oembed["author_name"],
+            "channel_url": oembed["author_url"],
+        }),
+    })
+}
+
+/// Caption XML to `[mm:ss] line` transcript lines.
+fn parse_timedtext(xml: &str) -> String {
+    CAPTION_TEXT
+        .captures_iter(xml)
+        .filter_map(|c| {
+            let secs = c[1].parse::<f64>().ok()? as u64;
+            // Caption text is escaped twice: once in the XML, once by YouTube.
+            let line = decode_entities(&decode_entities(&c[2])).replace('\n', " ");
+            let line = line.trim();
+            (!line.is_empty()).then(|| format!("[{:02}:{:02}] {}", secs / 60, secs % 60, line))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ---------------------------------------------------------------------------
+// GitHub
+// ---------------------------------------------------------------------------
+
+async fn github_get(client: &Client, path: &str, accept: &str, tokens: &ExtractorTokens) -> Result<reqwest::Response> {
+    let mut req = client
+        .get(format!("https://api.github.com{}", path))
+        .header("Accept", accept)
+        .header("User-Agent", "ClawForge/1.0");
+    if let Some(token) = &tokens.github {
+        req = req.bearer_auth(token);
+    }
+    Ok(req.send().await?.error_for_status()?)
+}
+
+async fn github_repo(client: &Client, owner: &str, repo: &str, tokens: &ExtractorTokens) -> Result<LinkExtraction> {
+    info!("[LinkUnderstanding] GitHub repo {}/{}", owner, repo);
+    let path = format!("/repos/{}/{}", owner, repo);
+    let json: serde_json::Value = github_get(client, &path, "application/vnd.github+json", tokens).await?.json().await?;
+    let readme = match github_get(client, &format!("{}/readme", path), "application/vnd.github.raw", tokens).await {
+        Ok(resp) => resp.text().await.ok(),
+        Err(_) => None,
+    };
+    Ok(LinkExtraction {
+        source: LinkSource::GitHub,
+        title: json["full_name"].as_str().map(str::to_string),
+        description: json["description"].as_str().map(str::to_string),
+        content_type: "repository".into(),
+        text: readme,
+        metadata: serde_json::json!({
+            "stars": json["stargazers_count"],
+            "forks": json["forks_count"],
+            "open_issues": json["open_issues_count"],
+            "language": json["language"],
+            "topics": json["topics"],
+            "license": json["license"]["spdx_id"],
+            "default_branch": json["default_branch"],
+            "pushed_at": json["pushed_at"],
+        }),
+    })
+}
+
+async fn github_issue(
+    client: &Client,
+    owner: &str,
+    repo: &str,
+    number: u64,
+    pull: bool,
+    tokens: &ExtractorTokens,
+) -> Result<LinkExtraction> {
+    info!("[LinkUnderstanding] GitHub {} {}/{}#{}", if pull { "PR" } else { "issue" }, owner, repo, number);
+    let kind = if pull { "pulls" } else { "issues" };
+    let path = format!("/repos/{}/{}/{}/{}", owner, repo, kind, number);
+    let json: serde_json::Value = github_get(client, &path, "application/vnd.github+json", tokens).await?.json().await?;
+    let labels: Vec<&str> = json["labels"]
+        .as_array()
+        .map(|l| l.iter().filter_map(|l| l["name"].as_str()).collect())
+        .unwrap_or_default();
+    let mut metadata = serde_json::json!({
+        "number": number,
+        "state": json["state"],
+        "author": json["user"]["login"],
+        "labels": labels,
+        "comments": json["comments"],
+        "created_at": json["created_at"],
+    });
+    if pull {
+        metadata["merged"] = json["merged"].clone();
+        metadata["additions"] = json["additions"].clone();
+        metadata["deletions"] = json["deletions"].clone();
+        metadata["changed_files"] = json["changed_files"].clone();
+        metadata["base"] = json["base"]["ref"].clone();
+        metadata["head"] = json["head"]["ref"].clone();
+    }
+    Ok(LinkExtraction {
+        source: LinkSource::GitHub,
+        title: json["title"].as_str().map(|t| format!("{}/{}#{}: {}", owner, repo, number, t)),
+        description: Some(format!(
+            "{} by {} ({})",
+            if pull { "Pull request" } else { "Issue" },
+            json["user"]["login"].as_str().unwrap_or("unknown"),
+            json["state"].as_str().unwrap_or("unknown")
+        )),
+        content_type: if pull { "pull_request" } else { "issue" }.into(),
+        text: json["body"].as_str().map(str::to_string),
+        metadata,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Twitter / X
+// ---------------------------------------------------------------------------
+
+static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]+>").unwrap());
+
+async fn tweet(client: &Client, user: &str, id: &str, tokens: &ExtractorTokens) -> Result<LinkExtraction> {
+    info!("[LinkUnderstanding] Tweet {} by @{}", id, user);
+    let url = format!("https://twitter.com/{}/status/{}", user, id);
+    let oembed: serde_json::Value = client
+        .get("https://publish.twitter.com/oembed")
+        .query(&[("url", url.as_str()), ("omit_script", "true"), ("dnt", "true")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let html = oembed["html"].as_str().context("Tweet oEmbed has no html")?;
+    let first = tweet_text_from_oembed(html);
+
+    // With API access, follow the author's replies in the same conversation.
+    let mut posts = vec![first];
+    if let Some(token) = &tokens.twitter {
+        let query = format!("conversation_id:{} from:{}", id, user);
+        match client
+            .get("https://api.twitter.com/2/tweets/search/recent")
+            .bearer_auth(token)
+            .query(&[("query", query.as_str()), ("max_results", "100"), ("tweet.fields", "created_at")])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+        {
+            Ok(resp) => {
+                let json: serde_json::Value = resp.json().await?;
+                let mut replies: Vec<(&str, &str)> = json["data"]
+                    .as_array()
+                    .map(|d| d.iter().filter_map(|t| Some((t["created_at"].as_str()?, t["text"].as_str()?))).collect())
+                    .unwrap_or_default();
+                replies.sort();
+                posts.extend(replies.into_iter().map(|(_, text)| text.to_string()));
+            }
+            Err(e) => info!("[LinkUnderstanding] Thread lookup failed, using the single tweet: {}", e),
+        }
+    }
+
+    Ok(LinkExtraction {
+        source: LinkSource::Twitter,
+        title: oembed["author_name"].as_str().map(|a| format!("Post by {} (@{})", a, user)),
+        description: None,
+        content_type: "post".into(),
+        metadata: serde_json::json!({ "id": id, "author": user, "posts": posts.len() }),
+        text: Some(posts.join("\n\n")),
+    })
+}
+
+/// The tweet body is the `<p>` of the embed blockquote.
+fn tweet_text_from_oembed(html: &str) -> String {
+    let body = html.split("<p").nth(1).and_then(|p| p.split_once('>')).map(|(_, rest)| rest).unwrap_or(html);
+    let body = body.split("</p>").next().unwrap_or(body).replace("<br>", "\n");
+    decode_entities(&TAG.replace_all(&body, "")).trim().to_string()
+}
+
+// ---------------------------------------------------------------------------
+// arXiv
+// ---------------------------------------------------------------------------
+
+static ATOM_ENTRY: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<entry>(.*?)</entry>").unwrap());
+static ATOM_AUTHOR: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<author>\s*<name>(.*?)</name>").unwrap());
+
+fn atom_field(entry: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let start = entry.find(&open)? + open.len();
+    let end = start + entry[start..].find(&format!("</{}>", tag))?;
+    Some(decode_entities(&entry[start..end].split_whitespace().collect::<Vec<_>>().join(" ")))
+}
+
+async fn arxiv(client: &Client, id: &str) -> Result<LinkExtraction> {
+    info!("[LinkUnderstanding] arXiv {}", id);
+    let xml = client
+        .get("https://export.arxiv.org/api/query")
+        .query(&[("id_list", id)])
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    parse_arxiv(id, &xml)
+}
+
+fn parse_arxiv(id: &str, xml: &str) -> Result<LinkExtraction> {
+    let Some(entry) = ATOM_ENTRY.captures(xml).map(|c| c[1].to_string()) else {
+        bail!("arXiv has no entry for {}", id);
+    };
+    let authors: Vec<String> = ATOM_AUTHOR.captures_iter(&entry).map(|c| decode_entities(c[1].trim())).collect();
+    Ok(LinkExtraction {
+        source: LinkSource::Arxiv,
+        title: atom_field(&entry, "title"),
+        description: Some(format!("arXiv:{} by {}", id, authors.join(", "))),
+        content_type: "paper".into(),
+        text: atom_field(&entry, "summary"),
+        metadata: serde_json::json!({
+            "id": id,
+            "authors": authors,
+            "published": atom_field(&entry, "published"),
+            "pdf": format!("https://arxiv.org/pdf/{}", id),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_are_classified_by_site() {
+        assert_eq!(
+            classify("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42"),
+            LinkTarget::YouTube { video_id: "dQw4w9WgXcQ".into() }
+        );
+        assert_eq!(classify("https://youtu.be/dQw4w9WgXcQ"), LinkTarget::YouTube { video_id: "dQw4w9WgXcQ".into() });
+        assert_eq!(
+            classify("https://github.com/rust-lang/rust"),
+            LinkTarget::GitHubRepo { owner: "rust-lang".into(), repo: "rust".into() }
+        );
+        assert_eq!(
+            classify("https://github.com/rust-lang/rust/pull/12345/files"),
+            LinkTarget::GitHubIssue { owner: "rust-lang".into(), repo: "rust".into(), number: 12345, pull: true }
+        );
+        assert_eq!(
+            classify("https://x.com/jack/status/20"),
+            LinkTarget::Tweet { user: "jack".into(), id: "20".into() }
+        );
+        assert_eq!(classify("https://arxiv.org/pdf/1706.03762v7.pdf"), LinkTarget::Arxiv { id: "1706.03762v7".into() });
+        assert_eq!(classify("https://github.com/rust-lang/rust/blob/master/README.md"), LinkTarget::Web);
+        assert_eq!(classify("https://example.com/watch"), LinkTarget::Web);
+    }
+
+    #[test]
+    fn arxiv_atom_entry_is_parsed() {
+        let xml = r#"<feed><title>ArXiv Query</title><entry>
+            <id>http://arxiv.org/abs/1706.03762v7</id>
+            <published>2017-06-12T17:57:34Z</published>
+            <title>Attention Is All
+              You Need</title>
+            <summary>  The dominant sequence transduction models &amp; more. </summary>
+            <author><name>Ashish Vaswani</name></author>
+            <author><name>Noam Shazeer</name></author>
+        </entry></feed>"#;
+        let extraction = parse_arxiv("1706.03762", xml).unwrap();
+        assert_eq!(extraction.title.as_deref(), Some("Attention Is All You Need"));
+        assert_eq!(extraction.text.as_deref(), Some("The dominant sequence transduction models & more."));
+        assert_eq!(extraction.metadata["authors"], serde_json::json!(["Ashish Vaswani", "Noam Shazeer"]));
+    }
+
+    #[test]
+    fn captions_and_tweets_are_decoded() {
+        let xml = r#"<transcript><text start="0.5" dur="2">Hello &amp;#39;world&amp;#39;</text><text start="75.2" dur="1">bye</text></transcript>"#;
+        assert_eq!(parse_timedtext(xml), "[00:00] Hello 'world'\n[01:15] bye");
+        let html = r##"<blockquote class="twitter-tweet"><p lang="en" dir="ltr">just setting up my <a href="#">twttr</a><br>&amp; more</p>&mdash; jack (@jack)</blockquote>"##;
+        assert_eq!(tweet_text_from_oembed(html), "just setting up my twttr\n& more");
+    }
+}
@@ -3,17 +3,25 @@
 //! Bridges Tesseract or LLM Vision endpoints to extract dense text from images
 //! sent to the agent by users or web scrapers.
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use tracing::info;
 
 pub struct OcrService;
 
 impl OcrService {
-    /// Dispatches an image to a Vision model to read and layout all discernible text.
+    /// Runs local Tesseract over an image file and returns the recognized text.
     pub async fn extract_text(image_path: &str) -> Result<String> {
         info!("Running OCR detection on image file: {}", image_path);
-        
-        // MOCK: read image bytes, POST to GPT-4V or run local Tesseract
-        Ok("extracted image text from document mock".into())
+
+        let output = tokio::process::Command::new("tesseract")
+            .args([image_path, "stdout"])
+            .kill_on_drop(true)
+            .output()
+            .await
+            .context("Failed to run tesseract; install it for OCR")?;
+        if !output.status.success() {
+            bail!("tesseract exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
 }
@@ -2,10 +2,19 @@
 //!
 //! Provides async wrappers for transcribing voice notes and audio attachments
 //! utilizing external models like Deepgram, OpenAI Whisper, or local ones.
+//!
+//! The local backend shells out to whisper.cpp so audio never leaves the
+//! host. GGML models live under the data dir and are downloaded from the
+//! upstream model repository on demand.
+
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
 use tracing::info;
 
+use crate::audio::{Transcript, TranscriptSegment};
+
 pub enum SttEngine {
     Deepgram,
     Whisper,
@@ -28,3 +37,316 @@ impl SttService {
         Ok("mock_transcription_text".into())
     }
 }
+
+// ---------------------------------------------------------------------------
+// whisper.cpp models
+// ---------------------------------------------------------------------------
+
+pub const DEFAULT_WHISPER_MODEL: &str = "base";
+
+/// Upstream repository of whisper.cpp GGML models.
+const MODEL_REPO: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// Model names become file names; keep them to the upstream alphabet
+/// (`base`, `small.en`, `large-v3-turbo`, `medium-q5_0`, ...).
+fn validate_model_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || name.contains("..")
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    {
+        bail!("Invalid whisper model name '{}'", name);
+    }
+    Ok(())
+}
+
+/// Download URL of an upstream model, e.g. `base.en` is `ggml-base.en.bin`.
+pub fn model_url(name: &str) -> Result<String> {
+    validate_model_name(name)?;
+    Ok(format!("{}/ggml-{}.bin", MODEL_REPO, name))
+}
+
+/// An installed GGML model.
+#[derive(Debug, Clone, Serialize)]
+pub struct WhisperModel {
+    pub name: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// `.en` models only transcribe English and can't auto-detect.
+    pub english_only: bool,
+}
+
+/// whisper.cpp models on disk, one `ggml-<name>.bin` per model.
+#[derive(Debug, Clone)]
+pub struct WhisperModels {
+    dir: PathBuf,
+}
+
+impl WhisperModels {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `$CLAWFORGE_STT_MODELS`, else `<data dir>/clawforge/stt-models`.
+    pub fn default_location() -> Self {
+        let dir = std::env::var("CLAWFORGE_STT_MODELS").map(PathBuf::from).unwrap_or_else(|_| {
+            dirs::data_dir().unwrap_or_else(std::env::temp_dir).join("clawforge").join("stt-models")
+        });
+        Self::new(dir)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn model_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("ggml-{}.bin", name))
+    }
+
+    fn describe(name: &str, path: PathBuf) -> Result<WhisperModel> {
+        let size_bytes = std::fs::metadata(&path)
+            .with_context(|| format!("Failed to stat {}", path.display()))?
+            .len();
+        Ok(WhisperModel {
+            name: name.to_string(),
+            english_only: name.ends_with(".en") || name.contains(".en-"),
+            path,
+            size_bytes,
+        })
+    }
+
+    /// Installed models, sorted by name.
+    pub fn list(&self) -> Result<Vec<WhisperModel>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", self.dir.display())),
+        };
+        let mut models = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix("ggml-"))
+                .and_then(|n| n.strip_suffix(".bin"))
+            else {
+                continue;
+            };
+            if let Ok(model) = Self::describe(name, path.clone()) {
+                models.push(model);
+            }
+        }
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(models)
+    }
+
+    pub fn get(&self, name: &str) -> Result<WhisperModel> {
+        validate_model_name(name)?;
+        let path = self.model_path(name);
+        if !path.exists() {
+            bail!("Whisper model '{}' is not installed", name);
+        }
+        Self::describe(name, path)
+    }
+
+    /// Download a model from the upstream repository. Writes to a temp file
+    /// first so an interrupted download never looks installed.
+    pub async fn install(&self, name: &str) -> Result<WhisperModel> {
+        let url = model_url(name)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        info!("[STT/whisper.cpp] Downloading {}", url);
+        let bytes = reqwest::Client::new().get(&url).send().await?.error_for_status()?.bytes().await?;
+        let target = self.model_path(name);
+        let partial = target.with_extension("bin.part");
+        tokio::fs::write(&partial, &bytes).await.with_context(|| format!("Failed to write {}", partial.display()))?;
+        tokio::fs::rename(&partial, &target).await?;
+        self.get(name)
+    }
+
+    /// The installed model, downloading it first when missing.
+    pub async fn ensure(&self, name: &str) -> Result<WhisperModel> {
+        match self.get(name) {
+            Ok(model) => Ok(model),
+            Err(_) => self.install(name).await,
+        }
+    }
+
+    pub async fn remove(&self, name: &str) -> Result<()> {
+        let model = self.get(name)?;
+        tokio::fs::remove_file(&model.path).await?;
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// whisper.cpp transcription
+// ---------------------------------------------------------------------------
+
+/// Local transcription with the whisper.cpp CLI (`whisper-cli`). Non-WAV
+/// input is converted to 16 kHz mono WAV with ffmpeg first, since stock
+/// builds only read WAV.
+pub struct WhisperCpp {
+    binary: PathBuf,
+    model: PathBuf,
+    language: Option<String>,
+    threads: Option<usize>,
+}
+
+impl WhisperCpp {
+    pub fn new(binary: impl Into<PathBuf>, model: impl Into<PathBuf>) -> Self {
+        Self { binary: binary.into(), model: model.into(), language: None, threads: None }
+    }
+
+    /// Force a language instead of auto-detecting, e.g. `"de"`.
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    pub async fn transcribe(&self, audio: &[u8], mime_type: &str) -> Result<Transcript> {
+        let work = std::env::temp_dir().join(format!("clawforge-whisper-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&work).await?;
+        let result = self.transcribe_in(&work, audio, mime_type).await;
+        let _ = tokio::fs::remove_dir_all(&work).await;
+        result
+    }
+
+    async fn transcribe_in(&self, work: &Path, audio: &[u8], mime_type: &str) -> Result<Transcript> {
+        let wav = work.join("input.wav");
+        if is_wav(mime_type) {
+            tokio::fs::write(&wav, audio).await?;
+        } else {
+            let source = work.join("input");
+            tokio::fs::write(&source, audio).await?;
+            to_wav_16k(&source, &wav).await?;
+        }
+
+        info!("[STT/whisper.cpp] Transcribing {} bytes with {}", audio.len(), self.model.display());
+        let prefix = work.join("transcript");
+        let mut cmd = tokio::process::Command::new(&self.binary);
+        cmd.arg("-m")
+            .arg(&self.model)
+            .arg("-f")
+            .arg(&wav)
+            .arg("-l")
+            .arg(self.language.as_deref().unwrap_or("auto"))
+            .arg("--output-json")
+            .arg("--output-file")
+            .arg(&prefix)
+            .arg("--no-prints")
+            .kill_on_drop(true);
+        if let Some(threads) = self.threads {
+            cmd.arg("-t").arg(threads.to_string());
+        }
+        let output = cmd.output().await.with_context(|| format!("Failed to run {}", self.binary.display()))?;
+        if !output.status.success() {
+            bail!("whisper.cpp exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr));
+        }
+
+        let json_path = prefix.with_extension("json");
+        let raw = tokio::fs::read_to_string(&json_path)
+            .await
+            .with_context(|| format!("whisper.cpp produced no {}", json_path.display()))?;
+        parse_whisper_json(&raw)
+    }
+}
+
+fn is_wav(mime: &str) -> bool {
+    matches!(mime.split(';').next().unwrap_or(mime).trim(), "audio/wav" | "audio/x-wav" | "audio/wave")
+}
+
+async fn to_wav_16k(source: &Path, target: &Path) -> Result<()> {
+    let output = tokio::process::Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(source)
+        .args(["-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le"])
+        .arg(target)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .context("Failed to run ffmpeg; it is needed to convert non-WAV audio for whisper.cpp")?;
+    if !output.status.success() {
+        bail!("ffmpeg exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr));
+    }
+    Ok(())
+}
+
+/// Read the `--output-json` file: `result.language` plus every
+/// `transcription` segment with its millisecond `offsets`.
+fn parse_whisper_json(raw: &str) -> Result<Transcript> {
+    let json: serde_json::Value = serde_json::from_str(raw).context("whisper.cpp JSON output is malformed")?;
+    let segments: Vec<TranscriptSegment> = json["transcription"]
+        .as_array()
+        .map(|segments| {
+            segments
+                .iter()
+                .map(|s| TranscriptSegment {
+                    start_ms: s["offsets"]["from"].as_u64().unwrap_or(0),
+                    end_ms: s["offsets"]["to"].as_u64().unwrap_or(0),
+                    text: s["text"].as_str().unwrap_or("").trim().to_string(),
+                    speaker: None,
+                })
+                .filter(|s| !s.text.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let text = segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" ");
+    let language = json["result"]["language"]
+        .as_str()
+        .filter(|l| !l.is_empty() && *l != "auto")
+        .map(str::to_string);
+    Ok(Transcript { text, language, segments })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_url_follows_repository_layout() {
+        assert_eq!(model_url("base.en").unwrap(), format!("{}/ggml-base.en.bin", MODEL_REPO));
+        assert_eq!(model_url("large-v3-turbo").unwrap(), format!("{}/ggml-large-v3-turbo.bin", MODEL_REPO));
+        assert!(model_url("../secrets").is_err());
+        assert!(model_url("").is_err());
+    }
+
+    #[test]
+    fn whisper_json_yields_text_and_detected_language() {
+        let raw = r#"{
+            "params": {"language": "auto"},
+            "result": {"language": "de"},
+            "transcription": [
+                {"offsets": {"from": 0, "to": 1800}, "text": " Guten Tag,"},
+                {"offsets": {"from": 1800, "to": 3200}, "text": " wie geht's?"}
+            ]
+        }"#;
+        let transcript = parse_whisper_json(raw).unwrap();
+        assert_eq!(transcript.text, "Guten Tag, wie geht's?");
+        assert_eq!(transcript.language.as_deref(), Some("de"));
+        assert_eq!((transcript.segments[1].start_ms, transcript.segments[1].end_ms), (1800, 3200));
+    }
+
+    #[test]
+    fn installed_models_are_listed() {
+        let dir = std::env::temp_dir().join(format!("clawforge-whisper-models-{}", uuid::Uuid::new_v4()));
+        let models = WhisperModels::new(&dir);
+        assert!(models.list().unwrap().is_empty());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ggml-small.en.bin"), b"ggml").unwrap();
+        std::fs::write(dir.join("ggml-base.bin.part"), b"partial").unwrap();
+
+        let listed = models.list().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "small.en");
+        assert!(listed[0].english_only);
+        assert!(models.get("base").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,9 +2,22 @@
 //!
 //! Small utility to pipe video streams through ffmpeg to rip the first discernible frame
 //! for Vision Model analysis when a video is shared with the bot.
+//!
+//! [`VideoPipeline`] builds on it: keyframes are picked by ffmpeg scene
+//! detection (uniform sampling when the video has no cuts), described by a
+//! vision model, and merged with the transcript of the audio track into one
+//! timeline.
+
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
-use tracing::{debug, info};
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::audio::{format_timestamp, transcribe_audio, AudioProvider, Transcript};
+use crate::vision::{describe_image, VisionProvider};
 
 pub struct VideoThumb;
 
@@ -12,9 +25,304 @@ impl VideoThumb {
     /// Executes native FFmpeg to grab exactly one frame at the 1-second mark inside a video blob.
     pub async fn extract_frame(video_path: &str, output_path: &str) -> Result<()> {
         info!("Extracting thumbnail from {} to {}", video_path, output_path);
-        
-        // MOCK: tokio::process::Command::new("ffmpeg").args(["-y", "-i", video_path, "-ss", "00:00:01", "-vframes", "1", output_path])
-        debug!("FFmpeg execution mocked successfully");
-        Ok(())
+        frame_at(Path::new(video_path), 1000, Path::new(output_path)).await
+    }
+}
+
+/// Grab the frame at `at_ms` as an image.
+async fn frame_at(video: &Path, at_ms: u64, output: &Path) -> Result<()> {
+    let seek = format!("{:.3}", at_ms as f64 / 1000.0);
+    ffmpeg(&["-y", "-ss", &seek, "-i"], video, &["-frames:v", "1", "-q:v", "3"], output).await?;
+    Ok(())
+}
+
+/// Run `ffmpeg <before> <input> <after> <output>` and return its stderr.
+async fn ffmpeg(before: &[&str], input: &Path, after: &[&str], output: &Path) -> Result<String> {
+    let out = tokio::process::Command::new("ffmpeg")
+        .args(["-hide_banner", "-nostdin"])
+        .args(before)
+        .arg(input)
+        .args(after)
+        .arg(output)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .context("Failed to run ffmpeg")?;
+    let stderr = String::from_utf8_lossy(&out.stderr).into_owned();
+    if !out.status.success() {
+        bail!("ffmpeg exited with {}: {}", out.status, stderr.lines().last().unwrap_or(""));
+    }
+    Ok(stderr)
+}
+
+/// Container duration in milliseconds, via `ffprobe`.
+pub async fn probe_duration_ms(video: &Path) -> Result<u64> {
+    let out = tokio::process::Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "format=duration", "-of", "default=nw=1:nk=1"])
+        .arg(video)
+        .output()
+        .await
+        .context("Failed to run ffprobe")?;
+    let secs: f64 = String::from_utf8_lossy(&out.stdout)
+        .trim()
+        .parse()
+        .with_context(|| format!("ffprobe reported no duration for {}", video.display()))?;
+    Ok((secs * 1000.0).round() as u64)
+}
+
+static PTS_TIME: Lazy<Regex> = Lazy::new(|| Regex::new(r"pts_time:\s*([0-9.]+)").unwrap());
+
+/// Timestamps of the frames `showinfo` reported, in output order.
+fn parse_showinfo(stderr: &str) -> Vec<u64> {
+    stderr
+        .lines()
+        .filter(|l| l.contains("Parsed_showinfo"))
+        .filter_map(|l| PTS_TIME.captures(l)?[1].parse::<f64>().ok())
+        .map(|secs| (secs * 1000.0).round() as u64)
+        .collect()
+}
+
+/// `count` timestamps spread evenly over the video, each in the middle of its slice.
+fn uniform_timestamps(duration_ms: u64, count: usize) -> Vec<u64> {
+    (0..count as u64).map(|i| duration_ms * (2 * i + 1) / (2 * count as u64)).collect()
+}
+
+/// A frame picked for description.
+#[derive(Debug, Clone, Serialize)]
+pub struct Keyframe {
+    pub at_ms: u64,
+    pub path: PathBuf,
+    /// Vision model description, when one ran.
+    pub description: Option<String>,
+}
+
+/// What a timeline entry came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelineKind {
+    Scene,
+    Speech,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    pub at_ms: u64,
+    pub kind: TimelineKind,
+    pub text: String,
+}
+
+/// Everything the pipeline learned about one video.
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoUnderstanding {
+    pub duration_ms: u64,
+    pub keyframes: Vec<Keyframe>,
+    pub transcript: Option<Transcript>,
+    /// Scene descriptions and speech, ordered by time.
+    pub timeline: Vec<TimelineEntry>,
+}
+
+impl VideoUnderstanding {
+    /// Render the timeline for the session context.
+    pub fn to_context(&self) -> String {
+        let mut out = format!(
+            "[Video: {}, {} keyframes{}]",
+            format_timestamp(self.duration_ms),
+            self.keyframes.len(),
+            if self.transcript.is_some() { ", transcribed" } else { "" }
+        );
+        for entry in &self.timeline {
+            out.push('\n');
+            match entry.kind {
+                TimelineKind::Scene => out.push_str(&format!("[{}] (scene) {}", format_timestamp(entry.at_ms), entry.text)),
+                TimelineKind::Speech => out.push_str(&format!("[{}] {}", format_timestamp(entry.at_ms), entry.text)),
+            }
+        }
+        out
+    }
+}
+
+/// Interleave frame descriptions with speech turns. Speech keeps its
+/// speaker label; a scene and speech at the same instant list the scene first.
+pub fn merge_timeline(keyframes: &[Keyframe], transcript: Option<&Transcript>) -> Vec<TimelineEntry> {
+    let mut timeline: Vec<TimelineEntry> = keyframes
+        .iter()
+        .filter_map(|k| {
+            let text = k.description.as_deref()?.trim();
+            (!text.is_empty()).then(|| TimelineEntry { at_ms: k.at_ms, kind: TimelineKind::Scene, text: text.to_string() })
+        })
+        .collect();
+    if let Some(transcript) = transcript {
+        if transcript.segments.is_empty() && !transcript.text.trim().is_empty() {
+            timeline.push(TimelineEntry { at_ms: 0, kind: TimelineKind::Speech, text: transcript.text.trim().to_string() });
+        }
+        for seg in transcript.segments.iter().filter(|s| !s.text.trim().is_empty()) {
+            let text = match &seg.speaker {
+                Some(speaker) => format!("{}: {}", speaker, seg.text.trim()),
+                None => seg.text.trim().to_string(),
+            };
+            timeline.push(TimelineEntry { at_ms: seg.start_ms, kind: TimelineKind::Speech, text });
+        }
+    }
+    timeline.sort_by_key(|e| (e.at_ms, e.kind == TimelineKind::Speech));
+    timeline
+}
+
+const FRAME_PROMPT: &str =
+    "This is a frame from a video the user sent. Describe what is shown in one or two sentences, including any legible text.";
+
+/// Keyframes + vision + transcript for a video file.
+pub struct VideoPipeline {
+    vision: Option<VisionProvider>,
+    audio: Option<AudioProvider>,
+    max_frames: usize,
+    scene_threshold: f32,
+}
+
+impl Default for VideoPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VideoPipeline {
+    pub fn new() -> Self {
+        Self { vision: None, audio: None, max_frames: 8, scene_threshold: 0.3 }
+    }
+
+    /// Describe sampled frames with a vision model.
+    pub fn with_vision(mut self, provider: VisionProvider) -> Self {
+        self.vision = Some(provider);
+        self
+    }
+
+    /// Transcribe the audio track.
+    pub fn with_audio(mut self, provider: AudioProvider) -> Self {
+        self.audio = Some(provider);
+        self
+    }
+
+    pub fn with_max_frames(mut self, max_frames: usize) -> Self {
+        self.max_frames = max_frames.max(1);
+        self
+    }
+
+    /// ffmpeg scene-change score (0.0-1.0) above which a frame is a keyframe.
+    pub fn with_scene_threshold(mut self, threshold: f32) -> Self {
+        self.scene_threshold = threshold;
+        self
+    }
+
+    pub async fn process(&self, video: &Path) -> Result<VideoUnderstanding> {
+        let work = std::env::temp_dir().join(format!("clawforge-video-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&work).await?;
+        let result = self.process_in(&work, video).await;
+        let _ = tokio::fs::remove_dir_all(&work).await;
+        result
+    }
+
+    async fn process_in(&self, work: &Path, video: &Path) -> Result<VideoUnderstanding> {
+        let duration_ms = probe_duration_ms(video).await?;
+        info!("[Video] Processing {} ({} ms)", video.display(), duration_ms);
+
+        let mut keyframes = self.extract_keyframes(work, video, duration_ms).await?;
+        if let Some(vision) = &self.vision {
+            for frame in &mut keyframes {
+                let bytes = tokio::fs::read(&frame.path).await?;
+                match describe_image(vision, &bytes, "image/jpeg", FRAME_PROMPT).await {
+                    Ok(description) => frame.description = Some(description),
+                    Err(e) => warn!("[Video] Describing frame at {} ms failed: {}", frame.at_ms, e),
+                }
+            }
+        }
+
+        let transcript = match &self.audio {
+            Some(provider) => self.transcribe_track(work, video, provider).await?,
+            None => None,
+        };
+        let timeline = merge_timeline(&keyframes, transcript.as_ref());
+        Ok(VideoUnderstanding { duration_ms, keyframes, transcript, timeline })
+    }
+
+    /// Scene-change frames, thinned to `max_frames`; evenly spaced frames
+    /// when the video has fewer than two cuts.
+    pub async fn extract_keyframes(&self, work: &Path, video: &Path, duration_ms: u64) -> Result<Vec<Keyframe>> {
+        let filter = format!("select='gt(scene,{})',showinfo", self.scene_threshold);
+        let pattern = work.join("scene-%04d.jpg");
+        let stderr = ffmpeg(&["-y", "-i"], video, &["-vf", &filter, "-vsync", "vfr", "-q:v", "3"], &pattern).await?;
+        let scenes: Vec<Keyframe> = parse_showinfo(&stderr)
+            .into_iter()
+            .enumerate()
+            .map(|(i, at_ms)| Keyframe { at_ms, path: work.join(format!("scene-{:04}.jpg", i + 1)), description: None })
+            .collect();
+
+        if scenes.len() >= 2 {
+            let step = scenes.len().div_ceil(self.max_frames);
+            return Ok(scenes.into_iter().step_by(step).take(self.max_frames).collect());
+        }
+        let count = self.max_frames.min((duration_ms / 2000).max(1) as usize);
+        let mut frames = Vec::with_capacity(count);
+        for (i, at_ms) in uniform_timestamps(duration_ms, count).into_iter().enumerate() {
+            let path = work.join(format!("frame-{:04}.jpg", i + 1));
+            frame_at(video, at_ms, &path).await?;
+            frames.push(Keyframe { at_ms, path, description: None });
+        }
+        Ok(frames)
+    }
+
+    /// `None` when the video has no audio stream.
+    async fn transcribe_track(&self, work: &Path, video: &Path, provider: &AudioProvider) -> Result<Option<Transcript>> {
+        let wav = work.join("audio.wav");
+        if let Err(e) = ffmpeg(&["-y", "-i"], video, &["-vn", "-ac", "1", "-ar", "16000", "-c:a", "pcm_s16le"], &wav).await {
+            warn!("[Video] No audio track extracted: {}", e);
+            return Ok(None);
+        }
+        let audio = tokio::fs::read(&wav).await?;
+        Ok(Some(transcribe_audio(provider, audio, "audio/wav").await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::TranscriptSegment;
+
+    #[test]
+    fn showinfo_timestamps_are_parsed() {
+        let stderr = "\
+[Parsed_showinfo_1 @ 0x5581] n:   0 pts:  38400 pts_time:3.2     duration: 512\n\
+frame=    2 fps=0.0 q=3.0 size=N/A time=00:00:12.50\n\
+[Parsed_showinfo_1 @ 0x5581] n:   1 pts: 150000 pts_time:12.5    duration: 512\n";
+        assert_eq!(parse_showinfo(stderr), vec![3200, 12_500]);
+        assert_eq!(uniform_timestamps(10_000, 4), vec![1250, 3750, 6250, 8750]);
+    }
+
+    #[test]
+    fn timeline_interleaves_scenes_and_speech() {
+        let keyframes = vec![
+            Keyframe { at_ms: 0, path: "a.jpg".into(), description: Some("A title slide".into()) },
+            Keyframe { at_ms: 9000, path: "b.jpg".into(), description: Some("A bar chart".into()) },
+        ];
+        let transcript = Transcript {
+            segments: vec![TranscriptSegment {
+                start_ms: 4000,
+                end_ms: 8000,
+                text: "Welcome everyone.".into(),
+                speaker: Some("Speaker 1".into()),
+            }],
+            ..Default::default()
+        };
+        let understanding = VideoUnderstanding {
+            duration_ms: 12_000,
+            timeline: merge_timeline(&keyframes, Some(&transcript)),
+            keyframes,
+            transcript: Some(transcript),
+        };
+        assert_eq!(
+            understanding.to_context(),
+            "[Video: 00:12, 2 keyframes, transcribed]\n\
+             [00:00] (scene) A title slide\n\
+             [00:04] Speaker 1: Welcome everyone.\n\
+             [00:09] (scene) A bar chart"
+        );
     }
 }